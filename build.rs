@@ -0,0 +1,22 @@
+//! Build script of the server.
+//!
+//! Tells utoipa what the key aliases of the domain stand for, it cannot see through type
+//! aliases when it generates the schemas of the API.
+
+use utoipa_config::Config;
+
+fn main() {
+	Config::new()
+		.alias_for("ArmyPresetKey", "Uuid")
+		.alias_for("CaravanKey", "Uuid")
+		.alias_for("ConstructionQueueKey", "Uuid")
+		.alias_for("JobKey", "Uuid")
+		.alias_for("PlayerBuildingKey", "Uuid")
+		.alias_for("PlayerKey", "Uuid")
+		.alias_for("SettlementKey", "Uuid")
+		.alias_for("TrainingQueueKey", "Uuid")
+		.alias_for("UnitKey", "Uuid")
+		.alias_for("BuildingKey", "i32")
+		.alias_for("FactionKey", "crate::domain::factions::FactionCode")
+		.write_to_file();
+}
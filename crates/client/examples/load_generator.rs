@@ -0,0 +1,110 @@
+//! Simple load generator built on the typed client.
+//!
+//! Registers a batch of throwaway players against a running server and has each of them poll
+//! the main game endpoints for a number of rounds, then prints request and latency totals.
+//!
+//! ```sh
+//! EMPIRE_URL=http://127.0.0.1:8080 LOAD_PLAYERS=50 LOAD_ROUNDS=20 \
+//!     cargo run -p empire_client --example load_generator
+//! ```
+
+use std::env;
+use std::time::{Duration, Instant};
+
+use empire::domain::factions::FactionCode;
+use empire_client::{EmpireClient, RegisterPayload};
+use tracing::{info, warn};
+
+/// Requests sent and time spent by a single virtual player.
+#[derive(Debug, Default)]
+struct PlayerStats {
+	requests: u64,
+	failures: u64,
+	elapsed: Duration,
+}
+
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+	env::var(key)
+		.ok()
+		.and_then(|value| value.parse().ok())
+		.unwrap_or(default)
+}
+
+/// Registers a player, then polls the game state, buildings, training queue and plans.
+async fn run_player(base_url: String, index: usize, rounds: usize) -> anyhow::Result<PlayerStats> {
+	let mut client = EmpireClient::new(base_url)?;
+	let run_id = uuid::Uuid::new_v4().simple().to_string();
+	client
+		.register(&RegisterPayload {
+			username: format!("loadgen_{}_{}", &run_id[..8], index),
+			password: "loadgen1234".to_string(),
+			email: None,
+		})
+		.await?;
+	client.join_faction(FactionCode::Human).await?;
+
+	let mut stats = PlayerStats::default();
+	for _ in 0..rounds {
+		let started = Instant::now();
+		let results = [
+			client.game_state().await.map(|_| ()),
+			client.buildings().await.map(|_| ()),
+			client.training_queue().await.map(|_| ()),
+			client.plans().await.map(|_| ()),
+		];
+		stats.elapsed += started.elapsed();
+		for result in results {
+			stats.requests += 1;
+			if let Err(err) = result {
+				stats.failures += 1;
+				warn!("Player {} request failed: {}", index, err);
+			}
+		}
+	}
+	Ok(stats)
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+	tracing_subscriber::fmt().with_env_filter("info").init();
+
+	let base_url = env::var("EMPIRE_URL").unwrap_or_else(|_| "http://127.0.0.1:8080".to_string());
+	let players: usize = env_or("LOAD_PLAYERS", 10);
+	let rounds: usize = env_or("LOAD_ROUNDS", 20);
+	info!(
+		"Running {} players x {} rounds against {}",
+		players, rounds, base_url
+	);
+
+	let started = Instant::now();
+	let handles = (0..players)
+		.map(|index| tokio::spawn(run_player(base_url.clone(), index, rounds)))
+		.collect::<Vec<_>>();
+
+	let mut total = PlayerStats::default();
+	for handle in handles {
+		match handle.await? {
+			Ok(stats) => {
+				total.requests += stats.requests;
+				total.failures += stats.failures;
+				total.elapsed += stats.elapsed;
+			}
+			Err(err) => warn!("Player setup failed: {}", err),
+		}
+	}
+
+	let wall = started.elapsed();
+	let avg_latency = total
+		.elapsed
+		.checked_div(total.requests.max(1) as u32)
+		.unwrap_or_default();
+	info!(
+		"{} requests ({} failed) in {:.2?}, {:.1} req/s, avg latency {:.2?}",
+		total.requests,
+		total.failures,
+		wall,
+		total.requests as f64 / wall.as_secs_f64(),
+		avg_latency
+	);
+	Ok(())
+}
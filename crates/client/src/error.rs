@@ -0,0 +1,76 @@
+//! Error type returned by the client.
+
+use std::fmt;
+
+use reqwest::StatusCode;
+
+pub type Result<T, E = ClientError> = std::result::Result<T, E>;
+
+/// Errors returned by [`EmpireClient`](crate::EmpireClient) calls.
+#[derive(Debug)]
+pub enum ClientError {
+	/// The request could not be sent or its response could not be decoded
+	Request(reqwest::Error),
+	/// The server answered with a non-success status
+	Api {
+		status: StatusCode,
+		/// Machine-readable code of the error, absent for errors of the auth middleware
+		code: Option<String>,
+		message: String,
+	},
+}
+
+impl ClientError {
+	/// Builds an API error from a response status and its raw body.
+	///
+	/// The server reports errors as `{"code": ..., "message": ...}`, anything else, like the
+	/// answers of proxies in front of it, is kept verbatim.
+	pub(crate) fn api(status: StatusCode, body: &str) -> Self {
+		let value = serde_json::from_str::<serde_json::Value>(body).ok();
+		let field = |name: &str| {
+			value
+				.as_ref()
+				.and_then(|value| value[name].as_str())
+				.map(str::to_owned)
+		};
+		ClientError::Api {
+			status,
+			code: field("code"),
+			message: field("message").unwrap_or_else(|| body.to_owned()),
+		}
+	}
+
+	/// The HTTP status of an API error, if the server answered at all.
+	pub fn status(&self) -> Option<StatusCode> {
+		match self {
+			ClientError::Request(err) => err.status(),
+			ClientError::Api { status, .. } => Some(*status),
+		}
+	}
+}
+
+impl fmt::Display for ClientError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ClientError::Request(err) => write!(f, "request failed: {err}"),
+			ClientError::Api {
+				status, message, ..
+			} => write!(f, "{status}: {message}"),
+		}
+	}
+}
+
+impl std::error::Error for ClientError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			ClientError::Request(err) => Some(err),
+			ClientError::Api { .. } => None,
+		}
+	}
+}
+
+impl From<reqwest::Error> for ClientError {
+	fn from(err: reqwest::Error) -> Self {
+		ClientError::Request(err)
+	}
+}
@@ -0,0 +1,356 @@
+//! Typed HTTP client for the Empire game server.
+//!
+//! The request and response types are the server's own controller DTOs, re-exported from
+//! the `empire` crate, so changing a DTO on the server breaks this crate (and everything
+//! built on top of it) at compile time instead of in an integration test run.
+//!
+//! Logging in or registering stores the session token handed out by the server, which then
+//! authenticates all subsequent requests. Alternatively, a JWT can be sent as Bearer token
+//! with [`EmpireClient::with_bearer_token`].
+
+mod error;
+
+pub use empire::controllers::auth::{LoginPayload, PlayerDto, PlayerDtoResponse, RegisterPayload};
+pub use empire::controllers::game::buildings::{ConstructBuildingRequest, GameBuilding};
+pub use empire::controllers::game::combat::{BattleReportDto, ReportListQuery, ReportListResponse};
+pub use empire::controllers::game::factions::{FactionBonusesResponse, FactionResponse};
+pub use empire::controllers::game::index::{GameSection, GameState};
+pub use empire::controllers::game::jobs::JobStatusDto;
+pub use empire::controllers::game::plans::{CreatePlanRequest, PlanListResponse, PlannedActionDto};
+pub use empire::controllers::game::units::{
+	AvailableUnitsResponse, CancelTrainingResponse, PlayerUnitsResponse, TrainUnitsRequest,
+	TrainUnitsResponse, TrainingQueueResponse,
+};
+pub use empire::controllers::player::{JoinFactionPayload, PlayerProfileResponse};
+pub use empire::controllers::user::UserBody;
+use empire::domain::combat::BattleReportKey;
+use empire::domain::factions::FactionCode;
+use empire::domain::jobs::JobKey;
+use empire::domain::player::buildings::PlayerBuildingKey;
+use empire::domain::player::planned_action::PlannedActionKey;
+use empire::domain::unit::training::TrainingQueueKey;
+use empire::net::SESSION_COOKIE_NAME;
+pub use error::{ClientError, Result};
+use reqwest::header::{COOKIE, SET_COOKIE};
+use reqwest::{Method, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use tracing::{debug, trace};
+
+/// Body of a successful registration. Only the created player is of interest.
+#[derive(serde::Deserialize)]
+struct RegisterResponse {
+	user: PlayerDto,
+}
+
+/// Client for the Empire REST API.
+#[derive(Debug, Clone)]
+pub struct EmpireClient {
+	http: reqwest::Client,
+	base_url: String,
+	bearer_token: Option<String>,
+	session_token: Option<String>,
+}
+
+impl EmpireClient {
+	/// Creates a client for the server at `base_url`, e.g. `http://127.0.0.1:8443`.
+	pub fn new(base_url: impl Into<String>) -> Result<Self> {
+		let http = reqwest::Client::builder().build()?;
+		Ok(Self {
+			http,
+			base_url: base_url.into().trim_end_matches('/').to_owned(),
+			bearer_token: None,
+			session_token: None,
+		})
+	}
+
+	/// Authenticates every request with the given Bearer token instead of the session cookie.
+	pub fn with_bearer_token(mut self, token: impl Into<String>) -> Self {
+		self.bearer_token = Some(token.into());
+		self
+	}
+
+	/// The base URL requests are sent to.
+	pub fn base_url(&self) -> &str {
+		&self.base_url
+	}
+
+	/// The session token obtained from the last login or registration, if any.
+	pub fn session_token(&self) -> Option<&str> {
+		self.session_token.as_deref()
+	}
+
+	// === Auth ===
+
+	/// POST /register
+	///
+	/// Registers a new player and stores the session token.
+	pub async fn register(&mut self, payload: &RegisterPayload) -> Result<PlayerDto> {
+		let response = self
+			.send(self.request(Method::POST, "/register").json(payload))
+			.await?;
+		self.store_session(&response);
+		let res: RegisterResponse = response.json().await?;
+		Ok(res.user)
+	}
+
+	/// POST /login
+	///
+	/// Logs in and stores the session token.
+	pub async fn login(&mut self, payload: &LoginPayload) -> Result<()> {
+		let response = self
+			.send(self.request(Method::POST, "/login").json(payload))
+			.await?;
+		self.store_session(&response);
+		Ok(())
+	}
+
+	/// POST /logout
+	///
+	/// Invalidates the current session and forgets its token.
+	pub async fn logout(&mut self) -> Result<()> {
+		self.send(self.request(Method::POST, "/logout")).await?;
+		self.session_token = None;
+		Ok(())
+	}
+
+	/// GET /session
+	pub async fn session(&self) -> Result<PlayerDtoResponse> {
+		self.send_json(self.request(Method::GET, "/session")).await
+	}
+
+	// === Player ===
+
+	/// GET /player/profile
+	pub async fn profile(&self) -> Result<PlayerProfileResponse> {
+		self.send_json(self.request(Method::GET, "/player/profile"))
+			.await
+	}
+
+	/// PUT /player/faction
+	pub async fn join_faction(&self, faction: FactionCode) -> Result<UserBody> {
+		let payload = JoinFactionPayload { faction };
+		self.send_json(self.request(Method::PUT, "/player/faction").json(&payload))
+			.await
+	}
+
+	// === Game ===
+
+	/// GET /game
+	pub async fn game_state(&self) -> Result<GameState> {
+		self.send_json(self.request(Method::GET, "/game")).await
+	}
+
+	/// GET /game?include=...
+	///
+	/// Fetches only the given sections of the game state, the player is always included.
+	pub async fn game_state_sections(&self, sections: &[GameSection]) -> Result<GameState> {
+		let include = sections
+			.iter()
+			.map(GameSection::as_str)
+			.collect::<Vec<_>>()
+			.join(",");
+		self.send_json(
+			self.request(Method::GET, "/game")
+				.query(&[("include", include)]),
+		)
+		.await
+	}
+
+	/// GET /game/factions
+	pub async fn factions(&self) -> Result<Vec<FactionResponse>> {
+		self.send_json(self.request(Method::GET, "/game/factions"))
+			.await
+	}
+
+	/// GET /game/factions/{faction_id}/bonuses
+	pub async fn faction_bonuses(&self, faction: FactionCode) -> Result<FactionBonusesResponse> {
+		let path = format!("/game/factions/{}/bonuses", faction.as_ref());
+		self.send_json(self.request(Method::GET, &path)).await
+	}
+
+	// === Buildings ===
+
+	/// GET /game/buildings
+	pub async fn buildings(&self) -> Result<Vec<GameBuilding>> {
+		self.send_json(self.request(Method::GET, "/game/buildings"))
+			.await
+	}
+
+	/// GET /game/buildings/{player_bld_id}
+	pub async fn building(&self, id: &PlayerBuildingKey) -> Result<GameBuilding> {
+		let path = format!("/game/buildings/{id}");
+		self.send_json(self.request(Method::GET, &path)).await
+	}
+
+	/// POST /game/buildings/construct
+	pub async fn construct_building(
+		&self,
+		request: &ConstructBuildingRequest,
+	) -> Result<GameBuilding> {
+		self.send_json(
+			self.request(Method::POST, "/game/buildings/construct")
+				.json(request),
+		)
+		.await
+	}
+
+	/// POST /game/buildings/{player_bld_id}/upgrade
+	pub async fn upgrade_building(&self, id: &PlayerBuildingKey) -> Result<GameBuilding> {
+		let path = format!("/game/buildings/{id}/upgrade");
+		self.send_json(self.request(Method::POST, &path)).await
+	}
+
+	/// POST /game/buildings/{player_bld_id}/upgrade/confirm
+	pub async fn confirm_upgrade(&self, id: &PlayerBuildingKey) -> Result<GameBuilding> {
+		let path = format!("/game/buildings/{id}/upgrade/confirm");
+		self.send_json(self.request(Method::POST, &path)).await
+	}
+
+	// === Units ===
+
+	/// GET /game/units/available?building_id={id}
+	pub async fn available_units(
+		&self,
+		building_id: &PlayerBuildingKey,
+	) -> Result<AvailableUnitsResponse> {
+		let path = format!("/game/units/available?building_id={building_id}");
+		self.send_json(self.request(Method::GET, &path)).await
+	}
+
+	/// POST /game/units/train
+	pub async fn train_units(&self, request: &TrainUnitsRequest) -> Result<TrainUnitsResponse> {
+		self.send_json(
+			self.request(Method::POST, "/game/units/train")
+				.json(request),
+		)
+		.await
+	}
+
+	/// GET /game/units/queue
+	pub async fn training_queue(&self) -> Result<TrainingQueueResponse> {
+		self.send_json(self.request(Method::GET, "/game/units/queue"))
+			.await
+	}
+
+	/// DELETE /game/units/queue/{training_id}
+	pub async fn cancel_training(&self, id: &TrainingQueueKey) -> Result<CancelTrainingResponse> {
+		let path = format!("/game/units/queue/{id}");
+		self.send_json(self.request(Method::DELETE, &path)).await
+	}
+
+	/// DELETE /game/units/queue/{training_id}?quantity=n
+	pub async fn cancel_training_units(
+		&self,
+		id: &TrainingQueueKey,
+		quantity: i64,
+	) -> Result<CancelTrainingResponse> {
+		let path = format!("/game/units/queue/{id}");
+		self.send_json(
+			self.request(Method::DELETE, &path)
+				.query(&[("quantity", quantity)]),
+		)
+		.await
+	}
+
+	/// GET /game/units/inventory
+	pub async fn inventory(&self) -> Result<PlayerUnitsResponse> {
+		self.send_json(self.request(Method::GET, "/game/units/inventory"))
+			.await
+	}
+
+	// === Plans ===
+
+	/// GET /game/plans
+	pub async fn plans(&self) -> Result<PlanListResponse> {
+		self.send_json(self.request(Method::GET, "/game/plans"))
+			.await
+	}
+
+	/// POST /game/plans
+	pub async fn create_plan(&self, request: &CreatePlanRequest) -> Result<PlannedActionDto> {
+		self.send_json(self.request(Method::POST, "/game/plans").json(request))
+			.await
+	}
+
+	/// DELETE /game/plans/{plan_id}
+	pub async fn cancel_plan(&self, id: &PlannedActionKey) -> Result<PlannedActionDto> {
+		let path = format!("/game/plans/{id}");
+		self.send_json(self.request(Method::DELETE, &path)).await
+	}
+
+	// === Combat ===
+
+	/// GET /game/combat/reports
+	pub async fn battle_reports(&self, query: &ReportListQuery) -> Result<ReportListResponse> {
+		self.send_json(
+			self.request(Method::GET, "/game/combat/reports")
+				.query(query),
+		)
+		.await
+	}
+
+	/// GET /game/combat/reports/{report_id}
+	pub async fn battle_report(&self, id: &BattleReportKey) -> Result<BattleReportDto> {
+		let path = format!("/game/combat/reports/{id}");
+		self.send_json(self.request(Method::GET, &path)).await
+	}
+
+	// === Jobs ===
+
+	/// GET /game/jobs/{job_id}
+	pub async fn job_status(&self, id: &JobKey) -> Result<JobStatusDto> {
+		let path = format!("/game/jobs/{id}");
+		self.send_json(self.request(Method::GET, &path)).await
+	}
+
+	// === Internals ===
+
+	/// Builds an authenticated request for a path relative to the base URL.
+	fn request(&self, method: Method, path: &str) -> RequestBuilder {
+		let url = format!("{}{}", self.base_url, path);
+		trace!("{} {}", method, url);
+		let builder = self.http.request(method, url);
+		match (&self.bearer_token, &self.session_token) {
+			(Some(token), _) => builder.bearer_auth(token),
+			(None, Some(token)) => builder.header(COOKIE, format!("{SESSION_COOKIE_NAME}={token}")),
+			(None, None) => builder,
+		}
+	}
+
+	/// Remembers the session token set by a login or registration response.
+	///
+	/// AIDEV-NOTE: The session cookie is marked `Secure`, so a regular cookie store would
+	/// not send it back over plain HTTP (tests, local load runs). It is replayed by hand.
+	fn store_session(&mut self, response: &Response) {
+		let prefix = format!("{SESSION_COOKIE_NAME}=");
+		let token = response
+			.headers()
+			.get_all(SET_COOKIE)
+			.iter()
+			.filter_map(|value| value.to_str().ok())
+			.filter_map(|value| value.split(';').next())
+			.find_map(|pair| pair.strip_prefix(prefix.as_str()));
+		if let Some(token) = token {
+			self.session_token = Some(token.to_owned());
+		}
+	}
+
+	/// Sends a request and turns non-success responses into [`ClientError::Api`].
+	async fn send(&self, builder: RequestBuilder) -> Result<Response> {
+		let response = builder.send().await?;
+		let status = response.status();
+		if status.is_success() {
+			return Ok(response);
+		}
+
+		let body = response.text().await.unwrap_or_default();
+		debug!("Request failed with {}: {}", status, body);
+		Err(ClientError::api(status, &body))
+	}
+
+	/// Sends a request and deserializes the JSON response body.
+	async fn send_json<T: DeserializeOwned>(&self, builder: RequestBuilder) -> Result<T> {
+		let response = self.send(builder).await?;
+		Ok(response.json().await?)
+	}
+}
@@ -0,0 +1,3 @@
+DROP TABLE planned_action;
+DROP TYPE IF EXISTS planned_action_status;
+DROP TYPE IF EXISTS planned_action_kind;
@@ -0,0 +1,36 @@
+CREATE TYPE planned_action_kind AS ENUM ('construct', 'upgrade');
+CREATE TYPE planned_action_status AS ENUM ('pending', 'completed', 'cancelled');
+
+-- AIDEV-NOTE: Player-queued actions that are executed by the building processor once
+-- the player can afford them. Exactly one of building_id / player_building_id is set,
+-- depending on the action kind.
+CREATE TABLE planned_action
+(
+    id                 UUID                  NOT NULL DEFAULT uuidv7(),
+    player_id          UUID                  NOT NULL,
+    action             planned_action_kind   NOT NULL,
+    building_id        INTEGER               NULL,
+    player_building_id UUID                  NULL,
+    status             planned_action_status NOT NULL DEFAULT 'pending'::planned_action_status,
+    last_error         TEXT                  NULL,
+    executed_at        TIMESTAMPTZ           NULL,
+    created_at         TIMESTAMPTZ           NOT NULL DEFAULT now(),
+    updated_at         TIMESTAMPTZ           NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE,
+    FOREIGN KEY (building_id) REFERENCES building (id) ON DELETE CASCADE,
+    FOREIGN KEY (player_building_id) REFERENCES player_building (id) ON DELETE CASCADE,
+    CONSTRAINT planned_action_target CHECK (
+        (action = 'construct' AND building_id IS NOT NULL AND player_building_id IS NULL) OR
+        (action = 'upgrade' AND player_building_id IS NOT NULL AND building_id IS NULL)
+        )
+);
+
+CREATE INDEX idx_planned_action_player_status ON planned_action (player_id, status);
+
+CREATE TRIGGER set_planned_action_updated_at
+    BEFORE UPDATE
+    ON planned_action
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
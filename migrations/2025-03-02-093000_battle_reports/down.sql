@@ -0,0 +1,4 @@
+DROP TABLE battle_report;
+-- Postgres cannot drop a single enum value; remove any combat jobs so the
+-- leftover 'combat' job_type value is unused.
+DELETE FROM job WHERE job_type = 'combat';
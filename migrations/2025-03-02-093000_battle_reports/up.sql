@@ -0,0 +1,37 @@
+ALTER TYPE job_type ADD VALUE IF NOT EXISTS 'combat';
+
+-- AIDEV-NOTE: Immutable record of a resolved battle. Unit losses and applied modifiers
+-- are stored as JSONB snapshots so reports stay readable after unit or modifier
+-- definitions change.
+CREATE TABLE battle_report
+(
+    id              UUID        NOT NULL DEFAULT uuidv7(),
+    attacker_id     UUID        NOT NULL,
+    defender_id     UUID        NOT NULL,
+    winner_id       UUID        NULL,
+    attacker_losses JSONB       NOT NULL DEFAULT '[]'::jsonb,
+    defender_losses JSONB       NOT NULL DEFAULT '[]'::jsonb,
+    loot_food       BIGINT      NOT NULL DEFAULT 0,
+    loot_wood       BIGINT      NOT NULL DEFAULT 0,
+    loot_stone      BIGINT      NOT NULL DEFAULT 0,
+    loot_gold       BIGINT      NOT NULL DEFAULT 0,
+    modifiers       JSONB       NOT NULL DEFAULT '[]'::jsonb,
+    fought_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (attacker_id) REFERENCES player (id) ON DELETE CASCADE,
+    FOREIGN KEY (defender_id) REFERENCES player (id) ON DELETE CASCADE,
+    FOREIGN KEY (winner_id) REFERENCES player (id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_battle_report_attacker ON battle_report (attacker_id, fought_at DESC);
+CREATE INDEX idx_battle_report_defender ON battle_report (defender_id, fought_at DESC);
+CREATE INDEX idx_battle_report_fought_at ON battle_report (fought_at);
+
+CREATE TRIGGER set_battle_report_updated_at
+    BEFORE UPDATE
+    ON battle_report
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
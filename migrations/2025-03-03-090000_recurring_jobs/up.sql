@@ -0,0 +1,29 @@
+-- AIDEV-NOTE: Definition of a periodic job. The job queue keeps exactly one instance
+-- enqueued per definition, tracked in next_job_id, and enqueues the following one when
+-- that instance finishes.
+CREATE TABLE recurring_job
+(
+    id              UUID        NOT NULL DEFAULT uuidv7(),
+    name            TEXT        NOT NULL,
+    cron_expression TEXT        NOT NULL,
+    job_type        job_type    NOT NULL,
+    payload         JSONB       NOT NULL DEFAULT '{}'::jsonb,
+    priority        INTEGER     NOT NULL DEFAULT 50,
+    next_job_id     UUID        NULL,
+    next_run_at     TIMESTAMPTZ NULL,
+    last_run_at     TIMESTAMPTZ NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    UNIQUE (name),
+    FOREIGN KEY (next_job_id) REFERENCES job (id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_recurring_job_next_job ON recurring_job (next_job_id);
+
+CREATE TRIGGER set_recurring_job_updated_at
+    BEFORE UPDATE
+    ON recurring_job
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
@@ -0,0 +1,3 @@
+DROP TABLE job_dead_letter;
+ALTER TABLE job
+    DROP COLUMN error_history;
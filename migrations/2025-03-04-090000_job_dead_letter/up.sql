@@ -0,0 +1,31 @@
+-- Every failed attempt is appended here, last_error only keeps the most recent one
+ALTER TABLE job
+    ADD COLUMN error_history JSONB NOT NULL DEFAULT '[]'::jsonb;
+
+-- AIDEV-NOTE: Jobs that exhausted their retries are moved here, keeping their original id
+-- so a requeued job is the same job again.
+CREATE TABLE job_dead_letter
+(
+    id              UUID        NOT NULL,
+    job_type        job_type    NOT NULL,
+    payload         JSONB       NOT NULL,
+    priority        INTEGER     NOT NULL,
+    retries         INTEGER     NOT NULL,
+    max_retries     INTEGER     NOT NULL,
+    timeout_seconds INTEGER     NOT NULL,
+    last_error      TEXT        NULL,
+    error_history   JSONB       NOT NULL DEFAULT '[]'::jsonb,
+    enqueued_at     TIMESTAMPTZ NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id)
+);
+
+CREATE INDEX idx_job_dead_letter_created_at ON job_dead_letter (created_at DESC);
+
+CREATE TRIGGER set_job_dead_letter_updated_at
+    BEFORE UPDATE
+    ON job_dead_letter
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
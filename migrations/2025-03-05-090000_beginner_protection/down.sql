@@ -0,0 +1,2 @@
+ALTER TABLE player
+    DROP COLUMN protected_until;
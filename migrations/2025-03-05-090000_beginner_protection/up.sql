@@ -0,0 +1,4 @@
+-- Beginner shield: players cannot attack or be attacked until this time.
+-- NULL means the player is not protected.
+ALTER TABLE player
+    ADD COLUMN protected_until TIMESTAMPTZ;
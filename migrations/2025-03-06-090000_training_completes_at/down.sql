@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_training_queue_completes_at;
+
+ALTER TABLE training_queue
+    DROP COLUMN completes_at;
@@ -0,0 +1,18 @@
+-- The completion time is fixed when training starts, so later modifier changes
+-- cannot move it. Existing entries take it from their completion job.
+ALTER TABLE training_queue
+    ADD COLUMN completes_at TIMESTAMPTZ;
+
+UPDATE training_queue tq
+SET completes_at = j.run_at
+FROM job j
+WHERE tq.job_id = j.id;
+
+UPDATE training_queue
+SET completes_at = COALESCE(completed_at, started_at)
+WHERE completes_at IS NULL;
+
+ALTER TABLE training_queue
+    ALTER COLUMN completes_at SET NOT NULL;
+
+CREATE INDEX idx_training_queue_completes_at ON training_queue (completes_at);
@@ -0,0 +1,14 @@
+-- Postgres cannot drop enum values, so the type is rebuilt without it
+UPDATE job SET status = 'cancelled' WHERE status = 'cancel_requested';
+
+ALTER TYPE job_status RENAME TO job_status_old;
+CREATE TYPE job_status AS ENUM ('pending', 'in_progress', 'completed', 'failed', 'cancelled');
+
+DROP INDEX idx_jobs_status_run_at;
+ALTER TABLE job ALTER COLUMN status DROP DEFAULT;
+ALTER TABLE job ALTER COLUMN status TYPE job_status USING status::text::job_status;
+ALTER TABLE job ALTER COLUMN status SET DEFAULT 'pending';
+
+CREATE INDEX idx_jobs_status_run_at ON job (status, run_at) WHERE status = 'pending';
+
+DROP TYPE job_status_old;
@@ -0,0 +1,3 @@
+-- Running jobs cannot be stopped from outside, so cancelling one only flags it.
+-- The worker running it finishes the job as cancelled instead of completed.
+ALTER TYPE job_status ADD VALUE IF NOT EXISTS 'cancel_requested' AFTER 'cancelled';
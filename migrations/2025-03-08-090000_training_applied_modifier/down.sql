@@ -0,0 +1,3 @@
+ALTER TABLE training_queue
+    DROP COLUMN seconds_per_unit,
+    DROP COLUMN training_modifier;
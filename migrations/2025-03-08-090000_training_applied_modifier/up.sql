@@ -0,0 +1,22 @@
+-- The training modifier and per-unit duration are recorded when training starts, so
+-- progress and refunds no longer depend on modifiers active at read time. Existing
+-- entries derive both from their fixed duration.
+ALTER TABLE training_queue
+    ADD COLUMN training_modifier NUMERIC,
+    ADD COLUMN seconds_per_unit  BIGINT;
+
+UPDATE training_queue tq
+SET seconds_per_unit  = COALESCE(
+            GREATEST(FLOOR(EXTRACT(EPOCH FROM tq.completes_at - tq.started_at)
+                               / NULLIF(tq.quantity, 0)), 0),
+            u.base_training_seconds),
+    training_modifier = COALESCE(
+            ROUND(EXTRACT(EPOCH FROM tq.completes_at - tq.started_at) / NULLIF(tq.quantity, 0)
+                      / NULLIF(u.base_training_seconds, 0), 4),
+            1)
+FROM unit u
+WHERE tq.unit_id = u.id;
+
+ALTER TABLE training_queue
+    ALTER COLUMN training_modifier SET NOT NULL,
+    ALTER COLUMN seconds_per_unit SET NOT NULL;
@@ -0,0 +1,3 @@
+-- Outcome of a completed job, for clients polling instead of guessing from timers
+ALTER TABLE job
+    ADD COLUMN result JSONB;
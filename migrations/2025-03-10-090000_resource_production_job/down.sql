@@ -0,0 +1,5 @@
+-- Postgres cannot drop a single enum value; remove every production job and definition
+-- so the leftover 'resource_production' job_type value is unused.
+DELETE FROM recurring_job WHERE job_type = 'resource_production';
+DELETE FROM job_dead_letter WHERE job_type = 'resource_production';
+DELETE FROM job WHERE job_type = 'resource_production';
@@ -0,0 +1,9 @@
+-- Production runs as recurring jobs per shard of players instead of a job per player
+-- that re-enqueues itself, so the per-player production jobs are retired.
+ALTER TYPE job_type ADD VALUE IF NOT EXISTS 'resource_production' AFTER 'resource';
+
+UPDATE job
+SET status = 'cancelled'
+WHERE job_type = 'resource'
+  AND status IN ('pending', 'failed')
+  AND payload ? 'ProduceResources';
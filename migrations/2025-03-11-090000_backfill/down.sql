@@ -0,0 +1,5 @@
+DROP TABLE backfill;
+-- Postgres cannot drop a single enum value; remove any backfill jobs so the
+-- leftover 'backfill' job_type value is unused.
+DELETE FROM job_dead_letter WHERE job_type = 'backfill';
+DELETE FROM job WHERE job_type = 'backfill';
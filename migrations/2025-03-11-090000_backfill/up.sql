@@ -0,0 +1,25 @@
+-- AIDEV-NOTE: Progress of an online data backfill. Backfills run as chunked jobs next to
+-- a schema migration; the cursor is the last key a committed chunk processed, so an
+-- interrupted backfill resumes where it stopped. job_id is the job currently scheduled.
+ALTER TYPE job_type ADD VALUE IF NOT EXISTS 'backfill';
+
+CREATE TABLE backfill
+(
+    name         TEXT        NOT NULL,
+    cursor       UUID        NULL,
+    processed    BIGINT      NOT NULL DEFAULT 0,
+    job_id       UUID        NULL,
+    started_at   TIMESTAMPTZ NULL,
+    completed_at TIMESTAMPTZ NULL,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (name),
+    FOREIGN KEY (job_id) REFERENCES job (id) ON DELETE SET NULL
+);
+
+CREATE TRIGGER set_backfill_updated_at
+    BEFORE UPDATE
+    ON backfill
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
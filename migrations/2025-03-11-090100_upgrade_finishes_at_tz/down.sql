@@ -0,0 +1,9 @@
+DROP TRIGGER sync_player_building_upgrade_finishes_at_tz ON player_building;
+DROP FUNCTION sync_upgrade_finishes_at_tz();
+
+ALTER TABLE player_building
+    DROP COLUMN upgrade_finishes_at_tz;
+
+DROP FUNCTION try_cast_timestamptz(TEXT);
+
+DELETE FROM backfill WHERE name = 'player_building_upgrade_finishes_at_tz';
@@ -0,0 +1,32 @@
+-- Expand step of moving upgrade_finishes_at from RFC 3339 text to timestamptz. Rewriting
+-- the column in place would lock player_building, so a nullable column is added instead:
+-- the trigger keeps it in sync for every write, and the `player_building_upgrade_finishes_at_tz`
+-- backfill converts the rows written before. Readers switch over once it completed.
+CREATE FUNCTION try_cast_timestamptz(value TEXT)
+    RETURNS TIMESTAMPTZ AS
+$$
+BEGIN
+    RETURN value::timestamptz;
+EXCEPTION
+    WHEN others THEN
+        RETURN NULL;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+ALTER TABLE player_building
+    ADD COLUMN upgrade_finishes_at_tz TIMESTAMPTZ NULL;
+
+CREATE FUNCTION sync_upgrade_finishes_at_tz()
+    RETURNS TRIGGER AS
+$$
+BEGIN
+    NEW.upgrade_finishes_at_tz := try_cast_timestamptz(NEW.upgrade_finishes_at);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER sync_player_building_upgrade_finishes_at_tz
+    BEFORE INSERT OR UPDATE OF upgrade_finishes_at
+    ON player_building
+    FOR EACH ROW
+EXECUTE FUNCTION sync_upgrade_finishes_at_tz();
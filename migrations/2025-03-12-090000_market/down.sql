@@ -0,0 +1,4 @@
+DROP TABLE market_trade;
+DROP TABLE market_order;
+DROP TYPE IF EXISTS market_order_status;
+DROP TYPE IF EXISTS market_order_side;
@@ -0,0 +1,57 @@
+CREATE TYPE market_order_side AS ENUM ('buy', 'sell');
+CREATE TYPE market_order_status AS ENUM ('open', 'filled', 'cancelled');
+
+-- AIDEV-NOTE: Gold is the currency of the market, orders trade food, wood and stone for it.
+-- Whatever an open order could still spend is held in escrow: sell orders hold the
+-- remaining resource, buy orders hold remaining * price in gold.
+CREATE TABLE market_order
+(
+    id         UUID                NOT NULL DEFAULT uuidv7(),
+    player_id  UUID                NOT NULL,
+    side       market_order_side   NOT NULL,
+    resource   resource_type       NOT NULL,
+    quantity   BIGINT              NOT NULL,
+    remaining  BIGINT              NOT NULL,
+    price      BIGINT              NOT NULL,
+    status     market_order_status NOT NULL DEFAULT 'open'::market_order_status,
+    created_at TIMESTAMPTZ         NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ         NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE,
+    CONSTRAINT market_order_resource CHECK (resource IN ('food', 'wood', 'stone')),
+    CONSTRAINT market_order_quantity CHECK (quantity > 0 AND remaining BETWEEN 0 AND quantity),
+    CONSTRAINT market_order_price CHECK (price > 0)
+);
+
+CREATE INDEX idx_market_order_player ON market_order (player_id, created_at);
+CREATE INDEX idx_market_order_book ON market_order (resource, side, price, created_at)
+    WHERE status = 'open';
+
+CREATE TRIGGER set_market_order_updated_at
+    BEFORE UPDATE
+    ON market_order
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
+
+-- Every fill between a buy and a sell order, at the price of the order that was resting
+CREATE TABLE market_trade
+(
+    id            UUID          NOT NULL DEFAULT uuidv7(),
+    resource      resource_type NOT NULL,
+    buy_order_id  UUID          NOT NULL,
+    sell_order_id UUID          NOT NULL,
+    buyer_id      UUID          NOT NULL,
+    seller_id     UUID          NOT NULL,
+    quantity      BIGINT        NOT NULL,
+    price         BIGINT        NOT NULL,
+    created_at    TIMESTAMPTZ   NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (buy_order_id) REFERENCES market_order (id) ON DELETE CASCADE,
+    FOREIGN KEY (sell_order_id) REFERENCES market_order (id) ON DELETE CASCADE,
+    FOREIGN KEY (buyer_id) REFERENCES player (id) ON DELETE CASCADE,
+    FOREIGN KEY (seller_id) REFERENCES player (id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_market_trade_resource ON market_trade (resource, created_at);
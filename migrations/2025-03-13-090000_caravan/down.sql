@@ -0,0 +1,2 @@
+DROP TABLE caravan;
+DROP TYPE IF EXISTS caravan_status;
@@ -0,0 +1,39 @@
+CREATE TYPE caravan_status AS ENUM ('travelling', 'delivered');
+
+-- AIDEV-NOTE: Resources shipped from one player to another. The cargo leaves the sender's
+-- storage when the caravan departs, and a resource job credits it to the receiver on
+-- arrival. Whatever does not fit into the receiver's storage goes back to the sender.
+CREATE TABLE caravan
+(
+    id             UUID           NOT NULL DEFAULT uuidv7(),
+    sender_id      UUID           NOT NULL,
+    receiver_id    UUID           NOT NULL,
+    food           BIGINT         NOT NULL DEFAULT 0,
+    wood           BIGINT         NOT NULL DEFAULT 0,
+    stone          BIGINT         NOT NULL DEFAULT 0,
+    gold           BIGINT         NOT NULL DEFAULT 0,
+    status         caravan_status NOT NULL DEFAULT 'travelling'::caravan_status,
+    job_id         UUID           NULL,
+    arrives_at     TIMESTAMPTZ    NOT NULL,
+    delivered_at   TIMESTAMPTZ    NULL,
+    created_at     TIMESTAMPTZ    NOT NULL DEFAULT now(),
+    updated_at     TIMESTAMPTZ    NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (sender_id) REFERENCES player (id) ON DELETE CASCADE,
+    FOREIGN KEY (receiver_id) REFERENCES player (id) ON DELETE CASCADE,
+    FOREIGN KEY (job_id) REFERENCES job (id) ON DELETE SET NULL,
+    CONSTRAINT caravan_cargo CHECK (
+        food >= 0 AND wood >= 0 AND stone >= 0 AND gold >= 0 AND food + wood + stone + gold > 0
+        ),
+    CONSTRAINT caravan_receiver CHECK (sender_id <> receiver_id)
+);
+
+CREATE INDEX idx_caravan_sender ON caravan (sender_id, created_at);
+CREATE INDEX idx_caravan_receiver ON caravan (receiver_id, created_at);
+
+CREATE TRIGGER set_caravan_updated_at
+    BEFORE UPDATE
+    ON caravan
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
@@ -0,0 +1 @@
+DROP TABLE building_upgrade;
@@ -0,0 +1,33 @@
+-- AIDEV-NOTE: Ledger of building upgrades, one row per upgrade that was started. The row
+-- records what the upgrade cost when it started and is completed when the upgrade is
+-- confirmed. player_building only keeps the current level, so activity stats read this.
+CREATE TABLE building_upgrade
+(
+    id                 UUID        NOT NULL DEFAULT uuidv7(),
+    player_id          UUID        NOT NULL,
+    player_building_id UUID        NOT NULL,
+    building_id        INT         NOT NULL,
+    level              INT         NOT NULL,
+    food               BIGINT      NOT NULL DEFAULT 0,
+    wood               BIGINT      NOT NULL DEFAULT 0,
+    stone              BIGINT      NOT NULL DEFAULT 0,
+    gold               BIGINT      NOT NULL DEFAULT 0,
+    completed_at       TIMESTAMPTZ NULL,
+    created_at         TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at         TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE,
+    FOREIGN KEY (player_building_id) REFERENCES player_building (id) ON DELETE CASCADE,
+    FOREIGN KEY (building_id) REFERENCES building (id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_building_upgrade_player ON building_upgrade (player_id, created_at);
+CREATE INDEX idx_building_upgrade_pending ON building_upgrade (player_building_id)
+    WHERE completed_at IS NULL;
+
+CREATE TRIGGER set_building_upgrade_updated_at
+    BEFORE UPDATE
+    ON building_upgrade
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
@@ -0,0 +1,6 @@
+DROP TABLE simulated_player;
+-- Postgres cannot drop a single enum value; remove any simulation jobs so the
+-- leftover 'simulation' job_type value is unused.
+DELETE FROM recurring_job WHERE job_type = 'simulation';
+DELETE FROM job_dead_letter WHERE job_type = 'simulation';
+DELETE FROM job WHERE job_type = 'simulation';
@@ -0,0 +1,12 @@
+-- AIDEV-NOTE: NPC players spawned by the simulation mode of development worlds. The
+-- simulation only ever acts for the players listed here, and the rows go with the player.
+ALTER TYPE job_type ADD VALUE IF NOT EXISTS 'simulation';
+
+CREATE TABLE simulated_player
+(
+    player_id  UUID        NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (player_id),
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE
+);
@@ -0,0 +1,5 @@
+ALTER TABLE player_accumulator
+    DROP COLUMN food_remainder,
+    DROP COLUMN wood_remainder,
+    DROP COLUMN stone_remainder,
+    DROP COLUMN gold_remainder;
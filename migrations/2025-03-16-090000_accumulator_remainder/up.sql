@@ -0,0 +1,7 @@
+-- Fractional production left over after a tick, carried into the next one so low
+-- production rates still add up to whole resources over time.
+ALTER TABLE player_accumulator
+    ADD COLUMN food_remainder  NUMERIC NOT NULL DEFAULT 0,
+    ADD COLUMN wood_remainder  NUMERIC NOT NULL DEFAULT 0,
+    ADD COLUMN stone_remainder NUMERIC NOT NULL DEFAULT 0,
+    ADD COLUMN gold_remainder  NUMERIC NOT NULL DEFAULT 0;
@@ -0,0 +1,3 @@
+ALTER TABLE unit
+    DROP COLUMN food_upkeep,
+    DROP COLUMN population;
@@ -0,0 +1,5 @@
+-- Upkeep of a single unit: the food it eats per hour and the population it houses.
+-- The population capacity of a player comes from building_resource.population.
+ALTER TABLE unit
+    ADD COLUMN food_upkeep BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN population  BIGINT NOT NULL DEFAULT 1;
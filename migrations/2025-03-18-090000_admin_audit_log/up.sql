@@ -0,0 +1,17 @@
+-- AIDEV-NOTE: Append-only record of the actions operators take through the admin API.
+-- The admin key is shared, so the operator is whatever the caller declared in the
+-- x-admin-operator header; the request id ties an entry to the request logs.
+CREATE TABLE admin_audit_log
+(
+    id         UUID        NOT NULL DEFAULT uuidv7(),
+    action     TEXT        NOT NULL,
+    subject    TEXT        NULL,
+    details    JSONB       NOT NULL DEFAULT '{}'::jsonb,
+    operator   TEXT        NULL,
+    request_id TEXT        NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id)
+);
+
+CREATE INDEX idx_admin_audit_log_created_at ON admin_audit_log (created_at DESC);
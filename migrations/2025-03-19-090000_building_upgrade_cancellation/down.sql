@@ -0,0 +1,6 @@
+DROP INDEX idx_building_upgrade_pending;
+CREATE INDEX idx_building_upgrade_pending ON building_upgrade (player_building_id)
+    WHERE completed_at IS NULL;
+
+ALTER TABLE building_upgrade
+    DROP COLUMN cancelled_at;
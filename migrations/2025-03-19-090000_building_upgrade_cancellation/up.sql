@@ -0,0 +1,8 @@
+-- AIDEV-NOTE: Cancelled upgrades stay in the ledger so the refund can be traced, but no
+-- longer count as pending or as spending.
+ALTER TABLE building_upgrade
+    ADD COLUMN cancelled_at TIMESTAMPTZ NULL;
+
+DROP INDEX idx_building_upgrade_pending;
+CREATE INDEX idx_building_upgrade_pending ON building_upgrade (player_building_id)
+    WHERE completed_at IS NULL AND cancelled_at IS NULL;
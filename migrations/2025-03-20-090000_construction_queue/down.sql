@@ -0,0 +1,2 @@
+DROP TABLE construction_queue;
+DROP TYPE IF EXISTS construction_status;
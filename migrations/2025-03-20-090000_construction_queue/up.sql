@@ -0,0 +1,34 @@
+-- AIDEV-NOTE: Upgrades queued behind each other, per player. Entries run one after another,
+-- each pointing at the building_upgrade ledger row that holds what it cost.
+CREATE TYPE construction_status AS ENUM ('pending', 'in_progress', 'completed');
+
+CREATE TABLE construction_queue
+(
+    id                 UUID                NOT NULL DEFAULT uuidv7(),
+    player_id          UUID                NOT NULL,
+    player_building_id UUID                NOT NULL,
+    upgrade_id         UUID                NOT NULL,
+    level              INTEGER             NOT NULL,
+    status             construction_status NOT NULL DEFAULT 'pending'::construction_status,
+    starts_at          TIMESTAMPTZ         NOT NULL,
+    completes_at       TIMESTAMPTZ         NOT NULL,
+    completed_at       TIMESTAMPTZ         NULL,
+    job_id             UUID                NULL,
+    created_at         TIMESTAMPTZ         NOT NULL DEFAULT now(),
+    updated_at         TIMESTAMPTZ         NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE,
+    FOREIGN KEY (player_building_id) REFERENCES player_building (id) ON DELETE CASCADE,
+    FOREIGN KEY (upgrade_id) REFERENCES building_upgrade (id) ON DELETE CASCADE,
+    FOREIGN KEY (job_id) REFERENCES job (id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_construction_queue_active ON construction_queue (player_id, completes_at)
+    WHERE status <> 'completed';
+
+CREATE TRIGGER set_construction_queue_updated_at
+    BEFORE UPDATE
+    ON construction_queue
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
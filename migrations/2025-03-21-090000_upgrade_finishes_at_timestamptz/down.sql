@@ -0,0 +1,36 @@
+ALTER TABLE player_building
+    RENAME COLUMN upgrade_finishes_at TO upgrade_finishes_at_tz;
+ALTER TABLE player_building
+    ADD COLUMN upgrade_finishes_at TEXT NULL;
+
+UPDATE player_building
+SET upgrade_finishes_at = to_char(upgrade_finishes_at_tz AT TIME ZONE 'UTC',
+                                  'YYYY-MM-DD"T"HH24:MI:SS.US"+00:00"')
+WHERE upgrade_finishes_at_tz IS NOT NULL;
+
+CREATE FUNCTION try_cast_timestamptz(value TEXT)
+    RETURNS TIMESTAMPTZ AS
+$$
+BEGIN
+    RETURN value::timestamptz;
+EXCEPTION
+    WHEN others THEN
+        RETURN NULL;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+CREATE FUNCTION sync_upgrade_finishes_at_tz()
+    RETURNS TRIGGER AS
+$$
+BEGIN
+    NEW.upgrade_finishes_at_tz := try_cast_timestamptz(NEW.upgrade_finishes_at);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER sync_player_building_upgrade_finishes_at_tz
+    BEFORE INSERT OR UPDATE OF upgrade_finishes_at
+    ON player_building
+    FOR EACH ROW
+EXECUTE FUNCTION sync_upgrade_finishes_at_tz();
+
@@ -0,0 +1,18 @@
+-- Contract step of moving upgrade_finishes_at from RFC 3339 text to timestamptz. Rows the
+-- backfill did not reach yet are converted here, then the timestamp column replaces the
+-- text column and the trigger keeping them in sync goes away.
+UPDATE player_building
+SET upgrade_finishes_at_tz = try_cast_timestamptz(upgrade_finishes_at)
+WHERE upgrade_finishes_at IS NOT NULL
+  AND upgrade_finishes_at_tz IS NULL;
+
+DROP TRIGGER sync_player_building_upgrade_finishes_at_tz ON player_building;
+DROP FUNCTION sync_upgrade_finishes_at_tz();
+DROP FUNCTION try_cast_timestamptz(TEXT);
+
+ALTER TABLE player_building
+    DROP COLUMN upgrade_finishes_at;
+ALTER TABLE player_building
+    RENAME COLUMN upgrade_finishes_at_tz TO upgrade_finishes_at;
+
+DELETE FROM backfill WHERE name = 'player_building_upgrade_finishes_at_tz';
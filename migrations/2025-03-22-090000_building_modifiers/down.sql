@@ -0,0 +1,4 @@
+DROP INDEX active_modifiers_player_building_idx;
+
+ALTER TABLE active_modifiers
+    DROP COLUMN player_building_id;
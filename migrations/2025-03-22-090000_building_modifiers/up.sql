@@ -0,0 +1,8 @@
+-- Scope an active modifier to one of the player's buildings, e.g. a single Lumberyard
+-- producing 25% more wood. Player-wide modifiers leave it empty.
+ALTER TABLE active_modifiers
+    ADD COLUMN player_building_id UUID NULL REFERENCES player_building (id) ON DELETE CASCADE;
+
+CREATE INDEX active_modifiers_player_building_idx
+    ON active_modifiers (player_building_id)
+    WHERE player_building_id IS NOT NULL;
@@ -0,0 +1,3 @@
+DROP TRIGGER new_player_privacy_trigger ON player;
+DROP FUNCTION new_player_privacy_fn;
+DROP TABLE player_privacy;
@@ -0,0 +1,41 @@
+-- AIDEV-NOTE: what a player hides from other players. Every player gets a row with
+-- everything visible; anything showing a player to someone else must honour these flags.
+CREATE TABLE player_privacy
+(
+    player_id          UUID        NOT NULL,
+    hide_online_status BOOLEAN     NOT NULL DEFAULT FALSE,
+    hide_stats         BOOLEAN     NOT NULL DEFAULT FALSE,
+    hide_alliance      BOOLEAN     NOT NULL DEFAULT FALSE,
+    created_at         TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at         TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (player_id),
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE
+);
+
+CREATE TRIGGER set_player_privacy_updated_at
+    BEFORE UPDATE
+    ON player_privacy
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
+
+INSERT INTO player_privacy (player_id)
+SELECT id
+FROM player;
+
+CREATE OR REPLACE FUNCTION new_player_privacy_fn()
+    RETURNS TRIGGER
+    LANGUAGE PLPGSQL
+AS
+$$
+BEGIN
+    INSERT INTO player_privacy (player_id) VALUES (NEW.id);
+    RETURN NEW;
+END;
+$$;
+
+CREATE TRIGGER new_player_privacy_trigger
+    AFTER INSERT
+    ON player
+    FOR EACH ROW
+EXECUTE FUNCTION new_player_privacy_fn();
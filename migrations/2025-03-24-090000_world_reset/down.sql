@@ -0,0 +1,5 @@
+DROP TABLE world_reset;
+-- Postgres cannot drop a single enum value; remove any world reset jobs so the
+-- leftover 'world_reset' job_type value is unused.
+DELETE FROM job_dead_letter WHERE job_type = 'world_reset';
+DELETE FROM job WHERE job_type = 'world_reset';
@@ -0,0 +1,32 @@
+-- AIDEV-NOTE: resets of the world between seasons. An operator requests a reset and gets a
+-- confirmation token, only its hash is stored. Confirming starts a world_reset job that wipes
+-- the progression tables one step at a time; completed_steps is the progress of that job.
+ALTER TYPE job_type ADD VALUE IF NOT EXISTS 'world_reset';
+
+CREATE TABLE world_reset
+(
+    id              UUID        NOT NULL DEFAULT uuidv7(),
+    token_hash      TEXT        NOT NULL,
+    requested_by    TEXT        NULL,
+    expires_at      TIMESTAMPTZ NOT NULL,
+    confirmed_by    TEXT        NULL,
+    confirmed_at    TIMESTAMPTZ NULL,
+    completed_steps INTEGER     NOT NULL DEFAULT 0,
+    completed_at    TIMESTAMPTZ NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    CHECK (completed_steps >= 0),
+    CHECK (completed_at IS NULL OR confirmed_at IS NOT NULL)
+);
+
+-- Only one reset may run at a time
+CREATE UNIQUE INDEX world_reset_running_idx ON world_reset ((TRUE))
+    WHERE confirmed_at IS NOT NULL AND completed_at IS NULL;
+
+CREATE TRIGGER set_world_reset_updated_at
+    BEFORE UPDATE
+    ON world_reset
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
@@ -0,0 +1,2 @@
+ALTER TABLE modifier_history
+    DROP COLUMN operator;
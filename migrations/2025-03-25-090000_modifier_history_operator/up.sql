@@ -0,0 +1,4 @@
+-- Operator who granted or revoked a modifier through the admin API. Changes made by the
+-- game itself, like expirations, leave it empty.
+ALTER TABLE modifier_history
+    ADD COLUMN operator TEXT NULL;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS player_item;
+DROP TABLE IF EXISTS item;
@@ -0,0 +1,43 @@
+-- Consumable items, like boosts, that apply a modifier to the player for a while when used.
+CREATE TABLE item
+(
+    id               UUID        NOT NULL DEFAULT uuidv7(),
+    name             TEXT        NOT NULL,
+    description      TEXT        NOT NULL,
+    modifier_id      UUID        NOT NULL,
+    duration_seconds INT         NOT NULL CHECK (duration_seconds > 0),
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (modifier_id) REFERENCES modifiers (id) ON DELETE CASCADE,
+    CONSTRAINT item_name UNIQUE (name)
+);
+
+CREATE TRIGGER set_item_updated_at
+    BEFORE UPDATE
+    ON item
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
+
+-- Items held by each player
+CREATE TABLE player_item
+(
+    id         UUID        NOT NULL DEFAULT uuidv7(),
+    player_id  UUID        NOT NULL,
+    item_id    UUID        NOT NULL,
+    quantity   BIGINT      NOT NULL DEFAULT 0 CHECK (quantity >= 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE,
+    FOREIGN KEY (item_id) REFERENCES item (id) ON DELETE CASCADE,
+    CONSTRAINT player_item_inventory UNIQUE (player_id, item_id)
+);
+
+CREATE TRIGGER set_player_item_updated_at
+    BEFORE UPDATE
+    ON player_item
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
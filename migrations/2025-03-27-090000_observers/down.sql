@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS observer_player;
+DROP TABLE IF EXISTS observer;
+
+ALTER TABLE player_privacy
+    DROP COLUMN allow_observers;
@@ -0,0 +1,41 @@
+-- Players opt in to being watched by tournament observers, nobody is watched by default
+ALTER TABLE player_privacy
+    ADD COLUMN allow_observers BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Read-only accounts for casting tournaments, created by operators. Observers authenticate
+-- with a token of which only the hash is stored, and can only see what their scopes allow.
+CREATE TABLE observer
+(
+    id         UUID        NOT NULL DEFAULT uuidv7(),
+    name       TEXT        NOT NULL,
+    token_hash TEXT        NOT NULL,
+    scopes     TEXT[]      NOT NULL DEFAULT '{}',
+    created_by TEXT        NULL,
+    expires_at TIMESTAMPTZ NULL,
+    revoked_at TIMESTAMPTZ NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    CONSTRAINT observer_token_hash UNIQUE (token_hash)
+);
+
+CREATE TRIGGER set_observer_updated_at
+    BEFORE UPDATE
+    ON observer
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
+
+-- Players an observer was selected to watch
+CREATE TABLE observer_player
+(
+    observer_id UUID        NOT NULL,
+    player_id   UUID        NOT NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (observer_id, player_id),
+    FOREIGN KEY (observer_id) REFERENCES observer (id) ON DELETE CASCADE,
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE
+);
+
+CREATE INDEX observer_player_player_id_idx ON observer_player (player_id);
@@ -0,0 +1,18 @@
+-- Debuffs do not fit the previous constraint
+DELETE
+FROM modifiers
+WHERE (magnitude_kind = 'percentage' AND magnitude < 0)
+   OR (magnitude_kind = 'flat' AND magnitude < 0)
+   OR (magnitude_kind = 'multiplier' AND magnitude < 1);
+
+ALTER TABLE modifiers
+    DROP CONSTRAINT magnitude_validity;
+
+ALTER TABLE modifiers
+    ADD CONSTRAINT magnitude_validity CHECK (
+        -- percentage modifiers must be between 0 and 1
+        (magnitude_kind = 'percentage' AND magnitude > 0 AND magnitude <= 1)
+            -- flat modifiers must be a positive number
+            OR (magnitude_kind = 'flat' AND magnitude >= 0)
+            -- multiplier modifiers must be a positive number greater than 1
+            OR (magnitude_kind = 'multiplier' AND magnitude >= 1));
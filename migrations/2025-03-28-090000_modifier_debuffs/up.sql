@@ -0,0 +1,12 @@
+-- Allow debuffs: negative percentages and flats, and multipliers below 1
+ALTER TABLE modifiers
+    DROP CONSTRAINT magnitude_validity;
+
+ALTER TABLE modifiers
+    ADD CONSTRAINT magnitude_validity CHECK (
+        -- percentage modifiers must be between -1 and 1, and change something
+        (magnitude_kind = 'percentage' AND magnitude >= -1 AND magnitude <= 1 AND magnitude != 0)
+            -- flat modifiers can be any number
+            OR magnitude_kind = 'flat'
+            -- multiplier modifiers must be a positive number
+            OR (magnitude_kind = 'multiplier' AND magnitude > 0));
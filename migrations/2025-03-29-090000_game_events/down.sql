@@ -0,0 +1,10 @@
+DROP TABLE IF EXISTS player_event_objective;
+DROP TABLE IF EXISTS player_event_currency;
+DROP TABLE IF EXISTS game_event_offer;
+DROP TABLE IF EXISTS game_event_objective;
+DROP TABLE IF EXISTS game_event;
+DROP TYPE IF EXISTS event_objective_kind;
+-- Postgres cannot drop a single enum value; remove any limited event jobs so the
+-- leftover 'limited_event' job_type value is unused.
+DELETE FROM job_dead_letter WHERE job_type = 'limited_event';
+DELETE FROM job WHERE job_type = 'limited_event';
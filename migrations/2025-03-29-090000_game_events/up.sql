@@ -0,0 +1,114 @@
+-- AIDEV-NOTE: time-limited events. Players earn the event's own currency by completing its
+-- objectives while the event runs and spend it in the event's shop. A limited_event job
+-- closes the event once it ends, converting the currency left over into a resource.
+ALTER TYPE job_type ADD VALUE IF NOT EXISTS 'limited_event';
+
+CREATE TYPE event_objective_kind AS ENUM ('training_completed', 'upgrade_completed', 'resources_collected');
+
+CREATE TABLE game_event
+(
+    id                UUID          NOT NULL DEFAULT uuidv7(),
+    name              TEXT          NOT NULL,
+    description       TEXT          NOT NULL,
+    currency_name     TEXT          NOT NULL,
+    starts_at         TIMESTAMPTZ   NOT NULL,
+    ends_at           TIMESTAMPTZ   NOT NULL,
+    leftover_resource resource_type NOT NULL,
+    leftover_rate     INTEGER       NOT NULL DEFAULT 0 CHECK (leftover_rate >= 0),
+    closed_at         TIMESTAMPTZ   NULL,
+    created_at        TIMESTAMPTZ   NOT NULL DEFAULT now(),
+    updated_at        TIMESTAMPTZ   NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    CONSTRAINT game_event_name UNIQUE (name),
+    CHECK (ends_at > starts_at),
+    CHECK (leftover_resource <> 'population')
+);
+
+CREATE TRIGGER set_game_event_updated_at
+    BEFORE UPDATE
+    ON game_event
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
+
+-- Objectives of an event. Every `target` occurrences of the kind earn `reward` currency,
+-- at most `max_completions` times if set.
+CREATE TABLE game_event_objective
+(
+    id              UUID                 NOT NULL DEFAULT uuidv7(),
+    event_id        UUID                 NOT NULL,
+    kind            event_objective_kind NOT NULL,
+    description     TEXT                 NOT NULL,
+    target          BIGINT               NOT NULL CHECK (target > 0),
+    reward          BIGINT               NOT NULL CHECK (reward > 0),
+    max_completions INTEGER              NULL CHECK (max_completions > 0),
+    created_at      TIMESTAMPTZ          NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (event_id) REFERENCES game_event (id) ON DELETE CASCADE
+);
+
+CREATE INDEX game_event_objective_event_id_idx ON game_event_objective (event_id);
+
+-- The event shop, each offer sells a quantity of either an item or a unit
+CREATE TABLE game_event_offer
+(
+    id         UUID        NOT NULL DEFAULT uuidv7(),
+    event_id   UUID        NOT NULL,
+    price      BIGINT      NOT NULL CHECK (price > 0),
+    item_id    UUID        NULL,
+    unit_id    UUID        NULL,
+    quantity   BIGINT      NOT NULL CHECK (quantity > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (event_id) REFERENCES game_event (id) ON DELETE CASCADE,
+    FOREIGN KEY (item_id) REFERENCES item (id) ON DELETE CASCADE,
+    FOREIGN KEY (unit_id) REFERENCES unit (id) ON DELETE CASCADE,
+    CHECK ((item_id IS NULL) <> (unit_id IS NULL))
+);
+
+CREATE INDEX game_event_offer_event_id_idx ON game_event_offer (event_id);
+
+-- Event currency held by each player
+CREATE TABLE player_event_currency
+(
+    player_id  UUID        NOT NULL,
+    event_id   UUID        NOT NULL,
+    balance    BIGINT      NOT NULL DEFAULT 0 CHECK (balance >= 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (player_id, event_id),
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE,
+    FOREIGN KEY (event_id) REFERENCES game_event (id) ON DELETE CASCADE
+);
+
+CREATE INDEX player_event_currency_event_id_idx ON player_event_currency (event_id);
+
+CREATE TRIGGER set_player_event_currency_updated_at
+    BEFORE UPDATE
+    ON player_event_currency
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
+
+-- Progress of each player towards the objectives of an event
+CREATE TABLE player_event_objective
+(
+    player_id    UUID        NOT NULL,
+    objective_id UUID        NOT NULL,
+    progress     BIGINT      NOT NULL DEFAULT 0 CHECK (progress >= 0),
+    completions  INTEGER     NOT NULL DEFAULT 0 CHECK (completions >= 0),
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (player_id, objective_id),
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE,
+    FOREIGN KEY (objective_id) REFERENCES game_event_objective (id) ON DELETE CASCADE
+);
+
+CREATE TRIGGER set_player_event_objective_updated_at
+    BEFORE UPDATE
+    ON player_event_objective
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
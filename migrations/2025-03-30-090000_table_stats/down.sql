@@ -0,0 +1,6 @@
+DROP TABLE IF EXISTS table_stats;
+-- Postgres cannot drop a single enum value; remove any table stats jobs so the
+-- leftover 'table_stats' job_type value is unused.
+DELETE FROM recurring_job WHERE job_type = 'table_stats';
+DELETE FROM job_dead_letter WHERE job_type = 'table_stats';
+DELETE FROM job WHERE job_type = 'table_stats';
@@ -0,0 +1,18 @@
+-- AIDEV-NOTE: periodic snapshots of the size of the busiest tables, captured by a recurring
+-- table_stats job, so capacity and the effect of the retention policies can be followed
+-- over time from the admin API.
+ALTER TYPE job_type ADD VALUE IF NOT EXISTS 'table_stats';
+
+CREATE TABLE table_stats
+(
+    id          UUID        NOT NULL DEFAULT uuidv7(),
+    table_name  TEXT        NOT NULL,
+    row_count   BIGINT      NOT NULL CHECK (row_count >= 0),
+    total_bytes BIGINT      NOT NULL CHECK (total_bytes >= 0),
+    captured_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id)
+);
+
+CREATE INDEX table_stats_table_name_captured_at_idx ON table_stats (table_name, captured_at);
+CREATE INDEX table_stats_captured_at_idx ON table_stats (captured_at);
@@ -0,0 +1,29 @@
+-- Keys players create for bots, companion apps and their own tooling. Only the hash of a key
+-- is stored, the prefix is kept so players can tell their keys apart.
+CREATE TABLE api_key
+(
+    id           UUID        NOT NULL DEFAULT uuidv7(),
+    player_id    UUID        NOT NULL,
+    name         TEXT        NOT NULL,
+    key_hash     TEXT        NOT NULL,
+    key_prefix   TEXT        NOT NULL,
+    scope        TEXT        NOT NULL DEFAULT 'read_only',
+    last_used_at TIMESTAMPTZ NULL,
+    expires_at   TIMESTAMPTZ NULL,
+    revoked_at   TIMESTAMPTZ NULL,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE,
+    CONSTRAINT api_key_key_hash UNIQUE (key_hash),
+    CONSTRAINT api_key_scope CHECK (scope IN ('read_only', 'full'))
+);
+
+CREATE INDEX api_key_player_id_idx ON api_key (player_id);
+
+CREATE TRIGGER set_api_key_updated_at
+    BEFORE UPDATE
+    ON api_key
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
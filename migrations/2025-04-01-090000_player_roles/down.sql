@@ -0,0 +1,4 @@
+ALTER TABLE player
+    DROP COLUMN role;
+
+DROP TYPE IF EXISTS player_role;
@@ -0,0 +1,5 @@
+-- What a player is allowed to do beyond playing, granted by operators through the admin API
+CREATE TYPE player_role AS ENUM ('player', 'moderator', 'admin');
+
+ALTER TABLE player
+    ADD COLUMN role player_role NOT NULL DEFAULT 'player';
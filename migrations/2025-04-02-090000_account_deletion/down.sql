@@ -0,0 +1,8 @@
+ALTER TABLE player
+    DROP COLUMN deletion_scheduled_for,
+    DROP COLUMN anonymized_at;
+-- Postgres cannot drop a single enum value; remove any account deletion jobs so the
+-- leftover 'account_deletion' job_type value is unused.
+DELETE FROM recurring_job WHERE job_type = 'account_deletion';
+DELETE FROM job_dead_letter WHERE job_type = 'account_deletion';
+DELETE FROM job WHERE job_type = 'account_deletion';
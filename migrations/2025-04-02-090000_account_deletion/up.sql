@@ -0,0 +1,10 @@
+-- AIDEV-NOTE: players can ask for their account to be deleted. After a grace period an
+-- account_deletion job anonymizes the player row instead of deleting it, so battle reports,
+-- market trades and the rest of the shared history keep pointing at a valid player.
+ALTER TYPE job_type ADD VALUE IF NOT EXISTS 'account_deletion';
+
+ALTER TABLE player
+    -- When the account is anonymized, NULL unless a deletion is pending
+    ADD COLUMN deletion_scheduled_for TIMESTAMPTZ NULL,
+    -- When the account was anonymized, NULL for live accounts
+    ADD COLUMN anonymized_at          TIMESTAMPTZ NULL;
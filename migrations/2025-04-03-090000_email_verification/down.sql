@@ -0,0 +1,2 @@
+ALTER TABLE player
+    DROP COLUMN email_verified_at;
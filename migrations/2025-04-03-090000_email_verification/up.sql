@@ -0,0 +1,5 @@
+-- AIDEV-NOTE: changing the email address of a player clears the verification, the new
+-- address has to be verified again.
+ALTER TABLE player
+    -- When the current email address was verified, NULL until it is
+    ADD COLUMN email_verified_at TIMESTAMPTZ NULL;
@@ -0,0 +1,27 @@
+-- Accounts of players at external identity providers, so they can log in with Google or
+-- Discord. A player has at most one identity per provider, and an identity belongs to a
+-- single player.
+CREATE TABLE player_identity
+(
+    id         UUID         NOT NULL DEFAULT uuidv7(),
+    player_id  UUID         NOT NULL,
+    provider   TEXT         NOT NULL,
+    -- Identifier of the account at the provider, stable across email and name changes
+    subject    TEXT         NOT NULL,
+    -- Email address the provider reported at the last login, if it verified it
+    email      VARCHAR(254) NULL,
+    created_at TIMESTAMPTZ  NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ  NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE,
+    CONSTRAINT player_identity_provider_subject UNIQUE (provider, subject),
+    CONSTRAINT player_identity_player_provider UNIQUE (player_id, provider),
+    CONSTRAINT player_identity_provider CHECK (provider IN ('google', 'discord'))
+);
+
+CREATE TRIGGER set_player_identity_updated_at
+    BEFORE UPDATE
+    ON player_identity
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
@@ -0,0 +1,11 @@
+DROP INDEX IF EXISTS idx_audit_log_action;
+DROP INDEX IF EXISTS idx_audit_log_player_id;
+
+ALTER TABLE audit_log
+    DROP COLUMN player_id;
+
+ALTER INDEX idx_audit_log_created_at RENAME TO idx_admin_audit_log_created_at;
+ALTER TABLE audit_log
+    RENAME CONSTRAINT audit_log_pkey TO admin_audit_log_pkey;
+ALTER TABLE audit_log
+    RENAME TO admin_audit_log;
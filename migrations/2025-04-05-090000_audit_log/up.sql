@@ -0,0 +1,15 @@
+-- AIDEV-NOTE: the admin audit log becomes the audit log of every sensitive action. Actions of
+-- players on their own account, like logging in or changing their password, record the
+-- player; actions through the admin API keep recording the declared operator.
+ALTER TABLE admin_audit_log
+    RENAME TO audit_log;
+ALTER TABLE audit_log
+    RENAME CONSTRAINT admin_audit_log_pkey TO audit_log_pkey;
+ALTER INDEX idx_admin_audit_log_created_at RENAME TO idx_audit_log_created_at;
+
+ALTER TABLE audit_log
+    -- Player who took the action, NULL for operators and the server itself
+    ADD COLUMN player_id UUID NULL REFERENCES player (id) ON DELETE SET NULL;
+
+CREATE INDEX idx_audit_log_player_id ON audit_log (player_id, created_at DESC);
+CREATE INDEX idx_audit_log_action ON audit_log (action, created_at DESC);
@@ -0,0 +1,5 @@
+DROP TABLE alliance_donation;
+DROP TABLE alliance_invite;
+DROP TABLE alliance_member;
+DROP TABLE alliance;
+DROP TYPE IF EXISTS alliance_role;
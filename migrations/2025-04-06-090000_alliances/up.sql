@@ -0,0 +1,83 @@
+CREATE TYPE alliance_role AS ENUM ('member', 'officer', 'leader');
+
+-- AIDEV-NOTE: The treasury only ever grows through donations of the members, nothing spends
+-- it yet. It is lost when the last member leaves and the alliance is disbanded.
+CREATE TABLE alliance
+(
+    id         UUID        NOT NULL DEFAULT uuidv7(),
+    name       TEXT        NOT NULL,
+    tag        TEXT        NOT NULL,
+    food       BIGINT      NOT NULL DEFAULT 0,
+    wood       BIGINT      NOT NULL DEFAULT 0,
+    stone      BIGINT      NOT NULL DEFAULT 0,
+    gold       BIGINT      NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    CONSTRAINT alliance_treasury CHECK (food >= 0 AND wood >= 0 AND stone >= 0 AND gold >= 0)
+);
+
+CREATE UNIQUE INDEX idx_alliance_name ON alliance (lower(name));
+CREATE UNIQUE INDEX idx_alliance_tag ON alliance (lower(tag));
+
+CREATE TRIGGER set_alliance_updated_at
+    BEFORE UPDATE
+    ON alliance
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
+
+-- A player is in at most one alliance, and every alliance has exactly one leader
+CREATE TABLE alliance_member
+(
+    player_id   UUID          NOT NULL,
+    alliance_id UUID          NOT NULL,
+    role        alliance_role NOT NULL DEFAULT 'member'::alliance_role,
+    joined_at   TIMESTAMPTZ   NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (player_id),
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE,
+    FOREIGN KEY (alliance_id) REFERENCES alliance (id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_alliance_member_alliance ON alliance_member (alliance_id, joined_at);
+CREATE UNIQUE INDEX idx_alliance_member_leader ON alliance_member (alliance_id)
+    WHERE role = 'leader';
+
+-- Players join an alliance by accepting an invite from its leader or an officer
+CREATE TABLE alliance_invite
+(
+    id          UUID        NOT NULL DEFAULT uuidv7(),
+    alliance_id UUID        NOT NULL,
+    player_id   UUID        NOT NULL,
+    invited_by  UUID        NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (alliance_id) REFERENCES alliance (id) ON DELETE CASCADE,
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE,
+    FOREIGN KEY (invited_by) REFERENCES player (id) ON DELETE SET NULL,
+    UNIQUE (alliance_id, player_id)
+);
+
+CREATE INDEX idx_alliance_invite_player ON alliance_invite (player_id);
+
+-- Every donation to a treasury, so alliances can see who contributed what
+CREATE TABLE alliance_donation
+(
+    id          UUID        NOT NULL DEFAULT uuidv7(),
+    alliance_id UUID        NOT NULL,
+    player_id   UUID        NULL,
+    food        BIGINT      NOT NULL,
+    wood        BIGINT      NOT NULL,
+    stone       BIGINT      NOT NULL,
+    gold        BIGINT      NOT NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (alliance_id) REFERENCES alliance (id) ON DELETE CASCADE,
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE SET NULL,
+    CONSTRAINT alliance_donation_amounts CHECK (food >= 0 AND wood >= 0 AND stone >= 0 AND gold >= 0)
+);
+
+CREATE INDEX idx_alliance_donation_alliance ON alliance_donation (alliance_id, created_at);
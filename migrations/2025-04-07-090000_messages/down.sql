@@ -0,0 +1,2 @@
+DROP TABLE message;
+DROP TYPE IF EXISTS message_kind;
@@ -0,0 +1,23 @@
+CREATE TYPE message_kind AS ENUM ('player', 'system', 'battle_report', 'training_complete');
+
+-- Every message has a single recipient, who can delete it without affecting anyone else.
+-- Messages from the server have no sender, and carry what they are about in the details.
+CREATE TABLE message
+(
+    id           UUID         NOT NULL DEFAULT uuidv7(),
+    recipient_id UUID         NOT NULL,
+    sender_id    UUID         NULL,
+    kind         message_kind NOT NULL DEFAULT 'player'::message_kind,
+    subject      TEXT         NOT NULL,
+    body         TEXT         NOT NULL,
+    details      JSONB        NOT NULL DEFAULT '{}'::jsonb,
+    read_at      TIMESTAMPTZ  NULL,
+    created_at   TIMESTAMPTZ  NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (recipient_id) REFERENCES player (id) ON DELETE CASCADE,
+    FOREIGN KEY (sender_id) REFERENCES player (id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_message_recipient ON message (recipient_id, created_at DESC);
+CREATE INDEX idx_message_unread ON message (recipient_id) WHERE read_at IS NULL;
@@ -0,0 +1,3 @@
+DROP TABLE chat_mute;
+DROP TABLE chat_message;
+DROP TYPE IF EXISTS chat_channel;
@@ -0,0 +1,34 @@
+CREATE TYPE chat_channel AS ENUM ('global', 'alliance');
+
+-- Messages of the alliance channel belong to the alliance they were posted in, and go with it
+CREATE TABLE chat_message
+(
+    id          UUID         NOT NULL DEFAULT uuidv7(),
+    channel     chat_channel NOT NULL,
+    alliance_id UUID         NULL,
+    player_id   UUID         NULL,
+    body        TEXT         NOT NULL,
+    created_at  TIMESTAMPTZ  NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (alliance_id) REFERENCES alliance (id) ON DELETE CASCADE,
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE SET NULL,
+    CONSTRAINT chat_message_alliance CHECK ((channel = 'alliance') = (alliance_id IS NOT NULL))
+);
+
+CREATE INDEX idx_chat_message_channel ON chat_message (channel, alliance_id, created_at DESC);
+CREATE INDEX idx_chat_message_player ON chat_message (player_id, created_at);
+
+-- Players muted by a moderator cannot post in any channel until the mute runs out
+CREATE TABLE chat_mute
+(
+    player_id   UUID        NOT NULL,
+    muted_until TIMESTAMPTZ NOT NULL,
+    reason      TEXT        NULL,
+    muted_by    UUID        NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (player_id),
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE,
+    FOREIGN KEY (muted_by) REFERENCES player (id) ON DELETE SET NULL
+);
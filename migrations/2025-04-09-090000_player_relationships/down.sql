@@ -0,0 +1,2 @@
+DROP TABLE player_relationship;
+DROP TYPE IF EXISTS relationship_status;
@@ -0,0 +1,29 @@
+CREATE TYPE relationship_status AS ENUM ('requested', 'accepted', 'blocked');
+
+-- A relationship points from the player who started it to the other player: the friend
+-- request they sent, which the other player turns into a friendship by accepting it, or the
+-- block they put on the other player. A pair of players shares at most one friendship, while
+-- both of them can block the other.
+CREATE TABLE player_relationship
+(
+    id         UUID                NOT NULL DEFAULT uuidv7(),
+    player_id  UUID                NOT NULL,
+    other_id   UUID                NOT NULL,
+    status     relationship_status NOT NULL,
+    created_at TIMESTAMPTZ         NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ         NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE,
+    FOREIGN KEY (other_id) REFERENCES player (id) ON DELETE CASCADE,
+    UNIQUE (player_id, other_id),
+    CONSTRAINT player_relationship_other CHECK (player_id <> other_id)
+);
+
+CREATE INDEX idx_player_relationship_other ON player_relationship (other_id, status);
+
+CREATE TRIGGER set_player_relationship_updated_at
+    BEFORE UPDATE
+    ON player_relationship
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
@@ -0,0 +1,90 @@
+DROP VIEW resource_generation;
+CREATE VIEW resource_generation AS
+SELECT pb.player_id,
+       SUM(br.population)::bigint    as population,
+       SUM(br.food)::bigint          as food,
+       SUM(br.wood)::bigint          as wood,
+       SUM(br.stone)::bigint         as stone,
+       SUM(br.gold)::bigint          as gold,
+       SUM(br.food_acc_cap)::bigint  as food_acc_cap,
+       SUM(br.wood_acc_cap)::bigint  as wood_acc_cap,
+       SUM(br.stone_acc_cap)::bigint as stone_acc_cap,
+       SUM(br.gold_acc_cap)::bigint  as gold_acc_cap
+FROM player_building pb
+         LEFT JOIN public.building_resource br
+                   ON pb.building_id = br.building_id
+                       AND pb.level = br.building_level
+GROUP BY pb.player_id;
+
+CREATE OR REPLACE FUNCTION update_player_resource_caps_fn()
+    RETURNS TRIGGER
+    LANGUAGE PLPGSQL
+AS
+$$
+DECLARE
+    old_caps RECORD;
+    new_caps RECORD;
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        SELECT food_cap, wood_cap, stone_cap, gold_cap
+        INTO new_caps
+        FROM building_resource
+        WHERE building_id = NEW.building_id
+          AND building_level = NEW.level;
+
+        UPDATE player_resource
+        SET food_cap  = food_cap + new_caps.food_cap,
+            wood_cap  = wood_cap + new_caps.wood_cap,
+            stone_cap = stone_cap + new_caps.stone_cap,
+            gold_cap  = gold_cap + new_caps.gold_cap
+        WHERE player_id = NEW.player_id;
+
+    ELSIF TG_OP = 'UPDATE' THEN
+        SELECT food_cap, wood_cap, stone_cap, gold_cap
+        INTO old_caps
+        FROM building_resource
+        WHERE building_id = OLD.building_id
+          AND building_level = OLD.level;
+
+        SELECT food_cap, wood_cap, stone_cap, gold_cap
+        INTO new_caps
+        FROM building_resource
+        WHERE building_id = NEW.building_id
+          AND building_level = NEW.level;
+
+        UPDATE player_resource
+        SET food_cap  = food_cap - old_caps.food_cap + new_caps.food_cap,
+            wood_cap  = wood_cap - old_caps.wood_cap + new_caps.wood_cap,
+            stone_cap = stone_cap - old_caps.stone_cap + new_caps.stone_cap,
+            gold_cap  = gold_cap - old_caps.gold_cap + new_caps.gold_cap
+        WHERE player_id = NEW.player_id;
+    END IF;
+
+    RETURN NEW;
+END;
+$$;
+
+DROP TRIGGER set_player_accumulator_settlement ON player_accumulator;
+DROP TRIGGER set_player_resource_settlement ON player_resource;
+DROP TRIGGER set_player_building_settlement ON player_building;
+DROP FUNCTION set_capital_settlement_fn;
+
+-- Buildings, storage and accumulators of settlements other than the capital are lost
+DELETE FROM settlement WHERE NOT is_capital;
+
+ALTER TABLE planned_action
+    DROP COLUMN settlement_id;
+ALTER TABLE player_accumulator
+    DROP COLUMN settlement_id,
+    ADD CONSTRAINT player_accumulator_player_id_key UNIQUE (player_id);
+ALTER TABLE player_resource
+    DROP COLUMN settlement_id,
+    ADD CONSTRAINT player_resource_player_id_key UNIQUE (player_id);
+ALTER TABLE player_building
+    DROP COLUMN settlement_id;
+
+DROP INDEX IF EXISTS idx_player_accumulator_player;
+DROP INDEX IF EXISTS idx_player_resource_player;
+
+DROP FUNCTION capital_settlement_id;
+DROP TABLE settlement;
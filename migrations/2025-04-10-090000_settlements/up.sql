@@ -0,0 +1,210 @@
+-- AIDEV-NOTE: A player's buildings, storage and accumulator belong to one of their
+-- settlements. Every player has exactly one capital, created with their first building or
+-- resource row, and founds further settlements once the Keep of their capital is high
+-- enough. Units, items and modifiers stay player-wide.
+CREATE TABLE settlement
+(
+    id         UUID        NOT NULL DEFAULT uuidv7(),
+    player_id  UUID        NOT NULL,
+    name       TEXT        NOT NULL,
+    is_capital BOOLEAN     NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE,
+    UNIQUE (player_id, name)
+);
+
+CREATE UNIQUE INDEX idx_settlement_capital ON settlement (player_id) WHERE is_capital;
+
+CREATE TRIGGER set_settlement_updated_at
+    BEFORE UPDATE
+    ON settlement
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
+
+-- Returns the capital of a player, founding it if they have none yet
+CREATE OR REPLACE FUNCTION capital_settlement_id(capital_player_id UUID)
+    RETURNS UUID
+    LANGUAGE PLPGSQL
+AS
+$$
+DECLARE
+    capital_id UUID;
+BEGIN
+    SELECT id INTO capital_id FROM settlement WHERE player_id = capital_player_id AND is_capital;
+    IF capital_id IS NULL THEN
+        INSERT INTO settlement (player_id, name, is_capital)
+        VALUES (capital_player_id, 'Capital', TRUE)
+        RETURNING id INTO capital_id;
+    END IF;
+    RETURN capital_id;
+END;
+$$;
+
+INSERT INTO settlement (player_id, name, is_capital)
+SELECT id, 'Capital', TRUE
+FROM player;
+
+-- Existing buildings, storage and accumulators are moved into the capital
+ALTER TABLE player_building
+    ADD COLUMN settlement_id UUID NULL REFERENCES settlement (id) ON DELETE CASCADE;
+ALTER TABLE player_resource
+    ADD COLUMN settlement_id UUID NULL REFERENCES settlement (id) ON DELETE CASCADE;
+ALTER TABLE player_accumulator
+    ADD COLUMN settlement_id UUID NULL REFERENCES settlement (id) ON DELETE CASCADE;
+
+-- The moves neither touch updated_at nor the storage caps
+ALTER TABLE player_building
+    DISABLE TRIGGER USER;
+ALTER TABLE player_resource
+    DISABLE TRIGGER USER;
+ALTER TABLE player_accumulator
+    DISABLE TRIGGER USER;
+
+UPDATE player_building pb
+SET settlement_id = s.id
+FROM settlement s
+WHERE s.player_id = pb.player_id
+  AND s.is_capital;
+UPDATE player_resource pr
+SET settlement_id = s.id
+FROM settlement s
+WHERE s.player_id = pr.player_id
+  AND s.is_capital;
+UPDATE player_accumulator pa
+SET settlement_id = s.id
+FROM settlement s
+WHERE s.player_id = pa.player_id
+  AND s.is_capital;
+
+ALTER TABLE player_building
+    ENABLE TRIGGER USER;
+ALTER TABLE player_resource
+    ENABLE TRIGGER USER;
+ALTER TABLE player_accumulator
+    ENABLE TRIGGER USER;
+
+ALTER TABLE player_building
+    ALTER COLUMN settlement_id SET NOT NULL;
+ALTER TABLE player_resource
+    ALTER COLUMN settlement_id SET NOT NULL,
+    DROP CONSTRAINT player_resource_player_id_key,
+    ADD CONSTRAINT player_resource_settlement_id_key UNIQUE (settlement_id);
+ALTER TABLE player_accumulator
+    ALTER COLUMN settlement_id SET NOT NULL,
+    DROP CONSTRAINT player_accumulator_player_id_key,
+    ADD CONSTRAINT player_accumulator_settlement_id_key UNIQUE (settlement_id);
+
+-- Construct plans build in a settlement, in the capital when it is not set
+ALTER TABLE planned_action
+    ADD COLUMN settlement_id UUID NULL REFERENCES settlement (id) ON DELETE CASCADE;
+
+CREATE INDEX idx_player_building_settlement ON player_building (settlement_id, building_id);
+CREATE INDEX idx_player_resource_player ON player_resource (player_id);
+CREATE INDEX idx_player_accumulator_player ON player_accumulator (player_id);
+
+-- Rows inserted without a settlement, like the ones of the new player and faction triggers,
+-- go to the capital
+CREATE OR REPLACE FUNCTION set_capital_settlement_fn()
+    RETURNS TRIGGER
+    LANGUAGE PLPGSQL
+AS
+$$
+BEGIN
+    IF NEW.settlement_id IS NULL THEN
+        NEW.settlement_id := capital_settlement_id(NEW.player_id);
+    END IF;
+    RETURN NEW;
+END;
+$$;
+
+CREATE TRIGGER set_player_building_settlement
+    BEFORE INSERT
+    ON player_building
+    FOR EACH ROW
+EXECUTE FUNCTION set_capital_settlement_fn();
+
+CREATE TRIGGER set_player_resource_settlement
+    BEFORE INSERT
+    ON player_resource
+    FOR EACH ROW
+EXECUTE FUNCTION set_capital_settlement_fn();
+
+CREATE TRIGGER set_player_accumulator_settlement
+    BEFORE INSERT
+    ON player_accumulator
+    FOR EACH ROW
+EXECUTE FUNCTION set_capital_settlement_fn();
+
+-- Storage caps are raised in the settlement of the building
+CREATE OR REPLACE FUNCTION update_player_resource_caps_fn()
+    RETURNS TRIGGER
+    LANGUAGE PLPGSQL
+AS
+$$
+DECLARE
+    old_caps RECORD;
+    new_caps RECORD;
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        -- Get the new caps from building_resource based on the building and level
+        SELECT food_cap, wood_cap, stone_cap, gold_cap
+        INTO new_caps
+        FROM building_resource
+        WHERE building_id = NEW.building_id
+          AND building_level = NEW.level;
+
+        UPDATE player_resource
+        SET food_cap  = food_cap + new_caps.food_cap,
+            wood_cap  = wood_cap + new_caps.wood_cap,
+            stone_cap = stone_cap + new_caps.stone_cap,
+            gold_cap  = gold_cap + new_caps.gold_cap
+        WHERE settlement_id = NEW.settlement_id;
+
+    ELSIF TG_OP = 'UPDATE' THEN
+        -- Get the previous cap values from building_resource
+        SELECT food_cap, wood_cap, stone_cap, gold_cap
+        INTO old_caps
+        FROM building_resource
+        WHERE building_id = OLD.building_id
+          AND building_level = OLD.level;
+
+        -- Get the updated cap values from building_resource
+        SELECT food_cap, wood_cap, stone_cap, gold_cap
+        INTO new_caps
+        FROM building_resource
+        WHERE building_id = NEW.building_id
+          AND building_level = NEW.level;
+
+        UPDATE player_resource
+        SET food_cap  = food_cap - old_caps.food_cap + new_caps.food_cap,
+            wood_cap  = wood_cap - old_caps.wood_cap + new_caps.wood_cap,
+            stone_cap = stone_cap - old_caps.stone_cap + new_caps.stone_cap,
+            gold_cap  = gold_cap - old_caps.gold_cap + new_caps.gold_cap
+        WHERE settlement_id = NEW.settlement_id;
+    END IF;
+
+    RETURN NEW;
+END;
+$$;
+
+DROP VIEW resource_generation;
+CREATE VIEW resource_generation AS
+SELECT pb.settlement_id,
+       pb.player_id,
+       SUM(br.population)::bigint    as population,
+       SUM(br.food)::bigint          as food,
+       SUM(br.wood)::bigint          as wood,
+       SUM(br.stone)::bigint         as stone,
+       SUM(br.gold)::bigint          as gold,
+       SUM(br.food_acc_cap)::bigint  as food_acc_cap,
+       SUM(br.wood_acc_cap)::bigint  as wood_acc_cap,
+       SUM(br.stone_acc_cap)::bigint as stone_acc_cap,
+       SUM(br.gold_acc_cap)::bigint  as gold_acc_cap
+FROM player_building pb
+         LEFT JOIN public.building_resource br
+                   ON pb.building_id = br.building_id
+                       AND pb.level = br.building_level
+GROUP BY pb.settlement_id, pb.player_id;
@@ -0,0 +1,11 @@
+DROP TABLE scout_mission;
+DROP TABLE intel_report;
+DELETE FROM player_building
+WHERE building_id IN (SELECT id FROM building WHERE name = 'Watchtower' AND faction = 'neutral');
+DELETE FROM building WHERE name = 'Watchtower' AND faction = 'neutral';
+-- Postgres cannot drop a single enum value; remove any espionage jobs and mails so the
+-- leftover values are unused.
+DELETE FROM message WHERE kind IN ('intel_report', 'scouts_spotted');
+DELETE FROM recurring_job WHERE job_type = 'espionage';
+DELETE FROM job_dead_letter WHERE job_type = 'espionage';
+DELETE FROM job WHERE job_type = 'espionage';
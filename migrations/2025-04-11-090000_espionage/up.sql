@@ -0,0 +1,75 @@
+ALTER TYPE job_type ADD VALUE IF NOT EXISTS 'espionage';
+ALTER TYPE message_kind ADD VALUE IF NOT EXISTS 'intel_report';
+ALTER TYPE message_kind ADD VALUE IF NOT EXISTS 'scouts_spotted';
+
+-- The Watchtower hides a city's army from scouts and spots them, every faction builds it
+INSERT INTO building (name, max_level, max_count, faction, starter)
+VALUES ('Watchtower', 10, 1, 'neutral', FALSE);
+
+-- AIDEV-NOTE: What the scouts of a mission saw in the capital of the defender. Resources are
+-- exact, the army is an estimate whose noise grows with the defender's Watchtower, and
+-- `noise_percent` tells the attacker how far off the estimate may be. Reports are pruned
+-- along with battle reports.
+CREATE TABLE intel_report
+(
+    id            UUID        NOT NULL DEFAULT uuidv7(),
+    attacker_id   UUID        NOT NULL,
+    defender_id   UUID        NOT NULL,
+    food          BIGINT      NOT NULL DEFAULT 0,
+    wood          BIGINT      NOT NULL DEFAULT 0,
+    stone         BIGINT      NOT NULL DEFAULT 0,
+    gold          BIGINT      NOT NULL DEFAULT 0,
+    army          JSONB       NOT NULL DEFAULT '[]'::jsonb,
+    noise_percent INTEGER     NOT NULL DEFAULT 0,
+    spotted       BOOLEAN     NOT NULL DEFAULT FALSE,
+    scouted_at    TIMESTAMPTZ NOT NULL DEFAULT now(),
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at    TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (attacker_id) REFERENCES player (id) ON DELETE CASCADE,
+    FOREIGN KEY (defender_id) REFERENCES player (id) ON DELETE CASCADE,
+    CONSTRAINT intel_report_noise CHECK (noise_percent BETWEEN 0 AND 100)
+);
+
+CREATE INDEX idx_intel_report_attacker ON intel_report (attacker_id, scouted_at DESC);
+CREATE INDEX idx_intel_report_scouted_at ON intel_report (scouted_at);
+
+CREATE TRIGGER set_intel_report_updated_at
+    BEFORE UPDATE
+    ON intel_report
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
+
+-- AIDEV-NOTE: Scouts sent to another player. They leave the attacker's army when the mission
+-- starts, and an espionage job writes the intel report on arrival and brings them back. A
+-- mission is travelling until `completed_at` is set.
+CREATE TABLE scout_mission
+(
+    id           UUID        NOT NULL DEFAULT uuidv7(),
+    attacker_id  UUID        NOT NULL,
+    defender_id  UUID        NOT NULL,
+    scouts       BIGINT      NOT NULL,
+    job_id       UUID        NULL,
+    report_id    UUID        NULL,
+    arrives_at   TIMESTAMPTZ NOT NULL,
+    completed_at TIMESTAMPTZ NULL,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (attacker_id) REFERENCES player (id) ON DELETE CASCADE,
+    FOREIGN KEY (defender_id) REFERENCES player (id) ON DELETE CASCADE,
+    FOREIGN KEY (job_id) REFERENCES job (id) ON DELETE SET NULL,
+    FOREIGN KEY (report_id) REFERENCES intel_report (id) ON DELETE SET NULL,
+    CONSTRAINT scout_mission_scouts CHECK (scouts > 0),
+    CONSTRAINT scout_mission_target CHECK (attacker_id <> defender_id)
+);
+
+CREATE INDEX idx_scout_mission_attacker ON scout_mission (attacker_id, created_at);
+
+CREATE TRIGGER set_scout_mission_updated_at
+    BEFORE UPDATE
+    ON scout_mission
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
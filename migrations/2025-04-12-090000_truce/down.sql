@@ -0,0 +1,3 @@
+-- Postgres cannot drop a single enum value; remove the truce modifiers so the leftover value
+-- is unused. Their items, active modifiers and history go with them.
+DELETE FROM modifiers WHERE target_type = 'truce';
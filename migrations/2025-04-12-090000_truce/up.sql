@@ -0,0 +1,2 @@
+-- Truce modifiers keep a player out of combat while they are active, see the truce items
+ALTER TYPE modifier_target ADD VALUE IF NOT EXISTS 'truce';
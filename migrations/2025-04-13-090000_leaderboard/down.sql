@@ -0,0 +1,8 @@
+DROP TABLE leaderboard_entry;
+DROP TABLE player_score;
+DROP TYPE leaderboard_category;
+-- Postgres cannot drop a single enum value; remove any leaderboard jobs so the
+-- leftover 'leaderboard' job_type value is unused.
+DELETE FROM recurring_job WHERE job_type = 'leaderboard';
+DELETE FROM job_dead_letter WHERE job_type = 'leaderboard';
+DELETE FROM job WHERE job_type = 'leaderboard';
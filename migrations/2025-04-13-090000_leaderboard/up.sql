@@ -0,0 +1,39 @@
+-- AIDEV-NOTE: scores are kept up to date per player as upgrades and trainings complete, and a
+-- recurring leaderboard job recalculates every score and snapshots the rankings.
+ALTER TYPE job_type ADD VALUE IF NOT EXISTS 'leaderboard';
+
+CREATE TYPE leaderboard_category AS ENUM ('power', 'economy', 'alliance');
+
+-- Points of every player, split by where they come from
+CREATE TABLE player_score
+(
+    player_id       UUID        NOT NULL,
+    building_points BIGINT      NOT NULL DEFAULT 0 CHECK (building_points >= 0),
+    unit_points     BIGINT      NOT NULL DEFAULT 0 CHECK (unit_points >= 0),
+    tech_points     BIGINT      NOT NULL DEFAULT 0 CHECK (tech_points >= 0),
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (player_id),
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE
+);
+
+CREATE TRIGGER set_player_score_updated_at
+    BEFORE UPDATE
+    ON player_score
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
+
+-- The rankings of the last snapshot. Subjects are players, or alliances on the alliance board.
+CREATE TABLE leaderboard_entry
+(
+    category    leaderboard_category NOT NULL,
+    subject_id  UUID                 NOT NULL,
+    rank        INT                  NOT NULL CHECK (rank > 0),
+    score       BIGINT               NOT NULL,
+    snapshot_at TIMESTAMPTZ          NOT NULL,
+
+    PRIMARY KEY (category, subject_id)
+);
+
+CREATE INDEX leaderboard_entry_category_rank_idx ON leaderboard_entry (category, rank);
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS player_quest;
+DROP TABLE IF EXISTS quest;
+DROP TYPE IF EXISTS quest_objective;
@@ -0,0 +1,59 @@
+-- AIDEV-NOTE: quest chains guiding new players. Quests are game data, seeded from
+-- seeds/400_quests.sql, and unlock once every quest they require is claimed. Players progress
+-- them by building, training and collecting, and claim their rewards once they reach the
+-- objective's amount.
+CREATE TYPE quest_objective AS ENUM ('build', 'train', 'collect');
+
+-- The objective names its target: a building for 'build', whose level it has to reach, a
+-- unit for 'train', and a resource for 'collect', of which the capital has to store the amount.
+CREATE TABLE quest
+(
+    id                   TEXT            NOT NULL,
+    name                 TEXT            NOT NULL,
+    description          TEXT            NOT NULL,
+    position             INTEGER         NOT NULL,
+    requires             TEXT[]          NOT NULL DEFAULT '{}',
+    objective            quest_objective NOT NULL,
+    target               TEXT            NOT NULL,
+    amount               BIGINT          NOT NULL CHECK (amount > 0),
+    reward_food          BIGINT          NOT NULL DEFAULT 0 CHECK (reward_food >= 0),
+    reward_wood          BIGINT          NOT NULL DEFAULT 0 CHECK (reward_wood >= 0),
+    reward_stone         BIGINT          NOT NULL DEFAULT 0 CHECK (reward_stone >= 0),
+    reward_gold          BIGINT          NOT NULL DEFAULT 0 CHECK (reward_gold >= 0),
+    reward_item_id       UUID            NULL,
+    reward_item_quantity BIGINT          NOT NULL DEFAULT 0 CHECK (reward_item_quantity >= 0),
+    created_at           TIMESTAMPTZ     NOT NULL DEFAULT now(),
+    updated_at           TIMESTAMPTZ     NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (reward_item_id) REFERENCES item (id) ON DELETE SET NULL
+);
+
+CREATE TRIGGER set_quest_updated_at
+    BEFORE UPDATE
+    ON quest
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
+
+-- Progress of each player towards the quests they unlocked
+CREATE TABLE player_quest
+(
+    player_id  UUID        NOT NULL,
+    quest_id   TEXT        NOT NULL,
+    progress   BIGINT      NOT NULL DEFAULT 0 CHECK (progress >= 0),
+    claimed_at TIMESTAMPTZ NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (player_id, quest_id),
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE,
+    FOREIGN KEY (quest_id) REFERENCES quest (id) ON DELETE CASCADE
+);
+
+CREATE INDEX player_quest_quest_id_idx ON player_quest (quest_id);
+
+CREATE TRIGGER set_player_quest_updated_at
+    BEFORE UPDATE
+    ON player_quest
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
@@ -0,0 +1,7 @@
+DROP TABLE IF EXISTS player_legacy;
+DROP TABLE IF EXISTS season_standing;
+DROP TABLE IF EXISTS season;
+-- Postgres cannot drop a single enum value; remove any season jobs so the
+-- leftover 'season' job_type value is unused.
+DELETE FROM job_dead_letter WHERE job_type = 'season';
+DELETE FROM job WHERE job_type = 'season';
@@ -0,0 +1,67 @@
+-- AIDEV-NOTE: seasons. A season job ends each season once it is over: it snapshots the
+-- leaderboards one last time, archives the standings and grants the ranked players legacy
+-- points. Legacy points belong to the account, so world resets never wipe them.
+ALTER TYPE job_type ADD VALUE IF NOT EXISTS 'season';
+
+CREATE TABLE season
+(
+    id         UUID        NOT NULL DEFAULT uuidv7(),
+    number     INT         NOT NULL CHECK (number > 0),
+    name       TEXT        NOT NULL,
+    starts_at  TIMESTAMPTZ NOT NULL,
+    ends_at    TIMESTAMPTZ NOT NULL,
+    ended_at   TIMESTAMPTZ NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    CONSTRAINT season_number UNIQUE (number),
+    CHECK (ends_at > starts_at)
+);
+
+-- Only one season can be running or upcoming at a time
+CREATE UNIQUE INDEX season_one_open_idx ON season ((ended_at IS NULL)) WHERE ended_at IS NULL;
+
+CREATE TRIGGER set_season_updated_at
+    BEFORE UPDATE
+    ON season
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
+
+-- The final rankings of an ended season. Names are archived along with the subjects, which
+-- may be renamed or deleted later on.
+CREATE TABLE season_standing
+(
+    season_id     UUID                 NOT NULL,
+    category      leaderboard_category NOT NULL,
+    subject_id    UUID                 NOT NULL,
+    name          TEXT                 NOT NULL,
+    rank          INT                  NOT NULL CHECK (rank > 0),
+    score         BIGINT               NOT NULL,
+    legacy_points BIGINT               NOT NULL DEFAULT 0 CHECK (legacy_points >= 0),
+
+    PRIMARY KEY (season_id, category, subject_id),
+    FOREIGN KEY (season_id) REFERENCES season (id) ON DELETE CASCADE
+);
+
+CREATE INDEX season_standing_season_category_rank_idx ON season_standing (season_id, category, rank);
+CREATE INDEX season_standing_subject_id_idx ON season_standing (subject_id);
+
+-- Legacy points players earned over all seasons
+CREATE TABLE player_legacy
+(
+    player_id     UUID        NOT NULL,
+    legacy_points BIGINT      NOT NULL DEFAULT 0 CHECK (legacy_points >= 0),
+    seasons       INT         NOT NULL DEFAULT 0 CHECK (seasons >= 0),
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at    TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (player_id),
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE
+);
+
+CREATE TRIGGER set_player_legacy_updated_at
+    BEFORE UPDATE
+    ON player_legacy
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
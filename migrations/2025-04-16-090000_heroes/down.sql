@@ -0,0 +1,8 @@
+-- Postgres cannot drop a single enum value; remove the hero modifiers so the leftover value is
+-- unused.
+DELETE FROM active_modifiers WHERE source_type = 'hero';
+DELETE FROM modifier_history WHERE source_type = 'hero';
+DROP TABLE IF EXISTS player_hero;
+DROP TABLE IF EXISTS hero_level;
+DROP TABLE IF EXISTS hero;
+DROP TYPE IF EXISTS hero_specialty;
@@ -0,0 +1,77 @@
+-- AIDEV-NOTE: heroes. Heroes are game data, seeded from seeds/500_heroes.sql, that players
+-- recruit and train. Every level of a hero has its own modifier, which applies while the hero
+-- is assigned: army heroes lead the army for a combat bonus, production heroes run a building
+-- for a bonus to its production. The active modifier names the player hero as its source.
+ALTER TYPE modifier_source_type ADD VALUE IF NOT EXISTS 'hero';
+
+CREATE TYPE hero_specialty AS ENUM ('army', 'production');
+
+CREATE TABLE hero
+(
+    id           UUID           NOT NULL DEFAULT uuidv7(),
+    name         TEXT           NOT NULL,
+    description  TEXT           NOT NULL,
+    specialty    hero_specialty NOT NULL,
+    recruit_gold BIGINT         NOT NULL CHECK (recruit_gold >= 0),
+    created_at   TIMESTAMPTZ    NOT NULL DEFAULT now(),
+    updated_at   TIMESTAMPTZ    NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    CONSTRAINT hero_name UNIQUE (name)
+);
+
+CREATE TRIGGER set_hero_updated_at
+    BEFORE UPDATE
+    ON hero
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
+
+-- The experience a hero needs for each level, and the modifier it applies at that level.
+-- Level 1 needs no experience.
+CREATE TABLE hero_level
+(
+    hero_id     UUID   NOT NULL,
+    level       INT    NOT NULL CHECK (level > 0),
+    xp_required BIGINT NOT NULL CHECK (xp_required >= 0),
+    modifier_id UUID   NOT NULL,
+
+    PRIMARY KEY (hero_id, level),
+    FOREIGN KEY (hero_id) REFERENCES hero (id) ON DELETE CASCADE,
+    FOREIGN KEY (modifier_id) REFERENCES modifiers (id) ON DELETE CASCADE
+);
+
+-- Heroes recruited by each player. A hero is assigned to a building or leads the army, never
+-- both, and a building has at most one hero, as does the army.
+CREATE TABLE player_hero
+(
+    id                 UUID        NOT NULL DEFAULT uuidv7(),
+    player_id          UUID        NOT NULL,
+    hero_id            UUID        NOT NULL,
+    level              INT         NOT NULL DEFAULT 1 CHECK (level > 0),
+    xp                 BIGINT      NOT NULL DEFAULT 0 CHECK (xp >= 0),
+    player_building_id UUID        NULL,
+    leads_army         BOOLEAN     NOT NULL DEFAULT false,
+    created_at         TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at         TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE,
+    FOREIGN KEY (hero_id) REFERENCES hero (id) ON DELETE CASCADE,
+    FOREIGN KEY (player_building_id) REFERENCES player_building (id) ON DELETE SET NULL,
+    CONSTRAINT player_hero_roster UNIQUE (player_id, hero_id),
+    CHECK (NOT (leads_army AND player_building_id IS NOT NULL))
+);
+
+CREATE UNIQUE INDEX player_hero_building_idx
+    ON player_hero (player_building_id)
+    WHERE player_building_id IS NOT NULL;
+
+CREATE UNIQUE INDEX player_hero_army_idx
+    ON player_hero (player_id)
+    WHERE leads_army;
+
+CREATE TRIGGER set_player_hero_updated_at
+    BEFORE UPDATE
+    ON player_hero
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
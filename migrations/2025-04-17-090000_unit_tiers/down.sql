@@ -0,0 +1,9 @@
+DROP TABLE unit_upgrade;
+DELETE FROM player_building
+WHERE building_id IN (SELECT id FROM building WHERE name = 'Blacksmith' AND faction = 'neutral');
+DELETE FROM building WHERE name = 'Blacksmith' AND faction = 'neutral';
+-- Upgraded units cannot exist without tiers, players lose them along with the definitions
+DELETE FROM unit WHERE tier > 1;
+ALTER TABLE unit
+    DROP CONSTRAINT unit_tier_positive,
+    DROP COLUMN tier;
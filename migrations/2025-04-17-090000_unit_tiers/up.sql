@@ -0,0 +1,41 @@
+-- AIDEV-NOTE: Units come in tiers. Tier 1 units are trained, higher tiers are only reached by
+-- upgrading trained units at the Blacksmith, and every tier above the first adds to the
+-- attack and defense of the unit in combat math.
+ALTER TABLE unit
+    ADD COLUMN tier INTEGER NOT NULL DEFAULT 1,
+    ADD CONSTRAINT unit_tier_positive CHECK (tier > 0);
+
+-- The Blacksmith upgrades units to higher tiers, every faction builds it
+INSERT INTO building (name, max_level, max_count, faction, starter)
+VALUES ('Blacksmith', 10, 1, 'neutral', FALSE);
+
+-- AIDEV-NOTE: How a unit is upgraded to the next tier. The resources are paid per unit, and
+-- the Blacksmith and military research building (Academy, Cadet School for goblins) of the
+-- capital must have reached the required levels.
+CREATE TABLE unit_upgrade
+(
+    unit_id          UUID        NOT NULL,
+    upgraded_unit_id UUID        NOT NULL,
+    blacksmith_level INTEGER     NOT NULL,
+    research_level   INTEGER     NOT NULL,
+    req_food         BIGINT      NOT NULL DEFAULT 0,
+    req_wood         BIGINT      NOT NULL DEFAULT 0,
+    req_stone        BIGINT      NOT NULL DEFAULT 0,
+    req_gold         BIGINT      NOT NULL DEFAULT 0,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (unit_id),
+    FOREIGN KEY (unit_id) REFERENCES unit (id) ON DELETE CASCADE,
+    FOREIGN KEY (upgraded_unit_id) REFERENCES unit (id) ON DELETE CASCADE,
+    CONSTRAINT unit_upgrade_upgraded_unique UNIQUE (upgraded_unit_id),
+    CONSTRAINT unit_upgrade_levels CHECK (blacksmith_level > 0 AND research_level >= 0),
+    CONSTRAINT unit_upgrade_costs CHECK (req_food >= 0 AND req_wood >= 0 AND req_stone >= 0 AND
+                                         req_gold >= 0)
+);
+
+CREATE TRIGGER set_unit_upgrade_updated_at
+    BEFORE UPDATE
+    ON unit_upgrade
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
@@ -0,0 +1,2 @@
+DROP TABLE army_preset_unit;
+DROP TABLE army_preset;
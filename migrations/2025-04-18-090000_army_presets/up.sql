@@ -0,0 +1,32 @@
+-- AIDEV-NOTE: Named army compositions a player saves to train again in one go. Training a
+-- preset queues every unit of it at the buildings of the capital, or none at all.
+CREATE TABLE army_preset
+(
+    id         UUID        NOT NULL DEFAULT uuidv7(),
+    player_id  UUID        NOT NULL,
+    name       TEXT        NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE,
+    CONSTRAINT army_preset_name_unique UNIQUE (player_id, name)
+);
+
+CREATE TRIGGER set_army_preset_updated_at
+    BEFORE UPDATE
+    ON army_preset
+    FOR EACH ROW
+EXECUTE FUNCTION set_current_timestamp_updated_at();
+
+CREATE TABLE army_preset_unit
+(
+    preset_id UUID   NOT NULL,
+    unit_id   UUID   NOT NULL,
+    quantity  BIGINT NOT NULL,
+
+    PRIMARY KEY (preset_id, unit_id),
+    FOREIGN KEY (preset_id) REFERENCES army_preset (id) ON DELETE CASCADE,
+    FOREIGN KEY (unit_id) REFERENCES unit (id) ON DELETE CASCADE,
+    CONSTRAINT army_preset_unit_quantity CHECK (quantity > 0)
+);
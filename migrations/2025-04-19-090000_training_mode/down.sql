@@ -0,0 +1,3 @@
+ALTER TABLE player_building
+    DROP COLUMN training_mode;
+DROP TYPE IF EXISTS training_mode;
@@ -0,0 +1,7 @@
+-- AIDEV-NOTE: How a military building works through its training queue. In parallel mode
+-- every entry trains at once, in sequential mode one entry trains at a time and the next
+-- one waits as pending until the previous completes.
+CREATE TYPE training_mode AS ENUM ('parallel', 'sequential');
+
+ALTER TABLE player_building
+    ADD COLUMN training_mode training_mode NOT NULL DEFAULT 'parallel';
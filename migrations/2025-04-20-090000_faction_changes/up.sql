@@ -0,0 +1,15 @@
+-- AIDEV-NOTE: Every faction change of a player after their first choice. The latest row
+-- gates the next change behind the configured cooldown, the rows are kept as a history.
+CREATE TABLE faction_change
+(
+    id           UUID         NOT NULL DEFAULT uuidv7(),
+    player_id    UUID         NOT NULL,
+    from_faction faction_code NOT NULL,
+    to_faction   faction_code NOT NULL,
+    changed_at   TIMESTAMPTZ  NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (id),
+    FOREIGN KEY (player_id) REFERENCES player (id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_faction_change_player ON faction_change (player_id, changed_at);
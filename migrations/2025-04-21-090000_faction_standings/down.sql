@@ -0,0 +1,6 @@
+DROP TABLE faction_standing;
+-- Postgres cannot drop a single enum value; remove any faction jobs so the
+-- leftover 'faction' job_type value is unused.
+DELETE FROM recurring_job WHERE job_type = 'faction';
+DELETE FROM job_dead_letter WHERE job_type = 'faction';
+DELETE FROM job WHERE job_type = 'faction';
@@ -0,0 +1,22 @@
+-- AIDEV-NOTE: faction standings. A recurring faction job aggregates the players, combined
+-- power and war score of every playable faction into one row per faction, and grants the
+-- war victory modifiers (seeds/600_faction_standings.sql) to the members of the leading
+-- faction until the next aggregation.
+ALTER TYPE job_type ADD VALUE IF NOT EXISTS 'faction';
+
+CREATE TABLE faction_standing
+(
+    faction     faction_code NOT NULL,
+    -- Members that did not leave the game
+    players     BIGINT       NOT NULL DEFAULT 0 CHECK (players >= 0),
+    -- Army points of the members, as last scored for the leaderboards
+    power       BIGINT       NOT NULL DEFAULT 0 CHECK (power >= 0),
+    -- Battles members won against players of other factions since the previous aggregation
+    war_score   BIGINT       NOT NULL DEFAULT 0 CHECK (war_score >= 0),
+    -- 1-based, by war score and then power, factions with the same both share a rank
+    rank        INT          NOT NULL CHECK (rank > 0),
+    computed_at TIMESTAMPTZ  NOT NULL,
+
+    PRIMARY KEY (faction),
+    FOREIGN KEY (faction) REFERENCES faction (id) ON DELETE CASCADE
+);
@@ -0,0 +1,83 @@
+CREATE OR REPLACE FUNCTION new_player_resource_fn()
+    RETURNS TRIGGER
+    LANGUAGE PLPGSQL
+AS
+$$
+BEGIN
+    INSERT INTO player_resource (player_id) VALUES (NEW.id);
+    RETURN NEW;
+END;
+$$;
+
+CREATE TRIGGER new_player_resource_trigger
+    AFTER INSERT
+    ON player
+    FOR EACH ROW
+EXECUTE FUNCTION new_player_resource_fn();
+
+CREATE OR REPLACE FUNCTION new_player_accumulator_fn()
+    RETURNS TRIGGER
+    LANGUAGE PLPGSQL
+AS
+$$
+BEGIN
+    INSERT INTO player_accumulator (player_id) VALUES (NEW.id);
+    RETURN NEW;
+END;
+$$;
+
+CREATE TRIGGER new_player_accumulator_trigger
+    AFTER INSERT
+    ON player
+    FOR EACH ROW
+EXECUTE FUNCTION new_player_accumulator_fn();
+
+CREATE OR REPLACE FUNCTION new_player_building_fn()
+    RETURNS TRIGGER
+    LANGUAGE PLPGSQL
+AS
+$$
+BEGIN
+    INSERT INTO player_building (player_id, building_id, level)
+    SELECT NEW.id, id,
+           CASE WHEN max_count = 1 THEN 1 ELSE 0 END -- headquarters start at level 1
+    FROM building
+    WHERE faction = NEW.faction
+      AND starter = TRUE;
+
+    RETURN NEW;
+END;
+$$;
+
+CREATE TRIGGER new_player_building_trigger
+    AFTER INSERT
+    ON player
+    FOR EACH ROW
+EXECUTE FUNCTION new_player_building_fn();
+
+CREATE OR REPLACE FUNCTION change_player_building_fn()
+    RETURNS TRIGGER
+    LANGUAGE PLPGSQL
+AS
+$$
+BEGIN
+    -- whenever a player switches from neutral to another faction,
+    -- insert all pre-built buildings for that faction
+    IF OLD.faction = 'neutral' THEN
+        INSERT INTO player_building (player_id, building_id, level)
+        SELECT NEW.id, id,
+               CASE WHEN max_count = 1 THEN 1 ELSE 0 END -- headquarters start at level 1
+        FROM building
+        WHERE faction = NEW.faction
+          AND starter = TRUE;
+    END IF;
+    RETURN NEW;
+END;
+$$;
+
+CREATE TRIGGER faction_select_player_building_trigger
+    AFTER UPDATE OF faction
+    ON player
+    FOR EACH ROW
+    WHEN (OLD.faction IS DISTINCT FROM NEW.faction)
+EXECUTE FUNCTION change_player_building_fn();
@@ -0,0 +1,16 @@
+-- AIDEV-NOTE: the starter kit of a player, storage, accumulator and starter buildings of
+-- their capital, is provisioned by game::player_operations::provision_player in the
+-- transaction that registers the player or selects their first faction. The triggers that
+-- used to insert these rows behind the application's back are dropped. Rows inserted without
+-- a settlement still go to the capital, see set_capital_settlement_fn.
+DROP TRIGGER new_player_resource_trigger ON player;
+DROP FUNCTION new_player_resource_fn;
+
+DROP TRIGGER new_player_accumulator_trigger ON player;
+DROP FUNCTION new_player_accumulator_fn;
+
+DROP TRIGGER new_player_building_trigger ON player;
+DROP FUNCTION new_player_building_fn;
+
+DROP TRIGGER faction_select_player_building_trigger ON player;
+DROP FUNCTION change_player_building_fn;
@@ -0,0 +1,2 @@
+ALTER TABLE player
+    DROP COLUMN password_changed_at;
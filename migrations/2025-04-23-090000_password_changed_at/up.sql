@@ -0,0 +1,5 @@
+-- AIDEV-NOTE: JWTs issued before the last password change are rejected by the auth
+-- middleware, so changing the password revokes stolen tokens along with the sessions.
+ALTER TABLE player
+    -- When the password was last changed, NULL if it never was
+    ADD COLUMN password_changed_at TIMESTAMPTZ NULL;
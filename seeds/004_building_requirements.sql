@@ -659,8 +659,10 @@ WHERE bl.building_id = 85
   AND bl.level > 0
 ON CONFLICT (building_level_id, required_building_id, required_building_level) DO NOTHING;
 
--- ===== NEUTRAL FACTION (building_id 86-88) =====
+-- ===== NEUTRAL FACTION (building_id 86-90) =====
 -- Neutral buildings have no requirements as they are special cross-faction buildings
 -- Guild Hall (building_id 86) - no requirements
 -- Market (building_id 87) - no requirements
 -- Embassy (building_id 88) - no requirements
+-- Watchtower (building_id 89) - no requirements
+-- Blacksmith (building_id 90) - no requirements, unit upgrades check the Academy instead
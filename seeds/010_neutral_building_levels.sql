@@ -1,5 +1,6 @@
 -- Neutral Building Levels Seed Data
--- Neutral buildings: Guild Hall (86), Market (87), Embassy (88)
+-- Neutral buildings: Guild Hall (86), Market (87), Embassy (88), Watchtower (89),
+-- Blacksmith (90)
 -- These buildings are shared across all factions
 -- Cost pattern: balanced, gold-focused (commerce and diplomacy)
 
@@ -42,5 +43,31 @@ VALUES
 (88, 7,  780,  1400, 2800, 2800, 5600), -- Embassy 7
 (88, 8,  1020, 1600, 3200, 3200, 6400), -- Embassy 8
 (88, 9,  1320, 1800, 3600, 3600, 7200), -- Embassy 9
-(88, 10, 1800, 2000, 4000, 4000, 8000)  -- Embassy 10
+(88, 10, 1800, 2000, 4000, 4000, 8000), -- Embassy 10
+
+-- Watchtower (building_id 89) - Counter-espionage, spots scouts
+(89, 0,  0,    0,    0,    0,    0   ), -- Watchtower 0
+(89, 1,  60,   0,    300,  200,  100 ), -- Watchtower 1
+(89, 2,  120,  0,    600,  400,  200 ), -- Watchtower 2
+(89, 3,  180,  0,    900,  600,  300 ), -- Watchtower 3
+(89, 4,  240,  0,    1200, 800,  400 ), -- Watchtower 4
+(89, 5,  300,  0,    1500, 1000, 500 ), -- Watchtower 5
+(89, 6,  360,  0,    1800, 1200, 600 ), -- Watchtower 6
+(89, 7,  420,  0,    2100, 1400, 700 ), -- Watchtower 7
+(89, 8,  480,  0,    2400, 1600, 800 ), -- Watchtower 8
+(89, 9,  540,  0,    2700, 1800, 900 ), -- Watchtower 9
+(89, 10, 600,  0,    3000, 2000, 1000), -- Watchtower 10
+
+-- Blacksmith (building_id 90) - Upgrades units to higher tiers
+(90, 0,  0,    0,    0,    0,    0   ), -- Blacksmith 0
+(90, 1,  90,   200,  400,  400,  200 ), -- Blacksmith 1
+(90, 2,  180,  400,  800,  800,  400 ), -- Blacksmith 2
+(90, 3,  270,  600,  1200, 1200, 600 ), -- Blacksmith 3
+(90, 4,  360,  800,  1600, 1600, 800 ), -- Blacksmith 4
+(90, 5,  450,  1000, 2000, 2000, 1000), -- Blacksmith 5
+(90, 6,  600,  1200, 2400, 2400, 1200), -- Blacksmith 6
+(90, 7,  780,  1400, 2800, 2800, 1400), -- Blacksmith 7
+(90, 8,  1020, 1600, 3200, 3200, 1600), -- Blacksmith 8
+(90, 9,  1320, 1800, 3600, 3600, 1800), -- Blacksmith 9
+(90, 10, 1800, 2000, 4000, 4000, 2000)  -- Blacksmith 10
 ON CONFLICT (building_id, level) DO NOTHING;
@@ -1,7 +1,8 @@
 -- Neutral Building Resources Seed Data
 -- Neutral buildings don't produce resources or add caps
 -- These entries prevent NULL constraint violations in the update_player_resource_caps_trigger
--- Building IDs: Guild Hall (86), Market (87), Embassy (88)
+-- Building IDs: Guild Hall (86), Market (87), Embassy (88), Watchtower (89),
+-- Blacksmith (90)
 
 INSERT INTO building_resource (building_id, building_level, food_cap, wood_cap, stone_cap, gold_cap)
 VALUES
@@ -42,5 +43,31 @@ VALUES
     (88, 7,  0, 0, 0, 0),
     (88, 8,  0, 0, 0, 0),
     (88, 9,  0, 0, 0, 0),
-    (88, 10, 0, 0, 0, 0)
+    (88, 10, 0, 0, 0, 0),
+
+    -- Watchtower (building_id 89)
+    (89, 0,  0, 0, 0, 0),
+    (89, 1,  0, 0, 0, 0),
+    (89, 2,  0, 0, 0, 0),
+    (89, 3,  0, 0, 0, 0),
+    (89, 4,  0, 0, 0, 0),
+    (89, 5,  0, 0, 0, 0),
+    (89, 6,  0, 0, 0, 0),
+    (89, 7,  0, 0, 0, 0),
+    (89, 8,  0, 0, 0, 0),
+    (89, 9,  0, 0, 0, 0),
+    (89, 10, 0, 0, 0, 0),
+
+    -- Blacksmith (building_id 90)
+    (90, 0,  0, 0, 0, 0),
+    (90, 1,  0, 0, 0, 0),
+    (90, 2,  0, 0, 0, 0),
+    (90, 3,  0, 0, 0, 0),
+    (90, 4,  0, 0, 0, 0),
+    (90, 5,  0, 0, 0, 0),
+    (90, 6,  0, 0, 0, 0),
+    (90, 7,  0, 0, 0, 0),
+    (90, 8,  0, 0, 0, 0),
+    (90, 9,  0, 0, 0, 0),
+    (90, 10, 0, 0, 0, 0)
 ON CONFLICT (building_id, building_level) DO NOTHING;
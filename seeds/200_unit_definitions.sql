@@ -10,6 +10,12 @@
 --   - Cavalry: Mounted, high mobility, moderate ATK/DEF
 --   - Artillery: Siege, very high ATK, very low DEF
 --
+-- The Scout is a light rider trained in the Stables like the Cavalry. It does not fight,
+-- it is sent on scout missions to gather intel on other players.
+--
+-- Veterans are tier 2 units. They are not trained, units are upgraded to them at the
+-- Blacksmith (see 203_unit_upgrades.sql), so they have no costs of their own.
+--
 -- AIDEV-NOTE: These are baseline stats - faction bonuses are applied at runtime via modifiers
 -- AIDEV-NOTE: Magical unit type is deferred to v0.2.0+
 
@@ -19,7 +25,18 @@ INSERT INTO unit (name, unit_type, base_atk, base_def, base_training_seconds, de
 VALUES ('Infantry',  'infantry',  10, 15, 60,  'Frontline fighters armed with sword and shield. Balanced offense and strong defense.'    ),
        ('Ranged',    'ranged',    15, 5,  90,  'Archers and crossbowmen dealing damage from afar. High attack but fragile.'              ),
        ('Cavalry',   'cavalry',   12, 10, 120, 'Mounted warriors with superior mobility. Fast flankers that excel against siege weapons.'),
-       ('Artillery', 'artillery', 20, 3,  180, 'Siege engines and war machines. Devastating firepower but extremely vulnerable.'         )
+       ('Artillery', 'artillery', 20, 3,  180, 'Siege engines and war machines. Devastating firepower but extremely vulnerable.'         ),
+       ('Scout',     'cavalry',   0,  1,  45,  'Light riders who spy on other cities. They report resources and an estimate of the army.' )
+ON CONFLICT (name) DO NOTHING;
+
+-- ===== VETERAN UNITS =====
+-- Same base stats as the units they come from, their tier adds to attack and defense
+
+INSERT INTO unit (name, unit_type, base_atk, base_def, base_training_seconds, tier, description)
+VALUES ('Veteran Infantry',  'infantry',  10, 15, 60,  2, 'Battle-hardened foot soldiers in forged plate. Hold the line longer than any recruit.'),
+       ('Veteran Ranged',    'ranged',    15, 5,  90,  2, 'Seasoned marksmen with reinforced bows. Strike harder and more often.'                ),
+       ('Veteran Cavalry',   'cavalry',   12, 10, 120, 2, 'Armoured riders on barded horses. Charge through lines that would stop others.'      ),
+       ('Veteran Artillery', 'artillery', 20, 3,  180, 2, 'Engines rebuilt with forged fittings. Hit harder and break down less.'               )
 ON CONFLICT (name) DO NOTHING;
 
 -- ===== UNIT COSTS =====
@@ -29,6 +46,7 @@ ON CONFLICT (name) DO NOTHING;
 --   Ranged:    Food 15, Wood 20          (Total: 35)
 --   Cavalry:   Food 30, Gold 15          (Total: 45)
 --   Artillery: Food 25, Wood 15, Stone 20 (Total: 60)
+--   Scout:     Food 10, Gold 10          (Total: 20)
 
 INSERT INTO unit_cost (unit_id, resource, amount)
 SELECT u.id, r.resource::resource_type, r.amount
@@ -41,6 +59,31 @@ FROM unit u
                             ('Cavalry', 'gold', 15),
                             ('Artillery', 'food', 25),
                             ('Artillery', 'wood', 15),
-                            ('Artillery', 'stone', 20)) AS r(unit_name, resource, amount)
+                            ('Artillery', 'stone', 20),
+                            ('Scout', 'food', 10),
+                            ('Scout', 'gold', 10)) AS r(unit_name, resource, amount)
 WHERE u.name = r.unit_name
 ON CONFLICT (unit_id, resource) DO NOTHING;
+
+-- ===== UNIT UPKEEP =====
+-- Food eaten per hour and population housed by a single unit
+--   Infantry:  Food 1/h, Population 1
+--   Ranged:    Food 1/h, Population 1
+--   Cavalry:   Food 3/h, Population 2
+--   Artillery: Food 2/h, Population 3
+--   Scout:     Food 1/h, Population 1
+--   Veterans eat and house like the units they come from
+
+UPDATE unit u
+SET food_upkeep = r.food_upkeep,
+    population  = r.population
+FROM (VALUES ('Infantry', 1, 1),
+             ('Ranged', 1, 1),
+             ('Cavalry', 3, 2),
+             ('Artillery', 2, 3),
+             ('Scout', 1, 1),
+             ('Veteran Infantry', 1, 1),
+             ('Veteran Ranged', 1, 1),
+             ('Veteran Cavalry', 3, 2),
+             ('Veteran Artillery', 2, 3)) AS r(unit_name, food_upkeep, population)
+WHERE u.name = r.unit_name;
@@ -0,0 +1,26 @@
+-- =========================================
+-- Unit Upgrades Seed
+-- =========================================
+-- Maps every trained unit to the veteran it is upgraded to at the Blacksmith.
+--
+-- Upgrades need the Blacksmith and the military research building of the capital (Academy,
+-- Cadet School for goblins) at a level, and cost resources per unit upgraded:
+--   Infantry  -> Veteran Infantry:  Blacksmith 1, Academy 1, Food 20, Wood 10, Gold 10
+--   Ranged    -> Veteran Ranged:    Blacksmith 2, Academy 2, Food 15, Wood 20, Gold 10
+--   Cavalry   -> Veteran Cavalry:   Blacksmith 3, Academy 3, Food 30, Gold 25
+--   Artillery -> Veteran Artillery: Blacksmith 4, Academy 4, Food 25, Wood 15, Stone 20, Gold 10
+--
+-- AIDEV-NOTE: Veterans keep the base stats of the unit they came from, the tier bonus is
+-- added in combat math. Scouts do not fight and have no upgrade.
+
+INSERT INTO unit_upgrade (unit_id, upgraded_unit_id, blacksmith_level, research_level,
+                          req_food, req_wood, req_stone, req_gold)
+SELECT u.id, v.id, r.blacksmith_level, r.research_level, r.food, r.wood, r.stone, r.gold
+FROM (VALUES ('Infantry', 'Veteran Infantry', 1, 1, 20, 10, 0, 10),
+             ('Ranged', 'Veteran Ranged', 2, 2, 15, 20, 0, 10),
+             ('Cavalry', 'Veteran Cavalry', 3, 3, 30, 0, 0, 25),
+             ('Artillery', 'Veteran Artillery', 4, 4, 25, 15, 20, 10))
+         AS r(unit_name, upgraded_name, blacksmith_level, research_level, food, wood, stone, gold)
+         JOIN unit u ON u.name = r.unit_name
+         JOIN unit v ON v.name = r.upgraded_name
+ON CONFLICT (unit_id) DO NOTHING;
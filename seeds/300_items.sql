@@ -0,0 +1,36 @@
+-- =========================================
+-- Starter Items Seed
+-- =========================================
+-- Seeds the consumable boosts players can use for a timed production bonus, and the truces
+-- that keep them out of combat for a while.
+--
+-- Every item applies its own modifier while it lasts. The item modifiers stack with faction
+-- bonuses, but boosts of the same resource share a stacking group and only the highest one
+-- counts, so using two wood boosts at once is wasted. Truce modifiers have no magnitude that
+-- matters, combat validation only checks whether one is active.
+
+-- ===== ITEM MODIFIERS =====
+
+INSERT INTO modifiers (name, description, magnitude_kind, magnitude, target_type, target_resource, stacking_behaviour, stacking_group)
+VALUES ('item_food_boost',        'Food production boost from an item',  'percentage', 0.25, 'resource', 'food',  'highest', 'item_food' ),
+       ('item_wood_boost',        'Wood production boost from an item',  'percentage', 0.50, 'resource', 'wood',  'highest', 'item_wood' ),
+       ('item_stone_boost',       'Stone production boost from an item', 'percentage', 0.50, 'resource', 'stone', 'highest', 'item_stone'),
+       ('item_gold_boost',        'Gold production boost from an item',  'percentage', 0.25, 'resource', 'gold',  'highest', 'item_gold' ),
+       ('item_wood_boost_greater', 'Wood production boost from an item', 'percentage', 1.00, 'resource', 'wood',  'highest', 'item_wood' ),
+       ('item_truce',             'Truce from an item, no attacks either way', 'flat', 1, 'truce', NULL, 'highest', 'item_truce')
+ON CONFLICT (name) DO NOTHING;
+
+-- ===== ITEM DEFINITIONS =====
+
+INSERT INTO item (name, description, modifier_id, duration_seconds)
+SELECT item.name, item.description, modifiers.id, item.duration_seconds
+FROM (VALUES ('Harvest Festival',   '+25% food production for 2 hours',   'item_food_boost',         7200 ),
+             ('Lumberjack''s Axe',  '+50% wood production for 2 hours',   'item_wood_boost',         7200 ),
+             ('Quarry Charter',     '+50% stone production for 2 hours',  'item_stone_boost',        7200 ),
+             ('Merchant''s Ledger', '+25% gold production for 2 hours',   'item_gold_boost',         7200 ),
+             ('Royal Timber Grant', '+100% wood production for 8 hours',  'item_wood_boost_greater', 28800),
+             ('Flag of Truce',      'No attacks to or from you for 8 hours',  'item_truce',       28800),
+             ('Royal Peace Treaty', 'No attacks to or from you for 24 hours', 'item_truce',       86400)
+     ) AS item (name, description, modifier_name, duration_seconds)
+JOIN modifiers ON modifiers.name = item.modifier_name
+ON CONFLICT (name) DO NOTHING;
@@ -0,0 +1,28 @@
+-- =========================================
+-- Tutorial Quests Seed
+-- =========================================
+-- Seeds the quest chain guiding new players through their first buildings, their first army
+-- and their first harvest.
+--
+-- A quest unlocks once every quest it requires is claimed, so the chain starts with the Farm
+-- and then splits into training and collecting, which both lead to the Lumberyard. Every
+-- faction builds a Farm and a Lumberyard, so the chain works for all of them.
+
+INSERT INTO quest (id, name, description, position, requires, objective, target, amount,
+                   reward_food, reward_wood, reward_stone, reward_gold, reward_item_id, reward_item_quantity)
+SELECT quest.id, quest.name, quest.description, quest.position, quest.requires::TEXT[],
+       quest.objective::quest_objective, quest.target, quest.amount,
+       quest.reward_food, quest.reward_wood, quest.reward_stone, quest.reward_gold,
+       item.id, CASE WHEN item.id IS NULL THEN 0 ELSE quest.reward_item_quantity END
+FROM (VALUES ('build_farm',       'Build a Farm',         'Build a Farm to feed your people.',
+              1, '{}',                             'build',   'Farm',       1,   0,   200, 100, 0,   NULL,                 0),
+             ('train_infantry',   'Train 5 Infantry',     'Train 5 Infantry to defend your lands.',
+              2, '{build_farm}',                   'train',   'Infantry',   5,   300, 0,   0,   50,  NULL,                 0),
+             ('collect_wood',     'Collect 500 wood',     'Stock 500 wood in the storage of your capital.',
+              3, '{build_farm}',                   'collect', 'wood',       500, 0,   0,   0,   100, 'Lumberjack''s Axe', 1),
+             ('upgrade_lumberyard', 'Grow the Lumberyard', 'Upgrade a Lumberyard to level 2.',
+              4, '{train_infantry,collect_wood}',  'build',   'Lumberyard', 2,   200, 200, 200, 100, NULL,                 0)
+     ) AS quest (id, name, description, position, requires, objective, target, amount,
+                 reward_food, reward_wood, reward_stone, reward_gold, reward_item_name, reward_item_quantity)
+LEFT JOIN item ON item.name = quest.reward_item_name
+ON CONFLICT (id) DO NOTHING;
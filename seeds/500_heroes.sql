@@ -0,0 +1,50 @@
+-- =========================================
+-- Heroes Seed
+-- =========================================
+-- Seeds the heroes players recruit, and the modifier each of their levels applies while the
+-- hero is assigned.
+--
+-- Army heroes lead the army for a combat bonus, production heroes run a building for a bonus
+-- to one resource. Hero modifiers are additive, so they add up with the faction bonuses. Every
+-- level needs 100 more experience than the last: 0, 100, 300, 600 and 1000 experience for
+-- levels 1 to 5.
+
+-- ===== HERO MODIFIERS =====
+
+INSERT INTO modifiers (name, description, magnitude_kind, magnitude, target_type, target_resource, stacking_behaviour, stacking_group)
+SELECT hero.modifier_prefix || '_' || level,
+       hero.description || ', level ' || level,
+       'percentage'::magnitude_kind,
+       hero.bonus_per_level * level,
+       hero.target_type::modifier_target,
+       hero.target_resource::resource_type,
+       'additive'::stacking_behaviour,
+       NULL
+FROM (VALUES ('hero_marshal',      'Combat bonus of an army hero',            0.05, 'combat',   NULL   ),
+             ('hero_forester',     'Wood production bonus of a hero',         0.10, 'resource', 'wood' ),
+             ('hero_quarrymaster', 'Stone production bonus of a hero',        0.10, 'resource', 'stone'),
+             ('hero_harvester',    'Food production bonus of a hero',         0.10, 'resource', 'food' )
+     ) AS hero (modifier_prefix, description, bonus_per_level, target_type, target_resource)
+CROSS JOIN generate_series(1, 5) AS level
+ON CONFLICT (name) DO NOTHING;
+
+-- ===== HERO DEFINITIONS =====
+
+INSERT INTO hero (name, description, specialty, recruit_gold)
+VALUES ('Aldric the Marshal',     'A veteran commander, +5% combat strength per level when leading the army', 'army',       1000),
+       ('Elin the Forester',      '+10% wood production per level in the building she runs',                  'production', 500 ),
+       ('Brann the Quarrymaster', '+10% stone production per level in the building he runs',                  'production', 500 ),
+       ('Isa the Harvester',      '+10% food production per level in the building she runs',                  'production', 500 )
+ON CONFLICT (name) DO NOTHING;
+
+INSERT INTO hero_level (hero_id, level, xp_required, modifier_id)
+SELECT hero.id, level, 50 * (level - 1) * level, modifiers.id
+FROM (VALUES ('Aldric the Marshal',     'hero_marshal'     ),
+             ('Elin the Forester',      'hero_forester'    ),
+             ('Brann the Quarrymaster', 'hero_quarrymaster'),
+             ('Isa the Harvester',      'hero_harvester'   )
+     ) AS mapping (hero_name, modifier_prefix)
+JOIN hero ON hero.name = mapping.hero_name
+CROSS JOIN generate_series(1, 5) AS level
+JOIN modifiers ON modifiers.name = mapping.modifier_prefix || '_' || level
+ON CONFLICT (hero_id, level) DO NOTHING;
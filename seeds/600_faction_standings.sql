@@ -0,0 +1,11 @@
+-- =========================================
+-- Faction Standings Seed
+-- =========================================
+-- Seeds the war victory modifiers. Every faction aggregation grants them to the members of
+-- the faction leading the standings, until the next aggregation. They are small and additive,
+-- so they add up with the faction bonuses without overshadowing them.
+
+INSERT INTO modifiers (name, description, magnitude_kind, magnitude, target_type, target_resource, stacking_behaviour, stacking_group)
+VALUES ('war_victory_combat',   'Combat bonus of the faction leading the war',         'percentage', 0.05, 'combat',   NULL, 'additive', NULL),
+       ('war_victory_training', 'Training speed bonus of the faction leading the war', 'percentage', 0.05, 'training', NULL, 'additive', NULL)
+ON CONFLICT (name) DO NOTHING;
@@ -4,7 +4,6 @@
 -- Password for all accounts: "password" (argon2id hash)
 
 -- ===== CREATE TEST PLAYERS =====
--- Note: Triggers automatically create player_resource and starter buildings
 
 INSERT INTO player (name, pwd_hash, email, faction)
 VALUES ('rookie',  '$argon2id$v=19$m=19456,t=2,p=1$GZlVQdTfzUOQraKTOJipGg$Ivmq9wyal+q849dYcD3X6aTLCjA/g8zZMroTUCVnWzM', 'rookie@neonrook.com',  'human'),
@@ -13,6 +12,30 @@ VALUES ('rookie',  '$argon2id$v=19$m=19456,t=2,p=1$GZlVQdTfzUOQraKTOJipGg$Ivmq9w
        ('emperor', '$argon2id$v=19$m=19456,t=2,p=1$GZlVQdTfzUOQraKTOJipGg$Ivmq9wyal+q849dYcD3X6aTLCjA/g8zZMroTUCVnWzM', 'emperor@neonrook.com', 'dwarf')
 ON CONFLICT (name) DO NOTHING;
 
+-- Provision the starter kit like game::player_operations::provision_player: storage and
+-- accumulator of the capital, founded by the settlement triggers, and the starter buildings
+INSERT INTO player_resource (player_id)
+SELECT id
+FROM player
+WHERE name IN ('rookie', 'prince', 'king', 'emperor')
+ON CONFLICT DO NOTHING;
+
+INSERT INTO player_accumulator (player_id)
+SELECT id
+FROM player
+WHERE name IN ('rookie', 'prince', 'king', 'emperor')
+ON CONFLICT DO NOTHING;
+
+INSERT INTO player_building (player_id, building_id, level)
+SELECT p.id, b.id, CASE WHEN b.max_count = 1 THEN 1 ELSE 0 END
+FROM player p
+         JOIN building b ON b.faction = p.faction AND b.starter = TRUE
+WHERE p.name IN ('rookie', 'prince', 'king', 'emperor')
+  AND NOT EXISTS (SELECT 1
+                  FROM player_building pb
+                  WHERE pb.player_id = p.id
+                    AND pb.building_id = b.id);
+
 
 -- ===== ROOKIE (Human, Level 1) - True Beginner =====
 -- Starter buildings only, 1 of each resource producer
@@ -0,0 +1,179 @@
+//! API keys for server-to-server integrations.
+//!
+//! Players create keys for their bots, companion apps and tooling, and receive each key once.
+//! Only the hash of a key is stored, with its first characters so players can tell their keys
+//! apart. Requests send the key in the [`API_KEY_HEADER`] header and are authenticated as the
+//! key's player, limited to what the key's [`ApiKeyScope`] allows.
+//!
+//! Keys cannot manage keys: creating, listing and revoking them needs a session or a JWT, so
+//! a leaked key cannot be used to mint more keys or lock its player out of theirs.
+
+use axum::http::Method;
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::Connection;
+use tracing::{debug, info, instrument};
+
+use crate::auth::session_operations::{encode_token, gen_token};
+use crate::db::{DbConn, api_keys};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::player::api_key::{ApiKey, ApiKeyKey, ApiKeyScope, NewApiKey};
+use crate::domain::player::{Player, PlayerKey};
+
+/// Header carrying the API key of a request.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Start of every API key, so leaked keys are easy to recognise.
+pub const API_KEY_PREFIX: &str = "emp_";
+
+/// Keys a player can have that are neither revoked nor expired.
+pub const MAX_ACTIVE_API_KEYS: i64 = 10;
+
+/// Longest name of a key.
+pub const MAX_API_KEY_NAME_LENGTH: usize = 64;
+
+/// Characters of a key stored to tell it apart, the prefix included.
+const KEY_PREFIX_LENGTH: usize = 12;
+
+/// How stale the last use of a key may get before it is recorded again.
+const LAST_USED_RESOLUTION: TimeDelta = TimeDelta::minutes(1);
+
+/// A key a player asked to create.
+#[derive(Debug, Clone)]
+pub struct ApiKeyRequest {
+	pub name: String,
+	pub scope: ApiKeyScope,
+	/// End of the key's validity, `None` to keep it until revoked
+	pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// A created key and its secret.
+#[derive(Debug, Clone)]
+pub struct CreatedApiKey {
+	pub api_key: ApiKey,
+	/// The key itself, only handed out once
+	pub key: String,
+}
+
+/// Creates an API key for a player.
+///
+/// Fails if the name is empty or too long, the key would already be expired, or the player
+/// has [`MAX_ACTIVE_API_KEYS`] active keys.
+#[instrument(skip(conn))]
+pub fn create_key(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	request: ApiKeyRequest,
+) -> Result<CreatedApiKey> {
+	let name = request.name.trim().to_string();
+	if name.is_empty() || name.chars().count() > MAX_API_KEY_NAME_LENGTH {
+		return Err(Error::from((
+			ErrorKind::InvalidData,
+			"API key name must be between 1 and 64 characters",
+		)));
+	}
+	let now = Utc::now();
+	if request
+		.expires_at
+		.is_some_and(|expires_at| expires_at <= now)
+	{
+		return Err(Error::from((
+			ErrorKind::InvalidData,
+			"API key would already be expired",
+		)));
+	}
+
+	let key = format!("{API_KEY_PREFIX}{}", gen_token());
+	let api_key = conn.transaction(|conn| {
+		if api_keys::count_active(conn, player_id, now)? >= MAX_ACTIVE_API_KEYS {
+			return Err(Error::from((
+				ErrorKind::ApiKeyLimitReachedError,
+				"Too many active API keys, revoke one first",
+			)));
+		}
+		api_keys::create(
+			conn,
+			NewApiKey {
+				player_id: *player_id,
+				name,
+				key_hash: encode_token(&key),
+				key_prefix: key.chars().take(KEY_PREFIX_LENGTH).collect(),
+				scope: request.scope.as_str().to_string(),
+				expires_at: request.expires_at,
+			},
+		)
+	})?;
+
+	info!(
+		"Created {} API key {} for player {}",
+		request.scope, api_key.id, player_id
+	);
+	Ok(CreatedApiKey { api_key, key })
+}
+
+/// Lists every key of a player, revoked ones included, newest first.
+pub fn list_keys(conn: &mut DbConn, player_id: &PlayerKey) -> Result<Vec<ApiKey>> {
+	api_keys::get_by_player(conn, player_id)
+}
+
+/// Revokes a key of a player, it stops working right away.
+///
+/// Revoking a key twice keeps the time of the first revocation. Keys of other players are
+/// reported as not found.
+#[instrument(skip(conn))]
+pub fn revoke_key(conn: &mut DbConn, player_id: &PlayerKey, key_id: &ApiKeyKey) -> Result<ApiKey> {
+	if let Some(revoked) = api_keys::revoke(conn, player_id, key_id, Utc::now())? {
+		info!("Revoked API key {} of player {}", revoked.id, player_id);
+		return Ok(revoked);
+	}
+	api_keys::find_by_player(conn, player_id, key_id)?
+		.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "API key not found")))
+}
+
+/// Finds the active key `key` and the player it belongs to, recording its use.
+///
+/// Returns `None` for unknown keys and for revoked or expired ones.
+#[instrument(skip_all)]
+pub fn authenticate(conn: &mut DbConn, key: &str) -> Result<Option<(ApiKey, Player)>> {
+	let now = Utc::now();
+	let Some((api_key, player)) = api_keys::find_by_key_hash(conn, &encode_token(key))? else {
+		return Ok(None);
+	};
+	if !api_key.is_active(now) {
+		debug!("API key {} is revoked or expired", api_key.id);
+		return Ok(None);
+	}
+	if api_key
+		.last_used_at
+		.is_none_or(|last_used_at| now - last_used_at >= LAST_USED_RESOLUTION)
+	{
+		api_keys::touch(conn, &api_key.id, now)?;
+	}
+	Ok(Some((api_key, player)))
+}
+
+/// Fails with [`ErrorKind::ApiKeyScopeError`] unless the key's scope allows requests of
+/// `method`. Read-only keys are limited to `GET`, `HEAD` and `OPTIONS`.
+pub fn require_scope(api_key: &ApiKey, method: &Method) -> Result<()> {
+	let reads = [Method::GET, Method::HEAD, Method::OPTIONS].contains(method);
+	if api_key.scope() == ApiKeyScope::ReadOnly && !reads {
+		debug!("Read-only API key {} used for {}", api_key.id, method);
+		return Err(Error::from((
+			ErrorKind::ApiKeyScopeError,
+			"API key is read-only",
+		)));
+	}
+	Ok(())
+}
+
+/// Fails with [`ErrorKind::ApiKeyScopeError`] if the request was authenticated with a key,
+/// for the endpoints that need the player to log in, like managing keys or deleting the
+/// account.
+pub fn require_login(api_key: Option<&ApiKey>) -> Result<()> {
+	if api_key.is_some() {
+		return Err(Error::from((
+			ErrorKind::ApiKeyScopeError,
+			"API keys cannot manage the account, log in instead",
+		)));
+	}
+	Ok(())
+}
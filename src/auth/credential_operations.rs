@@ -0,0 +1,91 @@
+//! Changing the credentials of a player.
+//!
+//! Both the password and the email address can only be changed by giving the current
+//! password again, so a hijacked session or a shared device is not enough to take an
+//! account over. A new password logs the player out of every other session and revokes the
+//! JWTs issued before it, and a new email address has to be verified again.
+
+use chrono::Utc;
+use diesel::Connection;
+use tracing::{info, instrument, warn};
+
+use crate::auth::password::{self, PasswordCheck};
+use crate::db::{DbConn, player_sessions, players};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::player::session::SessionKey;
+use crate::domain::player::{Player, UserEmail};
+
+/// Fails with [`ErrorKind::ReauthenticationError`] unless `current_password` is the password
+/// of the player.
+fn reauthenticate(player: &Player, current_password: &str) -> Result<()> {
+	let check = password::verify_password(current_password, &player.pwd_hash)
+		.map_err(|_| Error::from((ErrorKind::InternalError, "Failed to verify password")))?;
+	match check {
+		PasswordCheck::Valid | PasswordCheck::Outdated => Ok(()),
+		PasswordCheck::Invalid | PasswordCheck::ResetRequired => {
+			warn!(
+				target: "empire::security",
+				player_id = %player.id,
+				"Re-authentication failed"
+			);
+			Err(Error::from((
+				ErrorKind::ReauthenticationError,
+				"Current password is wrong",
+			)))
+		}
+	}
+}
+
+/// Changes the password of a player and deletes every session except `keep`.
+///
+/// JWTs issued before the change are rejected from then on, see
+/// [`Claims::issued_before`](crate::domain::auth::Claims::issued_before).
+///
+/// # Returns
+/// The number of sessions deleted
+#[instrument(skip_all, fields(player_id = %player.id))]
+pub fn change_password(
+	conn: &mut DbConn,
+	player: &Player,
+	current_password: &str,
+	new_password: &str,
+	keep: Option<&SessionKey>,
+) -> Result<usize> {
+	reauthenticate(player, current_password)?;
+	if new_password.is_empty() {
+		return Err(Error::from((
+			ErrorKind::InvalidPassword,
+			"New password must not be empty",
+		)));
+	}
+	let pwd_hash = password::hash_password(new_password)
+		.map_err(|_| Error::from((ErrorKind::InternalError, "Failed to hash password")))?;
+
+	let invalidated = conn.transaction(|conn| {
+		players::set_password(conn, &player.id, &pwd_hash, Utc::now())?;
+		player_sessions::delete_others_by_player(conn, &player.id, keep)
+	})?;
+	info!(
+		"Changed password of player {}, invalidated {} other sessions",
+		player.id, invalidated
+	);
+	Ok(invalidated)
+}
+
+/// Changes the email address of a player, which has to be verified again.
+#[instrument(skip_all, fields(player_id = %player.id))]
+pub fn change_email(
+	conn: &mut DbConn,
+	player: &Player,
+	current_password: &str,
+	new_email: String,
+) -> Result<Player> {
+	reauthenticate(player, current_password)?;
+	let new_email = UserEmail::parse(new_email)?;
+	let updated = players::set_email(conn, &player.id, &new_email)?;
+	info!(
+		"Changed email of player {}, awaiting verification",
+		player.id
+	);
+	Ok(updated)
+}
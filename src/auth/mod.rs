@@ -1,2 +1,6 @@
+pub mod api_key_operations;
+pub mod credential_operations;
+pub mod oauth_operations;
+pub mod password;
 pub mod session_operations;
 pub mod utils;
@@ -0,0 +1,374 @@
+//! Logging in with external identity providers through OAuth 2.0.
+//!
+//! The start route sends the player to the provider with a random state, which is also kept
+//! in the [`OAUTH_STATE_COOKIE_NAME`] cookie. The provider sends the player back to the
+//! callback route with a code, which [`fetch_account`] exchanges for the player's account at
+//! the provider once the state matches the cookie. [`login_or_register`] then finds the
+//! player linked to that account, or creates and onboards a new one, and the callback logs
+//! them in with a regular session.
+//!
+//! AIDEV-NOTE: identities are never linked to existing players by email address, an email a
+//! provider reports does not prove the player owns the account with that address here.
+//! Players registered through a provider have no password, see [`RESET_REQUIRED_HASH`].
+
+use chrono::Utc;
+use cookie::{Cookie, SameSite, time};
+use diesel::Connection;
+use reqwest::Url;
+use secrecy::ExposeSecret;
+use serde::Deserialize;
+use tracing::{debug, info, instrument, warn};
+
+use crate::auth::password::RESET_REQUIRED_HASH;
+use crate::configuration::{OAuthProviderSettings, OAuthSettings, Settings};
+use crate::db::{DbConn, player_identities, players};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::factions::FactionCode;
+use crate::domain::player::identity::{NewPlayerIdentity, OAuthProvider};
+use crate::domain::player::{NewPlayer, Player, UserEmail, UserName};
+use crate::game::account_deletion::deletion_operations;
+use crate::game::onboarding_operations;
+
+/// Cookie keeping the state of a login in progress until the provider sends the player back.
+pub const OAUTH_STATE_COOKIE_NAME: &str = "rsoauth";
+
+/// Minutes a player has to log in at the provider.
+pub const OAUTH_STATE_MAX_AGE_MINUTES: i64 = 10;
+
+/// Path of the OAuth routes, the only ones the state cookie is sent to.
+const OAUTH_PATH: &str = "/auth/oauth";
+
+/// Longest name taken over from a provider, a suffix is added if it is taken.
+const MAX_DERIVED_NAME_LENGTH: usize = 32;
+
+/// Names tried with a random suffix before giving up on the provider's name.
+const NAME_ATTEMPTS: usize = 5;
+
+/// The endpoints and scopes of a provider.
+struct Endpoints {
+	authorize_url: &'static str,
+	token_url: &'static str,
+	userinfo_url: &'static str,
+	scopes: &'static str,
+}
+
+fn endpoints(provider: OAuthProvider) -> Endpoints {
+	match provider {
+		OAuthProvider::Google => Endpoints {
+			authorize_url: "https://accounts.google.com/o/oauth2/v2/auth",
+			token_url: "https://oauth2.googleapis.com/token",
+			userinfo_url: "https://openidconnect.googleapis.com/v1/userinfo",
+			scopes: "openid email profile",
+		},
+		OAuthProvider::Discord => Endpoints {
+			authorize_url: "https://discord.com/oauth2/authorize",
+			token_url: "https://discord.com/api/oauth2/token",
+			userinfo_url: "https://discord.com/api/users/@me",
+			scopes: "identify email",
+		},
+	}
+}
+
+/// The account of a player at a provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalAccount {
+	pub provider: OAuthProvider,
+	/// Identifier of the account at the provider
+	pub subject: String,
+	/// Name the player goes by at the provider
+	pub name: Option<String>,
+	/// Email address of the account, only if the provider verified it
+	pub email: Option<String>,
+}
+
+/// A player logged in through a provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OAuthLogin {
+	pub player: Player,
+	/// Whether the player was registered by this login
+	pub created: bool,
+}
+
+/// The settings of a provider, failing with [`ErrorKind::NotFoundError`] if it is disabled.
+fn provider_settings(
+	settings: &OAuthSettings,
+	provider: OAuthProvider,
+) -> Result<&OAuthProviderSettings> {
+	settings
+		.provider(provider)
+		.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "OAuth provider not enabled")))
+}
+
+/// Address the provider sends the player back to.
+pub fn redirect_uri(settings: &OAuthSettings, provider: OAuthProvider) -> String {
+	format!(
+		"{}/auth/oauth/{}/callback",
+		settings.redirect_base_url.trim_end_matches('/'),
+		provider.as_str()
+	)
+}
+
+/// Address of the provider's login page for a login with the given state.
+pub fn authorize_url(
+	settings: &OAuthSettings,
+	provider: OAuthProvider,
+	state: &str,
+) -> Result<String> {
+	let client = provider_settings(settings, provider)?;
+	let defaults = endpoints(provider);
+	let base = client
+		.authorize_url
+		.as_deref()
+		.unwrap_or(defaults.authorize_url);
+	let url = Url::parse_with_params(
+		base,
+		[
+			("client_id", client.client_id.as_str()),
+			("redirect_uri", redirect_uri(settings, provider).as_str()),
+			("response_type", "code"),
+			("scope", defaults.scopes),
+			("state", state),
+		],
+	)
+	.map_err(|err| {
+		Error::from((
+			ErrorKind::InternalError,
+			"Invalid OAuth authorize URL",
+			err.to_string(),
+		))
+	})?;
+	Ok(url.into())
+}
+
+/// Cookie keeping the state of a login at `provider` until the provider sends the player back.
+///
+/// The cookie is sent along when the provider redirects back, unlike the strict session
+/// cookie, and only to the OAuth routes.
+pub fn state_cookie(provider: OAuthProvider, state: &str) -> Cookie<'static> {
+	Cookie::build((
+		OAUTH_STATE_COOKIE_NAME,
+		format!("{}:{state}", provider.as_str()),
+	))
+	.secure(true)
+	.http_only(true)
+	.same_site(SameSite::Lax)
+	.path(OAUTH_PATH)
+	.max_age(time::Duration::minutes(OAUTH_STATE_MAX_AGE_MINUTES))
+	.build()
+}
+
+/// The state cookie with its path, to remove it once the player is back.
+pub fn expired_state_cookie() -> Cookie<'static> {
+	Cookie::build(OAUTH_STATE_COOKIE_NAME)
+		.path(OAUTH_PATH)
+		.build()
+}
+
+/// Fails with [`ErrorKind::InvalidToken`] unless `state` is the one of the login the player
+/// started at `provider`, kept in the state cookie.
+pub fn verify_state(provider: OAuthProvider, cookie: Option<&str>, state: &str) -> Result<()> {
+	let expected = cookie.and_then(|value| value.split_once(':'));
+	match expected {
+		Some((expected_provider, expected_state))
+			if expected_provider == provider.as_str() && expected_state == state =>
+		{
+			Ok(())
+		}
+		_ => {
+			warn!(target: "empire::security", "OAuth state mismatch for {}", provider);
+			Err(Error::from((
+				ErrorKind::InvalidToken,
+				"Invalid OAuth state",
+			)))
+		}
+	}
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+	access_token: String,
+}
+
+#[derive(Deserialize)]
+struct GoogleUserInfo {
+	sub: String,
+	name: Option<String>,
+	email: Option<String>,
+	#[serde(default)]
+	email_verified: bool,
+}
+
+#[derive(Deserialize)]
+struct DiscordUser {
+	id: String,
+	username: String,
+	global_name: Option<String>,
+	email: Option<String>,
+	#[serde(default)]
+	verified: bool,
+}
+
+fn provider_error(desc: &'static str) -> impl FnOnce(reqwest::Error) -> Error {
+	move |err| {
+		warn!("OAuth provider request failed: {}", err);
+		Error::from((ErrorKind::OAuthProviderError, desc, err.to_string()))
+	}
+}
+
+/// Exchanges the code the provider sent the player back with for the player's account.
+#[instrument(skip(settings, code))]
+pub async fn fetch_account(
+	settings: &OAuthSettings,
+	provider: OAuthProvider,
+	code: &str,
+) -> Result<ExternalAccount> {
+	let client = provider_settings(settings, provider)?;
+	let defaults = endpoints(provider);
+	let http = reqwest::Client::new();
+	let redirect_uri = redirect_uri(settings, provider);
+
+	let token: TokenResponse = http
+		.post(client.token_url.as_deref().unwrap_or(defaults.token_url))
+		.form(&[
+			("grant_type", "authorization_code"),
+			("code", code),
+			("redirect_uri", redirect_uri.as_str()),
+			("client_id", client.client_id.as_str()),
+			("client_secret", client.client_secret.expose_secret()),
+		])
+		.send()
+		.await
+		.and_then(|response| response.error_for_status())
+		.map_err(provider_error("OAuth code exchange failed"))?
+		.json()
+		.await
+		.map_err(provider_error("Invalid OAuth token response"))?;
+
+	let response = http
+		.get(
+			client
+				.userinfo_url
+				.as_deref()
+				.unwrap_or(defaults.userinfo_url),
+		)
+		.bearer_auth(&token.access_token)
+		.send()
+		.await
+		.and_then(|response| response.error_for_status())
+		.map_err(provider_error("OAuth user info request failed"))?;
+	let account = match provider {
+		OAuthProvider::Google => {
+			let info: GoogleUserInfo = response
+				.json()
+				.await
+				.map_err(provider_error("Invalid OAuth user info"))?;
+			ExternalAccount {
+				provider,
+				subject: info.sub,
+				name: info.name,
+				email: info.email.filter(|_| info.email_verified),
+			}
+		}
+		OAuthProvider::Discord => {
+			let user: DiscordUser = response
+				.json()
+				.await
+				.map_err(provider_error("Invalid OAuth user info"))?;
+			ExternalAccount {
+				provider,
+				subject: user.id,
+				name: Some(user.global_name.unwrap_or(user.username)),
+				email: user.email.filter(|_| user.verified),
+			}
+		}
+	};
+	debug!("Fetched {} account {}", provider, account.subject);
+	Ok(account)
+}
+
+/// Finds the player linked to an account at a provider, or registers a new one.
+///
+/// Logging in cancels a scheduled deletion, like a password login. New players are onboarded
+/// like registered ones and named after their name at the provider.
+#[instrument(skip(conn, settings, account), fields(provider = %account.provider))]
+pub fn login_or_register(
+	conn: &mut DbConn,
+	settings: &Settings,
+	account: ExternalAccount,
+) -> Result<OAuthLogin> {
+	if let Some((identity, player)) =
+		player_identities::find_by_subject(conn, account.provider, &account.subject)?
+	{
+		if identity.email != account.email {
+			player_identities::set_email(conn, &identity, account.email.as_deref())?;
+		}
+		deletion_operations::cancel_on_login(conn, &player)?;
+		let player = players::get_by_id(conn, &player.id)?;
+		info!("Player {} logged in with {}", player.id, account.provider);
+		return Ok(OAuthLogin {
+			player,
+			created: false,
+		});
+	}
+
+	let name = available_name(conn, &account)?;
+	let email = account
+		.email
+		.clone()
+		.and_then(|email| UserEmail::parse(email).ok());
+	let player = conn.transaction(|conn| {
+		let onboarded = onboarding_operations::create_player(
+			conn,
+			&settings.protection,
+			&settings.onboarding,
+			NewPlayer {
+				name,
+				pwd_hash: RESET_REQUIRED_HASH.to_string(),
+				email,
+				faction: FactionCode::Neutral,
+			},
+		)?;
+		player_identities::create(
+			conn,
+			NewPlayerIdentity {
+				player_id: onboarded.player.id,
+				provider: account.provider.as_str().to_string(),
+				subject: account.subject.clone(),
+				email: account.email.clone(),
+			},
+		)?;
+		Ok::<_, Error>(onboarded.player)
+	})?;
+	info!("Registered player {} with {}", player.id, account.provider);
+	Ok(OAuthLogin {
+		player,
+		created: true,
+	})
+}
+
+/// A free name for a new player, derived from their name at the provider.
+fn available_name(conn: &mut DbConn, account: &ExternalAccount) -> Result<UserName> {
+	let derived: String = account
+		.name
+		.as_deref()
+		.unwrap_or_default()
+		.chars()
+		.filter(|c| !UserName::FORBIDDEN_CHARACTERS.contains(c))
+		.take(MAX_DERIVED_NAME_LENGTH)
+		.collect();
+	let base = match derived.trim() {
+		"" => account.provider.as_str().to_string(),
+		trimmed => trimmed.to_string(),
+	};
+
+	let candidates = std::iter::once(base.clone())
+		.chain((0..NAME_ATTEMPTS).map(|_| format!("{base}-{:04}", rand::random_range(0..10_000))));
+	for candidate in candidates {
+		if !players::exists_by_name(conn, &candidate)? {
+			return UserName::parse(candidate);
+		}
+	}
+	// Every attempt was taken, fall back to a name nobody else can have
+	let timestamp = Utc::now().timestamp_micros();
+	UserName::parse(format!("{base}-{timestamp}"))
+}
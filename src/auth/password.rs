@@ -0,0 +1,204 @@
+//! Versioned password hashing.
+//!
+//! Every stored hash starts with the version of the [`PasswordHasher`] that produced it,
+//! like `v1$argon2id$v=19$...`. Hashes written before versioning have no prefix and count
+//! as version 0. Changing the KDF or its parameters means registering a new hasher under
+//! the next version and pointing [`CURRENT_HASH_VERSION`] at it:
+//!
+//! - Players logging in with a hash of an older version are rehashed transparently.
+//! - Once an older version is no longer trusted, adding it to [`RETIRED_HASH_VERSIONS`]
+//!   retires it.
+//!   Hashes cannot be recomputed without the password, so the [`RETIRED_HASHES_BACKFILL`]
+//!   backfill erases retired hashes and their players have to reset their password. It
+//!   runs at startup like every backfill, and admins can enqueue it again as a
+//!   `Backfill` job.
+
+use argon2::password_hash::SaltString;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::{Algorithm, Argon2, Params, PasswordHash, PasswordVerifier, Version, password_hash};
+
+/// Version new hashes are written with.
+pub const CURRENT_HASH_VERSION: u32 = 1;
+
+/// Versions no longer accepted at login.
+///
+/// AIDEV-NOTE: retiring a version needs a new [`RETIRED_HASHES_BACKFILL`] name, completed
+/// backfills never run again.
+pub const RETIRED_HASH_VERSIONS: &[u32] = &[];
+
+/// Name of the backfill erasing hashes of the [`RETIRED_HASH_VERSIONS`].
+pub const RETIRED_HASHES_BACKFILL: &str = "player_password_retired_hashes_v0";
+
+/// Stored in place of an erased hash. It is no valid hash, so no password matches it.
+pub const RESET_REQUIRED_HASH: &str = "!reset";
+
+/// A key derivation function producing and checking password hashes.
+pub trait PasswordHasher: Send + Sync {
+	/// Version written in front of the hashes this hasher produces.
+	fn version(&self) -> u32;
+
+	/// Hashes a password with a fresh salt, without the version prefix.
+	fn hash(&self, pwd: &[u8]) -> Result<String, password_hash::Error>;
+
+	/// Checks a password against a hash of this hasher, without the version prefix.
+	fn verify(&self, pwd: &[u8], hash: &str) -> Result<bool, password_hash::Error>;
+}
+
+/// Argon2id in PHC string format with the given cost parameters.
+pub struct Argon2Hasher {
+	pub version: u32,
+	pub m_cost: u32,
+	pub t_cost: u32,
+	pub p_cost: u32,
+}
+
+impl PasswordHasher for Argon2Hasher {
+	fn version(&self) -> u32 {
+		self.version
+	}
+
+	fn hash(&self, pwd: &[u8]) -> Result<String, password_hash::Error> {
+		let params = Params::new(self.m_cost, self.t_cost, self.p_cost, None)?;
+		let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+		let salt = SaltString::generate(&mut OsRng);
+		Ok(argon2::PasswordHasher::hash_password(&argon2, pwd, &salt)?.to_string())
+	}
+
+	fn verify(&self, pwd: &[u8], hash: &str) -> Result<bool, password_hash::Error> {
+		// The hash carries its own parameters, so verification uses those
+		let hash = PasswordHash::new(hash)?;
+		match Argon2::default().verify_password(pwd, &hash) {
+			Ok(()) => Ok(true),
+			Err(password_hash::Error::Password) => Ok(false),
+			Err(err) => Err(err),
+		}
+	}
+}
+
+/// Unversioned hashes, written with the default Argon2id parameters.
+static LEGACY_ARGON2: Argon2Hasher = Argon2Hasher {
+	version: 0,
+	m_cost: Params::DEFAULT_M_COST,
+	t_cost: Params::DEFAULT_T_COST,
+	p_cost: Params::DEFAULT_P_COST,
+};
+
+static ARGON2_V1: Argon2Hasher = Argon2Hasher {
+	version: 1,
+	m_cost: Params::DEFAULT_M_COST,
+	t_cost: Params::DEFAULT_T_COST,
+	p_cost: Params::DEFAULT_P_COST,
+};
+
+/// Every hasher that can check stored hashes, by version.
+static HASHERS: &[&dyn PasswordHasher] = &[&LEGACY_ARGON2, &ARGON2_V1];
+
+/// Outcome of checking a password against a stored hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordCheck {
+	/// The password matches a hash of the current version
+	Valid,
+	/// The password matches, but its hash should be rewritten with the current version
+	Outdated,
+	/// The password does not match
+	Invalid,
+	/// The hash was erased or its version is retired, the password has to be reset
+	ResetRequired,
+}
+
+/// Returns the hasher registered for `version`.
+pub fn hasher(version: u32) -> Option<&'static dyn PasswordHasher> {
+	HASHERS
+		.iter()
+		.copied()
+		.find(|hasher| hasher.version() == version)
+}
+
+/// Hashes a password with the current hasher, prefixed with its version.
+pub fn hash_password(pwd: impl AsRef<[u8]>) -> Result<String, password_hash::Error> {
+	let hasher = hasher(CURRENT_HASH_VERSION).expect("the current hasher is registered");
+	let hash = hasher.hash(pwd.as_ref())?;
+	Ok(format!("v{}{}", hasher.version(), hash))
+}
+
+/// Splits a stored hash into its version and the hash of that version's hasher.
+///
+/// Returns `None` for erased hashes and anything that is not a hash.
+pub fn parse_stored_hash(stored: &str) -> Option<(u32, &str)> {
+	if stored.starts_with('$') {
+		return Some((0, stored));
+	}
+	let rest = stored.strip_prefix('v')?;
+	let split = rest.find('$')?;
+	let version = rest[..split].parse().ok()?;
+	Some((version, &rest[split..]))
+}
+
+/// Whether a stored hash was erased or is of a retired version.
+pub fn is_retired(stored: &str) -> bool {
+	parse_stored_hash(stored).is_none_or(|(version, _)| RETIRED_HASH_VERSIONS.contains(&version))
+}
+
+/// Checks a password against a stored hash of any registered version.
+pub fn verify_password(
+	pwd: impl AsRef<[u8]>,
+	stored: &str,
+) -> Result<PasswordCheck, password_hash::Error> {
+	let accepted =
+		parse_stored_hash(stored).filter(|(version, _)| !RETIRED_HASH_VERSIONS.contains(version));
+	let Some((version, hash)) = accepted else {
+		return Ok(PasswordCheck::ResetRequired);
+	};
+	let Some(hasher) = hasher(version) else {
+		return Ok(PasswordCheck::ResetRequired);
+	};
+
+	Ok(match hasher.verify(pwd.as_ref(), hash)? {
+		false => PasswordCheck::Invalid,
+		true if version == CURRENT_HASH_VERSION => PasswordCheck::Valid,
+		true => PasswordCheck::Outdated,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn hashes_carry_the_current_version() {
+		let stored = hash_password("hunter22").unwrap();
+		assert!(stored.starts_with(&format!("v{CURRENT_HASH_VERSION}$argon2id$")));
+		assert_eq!(
+			verify_password("hunter22", &stored).unwrap(),
+			PasswordCheck::Valid
+		);
+		assert_eq!(
+			verify_password("hunter23", &stored).unwrap(),
+			PasswordCheck::Invalid
+		);
+	}
+
+	#[test]
+	fn unversioned_hashes_are_outdated() {
+		let legacy = LEGACY_ARGON2.hash(b"hunter22").unwrap();
+		assert_eq!(parse_stored_hash(&legacy).map(|(v, _)| v), Some(0));
+		assert_eq!(
+			verify_password("hunter22", &legacy).unwrap(),
+			PasswordCheck::Outdated
+		);
+	}
+
+	#[test]
+	fn erased_and_unknown_hashes_require_a_reset() {
+		assert_eq!(
+			verify_password("hunter22", RESET_REQUIRED_HASH).unwrap(),
+			PasswordCheck::ResetRequired
+		);
+		assert_eq!(
+			verify_password("hunter22", "v99$argon2id$whatever").unwrap(),
+			PasswordCheck::ResetRequired
+		);
+		assert!(is_retired(RESET_REQUIRED_HASH));
+		assert!(!is_retired("$argon2id$v=19$m=19456,t=2,p=1$salt$hash"));
+	}
+}
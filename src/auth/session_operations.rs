@@ -196,7 +196,7 @@ pub fn invalidate_all(conn: &mut DbConn, player_key: &PlayerKey) {
 /// # Returns
 /// A String containing the hexadecimal representation of the hashed token
 #[instrument(skip_all)]
-fn encode_token(token: impl AsRef<[u8]>) -> String {
+pub fn encode_token(token: impl AsRef<[u8]>) -> String {
 	trace!("Encoding session token");
 	let mut hasher = Blake2s256::new();
 	Digest::update(&mut hasher, token);
@@ -1,3 +1,4 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::env;
 use std::net::Ipv4Addr;
 
@@ -7,11 +8,19 @@ use config::{Config, Environment, File};
 use reqwest::StatusCode;
 use secrecy::{ExposeSecret, SecretString};
 use serde::Deserialize;
-use serde_aux::prelude::deserialize_number_from_string;
+use serde_aux::prelude::{deserialize_number_from_string, deserialize_vec_from_string_or_vec};
 use tracing::{debug, instrument, trace};
 
 use crate::Result;
 use crate::domain::app_state::AppState;
+use crate::domain::jobs::JobType;
+use crate::domain::modifier::ModifierTarget;
+use crate::domain::player::identity::OAuthProvider;
+use crate::game::modifiers::modifier_operations::{DEFAULT_MODIFIER_FLOOR, ModifierLimits};
+use crate::game::resources::resource_scheduler::DEFAULT_PRODUCTION_SHARDS;
+use crate::game::resources::{OverflowPolicy, StarvationPolicy};
+use crate::job_queue::DEFAULT_PRIORITY_AGING_PER_MINUTE;
+use crate::job_queue::payload_encryption::PayloadCipher;
 
 #[derive(Deserialize, FromRef, Debug, Clone)]
 pub struct Settings {
@@ -19,6 +28,46 @@ pub struct Settings {
 	pub server: ServerSettings,
 	pub jwt: JwtSettings,
 	pub cache: CacheSettings,
+	#[serde(default)]
+	pub combat: CombatSettings,
+	#[serde(default)]
+	pub admin: AdminSettings,
+	#[serde(default)]
+	pub protection: ProtectionSettings,
+	#[serde(default)]
+	pub onboarding: OnboardingSettings,
+	#[serde(default)]
+	pub account_deletion: AccountDeletionSettings,
+	#[serde(default)]
+	pub alliances: AllianceSettings,
+	#[serde(default)]
+	pub chat: ChatSettings,
+	#[serde(default)]
+	pub settlements: SettlementSettings,
+	#[serde(default)]
+	pub espionage: EspionageSettings,
+	#[serde(default)]
+	pub units: UnitSettings,
+	#[serde(default)]
+	pub factions: FactionSettings,
+	#[serde(default)]
+	pub jobs: JobSettings,
+	#[serde(default)]
+	pub concurrency: ConcurrencySettings,
+	#[serde(default)]
+	pub cors: CorsSettings,
+	#[serde(default)]
+	pub login_limits: LoginLimitSettings,
+	#[serde(default)]
+	pub oauth: OAuthSettings,
+	#[serde(default)]
+	pub rate_limits: RateLimitSettings,
+	#[serde(default)]
+	pub resources: ResourceSettings,
+	#[serde(default)]
+	pub simulation: SimulationSettings,
+	#[serde(default)]
+	pub modifiers: ModifierSettings,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -37,7 +86,33 @@ pub struct ServerSettings {
 	pub axum_host: Ipv4Addr,
 	#[serde(deserialize_with = "deserialize_number_from_string")]
 	pub axum_port: u16,
+	/// Interface the admin API and metrics listen on, kept off the public interface
+	#[serde(default = "default_admin_host")]
+	pub admin_host: Ipv4Addr,
+	#[serde(
+		default = "default_admin_port",
+		deserialize_with = "deserialize_number_from_string"
+	)]
+	pub admin_port: u16,
 	pub workers: Option<usize>,
+	/// Largest request body accepted, in bytes
+	#[serde(
+		default = "default_max_body_bytes",
+		deserialize_with = "deserialize_number_from_string"
+	)]
+	pub max_body_bytes: usize,
+}
+
+fn default_admin_host() -> Ipv4Addr {
+	Ipv4Addr::LOCALHOST
+}
+
+fn default_admin_port() -> u16 {
+	9090
+}
+
+fn default_max_body_bytes() -> usize {
+	1024 * 1024
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -53,6 +128,574 @@ pub struct CacheSettings {
 	pub max_user_entries: usize,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+pub struct CombatSettings {
+	/// How many days battle reports are kept before the cleanup job prunes them
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub report_retention_days: i64,
+}
+
+impl Default for CombatSettings {
+	fn default() -> Self {
+		Self {
+			report_retention_days: 30,
+		}
+	}
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ProtectionSettings {
+	/// How many days new players are shielded from combat, 0 disables the shield
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub beginner_shield_days: i64,
+	/// Points at which the shield ends early, regardless of the days left
+	pub beginner_shield_max_points: Option<i64>,
+	/// Keep level of the capital at which the shield ends early, regardless of the days left
+	pub beginner_shield_max_keep_level: Option<i32>,
+}
+
+impl Default for ProtectionSettings {
+	fn default() -> Self {
+		Self {
+			beginner_shield_days: 3,
+			beginner_shield_max_points: Some(100),
+			beginner_shield_max_keep_level: Some(5),
+		}
+	}
+}
+
+/// What new players get on top of their faction's starter buildings and resources.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct OnboardingSettings {
+	pub welcome_pack: WelcomePack,
+}
+
+/// Resources and items granted once to every new player.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct WelcomePack {
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub food: i64,
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub wood: i64,
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub stone: i64,
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub gold: i64,
+	/// Items by name, items the game does not know are skipped
+	pub items: Vec<WelcomeItem>,
+}
+
+/// An item of the welcome pack.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WelcomeItem {
+	pub name: String,
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub quantity: i64,
+}
+
+/// Deletion of accounts at the request of their player.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct AccountDeletionSettings {
+	/// Days between the request and the anonymization, logging in before then cancels it
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub grace_period_days: i64,
+}
+
+impl Default for AccountDeletionSettings {
+	fn default() -> Self {
+		Self {
+			grace_period_days: 14,
+		}
+	}
+}
+
+/// Alliances of players.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct AllianceSettings {
+	/// Members an alliance can have at most, including its leader
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub max_members: i64,
+}
+
+impl Default for AllianceSettings {
+	fn default() -> Self {
+		Self { max_members: 30 }
+	}
+}
+
+/// Chat channels of players.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct ChatSettings {
+	/// Messages a player can post within the flood window, across all channels
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub flood_max_messages: i64,
+	/// Length of the flood window in seconds
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub flood_window_seconds: i64,
+}
+
+impl Default for ChatSettings {
+	fn default() -> Self {
+		Self {
+			flood_max_messages: 5,
+			flood_window_seconds: 10,
+		}
+	}
+}
+
+/// Settlements players found beyond their capital.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct SettlementSettings {
+	/// Level the Keep of the capital needs before further settlements can be founded
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub found_keep_level: i32,
+	/// Settlements a player can have at most, including their capital
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub max_settlements: i64,
+}
+
+impl Default for SettlementSettings {
+	fn default() -> Self {
+		Self {
+			found_keep_level: 5,
+			max_settlements: 3,
+		}
+	}
+}
+
+/// Scout missions and the Watchtower that counters them.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct EspionageSettings {
+	/// Seconds scouts travel before they report on their target
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub travel_seconds: i64,
+	/// Noise added to army estimates per Watchtower level of the target, in percent
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub noise_percent_per_level: i32,
+	/// Upper bound for the noise of army estimates, in percent
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub max_noise_percent: i32,
+	/// Watchtower level from which the target spots scouts and is told who sent them
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub spot_watchtower_level: i32,
+}
+
+impl Default for EspionageSettings {
+	fn default() -> Self {
+		Self {
+			travel_seconds: 600,
+			noise_percent_per_level: 10,
+			max_noise_percent: 80,
+			spot_watchtower_level: 1,
+		}
+	}
+}
+
+/// Units of players beyond their training.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct UnitSettings {
+	/// Share of their costs disbanded units refund, in percent
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub disband_refund_percent: i32,
+}
+
+impl Default for UnitSettings {
+	fn default() -> Self {
+		Self {
+			disband_refund_percent: 50,
+		}
+	}
+}
+
+/// Changes of players between factions.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct FactionSettings {
+	/// Days a player waits after changing faction before changing it again
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub change_cooldown_days: i64,
+}
+
+impl Default for FactionSettings {
+	fn default() -> Self {
+		Self {
+			change_cooldown_days: 30,
+		}
+	}
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct ResourceSettings {
+	/// What collecting does with accumulated resources that exceed the storage capacity
+	pub overflow: OverflowPolicy,
+	/// Decimal places of fractional production carried over between ticks, 0 drops fractions
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub accrual_precision: u32,
+	/// What happens to players whose units eat more food than they have
+	pub starvation: StarvationPolicy,
+}
+
+impl Default for ResourceSettings {
+	fn default() -> Self {
+		Self {
+			overflow: OverflowPolicy::default(),
+			accrual_precision: 6,
+			starvation: StarvationPolicy::default(),
+		}
+	}
+}
+
+/// Limits of the multipliers the modifier stacking engine calculates.
+///
+/// Checked when the settings load, see [`ModifierLimits`].
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ModifierSettings {
+	/// Lowest multiplier of each target, however many debuffs stack, targets without a floor
+	/// use [`DEFAULT_MODIFIER_FLOOR`]
+	pub floors: BTreeMap<ModifierTarget, f64>,
+	/// Highest multiplier of any target, however many bonuses stack
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub max_multiplier: f64,
+}
+
+impl ModifierSettings {
+	/// The lowest multiplier of a target.
+	pub fn floor(&self, target: ModifierTarget) -> f64 {
+		self.floors
+			.get(&target)
+			.copied()
+			.unwrap_or(DEFAULT_MODIFIER_FLOOR)
+	}
+}
+
+impl Default for ModifierSettings {
+	fn default() -> Self {
+		Self {
+			// Production never drops below 10% of its base
+			floors: BTreeMap::from([(ModifierTarget::Resource, 0.1)]),
+			max_multiplier: 3.0,
+		}
+	}
+}
+
+/// NPC players of development worlds, only used when built with the `simulation` feature.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct SimulationSettings {
+	/// NPC players kept in the world, 0 disables the simulation
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub npcs: u32,
+	/// Prefix of the NPC player names, followed by their number
+	pub name_prefix: String,
+	/// Cron schedule on which every NPC starts a construction or an upgrade
+	pub construct_schedule: String,
+	/// Cron schedule on which every NPC fills the training slots of its buildings
+	pub train_schedule: String,
+	/// Units an NPC trains at most per building and turn
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub train_batch: i64,
+}
+
+impl Default for SimulationSettings {
+	fn default() -> Self {
+		Self {
+			npcs: 0,
+			name_prefix: "npc_".to_string(),
+			construct_schedule: "0 */5 * * * *".to_string(),
+			train_schedule: "30 */5 * * * *".to_string(),
+			train_batch: 10,
+		}
+	}
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct JobSettings {
+	/// Worker counts, concurrency limits and timeouts of individual job types
+	#[serde(default)]
+	pub types: BTreeMap<JobType, JobTypeSettings>,
+	/// Priority points a due job gains per minute it waits, 0 disables aging
+	#[serde(
+		default = "default_priority_aging",
+		deserialize_with = "deserialize_number_from_string"
+	)]
+	pub priority_aging_per_minute: f64,
+	/// Base64-encoded 256-bit AES-GCM key for the payloads of types with `encrypt_payload`
+	pub payload_key: Option<SecretString>,
+	/// Shards players are split into for resource production, one recurring job each
+	#[serde(
+		default = "default_production_shards",
+		deserialize_with = "deserialize_number_from_string"
+	)]
+	pub production_shards: u32,
+}
+
+fn default_priority_aging() -> f64 {
+	DEFAULT_PRIORITY_AGING_PER_MINUTE
+}
+
+fn default_production_shards() -> u32 {
+	DEFAULT_PRODUCTION_SHARDS
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JobTypeSettings {
+	/// Workers started on this server, defaults to the server-wide worker count
+	pub workers: Option<usize>,
+	/// Jobs of this type in progress at once across all servers, unlimited if unset
+	pub max_concurrent: Option<usize>,
+	/// Seconds a job of this type may run before it is failed, defaults to 300
+	pub timeout_seconds: Option<i32>,
+	/// Whether payloads of this type are encrypted at rest, requires `jobs.payload_key`
+	#[serde(default)]
+	pub encrypt_payload: bool,
+}
+
+impl JobSettings {
+	/// Number of workers to start for `job_type`, falling back to `default_workers`.
+	pub fn workers_for(&self, job_type: JobType, default_workers: usize) -> usize {
+		self.types
+			.get(&job_type)
+			.and_then(|type_settings| type_settings.workers)
+			.unwrap_or(default_workers)
+	}
+
+	/// The configured concurrency limit of every job type that has one.
+	pub fn concurrency_limits(&self) -> BTreeMap<JobType, usize> {
+		self.types
+			.iter()
+			.filter_map(|(job_type, type_settings)| {
+				type_settings.max_concurrent.map(|limit| (*job_type, limit))
+			})
+			.collect()
+	}
+
+	/// Job types whose payloads are encrypted at rest.
+	pub fn encrypted_types(&self) -> BTreeSet<JobType> {
+		self.types
+			.iter()
+			.filter(|(_, type_settings)| type_settings.encrypt_payload)
+			.map(|(job_type, _)| *job_type)
+			.collect()
+	}
+
+	/// The cipher for encrypted payloads, if a key is configured.
+	pub fn payload_cipher(&self) -> Result<Option<PayloadCipher>> {
+		self.payload_key
+			.as_ref()
+			.map(PayloadCipher::from_base64_key)
+			.transpose()
+	}
+
+	/// The configured timeout of every job type that has one.
+	pub fn timeouts(&self) -> BTreeMap<JobType, i32> {
+		self.types
+			.iter()
+			.filter_map(|(job_type, type_settings)| {
+				type_settings
+					.timeout_seconds
+					.map(|timeout| (*job_type, timeout))
+			})
+			.collect()
+	}
+}
+
+impl Default for JobSettings {
+	/// Combat jobs only prune battle reports once a day, the chunks of a backfill and the
+	/// steps of a world reset run one after another, limited events only close once, the
+	/// table statistics are captured once an hour, the leaderboards are snapshotted every 15
+	/// minutes, seasons end once, accounts are rarely deleted, and the faction standings are
+	/// aggregated once a day, so a single worker is plenty for each.
+	fn default() -> Self {
+		let single_worker = JobTypeSettings {
+			workers: Some(1),
+			max_concurrent: None,
+			timeout_seconds: None,
+			encrypt_payload: false,
+		};
+		Self {
+			types: BTreeMap::from([
+				(JobType::Combat, single_worker),
+				(JobType::Backfill, single_worker),
+				(JobType::WorldReset, single_worker),
+				(JobType::LimitedEvent, single_worker),
+				(JobType::TableStats, single_worker),
+				(JobType::Leaderboard, single_worker),
+				(JobType::Season, single_worker),
+				(JobType::AccountDeletion, single_worker),
+				(JobType::Faction, single_worker),
+			]),
+			priority_aging_per_minute: DEFAULT_PRIORITY_AGING_PER_MINUTE,
+			payload_key: None,
+			production_shards: DEFAULT_PRODUCTION_SHARDS,
+		}
+	}
+}
+
+/// Limits on expensive routes, so a burst of them cannot exhaust the database pool.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct ConcurrencySettings {
+	/// Admin API requests served at once
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub admin: usize,
+	/// Building catalog requests served at once
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub catalog: usize,
+	/// Milliseconds a request waits for a free slot before it is turned away
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub queue_timeout_ms: u64,
+}
+
+impl Default for ConcurrencySettings {
+	fn default() -> Self {
+		Self {
+			admin: 2,
+			catalog: 8,
+			queue_timeout_ms: 500,
+		}
+	}
+}
+
+/// Origins browsers may call the API from.
+///
+/// Without allowed origins, browsers keep cross-origin calls out. `*` allows any origin.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct CorsSettings {
+	/// Origins allowed to call the API, as a list or a comma separated string
+	#[serde(deserialize_with = "deserialize_vec_from_string_or_vec")]
+	pub allowed_origins: Vec<String>,
+	/// Whether browsers send cookies along with cross-origin calls, never with the `*` origin
+	pub allow_credentials: bool,
+	/// Seconds browsers may cache the answer to a preflight request
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub max_age_seconds: u64,
+}
+
+/// Thresholds of failed logins after which a username or an IP is locked out.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct LoginLimitSettings {
+	/// Failed logins of a single username within the window that lock it
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub max_failures_per_username: u32,
+	/// Failed logins from a single IP within the window that lock it, whatever the username
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub max_failures_per_ip: u32,
+	/// Seconds after the first failure at which the count starts over
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub window_seconds: u64,
+	/// Seconds a locked username or IP is turned away
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub lockout_seconds: u64,
+}
+
+impl Default for LoginLimitSettings {
+	fn default() -> Self {
+		Self {
+			max_failures_per_username: 5,
+			max_failures_per_ip: 20,
+			window_seconds: 900,
+			lockout_seconds: 900,
+		}
+	}
+}
+
+/// Logging in with external identity providers.
+///
+/// A provider without settings is disabled, its routes answer 404.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct OAuthSettings {
+	/// Public address of the server, the providers send players back to its callback routes
+	pub redirect_base_url: String,
+	pub google: Option<OAuthProviderSettings>,
+	pub discord: Option<OAuthProviderSettings>,
+}
+
+impl OAuthSettings {
+	/// The settings of a provider, `None` if it is disabled.
+	pub fn provider(&self, provider: OAuthProvider) -> Option<&OAuthProviderSettings> {
+		match provider {
+			OAuthProvider::Google => self.google.as_ref(),
+			OAuthProvider::Discord => self.discord.as_ref(),
+		}
+	}
+}
+
+impl Default for OAuthSettings {
+	fn default() -> Self {
+		Self {
+			redirect_base_url: "http://localhost:8080".to_string(),
+			google: None,
+			discord: None,
+		}
+	}
+}
+
+/// The client registered at an identity provider.
+#[derive(Deserialize, Debug, Clone)]
+pub struct OAuthProviderSettings {
+	pub client_id: String,
+	pub client_secret: SecretString,
+	/// Overrides of the provider's endpoints, the provider's own ones if not set
+	#[serde(default)]
+	pub authorize_url: Option<String>,
+	#[serde(default)]
+	pub token_url: Option<String>,
+	#[serde(default)]
+	pub userinfo_url: Option<String>,
+}
+
+/// Token buckets limiting the request rate of every client of the public API.
+///
+/// A client can send up to its burst of requests at once, after which its bucket refills by
+/// the given number of requests per second. A burst of 0 disables the limit.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct RateLimitSettings {
+	/// Requests an authenticated player can send at once
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub player_burst: u32,
+	/// Requests per second added back to a player's bucket
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub player_refill_per_second: u32,
+	/// Requests an IP can send at once to the routes without a player
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub ip_burst: u32,
+	/// Requests per second added back to an IP's bucket
+	#[serde(deserialize_with = "deserialize_number_from_string")]
+	pub ip_refill_per_second: u32,
+}
+
+impl Default for RateLimitSettings {
+	fn default() -> Self {
+		Self {
+			player_burst: 60,
+			player_refill_per_second: 10,
+			ip_burst: 30,
+			ip_refill_per_second: 5,
+		}
+	}
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct AdminSettings {
+	/// Key expected in the `x-admin-key` header of admin API requests.
+	/// The admin API is disabled when no key is configured.
+	pub api_key: Option<SecretString>,
+}
+
 /// The possible runtime environment for our application.
 #[derive(Debug)]
 pub enum AppEnvironment {
@@ -127,6 +770,7 @@ impl TryFrom<String> for AppEnvironment {
 /// - The configuration files are missing or cannot be read.
 /// - The YAML content cannot be deserialized into the [`Settings`] struct.
 /// - The `APP_ENVIRONMENT` environment variable is invalid.
+/// - The modifier limits are invalid, see [`ModifierLimits`].
 ///
 /// [`Settings`]: Settings
 /// [`Result`]: Result
@@ -159,6 +803,8 @@ pub fn get_settings() -> Result<Settings> {
 
 	let settings = settings.try_deserialize::<Settings>()?;
 	trace!(?settings);
+	// Invalid limits would fail every multiplier calculation, so they fail the startup instead
+	ModifierLimits::try_from(&settings.modifiers)?;
 
 	Ok(settings)
 }
@@ -189,4 +835,94 @@ mod tests {
 	fn test_get() {
 		get_settings().unwrap();
 	}
+
+	#[test]
+	fn test_job_settings_fall_back_to_defaults() {
+		let settings = JobSettings {
+			types: BTreeMap::from([
+				(
+					JobType::Training,
+					JobTypeSettings {
+						workers: Some(4),
+						max_concurrent: Some(8),
+						timeout_seconds: Some(60),
+						encrypt_payload: true,
+					},
+				),
+				(
+					JobType::Resource,
+					JobTypeSettings {
+						workers: None,
+						max_concurrent: Some(2),
+						timeout_seconds: None,
+						encrypt_payload: false,
+					},
+				),
+			]),
+			priority_aging_per_minute: DEFAULT_PRIORITY_AGING_PER_MINUTE,
+			payload_key: None,
+			production_shards: DEFAULT_PRODUCTION_SHARDS,
+		};
+
+		assert_eq!(settings.workers_for(JobType::Training, 2), 4);
+		assert_eq!(settings.workers_for(JobType::Resource, 2), 2);
+		assert_eq!(settings.workers_for(JobType::Building, 2), 2);
+		assert_eq!(
+			settings.concurrency_limits(),
+			BTreeMap::from([(JobType::Training, 8), (JobType::Resource, 2)])
+		);
+		assert_eq!(
+			settings.timeouts(),
+			BTreeMap::from([(JobType::Training, 60)])
+		);
+		assert_eq!(
+			settings.encrypted_types(),
+			BTreeSet::from([JobType::Training])
+		);
+		assert!(settings.payload_cipher().unwrap().is_none());
+		assert_eq!(JobSettings::default().workers_for(JobType::Combat, 4), 1);
+	}
+
+	#[test]
+	fn test_modifier_limits_must_be_clampable() {
+		let default = ModifierSettings::default();
+		assert_eq!(
+			ModifierLimits::try_from(&default).unwrap(),
+			ModifierLimits::default()
+		);
+
+		let invalid = [
+			ModifierSettings {
+				max_multiplier: f64::INFINITY,
+				..default.clone()
+			},
+			ModifierSettings {
+				max_multiplier: f64::NAN,
+				..default.clone()
+			},
+			// Below the default floor of the targets without one
+			ModifierSettings {
+				max_multiplier: 0.4,
+				..default.clone()
+			},
+			ModifierSettings {
+				floors: BTreeMap::from([(ModifierTarget::Combat, 4.0)]),
+				..default.clone()
+			},
+			ModifierSettings {
+				floors: BTreeMap::from([(ModifierTarget::Resource, 0.0)]),
+				..default.clone()
+			},
+			ModifierSettings {
+				floors: BTreeMap::from([(ModifierTarget::Resource, f64::NAN)]),
+				..default.clone()
+			},
+		];
+		for settings in invalid {
+			assert!(
+				ModifierLimits::try_from(&settings).is_err(),
+				"{settings:?} should be rejected"
+			);
+		}
+	}
 }
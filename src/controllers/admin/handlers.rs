@@ -0,0 +1,483 @@
+//! Request handlers for the admin API endpoints.
+
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::{Json, debug_handler};
+use chrono::Utc;
+use tracing::{debug, instrument};
+
+use crate::controllers::admin::models::*;
+use crate::db::extractor::DatabaseConnection;
+use crate::db::{backfills, observers};
+use crate::domain::app_state::{AppMetrics, AppQueue, AppState};
+use crate::domain::audit::AuditFilter;
+use crate::domain::jobs::JobKey;
+use crate::domain::modifier::ModifierKey;
+use crate::domain::modifier::active_modifier::ModifierSourceType;
+use crate::domain::observer::ObserverKey;
+use crate::domain::player::PlayerKey;
+use crate::domain::world_reset::WorldResetKey;
+use crate::game::admin_operations::{AdminActor, AdminJobRequest, AdminModifierGrant};
+use crate::game::audit_operations::DEFAULT_AUDIT_LIMIT;
+use crate::game::limited_events::event_operations::{
+	self, EventRequest, ObjectiveRequest, OfferRequest,
+};
+use crate::game::modifiers::modifier_service::ModifierService;
+use crate::game::observer_operations::ObserverRequest;
+use crate::game::seasons::season_operations::{self, SeasonRequest};
+use crate::game::table_stats::stats_operations::{self, DEFAULT_GROWTH_WINDOW_DAYS};
+use crate::game::world::reset_operations;
+use crate::game::{
+	admin_operations, audit_operations, consistency_operations, observer_operations,
+};
+use crate::job_queue::JobPriority;
+use crate::net::ADMIN_OPERATOR_HEADER;
+use crate::{Error, ErrorKind, Result};
+
+/// GET /admin/overview
+///
+/// Returns a snapshot of player activity, job queue depth, upcoming completions and the
+/// recent error rate.
+#[instrument(skip_all)]
+#[debug_handler(state = AppState)]
+pub async fn get_overview(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(job_queue): State<AppQueue>,
+	State(metrics): State<AppMetrics>,
+) -> Result<impl IntoResponse> {
+	debug!("Assembling admin overview");
+	let overview = admin_operations::world_overview(&mut conn, &job_queue, &metrics)?;
+	Ok(Json(OverviewResponse::from(overview)))
+}
+
+/// GET /admin/jobs/stats
+///
+/// Returns the queue depth per status, waiting times and recent failure rate of every job
+/// type.
+#[instrument(skip_all)]
+#[debug_handler(state = AppState)]
+pub async fn get_job_stats(State(job_queue): State<AppQueue>) -> Result<impl IntoResponse> {
+	let stats = job_queue.stats()?;
+	Ok(Json(JobQueueStatsResponse::from(stats)))
+}
+
+/// POST /admin/jobs
+///
+/// Enqueues a job of any registered type after validating its payload, and records it in
+/// the audit log together with the operator and request ID headers.
+#[instrument(skip_all, fields(job_type = ?body.job_type))]
+#[debug_handler(state = AppState)]
+pub async fn enqueue_job(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(job_queue): State<AppQueue>,
+	headers: HeaderMap,
+	Json(body): Json<EnqueueJobRequest>,
+) -> Result<impl IntoResponse> {
+	let actor = admin_actor(&headers);
+	let request = AdminJobRequest {
+		job_type: body.job_type,
+		payload: body.payload,
+		priority: body.priority.unwrap_or(JobPriority::Normal),
+		run_at: body.run_at.unwrap_or_else(Utc::now),
+	};
+	let job = admin_operations::enqueue_job(&mut conn, &job_queue, request, actor)?;
+	Ok((StatusCode::ACCEPTED, Json(EnqueuedJobDto::from(job))))
+}
+
+/// GET /admin/backfills
+///
+/// Lists every backfill that was scheduled with its cursor and the rows processed so far.
+#[instrument(skip_all)]
+#[debug_handler(state = AppState)]
+pub async fn get_backfills(
+	DatabaseConnection(mut conn): DatabaseConnection,
+) -> Result<impl IntoResponse> {
+	let progress = backfills::list_progress(&mut conn)?;
+	Ok(Json(
+		progress
+			.into_iter()
+			.map(BackfillDto::from)
+			.collect::<Vec<_>>(),
+	))
+}
+
+/// GET /admin/table-stats
+///
+/// Returns the captured row counts and on-disk sizes of the tracked tables over the last
+/// days, with their growth over the window.
+#[instrument(skip(conn))]
+#[debug_handler(state = AppState)]
+pub async fn get_table_stats(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	Query(query): Query<TableStatsQuery>,
+) -> Result<impl IntoResponse> {
+	let days = query.days.unwrap_or(DEFAULT_GROWTH_WINDOW_DAYS);
+	let growth =
+		stats_operations::table_growth(&mut conn, days, query.table.as_deref(), Utc::now())?;
+	Ok(Json(TableStatsResponse {
+		days,
+		tables: growth.into_iter().map(TableGrowthDto::from).collect(),
+	}))
+}
+
+/// GET /admin/audit
+///
+/// Returns the most recent recorded actions, newest first, filtered by the player or
+/// operator who took them, the action and a time range.
+#[instrument(skip(conn))]
+#[debug_handler(state = AppState)]
+pub async fn get_audit_log(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	Query(query): Query<AuditLogQuery>,
+) -> Result<impl IntoResponse> {
+	let limit = query.limit.unwrap_or(DEFAULT_AUDIT_LIMIT);
+	let entries = audit_operations::find(&mut conn, &AuditFilter::from(&query), limit)?;
+	debug!("Listing {} audit entries", entries.len());
+	Ok(Json(
+		entries
+			.into_iter()
+			.map(AuditEntryDto::from)
+			.collect::<Vec<_>>(),
+	))
+}
+
+/// GET /admin/players/{player_id}/consistency
+///
+/// Checks a player's stored state against the game's invariants and lists every violation,
+/// without correcting anything.
+#[instrument(skip(conn))]
+#[debug_handler(state = AppState)]
+pub async fn get_player_consistency(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	Path(player_id): Path<PlayerKey>,
+) -> Result<impl IntoResponse> {
+	let report = consistency_operations::check_player(&mut conn, &player_id)?;
+	Ok(Json(ConsistencyResponse::from(report)))
+}
+
+/// GET /admin/dead-letters
+///
+/// Lists dead-lettered jobs, most recent first, optionally filtered by job type.
+#[instrument(skip_all)]
+#[debug_handler(state = AppState)]
+pub async fn get_dead_letters(
+	State(job_queue): State<AppQueue>,
+	Query(query): Query<DeadLetterListQuery>,
+) -> Result<impl IntoResponse> {
+	let page = job_queue.list_dead_letters(query.job_type, query.page, query.per_page)?;
+	debug!(
+		"Listing {} of {} dead-lettered jobs",
+		page.jobs.len(),
+		page.total
+	);
+	Ok(Json(DeadLetterListResponse::from(page)))
+}
+
+/// GET /admin/dead-letters/{job_id}
+///
+/// Returns a dead-lettered job with its payload and the error of every attempt.
+#[instrument(skip(job_queue))]
+#[debug_handler(state = AppState)]
+pub async fn get_dead_letter(
+	State(job_queue): State<AppQueue>,
+	Path(job_id): Path<JobKey>,
+) -> Result<impl IntoResponse> {
+	let dead = job_queue.get_dead_letter(&job_id)?;
+	Ok(Json(DeadLetterDto::from(dead)))
+}
+
+/// POST /admin/dead-letters/{job_id}/requeue
+///
+/// Moves a dead-lettered job back into the queue to run right away.
+#[instrument(skip(job_queue))]
+#[debug_handler(state = AppState)]
+pub async fn requeue_dead_letter(
+	State(job_queue): State<AppQueue>,
+	Path(job_id): Path<JobKey>,
+) -> Result<impl IntoResponse> {
+	let requeued = job_queue.requeue_dead_letter(&job_id)?;
+	Ok((StatusCode::ACCEPTED, Json(RequeuedJobDto::from(requeued))))
+}
+
+/// DELETE /admin/dead-letters/{job_id}
+///
+/// Discards a dead-lettered job for good.
+#[instrument(skip(job_queue))]
+#[debug_handler(state = AppState)]
+pub async fn discard_dead_letter(
+	State(job_queue): State<AppQueue>,
+	Path(job_id): Path<JobKey>,
+) -> Result<impl IntoResponse> {
+	if !job_queue.discard_dead_letter(&job_id)? {
+		return Err(Error::from((
+			ErrorKind::DeadLetterNotFoundError,
+			"Dead-lettered job not found",
+		)));
+	}
+	Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /admin/players/{player_id}/modifiers
+///
+/// Grants a modifier to a player, like an event buff, and records the operator in the
+/// modifier history and the audit log.
+#[instrument(skip(conn, service, headers, body))]
+#[debug_handler(state = AppState)]
+pub async fn grant_player_modifier(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(mut service): State<ModifierService>,
+	Path(player_id): Path<PlayerKey>,
+	headers: HeaderMap,
+	Json(body): Json<GrantModifierRequest>,
+) -> Result<impl IntoResponse> {
+	let grant = AdminModifierGrant {
+		modifier_id: body.modifier_id,
+		expires_at: body.expires_at,
+		source_type: body.source_type.unwrap_or(ModifierSourceType::Event),
+		player_building_id: body.player_building_id,
+		reason: body.reason,
+	};
+	let active = admin_operations::grant_modifier(
+		&mut conn,
+		&mut service,
+		&player_id,
+		grant,
+		admin_actor(&headers),
+	)
+	.await?;
+	Ok((
+		StatusCode::CREATED,
+		Json(AdminActiveModifierDto::from(active)),
+	))
+}
+
+/// DELETE /admin/players/{player_id}/modifiers/{modifier_id}
+///
+/// Revokes every instance of a modifier the player holds, like an exploited bonus, and
+/// records the operator in the modifier history and the audit log.
+#[instrument(skip(conn, service, headers))]
+#[debug_handler(state = AppState)]
+pub async fn revoke_player_modifier(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(service): State<ModifierService>,
+	Path((player_id, modifier_id)): Path<(PlayerKey, ModifierKey)>,
+	Query(query): Query<RevokeModifierQuery>,
+	headers: HeaderMap,
+) -> Result<impl IntoResponse> {
+	let revoked = admin_operations::revoke_modifier(
+		&mut conn,
+		&service,
+		&player_id,
+		&modifier_id,
+		query.reason,
+		admin_actor(&headers),
+	)
+	.await?;
+	Ok(Json(
+		revoked
+			.into_iter()
+			.map(AdminActiveModifierDto::from)
+			.collect::<Vec<_>>(),
+	))
+}
+
+/// PUT /admin/players/{player_id}/role
+///
+/// Gives a player a role, like making a community manager a moderator, and records the
+/// previous role in the audit log.
+#[instrument(skip(conn, headers))]
+#[debug_handler(state = AppState)]
+pub async fn set_player_role(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	Path(player_id): Path<PlayerKey>,
+	headers: HeaderMap,
+	Json(body): Json<SetPlayerRoleRequest>,
+) -> Result<impl IntoResponse> {
+	let player =
+		admin_operations::set_player_role(&mut conn, &player_id, body.role, admin_actor(&headers))?;
+	Ok(Json(PlayerRoleDto::from(player)))
+}
+
+/// POST /admin/world-resets
+///
+/// Requests a world reset and hands out the one-time token confirming it. Nothing is wiped
+/// until the reset is confirmed.
+#[instrument(skip_all)]
+#[debug_handler(state = AppState)]
+pub async fn request_world_reset(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	headers: HeaderMap,
+) -> Result<impl IntoResponse> {
+	let requested = reset_operations::request_reset(&mut conn, admin_actor(&headers))?;
+	Ok((
+		StatusCode::CREATED,
+		Json(RequestedWorldResetDto::from(requested)),
+	))
+}
+
+/// GET /admin/world-resets/{reset_id}
+///
+/// Returns the status of a world reset and the steps it completed.
+#[instrument(skip(conn))]
+#[debug_handler(state = AppState)]
+pub async fn get_world_reset(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	Path(reset_id): Path<WorldResetKey>,
+) -> Result<impl IntoResponse> {
+	let reset = reset_operations::get_reset(&mut conn, &reset_id)?;
+	Ok(Json(WorldResetDto::from(reset)))
+}
+
+/// POST /admin/world-resets/{reset_id}/confirm
+///
+/// Confirms a requested world reset with its token and starts wiping the world.
+#[instrument(skip(conn, job_queue, headers, body))]
+#[debug_handler(state = AppState)]
+pub async fn confirm_world_reset(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(job_queue): State<AppQueue>,
+	Path(reset_id): Path<WorldResetKey>,
+	headers: HeaderMap,
+	Json(body): Json<ConfirmWorldResetRequest>,
+) -> Result<impl IntoResponse> {
+	let reset = reset_operations::confirm_reset(
+		&mut conn,
+		&job_queue,
+		&reset_id,
+		&body.token,
+		admin_actor(&headers),
+	)?;
+	Ok((StatusCode::ACCEPTED, Json(WorldResetDto::from(reset))))
+}
+
+/// POST /admin/events
+///
+/// Creates a limited event with its objectives and its shop, and schedules its closing.
+#[instrument(skip(conn, job_queue, headers))]
+#[debug_handler(state = AppState)]
+pub async fn create_event(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(job_queue): State<AppQueue>,
+	headers: HeaderMap,
+	Json(body): Json<CreateEventRequest>,
+) -> Result<impl IntoResponse> {
+	let request = EventRequest {
+		name: body.name,
+		description: body.description,
+		currency_name: body.currency_name,
+		starts_at: body.starts_at,
+		ends_at: body.ends_at,
+		leftover_resource: body.leftover_resource,
+		leftover_rate: body.leftover_rate,
+		objectives: body
+			.objectives
+			.into_iter()
+			.map(|objective| ObjectiveRequest {
+				kind: objective.kind,
+				description: objective.description,
+				target: objective.target,
+				reward: objective.reward,
+				max_completions: objective.max_completions,
+			})
+			.collect(),
+		offers: body
+			.offers
+			.into_iter()
+			.map(|offer| OfferRequest {
+				price: offer.price,
+				item_id: offer.item_id,
+				unit_id: offer.unit_id,
+				quantity: offer.quantity,
+			})
+			.collect(),
+	};
+	let created =
+		event_operations::create_event(&mut conn, &job_queue, request, admin_actor(&headers))?;
+	Ok((StatusCode::CREATED, Json(LimitedEventDto::from(created))))
+}
+
+/// POST /admin/seasons
+///
+/// Creates the next season and schedules its end.
+#[instrument(skip(conn, job_queue, headers))]
+#[debug_handler(state = AppState)]
+pub async fn create_season(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(job_queue): State<AppQueue>,
+	headers: HeaderMap,
+	Json(body): Json<CreateSeasonRequest>,
+) -> Result<impl IntoResponse> {
+	let request = SeasonRequest {
+		name: body.name,
+		starts_at: body.starts_at,
+		ends_at: body.ends_at,
+	};
+	let season =
+		season_operations::create_season(&mut conn, &job_queue, request, admin_actor(&headers))?;
+	Ok((StatusCode::CREATED, Json(SeasonDto::from(season))))
+}
+
+/// POST /admin/observers
+///
+/// Creates a read-only observer for casting a tournament and hands out its token once.
+#[instrument(skip(conn, headers))]
+#[debug_handler(state = AppState)]
+pub async fn create_observer(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	headers: HeaderMap,
+	Json(body): Json<CreateObserverRequest>,
+) -> Result<impl IntoResponse> {
+	let request = ObserverRequest {
+		name: body.name,
+		scopes: body.scopes,
+		player_ids: body.player_ids,
+		expires_at: body.expires_at,
+	};
+	let created = observer_operations::create_observer(&mut conn, request, admin_actor(&headers))?;
+	Ok((StatusCode::CREATED, Json(CreatedObserverDto::from(created))))
+}
+
+/// GET /admin/observers
+///
+/// Lists every observer with the players selected for it, newest first.
+#[instrument(skip(conn))]
+#[debug_handler(state = AppState)]
+pub async fn get_observers(
+	DatabaseConnection(mut conn): DatabaseConnection,
+) -> Result<impl IntoResponse> {
+	let observers = observer_operations::list_observers(&mut conn)?;
+	Ok(Json(
+		observers
+			.into_iter()
+			.map(ObserverDto::from)
+			.collect::<Vec<_>>(),
+	))
+}
+
+/// DELETE /admin/observers/{observer_id}
+///
+/// Revokes an observer, its token stops working right away.
+#[instrument(skip(conn, headers))]
+#[debug_handler(state = AppState)]
+pub async fn revoke_observer(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	Path(observer_id): Path<ObserverKey>,
+	headers: HeaderMap,
+) -> Result<impl IntoResponse> {
+	let observer =
+		observer_operations::revoke_observer(&mut conn, &observer_id, admin_actor(&headers))?;
+	let player_ids = observers::get_player_ids(&mut conn, &observer.id)?;
+	Ok(Json(ObserverDto::from((observer, player_ids))))
+}
+
+/// The operator and request ID headers of an admin request.
+fn admin_actor(headers: &HeaderMap) -> AdminActor {
+	AdminActor {
+		operator: headers
+			.get(ADMIN_OPERATOR_HEADER)
+			.and_then(|value| value.to_str().ok())
+			.map(str::to_string),
+		request_id: audit_operations::request_id(headers),
+	}
+}
@@ -0,0 +1,16 @@
+//! Admin controller module for internal operations endpoints.
+//!
+//! Provides REST API endpoints for:
+//! - A world overview of player activity, job queue depth and upcoming completions
+//! - Job queue statistics per job type
+//! - Enqueueing jobs of any registered type, recorded in the audit log
+//! - Listing, inspecting, requeueing and discarding dead-lettered jobs
+//!
+//! All routes are guarded by the admin API key instead of player authentication.
+
+mod handlers;
+mod models;
+mod routes;
+
+pub use models::*;
+pub use routes::*;
@@ -0,0 +1,818 @@
+//! Request and response DTOs for the admin API endpoints.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::domain::audit::{AuditEntry, AuditEntryKey, AuditFilter};
+use crate::domain::backfill::BackfillProgress;
+use crate::domain::item::ItemKey;
+use crate::domain::jobs::{DeadLetterJob, Job, JobError, JobKey, JobStatus, JobType};
+use crate::domain::limited_event::{
+	EventObjectiveKey, EventObjectiveKind, EventOfferKey, LimitedEventKey,
+};
+use crate::domain::modifier::ModifierKey;
+use crate::domain::modifier::active_modifier::{
+	ActiveModifier, ActiveModifierKey, ModifierSourceType,
+};
+use crate::domain::observer::{Observer, ObserverKey, ObserverScope};
+use crate::domain::player::buildings::PlayerBuildingKey;
+use crate::domain::player::resource::ResourceType;
+use crate::domain::player::role::PlayerRole;
+use crate::domain::player::{Player, PlayerKey};
+use crate::domain::season::{Season, SeasonKey};
+use crate::domain::unit::UnitKey;
+use crate::domain::world_reset::{WorldReset, WorldResetKey, WorldResetStatus, WorldResetStep};
+use crate::game::admin_operations::WorldOverview;
+use crate::game::consistency_operations::{ConsistencyCheck, ConsistencyReport, Violation};
+use crate::game::limited_events::event_operations::EventDetails;
+use crate::game::observer_operations::CreatedObserver;
+use crate::game::table_stats::stats_operations::TableGrowth;
+use crate::game::world::reset_operations::RequestedReset;
+use crate::job_queue::JobPriority;
+use crate::job_queue::dead_letter::DeadLetterPage;
+use crate::job_queue::stats::{JobTypeStats, QueueStats};
+
+// === Request DTOs ===
+
+/// Query parameters for listing dead-lettered jobs
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DeadLetterListQuery {
+	/// Only list jobs of this type
+	pub job_type: Option<JobType>,
+	/// 1-based page number, defaults to the first page
+	pub page: Option<i64>,
+	/// Jobs per page, defaults to 20 and is capped at 100
+	pub per_page: Option<i64>,
+}
+
+/// Body of a request to enqueue a job
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EnqueueJobRequest {
+	pub job_type: JobType,
+	/// Payload in the shape the job type's processor expects
+	pub payload: serde_json::Value,
+	/// When the job becomes due, defaults to right away
+	pub run_at: Option<DateTime<Utc>>,
+	/// Defaults to normal priority
+	pub priority: Option<JobPriority>,
+}
+
+/// Body of a request to grant a modifier to a player
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct GrantModifierRequest {
+	pub modifier_id: ModifierKey,
+	/// When the modifier runs out, omit to keep it until it is revoked
+	pub expires_at: Option<DateTime<Utc>>,
+	/// Defaults to an event modifier
+	pub source_type: Option<ModifierSourceType>,
+	/// Scope the modifier to one of the player's buildings
+	pub player_building_id: Option<PlayerBuildingKey>,
+	/// Why the modifier is granted, recorded in the modifier history
+	pub reason: Option<String>,
+}
+
+/// Body of a request to change the role of a player
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SetPlayerRoleRequest {
+	pub role: PlayerRole,
+}
+
+/// Query parameters for revoking a modifier from a player
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RevokeModifierQuery {
+	/// Why the modifier is revoked, recorded in the modifier history
+	pub reason: Option<String>,
+}
+
+/// Body of a request to create a tournament observer
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CreateObserverRequest {
+	pub name: String,
+	/// What the observer can see of the players it watches
+	pub scopes: Vec<ObserverScope>,
+	/// Players the observer watches, as long as they allow observers
+	#[serde(default)]
+	pub player_ids: Vec<PlayerKey>,
+	/// End of the observer's access, omit to keep it until it is revoked
+	pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// An objective of a limited event to create
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CreateEventObjectiveRequest {
+	pub kind: EventObjectiveKind,
+	pub description: String,
+	/// Occurrences of the kind needed to complete the objective once
+	pub target: i64,
+	/// Currency earned for each completion
+	pub reward: i64,
+	/// How often the objective can be completed, omit for no limit
+	pub max_completions: Option<i32>,
+}
+
+/// An offer of a limited event's shop to create, selling either an item or a unit
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CreateEventOfferRequest {
+	pub price: i64,
+	pub item_id: Option<ItemKey>,
+	pub unit_id: Option<UnitKey>,
+	pub quantity: i64,
+}
+
+/// Body of a request to create a limited event
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CreateEventRequest {
+	pub name: String,
+	pub description: String,
+	/// Name of the currency earned during the event
+	pub currency_name: String,
+	pub starts_at: DateTime<Utc>,
+	pub ends_at: DateTime<Utc>,
+	/// Resource the currency left over at the end of the event is converted to
+	pub leftover_resource: ResourceType,
+	/// Amount of the resource granted for each unit of leftover currency
+	pub leftover_rate: i32,
+	pub objectives: Vec<CreateEventObjectiveRequest>,
+	#[serde(default)]
+	pub offers: Vec<CreateEventOfferRequest>,
+}
+
+/// Body of a request to create the next season
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CreateSeasonRequest {
+	pub name: String,
+	/// Start of the season, no earlier than the end of the last one
+	pub starts_at: DateTime<Utc>,
+	pub ends_at: DateTime<Utc>,
+}
+
+/// Query parameters for the audit log, every parameter that is set has to match
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AuditLogQuery {
+	/// Player who took the action
+	pub player_id: Option<PlayerKey>,
+	/// Operator who took the action
+	pub operator: Option<String>,
+	pub action: Option<String>,
+	/// Start of the time range, inclusive
+	pub from: Option<DateTime<Utc>>,
+	/// End of the time range, exclusive
+	pub until: Option<DateTime<Utc>>,
+	/// Entries to return, defaults to 100 and is capped at 500
+	pub limit: Option<i64>,
+}
+
+impl From<&AuditLogQuery> for AuditFilter {
+	fn from(query: &AuditLogQuery) -> Self {
+		Self {
+			player_id: query.player_id,
+			operator: query.operator.clone(),
+			action: query.action.clone(),
+			from: query.from,
+			until: query.until,
+		}
+	}
+}
+
+/// Query parameters for the growth of the tracked tables
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TableStatsQuery {
+	/// Days of samples to return, defaults to 30 and is capped at 365
+	pub days: Option<u32>,
+	/// Only return this table
+	pub table: Option<String>,
+}
+
+/// Body confirming a requested world reset
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ConfirmWorldResetRequest {
+	/// Token handed out when the reset was requested
+	pub token: String,
+}
+
+// === Response DTOs ===
+
+/// Outstanding jobs of a single type
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueDepthDto {
+	pub pending: i64,
+	pub in_progress: i64,
+}
+
+/// Work finishing within the next hour
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UpcomingCompletionsDto {
+	pub upgrades: i64,
+	pub trainings: i64,
+}
+
+/// Request and job failures over the last 15 minutes
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct ErrorRateDto {
+	pub requests: u64,
+	pub server_errors: u64,
+	/// Share of requests answered with a 5xx status, between 0 and 1
+	pub rate: f64,
+	pub failed_jobs: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OverviewResponse {
+	pub generated_at: DateTime<Utc>,
+	/// Players seen within the last 15 minutes
+	pub active_players: usize,
+	/// Outstanding jobs keyed by job type
+	pub queue_depth: BTreeMap<String, QueueDepthDto>,
+	pub completing_next_hour: UpcomingCompletionsDto,
+	pub errors: ErrorRateDto,
+}
+
+impl From<WorldOverview> for OverviewResponse {
+	fn from(overview: WorldOverview) -> Self {
+		Self {
+			generated_at: overview.generated_at,
+			active_players: overview.active_players,
+			queue_depth: overview
+				.queue_depth
+				.into_iter()
+				.map(|depth| {
+					(
+						depth.job_type.as_str().to_owned(),
+						QueueDepthDto {
+							pending: depth.pending,
+							in_progress: depth.in_progress,
+						},
+					)
+				})
+				.collect(),
+			completing_next_hour: UpcomingCompletionsDto {
+				upgrades: overview.upgrades_completing,
+				trainings: overview.trainings_completing,
+			},
+			errors: ErrorRateDto {
+				requests: overview.requests.requests,
+				server_errors: overview.requests.server_errors,
+				rate: overview.requests.error_rate(),
+				failed_jobs: overview.failed_jobs,
+			},
+		}
+	}
+}
+
+/// Statistics of a single job type
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct JobTypeStatsDto {
+	/// Jobs in the queue keyed by status
+	pub depth: BTreeMap<String, i64>,
+	/// Average time due pending jobs have been waiting, null without any
+	pub avg_wait_seconds: Option<f64>,
+	/// Wait of the longest-waiting due pending job, null without any
+	pub oldest_pending_seconds: Option<f64>,
+	pub completed: i64,
+	pub failed: i64,
+	/// Share of finished runs that failed, between 0 and 1
+	pub failure_rate: f64,
+}
+
+impl From<JobTypeStats> for JobTypeStatsDto {
+	fn from(stats: JobTypeStats) -> Self {
+		Self {
+			failure_rate: stats.failure_rate(),
+			depth: stats
+				.depth
+				.iter()
+				.map(|(job_status, count)| (job_status.as_str().to_owned(), *count))
+				.collect(),
+			avg_wait_seconds: stats.avg_wait_seconds,
+			oldest_pending_seconds: stats.oldest_pending_seconds,
+			completed: stats.completed,
+			failed: stats.failed,
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JobQueueStatsResponse {
+	pub generated_at: DateTime<Utc>,
+	/// Window completed and failed runs were counted over
+	pub window_seconds: i64,
+	/// Statistics keyed by job type
+	pub types: BTreeMap<String, JobTypeStatsDto>,
+}
+
+impl From<QueueStats> for JobQueueStatsResponse {
+	fn from(stats: QueueStats) -> Self {
+		Self {
+			generated_at: stats.generated_at,
+			window_seconds: stats.window.num_seconds(),
+			types: stats
+				.types
+				.into_iter()
+				.map(|type_stats| {
+					(
+						type_stats.job_type.as_str().to_owned(),
+						JobTypeStatsDto::from(type_stats),
+					)
+				})
+				.collect(),
+		}
+	}
+}
+
+/// A dead-lettered job without its payload and error history
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeadLetterSummaryDto {
+	pub id: JobKey,
+	pub job_type: JobType,
+	pub retries: i32,
+	pub last_error: Option<String>,
+	pub enqueued_at: DateTime<Utc>,
+	pub dead_lettered_at: DateTime<Utc>,
+}
+
+impl From<DeadLetterJob> for DeadLetterSummaryDto {
+	fn from(job: DeadLetterJob) -> Self {
+		Self {
+			id: job.id,
+			job_type: job.job_type,
+			retries: job.retries,
+			last_error: job.last_error,
+			enqueued_at: job.enqueued_at,
+			dead_lettered_at: job.created_at,
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeadLetterListResponse {
+	pub jobs: Vec<DeadLetterSummaryDto>,
+	pub page: i64,
+	pub per_page: i64,
+	pub total: i64,
+}
+
+impl From<DeadLetterPage> for DeadLetterListResponse {
+	fn from(page: DeadLetterPage) -> Self {
+		Self {
+			jobs: page
+				.jobs
+				.into_iter()
+				.map(DeadLetterSummaryDto::from)
+				.collect(),
+			page: page.page,
+			per_page: page.per_page,
+			total: page.total,
+		}
+	}
+}
+
+/// A dead-lettered job with everything needed to diagnose it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeadLetterDto {
+	pub id: JobKey,
+	pub job_type: JobType,
+	pub payload: serde_json::Value,
+	pub priority: i32,
+	pub retries: i32,
+	pub max_retries: i32,
+	pub last_error: Option<String>,
+	/// Every failed attempt, oldest first
+	pub errors: Vec<JobError>,
+	pub enqueued_at: DateTime<Utc>,
+	pub dead_lettered_at: DateTime<Utc>,
+}
+
+impl From<DeadLetterJob> for DeadLetterDto {
+	fn from(job: DeadLetterJob) -> Self {
+		let errors = serde_json::from_value(job.error_history).unwrap_or_else(|err| {
+			warn!("Unreadable error history on job {}: {}", job.id, err);
+			Vec::new()
+		});
+		Self {
+			id: job.id,
+			job_type: job.job_type,
+			payload: job.payload,
+			priority: job.priority,
+			retries: job.retries,
+			max_retries: job.max_retries,
+			last_error: job.last_error,
+			errors,
+			enqueued_at: job.enqueued_at,
+			dead_lettered_at: job.created_at,
+		}
+	}
+}
+
+/// A job moved back into the queue
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RequeuedJobDto {
+	pub id: JobKey,
+	pub job_type: JobType,
+	pub status: JobStatus,
+	pub run_at: DateTime<Utc>,
+}
+
+impl From<Job> for RequeuedJobDto {
+	fn from(job: Job) -> Self {
+		Self {
+			id: job.id,
+			job_type: job.job_type,
+			status: job.status,
+			run_at: job.run_at,
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BackfillDto {
+	pub name: String,
+	/// Last key a committed chunk processed
+	pub cursor: Option<Uuid>,
+	pub processed: i64,
+	pub completed: bool,
+	/// The job scheduled to run the next chunks
+	pub job_id: Option<JobKey>,
+	pub started_at: Option<DateTime<Utc>>,
+	pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl From<BackfillProgress> for BackfillDto {
+	fn from(progress: BackfillProgress) -> Self {
+		Self {
+			completed: progress.is_completed(),
+			name: progress.name,
+			cursor: progress.cursor,
+			processed: progress.processed,
+			job_id: progress.job_id,
+			started_at: progress.started_at,
+			completed_at: progress.completed_at,
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ViolationDto {
+	pub check: ConsistencyCheck,
+	/// What the violation was found on, for example `wood` or a training entry ID
+	pub subject: String,
+	pub detail: String,
+}
+
+impl From<Violation> for ViolationDto {
+	fn from(violation: Violation) -> Self {
+		Self {
+			check: violation.check,
+			subject: violation.subject,
+			detail: violation.detail,
+		}
+	}
+}
+
+/// Response for GET /admin/players/{player_id}/consistency
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ConsistencyResponse {
+	pub player_id: PlayerKey,
+	pub checked_at: DateTime<Utc>,
+	pub consistent: bool,
+	/// Checks that were run
+	pub checks: Vec<ConsistencyCheck>,
+	pub violations: Vec<ViolationDto>,
+}
+
+impl From<ConsistencyReport> for ConsistencyResponse {
+	fn from(report: ConsistencyReport) -> Self {
+		Self {
+			player_id: report.player_id,
+			checked_at: report.checked_at,
+			consistent: report.violations.is_empty(),
+			checks: report.checks,
+			violations: report
+				.violations
+				.into_iter()
+				.map(ViolationDto::from)
+				.collect(),
+		}
+	}
+}
+
+/// A job enqueued by an operator
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EnqueuedJobDto {
+	pub id: JobKey,
+	pub job_type: JobType,
+	pub status: JobStatus,
+	pub priority: i32,
+	pub payload: serde_json::Value,
+	pub run_at: DateTime<Utc>,
+}
+
+impl From<Job> for EnqueuedJobDto {
+	fn from(job: Job) -> Self {
+		Self {
+			id: job.id,
+			job_type: job.job_type,
+			status: job.status,
+			priority: job.priority,
+			payload: job.payload,
+			run_at: job.run_at,
+		}
+	}
+}
+
+/// A requested world reset and the token to confirm it with
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RequestedWorldResetDto {
+	pub id: WorldResetKey,
+	/// Only handed out once, the server keeps a hash of it
+	pub token: String,
+	/// The reset has to be confirmed before this time
+	pub expires_at: DateTime<Utc>,
+	/// Steps the reset runs once confirmed, in order
+	pub steps: Vec<WorldResetStep>,
+}
+
+impl From<RequestedReset> for RequestedWorldResetDto {
+	fn from(requested: RequestedReset) -> Self {
+		Self {
+			id: requested.reset.id,
+			token: requested.token,
+			expires_at: requested.reset.expires_at,
+			steps: WorldResetStep::ALL.to_vec(),
+		}
+	}
+}
+
+/// Where a world reset stands
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WorldResetDto {
+	pub id: WorldResetKey,
+	pub status: WorldResetStatus,
+	pub requested_by: Option<String>,
+	pub expires_at: DateTime<Utc>,
+	pub confirmed_by: Option<String>,
+	pub confirmed_at: Option<DateTime<Utc>>,
+	pub completed_steps: i32,
+	/// The step running next, `None` once the reset completed
+	pub next_step: Option<WorldResetStep>,
+	pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl From<WorldReset> for WorldResetDto {
+	fn from(reset: WorldReset) -> Self {
+		Self {
+			id: reset.id,
+			status: reset.status(Utc::now()),
+			next_step: reset.next_step(),
+			requested_by: reset.requested_by,
+			expires_at: reset.expires_at,
+			confirmed_by: reset.confirmed_by,
+			confirmed_at: reset.confirmed_at,
+			completed_steps: reset.completed_steps,
+			completed_at: reset.completed_at,
+		}
+	}
+}
+
+/// A modifier held by a player, as granted or revoked by an operator
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AdminActiveModifierDto {
+	pub id: ActiveModifierKey,
+	pub player_id: PlayerKey,
+	pub modifier_id: ModifierKey,
+	pub started_at: DateTime<Utc>,
+	pub expires_at: Option<DateTime<Utc>>,
+	pub source_type: ModifierSourceType,
+	pub player_building_id: Option<PlayerBuildingKey>,
+}
+
+impl From<ActiveModifier> for AdminActiveModifierDto {
+	fn from(active: ActiveModifier) -> Self {
+		Self {
+			id: active.id,
+			player_id: active.player_id,
+			modifier_id: active.modifier_id,
+			started_at: active.started_at,
+			expires_at: active.expires_at,
+			source_type: active.source_type,
+			player_building_id: active.player_building_id,
+		}
+	}
+}
+
+/// A player and their role
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PlayerRoleDto {
+	pub player_id: PlayerKey,
+	pub name: String,
+	pub role: PlayerRole,
+}
+
+impl From<Player> for PlayerRoleDto {
+	fn from(player: Player) -> Self {
+		Self {
+			player_id: player.id,
+			name: player.name,
+			role: player.role,
+		}
+	}
+}
+
+/// A tournament observer
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ObserverDto {
+	pub id: ObserverKey,
+	pub name: String,
+	pub scopes: Vec<ObserverScope>,
+	/// Players selected for the observer, including those that do not allow observers
+	pub player_ids: Vec<PlayerKey>,
+	/// Whether the observer's token still works
+	pub active: bool,
+	pub created_by: Option<String>,
+	pub expires_at: Option<DateTime<Utc>>,
+	pub revoked_at: Option<DateTime<Utc>>,
+	pub created_at: DateTime<Utc>,
+}
+
+impl From<(Observer, Vec<PlayerKey>)> for ObserverDto {
+	fn from((observer, player_ids): (Observer, Vec<PlayerKey>)) -> Self {
+		Self {
+			id: observer.id,
+			scopes: observer.scopes(),
+			active: observer.is_active(Utc::now()),
+			name: observer.name,
+			player_ids,
+			created_by: observer.created_by,
+			expires_at: observer.expires_at,
+			revoked_at: observer.revoked_at,
+			created_at: observer.created_at,
+		}
+	}
+}
+
+/// A limited event with the IDs of its objectives and offers
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LimitedEventDto {
+	pub id: LimitedEventKey,
+	pub name: String,
+	pub currency_name: String,
+	pub starts_at: DateTime<Utc>,
+	pub ends_at: DateTime<Utc>,
+	pub leftover_resource: ResourceType,
+	pub leftover_rate: i32,
+	pub closed_at: Option<DateTime<Utc>>,
+	pub objective_ids: Vec<EventObjectiveKey>,
+	pub offer_ids: Vec<EventOfferKey>,
+}
+
+impl From<EventDetails> for LimitedEventDto {
+	fn from(details: EventDetails) -> Self {
+		let event = details.event;
+		Self {
+			id: event.id,
+			name: event.name,
+			currency_name: event.currency_name,
+			starts_at: event.starts_at,
+			ends_at: event.ends_at,
+			leftover_resource: event.leftover_resource,
+			leftover_rate: event.leftover_rate,
+			closed_at: event.closed_at,
+			objective_ids: details
+				.objectives
+				.iter()
+				.map(|objective| objective.id)
+				.collect(),
+			offer_ids: details.offers.iter().map(|offer| offer.id).collect(),
+		}
+	}
+}
+
+/// A created season
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SeasonDto {
+	pub id: SeasonKey,
+	pub number: i32,
+	pub name: String,
+	pub starts_at: DateTime<Utc>,
+	pub ends_at: DateTime<Utc>,
+	pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl From<Season> for SeasonDto {
+	fn from(season: Season) -> Self {
+		Self {
+			id: season.id,
+			number: season.number,
+			name: season.name,
+			starts_at: season.starts_at,
+			ends_at: season.ends_at,
+			ended_at: season.ended_at,
+		}
+	}
+}
+
+/// A created tournament observer and its token
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CreatedObserverDto {
+	/// Only handed out once, the server keeps a hash of it
+	pub token: String,
+	#[serde(flatten)]
+	pub observer: ObserverDto,
+}
+
+impl From<CreatedObserver> for CreatedObserverDto {
+	fn from(created: CreatedObserver) -> Self {
+		Self {
+			token: created.token,
+			observer: ObserverDto::from((created.observer, created.player_ids)),
+		}
+	}
+}
+
+/// Size of a table at the time it was captured
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TableStatsSampleDto {
+	pub captured_at: DateTime<Utc>,
+	/// Live rows estimated by the statistics collector
+	pub row_count: i64,
+	/// Size on disk, indexes included
+	pub total_bytes: i64,
+}
+
+/// Growth of a table over the requested window, ready to chart
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TableGrowthDto {
+	pub table_name: String,
+	/// Oldest first
+	pub samples: Vec<TableStatsSampleDto>,
+	/// Change between the first and the last sample
+	pub row_growth: i64,
+	pub byte_growth: i64,
+}
+
+impl From<TableGrowth> for TableGrowthDto {
+	fn from(growth: TableGrowth) -> Self {
+		Self {
+			table_name: growth.table_name,
+			samples: growth
+				.samples
+				.into_iter()
+				.map(|sample| TableStatsSampleDto {
+					captured_at: sample.captured_at,
+					row_count: sample.row_count,
+					total_bytes: sample.total_bytes,
+				})
+				.collect(),
+			row_growth: growth.row_growth,
+			byte_growth: growth.byte_growth,
+		}
+	}
+}
+
+/// Growth of the tracked tables
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TableStatsResponse {
+	pub days: u32,
+	pub tables: Vec<TableGrowthDto>,
+}
+
+/// A recorded action
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditEntryDto {
+	pub id: AuditEntryKey,
+	pub action: String,
+	pub subject: Option<String>,
+	pub details: serde_json::Value,
+	/// Player who took the action, if a player took it
+	pub player_id: Option<PlayerKey>,
+	/// Operator who took the action, if an operator took it
+	pub operator: Option<String>,
+	pub request_id: Option<String>,
+	pub created_at: DateTime<Utc>,
+}
+
+impl From<AuditEntry> for AuditEntryDto {
+	fn from(entry: AuditEntry) -> Self {
+		Self {
+			id: entry.id,
+			action: entry.action,
+			subject: entry.subject,
+			details: entry.details,
+			player_id: entry.player_id,
+			operator: entry.operator,
+			request_id: entry.request_id,
+			created_at: entry.created_at,
+		}
+	}
+}
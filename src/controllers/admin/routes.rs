@@ -0,0 +1,76 @@
+//! Route definitions for the admin API endpoints.
+
+use axum::Router;
+use axum::routing::{delete, get, post, put};
+
+use crate::controllers::admin::handlers::*;
+use crate::domain::app_state::AppState;
+
+/// Returns a router with all admin routes.
+///
+/// Routes:
+/// - `GET /admin/overview` - Summarize player activity, queues and error rate
+/// - `POST /admin/jobs` - Enqueue a job of any registered type with a validated payload
+/// - `GET /admin/jobs/stats` - Queue depth, wait times and failure rate per job type
+/// - `GET /admin/backfills` - Progress of the online data backfills
+/// - `GET /admin/table-stats` - Row counts and on-disk sizes of the busiest tables over time
+/// - `GET /admin/audit` - Recorded player and operator actions, filtered by actor, action and time
+/// - `GET /admin/players/{player_id}/consistency` - Check a player's state for violations
+/// - `POST /admin/players/{player_id}/modifiers` - Grant a modifier to a player
+/// - `DELETE /admin/players/{player_id}/modifiers/{modifier_id}` - Revoke a modifier from a player
+/// - `PUT /admin/players/{player_id}/role` - Make a player a moderator or an admin, or demote them
+/// - `GET /admin/dead-letters` - List dead-lettered jobs
+/// - `GET /admin/dead-letters/{job_id}` - Inspect a dead-lettered job
+/// - `DELETE /admin/dead-letters/{job_id}` - Discard a dead-lettered job
+/// - `POST /admin/dead-letters/{job_id}/requeue` - Move a dead-lettered job back into the queue
+/// - `POST /admin/events` - Create a limited event with its objectives and its shop
+/// - `POST /admin/seasons` - Create the next season and schedule its end
+/// - `GET /admin/observers` - List the tournament observers
+/// - `POST /admin/observers` - Create a tournament observer and receive its token
+/// - `DELETE /admin/observers/{observer_id}` - Revoke a tournament observer
+/// - `POST /admin/world-resets` - Request a world reset and receive its confirmation token
+/// - `GET /admin/world-resets/{reset_id}` - Status and progress of a world reset
+/// - `POST /admin/world-resets/{reset_id}/confirm` - Confirm a world reset with its token
+pub fn admin_routes() -> Router<AppState> {
+	Router::new().nest(
+		"/admin",
+		Router::new()
+			.route("/overview", get(get_overview))
+			.route("/jobs", post(enqueue_job))
+			.route("/jobs/stats", get(get_job_stats))
+			.route("/backfills", get(get_backfills))
+			.route("/table-stats", get(get_table_stats))
+			.route("/audit", get(get_audit_log))
+			.route(
+				"/players/{player_id}/consistency",
+				get(get_player_consistency),
+			)
+			.route(
+				"/players/{player_id}/modifiers",
+				post(grant_player_modifier),
+			)
+			.route(
+				"/players/{player_id}/modifiers/{modifier_id}",
+				delete(revoke_player_modifier),
+			)
+			.route("/players/{player_id}/role", put(set_player_role))
+			.route("/dead-letters", get(get_dead_letters))
+			.nest(
+				"/dead-letters/{job_id}",
+				Router::new()
+					.route("/", get(get_dead_letter).delete(discard_dead_letter))
+					.route("/requeue", post(requeue_dead_letter)),
+			)
+			.route("/events", post(create_event))
+			.route("/seasons", post(create_season))
+			.route("/observers", get(get_observers).post(create_observer))
+			.route("/observers/{observer_id}", delete(revoke_observer))
+			.route("/world-resets", post(request_world_reset))
+			.nest(
+				"/world-resets/{reset_id}",
+				Router::new()
+					.route("/", get(get_world_reset))
+					.route("/confirm", post(confirm_world_reset)),
+			),
+	)
+}
@@ -1,65 +1,85 @@
-use argon2::{Argon2, PasswordHash, PasswordVerifier};
-use axum::http::StatusCode;
-use axum::response::IntoResponse;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Redirect};
 use axum::{Extension, Json, debug_handler};
 use axum_extra::extract::CookieJar;
 use cookie::Cookie;
 use serde_json::json;
 use tracing::{debug, error, info, instrument, trace, warn};
 
+use crate::auth::oauth_operations::{self, OAUTH_STATE_COOKIE_NAME};
+use crate::auth::password::{self, PasswordCheck};
 use crate::auth::session_operations;
 use crate::configuration::Settings;
 use crate::controllers::auth::models::{
-	LoginPayload, PlayerDto, PlayerDtoResponse, RegisterPayload, SessionDto,
+	LoginPayload, OAuthCallbackQuery, OAuthLoginResponse, PlayerDto, PlayerDtoResponse,
+	RegisterPayload, RegisterResponse, SessionDto, StatusBody,
 };
 use crate::db::extractor::DatabaseConnection;
-use crate::db::players;
-use crate::domain::app_state::AppState;
+use crate::db::{DbConn, players};
+use crate::domain::app_state::{AppPool, AppState};
 use crate::domain::auth::{AuthError, AuthenticatedUser};
-use crate::domain::player::NewPlayer;
+use crate::domain::error::ErrorBody;
+use crate::domain::player::identity::OAuthProvider;
 use crate::domain::player::session::PlayerSession;
+use crate::domain::player::{NewPlayer, PlayerKey, UpdatePlayer};
+use crate::game::account_deletion::deletion_operations;
+use crate::game::audit_operations::{self, LOGIN_ACTION, PlayerActor};
+use crate::game::onboarding_operations;
 use crate::net::{SESSION_COOKIE_NAME, SessionToken, TOKEN_COOKIE_NAME};
 
-#[instrument(skip(conn, payload), fields(username = %payload.username))]
+/// Registers a neutral player and logs them in with a session cookie.
+#[utoipa::path(
+	post,
+	path = "/register",
+	tag = "auth",
+	request_body = RegisterPayload,
+	responses(
+		(status = CREATED, description = "Player registered and logged in", body = RegisterResponse),
+		(status = BAD_REQUEST, description = "Invalid username, email or password", body = ErrorBody),
+		(status = CONFLICT, description = "Username already taken", body = ErrorBody),
+	)
+)]
+#[instrument(skip(conn, settings, payload), fields(username = %payload.username))]
 #[debug_handler(state = AppState)]
 pub(super) async fn register(
 	DatabaseConnection(mut conn): DatabaseConnection,
+	settings: Settings,
 	jar: CookieJar,
 	Json(payload): Json<RegisterPayload>,
-) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+) -> crate::Result<impl IntoResponse> {
 	trace!("Starting player registration process");
-	let new_user = NewPlayer::try_from(payload).map_err(|err| {
+	let new_user = NewPlayer::try_from(payload).inspect_err(|err| {
 		error!("Failed to parse player during registration: {}", err);
-		let body = json!({ "status": "error", "message": err.to_string() });
-		(StatusCode::BAD_REQUEST, Json(body))
 	})?;
 
 	debug!("Player data parsed successfully");
 
-	match players::exists_by_name(&mut conn, &new_user.name) {
-		Ok(exists) => {
-			if exists {
-				warn!(
-					"Registration attempted with existing username: {}",
-					new_user.name
-				);
-				let body = json!({ "status": "error", "message": "Username already taken" });
-				return Err((StatusCode::CONFLICT, Json(body)));
-			}
-			debug!("Username {} is available for registration", new_user.name);
-		}
-		Err(err) => {
-			error!("Failed to check if player exists: {}", err);
-			let body = json!({ "status": "error", "message": "Please try again later" });
-			return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(body)));
-		}
+	let exists = players::exists_by_name(&mut conn, &new_user.name).inspect_err(|err| {
+		error!("Failed to check if player exists: {}", err);
+	})?;
+	if exists {
+		warn!(
+			"Registration attempted with existing username: {}",
+			new_user.name
+		);
+		return Err(crate::Error::from((
+			crate::ErrorKind::UsernameTakenError,
+			"Username already taken",
+		)));
 	}
+	debug!("Username {} is available for registration", new_user.name);
 
-	let created_user = players::create(&mut conn, new_user).map_err(|err| {
-		error!("Failed to insert player: {:#?}", err);
-		let body = json!({ "status": "error", "message": err.to_string() });
-		(StatusCode::INTERNAL_SERVER_ERROR, Json(body))
-	})?;
+	let created_user = onboarding_operations::create_player(
+		&mut conn,
+		&settings.protection,
+		&settings.onboarding,
+		new_user,
+	)
+	.inspect_err(|err| {
+		error!("Failed to create player: {:#?}", err);
+	})?
+	.player;
 	info!(
 		player_id = created_user.id.to_string(),
 		"Created player successfully"
@@ -67,15 +87,11 @@ pub(super) async fn register(
 
 	let session_token = session_operations::gen_token();
 	let session = session_operations::create(&mut conn, session_token.clone(), &created_user.id)
-		.map_err(|e| {
+		.inspect_err(|e| {
 			error!(
 				"Failed to create session for player {}: {:?}",
 				&created_user.id, e
 			);
-			(
-				StatusCode::INTERNAL_SERVER_ERROR,
-				Json(json!({ "message": "Failed to create session" })),
-			)
 		})?;
 	let cookie = session_operations::gen_cookie(&session, &session_token);
 
@@ -84,18 +100,35 @@ pub(super) async fn register(
 		"Player registration completed successfully"
 	);
 
-	let user = PlayerDto::from(created_user);
-	let body =
-		json!({ "status": "success", "message": "Player registered successfully", "user": user });
+	let body = RegisterResponse {
+		status: "success".to_string(),
+		message: "Player registered successfully".to_string(),
+		user: PlayerDto::from(created_user),
+	};
 	Ok((StatusCode::CREATED, jar.add(cookie), Json(body)))
 }
 
+/// Logs a player in with their password, setting the session cookie.
+#[utoipa::path(
+	post,
+	path = "/login",
+	tag = "auth",
+	request_body = LoginPayload,
+	responses(
+		(status = OK, description = "Logged in, the session is set as a cookie"),
+		(status = BAD_REQUEST, description = "Missing credentials", body = ErrorBody),
+		(status = UNAUTHORIZED, description = "Wrong credentials", body = ErrorBody),
+		(status = FORBIDDEN, description = "The password has to be reset", body = ErrorBody),
+		(status = TOO_MANY_REQUESTS, description = "Too many failed logins"),
+	)
+)]
 #[instrument(skip_all, fields(username = %payload.username))]
 #[debug_handler(state = AppState)]
 pub(super) async fn login(
 	DatabaseConnection(mut conn): DatabaseConnection,
 	jar: CookieJar,
 	settings: Settings,
+	headers: HeaderMap,
 	Json(payload): Json<LoginPayload>,
 ) -> Result<impl IntoResponse, AuthError> {
 	if payload.username.is_empty() || payload.password.is_empty() {
@@ -111,29 +144,42 @@ pub(super) async fn login(
 	debug!("Found player record for login attempt");
 
 	trace!("Verifying password for player {}", user.name);
-	let argon2 = Argon2::default();
-	let hash = PasswordHash::new(&user.pwd_hash).map_err(|e| {
+	let check = password::verify_password(&payload.password, &user.pwd_hash).map_err(|e| {
 		error!(
-			"Failed to parse password hash for player {}: {:?}",
+			"Failed to verify password hash for player {}: {:?}",
 			user.name, e
 		);
 		AuthError::ArgonError
 	})?;
 
-	if argon2
-		.verify_password(payload.password.as_ref(), &hash)
-		.is_err()
-	{
-		warn!(
-			player_id = %user.id,
-			"Authentication failed - invalid password for player: {}",
-			user.name
-		);
-		return Err(AuthError::WrongCredentials);
+	match check {
+		PasswordCheck::Valid => {}
+		PasswordCheck::Outdated => rehash_password(&mut conn, &user.id, &payload.password),
+		PasswordCheck::Invalid => {
+			warn!(
+				player_id = %user.id,
+				"Authentication failed - invalid password for player: {}",
+				user.name
+			);
+			return Err(AuthError::WrongCredentials);
+		}
+		PasswordCheck::ResetRequired => {
+			warn!(
+				player_id = %user.id,
+				"Authentication refused - password of player {} has to be reset",
+				user.name
+			);
+			return Err(AuthError::PasswordResetRequired);
+		}
 	}
 
 	debug!("Password verified successfully for player {}", user.name);
 
+	deletion_operations::cancel_on_login(&mut conn, &user).map_err(|e| {
+		error!("Failed to cancel deletion of player {}: {:?}", user.id, e);
+		AuthError::TokenCreation
+	})?;
+
 	trace!("Generating session token for player {}", user.id);
 	let session_token = session_operations::gen_token();
 
@@ -149,12 +195,30 @@ pub(super) async fn login(
 		expires_at = %session.expires_at,
 		"Player successfully logged in"
 	);
+	audit_operations::record(
+		&mut conn,
+		&PlayerActor::new(user.id, &headers),
+		LOGIN_ACTION,
+		Some(user.id.to_string()),
+		json!({ "method": "password" }),
+	);
 
 	let cookie = session_operations::gen_cookie(&session, &session_token);
 
 	Ok(jar.add(cookie))
 }
 
+/// Ends the session of the player and removes their cookies.
+#[utoipa::path(
+	post,
+	path = "/logout",
+	tag = "auth",
+	responses(
+		(status = OK, description = "Logged out", body = StatusBody),
+		(status = UNAUTHORIZED, description = "Not logged in"),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
 #[instrument(skip(jar, conn, maybe_session))]
 #[debug_handler(state = AppState)]
 pub(super) async fn logout(
@@ -177,16 +241,31 @@ pub(super) async fn logout(
 			session.id
 		);
 
-		let body = json!({ "status": "ok" });
+		let body = StatusBody {
+			status: "ok".to_string(),
+			message: None,
+		};
 		Ok((jar, Json(body)))
 	} else {
 		// User is authenticated via JWT, which doesn't have a server-side session to invalidate
 		let jar = jar.remove(Cookie::from(TOKEN_COOKIE_NAME));
-		let body = json!({ "status": "ok", "message": "JWT token removed" });
+		let body = StatusBody::new("ok", "JWT token removed");
 		Ok((jar, Json(body)))
 	}
 }
 
+/// Returns the player and the session they are logged in with.
+#[utoipa::path(
+	get,
+	path = "/session",
+	tag = "auth",
+	responses(
+		(status = OK, description = "The player and their session", body = PlayerDtoResponse),
+		(status = BAD_REQUEST, description = "Logged in with a token instead of a session", body = ErrorBody),
+		(status = UNAUTHORIZED, description = "Not logged in"),
+	),
+	security(("session" = []))
+)]
 #[instrument(skip_all, fields(player_id = %player.id))]
 #[debug_handler(state = AppState)]
 pub(super) async fn session(
@@ -224,3 +303,151 @@ pub(super) async fn session(
 		Err(AuthError::MismatchedModality)
 	}
 }
+
+/// Sends the player to log in with an OAuth provider.
+#[utoipa::path(
+	get,
+	path = "/auth/oauth/{provider}/start",
+	tag = "auth",
+	params(("provider" = String, Path, description = "OAuth provider to log in with")),
+	responses(
+		(status = SEE_OTHER, description = "Redirect to the login page of the provider"),
+		(status = NOT_FOUND, description = "Unknown provider", body = ErrorBody),
+	)
+)]
+#[instrument(skip(settings, jar))]
+#[debug_handler(state = AppState)]
+pub(super) async fn oauth_start(
+	Path(provider): Path<String>,
+	settings: Settings,
+	jar: CookieJar,
+) -> crate::Result<impl IntoResponse> {
+	let provider = parse_provider(&provider)?;
+	let state = session_operations::gen_token();
+	let url = oauth_operations::authorize_url(&settings.oauth, provider, &state)?;
+	debug!("Sending player to log in with {}", provider);
+	Ok((
+		jar.add(oauth_operations::state_cookie(provider, &state)),
+		Redirect::to(&url),
+	))
+}
+
+/// Logs in the player the provider sent back, registering them on their first login.
+#[utoipa::path(
+	get,
+	path = "/auth/oauth/{provider}/callback",
+	tag = "auth",
+	params(
+		("provider" = String, Path, description = "OAuth provider the player logged in with"),
+		OAuthCallbackQuery,
+	),
+	responses(
+		(status = OK, description = "Logged in", body = OAuthLoginResponse),
+		(status = CREATED, description = "Registered and logged in", body = OAuthLoginResponse),
+		(status = BAD_REQUEST, description = "The login failed at the provider", body = ErrorBody),
+		(status = NOT_FOUND, description = "Unknown provider", body = ErrorBody),
+	)
+)]
+#[instrument(skip(pool, query, settings, jar, headers))]
+#[debug_handler(state = AppState)]
+pub(super) async fn oauth_callback(
+	State(pool): State<AppPool>,
+	Path(provider): Path<String>,
+	Query(query): Query<OAuthCallbackQuery>,
+	settings: Settings,
+	jar: CookieJar,
+	headers: HeaderMap,
+) -> crate::Result<impl IntoResponse> {
+	let provider = parse_provider(&provider)?;
+	let cookie = jar
+		.get(OAUTH_STATE_COOKIE_NAME)
+		.map(|cookie| cookie.value().to_string());
+	let jar = jar.remove(oauth_operations::expired_state_cookie());
+
+	if let Some(error) = query.error {
+		warn!("Login with {} failed at the provider: {}", provider, error);
+		return Err(crate::Error::from((
+			crate::ErrorKind::InvalidData,
+			"Login at the provider failed",
+			error,
+		)));
+	}
+	let (Some(code), Some(state)) = (query.code, query.state) else {
+		return Err(crate::Error::from((
+			crate::ErrorKind::InvalidData,
+			"Missing OAuth code or state",
+		)));
+	};
+	oauth_operations::verify_state(provider, cookie.as_deref(), &state)?;
+
+	// The provider is asked before taking a connection, it may take a while to answer
+	let account = oauth_operations::fetch_account(&settings.oauth, provider, &code).await?;
+	let mut conn = pool.get()?;
+	let login = oauth_operations::login_or_register(&mut conn, &settings, account)?;
+
+	let session_token = session_operations::gen_token();
+	let session = session_operations::create(&mut conn, session_token.clone(), &login.player.id)?;
+	info!(
+		player_id = %login.player.id,
+		session_id = %session.id,
+		created = login.created,
+		"Player logged in with {}",
+		provider
+	);
+	audit_operations::record(
+		&mut conn,
+		&PlayerActor::new(login.player.id, &headers),
+		LOGIN_ACTION,
+		Some(login.player.id.to_string()),
+		json!({ "method": provider, "registered": login.created }),
+	);
+
+	let status = if login.created {
+		StatusCode::CREATED
+	} else {
+		StatusCode::OK
+	};
+	let body = OAuthLoginResponse {
+		created: login.created,
+		player: PlayerDto::from(login.player),
+	};
+	Ok((
+		status,
+		jar.add(session_operations::gen_cookie(&session, &session_token)),
+		Json(body),
+	))
+}
+
+/// Parses the provider of an OAuth route, unknown providers are not found.
+fn parse_provider(provider: &str) -> crate::Result<OAuthProvider> {
+	provider.parse().map_err(|_| {
+		crate::Error::from((crate::ErrorKind::NotFoundError, "Unknown OAuth provider"))
+	})
+}
+
+/// Rewrites the hash of a player who logged in with an outdated one with the current hasher.
+///
+/// The login goes on if this fails, the hash is rewritten at a later login instead.
+fn rehash_password(conn: &mut DbConn, player_id: &PlayerKey, pwd: &str) {
+	let pwd_hash = match password::hash_password(pwd) {
+		Ok(pwd_hash) => pwd_hash,
+		Err(e) => {
+			warn!("Failed to rehash password of player {}: {:?}", player_id, e);
+			return;
+		}
+	};
+	let changeset = UpdatePlayer {
+		id: *player_id,
+		name: None,
+		pwd_hash: Some(pwd_hash),
+		email: None,
+		faction: None,
+	};
+	match players::update(conn, &changeset) {
+		Ok(_) => debug!("Rehashed outdated password of player {}", player_id),
+		Err(e) => warn!(
+			"Failed to store rehashed password of player {}: {}",
+			player_id, e
+		),
+	}
+}
@@ -2,5 +2,8 @@ mod handlers;
 mod models;
 mod routes;
 
-pub use models::{LoginPayload, PlayerDto, PlayerDtoResponse, RegisterPayload, SessionDto};
-pub use routes::{auth_routes, protected_auth_routes};
+pub use models::{
+	LoginPayload, OAuthLoginResponse, PlayerDto, PlayerDtoResponse, RegisterPayload,
+	RegisterResponse, SessionDto, StatusBody,
+};
+pub use routes::{AuthApi, auth_routes, protected_auth_routes};
@@ -2,6 +2,7 @@ use std::fmt::Debug;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
 use crate::ErrorKind;
 use crate::auth::utils::hash_password;
@@ -9,7 +10,7 @@ use crate::domain::factions::FactionCode;
 use crate::domain::player;
 use crate::domain::player::{NewPlayer, PlayerKey};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct RegisterPayload {
 	pub username: String,
 	pub password: String,
@@ -46,7 +47,7 @@ impl TryFrom<RegisterPayload> for NewPlayer {
 	}
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct LoginPayload {
 	pub username: String,
 	pub password: String,
@@ -61,13 +62,13 @@ impl Debug for LoginPayload {
 	}
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct PlayerDtoResponse {
 	pub player: PlayerDto,
 	pub session: SessionDto,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct PlayerDto {
 	pub id: PlayerKey,
 	pub name: String,
@@ -86,8 +87,48 @@ impl From<player::Player> for PlayerDto {
 	}
 }
 
-#[derive(Serialize, Deserialize)]
+/// Response of a successful registration
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct RegisterResponse {
+	pub status: String,
+	pub message: String,
+	pub user: PlayerDto,
+}
+
+/// Outcome of a request that only reports whether it succeeded
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct StatusBody {
+	pub status: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub message: Option<String>,
+}
+
+impl StatusBody {
+	pub fn new(status: &str, message: impl Into<String>) -> Self {
+		Self {
+			status: status.to_string(),
+			message: Some(message.into()),
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct SessionDto {
 	pub token: String,
 	pub expires_at: DateTime<Utc>,
 }
+
+/// Where a provider sends a player back to, with a code or the reason the login failed
+#[derive(Deserialize, IntoParams, Debug)]
+pub struct OAuthCallbackQuery {
+	pub code: Option<String>,
+	pub state: Option<String>,
+	pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct OAuthLoginResponse {
+	/// Whether the login registered the player
+	pub created: bool,
+	pub player: PlayerDto,
+}
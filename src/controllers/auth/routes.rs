@@ -1,5 +1,6 @@
 use axum::Router;
 use axum::routing::{get, post};
+use utoipa::OpenApi;
 
 use crate::controllers::auth::handlers::*;
 use crate::domain::app_state::AppState;
@@ -8,6 +9,8 @@ pub fn auth_routes() -> Router<AppState> {
 	Router::new()
 		.route("/login", post(login))
 		.route("/register", post(register))
+		.route("/auth/oauth/{provider}/start", get(oauth_start))
+		.route("/auth/oauth/{provider}/callback", get(oauth_callback))
 }
 
 pub fn protected_auth_routes() -> Router<AppState> {
@@ -15,3 +18,8 @@ pub fn protected_auth_routes() -> Router<AppState> {
 		.route("/logout", post(logout))
 		.route("/session", get(session))
 }
+
+/// OpenAPI document of the authentication routes.
+#[derive(OpenApi)]
+#[openapi(paths(register, login, logout, session, oauth_start, oauth_callback))]
+pub struct AuthApi;
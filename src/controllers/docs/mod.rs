@@ -0,0 +1,11 @@
+//! OpenAPI document of the API and the Swagger UI to browse it.
+//!
+//! Each controller describes its own routes, see [`AuthApi`](crate::controllers::auth::AuthApi)
+//! for instance, and [`api_doc`] merges them into the document served at
+//! [`OPENAPI_PATH`].
+
+mod openapi;
+mod routes;
+
+pub use openapi::{ApiDoc, api_doc};
+pub use routes::{OPENAPI_PATH, SWAGGER_UI_PATH, docs_routes};
@@ -0,0 +1,61 @@
+use utoipa::openapi::OpenApi as OpenApiDoc;
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::controllers::auth::AuthApi;
+use crate::controllers::game::game_api_doc;
+use crate::controllers::health::HealthApi;
+use crate::domain::error::ErrorBody;
+use crate::net::SESSION_COOKIE_NAME;
+
+/// Base of the OpenAPI document, with what every documented route shares.
+#[derive(OpenApi)]
+#[openapi(
+	info(
+		title = "Empire API",
+		description = "HTTP API of the Empire game server. Game routes act on the capital of \
+		               the player unless a settlement is selected with the `x-settlement-id` \
+		               header."
+	),
+	modifiers(&SecurityAddon),
+	components(schemas(ErrorBody)),
+	tags(
+		(name = "auth", description = "Registration, logins and sessions"),
+		(name = "health", description = "Health checks of the server"),
+		(name = "buildings", description = "Buildings of the player and the building catalog"),
+		(name = "units", description = "Training, upgrading and disbanding units"),
+		(name = "resources", description = "Collecting and sending resources"),
+	)
+)]
+pub struct ApiDoc;
+
+/// Declares the ways players authenticate, a bearer token or the session cookie.
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+	fn modify(&self, openapi: &mut OpenApiDoc) {
+		let components = openapi.components.get_or_insert_with(Default::default);
+		components.add_security_scheme(
+			"bearer",
+			SecurityScheme::Http(
+				HttpBuilder::new()
+					.scheme(HttpAuthScheme::Bearer)
+					.bearer_format("JWT")
+					.build(),
+			),
+		);
+		components.add_security_scheme(
+			"session",
+			SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new(SESSION_COOKIE_NAME))),
+		);
+	}
+}
+
+/// Returns the OpenAPI document of the API, merged from the documents of the controllers.
+pub fn api_doc() -> OpenApiDoc {
+	let mut doc = ApiDoc::openapi();
+	doc.merge(HealthApi::openapi());
+	doc.merge(AuthApi::openapi());
+	doc.merge(game_api_doc());
+	doc
+}
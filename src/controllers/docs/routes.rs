@@ -0,0 +1,18 @@
+use axum::Router;
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::controllers::docs::api_doc;
+use crate::domain::app_state::AppState;
+
+/// Where the OpenAPI document is served.
+pub const OPENAPI_PATH: &str = "/api-docs/openapi.json";
+
+/// Where the Swagger UI is served.
+pub const SWAGGER_UI_PATH: &str = "/swagger-ui";
+
+/// Function to define the routes serving the OpenAPI document and the Swagger UI
+pub fn docs_routes() -> Router<AppState> {
+	SwaggerUi::new(SWAGGER_UI_PATH)
+		.url(OPENAPI_PATH, api_doc())
+		.into()
+}
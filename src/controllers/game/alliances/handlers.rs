@@ -0,0 +1,217 @@
+//! Request handlers for the alliance API endpoints.
+
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json, debug_handler};
+use tracing::{debug, info, instrument};
+
+use crate::configuration::Settings;
+use crate::controllers::game::alliances::models::*;
+use crate::db::extractor::DatabaseConnection;
+use crate::domain::alliance::AllianceKey;
+use crate::domain::app_state::AppState;
+use crate::domain::auth::AuthenticatedUser;
+use crate::domain::player::PlayerKey;
+use crate::game::alliances::alliance_operations;
+use crate::{Error, ErrorKind, Result};
+
+/// POST /game/alliances
+///
+/// Founds an alliance with the player as its leader.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn create_alliance(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Json(request): Json<CreateAllianceRequest>,
+) -> Result<impl IntoResponse> {
+	let details =
+		alliance_operations::create_alliance(&mut conn, &player.id, &request.name, &request.tag)?;
+	Ok((StatusCode::CREATED, Json(AllianceDto::from(details))))
+}
+
+/// GET /game/alliances/me
+///
+/// Returns the player's alliance with its members and treasury.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_my_alliance(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	let details = alliance_operations::get_player_alliance(&mut conn, &player.id)?
+		.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Not in an alliance")))?;
+	Ok(Json(AllianceDto::from(details)))
+}
+
+/// GET /game/alliances/{alliance_id}
+///
+/// Returns an alliance with its members, the treasury only to its members.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_alliance(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path(alliance_id): Path<AllianceKey>,
+) -> Result<impl IntoResponse> {
+	let details = alliance_operations::get_alliance(&mut conn, &alliance_id)?;
+	let is_member = details
+		.members
+		.iter()
+		.any(|info| info.member.player_id == player.id);
+	let mut dto = AllianceDto::from(details);
+	if !is_member {
+		dto.treasury = None;
+	}
+	Ok(Json(dto))
+}
+
+/// GET /game/alliances/invites
+///
+/// Returns the player's pending invites, newest first.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_invites(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	let invites = alliance_operations::list_invites(&mut conn, &player.id)?;
+	debug!(
+		"Retrieved {} invites for player {}",
+		invites.len(),
+		player.id
+	);
+	Ok(Json(InviteListResponse {
+		invites: invites.into_iter().map(AllianceInviteDto::from).collect(),
+	}))
+}
+
+/// POST /game/alliances/{alliance_id}/invites
+///
+/// Invites a player to the alliance, for its leader and officers.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn invite_player(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path(alliance_id): Path<AllianceKey>,
+	Json(request): Json<InvitePlayerRequest>,
+) -> Result<impl IntoResponse> {
+	let invite =
+		alliance_operations::invite(&mut conn, &player.id, &alliance_id, &request.player_id)?;
+	let alliance = alliance_operations::get_alliance(&mut conn, &alliance_id)?.alliance;
+	Ok((
+		StatusCode::CREATED,
+		Json(AllianceInviteDto::from((invite, alliance))),
+	))
+}
+
+/// POST /game/alliances/{alliance_id}/join
+///
+/// Joins the alliance by accepting the player's invite to it.
+#[instrument(skip(conn, settings, player))]
+#[debug_handler(state = AppState)]
+pub async fn join_alliance(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	settings: Settings,
+	player: Extension<AuthenticatedUser>,
+	Path(alliance_id): Path<AllianceKey>,
+) -> Result<impl IntoResponse> {
+	alliance_operations::join(&mut conn, &settings.alliances, &player.id, &alliance_id)?;
+	let details = alliance_operations::get_alliance(&mut conn, &alliance_id)?;
+	Ok(Json(AllianceDto::from(details)))
+}
+
+/// POST /game/alliances/leave
+///
+/// Leaves the player's alliance. A leaving leader hands the leadership over to the most
+/// senior officer or member, the last member leaving disbands the alliance.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn leave_alliance(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	let departure = alliance_operations::leave(&mut conn, &player.id)?;
+	Ok(Json(DepartureResponse::from(departure)))
+}
+
+/// DELETE /game/alliances/{alliance_id}/members/{player_id}
+///
+/// Kicks a member of a lower role out of the alliance.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn kick_member(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path((alliance_id, member_id)): Path<(AllianceKey, PlayerKey)>,
+) -> Result<impl IntoResponse> {
+	alliance_operations::kick(&mut conn, &player.id, &alliance_id, &member_id)?;
+	Ok(StatusCode::NO_CONTENT)
+}
+
+/// PUT /game/alliances/{alliance_id}/members/{player_id}/role
+///
+/// Changes the role of a member, for the leader.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn set_member_role(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path((alliance_id, member_id)): Path<(AllianceKey, PlayerKey)>,
+	Json(request): Json<SetAllianceRoleRequest>,
+) -> Result<impl IntoResponse> {
+	alliance_operations::set_role(
+		&mut conn,
+		&player.id,
+		&alliance_id,
+		&member_id,
+		request.role,
+	)?;
+	let details = alliance_operations::get_alliance(&mut conn, &alliance_id)?;
+	Ok(Json(AllianceDto::from(details)))
+}
+
+/// POST /game/alliances/{alliance_id}/treasury
+///
+/// Moves resources from the player to the treasury of their alliance.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn donate(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path(alliance_id): Path<AllianceKey>,
+	Json(request): Json<DonateRequest>,
+) -> Result<impl IntoResponse> {
+	let (alliance, donation) = alliance_operations::donate(
+		&mut conn,
+		&player.id,
+		&alliance_id,
+		(request.food, request.wood, request.stone, request.gold),
+	)?;
+	info!("Player {} donated to alliance {}", player.id, alliance_id);
+	Ok((
+		StatusCode::CREATED,
+		Json(DonateResponse {
+			treasury: TreasuryDto::from(&alliance),
+			donation: DonationDto::from(donation),
+		}),
+	))
+}
+
+/// GET /game/alliances/{alliance_id}/donations
+///
+/// Returns the most recent donations to the treasury, for the members.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_donations(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path(alliance_id): Path<AllianceKey>,
+) -> Result<impl IntoResponse> {
+	let donations = alliance_operations::list_donations(&mut conn, &player.id, &alliance_id)?;
+	Ok(Json(DonationListResponse {
+		donations: donations.into_iter().map(DonationDto::from).collect(),
+	}))
+}
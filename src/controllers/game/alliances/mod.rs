@@ -0,0 +1,14 @@
+//! Alliance controller module for players banding together.
+//!
+//! Provides REST API endpoints for:
+//! - Founding an alliance and viewing alliances with their members
+//! - Inviting players, and joining an alliance by accepting an invite
+//! - Leaving an alliance, kicking members and changing their roles
+//! - Donating resources to the alliance treasury
+
+mod handlers;
+mod models;
+mod routes;
+
+pub use models::*;
+pub use routes::*;
@@ -0,0 +1,211 @@
+//! Request and response DTOs for the alliance API endpoints.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::alliance::{
+	Alliance, AllianceDonation, AllianceDonationKey, AllianceInvite, AllianceInviteKey,
+	AllianceKey, AllianceRole,
+};
+use crate::domain::player::PlayerKey;
+use crate::game::alliances::alliance_operations::{AllianceDetails, Departure, MemberInfo};
+
+// === Request DTOs ===
+
+/// Request body for POST /alliances
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateAllianceRequest {
+	pub name: String,
+	/// 2 to 5 letters or digits
+	pub tag: String,
+}
+
+/// Request body for POST /alliances/{alliance_id}/invites
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InvitePlayerRequest {
+	pub player_id: PlayerKey,
+}
+
+/// Request body for PUT /alliances/{alliance_id}/members/{player_id}/role
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SetAllianceRoleRequest {
+	/// Making a member the leader hands the leadership over
+	pub role: AllianceRole,
+}
+
+/// Request body for POST /alliances/{alliance_id}/treasury
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct DonateRequest {
+	pub food: i64,
+	pub wood: i64,
+	pub stone: i64,
+	pub gold: i64,
+}
+
+// === Response DTOs ===
+
+/// Resources held by an alliance.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct TreasuryDto {
+	pub food: i64,
+	pub wood: i64,
+	pub stone: i64,
+	pub gold: i64,
+}
+
+impl From<&Alliance> for TreasuryDto {
+	fn from(alliance: &Alliance) -> Self {
+		Self {
+			food: alliance.food,
+			wood: alliance.wood,
+			stone: alliance.stone,
+			gold: alliance.gold,
+		}
+	}
+}
+
+/// A member of an alliance.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AllianceMemberDto {
+	pub player_id: PlayerKey,
+	pub name: String,
+	pub role: AllianceRole,
+	pub joined_at: DateTime<Utc>,
+}
+
+impl From<MemberInfo> for AllianceMemberDto {
+	fn from(info: MemberInfo) -> Self {
+		Self {
+			player_id: info.member.player_id,
+			name: info.name,
+			role: info.member.role,
+			joined_at: info.member.joined_at,
+		}
+	}
+}
+
+/// An alliance with its members.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AllianceDto {
+	pub id: AllianceKey,
+	pub name: String,
+	pub tag: String,
+	/// Highest role first, then by seniority
+	pub members: Vec<AllianceMemberDto>,
+	/// Only shown to the members
+	pub treasury: Option<TreasuryDto>,
+	pub created_at: DateTime<Utc>,
+}
+
+impl From<AllianceDetails> for AllianceDto {
+	fn from(details: AllianceDetails) -> Self {
+		Self {
+			treasury: Some(TreasuryDto::from(&details.alliance)),
+			id: details.alliance.id,
+			name: details.alliance.name,
+			tag: details.alliance.tag,
+			members: details
+				.members
+				.into_iter()
+				.map(AllianceMemberDto::from)
+				.collect(),
+			created_at: details.alliance.created_at,
+		}
+	}
+}
+
+/// An invite to join an alliance.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AllianceInviteDto {
+	pub id: AllianceInviteKey,
+	pub alliance_id: AllianceKey,
+	pub alliance_name: String,
+	pub alliance_tag: String,
+	pub player_id: PlayerKey,
+	pub invited_by: Option<PlayerKey>,
+	pub created_at: DateTime<Utc>,
+}
+
+impl From<(AllianceInvite, Alliance)> for AllianceInviteDto {
+	fn from((invite, alliance): (AllianceInvite, Alliance)) -> Self {
+		Self {
+			id: invite.id,
+			alliance_id: alliance.id,
+			alliance_name: alliance.name,
+			alliance_tag: alliance.tag,
+			player_id: invite.player_id,
+			invited_by: invite.invited_by,
+			created_at: invite.created_at,
+		}
+	}
+}
+
+/// Response for GET /alliances/invites
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InviteListResponse {
+	/// Pending invites, newest first
+	pub invites: Vec<AllianceInviteDto>,
+}
+
+/// Response for POST /alliances/leave
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DepartureResponse {
+	pub alliance_id: AllianceKey,
+	/// Member who took over the leadership, if the leader left
+	pub successor: Option<PlayerKey>,
+	/// Whether the player was the last member and the alliance is gone
+	pub disbanded: bool,
+}
+
+impl From<Departure> for DepartureResponse {
+	fn from(departure: Departure) -> Self {
+		Self {
+			alliance_id: departure.alliance_id,
+			successor: departure.successor,
+			disbanded: departure.disbanded,
+		}
+	}
+}
+
+/// A donation to an alliance treasury.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DonationDto {
+	pub id: AllianceDonationKey,
+	/// Player who donated, `None` once they are gone
+	pub player_id: Option<PlayerKey>,
+	pub food: i64,
+	pub wood: i64,
+	pub stone: i64,
+	pub gold: i64,
+	pub created_at: DateTime<Utc>,
+}
+
+impl From<AllianceDonation> for DonationDto {
+	fn from(donation: AllianceDonation) -> Self {
+		Self {
+			id: donation.id,
+			player_id: donation.player_id,
+			food: donation.food,
+			wood: donation.wood,
+			stone: donation.stone,
+			gold: donation.gold,
+			created_at: donation.created_at,
+		}
+	}
+}
+
+/// Response for POST /alliances/{alliance_id}/treasury
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DonateResponse {
+	/// The treasury after the donation
+	pub treasury: TreasuryDto,
+	pub donation: DonationDto,
+}
+
+/// Response for GET /alliances/{alliance_id}/donations
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DonationListResponse {
+	/// Most recent donations, newest first
+	pub donations: Vec<DonationDto>,
+}
@@ -0,0 +1,43 @@
+//! Route definitions for the alliance API endpoints.
+
+use axum::Router;
+use axum::routing::{delete, get, post, put};
+
+use crate::controllers::game::alliances::handlers::*;
+use crate::domain::app_state::AppState;
+
+/// Returns a router with all alliance routes.
+///
+/// Routes:
+/// - `POST /alliances` - Found an alliance led by the player
+/// - `GET /alliances/me` - Get the player's alliance
+/// - `GET /alliances/invites` - Get the player's pending invites
+/// - `POST /alliances/leave` - Leave the player's alliance
+/// - `GET /alliances/{alliance_id}` - Get an alliance with its members
+/// - `POST /alliances/{alliance_id}/invites` - Invite a player to the alliance
+/// - `POST /alliances/{alliance_id}/join` - Accept an invite to the alliance
+/// - `DELETE /alliances/{alliance_id}/members/{player_id}` - Kick a member
+/// - `PUT /alliances/{alliance_id}/members/{player_id}/role` - Change the role of a member
+/// - `POST /alliances/{alliance_id}/treasury` - Donate resources to the treasury
+/// - `GET /alliances/{alliance_id}/donations` - Get the most recent donations
+pub fn alliances_routes() -> Router<AppState> {
+	Router::new().nest(
+		"/alliances",
+		Router::new()
+			.route("/", post(create_alliance))
+			.route("/me", get(get_my_alliance))
+			.route("/invites", get(get_invites))
+			.route("/leave", post(leave_alliance))
+			.nest(
+				"/{alliance_id}",
+				Router::new()
+					.route("/", get(get_alliance))
+					.route("/invites", post(invite_player))
+					.route("/join", post(join_alliance))
+					.route("/members/{player_id}", delete(kick_member))
+					.route("/members/{player_id}/role", put(set_member_role))
+					.route("/treasury", post(donate))
+					.route("/donations", get(get_donations)),
+			),
+	)
+}
@@ -1,40 +1,61 @@
 use std::collections::HashMap;
 
-use axum::extract::Path;
-use axum::http::StatusCode;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::IntoResponse;
 use axum::{Extension, Json, debug_handler};
 use axum_extra::json;
+use chrono::Utc;
 use tracing::{debug, info, instrument, trace};
 
 use crate::Result;
 use crate::controllers::game::buildings::models::{
-	BuildingDefinition, BuildingLevelInfo, ConstructBuildingRequest, GameBuilding,
-	LevelRequirement, ResourceCapacity, ResourceCosts, ResourceProduction,
+	BuildingDefinition, BuildingLevelInfo, BuildingRefundResponse, ConstructBuildingRequest,
+	ConstructionQueueEntryDto, ConstructionQueueResponse, GameBuilding, LevelRequirement,
+	QueueUpgradeRequest, ResourceCapacity, ResourceCosts, ResourceProduction,
 };
 use crate::db::building_requirements::get_construction_reqs;
-use crate::db::extractor::DatabaseConnection;
+use crate::db::extractor::{DatabaseConnection, SelectedSettlement};
 use crate::db::player_buildings::get_player_bld_counts_levels;
-use crate::db::{building_requirements, building_unit_types, buildings, player_buildings};
-use crate::domain::app_state::AppState;
+use crate::db::{DbConn, building_requirements, building_unit_types, buildings, player_buildings};
+use crate::domain::app_state::{AppEvents, AppModifierCache, AppQueue, AppState};
 use crate::domain::auth::AuthenticatedUser;
 use crate::domain::building::BuildingKey;
 use crate::domain::building::level::BuildingLevelKey;
+use crate::domain::error::ErrorBody;
+use crate::domain::events::GameEvent;
+use crate::domain::player::PlayerKey;
 use crate::domain::player::buildings::PlayerBuildingKey;
 use crate::domain::unit::UnitType;
-use crate::game::buildings::building_operations;
-use crate::game::buildings::requirement_operations::gen_avail_list;
-
-#[instrument(skip(conn, player))]
+use crate::game::buildings::requirement_operations::{BuildingAvailability, gen_avail_list};
+use crate::game::buildings::{building_operations, construction_operations};
+use crate::net::tagged_json;
+
+/// Returns the buildings of the selected settlement.
+#[utoipa::path(
+	get,
+	path = "/game/buildings",
+	tag = "buildings",
+	params(("x-settlement-id" = Option<Uuid>, Header, description = "Settlement to act on, the capital by default")),
+	responses(
+		(status = OK, description = "Buildings of the settlement", body = Vec<GameBuilding>),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
+#[instrument(skip(conn, player, settlement))]
 #[debug_handler(state = AppState)]
 pub async fn get_player_buildings(
 	DatabaseConnection(mut conn): DatabaseConnection,
 	player: Extension<AuthenticatedUser>,
+	settlement: SelectedSettlement,
 ) -> impl IntoResponse {
 	let player_key = player.id;
-	debug!("Getting buildings for player: {}", player_key);
+	debug!(
+		"Getting buildings for player {} in settlement {}",
+		player_key, settlement.id
+	);
 	let buildings =
-		player_buildings::get_game_buildings(&mut conn, &player_key).unwrap_or_default();
+		player_buildings::get_settlement_buildings(&mut conn, &settlement.id).unwrap_or_default();
 	trace!("Found {} buildings for player", buildings.len());
 	let body: Vec<GameBuilding> = buildings.into_iter().map(GameBuilding::from).collect();
 	info!(
@@ -45,13 +66,25 @@ pub async fn get_player_buildings(
 	json!(body)
 }
 
+/// Returns a building of the player.
+#[utoipa::path(
+	get,
+	path = "/game/buildings/{player_bld_key}",
+	tag = "buildings",
+	params(("player_bld_key" = Uuid, Path, description = "Building of the player")),
+	responses(
+		(status = OK, description = "The building", body = GameBuilding),
+		(status = NOT_FOUND, description = "Building not found", body = ErrorBody),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
 #[instrument(skip(conn, player))]
 #[debug_handler(state = AppState)]
 pub async fn get_player_building(
 	DatabaseConnection(mut conn): DatabaseConnection,
 	player_building_key: Path<PlayerBuildingKey>,
 	player: Extension<AuthenticatedUser>,
-) -> Result<impl IntoResponse, StatusCode> {
+) -> Result<impl IntoResponse> {
 	let player_key = player.id;
 	let building_key = player_building_key.0;
 	debug!(
@@ -59,13 +92,8 @@ pub async fn get_player_building(
 		building_key, player_key
 	);
 
-	let result = player_buildings::get_game_building(&mut conn, &player_key, &building_key);
-	if let Err(e) = &result {
-		debug!("Building not found: {}", e);
-		return Err(StatusCode::NOT_FOUND);
-	}
-
-	let building = result.unwrap();
+	player_buildings::get_owned(&mut conn, &player_key, &building_key)?;
+	let building = player_buildings::get_game_building(&mut conn, &player_key, &building_key)?;
 	trace!("Found building details: {:?}", building);
 
 	let game_bld = GameBuilding::from(building);
@@ -77,12 +105,23 @@ pub async fn get_player_building(
 	Ok(json!(game_bld))
 }
 
-/// Returns the full building catalog with availability metadata.
+/// Returns the full building catalog with availability metadata for the selected settlement.
+#[utoipa::path(
+	get,
+	path = "/game/buildings/available",
+	tag = "buildings",
+	params(("x-settlement-id" = Option<Uuid>, Header, description = "Settlement to act on, the capital by default")),
+	responses(
+		(status = OK, description = "Buildings of the faction and what keeps them from being built", body = Vec<BuildingAvailability>),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
 #[instrument(skip_all)]
 #[debug_handler(state = AppState)]
 pub async fn get_available_buildings(
 	DatabaseConnection(mut conn): DatabaseConnection,
 	player: Extension<AuthenticatedUser>,
+	settlement: SelectedSettlement,
 ) -> Result<impl IntoResponse> {
 	// Requirements
 	// - Player faction == building faction (or neutral)
@@ -93,7 +132,7 @@ pub async fn get_available_buildings(
 		"Getting available buildings for faction: {}",
 		&player.faction
 	);
-	let (blds, bld_data) = get_player_bld_counts_levels(&mut conn, &player)?;
+	let (blds, bld_data) = get_player_bld_counts_levels(&mut conn, &player, &settlement.id)?;
 	let reqs = get_construction_reqs(&mut conn, &player.faction)?;
 	let mut avail = gen_avail_list(blds, bld_data, reqs);
 	avail.sort_by_key(|a| a.building.id);
@@ -101,21 +140,37 @@ pub async fn get_available_buildings(
 	Ok(Json(avail))
 }
 
+/// Constructs a building in the selected settlement.
+#[utoipa::path(
+	post,
+	path = "/game/buildings/construct",
+	tag = "buildings",
+	params(("x-settlement-id" = Option<Uuid>, Header, description = "Settlement to act on, the capital by default")),
+	request_body = ConstructBuildingRequest,
+	responses(
+		(status = OK, description = "The building under construction", body = GameBuilding),
+		(status = BAD_REQUEST, description = "The building cannot be constructed", body = ErrorBody),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
 #[instrument(skip_all)]
 #[debug_handler(state = AppState)]
 pub async fn construct_player_building(
 	DatabaseConnection(mut conn): DatabaseConnection,
+	State(job_queue): State<AppQueue>,
 	player: Extension<AuthenticatedUser>,
+	settlement: SelectedSettlement,
 	Json(bld_req): Json<ConstructBuildingRequest>,
 ) -> Result<impl IntoResponse> {
 	let player_key = player.id;
 	let bld_key = bld_req.building_id;
 	debug!(
-		"Starting building {} construction for player {}",
-		bld_key, player_key
+		"Starting building {} construction for player {} in settlement {}",
+		bld_key, player_key, settlement.id
 	);
 
-	let bld = building_operations::construct_building(&mut conn, &player_key, &bld_key)?;
+	let bld =
+		building_operations::construct_building(&mut conn, &job_queue, &settlement, &bld_key)?;
 	trace!("Building construction details: {:?}", bld);
 
 	let res = player_buildings::get_game_building(&mut conn, &player_key, &bld.id)
@@ -129,10 +184,24 @@ pub async fn construct_player_building(
 	Ok(json!(res))
 }
 
-#[instrument(skip(conn, player))]
+/// Starts the upgrade of a building to its next level.
+#[utoipa::path(
+	post,
+	path = "/game/buildings/{player_bld_key}/upgrade",
+	tag = "buildings",
+	params(("player_bld_key" = Uuid, Path, description = "Building of the player")),
+	responses(
+		(status = OK, description = "The building being upgraded", body = GameBuilding),
+		(status = BAD_REQUEST, description = "The building cannot be upgraded", body = ErrorBody),
+		(status = NOT_FOUND, description = "Building not found", body = ErrorBody),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
+#[instrument(skip(conn, job_queue, player))]
 #[debug_handler(state = AppState)]
 pub async fn upgrade_building(
 	DatabaseConnection(mut conn): DatabaseConnection,
+	State(job_queue): State<AppQueue>,
 	player_bld_key: Path<PlayerBuildingKey>,
 	player: Extension<AuthenticatedUser>,
 ) -> Result<impl IntoResponse> {
@@ -143,11 +212,14 @@ pub async fn upgrade_building(
 		building_key, player_key
 	);
 
-	let bld = building_operations::upgrade_building(&mut conn, &building_key)?;
+	player_buildings::get_owned(&mut conn, &player_key, &building_key)?;
+	let bld = building_operations::upgrade_building(&mut conn, &job_queue, &building_key)?;
 	trace!("Building upgrade details: {:?}", bld);
 
-	let upgrade_time = bld.upgrade_finishes_at.unwrap_or_default();
-	debug!("Building upgrade will be ready at {}", upgrade_time);
+	debug!(
+		"Building upgrade will be ready at {:?}",
+		bld.upgrade_finishes_at
+	);
 
 	let res = player_buildings::get_game_building(&mut conn, &player_key, &bld.id)
 		.map(GameBuilding::from)?;
@@ -160,31 +232,217 @@ pub async fn upgrade_building(
 	Ok(json!(res))
 }
 
+/// Confirms a finished upgrade, raising the level of the building.
+#[utoipa::path(
+	post,
+	path = "/game/buildings/{player_bld_key}/upgrade/confirm",
+	tag = "buildings",
+	params(("player_bld_key" = Uuid, Path, description = "Building of the player")),
+	responses(
+		(status = OK, description = "The upgraded building", body = GameBuilding),
+		(status = BAD_REQUEST, description = "The upgrade is not finished yet", body = ErrorBody),
+		(status = NOT_FOUND, description = "Building not found", body = ErrorBody),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
 #[instrument(skip_all)]
 #[debug_handler(state = AppState)]
 pub async fn confirm_upgrade(
 	DatabaseConnection(mut conn): DatabaseConnection,
+	State(events): State<AppEvents>,
+	State(modifier_cache): State<AppModifierCache>,
 	player_bld_key: Path<PlayerBuildingKey>,
 	player: Extension<AuthenticatedUser>,
 ) -> Result<impl IntoResponse> {
 	let player_key = player.id;
 
-	building_operations::confirm_upgrade(&mut conn, &player_bld_key)?;
+	let before = player_buildings::get_owned(&mut conn, &player_key, &player_bld_key)?;
+	let bld = building_operations::confirm_upgrade(&mut conn, &player_bld_key)?;
+	// Upgrades already confirmed by their job were announced by the building processor
+	if bld.level != before.level {
+		modifier_cache.invalidate_user(player_key);
+		events.publish(GameEvent::UpgradeCompleted {
+			player_id: player_key,
+			player_building_id: bld.id,
+			level: bld.level,
+		});
+	}
 	let res = player_buildings::get_game_building(&mut conn, &player_key, &player_bld_key)
 		.map(GameBuilding::from)?;
 
 	Ok(json!(res))
 }
 
+/// Cancels the upgrade underway on a building, refunding part of its cost by the time left.
+#[utoipa::path(
+	post,
+	path = "/game/buildings/{player_bld_key}/cancel-upgrade",
+	tag = "buildings",
+	params(("player_bld_key" = Uuid, Path, description = "Building of the player")),
+	responses(
+		(status = OK, description = "The building with the refund", body = BuildingRefundResponse),
+		(status = BAD_REQUEST, description = "The building is not being upgraded", body = ErrorBody),
+		(status = NOT_FOUND, description = "Building not found", body = ErrorBody),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn cancel_upgrade(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player_bld_key: Path<PlayerBuildingKey>,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	let player_key = player.id;
+
+	player_buildings::get_owned(&mut conn, &player_key, &player_bld_key)?;
+	let (bld, refund) = building_operations::cancel_upgrade(&mut conn, &player_bld_key)?;
+	let building = player_buildings::get_game_building(&mut conn, &player_key, &bld.id)
+		.map(GameBuilding::from)?;
+
+	Ok(json!(BuildingRefundResponse {
+		building,
+		refunded: refund.into(),
+	}))
+}
+
+/// Lowers a building by one level, refunding part of what the level cost.
+#[utoipa::path(
+	post,
+	path = "/game/buildings/{player_bld_key}/downgrade",
+	tag = "buildings",
+	params(("player_bld_key" = Uuid, Path, description = "Building of the player")),
+	responses(
+		(status = OK, description = "The building with the refund", body = BuildingRefundResponse),
+		(status = BAD_REQUEST, description = "The building cannot be downgraded", body = ErrorBody),
+		(status = NOT_FOUND, description = "Building not found", body = ErrorBody),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn downgrade_building(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player_bld_key: Path<PlayerBuildingKey>,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	let player_key = player.id;
+
+	player_buildings::get_owned(&mut conn, &player_key, &player_bld_key)?;
+	let (bld, refund) = building_operations::downgrade_building(&mut conn, &player_bld_key)?;
+	let building = player_buildings::get_game_building(&mut conn, &player_key, &bld.id)
+		.map(GameBuilding::from)?;
+
+	Ok(json!(BuildingRefundResponse {
+		building,
+		refunded: refund.into(),
+	}))
+}
+
+/// GET /game/buildings/queue
+///
+/// Returns the player's construction queue with its capacity and the progress of each entry.
+#[utoipa::path(
+	get,
+	path = "/game/buildings/queue",
+	tag = "buildings",
+	responses(
+		(status = OK, description = "The construction queue", body = ConstructionQueueResponse),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_construction_queue(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	Ok(json!(load_construction_queue(&mut conn, &player.id)?))
+}
+
+/// Loads the construction queue of a player, see [`get_construction_queue`].
+pub(crate) fn load_construction_queue(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+) -> Result<ConstructionQueueResponse> {
+	let queue = construction_operations::get_queue(conn, player_key)?;
+	let now = Utc::now();
+	let entries: Vec<ConstructionQueueEntryDto> = queue
+		.entries
+		.into_iter()
+		.map(|entry| ConstructionQueueEntryDto::new(entry, now))
+		.collect();
+	trace!("Found {} construction queue entries", entries.len());
+
+	Ok(ConstructionQueueResponse {
+		capacity: queue.capacity,
+		free_slots: (queue.capacity - entries.len() as i64).max(0),
+		entries,
+	})
+}
+
+/// POST /game/buildings/queue
+///
+/// Queues the next upgrade of a building, to start once the upgrades ahead of it complete.
+#[utoipa::path(
+	post,
+	path = "/game/buildings/queue",
+	tag = "buildings",
+	request_body = QueueUpgradeRequest,
+	responses(
+		(status = CREATED, description = "The queued upgrade", body = ConstructionQueueEntryDto),
+		(status = BAD_REQUEST, description = "The upgrade cannot be queued", body = ErrorBody),
+		(status = NOT_FOUND, description = "Building not found", body = ErrorBody),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
+#[instrument(skip(conn, job_queue, player))]
+#[debug_handler(state = AppState)]
+pub async fn queue_upgrade(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(job_queue): State<AppQueue>,
+	player: Extension<AuthenticatedUser>,
+	Json(request): Json<QueueUpgradeRequest>,
+) -> Result<impl IntoResponse> {
+	let player_key = player.id;
+	let entry = construction_operations::queue_upgrade(
+		&mut conn,
+		&job_queue,
+		&player_key,
+		&request.player_building_id,
+	)?;
+	info!(
+		"Queued upgrade of building {} to level {} for player {}",
+		entry.player_building_id, entry.level, player_key
+	);
+
+	Ok((
+		StatusCode::CREATED,
+		Json(ConstructionQueueEntryDto::new(entry, Utc::now())),
+	))
+}
+
 /// Returns all building definitions for the player's faction with all levels.
 ///
 /// Includes resources, capacities, upgrade times & requirements, units available,
 /// and queue size (training capacity).
+#[utoipa::path(
+	get,
+	path = "/game/buildings/all",
+	tag = "buildings",
+	params(("If-None-Match" = Option<String>, Header, description = "ETag of the definitions the client holds")),
+	responses(
+		(status = OK, description = "Building definitions of the faction", body = Vec<BuildingDefinition>),
+		(status = NOT_MODIFIED, description = "The definitions the client holds are current"),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
 #[instrument(skip_all)]
 #[debug_handler(state = AppState)]
 pub async fn get_all_building_definitions(
 	DatabaseConnection(mut conn): DatabaseConnection,
 	player: Extension<AuthenticatedUser>,
+	headers: HeaderMap,
 ) -> Result<impl IntoResponse> {
 	debug!(
 		"Getting all building definitions for faction: {}",
@@ -280,5 +538,5 @@ pub async fn get_all_building_definitions(
 		&player.faction
 	);
 
-	Ok(json!(definitions))
+	tagged_json(&headers, definitions)
 }
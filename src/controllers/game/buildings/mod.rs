@@ -2,4 +2,6 @@ mod handlers;
 mod models;
 mod routes;
 
+pub(crate) use handlers::load_construction_queue;
+pub use models::*;
 pub use routes::*;
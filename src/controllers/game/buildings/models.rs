@@ -1,16 +1,25 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::db::player_buildings::FullBuilding;
+use crate::db::resources::ResourceDelta;
+use crate::domain::building::construction::{
+	ConstructionQueueEntry, ConstructionQueueKey, ConstructionStatus,
+};
 use crate::domain::factions::FactionKey;
 use crate::domain::player::PlayerKey;
 use crate::domain::player::buildings::PlayerBuildingKey;
+use crate::domain::settlement::SettlementKey;
 use crate::domain::unit::UnitType;
+use crate::game::buildings::{building_operations, construction_operations};
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, PartialEq)]
 pub struct GameBuilding {
 	pub id: PlayerBuildingKey,
 	pub player_id: PlayerKey,
+	pub settlement_id: SettlementKey,
 	pub building_id: i32,
 	pub level: i32,
 	pub max_level: i32,
@@ -20,14 +29,27 @@ pub struct GameBuilding {
 	pub req_wood: Option<i64>,
 	pub req_stone: Option<i64>,
 	pub req_gold: Option<i64>,
+	pub upgrade_finishes_at: Option<DateTime<Utc>>,
+	/// Whether a construction or upgrade is underway, including finished but unconfirmed ones
+	pub upgrading: bool,
+	/// Progress percentage (0.0 - 100.0) of the upgrade, measured on the server clock
+	pub upgrade_progress_percent: Option<f64>,
+	/// Seconds until the upgrade can be confirmed
+	pub seconds_remaining: Option<i64>,
 }
 
 impl From<FullBuilding> for GameBuilding {
 	fn from(value: FullBuilding) -> Self {
 		let (pb, bld, bl, br) = value;
+		let progress = building_operations::upgrade_progress(
+			pb.upgrade_finishes_at,
+			bl.upgrade_seconds,
+			Utc::now(),
+		);
 		GameBuilding {
 			id: pb.id,
 			player_id: pb.player_id,
+			settlement_id: pb.settlement_id,
 			building_id: bld.id,
 			level: pb.level,
 			max_level: bld.max_level,
@@ -37,17 +59,21 @@ impl From<FullBuilding> for GameBuilding {
 			req_wood: bl.req_wood,
 			req_stone: bl.req_stone,
 			req_gold: bl.req_gold,
+			upgrade_finishes_at: pb.upgrade_finishes_at,
+			upgrading: progress.is_some(),
+			upgrade_progress_percent: progress.map(|p| p.progress_percent),
+			seconds_remaining: progress.map(|p| p.seconds_remaining),
 		}
 	}
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ConstructBuildingRequest {
 	pub building_id: i32,
 }
 
 /// Full building definition with all levels, used by `/game/buildings/all`
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
 pub struct BuildingDefinition {
 	pub id: i32,
 	pub name: String,
@@ -60,7 +86,7 @@ pub struct BuildingDefinition {
 }
 
 /// Level-specific information including costs, production, capacity, and requirements
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
 pub struct BuildingLevelInfo {
 	pub level: i32,
 	pub upgrade_seconds: i64,
@@ -72,7 +98,7 @@ pub struct BuildingLevelInfo {
 }
 
 /// Resource costs required for construction or upgrade
-#[derive(Serialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, Default)]
 pub struct ResourceCosts {
 	pub food: i64,
 	pub wood: i64,
@@ -80,8 +106,82 @@ pub struct ResourceCosts {
 	pub gold: i64,
 }
 
+impl From<ResourceDelta> for ResourceCosts {
+	fn from((food, wood, stone, gold): ResourceDelta) -> Self {
+		Self {
+			food,
+			wood,
+			stone,
+			gold,
+		}
+	}
+}
+
+/// A building after its upgrade was cancelled or it was downgraded, with the refund
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+pub struct BuildingRefundResponse {
+	pub building: GameBuilding,
+	/// Resources refunded to the player
+	pub refunded: ResourceCosts,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QueueUpgradeRequest {
+	pub player_building_id: PlayerBuildingKey,
+}
+
+/// An upgrade in the construction queue, with its progress on the server clock
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "graphql",
+	derive(async_graphql::SimpleObject),
+	graphql(name = "ConstructionQueueEntry")
+)]
+pub struct ConstructionQueueEntryDto {
+	pub id: ConstructionQueueKey,
+	pub player_building_id: PlayerBuildingKey,
+	/// The level the building reaches once the entry completes
+	pub level: i32,
+	pub status: ConstructionStatus,
+	pub starts_at: DateTime<Utc>,
+	pub completes_at: DateTime<Utc>,
+	/// Progress percentage (0.0 - 100.0), 0 for entries waiting their turn
+	pub progress_percent: f64,
+	pub seconds_remaining: i64,
+}
+
+impl ConstructionQueueEntryDto {
+	pub fn new(entry: ConstructionQueueEntry, now: DateTime<Utc>) -> Self {
+		Self {
+			progress_percent: construction_operations::construction_progress(&entry, now) * 100.0,
+			seconds_remaining: (entry.completes_at - now).num_seconds().max(0),
+			id: entry.id,
+			player_building_id: entry.player_building_id,
+			level: entry.level,
+			status: entry.status,
+			starts_at: entry.starts_at,
+			completes_at: entry.completes_at,
+		}
+	}
+}
+
+/// A player's construction queue, used by `/game/buildings/queue`
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, PartialEq)]
+#[cfg_attr(
+	feature = "graphql",
+	derive(async_graphql::SimpleObject),
+	graphql(name = "ConstructionQueue")
+)]
+pub struct ConstructionQueueResponse {
+	/// Number of upgrades the player can queue, derived from their Keep level
+	pub capacity: i64,
+	pub free_slots: i64,
+	/// Active entries, in the order they complete
+	pub entries: Vec<ConstructionQueueEntryDto>,
+}
+
 /// Resource production rates per hour
-#[derive(Serialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, Default)]
 pub struct ResourceProduction {
 	pub population: i64,
 	pub food: i64,
@@ -91,7 +191,7 @@ pub struct ResourceProduction {
 }
 
 /// Resource storage and accumulator capacities
-#[derive(Serialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, Default)]
 pub struct ResourceCapacity {
 	pub food: i64,
 	pub wood: i64,
@@ -104,7 +204,7 @@ pub struct ResourceCapacity {
 }
 
 /// Prerequisite for upgrading to a specific building level
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
 pub struct LevelRequirement {
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub required_building_id: Option<i32>,
@@ -1,5 +1,6 @@
 use axum::Router;
 use axum::routing::{get, post};
+use utoipa::OpenApi;
 
 use crate::controllers::game::buildings::handlers::*;
 use crate::domain::app_state::AppState;
@@ -12,12 +13,32 @@ pub fn buildings_routes() -> Router<AppState> {
 			.route("/all", get(get_all_building_definitions))
 			.route("/available", get(get_available_buildings))
 			.route("/construct", post(construct_player_building))
+			.route("/queue", get(get_construction_queue).post(queue_upgrade))
 			.nest(
 				"/{player_bld_key}",
 				Router::new()
 					.route("/", get(get_player_building))
 					.route("/upgrade", post(upgrade_building))
-					.route("/upgrade/confirm", post(confirm_upgrade)),
+					.route("/upgrade/confirm", post(confirm_upgrade))
+					.route("/cancel-upgrade", post(cancel_upgrade))
+					.route("/downgrade", post(downgrade_building)),
 			),
 	)
 }
+
+/// OpenAPI document of the buildings routes.
+#[derive(OpenApi)]
+#[openapi(paths(
+	get_player_buildings,
+	get_all_building_definitions,
+	get_available_buildings,
+	construct_player_building,
+	get_construction_queue,
+	queue_upgrade,
+	get_player_building,
+	upgrade_building,
+	confirm_upgrade,
+	cancel_upgrade,
+	downgrade_building,
+))]
+pub struct BuildingsApi;
@@ -0,0 +1,119 @@
+//! Request handlers for the chat API endpoints.
+
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::{Extension, Json, debug_handler};
+use serde_json::json;
+use tracing::{debug, instrument};
+
+use crate::Result;
+use crate::configuration::Settings;
+use crate::controllers::game::chat::models::*;
+use crate::db::extractor::DatabaseConnection;
+use crate::domain::app_state::{AppChat, AppState};
+use crate::domain::auth::AuthenticatedUser;
+use crate::domain::chat::ChatChannel;
+use crate::domain::player::PlayerKey;
+use crate::game::audit_operations::{self, MUTE_PLAYER_ACTION, PlayerActor, UNMUTE_PLAYER_ACTION};
+use crate::game::chat::chat_operations;
+
+/// GET /game/chat/{channel}
+///
+/// Returns the most recent messages of the channel, newest first.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_history(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path(channel): Path<ChatChannel>,
+	Query(query): Query<ChatHistoryQuery>,
+) -> Result<impl IntoResponse> {
+	let lines =
+		chat_operations::history(&mut conn, &player.id, channel, query.before, query.limit)?;
+	debug!(
+		"Retrieved {} {} chat messages for player {}",
+		lines.len(),
+		channel,
+		player.id
+	);
+	Ok(Json(ChatHistoryResponse {
+		messages: lines.into_iter().map(ChatMessageDto::from).collect(),
+	}))
+}
+
+/// POST /game/chat/{channel}
+///
+/// Posts a message in the channel and pushes it to the connected players in it.
+#[instrument(skip(conn, settings, chat, player, request))]
+#[debug_handler(state = AppState)]
+pub async fn post_message(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	settings: Settings,
+	State(chat): State<AppChat>,
+	player: Extension<AuthenticatedUser>,
+	Path(channel): Path<ChatChannel>,
+	Json(request): Json<PostChatMessageRequest>,
+) -> Result<impl IntoResponse> {
+	let broadcast = chat_operations::post(
+		&mut conn,
+		&settings.chat,
+		&player.id,
+		channel,
+		&request.body,
+	)?;
+	let dto = ChatMessageDto::from(broadcast.line.clone());
+	chat.publish(broadcast);
+	Ok((StatusCode::CREATED, Json(dto)))
+}
+
+/// PUT /game/chat/mutes/{player_id}
+///
+/// Mutes a player in every channel, replacing the mute they already have.
+#[instrument(skip(conn, moderator, headers, request))]
+#[debug_handler(state = AppState)]
+pub async fn mute_player(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	moderator: Extension<AuthenticatedUser>,
+	headers: HeaderMap,
+	Path(player_id): Path<PlayerKey>,
+	Json(request): Json<MutePlayerRequest>,
+) -> Result<impl IntoResponse> {
+	let mute = chat_operations::mute(
+		&mut conn,
+		&moderator.id,
+		&player_id,
+		request.minutes,
+		request.reason,
+	)?;
+	audit_operations::record(
+		&mut conn,
+		&PlayerActor::new(moderator.id, &headers),
+		MUTE_PLAYER_ACTION,
+		Some(player_id.to_string()),
+		json!({ "muted_until": mute.muted_until, "reason": mute.reason }),
+	);
+	Ok(Json(ChatMuteDto::from(mute)))
+}
+
+/// DELETE /game/chat/mutes/{player_id}
+///
+/// Lifts the mute of a player.
+#[instrument(skip(conn, moderator, headers))]
+#[debug_handler(state = AppState)]
+pub async fn unmute_player(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	moderator: Extension<AuthenticatedUser>,
+	headers: HeaderMap,
+	Path(player_id): Path<PlayerKey>,
+) -> Result<impl IntoResponse> {
+	chat_operations::unmute(&mut conn, &moderator.id, &player_id)?;
+	audit_operations::record(
+		&mut conn,
+		&PlayerActor::new(moderator.id, &headers),
+		UNMUTE_PLAYER_ACTION,
+		Some(player_id.to_string()),
+		json!({}),
+	);
+	Ok(StatusCode::NO_CONTENT)
+}
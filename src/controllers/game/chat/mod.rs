@@ -0,0 +1,14 @@
+//! Chat controller module for the global and alliance channels.
+//!
+//! Provides REST API endpoints for:
+//! - Reading the history of a channel, and posting in it
+//! - Muting players and lifting their mutes, for moderators
+//!
+//! Connected players receive new messages over the WebSocket, see `net::ws`.
+
+mod handlers;
+mod models;
+mod routes;
+
+pub use models::*;
+pub use routes::*;
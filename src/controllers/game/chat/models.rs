@@ -0,0 +1,92 @@
+//! Request and response DTOs for the chat API endpoints.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::alliance::AllianceKey;
+use crate::domain::chat::{ChatChannel, ChatLine, ChatMessageKey, ChatMute};
+use crate::domain::player::PlayerKey;
+
+// === Request DTOs ===
+
+/// Query parameters for GET /chat/{channel}
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ChatHistoryQuery {
+	/// Only messages posted before this time, to page back through the history
+	pub before: Option<DateTime<Utc>>,
+	/// Messages to return, defaults to 50 and is capped at 100
+	pub limit: Option<i64>,
+}
+
+/// Request body for POST /chat/{channel}, and the frame clients send over the WebSocket
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PostChatMessageRequest {
+	/// 1 to 500 characters
+	pub body: String,
+}
+
+/// Request body for PUT /chat/mutes/{player_id}
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MutePlayerRequest {
+	/// How long the player is muted, up to 30 days
+	pub minutes: i64,
+	/// Shown to the muted player
+	pub reason: Option<String>,
+}
+
+// === Response DTOs ===
+
+/// A single chat message.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChatMessageDto {
+	pub id: ChatMessageKey,
+	pub channel: ChatChannel,
+	/// Alliance of the alliance channel, `null` for the global channel
+	pub alliance_id: Option<AllianceKey>,
+	/// Player who posted the message, `null` once they are gone
+	pub player_id: Option<PlayerKey>,
+	pub player_name: Option<String>,
+	pub body: String,
+	pub created_at: DateTime<Utc>,
+}
+
+impl From<ChatLine> for ChatMessageDto {
+	fn from(line: ChatLine) -> Self {
+		Self {
+			id: line.message.id,
+			channel: line.message.channel,
+			alliance_id: line.message.alliance_id,
+			player_id: line.message.player_id,
+			player_name: line.player_name,
+			body: line.message.body,
+			created_at: line.message.created_at,
+		}
+	}
+}
+
+/// Response for GET /chat/{channel}
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ChatHistoryResponse {
+	/// Messages, newest first
+	pub messages: Vec<ChatMessageDto>,
+}
+
+/// A player's mute.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ChatMuteDto {
+	pub player_id: PlayerKey,
+	pub muted_until: DateTime<Utc>,
+	pub reason: Option<String>,
+	pub muted_by: Option<PlayerKey>,
+}
+
+impl From<ChatMute> for ChatMuteDto {
+	fn from(mute: ChatMute) -> Self {
+		Self {
+			player_id: mute.player_id,
+			muted_until: mute.muted_until,
+			reason: mute.reason,
+			muted_by: mute.muted_by,
+		}
+	}
+}
@@ -0,0 +1,32 @@
+//! Route definitions for the chat API endpoints.
+
+use axum::routing::{get, put};
+use axum::{Router, middleware};
+
+use crate::controllers::game::chat::handlers::*;
+use crate::domain::app_state::AppState;
+use crate::domain::player::role::PlayerRole;
+use crate::net::require_role;
+
+/// Returns a router with all chat routes.
+///
+/// Routes:
+/// - `GET /chat/{channel}` - Get the history of the `global` or `alliance` channel
+/// - `POST /chat/{channel}` - Post a message in the channel
+/// - `PUT /chat/mutes/{player_id}` - Mute a player, for moderators
+/// - `DELETE /chat/mutes/{player_id}` - Lift the mute of a player, for moderators
+pub fn chat_routes() -> Router<AppState> {
+	let moderation_routes = Router::new()
+		.route("/mutes/{player_id}", put(mute_player).delete(unmute_player))
+		.route_layer(middleware::from_fn_with_state(
+			PlayerRole::Moderator,
+			require_role,
+		));
+
+	Router::new().nest(
+		"/chat",
+		Router::new()
+			.route("/{channel}", get(get_history).post(post_message))
+			.merge(moderation_routes),
+	)
+}
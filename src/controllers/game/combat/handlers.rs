@@ -0,0 +1,159 @@
+//! Request handlers for the combat API endpoints.
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json, debug_handler};
+use tracing::{debug, info, instrument};
+
+use crate::Result;
+use crate::configuration::Settings;
+use crate::controllers::game::combat::models::*;
+use crate::db::extractor::DatabaseConnection;
+use crate::domain::app_state::{AppQueue, AppState};
+use crate::domain::auth::AuthenticatedUser;
+use crate::domain::combat::{BattleReportKey, IntelReportKey};
+use crate::game::combat::{combat_operations, espionage_operations, protection_operations};
+
+/// GET /game/combat/reports
+///
+/// Returns a page of battle reports the player took part in, newest first.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_reports(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Query(query): Query<ReportListQuery>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!("Getting battle reports for player {}", player_id);
+
+	let page = combat_operations::list_reports(&mut conn, &player_id, query.page, query.per_page)?;
+
+	info!(
+		"Retrieved {} battle reports for player {}",
+		page.reports.len(),
+		player_id
+	);
+	Ok(Json(ReportListResponse::try_from(page)?))
+}
+
+/// GET /game/combat/reports/{report_id}
+///
+/// Returns a single battle report, if the player was the attacker or the defender.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_report(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path(report_id): Path<BattleReportKey>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!(
+		"Getting battle report {} for player {}",
+		report_id, player_id
+	);
+
+	let report = combat_operations::get_report(&mut conn, &player_id, &report_id)?;
+
+	info!(
+		"Retrieved battle report {} for player {}",
+		report_id, player_id
+	);
+	Ok(Json(BattleReportDto::try_from(report)?))
+}
+
+/// DELETE /game/combat/protection
+///
+/// Drops the player's beginner shield early, so they can attack and be attacked.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn drop_protection(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	protection_operations::opt_out(&mut conn, &player.id)?;
+	Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /game/combat/scout
+///
+/// Sends scouts to spy on another player. The scouts leave the army right away, and the
+/// intel report is written once they arrive.
+#[instrument(skip(conn, job_queue, settings, player))]
+#[debug_handler(state = AppState)]
+pub async fn send_scouts(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(job_queue): State<AppQueue>,
+	settings: Settings,
+	player: Extension<AuthenticatedUser>,
+	Json(request): Json<ScoutRequest>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!(
+		"Sending {} scouts from player {} to player {}",
+		request.scouts, player_id, request.defender_id
+	);
+
+	let mission = espionage_operations::send_scouts(
+		&mut conn,
+		&job_queue,
+		&settings.espionage,
+		&settings.protection,
+		&player_id,
+		&request.defender_id,
+		request.scouts,
+	)?;
+
+	info!("Player {} sent scout mission {}", player_id, mission.id);
+	Ok((StatusCode::CREATED, Json(ScoutMissionDto::from(mission))))
+}
+
+/// GET /game/combat/intel
+///
+/// Returns a page of the intel reports of the player's scout missions, newest first.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_intel_reports(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Query(query): Query<ReportListQuery>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!("Getting intel reports for player {}", player_id);
+
+	let page =
+		espionage_operations::list_reports(&mut conn, &player_id, query.page, query.per_page)?;
+
+	info!(
+		"Retrieved {} intel reports for player {}",
+		page.reports.len(),
+		player_id
+	);
+	Ok(Json(IntelReportListResponse::try_from(page)?))
+}
+
+/// GET /game/combat/intel/{report_id}
+///
+/// Returns a single intel report, if the player's scouts wrote it.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_intel_report(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path(report_id): Path<IntelReportKey>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!(
+		"Getting intel report {} for player {}",
+		report_id, player_id
+	);
+
+	let report = espionage_operations::get_report(&mut conn, &player_id, &report_id)?;
+
+	info!(
+		"Retrieved intel report {} for player {}",
+		report_id, player_id
+	);
+	Ok(Json(IntelReportDto::try_from(report)?))
+}
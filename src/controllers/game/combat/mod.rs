@@ -0,0 +1,14 @@
+//! Combat controller module for battle reports and beginner protection.
+//!
+//! Provides REST API endpoints for:
+//! - Listing the battle reports a player took part in, with pagination
+//! - Viewing a single battle report as attacker or defender
+//! - Dropping the beginner shield before it runs out
+//! - Sending scouts to other players and reading the intel reports they bring back
+
+mod handlers;
+mod models;
+mod routes;
+
+pub use models::*;
+pub use routes::*;
@@ -0,0 +1,188 @@
+//! Request and response DTOs for the combat API endpoints.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+use crate::domain::combat::{
+	AppliedModifier, BattleReport, BattleReportKey, IntelReport, IntelReportKey, Loot,
+	ScoutMission, ScoutMissionKey, UnitEstimate, UnitLoss,
+};
+use crate::domain::jobs::JobKey;
+use crate::domain::player::PlayerKey;
+use crate::game::combat::combat_operations::ReportPage;
+use crate::game::combat::espionage_operations::IntelReportPage;
+
+// === Request DTOs ===
+
+/// Query parameters for GET /combat/reports
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ReportListQuery {
+	/// 1-based page number, defaults to the first page
+	pub page: Option<i64>,
+	/// Reports per page, defaults to 20 and is capped at 100
+	pub per_page: Option<i64>,
+}
+
+/// Request body for POST /combat/scout
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ScoutRequest {
+	pub defender_id: PlayerKey,
+	/// Number of scouts to send
+	pub scouts: i64,
+}
+
+// === Response DTOs ===
+
+/// A single battle report.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BattleReportDto {
+	pub id: BattleReportKey,
+	pub attacker_id: PlayerKey,
+	pub defender_id: PlayerKey,
+	/// Winning player, or `null` for a draw
+	pub winner_id: Option<PlayerKey>,
+	pub attacker_losses: Vec<UnitLoss>,
+	pub defender_losses: Vec<UnitLoss>,
+	pub loot: Loot,
+	pub modifiers: Vec<AppliedModifier>,
+	pub fought_at: DateTime<Utc>,
+}
+
+impl TryFrom<BattleReport> for BattleReportDto {
+	type Error = crate::Error;
+
+	fn try_from(report: BattleReport) -> Result<Self> {
+		let loot = report.loot();
+		Ok(Self {
+			id: report.id,
+			attacker_id: report.attacker_id,
+			defender_id: report.defender_id,
+			winner_id: report.winner_id,
+			attacker_losses: serde_json::from_value(report.attacker_losses)?,
+			defender_losses: serde_json::from_value(report.defender_losses)?,
+			loot,
+			modifiers: serde_json::from_value(report.modifiers)?,
+			fought_at: report.fought_at,
+		})
+	}
+}
+
+/// Response for GET /combat/reports
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReportListResponse {
+	/// Reports on this page, newest first
+	pub reports: Vec<BattleReportDto>,
+	pub page: i64,
+	pub per_page: i64,
+	/// Total number of reports available to the player
+	pub total: i64,
+}
+
+impl TryFrom<ReportPage> for ReportListResponse {
+	type Error = crate::Error;
+
+	fn try_from(page: ReportPage) -> Result<Self> {
+		Ok(Self {
+			reports: page
+				.reports
+				.into_iter()
+				.map(BattleReportDto::try_from)
+				.collect::<Result<_>>()?,
+			page: page.page,
+			per_page: page.per_page,
+			total: page.total,
+		})
+	}
+}
+
+/// A scout mission on its way to its target.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ScoutMissionDto {
+	pub id: ScoutMissionKey,
+	pub attacker_id: PlayerKey,
+	pub defender_id: PlayerKey,
+	pub scouts: i64,
+	/// The job completing the mission, can be polled through the jobs API
+	pub job_id: Option<JobKey>,
+	/// The intel report, once the mission completed
+	pub report_id: Option<IntelReportKey>,
+	pub arrives_at: DateTime<Utc>,
+	pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl From<ScoutMission> for ScoutMissionDto {
+	fn from(mission: ScoutMission) -> Self {
+		Self {
+			id: mission.id,
+			attacker_id: mission.attacker_id,
+			defender_id: mission.defender_id,
+			scouts: mission.scouts,
+			job_id: mission.job_id,
+			report_id: mission.report_id,
+			arrives_at: mission.arrives_at,
+			completed_at: mission.completed_at,
+		}
+	}
+}
+
+/// A single intel report.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IntelReportDto {
+	pub id: IntelReportKey,
+	pub defender_id: PlayerKey,
+	/// Resources in the storage of the defender's capital
+	pub resources: Loot,
+	/// Estimated army of the defender
+	pub army: Vec<UnitEstimate>,
+	/// How far off each estimate may be, in percent
+	pub noise_percent: i32,
+	/// Whether the defender's Watchtower spotted the scouts
+	pub spotted: bool,
+	pub scouted_at: DateTime<Utc>,
+}
+
+impl TryFrom<IntelReport> for IntelReportDto {
+	type Error = crate::Error;
+
+	fn try_from(report: IntelReport) -> Result<Self> {
+		let resources = report.resources();
+		Ok(Self {
+			id: report.id,
+			defender_id: report.defender_id,
+			resources,
+			army: serde_json::from_value(report.army)?,
+			noise_percent: report.noise_percent,
+			spotted: report.spotted,
+			scouted_at: report.scouted_at,
+		})
+	}
+}
+
+/// Response for GET /combat/intel
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IntelReportListResponse {
+	/// Reports on this page, newest first
+	pub reports: Vec<IntelReportDto>,
+	pub page: i64,
+	pub per_page: i64,
+	/// Total number of reports available to the player
+	pub total: i64,
+}
+
+impl TryFrom<IntelReportPage> for IntelReportListResponse {
+	type Error = crate::Error;
+
+	fn try_from(page: IntelReportPage) -> Result<Self> {
+		Ok(Self {
+			reports: page
+				.reports
+				.into_iter()
+				.map(IntelReportDto::try_from)
+				.collect::<Result<_>>()?,
+			page: page.page,
+			per_page: page.per_page,
+			total: page.total,
+		})
+	}
+}
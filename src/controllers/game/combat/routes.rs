@@ -0,0 +1,29 @@
+//! Route definitions for the combat API endpoints.
+
+use axum::Router;
+use axum::routing::{delete, get, post};
+
+use crate::controllers::game::combat::handlers::*;
+use crate::domain::app_state::AppState;
+
+/// Returns a router with all combat routes.
+///
+/// Routes:
+/// - `GET /combat/reports` - Get a page of the player's battle reports
+/// - `GET /combat/reports/{report_id}` - Get a single battle report
+/// - `DELETE /combat/protection` - Drop the beginner shield early
+/// - `POST /combat/scout` - Send scouts to spy on another player
+/// - `GET /combat/intel` - Get a page of the player's intel reports
+/// - `GET /combat/intel/{report_id}` - Get a single intel report
+pub fn combat_routes() -> Router<AppState> {
+	Router::new().nest(
+		"/combat",
+		Router::new()
+			.route("/reports", get(get_reports))
+			.route("/reports/{report_id}", get(get_report))
+			.route("/protection", delete(drop_protection))
+			.route("/scout", post(send_scouts))
+			.route("/intel", get(get_intel_reports))
+			.route("/intel/{report_id}", get(get_intel_report)),
+	)
+}
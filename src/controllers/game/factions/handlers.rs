@@ -2,26 +2,32 @@ use std::collections::HashMap;
 use std::str::FromStr;
 
 use axum::extract::Path;
-use axum::http::StatusCode;
+use axum::http::HeaderMap;
 use axum::response::IntoResponse;
 use axum::{Json, debug_handler};
 use tracing::{debug, info, instrument};
 
-use crate::Result;
 use crate::controllers::game::factions::FactionBonus;
-use crate::controllers::game::factions::models::{FactionDetails, FactionResponse};
+use crate::controllers::game::factions::models::{
+	FactionBonusesResponse, FactionDetails, FactionModifierDetail, FactionResponse,
+	FactionStandingEntry, FactionStandingsResponse,
+};
 use crate::db::extractor::DatabaseConnection;
-use crate::db::factions;
+use crate::db::{DbConn, factions};
 use crate::domain::app_state::AppState;
-use crate::domain::factions::{FactionCode, FactionKey};
+use crate::domain::factions::{Faction, FactionCode, FactionKey};
+use crate::game::factions::standing_operations;
+use crate::net::tagged_json;
+use crate::{Error, ErrorKind, Result};
 
 /// GET `/game/factions`
-/// List all available factions with their bonuses
-#[instrument(skip(conn))]
+/// List all available factions with their bonuses, tagged for conditional requests
+#[instrument(skip(conn, headers))]
 #[debug_handler(state = AppState)]
 pub(super) async fn get_factions(
 	DatabaseConnection(mut conn): DatabaseConnection,
-) -> impl IntoResponse {
+	headers: HeaderMap,
+) -> Result<impl IntoResponse> {
 	debug!("Getting all available factions");
 	let map: HashMap<FactionKey, Vec<FactionBonus>> = HashMap::new();
 	let faction_bonuses = factions::get_bonuses(&mut conn, None)
@@ -63,7 +69,7 @@ pub(super) async fn get_factions(
 		})
 		.collect();
 	info!("Retrieved factions list with {} items", factions.len());
-	Json(factions)
+	tagged_json(&headers, factions)
 }
 
 /// GET `/game/factions/{faction_id}`
@@ -73,11 +79,9 @@ pub(super) async fn get_factions(
 pub(super) async fn get_faction(
 	Path(faction_id): Path<FactionKey>,
 	DatabaseConnection(mut conn): DatabaseConnection,
-) -> Result<impl IntoResponse, StatusCode> {
+) -> Result<impl IntoResponse> {
 	debug!("Getting faction details");
-	let mut faction = factions::get_by_id(&mut conn, &faction_id)
-		.map(FactionDetails::from)
-		.map_err(|_| StatusCode::NOT_FOUND)?;
+	let mut faction = find_faction(&mut conn, &faction_id).map(FactionDetails::from)?;
 	let mut bonuses = factions::get_bonuses(&mut conn, Some(&faction_id))
 		.map(|mods| mods.into_iter().map(FactionBonus::from).collect())
 		.unwrap_or_default();
@@ -85,3 +89,55 @@ pub(super) async fn get_faction(
 	info!("Retrieved faction details");
 	Ok(Json(faction))
 }
+
+/// GET `/game/factions/{faction_id}/bonuses`
+/// List the modifiers a faction grants, with effect summaries generated from the modifier data
+#[instrument(skip(conn))]
+#[debug_handler(state = AppState)]
+pub(super) async fn get_faction_bonuses(
+	Path(faction_id): Path<FactionKey>,
+	DatabaseConnection(mut conn): DatabaseConnection,
+) -> Result<impl IntoResponse> {
+	debug!("Getting faction bonuses");
+	let faction = find_faction(&mut conn, &faction_id)?;
+	let mut bonuses: Vec<FactionModifierDetail> =
+		factions::get_bonuses(&mut conn, Some(&faction.id))?
+			.into_iter()
+			.map(FactionModifierDetail::from)
+			.collect();
+	bonuses.sort_by(|a, b| a.name.cmp(&b.name));
+	info!("Retrieved {} faction bonuses", bonuses.len());
+	Ok(Json(FactionBonusesResponse {
+		faction: faction.id,
+		bonuses,
+	}))
+}
+
+/// GET `/game/factions/standings`
+/// The standings of the factions in the last aggregation, leading faction first, tagged for
+/// conditional requests
+#[instrument(skip(conn, headers))]
+#[debug_handler(state = AppState)]
+pub(super) async fn get_faction_standings(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	headers: HeaderMap,
+) -> Result<impl IntoResponse> {
+	debug!("Getting faction standings");
+	let standings = standing_operations::get_standings(&mut conn)?;
+	let computed_at = standings.first().map(|standing| standing.computed_at);
+	info!("Retrieved {} faction standings", standings.len());
+	let body = FactionStandingsResponse {
+		computed_at,
+		standings: standings
+			.into_iter()
+			.map(FactionStandingEntry::from)
+			.collect(),
+	};
+	tagged_json(&headers, body)
+}
+
+/// Looks up a faction, unknown factions are not found.
+fn find_faction(conn: &mut DbConn, faction_id: &FactionKey) -> Result<Faction> {
+	factions::get_by_id(conn, faction_id)
+		.map_err(|_| Error::from((ErrorKind::NotFoundError, "Faction not found")))
+}
@@ -8,10 +8,11 @@
 use std::default::Default;
 
 use bigdecimal::ToPrimitive;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::domain::factions::{Faction, FactionKey};
-use crate::domain::modifier::{MagnitudeKind, Modifier, ModifierTarget};
+use crate::domain::factions::{Faction, FactionKey, FactionStanding};
+use crate::domain::modifier::{MagnitudeKind, Modifier, ModifierTarget, StackingBehaviour};
 use crate::domain::player::resource::ResourceType;
 
 /// A specific bonus or advantage provided by a faction.
@@ -46,6 +47,55 @@ impl From<Modifier> for FactionBonus {
 	}
 }
 
+/// A faction modifier as the server applies it, for the faction bonus documentation.
+///
+/// Unlike [`FactionBonus`], this carries the stacking rules and an `effect` summary
+/// generated from the modifier's magnitude and target, so clients can show exactly what
+/// the server calculates with.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FactionModifierDetail {
+	/// Internal modifier name (e.g., "human_wood_production")
+	pub name: String,
+	/// What the modifier affects
+	pub target: ModifierTarget,
+	/// Specific resource type this modifier affects, if applicable
+	pub target_resource: Option<ResourceType>,
+	/// The numerical value of the modifier
+	pub value: f64,
+	/// How the value is applied
+	pub scaling: MagnitudeKind,
+	/// How this modifier combines with others
+	pub stacking: StackingBehaviour,
+	/// Generated summary of the effect (e.g., "+15% wood production")
+	pub effect: String,
+	/// Free-form description from the modifier definition
+	pub description: String,
+}
+
+impl From<Modifier> for FactionModifierDetail {
+	fn from(modifier: Modifier) -> Self {
+		Self {
+			effect: modifier.effect_summary(),
+			name: modifier.name,
+			target: modifier.target_type,
+			target_resource: modifier.target_resource,
+			value: modifier.magnitude.to_f64().unwrap_or_default(),
+			scaling: modifier.magnitude_kind,
+			stacking: modifier.stacking_behaviour,
+			description: modifier.description,
+		}
+	}
+}
+
+/// Response for GET `/game/factions/{faction_id}/bonuses`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FactionBonusesResponse {
+	/// The faction the bonuses belong to
+	pub faction: FactionKey,
+	/// All modifiers granted by the faction
+	pub bonuses: Vec<FactionModifierDetail>,
+}
+
 /// Basic faction information for API responses.
 ///
 /// This struct is used when returning faction data in API responses
@@ -111,3 +161,41 @@ impl From<Faction> for FactionDetails {
 		}
 	}
 }
+
+/// The standing of a faction in the last aggregation
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FactionStandingEntry {
+	pub faction: FactionKey,
+	/// 1-based rank by war score and then power
+	pub rank: i32,
+	/// Members that did not leave the game
+	pub players: i64,
+	/// Combined army power of the members
+	pub power: i64,
+	/// Battles won against other factions during the last cycle
+	pub war_score: i64,
+	/// Whether the members hold the war victory bonuses until the next aggregation
+	pub leading: bool,
+}
+
+impl From<FactionStanding> for FactionStandingEntry {
+	fn from(standing: FactionStanding) -> Self {
+		Self {
+			leading: standing.rank == 1 && standing.war_score > 0,
+			faction: standing.faction,
+			rank: standing.rank,
+			players: standing.players,
+			power: standing.power,
+			war_score: standing.war_score,
+		}
+	}
+}
+
+/// Response for GET `/game/factions/standings`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FactionStandingsResponse {
+	/// When the standings were aggregated, `None` before the first aggregation
+	pub computed_at: Option<DateTime<Utc>>,
+	/// Every playable faction, leading faction first
+	pub standings: Vec<FactionStandingEntry>,
+}
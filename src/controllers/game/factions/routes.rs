@@ -9,6 +9,8 @@ pub fn factions_routes() -> Router<AppState> {
 		"/factions",
 		Router::new()
 			.route("/", get(get_factions))
-			.route("/{faction_id}", get(get_faction)),
+			.route("/standings", get(get_faction_standings))
+			.route("/{faction_id}", get(get_faction))
+			.route("/{faction_id}/bonuses", get(get_faction_bonuses)),
 	)
 }
@@ -0,0 +1,105 @@
+//! Request handlers for the friends API endpoints.
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json, debug_handler};
+use tracing::{debug, instrument};
+
+use crate::Result;
+use crate::controllers::game::friends::models::*;
+use crate::db::extractor::DatabaseConnection;
+use crate::domain::app_state::{AppMetrics, AppState};
+use crate::domain::auth::AuthenticatedUser;
+use crate::domain::player::PlayerKey;
+use crate::game::friends::friend_operations;
+
+/// GET /game/friends
+///
+/// Lists the player's friends with their online status, their friend requests and the
+/// players they blocked.
+#[instrument(skip(conn, metrics, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_friends(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(metrics): State<AppMetrics>,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	let list = friend_operations::list(&mut conn, &metrics, &player.id)?;
+	debug!("Player {} has {} friends", player.id, list.friends.len());
+	Ok(Json(FriendListResponse::from(list)))
+}
+
+/// POST /game/friends
+///
+/// Asks a player to be friends, or accepts their request if they asked first.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn request_friend(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Json(request): Json<FriendRequest>,
+) -> Result<impl IntoResponse> {
+	let relationship =
+		friend_operations::request_friend(&mut conn, &player.id, &request.player_id)?;
+	Ok((
+		StatusCode::CREATED,
+		Json(RelationshipDto::from(relationship)),
+	))
+}
+
+/// POST /game/friends/{player_id}/accept
+///
+/// Accepts the friend request of a player.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn accept_friend(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path(requester_id): Path<PlayerKey>,
+) -> Result<impl IntoResponse> {
+	let friendship = friend_operations::accept_friend(&mut conn, &player.id, &requester_id)?;
+	Ok(Json(RelationshipDto::from(friendship)))
+}
+
+/// DELETE /game/friends/{player_id}
+///
+/// Ends a friendship, or withdraws or declines a friend request.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn remove_friend(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path(other_id): Path<PlayerKey>,
+) -> Result<impl IntoResponse> {
+	friend_operations::remove_friend(&mut conn, &player.id, &other_id)?;
+	Ok(StatusCode::NO_CONTENT)
+}
+
+/// PUT /game/blocks/{player_id}
+///
+/// Blocks a player, ending the friendship or friend requests between the two.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn block_player(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path(other_id): Path<PlayerKey>,
+) -> Result<impl IntoResponse> {
+	let block = friend_operations::block(&mut conn, &player.id, &other_id)?;
+	Ok(Json(RelationshipDto::from(block)))
+}
+
+/// DELETE /game/blocks/{player_id}
+///
+/// Lifts the block the player put on another player.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn unblock_player(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path(other_id): Path<PlayerKey>,
+) -> Result<impl IntoResponse> {
+	friend_operations::unblock(&mut conn, &player.id, &other_id)?;
+	Ok(StatusCode::NO_CONTENT)
+}
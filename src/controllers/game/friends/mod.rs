@@ -0,0 +1,13 @@
+//! Friends controller module for friend requests, friendships and blocks.
+//!
+//! Provides REST API endpoints for:
+//! - Listing friends with their online status, friend requests and blocked players
+//! - Sending, accepting, declining and withdrawing friend requests, and ending friendships
+//! - Blocking players and lifting the blocks
+
+mod handlers;
+mod models;
+mod routes;
+
+pub use models::*;
+pub use routes::*;
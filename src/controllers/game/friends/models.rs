@@ -0,0 +1,106 @@
+//! Request and response DTOs for the friends API endpoints.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::player::PlayerKey;
+use crate::domain::player::relationship::{
+	PlayerRelationship, PlayerRelationshipKey, RelationshipStatus,
+};
+use crate::game::friends::friend_operations::{Friend, FriendList, RelatedPlayer};
+
+// === Request DTOs ===
+
+/// Request body for POST /friends
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FriendRequest {
+	pub player_id: PlayerKey,
+}
+
+// === Response DTOs ===
+
+/// A player on the other end of a relationship.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RelatedPlayerDto {
+	pub player_id: PlayerKey,
+	pub name: String,
+	pub since: DateTime<Utc>,
+}
+
+impl From<RelatedPlayer> for RelatedPlayerDto {
+	fn from(player: RelatedPlayer) -> Self {
+		Self {
+			player_id: player.player_id,
+			name: player.name,
+			since: player.since,
+		}
+	}
+}
+
+/// A friend with their online status.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FriendDto {
+	pub player_id: PlayerKey,
+	pub name: String,
+	/// When the friendship began
+	pub since: DateTime<Utc>,
+	/// `null` if the friend hides their online status
+	pub online: Option<bool>,
+}
+
+impl From<Friend> for FriendDto {
+	fn from(friend: Friend) -> Self {
+		Self {
+			player_id: friend.player.player_id,
+			name: friend.player.name,
+			since: friend.player.since,
+			online: friend.online,
+		}
+	}
+}
+
+/// Response for GET /friends
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FriendListResponse {
+	pub friends: Vec<FriendDto>,
+	/// Friend requests waiting for the player's answer
+	pub incoming: Vec<RelatedPlayerDto>,
+	/// Friend requests the player sent
+	pub outgoing: Vec<RelatedPlayerDto>,
+	pub blocked: Vec<RelatedPlayerDto>,
+}
+
+impl From<FriendList> for FriendListResponse {
+	fn from(list: FriendList) -> Self {
+		let players = |players: Vec<RelatedPlayer>| players.into_iter().map(Into::into).collect();
+		Self {
+			friends: list.friends.into_iter().map(FriendDto::from).collect(),
+			incoming: players(list.incoming),
+			outgoing: players(list.outgoing),
+			blocked: players(list.blocked),
+		}
+	}
+}
+
+/// A friend request, friendship or block.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RelationshipDto {
+	pub id: PlayerRelationshipKey,
+	/// Player who sent the request or put the block on
+	pub player_id: PlayerKey,
+	pub other_id: PlayerKey,
+	pub status: RelationshipStatus,
+	pub updated_at: DateTime<Utc>,
+}
+
+impl From<PlayerRelationship> for RelationshipDto {
+	fn from(relationship: PlayerRelationship) -> Self {
+		Self {
+			id: relationship.id,
+			player_id: relationship.player_id,
+			other_id: relationship.other_id,
+			status: relationship.status,
+			updated_at: relationship.updated_at,
+		}
+	}
+}
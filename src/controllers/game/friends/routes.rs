@@ -0,0 +1,31 @@
+//! Route definitions for the friends API endpoints.
+
+use axum::Router;
+use axum::routing::{delete, get, post, put};
+
+use crate::controllers::game::friends::handlers::*;
+use crate::domain::app_state::AppState;
+
+/// Returns a router with all friends routes.
+///
+/// Routes:
+/// - `GET /friends` - List friends, friend requests and blocked players
+/// - `POST /friends` - Ask a player to be friends
+/// - `POST /friends/{player_id}/accept` - Accept the friend request of a player
+/// - `DELETE /friends/{player_id}` - End a friendship, or withdraw or decline a request
+/// - `PUT /blocks/{player_id}` - Block a player
+/// - `DELETE /blocks/{player_id}` - Lift the block on a player
+pub fn friends_routes() -> Router<AppState> {
+	Router::new()
+		.nest(
+			"/friends",
+			Router::new()
+				.route("/", get(get_friends).post(request_friend))
+				.route("/{player_id}", delete(remove_friend))
+				.route("/{player_id}/accept", post(accept_friend)),
+		)
+		.route(
+			"/blocks/{player_id}",
+			put(block_player).delete(unblock_player),
+		)
+}
@@ -0,0 +1,119 @@
+//! Request handlers for the heroes API endpoints.
+
+use axum::extract::{Path, State};
+use axum::response::IntoResponse;
+use axum::{Extension, Json, debug_handler};
+use tracing::{debug, info, instrument};
+
+use crate::Result;
+use crate::controllers::game::heroes::models::*;
+use crate::db::extractor::DatabaseConnection;
+use crate::domain::app_state::AppState;
+use crate::domain::auth::AuthenticatedUser;
+use crate::domain::hero::HeroKey;
+use crate::game::heroes::hero_operations::{self, Assignment};
+use crate::game::modifiers::modifier_service::ModifierService;
+
+/// GET /game/heroes
+///
+/// Returns every hero sorted by name, with the player's progress on the ones they recruited.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_heroes(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!("Getting heroes for player {}", player_id);
+
+	let heroes = hero_operations::list_heroes(&mut conn, &player_id)?;
+
+	info!("Retrieved {} heroes for player {}", heroes.len(), player_id);
+	Ok(Json(HeroesResponse {
+		heroes: heroes.into_iter().map(HeroDto::from).collect(),
+	}))
+}
+
+/// POST /game/heroes/{hero_id}/recruit
+///
+/// Recruits a hero for their gold.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn recruit_hero(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path(hero_id): Path<HeroKey>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!("Recruiting hero {} for player {}", hero_id, player_id);
+
+	let recruited = hero_operations::recruit_hero(&mut conn, &player_id, &hero_id)?;
+
+	Ok(Json(HeroDto::from(recruited)))
+}
+
+/// POST /game/heroes/{hero_id}/train
+///
+/// Trains a recruited hero for gold, gaining levels on the way.
+#[instrument(skip(conn, service, player))]
+#[debug_handler(state = AppState)]
+pub async fn train_hero(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(service): State<ModifierService>,
+	player: Extension<AuthenticatedUser>,
+	Path(hero_id): Path<HeroKey>,
+	Json(request): Json<TrainHeroRequest>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!("Training hero {} for player {}", hero_id, player_id);
+
+	let trained =
+		hero_operations::train_hero(&mut conn, &service, &player_id, &hero_id, request.xp).await?;
+
+	Ok(Json(TrainHeroResponse::from(trained)))
+}
+
+/// PUT /game/heroes/{hero_id}/assignment
+///
+/// Assigns a recruited hero to lead the army or run a building, where they apply the modifier
+/// of their level.
+#[instrument(skip(conn, service, player))]
+#[debug_handler(state = AppState)]
+pub async fn assign_hero(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(service): State<ModifierService>,
+	player: Extension<AuthenticatedUser>,
+	Path(hero_id): Path<HeroKey>,
+	Json(request): Json<AssignHeroRequest>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!("Assigning hero {} for player {}", hero_id, player_id);
+
+	let assignment = request
+		.player_building_id
+		.map_or(Assignment::Army, Assignment::Building);
+	let assigned =
+		hero_operations::assign_hero(&mut conn, &service, &player_id, &hero_id, assignment).await?;
+
+	Ok(Json(AssignHeroResponse::from(assigned)))
+}
+
+/// DELETE /game/heroes/{hero_id}/assignment
+///
+/// Unassigns a hero, removing the modifier they apply.
+#[instrument(skip(conn, service, player))]
+#[debug_handler(state = AppState)]
+pub async fn unassign_hero(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(service): State<ModifierService>,
+	player: Extension<AuthenticatedUser>,
+	Path(hero_id): Path<HeroKey>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!("Unassigning hero {} for player {}", hero_id, player_id);
+
+	let unassigned =
+		hero_operations::unassign_hero(&mut conn, &service, &player_id, &hero_id).await?;
+
+	Ok(Json(HeroDto::from(unassigned)))
+}
@@ -0,0 +1,13 @@
+//! Heroes controller module for the heroes players recruit.
+//!
+//! Provides REST API endpoints for:
+//! - Listing the heroes, with the player's progress on the ones they recruited
+//! - Recruiting and training heroes
+//! - Assigning heroes to lead the army or run a building, and unassigning them
+
+mod handlers;
+mod models;
+mod routes;
+
+pub use models::*;
+pub use routes::*;
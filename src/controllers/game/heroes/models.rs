@@ -0,0 +1,118 @@
+//! Request and response DTOs for the heroes API endpoints.
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::hero::{HeroKey, HeroSpecialty};
+use crate::domain::modifier::active_modifier::ActiveModifierKey;
+use crate::domain::player::buildings::PlayerBuildingKey;
+use crate::game::heroes::hero_operations::{AssignedHero, HeroStatus, TrainedHero};
+
+// === Request DTOs ===
+
+/// Request body for POST /heroes/{hero_id}/train
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TrainHeroRequest {
+	/// Experience to train for, paid for in gold
+	pub xp: i64,
+}
+
+/// Request body for PUT /heroes/{hero_id}/assignment
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct AssignHeroRequest {
+	/// Building for a production hero to run, left out for an army hero to lead the army
+	pub player_building_id: Option<PlayerBuildingKey>,
+}
+
+// === Response DTOs ===
+
+/// The progress of a hero the player recruited.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RecruitedHeroDto {
+	pub level: i32,
+	pub xp: i64,
+	/// Experience needed in total for the next level, `None` at the highest level
+	pub next_level_xp: Option<i64>,
+	/// Building the hero runs, if assigned to one
+	pub player_building_id: Option<PlayerBuildingKey>,
+	pub leads_army: bool,
+}
+
+/// A hero, with the player's progress if they recruited them.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HeroDto {
+	pub id: HeroKey,
+	pub name: String,
+	pub description: String,
+	pub specialty: HeroSpecialty,
+	pub recruit_gold: i64,
+	/// Human-readable effect of the hero at their level, e.g. `+10% wood production`
+	pub effect: String,
+	/// `None` until the player recruits the hero
+	pub recruited: Option<RecruitedHeroDto>,
+}
+
+impl From<HeroStatus> for HeroDto {
+	fn from(status: HeroStatus) -> Self {
+		let next_level_xp = status.next_level.map(|level| level.xp_required);
+		Self {
+			id: status.hero.id,
+			name: status.hero.name,
+			description: status.hero.description,
+			specialty: status.hero.specialty,
+			recruit_gold: status.hero.recruit_gold,
+			effect: status.modifier.effect_summary(),
+			recruited: status.recruited.map(|recruited| RecruitedHeroDto {
+				level: recruited.level,
+				xp: recruited.xp,
+				next_level_xp,
+				player_building_id: recruited.player_building_id,
+				leads_army: recruited.leads_army,
+			}),
+		}
+	}
+}
+
+/// Response for GET /heroes
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HeroesResponse {
+	pub heroes: Vec<HeroDto>,
+}
+
+/// Response for POST /heroes/{hero_id}/train
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TrainHeroResponse {
+	pub hero: HeroDto,
+	/// Experience gained, less than asked for once the hero reached the highest level
+	pub xp_gained: i64,
+	pub gold_spent: i64,
+	pub levels_gained: i32,
+}
+
+impl From<TrainedHero> for TrainHeroResponse {
+	fn from(trained: TrainedHero) -> Self {
+		Self {
+			hero: HeroDto::from(trained.status),
+			xp_gained: trained.xp_gained,
+			gold_spent: trained.gold_spent,
+			levels_gained: trained.levels_gained,
+		}
+	}
+}
+
+/// Response for PUT /heroes/{hero_id}/assignment
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AssignHeroResponse {
+	pub hero: HeroDto,
+	/// The modifier the hero applies while assigned
+	pub active_modifier_id: ActiveModifierKey,
+}
+
+impl From<AssignedHero> for AssignHeroResponse {
+	fn from(assigned: AssignedHero) -> Self {
+		Self {
+			hero: HeroDto::from(assigned.status),
+			active_modifier_id: assigned.active.id,
+		}
+	}
+}
@@ -0,0 +1,29 @@
+//! Route definitions for the heroes API endpoints.
+
+use axum::Router;
+use axum::routing::{get, post, put};
+
+use crate::controllers::game::heroes::handlers::*;
+use crate::domain::app_state::AppState;
+
+/// Returns a router with all hero routes.
+///
+/// Routes:
+/// - `GET /heroes` - Get the heroes along with the player's progress
+/// - `POST /heroes/{hero_id}/recruit` - Recruit a hero for gold
+/// - `POST /heroes/{hero_id}/train` - Train a recruited hero for gold
+/// - `PUT /heroes/{hero_id}/assignment` - Assign a hero to the army or a building
+/// - `DELETE /heroes/{hero_id}/assignment` - Unassign a hero
+pub fn heroes_routes() -> Router<AppState> {
+	Router::new().nest(
+		"/heroes",
+		Router::new()
+			.route("/", get(get_heroes))
+			.route("/{hero_id}/recruit", post(recruit_hero))
+			.route("/{hero_id}/train", post(train_hero))
+			.route(
+				"/{hero_id}/assignment",
+				put(assign_hero).delete(unassign_hero),
+			),
+	)
+}
@@ -0,0 +1,245 @@
+//! Loaders of the sections of the game state, composed by `GET /game` and by the dataloaders
+//! of the GraphQL endpoint.
+//!
+//! Each loader is independent of the others, so the handler only runs the ones of the
+//! sections a client asked for.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{Text, Timestamptz, Uuid};
+
+use super::models::{BuildingsState, PlayerState, ProtectionState, QueueState, ResourcesState};
+use crate::Result;
+use crate::configuration::Settings;
+use crate::controllers::game::buildings::load_construction_queue;
+use crate::controllers::game::units::load_training_queue;
+use crate::db::DbConn;
+use crate::domain::building::BuildingKey;
+use crate::domain::factions::FactionCode;
+use crate::domain::player::PlayerKey;
+use crate::domain::player::buildings::PlayerBuildingKey;
+use crate::domain::settlement::{Settlement, SettlementKey};
+use crate::game::buildings::building_operations;
+use crate::game::combat::protection_operations;
+use crate::game::resources::resource_operations;
+use crate::schema::player_building::dsl::player_building;
+
+/// Watermarks of the rows the game state of a player and their settlement is loaded from.
+#[derive(QueryableByName, Debug)]
+struct StateWatermarks {
+	#[diesel(sql_type = Text)]
+	watermarks: String,
+}
+
+/// Loads a version of the game state of a player and their settlement, far cheaper than the
+/// state itself.
+///
+/// The version is made of the last update and the number of the rows of every table the state
+/// is loaded from, so it changes with every write to them, deletes included. Protections,
+/// modifiers, upgrades and queue entries also change state when their time comes, so the
+/// number of them whose time has come by `now` is part of it too.
+pub(super) fn load_version(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	settlement_id: &SettlementKey,
+	now: DateTime<Utc>,
+) -> Result<String> {
+	let version = diesel::sql_query(
+		"SELECT concat_ws('|',
+		   (SELECT concat(updated_at, ':', protected_until <= $3)
+		    FROM player WHERE id = $1),
+		   (SELECT concat(max(updated_at), ':', count(*))
+		    FROM player_resource WHERE settlement_id = $2),
+		   (SELECT concat(max(updated_at), ':', count(*))
+		    FROM player_accumulator WHERE settlement_id = $2),
+		   (SELECT concat(max(updated_at), ':', count(*), ':',
+		                  count(*) FILTER (WHERE upgrade_finishes_at <= $3))
+		    FROM player_building WHERE player_id = $1),
+		   (SELECT concat(max(updated_at), ':', count(*), ':',
+		                  count(*) FILTER (WHERE starts_at <= $3), ':',
+		                  count(*) FILTER (WHERE completes_at <= $3))
+		    FROM construction_queue WHERE player_id = $1),
+		   (SELECT concat(max(updated_at), ':', count(*), ':',
+		                  count(*) FILTER (WHERE completes_at <= $3))
+		    FROM training_queue WHERE player_id = $1),
+		   (SELECT concat(max(updated_at), ':', count(*))
+		    FROM player_unit WHERE player_id = $1),
+		   (SELECT concat(max(updated_at), ':', count(*), ':',
+		                  count(*) FILTER (WHERE started_at <= $3), ':',
+		                  count(*) FILTER (WHERE expires_at <= $3))
+		    FROM active_modifiers WHERE player_id = $1)
+		 ) AS watermarks",
+	)
+	.bind::<Uuid, _>(player_key)
+	.bind::<Uuid, _>(settlement_id)
+	.bind::<Timestamptz, _>(now)
+	.get_result::<StateWatermarks>(conn)?;
+	Ok(version.watermarks)
+}
+
+/// Loads the player with their active protections.
+pub(crate) fn load_player(
+	conn: &mut DbConn,
+	settings: &Settings,
+	player_key: &PlayerKey,
+) -> Result<PlayerState> {
+	let mut player_state = get_player_data(conn, *player_key)?;
+	player_state.protection =
+		protection_operations::active_shield(conn, &settings.protection, player_key)?
+			.map(ProtectionState::until);
+	player_state.truce =
+		protection_operations::active_truce(conn, player_key)?.map(ProtectionState::until);
+	Ok(player_state)
+}
+
+/// Loads the resources of a settlement.
+pub(crate) fn load_resources(conn: &mut DbConn, settlement: &Settlement) -> Result<ResourcesState> {
+	let resource_snapshot = resource_operations::get_resource_snapshot(conn, settlement)?;
+	Ok(ResourcesState::from(resource_snapshot))
+}
+
+/// Loads the buildings of a settlement, grouped by their building type.
+pub(super) fn load_buildings(
+	conn: &mut DbConn,
+	settlement_id: &SettlementKey,
+) -> Result<HashMap<BuildingKey, Vec<BuildingsState>>> {
+	let buildings_list = get_player_buildings_data(conn, *settlement_id)?;
+
+	let mut buildings_map: HashMap<BuildingKey, Vec<BuildingsState>> = HashMap::new();
+	for building_state in buildings_list {
+		buildings_map
+			.entry(building_state.building_id)
+			.or_default()
+			.push(building_state);
+	}
+	Ok(buildings_map)
+}
+
+/// Loads the construction and training queues of the player.
+pub(super) fn load_queue(conn: &mut DbConn, player_key: &PlayerKey) -> Result<QueueState> {
+	Ok(QueueState {
+		construction: load_construction_queue(conn, player_key)?,
+		training: load_training_queue(conn, player_key)?,
+	})
+}
+
+fn get_player_data(conn: &mut DbConn, current_player_id: PlayerKey) -> QueryResult<PlayerState> {
+	use crate::schema::player::dsl::*;
+
+	#[derive(Queryable, Debug)]
+	struct PlayerData {
+		id: PlayerKey,
+		name: String,
+		faction: FactionCode,
+	}
+
+	player
+		.filter(id.eq(current_player_id))
+		.select((id, name, faction)) // Ensure these fields match PlayerState or PlayerData
+		.first::<PlayerData>(conn)
+		.map(|pd| PlayerState {
+			id: pd.id,
+			name: pd.name,
+			faction: pd.faction,
+			protection: None,
+			truce: None,
+		})
+}
+
+/// Loads the buildings of a settlement with their next upgrade and production.
+pub(crate) fn get_player_buildings_data(
+	conn: &mut PgConnection,
+	current_settlement_id: SettlementKey,
+) -> QueryResult<Vec<BuildingsState>> {
+	use crate::schema::building::dsl as b;
+	use crate::schema::building_level::dsl as bl;
+	use crate::schema::building_resource::dsl as br;
+	use crate::schema::player_building::dsl as pb;
+
+	let results = player_building
+		.filter(pb::settlement_id.eq(current_settlement_id))
+		.inner_join(b::building.on(pb::building_id.eq(b::id)))
+		.inner_join(
+			bl::building_level.on(pb::building_id
+				.eq(bl::building_id)
+				.and(bl::level.eq(pb::level + 1))),
+		)
+		.inner_join(
+			br::building_resource.on(pb::building_id
+				.eq(br::building_id)
+				.and(pb::level.eq(br::building_level))),
+		)
+		.select((
+			pb::id,
+			pb::building_id,
+			pb::level,
+			b::name,
+			b::max_level,
+			b::max_count,
+			bl::upgrade_seconds,
+			pb::upgrade_finishes_at,
+			bl::req_food,
+			bl::req_wood,
+			bl::req_stone,
+			bl::req_gold,
+			br::population,
+			br::food,  // Assuming br.food maps to food_per_hour
+			br::wood,  // Assuming br.wood maps to wood_per_hour
+			br::stone, // Assuming br.stone maps to stone_per_hour
+			br::gold,  // Assuming br.gold maps to gold_per_hour
+			pb::updated_at,
+		))
+		.load::<(
+			PlayerBuildingKey,
+			BuildingKey,
+			i32, // from player_building
+			String,
+			i32,
+			i32, // from building
+			i64, // in seconds
+			Option<DateTime<Utc>>,
+			Option<i64>,
+			Option<i64>,
+			Option<i64>,
+			Option<i64>, // from building_level
+			i64,         // from building_resource (population housed)
+			i64,
+			i64,
+			i64,
+			i64, // from building_resource (production per hour)
+			DateTime<Utc>,
+		)>(conn)?;
+
+	let now = Utc::now();
+	Ok(results
+		.into_iter()
+		.map(|row| {
+			let progress = building_operations::upgrade_progress(row.7, row.6, now);
+			BuildingsState {
+				id: row.0,
+				building_id: row.1,
+				level: row.2,
+				name: row.3,
+				max_level: row.4,
+				max_count: row.5,
+				upgrade_seconds: row.6,
+				upgrade_finishes_at: row.7,
+				upgrading: progress.is_some(),
+				upgrade_progress_percent: progress.map(|p| p.progress_percent),
+				seconds_remaining: progress.map(|p| p.seconds_remaining),
+				req_food: row.8,
+				req_wood: row.9,
+				req_stone: row.10,
+				req_gold: row.11,
+				population_per_hour: row.12,
+				food_per_hour: row.13,
+				wood_per_hour: row.14,
+				stone_per_hour: row.15,
+				gold_per_hour: row.16,
+				updated_at: row.17,
+			}
+		})
+		.collect())
+}
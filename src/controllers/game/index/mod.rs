@@ -1,6 +1,9 @@
 mod handlers;
+mod loaders;
 mod models;
 mod routes;
 
-pub use models::ResourcesState;
+#[cfg(feature = "graphql")]
+pub(crate) use loaders::{get_player_buildings_data, load_player, load_resources};
+pub use models::*;
 pub use routes::*;
@@ -0,0 +1,56 @@
+//! Request handlers for the items API endpoints.
+
+use axum::extract::{Path, State};
+use axum::response::IntoResponse;
+use axum::{Extension, Json, debug_handler};
+use tracing::{debug, info, instrument};
+
+use crate::Result;
+use crate::controllers::game::items::models::*;
+use crate::db::extractor::DatabaseConnection;
+use crate::db::player_items;
+use crate::domain::app_state::AppState;
+use crate::domain::auth::AuthenticatedUser;
+use crate::domain::item::ItemKey;
+use crate::game::items::item_operations;
+use crate::game::modifiers::modifier_service::ModifierService;
+
+/// GET /game/items
+///
+/// Returns the items the player holds, sorted by name.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_inventory(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!("Getting items for player {}", player_id);
+
+	let items = player_items::get_for_player(&mut conn, &player_id)?;
+
+	info!("Retrieved {} items for player {}", items.len(), player_id);
+	Ok(Json(InventoryResponse {
+		items: items.into_iter().map(InventoryItemDto::from).collect(),
+	}))
+}
+
+/// POST /game/items/{item_id}/use
+///
+/// Uses one of an item and applies its boost to the player until the item's duration runs
+/// out.
+#[instrument(skip(conn, service, player))]
+#[debug_handler(state = AppState)]
+pub async fn use_item(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(service): State<ModifierService>,
+	player: Extension<AuthenticatedUser>,
+	Path(item_id): Path<ItemKey>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!("Using item {} for player {}", item_id, player_id);
+
+	let used = item_operations::use_item(&mut conn, &service, &player_id, &item_id).await?;
+
+	Ok(Json(UseItemResponse::from(used)))
+}
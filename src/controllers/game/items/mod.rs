@@ -0,0 +1,12 @@
+//! Items controller module for the consumable items players hold.
+//!
+//! Provides REST API endpoints for:
+//! - Listing the player's inventory of items
+//! - Using an item, which applies its modifier for a while
+
+mod handlers;
+mod models;
+mod routes;
+
+pub use models::*;
+pub use routes::*;
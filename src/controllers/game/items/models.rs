@@ -0,0 +1,73 @@
+//! Response DTOs for the items API endpoints.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::item::{Item, ItemKey, PlayerItem};
+use crate::domain::modifier::active_modifier::ActiveModifierKey;
+use crate::game::items::item_operations::UsedItem;
+
+// === Response DTOs ===
+
+/// An item held by the player.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InventoryItemDto {
+	pub id: ItemKey,
+	pub name: String,
+	pub description: String,
+	pub quantity: i64,
+	/// How long the item's boost lasts once used
+	pub duration_seconds: i32,
+}
+
+impl From<(PlayerItem, Item)> for InventoryItemDto {
+	fn from((held, item): (PlayerItem, Item)) -> Self {
+		Self {
+			id: item.id,
+			name: item.name,
+			description: item.description,
+			quantity: held.quantity,
+			duration_seconds: item.duration_seconds,
+		}
+	}
+}
+
+/// Response for GET /items
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InventoryResponse {
+	pub items: Vec<InventoryItemDto>,
+}
+
+/// The boost applied by using an item.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ItemBoostDto {
+	pub active_modifier_id: ActiveModifierKey,
+	/// Human-readable effect of the boost, e.g. `+50% wood production`
+	pub effect: String,
+	pub started_at: DateTime<Utc>,
+	pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Response for POST /items/{item_id}/use
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UseItemResponse {
+	pub item_id: ItemKey,
+	/// Items of this kind left in the inventory
+	pub remaining: i64,
+	pub boost: ItemBoostDto,
+}
+
+impl From<UsedItem> for UseItemResponse {
+	fn from(used: UsedItem) -> Self {
+		Self {
+			item_id: used.item.id,
+			remaining: used.remaining.quantity,
+			boost: ItemBoostDto {
+				active_modifier_id: used.active.id,
+				effect: used.modifier.effect_summary(),
+				started_at: used.active.started_at,
+				expires_at: used.active.expires_at,
+			},
+		}
+	}
+}
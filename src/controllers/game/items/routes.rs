@@ -0,0 +1,21 @@
+//! Route definitions for the items API endpoints.
+
+use axum::Router;
+use axum::routing::{get, post};
+
+use crate::controllers::game::items::handlers::*;
+use crate::domain::app_state::AppState;
+
+/// Returns a router with all item routes.
+///
+/// Routes:
+/// - `GET /items` - Get the player's inventory of items
+/// - `POST /items/{item_id}/use` - Use one of an item
+pub fn items_routes() -> Router<AppState> {
+	Router::new().nest(
+		"/items",
+		Router::new()
+			.route("/", get(get_inventory))
+			.route("/{item_id}/use", post(use_item)),
+	)
+}
@@ -0,0 +1,33 @@
+//! Request handlers for the jobs API endpoints.
+
+use axum::extract::{Path, State};
+use axum::response::IntoResponse;
+use axum::{Extension, Json, debug_handler};
+use tracing::{debug, instrument};
+
+use crate::controllers::game::jobs::models::*;
+use crate::domain::app_state::{AppQueue, AppState};
+use crate::domain::auth::AuthenticatedUser;
+use crate::domain::jobs::JobKey;
+use crate::{Error, ErrorKind, Result};
+
+/// GET /game/jobs/{job_id}
+///
+/// Returns the status and result of a job that runs for the player. Jobs of other players
+/// and server maintenance jobs are reported as not found.
+#[instrument(skip(job_queue, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_job(
+	State(job_queue): State<AppQueue>,
+	player: Extension<AuthenticatedUser>,
+	Path(job_id): Path<JobKey>,
+) -> Result<impl IntoResponse> {
+	debug!("Getting job {} for player {}", job_id, player.id);
+
+	let job = job_queue.get_job(&job_id)?;
+	if job.player_id() != Some(player.id) {
+		return Err(Error::from((ErrorKind::JobNotFoundError, "Job not found")));
+	}
+
+	Ok(Json(JobStatusDto::from(job)))
+}
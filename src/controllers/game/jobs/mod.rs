@@ -0,0 +1,11 @@
+//! Jobs controller module for polling the outcome of background work.
+//!
+//! Provides REST API endpoints for:
+//! - Viewing the status and result of a job that runs for the player
+
+mod handlers;
+mod models;
+mod routes;
+
+pub use models::*;
+pub use routes::*;
@@ -0,0 +1,35 @@
+//! Response DTOs for the jobs API endpoints.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::jobs::{Job, JobKey, JobStatus, JobType};
+
+/// Status of a single job, as polled by clients.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct JobStatusDto {
+	pub id: JobKey,
+	pub job_type: JobType,
+	pub status: JobStatus,
+	/// When the job is due to run, or ran
+	pub run_at: DateTime<Utc>,
+	/// Number of failed attempts that were retried
+	pub retries: i32,
+	/// Outcome reported by the job once it completed
+	pub result: Option<serde_json::Value>,
+	pub updated_at: DateTime<Utc>,
+}
+
+impl From<Job> for JobStatusDto {
+	fn from(job: Job) -> Self {
+		Self {
+			id: job.id,
+			job_type: job.job_type,
+			status: job.status,
+			run_at: job.run_at,
+			retries: job.retries,
+			result: job.result,
+			updated_at: job.updated_at,
+		}
+	}
+}
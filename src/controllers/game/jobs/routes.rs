@@ -0,0 +1,15 @@
+//! Route definitions for the jobs API endpoints.
+
+use axum::Router;
+use axum::routing::get;
+
+use crate::controllers::game::jobs::handlers::*;
+use crate::domain::app_state::AppState;
+
+/// Returns a router with all job routes.
+///
+/// Routes:
+/// - `GET /jobs/{job_id}` - Get the status and result of a job
+pub fn jobs_routes() -> Router<AppState> {
+	Router::new().nest("/jobs", Router::new().route("/{job_id}", get(get_job)))
+}
@@ -0,0 +1,57 @@
+//! Request handlers for the leaderboard API endpoints.
+
+use axum::extract::Query;
+use axum::response::IntoResponse;
+use axum::{Extension, Json, debug_handler};
+use tracing::{debug, instrument};
+
+use crate::Result;
+use crate::controllers::game::leaderboard::models::*;
+use crate::db::extractor::DatabaseConnection;
+use crate::domain::app_state::AppState;
+use crate::domain::auth::AuthenticatedUser;
+use crate::game::leaderboard::leaderboard_operations;
+
+/// GET /game/leaderboard
+///
+/// Returns a page of the power, economy or alliance board of the last snapshot, along with
+/// the rank of the player or their alliance.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_leaderboard(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Query(query): Query<LeaderboardQuery>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	let category = query.category.unwrap_or_default();
+	debug!("Getting the {} board for player {}", category, player_id);
+
+	let page = leaderboard_operations::get_page(
+		&mut conn,
+		&player_id,
+		category,
+		query.page,
+		query.per_page,
+	)?;
+
+	Ok(Json(LeaderboardResponse::from(page)))
+}
+
+/// GET /game/leaderboard/score
+///
+/// Returns the player's current points. They may be ahead of the ones on the boards, which
+/// only change with every snapshot.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_score(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!("Getting the score of player {}", player_id);
+
+	let score = leaderboard_operations::get_score(&mut conn, &player_id)?;
+
+	Ok(Json(ScoreResponse::from(score)))
+}
@@ -0,0 +1,12 @@
+//! Leaderboard controller module for player and alliance rankings.
+//!
+//! Provides REST API endpoints for:
+//! - Viewing a page of the power, economy or alliance board, with the player's own rank
+//! - Viewing the player's current score
+
+mod handlers;
+mod models;
+mod routes;
+
+pub use models::*;
+pub use routes::*;
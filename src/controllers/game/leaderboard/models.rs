@@ -0,0 +1,122 @@
+//! Request and response DTOs for the leaderboard API endpoints.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::leaderboard::{LeaderboardCategory, LeaderboardEntry, PlayerScore};
+use crate::game::leaderboard::leaderboard_operations::{LeaderboardPage, RankedEntry};
+
+// === Request DTOs ===
+
+/// Query parameters for GET /leaderboard
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct LeaderboardQuery {
+	/// The board to show, defaults to `power`
+	pub category: Option<LeaderboardCategory>,
+	/// 1-based page number, defaults to 1
+	pub page: Option<i64>,
+	/// Entries per page, defaults to 50 and is capped at 100
+	pub per_page: Option<i64>,
+}
+
+// === Response DTOs ===
+
+/// A ranked player, or alliance on the alliance board.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct LeaderboardEntryDto {
+	pub rank: i32,
+	/// ID of the player or alliance
+	pub id: Uuid,
+	pub name: String,
+	pub score: i64,
+}
+
+impl From<RankedEntry> for LeaderboardEntryDto {
+	fn from(ranked: RankedEntry) -> Self {
+		Self {
+			rank: ranked.entry.rank,
+			id: ranked.entry.subject_id,
+			name: ranked.name,
+			score: ranked.entry.score,
+		}
+	}
+}
+
+/// The rank of the player, or of their alliance on the alliance board.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct OwnRankDto {
+	pub rank: i32,
+	pub score: i64,
+}
+
+impl From<LeaderboardEntry> for OwnRankDto {
+	fn from(entry: LeaderboardEntry) -> Self {
+		Self {
+			rank: entry.rank,
+			score: entry.score,
+		}
+	}
+}
+
+/// Response for GET /leaderboard
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LeaderboardResponse {
+	pub category: LeaderboardCategory,
+	/// Entries on this page, best first
+	pub entries: Vec<LeaderboardEntryDto>,
+	pub page: i64,
+	pub per_page: i64,
+	/// Total number of entries on the board
+	pub total: i64,
+	/// The player's own rank, missing if they are not ranked
+	pub own: Option<OwnRankDto>,
+	/// When the shown rankings were snapshotted, missing if nothing is shown
+	pub snapshot_at: Option<DateTime<Utc>>,
+}
+
+impl From<LeaderboardPage> for LeaderboardResponse {
+	fn from(page: LeaderboardPage) -> Self {
+		let snapshot_at = page
+			.entries
+			.first()
+			.map(|ranked| &ranked.entry)
+			.or(page.own.as_ref())
+			.map(|entry| entry.snapshot_at);
+		Self {
+			category: page.category,
+			entries: page.entries.into_iter().map(Into::into).collect(),
+			page: page.page,
+			per_page: page.per_page,
+			total: page.total,
+			own: page.own.map(Into::into),
+			snapshot_at,
+		}
+	}
+}
+
+/// Response for GET /leaderboard/score
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct ScoreResponse {
+	/// Points on the power board
+	pub power: i64,
+	/// Points on the economy board
+	pub economy: i64,
+	pub building_points: i64,
+	pub unit_points: i64,
+	pub tech_points: i64,
+	pub updated_at: DateTime<Utc>,
+}
+
+impl From<PlayerScore> for ScoreResponse {
+	fn from(score: PlayerScore) -> Self {
+		Self {
+			power: score.power(),
+			economy: score.economy(),
+			building_points: score.building_points,
+			unit_points: score.unit_points,
+			tech_points: score.tech_points,
+			updated_at: score.updated_at,
+		}
+	}
+}
@@ -0,0 +1,21 @@
+//! Route definitions for the leaderboard API endpoints.
+
+use axum::Router;
+use axum::routing::get;
+
+use crate::controllers::game::leaderboard::handlers::*;
+use crate::domain::app_state::AppState;
+
+/// Returns a router with all leaderboard routes.
+///
+/// Routes:
+/// - `GET /leaderboard` - Get a page of a board with the player's own rank
+/// - `GET /leaderboard/score` - Get the player's current score
+pub fn leaderboard_routes() -> Router<AppState> {
+	Router::new().nest(
+		"/leaderboard",
+		Router::new()
+			.route("/", get(get_leaderboard))
+			.route("/score", get(get_score)),
+	)
+}
@@ -0,0 +1,61 @@
+//! Request handlers for the limited events API endpoints.
+
+use axum::extract::Path;
+use axum::response::IntoResponse;
+use axum::{Extension, Json, debug_handler};
+use chrono::Utc;
+use tracing::{debug, info, instrument};
+
+use crate::Result;
+use crate::controllers::game::limited_events::models::*;
+use crate::db::extractor::DatabaseConnection;
+use crate::domain::app_state::AppState;
+use crate::domain::auth::AuthenticatedUser;
+use crate::domain::limited_event::{EventOfferKey, LimitedEventKey};
+use crate::game::limited_events::event_operations;
+
+/// GET /game/limited-events
+///
+/// Returns the running events with the player's balance of their currency and progress
+/// towards their objectives, ending first listed first.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_events(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!("Getting running events for player {}", player_id);
+
+	let events = event_operations::list_running(&mut conn, &player_id, Utc::now())?;
+
+	info!(
+		"Retrieved {} running events for player {}",
+		events.len(),
+		player_id
+	);
+	Ok(Json(EventsResponse {
+		events: events.into_iter().map(EventDto::from).collect(),
+	}))
+}
+
+/// POST /game/limited-events/{event_id}/offers/{offer_id}/purchase
+///
+/// Buys an offer of a running event's shop with the event's currency.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn purchase_offer(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path((event_id, offer_id)): Path<(LimitedEventKey, EventOfferKey)>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!(
+		"Buying offer {} of event {} for player {}",
+		offer_id, event_id, player_id
+	);
+
+	let purchase = event_operations::purchase_offer(&mut conn, &player_id, &event_id, &offer_id)?;
+
+	Ok(Json(PurchaseResponse::from(purchase)))
+}
@@ -0,0 +1,12 @@
+//! Limited events controller module for time-limited events and their shops.
+//!
+//! Provides REST API endpoints for:
+//! - Listing the running events with the player's currency and objective progress
+//! - Buying an offer of an event's shop with the event's currency
+
+mod handlers;
+mod models;
+mod routes;
+
+pub use models::*;
+pub use routes::*;
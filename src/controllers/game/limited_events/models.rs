@@ -0,0 +1,147 @@
+//! Response DTOs for the limited events API endpoints.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::item::ItemKey;
+use crate::domain::limited_event::{
+	EventObjective, EventObjectiveKey, EventObjectiveKind, EventOffer, EventOfferKey,
+	LimitedEventKey, PlayerEventObjective,
+};
+use crate::domain::player::resource::ResourceType;
+use crate::domain::unit::UnitKey;
+use crate::game::limited_events::event_operations::{PlayerEventView, Purchase};
+
+// === Response DTOs ===
+
+/// An objective of an event and the player's progress towards it.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EventObjectiveDto {
+	pub id: EventObjectiveKey,
+	pub kind: EventObjectiveKind,
+	pub description: String,
+	/// Occurrences needed to complete the objective once
+	pub target: i64,
+	/// Currency earned for each completion
+	pub reward: i64,
+	pub max_completions: Option<i32>,
+	/// Occurrences counted so far
+	pub progress: i64,
+	pub completions: i32,
+}
+
+impl From<(EventObjective, Option<&PlayerEventObjective>)> for EventObjectiveDto {
+	fn from((objective, progress): (EventObjective, Option<&PlayerEventObjective>)) -> Self {
+		Self {
+			id: objective.id,
+			kind: objective.kind,
+			description: objective.description,
+			target: objective.target,
+			reward: objective.reward,
+			max_completions: objective.max_completions,
+			progress: progress.map_or(0, |progress| progress.progress),
+			completions: progress.map_or(0, |progress| progress.completions),
+		}
+	}
+}
+
+/// An offer of an event's shop, selling either an item or a unit.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EventOfferDto {
+	pub id: EventOfferKey,
+	/// Price in the event's currency
+	pub price: i64,
+	pub item_id: Option<ItemKey>,
+	pub unit_id: Option<UnitKey>,
+	pub quantity: i64,
+}
+
+impl From<EventOffer> for EventOfferDto {
+	fn from(offer: EventOffer) -> Self {
+		Self {
+			id: offer.id,
+			price: offer.price,
+			item_id: offer.item_id,
+			unit_id: offer.unit_id,
+			quantity: offer.quantity,
+		}
+	}
+}
+
+/// A running event as the player sees it.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EventDto {
+	pub id: LimitedEventKey,
+	pub name: String,
+	pub description: String,
+	pub currency_name: String,
+	/// The player's balance of the event's currency
+	pub balance: i64,
+	pub starts_at: DateTime<Utc>,
+	pub ends_at: DateTime<Utc>,
+	/// Resource the leftover currency is converted to once the event ends
+	pub leftover_resource: ResourceType,
+	/// Amount of the resource granted for each unit of leftover currency
+	pub leftover_rate: i32,
+	pub objectives: Vec<EventObjectiveDto>,
+	pub offers: Vec<EventOfferDto>,
+}
+
+impl From<PlayerEventView> for EventDto {
+	fn from(view: PlayerEventView) -> Self {
+		let event = view.details.event;
+		let objectives = view
+			.details
+			.objectives
+			.into_iter()
+			.map(|objective| {
+				let progress = view
+					.progress
+					.iter()
+					.find(|progress| progress.objective_id == objective.id);
+				EventObjectiveDto::from((objective, progress))
+			})
+			.collect();
+		Self {
+			id: event.id,
+			name: event.name,
+			description: event.description,
+			currency_name: event.currency_name,
+			balance: view.balance,
+			starts_at: event.starts_at,
+			ends_at: event.ends_at,
+			leftover_resource: event.leftover_resource,
+			leftover_rate: event.leftover_rate,
+			objectives,
+			offers: view
+				.details
+				.offers
+				.into_iter()
+				.map(EventOfferDto::from)
+				.collect(),
+		}
+	}
+}
+
+/// Response for GET /limited-events
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EventsResponse {
+	pub events: Vec<EventDto>,
+}
+
+/// Response for POST /limited-events/{event_id}/offers/{offer_id}/purchase
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PurchaseResponse {
+	pub offer: EventOfferDto,
+	/// The player's balance of the event's currency after the purchase
+	pub balance: i64,
+}
+
+impl From<Purchase> for PurchaseResponse {
+	fn from(purchase: Purchase) -> Self {
+		Self {
+			offer: EventOfferDto::from(purchase.offer),
+			balance: purchase.balance,
+		}
+	}
+}
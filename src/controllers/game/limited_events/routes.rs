@@ -0,0 +1,22 @@
+//! Route definitions for the limited events API endpoints.
+
+use axum::Router;
+use axum::routing::{get, post};
+
+use crate::controllers::game::limited_events::handlers::*;
+use crate::domain::app_state::AppState;
+
+/// Returns a router with all limited event routes.
+///
+/// Routes:
+/// - `GET /limited-events` - Get the running events with the player's progress
+/// - `POST /limited-events/{event_id}/offers/{offer_id}/purchase` - Buy an offer of an event's shop
+pub fn limited_events_routes() -> Router<AppState> {
+	Router::new().nest(
+		"/limited-events",
+		Router::new().route("/", get(get_events)).route(
+			"/{event_id}/offers/{offer_id}/purchase",
+			post(purchase_offer),
+		),
+	)
+}
@@ -0,0 +1,132 @@
+//! Request handlers for the mail API endpoints.
+
+use axum::extract::{Path, Query};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json, debug_handler};
+use tracing::{debug, info, instrument};
+
+use crate::Result;
+use crate::controllers::game::mail::models::*;
+use crate::db::extractor::DatabaseConnection;
+use crate::domain::app_state::AppState;
+use crate::domain::auth::AuthenticatedUser;
+use crate::domain::message::MessageKey;
+use crate::game::mail::mail_operations;
+
+/// GET /game/mail
+///
+/// Returns a page of the player's inbox, newest first.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_inbox(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Query(query): Query<InboxQuery>,
+) -> Result<impl IntoResponse> {
+	let page = mail_operations::list_inbox(
+		&mut conn,
+		&player.id,
+		query.unread,
+		query.page,
+		query.per_page,
+	)?;
+	debug!(
+		"Retrieved {} messages for player {}",
+		page.messages.len(),
+		player.id
+	);
+	Ok(Json(InboxResponse::from(page)))
+}
+
+/// POST /game/mail
+///
+/// Sends a message to another player.
+#[instrument(skip(conn, player, request))]
+#[debug_handler(state = AppState)]
+pub async fn send_message(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Json(request): Json<SendMessageRequest>,
+) -> Result<impl IntoResponse> {
+	let message = mail_operations::send(
+		&mut conn,
+		&player.id,
+		&request.recipient_id,
+		&request.subject,
+		&request.body,
+	)?;
+	info!(
+		"Player {} sent a message to player {}",
+		player.id, request.recipient_id
+	);
+	Ok((StatusCode::CREATED, Json(MessageDto::from(message))))
+}
+
+/// GET /game/mail/unread
+///
+/// Returns the number of unread messages, for the client's badge.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_unread_count(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	let unread = mail_operations::unread_count(&mut conn, &player.id)?;
+	Ok(Json(UnreadCountResponse { unread }))
+}
+
+/// POST /game/mail/read
+///
+/// Marks every message in the player's inbox as read.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn mark_all_read(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	let marked = mail_operations::mark_all_read(&mut conn, &player.id)?;
+	Ok(Json(MarkAllReadResponse { marked }))
+}
+
+/// GET /game/mail/{message_id}
+///
+/// Returns a single message from the player's inbox.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_message(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path(message_id): Path<MessageKey>,
+) -> Result<impl IntoResponse> {
+	let message = mail_operations::get_message(&mut conn, &player.id, &message_id)?;
+	Ok(Json(MessageDto::from(message)))
+}
+
+/// POST /game/mail/{message_id}/read
+///
+/// Marks a message in the player's inbox as read.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn mark_read(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path(message_id): Path<MessageKey>,
+) -> Result<impl IntoResponse> {
+	let message = mail_operations::mark_read(&mut conn, &player.id, &message_id)?;
+	Ok(Json(MessageDto::from(message)))
+}
+
+/// DELETE /game/mail/{message_id}
+///
+/// Deletes a message from the player's inbox.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn delete_message(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path(message_id): Path<MessageKey>,
+) -> Result<impl IntoResponse> {
+	mail_operations::delete_message(&mut conn, &player.id, &message_id)?;
+	Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,13 @@
+//! Mail controller module for in-game messages.
+//!
+//! Provides REST API endpoints for:
+//! - Sending messages to other players
+//! - Reading the inbox with pagination, and counting the unread messages
+//! - Marking messages as read and deleting them
+
+mod handlers;
+mod models;
+mod routes;
+
+pub use models::*;
+pub use routes::*;
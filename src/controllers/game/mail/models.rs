@@ -0,0 +1,104 @@
+//! Request and response DTOs for the mail API endpoints.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::message::{Message, MessageKey, MessageKind};
+use crate::domain::player::PlayerKey;
+use crate::game::mail::mail_operations::MessagePage;
+
+// === Request DTOs ===
+
+/// Query parameters for GET /mail
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct InboxQuery {
+	/// 1-based page number, defaults to the first page
+	pub page: Option<i64>,
+	/// Messages per page, defaults to 20 and is capped at 100
+	pub per_page: Option<i64>,
+	/// Only list unread messages
+	#[serde(default)]
+	pub unread: bool,
+}
+
+/// Request body for POST /mail
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SendMessageRequest {
+	pub recipient_id: PlayerKey,
+	/// 1 to 100 characters
+	pub subject: String,
+	/// 1 to 5000 characters
+	pub body: String,
+}
+
+// === Response DTOs ===
+
+/// A single message.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MessageDto {
+	pub id: MessageKey,
+	/// Player who wrote the message, `null` for messages from the server
+	pub sender_id: Option<PlayerKey>,
+	pub kind: MessageKind,
+	pub subject: String,
+	pub body: String,
+	/// What a server message is about, like the ID of a battle report
+	pub details: serde_json::Value,
+	pub read: bool,
+	pub read_at: Option<DateTime<Utc>>,
+	pub created_at: DateTime<Utc>,
+}
+
+impl From<Message> for MessageDto {
+	fn from(message: Message) -> Self {
+		Self {
+			id: message.id,
+			sender_id: message.sender_id,
+			kind: message.kind,
+			read: message.is_read(),
+			subject: message.subject,
+			body: message.body,
+			details: message.details,
+			read_at: message.read_at,
+			created_at: message.created_at,
+		}
+	}
+}
+
+/// Response for GET /mail
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InboxResponse {
+	/// Messages on this page, newest first
+	pub messages: Vec<MessageDto>,
+	pub page: i64,
+	pub per_page: i64,
+	/// Total number of messages matching the query
+	pub total: i64,
+	/// Number of unread messages in the whole inbox
+	pub unread: i64,
+}
+
+impl From<MessagePage> for InboxResponse {
+	fn from(page: MessagePage) -> Self {
+		Self {
+			messages: page.messages.into_iter().map(MessageDto::from).collect(),
+			page: page.page,
+			per_page: page.per_page,
+			total: page.total,
+			unread: page.unread,
+		}
+	}
+}
+
+/// Response for GET /mail/unread
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UnreadCountResponse {
+	pub unread: i64,
+}
+
+/// Response for POST /mail/read
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MarkAllReadResponse {
+	/// Number of messages that were unread
+	pub marked: usize,
+}
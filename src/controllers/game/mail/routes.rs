@@ -0,0 +1,29 @@
+//! Route definitions for the mail API endpoints.
+
+use axum::Router;
+use axum::routing::{get, post};
+
+use crate::controllers::game::mail::handlers::*;
+use crate::domain::app_state::AppState;
+
+/// Returns a router with all mail routes.
+///
+/// Routes:
+/// - `GET /mail` - Get a page of the player's inbox
+/// - `POST /mail` - Send a message to another player
+/// - `GET /mail/unread` - Count the unread messages
+/// - `POST /mail/read` - Mark every message as read
+/// - `GET /mail/{message_id}` - Get a single message
+/// - `DELETE /mail/{message_id}` - Delete a message
+/// - `POST /mail/{message_id}/read` - Mark a message as read
+pub fn mail_routes() -> Router<AppState> {
+	Router::new().nest(
+		"/mail",
+		Router::new()
+			.route("/", get(get_inbox).post(send_message))
+			.route("/unread", get(get_unread_count))
+			.route("/read", post(mark_all_read))
+			.route("/{message_id}", get(get_message).delete(delete_message))
+			.route("/{message_id}/read", post(mark_read)),
+	)
+}
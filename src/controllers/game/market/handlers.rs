@@ -0,0 +1,118 @@
+//! Request handlers for the market API endpoints.
+
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json, debug_handler};
+use tracing::{debug, info, instrument};
+
+use crate::Result;
+use crate::controllers::game::market::models::*;
+use crate::db::extractor::DatabaseConnection;
+use crate::domain::app_state::AppState;
+use crate::domain::auth::AuthenticatedUser;
+use crate::domain::market::MarketOrderKey;
+use crate::domain::player::resource::ResourceType;
+use crate::game::market::market_operations;
+
+/// GET /game/market/orders
+///
+/// Returns the player's most recent orders, newest first.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_orders(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!("Getting market orders for player {}", player_id);
+
+	let orders = market_operations::list_orders(&mut conn, &player_id)?;
+
+	info!(
+		"Retrieved {} market orders for player {}",
+		orders.len(),
+		player_id
+	);
+	Ok(Json(OrderListResponse {
+		orders: orders.into_iter().map(MarketOrderDto::from).collect(),
+	}))
+}
+
+/// POST /game/market/orders
+///
+/// Places a limit order and matches it against the order book right away.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn place_order(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Json(request): Json<PlaceOrderRequest>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!("Placing market order for player {}", player_id);
+
+	let (order, trades) = market_operations::place_order(
+		&mut conn,
+		&player_id,
+		request.side,
+		request.resource,
+		request.quantity,
+		request.price,
+	)?;
+
+	info!(
+		"Placed market order {} for player {} with {} trades",
+		order.id,
+		player_id,
+		trades.len()
+	);
+	Ok((
+		StatusCode::CREATED,
+		Json(PlaceOrderResponse {
+			order: MarketOrderDto::from(order),
+			trades: trades.into_iter().map(MarketTradeDto::from).collect(),
+		}),
+	))
+}
+
+/// DELETE /game/market/orders/{order_id}
+///
+/// Cancels an open order and refunds its unfilled remainder.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn cancel_order(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path(order_id): Path<MarketOrderKey>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!(
+		"Cancelling market order {} for player {}",
+		order_id, player_id
+	);
+
+	let order = market_operations::cancel_order(&mut conn, &player_id, &order_id)?;
+
+	info!(
+		"Cancelled market order {} for player {}",
+		order_id, player_id
+	);
+	Ok(Json(MarketOrderDto::from(order)))
+}
+
+/// GET /game/market/book/{resource}
+///
+/// Returns the open orders of a resource, aggregated per price.
+#[instrument(skip(conn))]
+#[debug_handler(state = AppState)]
+pub async fn get_order_book(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	Path(resource): Path<ResourceType>,
+) -> Result<impl IntoResponse> {
+	debug!("Getting order book for {:?}", resource);
+
+	let book = market_operations::get_order_book(&mut conn, resource)?;
+
+	Ok(Json(OrderBookResponse::from(book)))
+}
@@ -0,0 +1,14 @@
+//! Market controller module for trading resources between players.
+//!
+//! Provides REST API endpoints for:
+//! - Placing buy and sell orders, which are matched right away
+//! - Cancelling an open order
+//! - Listing the player's orders
+//! - Viewing the order book of a resource
+
+mod handlers;
+mod models;
+mod routes;
+
+pub use models::*;
+pub use routes::*;
@@ -0,0 +1,116 @@
+//! Request and response DTOs for the market API endpoints.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::market::{
+	MarketOrder, MarketOrderKey, MarketOrderSide, MarketOrderStatus, MarketTrade, MarketTradeKey,
+	PriceLevel,
+};
+use crate::domain::player::resource::ResourceType;
+use crate::game::market::market_operations::OrderBook;
+
+// === Request DTOs ===
+
+/// Request body for POST /market/orders
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlaceOrderRequest {
+	pub side: MarketOrderSide,
+	/// Resource to trade, one of `food`, `wood` or `stone`
+	pub resource: ResourceType,
+	pub quantity: i64,
+	/// Limit price in gold per unit
+	pub price: i64,
+}
+
+// === Response DTOs ===
+
+/// A single market order.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MarketOrderDto {
+	pub id: MarketOrderKey,
+	pub side: MarketOrderSide,
+	pub resource: ResourceType,
+	pub quantity: i64,
+	/// Units that are not filled yet
+	pub remaining: i64,
+	pub price: i64,
+	pub status: MarketOrderStatus,
+	pub created_at: DateTime<Utc>,
+}
+
+impl From<MarketOrder> for MarketOrderDto {
+	fn from(order: MarketOrder) -> Self {
+		Self {
+			id: order.id,
+			side: order.side,
+			resource: order.resource,
+			quantity: order.quantity,
+			remaining: order.remaining,
+			price: order.price,
+			status: order.status,
+			created_at: order.created_at,
+		}
+	}
+}
+
+/// A fill of a market order.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MarketTradeDto {
+	pub id: MarketTradeKey,
+	pub buy_order_id: MarketOrderKey,
+	pub sell_order_id: MarketOrderKey,
+	pub quantity: i64,
+	/// Gold paid per unit
+	pub price: i64,
+	pub created_at: DateTime<Utc>,
+}
+
+impl From<MarketTrade> for MarketTradeDto {
+	fn from(trade: MarketTrade) -> Self {
+		Self {
+			id: trade.id,
+			buy_order_id: trade.buy_order_id,
+			sell_order_id: trade.sell_order_id,
+			quantity: trade.quantity,
+			price: trade.price,
+			created_at: trade.created_at,
+		}
+	}
+}
+
+/// Response for POST /market/orders
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlaceOrderResponse {
+	/// The order as it was left after matching
+	pub order: MarketOrderDto,
+	/// Trades the order was filled by right away
+	pub trades: Vec<MarketTradeDto>,
+}
+
+/// Response for GET /market/orders
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrderListResponse {
+	/// The player's most recent orders, newest first
+	pub orders: Vec<MarketOrderDto>,
+}
+
+/// Response for GET /market/book/{resource}
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrderBookResponse {
+	pub resource: ResourceType,
+	/// Buy orders per price, highest price first
+	pub bids: Vec<PriceLevel>,
+	/// Sell orders per price, lowest price first
+	pub asks: Vec<PriceLevel>,
+}
+
+impl From<OrderBook> for OrderBookResponse {
+	fn from(book: OrderBook) -> Self {
+		Self {
+			resource: book.resource,
+			bids: book.bids,
+			asks: book.asks,
+		}
+	}
+}
@@ -0,0 +1,24 @@
+//! Route definitions for the market API endpoints.
+
+use axum::Router;
+use axum::routing::{delete, get};
+
+use crate::controllers::game::market::handlers::*;
+use crate::domain::app_state::AppState;
+
+/// Returns a router with all market routes.
+///
+/// Routes:
+/// - `GET /market/orders` - Get the player's orders
+/// - `POST /market/orders` - Place a new order
+/// - `DELETE /market/orders/{order_id}` - Cancel an open order
+/// - `GET /market/book/{resource}` - Get the order book of a resource
+pub fn market_routes() -> Router<AppState> {
+	Router::new().nest(
+		"/market",
+		Router::new()
+			.route("/orders", get(get_orders).post(place_order))
+			.route("/orders/{order_id}", delete(cancel_order))
+			.route("/book/{resource}", get(get_order_book)),
+	)
+}
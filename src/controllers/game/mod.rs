@@ -1,26 +1,88 @@
-use axum::Router;
+use axum::{Router, middleware};
+use utoipa::OpenApi;
+use utoipa::openapi::OpenApi as OpenApiDoc;
 
-use crate::controllers::game::buildings::buildings_routes;
+use crate::controllers::game::alliances::alliances_routes;
+use crate::controllers::game::buildings::{BuildingsApi, buildings_routes};
+use crate::controllers::game::chat::chat_routes;
+use crate::controllers::game::combat::combat_routes;
 use crate::controllers::game::factions::factions_routes;
+use crate::controllers::game::friends::friends_routes;
+use crate::controllers::game::heroes::heroes_routes;
 use crate::controllers::game::index::index_routes;
-use crate::controllers::game::resources::resource_routes;
-use crate::controllers::game::units::units_routes;
+use crate::controllers::game::items::items_routes;
+use crate::controllers::game::jobs::jobs_routes;
+use crate::controllers::game::leaderboard::leaderboard_routes;
+use crate::controllers::game::limited_events::limited_events_routes;
+use crate::controllers::game::mail::mail_routes;
+use crate::controllers::game::market::market_routes;
+use crate::controllers::game::plans::plans_routes;
+use crate::controllers::game::quests::quests_routes;
+use crate::controllers::game::resources::{ResourcesApi, resource_routes};
+use crate::controllers::game::seasons::seasons_routes;
+use crate::controllers::game::settlements::settlements_routes;
+use crate::controllers::game::stats::stats_routes;
+use crate::controllers::game::units::{UnitsApi, units_routes};
 use crate::domain::app_state::AppState;
+use crate::net::require_faction;
 
-mod buildings;
-mod factions;
+pub mod alliances;
+pub mod buildings;
+pub mod chat;
+pub mod combat;
+pub mod factions;
+pub mod friends;
+pub mod heroes;
 pub mod index;
+pub mod items;
+pub mod jobs;
+pub mod leaderboard;
+pub mod limited_events;
+pub mod mail;
+pub mod market;
+pub mod plans;
+pub mod quests;
 mod resources;
-mod units;
+pub mod seasons;
+pub mod settlements;
+pub mod stats;
+pub mod units;
 
+/// Returns the router of the game API.
+///
+/// Neutral players can only browse the factions until they select one, every other game
+/// route requires a faction.
 pub fn game_routes() -> Router<AppState> {
-	Router::new().nest(
-		"/game",
-		Router::new()
-			.merge(index_routes())
-			.merge(buildings_routes())
-			.merge(resource_routes())
-			.merge(factions_routes())
-			.merge(units_routes()),
-	)
+	let playing_routes = Router::new()
+		.merge(index_routes())
+		.merge(buildings_routes())
+		.merge(resource_routes())
+		.merge(settlements_routes())
+		.merge(units_routes())
+		.merge(plans_routes())
+		.merge(combat_routes())
+		.merge(market_routes())
+		.merge(alliances_routes())
+		.merge(mail_routes())
+		.merge(chat_routes())
+		.merge(friends_routes())
+		.merge(items_routes())
+		.merge(heroes_routes())
+		.merge(limited_events_routes())
+		.merge(leaderboard_routes())
+		.merge(quests_routes())
+		.merge(seasons_routes())
+		.merge(jobs_routes())
+		.merge(stats_routes())
+		.route_layer(middleware::from_fn(require_faction));
+
+	Router::new().nest("/game", playing_routes.merge(factions_routes()))
+}
+
+/// Returns the OpenAPI document of the documented game routes.
+pub fn game_api_doc() -> OpenApiDoc {
+	let mut doc = BuildingsApi::openapi();
+	doc.merge(ResourcesApi::openapi());
+	doc.merge(UnitsApi::openapi());
+	doc
 }
@@ -0,0 +1,90 @@
+//! Request handlers for the planned actions API endpoints.
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json, debug_handler};
+use tracing::{debug, info, instrument};
+
+use crate::Result;
+use crate::controllers::game::plans::models::*;
+use crate::db::extractor::{DatabaseConnection, SelectedSettlement};
+use crate::domain::app_state::{AppQueue, AppState};
+use crate::domain::auth::AuthenticatedUser;
+use crate::domain::player::planned_action::PlannedActionKey;
+use crate::game::buildings::plan_operations;
+
+/// GET /game/plans
+///
+/// Returns the player's pending planned actions in execution order.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_plans(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!("Getting planned actions for player {}", player_id);
+
+	let plans = plan_operations::list_plans(&mut conn, &player_id)?;
+	let capacity = plan_operations::plan_capacity(&mut conn, &player_id)?;
+
+	info!(
+		"Retrieved {} planned actions for player {}",
+		plans.len(),
+		player_id
+	);
+	Ok(Json(PlanListResponse {
+		plans: plans.into_iter().map(PlannedActionDto::from).collect(),
+		capacity,
+	}))
+}
+
+/// POST /game/plans
+///
+/// Queues a construction or upgrade to be executed once the player can afford it. Planned
+/// constructions are built in the selected settlement.
+#[instrument(skip(conn, job_queue, player, settlement))]
+#[debug_handler(state = AppState)]
+pub async fn create_plan(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(job_queue): State<AppQueue>,
+	player: Extension<AuthenticatedUser>,
+	settlement: SelectedSettlement,
+	Json(request): Json<CreatePlanRequest>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!("Creating planned action for player {}", player_id);
+
+	let plan = plan_operations::create_plan(
+		&mut conn,
+		&job_queue,
+		&player_id,
+		request.action,
+		request.building_id,
+		request.player_building_id,
+		Some(settlement.id),
+	)?;
+
+	info!("Created plan {} for player {}", plan.id, player_id);
+	Ok((StatusCode::CREATED, Json(PlannedActionDto::from(plan))))
+}
+
+/// DELETE /game/plans/{plan_id}
+///
+/// Removes a pending plan from the player's queue.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn cancel_plan(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path(plan_id): Path<PlannedActionKey>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!("Cancelling plan {} for player {}", plan_id, player_id);
+
+	let plan = plan_operations::cancel_plan(&mut conn, &player_id, &plan_id)?;
+
+	info!("Cancelled plan {} for player {}", plan_id, player_id);
+	Ok(Json(PlannedActionDto::from(plan)))
+}
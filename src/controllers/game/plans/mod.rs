@@ -0,0 +1,13 @@
+//! Plans controller module for the planned action queue.
+//!
+//! Provides REST API endpoints for:
+//! - Listing the player's pending planned actions
+//! - Queueing a construction or upgrade to run once it becomes affordable
+//! - Cancelling a pending plan
+
+mod handlers;
+mod models;
+mod routes;
+
+pub use models::*;
+pub use routes::*;
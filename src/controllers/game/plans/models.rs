@@ -0,0 +1,64 @@
+//! Request and response DTOs for the planned actions API endpoints.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::building::BuildingKey;
+use crate::domain::player::buildings::PlayerBuildingKey;
+use crate::domain::player::planned_action::{
+	PlannedAction, PlannedActionKey, PlannedActionKind, PlannedActionStatus,
+};
+use crate::domain::settlement::SettlementKey;
+
+// === Request DTOs ===
+
+/// Request body for POST /plans
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreatePlanRequest {
+	pub action: PlannedActionKind,
+	/// Building type to construct, required for `construct` plans
+	pub building_id: Option<BuildingKey>,
+	/// Player building to upgrade, required for `upgrade` plans
+	pub player_building_id: Option<PlayerBuildingKey>,
+}
+
+// === Response DTOs ===
+
+/// A single planned action.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlannedActionDto {
+	pub id: PlannedActionKey,
+	pub action: PlannedActionKind,
+	pub building_id: Option<BuildingKey>,
+	pub player_building_id: Option<PlayerBuildingKey>,
+	/// Settlement a construction is built in, the capital if it is not set
+	pub settlement_id: Option<SettlementKey>,
+	pub status: PlannedActionStatus,
+	/// Why the last execution attempt did not go through, if any
+	pub last_error: Option<String>,
+	pub created_at: DateTime<Utc>,
+}
+
+impl From<PlannedAction> for PlannedActionDto {
+	fn from(plan: PlannedAction) -> Self {
+		Self {
+			id: plan.id,
+			action: plan.action,
+			building_id: plan.building_id,
+			player_building_id: plan.player_building_id,
+			settlement_id: plan.settlement_id,
+			status: plan.status,
+			last_error: plan.last_error,
+			created_at: plan.created_at,
+		}
+	}
+}
+
+/// Response for GET /plans
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlanListResponse {
+	/// Pending plans in execution order
+	pub plans: Vec<PlannedActionDto>,
+	/// Maximum number of pending plans allowed for the player
+	pub capacity: i64,
+}
@@ -0,0 +1,22 @@
+//! Route definitions for the planned actions API endpoints.
+
+use axum::Router;
+use axum::routing::{delete, get};
+
+use crate::controllers::game::plans::handlers::*;
+use crate::domain::app_state::AppState;
+
+/// Returns a router with all planned action routes.
+///
+/// Routes:
+/// - `GET /plans` - Get the player's pending plans
+/// - `POST /plans` - Queue a new plan
+/// - `DELETE /plans/{plan_id}` - Cancel a pending plan
+pub fn plans_routes() -> Router<AppState> {
+	Router::new().nest(
+		"/plans",
+		Router::new()
+			.route("/", get(get_plans).post(create_plan))
+			.route("/{plan_id}", delete(cancel_plan)),
+	)
+}
@@ -0,0 +1,55 @@
+//! Request handlers for the quests API endpoints.
+
+use axum::extract::Path;
+use axum::response::IntoResponse;
+use axum::{Extension, Json, debug_handler};
+use chrono::Utc;
+use tracing::{debug, info, instrument};
+
+use crate::Result;
+use crate::controllers::game::quests::models::*;
+use crate::db::extractor::DatabaseConnection;
+use crate::domain::app_state::AppState;
+use crate::domain::auth::AuthenticatedUser;
+use crate::domain::quest::QuestKey;
+use crate::game::quests::quest_operations;
+
+/// GET /game/quests
+///
+/// Returns the quests open to the player and the ones they claimed, in the order of the quest
+/// log, with their progress.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_quests(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!("Getting quests for player {}", player_id);
+
+	let quests = quest_operations::list_quests(&mut conn, &player_id)?;
+
+	info!("Retrieved {} quests for player {}", quests.len(), player_id);
+	Ok(Json(QuestLogResponse {
+		quests: quests.into_iter().map(QuestDto::from).collect(),
+	}))
+}
+
+/// POST /game/quests/{quest_id}/claim
+///
+/// Claims the rewards of a quest whose objective the player reached, which unlocks the quests
+/// requiring it.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn claim_quest(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path(quest_id): Path<QuestKey>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!("Claiming quest {} for player {}", quest_id, player_id);
+
+	let claimed = quest_operations::claim_quest(&mut conn, &player_id, &quest_id, Utc::now())?;
+
+	Ok(Json(QuestDto::from(claimed)))
+}
@@ -0,0 +1,12 @@
+//! Quests controller module for the quest chains guiding new players.
+//!
+//! Provides REST API endpoints for:
+//! - Listing the quests open to the player and the ones they claimed, with their progress
+//! - Claiming the rewards of a completed quest
+
+mod handlers;
+mod models;
+mod routes;
+
+pub use models::*;
+pub use routes::*;
@@ -0,0 +1,70 @@
+//! Response DTOs for the quests API endpoints.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::item::ItemKey;
+use crate::domain::quest::{QuestKey, QuestObjective};
+use crate::game::quests::quest_operations::{QuestState, QuestStatus};
+
+// === Response DTOs ===
+
+/// Rewards granted when a quest is claimed.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct QuestRewardsDto {
+	pub food: i64,
+	pub wood: i64,
+	pub stone: i64,
+	pub gold: i64,
+	pub item_id: Option<ItemKey>,
+	pub item_quantity: i64,
+}
+
+/// A quest of the player's quest log.
+#[derive(Serialize, Debug)]
+pub struct QuestDto {
+	pub id: QuestKey,
+	pub name: String,
+	pub description: String,
+	pub objective: QuestObjective,
+	/// Building, unit or resource the objective is about
+	pub target: String,
+	/// Level, quantity or stored amount the objective asks for
+	pub amount: i64,
+	/// Progress towards the amount, capped at the amount
+	pub progress: i64,
+	pub state: QuestState,
+	pub rewards: QuestRewardsDto,
+	pub claimed_at: Option<DateTime<Utc>>,
+}
+
+impl From<QuestStatus> for QuestDto {
+	fn from(status: QuestStatus) -> Self {
+		let quest = status.quest;
+		Self {
+			rewards: QuestRewardsDto {
+				food: quest.reward_food,
+				wood: quest.reward_wood,
+				stone: quest.reward_stone,
+				gold: quest.reward_gold,
+				item_id: quest.reward_item_id,
+				item_quantity: quest.reward_item_quantity,
+			},
+			id: quest.id,
+			name: quest.name,
+			description: quest.description,
+			objective: quest.objective,
+			target: quest.target,
+			amount: quest.amount,
+			progress: status.progress,
+			state: status.state,
+			claimed_at: status.claimed_at,
+		}
+	}
+}
+
+/// Response for GET /quests
+#[derive(Serialize, Debug)]
+pub struct QuestLogResponse {
+	pub quests: Vec<QuestDto>,
+}
@@ -0,0 +1,21 @@
+//! Route definitions for the quests API endpoints.
+
+use axum::Router;
+use axum::routing::{get, post};
+
+use crate::controllers::game::quests::handlers::*;
+use crate::domain::app_state::AppState;
+
+/// Returns a router with all quest routes.
+///
+/// Routes:
+/// - `GET /quests` - Get the player's quest log
+/// - `POST /quests/{quest_id}/claim` - Claim the rewards of a completed quest
+pub fn quests_routes() -> Router<AppState> {
+	Router::new().nest(
+		"/quests",
+		Router::new()
+			.route("/", get(get_quests))
+			.route("/{quest_id}/claim", post(claim_quest)),
+	)
+}
@@ -1,47 +1,98 @@
+use axum::extract::State;
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::{Extension, Json, debug_handler};
-use serde_json::json;
 use tracing::{debug, info, instrument, warn};
 
 use crate::Result;
+use crate::configuration::Settings;
 use crate::controllers::game::index::ResourcesState;
-use crate::db::extractor::DatabaseConnection;
-use crate::domain::app_state::AppState;
+use crate::controllers::game::resources::models::*;
+use crate::db::extractor::{DatabaseConnection, SelectedSettlement};
+use crate::domain::app_state::{AppQueue, AppState};
 use crate::domain::auth::AuthenticatedUser;
-use crate::game::resources::resource_operations;
+use crate::domain::error::ErrorBody;
+use crate::game::resources::{caravan_operations, resource_operations};
 
-#[instrument(skip(conn))]
+/// Produces the resources of the selected settlement up to now and collects them.
+#[utoipa::path(
+	post,
+	path = "/game/resources/collect",
+	tag = "resources",
+	params(("x-settlement-id" = Option<Uuid>, Header, description = "Settlement to act on, the capital by default")),
+	responses(
+		(status = OK, description = "Resources of the settlement after collecting", body = ResourcesState),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
+#[instrument(skip(conn, settings))]
 #[debug_handler(state = AppState)]
 pub async fn collect_resources(
 	DatabaseConnection(mut conn): DatabaseConnection,
-	player: Extension<AuthenticatedUser>,
+	settings: Settings,
+	SelectedSettlement(settlement): SelectedSettlement,
 ) -> Result<impl IntoResponse> {
-	let player_key = player.id;
-	debug!("Collecting resources for player: {}", player_key);
+	debug!(
+		"Collecting resources in settlement {} of player {}",
+		settlement.id, settlement.player_id
+	);
 
 	// Calculate production rates with modifiers (no caching, fresh values)
-	let production_rates = resource_operations::calc_prod_rates(&mut conn, &player_key)?;
+	let production_rates = resource_operations::calc_prod_rates(&mut conn, &settlement)?;
 
 	// Produce resources up to now and then collect them
 	let result = resource_operations::produce_and_collect_resources(
 		&mut conn,
-		&player_key,
+		&settlement,
 		&production_rates,
+		&settings.resources,
+	);
+
+	let (_accumulator, res) =
+		result.inspect_err(|err| warn!("Error producing/collecting resources: {}", err))?;
+	info!("Produced and collected resources: {}", res.id);
+	let snapshot = resource_operations::get_resource_snapshot(&mut conn, &settlement)?;
+	Ok(Json(ResourcesState::from(snapshot)))
+}
+
+/// POST /game/resources/send
+///
+/// Sends a caravan with resources to another player. The resources leave the player's
+/// storage right away and are delivered when the caravan arrives.
+#[utoipa::path(
+	post,
+	path = "/game/resources/send",
+	tag = "resources",
+	request_body = SendResourcesRequest,
+	responses(
+		(status = CREATED, description = "The caravan on its way", body = CaravanDto),
+		(status = BAD_REQUEST, description = "The resources cannot be sent", body = ErrorBody),
+		(status = NOT_FOUND, description = "Receiver not found", body = ErrorBody),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
+#[instrument(skip(conn, job_queue, player))]
+#[debug_handler(state = AppState)]
+pub async fn send_resources(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(job_queue): State<AppQueue>,
+	player: Extension<AuthenticatedUser>,
+	Json(request): Json<SendResourcesRequest>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!(
+		"Sending resources from player {} to player {}",
+		player_id, request.receiver_id
 	);
 
-	match result {
-		Ok((_accumulator, res)) => {
-			info!("Produced and collected resources: {}", res.id);
-			let snapshot = resource_operations::get_resource_snapshot(&mut conn, &player_key)?;
-			let res_state = ResourcesState::from(snapshot);
-			let body = json!(res_state);
-			Ok((StatusCode::OK, Json(body)))
-		}
-		Err(err) => {
-			warn!("Error producing/collecting resources: {}", err);
-			let body = json!({ "status": "fail", "message": err.to_string() });
-			Ok((StatusCode::INTERNAL_SERVER_ERROR, Json(body)))
-		}
-	}
+	let caravan = caravan_operations::send_caravan(
+		&mut conn,
+		&job_queue,
+		&player_id,
+		&request.receiver_id,
+		request.cargo(),
+	)?;
+
+	info!("Player {} sent caravan {}", player_id, caravan.id);
+	Ok((StatusCode::CREATED, Json(CaravanDto::from(caravan))))
 }
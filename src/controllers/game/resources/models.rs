@@ -1 +1,65 @@
-// stubbed
+//! Request and response DTOs for the resource API endpoints.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::domain::caravan::{Caravan, CaravanKey, CaravanStatus, Cargo};
+use crate::domain::jobs::JobKey;
+use crate::domain::player::PlayerKey;
+
+// === Request DTOs ===
+
+/// Request body for POST /resources/send
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct SendResourcesRequest {
+	pub receiver_id: PlayerKey,
+	#[serde(default)]
+	pub food: i64,
+	#[serde(default)]
+	pub wood: i64,
+	#[serde(default)]
+	pub stone: i64,
+	#[serde(default)]
+	pub gold: i64,
+}
+
+impl SendResourcesRequest {
+	pub fn cargo(&self) -> Cargo {
+		Cargo {
+			food: self.food,
+			wood: self.wood,
+			stone: self.stone,
+			gold: self.gold,
+		}
+	}
+}
+
+// === Response DTOs ===
+
+/// A caravan on its way to another player.
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct CaravanDto {
+	pub id: CaravanKey,
+	pub sender_id: PlayerKey,
+	pub receiver_id: PlayerKey,
+	pub cargo: Cargo,
+	pub status: CaravanStatus,
+	/// The job delivering the caravan, can be polled through the jobs API
+	pub job_id: Option<JobKey>,
+	pub arrives_at: DateTime<Utc>,
+}
+
+impl From<Caravan> for CaravanDto {
+	fn from(caravan: Caravan) -> Self {
+		Self {
+			cargo: caravan.cargo(),
+			id: caravan.id,
+			sender_id: caravan.sender_id,
+			receiver_id: caravan.receiver_id,
+			status: caravan.status,
+			job_id: caravan.job_id,
+			arrives_at: caravan.arrives_at,
+		}
+	}
+}
@@ -1,14 +1,28 @@
 use axum::Router;
 use axum::routing::post;
+use utoipa::OpenApi;
 
 use crate::domain::app_state::AppState;
 
 pub fn resource_routes() -> Router<AppState> {
 	Router::new().nest(
 		"/resources",
-		Router::new().route(
-			"/collect",
-			post(crate::controllers::game::resources::handlers::collect_resources),
-		),
+		Router::new()
+			.route(
+				"/collect",
+				post(crate::controllers::game::resources::handlers::collect_resources),
+			)
+			.route(
+				"/send",
+				post(crate::controllers::game::resources::handlers::send_resources),
+			),
 	)
 }
+
+/// OpenAPI document of the resources routes.
+#[derive(OpenApi)]
+#[openapi(paths(
+	crate::controllers::game::resources::handlers::collect_resources,
+	crate::controllers::game::resources::handlers::send_resources,
+))]
+pub struct ResourcesApi;
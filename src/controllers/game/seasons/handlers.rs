@@ -0,0 +1,74 @@
+//! Request handlers for the seasons API endpoints.
+
+use axum::extract::{Path, Query};
+use axum::response::IntoResponse;
+use axum::{Extension, Json, debug_handler};
+use tracing::{debug, instrument};
+
+use crate::Result;
+use crate::controllers::game::seasons::models::*;
+use crate::db::extractor::DatabaseConnection;
+use crate::domain::app_state::AppState;
+use crate::domain::auth::AuthenticatedUser;
+use crate::domain::season::SeasonKey;
+use crate::game::seasons::season_operations;
+
+/// GET /game/seasons
+///
+/// Returns the seasons that ended, latest first.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_seasons(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	debug!("Getting the past seasons for player {}", player.id);
+
+	let seasons = season_operations::list_history(&mut conn)?;
+
+	Ok(Json(SeasonHistoryResponse {
+		seasons: seasons.into_iter().map(SeasonDto::from).collect(),
+	}))
+}
+
+/// GET /game/seasons/current
+///
+/// Returns the season being played or coming up, if any, along with the legacy points the
+/// player earned in the past seasons.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_current_season(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!("Getting the current season for player {}", player_id);
+
+	let overview = season_operations::get_overview(&mut conn, &player_id)?;
+
+	Ok(Json(CurrentSeasonResponse::from(overview)))
+}
+
+/// GET /game/seasons/{season_id}
+///
+/// Returns the best standings of the power, economy or alliance board of a season, along
+/// with the standing of the player or their alliance.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_season(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path(season_id): Path<SeasonKey>,
+	Query(query): Query<SeasonQuery>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	let category = query.category.unwrap_or_default();
+	debug!(
+		"Getting the {} board of season {} for player {}",
+		category, season_id, player_id
+	);
+
+	let results = season_operations::get_results(&mut conn, &player_id, &season_id, category)?;
+
+	Ok(Json(SeasonResultsResponse::from(results)))
+}
@@ -0,0 +1,13 @@
+//! Seasons controller module for the seasons of the world and the players' legacy.
+//!
+//! Provides REST API endpoints for:
+//! - Viewing the season being played or coming up, with the player's legacy points
+//! - Listing the seasons that ended
+//! - Viewing the final standings of a season, with the player's own standing
+
+mod handlers;
+mod models;
+mod routes;
+
+pub use models::*;
+pub use routes::*;
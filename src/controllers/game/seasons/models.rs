@@ -0,0 +1,122 @@
+//! Request and response DTOs for the seasons API endpoints.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::leaderboard::LeaderboardCategory;
+use crate::domain::season::{Season, SeasonKey, SeasonStanding};
+use crate::game::seasons::season_operations::{SeasonOverview, SeasonResults};
+
+// === Request DTOs ===
+
+/// Query parameters for GET /seasons/{season_id}
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SeasonQuery {
+	/// The board to show, defaults to `power`
+	pub category: Option<LeaderboardCategory>,
+}
+
+// === Response DTOs ===
+
+/// A season of the world.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct SeasonDto {
+	pub id: SeasonKey,
+	pub number: i32,
+	pub name: String,
+	pub starts_at: DateTime<Utc>,
+	pub ends_at: DateTime<Utc>,
+	/// When the standings were archived, missing until the season ended
+	pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl From<Season> for SeasonDto {
+	fn from(season: Season) -> Self {
+		Self {
+			id: season.id,
+			number: season.number,
+			name: season.name,
+			starts_at: season.starts_at,
+			ends_at: season.ends_at,
+			ended_at: season.ended_at,
+		}
+	}
+}
+
+/// A final rank on a board of a season.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct SeasonStandingDto {
+	pub rank: i32,
+	/// ID of the player or alliance
+	pub id: Uuid,
+	/// Name of the player or alliance at the end of the season
+	pub name: String,
+	pub score: i64,
+	pub legacy_points: i64,
+}
+
+impl From<SeasonStanding> for SeasonStandingDto {
+	fn from(standing: SeasonStanding) -> Self {
+		Self {
+			rank: standing.rank,
+			id: standing.subject_id,
+			name: standing.name,
+			score: standing.score,
+			legacy_points: standing.legacy_points,
+		}
+	}
+}
+
+/// Response for GET /seasons
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SeasonHistoryResponse {
+	/// Seasons that ended, latest first
+	pub seasons: Vec<SeasonDto>,
+}
+
+/// Response for GET /seasons/current
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CurrentSeasonResponse {
+	/// The season being played or coming up, missing if none was created
+	pub season: Option<SeasonDto>,
+	/// Legacy points the player earned in the past seasons
+	pub legacy_points: i64,
+	/// Number of past seasons the player was ranked in
+	pub seasons_played: i32,
+}
+
+impl From<SeasonOverview> for CurrentSeasonResponse {
+	fn from(overview: SeasonOverview) -> Self {
+		Self {
+			season: overview.current.map(Into::into),
+			legacy_points: overview
+				.legacy
+				.as_ref()
+				.map_or(0, |legacy| legacy.legacy_points),
+			seasons_played: overview.legacy.map_or(0, |legacy| legacy.seasons),
+		}
+	}
+}
+
+/// Response for GET /seasons/{season_id}
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SeasonResultsResponse {
+	pub season: SeasonDto,
+	pub category: LeaderboardCategory,
+	/// The best standings, empty until the season ended
+	pub standings: Vec<SeasonStandingDto>,
+	/// The player's own standing, missing if they were not ranked
+	pub own: Option<SeasonStandingDto>,
+}
+
+impl From<SeasonResults> for SeasonResultsResponse {
+	fn from(results: SeasonResults) -> Self {
+		Self {
+			season: results.season.into(),
+			category: results.category,
+			standings: results.standings.into_iter().map(Into::into).collect(),
+			own: results.own.map(Into::into),
+		}
+	}
+}
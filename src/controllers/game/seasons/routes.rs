@@ -0,0 +1,23 @@
+//! Route definitions for the seasons API endpoints.
+
+use axum::Router;
+use axum::routing::get;
+
+use crate::controllers::game::seasons::handlers::*;
+use crate::domain::app_state::AppState;
+
+/// Returns a router with all season routes.
+///
+/// Routes:
+/// - `GET /seasons` - List the seasons that ended, latest first
+/// - `GET /seasons/current` - Get the current season and the player's legacy
+/// - `GET /seasons/{season_id}` - Get a board of a season with the player's own standing
+pub fn seasons_routes() -> Router<AppState> {
+	Router::new().nest(
+		"/seasons",
+		Router::new()
+			.route("/", get(get_seasons))
+			.route("/current", get(get_current_season))
+			.route("/{season_id}", get(get_season)),
+	)
+}
@@ -0,0 +1,46 @@
+//! Request handlers for the settlements API endpoints.
+
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json, debug_handler};
+use tracing::{debug, instrument};
+
+use crate::Result;
+use crate::configuration::Settings;
+use crate::controllers::game::settlements::models::*;
+use crate::db::extractor::DatabaseConnection;
+use crate::domain::app_state::AppState;
+use crate::domain::auth::AuthenticatedUser;
+use crate::game::settlements::settlement_operations;
+
+/// GET /game/settlements
+///
+/// Lists the player's settlements.
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_settlements(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	let settlements = settlement_operations::list(&mut conn, &player.id)?;
+	debug!("Player {} has {} settlements", player.id, settlements.len());
+	Ok(Json(SettlementListResponse {
+		settlements: settlements.into_iter().map(SettlementDto::from).collect(),
+	}))
+}
+
+/// POST /game/settlements
+///
+/// Founds a new settlement with the starter buildings.
+#[instrument(skip(conn, settings, player))]
+#[debug_handler(state = AppState)]
+pub async fn found_settlement(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	settings: Settings,
+	player: Extension<AuthenticatedUser>,
+	Json(request): Json<FoundSettlementRequest>,
+) -> Result<impl IntoResponse> {
+	let settlement =
+		settlement_operations::found(&mut conn, &settings.settlements, &player.id, &request.name)?;
+	Ok((StatusCode::CREATED, Json(SettlementDto::from(settlement))))
+}
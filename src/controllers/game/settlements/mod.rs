@@ -0,0 +1,15 @@
+//! Settlements controller module for founding and listing settlements.
+//!
+//! Provides REST API endpoints for:
+//! - Listing the player's settlements
+//! - Founding a new settlement
+//!
+//! Other game endpoints act on the settlement named by the `X-Settlement-Id` header, or on
+//! the capital without it.
+
+mod handlers;
+mod models;
+mod routes;
+
+pub use models::*;
+pub use routes::*;
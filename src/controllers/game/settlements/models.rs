@@ -0,0 +1,43 @@
+//! Request and response DTOs for the settlements API endpoints.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::settlement::{Settlement, SettlementKey};
+
+// === Request DTOs ===
+
+/// Request body for POST /settlements
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FoundSettlementRequest {
+	pub name: String,
+}
+
+// === Response DTOs ===
+
+/// A settlement of the player.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SettlementDto {
+	pub id: SettlementKey,
+	pub name: String,
+	pub is_capital: bool,
+	pub created_at: DateTime<Utc>,
+}
+
+impl From<Settlement> for SettlementDto {
+	fn from(settlement: Settlement) -> Self {
+		Self {
+			id: settlement.id,
+			name: settlement.name,
+			is_capital: settlement.is_capital,
+			created_at: settlement.created_at,
+		}
+	}
+}
+
+/// Response body for GET /settlements
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SettlementListResponse {
+	/// The capital first, the others in the order they were founded
+	pub settlements: Vec<SettlementDto>,
+}
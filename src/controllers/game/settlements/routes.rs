@@ -0,0 +1,16 @@
+//! Route definitions for the settlements API endpoints.
+
+use axum::Router;
+use axum::routing::get;
+
+use crate::controllers::game::settlements::handlers::*;
+use crate::domain::app_state::AppState;
+
+/// Returns a router with all settlements routes.
+///
+/// Routes:
+/// - `GET /settlements` - List the player's settlements
+/// - `POST /settlements` - Found a new settlement
+pub fn settlements_routes() -> Router<AppState> {
+	Router::new().route("/settlements", get(get_settlements).post(found_settlement))
+}
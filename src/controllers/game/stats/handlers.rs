@@ -0,0 +1,41 @@
+//! Request handlers for the stats API endpoints.
+
+use axum::extract::{Query, State};
+use axum::response::IntoResponse;
+use axum::{Extension, Json, debug_handler};
+use tracing::{debug, instrument};
+
+use crate::Result;
+use crate::controllers::game::stats::models::*;
+use crate::db::extractor::DatabaseConnection;
+use crate::domain::app_state::{AppActivityCache, AppState};
+use crate::domain::auth::AuthenticatedUser;
+use crate::game::activity_operations::{self, ActivityWindow};
+
+/// GET /game/stats/activity
+///
+/// Returns the units trained, buildings upgraded, battles fought and resources spent by
+/// the player per day over the requested window.
+#[instrument(skip(conn, cache, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_activity(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(cache): State<AppActivityCache>,
+	player: Extension<AuthenticatedUser>,
+	Query(query): Query<ActivityQuery>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	let window = match query.window {
+		Some(window) => window.parse::<ActivityWindow>()?,
+		None => ActivityWindow::default(),
+	};
+	debug!(
+		"Getting {} days of activity for player {}",
+		window.days(),
+		player_id
+	);
+
+	let activity = activity_operations::get_player_activity(&mut conn, &cache, &player_id, window)?;
+
+	Ok(Json(ActivityResponse::from(activity.as_ref())))
+}
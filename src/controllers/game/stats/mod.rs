@@ -0,0 +1,11 @@
+//! Stats controller module for player progress statistics.
+//!
+//! Provides REST API endpoints for:
+//! - Viewing the player's activity per day over a window of days
+
+mod handlers;
+mod models;
+mod routes;
+
+pub use models::*;
+pub use routes::*;
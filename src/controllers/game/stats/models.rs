@@ -0,0 +1,39 @@
+//! Request and response DTOs for the stats API endpoints.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::player::activity::DailyActivity;
+use crate::game::activity_operations::PlayerActivity;
+
+// === Request DTOs ===
+
+/// Query parameters for GET /stats/activity
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ActivityQuery {
+	/// Number of days to cover, like `7d`, defaults to 7 days and is capped at 90
+	pub window: Option<String>,
+}
+
+// === Response DTOs ===
+
+/// Response for GET /stats/activity
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ActivityResponse {
+	/// Number of UTC days covered, the current day included
+	pub window_days: u32,
+	/// One entry per day, oldest first
+	pub days: Vec<DailyActivity>,
+	/// When the statistics were aggregated, they may be served from cache for a minute
+	pub generated_at: DateTime<Utc>,
+}
+
+impl From<&PlayerActivity> for ActivityResponse {
+	fn from(activity: &PlayerActivity) -> Self {
+		Self {
+			window_days: activity.window.days(),
+			days: activity.days.clone(),
+			generated_at: activity.generated_at,
+		}
+	}
+}
@@ -0,0 +1,18 @@
+//! Route definitions for the stats API endpoints.
+
+use axum::Router;
+use axum::routing::get;
+
+use crate::controllers::game::stats::handlers::*;
+use crate::domain::app_state::AppState;
+
+/// Returns a router with all stats routes.
+///
+/// Routes:
+/// - `GET /stats/activity` - Get the player's activity per day
+pub fn stats_routes() -> Router<AppState> {
+	Router::new().nest(
+		"/stats",
+		Router::new().route("/activity", get(get_activity)),
+	)
+}
@@ -1,7 +1,8 @@
 //! Request handlers for the units API endpoints.
 //!
 //! Provides handlers for unit training operations including listing available units,
-//! starting training, viewing the queue, cancelling, and checking inventory.
+//! starting training, viewing the queue, cancelling, checking inventory, disbanding and
+//! upgrading units, and saving and training army presets.
 
 use std::collections::HashMap;
 
@@ -10,28 +11,49 @@ use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::{Extension, Json, debug_handler};
 use bigdecimal::ToPrimitive;
-use chrono::{TimeDelta, Utc};
+use chrono::Utc;
 use tracing::{debug, info, instrument, trace};
 
 use crate::Result;
+use crate::configuration::Settings;
 use crate::controllers::game::units::models::*;
 use crate::db::extractor::DatabaseConnection;
-use crate::db::{player_units, resources, training_queue, unit_costs, units};
-use crate::domain::app_state::{AppQueue, AppState};
+use crate::db::{
+	DbConn, player_buildings, player_units, resources, training_queue, unit_costs, units,
+};
+use crate::domain::app_state::{AppEvents, AppModifierCache, AppQueue, AppState};
 use crate::domain::auth::AuthenticatedUser;
+use crate::domain::error::ErrorBody;
+use crate::domain::events::GameEvent;
 use crate::domain::modifier::ModifierTarget;
-use crate::domain::unit::training::TrainingQueueKey;
+use crate::domain::player::PlayerKey;
+use crate::domain::unit::preset::ArmyPresetKey;
+use crate::domain::unit::training::{TrainingQueueKey, TrainingStatus};
 use crate::game::modifiers::modifier_operations;
-use crate::game::units::training_operations;
+use crate::game::units::{
+	disband_operations, preset_operations, training_operations, upgrade_operations,
+};
 
 /// GET /game/units/available?building_id={uuid}
 ///
 /// Returns all units that can be trained at the specified building, enriched with
 /// cost information, faction-modified training times, and affordability calculations.
-#[instrument(skip(conn, player))]
+#[utoipa::path(
+	get,
+	path = "/game/units/available",
+	tag = "units",
+	params(AvailableUnitsQuery),
+	responses(
+		(status = OK, description = "Units trainable at the building", body = AvailableUnitsResponse),
+		(status = NOT_FOUND, description = "Building not found", body = ErrorBody),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
+#[instrument(skip(conn, modifier_cache, player))]
 #[debug_handler(state = AppState)]
 pub async fn get_available_units(
 	DatabaseConnection(mut conn): DatabaseConnection,
+	State(modifier_cache): State<AppModifierCache>,
 	player: Extension<AuthenticatedUser>,
 	Query(query): Query<AvailableUnitsQuery>,
 ) -> Result<impl IntoResponse> {
@@ -48,15 +70,21 @@ pub async fn get_available_units(
 		&query.building_id,
 	)?;
 
-	// Get player's current resources for affordability calculation
-	let player_res = resources::get_by_player_id(&mut conn, &player_id)?;
+	// Get the resources of the building's settlement for affordability calculation
+	let settlement_id = player_buildings::get_by_id(&mut conn, &query.building_id)?.settlement_id;
+	let player_res = resources::get_by_settlement(&mut conn, &settlement_id)?;
 
 	// Get training speed modifier for this player
 	// AIDEV-NOTE: Modifier < 1.0 means faster training (e.g., Goblin 0.8 = 20% faster)
-	let training_modifier =
-		modifier_operations::calc_multiplier(&mut conn, &player_id, ModifierTarget::Training, None)
-			.map(|m| m.to_f64().unwrap_or(1.0))
-			.unwrap_or(1.0);
+	let training_modifier = modifier_operations::calc_multiplier(
+		&mut conn,
+		&modifier_cache,
+		&player_id,
+		ModifierTarget::Training,
+		None,
+	)
+	.map(|m| m.to_f64().unwrap_or(1.0))
+	.unwrap_or(1.0);
 
 	// Batch fetch all unit costs to avoid N+1 query problem
 	let unit_ids: Vec<_> = available_units.iter().map(|u| u.id).collect();
@@ -84,31 +112,12 @@ pub async fn get_available_units(
 			}
 		}
 
-		// Calculate affordability
-		let can_afford = player_res.food >= cost_dto.food
-			&& player_res.wood >= cost_dto.wood
-			&& player_res.stone >= cost_dto.stone
-			&& player_res.gold >= cost_dto.gold;
-
-		// Calculate max affordable quantity
-		let max_affordable = if !can_afford {
-			0
-		} else {
-			let mut max = i64::MAX;
-			if cost_dto.food > 0 {
-				max = max.min(player_res.food / cost_dto.food);
-			}
-			if cost_dto.wood > 0 {
-				max = max.min(player_res.wood / cost_dto.wood);
-			}
-			if cost_dto.stone > 0 {
-				max = max.min(player_res.stone / cost_dto.stone);
-			}
-			if cost_dto.gold > 0 {
-				max = max.min(player_res.gold / cost_dto.gold);
-			}
-			max
-		};
+		// Calculate affordability with the same math train-to-fill uses
+		let max_affordable = training_operations::max_affordable(
+			&player_res,
+			(cost_dto.food, cost_dto.wood, cost_dto.stone, cost_dto.gold),
+		);
+		let can_afford = max_affordable > 0;
 
 		// Calculate modified training time
 		let modified_training_seconds =
@@ -132,6 +141,7 @@ pub async fn get_available_units(
 	}
 
 	let queue_status = training_queue::get_queue_status(&mut conn, &query.building_id)?;
+	let training_mode = player_buildings::get_by_id(&mut conn, &query.building_id)?.training_mode;
 
 	trace!("Found {} available units", unit_dtos.len());
 	info!(
@@ -147,6 +157,7 @@ pub async fn get_available_units(
 		training_slots: queue_status.active_count,
 		max_training_slots: queue_status.capacity,
 		free_training_slots: queue_status.capacity - queue_status.active_count,
+		training_mode,
 	}))
 }
 
@@ -154,11 +165,25 @@ pub async fn get_available_units(
 ///
 /// Starts training units at a building. Validates resources, queue capacity,
 /// and building ownership before creating the training entry.
-#[instrument(skip(conn, job_queue, player))]
+#[utoipa::path(
+	post,
+	path = "/game/units/train",
+	tag = "units",
+	request_body = TrainUnitsRequest,
+	responses(
+		(status = CREATED, description = "The training started", body = TrainUnitsResponse),
+		(status = BAD_REQUEST, description = "The units cannot be trained", body = ErrorBody),
+		(status = NOT_FOUND, description = "Building or unit not found", body = ErrorBody),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
+#[instrument(skip(conn, job_queue, modifier_cache, events, player))]
 #[debug_handler(state = AppState)]
 pub async fn train_units(
 	DatabaseConnection(mut conn): DatabaseConnection,
 	State(job_queue): State<AppQueue>,
+	State(modifier_cache): State<AppModifierCache>,
+	State(events): State<AppEvents>,
 	player: Extension<AuthenticatedUser>,
 	Json(request): Json<TrainUnitsRequest>,
 ) -> Result<impl IntoResponse> {
@@ -171,83 +196,243 @@ pub async fn train_units(
 	// Get the unit details for the response
 	let unit = units::get_by_id(&mut conn, &request.unit_id)?;
 
-	// Get costs for the response
-	let costs = unit_costs::get_by_unit(&mut conn, &request.unit_id).unwrap_or_default();
-	let mut cost_dto = UnitCostDto::default();
-	for cost in &costs {
-		match cost.resource.as_str() {
-			"food" => cost_dto.food = cost.amount * request.quantity,
-			"wood" => cost_dto.wood = cost.amount * request.quantity,
-			"stone" => cost_dto.stone = cost.amount * request.quantity,
-			"gold" => cost_dto.gold = cost.amount * request.quantity,
-			_ => {}
-		}
-	}
-
 	// Start training via service layer (errors have proper status codes via IntoResponse)
-	// AIDEV-NOTE: completion_time is returned from start_training to ensure consistency
-	// between API response and actual job scheduling (avoids rounding discrepancies)
-	let (entry, completion_time) = training_operations::start_training(
+	// AIDEV-NOTE: the completion time and costs come from start_training, so the response
+	// matches the scheduled job and the resources actually deducted
+	let started = training_operations::start_training(
 		&mut conn,
 		&job_queue,
+		&modifier_cache,
 		&player_id,
 		&request.building_id,
 		&request.unit_id,
 		request.quantity,
 	)?;
-
-	// Calculate total_seconds from the authoritative completion_time
-	let total_seconds = (completion_time - entry.started_at).num_seconds();
+	publish_training_started(&events, &started);
 
 	info!(
 		"Started training for player {}: {} x {} units, completes at {}",
-		player_id, request.quantity, unit.name, completion_time
+		player_id, request.quantity, unit.name, started.completion_time
 	);
 
 	Ok((
 		StatusCode::CREATED,
-		Json(TrainUnitsResponse {
-			training_id: entry.id,
-			unit_id: entry.unit_id,
-			unit_name: unit.name,
-			quantity: entry.quantity,
-			started_at: entry.started_at,
-			completion_time,
-			total_training_seconds: total_seconds,
-			resources_spent: cost_dto,
+		Json(TrainUnitsResponse::new(unit.name, started)),
+	))
+}
+
+/// POST /game/units/train/fill
+///
+/// Trains as many units as the player's resources allow, optionally capped, at a building
+/// with a free training slot. The quantity is computed server-side with the same math as
+/// the `max_affordable` of the availability endpoint.
+#[utoipa::path(
+	post,
+	path = "/game/units/train/fill",
+	tag = "units",
+	request_body = TrainToFillRequest,
+	responses(
+		(status = CREATED, description = "The training started", body = TrainUnitsResponse),
+		(status = BAD_REQUEST, description = "The units cannot be trained", body = ErrorBody),
+		(status = NOT_FOUND, description = "Building or unit not found", body = ErrorBody),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
+#[instrument(skip(conn, job_queue, modifier_cache, events, player))]
+#[debug_handler(state = AppState)]
+pub async fn train_units_to_fill(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(job_queue): State<AppQueue>,
+	State(modifier_cache): State<AppModifierCache>,
+	State(events): State<AppEvents>,
+	player: Extension<AuthenticatedUser>,
+	Json(request): Json<TrainToFillRequest>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!(
+		"Filling training for player {}: unit {} (max {:?}) at building {}",
+		player_id, request.unit_id, request.max_quantity, request.building_id
+	);
+
+	let unit = units::get_by_id(&mut conn, &request.unit_id)?;
+	let started = training_operations::train_to_fill(
+		&mut conn,
+		&job_queue,
+		&modifier_cache,
+		&player_id,
+		&request.building_id,
+		&request.unit_id,
+		request.max_quantity,
+	)?;
+	publish_training_started(&events, &started);
+
+	info!(
+		"Started training for player {}: {} x {} units to fill, completes at {}",
+		player_id, started.entry.quantity, unit.name, started.completion_time
+	);
+
+	Ok((
+		StatusCode::CREATED,
+		Json(TrainUnitsResponse::new(unit.name, started)),
+	))
+}
+
+/// POST /game/units/train/batch
+///
+/// Starts trainings of several units at once. The total cost and the queue capacity of
+/// every building are checked for all orders together, and either all trainings start or
+/// none of them.
+#[utoipa::path(
+	post,
+	path = "/game/units/train/batch",
+	tag = "units",
+	request_body = TrainBatchRequest,
+	responses(
+		(status = CREATED, description = "All trainings started", body = TrainBatchResponse),
+		(status = BAD_REQUEST, description = "Any of the units cannot be trained", body = ErrorBody),
+		(status = NOT_FOUND, description = "Building or unit not found", body = ErrorBody),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
+#[instrument(skip(conn, job_queue, modifier_cache, events, player))]
+#[debug_handler(state = AppState)]
+pub async fn train_units_batch(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(job_queue): State<AppQueue>,
+	State(modifier_cache): State<AppModifierCache>,
+	State(events): State<AppEvents>,
+	player: Extension<AuthenticatedUser>,
+	Json(request): Json<TrainBatchRequest>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!(
+		"Starting {} trainings for player {}",
+		request.orders.len(),
+		player_id
+	);
+
+	let orders: Vec<_> = request
+		.orders
+		.iter()
+		.map(|order| training_operations::TrainingOrder {
+			building_id: order.building_id,
+			unit_id: order.unit_id,
+			quantity: order.quantity,
+		})
+		.collect();
+	let started = training_operations::start_training_batch(
+		&mut conn,
+		&job_queue,
+		&modifier_cache,
+		&player_id,
+		&orders,
+	)?;
+
+	// Batch fetch the units for their names
+	let unit_ids: Vec<_> = orders.iter().map(|order| order.unit_id).collect();
+	let units_map: HashMap<_, _> = units::get_all_by_id(&mut conn, &unit_ids)?
+		.into_iter()
+		.map(|u| (u.id, u.name))
+		.collect();
+	let trainings = started
+		.into_iter()
+		.map(|started| {
+			let unit_name = units_map
+				.get(&started.entry.unit_id)
+				.cloned()
+				.unwrap_or_default();
+			(unit_name, started)
+		})
+		.collect();
+	let (training_dtos, spent) = publish_training_batch(&events, trainings);
+
+	info!(
+		"Started {} trainings for player {}",
+		training_dtos.len(),
+		player_id
+	);
+	Ok((
+		StatusCode::CREATED,
+		Json(TrainBatchResponse {
+			trainings: training_dtos,
+			resources_spent: spent,
 		}),
 	))
 }
 
+/// Tells connected clients about a training that was just started.
+fn publish_training_started(events: &AppEvents, started: &training_operations::TrainingStarted) {
+	events.publish(GameEvent::TrainingStarted {
+		player_id: started.entry.player_id,
+		training_id: started.entry.id,
+		unit_id: started.entry.unit_id,
+		quantity: started.entry.quantity,
+		completes_at: started.completion_time,
+	});
+}
+
+/// Tells connected clients about trainings started together, given with the names of their
+/// units, and returns their responses with the resources spent on all of them.
+fn publish_training_batch(
+	events: &AppEvents,
+	trainings: Vec<(String, training_operations::TrainingStarted)>,
+) -> (Vec<TrainUnitsResponse>, UnitCostDto) {
+	let mut spent = (0, 0, 0, 0);
+	let mut training_dtos = Vec::with_capacity(trainings.len());
+	for (unit_name, started) in trainings {
+		publish_training_started(events, &started);
+		let costs = started.costs;
+		spent = (
+			spent.0 + costs.0,
+			spent.1 + costs.1,
+			spent.2 + costs.2,
+			spent.3 + costs.3,
+		);
+		training_dtos.push(TrainUnitsResponse::new(unit_name, started));
+	}
+	(training_dtos, UnitCostDto::from_tuple(spent))
+}
+
 /// GET /game/units/queue
 ///
 /// Returns the player's active training queue with progress calculations
 /// for each entry. Entries are sorted by start time.
+#[utoipa::path(
+	get,
+	path = "/game/units/queue",
+	tag = "units",
+	responses(
+		(status = OK, description = "The training queue", body = TrainingQueueResponse),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
 #[instrument(skip(conn, player))]
 #[debug_handler(state = AppState)]
 pub async fn get_training_queue(
 	DatabaseConnection(mut conn): DatabaseConnection,
 	player: Extension<AuthenticatedUser>,
 ) -> Result<impl IntoResponse> {
-	let player_id = player.id;
-	debug!("Getting training queue for player {}", player_id);
+	debug!("Getting training queue for player {}", player.id);
+	Ok(Json(load_training_queue(&mut conn, &player.id)?))
+}
 
+/// Loads the active training queue of a player, see [`get_training_queue`].
+pub(crate) fn load_training_queue(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+) -> Result<TrainingQueueResponse> {
 	// Get active training entries
-	let entries = training_queue::get_active_for_player(&mut conn, &player_id)?;
+	let entries = training_queue::get_active_for_player(conn, player_id)?;
 
 	// Batch fetch all units to avoid N+1 query problem
 	let unit_ids: Vec<_> = entries.iter().map(|e| e.unit_id).collect();
-	let units_list = units::get_all_by_id(&mut conn, &unit_ids)?;
+	let units_list = units::get_all_by_id(conn, &unit_ids)?;
 	let units_map: HashMap<_, _> = units_list.into_iter().map(|u| (u.id, u)).collect();
 
-	// Get training modifier for progress calculations
-	// AIDEV-NOTE: Progress uses faction-modified duration, not base duration
-	let training_modifier =
-		modifier_operations::calc_multiplier(&mut conn, &player_id, ModifierTarget::Training, None)
-			.map(|m| m.to_f64().unwrap_or(1.0))
-			.unwrap_or(1.0);
-
 	let now = Utc::now();
+	// AIDEV-NOTE: pending trainings of sequential queues are estimated to run back to back
+	// after the training of their building, recalculated on every request
+	let estimates = training_operations::estimate_queue(&entries, now);
 	let mut entry_dtos = Vec::with_capacity(entries.len());
 
 	for entry in &entries {
@@ -257,19 +442,15 @@ pub async fn get_training_queue(
 			None => continue, // Skip entries with missing units
 		};
 
-		// Calculate total training time with modifier
-		let total_seconds =
-			(unit.base_training_seconds as f64 * training_modifier * entry.quantity as f64) as i64;
-		let estimated_completion = entry.started_at + TimeDelta::seconds(total_seconds);
-
-		// Calculate progress
-		let elapsed_seconds = (now - entry.started_at).num_seconds().max(0);
-		let progress_percent = if total_seconds > 0 {
-			((elapsed_seconds as f64 / total_seconds as f64) * 100.0).min(100.0)
-		} else {
-			100.0
-		};
-		let seconds_remaining = (total_seconds - elapsed_seconds).max(0);
+		// AIDEV-NOTE: progress uses the modifier and duration stored at start, modifier
+		// changes since then do not move it
+		let progress_percent = training_operations::training_progress(entry, now) * 100.0;
+		let (estimated_start, estimated_completion) = estimates
+			.get(&entry.id)
+			.map_or((entry.started_at, entry.completes_at), |estimate| {
+				(estimate.starts_at, estimate.completes_at)
+			});
+		let seconds_remaining = (estimated_completion - now).num_seconds().max(0);
 
 		let dto = TrainingQueueEntryDto {
 			id: entry.id,
@@ -280,15 +461,18 @@ pub async fn get_training_queue(
 			quantity: entry.quantity,
 			started_at: entry.started_at,
 			status: entry.status,
+			estimated_start,
 			estimated_completion,
 			progress_percent,
 			seconds_remaining,
+			training_modifier: entry.training_modifier.to_f64().unwrap_or(1.0),
+			seconds_per_unit: entry.seconds_per_unit,
 		};
 		entry_dtos.push(dto);
 	}
 
-	// Sort by started_at for consistent ordering
-	entry_dtos.sort_by_key(|a| a.started_at);
+	// Sort by estimated start, pending trainings after the ones they wait for
+	entry_dtos.sort_by_key(|a| (a.estimated_start, a.started_at));
 
 	let total = entry_dtos.len();
 	trace!("Found {} training queue entries", total);
@@ -297,16 +481,29 @@ pub async fn get_training_queue(
 		total, player_id
 	);
 
-	Ok(Json(TrainingQueueResponse {
+	Ok(TrainingQueueResponse {
 		entries: entry_dtos,
 		total_entries: total,
-	}))
+	})
 }
 
-/// DELETE /game/units/queue/{training_id}
+/// DELETE /game/units/queue/{training_id}?quantity=n
 ///
 /// Cancels an in-progress or pending training entry and refunds a portion
-/// of the resources based on remaining time.
+/// of the resources based on remaining time. With a quantity, only that many
+/// units are cancelled and the rest keep training.
+#[utoipa::path(
+	delete,
+	path = "/game/units/queue/{training_id}",
+	tag = "units",
+	params(("training_id" = Uuid, Path, description = "Training to cancel"), CancelTrainingQuery),
+	responses(
+		(status = OK, description = "The training was cancelled", body = CancelTrainingResponse),
+		(status = BAD_REQUEST, description = "The training cannot be cancelled", body = ErrorBody),
+		(status = NOT_FOUND, description = "Training not found", body = ErrorBody),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
 #[instrument(skip(conn, job_queue, player))]
 #[debug_handler(state = AppState)]
 pub async fn cancel_training(
@@ -314,6 +511,7 @@ pub async fn cancel_training(
 	State(job_queue): State<AppQueue>,
 	player: Extension<AuthenticatedUser>,
 	Path(training_id): Path<TrainingQueueKey>,
+	Query(query): Query<CancelTrainingQuery>,
 ) -> Result<impl IntoResponse> {
 	let player_id = player.id;
 	debug!(
@@ -322,17 +520,39 @@ pub async fn cancel_training(
 	);
 
 	// Cancel training via service layer (returns entry and refund tuple)
-	let (cancelled_entry, refund) =
-		training_operations::cancel_training(&mut conn, &job_queue, &player_id, &training_id)?;
+	let (entry, refund) = match query.quantity {
+		Some(quantity) => training_operations::cancel_training_units(
+			&mut conn,
+			&job_queue,
+			&player_id,
+			&training_id,
+			quantity,
+		)?,
+		None => {
+			training_operations::cancel_training(&mut conn, &job_queue, &player_id, &training_id)?
+		}
+	};
 
 	info!(
 		"Cancelled training {} for player {}, refunded {:?}",
 		training_id, player_id, refund
 	);
 
+	// A fully cancelled entry keeps its quantity, a reduced one holds what is left
+	let (cancelled_quantity, remaining_quantity, estimated_completion) =
+		if entry.status == TrainingStatus::Cancelled {
+			(entry.quantity, 0, None)
+		} else {
+			let cancelled = query.quantity.unwrap_or_default();
+			(cancelled, entry.quantity, Some(entry.completes_at))
+		};
+
 	Ok(Json(CancelTrainingResponse {
-		training_id: cancelled_entry.id,
-		status: cancelled_entry.status,
+		training_id: entry.id,
+		status: entry.status,
+		cancelled_quantity,
+		remaining_quantity,
+		estimated_completion,
 		refunded: UnitCostDto::from_tuple(refund),
 	}))
 }
@@ -340,21 +560,36 @@ pub async fn cancel_training(
 /// GET /game/units/inventory
 ///
 /// Returns all units owned by the player with their quantities.
+#[utoipa::path(
+	get,
+	path = "/game/units/inventory",
+	tag = "units",
+	responses(
+		(status = OK, description = "Units of the player", body = PlayerUnitsResponse),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
 #[instrument(skip(conn, player))]
 #[debug_handler(state = AppState)]
 pub async fn get_player_inventory(
 	DatabaseConnection(mut conn): DatabaseConnection,
 	player: Extension<AuthenticatedUser>,
 ) -> Result<impl IntoResponse> {
-	let player_id = player.id;
-	debug!("Getting unit inventory for player {}", player_id);
+	debug!("Getting unit inventory for player {}", player.id);
+	Ok(Json(load_player_units(&mut conn, &player.id)?))
+}
 
+/// Loads the unit inventory of a player, see [`get_player_inventory`].
+pub(crate) fn load_player_units(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+) -> Result<PlayerUnitsResponse> {
 	// Get all player units
-	let player_units_list = player_units::get_for_player(&mut conn, &player_id)?;
+	let player_units_list = player_units::get_for_player(conn, player_id)?;
 
 	// Batch fetch all units to avoid N+1 query problem
 	let unit_ids: Vec<_> = player_units_list.iter().map(|pu| pu.unit_id).collect();
-	let units_list = units::get_all_by_id(&mut conn, &unit_ids)?;
+	let units_list = units::get_all_by_id(conn, &unit_ids)?;
 	let units_map: HashMap<_, _> = units_list.into_iter().map(|u| (u.id, u)).collect();
 
 	let mut unit_dtos = Vec::with_capacity(player_units_list.len());
@@ -373,6 +608,7 @@ pub async fn get_player_inventory(
 			unit_id: pu.unit_id,
 			unit_name: unit.name.clone(),
 			unit_type: unit.unit_type,
+			tier: unit.tier,
 			quantity: pu.quantity,
 		};
 		unit_dtos.push(dto);
@@ -386,8 +622,332 @@ pub async fn get_player_inventory(
 		player_id
 	);
 
-	Ok(Json(PlayerUnitsResponse {
+	Ok(PlayerUnitsResponse {
 		units: unit_dtos,
 		total_units,
+	})
+}
+
+/// PUT /game/units/training-mode
+///
+/// Sets whether the trainings queued at a building run at the same time or one after
+/// another. The mode only changes while the building has no trainings queued.
+#[utoipa::path(
+	put,
+	path = "/game/units/training-mode",
+	tag = "units",
+	request_body = SetTrainingModeRequest,
+	responses(
+		(status = OK, description = "The training mode of the building", body = TrainingModeResponse),
+		(status = BAD_REQUEST, description = "The building has trainings queued", body = ErrorBody),
+		(status = NOT_FOUND, description = "Building not found", body = ErrorBody),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn set_training_mode(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Json(request): Json<SetTrainingModeRequest>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!(
+		"Setting training mode of building {} to {:?} for player {}",
+		request.building_id, request.mode, player_id
+	);
+
+	let building = training_operations::set_training_mode(
+		&mut conn,
+		&player_id,
+		&request.building_id,
+		request.mode,
+	)?;
+
+	info!(
+		"Building {} of player {} now trains in {:?} mode",
+		building.id, player_id, building.training_mode
+	);
+	Ok(Json(TrainingModeResponse {
+		building_id: building.id,
+		training_mode: building.training_mode,
+	}))
+}
+
+/// POST /game/units/disband
+///
+/// Dismisses units of the player, refunding a share of their costs to the capital and
+/// freeing their upkeep.
+#[utoipa::path(
+	post,
+	path = "/game/units/disband",
+	tag = "units",
+	request_body = DisbandUnitsRequest,
+	responses(
+		(status = OK, description = "The units were disbanded", body = DisbandUnitsResponse),
+		(status = BAD_REQUEST, description = "The player does not own enough units", body = ErrorBody),
+		(status = NOT_FOUND, description = "Unit not found", body = ErrorBody),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
+#[instrument(skip(conn, settings, player))]
+#[debug_handler(state = AppState)]
+pub async fn disband_units(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	settings: Settings,
+	player: Extension<AuthenticatedUser>,
+	Json(request): Json<DisbandUnitsRequest>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!(
+		"Disbanding {} units of {} for player {}",
+		request.quantity, request.unit_id, player_id
+	);
+
+	let disbanded = disband_operations::disband_units(
+		&mut conn,
+		&settings.units,
+		&player_id,
+		&request.unit_id,
+		request.quantity,
+	)?;
+
+	Ok(Json(DisbandUnitsResponse::from(disbanded)))
+}
+
+/// GET /game/units/upgrades
+///
+/// Returns every unit upgrade of the Blacksmith, with the units the player owns and whether
+/// their capital unlocked the upgrade.
+#[utoipa::path(
+	get,
+	path = "/game/units/upgrades",
+	tag = "units",
+	responses(
+		(status = OK, description = "Unit upgrades of the Blacksmith", body = UnitUpgradesResponse),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_unit_upgrades(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!("Getting unit upgrades for player {}", player_id);
+
+	let upgrades = upgrade_operations::list_upgrades(&mut conn, &player_id)?;
+
+	info!(
+		"Retrieved {} unit upgrades for player {}",
+		upgrades.len(),
+		player_id
+	);
+	Ok(Json(UnitUpgradesResponse {
+		upgrades: upgrades.into_iter().map(UnitUpgradeDto::from).collect(),
 	}))
 }
+
+/// POST /game/units/upgrade
+///
+/// Upgrades units to the unit of the next tier, paying the upgrade from the capital.
+#[utoipa::path(
+	post,
+	path = "/game/units/upgrade",
+	tag = "units",
+	request_body = UpgradeUnitsRequest,
+	responses(
+		(status = OK, description = "The units were upgraded", body = UpgradeUnitsResponse),
+		(status = BAD_REQUEST, description = "The units cannot be upgraded", body = ErrorBody),
+		(status = NOT_FOUND, description = "Unit or upgrade not found", body = ErrorBody),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn upgrade_units(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Json(request): Json<UpgradeUnitsRequest>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!(
+		"Upgrading {} units of {} for player {}",
+		request.quantity, request.unit_id, player_id
+	);
+
+	let upgraded = upgrade_operations::upgrade_units(
+		&mut conn,
+		&player_id,
+		&request.unit_id,
+		request.quantity,
+	)?;
+
+	Ok(Json(UpgradeUnitsResponse::from(upgraded)))
+}
+
+/// GET /game/units/presets
+///
+/// Returns the army presets of the player, sorted by name.
+#[utoipa::path(
+	get,
+	path = "/game/units/presets",
+	tag = "units",
+	responses(
+		(status = OK, description = "Army presets of the player", body = ArmyPresetsResponse),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn get_army_presets(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!("Getting army presets for player {}", player_id);
+
+	let presets = preset_operations::list_presets(&mut conn, &player_id)?;
+
+	info!(
+		"Retrieved {} army presets for player {}",
+		presets.len(),
+		player_id
+	);
+	Ok(Json(ArmyPresetsResponse {
+		presets: presets.into_iter().map(ArmyPresetDto::from).collect(),
+	}))
+}
+
+/// POST /game/units/presets
+///
+/// Saves a named army composition to train again in one go.
+#[utoipa::path(
+	post,
+	path = "/game/units/presets",
+	tag = "units",
+	request_body = CreatePresetRequest,
+	responses(
+		(status = CREATED, description = "The saved preset", body = ArmyPresetDto),
+		(status = BAD_REQUEST, description = "Invalid preset", body = ErrorBody),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn create_army_preset(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Json(request): Json<CreatePresetRequest>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!(
+		"Saving army preset {} for player {}",
+		request.name, player_id
+	);
+
+	let units: Vec<_> = request
+		.units
+		.iter()
+		.map(|unit| (unit.unit_id, unit.quantity))
+		.collect();
+	let preset = preset_operations::create_preset(&mut conn, &player_id, &request.name, &units)?;
+
+	Ok((StatusCode::CREATED, Json(ArmyPresetDto::from(preset))))
+}
+
+/// DELETE /game/units/presets/{preset_id}
+///
+/// Deletes an army preset of the player.
+#[utoipa::path(
+	delete,
+	path = "/game/units/presets/{preset_id}",
+	tag = "units",
+	params(("preset_id" = Uuid, Path, description = "Preset to delete")),
+	responses(
+		(status = NO_CONTENT, description = "The preset was deleted"),
+		(status = NOT_FOUND, description = "Preset not found", body = ErrorBody),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
+#[instrument(skip(conn, player))]
+#[debug_handler(state = AppState)]
+pub async fn delete_army_preset(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Path(preset_id): Path<ArmyPresetKey>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!(
+		"Deleting army preset {} for player {}",
+		preset_id, player_id
+	);
+
+	preset_operations::delete_preset(&mut conn, &player_id, &preset_id)?;
+
+	info!("Deleted army preset {} for player {}", preset_id, player_id);
+	Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /game/units/train/preset/{preset_id}
+///
+/// Trains every unit of an army preset at the military buildings of the capital. Either all
+/// trainings start or, if any resource or queue check fails, none of them.
+#[utoipa::path(
+	post,
+	path = "/game/units/train/preset/{preset_id}",
+	tag = "units",
+	params(("preset_id" = Uuid, Path, description = "Preset to train")),
+	responses(
+		(status = CREATED, description = "All trainings of the preset started", body = TrainPresetResponse),
+		(status = BAD_REQUEST, description = "Any of the units cannot be trained", body = ErrorBody),
+		(status = NOT_FOUND, description = "Preset not found", body = ErrorBody),
+	),
+	security(("bearer" = []), ("session" = []))
+)]
+#[instrument(skip(conn, job_queue, modifier_cache, events, player))]
+#[debug_handler(state = AppState)]
+pub async fn train_army_preset(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(job_queue): State<AppQueue>,
+	State(modifier_cache): State<AppModifierCache>,
+	State(events): State<AppEvents>,
+	player: Extension<AuthenticatedUser>,
+	Path(preset_id): Path<ArmyPresetKey>,
+) -> Result<impl IntoResponse> {
+	let player_id = player.id;
+	debug!(
+		"Training army preset {} for player {}",
+		preset_id, player_id
+	);
+
+	let trainings = preset_operations::train_preset(
+		&mut conn,
+		&job_queue,
+		&modifier_cache,
+		&player_id,
+		&preset_id,
+	)?;
+
+	let trainings = trainings
+		.into_iter()
+		.map(|(unit, started)| (unit.name, started))
+		.collect();
+	let (training_dtos, spent) = publish_training_batch(&events, trainings);
+
+	info!(
+		"Started {} trainings of army preset {} for player {}",
+		training_dtos.len(),
+		preset_id,
+		player_id
+	);
+	Ok((
+		StatusCode::CREATED,
+		Json(TrainPresetResponse {
+			preset_id,
+			trainings: training_dtos,
+			resources_spent: spent,
+		}),
+	))
+}
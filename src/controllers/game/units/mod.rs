@@ -11,4 +11,8 @@ mod handlers;
 mod models;
 mod routes;
 
+#[cfg(feature = "graphql")]
+pub(crate) use handlers::load_player_units;
+pub(crate) use handlers::load_training_queue;
+pub use models::*;
 pub use routes::*;
@@ -5,32 +5,97 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
 use crate::domain::player::buildings::PlayerBuildingKey;
-use crate::domain::unit::training::{TrainingQueueKey, TrainingStatus};
+use crate::domain::unit::preset::ArmyPresetKey;
+use crate::domain::unit::training::{TrainingMode, TrainingQueueKey, TrainingStatus};
 use crate::domain::unit::{UnitKey, UnitType};
+use crate::game::units::disband_operations::UnitsDisbanded;
+use crate::game::units::preset_operations::FullArmyPreset;
+use crate::game::units::training_operations::TrainingStarted;
+use crate::game::units::upgrade_operations::{UnitsUpgraded, UpgradeStatus};
 
 // === Request DTOs ===
 
 /// Query parameters for GET /units/available
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, IntoParams, Debug)]
 pub struct AvailableUnitsQuery {
 	pub building_id: PlayerBuildingKey,
 }
 
 /// Request body for POST /units/train
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
 pub struct TrainUnitsRequest {
 	pub building_id: PlayerBuildingKey,
 	pub unit_id: UnitKey,
 	pub quantity: i64,
 }
 
+/// Request body for POST /units/train/batch
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct TrainBatchRequest {
+	/// Trainings to start together, all of them or none
+	pub orders: Vec<TrainUnitsRequest>,
+}
+
+/// Request body for POST /units/train/fill
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct TrainToFillRequest {
+	pub building_id: PlayerBuildingKey,
+	pub unit_id: UnitKey,
+	/// Upper bound for the quantity, defaults to as many as are affordable
+	pub max_quantity: Option<i64>,
+}
+
+/// Request body for POST /units/upgrade
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct UpgradeUnitsRequest {
+	/// Unit to upgrade to the next tier
+	pub unit_id: UnitKey,
+	pub quantity: i64,
+}
+
+/// A unit of an army preset with its quantity.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+pub struct PresetUnitDto {
+	pub unit_id: UnitKey,
+	pub quantity: i64,
+}
+
+/// Request body for POST /units/presets
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct CreatePresetRequest {
+	pub name: String,
+	pub units: Vec<PresetUnitDto>,
+}
+
+/// Request body for POST /units/disband
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct DisbandUnitsRequest {
+	pub unit_id: UnitKey,
+	pub quantity: i64,
+}
+
+/// Request body for PUT /units/training-mode
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct SetTrainingModeRequest {
+	pub building_id: PlayerBuildingKey,
+	pub mode: TrainingMode,
+}
+
+/// Query parameters for DELETE /units/queue/{id}
+#[derive(Serialize, Deserialize, IntoParams, Debug, Default)]
+pub struct CancelTrainingQuery {
+	/// Number of units to cancel, defaults to the whole entry
+	pub quantity: Option<i64>,
+}
+
 // === Response DTOs ===
 
 /// Resource cost breakdown for a unit.
 /// Represents the cost to train a single unit.
-#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, Default)]
 pub struct UnitCostDto {
 	pub food: i64,
 	pub wood: i64,
@@ -52,7 +117,7 @@ impl UnitCostDto {
 
 /// A single available unit with all UI-relevant data.
 /// Used in the available units response to show what can be trained.
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
 pub struct AvailableUnitDto {
 	pub id: UnitKey,
 	pub name: String,
@@ -76,7 +141,7 @@ pub struct AvailableUnitDto {
 }
 
 /// Response for GET /units/available
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
 pub struct AvailableUnitsResponse {
 	pub building_id: PlayerBuildingKey,
 	pub units: Vec<AvailableUnitDto>,
@@ -86,10 +151,19 @@ pub struct AvailableUnitsResponse {
 	pub max_training_slots: i64,
 	/// Free training slots (max - used)
 	pub free_training_slots: i64,
+	/// Whether the queued trainings run at the same time or one after another
+	pub training_mode: TrainingMode,
+}
+
+/// Response for PUT /units/training-mode
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct TrainingModeResponse {
+	pub building_id: PlayerBuildingKey,
+	pub training_mode: TrainingMode,
 }
 
 /// Response for POST /units/train
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
 pub struct TrainUnitsResponse {
 	pub training_id: TrainingQueueKey,
 	pub unit_id: UnitKey,
@@ -104,9 +178,35 @@ pub struct TrainUnitsResponse {
 	pub resources_spent: UnitCostDto,
 }
 
+impl TrainUnitsResponse {
+	/// Creates the response for a training of the unit named `unit_name`.
+	pub fn new(unit_name: String, started: TrainingStarted) -> Self {
+		let TrainingStarted {
+			entry,
+			completion_time,
+			costs,
+		} = started;
+		Self {
+			training_id: entry.id,
+			unit_id: entry.unit_id,
+			unit_name,
+			quantity: entry.quantity,
+			started_at: entry.started_at,
+			completion_time,
+			total_training_seconds: (entry.completes_at - entry.started_at).num_seconds(),
+			resources_spent: UnitCostDto::from_tuple(costs),
+		}
+	}
+}
+
 /// A single training queue entry with progress information.
 /// Includes all data needed for client-side progress bar rendering.
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+#[cfg_attr(
+	feature = "graphql",
+	derive(async_graphql::SimpleObject),
+	graphql(name = "TrainingQueueEntry")
+)]
 pub struct TrainingQueueEntryDto {
 	pub id: TrainingQueueKey,
 	pub building_id: PlayerBuildingKey,
@@ -116,33 +216,55 @@ pub struct TrainingQueueEntryDto {
 	pub quantity: i64,
 	pub started_at: DateTime<Utc>,
 	pub status: TrainingStatus,
+	/// Estimated start time, later than `started_at` for a training pending in a sequential queue
+	pub estimated_start: DateTime<Utc>,
 	/// Estimated completion time (ISO 8601 format)
 	pub estimated_completion: DateTime<Utc>,
 	/// Progress percentage (0.0 - 100.0)
-	/// AIDEV-NOTE: Progress uses faction-modified duration, not base duration
+	/// AIDEV-NOTE: Progress uses the duration recorded at start, not the current modifiers
 	pub progress_percent: f64,
 	/// Seconds remaining until completion
 	pub seconds_remaining: i64,
+	/// Training speed multiplier applied when the training started
+	pub training_modifier: f64,
+	/// Modified training time of a single unit
+	pub seconds_per_unit: i64,
 }
 
 /// Response for GET /units/queue
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+#[cfg_attr(
+	feature = "graphql",
+	derive(async_graphql::SimpleObject),
+	graphql(name = "TrainingQueue")
+)]
 pub struct TrainingQueueResponse {
 	pub entries: Vec<TrainingQueueEntryDto>,
 	pub total_entries: usize,
 }
 
 /// A single player unit in the inventory.
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+#[cfg_attr(
+	feature = "graphql",
+	derive(async_graphql::SimpleObject),
+	graphql(name = "PlayerUnit")
+)]
 pub struct PlayerUnitDto {
 	pub unit_id: UnitKey,
 	pub unit_name: String,
 	pub unit_type: UnitType,
+	pub tier: i32,
 	pub quantity: i64,
 }
 
 /// Response for GET /units/inventory
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+#[cfg_attr(
+	feature = "graphql",
+	derive(async_graphql::SimpleObject),
+	graphql(name = "PlayerUnits")
+)]
 pub struct PlayerUnitsResponse {
 	pub units: Vec<PlayerUnitDto>,
 	/// Total count of all units owned by the player
@@ -150,11 +272,168 @@ pub struct PlayerUnitsResponse {
 }
 
 /// Response for DELETE /units/queue/{id}
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
 pub struct CancelTrainingResponse {
 	pub training_id: TrainingQueueKey,
 	pub status: TrainingStatus,
+	/// Number of units that were cancelled
+	pub cancelled_quantity: i64,
+	/// Number of units still in training
+	pub remaining_quantity: i64,
+	/// Completion time of the remaining units, if any are left
+	pub estimated_completion: Option<DateTime<Utc>>,
 	/// Resources refunded to the player
-	/// AIDEV-NOTE: Refund is 80% * remaining_ratio, where remaining_ratio = (1 - elapsed/total)
+	/// AIDEV-NOTE: Refund is 80% * remaining_ratio of the cancelled units, where
+	/// remaining_ratio is the share of their training time that had not elapsed
+	pub refunded: UnitCostDto,
+}
+
+/// Response for POST /units/disband
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct DisbandUnitsResponse {
+	pub unit_id: UnitKey,
+	pub unit_name: String,
+	pub quantity: i64,
+	/// Units of the type the player owns after disbanding
+	pub remaining: i64,
+	/// Resources refunded to the capital
 	pub refunded: UnitCostDto,
 }
+
+impl From<UnitsDisbanded> for DisbandUnitsResponse {
+	fn from(disbanded: UnitsDisbanded) -> Self {
+		Self {
+			unit_id: disbanded.unit.id,
+			unit_name: disbanded.unit.name,
+			quantity: disbanded.quantity,
+			remaining: disbanded.remaining,
+			refunded: UnitCostDto::from_tuple(disbanded.refund),
+		}
+	}
+}
+
+/// A unit upgrade of the Blacksmith.
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct UnitUpgradeDto {
+	pub unit_id: UnitKey,
+	pub unit_name: String,
+	pub upgraded_unit_id: UnitKey,
+	pub upgraded_unit_name: String,
+	/// Tier of the upgraded unit
+	pub tier: i32,
+	/// Attack and defense of a single upgraded unit, tier bonus included
+	pub attack: i64,
+	pub defense: i64,
+	/// Level of the Blacksmith needed for the upgrade
+	pub blacksmith_level: i32,
+	/// Level of the military research building needed for the upgrade
+	pub research_level: i32,
+	/// Resource cost per single unit upgraded
+	pub cost: UnitCostDto,
+	/// Units the player owns that can be upgraded
+	pub owned: i64,
+	/// Whether the capital's Blacksmith and research are high enough for the upgrade
+	pub unlocked: bool,
+}
+
+impl From<UpgradeStatus> for UnitUpgradeDto {
+	fn from(status: UpgradeStatus) -> Self {
+		Self {
+			unit_id: status.unit.id,
+			unit_name: status.unit.name,
+			upgraded_unit_id: status.upgraded_unit.id,
+			attack: status.upgraded_unit.attack(),
+			defense: status.upgraded_unit.defense(),
+			upgraded_unit_name: status.upgraded_unit.name,
+			tier: status.upgraded_unit.tier,
+			blacksmith_level: status.upgrade.blacksmith_level,
+			research_level: status.upgrade.research_level,
+			cost: UnitCostDto::from_tuple(status.upgrade.costs(1)),
+			owned: status.owned,
+			unlocked: status.unlocked,
+		}
+	}
+}
+
+/// Response for GET /units/upgrades
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct UnitUpgradesResponse {
+	pub upgrades: Vec<UnitUpgradeDto>,
+}
+
+/// Response for POST /units/upgrade
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct UpgradeUnitsResponse {
+	pub unit_id: UnitKey,
+	pub upgraded_unit_id: UnitKey,
+	pub upgraded_unit_name: String,
+	pub quantity: i64,
+	/// Units of the upgraded unit the player owns after the upgrade
+	pub upgraded_total: i64,
+	/// Total resources spent for the upgrade
+	pub resources_spent: UnitCostDto,
+}
+
+impl From<UnitsUpgraded> for UpgradeUnitsResponse {
+	fn from(upgraded: UnitsUpgraded) -> Self {
+		Self {
+			unit_id: upgraded.unit.id,
+			upgraded_unit_id: upgraded.upgraded_unit.id,
+			upgraded_unit_name: upgraded.upgraded_unit.name,
+			quantity: upgraded.quantity,
+			upgraded_total: upgraded.upgraded.quantity,
+			resources_spent: UnitCostDto::from_tuple(upgraded.costs),
+		}
+	}
+}
+
+/// A named army composition of the player.
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct ArmyPresetDto {
+	pub id: ArmyPresetKey,
+	pub name: String,
+	pub units: Vec<PresetUnitDto>,
+	pub created_at: DateTime<Utc>,
+}
+
+impl From<FullArmyPreset> for ArmyPresetDto {
+	fn from((preset, units): FullArmyPreset) -> Self {
+		Self {
+			id: preset.id,
+			name: preset.name,
+			units: units
+				.into_iter()
+				.map(|unit| PresetUnitDto {
+					unit_id: unit.unit_id,
+					quantity: unit.quantity,
+				})
+				.collect(),
+			created_at: preset.created_at,
+		}
+	}
+}
+
+/// Response for GET /units/presets
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct ArmyPresetsResponse {
+	pub presets: Vec<ArmyPresetDto>,
+}
+
+/// Response for POST /units/train/batch
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct TrainBatchResponse {
+	/// One training per order, in the order of the request
+	pub trainings: Vec<TrainUnitsResponse>,
+	/// Total resources spent for all trainings
+	pub resources_spent: UnitCostDto,
+}
+
+/// Response for POST /units/train/preset/{id}
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct TrainPresetResponse {
+	pub preset_id: ArmyPresetKey,
+	/// One training per unit of the preset
+	pub trainings: Vec<TrainUnitsResponse>,
+	/// Total resources spent for all trainings
+	pub resources_spent: UnitCostDto,
+}
@@ -1,7 +1,8 @@
 //! Route definitions for the units API endpoints.
 
 use axum::Router;
-use axum::routing::{delete, get, post};
+use axum::routing::{delete, get, post, put};
+use utoipa::OpenApi;
 
 use crate::controllers::game::units::handlers::*;
 use crate::domain::app_state::AppState;
@@ -11,17 +12,57 @@ use crate::domain::app_state::AppState;
 /// Routes:
 /// - `GET /units/available?building_id={uuid}` - Get trainable units for a building
 /// - `POST /units/train` - Start training units
+/// - `POST /units/train/fill` - Train as many units as are affordable
+/// - `POST /units/train/batch` - Start trainings of several units at once
 /// - `GET /units/queue` - Get player's training queue
 /// - `DELETE /units/queue/{training_id}` - Cancel training
+/// - `PUT /units/training-mode` - Train a building's queue in parallel or sequentially
 /// - `GET /units/inventory` - Get player's unit counts
+/// - `POST /units/disband` - Dismiss units for a partial refund
+/// - `GET /units/upgrades` - Get the unit upgrades of the Blacksmith
+/// - `POST /units/upgrade` - Upgrade units to the next tier
+/// - `GET /units/presets` - Get player's army presets
+/// - `POST /units/presets` - Save an army preset
+/// - `DELETE /units/presets/{preset_id}` - Delete an army preset
+/// - `POST /units/train/preset/{preset_id}` - Train every unit of an army preset
 pub fn units_routes() -> Router<AppState> {
 	Router::new().nest(
 		"/units",
 		Router::new()
 			.route("/available", get(get_available_units))
 			.route("/train", post(train_units))
+			.route("/train/fill", post(train_units_to_fill))
+			.route("/train/batch", post(train_units_batch))
 			.route("/queue", get(get_training_queue))
 			.route("/queue/{training_id}", delete(cancel_training))
-			.route("/inventory", get(get_player_inventory)),
+			.route("/training-mode", put(set_training_mode))
+			.route("/inventory", get(get_player_inventory))
+			.route("/disband", post(disband_units))
+			.route("/upgrades", get(get_unit_upgrades))
+			.route("/upgrade", post(upgrade_units))
+			.route("/presets", get(get_army_presets).post(create_army_preset))
+			.route("/presets/{preset_id}", delete(delete_army_preset))
+			.route("/train/preset/{preset_id}", post(train_army_preset)),
 	)
 }
+
+/// OpenAPI document of the units routes.
+#[derive(OpenApi)]
+#[openapi(paths(
+	get_available_units,
+	train_units,
+	train_units_to_fill,
+	train_units_batch,
+	get_training_queue,
+	cancel_training,
+	set_training_mode,
+	get_player_inventory,
+	disband_units,
+	get_unit_upgrades,
+	upgrade_units,
+	get_army_presets,
+	create_army_preset,
+	delete_army_preset,
+	train_army_preset,
+))]
+pub struct UnitsApi;
@@ -0,0 +1,34 @@
+use std::sync::LazyLock;
+
+use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use axum::extract::State;
+use axum::{Extension, debug_handler};
+use tracing::instrument;
+
+use super::loaders::with_loaders;
+use super::models::{GameSchema, game_schema};
+use crate::configuration::Settings;
+use crate::domain::app_state::{AppPool, AppState};
+use crate::domain::auth::AuthenticatedUser;
+
+/// The schema holds no state, so every request shares it.
+static SCHEMA: LazyLock<GameSchema> = LazyLock::new(game_schema);
+
+/// POST /graphql
+///
+/// Runs a query against the read model of the authenticated player, fetching nested state
+/// like the buildings of every settlement in one round trip.
+#[instrument(skip_all, fields(player_id = %player.id))]
+#[debug_handler(state = AppState)]
+pub(super) async fn graphql(
+	State(pool): State<AppPool>,
+	settings: Settings,
+	player: Extension<AuthenticatedUser>,
+	request: GraphQLRequest,
+) -> GraphQLResponse {
+	let request = request.into_inner().data(player.0);
+	SCHEMA
+		.execute(with_loaders(request, pool, settings))
+		.await
+		.into()
+}
@@ -0,0 +1,198 @@
+//! Dataloaders of the GraphQL read model.
+//!
+//! Resolvers ask the loaders for the state of players and settlements. Every loader gathers
+//! the keys requested while a query resolves and loads each of them once, over a single
+//! connection. Loaders are created for every request, so their cache never serves stale state.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use async_graphql::Request;
+use async_graphql::dataloader::{DataLoader, Loader};
+use tracing::trace;
+
+use crate::configuration::Settings;
+use crate::controllers::game::buildings::{ConstructionQueueResponse, load_construction_queue};
+use crate::controllers::game::index::{
+	BuildingsState, PlayerState, ResourcesState, get_player_buildings_data, load_player,
+	load_resources,
+};
+use crate::controllers::game::units::{
+	PlayerUnitsResponse, TrainingQueueResponse, load_player_units, load_training_queue,
+};
+use crate::db::{DbConn, settlements};
+use crate::domain::app_state::AppPool;
+use crate::domain::player::PlayerKey;
+use crate::domain::settlement::{Settlement, SettlementKey};
+use crate::{Error, Result};
+
+/// Error of a failed batch, shared by every resolver waiting on it.
+pub type LoaderError = Arc<Error>;
+
+/// Attaches a fresh set of loaders to a request.
+pub fn with_loaders(request: Request, pool: AppPool, settings: Settings) -> Request {
+	request
+		.data(DataLoader::new(
+			PlayerLoader {
+				pool: pool.clone(),
+				settings,
+			},
+			tokio::spawn,
+		))
+		.data(DataLoader::new(
+			SettlementsLoader(pool.clone()),
+			tokio::spawn,
+		))
+		.data(DataLoader::new(ResourcesLoader(pool.clone()), tokio::spawn))
+		.data(DataLoader::new(BuildingsLoader(pool.clone()), tokio::spawn))
+		.data(DataLoader::new(UnitsLoader(pool.clone()), tokio::spawn))
+		.data(DataLoader::new(
+			ConstructionQueueLoader(pool.clone()),
+			tokio::spawn,
+		))
+		.data(DataLoader::new(TrainingQueueLoader(pool), tokio::spawn))
+}
+
+/// Runs `load` with a connection of the pool.
+fn with_conn<T>(
+	pool: &AppPool,
+	load: impl FnOnce(&mut DbConn) -> Result<T>,
+) -> Result<T, LoaderError> {
+	let mut conn = pool.get().map_err(|err| Arc::new(Error::from(err)))?;
+	load(&mut conn).map_err(Arc::new)
+}
+
+/// Loads every key with `load`, one after the other over the same connection.
+fn load_each<K, V>(
+	pool: &AppPool,
+	keys: &[K],
+	mut load: impl FnMut(&mut DbConn, &K) -> Result<V>,
+) -> Result<HashMap<K, V>, LoaderError>
+where
+	K: Clone + Eq + Hash,
+{
+	trace!("Loading a batch of {} keys", keys.len());
+	with_conn(pool, |conn| {
+		keys.iter()
+			.map(|key| Ok((key.clone(), load(conn, key)?)))
+			.collect()
+	})
+}
+
+/// Loads players with their active protections.
+pub struct PlayerLoader {
+	pool: AppPool,
+	settings: Settings,
+}
+
+impl Loader<PlayerKey> for PlayerLoader {
+	type Value = PlayerState;
+	type Error = LoaderError;
+
+	async fn load(
+		&self,
+		keys: &[PlayerKey],
+	) -> Result<HashMap<PlayerKey, Self::Value>, Self::Error> {
+		load_each(&self.pool, keys, |conn, player_id| {
+			load_player(conn, &self.settings, player_id)
+		})
+	}
+}
+
+/// Loads the settlements of players, their capital first.
+pub struct SettlementsLoader(AppPool);
+
+impl Loader<PlayerKey> for SettlementsLoader {
+	type Value = Vec<Settlement>;
+	type Error = LoaderError;
+
+	async fn load(
+		&self,
+		keys: &[PlayerKey],
+	) -> Result<HashMap<PlayerKey, Self::Value>, Self::Error> {
+		load_each(&self.0, keys, settlements::get_by_player)
+	}
+}
+
+/// Loads the resources of settlements.
+pub struct ResourcesLoader(AppPool);
+
+impl Loader<SettlementKey> for ResourcesLoader {
+	type Value = ResourcesState;
+	type Error = LoaderError;
+
+	async fn load(
+		&self,
+		keys: &[SettlementKey],
+	) -> Result<HashMap<SettlementKey, Self::Value>, Self::Error> {
+		with_conn(&self.0, |conn| {
+			settlements::get_all_by_id(conn, keys)?
+				.iter()
+				.map(|settlement| Ok((settlement.id, load_resources(conn, settlement)?)))
+				.collect()
+		})
+	}
+}
+
+/// Loads the buildings of settlements.
+pub struct BuildingsLoader(AppPool);
+
+impl Loader<SettlementKey> for BuildingsLoader {
+	type Value = Vec<BuildingsState>;
+	type Error = LoaderError;
+
+	async fn load(
+		&self,
+		keys: &[SettlementKey],
+	) -> Result<HashMap<SettlementKey, Self::Value>, Self::Error> {
+		load_each(&self.0, keys, |conn, settlement_id| {
+			Ok(get_player_buildings_data(conn, *settlement_id)?)
+		})
+	}
+}
+
+/// Loads the unit inventories of players.
+pub struct UnitsLoader(AppPool);
+
+impl Loader<PlayerKey> for UnitsLoader {
+	type Value = PlayerUnitsResponse;
+	type Error = LoaderError;
+
+	async fn load(
+		&self,
+		keys: &[PlayerKey],
+	) -> Result<HashMap<PlayerKey, Self::Value>, Self::Error> {
+		load_each(&self.0, keys, load_player_units)
+	}
+}
+
+/// Loads the construction queues of players.
+pub struct ConstructionQueueLoader(AppPool);
+
+impl Loader<PlayerKey> for ConstructionQueueLoader {
+	type Value = ConstructionQueueResponse;
+	type Error = LoaderError;
+
+	async fn load(
+		&self,
+		keys: &[PlayerKey],
+	) -> Result<HashMap<PlayerKey, Self::Value>, Self::Error> {
+		load_each(&self.0, keys, load_construction_queue)
+	}
+}
+
+/// Loads the training queues of players.
+pub struct TrainingQueueLoader(AppPool);
+
+impl Loader<PlayerKey> for TrainingQueueLoader {
+	type Value = TrainingQueueResponse;
+	type Error = LoaderError;
+
+	async fn load(
+		&self,
+		keys: &[PlayerKey],
+	) -> Result<HashMap<PlayerKey, Self::Value>, Self::Error> {
+		load_each(&self.0, keys, load_training_queue)
+	}
+}
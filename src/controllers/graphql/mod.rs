@@ -0,0 +1,13 @@
+//! GraphQL endpoint over the game read model, built with the `graphql` feature.
+//!
+//! Exposes the player with their settlements, resources, buildings, units and queues, so
+//! clients can fetch nested state in a single request. Fields resolve through dataloaders
+//! over the loaders of the REST endpoints.
+
+mod handlers;
+mod loaders;
+mod models;
+mod routes;
+
+pub use models::{GameSchema, game_schema};
+pub use routes::graphql_routes;
@@ -0,0 +1,167 @@
+//! Object types of the GraphQL read model.
+//!
+//! The player, their settlements and their queues resolve through the dataloaders, their
+//! fields reuse the read models of the REST endpoints.
+
+use std::hash::Hash;
+
+use async_graphql::dataloader::{DataLoader, Loader};
+use async_graphql::{Context, EmptyMutation, EmptySubscription, ErrorExtensions, Object, Schema};
+use tracing::error;
+
+use super::loaders::{
+	BuildingsLoader, ConstructionQueueLoader, LoaderError, PlayerLoader, ResourcesLoader,
+	SettlementsLoader, TrainingQueueLoader, UnitsLoader,
+};
+use crate::controllers::game::buildings::ConstructionQueueResponse;
+use crate::controllers::game::index::{
+	BuildingsState, PlayerState, ProtectionState, ResourcesState,
+};
+use crate::controllers::game::units::{PlayerUnitsResponse, TrainingQueueResponse};
+use crate::domain::auth::AuthenticatedUser;
+use crate::domain::factions::FactionCode;
+use crate::domain::player::PlayerKey;
+use crate::domain::settlement::{Settlement, SettlementKey};
+use crate::{Error, ErrorKind};
+
+/// Schema of the read-only GraphQL endpoint.
+pub type GameSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Deepest nesting of a query, the schema is only a few levels deep
+const MAX_DEPTH: usize = 8;
+/// Most fields a query selects, enough for the whole state of a player
+const MAX_COMPLEXITY: usize = 500;
+
+/// Builds the schema, the loaders and the player are attached to every request.
+pub fn game_schema() -> GameSchema {
+	Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+		.limit_depth(MAX_DEPTH)
+		.limit_complexity(MAX_COMPLEXITY)
+		.finish()
+}
+
+/// Converts an error to a GraphQL error, with the message and code clients see on the REST
+/// endpoints.
+fn graphql_error(err: &Error) -> async_graphql::Error {
+	let (status, message) = err.public_parts();
+	if status.is_server_error() {
+		// Clients only see the public message, the cause goes to the logs
+		error!("GraphQL resolver failed: {err}");
+	}
+	let code = async_graphql::Value::from_json(serde_json::json!(err.kind())).unwrap_or_default();
+	async_graphql::Error::new(message).extend_with(|_, extensions| extensions.set("code", code))
+}
+
+/// Loads `key` with the loader of type `L`.
+async fn load<L, K>(ctx: &Context<'_>, key: K) -> async_graphql::Result<L::Value>
+where
+	L: Loader<K, Error = LoaderError>,
+	K: Send + Sync + Hash + Eq + Clone + 'static,
+{
+	ctx.data_unchecked::<DataLoader<L>>()
+		.load_one(key)
+		.await
+		.map_err(|err| graphql_error(&err))?
+		.ok_or_else(|| graphql_error(&Error::new(ErrorKind::NotFoundError, "Not found")))
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+	/// The authenticated player
+	async fn me(&self, ctx: &Context<'_>) -> async_graphql::Result<PlayerNode> {
+		let player_id = ctx.data::<AuthenticatedUser>()?.id;
+		load::<PlayerLoader, _>(ctx, player_id)
+			.await
+			.map(PlayerNode)
+	}
+}
+
+/// A player, with their settlements, units and queues
+pub struct PlayerNode(PlayerState);
+
+#[Object(name = "Player")]
+impl PlayerNode {
+	async fn id(&self) -> PlayerKey {
+		self.0.id
+	}
+
+	async fn name(&self) -> &str {
+		&self.0.name
+	}
+
+	async fn faction(&self) -> FactionCode {
+		self.0.faction
+	}
+
+	/// The player's beginner shield while it lasts
+	async fn protection(&self) -> Option<&ProtectionState> {
+		self.0.protection.as_ref()
+	}
+
+	/// The player's truce while it lasts
+	async fn truce(&self) -> Option<&ProtectionState> {
+		self.0.truce.as_ref()
+	}
+
+	/// The settlements of the player, the capital first
+	async fn settlements(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<SettlementNode>> {
+		let settlements = load::<SettlementsLoader, _>(ctx, self.0.id).await?;
+		Ok(settlements.into_iter().map(SettlementNode).collect())
+	}
+
+	/// The units of the player, units are shared by all their settlements
+	async fn units(&self, ctx: &Context<'_>) -> async_graphql::Result<PlayerUnitsResponse> {
+		load::<UnitsLoader, _>(ctx, self.0.id).await
+	}
+
+	/// The construction and training queues of the player
+	async fn queue(&self) -> QueueNode {
+		QueueNode(self.0.id)
+	}
+}
+
+/// A settlement of the player, with its resources and buildings
+pub struct SettlementNode(Settlement);
+
+#[Object(name = "Settlement")]
+impl SettlementNode {
+	async fn id(&self) -> SettlementKey {
+		self.0.id
+	}
+
+	async fn name(&self) -> &str {
+		&self.0.name
+	}
+
+	/// Whether this is the player's capital, which pays the upkeep of their units
+	async fn is_capital(&self) -> bool {
+		self.0.is_capital
+	}
+
+	async fn resources(&self, ctx: &Context<'_>) -> async_graphql::Result<ResourcesState> {
+		load::<ResourcesLoader, _>(ctx, self.0.id).await
+	}
+
+	async fn buildings(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<BuildingsState>> {
+		load::<BuildingsLoader, _>(ctx, self.0.id).await
+	}
+}
+
+/// The construction and training queues of a player
+pub struct QueueNode(PlayerKey);
+
+#[Object(name = "Queue")]
+impl QueueNode {
+	async fn construction(
+		&self,
+		ctx: &Context<'_>,
+	) -> async_graphql::Result<ConstructionQueueResponse> {
+		load::<ConstructionQueueLoader, _>(ctx, self.0).await
+	}
+
+	async fn training(&self, ctx: &Context<'_>) -> async_graphql::Result<TrainingQueueResponse> {
+		load::<TrainingQueueLoader, _>(ctx, self.0).await
+	}
+}
@@ -0,0 +1,10 @@
+use axum::Router;
+use axum::routing::post;
+
+use super::handlers::graphql;
+use crate::domain::app_state::AppState;
+
+/// Function to define the GraphQL route
+pub fn graphql_routes() -> Router<AppState> {
+	Router::new().route("/graphql", post(graphql))
+}
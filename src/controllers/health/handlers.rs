@@ -1,6 +1,7 @@
 use std::time::Duration;
 
 use axum::extract::State;
+use axum::http::header;
 use axum::response::IntoResponse;
 use axum::{Json, debug_handler};
 use chrono::TimeDelta;
@@ -8,10 +9,16 @@ use chrono::TimeDelta;
 use crate::controllers::health::models::{
 	HealthCheckBody, LivenessCheckBody, ReadyCheckBody, ServiceReadiness,
 };
-use crate::domain::app_state::{AppPool, AppQueue, AppState};
+use crate::domain::app_state::{AppMetrics, AppPool, AppQueue, AppState};
 use crate::not_implemented;
 
 /// Health check handler
+#[utoipa::path(
+	get,
+	path = "/health",
+	tag = "health",
+	responses((status = OK, description = "The server is up", body = HealthCheckBody))
+)]
 #[debug_handler]
 pub(super) async fn health_check() -> impl IntoResponse {
 	let body = HealthCheckBody {
@@ -21,6 +28,13 @@ pub(super) async fn health_check() -> impl IntoResponse {
 	Json(body)
 }
 
+/// Whether the database and the job queue are ready
+#[utoipa::path(
+	get,
+	path = "/health/ready",
+	tag = "health",
+	responses((status = OK, description = "Readiness of the services", body = ReadyCheckBody))
+)]
 #[debug_handler(state = AppState)]
 pub(super) async fn readiness_check(
 	State(pool): State<AppPool>,
@@ -38,6 +52,13 @@ pub(super) async fn readiness_check(
 	Json(body)
 }
 
+/// Whether the server is alive, with its uptime
+#[utoipa::path(
+	get,
+	path = "/health/live",
+	tag = "health",
+	responses((status = OK, description = "The server is alive", body = LivenessCheckBody))
+)]
 #[debug_handler]
 pub(super) async fn liveness_check() -> impl IntoResponse {
 	let uptime = TimeDelta::from_std(get_uptime().unwrap_or_default()).unwrap_or_default();
@@ -69,7 +90,11 @@ pub(super) async fn services() -> impl IntoResponse {
 	not_implemented!()
 }
 
-#[debug_handler]
-pub(super) async fn metrics() -> impl IntoResponse {
-	not_implemented!()
+/// Endpoint metrics in the Prometheus text exposition format
+#[debug_handler(state = AppState)]
+pub(super) async fn metrics(State(metrics): State<AppMetrics>) -> impl IntoResponse {
+	(
+		[(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+		metrics.render_prometheus(),
+	)
 }
@@ -3,4 +3,4 @@ mod models;
 mod routes;
 
 pub use models::{HealthCheckBody, LivenessCheckBody, ReadyCheckBody};
-pub use routes::health_routes;
+pub use routes::{HealthApi, health_routes, metrics_routes};
@@ -1,26 +1,27 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 /// Struct representing the health check response
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct HealthCheckBody {
 	pub status: String,
 	pub timestamp: DateTime<Utc>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct ServiceReadiness {
 	pub database: bool,
 	pub queue: bool,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct ReadyCheckBody {
 	pub ready: bool,
 	pub services: ServiceReadiness,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct LivenessCheckBody {
 	pub alive: bool,
 	pub uptime: String,
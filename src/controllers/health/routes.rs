@@ -1,5 +1,6 @@
 use axum::Router;
 use axum::routing::get;
+use utoipa::OpenApi;
 
 use crate::controllers::health::handlers::*;
 use crate::domain::app_state::AppState;
@@ -12,7 +13,16 @@ pub fn health_routes() -> Router<AppState> {
 			.route("/", get(health_check))
 			.route("/ready", get(readiness_check))
 			.route("/live", get(liveness_check))
-			.route("/service", get(services))
-			.route("/metrics", get(metrics)),
+			.route("/service", get(services)),
 	)
 }
+
+/// Function to define the metrics route, served on the admin listener only
+pub fn metrics_routes() -> Router<AppState> {
+	Router::new().route("/metrics", get(metrics))
+}
+
+/// OpenAPI document of the health check routes.
+#[derive(OpenApi)]
+#[openapi(paths(health_check, readiness_check, liveness_check))]
+pub struct HealthApi;
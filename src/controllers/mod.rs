@@ -1,14 +1,24 @@
+pub mod admin;
 pub mod auth;
 pub mod dashboard;
+pub mod docs;
 pub mod game;
+#[cfg(feature = "graphql")]
+pub mod graphql;
 pub mod health;
+pub mod observer;
 pub mod player;
 pub mod user;
 
 pub mod routes {
+	pub use crate::controllers::admin::admin_routes;
 	pub use crate::controllers::auth::{auth_routes, protected_auth_routes};
+	pub use crate::controllers::docs::docs_routes;
 	pub use crate::controllers::game::game_routes;
-	pub use crate::controllers::health::health_routes;
+	#[cfg(feature = "graphql")]
+	pub use crate::controllers::graphql::graphql_routes;
+	pub use crate::controllers::health::{health_routes, metrics_routes};
+	pub use crate::controllers::observer::observer_routes;
 	pub use crate::controllers::player::player_routes;
 	pub use crate::controllers::user::user_routes;
 }
@@ -0,0 +1,92 @@
+//! Request handlers for the observer API endpoints.
+
+use axum::extract::{Path, Query};
+use axum::response::IntoResponse;
+use axum::{Extension, Json, debug_handler};
+use tracing::{debug, instrument};
+
+use crate::Result;
+use crate::controllers::game::combat::{ReportListQuery, ReportListResponse};
+use crate::controllers::observer::models::*;
+use crate::db::extractor::DatabaseConnection;
+use crate::domain::app_state::AppState;
+use crate::domain::auth::AuthenticatedObserver;
+use crate::domain::player::PlayerKey;
+use crate::game::observer_operations;
+
+/// GET /observe/players
+///
+/// Returns the players the observer watches, by name.
+#[instrument(skip(conn, observer))]
+#[debug_handler(state = AppState)]
+pub async fn get_watched_players(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	observer: Extension<AuthenticatedObserver>,
+) -> Result<impl IntoResponse> {
+	debug!("Getting watched players for observer {}", observer.id);
+	let players = observer_operations::list_watched_players(&mut conn, &observer)?;
+	Ok(Json(WatchedPlayersResponse {
+		players: players.into_iter().map(WatchedPlayerDto::from).collect(),
+	}))
+}
+
+/// GET /observe/players/{player_id}/city
+///
+/// Returns the buildings of a watched player. Needs the `city` scope.
+#[instrument(skip(conn, observer))]
+#[debug_handler(state = AppState)]
+pub async fn get_city(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	observer: Extension<AuthenticatedObserver>,
+	Path(player_id): Path<PlayerKey>,
+) -> Result<impl IntoResponse> {
+	debug!(
+		"Getting city of player {} for observer {}",
+		player_id, observer.id
+	);
+	let city = observer_operations::get_city(&mut conn, &observer, &player_id)?;
+	Ok(Json(ObservedCityResponse::from(city)))
+}
+
+/// GET /observe/players/{player_id}/battles
+///
+/// Returns a page of a watched player's battle reports, newest first. Needs the `battles`
+/// scope.
+#[instrument(skip(conn, observer))]
+#[debug_handler(state = AppState)]
+pub async fn get_battles(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	observer: Extension<AuthenticatedObserver>,
+	Path(player_id): Path<PlayerKey>,
+	Query(query): Query<ReportListQuery>,
+) -> Result<impl IntoResponse> {
+	debug!(
+		"Getting battles of player {} for observer {}",
+		player_id, observer.id
+	);
+	let page = observer_operations::list_battles(
+		&mut conn,
+		&observer,
+		&player_id,
+		query.page,
+		query.per_page,
+	)?;
+	Ok(Json(ReportListResponse::try_from(page)?))
+}
+
+/// GET /observe/leaderboard
+///
+/// Returns the watched players ranked by their buildings and battles won. Needs the
+/// `leaderboard` scope.
+#[instrument(skip(conn, observer))]
+#[debug_handler(state = AppState)]
+pub async fn get_leaderboard(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	observer: Extension<AuthenticatedObserver>,
+) -> Result<impl IntoResponse> {
+	debug!("Getting leaderboard for observer {}", observer.id);
+	let entries = observer_operations::leaderboard(&mut conn, &observer)?;
+	Ok(Json(LeaderboardResponse {
+		entries: entries.into_iter().map(LeaderboardEntryDto::from).collect(),
+	}))
+}
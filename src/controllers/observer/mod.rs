@@ -0,0 +1,14 @@
+//! Observer controller module for casting community tournaments.
+//!
+//! Provides read-only REST API endpoints for observers to see:
+//! - The players they watch
+//! - The city of a watched player
+//! - The battle reports of a watched player
+//! - The leaderboard of the watched players
+
+mod handlers;
+mod models;
+mod routes;
+
+pub use models::*;
+pub use routes::*;
@@ -0,0 +1,109 @@
+//! Response DTOs for the observer API endpoints.
+
+use serde::{Deserialize, Serialize};
+
+use crate::db::player_buildings::FullBuilding;
+use crate::domain::factions::FactionCode;
+use crate::domain::player::buildings::PlayerBuildingKey;
+use crate::domain::player::{Player, PlayerKey};
+use crate::game::observer_operations::{LeaderboardEntry, ObservedCity};
+
+// === Response DTOs ===
+
+/// A player the observer watches.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WatchedPlayerDto {
+	pub id: PlayerKey,
+	pub name: String,
+	pub faction: FactionCode,
+}
+
+impl From<Player> for WatchedPlayerDto {
+	fn from(player: Player) -> Self {
+		Self {
+			id: player.id,
+			name: player.name,
+			faction: player.faction,
+		}
+	}
+}
+
+/// Response for GET /observe/players
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WatchedPlayersResponse {
+	pub players: Vec<WatchedPlayerDto>,
+}
+
+/// A building in a watched player's city.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ObservedBuildingDto {
+	pub id: PlayerBuildingKey,
+	pub building_id: i32,
+	pub name: String,
+	pub level: i32,
+	pub max_level: i32,
+	/// Whether a construction or upgrade is underway
+	pub upgrading: bool,
+}
+
+impl From<FullBuilding> for ObservedBuildingDto {
+	fn from((pb, bld, _, _): FullBuilding) -> Self {
+		Self {
+			id: pb.id,
+			building_id: bld.id,
+			name: bld.name,
+			level: pb.level,
+			max_level: bld.max_level,
+			upgrading: pb.upgrade_finishes_at.is_some(),
+		}
+	}
+}
+
+/// Response for GET /observe/players/{player_id}/city
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ObservedCityResponse {
+	pub player: WatchedPlayerDto,
+	pub buildings: Vec<ObservedBuildingDto>,
+}
+
+impl From<ObservedCity> for ObservedCityResponse {
+	fn from(city: ObservedCity) -> Self {
+		Self {
+			player: WatchedPlayerDto::from(city.player),
+			buildings: city
+				.buildings
+				.into_iter()
+				.map(ObservedBuildingDto::from)
+				.collect(),
+		}
+	}
+}
+
+/// A watched player's place on the leaderboard.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LeaderboardEntryDto {
+	/// 1-based rank among the watched players
+	pub rank: usize,
+	pub player: WatchedPlayerDto,
+	/// Levels of all the player's buildings, summed up
+	pub building_levels: i64,
+	pub battles_won: i64,
+}
+
+impl From<LeaderboardEntry> for LeaderboardEntryDto {
+	fn from(entry: LeaderboardEntry) -> Self {
+		Self {
+			rank: entry.rank,
+			player: WatchedPlayerDto::from(entry.player),
+			building_levels: entry.building_levels,
+			battles_won: entry.battles_won,
+		}
+	}
+}
+
+/// Response for GET /observe/leaderboard
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LeaderboardResponse {
+	/// Watched players, best first
+	pub entries: Vec<LeaderboardEntryDto>,
+}
@@ -0,0 +1,25 @@
+//! Route definitions for the observer API endpoints.
+
+use axum::Router;
+use axum::routing::get;
+
+use crate::controllers::observer::handlers::*;
+use crate::domain::app_state::AppState;
+
+/// Returns a router with all observer routes, to be guarded by the observer middleware.
+///
+/// Routes:
+/// - `GET /observe/players` - Get the players the observer watches
+/// - `GET /observe/players/{player_id}/city` - Get a watched player's buildings
+/// - `GET /observe/players/{player_id}/battles` - Get a watched player's battle reports
+/// - `GET /observe/leaderboard` - Get the ranking of the watched players
+pub fn observer_routes() -> Router<AppState> {
+	Router::new().nest(
+		"/observe",
+		Router::new()
+			.route("/players", get(get_watched_players))
+			.route("/players/{player_id}/city", get(get_city))
+			.route("/players/{player_id}/battles", get(get_battles))
+			.route("/leaderboard", get(get_leaderboard)),
+	)
+}
@@ -1,68 +1,291 @@
-use axum::extract::State;
-use axum::http::StatusCode;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::IntoResponse;
 use axum::{Extension, Json, debug_handler};
+use chrono::Utc;
+use serde_json::json;
 use tracing::{debug, error, info, instrument};
 
-use crate::controllers::player::{JoinFactionPayload, PlayerProfileResponse};
+use crate::auth::api_key_operations::{self, ApiKeyRequest};
+use crate::auth::credential_operations;
+use crate::configuration::Settings;
+use crate::controllers::player::{
+	AccountDeletionResponse, ApiKeyResponse, ChangeEmailPayload, ChangeFactionPayload,
+	ChangePasswordPayload, ChangePasswordResponse, CreateApiKeyPayload, CreatedApiKeyResponse,
+	EmailResponse, FactionChangeResponse, FactionSelectionResponse, JoinFactionPayload,
+	PlayerProfileResponse, PrivacySettingsResponse, UpdatePrivacyPayload,
+};
 use crate::controllers::user::{UpdateUserPayload, UserBody};
 use crate::db::extractor::DatabaseConnection;
-use crate::domain::app_state::AppState;
+use crate::domain::app_state::{AppModifierCache, AppQueue, AppState};
 use crate::domain::auth::AuthenticatedUser;
+use crate::domain::player::api_key::{ApiKey, ApiKeyKey};
+use crate::domain::player::session::PlayerSession;
+use crate::game::account_deletion::deletion_operations;
+use crate::game::audit_operations::{
+	self, CHANGE_EMAIL_ACTION, CHANGE_PASSWORD_ACTION, PlayerActor, REQUEST_DELETION_ACTION,
+};
+use crate::game::factions::faction_operations;
 use crate::game::player_operations;
-use crate::game::resources::resource_scheduler::ProductionScheduler;
+use crate::{Error, ErrorKind};
 
 #[instrument(skip_all, fields(player_id = %player.id))]
 #[debug_handler(state = AppState)]
 pub(super) async fn get_player_profile(
 	DatabaseConnection(mut conn): DatabaseConnection,
 	player: Extension<AuthenticatedUser>,
-) -> crate::Result<impl IntoResponse, StatusCode> {
+) -> crate::Result<impl IntoResponse> {
 	debug!("Starting player profile retrieval");
 	let profile = player_operations::get_player(&mut conn, &player.id)
 		.map(PlayerProfileResponse::from)
-		.map_err(|err| {
-			error!("Failed to fetch user profile");
-			StatusCode::INTERNAL_SERVER_ERROR
-		})?;
+		.inspect_err(|_| error!("Failed to fetch user profile"))?;
 	info!(?profile, "Fetched user profile");
 	Ok(Json(profile))
 }
 
-#[instrument(skip(conn, scheduler, player), fields(player_id = %player.id))]
+#[instrument(skip(conn, modifier_cache, player), fields(player_id = %player.id))]
 #[debug_handler(state = AppState)]
 pub(super) async fn update_player_profile(
 	DatabaseConnection(mut conn): DatabaseConnection,
-	State(scheduler): State<ProductionScheduler>,
+	State(modifier_cache): State<AppModifierCache>,
 	player: Extension<AuthenticatedUser>,
 	Json(payload): Json<UpdateUserPayload>,
-) -> crate::Result<impl IntoResponse, StatusCode> {
+) -> crate::Result<impl IntoResponse> {
 	debug!("Starting player profile update");
-	let profile = player_operations::update_player(&mut conn, &scheduler, player.id, payload)
+	// Credentials change through /player/me/password and /player/me/email, which re-authenticate
+	if payload.password.is_some() || payload.email.is_some() {
+		debug!("Rejected credentials change through the profile update");
+		return Err(Error::from((
+			ErrorKind::InvalidData,
+			"Change credentials through their own endpoints",
+		)));
+	}
+	let profile = player_operations::update_player(&mut conn, &modifier_cache, player.id, payload)
 		.map(PlayerProfileResponse::from)
-		.map_err(|_| {
-			error!("Failed to update user profile");
-			StatusCode::INTERNAL_SERVER_ERROR
-		})?;
+		.inspect_err(|_| error!("Failed to update user profile"))?;
 	info!(?profile, "Updated user profile");
 	Ok((StatusCode::ACCEPTED, Json(profile)))
 }
 
-#[instrument(skip(conn, scheduler, player), fields(player_id = %player.id))]
+#[instrument(skip(conn, modifier_cache, player), fields(player_id = %player.id))]
 #[debug_handler(state = AppState)]
 pub(super) async fn join_faction(
 	DatabaseConnection(mut conn): DatabaseConnection,
-	State(scheduler): State<ProductionScheduler>,
+	State(modifier_cache): State<AppModifierCache>,
 	player: Extension<AuthenticatedUser>,
 	Json(payload): Json<JoinFactionPayload>,
-) -> crate::Result<impl IntoResponse, StatusCode> {
+) -> crate::Result<impl IntoResponse> {
 	debug!("Starting player faction join");
-	let body = player_operations::update_player(&mut conn, &scheduler, player.id, payload.into())
-		.map(UserBody::from)
-		.map_err(|_| {
-			error!("Failed to join faction");
-			StatusCode::INTERNAL_SERVER_ERROR
-		})?;
+	let body =
+		player_operations::update_player(&mut conn, &modifier_cache, player.id, payload.into())
+			.map(UserBody::from)
+			.inspect_err(|_| error!("Failed to join faction"))?;
 	info!(faction = %body.faction, "Joined faction successfully");
 	Ok((StatusCode::ACCEPTED, Json(body)))
 }
+
+#[instrument(skip(conn, modifier_cache, player), fields(player_id = %player.id))]
+#[debug_handler(state = AppState)]
+pub(super) async fn select_faction(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(modifier_cache): State<AppModifierCache>,
+	player: Extension<AuthenticatedUser>,
+	Json(payload): Json<ChangeFactionPayload>,
+) -> crate::Result<impl IntoResponse> {
+	let selected = faction_operations::select_faction(
+		&mut conn,
+		&modifier_cache,
+		&player.id,
+		payload.faction,
+	)?;
+	info!(faction = %selected.player.faction, "Selected faction");
+	Ok((
+		StatusCode::CREATED,
+		Json(FactionSelectionResponse::from(selected)),
+	))
+}
+
+#[instrument(skip(conn, modifier_cache, settings, player), fields(player_id = %player.id))]
+#[debug_handler(state = AppState)]
+pub(super) async fn change_faction(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(modifier_cache): State<AppModifierCache>,
+	settings: Settings,
+	player: Extension<AuthenticatedUser>,
+	Json(payload): Json<ChangeFactionPayload>,
+) -> crate::Result<impl IntoResponse> {
+	let changed = faction_operations::change_faction(
+		&mut conn,
+		&modifier_cache,
+		&settings.factions,
+		&player.id,
+		payload.faction,
+		Utc::now(),
+	)?;
+	info!(faction = %changed.player.faction, "Changed faction");
+	Ok(Json(FactionChangeResponse::from(changed)))
+}
+
+#[instrument(skip_all, fields(player_id = %player.id))]
+#[debug_handler(state = AppState)]
+pub(super) async fn get_privacy_settings(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+) -> crate::Result<impl IntoResponse> {
+	let privacy = player_operations::get_privacy(&mut conn, &player.id)?;
+	Ok(Json(PrivacySettingsResponse::from(privacy)))
+}
+
+#[instrument(skip(conn, player), fields(player_id = %player.id))]
+#[debug_handler(state = AppState)]
+pub(super) async fn update_privacy_settings(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	Json(payload): Json<UpdatePrivacyPayload>,
+) -> crate::Result<impl IntoResponse> {
+	let privacy = player_operations::update_privacy(&mut conn, &player.id, payload.into())?;
+	Ok(Json(PrivacySettingsResponse::from(privacy)))
+}
+
+#[instrument(skip_all, fields(player_id = %player.id))]
+#[debug_handler(state = AppState)]
+pub(super) async fn get_api_keys(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	api_key: Option<Extension<ApiKey>>,
+) -> crate::Result<impl IntoResponse> {
+	api_key_operations::require_login(api_key.as_deref())?;
+	let keys = api_key_operations::list_keys(&mut conn, &player.id)?;
+	Ok(Json(
+		keys.into_iter()
+			.map(ApiKeyResponse::from)
+			.collect::<Vec<_>>(),
+	))
+}
+
+#[instrument(skip(conn, player, api_key), fields(player_id = %player.id))]
+#[debug_handler(state = AppState)]
+pub(super) async fn create_api_key(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	api_key: Option<Extension<ApiKey>>,
+	Json(payload): Json<CreateApiKeyPayload>,
+) -> crate::Result<impl IntoResponse> {
+	api_key_operations::require_login(api_key.as_deref())?;
+	let request = ApiKeyRequest {
+		name: payload.name,
+		scope: payload.scope,
+		expires_at: payload.expires_at,
+	};
+	let created = api_key_operations::create_key(&mut conn, &player.id, request)?;
+	Ok((
+		StatusCode::CREATED,
+		Json(CreatedApiKeyResponse::from(created)),
+	))
+}
+
+#[instrument(skip(conn, player, api_key), fields(player_id = %player.id))]
+#[debug_handler(state = AppState)]
+pub(super) async fn revoke_api_key(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	api_key: Option<Extension<ApiKey>>,
+	Path(key_id): Path<ApiKeyKey>,
+) -> crate::Result<impl IntoResponse> {
+	api_key_operations::require_login(api_key.as_deref())?;
+	let revoked = api_key_operations::revoke_key(&mut conn, &player.id, &key_id)?;
+	Ok(Json(ApiKeyResponse::from(revoked)))
+}
+
+#[instrument(
+	skip(conn, job_queue, settings, player, api_key, headers),
+	fields(player_id = %player.id)
+)]
+#[debug_handler(state = AppState)]
+pub(super) async fn delete_account(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	State(job_queue): State<AppQueue>,
+	settings: Settings,
+	player: Extension<AuthenticatedUser>,
+	api_key: Option<Extension<ApiKey>>,
+	headers: HeaderMap,
+) -> crate::Result<impl IntoResponse> {
+	api_key_operations::require_login(api_key.as_deref())?;
+	let deletion_scheduled_for = deletion_operations::request_deletion(
+		&mut conn,
+		&job_queue,
+		&settings.account_deletion,
+		&player.id,
+		Utc::now(),
+	)?;
+	audit_operations::record(
+		&mut conn,
+		&PlayerActor::new(player.id, &headers),
+		REQUEST_DELETION_ACTION,
+		Some(player.id.to_string()),
+		json!({ "deletion_scheduled_for": deletion_scheduled_for }),
+	);
+	Ok((
+		StatusCode::ACCEPTED,
+		Json(AccountDeletionResponse {
+			deletion_scheduled_for,
+		}),
+	))
+}
+
+#[instrument(skip_all, fields(player_id = %player.id))]
+#[debug_handler(state = AppState)]
+pub(super) async fn change_password(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	api_key: Option<Extension<ApiKey>>,
+	session: Option<Extension<PlayerSession>>,
+	headers: HeaderMap,
+	Json(payload): Json<ChangePasswordPayload>,
+) -> crate::Result<impl IntoResponse> {
+	api_key_operations::require_login(api_key.as_deref())?;
+	let invalidated_sessions = credential_operations::change_password(
+		&mut conn,
+		&player,
+		&payload.current_password,
+		&payload.new_password,
+		session.as_ref().map(|session| &session.id),
+	)?;
+	audit_operations::record(
+		&mut conn,
+		&PlayerActor::new(player.id, &headers),
+		CHANGE_PASSWORD_ACTION,
+		Some(player.id.to_string()),
+		json!({ "invalidated_sessions": invalidated_sessions }),
+	);
+	Ok(Json(ChangePasswordResponse {
+		invalidated_sessions,
+	}))
+}
+
+#[instrument(skip_all, fields(player_id = %player.id))]
+#[debug_handler(state = AppState)]
+pub(super) async fn change_email(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	player: Extension<AuthenticatedUser>,
+	api_key: Option<Extension<ApiKey>>,
+	headers: HeaderMap,
+	Json(payload): Json<ChangeEmailPayload>,
+) -> crate::Result<impl IntoResponse> {
+	api_key_operations::require_login(api_key.as_deref())?;
+	let updated = credential_operations::change_email(
+		&mut conn,
+		&player,
+		&payload.current_password,
+		payload.email,
+	)?;
+	// The addresses are left out, the log is kept longer than the player's data
+	audit_operations::record(
+		&mut conn,
+		&PlayerActor::new(player.id, &headers),
+		CHANGE_EMAIL_ACTION,
+		Some(player.id.to_string()),
+		json!({ "had_email": player.email.is_some() }),
+	);
+	Ok(Json(EmailResponse::from(updated)))
+}
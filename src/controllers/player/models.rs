@@ -1,9 +1,15 @@
+use std::fmt::Debug;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::auth::api_key_operations::CreatedApiKey;
 use crate::controllers::user::UpdateUserPayload;
 use crate::domain::factions::FactionCode;
+use crate::domain::player::api_key::{ApiKey, ApiKeyKey, ApiKeyScope};
+use crate::domain::player::privacy::{PlayerPrivacy, UpdatePlayerPrivacy};
 use crate::domain::player::{Player, PlayerKey};
+use crate::game::factions::faction_operations::{FactionChanged, FactionSelected};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PlayerProfileResponse {
@@ -33,9 +39,9 @@ impl From<Player> for PlayerProfileResponse {
 	}
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct JoinFactionPayload {
-	faction: FactionCode,
+	pub faction: FactionCode,
 }
 
 impl From<JoinFactionPayload> for UpdateUserPayload {
@@ -48,3 +54,219 @@ impl From<JoinFactionPayload> for UpdateUserPayload {
 		}
 	}
 }
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ChangeFactionPayload {
+	pub faction: FactionCode,
+}
+
+/// Outcome of the first faction choice
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FactionSelectionResponse {
+	pub faction: FactionCode,
+	/// Buildings owned after joining, the starter buildings of the faction
+	pub starter_buildings: usize,
+}
+
+impl From<FactionSelected> for FactionSelectionResponse {
+	fn from(value: FactionSelected) -> Self {
+		Self {
+			faction: value.player.faction,
+			starter_buildings: value.starter_buildings,
+		}
+	}
+}
+
+/// Outcome of a faction change
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FactionChangeResponse {
+	pub faction: FactionCode,
+	pub previous_faction: FactionCode,
+	/// Buildings converted to the new faction's equivalents
+	pub converted_buildings: usize,
+	/// Pending construction plans converted to the new faction's equivalents
+	pub converted_plans: usize,
+	/// When the faction can be changed again
+	pub next_change_at: DateTime<Utc>,
+}
+
+impl From<FactionChanged> for FactionChangeResponse {
+	fn from(value: FactionChanged) -> Self {
+		Self {
+			faction: value.change.to_faction,
+			previous_faction: value.change.from_faction,
+			converted_buildings: value.converted_buildings,
+			converted_plans: value.converted_plans,
+			next_change_at: value.next_change_at,
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PrivacySettingsResponse {
+	pub hide_online_status: bool,
+	pub hide_stats: bool,
+	pub hide_alliance: bool,
+	pub allow_observers: bool,
+	pub updated_at: DateTime<Utc>,
+}
+
+impl From<PlayerPrivacy> for PrivacySettingsResponse {
+	fn from(value: PlayerPrivacy) -> Self {
+		Self {
+			hide_online_status: value.hide_online_status,
+			hide_stats: value.hide_stats,
+			hide_alliance: value.hide_alliance,
+			allow_observers: value.allow_observers,
+			updated_at: value.updated_at,
+		}
+	}
+}
+
+/// Settings to change, omitted ones are kept
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct UpdatePrivacyPayload {
+	pub hide_online_status: Option<bool>,
+	pub hide_stats: Option<bool>,
+	pub hide_alliance: Option<bool>,
+	/// Let tournament observers watch the player
+	pub allow_observers: Option<bool>,
+}
+
+impl From<UpdatePrivacyPayload> for UpdatePlayerPrivacy {
+	fn from(value: UpdatePrivacyPayload) -> Self {
+		Self {
+			hide_online_status: value.hide_online_status,
+			hide_stats: value.hide_stats,
+			hide_alliance: value.hide_alliance,
+			allow_observers: value.allow_observers,
+		}
+	}
+}
+
+/// Body of a request to create an API key
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CreateApiKeyPayload {
+	pub name: String,
+	/// What the key may do, read-only unless asked otherwise
+	#[serde(default = "default_api_key_scope")]
+	pub scope: ApiKeyScope,
+	/// End of the key's validity, omit to keep it until it is revoked
+	pub expires_at: Option<DateTime<Utc>>,
+}
+
+fn default_api_key_scope() -> ApiKeyScope {
+	ApiKeyScope::ReadOnly
+}
+
+/// An API key of the player, without the key itself
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ApiKeyResponse {
+	pub id: ApiKeyKey,
+	pub name: String,
+	/// Start of the key, to tell keys apart
+	pub prefix: String,
+	pub scope: ApiKeyScope,
+	/// Whether the key still works
+	pub active: bool,
+	pub last_used_at: Option<DateTime<Utc>>,
+	pub expires_at: Option<DateTime<Utc>>,
+	pub revoked_at: Option<DateTime<Utc>>,
+	pub created_at: DateTime<Utc>,
+}
+
+impl From<ApiKey> for ApiKeyResponse {
+	fn from(api_key: ApiKey) -> Self {
+		Self {
+			id: api_key.id,
+			scope: api_key.scope(),
+			active: api_key.is_active(Utc::now()),
+			name: api_key.name,
+			prefix: api_key.key_prefix,
+			last_used_at: api_key.last_used_at,
+			expires_at: api_key.expires_at,
+			revoked_at: api_key.revoked_at,
+			created_at: api_key.created_at,
+		}
+	}
+}
+
+/// A created API key and the key itself
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CreatedApiKeyResponse {
+	/// Only handed out once, the server keeps a hash of it
+	pub key: String,
+	#[serde(flatten)]
+	pub api_key: ApiKeyResponse,
+}
+
+impl From<CreatedApiKey> for CreatedApiKeyResponse {
+	fn from(created: CreatedApiKey) -> Self {
+		Self {
+			key: created.key,
+			api_key: ApiKeyResponse::from(created.api_key),
+		}
+	}
+}
+
+/// When the account of a player who asked for its deletion will be anonymized
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AccountDeletionResponse {
+	/// Logging in before then cancels the deletion
+	pub deletion_scheduled_for: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ChangePasswordPayload {
+	pub current_password: String,
+	pub new_password: String,
+}
+
+impl Debug for ChangePasswordPayload {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("ChangePasswordPayload")
+			.field("current_password", &"[redacted]")
+			.field("new_password", &"[redacted]")
+			.finish()
+	}
+}
+
+/// Outcome of a password change
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChangePasswordResponse {
+	/// Sessions logged out, every one except the one of the request
+	pub invalidated_sessions: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ChangeEmailPayload {
+	pub current_password: String,
+	pub email: String,
+}
+
+impl Debug for ChangeEmailPayload {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("ChangeEmailPayload")
+			.field("current_password", &"[redacted]")
+			.field("email", &self.email)
+			.finish()
+	}
+}
+
+/// The email address of a player and whether it was verified
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EmailResponse {
+	pub email: Option<String>,
+	pub email_verified: bool,
+}
+
+impl From<Player> for EmailResponse {
+	fn from(player: Player) -> Self {
+		Self {
+			email_verified: player.email.is_some() && player.email_verified_at.is_some(),
+			email: player.email,
+		}
+	}
+}
@@ -1,5 +1,5 @@
 use axum::Router;
-use axum::routing::{get, put};
+use axum::routing::{delete, get, post, put};
 
 use crate::controllers::player::handlers::*;
 use crate::domain::app_state::AppState;
@@ -12,6 +12,17 @@ pub fn player_routes() -> Router<AppState> {
 				"/profile",
 				get(get_player_profile).put(update_player_profile),
 			)
-			.route("/faction", put(join_faction)),
+			.route("/me", delete(delete_account))
+			.route("/me/password", post(change_password))
+			.route("/me/email", post(change_email))
+			.route("/me/faction", post(change_faction))
+			.route("/me/faction/select", post(select_faction))
+			.route("/faction", put(join_faction))
+			.route(
+				"/privacy",
+				get(get_privacy_settings).patch(update_privacy_settings),
+			)
+			.route("/api-keys", get(get_api_keys).post(create_api_key))
+			.route("/api-keys/{key_id}", delete(revoke_api_key)),
 	)
 }
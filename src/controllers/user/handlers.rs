@@ -1,34 +1,36 @@
 use std::time::Instant;
 
 use axum::extract::{Path, State};
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::IntoResponse;
-use axum::{Json, debug_handler};
-use chrono::Utc;
+use axum::{Extension, Json, debug_handler};
+use serde_json::json;
 use tracing::{debug, error, info, instrument, trace, warn};
 
 use crate::Result;
+use crate::configuration::Settings;
 use crate::controllers::user::models::{NewUserPayload, UpdateUserPayload, UserBody, UserListBody};
 use crate::db::extractor::DatabaseConnection;
 use crate::db::players;
-use crate::domain::app_state::AppState;
-use crate::domain::factions::FactionCode;
+use crate::domain::app_state::{AppModifierCache, AppState};
+use crate::domain::auth::AuthenticatedUser;
 use crate::domain::player;
 use crate::domain::player::NewPlayer;
-use crate::game::player_operations;
-use crate::game::resources::resource_scheduler::ProductionScheduler;
+use crate::game::audit_operations::{
+	self, DELETE_PLAYER_ACTION, PlayerActor, UPDATE_PLAYER_ACTION,
+};
+use crate::game::{onboarding_operations, player_operations};
 
 // === CRUD HANDLERS === //
 #[instrument(skip(conn))]
 #[debug_handler(state = AppState)]
 pub(super) async fn get_users(
 	DatabaseConnection(mut conn): DatabaseConnection,
-) -> Result<Json<UserListBody>, StatusCode> {
+) -> Result<Json<UserListBody>> {
 	debug!("Starting fetch all users");
 
-	let result = players::get_all(&mut conn).map_err(|err| {
+	let result = players::get_all(&mut conn).inspect_err(|err| {
 		error!("Failed to fetch users: {}", err);
-		StatusCode::INTERNAL_SERVER_ERROR
 	})?;
 
 	let count = result.len();
@@ -44,14 +46,10 @@ pub(super) async fn get_users(
 pub(super) async fn get_user_by_id(
 	DatabaseConnection(mut conn): DatabaseConnection,
 	Path(player_id): Path<player::PlayerKey>,
-) -> Result<Json<UserBody>, StatusCode> {
+) -> Result<Json<UserBody>> {
 	debug!("Starting fetch user by ID");
 
-	let user = players::get_by_id(&mut conn, &player_id).map_err(|err| {
-		// TODO: differentiate between not found and other errors
-		error!(player_id = %player_id, "Failed to fetch player: {}", err);
-		StatusCode::NOT_FOUND
-	})?;
+	let user = player_operations::get_player(&mut conn, &player_id)?;
 
 	info!(player_id = %player_id, "Completed fetch user successfully");
 	trace!(?user, "User details");
@@ -59,41 +57,31 @@ pub(super) async fn get_user_by_id(
 	Ok(Json(user.into()))
 }
 
-#[instrument(skip(conn, prod_scheduler), fields(username = ?payload.username, faction = ?payload.faction))]
+#[instrument(skip(conn, settings), fields(username = ?payload.username, faction = ?payload.faction))]
 #[debug_handler(state = AppState)]
 pub(super) async fn create_user(
 	DatabaseConnection(mut conn): DatabaseConnection,
-	State(prod_scheduler): State<ProductionScheduler>,
+	settings: Settings,
 	Json(payload): Json<NewUserPayload>,
-) -> Result<(StatusCode, Json<UserBody>), StatusCode> {
-	// AIDEV-NOTE: Critical user creation path with production scheduling
+) -> Result<(StatusCode, Json<UserBody>)> {
+	// AIDEV-NOTE: Critical user creation path, onboarded exactly like registered players
 	debug!("Starting user creation");
 	let start = Instant::now();
 
-	let new_user = match NewPlayer::try_from(payload) {
-		Ok(new_user) => new_user,
-		Err(err) => {
-			warn!(error = %err, "User validation failed");
-			return Err(StatusCode::BAD_REQUEST);
-		}
-	};
-
-	let created_user = players::create(&mut conn, new_user).map_err(|err| {
-		error!(error = %err, "Failed to insert player in database");
-		StatusCode::INTERNAL_SERVER_ERROR
+	let new_user = NewPlayer::try_from(payload).inspect_err(|err| {
+		warn!(error = %err, "User validation failed");
 	})?;
 
-	debug!(player_id = %created_user.id, "User created, checking faction for production scheduling");
-
-	if created_user.faction != FactionCode::Neutral {
-		debug!(player_id = %created_user.id, faction = ?created_user.faction, "Scheduling initial production");
-		prod_scheduler
-			.schedule_production(&created_user.id, Utc::now())
-			.map_err(|err| {
-				error!(player_id = %created_user.id, error = %err, "Failed to schedule production");
-				StatusCode::INTERNAL_SERVER_ERROR
-			})?;
-	}
+	let created_user = onboarding_operations::create_player(
+		&mut conn,
+		&settings.protection,
+		&settings.onboarding,
+		new_user,
+	)
+	.inspect_err(|err| {
+		error!(error = %err, "Failed to create player");
+	})?
+	.player;
 
 	let duration = start.elapsed();
 	info!(
@@ -107,15 +95,16 @@ pub(super) async fn create_user(
 	Ok((StatusCode::CREATED, Json(created_user.into())))
 }
 
-#[instrument(skip(conn, scheduler), fields(player_id = ?player_key))]
+#[instrument(skip(conn, modifier_cache, admin, headers), fields(player_id = ?player_key))]
 #[debug_handler(state = AppState)]
 pub(super) async fn update_user(
 	DatabaseConnection(mut conn): DatabaseConnection,
-	State(scheduler): State<ProductionScheduler>,
+	State(modifier_cache): State<AppModifierCache>,
 	Path(player_key): Path<player::PlayerKey>,
+	admin: Extension<AuthenticatedUser>,
+	headers: HeaderMap,
 	Json(payload): Json<UpdateUserPayload>,
-) -> Result<impl IntoResponse, StatusCode> {
-	// AIDEV-NOTE: User update with potential faction change requires production scheduling
+) -> Result<impl IntoResponse> {
 	debug!("Starting user update");
 	let start = Instant::now();
 
@@ -126,7 +115,7 @@ pub(super) async fn update_user(
 	let faction_changed = payload.faction.is_some();
 
 	let updated_user =
-		player_operations::update_player(&mut conn, &scheduler, player_key, payload)?;
+		player_operations::update_player(&mut conn, &modifier_cache, player_key, payload)?;
 	let duration = start.elapsed();
 	info!(
 		player_id = %player_key,
@@ -137,30 +126,42 @@ pub(super) async fn update_user(
 		duration_ms = duration.as_millis(),
 		"Completed user update successfully"
 	);
+	audit_operations::record(
+		&mut conn,
+		&PlayerActor::new(admin.id, &headers),
+		UPDATE_PLAYER_ACTION,
+		Some(player_key.to_string()),
+		json!({
+			"name_changed": name_changed,
+			"email_changed": email_changed,
+			"password_changed": password_changed,
+			"faction_changed": faction_changed,
+		}),
+	);
 
 	trace!(?updated_user, "Updated user details");
 
 	Ok((StatusCode::ACCEPTED, Json(UserBody::from(updated_user))))
 }
 
-#[instrument(skip(conn), fields(player_id = ?player_id))]
+#[instrument(skip(conn, admin, headers), fields(player_id = ?player_id))]
 #[debug_handler(state = AppState)]
 pub(super) async fn delete_user(
 	DatabaseConnection(mut conn): DatabaseConnection,
 	Path(player_id): Path<player::PlayerKey>,
-) -> Result<StatusCode, StatusCode> {
+	admin: Extension<AuthenticatedUser>,
+	headers: HeaderMap,
+) -> Result<StatusCode> {
 	debug!(player_id = %player_id, "Starting user deletion");
 	let start = Instant::now();
 
 	// First check if user exists
-	if let Err(err) = players::get_by_id(&mut conn, &player_id) {
-		warn!(player_id = %player_id, error = %err, "Attempted to delete non-existent user");
-		return Err(StatusCode::NOT_FOUND);
-	}
+	player_operations::get_player(&mut conn, &player_id).inspect_err(|_| {
+		warn!(player_id = %player_id, "Attempted to delete non-existent user");
+	})?;
 
-	let count = players::delete(&mut conn, &player_id).map_err(|err| {
+	let count = players::delete(&mut conn, &player_id).inspect_err(|err| {
 		error!(player_id = %player_id, error = %err, "Failed to delete player from database");
-		StatusCode::INTERNAL_SERVER_ERROR
 	})?;
 
 	let duration = start.elapsed();
@@ -170,6 +171,13 @@ pub(super) async fn delete_user(
 		duration_ms = duration.as_millis(),
 		"Completed user deletion successfully"
 	);
+	audit_operations::record(
+		&mut conn,
+		&PlayerActor::new(admin.id, &headers),
+		DELETE_PLAYER_ACTION,
+		Some(player_id.to_string()),
+		json!({}),
+	);
 
 	Ok(StatusCode::NO_CONTENT)
 }
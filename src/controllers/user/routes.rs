@@ -1,19 +1,30 @@
-use axum::Router;
-use axum::routing::get;
+use axum::routing::{get, post, put};
+use axum::{Router, middleware};
 
 use crate::controllers::user::handlers::{
 	create_user, delete_user, get_user_by_id, get_users, update_user,
 };
 use crate::domain::app_state::AppState;
+use crate::domain::player::role::PlayerRole;
+use crate::net::require_role;
 
+/// Account management of other players: moderators can look accounts up, only admins can
+/// create, change and delete them.
 pub fn user_routes() -> Router<AppState> {
-	Router::new().nest(
-		"/users",
-		Router::new()
-			.route("/", get(get_users).post(create_user))
-			.route(
-				"/{id}",
-				get(get_user_by_id).put(update_user).delete(delete_user),
-			),
-	)
+	let read_routes = Router::new()
+		.route("/", get(get_users))
+		.route("/{id}", get(get_user_by_id))
+		.route_layer(middleware::from_fn_with_state(
+			PlayerRole::Moderator,
+			require_role,
+		));
+	let write_routes = Router::new()
+		.route("/", post(create_user))
+		.route("/{id}", put(update_user).delete(delete_user))
+		.route_layer(middleware::from_fn_with_state(
+			PlayerRole::Admin,
+			require_role,
+		));
+
+	Router::new().nest("/users", read_routes.merge(write_routes))
 }
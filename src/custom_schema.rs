@@ -1,6 +1,7 @@
 // this isn't a table but instead a view. The problem is that diesel doesn't support views yet
 diesel::table! {
-	resource_generation (player_id) {
+	resource_generation (settlement_id) {
+		settlement_id -> Uuid,
 		player_id -> Uuid,
 		population -> BigInt,
 		food -> BigInt,
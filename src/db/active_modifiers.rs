@@ -4,13 +4,16 @@
 //! modifications to game entities (typically players). It provides standard CRUD
 //! operations along with specialized queries for retrieving modifiers by player ID.
 
+use chrono::{DateTime, Utc};
 use diesel::prelude::*;
+use uuid::Uuid;
 
 use crate::Result;
 use crate::db::DbConn;
 use crate::domain::modifier::active_modifier::{
-	ActiveModifier, ActiveModifierKey, NewActiveModifier, UpdateActiveModifier,
+	ActiveModifier, ActiveModifierKey, ModifierSourceType, NewActiveModifier, UpdateActiveModifier,
 };
+use crate::domain::modifier::{ModifierKey, ModifierTarget};
 use crate::domain::player::PlayerKey;
 use crate::schema::active_modifiers::dsl::*;
 
@@ -57,6 +60,25 @@ pub fn create(conn: &mut DbConn, entity: NewActiveModifier) -> Result<ActiveModi
 	Ok(modifier)
 }
 
+/// Creates a batch of active modifiers in the database.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `entities` - The [`NewActiveModifier`] entities to create
+///
+/// # Returns
+/// * `Result<Vec<ActiveModifier>>` - The created modifiers or an error
+pub fn create_batch(
+	conn: &mut DbConn,
+	entities: &[NewActiveModifier],
+) -> Result<Vec<ActiveModifier>> {
+	let created = diesel::insert_into(active_modifiers)
+		.values(entities)
+		.returning(ActiveModifier::as_returning())
+		.get_results(conn)?;
+	Ok(created)
+}
+
 /// Updates an existing active modifier in the database.
 ///
 /// # Arguments
@@ -99,3 +121,114 @@ pub fn get_by_player_id(conn: &mut DbConn, player_key: &PlayerKey) -> Result<Vec
 		.get_results(conn)?;
 	Ok(active_mods)
 }
+
+/// Deletes every active modifier that expired at or before `now`.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `now` - The point in time modifiers are expired at
+///
+/// # Returns
+/// * `Result<Vec<ActiveModifier>>` - The deleted modifiers or an error
+pub fn delete_expired(conn: &mut DbConn, now: DateTime<Utc>) -> Result<Vec<ActiveModifier>> {
+	let expired = diesel::delete(active_modifiers.filter(expires_at.le(now)))
+		.returning(ActiveModifier::as_returning())
+		.get_results(conn)?;
+	Ok(expired)
+}
+
+/// Deletes every active instance of a modifier held by a player.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `player_key` - Reference to the [`PlayerKey`] holding the modifier
+/// * `modifier_key` - Reference to the [`ModifierKey`] of the modifier to delete
+///
+/// # Returns
+/// * `Result<Vec<ActiveModifier>>` - The deleted modifiers or an error
+pub fn delete_for_player(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	modifier_key: &ModifierKey,
+) -> Result<Vec<ActiveModifier>> {
+	let deleted = diesel::delete(
+		active_modifiers
+			.filter(player_id.eq(player_key))
+			.filter(modifier_id.eq(modifier_key)),
+	)
+	.returning(ActiveModifier::as_returning())
+	.get_results(conn)?;
+	Ok(deleted)
+}
+
+/// Deletes the active modifiers applied by a single source, like a hero.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `source` - The [`ModifierSourceType`] of the source
+/// * `source_key` - Reference to the ID of the source
+///
+/// # Returns
+/// * `Result<Vec<ActiveModifier>>` - The deleted modifiers or an error
+pub fn delete_by_source(
+	conn: &mut DbConn,
+	source: ModifierSourceType,
+	source_key: &Uuid,
+) -> Result<Vec<ActiveModifier>> {
+	let deleted = diesel::delete(
+		active_modifiers
+			.filter(source_type.eq(source))
+			.filter(source_id.eq(source_key)),
+	)
+	.returning(ActiveModifier::as_returning())
+	.get_results(conn)?;
+	Ok(deleted)
+}
+
+/// Deletes the active modifiers of some modifiers, whichever players hold them.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `modifier_keys` - The modifiers to remove from every player
+///
+/// # Returns
+/// * `Result<Vec<ActiveModifier>>` - The deleted modifiers or an error
+pub fn delete_by_modifiers(
+	conn: &mut DbConn,
+	modifier_keys: &[ModifierKey],
+) -> Result<Vec<ActiveModifier>> {
+	let deleted = diesel::delete(active_modifiers.filter(modifier_id.eq_any(modifier_keys)))
+		.returning(ActiveModifier::as_returning())
+		.get_results(conn)?;
+	Ok(deleted)
+}
+
+/// Returns when the last active modifier of a target held by a player runs out.
+///
+/// Only modifiers that expire after `now` count, permanent modifiers are ignored.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `player_key` - Reference to the [`PlayerKey`] holding the modifiers
+/// * `target` - The [`ModifierTarget`] of the modifiers
+/// * `now` - The point in time the modifiers must outlast
+///
+/// # Returns
+/// * `Result<Option<DateTime<Utc>>>` - The latest expiry, or `None` if no modifier is active
+pub fn latest_expiry_for_target(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	target: ModifierTarget,
+	now: DateTime<Utc>,
+) -> Result<Option<DateTime<Utc>>> {
+	use crate::schema::modifiers;
+
+	let latest = active_modifiers
+		.inner_join(modifiers::table)
+		.filter(player_id.eq(player_key))
+		.filter(modifiers::target_type.eq(target))
+		.filter(expires_at.gt(now))
+		.select(diesel::dsl::max(expires_at))
+		.get_result(conn)?;
+	Ok(latest)
+}
@@ -0,0 +1,36 @@
+//! Database access layer for donations to alliance treasuries.
+
+use diesel::prelude::*;
+use tracing::{instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::alliance::{AllianceDonation, AllianceKey, NewAllianceDonation};
+use crate::schema::alliance_donation as ad;
+
+/// Records a donation.
+#[instrument(skip(conn, entity))]
+pub fn create(conn: &mut DbConn, entity: NewAllianceDonation) -> Result<AllianceDonation> {
+	let donation = diesel::insert_into(ad::table)
+		.values(entity)
+		.returning(AllianceDonation::as_returning())
+		.get_result(conn)?;
+	trace!("Recorded alliance donation: {:?}", donation);
+	Ok(donation)
+}
+
+/// Retrieves the most recent donations to an alliance, newest first.
+#[instrument(skip(conn))]
+pub fn get_by_alliance(
+	conn: &mut DbConn,
+	alliance_id: &AllianceKey,
+	limit: i64,
+) -> Result<Vec<AllianceDonation>> {
+	let donations = ad::table
+		.filter(ad::alliance_id.eq(alliance_id))
+		.order((ad::created_at.desc(), ad::id.desc()))
+		.limit(limit)
+		.select(AllianceDonation::as_select())
+		.load(conn)?;
+	Ok(donations)
+}
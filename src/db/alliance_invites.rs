@@ -0,0 +1,63 @@
+//! Database access layer for invites to join an alliance.
+
+use diesel::prelude::*;
+use tracing::{debug, instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::alliance::{Alliance, AllianceInvite, AllianceKey, NewAllianceInvite};
+use crate::domain::player::PlayerKey;
+use crate::schema::{alliance, alliance_invite as ai};
+
+/// Invites a player to an alliance.
+#[instrument(skip(conn, entity))]
+pub fn create(conn: &mut DbConn, entity: NewAllianceInvite) -> Result<AllianceInvite> {
+	debug!(
+		"Inviting player {} to alliance {}",
+		entity.player_id, entity.alliance_id
+	);
+	let invite = diesel::insert_into(ai::table)
+		.values(entity)
+		.returning(AllianceInvite::as_returning())
+		.get_result(conn)?;
+	trace!("Created alliance invite: {:?}", invite);
+	Ok(invite)
+}
+
+/// Retrieves the invite of a player to an alliance, if there is one.
+#[instrument(skip(conn))]
+pub fn find(
+	conn: &mut DbConn,
+	alliance_id: &AllianceKey,
+	player_id: &PlayerKey,
+) -> Result<Option<AllianceInvite>> {
+	let invite = ai::table
+		.filter(ai::alliance_id.eq(alliance_id))
+		.filter(ai::player_id.eq(player_id))
+		.select(AllianceInvite::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(invite)
+}
+
+/// Retrieves the pending invites of a player with the alliances they are for, newest first.
+#[instrument(skip(conn))]
+pub fn get_by_player(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+) -> Result<Vec<(AllianceInvite, Alliance)>> {
+	let invites = ai::table
+		.inner_join(alliance::table)
+		.filter(ai::player_id.eq(player_id))
+		.order(ai::created_at.desc())
+		.select((AllianceInvite::as_select(), Alliance::as_select()))
+		.load(conn)?;
+	Ok(invites)
+}
+
+/// Removes every invite of a player, once they joined an alliance.
+#[instrument(skip(conn))]
+pub fn delete_by_player(conn: &mut DbConn, player_id: &PlayerKey) -> Result<usize> {
+	let deleted = diesel::delete(ai::table.filter(ai::player_id.eq(player_id))).execute(conn)?;
+	Ok(deleted)
+}
@@ -0,0 +1,109 @@
+//! Database access layer for the members of alliances.
+
+use diesel::prelude::*;
+use tracing::{debug, instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::alliance::{AllianceKey, AllianceMember, AllianceRole, NewAllianceMember};
+use crate::domain::player::PlayerKey;
+use crate::schema::{alliance_member as am, player};
+
+/// Adds a player to an alliance.
+#[instrument(skip(conn, entity))]
+pub fn create(conn: &mut DbConn, entity: NewAllianceMember) -> Result<AllianceMember> {
+	debug!(
+		"Adding player {} to alliance {} as {}",
+		entity.player_id, entity.alliance_id, entity.role
+	);
+	let member = diesel::insert_into(am::table)
+		.values(entity)
+		.returning(AllianceMember::as_returning())
+		.get_result(conn)?;
+	trace!("Created alliance member: {:?}", member);
+	Ok(member)
+}
+
+/// Retrieves the membership of a player, if they are in an alliance.
+#[instrument(skip(conn))]
+pub fn find_by_player(conn: &mut DbConn, player_id: &PlayerKey) -> Result<Option<AllianceMember>> {
+	let member = am::table
+		.find(player_id)
+		.select(AllianceMember::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(member)
+}
+
+/// Retrieves the IDs of the members of an alliance.
+#[instrument(skip(conn))]
+pub fn get_player_ids(conn: &mut DbConn, alliance_id: &AllianceKey) -> Result<Vec<PlayerKey>> {
+	let ids = am::table
+		.filter(am::alliance_id.eq(alliance_id))
+		.select(am::player_id)
+		.load(conn)?;
+	Ok(ids)
+}
+
+/// Retrieves the members of an alliance with their names, highest role first, then by
+/// seniority.
+#[instrument(skip(conn))]
+pub fn get_by_alliance(
+	conn: &mut DbConn,
+	alliance_id: &AllianceKey,
+) -> Result<Vec<(AllianceMember, String)>> {
+	let members = am::table
+		.inner_join(player::table)
+		.filter(am::alliance_id.eq(alliance_id))
+		.order((am::role.desc(), am::joined_at.asc()))
+		.select((AllianceMember::as_select(), player::name))
+		.load(conn)?;
+	Ok(members)
+}
+
+/// Counts the members of an alliance.
+#[instrument(skip(conn))]
+pub fn count_by_alliance(conn: &mut DbConn, alliance_id: &AllianceKey) -> Result<i64> {
+	let count = am::table
+		.filter(am::alliance_id.eq(alliance_id))
+		.count()
+		.get_result(conn)?;
+	Ok(count)
+}
+
+/// Retrieves the member next in line to lead an alliance: the most senior of the highest
+/// role.
+#[instrument(skip(conn))]
+pub fn find_successor(
+	conn: &mut DbConn,
+	alliance_id: &AllianceKey,
+) -> Result<Option<AllianceMember>> {
+	let member = am::table
+		.filter(am::alliance_id.eq(alliance_id))
+		.order((am::role.desc(), am::joined_at.asc()))
+		.select(AllianceMember::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(member)
+}
+
+/// Changes the role of a member.
+#[instrument(skip(conn))]
+pub fn set_role(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	role: AllianceRole,
+) -> Result<AllianceMember> {
+	let member = diesel::update(am::table.find(player_id))
+		.set(am::role.eq(role))
+		.returning(AllianceMember::as_returning())
+		.get_result(conn)?;
+	Ok(member)
+}
+
+/// Removes a player from their alliance.
+#[instrument(skip(conn))]
+pub fn delete(conn: &mut DbConn, player_id: &PlayerKey) -> Result<usize> {
+	let deleted = diesel::delete(am::table.find(player_id)).execute(conn)?;
+	Ok(deleted)
+}
@@ -0,0 +1,97 @@
+//! Database access layer for alliance entities.
+//!
+//! This module provides operations for founding and disbanding alliances, looking them up
+//! by their name or tag, and depositing donations into their treasury.
+
+use diesel::prelude::*;
+use diesel::sql_types::Text;
+use tracing::{debug, instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::db::resources::ResourceDelta;
+use crate::domain::alliance::{Alliance, AllianceKey, NewAlliance};
+use crate::schema::alliance as al;
+
+define_sql_function! {
+	/// Names and tags are unique regardless of case.
+	fn lower(text: Text) -> Text
+}
+
+/// Founds a new alliance with an empty treasury.
+#[instrument(skip(conn, entity))]
+pub fn create(conn: &mut DbConn, entity: NewAlliance) -> Result<Alliance> {
+	debug!("Creating alliance {} [{}]", entity.name, entity.tag);
+	let alliance = diesel::insert_into(al::table)
+		.values(entity)
+		.returning(Alliance::as_returning())
+		.get_result(conn)?;
+	trace!("Created alliance: {:?}", alliance);
+	Ok(alliance)
+}
+
+/// Finds an alliance by its ID.
+#[instrument(skip(conn))]
+pub fn find_by_id(conn: &mut DbConn, alliance_id: &AllianceKey) -> Result<Option<Alliance>> {
+	let alliance = al::table
+		.find(alliance_id)
+		.select(Alliance::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(alliance)
+}
+
+/// Retrieves an alliance by its ID and locks it for the rest of the transaction.
+///
+/// Changes to the members of an alliance lock it first, so they are applied one at a time.
+#[instrument(skip(conn))]
+pub fn lock_by_id(conn: &mut DbConn, alliance_id: &AllianceKey) -> Result<Alliance> {
+	let alliance = al::table
+		.find(alliance_id)
+		.select(Alliance::as_select())
+		.for_update()
+		.first(conn)?;
+	Ok(alliance)
+}
+
+/// Checks whether an alliance already uses the name or the tag, regardless of case.
+#[instrument(skip(conn))]
+pub fn exists_by_name_or_tag(conn: &mut DbConn, name: &str, tag: &str) -> Result<bool> {
+	let exists = diesel::select(diesel::dsl::exists(
+		al::table.filter(
+			lower(al::name)
+				.eq(name.to_lowercase())
+				.or(lower(al::tag).eq(tag.to_lowercase())),
+		),
+	))
+	.get_result(conn)?;
+	Ok(exists)
+}
+
+/// Adds resources to the treasury of an alliance.
+#[instrument(skip(conn))]
+pub fn deposit(
+	conn: &mut DbConn,
+	alliance_id: &AllianceKey,
+	amounts: &ResourceDelta,
+) -> Result<Alliance> {
+	let alliance = diesel::update(al::table.find(alliance_id))
+		.set((
+			al::food.eq(al::food + amounts.0),
+			al::wood.eq(al::wood + amounts.1),
+			al::stone.eq(al::stone + amounts.2),
+			al::gold.eq(al::gold + amounts.3),
+		))
+		.returning(Alliance::as_returning())
+		.get_result(conn)?;
+	trace!("Updated treasury: {:?}", alliance);
+	Ok(alliance)
+}
+
+/// Disbands an alliance, removing its members, invites and donations with it.
+#[instrument(skip(conn))]
+pub fn delete(conn: &mut DbConn, alliance_id: &AllianceKey) -> Result<usize> {
+	debug!("Deleting alliance {}", alliance_id);
+	let deleted = diesel::delete(al::table.find(alliance_id)).execute(conn)?;
+	Ok(deleted)
+}
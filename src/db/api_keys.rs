@@ -0,0 +1,117 @@
+//! Database access layer for the API keys of players.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use tracing::{instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::player::api_key::{ApiKey, ApiKeyKey, NewApiKey};
+use crate::domain::player::{Player, PlayerKey};
+use crate::schema::{api_key, player};
+
+/// Creates an API key.
+#[instrument(skip(conn, entity))]
+pub fn create(conn: &mut DbConn, entity: NewApiKey) -> Result<ApiKey> {
+	let created = diesel::insert_into(api_key::table)
+		.values(entity)
+		.returning(ApiKey::as_returning())
+		.get_result(conn)?;
+	trace!(?created, "Created API key");
+	Ok(created)
+}
+
+/// Retrieves every API key of a player, revoked ones included, newest first.
+#[instrument(skip(conn))]
+pub fn get_by_player(conn: &mut DbConn, player_id: &PlayerKey) -> Result<Vec<ApiKey>> {
+	let keys = api_key::table
+		.filter(api_key::player_id.eq(player_id))
+		.order(api_key::created_at.desc())
+		.select(ApiKey::as_select())
+		.load(conn)?;
+	Ok(keys)
+}
+
+/// Counts the API keys of a player that are neither revoked nor expired at `now`.
+#[instrument(skip(conn))]
+pub fn count_active(conn: &mut DbConn, player_id: &PlayerKey, now: DateTime<Utc>) -> Result<i64> {
+	let count = api_key::table
+		.filter(api_key::player_id.eq(player_id))
+		.filter(api_key::revoked_at.is_null())
+		.filter(
+			api_key::expires_at
+				.is_null()
+				.or(api_key::expires_at.gt(now)),
+		)
+		.count()
+		.get_result(conn)?;
+	Ok(count)
+}
+
+/// Retrieves the API key of the given hash with the player it belongs to.
+#[instrument(skip_all)]
+pub fn find_by_key_hash(conn: &mut DbConn, hash: &str) -> Result<Option<(ApiKey, Player)>> {
+	let found = api_key::table
+		.inner_join(player::table)
+		.filter(api_key::key_hash.eq(hash))
+		.select((ApiKey::as_select(), Player::as_select()))
+		.first(conn)
+		.optional()?;
+	Ok(found)
+}
+
+/// Retrieves an API key of a player, `None` if it does not exist or belongs to someone else.
+#[instrument(skip(conn))]
+pub fn find_by_player(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	key_id: &ApiKeyKey,
+) -> Result<Option<ApiKey>> {
+	let found = api_key::table
+		.find(key_id)
+		.filter(api_key::player_id.eq(player_id))
+		.select(ApiKey::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(found)
+}
+
+/// Marks an API key of a player as revoked.
+///
+/// Returns `None` if the key does not exist, belongs to someone else or was revoked already.
+#[instrument(skip(conn))]
+pub fn revoke(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	key_id: &ApiKeyKey,
+	now: DateTime<Utc>,
+) -> Result<Option<ApiKey>> {
+	let revoked = diesel::update(
+		api_key::table
+			.find(key_id)
+			.filter(api_key::player_id.eq(player_id))
+			.filter(api_key::revoked_at.is_null()),
+	)
+	.set(api_key::revoked_at.eq(now))
+	.returning(ApiKey::as_returning())
+	.get_result(conn)
+	.optional()?;
+	Ok(revoked)
+}
+
+/// Records that an API key authenticated a request at `now`.
+#[instrument(skip(conn))]
+pub fn touch(conn: &mut DbConn, key_id: &ApiKeyKey, now: DateTime<Utc>) -> Result<()> {
+	diesel::update(api_key::table.find(key_id))
+		.set(api_key::last_used_at.eq(now))
+		.execute(conn)?;
+	Ok(())
+}
+
+/// Deletes every API key of a player.
+#[instrument(skip(conn))]
+pub fn delete_by_player(conn: &mut DbConn, player_id: &PlayerKey) -> Result<usize> {
+	let deleted =
+		diesel::delete(api_key::table.filter(api_key::player_id.eq(player_id))).execute(conn)?;
+	Ok(deleted)
+}
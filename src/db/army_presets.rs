@@ -0,0 +1,118 @@
+//! Database access layer for army presets and their units.
+
+use diesel::prelude::*;
+use tracing::{instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::error::{Error, ErrorKind};
+use crate::domain::player::PlayerKey;
+use crate::domain::unit::UnitKey;
+use crate::domain::unit::preset::{ArmyPreset, ArmyPresetKey, ArmyPresetUnit, NewArmyPreset};
+use crate::schema::{army_preset as ap, army_preset_unit as apu};
+
+/// An army preset with its units.
+pub type FullArmyPreset = (ArmyPreset, Vec<ArmyPresetUnit>);
+
+/// Retrieves the presets of a player with their units, sorted by name.
+#[instrument(skip(conn))]
+pub fn get_for_player(conn: &mut DbConn, player_key: &PlayerKey) -> Result<Vec<FullArmyPreset>> {
+	let presets = ap::table
+		.filter(ap::player_id.eq(player_key))
+		.order(ap::name)
+		.select(ArmyPreset::as_select())
+		.load(conn)?;
+	let units = ArmyPresetUnit::belonging_to(&presets)
+		.select(ArmyPresetUnit::as_select())
+		.load(conn)?
+		.grouped_by(&presets);
+	Ok(presets.into_iter().zip(units).collect())
+}
+
+/// Retrieves a preset of a player with its units.
+///
+/// Fails with [`ErrorKind::NotFoundError`] if the player has no such preset.
+#[instrument(skip(conn))]
+pub fn get_owned(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	preset_key: &ArmyPresetKey,
+) -> Result<FullArmyPreset> {
+	let preset = ap::table
+		.filter(ap::id.eq(preset_key))
+		.filter(ap::player_id.eq(player_key))
+		.select(ArmyPreset::as_select())
+		.first(conn)
+		.optional()?
+		.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Preset not found")))?;
+	let units = ArmyPresetUnit::belonging_to(&preset)
+		.select(ArmyPresetUnit::as_select())
+		.load(conn)?;
+	Ok((preset, units))
+}
+
+/// Counts the presets of a player.
+#[instrument(skip(conn))]
+pub fn count_for_player(conn: &mut DbConn, player_key: &PlayerKey) -> Result<i64> {
+	let count = ap::table
+		.filter(ap::player_id.eq(player_key))
+		.count()
+		.get_result(conn)?;
+	Ok(count)
+}
+
+/// Creates a preset with its units, given as pairs of unit and quantity.
+///
+/// Returns `None` without changing anything if the player has a preset of the same name.
+#[instrument(skip(conn))]
+pub fn create(
+	conn: &mut DbConn,
+	entity: NewArmyPreset,
+	units: &[(UnitKey, i64)],
+) -> Result<Option<FullArmyPreset>> {
+	conn.transaction::<_, Error, _>(|conn| {
+		let Some(preset) = diesel::insert_into(ap::table)
+			.values(entity)
+			.on_conflict((ap::player_id, ap::name))
+			.do_nothing()
+			.returning(ArmyPreset::as_returning())
+			.get_result(conn)
+			.optional()?
+		else {
+			return Ok(None);
+		};
+		let rows: Vec<_> = units
+			.iter()
+			.map(|(unit_id, quantity)| ArmyPresetUnit {
+				preset_id: preset.id,
+				unit_id: *unit_id,
+				quantity: *quantity,
+			})
+			.collect();
+		let units = diesel::insert_into(apu::table)
+			.values(&rows)
+			.returning(ArmyPresetUnit::as_returning())
+			.get_results(conn)?;
+		trace!("Created army preset: {:?}", preset);
+		Ok(Some((preset, units)))
+	})
+}
+
+/// Deletes a preset of a player, along with its units.
+///
+/// # Returns
+/// The number of presets deleted, 0 if the player has no such preset
+#[instrument(skip(conn))]
+pub fn delete(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	preset_key: &ArmyPresetKey,
+) -> Result<usize> {
+	let deleted = diesel::delete(
+		ap::table
+			.filter(ap::id.eq(preset_key))
+			.filter(ap::player_id.eq(player_key)),
+	)
+	.execute(conn)?;
+	Ok(deleted)
+}
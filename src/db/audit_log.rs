@@ -0,0 +1,61 @@
+//! Database access layer for the audit log.
+//!
+//! Entries are only ever appended, never updated or deleted.
+
+use diesel::prelude::*;
+use tracing::{debug, instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::audit::{AuditEntry, AuditFilter, NewAuditEntry};
+use crate::schema::audit_log as al;
+
+/// Records an action.
+#[instrument(skip(conn, entity))]
+pub fn create(conn: &mut DbConn, entity: NewAuditEntry) -> Result<AuditEntry> {
+	debug!("Recording action {} on {:?}", entity.action, entity.subject);
+	let entry = diesel::insert_into(al::table)
+		.values(entity)
+		.returning(AuditEntry::as_returning())
+		.get_result(conn)?;
+	trace!("Recorded audit entry: {:?}", entry);
+	Ok(entry)
+}
+
+/// Returns the most recent audit entries, newest first.
+#[instrument(skip(conn))]
+pub fn get_recent(conn: &mut DbConn, limit: i64) -> Result<Vec<AuditEntry>> {
+	let entries = al::table
+		.order(al::created_at.desc())
+		.limit(limit)
+		.select(AuditEntry::as_select())
+		.load(conn)?;
+	Ok(entries)
+}
+
+/// Returns the most recent audit entries matching `filter`, newest first.
+#[instrument(skip(conn))]
+pub fn find(conn: &mut DbConn, filter: &AuditFilter, limit: i64) -> Result<Vec<AuditEntry>> {
+	let mut query = al::table.into_boxed();
+	if let Some(player_id) = filter.player_id {
+		query = query.filter(al::player_id.eq(player_id));
+	}
+	if let Some(operator) = &filter.operator {
+		query = query.filter(al::operator.eq(operator));
+	}
+	if let Some(action) = &filter.action {
+		query = query.filter(al::action.eq(action));
+	}
+	if let Some(from) = filter.from {
+		query = query.filter(al::created_at.ge(from));
+	}
+	if let Some(until) = filter.until {
+		query = query.filter(al::created_at.lt(until));
+	}
+	let entries = query
+		.order(al::created_at.desc())
+		.limit(limit)
+		.select(AuditEntry::as_select())
+		.load(conn)?;
+	Ok(entries)
+}
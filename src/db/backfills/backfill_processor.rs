@@ -0,0 +1,87 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::time::sleep;
+use tracing::{debug, info, instrument, trace};
+
+use crate::db::backfills::{self, BACKFILL_CHUNK_SIZE};
+use crate::domain::app_state::{AppPool, AppQueue, AppState};
+use crate::domain::backfill::BackfillJobPayload;
+use crate::domain::jobs::{Job, JobType};
+use crate::job_queue::job_processor::{JobHandler, JobOutcome, JobWorker};
+use crate::{Error, ErrorKind};
+
+/// How long a single job keeps running chunks before it hands over to a new job.
+///
+/// Well below the default job timeout, and short enough that other job types get their
+/// turn between the jobs of a long backfill.
+pub const BACKFILL_JOB_BUDGET: Duration = Duration::from_secs(30);
+
+/// Pause between two chunks, so a backfill does not saturate the database.
+pub const BACKFILL_CHUNK_PAUSE: Duration = Duration::from_millis(50);
+
+/// A handler for [`JobType::Backfill`] jobs.
+///
+/// A job runs chunks of its backfill until the backfill completes or the job used up its
+/// [`BACKFILL_JOB_BUDGET`], and then enqueues a successor to continue from the cursor.
+pub struct BackfillHandler {
+	/// Database pool the chunks run on
+	pool: AppPool,
+	/// Job queue successor jobs are enqueued on
+	job_queue: AppQueue,
+}
+
+/// Workers running the [`BackfillHandler`].
+pub type BackfillProcessor = JobWorker<BackfillHandler>;
+
+impl JobHandler for BackfillHandler {
+	const JOB_TYPE: JobType = JobType::Backfill;
+	const WORKER_NAME: &'static str = "backfill-goblin";
+
+	/// Creates a new `BackfillHandler` with what it needs from the application state.
+	fn new(app_state: &AppState) -> Self {
+		Self {
+			pool: Arc::clone(&app_state.db_pool),
+			job_queue: Arc::clone(&app_state.job_queue),
+		}
+	}
+
+	#[instrument(skip(self, job))]
+	async fn handle(&self, job: Job) -> Result<JobOutcome, Error> {
+		debug!("Processing job: {}", job.id);
+		trace!("Job details: {:?}", job);
+
+		let BackfillJobPayload { name } = serde_json::from_value(job.payload.clone())?;
+		let backfill = backfills::find(&name)
+			.ok_or_else(|| Error::from((ErrorKind::InternalError, "Backfill is not registered")))?;
+
+		let deadline = Instant::now() + BACKFILL_JOB_BUDGET;
+		let mut progress = {
+			let mut conn = self.pool.get()?;
+			backfills::run_next_chunk(&mut conn, backfill, BACKFILL_CHUNK_SIZE)?
+		};
+		while !progress.is_completed() && Instant::now() < deadline {
+			sleep(BACKFILL_CHUNK_PAUSE).await;
+			let mut conn = self.pool.get()?;
+			progress = backfills::run_next_chunk(&mut conn, backfill, BACKFILL_CHUNK_SIZE)?;
+		}
+
+		if progress.is_completed() {
+			info!("Backfill '{}' is complete", name);
+		} else {
+			let mut conn = self.pool.get()?;
+			let next = backfills::enqueue(&mut conn, &self.job_queue, &name)?;
+			info!(
+				"Backfill '{}' processed {} rows so far, continuing in job {}",
+				name, progress.processed, next
+			);
+		}
+
+		debug!("Completed process job: {}", job.id);
+		Ok(Some(serde_json::json!({
+			"name": name,
+			"processed": progress.processed,
+			"completed": progress.is_completed(),
+		})))
+	}
+}
@@ -0,0 +1,179 @@
+//! Online data backfills.
+//!
+//! A data migration that would lock a big table is split in two: a schema migration that
+//! only adds what the new shape needs, such as a nullable column and a trigger keeping it
+//! in sync for new writes, and a [`Backfill`] converting the existing rows in chunks of
+//! [`BACKFILL_CHUNK_SIZE`]. Every chunk commits together with the backfill's cursor in the
+//! `backfill` table, so an interrupted backfill resumes after its last committed chunk.
+//!
+//! [`crate::db::migrations::schedule_backfills`] enqueues every registered backfill that
+//! has not completed, and the [`backfill_processor::BackfillProcessor`] runs the chunks.
+
+use chrono::Utc;
+use diesel::prelude::*;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::db::DbConn;
+use crate::domain::backfill::{BackfillJobPayload, BackfillProgress};
+use crate::domain::jobs::{JobKey, JobStatus, JobType};
+use crate::job_queue::{JobPriority, JobQueue};
+use crate::schema::{backfill, job};
+use crate::{Error, ErrorKind, Result};
+
+pub mod backfill_processor;
+mod retired_password_hashes;
+
+/// Rows every chunk processes, small enough to keep row locks short.
+pub const BACKFILL_CHUNK_SIZE: i64 = 1000;
+
+/// Every backfill the migrations module schedules, in registration order.
+pub static BACKFILLS: &[&dyn Backfill] = &[&retired_password_hashes::RetiredPasswordHashes];
+
+/// Outcome of a single chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackfillChunk {
+	/// Rows the chunk walked over, including rows that needed no change
+	pub rows: i64,
+	/// Greatest key of the chunk, `None` if it was empty
+	pub last_key: Option<Uuid>,
+}
+
+/// A data migration that runs in chunks next to the schema migration it belongs to.
+///
+/// Implementations walk their table in key order and must be idempotent, a chunk can be
+/// repeated when its job is retried before the cursor was committed.
+pub trait Backfill: Send + Sync {
+	/// Unique name the progress is stored under.
+	fn name(&self) -> &'static str;
+
+	/// Processes up to `limit` rows with a key greater than `after`, in key order.
+	fn run_chunk(
+		&self,
+		conn: &mut DbConn,
+		after: Option<Uuid>,
+		limit: i64,
+	) -> Result<BackfillChunk>;
+}
+
+/// Looks up a registered backfill by its name.
+pub fn find(name: &str) -> Option<&'static dyn Backfill> {
+	BACKFILLS
+		.iter()
+		.copied()
+		.find(|backfill| backfill.name() == name)
+}
+
+/// Retrieves the progress of a backfill, creating it if the backfill never ran.
+pub fn ensure_progress(conn: &mut DbConn, name: &str) -> Result<BackfillProgress> {
+	diesel::insert_into(backfill::table)
+		.values(backfill::name.eq(name))
+		.on_conflict_do_nothing()
+		.execute(conn)?;
+	get_progress(conn, name)
+}
+
+/// Retrieves the progress of a backfill.
+pub fn get_progress(conn: &mut DbConn, name: &str) -> Result<BackfillProgress> {
+	let progress = backfill::table
+		.find(name)
+		.select(BackfillProgress::as_select())
+		.first(conn)?;
+	Ok(progress)
+}
+
+/// Lists the progress of every backfill that was scheduled, ordered by name.
+pub fn list_progress(conn: &mut DbConn) -> Result<Vec<BackfillProgress>> {
+	let progress = backfill::table
+		.order_by(backfill::name.asc())
+		.select(BackfillProgress::as_select())
+		.load(conn)?;
+	Ok(progress)
+}
+
+/// Whether the job recorded on `progress` is still waiting to run, running or retrying.
+pub fn has_outstanding_job(conn: &mut DbConn, progress: &BackfillProgress) -> Result<bool> {
+	let Some(job_id) = progress.job_id else {
+		return Ok(false);
+	};
+
+	let outstanding = diesel::select(diesel::dsl::exists(
+		job::table.find(job_id).filter(
+			job::status
+				.eq_any([
+					JobStatus::Pending,
+					JobStatus::InProgress,
+					JobStatus::CancelRequested,
+				])
+				.or(job::status
+					.eq(JobStatus::Failed)
+					.and(job::retries.le(job::max_retries))),
+		),
+	))
+	.get_result(conn)?;
+	Ok(outstanding)
+}
+
+/// Enqueues a job running the next chunks of a backfill and records it on the progress.
+pub fn enqueue(conn: &mut DbConn, job_queue: &JobQueue, name: &str) -> Result<JobKey> {
+	let job_id = job_queue.enqueue(
+		JobType::Backfill,
+		BackfillJobPayload {
+			name: name.to_string(),
+		},
+		JobPriority::Low,
+		Utc::now(),
+	)?;
+	diesel::update(backfill::table.find(name))
+		.set(backfill::job_id.eq(Some(job_id)))
+		.execute(conn)?;
+
+	debug!("Enqueued job {} for backfill '{}'", job_id, name);
+	Ok(job_id)
+}
+
+/// Runs the next chunk of `backfill` and commits it together with the new cursor.
+///
+/// The progress row is locked for the chunk, so two jobs for the same backfill never
+/// process the same rows at once. A completed backfill is returned unchanged.
+pub fn run_next_chunk(
+	conn: &mut DbConn,
+	backfill: &dyn Backfill,
+	limit: i64,
+) -> Result<BackfillProgress> {
+	conn.transaction(|conn| -> Result<BackfillProgress> {
+		let progress: BackfillProgress = backfill::table
+			.find(backfill.name())
+			.select(BackfillProgress::as_select())
+			.for_update()
+			.first(conn)
+			.optional()?
+			.ok_or_else(|| {
+				Error::from((ErrorKind::InternalError, "Backfill was never scheduled"))
+			})?;
+		if progress.is_completed() {
+			return Ok(progress);
+		}
+
+		let chunk = backfill.run_chunk(conn, progress.cursor, limit)?;
+		let now = Utc::now();
+		let completed_at = (chunk.rows < limit).then_some(now);
+		let updated = diesel::update(backfill::table.find(backfill.name()))
+			.set((
+				backfill::cursor.eq(chunk.last_key.or(progress.cursor)),
+				backfill::processed.eq(progress.processed + chunk.rows),
+				backfill::started_at.eq(progress.started_at.unwrap_or(now)),
+				backfill::completed_at.eq(completed_at),
+			))
+			.returning(BackfillProgress::as_returning())
+			.get_result(conn)?;
+
+		if updated.is_completed() {
+			info!(
+				"Backfill '{}' completed after {} rows",
+				updated.name, updated.processed
+			);
+		}
+		Ok(updated)
+	})
+}
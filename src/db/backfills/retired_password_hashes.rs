@@ -0,0 +1,61 @@
+//! Erases password hashes whose version was retired, see [`crate::auth::password`].
+//!
+//! Hashes can only be rewritten with the password, which players only send when they log
+//! in, so outdated hashes are rehashed at login instead. Hashes of the
+//! [`RETIRED_HASH_VERSIONS`](crate::auth::password::RETIRED_HASH_VERSIONS) are no longer
+//! trusted: this backfill replaces them with a marker no password matches, and their
+//! players have to reset their password.
+
+use diesel::prelude::*;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::Result;
+use crate::auth::password::{self, RESET_REQUIRED_HASH, RETIRED_HASHES_BACKFILL};
+use crate::db::DbConn;
+use crate::db::backfills::{Backfill, BackfillChunk};
+use crate::schema::player;
+
+pub struct RetiredPasswordHashes;
+
+impl Backfill for RetiredPasswordHashes {
+	fn name(&self) -> &'static str {
+		RETIRED_HASHES_BACKFILL
+	}
+
+	fn run_chunk(
+		&self,
+		conn: &mut DbConn,
+		after: Option<Uuid>,
+		limit: i64,
+	) -> Result<BackfillChunk> {
+		let mut query = player::table
+			.select((player::id, player::pwd_hash))
+			.order_by(player::id.asc())
+			.limit(limit)
+			.into_boxed();
+		if let Some(after) = after {
+			query = query.filter(player::id.gt(after));
+		}
+		let chunk: Vec<(Uuid, String)> = query.load(conn)?;
+
+		let retired: Vec<Uuid> = chunk
+			.iter()
+			.filter(|(_, pwd_hash)| {
+				pwd_hash != RESET_REQUIRED_HASH && password::is_retired(pwd_hash)
+			})
+			.map(|(id, _)| *id)
+			.collect();
+		if !retired.is_empty() {
+			diesel::update(player::table.filter(player::id.eq_any(&retired)))
+				.set(player::pwd_hash.eq(RESET_REQUIRED_HASH))
+				.execute(conn)?;
+			info!("Erased {} retired password hashes", retired.len());
+		}
+
+		Ok(BackfillChunk {
+			rows: chunk.len() as i64,
+			last_key: chunk.last().map(|(id, _)| *id),
+		})
+	}
+}
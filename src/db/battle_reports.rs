@@ -0,0 +1,91 @@
+//! Database access layer for battle report entities.
+//!
+//! This module provides operations for storing battle reports, paginated
+//! per-player listings, and retention-based pruning.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use tracing::{debug, instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::combat::{BattleReport, BattleReportKey, NewBattleReport};
+use crate::domain::player::PlayerKey;
+use crate::schema::battle_report as br;
+
+/// Creates a new battle report.
+#[instrument(skip(conn, entity))]
+pub fn create(conn: &mut DbConn, entity: NewBattleReport) -> Result<BattleReport> {
+	debug!(
+		"Creating battle report for attacker {} and defender {}",
+		entity.attacker_id, entity.defender_id
+	);
+	let report = diesel::insert_into(br::table)
+		.values(entity)
+		.returning(BattleReport::as_returning())
+		.get_result(conn)?;
+	trace!("Created battle report: {:?}", report);
+	Ok(report)
+}
+
+/// Retrieves a battle report by its ID.
+#[instrument(skip(conn))]
+pub fn get_by_id(conn: &mut DbConn, report_id: &BattleReportKey) -> Result<BattleReport> {
+	let report = br::table.find(report_id).first(conn)?;
+	Ok(report)
+}
+
+/// Retrieves a battle report by its ID, if it exists.
+#[instrument(skip(conn))]
+pub fn find_by_id(conn: &mut DbConn, report_id: &BattleReportKey) -> Result<Option<BattleReport>> {
+	let report = br::table.find(report_id).first(conn).optional()?;
+	Ok(report)
+}
+
+/// Retrieves a page of battle reports the player took part in, newest first.
+///
+/// # Returns
+/// A tuple of the reports on the requested page and the total number of reports
+/// available for the player.
+#[instrument(skip(conn))]
+pub fn get_page_for_player(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	limit: i64,
+	offset: i64,
+) -> Result<(Vec<BattleReport>, i64)> {
+	let involves_player = br::attacker_id
+		.eq(player_key)
+		.or(br::defender_id.eq(player_key));
+
+	let total = br::table.filter(involves_player).count().get_result(conn)?;
+	let reports = br::table
+		.filter(involves_player)
+		.order((br::fought_at.desc(), br::id.desc()))
+		.limit(limit)
+		.offset(offset)
+		.select(BattleReport::as_select())
+		.load(conn)?;
+	Ok((reports, total))
+}
+
+/// Deletes all battle reports fought before the cutoff.
+///
+/// # Returns
+/// The number of deleted reports
+#[instrument(skip(conn))]
+pub fn delete_older_than(conn: &mut DbConn, cutoff: DateTime<Utc>) -> Result<usize> {
+	let count = diesel::delete(br::table.filter(br::fought_at.lt(cutoff))).execute(conn)?;
+	debug!("Deleted {} battle reports older than {}", count, cutoff);
+	Ok(count)
+}
+
+/// Counts the battles a player won, as attacker or defender.
+#[instrument(skip(conn))]
+pub fn count_wins(conn: &mut DbConn, player_key: &PlayerKey) -> Result<i64> {
+	let wins = br::table
+		.filter(br::winner_id.eq(player_key))
+		.count()
+		.get_result(conn)?;
+	Ok(wins)
+}
@@ -0,0 +1,95 @@
+//! Database access layer for the building upgrade ledger.
+//!
+//! This module provides operations for recording started upgrades and completing or
+//! cancelling them once they are confirmed or aborted.
+
+use chrono::Utc;
+use diesel::prelude::*;
+use tracing::{instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::building::upgrade::{BuildingUpgrade, BuildingUpgradeKey, NewBuildingUpgrade};
+use crate::domain::player::buildings::PlayerBuildingKey;
+use crate::schema::building_upgrade as bu;
+
+/// Records a started building upgrade.
+#[instrument(skip(conn, entity))]
+pub fn create(conn: &mut DbConn, entity: NewBuildingUpgrade) -> Result<BuildingUpgrade> {
+	let upgrade = diesel::insert_into(bu::table)
+		.values(entity)
+		.returning(BuildingUpgrade::as_returning())
+		.get_result(conn)?;
+	trace!("Recorded building upgrade: {:?}", upgrade);
+	Ok(upgrade)
+}
+
+/// Completes the pending upgrade of a player building, returning the number of updated rows.
+#[instrument(skip(conn))]
+pub fn complete_pending(conn: &mut DbConn, player_bld_id: &PlayerBuildingKey) -> Result<usize> {
+	let rows = diesel::update(
+		bu::table
+			.filter(bu::player_building_id.eq(player_bld_id))
+			.filter(bu::completed_at.is_null())
+			.filter(bu::cancelled_at.is_null()),
+	)
+	.set(bu::completed_at.eq(Some(Utc::now())))
+	.execute(conn)?;
+	Ok(rows)
+}
+
+/// Cancels the pending upgrade of a player building.
+///
+/// Returns the cancelled upgrade, or `None` if the building had no pending upgrade.
+#[instrument(skip(conn))]
+pub fn cancel_pending(
+	conn: &mut DbConn,
+	player_bld_id: &PlayerBuildingKey,
+) -> Result<Option<BuildingUpgrade>> {
+	let upgrade = diesel::update(
+		bu::table
+			.filter(bu::player_building_id.eq(player_bld_id))
+			.filter(bu::completed_at.is_null())
+			.filter(bu::cancelled_at.is_null()),
+	)
+	.set(bu::cancelled_at.eq(Some(Utc::now())))
+	.returning(BuildingUpgrade::as_returning())
+	.get_result(conn)
+	.optional()?;
+	trace!("Cancelled building upgrade: {:?}", upgrade);
+	Ok(upgrade)
+}
+
+/// Completes a single upgrade, used for queued upgrades that complete one at a time.
+#[instrument(skip(conn))]
+pub fn complete(conn: &mut DbConn, upgrade_id: &BuildingUpgradeKey) -> Result<BuildingUpgrade> {
+	let upgrade = diesel::update(bu::table.find(upgrade_id))
+		.set(bu::completed_at.eq(Some(Utc::now())))
+		.returning(BuildingUpgrade::as_returning())
+		.get_result(conn)?;
+	trace!("Completed building upgrade: {:?}", upgrade);
+	Ok(upgrade)
+}
+
+/// Deletes an upgrade that never got underway.
+///
+/// Used for cleanup when scheduling a queued upgrade fails after it was recorded.
+#[instrument(skip(conn))]
+pub fn delete(conn: &mut DbConn, upgrade_id: &BuildingUpgradeKey) -> Result<usize> {
+	let count = diesel::delete(bu::table.find(upgrade_id)).execute(conn)?;
+	Ok(count)
+}
+
+/// Retrieves the upgrades started for a player building, oldest first.
+#[instrument(skip(conn))]
+pub fn get_for_player_building(
+	conn: &mut DbConn,
+	player_bld_id: &PlayerBuildingKey,
+) -> Result<Vec<BuildingUpgrade>> {
+	let upgrades = bu::table
+		.filter(bu::player_building_id.eq(player_bld_id))
+		.order((bu::created_at.asc(), bu::id.asc()))
+		.select(BuildingUpgrade::as_select())
+		.load(conn)?;
+	Ok(upgrades)
+}
@@ -0,0 +1,78 @@
+//! Database access layer for caravan entities.
+//!
+//! This module provides operations for dispatching caravans, linking them to their
+//! delivery job, and marking them delivered.
+
+use chrono::Utc;
+use diesel::prelude::*;
+use tracing::{debug, instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::caravan::{Caravan, CaravanKey, CaravanStatus, NewCaravan};
+use crate::domain::jobs::JobKey;
+use crate::schema::caravan as cv;
+
+/// Creates a new caravan.
+#[instrument(skip(conn, entity))]
+pub fn create(conn: &mut DbConn, entity: NewCaravan) -> Result<Caravan> {
+	debug!(
+		"Dispatching caravan from player {} to player {}",
+		entity.sender_id, entity.receiver_id
+	);
+	let caravan = diesel::insert_into(cv::table)
+		.values(entity)
+		.returning(Caravan::as_returning())
+		.get_result(conn)?;
+	trace!("Created caravan: {:?}", caravan);
+	Ok(caravan)
+}
+
+/// Retrieves a caravan by its ID.
+#[instrument(skip(conn))]
+pub fn get_by_id(conn: &mut DbConn, caravan_id: &CaravanKey) -> Result<Caravan> {
+	let caravan = cv::table.find(caravan_id).first(conn)?;
+	Ok(caravan)
+}
+
+/// Retrieves a caravan by its ID, returning `None` if it does not exist.
+#[instrument(skip(conn))]
+pub fn find_by_id(conn: &mut DbConn, caravan_id: &CaravanKey) -> Result<Option<Caravan>> {
+	let caravan = cv::table.find(caravan_id).first(conn).optional()?;
+	Ok(caravan)
+}
+
+/// Retrieves a caravan by its ID and locks it for the rest of the transaction.
+#[instrument(skip(conn))]
+pub fn lock_by_id(conn: &mut DbConn, caravan_id: &CaravanKey) -> Result<Caravan> {
+	let caravan = cv::table
+		.find(caravan_id)
+		.select(Caravan::as_select())
+		.for_update()
+		.first(conn)?;
+	Ok(caravan)
+}
+
+/// Links a caravan to the job delivering it.
+#[instrument(skip(conn))]
+pub fn set_job_id(conn: &mut DbConn, caravan_id: &CaravanKey, job_key: &JobKey) -> Result<Caravan> {
+	let caravan = diesel::update(cv::table.find(caravan_id))
+		.set(cv::job_id.eq(Some(job_key)))
+		.returning(Caravan::as_returning())
+		.get_result(conn)?;
+	Ok(caravan)
+}
+
+/// Marks a caravan as delivered.
+#[instrument(skip(conn))]
+pub fn deliver(conn: &mut DbConn, caravan_id: &CaravanKey) -> Result<Caravan> {
+	debug!("Delivering caravan {}", caravan_id);
+	let caravan = diesel::update(cv::table.find(caravan_id))
+		.set((
+			cv::status.eq(CaravanStatus::Delivered),
+			cv::delivered_at.eq(Some(Utc::now())),
+		))
+		.returning(Caravan::as_returning())
+		.get_result(conn)?;
+	Ok(caravan)
+}
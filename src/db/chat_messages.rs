@@ -0,0 +1,81 @@
+//! Database access layer for chat messages.
+//!
+//! This module provides operations for posting messages, reading back the history of a
+//! channel, and counting a player's recent messages for flood control.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use tracing::{debug, instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::alliance::AllianceKey;
+use crate::domain::chat::{ChatChannel, ChatLine, ChatMessage, NewChatMessage};
+use crate::domain::player::PlayerKey;
+use crate::schema::{chat_message as cm, player};
+
+/// Posts a new message.
+#[instrument(skip(conn, entity))]
+pub fn create(conn: &mut DbConn, entity: NewChatMessage) -> Result<ChatMessage> {
+	debug!(
+		"Posting chat message in {} by player {:?}",
+		entity.channel, entity.player_id
+	);
+	let message = diesel::insert_into(cm::table)
+		.values(entity)
+		.returning(ChatMessage::as_returning())
+		.get_result(conn)?;
+	trace!("Created chat message: {:?}", message);
+	Ok(message)
+}
+
+/// Retrieves up to `limit` messages of a channel posted before `before`, newest first.
+///
+/// The alliance channel is read for `alliance_id`, the global channel ignores it.
+#[instrument(skip(conn))]
+pub fn get_history(
+	conn: &mut DbConn,
+	channel: ChatChannel,
+	alliance_id: Option<&AllianceKey>,
+	before: Option<DateTime<Utc>>,
+	limit: i64,
+) -> Result<Vec<ChatLine>> {
+	let mut query = cm::table
+		.left_join(player::table)
+		.filter(cm::channel.eq(channel))
+		.into_boxed();
+	if let Some(alliance_id) = alliance_id {
+		query = query.filter(cm::alliance_id.eq(alliance_id));
+	}
+	if let Some(before) = before {
+		query = query.filter(cm::created_at.lt(before));
+	}
+
+	let lines = query
+		.order((cm::created_at.desc(), cm::id.desc()))
+		.limit(limit)
+		.select((ChatMessage::as_select(), player::name.nullable()))
+		.load::<(ChatMessage, Option<String>)>(conn)?
+		.into_iter()
+		.map(|(message, player_name)| ChatLine {
+			message,
+			player_name,
+		})
+		.collect();
+	Ok(lines)
+}
+
+/// Counts the messages a player posted since `since`, in any channel.
+#[instrument(skip(conn))]
+pub fn count_by_player_since(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	since: DateTime<Utc>,
+) -> Result<i64> {
+	let count = cm::table
+		.filter(cm::player_id.eq(player_id))
+		.filter(cm::created_at.ge(since))
+		.count()
+		.get_result(conn)?;
+	Ok(count)
+}
@@ -0,0 +1,48 @@
+//! Database access layer for players muted in the chat.
+
+use diesel::prelude::*;
+use tracing::{debug, instrument};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::chat::{ChatMute, NewChatMute};
+use crate::domain::player::PlayerKey;
+use crate::schema::chat_mute as mute;
+
+/// Mutes a player, replacing the mute they already have.
+#[instrument(skip(conn))]
+pub fn upsert(conn: &mut DbConn, entity: &NewChatMute) -> Result<ChatMute> {
+	debug!(
+		"Muting player {} until {}",
+		entity.player_id, entity.muted_until
+	);
+	let mute = diesel::insert_into(mute::table)
+		.values(entity)
+		.on_conflict(mute::player_id)
+		.do_update()
+		.set(entity)
+		.returning(ChatMute::as_returning())
+		.get_result(conn)?;
+	Ok(mute)
+}
+
+/// Retrieves the mute of a player, if they have one, including one that ran out.
+#[instrument(skip(conn))]
+pub fn find_by_player(conn: &mut DbConn, player_id: &PlayerKey) -> Result<Option<ChatMute>> {
+	let mute = mute::table
+		.find(player_id)
+		.select(ChatMute::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(mute)
+}
+
+/// Lifts the mute of a player.
+///
+/// # Returns
+/// The number of lifted mutes, `0` if the player was not muted
+#[instrument(skip(conn))]
+pub fn delete(conn: &mut DbConn, player_id: &PlayerKey) -> Result<usize> {
+	let count = diesel::delete(mute::table.find(player_id)).execute(conn)?;
+	Ok(count)
+}
@@ -0,0 +1,171 @@
+//! Database access layer for construction queue entities.
+//!
+//! This module provides operations for managing the construction queue, including creating
+//! entries, completing them and starting the next one, and querying a player's active queue.
+
+use chrono::Utc;
+use diesel::prelude::*;
+use tracing::{debug, instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::building::construction::{
+	ConstructionQueueEntry, ConstructionQueueKey, ConstructionStatus, NewConstructionQueueEntry,
+};
+use crate::domain::jobs::JobKey;
+use crate::domain::player::PlayerKey;
+use crate::domain::player::buildings::PlayerBuildingKey;
+use crate::schema::construction_queue as cq;
+
+/// Creates a new construction queue entry.
+#[instrument(skip(conn, entity))]
+pub fn create(
+	conn: &mut DbConn,
+	entity: NewConstructionQueueEntry,
+) -> Result<ConstructionQueueEntry> {
+	debug!(
+		"Creating construction queue entry for player {} building {}",
+		entity.player_id, entity.player_building_id
+	);
+	let entry = diesel::insert_into(cq::table)
+		.values(entity)
+		.returning(ConstructionQueueEntry::as_returning())
+		.get_result(conn)?;
+	trace!("Created construction queue entry: {:?}", entry);
+	Ok(entry)
+}
+
+/// Retrieves a player's active (pending or in-progress) entries, in the order they complete.
+#[instrument(skip(conn))]
+pub fn get_active_for_player(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+) -> Result<Vec<ConstructionQueueEntry>> {
+	let entries = cq::table
+		.filter(cq::player_id.eq(player_key))
+		.filter(cq::status.ne(ConstructionStatus::Completed))
+		.order(cq::completes_at.asc())
+		.select(ConstructionQueueEntry::as_select())
+		.load(conn)?;
+	Ok(entries)
+}
+
+/// Counts the active entries queued for a single player building.
+#[instrument(skip(conn))]
+pub fn count_active_for_building(
+	conn: &mut DbConn,
+	player_bld_key: &PlayerBuildingKey,
+) -> Result<i64> {
+	let count = cq::table
+		.filter(cq::player_building_id.eq(player_bld_key))
+		.filter(cq::status.ne(ConstructionStatus::Completed))
+		.count()
+		.get_result(conn)?;
+	Ok(count)
+}
+
+/// Retrieves a construction queue entry by its ID.
+#[instrument(skip(conn))]
+pub fn get_by_id(
+	conn: &mut DbConn,
+	entry_id: &ConstructionQueueKey,
+) -> Result<ConstructionQueueEntry> {
+	let entry = cq::table
+		.find(entry_id)
+		.select(ConstructionQueueEntry::as_select())
+		.first(conn)?;
+	Ok(entry)
+}
+
+/// Retrieves a construction queue entry by its ID, returning `None` if it does not exist.
+#[instrument(skip(conn))]
+pub fn find_by_id(
+	conn: &mut DbConn,
+	entry_id: &ConstructionQueueKey,
+) -> Result<Option<ConstructionQueueEntry>> {
+	let entry = cq::table
+		.find(entry_id)
+		.select(ConstructionQueueEntry::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(entry)
+}
+
+/// Sets the job ID for a construction queue entry.
+///
+/// Called after scheduling the completion job.
+#[instrument(skip(conn))]
+pub fn set_job_id(
+	conn: &mut DbConn,
+	entry_id: &ConstructionQueueKey,
+	job_key: &JobKey,
+) -> Result<ConstructionQueueEntry> {
+	let entry = diesel::update(cq::table.find(entry_id))
+		.set(cq::job_id.eq(Some(job_key)))
+		.returning(ConstructionQueueEntry::as_returning())
+		.get_result(conn)?;
+	Ok(entry)
+}
+
+/// Marks a construction queue entry as completed, unless it already is.
+///
+/// The status check and the update are a single statement, so a completion job running
+/// twice applies the upgrade only once.
+///
+/// # Returns
+/// The completed entry, or `None` if the entry was already completed
+#[instrument(skip(conn))]
+pub fn complete(
+	conn: &mut DbConn,
+	entry_id: &ConstructionQueueKey,
+) -> Result<Option<ConstructionQueueEntry>> {
+	debug!("Completing construction queue entry {}", entry_id);
+	let entry = diesel::update(cq::table.find(entry_id))
+		.filter(cq::status.ne(ConstructionStatus::Completed))
+		.set((
+			cq::status.eq(ConstructionStatus::Completed),
+			cq::completed_at.eq(Some(Utc::now())),
+		))
+		.returning(ConstructionQueueEntry::as_returning())
+		.get_result(conn)
+		.optional()?;
+	trace!("Completed construction queue entry: {:?}", entry);
+	Ok(entry)
+}
+
+/// Moves the next pending entry of a player's queue in progress.
+///
+/// # Returns
+/// The started entry, or `None` if nothing is waiting in the queue
+#[instrument(skip(conn))]
+pub fn start_next(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+) -> Result<Option<ConstructionQueueEntry>> {
+	let next: Option<ConstructionQueueKey> = cq::table
+		.filter(cq::player_id.eq(player_key))
+		.filter(cq::status.eq(ConstructionStatus::Pending))
+		.order(cq::completes_at.asc())
+		.select(cq::id)
+		.first(conn)
+		.optional()?;
+	let Some(next) = next else {
+		return Ok(None);
+	};
+	let entry = diesel::update(cq::table.find(next))
+		.set(cq::status.eq(ConstructionStatus::InProgress))
+		.returning(ConstructionQueueEntry::as_returning())
+		.get_result(conn)?;
+	trace!("Started construction queue entry: {:?}", entry);
+	Ok(Some(entry))
+}
+
+/// Deletes a construction queue entry.
+///
+/// Used for cleanup when job scheduling fails after entry creation.
+#[instrument(skip(conn))]
+pub fn delete(conn: &mut DbConn, entry_id: &ConstructionQueueKey) -> Result<usize> {
+	debug!("Deleting construction queue entry {}", entry_id);
+	let count = diesel::delete(cq::table.find(entry_id)).execute(conn)?;
+	Ok(count)
+}
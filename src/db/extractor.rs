@@ -1,11 +1,13 @@
 use axum::extract::{FromRef, FromRequestParts};
-use axum::http::StatusCode;
 use axum::http::request::Parts;
 use derive_more::Deref;
 use tracing::{error, trace};
 
-use crate::db::DbConn;
+use crate::db::{DbConn, settlements};
 use crate::domain::app_state::AppPool;
+use crate::domain::auth::AuthenticatedUser;
+use crate::domain::error::{Error, ErrorKind};
+use crate::domain::settlement::{Settlement, SettlementKey};
 
 /// An extractor that acquires a database connection from a configured connection pool.
 ///
@@ -16,8 +18,8 @@ use crate::domain::app_state::AppPool;
 /// in an [axum] handler without manually managing the pool.
 ///
 /// # Error Handling
-/// If getting a connection from the pool fails, this type returns a tuple
-/// containing [`StatusCode::INTERNAL_SERVER_ERROR`] as well as the error message.
+/// If getting a connection from the pool fails, this type rejects the request with an
+/// internal [`Error`], the cause of which only ends up in the logs.
 ///
 /// # Notes
 /// 1. The reliability of the database operations depends on proper configuration
@@ -33,16 +35,65 @@ where
 	S: Send + Sync,
 	AppPool: FromRef<S>,
 {
-	type Rejection = (StatusCode, String);
+	type Rejection = Error;
 
 	async fn from_request_parts(_parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
 		let pool = AppPool::from_ref(state);
 		let conn = pool.get().map_err(|err| {
 			error!("Failed to get a database connection: {}", err);
-			(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+			Error::from(err)
 		})?;
 		trace!("Acquired a database connection.");
 
 		Ok(Self(conn))
 	}
 }
+
+/// Header selecting the settlement a game request acts on.
+pub const SETTLEMENT_HEADER: &str = "x-settlement-id";
+
+/// An extractor for the settlement of the authenticated player a request acts on.
+///
+/// Clients select a settlement with the [`SETTLEMENT_HEADER`], requests without it act on
+/// the player's capital. Settlements of other players are indistinguishable from missing
+/// ones. Must run behind the authentication middleware.
+#[derive(Debug, Clone, Deref)]
+pub struct SelectedSettlement(pub Settlement);
+
+impl<S> FromRequestParts<S> for SelectedSettlement
+where
+	S: Send + Sync,
+	AppPool: FromRef<S>,
+{
+	type Rejection = Error;
+
+	async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+		let Some(player) = parts.extensions.get::<AuthenticatedUser>() else {
+			return Err(Error::from((
+				ErrorKind::NoSessionError,
+				"Not authenticated",
+			)));
+		};
+		let selected = parts
+			.headers
+			.get(SETTLEMENT_HEADER)
+			.map(|value| {
+				value
+					.to_str()
+					.ok()
+					.and_then(|value| SettlementKey::parse_str(value.trim()).ok())
+					.ok_or_else(|| Error::from((ErrorKind::InvalidData, "Invalid settlement ID")))
+			})
+			.transpose()?;
+
+		let mut conn = AppPool::from_ref(state).get()?;
+		let settlement = match selected {
+			Some(settlement_id) => settlements::find_owned(&mut conn, &player.id, &settlement_id)?
+				.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Settlement not found")))?,
+			None => settlements::get_capital(&mut conn, &player.id)?,
+		};
+		trace!("Selected settlement {}", settlement.id);
+
+		Ok(Self(settlement))
+	}
+}
@@ -0,0 +1,37 @@
+//! Database access layer for the faction change history of players.
+
+use diesel::prelude::*;
+use tracing::{debug, instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::player::PlayerKey;
+use crate::domain::player::faction_change::{FactionChange, NewFactionChange};
+use crate::schema::faction_change as fc;
+
+/// Records a faction change.
+#[instrument(skip(conn))]
+pub fn create(conn: &mut DbConn, entity: NewFactionChange) -> Result<FactionChange> {
+	debug!(
+		"Recording faction change of player {} from {} to {}",
+		entity.player_id, entity.from_faction, entity.to_faction
+	);
+	let change = diesel::insert_into(fc::table)
+		.values(entity)
+		.returning(FactionChange::as_returning())
+		.get_result(conn)?;
+	trace!(?change, "Recorded faction change");
+	Ok(change)
+}
+
+/// Retrieves the latest faction change of a player, `None` if they never changed.
+#[instrument(skip(conn))]
+pub fn find_latest(conn: &mut DbConn, player_key: &PlayerKey) -> Result<Option<FactionChange>> {
+	let change = fc::table
+		.filter(fc::player_id.eq(player_key))
+		.order(fc::changed_at.desc())
+		.select(FactionChange::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(change)
+}
@@ -0,0 +1,89 @@
+//! Database access layer for the faction standings.
+//!
+//! This module aggregates the statistics of every playable faction into its standing and
+//! reads the standings of the last aggregation.
+
+use chrono::{DateTime, Utc};
+use diesel::dsl::max;
+use diesel::prelude::*;
+use diesel::sql_types::{Nullable, Timestamptz};
+use tracing::{debug, instrument};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::factions::FactionStanding;
+use crate::schema::faction_standing as fs;
+
+/// Replaces the standing of every playable faction.
+///
+/// AIDEV-NOTE: players count by their current faction, so a battle won before a faction
+/// change scores for the faction the winner is in now. Power is the sum of the army points of
+/// the members as the leaderboards last scored them. The war score counts the battles won
+/// against players of another faction fought after `$2`, every battle if it is `NULL`.
+const REPLACE_STANDINGS_SQL: &str = "
+	INSERT INTO faction_standing (faction, players, power, war_score, rank, computed_at)
+	SELECT f.id,
+	       COALESCE(members.players, 0),
+	       COALESCE(members.power, 0),
+	       COALESCE(wins.war_score, 0),
+	       RANK() OVER (ORDER BY COALESCE(wins.war_score, 0) DESC, COALESCE(members.power, 0) DESC),
+	       $1
+	FROM faction f
+	LEFT JOIN (SELECT p.faction, COUNT(*) AS players, SUM(COALESCE(s.unit_points, 0))::BIGINT AS power
+	           FROM player p
+	           LEFT JOIN player_score s ON s.player_id = p.id
+	           WHERE p.anonymized_at IS NULL
+	           GROUP BY p.faction) members ON members.faction = f.id
+	LEFT JOIN (SELECT winner.faction, COUNT(*) AS war_score
+	           FROM battle_report br
+	           JOIN player winner ON winner.id = br.winner_id
+	           JOIN player loser ON loser.id = CASE WHEN br.winner_id = br.attacker_id
+	                                                THEN br.defender_id
+	                                                ELSE br.attacker_id END
+	           WHERE winner.faction <> loser.faction
+	             AND br.fought_at <= $1
+	             AND ($2::TIMESTAMPTZ IS NULL OR br.fought_at > $2)
+	           GROUP BY winner.faction) wins ON wins.faction = f.id
+	WHERE f.id <> 'neutral'
+	ON CONFLICT (faction) DO UPDATE
+	    SET players     = EXCLUDED.players,
+	        power       = EXCLUDED.power,
+	        war_score   = EXCLUDED.war_score,
+	        rank        = EXCLUDED.rank,
+	        computed_at = EXCLUDED.computed_at";
+
+/// Aggregates the standing of every playable faction, counting the battles fought since
+/// `since` into the war score.
+///
+/// # Returns
+/// The number of standings written
+#[instrument(skip(conn))]
+pub fn replace_standings(
+	conn: &mut DbConn,
+	computed_at: DateTime<Utc>,
+	since: Option<DateTime<Utc>>,
+) -> Result<usize> {
+	let written = diesel::sql_query(REPLACE_STANDINGS_SQL)
+		.bind::<Timestamptz, _>(computed_at)
+		.bind::<Nullable<Timestamptz>, _>(since)
+		.execute(conn)?;
+	debug!("Aggregated {} faction standings", written);
+	Ok(written)
+}
+
+/// Retrieves the standings of the last aggregation, leading faction first.
+#[instrument(skip(conn))]
+pub fn get_all(conn: &mut DbConn) -> Result<Vec<FactionStanding>> {
+	let standings = fs::table
+		.order((fs::rank.asc(), fs::faction.asc()))
+		.select(FactionStanding::as_select())
+		.load(conn)?;
+	Ok(standings)
+}
+
+/// Returns when the standings were last aggregated, `None` if they never were.
+#[instrument(skip(conn))]
+pub fn last_computed_at(conn: &mut DbConn) -> Result<Option<DateTime<Utc>>> {
+	let computed_at = fs::table.select(max(fs::computed_at)).first(conn)?;
+	Ok(computed_at)
+}
@@ -0,0 +1,173 @@
+//! Database access layer for heroes, their levels and the heroes recruited by players.
+
+use diesel::prelude::*;
+use tracing::{instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::error::{Error, ErrorKind};
+use crate::domain::hero::{Hero, HeroKey, HeroLevel, NewPlayerHero, PlayerHero, PlayerHeroKey};
+use crate::domain::player::PlayerKey;
+use crate::domain::player::buildings::PlayerBuildingKey;
+use crate::schema::{hero, hero_level, player_hero as ph};
+
+/// Retrieves all hero definitions, sorted by name.
+#[instrument(skip(conn))]
+pub fn get_all(conn: &mut DbConn) -> Result<Vec<Hero>> {
+	let heroes = hero::table
+		.order(hero::name)
+		.select(Hero::as_select())
+		.load(conn)?;
+	Ok(heroes)
+}
+
+/// Retrieves a single hero by its ID.
+#[instrument(skip(conn))]
+pub fn get_by_id(conn: &mut DbConn, hero_id: &HeroKey) -> Result<Hero> {
+	hero::table
+		.find(hero_id)
+		.select(Hero::as_select())
+		.first(conn)
+		.optional()?
+		.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Hero not found")))
+}
+
+/// Retrieves a single hero by its unique name.
+#[instrument(skip(conn))]
+pub fn get_by_name(conn: &mut DbConn, name: &str) -> Result<Hero> {
+	let result = hero::table
+		.filter(hero::name.eq(name))
+		.select(Hero::as_select())
+		.first(conn)?;
+	Ok(result)
+}
+
+/// Retrieves the levels of a hero, lowest first.
+#[instrument(skip(conn))]
+pub fn get_levels(conn: &mut DbConn, hero_id: &HeroKey) -> Result<Vec<HeroLevel>> {
+	let levels = hero_level::table
+		.filter(hero_level::hero_id.eq(hero_id))
+		.order(hero_level::level)
+		.select(HeroLevel::as_select())
+		.load(conn)?;
+	Ok(levels)
+}
+
+/// Retrieves a single level of a hero.
+#[instrument(skip(conn))]
+pub fn get_level(conn: &mut DbConn, hero_id: &HeroKey, level: i32) -> Result<HeroLevel> {
+	let result = hero_level::table
+		.find((hero_id, level))
+		.select(HeroLevel::as_select())
+		.first(conn)?;
+	Ok(result)
+}
+
+/// Retrieves the heroes a player recruited together with their definitions, sorted by name.
+#[instrument(skip(conn))]
+pub fn get_for_player(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+) -> Result<Vec<(PlayerHero, Hero)>> {
+	let heroes = ph::table
+		.inner_join(hero::table)
+		.filter(ph::player_id.eq(player_key))
+		.order(hero::name)
+		.select((PlayerHero::as_select(), Hero::as_select()))
+		.load(conn)?;
+	Ok(heroes)
+}
+
+/// Recruits a hero for a player.
+///
+/// Returns `None` without changing anything if the player recruited the hero before.
+#[instrument(skip(conn))]
+pub fn recruit(conn: &mut DbConn, entity: NewPlayerHero) -> Result<Option<PlayerHero>> {
+	let recruited = diesel::insert_into(ph::table)
+		.values(entity)
+		.on_conflict((ph::player_id, ph::hero_id))
+		.do_nothing()
+		.returning(PlayerHero::as_returning())
+		.get_result(conn)
+		.optional()?;
+	trace!("Recruited player hero: {:?}", recruited);
+	Ok(recruited)
+}
+
+/// Retrieves the player's recruit of a hero and locks it for the rest of the transaction.
+///
+/// Fails with [`ErrorKind::NotFoundError`] if the player did not recruit the hero.
+#[instrument(skip(conn))]
+pub fn lock_recruited(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	hero_key: &HeroKey,
+) -> Result<PlayerHero> {
+	ph::table
+		.filter(ph::player_id.eq(player_key))
+		.filter(ph::hero_id.eq(hero_key))
+		.select(PlayerHero::as_select())
+		.for_update()
+		.first(conn)
+		.optional()?
+		.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Hero not recruited")))
+}
+
+/// Finds the hero running a building, if any.
+#[instrument(skip(conn))]
+pub fn find_by_building(
+	conn: &mut DbConn,
+	player_bld_id: &PlayerBuildingKey,
+) -> Result<Option<PlayerHero>> {
+	let found = ph::table
+		.filter(ph::player_building_id.eq(player_bld_id))
+		.select(PlayerHero::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(found)
+}
+
+/// Finds the hero leading a player's army, if any.
+#[instrument(skip(conn))]
+pub fn find_army_leader(conn: &mut DbConn, player_key: &PlayerKey) -> Result<Option<PlayerHero>> {
+	let found = ph::table
+		.filter(ph::player_id.eq(player_key))
+		.filter(ph::leads_army.eq(true))
+		.select(PlayerHero::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(found)
+}
+
+/// Sets the level and experience of a player hero.
+#[instrument(skip(conn))]
+pub fn set_progress(
+	conn: &mut DbConn,
+	id: &PlayerHeroKey,
+	level: i32,
+	xp: i64,
+) -> Result<PlayerHero> {
+	let updated = diesel::update(ph::table.find(id))
+		.set((ph::level.eq(level), ph::xp.eq(xp)))
+		.returning(PlayerHero::as_returning())
+		.get_result(conn)?;
+	Ok(updated)
+}
+
+/// Sets what a player hero is assigned to, unassigning them if neither is given.
+#[instrument(skip(conn))]
+pub fn set_assignment(
+	conn: &mut DbConn,
+	id: &PlayerHeroKey,
+	player_bld_id: Option<PlayerBuildingKey>,
+	leads_army: bool,
+) -> Result<PlayerHero> {
+	let updated = diesel::update(ph::table.find(id))
+		.set((
+			ph::player_building_id.eq(player_bld_id),
+			ph::leads_army.eq(leads_army),
+		))
+		.returning(PlayerHero::as_returning())
+		.get_result(conn)?;
+	Ok(updated)
+}
@@ -0,0 +1,82 @@
+//! Database access layer for intel report entities.
+//!
+//! This module provides operations for storing intel reports, paginated listings of the
+//! reports a player's scouts wrote, and retention-based pruning.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use tracing::{debug, instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::combat::{IntelReport, IntelReportKey, NewIntelReport};
+use crate::domain::player::PlayerKey;
+use crate::schema::intel_report as ir;
+
+/// Creates a new intel report.
+#[instrument(skip(conn, entity))]
+pub fn create(conn: &mut DbConn, entity: NewIntelReport) -> Result<IntelReport> {
+	debug!(
+		"Creating intel report for attacker {} on defender {}",
+		entity.attacker_id, entity.defender_id
+	);
+	let report = diesel::insert_into(ir::table)
+		.values(entity)
+		.returning(IntelReport::as_returning())
+		.get_result(conn)?;
+	trace!("Created intel report: {:?}", report);
+	Ok(report)
+}
+
+/// Retrieves an intel report, if the player's scouts wrote it.
+#[instrument(skip(conn))]
+pub fn find_for_attacker(
+	conn: &mut DbConn,
+	attacker_id: &PlayerKey,
+	report_id: &IntelReportKey,
+) -> Result<Option<IntelReport>> {
+	let report = ir::table
+		.find(report_id)
+		.filter(ir::attacker_id.eq(attacker_id))
+		.select(IntelReport::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(report)
+}
+
+/// Retrieves a page of the intel reports the player's scouts wrote, newest first.
+///
+/// # Returns
+/// A tuple of the reports on the requested page and the total number of reports
+/// available for the player.
+#[instrument(skip(conn))]
+pub fn get_page_for_attacker(
+	conn: &mut DbConn,
+	attacker_id: &PlayerKey,
+	limit: i64,
+	offset: i64,
+) -> Result<(Vec<IntelReport>, i64)> {
+	let total = ir::table
+		.filter(ir::attacker_id.eq(attacker_id))
+		.count()
+		.get_result(conn)?;
+	let reports = ir::table
+		.filter(ir::attacker_id.eq(attacker_id))
+		.order((ir::scouted_at.desc(), ir::id.desc()))
+		.limit(limit)
+		.offset(offset)
+		.select(IntelReport::as_select())
+		.load(conn)?;
+	Ok((reports, total))
+}
+
+/// Deletes all intel reports scouted before the cutoff.
+///
+/// # Returns
+/// The number of deleted reports
+#[instrument(skip(conn))]
+pub fn delete_older_than(conn: &mut DbConn, cutoff: DateTime<Utc>) -> Result<usize> {
+	let count = diesel::delete(ir::table.filter(ir::scouted_at.lt(cutoff))).execute(conn)?;
+	debug!("Deleted {} intel reports older than {}", count, cutoff);
+	Ok(count)
+}
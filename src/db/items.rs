@@ -0,0 +1,59 @@
+//! Database access layer for item definitions.
+
+use diesel::prelude::*;
+use tracing::instrument;
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::item::{Item, ItemKey};
+use crate::schema::item;
+
+/// Retrieves all item definitions, sorted by name.
+#[instrument(skip(conn))]
+pub fn get_all(conn: &mut DbConn) -> Result<Vec<Item>> {
+	let items = item::table
+		.order(item::name)
+		.select(Item::as_select())
+		.load(conn)?;
+	Ok(items)
+}
+
+/// Retrieves a single item by its ID.
+#[instrument(skip(conn))]
+pub fn get_by_id(conn: &mut DbConn, item_id: &ItemKey) -> Result<Item> {
+	let result = item::table
+		.find(item_id)
+		.select(Item::as_select())
+		.first(conn)?;
+	Ok(result)
+}
+
+/// Retrieves all matching items by ID.
+#[instrument(skip(conn))]
+pub fn get_all_by_id(conn: &mut DbConn, item_ids: &[ItemKey]) -> Result<Vec<Item>> {
+	let items = item::table
+		.filter(item::id.eq_any(item_ids))
+		.select(Item::as_select())
+		.load(conn)?;
+	Ok(items)
+}
+
+/// Retrieves a single item by its unique name.
+#[instrument(skip(conn))]
+pub fn get_by_name(conn: &mut DbConn, name: &str) -> Result<Item> {
+	let result = item::table
+		.filter(item::name.eq(name))
+		.select(Item::as_select())
+		.first(conn)?;
+	Ok(result)
+}
+
+/// Retrieves all matching items by name.
+#[instrument(skip(conn))]
+pub fn get_all_by_name(conn: &mut DbConn, names: &[&str]) -> Result<Vec<Item>> {
+	let items = item::table
+		.filter(item::name.eq_any(names))
+		.select(Item::as_select())
+		.load(conn)?;
+	Ok(items)
+}
@@ -0,0 +1,188 @@
+//! Database access layer for player scores and leaderboard entities.
+//!
+//! This module calculates the scores of players from their buildings and units, snapshots the
+//! rankings of every board, and reads pages of the last snapshot.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{Array, BigInt, Nullable, Text, Timestamptz, Uuid as SqlUuid};
+use tracing::{debug, instrument};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::leaderboard::{LeaderboardCategory, LeaderboardEntry, PlayerScore};
+use crate::domain::player::PlayerKey;
+use crate::domain::unit::TIER_BONUS_PERCENT;
+use crate::schema::{alliance, leaderboard_entry as le, player, player_score as ps};
+
+/// Calculates the score of every player, or of `player_id` only, from scratch.
+///
+/// AIDEV-NOTE: `research_buildings` are scored as tech points instead of building points.
+/// Both levels and units are summed as they are, 0-level buildings score nothing. Units score
+/// their attack and defense with the bonus of their tier, rounded like [`Unit::attack`].
+///
+/// [`Unit::attack`]: crate::domain::unit::Unit::attack
+const REFRESH_SCORES_SQL: &str = "
+	INSERT INTO player_score (player_id, building_points, unit_points, tech_points)
+	SELECT p.id,
+	       COALESCE((SELECT SUM(pb.level)
+	                 FROM player_building pb
+	                 JOIN building b ON b.id = pb.building_id
+	                 WHERE pb.player_id = p.id AND b.name <> ALL ($1)), 0),
+	       COALESCE((SELECT SUM(pu.quantity * (u.base_atk * (100 + $3 * (u.tier - 1)) / 100 +
+	                                            u.base_def * (100 + $3 * (u.tier - 1)) / 100))::BIGINT
+	                 FROM player_unit pu
+	                 JOIN unit u ON u.id = pu.unit_id
+	                 WHERE pu.player_id = p.id), 0),
+	       COALESCE((SELECT SUM(pb.level)
+	                 FROM player_building pb
+	                 JOIN building b ON b.id = pb.building_id
+	                 WHERE pb.player_id = p.id AND b.name = ANY ($1)), 0)
+	FROM player p
+	WHERE $2::UUID IS NULL OR p.id = $2
+	ON CONFLICT (player_id) DO UPDATE
+	    SET building_points = EXCLUDED.building_points,
+	        unit_points     = EXCLUDED.unit_points,
+	        tech_points     = EXCLUDED.tech_points";
+
+/// Ranks the players by one of their points. Players who left the game or hide their stats
+/// are not ranked.
+fn rank_players_sql(category: &str, points: &str) -> String {
+	format!(
+		"INSERT INTO leaderboard_entry (category, subject_id, rank, score, snapshot_at)
+		 SELECT '{category}', s.player_id, RANK() OVER (ORDER BY {points} DESC), {points}, $1
+		 FROM player_score s
+		 JOIN player p ON p.id = s.player_id
+		 JOIN player_privacy pp ON pp.player_id = s.player_id
+		 WHERE p.anonymized_at IS NULL AND NOT pp.hide_stats"
+	)
+}
+
+/// Ranks the alliances by the total points of their members, hidden stats included since
+/// only the sum is shown.
+const RANK_ALLIANCES_SQL: &str = "
+	INSERT INTO leaderboard_entry (category, subject_id, rank, score, snapshot_at)
+	SELECT 'alliance', am.alliance_id,
+	       RANK() OVER (ORDER BY SUM(s.building_points + s.unit_points + s.tech_points) DESC),
+	       SUM(s.building_points + s.unit_points + s.tech_points)::BIGINT, $1
+	FROM alliance_member am
+	JOIN player_score s ON s.player_id = am.player_id
+	GROUP BY am.alliance_id";
+
+/// Recalculates the score of every player.
+///
+/// # Returns
+/// The number of scores written
+#[instrument(skip(conn))]
+pub fn refresh_scores(conn: &mut DbConn, research_buildings: &[&str]) -> Result<usize> {
+	let refreshed = diesel::sql_query(REFRESH_SCORES_SQL)
+		.bind::<Array<Text>, _>(research_buildings)
+		.bind::<Nullable<SqlUuid>, _>(None::<PlayerKey>)
+		.bind::<BigInt, _>(TIER_BONUS_PERCENT)
+		.execute(conn)?;
+	debug!("Refreshed {} player scores", refreshed);
+	Ok(refreshed)
+}
+
+/// Recalculates the score of a single player.
+#[instrument(skip(conn))]
+pub fn refresh_player_score(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	research_buildings: &[&str],
+) -> Result<PlayerScore> {
+	diesel::sql_query(REFRESH_SCORES_SQL)
+		.bind::<Array<Text>, _>(research_buildings)
+		.bind::<Nullable<SqlUuid>, _>(Some(*player_id))
+		.bind::<BigInt, _>(TIER_BONUS_PERCENT)
+		.execute(conn)?;
+	let score = ps::table
+		.find(player_id)
+		.select(PlayerScore::as_select())
+		.first(conn)?;
+	Ok(score)
+}
+
+/// Retrieves the score of a player, if it was calculated yet.
+#[instrument(skip(conn))]
+pub fn find_score(conn: &mut DbConn, player_id: &PlayerKey) -> Result<Option<PlayerScore>> {
+	let score = ps::table
+		.find(player_id)
+		.select(PlayerScore::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(score)
+}
+
+/// Replaces the rankings of every board with a snapshot of the current scores.
+///
+/// # Returns
+/// The number of ranked players and alliances
+#[instrument(skip(conn))]
+pub fn replace_snapshot(conn: &mut DbConn, snapshot_at: DateTime<Utc>) -> Result<usize> {
+	diesel::delete(le::table).execute(conn)?;
+	let mut ranked = 0;
+	for (category, points) in [
+		("power", "s.unit_points"),
+		("economy", "s.building_points + s.tech_points"),
+	] {
+		ranked += diesel::sql_query(rank_players_sql(category, points))
+			.bind::<Timestamptz, _>(snapshot_at)
+			.execute(conn)?;
+	}
+	ranked += diesel::sql_query(RANK_ALLIANCES_SQL)
+		.bind::<Timestamptz, _>(snapshot_at)
+		.execute(conn)?;
+	debug!("Snapshotted {} leaderboard entries", ranked);
+	Ok(ranked)
+}
+
+/// Retrieves a page of a board with the names of the ranked players or alliances, best
+/// first, along with the number of entries on the board.
+#[instrument(skip(conn))]
+pub fn get_page(
+	conn: &mut DbConn,
+	category: LeaderboardCategory,
+	limit: i64,
+	offset: i64,
+) -> Result<(Vec<(LeaderboardEntry, String)>, i64)> {
+	let entries = if category == LeaderboardCategory::Alliance {
+		le::table
+			.inner_join(alliance::table.on(alliance::id.eq(le::subject_id)))
+			.filter(le::category.eq(category))
+			.order((le::rank.asc(), le::subject_id.asc()))
+			.limit(limit)
+			.offset(offset)
+			.select((LeaderboardEntry::as_select(), alliance::name))
+			.load(conn)?
+	} else {
+		le::table
+			.inner_join(player::table.on(player::id.eq(le::subject_id)))
+			.filter(le::category.eq(category))
+			.order((le::rank.asc(), le::subject_id.asc()))
+			.limit(limit)
+			.offset(offset)
+			.select((LeaderboardEntry::as_select(), player::name))
+			.load(conn)?
+	};
+	let total = le::table
+		.filter(le::category.eq(category))
+		.count()
+		.get_result(conn)?;
+	Ok((entries, total))
+}
+
+/// Retrieves the entry of a player or alliance on a board, if it was ranked.
+#[instrument(skip(conn))]
+pub fn find_entry(
+	conn: &mut DbConn,
+	category: LeaderboardCategory,
+	subject_id: &uuid::Uuid,
+) -> Result<Option<LeaderboardEntry>> {
+	let entry = le::table
+		.find((category, subject_id))
+		.select(LeaderboardEntry::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(entry)
+}
@@ -0,0 +1,167 @@
+//! Database access layer for time-limited events, their objectives and their shop offers.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use tracing::{instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::limited_event::{
+	EventObjective, EventObjectiveKind, EventOffer, EventOfferKey, LimitedEvent, LimitedEventKey,
+	NewEventObjective, NewEventOffer, NewLimitedEvent,
+};
+use crate::schema::{game_event, game_event_objective as geo, game_event_offer as offer};
+
+/// Creates a new limited event.
+#[instrument(skip(conn))]
+pub fn create(conn: &mut DbConn, entity: NewLimitedEvent) -> Result<LimitedEvent> {
+	let event = diesel::insert_into(game_event::table)
+		.values(entity)
+		.returning(LimitedEvent::as_returning())
+		.get_result(conn)?;
+	trace!("Created limited event: {:?}", event);
+	Ok(event)
+}
+
+/// Creates the objectives of an event.
+#[instrument(skip(conn))]
+pub fn create_objectives(
+	conn: &mut DbConn,
+	entities: Vec<NewEventObjective>,
+) -> Result<Vec<EventObjective>> {
+	let objectives = diesel::insert_into(geo::table)
+		.values(entities)
+		.returning(EventObjective::as_returning())
+		.get_results(conn)?;
+	Ok(objectives)
+}
+
+/// Creates the shop offers of an event.
+#[instrument(skip(conn))]
+pub fn create_offers(conn: &mut DbConn, entities: Vec<NewEventOffer>) -> Result<Vec<EventOffer>> {
+	let offers = diesel::insert_into(offer::table)
+		.values(entities)
+		.returning(EventOffer::as_returning())
+		.get_results(conn)?;
+	Ok(offers)
+}
+
+/// Finds an event by its ID.
+#[instrument(skip(conn))]
+pub fn find_by_id(conn: &mut DbConn, event_id: &LimitedEventKey) -> Result<Option<LimitedEvent>> {
+	let event = game_event::table
+		.find(event_id)
+		.select(LimitedEvent::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(event)
+}
+
+/// Finds an event by its unique name.
+#[instrument(skip(conn))]
+pub fn find_by_name(conn: &mut DbConn, name: &str) -> Result<Option<LimitedEvent>> {
+	let event = game_event::table
+		.filter(game_event::name.eq(name))
+		.select(LimitedEvent::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(event)
+}
+
+/// Locks an event for the rest of the transaction.
+#[instrument(skip(conn))]
+pub fn lock_by_id(conn: &mut DbConn, event_id: &LimitedEventKey) -> Result<LimitedEvent> {
+	let event = game_event::table
+		.find(event_id)
+		.select(LimitedEvent::as_select())
+		.for_update()
+		.first(conn)?;
+	Ok(event)
+}
+
+/// Retrieves the events running at `now`, ending first listed first.
+#[instrument(skip(conn))]
+pub fn get_running(conn: &mut DbConn, now: DateTime<Utc>) -> Result<Vec<LimitedEvent>> {
+	let events = game_event::table
+		.filter(game_event::closed_at.is_null())
+		.filter(game_event::starts_at.le(now))
+		.filter(game_event::ends_at.gt(now))
+		.order((game_event::ends_at, game_event::name))
+		.select(LimitedEvent::as_select())
+		.load(conn)?;
+	Ok(events)
+}
+
+/// Retrieves the objectives of an event.
+#[instrument(skip(conn))]
+pub fn get_objectives(
+	conn: &mut DbConn,
+	event_id: &LimitedEventKey,
+) -> Result<Vec<EventObjective>> {
+	let objectives = geo::table
+		.filter(geo::event_id.eq(event_id))
+		.order(geo::id)
+		.select(EventObjective::as_select())
+		.load(conn)?;
+	Ok(objectives)
+}
+
+/// Retrieves the objectives of `kind` of every event running at `now`.
+#[instrument(skip(conn))]
+pub fn get_running_objectives(
+	conn: &mut DbConn,
+	kind: EventObjectiveKind,
+	now: DateTime<Utc>,
+) -> Result<Vec<EventObjective>> {
+	let objectives = geo::table
+		.inner_join(game_event::table)
+		.filter(geo::kind.eq(kind))
+		.filter(game_event::closed_at.is_null())
+		.filter(game_event::starts_at.le(now))
+		.filter(game_event::ends_at.gt(now))
+		.order(geo::id)
+		.select(EventObjective::as_select())
+		.load(conn)?;
+	Ok(objectives)
+}
+
+/// Retrieves the shop offers of an event, cheapest first.
+#[instrument(skip(conn))]
+pub fn get_offers(conn: &mut DbConn, event_id: &LimitedEventKey) -> Result<Vec<EventOffer>> {
+	let offers = offer::table
+		.filter(offer::event_id.eq(event_id))
+		.order((offer::price, offer::id))
+		.select(EventOffer::as_select())
+		.load(conn)?;
+	Ok(offers)
+}
+
+/// Finds an offer of an event's shop.
+#[instrument(skip(conn))]
+pub fn find_offer(
+	conn: &mut DbConn,
+	event_id: &LimitedEventKey,
+	offer_id: &EventOfferKey,
+) -> Result<Option<EventOffer>> {
+	let found = offer::table
+		.filter(offer::event_id.eq(event_id))
+		.filter(offer::id.eq(offer_id))
+		.select(EventOffer::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(found)
+}
+
+/// Marks an event as closed at `now`.
+#[instrument(skip(conn))]
+pub fn close(
+	conn: &mut DbConn,
+	event_id: &LimitedEventKey,
+	now: DateTime<Utc>,
+) -> Result<LimitedEvent> {
+	let event = diesel::update(game_event::table.find(event_id))
+		.set(game_event::closed_at.eq(now))
+		.returning(LimitedEvent::as_returning())
+		.get_result(conn)?;
+	Ok(event)
+}
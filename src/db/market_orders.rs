@@ -0,0 +1,188 @@
+//! Database access layer for market order entities.
+//!
+//! This module provides operations for placing, filling and cancelling market orders,
+//! selecting the resting orders an incoming order crosses, and aggregating the order book.
+
+use diesel::prelude::*;
+use diesel::sql_types::BigInt;
+use tracing::{debug, instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::market::{
+	MarketOrder, MarketOrderKey, MarketOrderSide, MarketOrderStatus, NewMarketOrder, PriceLevel,
+};
+use crate::domain::player::PlayerKey;
+use crate::domain::player::resource::ResourceType;
+use crate::schema::market_order as mo;
+
+/// Key of the advisory lock serializing changes to the market.
+const MARKET_LOCK_KEY: i64 = 0x6d61_726b_6574;
+
+/// Creates a new market order.
+#[instrument(skip(conn, entity))]
+pub fn create(conn: &mut DbConn, entity: NewMarketOrder) -> Result<MarketOrder> {
+	debug!(
+		"Creating {:?} order for {} {:?} at {} from player {}",
+		entity.side, entity.quantity, entity.resource, entity.price, entity.player_id
+	);
+	let order = diesel::insert_into(mo::table)
+		.values(entity)
+		.returning(MarketOrder::as_returning())
+		.get_result(conn)?;
+	trace!("Created market order: {:?}", order);
+	Ok(order)
+}
+
+/// Retrieves a market order by its ID.
+#[instrument(skip(conn))]
+pub fn get_by_id(conn: &mut DbConn, order_id: &MarketOrderKey) -> Result<MarketOrder> {
+	let order = mo::table.find(order_id).first(conn)?;
+	Ok(order)
+}
+
+/// Retrieves a market order, if it belongs to the player, and locks it for the rest of the
+/// transaction.
+#[instrument(skip(conn))]
+pub fn lock_owned(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	order_id: &MarketOrderKey,
+) -> Result<Option<MarketOrder>> {
+	let order = mo::table
+		.find(order_id)
+		.filter(mo::player_id.eq(player_id))
+		.select(MarketOrder::as_select())
+		.for_update()
+		.first(conn)
+		.optional()?;
+	Ok(order)
+}
+
+/// Retrieves the player's most recent orders, newest first.
+#[instrument(skip(conn))]
+pub fn get_for_player(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	limit: i64,
+) -> Result<Vec<MarketOrder>> {
+	let orders = mo::table
+		.filter(mo::player_id.eq(player_key))
+		.order((mo::created_at.desc(), mo::id.desc()))
+		.limit(limit)
+		.select(MarketOrder::as_select())
+		.load(conn)?;
+	Ok(orders)
+}
+
+/// Takes the transaction-scoped lock that serializes all changes to the market.
+///
+/// Matching moves resources between several players in one transaction, so two
+/// concurrent matches could otherwise lock the same players' resources in opposite order.
+#[instrument(skip(conn))]
+pub fn lock_market(conn: &mut DbConn) -> Result<()> {
+	diesel::sql_query("SELECT pg_advisory_xact_lock($1)")
+		.bind::<BigInt, _>(MARKET_LOCK_KEY)
+		.execute(conn)?;
+	Ok(())
+}
+
+/// Retrieves and locks the open orders an incoming order would trade with, best first.
+///
+/// Orders of the `side` that is resting in the book are matched best price first, then
+/// oldest first. Orders of `player_key` are skipped, players never trade with themselves.
+#[instrument(skip(conn))]
+pub fn lock_crossing(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	resource: ResourceType,
+	side: MarketOrderSide,
+	limit_price: i64,
+) -> Result<Vec<MarketOrder>> {
+	let open_orders = mo::table
+		.filter(mo::resource.eq(resource))
+		.filter(mo::side.eq(side))
+		.filter(mo::status.eq(MarketOrderStatus::Open))
+		.filter(mo::player_id.ne(player_key))
+		.select(MarketOrder::as_select());
+	// Row locks cannot be taken on boxed queries, so both orderings are spelled out
+	let orders = match side {
+		MarketOrderSide::Sell => open_orders
+			.filter(mo::price.le(limit_price))
+			.order((mo::price.asc(), mo::created_at.asc(), mo::id.asc()))
+			.for_update()
+			.load(conn)?,
+		MarketOrderSide::Buy => open_orders
+			.filter(mo::price.ge(limit_price))
+			.order((mo::price.desc(), mo::created_at.asc(), mo::id.asc()))
+			.for_update()
+			.load(conn)?,
+	};
+	trace!("Found {} crossing orders", orders.len());
+	Ok(orders)
+}
+
+/// Sets the unfilled remainder of an order, marking it filled once nothing remains.
+#[instrument(skip(conn))]
+pub fn set_remaining(
+	conn: &mut DbConn,
+	order_id: &MarketOrderKey,
+	remaining: i64,
+) -> Result<MarketOrder> {
+	let status = if remaining == 0 {
+		MarketOrderStatus::Filled
+	} else {
+		MarketOrderStatus::Open
+	};
+	let order = diesel::update(mo::table.find(order_id))
+		.set((mo::remaining.eq(remaining), mo::status.eq(status)))
+		.returning(MarketOrder::as_returning())
+		.get_result(conn)?;
+	Ok(order)
+}
+
+/// Cancels a market order.
+#[instrument(skip(conn))]
+pub fn cancel(conn: &mut DbConn, order_id: &MarketOrderKey) -> Result<MarketOrder> {
+	debug!("Cancelling market order {}", order_id);
+	let order = diesel::update(mo::table.find(order_id))
+		.set(mo::status.eq(MarketOrderStatus::Cancelled))
+		.returning(MarketOrder::as_returning())
+		.get_result(conn)?;
+	Ok(order)
+}
+
+/// Aggregates the open orders of one side of a resource's book per price, best first.
+#[instrument(skip(conn))]
+pub fn get_book_side(
+	conn: &mut DbConn,
+	resource: ResourceType,
+	side: MarketOrderSide,
+	depth: i64,
+) -> Result<Vec<PriceLevel>> {
+	let query = mo::table
+		.filter(mo::resource.eq(resource))
+		.filter(mo::side.eq(side))
+		.filter(mo::status.eq(MarketOrderStatus::Open))
+		.group_by(mo::price)
+		.select((
+			mo::price,
+			diesel::dsl::sql::<BigInt>("sum(remaining)::bigint"),
+			diesel::dsl::count_star(),
+		))
+		.limit(depth)
+		.into_boxed();
+	let levels: Vec<(i64, i64, i64)> = match side {
+		MarketOrderSide::Sell => query.order(mo::price.asc()),
+		MarketOrderSide::Buy => query.order(mo::price.desc()),
+	}
+	.load(conn)?;
+	Ok(levels
+		.into_iter()
+		.map(|(price, quantity, orders)| PriceLevel {
+			price,
+			quantity,
+			orders,
+		})
+		.collect())
+}
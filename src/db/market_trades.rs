@@ -0,0 +1,38 @@
+//! Database access layer for market trade entities.
+//!
+//! This module provides operations for recording the fills of market orders and
+//! retrieving the trades an order took part in.
+
+use diesel::prelude::*;
+use tracing::{instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::market::{MarketOrderKey, MarketTrade, NewMarketTrade};
+use crate::schema::market_trade as mt;
+
+/// Records a new market trade.
+#[instrument(skip(conn, entity))]
+pub fn create(conn: &mut DbConn, entity: NewMarketTrade) -> Result<MarketTrade> {
+	let trade = diesel::insert_into(mt::table)
+		.values(entity)
+		.returning(MarketTrade::as_returning())
+		.get_result(conn)?;
+	trace!("Recorded market trade: {:?}", trade);
+	Ok(trade)
+}
+
+/// Retrieves the trades an order took part in, oldest first.
+#[instrument(skip(conn))]
+pub fn get_for_order(conn: &mut DbConn, order_id: &MarketOrderKey) -> Result<Vec<MarketTrade>> {
+	let trades = mt::table
+		.filter(
+			mt::buy_order_id
+				.eq(order_id)
+				.or(mt::sell_order_id.eq(order_id)),
+		)
+		.order((mt::created_at.asc(), mt::id.asc()))
+		.select(MarketTrade::as_select())
+		.load(conn)?;
+	Ok(trades)
+}
@@ -0,0 +1,167 @@
+//! Database access layer for in-game mail.
+//!
+//! This module provides operations for delivering messages, paginated inbox listings,
+//! unread counts, and marking and deleting the messages of a recipient.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use tracing::{debug, instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::message::{Message, MessageKey, NewMessage};
+use crate::domain::player::PlayerKey;
+use crate::schema::message as msg;
+
+/// Delivers a new message to its recipient.
+#[instrument(skip(conn, entity))]
+pub fn create(conn: &mut DbConn, entity: NewMessage) -> Result<Message> {
+	debug!(
+		"Delivering {} message to player {}",
+		entity.kind, entity.recipient_id
+	);
+	let message = diesel::insert_into(msg::table)
+		.values(entity)
+		.returning(Message::as_returning())
+		.get_result(conn)?;
+	trace!("Created message: {:?}", message);
+	Ok(message)
+}
+
+/// Retrieves a message, if it is in the inbox of the recipient.
+#[instrument(skip(conn))]
+pub fn find_for_recipient(
+	conn: &mut DbConn,
+	recipient_id: &PlayerKey,
+	message_id: &MessageKey,
+) -> Result<Option<Message>> {
+	let message = msg::table
+		.find(message_id)
+		.filter(msg::recipient_id.eq(recipient_id))
+		.select(Message::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(message)
+}
+
+/// Retrieves a page of the inbox of the recipient, newest first.
+///
+/// # Returns
+/// A tuple of the messages on the requested page and the total number of messages
+/// matching, only unread ones if `unread_only` is set.
+#[instrument(skip(conn))]
+pub fn get_page_for_recipient(
+	conn: &mut DbConn,
+	recipient_id: &PlayerKey,
+	unread_only: bool,
+	limit: i64,
+	offset: i64,
+) -> Result<(Vec<Message>, i64)> {
+	let inbox = || {
+		let mut query = msg::table
+			.filter(msg::recipient_id.eq(recipient_id))
+			.into_boxed();
+		if unread_only {
+			query = query.filter(msg::read_at.is_null());
+		}
+		query
+	};
+
+	let total = inbox().count().get_result(conn)?;
+	let messages = inbox()
+		.order((msg::created_at.desc(), msg::id.desc()))
+		.limit(limit)
+		.offset(offset)
+		.select(Message::as_select())
+		.load(conn)?;
+	Ok((messages, total))
+}
+
+/// Counts the unread messages in the inbox of the recipient.
+#[instrument(skip(conn))]
+pub fn count_unread(conn: &mut DbConn, recipient_id: &PlayerKey) -> Result<i64> {
+	let unread = msg::table
+		.filter(msg::recipient_id.eq(recipient_id))
+		.filter(msg::read_at.is_null())
+		.count()
+		.get_result(conn)?;
+	Ok(unread)
+}
+
+/// Marks a message of the recipient as read at `now`, unless it already was.
+///
+/// # Returns
+/// The message, or `None` if it is not in the inbox of the recipient
+#[instrument(skip(conn))]
+pub fn mark_read(
+	conn: &mut DbConn,
+	recipient_id: &PlayerKey,
+	message_id: &MessageKey,
+	now: DateTime<Utc>,
+) -> Result<Option<Message>> {
+	let updated = diesel::update(
+		msg::table
+			.find(message_id)
+			.filter(msg::recipient_id.eq(recipient_id))
+			.filter(msg::read_at.is_null()),
+	)
+	.set(msg::read_at.eq(now))
+	.returning(Message::as_returning())
+	.get_result(conn)
+	.optional()?;
+	match updated {
+		Some(message) => Ok(Some(message)),
+		None => find_for_recipient(conn, recipient_id, message_id),
+	}
+}
+
+/// Marks every unread message of the recipient as read at `now`.
+///
+/// # Returns
+/// The number of messages marked as read
+#[instrument(skip(conn))]
+pub fn mark_all_read(
+	conn: &mut DbConn,
+	recipient_id: &PlayerKey,
+	now: DateTime<Utc>,
+) -> Result<usize> {
+	let count = diesel::update(
+		msg::table
+			.filter(msg::recipient_id.eq(recipient_id))
+			.filter(msg::read_at.is_null()),
+	)
+	.set(msg::read_at.eq(now))
+	.execute(conn)?;
+	Ok(count)
+}
+
+/// Deletes a message from the inbox of the recipient.
+///
+/// # Returns
+/// The number of deleted messages, `0` if it is not in the inbox of the recipient
+#[instrument(skip(conn))]
+pub fn delete(
+	conn: &mut DbConn,
+	recipient_id: &PlayerKey,
+	message_id: &MessageKey,
+) -> Result<usize> {
+	let count = diesel::delete(
+		msg::table
+			.find(message_id)
+			.filter(msg::recipient_id.eq(recipient_id)),
+	)
+	.execute(conn)?;
+	Ok(count)
+}
+
+/// Deletes every message in the inbox of the recipient.
+///
+/// # Returns
+/// The number of deleted messages
+#[instrument(skip(conn))]
+pub fn delete_by_recipient(conn: &mut DbConn, recipient_id: &PlayerKey) -> Result<usize> {
+	let count =
+		diesel::delete(msg::table.filter(msg::recipient_id.eq(recipient_id))).execute(conn)?;
+	debug!("Deleted {} messages of player {}", count, recipient_id);
+	Ok(count)
+}
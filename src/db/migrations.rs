@@ -2,6 +2,10 @@ use std::error::Error;
 
 use diesel::pg::Pg;
 use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
+use tracing::info;
+
+use crate::db::{DbConn, backfills};
+use crate::job_queue::JobQueue;
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
 
@@ -21,3 +25,29 @@ pub fn run_pending(
 
 	Ok(())
 }
+
+/// Enqueues every registered backfill that has not completed yet.
+///
+/// Runs after [`run_pending`] at startup, once the job queue is up. Backfills with a job
+/// still waiting or running are left alone, so a restart never runs one twice. See
+/// [`crate::db::backfills`].
+///
+/// # Returns
+/// The number of backfills that were enqueued
+pub fn schedule_backfills(conn: &mut DbConn, job_queue: &JobQueue) -> crate::Result<usize> {
+	let mut enqueued = 0;
+	for backfill in backfills::BACKFILLS {
+		let progress = backfills::ensure_progress(conn, backfill.name())?;
+		if progress.is_completed() || backfills::has_outstanding_job(conn, &progress)? {
+			continue;
+		}
+		backfills::enqueue(conn, job_queue, backfill.name())?;
+		info!(
+			"Scheduled backfill '{}', resuming after {} rows",
+			backfill.name(),
+			progress.processed
+		);
+		enqueued += 1;
+	}
+	Ok(enqueued)
+}
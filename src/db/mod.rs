@@ -1,21 +1,62 @@
 pub mod active_modifiers;
+pub mod alliance_donations;
+pub mod alliance_invites;
+pub mod alliance_members;
+pub mod alliances;
+pub mod api_keys;
+pub mod army_presets;
+pub mod audit_log;
+pub mod backfills;
+pub mod battle_reports;
 pub mod building_levels;
 pub mod building_requirements;
 pub mod building_unit_types;
+pub mod building_upgrades;
 pub mod buildings;
+pub mod caravans;
+pub mod chat_messages;
+pub mod chat_mutes;
 pub mod connection;
+pub mod construction_queue;
 pub mod extractor;
+pub mod faction_changes;
+pub mod faction_standings;
 pub mod factions;
+pub mod heroes;
+pub mod intel_reports;
+pub mod items;
+pub mod leaderboards;
+pub mod limited_events;
+pub mod market_orders;
+pub mod market_trades;
+pub mod messages;
 pub mod migrations;
+pub mod modifier_history;
 pub mod modifiers;
+pub mod observers;
+pub mod planned_actions;
+pub mod player_activity;
 pub mod player_buildings;
+pub mod player_event_progress;
+pub mod player_identities;
+pub mod player_items;
+pub mod player_privacy;
+pub mod player_relationships;
 pub mod player_sessions;
 pub mod player_units;
 pub mod players;
+pub mod quests;
 pub mod resources;
+pub mod scout_missions;
+pub mod seasons;
 pub mod seeds;
+pub mod settlements;
+pub mod simulated_players;
+pub mod table_stats;
 pub mod training_queue;
 pub mod unit_costs;
+pub mod unit_upgrades;
 pub mod units;
+pub mod world_resets;
 
 pub use connection::{DbConn, DbPool};
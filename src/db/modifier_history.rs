@@ -0,0 +1,44 @@
+//! Database access layer for the modifier history.
+//!
+//! The history is an append-only log of modifiers being applied to, expiring for and being
+//! removed from players.
+
+use diesel::prelude::*;
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::modifier::modifier_history::{ModifierHistory, NewModifierHistory};
+use crate::domain::player::PlayerKey;
+use crate::schema::modifier_history::dsl::*;
+
+/// Records a batch of history entries.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `entries` - The [`NewModifierHistory`] entries to record
+///
+/// # Returns
+/// * `Result<usize>` - The number of recorded entries or an error
+pub fn create_batch(conn: &mut DbConn, entries: &[NewModifierHistory]) -> Result<usize> {
+	let rows = diesel::insert_into(modifier_history)
+		.values(entries)
+		.execute(conn)?;
+	Ok(rows)
+}
+
+/// Retrieves the history of a player, oldest first.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `player_key` - Reference to the [`PlayerKey`] to filter the history by
+///
+/// # Returns
+/// * `Result<Vec<ModifierHistory>>` - The player's history entries or an error
+pub fn get_by_player_id(conn: &mut DbConn, player_key: &PlayerKey) -> Result<Vec<ModifierHistory>> {
+	let entries = modifier_history
+		.filter(player_id.eq(player_key))
+		.order((occurred_at.asc(), id.asc()))
+		.select(ModifierHistory::as_select())
+		.load(conn)?;
+	Ok(entries)
+}
@@ -36,6 +36,22 @@ pub fn get_by_id(conn: &mut DbConn, modifier_id: &ModifierKey) -> Result<Modifie
 	Ok(modifier)
 }
 
+/// Retrieves the modifiers with the given names.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `names` - The names of the modifiers
+///
+/// # Returns
+/// A Result containing the found [`Modifier`] entities, names without a modifier are skipped
+pub fn get_by_names(conn: &mut DbConn, names: &[&str]) -> Result<Vec<Modifier>> {
+	let mod_list = modifiers
+		.filter(name.eq_any(names))
+		.select(Modifier::as_select())
+		.load(conn)?;
+	Ok(mod_list)
+}
+
 /// Creates a new modifier in the database.
 ///
 /// # Arguments
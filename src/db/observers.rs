@@ -0,0 +1,138 @@
+//! Database access layer for observers and the players they watch.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use tracing::{instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::observer::{NewObserver, NewObserverPlayer, Observer, ObserverKey};
+use crate::domain::player::{Player, PlayerKey};
+use crate::schema::{observer, observer_player as op, player, player_privacy};
+
+/// Creates an observer.
+#[instrument(skip(conn, entity))]
+pub fn create(conn: &mut DbConn, entity: NewObserver) -> Result<Observer> {
+	let created = diesel::insert_into(observer::table)
+		.values(entity)
+		.returning(Observer::as_returning())
+		.get_result(conn)?;
+	trace!(?created, "Created observer");
+	Ok(created)
+}
+
+/// Selects players for an observer to watch, skipping those already selected.
+#[instrument(skip(conn))]
+pub fn add_players(
+	conn: &mut DbConn,
+	observer_id: &ObserverKey,
+	player_ids: &[PlayerKey],
+) -> Result<usize> {
+	let rows: Vec<_> = player_ids
+		.iter()
+		.map(|player_id| NewObserverPlayer {
+			observer_id: *observer_id,
+			player_id: *player_id,
+		})
+		.collect();
+	let count = diesel::insert_into(op::table)
+		.values(&rows)
+		.on_conflict_do_nothing()
+		.execute(conn)?;
+	Ok(count)
+}
+
+/// Retrieves every observer, newest first.
+#[instrument(skip(conn))]
+pub fn get_all(conn: &mut DbConn) -> Result<Vec<Observer>> {
+	let observers = observer::table
+		.order(observer::created_at.desc())
+		.select(Observer::as_select())
+		.load(conn)?;
+	Ok(observers)
+}
+
+/// Retrieves an observer by its ID, `None` if it does not exist.
+#[instrument(skip(conn))]
+pub fn find_by_id(conn: &mut DbConn, observer_id: &ObserverKey) -> Result<Option<Observer>> {
+	let found = observer::table
+		.find(observer_id)
+		.select(Observer::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(found)
+}
+
+/// Retrieves the observer authenticating with the token of the given hash.
+#[instrument(skip_all)]
+pub fn find_by_token_hash(conn: &mut DbConn, hash: &str) -> Result<Option<Observer>> {
+	let found = observer::table
+		.filter(observer::token_hash.eq(hash))
+		.select(Observer::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(found)
+}
+
+/// Marks an observer as revoked.
+///
+/// Returns `None` if the observer does not exist or was revoked already.
+#[instrument(skip(conn))]
+pub fn revoke(
+	conn: &mut DbConn,
+	observer_id: &ObserverKey,
+	now: DateTime<Utc>,
+) -> Result<Option<Observer>> {
+	let revoked = diesel::update(
+		observer::table
+			.find(observer_id)
+			.filter(observer::revoked_at.is_null()),
+	)
+	.set(observer::revoked_at.eq(now))
+	.returning(Observer::as_returning())
+	.get_result(conn)
+	.optional()?;
+	Ok(revoked)
+}
+
+/// Retrieves the IDs of the players selected for an observer, whether they opted in or not.
+#[instrument(skip(conn))]
+pub fn get_player_ids(conn: &mut DbConn, observer_id: &ObserverKey) -> Result<Vec<PlayerKey>> {
+	let ids = op::table
+		.filter(op::observer_id.eq(observer_id))
+		.select(op::player_id)
+		.load(conn)?;
+	Ok(ids)
+}
+
+/// Retrieves the players an observer can watch: those selected for it that allow observers.
+#[instrument(skip(conn))]
+pub fn get_watched_players(conn: &mut DbConn, observer_id: &ObserverKey) -> Result<Vec<Player>> {
+	let players = op::table
+		.inner_join(player::table.inner_join(player_privacy::table))
+		.filter(op::observer_id.eq(observer_id))
+		.filter(player_privacy::allow_observers.eq(true))
+		.order(player::name)
+		.select(Player::as_select())
+		.load(conn)?;
+	Ok(players)
+}
+
+/// Retrieves a player the observer can watch, `None` if it was not selected for the observer
+/// or does not allow observers.
+#[instrument(skip(conn))]
+pub fn find_watched_player(
+	conn: &mut DbConn,
+	observer_id: &ObserverKey,
+	player_id: &PlayerKey,
+) -> Result<Option<Player>> {
+	let found = op::table
+		.inner_join(player::table.inner_join(player_privacy::table))
+		.filter(op::observer_id.eq(observer_id))
+		.filter(op::player_id.eq(player_id))
+		.filter(player_privacy::allow_observers.eq(true))
+		.select(Player::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(found)
+}
@@ -0,0 +1,139 @@
+//! Database access layer for planned action entities.
+//!
+//! This module provides operations for managing the per-player queue of planned
+//! construction and upgrade actions, including creation, listing, and status updates.
+
+use chrono::Utc;
+use diesel::prelude::*;
+use tracing::{debug, instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::building::BuildingKey;
+use crate::domain::player::PlayerKey;
+use crate::domain::player::planned_action::{
+	NewPlannedAction, PlannedAction, PlannedActionKey, PlannedActionStatus,
+};
+use crate::schema::planned_action as pa;
+
+/// Creates a new planned action.
+#[instrument(skip(conn, entity))]
+pub fn create(conn: &mut DbConn, entity: NewPlannedAction) -> Result<PlannedAction> {
+	debug!(
+		"Creating {:?} planned action for player {}",
+		entity.action, entity.player_id
+	);
+	let plan = diesel::insert_into(pa::table)
+		.values(entity)
+		.returning(PlannedAction::as_returning())
+		.get_result(conn)?;
+	trace!("Created planned action: {:?}", plan);
+	Ok(plan)
+}
+
+/// Retrieves a planned action by its ID.
+#[instrument(skip(conn))]
+pub fn get_by_id(conn: &mut DbConn, plan_id: &PlannedActionKey) -> Result<PlannedAction> {
+	let plan = pa::table.find(plan_id).first(conn)?;
+	Ok(plan)
+}
+
+/// Retrieves a planned action, if it belongs to the player.
+#[instrument(skip(conn))]
+pub fn find_owned(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	plan_id: &PlannedActionKey,
+) -> Result<Option<PlannedAction>> {
+	let plan = pa::table
+		.find(plan_id)
+		.filter(pa::player_id.eq(player_id))
+		.select(PlannedAction::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(plan)
+}
+
+/// Retrieves all pending planned actions for a player, oldest first.
+///
+/// The ordering is the execution order used by the plan evaluator.
+#[instrument(skip(conn))]
+pub fn get_pending_for_player(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+) -> Result<Vec<PlannedAction>> {
+	let plans = pa::table
+		.filter(pa::player_id.eq(player_key))
+		.filter(pa::status.eq(PlannedActionStatus::Pending))
+		.order(pa::created_at.asc())
+		.select(PlannedAction::as_select())
+		.load(conn)?;
+	Ok(plans)
+}
+
+/// Counts the pending planned actions for a player.
+///
+/// Used to enforce the per-player plan limit.
+#[instrument(skip(conn))]
+pub fn count_pending_for_player(conn: &mut DbConn, player_key: &PlayerKey) -> Result<i64> {
+	let count = pa::table
+		.filter(pa::player_id.eq(player_key))
+		.filter(pa::status.eq(PlannedActionStatus::Pending))
+		.count()
+		.get_result(conn)?;
+	Ok(count)
+}
+
+/// Marks a planned action as completed and records the execution timestamp.
+#[instrument(skip(conn))]
+pub fn complete(conn: &mut DbConn, plan_id: &PlannedActionKey) -> Result<PlannedAction> {
+	debug!("Completing planned action {}", plan_id);
+	let plan = diesel::update(pa::table.find(plan_id))
+		.set((
+			pa::status.eq(PlannedActionStatus::Completed),
+			pa::executed_at.eq(Some(Utc::now())),
+			pa::last_error.eq(None::<String>),
+		))
+		.returning(PlannedAction::as_returning())
+		.get_result(conn)?;
+	Ok(plan)
+}
+
+/// Cancels a planned action.
+#[instrument(skip(conn))]
+pub fn cancel(conn: &mut DbConn, plan_id: &PlannedActionKey) -> Result<PlannedAction> {
+	debug!("Cancelling planned action {}", plan_id);
+	let plan = diesel::update(pa::table.find(plan_id))
+		.set(pa::status.eq(PlannedActionStatus::Cancelled))
+		.returning(PlannedAction::as_returning())
+		.get_result(conn)?;
+	Ok(plan)
+}
+
+/// Replaces the building type a planned construction builds.
+#[instrument(skip(conn))]
+pub fn set_building(
+	conn: &mut DbConn,
+	plan_id: &PlannedActionKey,
+	building_id: BuildingKey,
+) -> Result<PlannedAction> {
+	let plan = diesel::update(pa::table.find(plan_id))
+		.set(pa::building_id.eq(Some(building_id)))
+		.returning(PlannedAction::as_returning())
+		.get_result(conn)?;
+	Ok(plan)
+}
+
+/// Records why the last execution attempt of a planned action did not go through.
+#[instrument(skip(conn))]
+pub fn set_last_error(
+	conn: &mut DbConn,
+	plan_id: &PlannedActionKey,
+	error: &str,
+) -> Result<PlannedAction> {
+	let plan = diesel::update(pa::table.find(plan_id))
+		.set(pa::last_error.eq(Some(error)))
+		.returning(PlannedAction::as_returning())
+		.get_result(conn)?;
+	Ok(plan)
+}
@@ -0,0 +1,84 @@
+//! Database access layer for player activity statistics.
+//!
+//! The activity of a player is aggregated per UTC day in a single query over the training
+//! queue, the building upgrade ledger and the battle reports.
+
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use diesel::sql_types::{Date, Uuid};
+use tracing::{instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::player::PlayerKey;
+use crate::domain::player::activity::DailyActivity;
+
+/// Aggregates a player's activity for every day from `first_day` to `last_day`, inclusive.
+///
+/// Days without any activity are included with zeroes. Cancelled trainings are not
+/// counted as spending, since most of their cost was refunded.
+#[instrument(skip(conn))]
+pub fn get_daily(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	first_day: NaiveDate,
+	last_day: NaiveDate,
+) -> Result<Vec<DailyActivity>> {
+	let days: Vec<DailyActivity> = diesel::sql_query(
+		"WITH bounds AS ( \
+		 SELECT ($2::date)::timestamp AT TIME ZONE 'UTC' AS since \
+		 ), days AS ( \
+		 SELECT generate_series($2::date, $3::date, interval '1 day')::date AS day \
+		 ), trained AS ( \
+		 SELECT (completed_at AT TIME ZONE 'UTC')::date AS day, sum(quantity)::int8 AS units \
+		 FROM training_queue, bounds \
+		 WHERE player_id = $1 AND status = 'completed' AND completed_at >= bounds.since \
+		 GROUP BY 1 \
+		 ), spent AS ( \
+		 SELECT (tq.started_at AT TIME ZONE 'UTC')::date AS day, \
+		 coalesce(sum(tq.quantity * uc.amount) FILTER (WHERE uc.resource = 'food'), 0) AS food, \
+		 coalesce(sum(tq.quantity * uc.amount) FILTER (WHERE uc.resource = 'wood'), 0) AS wood, \
+		 coalesce(sum(tq.quantity * uc.amount) FILTER (WHERE uc.resource = 'stone'), 0) AS stone, \
+		 coalesce(sum(tq.quantity * uc.amount) FILTER (WHERE uc.resource = 'gold'), 0) AS gold \
+		 FROM training_queue tq JOIN unit_cost uc ON uc.unit_id = tq.unit_id, bounds \
+		 WHERE tq.player_id = $1 AND tq.status <> 'cancelled' AND tq.started_at >= bounds.since \
+		 GROUP BY 1 \
+		 UNION ALL \
+		 SELECT (created_at AT TIME ZONE 'UTC')::date, sum(food), sum(wood), sum(stone), sum(gold) \
+		 FROM building_upgrade, bounds \
+		 WHERE player_id = $1 AND cancelled_at IS NULL AND created_at >= bounds.since \
+		 GROUP BY 1 \
+		 ), upgraded AS ( \
+		 SELECT (completed_at AT TIME ZONE 'UTC')::date AS day, count(*) AS upgrades \
+		 FROM building_upgrade, bounds \
+		 WHERE player_id = $1 AND completed_at >= bounds.since \
+		 GROUP BY 1 \
+		 ), battles AS ( \
+		 SELECT (fought_at AT TIME ZONE 'UTC')::date AS day, count(*) AS fought, \
+		 count(*) FILTER (WHERE winner_id = $1) AS won \
+		 FROM battle_report, bounds \
+		 WHERE (attacker_id = $1 OR defender_id = $1) AND fought_at >= bounds.since \
+		 GROUP BY 1 \
+		 ) \
+		 SELECT d.day, \
+		 coalesce(t.units, 0) AS units_trained, \
+		 coalesce(u.upgrades, 0) AS buildings_upgraded, \
+		 coalesce(b.fought, 0) AS battles_fought, \
+		 coalesce(b.won, 0) AS battles_won, \
+		 coalesce((SELECT sum(s.food) FROM spent s WHERE s.day = d.day), 0)::int8 AS food_spent, \
+		 coalesce((SELECT sum(s.wood) FROM spent s WHERE s.day = d.day), 0)::int8 AS wood_spent, \
+		 coalesce((SELECT sum(s.stone) FROM spent s WHERE s.day = d.day), 0)::int8 AS stone_spent, \
+		 coalesce((SELECT sum(s.gold) FROM spent s WHERE s.day = d.day), 0)::int8 AS gold_spent \
+		 FROM days d \
+		 LEFT JOIN trained t ON t.day = d.day \
+		 LEFT JOIN upgraded u ON u.day = d.day \
+		 LEFT JOIN battles b ON b.day = d.day \
+		 ORDER BY d.day",
+	)
+	.bind::<Uuid, _>(player_id)
+	.bind::<Date, _>(first_day)
+	.bind::<Date, _>(last_day)
+	.load(conn)?;
+	trace!("Aggregated {} days of activity", days.len());
+	Ok(days)
+}
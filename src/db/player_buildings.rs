@@ -8,6 +8,7 @@
 
 use std::collections::HashMap;
 
+use chrono::{DateTime, Utc};
 use diesel::dsl::{count, max};
 use diesel::prelude::*;
 use tracing::info;
@@ -16,12 +17,14 @@ use crate::db::DbConn;
 use crate::domain::building::level::BuildingLevel;
 use crate::domain::building::resources::BuildingResource;
 use crate::domain::building::{Building, BuildingKey};
-use crate::domain::error::Result;
+use crate::domain::error::{Error, ErrorKind, Result};
 use crate::domain::factions::FactionCode;
 use crate::domain::player::buildings::{
 	NewPlayerBuilding, PlayerBuilding, PlayerBuildingKey, UpdatePlayerBuilding,
 };
 use crate::domain::player::{Player, PlayerKey};
+use crate::domain::settlement::SettlementKey;
+use crate::domain::unit::training::TrainingMode;
 use crate::game::buildings::requirement_operations::AvailabilityData;
 use crate::schema::{building, player_building};
 
@@ -39,6 +42,96 @@ pub fn get_all(conn: &mut DbConn) -> Result<Vec<PlayerBuilding>> {
 	Ok(buildings)
 }
 
+/// Counts the upgrades across all players that finish by `until`.
+pub fn count_upgrades_finishing_before(conn: &mut DbConn, until: DateTime<Utc>) -> Result<i64> {
+	let count = player_building::table
+		.filter(player_building::upgrade_finishes_at.le(until))
+		.count()
+		.get_result(conn)?;
+	Ok(count)
+}
+
+/// Counts a player's buildings whose upgrade finished by `now` but was not confirmed yet.
+pub fn count_upgrades_ready(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	now: DateTime<Utc>,
+) -> Result<i64> {
+	let count = player_building::table
+		.filter(player_building::player_id.eq(player_id))
+		.filter(player_building::upgrade_finishes_at.le(now))
+		.count()
+		.get_result(conn)?;
+	Ok(count)
+}
+
+/// Counts the buildings a player constructed since `since`.
+///
+/// The starter buildings are inserted together when the player joins a faction, so rows
+/// sharing the player's earliest timestamp are not counted as constructions.
+pub fn count_constructed_since(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	since: DateTime<Utc>,
+) -> Result<i64> {
+	let starters_granted_at: Option<DateTime<Utc>> = player_building::table
+		.filter(player_building::player_id.eq(player_id))
+		.select(diesel::dsl::min(player_building::created_at))
+		.get_result(conn)?;
+	let Some(starters_granted_at) = starters_granted_at else {
+		return Ok(0);
+	};
+
+	let count = player_building::table
+		.filter(player_building::player_id.eq(player_id))
+		.filter(player_building::created_at.ge(since))
+		.filter(player_building::created_at.gt(starters_granted_at))
+		.count()
+		.get_result(conn)?;
+	Ok(count)
+}
+
+/// Sums the levels of all buildings a player owns.
+pub fn sum_levels(conn: &mut DbConn, player_id: &PlayerKey) -> Result<i64> {
+	let total: Option<i64> = player_building::table
+		.filter(player_building::player_id.eq(player_id))
+		.select(diesel::dsl::sum(player_building::level))
+		.get_result(conn)?;
+	Ok(total.unwrap_or(0))
+}
+
+/// Returns the level of a settlement's Keep, or 0 if it has none.
+///
+/// AIDEV-NOTE: every faction names its Keep differently (Stronghold, Tree of Life, ...), it
+/// is told apart as the only starter building limited to a single instance.
+pub fn get_keep_level(conn: &mut DbConn, settlement_id: &SettlementKey) -> Result<i32> {
+	let level: Option<i32> = player_building::table
+		.inner_join(building::table)
+		.filter(player_building::settlement_id.eq(settlement_id))
+		.filter(building::starter.eq(true))
+		.filter(building::max_count.eq(1))
+		.select(max(player_building::level))
+		.get_result(conn)?;
+	Ok(level.unwrap_or(0))
+}
+
+/// Returns the level of the settlement's building named `name`, or 0 if it has none.
+///
+/// Meant for buildings limited to a single instance, of others the highest level counts.
+pub fn get_level_by_name(
+	conn: &mut DbConn,
+	settlement_id: &SettlementKey,
+	name: &str,
+) -> Result<i32> {
+	let level: Option<i32> = player_building::table
+		.inner_join(building::table)
+		.filter(player_building::settlement_id.eq(settlement_id))
+		.filter(building::name.eq(name))
+		.select(max(player_building::level))
+		.get_result(conn)?;
+	Ok(level.unwrap_or(0))
+}
+
 /// Retrieves a single player building by its ID.
 ///
 /// # Arguments
@@ -52,6 +145,60 @@ pub fn get_by_id(conn: &mut DbConn, id: &PlayerBuildingKey) -> Result<PlayerBuil
 	Ok(building)
 }
 
+/// Retrieves a single player building by its ID, returning `None` if it does not exist.
+pub fn find_by_id(conn: &mut DbConn, id: &PlayerBuildingKey) -> Result<Option<PlayerBuilding>> {
+	let building = player_building::table
+		.find(id)
+		.select(PlayerBuilding::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(building)
+}
+
+/// Retrieves a single player building by its ID and locks it for the rest of the transaction.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `id` - The unique identifier of the player building to lock
+///
+/// # Returns
+/// A Result containing the locked PlayerBuilding entity
+pub fn lock_by_id(conn: &mut DbConn, id: &PlayerBuildingKey) -> Result<PlayerBuilding> {
+	let building = player_building::table
+		.find(id)
+		.select(PlayerBuilding::as_select())
+		.for_update()
+		.first(conn)?;
+	Ok(building)
+}
+
+/// Retrieves a single player building by its ID, as long as it belongs to the player.
+///
+/// Buildings owned by other players are indistinguishable from missing ones, so
+/// callers never leak the existence of foreign buildings.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `player_key` - The player who must own the building
+/// * `id` - The unique identifier of the player building
+///
+/// # Returns
+/// A Result containing the requested PlayerBuilding entity, or a
+/// `NotFoundError` if the player does not own such a building
+pub fn get_owned(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	id: &PlayerBuildingKey,
+) -> Result<PlayerBuilding> {
+	player_building::table
+		.filter(player_building::id.eq(id))
+		.filter(player_building::player_id.eq(player_key))
+		.select(PlayerBuilding::as_select())
+		.first(conn)
+		.optional()?
+		.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Building not found")))
+}
+
 /// Creates a new player building in the database.
 ///
 /// # Arguments
@@ -180,6 +327,35 @@ pub fn get_game_buildings(conn: &mut DbConn, player_key: &PlayerKey) -> Result<V
 	Ok(results)
 }
 
+/// Retrieves the full set of buildings in a settlement, like [`get_game_buildings`] does for
+/// all of a player's buildings.
+pub fn get_settlement_buildings(
+	conn: &mut DbConn,
+	settlement_key: &SettlementKey,
+) -> Result<Vec<FullBuilding>> {
+	use crate::schema::building::dsl as b;
+	use crate::schema::building_level::dsl as bl;
+	use crate::schema::building_resource::dsl as br;
+	use crate::schema::player_building::dsl as pb;
+
+	let results = pb::player_building
+		.filter(pb::settlement_id.eq(settlement_key))
+		.inner_join(b::building.on(pb::building_id.eq(b::id)))
+		.inner_join(
+			bl::building_level.on(pb::building_id
+				.eq(bl::building_id)
+				.and(bl::level.eq(pb::level + 1))),
+		)
+		.inner_join(
+			br::building_resource.on(pb::building_id
+				.eq(br::building_id)
+				.and(pb::level.eq(br::building_level))),
+		)
+		.get_results::<FullBuilding>(conn)?;
+
+	Ok(results)
+}
+
 /// Retrieves detailed information about a specific building for a player in the game.
 ///
 /// This function queries the database to fetch a specific building's information,
@@ -244,39 +420,39 @@ pub fn construct(conn: &mut DbConn, new_building: NewPlayerBuilding) -> Result<P
 	Ok(new_building)
 }
 
-/// Checks if a player can construct a specific building.
+/// Checks if a specific building can be constructed in a settlement.
 ///
-/// Validates if the player hasn't reached the maximum allowed number of buildings
+/// Validates if the settlement hasn't reached the maximum allowed number of buildings
 /// for the specified type.
 ///
 /// # Arguments
 /// * `conn` - Database connection
-/// * `player_id` - The unique identifier of the player
+/// * `settlement_id` - The unique identifier of the settlement
 /// * `bld_id` - The unique identifier of the building type
 ///
 /// # Returns
-/// `true` if the player can construct the building, `false` otherwise
+/// `true` if the building can be constructed, `false` otherwise
 pub fn can_construct(
 	conn: &mut DbConn,
-	player_id: &PlayerKey,
+	settlement_id: &SettlementKey,
 	bld_id: &BuildingKey,
 ) -> Result<bool> {
 	info!(
-		"Checking if player {} can construct building: {}",
-		player_id, bld_id
+		"Checking if settlement {} can construct building: {}",
+		settlement_id, bld_id
 	);
 	let bld = building::table
 		.find(bld_id)
 		.select(Building::as_select())
 		.first(conn)?;
 	let count = player_building::table
-		.filter(player_building::player_id.eq(player_id))
+		.filter(player_building::settlement_id.eq(settlement_id))
 		.filter(player_building::building_id.eq(bld_id))
 		.count()
 		.get_result::<i64>(conn)?;
 	info!(
-		"Player {} has {} buildings of type {}. Maximum is {}",
-		player_id, count, bld_id, bld.max_count
+		"Settlement {} has {} buildings of type {}. Maximum is {}",
+		settlement_id, count, bld_id, bld.max_count
 	);
 
 	Ok(count < bld.max_count as i64)
@@ -309,14 +485,14 @@ pub fn get_upgrade_tuple(
 /// # Arguments
 /// * `conn` - Database connection
 /// * `player_building_key` - The unique identifier of the player's building
-/// * `upgrade_eta` - The upgrade completion time as string, or None to clear it
+/// * `upgrade_eta` - The upgrade completion time, or None to clear it
 ///
 /// # Returns
 /// Updated PlayerBuilding instance
 pub fn set_upgrade_eta(
 	conn: &mut DbConn,
 	player_building_key: &PlayerBuildingKey,
-	upgrade_eta: Option<&str>,
+	upgrade_eta: Option<DateTime<Utc>>,
 ) -> Result<PlayerBuilding> {
 	let building = diesel::update(player_building::table.find(player_building_key))
 		.set(player_building::upgrade_finishes_at.eq(upgrade_eta))
@@ -325,6 +501,48 @@ pub fn set_upgrade_eta(
 	Ok(building)
 }
 
+/// Replaces the building type of a player's building, keeping its level and upgrades.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `player_building_key` - The unique identifier of the player's building
+/// * `building_id` - The new building type
+///
+/// # Returns
+/// Updated PlayerBuilding instance
+pub fn set_building(
+	conn: &mut DbConn,
+	player_building_key: &PlayerBuildingKey,
+	building_id: BuildingKey,
+) -> Result<PlayerBuilding> {
+	let building = diesel::update(player_building::table.find(player_building_key))
+		.set(player_building::building_id.eq(building_id))
+		.returning(PlayerBuilding::as_returning())
+		.get_result(conn)?;
+	Ok(building)
+}
+
+/// Sets how a player's building works through its training queue.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `player_building_key` - The unique identifier of the player's building
+/// * `mode` - The new training mode
+///
+/// # Returns
+/// Updated PlayerBuilding instance
+pub fn set_training_mode(
+	conn: &mut DbConn,
+	player_building_key: &PlayerBuildingKey,
+	mode: TrainingMode,
+) -> Result<PlayerBuilding> {
+	let building = diesel::update(player_building::table.find(player_building_key))
+		.set(player_building::training_mode.eq(mode))
+		.returning(PlayerBuilding::as_returning())
+		.get_result(conn)?;
+	Ok(building)
+}
+
 /// Increases the level of a building by one and resets the upgrade timer.
 ///
 /// # Arguments
@@ -337,16 +555,36 @@ pub fn inc_level(conn: &mut DbConn, id: &PlayerBuildingKey) -> Result<PlayerBuil
 	let building = diesel::update(player_building::table.find(id))
 		.set((
 			player_building::level.eq(player_building::level + 1),
-			player_building::upgrade_finishes_at.eq(None::<String>),
+			player_building::upgrade_finishes_at.eq(None::<DateTime<Utc>>),
 		))
 		.returning(PlayerBuilding::as_returning())
 		.get_result(conn)?;
 	Ok(building)
 }
 
-/// Retrieves building counts and maximum levels for all buildings available to a player.
+/// Decreases the level of a building by one and resets the upgrade timer.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `id` - The unique identifier of the player's building
 ///
-/// This function returns a map containing counts and maximum levels of buildings for a given player,
+/// # Returns
+/// Updated PlayerBuilding instance with decremented level
+pub fn dec_level(conn: &mut DbConn, id: &PlayerBuildingKey) -> Result<PlayerBuilding> {
+	let building = diesel::update(player_building::table.find(id))
+		.set((
+			player_building::level.eq(player_building::level - 1),
+			player_building::upgrade_finishes_at.eq(None::<DateTime<Utc>>),
+		))
+		.returning(PlayerBuilding::as_returning())
+		.get_result(conn)?;
+	Ok(building)
+}
+
+/// Retrieves building counts and maximum levels for all buildings available to a player in one
+/// of their settlements.
+///
+/// This function returns a map containing counts and maximum levels of buildings in a settlement,
 /// filtered by the player's faction. It includes both faction-specific buildings and neutral buildings
 /// that are available to all factions.
 ///
@@ -354,13 +592,14 @@ pub fn inc_level(conn: &mut DbConn, id: &PlayerBuildingKey) -> Result<PlayerBuil
 ///
 /// * `conn` - Database connection
 /// * `player` - Reference to the Player entity whose building information is being queried
+/// * `settlement_id` - The settlement whose buildings are counted
 ///
 /// # Returns
 ///
 /// Returns a Result containing a `HashMap` where:
 /// * Key is the BuildingKey (unique identifier for each building type)
 /// * Value is a tuple containing:
-///   * First element (`i64`): Count of how many instances of this building the player has
+///   * First element (`i64`): Count of how many instances of this building the settlement has
 ///   * Second element (`Option<i32>`): Maximum level achieved across all instances of this building,
 ///     or None if the player has no instances of this building
 ///
@@ -370,6 +609,7 @@ pub fn inc_level(conn: &mut DbConn, id: &PlayerBuildingKey) -> Result<PlayerBuil
 pub fn get_player_bld_counts_levels(
 	conn: &mut DbConn,
 	player: &Player,
+	settlement_id: &SettlementKey,
 ) -> Result<(
 	HashMap<BuildingKey, Building>,
 	HashMap<BuildingKey, AvailabilityData>,
@@ -383,7 +623,7 @@ pub fn get_player_bld_counts_levels(
 		.left_join(
 			player_building::table.on(building::id
 				.eq(player_building::building_id)
-				.and(player_building::player_id.eq(&player.id))),
+				.and(player_building::settlement_id.eq(settlement_id))),
 		)
 		.group_by(building::id)
 		.select((
@@ -405,27 +645,27 @@ pub fn get_player_bld_counts_levels(
 	Ok((buildings, player_building_levels))
 }
 
-/// Retrieves building count and maximum level for a specific building type owned by a player.
+/// Retrieves building count and maximum level for a specific building type in a settlement.
 ///
 /// This is a single-building variant of [`get_player_bld_counts_levels`] that queries
 /// availability data for one specific building type rather than all buildings.
 ///
 /// # Arguments
 /// * `conn` - Database connection
-/// * `player` - Reference to the Player entity whose building information is being queried
+/// * `settlement_key` - The settlement whose buildings are counted
 /// * `bld_key` - The unique identifier of the building type to query
 ///
 /// # Returns
 /// A [`Result`] containing a tuple `(Building, AvailabilityData)` where:
 /// * First element (`Building`): The building definition associated with `bld_key`
 /// * Second element ([`AvailabilityData`]): A tuple with:
-///   * First element (`i64`): Count of how many instances of this building the player has
+///   * First element (`i64`): Count of how many instances of this building the settlement has
 ///   * Second element (`i32`): Maximum count allowed for this building type
 ///   * Third element (`Option<i32>`): Maximum level achieved across all instances,
 ///     or `None` if the player has no instances of this building
 pub fn get_player_bld_count_level(
 	conn: &mut DbConn,
-	player_key: &PlayerKey,
+	settlement_key: &SettlementKey,
 	bld_key: &BuildingKey,
 ) -> Result<(Building, AvailabilityData)> {
 	let (bld, bld_count, max_count, max_lvl): (Building, i64, i32, Option<i32>) = building::table
@@ -433,7 +673,7 @@ pub fn get_player_bld_count_level(
 		.left_join(
 			player_building::table.on(building::id
 				.eq(player_building::building_id)
-				.and(player_building::player_id.eq(&player_key))),
+				.and(player_building::settlement_id.eq(settlement_key))),
 		)
 		.group_by(building::id)
 		.select((
@@ -0,0 +1,165 @@
+//! Database access layer for the progress of players in time-limited events: their event
+//! currency and their progress towards the objectives.
+
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+use tracing::{debug, instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::limited_event::{
+	EventObjectiveKey, LimitedEventKey, PlayerEventCurrency, PlayerEventObjective,
+};
+use crate::domain::player::PlayerKey;
+use crate::schema::{player_event_currency as pec, player_event_objective as peo};
+
+/// Adds `amount` to a player's progress towards an objective.
+///
+/// Creates the progress if the player never progressed the objective. The returned progress
+/// still holds the completions rewarded before, see [`set_completions`].
+#[instrument(skip(conn))]
+pub fn add_progress(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	objective_key: &EventObjectiveKey,
+	amount: i64,
+) -> Result<PlayerEventObjective> {
+	let progress = diesel::insert_into(peo::table)
+		.values((
+			peo::player_id.eq(player_key),
+			peo::objective_id.eq(objective_key),
+			peo::progress.eq(amount),
+		))
+		.on_conflict((peo::player_id, peo::objective_id))
+		.do_update()
+		.set(peo::progress.eq(peo::progress + excluded(peo::progress)))
+		.returning(PlayerEventObjective::as_returning())
+		.get_result(conn)?;
+	trace!("Upserted objective progress: {:?}", progress);
+	Ok(progress)
+}
+
+/// Records the completions of an objective a player was rewarded for.
+#[instrument(skip(conn))]
+pub fn set_completions(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	objective_key: &EventObjectiveKey,
+	completions: i32,
+) -> Result<PlayerEventObjective> {
+	let progress = diesel::update(peo::table.find((player_key, objective_key)))
+		.set(peo::completions.eq(completions))
+		.returning(PlayerEventObjective::as_returning())
+		.get_result(conn)?;
+	Ok(progress)
+}
+
+/// Retrieves a player's progress towards the given objectives.
+#[instrument(skip(conn))]
+pub fn get_progress(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	objective_keys: &[EventObjectiveKey],
+) -> Result<Vec<PlayerEventObjective>> {
+	let progress = peo::table
+		.filter(peo::player_id.eq(player_key))
+		.filter(peo::objective_id.eq_any(objective_keys))
+		.select(PlayerEventObjective::as_select())
+		.load(conn)?;
+	Ok(progress)
+}
+
+/// Credits event currency to a player.
+#[instrument(skip(conn))]
+pub fn add_currency(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	event_key: &LimitedEventKey,
+	amount: i64,
+) -> Result<PlayerEventCurrency> {
+	debug!(
+		"Adding {} of the currency of event {} to player {}",
+		amount, event_key, player_key
+	);
+	let currency = diesel::insert_into(pec::table)
+		.values((
+			pec::player_id.eq(player_key),
+			pec::event_id.eq(event_key),
+			pec::balance.eq(amount),
+		))
+		.on_conflict((pec::player_id, pec::event_id))
+		.do_update()
+		.set(pec::balance.eq(pec::balance + excluded(pec::balance)))
+		.returning(PlayerEventCurrency::as_returning())
+		.get_result(conn)?;
+	trace!("Upserted event currency: {:?}", currency);
+	Ok(currency)
+}
+
+/// Takes `amount` of event currency from a player.
+///
+/// Returns `None` without changing anything if the player holds less, so concurrent
+/// purchases never spend more than the player holds.
+#[instrument(skip(conn))]
+pub fn spend_currency(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	event_key: &LimitedEventKey,
+	amount: i64,
+) -> Result<Option<PlayerEventCurrency>> {
+	let currency = diesel::update(
+		pec::table
+			.find((player_key, event_key))
+			.filter(pec::balance.ge(amount)),
+	)
+	.set(pec::balance.eq(pec::balance - amount))
+	.returning(PlayerEventCurrency::as_returning())
+	.get_result(conn)
+	.optional()?;
+	trace!("Spent event currency: {:?}", currency);
+	Ok(currency)
+}
+
+/// Retrieves a player's balance of an event's currency, 0 if they never earned any.
+#[instrument(skip(conn))]
+pub fn get_balance(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	event_key: &LimitedEventKey,
+) -> Result<i64> {
+	let balance = pec::table
+		.find((player_key, event_key))
+		.select(pec::balance)
+		.first(conn)
+		.optional()?;
+	Ok(balance.unwrap_or_default())
+}
+
+/// Locks the balances of an event's currency that are left over, by player.
+#[instrument(skip(conn))]
+pub fn lock_leftovers(
+	conn: &mut DbConn,
+	event_key: &LimitedEventKey,
+) -> Result<Vec<PlayerEventCurrency>> {
+	let leftovers = pec::table
+		.filter(pec::event_id.eq(event_key))
+		.filter(pec::balance.gt(0))
+		.order(pec::player_id)
+		.select(PlayerEventCurrency::as_select())
+		.for_update()
+		.load(conn)?;
+	Ok(leftovers)
+}
+
+/// Empties a player's balance of an event's currency.
+#[instrument(skip(conn))]
+pub fn clear_balance(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	event_key: &LimitedEventKey,
+) -> Result<usize> {
+	let cleared = diesel::update(pec::table.find((player_key, event_key)))
+		.set(pec::balance.eq(0))
+		.execute(conn)?;
+	Ok(cleared)
+}
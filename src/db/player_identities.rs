@@ -0,0 +1,72 @@
+//! Database access layer for the identities of players at external providers.
+
+use diesel::prelude::*;
+use tracing::{instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::player::identity::{NewPlayerIdentity, OAuthProvider, PlayerIdentity};
+use crate::domain::player::{Player, PlayerKey};
+use crate::schema::{player, player_identity};
+
+/// Links an identity to a player.
+#[instrument(skip(conn, entity))]
+pub fn create(conn: &mut DbConn, entity: NewPlayerIdentity) -> Result<PlayerIdentity> {
+	let created = diesel::insert_into(player_identity::table)
+		.values(entity)
+		.returning(PlayerIdentity::as_returning())
+		.get_result(conn)?;
+	trace!(?created, "Created player identity");
+	Ok(created)
+}
+
+/// Retrieves the identity of an account at a provider with the player it belongs to.
+#[instrument(skip(conn))]
+pub fn find_by_subject(
+	conn: &mut DbConn,
+	provider: OAuthProvider,
+	subject: &str,
+) -> Result<Option<(PlayerIdentity, Player)>> {
+	let found = player_identity::table
+		.inner_join(player::table)
+		.filter(player_identity::provider.eq(provider.as_str()))
+		.filter(player_identity::subject.eq(subject))
+		.select((PlayerIdentity::as_select(), Player::as_select()))
+		.first(conn)
+		.optional()?;
+	Ok(found)
+}
+
+/// Retrieves every identity of a player.
+#[instrument(skip(conn))]
+pub fn get_by_player(conn: &mut DbConn, player_id: &PlayerKey) -> Result<Vec<PlayerIdentity>> {
+	let identities = player_identity::table
+		.filter(player_identity::player_id.eq(player_id))
+		.order(player_identity::created_at.asc())
+		.select(PlayerIdentity::as_select())
+		.load(conn)?;
+	Ok(identities)
+}
+
+/// Records the email address the provider reported at a login.
+#[instrument(skip(conn))]
+pub fn set_email(
+	conn: &mut DbConn,
+	identity: &PlayerIdentity,
+	new_email: Option<&str>,
+) -> Result<PlayerIdentity> {
+	let updated = diesel::update(player_identity::table.find(identity.id))
+		.set(player_identity::email.eq(new_email))
+		.returning(PlayerIdentity::as_returning())
+		.get_result(conn)?;
+	Ok(updated)
+}
+
+/// Deletes every identity of a player.
+#[instrument(skip(conn))]
+pub fn delete_by_player(conn: &mut DbConn, player_id: &PlayerKey) -> Result<usize> {
+	let deleted =
+		diesel::delete(player_identity::table.filter(player_identity::player_id.eq(player_id)))
+			.execute(conn)?;
+	Ok(deleted)
+}
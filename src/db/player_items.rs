@@ -0,0 +1,87 @@
+//! Database access layer for the items held by players.
+//!
+//! This module provides operations for reading a player's inventory and for adding and
+//! consuming items.
+
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+use tracing::{debug, instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::item::{Item, ItemKey, NewPlayerItem, PlayerItem};
+use crate::domain::player::PlayerKey;
+use crate::schema::{item, player_item as pi};
+
+/// Retrieves the items a player holds together with their definitions, sorted by name.
+///
+/// Items that were used up are left out.
+#[instrument(skip(conn))]
+pub fn get_for_player(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+) -> Result<Vec<(PlayerItem, Item)>> {
+	let items = pi::table
+		.inner_join(item::table)
+		.filter(pi::player_id.eq(player_key))
+		.filter(pi::quantity.gt(0))
+		.order(item::name)
+		.select((PlayerItem::as_select(), Item::as_select()))
+		.load(conn)?;
+	Ok(items)
+}
+
+/// Adds items to a player's inventory.
+///
+/// Creates a new entry if the player never held this item, or adds to the existing
+/// quantity if they did.
+#[instrument(skip(conn))]
+pub fn add_items(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	item_key: &ItemKey,
+	quantity: i64,
+) -> Result<PlayerItem> {
+	debug!(
+		"Adding {} items of {} to player {}",
+		quantity, item_key, player_key
+	);
+	let entity = NewPlayerItem {
+		player_id: *player_key,
+		item_id: *item_key,
+		quantity,
+	};
+	let result = diesel::insert_into(pi::table)
+		.values(&entity)
+		.on_conflict((pi::player_id, pi::item_id))
+		.do_update()
+		.set(pi::quantity.eq(pi::quantity + excluded(pi::quantity)))
+		.returning(PlayerItem::as_returning())
+		.get_result(conn)?;
+	trace!("Upserted player item: {:?}", result);
+	Ok(result)
+}
+
+/// Takes one of an item from a player's inventory.
+///
+/// Returns `None` without changing anything if the player holds none of the item, so
+/// concurrent uses never take more items than the player holds.
+#[instrument(skip(conn))]
+pub fn consume_one(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	item_key: &ItemKey,
+) -> Result<Option<PlayerItem>> {
+	let updated = diesel::update(
+		pi::table
+			.filter(pi::player_id.eq(player_key))
+			.filter(pi::item_id.eq(item_key))
+			.filter(pi::quantity.gt(0)),
+	)
+	.set(pi::quantity.eq(pi::quantity - 1))
+	.returning(PlayerItem::as_returning())
+	.get_result(conn)
+	.optional()?;
+	trace!("Consumed player item: {:?}", updated);
+	Ok(updated)
+}
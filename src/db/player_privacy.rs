@@ -0,0 +1,43 @@
+//! Database access layer for player privacy settings.
+//!
+//! Privacy rows are created by a trigger along with their player, so every player has one.
+
+use diesel::prelude::*;
+use tracing::{instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::player::PlayerKey;
+use crate::domain::player::privacy::{PlayerPrivacy, UpdatePlayerPrivacy};
+use crate::schema::player_privacy as pp;
+
+/// Retrieves the privacy settings of a player.
+#[instrument(skip(conn))]
+pub fn get_by_player_id(conn: &mut DbConn, player_id: &PlayerKey) -> Result<PlayerPrivacy> {
+	let privacy = pp::table
+		.find(player_id)
+		.select(PlayerPrivacy::as_select())
+		.first(conn)?;
+	trace!(?privacy, "Got privacy settings");
+	Ok(privacy)
+}
+
+/// Applies changes to the privacy settings of a player.
+///
+/// An empty changeset leaves the settings untouched.
+#[instrument(skip(conn))]
+pub fn update(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	changeset: &UpdatePlayerPrivacy,
+) -> Result<PlayerPrivacy> {
+	if changeset.is_empty() {
+		return get_by_player_id(conn, player_id);
+	}
+	let privacy = diesel::update(pp::table.find(player_id))
+		.set(changeset)
+		.returning(PlayerPrivacy::as_returning())
+		.get_result(conn)?;
+	trace!(?privacy, "Updated privacy settings");
+	Ok(privacy)
+}
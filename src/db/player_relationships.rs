@@ -0,0 +1,167 @@
+//! Database access layer for friend requests, friendships and blocks between players.
+
+use diesel::prelude::*;
+use tracing::{debug, instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::player::PlayerKey;
+use crate::domain::player::relationship::{
+	NewPlayerRelationship, PlayerRelationship, PlayerRelationshipKey, RelationshipStatus,
+};
+use crate::schema::{player, player_privacy, player_relationship as pr};
+
+/// A relationship with the name of the player on the other end, and whether they hide
+/// their online status.
+pub type RelationshipWithCounterpart = (PlayerRelationship, String, bool);
+
+/// Starts a relationship from one player to another.
+#[instrument(skip(conn, entity))]
+pub fn create(conn: &mut DbConn, entity: NewPlayerRelationship) -> Result<PlayerRelationship> {
+	debug!(
+		"Creating {} relationship from player {} to player {}",
+		entity.status, entity.player_id, entity.other_id
+	);
+	let relationship = diesel::insert_into(pr::table)
+		.values(entity)
+		.returning(PlayerRelationship::as_returning())
+		.get_result(conn)?;
+	trace!("Created player relationship: {:?}", relationship);
+	Ok(relationship)
+}
+
+/// Blocks a player, replacing the friend request or friendship the player started.
+#[instrument(skip(conn))]
+pub fn upsert_block(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	other_id: &PlayerKey,
+) -> Result<PlayerRelationship> {
+	let relationship = diesel::insert_into(pr::table)
+		.values(NewPlayerRelationship {
+			player_id: *player_id,
+			other_id: *other_id,
+			status: RelationshipStatus::Blocked,
+		})
+		.on_conflict((pr::player_id, pr::other_id))
+		.do_update()
+		.set(pr::status.eq(RelationshipStatus::Blocked))
+		.returning(PlayerRelationship::as_returning())
+		.get_result(conn)?;
+	Ok(relationship)
+}
+
+/// Retrieves the relationship a player started with another player, if any.
+#[instrument(skip(conn))]
+pub fn find(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	other_id: &PlayerKey,
+) -> Result<Option<PlayerRelationship>> {
+	let relationship = pr::table
+		.filter(pr::player_id.eq(player_id))
+		.filter(pr::other_id.eq(other_id))
+		.select(PlayerRelationship::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(relationship)
+}
+
+/// Retrieves the relationships between two players, started by either of them.
+#[instrument(skip(conn))]
+pub fn get_between(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	other_id: &PlayerKey,
+) -> Result<Vec<PlayerRelationship>> {
+	let relationships = pr::table
+		.filter(
+			pr::player_id
+				.eq(player_id)
+				.and(pr::other_id.eq(other_id))
+				.or(pr::player_id.eq(other_id).and(pr::other_id.eq(player_id))),
+		)
+		.select(PlayerRelationship::as_select())
+		.load(conn)?;
+	Ok(relationships)
+}
+
+/// Retrieves the relationships of a player with the players on the other end.
+///
+/// Blocks put on the player by others are left out, players do not learn who blocked them.
+#[instrument(skip(conn))]
+pub fn get_for_player(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+) -> Result<Vec<RelationshipWithCounterpart>> {
+	let mut started = pr::table
+		.inner_join(player::table.on(player::id.eq(pr::other_id)))
+		.inner_join(player_privacy::table.on(player_privacy::player_id.eq(pr::other_id)))
+		.filter(pr::player_id.eq(player_id))
+		.order(player::name.asc())
+		.select((
+			PlayerRelationship::as_select(),
+			player::name,
+			player_privacy::hide_online_status,
+		))
+		.load::<RelationshipWithCounterpart>(conn)?;
+	let received = pr::table
+		.inner_join(player::table.on(player::id.eq(pr::player_id)))
+		.inner_join(player_privacy::table.on(player_privacy::player_id.eq(pr::player_id)))
+		.filter(pr::other_id.eq(player_id))
+		.filter(pr::status.ne(RelationshipStatus::Blocked))
+		.order(player::name.asc())
+		.select((
+			PlayerRelationship::as_select(),
+			player::name,
+			player_privacy::hide_online_status,
+		))
+		.load::<RelationshipWithCounterpart>(conn)?;
+	started.extend(received);
+	Ok(started)
+}
+
+/// Whether either player blocked the other.
+#[instrument(skip(conn))]
+pub fn is_blocked_between(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	other_id: &PlayerKey,
+) -> Result<bool> {
+	let blocked = diesel::select(diesel::dsl::exists(
+		pr::table
+			.filter(pr::status.eq(RelationshipStatus::Blocked))
+			.filter(
+				pr::player_id
+					.eq(player_id)
+					.and(pr::other_id.eq(other_id))
+					.or(pr::player_id.eq(other_id).and(pr::other_id.eq(player_id))),
+			),
+	))
+	.get_result(conn)?;
+	Ok(blocked)
+}
+
+/// Sets the status of a relationship.
+#[instrument(skip(conn))]
+pub fn set_status(
+	conn: &mut DbConn,
+	relationship_id: &PlayerRelationshipKey,
+	status: RelationshipStatus,
+) -> Result<PlayerRelationship> {
+	let relationship = diesel::update(pr::table.find(relationship_id))
+		.set(pr::status.eq(status))
+		.returning(PlayerRelationship::as_returning())
+		.get_result(conn)?;
+	Ok(relationship)
+}
+
+/// Deletes a relationship.
+///
+/// # Returns
+/// The number of deleted relationships
+#[instrument(skip(conn))]
+pub fn delete(conn: &mut DbConn, relationship_id: &PlayerRelationshipKey) -> Result<usize> {
+	let count = diesel::delete(pr::table.find(relationship_id)).execute(conn)?;
+	Ok(count)
+}
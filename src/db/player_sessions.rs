@@ -85,6 +85,29 @@ pub fn delete_by_player(conn: &mut DbConn, player_key: &PlayerKey) -> Result<usi
 	Ok(deleted_count)
 }
 
+/// Deletes all sessions of a player except the one given.
+///
+/// # Parameters
+/// * `conn` - Database connection
+/// * `player_key` - The unique identifier of the player whose sessions should be deleted
+/// * `keep` - The session to keep, `None` to delete every session
+///
+/// # Returns
+/// * `Ok(usize)` - The number of sessions deleted
+/// * `Err` - If there was an error executing the database deletion
+pub fn delete_others_by_player(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	keep: Option<&SessionKey>,
+) -> Result<usize> {
+	let sessions = player_session.filter(player_id.eq(player_key));
+	let deleted_count = match keep {
+		Some(key) => diesel::delete(sessions.filter(id.ne(key))).execute(conn)?,
+		None => diesel::delete(sessions).execute(conn)?,
+	};
+	Ok(deleted_count)
+}
+
 /// Refreshes a session's expiration date by extending it by 30 days from the current time.
 ///
 /// # Parameters
@@ -3,6 +3,8 @@
 //! This module provides operations for managing player unit quantities,
 //! including retrieving owned units and updating quantities.
 
+use bigdecimal::{BigDecimal, ToPrimitive};
+use diesel::dsl::sum;
 use diesel::prelude::*;
 use diesel::upsert::excluded;
 use tracing::{debug, instrument, trace};
@@ -10,9 +12,9 @@ use tracing::{debug, instrument, trace};
 use crate::Result;
 use crate::db::DbConn;
 use crate::domain::player::PlayerKey;
-use crate::domain::unit::UnitKey;
 use crate::domain::unit::player_unit::{NewPlayerUnit, PlayerUnit};
-use crate::schema::player_unit as pu;
+use crate::domain::unit::{Unit, UnitKey};
+use crate::schema::{player_unit as pu, unit};
 
 /// Retrieves all units owned by a player.
 #[instrument(skip(conn))]
@@ -42,6 +44,49 @@ pub fn get_player_unit_count(
 	Ok(count.unwrap_or(0))
 }
 
+/// Upkeep of a player's army, summed over all the units they own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArmyUpkeep {
+	/// Food eaten per hour
+	pub food_per_hour: i64,
+	/// Population taken up
+	pub population: i64,
+}
+
+/// Sums the upkeep of all units owned by a player.
+#[instrument(skip(conn))]
+pub fn get_upkeep(conn: &mut DbConn, player_key: &PlayerKey) -> Result<ArmyUpkeep> {
+	let (food, population): (Option<BigDecimal>, Option<BigDecimal>) = pu::table
+		.inner_join(unit::table)
+		.filter(pu::player_id.eq(player_key))
+		.select((
+			sum(pu::quantity * unit::food_upkeep),
+			sum(pu::quantity * unit::population),
+		))
+		.first(conn)?;
+	Ok(ArmyUpkeep {
+		food_per_hour: food.and_then(|f| f.to_i64()).unwrap_or_default(),
+		population: population.and_then(|p| p.to_i64()).unwrap_or_default(),
+	})
+}
+
+/// Retrieves the units owned by a player together with their definitions, those eating the
+/// most food first.
+#[instrument(skip(conn))]
+pub fn get_by_food_upkeep(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+) -> Result<Vec<(PlayerUnit, Unit)>> {
+	let units = pu::table
+		.inner_join(unit::table)
+		.filter(pu::player_id.eq(player_key))
+		.filter(pu::quantity.gt(0))
+		.order((unit::food_upkeep.desc(), unit::id))
+		.select((PlayerUnit::as_select(), Unit::as_select()))
+		.load(conn)?;
+	Ok(units)
+}
+
 /// Updates the quantity of a specific unit for a player by a delta amount.
 ///
 /// The delta can be positive (adding units) or negative (removing units).
@@ -68,6 +113,35 @@ pub fn update_quantity(
 	Ok(updated)
 }
 
+/// Takes `quantity` units of a type out of a player's army, if they have that many.
+///
+/// # Returns
+/// The updated player unit, or `None` if the player owns fewer units, in which case
+/// nothing changes
+#[instrument(skip(conn))]
+pub fn remove_units(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	unit_key: &UnitKey,
+	quantity: i64,
+) -> Result<Option<PlayerUnit>> {
+	debug!(
+		"Removing {} units of {} from player {}",
+		quantity, unit_key, player_key
+	);
+	let updated = diesel::update(
+		pu::table
+			.filter(pu::player_id.eq(player_key))
+			.filter(pu::unit_id.eq(unit_key))
+			.filter(pu::quantity.ge(quantity)),
+	)
+	.set(pu::quantity.eq(pu::quantity - quantity))
+	.returning(PlayerUnit::as_returning())
+	.get_result(conn)
+	.optional()?;
+	Ok(updated)
+}
+
 /// Creates a new player unit entry or updates the quantity if it already exists.
 ///
 /// Uses PostgreSQL's ON CONFLICT to upsert the record.
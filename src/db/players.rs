@@ -4,11 +4,14 @@
 //! including standard database operations and specialized functionality for
 //! finding players by name and checking existence.
 
+use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 
 use crate::db::DbConn;
 use crate::domain::error::Result;
-use crate::domain::player::{NewPlayer, Player, PlayerKey, UpdatePlayer};
+use crate::domain::factions::FactionCode;
+use crate::domain::player::role::PlayerRole;
+use crate::domain::player::{NewPlayer, Player, PlayerKey, UpdatePlayer, UserEmail};
 use crate::schema::player::dsl::*;
 
 /// Retrieves all players from the database.
@@ -23,6 +26,40 @@ pub fn get_all(conn: &mut DbConn) -> Result<Vec<Player>> {
 	Ok(player_list)
 }
 
+/// Retrieves the IDs of all players that produce resources.
+///
+/// Neutral players have not joined a faction yet and produce nothing.
+///
+/// # Arguments
+/// * `conn` - Database connection
+///
+/// # Returns
+/// A Result containing the IDs of all players outside the neutral faction
+pub fn get_producing_ids(conn: &mut DbConn) -> Result<Vec<PlayerKey>> {
+	let ids = player
+		.filter(faction.ne(FactionCode::Neutral))
+		.select(id)
+		.load(conn)?;
+	Ok(ids)
+}
+
+/// Retrieves the IDs of the members of some factions that did not leave the game.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `factions` - The factions to retrieve the members of
+///
+/// # Returns
+/// A Result containing the IDs of the members
+pub fn get_member_ids(conn: &mut DbConn, factions: &[FactionCode]) -> Result<Vec<PlayerKey>> {
+	let ids = player
+		.filter(faction.eq_any(factions))
+		.filter(anonymized_at.is_null())
+		.select(id)
+		.load(conn)?;
+	Ok(ids)
+}
+
 /// Retrieves a single player by their ID.
 ///
 /// # Arguments
@@ -61,7 +98,175 @@ pub fn create(conn: &mut DbConn, entity: NewPlayer) -> Result<Player> {
 /// # Returns
 /// A Result containing the updated [`Player`] entity
 pub fn update(conn: &mut DbConn, changeset: &UpdatePlayer) -> Result<Player> {
-	let player_ = diesel::update(player).set(changeset).get_result(conn)?;
+	let player_ = diesel::update(changeset).set(changeset).get_result(conn)?;
+	Ok(player_)
+}
+
+/// Moves a neutral player into their first faction.
+///
+/// The update only applies while the player is neutral, so concurrent selections cannot both
+/// succeed. The faction triggers swap in the faction modifiers of the new faction.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `player_id` - The unique identifier of the player
+/// * `new_faction` - The faction to join
+///
+/// # Returns
+/// A Result containing the updated [`Player`], or `None` if the player is not neutral
+pub fn select_faction(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	new_faction: FactionCode,
+) -> Result<Option<Player>> {
+	let player_ = diesel::update(
+		player
+			.find(player_id)
+			.filter(faction.eq(FactionCode::Neutral)),
+	)
+	.set(faction.eq(new_faction))
+	.returning(Player::as_returning())
+	.get_result(conn)
+	.optional()?;
+	Ok(player_)
+}
+
+/// Sets or clears the end of a player's beginner shield.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `player_id` - The unique identifier of the player
+/// * `until` - End of the shield, or `None` to drop it
+///
+/// # Returns
+/// A Result containing the updated [`Player`] entity
+pub fn set_protected_until(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	until: Option<DateTime<Utc>>,
+) -> Result<Player> {
+	let player_ = diesel::update(player.find(player_id))
+		.set(protected_until.eq(until))
+		.returning(Player::as_returning())
+		.get_result(conn)?;
+	Ok(player_)
+}
+
+/// Changes the role of a player.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `player_id` - The unique identifier of the player
+/// * `new_role` - The role to give the player
+///
+/// # Returns
+/// A Result containing the updated [`Player`] entity
+pub fn set_role(conn: &mut DbConn, player_id: &PlayerKey, new_role: PlayerRole) -> Result<Player> {
+	let player_ = diesel::update(player.find(player_id))
+		.set(role.eq(new_role))
+		.returning(Player::as_returning())
+		.get_result(conn)?;
+	Ok(player_)
+}
+
+/// Replaces the password of a player, which revokes the tokens issued before `at`.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `player_id` - The unique identifier of the player
+/// * `new_pwd_hash` - Hash of the new password
+/// * `at` - When the password was changed
+///
+/// # Returns
+/// A Result containing the updated [`Player`] entity
+pub fn set_password(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	new_pwd_hash: &str,
+	at: DateTime<Utc>,
+) -> Result<Player> {
+	let player_ = diesel::update(player.find(player_id))
+		.set((pwd_hash.eq(new_pwd_hash), password_changed_at.eq(at)))
+		.returning(Player::as_returning())
+		.get_result(conn)?;
+	Ok(player_)
+}
+
+/// Replaces the email address of a player, which has to be verified again.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `player_id` - The unique identifier of the player
+/// * `new_email` - The new email address
+///
+/// # Returns
+/// A Result containing the updated [`Player`] entity
+pub fn set_email(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	new_email: &UserEmail,
+) -> Result<Player> {
+	let player_ = diesel::update(player.find(player_id))
+		.set((
+			email.eq(new_email),
+			email_verified_at.eq(None::<DateTime<Utc>>),
+		))
+		.returning(Player::as_returning())
+		.get_result(conn)?;
+	Ok(player_)
+}
+
+/// Sets or clears the time the account of a player is anonymized at.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `player_id` - The unique identifier of the player
+/// * `at` - When to anonymize the account, or `None` to cancel its deletion
+///
+/// # Returns
+/// A Result containing the updated [`Player`] entity
+pub fn set_deletion_scheduled_for(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	at: Option<DateTime<Utc>>,
+) -> Result<Player> {
+	let player_ = diesel::update(player.find(player_id))
+		.set(deletion_scheduled_for.eq(at))
+		.returning(Player::as_returning())
+		.get_result(conn)?;
+	Ok(player_)
+}
+
+/// Replaces the personal data of a player, keeping the row for the history referencing it.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `player_id` - The unique identifier of the player
+/// * `new_name` - Placeholder name replacing the player's
+/// * `new_pwd_hash` - Hash no password is known for
+/// * `at` - When the account was anonymized
+///
+/// # Returns
+/// A Result containing the updated [`Player`] entity
+pub fn anonymize(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	new_name: &str,
+	new_pwd_hash: &str,
+	at: DateTime<Utc>,
+) -> Result<Player> {
+	let player_ = diesel::update(player.find(player_id))
+		.set((
+			name.eq(new_name),
+			pwd_hash.eq(new_pwd_hash),
+			email.eq(None::<String>),
+			role.eq(PlayerRole::Player),
+			deletion_scheduled_for.eq(None::<DateTime<Utc>>),
+			anonymized_at.eq(at),
+			email_verified_at.eq(None::<DateTime<Utc>>),
+		))
+		.returning(Player::as_returning())
+		.get_result(conn)?;
 	Ok(player_)
 }
 
@@ -0,0 +1,131 @@
+//! Database access layer for quests and the progress of players towards them.
+
+use chrono::{DateTime, Utc};
+use diesel::dsl::{max, sql};
+use diesel::prelude::*;
+use diesel::sql_types::BigInt;
+use diesel::upsert::excluded;
+use tracing::{debug, instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::player::PlayerKey;
+use crate::domain::quest::{PlayerQuest, Quest};
+use crate::schema::{building, player_building, player_quest as pq, quest};
+
+/// Retrieves every quest, in the order of the quest log.
+#[instrument(skip(conn))]
+pub fn get_all(conn: &mut DbConn) -> Result<Vec<Quest>> {
+	let quests = quest::table
+		.order((quest::position.asc(), quest::id.asc()))
+		.select(Quest::as_select())
+		.load(conn)?;
+	Ok(quests)
+}
+
+/// Retrieves a player's progress towards every quest they progressed or claimed.
+#[instrument(skip(conn))]
+pub fn get_for_player(conn: &mut DbConn, player_key: &PlayerKey) -> Result<Vec<PlayerQuest>> {
+	let progress = pq::table
+		.filter(pq::player_id.eq(player_key))
+		.select(PlayerQuest::as_select())
+		.load(conn)?;
+	Ok(progress)
+}
+
+/// Adds `amount` to a player's progress towards a quest.
+///
+/// Creates the progress if the player never progressed the quest.
+#[instrument(skip(conn))]
+pub fn add_progress(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	quest_key: &str,
+	amount: i64,
+) -> Result<PlayerQuest> {
+	let progress = diesel::insert_into(pq::table)
+		.values((
+			pq::player_id.eq(player_key),
+			pq::quest_id.eq(quest_key),
+			pq::progress.eq(amount),
+		))
+		.on_conflict((pq::player_id, pq::quest_id))
+		.do_update()
+		.set(pq::progress.eq(pq::progress + excluded(pq::progress)))
+		.returning(PlayerQuest::as_returning())
+		.get_result(conn)?;
+	trace!("Upserted quest progress: {:?}", progress);
+	Ok(progress)
+}
+
+/// Raises a player's progress towards a quest to `reached`, keeping it if it is higher already.
+///
+/// Creates the progress if the player never progressed the quest.
+#[instrument(skip(conn))]
+pub fn raise_progress(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	quest_key: &str,
+	reached: i64,
+) -> Result<PlayerQuest> {
+	let progress = diesel::insert_into(pq::table)
+		.values((
+			pq::player_id.eq(player_key),
+			pq::quest_id.eq(quest_key),
+			pq::progress.eq(reached),
+		))
+		.on_conflict((pq::player_id, pq::quest_id))
+		.do_update()
+		.set(pq::progress.eq(sql::<BigInt>(
+			"GREATEST(player_quest.progress, excluded.progress)",
+		)))
+		.returning(PlayerQuest::as_returning())
+		.get_result(conn)?;
+	trace!("Upserted quest progress: {:?}", progress);
+	Ok(progress)
+}
+
+/// Marks a quest as claimed by a player, with their final progress.
+///
+/// Returns `None` without changing anything if the player claimed it already, so concurrent
+/// claims never reward a quest twice.
+#[instrument(skip(conn))]
+pub fn claim(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	quest_key: &str,
+	progress: i64,
+	now: DateTime<Utc>,
+) -> Result<Option<PlayerQuest>> {
+	debug!("Player {} claims quest {}", player_key, quest_key);
+	diesel::insert_into(pq::table)
+		.values((pq::player_id.eq(player_key), pq::quest_id.eq(quest_key)))
+		.on_conflict_do_nothing()
+		.execute(conn)?;
+	let claimed = diesel::update(
+		pq::table
+			.find((player_key, quest_key))
+			.filter(pq::claimed_at.is_null()),
+	)
+	.set((pq::progress.eq(progress), pq::claimed_at.eq(now)))
+	.returning(PlayerQuest::as_returning())
+	.get_result(conn)
+	.optional()?;
+	Ok(claimed)
+}
+
+/// Retrieves the highest level of a player's buildings of the given name, 0 if they have none.
+#[instrument(skip(conn))]
+pub fn max_building_level(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	building_name: &str,
+) -> Result<i64> {
+	let level: Option<i32> = player_building::table
+		.inner_join(building::table)
+		.filter(player_building::player_id.eq(player_key))
+		.filter(building::name.eq(building_name))
+		.select(max(player_building::level))
+		.first(conn)?;
+	Ok(level.unwrap_or_default().into())
+}
@@ -2,17 +2,18 @@
 //!
 //! This module provides comprehensive CRUD operations for player resource management,
 //! including standard database operations and specialized functionality for resource
-//! deduction and player-specific queries.
+//! deduction and player- and settlement-specific queries.
 
 use diesel::prelude::*;
 use tracing::{debug, info, instrument, trace};
 
 use crate::Result;
-use crate::db::DbConn;
+use crate::db::{DbConn, settlements};
 use crate::domain::player::PlayerKey;
 use crate::domain::player::resource::{
 	NewPlayerResource, PlayerResource, PlayerResourceKey, UpdatePlayerResource,
 };
+use crate::domain::settlement::SettlementKey;
 use crate::schema::player_resource::dsl::*;
 
 /// Retrieves all player resources from the database.
@@ -125,7 +126,10 @@ pub fn delete(conn: &mut DbConn, resource_key: &PlayerResourceKey) -> Result<usi
 /// * gold (i64)
 pub type ResourceDelta = (i64, i64, i64, i64);
 
-/// Retrieves resource information for a specific player by their player ID.
+/// Retrieves the resources in the capital of a player.
+///
+/// Resources gained and spent player-wide, like market trades or caravans, go through the
+/// capital. See [`get_by_settlement`] for the other settlements.
 ///
 /// # Arguments
 /// * `conn` - Database connection
@@ -136,15 +140,70 @@ pub type ResourceDelta = (i64, i64, i64, i64);
 #[instrument(skip(conn))]
 pub fn get_by_player_id(conn: &mut DbConn, player_key: &PlayerKey) -> Result<PlayerResource> {
 	debug!("Getting player resources");
+	let capital = settlements::get_capital(conn, player_key)?;
+	get_by_settlement(conn, &capital.id)
+}
+
+/// Retrieves the resources stored in a settlement.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `settlement_key` - The unique identifier of the settlement
+///
+/// # Returns
+/// A Result containing the [`PlayerResource`] of the settlement
+#[instrument(skip(conn))]
+pub fn get_by_settlement(
+	conn: &mut DbConn,
+	settlement_key: &SettlementKey,
+) -> Result<PlayerResource> {
 	let res = player_resource
 		.select(PlayerResource::as_select())
-		.filter(player_id.eq(player_key))
+		.filter(settlement_id.eq(settlement_key))
 		.first(conn)?;
 	trace!("Fetched player resource details: {:?}", res);
 	Ok(res)
 }
 
-/// Deducts specified amounts of resources from a player's resource pool.
+/// Retrieves the resources in a player's capital and locks them for the rest of the
+/// transaction.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `player_key` - The unique identifier of the player
+///
+/// # Returns
+/// A Result containing the locked [`PlayerResource`]
+#[instrument(skip(conn))]
+pub fn lock_by_player_id(conn: &mut DbConn, player_key: &PlayerKey) -> Result<PlayerResource> {
+	let capital = settlements::get_capital(conn, player_key)?;
+	lock_by_settlement(conn, &capital.id)
+}
+
+/// Retrieves the resources stored in a settlement and locks them for the rest of the
+/// transaction.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `settlement_key` - The unique identifier of the settlement
+///
+/// # Returns
+/// A Result containing the locked [`PlayerResource`]
+#[instrument(skip(conn))]
+pub fn lock_by_settlement(
+	conn: &mut DbConn,
+	settlement_key: &SettlementKey,
+) -> Result<PlayerResource> {
+	let res = player_resource
+		.select(PlayerResource::as_select())
+		.filter(settlement_id.eq(settlement_key))
+		.for_update()
+		.first(conn)?;
+	trace!("Locked player resource details: {:?}", res);
+	Ok(res)
+}
+
+/// Deducts specified amounts of resources from the storage of a player's capital.
 ///
 /// # Arguments
 /// * `conn` - Database connection
@@ -158,16 +217,31 @@ pub fn deduct(
 	conn: &mut DbConn,
 	player_key: &PlayerKey,
 	amounts: &ResourceDelta,
+) -> Result<PlayerResource> {
+	let capital = settlements::get_capital(conn, player_key)?;
+	deduct_from_settlement(conn, &capital.id, amounts)
+}
+
+/// Deducts specified amounts of resources from the storage of a settlement.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `settlement_key` - The unique identifier of the settlement
+/// * `amounts` - The amounts to deduct as a tuple of (food, wood, stone, gold)
+///
+/// # Returns
+/// A Result containing the updated [`PlayerResource`] after deduction
+#[instrument(skip(conn))]
+pub fn deduct_from_settlement(
+	conn: &mut DbConn,
+	settlement_key: &SettlementKey,
+	amounts: &ResourceDelta,
 ) -> Result<PlayerResource> {
 	debug!(
-		"Starting deduct resources from player {}: food={}, wood={}, stone={}, gold={}",
-		player_key, amounts.0, amounts.1, amounts.2, amounts.3
+		"Starting deduct resources from settlement {}: food={}, wood={}, stone={}, gold={}",
+		settlement_key, amounts.0, amounts.1, amounts.2, amounts.3
 	);
-	let res: PlayerResource = player_resource
-		.filter(player_id.eq(player_key))
-		.first(conn)?;
-	trace!("Current resources before deduction: {:?}", res);
-	let updated_res = diesel::update(player_resource.filter(player_id.eq(player_key)))
+	let updated_res = diesel::update(player_resource.filter(settlement_id.eq(settlement_key)))
 		.set((
 			food.eq(food - amounts.0),
 			wood.eq(wood - amounts.1),
@@ -178,16 +252,13 @@ pub fn deduct(
 		.get_result(conn)?;
 	trace!("Updated resources after deduction: {:?}", updated_res);
 	info!(
-		"Completed deduct resources from player {}: food={}, wood={}, stone={}, gold={}",
-		player_key, amounts.0, amounts.1, amounts.2, amounts.3
+		"Completed deduct resources from settlement {}: food={}, wood={}, stone={}, gold={}",
+		settlement_key, amounts.0, amounts.1, amounts.2, amounts.3
 	);
 	Ok(updated_res)
 }
 
-/// Adds resources to a player's inventory.
-///
-/// This function increases the resource amounts for a specific player.
-/// Used for refunds when cancelling training.
+/// Adds resources to the storage of a player's capital.
 ///
 /// # Arguments
 /// * `conn` - Database connection
@@ -201,12 +272,33 @@ pub fn add(
 	conn: &mut DbConn,
 	player_key: &PlayerKey,
 	amounts: &ResourceDelta,
+) -> Result<PlayerResource> {
+	let capital = settlements::get_capital(conn, player_key)?;
+	add_to_settlement(conn, &capital.id, amounts)
+}
+
+/// Adds resources to the storage of a settlement.
+///
+/// Used for refunds when cancelling training or upgrades in the settlement.
+///
+/// # Arguments
+/// * `conn` - Database connection
+/// * `settlement_key` - The unique identifier of the settlement
+/// * `amounts` - The amounts to add as a tuple of (food, wood, stone, gold)
+///
+/// # Returns
+/// A Result containing the updated [`PlayerResource`] after addition
+#[instrument(skip(conn))]
+pub fn add_to_settlement(
+	conn: &mut DbConn,
+	settlement_key: &SettlementKey,
+	amounts: &ResourceDelta,
 ) -> Result<PlayerResource> {
 	debug!(
-		"Adding resources to player {}: food={}, wood={}, stone={}, gold={}",
-		player_key, amounts.0, amounts.1, amounts.2, amounts.3
+		"Adding resources to settlement {}: food={}, wood={}, stone={}, gold={}",
+		settlement_key, amounts.0, amounts.1, amounts.2, amounts.3
 	);
-	let updated_res = diesel::update(player_resource.filter(player_id.eq(player_key)))
+	let updated_res = diesel::update(player_resource.filter(settlement_id.eq(settlement_key)))
 		.set((
 			food.eq(food + amounts.0),
 			wood.eq(wood + amounts.1),
@@ -0,0 +1,79 @@
+//! Database access layer for scout mission entities.
+//!
+//! This module provides operations for sending scout missions, linking them to the job
+//! completing them, and marking them completed with their intel report.
+
+use chrono::Utc;
+use diesel::prelude::*;
+use tracing::{debug, instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::combat::{IntelReportKey, NewScoutMission, ScoutMission, ScoutMissionKey};
+use crate::domain::jobs::JobKey;
+use crate::schema::scout_mission as sm;
+
+/// Creates a new scout mission.
+#[instrument(skip(conn, entity))]
+pub fn create(conn: &mut DbConn, entity: NewScoutMission) -> Result<ScoutMission> {
+	debug!(
+		"Sending {} scouts from player {} to player {}",
+		entity.scouts, entity.attacker_id, entity.defender_id
+	);
+	let mission = diesel::insert_into(sm::table)
+		.values(entity)
+		.returning(ScoutMission::as_returning())
+		.get_result(conn)?;
+	trace!("Created scout mission: {:?}", mission);
+	Ok(mission)
+}
+
+/// Retrieves a scout mission by its ID, returning `None` if it does not exist.
+#[instrument(skip(conn))]
+pub fn find_by_id(conn: &mut DbConn, mission_id: &ScoutMissionKey) -> Result<Option<ScoutMission>> {
+	let mission = sm::table.find(mission_id).first(conn).optional()?;
+	Ok(mission)
+}
+
+/// Retrieves a scout mission by its ID and locks it for the rest of the transaction.
+#[instrument(skip(conn))]
+pub fn lock_by_id(conn: &mut DbConn, mission_id: &ScoutMissionKey) -> Result<ScoutMission> {
+	let mission = sm::table
+		.find(mission_id)
+		.select(ScoutMission::as_select())
+		.for_update()
+		.first(conn)?;
+	Ok(mission)
+}
+
+/// Links a scout mission to the job completing it.
+#[instrument(skip(conn))]
+pub fn set_job_id(
+	conn: &mut DbConn,
+	mission_id: &ScoutMissionKey,
+	job_key: &JobKey,
+) -> Result<ScoutMission> {
+	let mission = diesel::update(sm::table.find(mission_id))
+		.set(sm::job_id.eq(Some(job_key)))
+		.returning(ScoutMission::as_returning())
+		.get_result(conn)?;
+	Ok(mission)
+}
+
+/// Marks a scout mission as completed, with the intel report it wrote if any.
+#[instrument(skip(conn))]
+pub fn complete(
+	conn: &mut DbConn,
+	mission_id: &ScoutMissionKey,
+	report_id: Option<&IntelReportKey>,
+) -> Result<ScoutMission> {
+	debug!("Completing scout mission {}", mission_id);
+	let mission = diesel::update(sm::table.find(mission_id))
+		.set((
+			sm::report_id.eq(report_id),
+			sm::completed_at.eq(Some(Utc::now())),
+		))
+		.returning(ScoutMission::as_returning())
+		.get_result(conn)?;
+	Ok(mission)
+}
@@ -0,0 +1,214 @@
+//! Database access layer for seasons, their archived standings and the legacy of players.
+
+use chrono::{DateTime, Utc};
+use diesel::dsl::sql;
+use diesel::prelude::*;
+use diesel::sql_types::{Array, BigInt, Integer, Uuid as SqlUuid};
+use tracing::{debug, instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::leaderboard::LeaderboardCategory;
+use crate::domain::player::PlayerKey;
+use crate::domain::season::{NewSeason, PlayerLegacy, Season, SeasonKey, SeasonStanding};
+use crate::schema::{player_legacy, season, season_standing as ss};
+
+/// Copies the rankings of the last leaderboard snapshot into the standings of a season, with
+/// the names of the ranked players and alliances.
+const ARCHIVE_STANDINGS_SQL: &str = "
+	INSERT INTO season_standing (season_id, category, subject_id, name, rank, score)
+	SELECT $1, le.category, le.subject_id, COALESCE(p.name, a.name), le.rank, le.score
+	FROM leaderboard_entry le
+	LEFT JOIN player p ON le.category <> 'alliance' AND p.id = le.subject_id
+	LEFT JOIN alliance a ON le.category = 'alliance' AND a.id = le.subject_id
+	WHERE COALESCE(p.name, a.name) IS NOT NULL";
+
+/// Grants the player standings of a season the legacy points of the best tier their rank
+/// falls into. Tiers are given as their lowest rank and their points.
+const GRANT_STANDING_POINTS_SQL: &str = "
+	UPDATE season_standing s
+	SET legacy_points = COALESCE((SELECT t.points
+	                              FROM unnest($2::INT[], $3::BIGINT[]) AS t(max_rank, points)
+	                              WHERE s.rank <= t.max_rank
+	                              ORDER BY t.max_rank
+	                              LIMIT 1), 0)
+	WHERE s.season_id = $1 AND s.category <> 'alliance'";
+
+/// Adds the legacy points of a season's player standings to the legacy of the players.
+const ADD_LEGACY_SQL: &str = "
+	INSERT INTO player_legacy (player_id, legacy_points, seasons)
+	SELECT s.subject_id, SUM(s.legacy_points)::BIGINT, 1
+	FROM season_standing s
+	WHERE s.season_id = $1 AND s.category <> 'alliance'
+	GROUP BY s.subject_id
+	ON CONFLICT (player_id) DO UPDATE
+	    SET legacy_points = player_legacy.legacy_points + EXCLUDED.legacy_points,
+	        seasons       = player_legacy.seasons + 1";
+
+/// Creates a new season.
+#[instrument(skip(conn))]
+pub fn create(conn: &mut DbConn, entity: NewSeason) -> Result<Season> {
+	let season = diesel::insert_into(season::table)
+		.values(entity)
+		.returning(Season::as_returning())
+		.get_result(conn)?;
+	trace!("Created season: {:?}", season);
+	Ok(season)
+}
+
+/// Finds a season by its ID.
+#[instrument(skip(conn))]
+pub fn find_by_id(conn: &mut DbConn, season_id: &SeasonKey) -> Result<Option<Season>> {
+	let season = season::table
+		.find(season_id)
+		.select(Season::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(season)
+}
+
+/// Locks a season for the rest of the transaction.
+#[instrument(skip(conn))]
+pub fn lock_by_id(conn: &mut DbConn, season_id: &SeasonKey) -> Result<Season> {
+	let season = season::table
+		.find(season_id)
+		.select(Season::as_select())
+		.for_update()
+		.first(conn)?;
+	Ok(season)
+}
+
+/// Finds the season that has not ended yet, running or upcoming. There is at most one.
+#[instrument(skip(conn))]
+pub fn find_open(conn: &mut DbConn) -> Result<Option<Season>> {
+	let season = season::table
+		.filter(season::ended_at.is_null())
+		.select(Season::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(season)
+}
+
+/// Finds the season with the highest number.
+#[instrument(skip(conn))]
+pub fn find_latest(conn: &mut DbConn) -> Result<Option<Season>> {
+	let season = season::table
+		.order(season::number.desc())
+		.select(Season::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(season)
+}
+
+/// Retrieves the seasons that ended, latest first.
+#[instrument(skip(conn))]
+pub fn get_ended(conn: &mut DbConn) -> Result<Vec<Season>> {
+	let seasons = season::table
+		.filter(season::ended_at.is_not_null())
+		.order(season::number.desc())
+		.select(Season::as_select())
+		.load(conn)?;
+	Ok(seasons)
+}
+
+/// Marks a season as ended at `now`.
+#[instrument(skip(conn))]
+pub fn end(conn: &mut DbConn, season_id: &SeasonKey, now: DateTime<Utc>) -> Result<Season> {
+	let season = diesel::update(season::table.find(season_id))
+		.set(season::ended_at.eq(now))
+		.returning(Season::as_returning())
+		.get_result(conn)?;
+	Ok(season)
+}
+
+/// Archives the rankings of the last leaderboard snapshot as the standings of a season.
+///
+/// # Returns
+/// The number of archived standings
+#[instrument(skip(conn))]
+pub fn archive_standings(conn: &mut DbConn, season_id: &SeasonKey) -> Result<usize> {
+	let archived = diesel::sql_query(ARCHIVE_STANDINGS_SQL)
+		.bind::<SqlUuid, _>(season_id)
+		.execute(conn)?;
+	debug!("Archived {} standings of season {}", archived, season_id);
+	Ok(archived)
+}
+
+/// Grants the player standings of a season their legacy points, and adds them to the legacy
+/// of the players.
+///
+/// # Returns
+/// The number of players whose legacy grew
+#[instrument(skip(conn))]
+pub fn grant_legacy(
+	conn: &mut DbConn,
+	season_id: &SeasonKey,
+	tiers: &[(i32, i64)],
+) -> Result<usize> {
+	let (max_ranks, points): (Vec<i32>, Vec<i64>) = tiers.iter().copied().unzip();
+	diesel::sql_query(GRANT_STANDING_POINTS_SQL)
+		.bind::<SqlUuid, _>(season_id)
+		.bind::<Array<Integer>, _>(max_ranks)
+		.bind::<Array<BigInt>, _>(points)
+		.execute(conn)?;
+	let players = diesel::sql_query(ADD_LEGACY_SQL)
+		.bind::<SqlUuid, _>(season_id)
+		.execute(conn)?;
+	debug!("Granted {} players legacy in season {}", players, season_id);
+	Ok(players)
+}
+
+/// Sums the legacy points granted in a season.
+#[instrument(skip(conn))]
+pub fn sum_legacy_points(conn: &mut DbConn, season_id: &SeasonKey) -> Result<i64> {
+	let total = ss::table
+		.filter(ss::season_id.eq(season_id))
+		.select(sql::<BigInt>("COALESCE(SUM(legacy_points), 0)::BIGINT"))
+		.first(conn)?;
+	Ok(total)
+}
+
+/// Retrieves the best standings of a board of a season.
+#[instrument(skip(conn))]
+pub fn get_standings(
+	conn: &mut DbConn,
+	season_id: &SeasonKey,
+	category: LeaderboardCategory,
+	limit: i64,
+) -> Result<Vec<SeasonStanding>> {
+	let standings = ss::table
+		.filter(ss::season_id.eq(season_id))
+		.filter(ss::category.eq(category))
+		.order((ss::rank.asc(), ss::subject_id.asc()))
+		.limit(limit)
+		.select(SeasonStanding::as_select())
+		.load(conn)?;
+	Ok(standings)
+}
+
+/// Retrieves the standing of a player or alliance on a board of a season, if they were ranked.
+#[instrument(skip(conn))]
+pub fn find_standing(
+	conn: &mut DbConn,
+	season_id: &SeasonKey,
+	category: LeaderboardCategory,
+	subject_id: &uuid::Uuid,
+) -> Result<Option<SeasonStanding>> {
+	let standing = ss::table
+		.find((season_id, category, subject_id))
+		.select(SeasonStanding::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(standing)
+}
+
+/// Retrieves the legacy of a player, if they earned any.
+#[instrument(skip(conn))]
+pub fn find_legacy(conn: &mut DbConn, player_key: &PlayerKey) -> Result<Option<PlayerLegacy>> {
+	let legacy = player_legacy::table
+		.find(player_key)
+		.select(PlayerLegacy::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(legacy)
+}
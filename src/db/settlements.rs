@@ -0,0 +1,173 @@
+//! Database access layer for settlement entities.
+//!
+//! This module provides operations for founding settlements, capitals included, with their
+//! storage, accumulator and starter buildings, and looking up the settlements of a player.
+
+use diesel::prelude::*;
+use tracing::{debug, instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::player::PlayerKey;
+use crate::domain::player::resource::NewPlayerResource;
+use crate::domain::settlement::{CAPITAL_NAME, NewSettlement, Settlement, SettlementKey};
+use crate::schema::settlement as st;
+
+/// Founds a new settlement with the storage, accumulator and starter buildings a new player
+/// gets for their capital, see [`stock`].
+#[instrument(skip(conn, entity))]
+pub fn found(conn: &mut DbConn, entity: NewSettlement) -> Result<Settlement> {
+	debug!(
+		"Founding settlement {} for player {}",
+		entity.name, entity.player_id
+	);
+	let settlement = diesel::insert_into(st::table)
+		.values(entity)
+		.returning(Settlement::as_returning())
+		.get_result(conn)?;
+	let buildings = stock(conn, &settlement)?;
+	trace!(
+		"Founded settlement {:?} with {} buildings",
+		settlement, buildings
+	);
+	Ok(settlement)
+}
+
+/// Founds the capital of a player, or retrieves it if they already have one.
+#[instrument(skip(conn))]
+pub fn found_capital(conn: &mut DbConn, player_id: &PlayerKey) -> Result<Settlement> {
+	let founded = diesel::insert_into(st::table)
+		.values((
+			st::player_id.eq(player_id),
+			st::name.eq(CAPITAL_NAME),
+			st::is_capital.eq(true),
+		))
+		.on_conflict_do_nothing()
+		.execute(conn)?;
+	trace!("Founded {} capital for player {}", founded, player_id);
+	get_capital(conn, player_id)
+}
+
+/// Gives a settlement the storage, accumulator and starter buildings of its owner's faction
+/// it does not have yet, neutral players get no buildings.
+///
+/// Storage is created before the buildings, so the buildings raise its caps. Returns the
+/// number of buildings added.
+#[instrument(skip(conn, settlement), fields(settlement_id = %settlement.id))]
+pub fn stock(conn: &mut DbConn, settlement: &Settlement) -> Result<usize> {
+	use crate::schema::{player_accumulator as pa, player_resource as pr};
+
+	diesel::insert_into(pr::table)
+		.values(NewPlayerResource {
+			player_id: settlement.player_id,
+			settlement_id: Some(settlement.id),
+			food: None,
+			wood: None,
+			stone: None,
+			gold: None,
+		})
+		.on_conflict(pr::settlement_id)
+		.do_nothing()
+		.execute(conn)?;
+	diesel::insert_into(pa::table)
+		.values((
+			pa::player_id.eq(settlement.player_id),
+			pa::settlement_id.eq(settlement.id),
+		))
+		.on_conflict(pa::settlement_id)
+		.do_nothing()
+		.execute(conn)?;
+	// Headquarters start at level 1, every other starter building is yet to be built
+	let buildings = diesel::sql_query(
+		"INSERT INTO player_building (player_id, settlement_id, building_id, level)
+		 SELECT s.player_id, s.id, b.id, CASE WHEN b.max_count = 1 THEN 1 ELSE 0 END
+		 FROM settlement s
+		 JOIN player p ON p.id = s.player_id
+		 JOIN building b ON b.faction = p.faction AND b.starter = TRUE
+		 WHERE s.id = $1
+		   AND NOT EXISTS (SELECT 1
+		                   FROM player_building pb
+		                   WHERE pb.settlement_id = s.id
+		                     AND pb.building_id = b.id)",
+	)
+	.bind::<diesel::sql_types::Uuid, _>(settlement.id)
+	.execute(conn)?;
+	Ok(buildings)
+}
+
+/// Retrieves a settlement, if it belongs to the player.
+#[instrument(skip(conn))]
+pub fn find_owned(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	settlement_id: &SettlementKey,
+) -> Result<Option<Settlement>> {
+	let settlement = st::table
+		.find(settlement_id)
+		.filter(st::player_id.eq(player_id))
+		.select(Settlement::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(settlement)
+}
+
+/// Retrieves all matching settlements by ID.
+#[instrument(skip(conn))]
+pub fn get_all_by_id(
+	conn: &mut DbConn,
+	settlement_ids: &[SettlementKey],
+) -> Result<Vec<Settlement>> {
+	let settlements = st::table
+		.filter(st::id.eq_any(settlement_ids))
+		.select(Settlement::as_select())
+		.load(conn)?;
+	Ok(settlements)
+}
+
+/// Retrieves the capital of a player.
+///
+/// Every player has one, it is founded when they are provisioned, see
+/// [`crate::game::player_operations::provision_player`].
+#[instrument(skip(conn))]
+pub fn get_capital(conn: &mut DbConn, player_id: &PlayerKey) -> Result<Settlement> {
+	let capital = st::table
+		.filter(st::player_id.eq(player_id))
+		.filter(st::is_capital.eq(true))
+		.select(Settlement::as_select())
+		.first(conn)?;
+	Ok(capital)
+}
+
+/// Retrieves the settlements of a player, the capital first and the others in the order
+/// they were founded.
+#[instrument(skip(conn))]
+pub fn get_by_player(conn: &mut DbConn, player_id: &PlayerKey) -> Result<Vec<Settlement>> {
+	let settlements = st::table
+		.filter(st::player_id.eq(player_id))
+		.order((st::is_capital.desc(), st::created_at.asc(), st::id.asc()))
+		.select(Settlement::as_select())
+		.load(conn)?;
+	Ok(settlements)
+}
+
+/// Counts the settlements of a player, their capital included.
+#[instrument(skip(conn))]
+pub fn count_by_player(conn: &mut DbConn, player_id: &PlayerKey) -> Result<i64> {
+	let count = st::table
+		.filter(st::player_id.eq(player_id))
+		.count()
+		.get_result(conn)?;
+	Ok(count)
+}
+
+/// Checks whether the player already has a settlement with the name.
+#[instrument(skip(conn))]
+pub fn exists_by_name(conn: &mut DbConn, player_id: &PlayerKey, name: &str) -> Result<bool> {
+	let exists = diesel::select(diesel::dsl::exists(
+		st::table
+			.filter(st::player_id.eq(player_id))
+			.filter(st::name.eq(name)),
+	))
+	.get_result(conn)?;
+	Ok(exists)
+}
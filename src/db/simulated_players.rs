@@ -0,0 +1,33 @@
+//! Database access layer for the NPC players of the simulation mode.
+//!
+//! This module provides operations for marking players as simulated and listing them,
+//! so the simulation never acts for a real player.
+
+use diesel::prelude::*;
+use tracing::{instrument, trace};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::player::PlayerKey;
+use crate::schema::simulated_player as sp;
+
+/// Marks a player as simulated.
+#[instrument(skip(conn))]
+pub fn create(conn: &mut DbConn, player_id: &PlayerKey) -> Result<usize> {
+	let rows = diesel::insert_into(sp::table)
+		.values(sp::player_id.eq(player_id))
+		.on_conflict_do_nothing()
+		.execute(conn)?;
+	trace!("Marked player {} as simulated", player_id);
+	Ok(rows)
+}
+
+/// Retrieves the IDs of all simulated players, oldest first.
+#[instrument(skip(conn))]
+pub fn get_all_ids(conn: &mut DbConn) -> Result<Vec<PlayerKey>> {
+	let ids = sp::table
+		.order((sp::created_at.asc(), sp::player_id.asc()))
+		.select(sp::player_id)
+		.load(conn)?;
+	Ok(ids)
+}
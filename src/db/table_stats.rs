@@ -0,0 +1,68 @@
+//! Database access layer for the periodic samples of table sizes.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{Array, Text, Timestamptz};
+use tracing::{debug, instrument};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::table_stats::TableStats;
+use crate::schema::table_stats as ts;
+
+/// Captures the row count and on-disk size of every listed table that exists.
+///
+/// Row counts are the live tuple estimates of the statistics collector rather than a
+/// `COUNT(*)`, so capturing the largest tables never scans them.
+///
+/// # Returns
+/// The captured samples, one per existing table
+#[instrument(skip(conn))]
+pub fn capture(
+	conn: &mut DbConn,
+	tables: &[&str],
+	captured_at: DateTime<Utc>,
+) -> Result<Vec<TableStats>> {
+	let samples: Vec<TableStats> = diesel::sql_query(
+		"INSERT INTO table_stats (table_name, row_count, total_bytes, captured_at) \
+		 SELECT relname::text, n_live_tup, pg_total_relation_size(relid), $2 \
+		 FROM pg_stat_user_tables \
+		 WHERE schemaname = current_schema() AND relname = ANY($1) \
+		 RETURNING *",
+	)
+	.bind::<Array<Text>, _>(tables)
+	.bind::<Timestamptz, _>(captured_at)
+	.load(conn)?;
+	debug!("Captured the size of {} tables", samples.len());
+	Ok(samples)
+}
+
+/// Returns the samples captured since `since`, optionally of a single table, ordered by
+/// table and capture time.
+#[instrument(skip(conn))]
+pub fn get_since(
+	conn: &mut DbConn,
+	since: DateTime<Utc>,
+	table_name: Option<&str>,
+) -> Result<Vec<TableStats>> {
+	let mut query = ts::table
+		.filter(ts::captured_at.ge(since))
+		.select(TableStats::as_select())
+		.order((ts::table_name.asc(), ts::captured_at.asc()))
+		.into_boxed();
+	if let Some(table_name) = table_name {
+		query = query.filter(ts::table_name.eq(table_name));
+	}
+	Ok(query.load(conn)?)
+}
+
+/// Deletes all samples captured before the cutoff.
+///
+/// # Returns
+/// The number of deleted samples
+#[instrument(skip(conn))]
+pub fn delete_older_than(conn: &mut DbConn, cutoff: DateTime<Utc>) -> Result<usize> {
+	let count = diesel::delete(ts::table.filter(ts::captured_at.lt(cutoff))).execute(conn)?;
+	debug!("Deleted {} table stats older than {}", count, cutoff);
+	Ok(count)
+}
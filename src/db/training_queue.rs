@@ -3,19 +3,22 @@
 //! This module provides operations for managing the training queue,
 //! including creating entries, updating status, and querying by player or status.
 
-use chrono::Utc;
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::{DateTime, Utc};
+use diesel::dsl::{Eq, Filter, Nullable, exists, not};
 use diesel::prelude::*;
 use tracing::{debug, instrument, trace};
 
 use crate::Result;
 use crate::db::DbConn;
-use crate::domain::jobs::JobKey;
+use crate::domain::error::{Error, ErrorKind};
+use crate::domain::jobs::{JobKey, JobStatus};
 use crate::domain::player::PlayerKey;
 use crate::domain::player::buildings::PlayerBuildingKey;
 use crate::domain::unit::training::{
 	NewTrainingQueueEntry, TrainingQueueEntry, TrainingQueueKey, TrainingStatus,
 };
-use crate::schema::{building_level as bl, player_building as pb, training_queue as tq};
+use crate::schema::{building_level as bl, job, player_building as pb, training_queue as tq};
 
 /// Current queue state for a building, including active count and capacity.
 #[derive(Debug, Clone)]
@@ -59,6 +62,72 @@ pub fn get_active_for_player(
 	Ok(entries)
 }
 
+/// Retrieves a player's active training entries with the status of their completion job.
+///
+/// The status is `None` for entries without a job or whose job no longer exists.
+#[instrument(skip(conn))]
+pub fn get_active_with_job_status(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+) -> Result<Vec<(TrainingQueueEntry, Option<JobStatus>)>> {
+	let entries = tq::table
+		.left_join(job::table)
+		.filter(tq::player_id.eq(player_key))
+		.filter(tq::status.eq_any([TrainingStatus::Pending, TrainingStatus::InProgress]))
+		.select((TrainingQueueEntry::as_select(), job::status.nullable()))
+		.load(conn)?;
+	Ok(entries)
+}
+
+/// Sums the population taken up by the units in a player's active trainings.
+#[instrument(skip(conn))]
+pub fn get_training_population(conn: &mut DbConn, player_key: &PlayerKey) -> Result<i64> {
+	use diesel::dsl::sum;
+
+	use crate::schema::unit;
+
+	let population: Option<BigDecimal> = tq::table
+		.inner_join(unit::table)
+		.filter(tq::player_id.eq(player_key))
+		.filter(tq::status.eq_any([TrainingStatus::Pending, TrainingStatus::InProgress]))
+		.select(sum(tq::quantity * unit::population))
+		.first(conn)?;
+	Ok(population.and_then(|p| p.to_i64()).unwrap_or_default())
+}
+
+/// Counts the active training entries across all players that complete by `until`.
+#[instrument(skip(conn))]
+pub fn count_completing_before(conn: &mut DbConn, until: DateTime<Utc>) -> Result<i64> {
+	let count = tq::table
+		.filter(
+			tq::status
+				.eq(TrainingStatus::Pending)
+				.or(tq::status.eq(TrainingStatus::InProgress)),
+		)
+		.filter(tq::completes_at.le(until))
+		.count()
+		.get_result(conn)?;
+	Ok(count)
+}
+
+/// Counts the training entries started at a building since `since`, cancelled ones included.
+///
+/// Entries count from the time they were queued, a pending entry that starts later is not
+/// counted a second time.
+#[instrument(skip(conn))]
+pub fn count_started_since(
+	conn: &mut DbConn,
+	building_id: &PlayerBuildingKey,
+	since: DateTime<Utc>,
+) -> Result<i64> {
+	let count = tq::table
+		.filter(tq::building_id.eq(building_id))
+		.filter(tq::created_at.ge(since))
+		.count()
+		.get_result(conn)?;
+	Ok(count)
+}
+
 /// Retrieves all training queue entries with a specific status.
 #[instrument(skip(conn))]
 pub fn get_by_status(
@@ -72,33 +141,145 @@ pub fn get_by_status(
 	Ok(entries)
 }
 
-/// Marks a training queue entry as completed.
+/// Marks a training queue entry as completed, unless it is no longer active.
 ///
-/// Sets the status to Completed and records the completion timestamp.
+/// Sets the status to Completed and records the completion timestamp. The status check and
+/// the update are a single statement, so a concurrent [`cancel`] and this cannot both succeed.
+///
+/// # Returns
+/// The completed entry, or `None` if the entry was already completed or cancelled
 #[instrument(skip(conn))]
-pub fn complete(conn: &mut DbConn, entry_id: &TrainingQueueKey) -> Result<TrainingQueueEntry> {
+pub fn complete(
+	conn: &mut DbConn,
+	entry_id: &TrainingQueueKey,
+) -> Result<Option<TrainingQueueEntry>> {
 	debug!("Completing training queue entry {}", entry_id);
 	let entry = diesel::update(tq::table.find(entry_id))
+		.filter(tq::status.eq_any([TrainingStatus::Pending, TrainingStatus::InProgress]))
 		.set((
 			tq::status.eq(TrainingStatus::Completed),
 			tq::completed_at.eq(Some(Utc::now())),
 		))
 		.returning(TrainingQueueEntry::as_returning())
-		.get_result(conn)?;
+		.get_result(conn)
+		.optional()?;
 	trace!("Completed training queue entry: {:?}", entry);
 	Ok(entry)
 }
 
-/// Cancels a training queue entry.
+/// Starts the oldest pending entry of a building, unless one of its entries is in training.
+///
+/// Locks the active entries of the building, so concurrent calls start at most one entry.
+/// The entry starts at `now` and takes the duration recorded when it was queued.
+/// Must be called within a transaction.
+///
+/// # Returns
+/// The started entry, or `None` if an entry is in training or none is pending
+#[instrument(skip(conn))]
+pub fn start_next_pending(
+	conn: &mut DbConn,
+	building_key: &PlayerBuildingKey,
+	now: DateTime<Utc>,
+) -> Result<Option<TrainingQueueEntry>> {
+	let active: Vec<TrainingQueueEntry> = tq::table
+		.filter(tq::building_id.eq(building_key))
+		.filter(tq::status.eq_any([TrainingStatus::Pending, TrainingStatus::InProgress]))
+		.order((tq::created_at.asc(), tq::id.asc()))
+		.select(TrainingQueueEntry::as_select())
+		.for_update()
+		.load(conn)?;
+	if active
+		.iter()
+		.any(|entry| entry.status == TrainingStatus::InProgress)
+	{
+		trace!("Building {} is still training", building_key);
+		return Ok(None);
+	}
+	let Some(next) = active.into_iter().next() else {
+		trace!("Building {} has no pending training", building_key);
+		return Ok(None);
+	};
+
+	debug!("Starting pending training queue entry {}", next.id);
+	let entry = diesel::update(tq::table.find(next.id))
+		.set((
+			tq::status.eq(TrainingStatus::InProgress),
+			tq::started_at.eq(now),
+			tq::completes_at.eq(now + next.duration()),
+		))
+		.returning(TrainingQueueEntry::as_returning())
+		.get_result(conn)?;
+	trace!("Started training queue entry: {:?}", entry);
+	Ok(Some(entry))
+}
+
+/// Reduces the quantity of a training queue entry and moves its completion time.
+///
+/// Used when part of a training batch is cancelled. Like [`cancel`], this only applies while
+/// the entry is active and its completion job has not completed. The entry must also still
+/// hold the `expected` quantity the new one was computed from, so of two cancellations racing
+/// each other only one reduces it.
+///
+/// # Returns
+/// The reduced entry, or `None` if the entry can no longer be changed or was changed since
+#[instrument(skip(conn))]
+pub fn reduce_quantity(
+	conn: &mut DbConn,
+	entry_id: &TrainingQueueKey,
+	expected: i64,
+	quantity: i64,
+	completes_at: DateTime<Utc>,
+) -> Result<Option<TrainingQueueEntry>> {
+	debug!(
+		"Reducing training queue entry {} from {} to {} units",
+		entry_id, expected, quantity
+	);
+	let entry = diesel::update(tq::table.find(entry_id))
+		.filter(tq::status.eq_any([TrainingStatus::Pending, TrainingStatus::InProgress]))
+		.filter(tq::quantity.eq(expected))
+		.filter(not(exists(completed_job())))
+		.set((tq::quantity.eq(quantity), tq::completes_at.eq(completes_at)))
+		.returning(TrainingQueueEntry::as_returning())
+		.get_result(conn)
+		.optional()?;
+	trace!("Reduced training queue entry: {:?}", entry);
+	Ok(entry)
+}
+
+/// Cancels a training queue entry, unless it is no longer active, left the `expected` status
+/// or its completion job has already completed.
 ///
-/// Sets the status to Cancelled. Does not record a completion timestamp.
+/// Sets the status to Cancelled. Does not record a completion timestamp. The checks and the
+/// update are a single statement, so a concurrent [`complete`] and this cannot both succeed.
+///
+/// # Returns
+/// The cancelled entry, or `None` if the entry can no longer be cancelled
 #[instrument(skip(conn))]
-pub fn cancel(conn: &mut DbConn, entry_id: &TrainingQueueKey) -> Result<usize> {
+pub fn cancel(
+	conn: &mut DbConn,
+	entry_id: &TrainingQueueKey,
+	expected: &TrainingStatus,
+) -> Result<Option<TrainingQueueEntry>> {
 	debug!("Cancelling training queue entry {}", entry_id);
-	let count = diesel::update(tq::table.find(entry_id))
+	let entry = diesel::update(tq::table.find(entry_id))
+		.filter(tq::status.eq_any([TrainingStatus::Pending, TrainingStatus::InProgress]))
+		.filter(tq::status.eq(expected))
+		.filter(not(exists(completed_job())))
 		.set(tq::status.eq(TrainingStatus::Cancelled))
-		.execute(conn)?;
-	Ok(count)
+		.returning(TrainingQueueEntry::as_returning())
+		.get_result(conn)
+		.optional()?;
+	trace!("Cancelled training queue entry: {:?}", entry);
+	Ok(entry)
+}
+
+type LinkedJob = Filter<job::table, Eq<Nullable<job::id>, tq::job_id>>;
+
+/// The completed job linked to a training queue entry, to be used in a filter on the entry.
+fn completed_job() -> Filter<LinkedJob, Eq<job::status, JobStatus>> {
+	job::table
+		.filter(job::id.nullable().eq(tq::job_id))
+		.filter(job::status.eq(JobStatus::Completed))
 }
 
 /// Updates the status of a training queue entry.
@@ -127,6 +308,24 @@ pub fn get_by_id(conn: &mut DbConn, entry_id: &TrainingQueueKey) -> Result<Train
 	Ok(entry)
 }
 
+/// Retrieves a training queue entry by its ID, as long as it belongs to the player.
+///
+/// Returns a `NotFoundError` for missing entries and for entries of other players alike.
+#[instrument(skip(conn))]
+pub fn get_owned(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	entry_id: &TrainingQueueKey,
+) -> Result<TrainingQueueEntry> {
+	tq::table
+		.filter(tq::id.eq(entry_id))
+		.filter(tq::player_id.eq(player_key))
+		.select(TrainingQueueEntry::as_select())
+		.first(conn)
+		.optional()?
+		.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Training entry not found")))
+}
+
 /// Gets the count of active training entries for a specific building.
 ///
 /// Used to enforce per-building queue capacity limits.
@@ -0,0 +1,48 @@
+//! Database access layer for unit upgrade entities.
+//!
+//! This module provides operations for retrieving how units are upgraded to higher tiers.
+
+use diesel::prelude::*;
+use tracing::instrument;
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::error::{Error, ErrorKind};
+use crate::domain::unit::UnitKey;
+use crate::domain::unit::upgrade::UnitUpgrade;
+use crate::schema::{unit, unit_upgrade as uu};
+
+/// Retrieves all unit upgrades, lowest Blacksmith level first.
+#[instrument(skip(conn))]
+pub fn get_all(conn: &mut DbConn) -> Result<Vec<UnitUpgrade>> {
+	let upgrades = uu::table
+		.inner_join(unit::table)
+		.order((uu::blacksmith_level, unit::name))
+		.select(UnitUpgrade::as_select())
+		.load(conn)?;
+	Ok(upgrades)
+}
+
+/// Retrieves the upgrade of a unit.
+///
+/// Fails with [`ErrorKind::NotFoundError`] if the unit cannot be upgraded.
+#[instrument(skip(conn))]
+pub fn get_by_unit(conn: &mut DbConn, unit_key: &UnitKey) -> Result<UnitUpgrade> {
+	uu::table
+		.find(unit_key)
+		.select(UnitUpgrade::as_select())
+		.first(conn)
+		.optional()?
+		.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Unit cannot be upgraded")))
+}
+
+/// Retrieves the upgrade that leads to a unit, `None` for units that are trained.
+#[instrument(skip(conn))]
+pub fn find_by_upgraded_unit(conn: &mut DbConn, unit_key: &UnitKey) -> Result<Option<UnitUpgrade>> {
+	let upgrade = uu::table
+		.filter(uu::upgraded_unit_id.eq(unit_key))
+		.select(UnitUpgrade::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(upgrade)
+}
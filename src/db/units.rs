@@ -25,6 +25,17 @@ pub fn get_by_id(conn: &mut DbConn, unit_id: &UnitKey) -> Result<Unit> {
 	Ok(result)
 }
 
+/// Retrieves a unit by its name, returning `None` if there is none.
+#[instrument(skip(conn))]
+pub fn find_by_name(conn: &mut DbConn, unit_name: &str) -> Result<Option<Unit>> {
+	let result = unit
+		.filter(name.eq(unit_name))
+		.select(Unit::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(result)
+}
+
 /// Retrieves all matching units by ID.
 #[instrument(skip(conn))]
 pub fn get_all_by_id(conn: &mut DbConn, unit_ids: &[UnitKey]) -> Result<Vec<Unit>> {
@@ -32,11 +43,12 @@ pub fn get_all_by_id(conn: &mut DbConn, unit_ids: &[UnitKey]) -> Result<Vec<Unit
 	Ok(result)
 }
 
-/// Retrieves all units of a specific type.
+/// Retrieves all units of a specific type, lowest tier first.
 #[instrument(skip(conn))]
 pub fn get_by_type(conn: &mut DbConn, unit_type_filter: &UnitType) -> Result<Vec<Unit>> {
 	let unit_list = unit
 		.filter(unit_type.eq(unit_type_filter))
+		.order((tier, name))
 		.select(Unit::as_select())
 		.load(conn)?;
 	Ok(unit_list)
@@ -0,0 +1,84 @@
+//! Database access layer for world resets.
+//!
+//! A unique index allows a single confirmed reset that has not completed, so confirming a
+//! second reset while one runs fails with a unique violation.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::world_reset::{NewWorldReset, WorldReset, WorldResetKey};
+use crate::schema::world_reset::dsl::*;
+
+/// Records a requested world reset.
+pub fn create(conn: &mut DbConn, entity: NewWorldReset) -> Result<WorldReset> {
+	let reset = diesel::insert_into(world_reset)
+		.values(entity)
+		.returning(WorldReset::as_returning())
+		.get_result(conn)?;
+	Ok(reset)
+}
+
+/// Retrieves a world reset by its ID, `None` if it does not exist.
+pub fn find_by_id(conn: &mut DbConn, reset_id: &WorldResetKey) -> Result<Option<WorldReset>> {
+	let reset = world_reset
+		.find(reset_id)
+		.select(WorldReset::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(reset)
+}
+
+/// Retrieves a world reset by its ID and locks it until the transaction ends.
+pub fn find_for_update(conn: &mut DbConn, reset_id: &WorldResetKey) -> Result<Option<WorldReset>> {
+	let reset = world_reset
+		.find(reset_id)
+		.select(WorldReset::as_select())
+		.for_update()
+		.first(conn)
+		.optional()?;
+	Ok(reset)
+}
+
+/// Marks a world reset as confirmed.
+pub fn confirm(
+	conn: &mut DbConn,
+	reset_id: &WorldResetKey,
+	operator: Option<String>,
+	now: DateTime<Utc>,
+) -> Result<WorldReset> {
+	let reset = diesel::update(world_reset.find(reset_id))
+		.set((confirmed_by.eq(operator), confirmed_at.eq(now)))
+		.returning(WorldReset::as_returning())
+		.get_result(conn)?;
+	Ok(reset)
+}
+
+/// Records that one more step of a world reset ran, completing the reset with its last step.
+pub fn complete_step(
+	conn: &mut DbConn,
+	reset_id: &WorldResetKey,
+	last: bool,
+	now: DateTime<Utc>,
+) -> Result<WorldReset> {
+	let reset = diesel::update(world_reset.find(reset_id))
+		.set((
+			completed_steps.eq(completed_steps + 1),
+			completed_at.eq(last.then_some(now)),
+		))
+		.returning(WorldReset::as_returning())
+		.get_result(conn)?;
+	Ok(reset)
+}
+
+/// Retrieves the confirmed world reset that has not completed yet, if any.
+pub fn find_running(conn: &mut DbConn) -> Result<Option<WorldReset>> {
+	let reset = world_reset
+		.filter(confirmed_at.is_not_null())
+		.filter(completed_at.is_null())
+		.select(WorldReset::as_select())
+		.first(conn)
+		.optional()?;
+	Ok(reset)
+}
@@ -0,0 +1,19 @@
+//! Contains domain entities for the deletion of player accounts.
+//! Players ask for their account to be deleted, and once the grace period ends their personal
+//! data is anonymized while the history referencing them is kept. See
+//! [`crate::game::account_deletion`].
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::player::PlayerKey;
+
+/// Payload of a [`crate::domain::jobs::JobType::AccountDeletion`] job, anonymizing an account
+/// once its grace period ends.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountDeletionJobPayload {
+	pub player_id: PlayerKey,
+	/// The deletion the job was enqueued for, the job does nothing if it was cancelled or
+	/// scheduled again since
+	pub scheduled_for: DateTime<Utc>,
+}
@@ -0,0 +1,184 @@
+//! Contains domain entities for alliances.
+//! Players band together in alliances led by a single leader, who is helped by officers.
+//! Players join by accepting an invite, and members pool resources in the alliance treasury.
+//! Members of the same alliance cannot attack each other.
+
+use std::io::Write;
+use std::str::from_utf8;
+
+use chrono::{DateTime, Utc};
+use derive_more::Display;
+use diesel::deserialize::FromSql;
+use diesel::pg::{Pg, PgValue};
+use diesel::prelude::*;
+use diesel::serialize::{IsNull, Output, ToSql};
+use diesel::{AsExpression, FromSqlRow, deserialize, serialize};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::player::{Player, PlayerKey};
+use crate::schema::{alliance, alliance_donation, alliance_invite, alliance_member};
+
+/// Unique identifier for an alliance
+pub type AllianceKey = Uuid;
+
+/// Unique identifier for an alliance invite
+pub type AllianceInviteKey = Uuid;
+
+/// Unique identifier for a donation to an alliance treasury
+pub type AllianceDonationKey = Uuid;
+
+/// Rank of a player in their alliance.
+///
+/// Roles are ordered, every role can do what the roles before it can.
+#[derive(
+	AsExpression,
+	FromSqlRow,
+	Serialize,
+	Deserialize,
+	Display,
+	Debug,
+	Default,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	Hash,
+	PartialOrd,
+	Ord,
+)]
+#[diesel(sql_type = crate::schema::sql_types::AllianceRole)]
+#[serde(rename_all = "snake_case")]
+pub enum AllianceRole {
+	/// Can donate to the treasury
+	#[default]
+	Member,
+	/// Can invite players and kick members
+	Officer,
+	/// Can also kick officers and change roles, there is exactly one per alliance
+	Leader,
+}
+
+impl AsRef<str> for AllianceRole {
+	fn as_ref(&self) -> &str {
+		match self {
+			AllianceRole::Member => "member",
+			AllianceRole::Officer => "officer",
+			AllianceRole::Leader => "leader",
+		}
+	}
+}
+
+impl ToSql<crate::schema::sql_types::AllianceRole, Pg> for AllianceRole {
+	fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+		out.write_all(self.as_ref().as_bytes())?;
+		Ok(IsNull::No)
+	}
+}
+
+impl FromSql<crate::schema::sql_types::AllianceRole, Pg> for AllianceRole {
+	fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+		match from_utf8(bytes.as_bytes())? {
+			"member" => Ok(AllianceRole::Member),
+			"officer" => Ok(AllianceRole::Officer),
+			"leader" => Ok(AllianceRole::Leader),
+			other => Err(format!("Unrecognized enum variant: {other}").into()),
+		}
+	}
+}
+
+/// Represents an alliance with its treasury
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = alliance, check_for_backend(diesel::pg::Pg))]
+pub struct Alliance {
+	pub id: AllianceKey,
+	/// Unique name, regardless of case
+	pub name: String,
+	/// Short unique tag shown next to the names of the members, regardless of case
+	pub tag: String,
+	pub food: i64,
+	pub wood: i64,
+	pub stone: i64,
+	pub gold: i64,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+/// Data transfer object for founding a new alliance
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = alliance, check_for_backend(diesel::pg::Pg))]
+pub struct NewAlliance {
+	pub name: String,
+	pub tag: String,
+}
+
+/// Represents the membership of a player in an alliance
+#[derive(
+	Queryable, Selectable, Identifiable, Associations, Debug, Clone, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[diesel(belongs_to(Player), belongs_to(Alliance))]
+#[diesel(table_name = alliance_member, primary_key(player_id), check_for_backend(diesel::pg::Pg))]
+pub struct AllianceMember {
+	pub player_id: PlayerKey,
+	pub alliance_id: AllianceKey,
+	pub role: AllianceRole,
+	pub joined_at: DateTime<Utc>,
+}
+
+/// Data transfer object for adding a player to an alliance
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = alliance_member, check_for_backend(diesel::pg::Pg))]
+pub struct NewAllianceMember {
+	pub player_id: PlayerKey,
+	pub alliance_id: AllianceKey,
+	pub role: AllianceRole,
+}
+
+/// Represents an invite for a player to join an alliance
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = alliance_invite, check_for_backend(diesel::pg::Pg))]
+pub struct AllianceInvite {
+	pub id: AllianceInviteKey,
+	pub alliance_id: AllianceKey,
+	/// Player who is invited
+	pub player_id: PlayerKey,
+	/// Leader or officer who sent the invite, `None` once they are gone
+	pub invited_by: Option<PlayerKey>,
+	pub created_at: DateTime<Utc>,
+}
+
+/// Data transfer object for inviting a player
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = alliance_invite, check_for_backend(diesel::pg::Pg))]
+pub struct NewAllianceInvite {
+	pub alliance_id: AllianceKey,
+	pub player_id: PlayerKey,
+	pub invited_by: Option<PlayerKey>,
+}
+
+/// Represents resources a member donated to the treasury
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = alliance_donation, check_for_backend(diesel::pg::Pg))]
+pub struct AllianceDonation {
+	pub id: AllianceDonationKey,
+	pub alliance_id: AllianceKey,
+	/// Player who donated, `None` once they are gone
+	pub player_id: Option<PlayerKey>,
+	pub food: i64,
+	pub wood: i64,
+	pub stone: i64,
+	pub gold: i64,
+	pub created_at: DateTime<Utc>,
+}
+
+/// Data transfer object for recording a donation
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = alliance_donation, check_for_backend(diesel::pg::Pg))]
+pub struct NewAllianceDonation {
+	pub alliance_id: AllianceKey,
+	pub player_id: Option<PlayerKey>,
+	pub food: i64,
+	pub wood: i64,
+	pub stone: i64,
+	pub gold: i64,
+}
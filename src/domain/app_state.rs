@@ -4,6 +4,10 @@
 //! - Shared database connection pool for efficient database access
 //! - Background job queue for asynchronous task processing
 //! - Centralized modifier system integration
+//! - Event bus for real-time pushes to connected clients
+//! - Chat bus for the messages posted in the chat channels
+//! - In-memory request and player activity metrics
+//! - Short-lived cache of player activity statistics
 //! - Immutable application settings
 //!
 //! All components are wrapped in [`Arc`] to enable safe concurrency and sharing across threads.
@@ -17,6 +21,11 @@ use derive_more::Deref;
 
 use crate::configuration::Settings;
 use crate::db::{DbPool, connection};
+use crate::domain::chat::ChatBus;
+use crate::domain::events::EventBus;
+use crate::domain::metrics::ServerMetrics;
+use crate::game::activity_operations::ActivityCache;
+use crate::game::modifiers::modifier_cache::ModifierCache;
 use crate::game::modifiers::modifier_system::ModifierSystem;
 use crate::job_queue::JobQueue;
 
@@ -45,12 +54,73 @@ impl FromRef<AppState> for AppQueue {
 	}
 }
 
+/// Thread-safe shared handle to the game event bus.
+///
+/// Implements `FromRef<App>` so processors and handlers can publish events.
+pub type AppEvents = Arc<EventBus>;
+
+impl FromRef<AppState> for AppEvents {
+	fn from_ref(state: &AppState) -> Self {
+		Arc::clone(&state.events)
+	}
+}
+
+/// Thread-safe shared handle to the chat bus.
+///
+/// Implements `FromRef<App>` so handlers can publish posted messages and connections can
+/// subscribe to them.
+pub type AppChat = Arc<ChatBus>;
+
+impl FromRef<AppState> for AppChat {
+	fn from_ref(state: &AppState) -> Self {
+		Arc::clone(&state.chat)
+	}
+}
+
+/// Thread-safe shared handle to the server metrics.
+///
+/// Implements `FromRef<App>` so middleware can record requests and player activity.
+pub type AppMetrics = Arc<ServerMetrics>;
+
+impl FromRef<AppState> for AppMetrics {
+	fn from_ref(state: &AppState) -> Self {
+		Arc::clone(&state.metrics)
+	}
+}
+
+/// Thread-safe shared handle to the modifier multiplier cache.
+///
+/// Implements `FromRef<App>` so handlers calculating multipliers share the cache the modifier
+/// services keep up to date.
+pub type AppModifierCache = Arc<ModifierCache>;
+
+impl FromRef<AppState> for AppModifierCache {
+	fn from_ref(state: &AppState) -> Self {
+		Arc::clone(&state.modifier_system.cache)
+	}
+}
+
+/// Thread-safe shared handle to the player activity statistics cache.
+///
+/// Implements `FromRef<App>` so handlers can serve recently aggregated statistics.
+pub type AppActivityCache = Arc<ActivityCache>;
+
+impl FromRef<AppState> for AppActivityCache {
+	fn from_ref(state: &AppState) -> Self {
+		Arc::clone(&state.activity_cache)
+	}
+}
+
 /// Core application state shared across all request handlers.
 ///
 /// This struct holds primary shared resources:
 /// - Database pool for handling DB queries
 /// - Job queue for async/background tasks
 /// - Modifier system for game-related logic
+/// - Event bus for real-time game events
+/// - Chat bus for posted chat messages
+/// - Server metrics for the admin overview
+/// - Activity statistics cache for progress graphs
 /// - Application settings loaded at startup
 #[derive(Clone, FromRef)]
 pub struct App {
@@ -60,6 +130,14 @@ pub struct App {
 	pub job_queue: AppQueue,
 	/// Modifier system instance
 	pub modifier_system: ModifierSystem,
+	/// Broadcast channel for real-time game events
+	pub events: AppEvents,
+	/// Broadcast channel for posted chat messages
+	pub chat: AppChat,
+	/// Request and player activity counters
+	pub metrics: AppMetrics,
+	/// Recently aggregated player activity statistics
+	pub activity_cache: AppActivityCache,
 	/// Global application settings
 	pub settings: Settings,
 }
@@ -82,7 +160,19 @@ impl App {
 		// Initialize DB pool from settings
 		let db_pool = Arc::new(connection::initialize_pool(&settings.database));
 		// Create job queue linked to DB pool for persisting jobs
-		let job_queue = Arc::new(JobQueue::new(Arc::clone(&db_pool)));
+		let job_queue = Arc::new(
+			JobQueue::new(Arc::clone(&db_pool))
+				.with_concurrency_limits(settings.jobs.concurrency_limits())
+				.with_priority_aging(settings.jobs.priority_aging_per_minute)
+				.with_timeouts(settings.jobs.timeouts())
+				.with_payload_encryption(
+					settings
+						.jobs
+						.payload_cipher()
+						.expect("Invalid job payload key"),
+					settings.jobs.encrypted_types(),
+				),
+		);
 		// Setup modifier system with access to job queue and settings
 		let modifier_system = ModifierSystem::with_job_queue(&settings, &job_queue);
 
@@ -90,6 +180,10 @@ impl App {
 			db_pool,
 			job_queue,
 			modifier_system,
+			events: Arc::new(EventBus::default()),
+			chat: Arc::new(ChatBus::default()),
+			metrics: Arc::new(ServerMetrics::default()),
+			activity_cache: Arc::new(ActivityCache::default()),
 			settings,
 		}
 	}
@@ -103,13 +197,29 @@ impl App {
 	/// * `db_pool` - Pre-existing shared database pool
 	/// * `settings` - Application configuration
 	pub fn with_pool(db_pool: AppPool, settings: Settings) -> Self {
-		let job_queue = Arc::new(JobQueue::new(Arc::clone(&db_pool)));
+		let job_queue = Arc::new(
+			JobQueue::new(Arc::clone(&db_pool))
+				.with_concurrency_limits(settings.jobs.concurrency_limits())
+				.with_priority_aging(settings.jobs.priority_aging_per_minute)
+				.with_timeouts(settings.jobs.timeouts())
+				.with_payload_encryption(
+					settings
+						.jobs
+						.payload_cipher()
+						.expect("Invalid job payload key"),
+					settings.jobs.encrypted_types(),
+				),
+		);
 		let modifier_system = ModifierSystem::with_job_queue(&settings, &job_queue);
 
 		Self {
 			db_pool,
 			job_queue,
 			modifier_system,
+			events: Arc::new(EventBus::default()),
+			chat: Arc::new(ChatBus::default()),
+			metrics: Arc::new(ServerMetrics::default()),
+			activity_cache: Arc::new(ActivityCache::default()),
 			settings,
 		}
 	}
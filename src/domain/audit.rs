@@ -0,0 +1,60 @@
+//! Contains domain entities for the audit log.
+//! Every action an operator takes through the admin API is recorded as an entry, together
+//! with the operator and request it came from, and so are the sensitive actions players take
+//! on their own account, like logging in. See [`crate::game::audit_operations`].
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::domain::player::PlayerKey;
+use crate::schema::audit_log;
+
+/// Unique identifier for an audit log entry
+pub type AuditEntryKey = Uuid;
+
+/// Represents a recorded action
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, PartialEq)]
+#[diesel(table_name = audit_log, check_for_backend(diesel::pg::Pg))]
+pub struct AuditEntry {
+	pub id: AuditEntryKey,
+	/// What was done, like `enqueue_job`
+	pub action: String,
+	/// What the action was done to, like the ID of the enqueued job
+	pub subject: Option<String>,
+	/// JSON object with the parameters of the action
+	pub details: serde_json::Value,
+	/// Operator as declared by the caller, the admin key itself is shared
+	pub operator: Option<String>,
+	/// ID of the request that took the action
+	pub request_id: Option<String>,
+	pub created_at: DateTime<Utc>,
+	/// Player who took the action, `None` for operators and the server itself
+	pub player_id: Option<PlayerKey>,
+}
+
+/// Data transfer object for recording an action
+#[derive(Insertable, Debug, Clone, PartialEq)]
+#[diesel(table_name = audit_log, check_for_backend(diesel::pg::Pg))]
+pub struct NewAuditEntry {
+	pub action: String,
+	pub subject: Option<String>,
+	pub details: serde_json::Value,
+	pub operator: Option<String>,
+	pub request_id: Option<String>,
+	pub player_id: Option<PlayerKey>,
+}
+
+/// Narrows the audit log down, every field that is set has to match.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditFilter {
+	/// Player who took the action
+	pub player_id: Option<PlayerKey>,
+	/// Operator who took the action
+	pub operator: Option<String>,
+	pub action: Option<String>,
+	/// Start of the time range, inclusive
+	pub from: Option<DateTime<Utc>>,
+	/// End of the time range, exclusive
+	pub until: Option<DateTime<Utc>>,
+}
@@ -1,24 +1,26 @@
 use std::fmt::{Display, Formatter};
 use std::sync::OnceLock;
 
+use axum::RequestPartsExt;
 use axum::extract::{FromRef, FromRequestParts};
 use axum::http::StatusCode;
 use axum::http::request::Parts;
 use axum::response::{IntoResponse, Response};
-use axum::{Json, RequestPartsExt};
 use axum_extra::TypedHeader;
 use axum_extra::headers::Authorization;
 use axum_extra::headers::authorization::Bearer;
+use chrono::{DateTime, Utc};
 use derive_more::Deref;
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, TokenData, Validation, decode, encode};
 use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
-use serde_json::json;
 use tracing::warn;
 use uuid::Uuid;
 
 use crate::configuration::Settings;
+use crate::domain::observer::Observer;
 use crate::domain::player::Player;
+use crate::{Error, ErrorKind};
 
 /// Static secret keys holder used for encoding and decoding JWTs.
 /// Using `OnceLock` ensures one-time initialization.
@@ -68,6 +70,13 @@ pub struct Claims {
 	pub iat: usize, // Issued at time (timestamp)
 }
 
+impl Claims {
+	/// Whether the token was issued before `at`, to the second `iat` is precise to.
+	pub fn issued_before(&self, at: DateTime<Utc>) -> bool {
+		(self.iat as i64) < at.timestamp()
+	}
+}
+
 impl Display for Claims {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
 		// Custom Display implementation for convenient logging/debugging.
@@ -133,6 +142,7 @@ pub enum AuthError {
 	InvalidToken,
 	MissingSession,
 	MismatchedModality,
+	PasswordResetRequired,
 }
 
 impl AuthError {
@@ -152,22 +162,35 @@ impl AuthError {
 	}
 }
 
-impl IntoResponse for AuthError {
-	/// Converts an AuthError into an HTTP response with an appropriate status code and JSON body.
-	fn into_response(self) -> Response {
-		let (status, error_message) = match self {
-			AuthError::WrongCredentials => (StatusCode::UNAUTHORIZED, "Wrong credentials"),
-			AuthError::MissingCredentials => (StatusCode::BAD_REQUEST, "Missing credentials"),
-			AuthError::TokenCreation => (StatusCode::INTERNAL_SERVER_ERROR, "Token creation error"),
-			AuthError::ArgonError => (StatusCode::INTERNAL_SERVER_ERROR, "Cryptographic error"),
-			AuthError::InvalidToken => (StatusCode::BAD_REQUEST, "Invalid token"),
-			AuthError::MissingSession => (StatusCode::BAD_REQUEST, "Missing session"),
-			AuthError::MismatchedModality => {
-				(StatusCode::BAD_REQUEST, "Authentication modality mismatch")
+impl From<AuthError> for Error {
+	fn from(err: AuthError) -> Self {
+		let (kind, message) = match err {
+			AuthError::WrongCredentials => (ErrorKind::WrongCredentialsError, "Wrong credentials"),
+			AuthError::MissingCredentials => {
+				(ErrorKind::MissingCredentialsError, "Missing credentials")
 			}
+			AuthError::TokenCreation => (ErrorKind::TokenCreationError, "Token creation error"),
+			AuthError::ArgonError => (ErrorKind::CryptographicError, "Cryptographic error"),
+			AuthError::InvalidToken => (ErrorKind::InvalidToken, "Invalid token"),
+			AuthError::MissingSession => (ErrorKind::MissingSessionError, "Missing session"),
+			AuthError::MismatchedModality => (
+				ErrorKind::ModalityMismatchError,
+				"Authentication modality mismatch",
+			),
+			AuthError::PasswordResetRequired => (
+				ErrorKind::PasswordResetRequiredError,
+				"Password reset required",
+			),
 		};
-		let body = json!({ "error": error_message });
-		(status, Json(body)).into_response()
+		Error::new(kind, message)
+	}
+}
+
+impl IntoResponse for AuthError {
+	/// Converts an AuthError into an HTTP response with an appropriate status code and the
+	/// error body of [`Error`].
+	fn into_response(self) -> Response {
+		Error::from(self).into_response()
 	}
 }
 
@@ -189,3 +212,8 @@ pub fn decode_token(token: &str) -> Result<TokenData<Claims>, AuthError> {
 /// Represents an authenticated user, regardless of the authentication method.
 #[derive(Debug, Clone, Deref)]
 pub struct AuthenticatedUser(pub Player);
+
+/// Represents an authenticated tournament observer, see
+/// [`crate::game::observer_operations`].
+#[derive(Debug, Clone, Deref)]
+pub struct AuthenticatedObserver(pub Observer);
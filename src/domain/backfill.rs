@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::jobs::JobKey;
+use crate::schema::backfill;
+
+/// Progress of an online data backfill, see [`crate::db::backfills`].
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = backfill, primary_key(name))]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct BackfillProgress {
+	/// Unique name the backfill is registered under.
+	pub name: String,
+	/// Last key processed by a committed chunk, `None` before the first chunk.
+	pub cursor: Option<Uuid>,
+	/// Rows processed so far.
+	pub processed: i64,
+	/// The job currently scheduled to run the next chunks, if any.
+	pub job_id: Option<JobKey>,
+	/// Time the first chunk was committed.
+	pub started_at: Option<DateTime<Utc>>,
+	/// Time the last chunk was committed, `None` while rows remain.
+	pub completed_at: Option<DateTime<Utc>>,
+	/// Creation timestamp.
+	pub created_at: DateTime<Utc>,
+	/// Last update timestamp.
+	pub updated_at: DateTime<Utc>,
+}
+
+impl BackfillProgress {
+	/// Whether every row has been backfilled.
+	pub fn is_completed(&self) -> bool {
+		self.completed_at.is_some()
+	}
+}
+
+/// Payload of a [`crate::domain::jobs::JobType::Backfill`] job.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackfillJobPayload {
+	/// Name of the backfill to continue.
+	pub name: String,
+}
@@ -0,0 +1,114 @@
+//! Contains domain entities for the construction queue.
+//! Tracks building upgrades a player queued to run one after another.
+
+use std::io::Write;
+use std::str::from_utf8;
+
+use chrono::{DateTime, Utc};
+use diesel::deserialize::FromSql;
+use diesel::pg::{Pg, PgValue};
+use diesel::prelude::*;
+use diesel::serialize::{IsNull, Output, ToSql};
+use diesel::{AsExpression, FromSqlRow, deserialize, serialize};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::upgrade::BuildingUpgradeKey;
+use crate::domain::jobs::JobKey;
+use crate::domain::player::buildings::PlayerBuildingKey;
+use crate::domain::player::{Player, PlayerKey};
+use crate::schema::construction_queue;
+
+/// Unique identifier for a construction queue entry
+pub type ConstructionQueueKey = Uuid;
+
+/// Represents the status of a construction queue entry
+#[derive(
+	AsExpression,
+	FromSqlRow,
+	Serialize,
+	Deserialize,
+	ToSchema,
+	Debug,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	Hash,
+	PartialOrd,
+	Ord,
+)]
+#[diesel(sql_type = crate::schema::sql_types::ConstructionStatus)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "graphql", derive(async_graphql::Enum))]
+pub enum ConstructionStatus {
+	Pending,
+	InProgress,
+	Completed,
+}
+
+impl AsRef<str> for ConstructionStatus {
+	fn as_ref(&self) -> &str {
+		match self {
+			ConstructionStatus::Pending => "pending",
+			ConstructionStatus::InProgress => "in_progress",
+			ConstructionStatus::Completed => "completed",
+		}
+	}
+}
+
+impl ToSql<crate::schema::sql_types::ConstructionStatus, Pg> for ConstructionStatus {
+	fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+		out.write_all(self.as_ref().as_bytes())?;
+		Ok(IsNull::No)
+	}
+}
+
+impl FromSql<crate::schema::sql_types::ConstructionStatus, Pg> for ConstructionStatus {
+	fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+		match from_utf8(bytes.as_bytes())? {
+			"pending" => Ok(ConstructionStatus::Pending),
+			"in_progress" => Ok(ConstructionStatus::InProgress),
+			"completed" => Ok(ConstructionStatus::Completed),
+			other => Err(format!("Unrecognized enum variant: {other}").into()),
+		}
+	}
+}
+
+/// Represents an upgrade in the construction queue
+#[derive(
+	Queryable, Selectable, Identifiable, Associations, Serialize, Debug, Clone, PartialEq, Eq,
+)]
+#[diesel(belongs_to(Player))]
+#[diesel(table_name = construction_queue, check_for_backend(diesel::pg::Pg))]
+pub struct ConstructionQueueEntry {
+	pub id: ConstructionQueueKey,
+	pub player_id: PlayerKey,
+	pub player_building_id: PlayerBuildingKey,
+	/// The ledger entry recording what the upgrade cost
+	pub upgrade_id: BuildingUpgradeKey,
+	/// The level the building reaches once the entry completes
+	pub level: i32,
+	pub status: ConstructionStatus,
+	/// When the work starts, right after the entry ahead of it in the queue
+	pub starts_at: DateTime<Utc>,
+	pub completes_at: DateTime<Utc>,
+	pub completed_at: Option<DateTime<Utc>>,
+	pub job_id: Option<JobKey>,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+/// Data transfer object for creating a new construction queue entry
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = construction_queue, check_for_backend(diesel::pg::Pg))]
+pub struct NewConstructionQueueEntry {
+	pub player_id: PlayerKey,
+	pub player_building_id: PlayerBuildingKey,
+	pub upgrade_id: BuildingUpgradeKey,
+	pub level: i32,
+	pub status: ConstructionStatus,
+	pub starts_at: DateTime<Utc>,
+	pub completes_at: DateTime<Utc>,
+}
@@ -1,14 +1,17 @@
 //! Contains domain entities and types related to buildings in the game.
 //! Buildings are structures that can be constructed by factions and have various levels and counts.
 
+pub mod construction;
 pub mod level;
 pub mod requirement;
 pub mod resources;
 pub mod unit_type;
+pub mod upgrade;
 
 use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use serde::Serialize;
+use utoipa::ToSchema;
 
 use crate::domain::factions::FactionKey;
 use crate::schema::building;
@@ -17,7 +20,9 @@ use crate::schema::building;
 pub type BuildingKey = i32;
 
 /// Represents a building type that can be constructed in the game
-#[derive(Queryable, Selectable, Identifiable, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(
+	Queryable, Selectable, Identifiable, Serialize, ToSchema, Debug, Clone, PartialEq, Eq, Hash,
+)]
 #[diesel(table_name = building, check_for_backend(diesel::pg::Pg))]
 pub struct Building {
 	pub id: BuildingKey,
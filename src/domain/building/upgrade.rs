@@ -0,0 +1,53 @@
+//! Contains domain entities for the building upgrade ledger.
+//! Every upgrade a player starts is recorded with what it cost, and completed once the
+//! upgrade is confirmed or cancelled, so past upgrades can be counted after the building
+//! moved on.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::BuildingKey;
+use crate::domain::player::PlayerKey;
+use crate::domain::player::buildings::PlayerBuildingKey;
+use crate::schema::building_upgrade;
+
+/// Unique identifier for a building upgrade ledger entry
+pub type BuildingUpgradeKey = Uuid;
+
+/// A building upgrade that was started, with the resources it cost
+#[derive(Queryable, Selectable, Identifiable, Serialize, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = building_upgrade, check_for_backend(diesel::pg::Pg))]
+pub struct BuildingUpgrade {
+	pub id: BuildingUpgradeKey,
+	pub player_id: PlayerKey,
+	pub player_building_id: PlayerBuildingKey,
+	pub building_id: BuildingKey,
+	/// The level the building reaches with this upgrade
+	pub level: i32,
+	pub food: i64,
+	pub wood: i64,
+	pub stone: i64,
+	pub gold: i64,
+	/// When the upgrade was confirmed, `None` while it is underway
+	pub completed_at: Option<DateTime<Utc>>,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+	/// When the upgrade was cancelled and refunded, `None` unless it was
+	pub cancelled_at: Option<DateTime<Utc>>,
+}
+
+/// Data transfer object for recording a started upgrade
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = building_upgrade, check_for_backend(diesel::pg::Pg))]
+pub struct NewBuildingUpgrade {
+	pub player_id: PlayerKey,
+	pub player_building_id: PlayerBuildingKey,
+	pub building_id: BuildingKey,
+	pub level: i32,
+	pub food: i64,
+	pub wood: i64,
+	pub stone: i64,
+	pub gold: i64,
+}
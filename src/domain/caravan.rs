@@ -0,0 +1,137 @@
+//! Contains domain entities for resource caravans.
+//! A caravan carries resources from one player to another and delivers them when it
+//! arrives, after a travel time that depends on the size of its cargo.
+
+use std::io::Write;
+use std::str::from_utf8;
+
+use chrono::{DateTime, Utc};
+use diesel::deserialize::FromSql;
+use diesel::pg::{Pg, PgValue};
+use diesel::prelude::*;
+use diesel::serialize::{IsNull, Output, ToSql};
+use diesel::{AsExpression, FromSqlRow, deserialize, serialize};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::domain::jobs::JobKey;
+use crate::domain::player::PlayerKey;
+use crate::schema::caravan;
+
+/// Unique identifier for a caravan
+pub type CaravanKey = Uuid;
+
+/// Lifecycle state of a caravan
+#[derive(
+	AsExpression,
+	FromSqlRow,
+	Serialize,
+	Deserialize,
+	ToSchema,
+	Debug,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	Hash,
+	PartialOrd,
+	Ord,
+)]
+#[diesel(sql_type = crate::schema::sql_types::CaravanStatus)]
+#[serde(rename_all = "snake_case")]
+pub enum CaravanStatus {
+	/// On its way to the receiver, the cargo belongs to neither player
+	Travelling,
+	/// Arrived, the cargo was credited to the receiver and any overflow to the sender
+	Delivered,
+}
+
+impl AsRef<str> for CaravanStatus {
+	fn as_ref(&self) -> &str {
+		match self {
+			CaravanStatus::Travelling => "travelling",
+			CaravanStatus::Delivered => "delivered",
+		}
+	}
+}
+
+impl ToSql<crate::schema::sql_types::CaravanStatus, Pg> for CaravanStatus {
+	fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+		out.write_all(self.as_ref().as_bytes())?;
+		Ok(IsNull::No)
+	}
+}
+
+impl FromSql<crate::schema::sql_types::CaravanStatus, Pg> for CaravanStatus {
+	fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+		match from_utf8(bytes.as_bytes())? {
+			"travelling" => Ok(CaravanStatus::Travelling),
+			"delivered" => Ok(CaravanStatus::Delivered),
+			other => Err(format!("Unrecognized enum variant: {other}").into()),
+		}
+	}
+}
+
+/// Resources carried by a caravan
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cargo {
+	pub food: i64,
+	pub wood: i64,
+	pub stone: i64,
+	pub gold: i64,
+}
+
+impl Cargo {
+	/// Total number of units carried, or `None` if it does not fit into an `i64`.
+	pub fn total(&self) -> Option<i64> {
+		[self.wood, self.stone, self.gold]
+			.into_iter()
+			.try_fold(self.food, i64::checked_add)
+	}
+}
+
+/// Represents resources shipped from one player to another
+#[derive(Queryable, Selectable, Identifiable, Serialize, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = caravan, check_for_backend(diesel::pg::Pg))]
+pub struct Caravan {
+	pub id: CaravanKey,
+	pub sender_id: PlayerKey,
+	pub receiver_id: PlayerKey,
+	pub food: i64,
+	pub wood: i64,
+	pub stone: i64,
+	pub gold: i64,
+	pub status: CaravanStatus,
+	/// The resource job delivering the caravan on arrival
+	pub job_id: Option<JobKey>,
+	pub arrives_at: DateTime<Utc>,
+	pub delivered_at: Option<DateTime<Utc>>,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+impl Caravan {
+	/// Returns the resources the caravan carries.
+	pub fn cargo(&self) -> Cargo {
+		Cargo {
+			food: self.food,
+			wood: self.wood,
+			stone: self.stone,
+			gold: self.gold,
+		}
+	}
+}
+
+/// Data transfer object for dispatching a new caravan
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = caravan, check_for_backend(diesel::pg::Pg))]
+pub struct NewCaravan {
+	pub sender_id: PlayerKey,
+	pub receiver_id: PlayerKey,
+	pub food: i64,
+	pub wood: i64,
+	pub stone: i64,
+	pub gold: i64,
+	pub arrives_at: DateTime<Utc>,
+}
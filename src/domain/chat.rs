@@ -0,0 +1,195 @@
+//! Contains domain entities for the chat.
+//! Players talk in channels: the global channel reaches every player, the alliance channel
+//! only the members of the poster's alliance. Messages are kept, so players catch up on what
+//! they missed, and moderators mute players who misbehave.
+//!
+//! Posted messages are broadcast on the [`ChatBus`], from which the WebSocket endpoint
+//! (`net::ws`) forwards them to the connected players in the channel.
+
+use std::io::Write;
+use std::str::from_utf8;
+
+use chrono::{DateTime, Utc};
+use derive_more::Display;
+use diesel::deserialize::FromSql;
+use diesel::pg::{Pg, PgValue};
+use diesel::prelude::*;
+use diesel::serialize::{IsNull, Output, ToSql};
+use diesel::{AsExpression, FromSqlRow, deserialize, serialize};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tracing::trace;
+use uuid::Uuid;
+
+use crate::domain::alliance::AllianceKey;
+use crate::domain::player::PlayerKey;
+use crate::schema::{chat_message, chat_mute};
+
+/// Unique identifier for a chat message
+pub type ChatMessageKey = Uuid;
+
+/// Number of chat messages buffered per subscriber before slow receivers start lagging.
+pub const CHAT_BUS_CAPACITY: usize = 1024;
+
+/// Channel a chat message is posted in.
+#[derive(
+	AsExpression,
+	FromSqlRow,
+	Serialize,
+	Deserialize,
+	Display,
+	Debug,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	Hash,
+)]
+#[diesel(sql_type = crate::schema::sql_types::ChatChannel)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatChannel {
+	/// Every player
+	Global,
+	/// The members of the poster's alliance
+	Alliance,
+}
+
+impl AsRef<str> for ChatChannel {
+	fn as_ref(&self) -> &str {
+		match self {
+			ChatChannel::Global => "global",
+			ChatChannel::Alliance => "alliance",
+		}
+	}
+}
+
+impl ToSql<crate::schema::sql_types::ChatChannel, Pg> for ChatChannel {
+	fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+		out.write_all(self.as_ref().as_bytes())?;
+		Ok(IsNull::No)
+	}
+}
+
+impl FromSql<crate::schema::sql_types::ChatChannel, Pg> for ChatChannel {
+	fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+		match from_utf8(bytes.as_bytes())? {
+			"global" => Ok(ChatChannel::Global),
+			"alliance" => Ok(ChatChannel::Alliance),
+			other => Err(format!("Unrecognized enum variant: {other}").into()),
+		}
+	}
+}
+
+/// Represents a message posted in a chat channel
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = chat_message, check_for_backend(diesel::pg::Pg))]
+pub struct ChatMessage {
+	pub id: ChatMessageKey,
+	pub channel: ChatChannel,
+	/// Alliance the message was posted in, set for the alliance channel only
+	pub alliance_id: Option<AllianceKey>,
+	/// Player who posted the message, `None` once they are gone
+	pub player_id: Option<PlayerKey>,
+	pub body: String,
+	pub created_at: DateTime<Utc>,
+}
+
+/// Data transfer object for posting a message
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = chat_message, check_for_backend(diesel::pg::Pg))]
+pub struct NewChatMessage {
+	pub channel: ChatChannel,
+	pub alliance_id: Option<AllianceKey>,
+	pub player_id: Option<PlayerKey>,
+	pub body: String,
+}
+
+/// A chat message with the name of its poster, as players see it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatLine {
+	pub message: ChatMessage,
+	/// Name of the poster, `None` once they are gone
+	pub player_name: Option<String>,
+}
+
+/// Represents a player who cannot post until the mute runs out
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = chat_mute, primary_key(player_id), check_for_backend(diesel::pg::Pg))]
+pub struct ChatMute {
+	pub player_id: PlayerKey,
+	pub muted_until: DateTime<Utc>,
+	/// Reason the moderator gave, shown to the muted player
+	pub reason: Option<String>,
+	/// Moderator who muted the player, `None` once they are gone
+	pub muted_by: Option<PlayerKey>,
+	pub created_at: DateTime<Utc>,
+}
+
+impl ChatMute {
+	/// Whether the mute still applies at `now`.
+	pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+		self.muted_until > now
+	}
+}
+
+/// Data transfer object for muting a player, replacing an earlier mute
+#[derive(Insertable, AsChangeset, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = chat_mute, check_for_backend(diesel::pg::Pg))]
+#[diesel(treat_none_as_null = true)]
+pub struct NewChatMute {
+	pub player_id: PlayerKey,
+	pub muted_until: DateTime<Utc>,
+	pub reason: Option<String>,
+	pub muted_by: Option<PlayerKey>,
+}
+
+/// A posted chat message and the players it is for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatBroadcast {
+	pub line: ChatLine,
+	/// Players in the channel, `None` for the global channel which reaches everyone
+	pub recipients: Option<Vec<PlayerKey>>,
+}
+
+impl ChatBroadcast {
+	/// Whether the message is for the given player.
+	pub fn is_for(&self, player_id: &PlayerKey) -> bool {
+		self.recipients
+			.as_ref()
+			.is_none_or(|recipients| recipients.contains(player_id))
+	}
+}
+
+/// Broadcast channel carrying posted chat messages to client connections.
+#[derive(Debug, Clone)]
+pub struct ChatBus {
+	tx: broadcast::Sender<ChatBroadcast>,
+}
+
+impl Default for ChatBus {
+	fn default() -> Self {
+		Self::new(CHAT_BUS_CAPACITY)
+	}
+}
+
+impl ChatBus {
+	/// Creates a new chat bus buffering up to `capacity` messages per subscriber.
+	pub fn new(capacity: usize) -> Self {
+		let (tx, _) = broadcast::channel(capacity);
+		Self { tx }
+	}
+
+	/// Publishes a message to all current subscribers.
+	///
+	/// Messages published while nobody is connected are dropped, they are still in the
+	/// history.
+	pub fn publish(&self, broadcast: ChatBroadcast) {
+		trace!("Publishing chat message: {:?}", broadcast.line.message.id);
+		let _ = self.tx.send(broadcast);
+	}
+
+	/// Subscribes to all messages published from now on.
+	pub fn subscribe(&self) -> broadcast::Receiver<ChatBroadcast> {
+		self.tx.subscribe()
+	}
+}
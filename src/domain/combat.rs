@@ -0,0 +1,206 @@
+//! Contains domain entities for combat outcomes.
+//! Battle reports are the persisted record of a resolved battle, visible to both
+//! the attacker and the defender. Intel reports are what the scouts of a scout mission saw,
+//! visible to the attacker only.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::jobs::JobKey;
+use crate::domain::modifier::{MagnitudeKind, ModifierTarget};
+use crate::domain::player::PlayerKey;
+use crate::domain::unit::UnitKey;
+use crate::schema::{battle_report, intel_report, scout_mission};
+
+/// Unique identifier for a battle report
+pub type BattleReportKey = Uuid;
+
+/// Unique identifier for an intel report
+pub type IntelReportKey = Uuid;
+
+/// Unique identifier for a scout mission
+pub type ScoutMissionKey = Uuid;
+
+/// Units of a single type lost by one side of a battle
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UnitLoss {
+	pub unit_id: UnitKey,
+	/// Units sent into the battle
+	pub deployed: i64,
+	/// Units that did not survive
+	pub lost: i64,
+}
+
+/// A modifier that was in effect when the battle was resolved
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AppliedModifier {
+	pub player_id: PlayerKey,
+	pub name: String,
+	pub target: ModifierTarget,
+	pub magnitude: f64,
+	pub magnitude_kind: MagnitudeKind,
+}
+
+/// Resources carried off by the winning side
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Loot {
+	pub food: i64,
+	pub wood: i64,
+	pub stone: i64,
+	pub gold: i64,
+}
+
+/// Represents the persisted outcome of a battle
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, PartialEq)]
+#[diesel(table_name = battle_report, check_for_backend(diesel::pg::Pg))]
+pub struct BattleReport {
+	pub id: BattleReportKey,
+	pub attacker_id: PlayerKey,
+	pub defender_id: PlayerKey,
+	/// Winning player, or `None` for a draw
+	pub winner_id: Option<PlayerKey>,
+	/// JSON array of [`UnitLoss`] for the attacking side
+	pub attacker_losses: serde_json::Value,
+	/// JSON array of [`UnitLoss`] for the defending side
+	pub defender_losses: serde_json::Value,
+	pub loot_food: i64,
+	pub loot_wood: i64,
+	pub loot_stone: i64,
+	pub loot_gold: i64,
+	/// JSON array of [`AppliedModifier`] snapshots
+	pub modifiers: serde_json::Value,
+	pub fought_at: DateTime<Utc>,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+impl BattleReport {
+	/// Whether the given player took part in this battle.
+	pub fn involves(&self, player_id: &PlayerKey) -> bool {
+		self.attacker_id == *player_id || self.defender_id == *player_id
+	}
+
+	/// Resources looted in this battle.
+	pub fn loot(&self) -> Loot {
+		Loot {
+			food: self.loot_food,
+			wood: self.loot_wood,
+			stone: self.loot_stone,
+			gold: self.loot_gold,
+		}
+	}
+}
+
+/// Data transfer object for creating a new battle report
+#[derive(Insertable, Debug, Clone, PartialEq)]
+#[diesel(table_name = battle_report, check_for_backend(diesel::pg::Pg))]
+pub struct NewBattleReport {
+	pub attacker_id: PlayerKey,
+	pub defender_id: PlayerKey,
+	pub winner_id: Option<PlayerKey>,
+	pub attacker_losses: serde_json::Value,
+	pub defender_losses: serde_json::Value,
+	pub loot_food: i64,
+	pub loot_wood: i64,
+	pub loot_stone: i64,
+	pub loot_gold: i64,
+	pub modifiers: serde_json::Value,
+	pub fought_at: DateTime<Utc>,
+}
+
+/// Estimated number of units of a single type in a scouted army
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UnitEstimate {
+	pub unit_id: UnitKey,
+	/// Number of units the scouts counted, off by up to the noise of the report
+	pub estimate: i64,
+}
+
+/// Represents what the scouts of a mission saw in the capital of the defender
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, PartialEq)]
+#[diesel(table_name = intel_report, check_for_backend(diesel::pg::Pg))]
+pub struct IntelReport {
+	pub id: IntelReportKey,
+	pub attacker_id: PlayerKey,
+	pub defender_id: PlayerKey,
+	pub food: i64,
+	pub wood: i64,
+	pub stone: i64,
+	pub gold: i64,
+	/// JSON array of [`UnitEstimate`] for the army of the defender
+	pub army: serde_json::Value,
+	/// How far off each estimate may be, in percent
+	pub noise_percent: i32,
+	/// Whether the Watchtower of the defender spotted the scouts
+	pub spotted: bool,
+	pub scouted_at: DateTime<Utc>,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+impl IntelReport {
+	/// Resources the scouts saw in the storage of the defender.
+	pub fn resources(&self) -> Loot {
+		Loot {
+			food: self.food,
+			wood: self.wood,
+			stone: self.stone,
+			gold: self.gold,
+		}
+	}
+}
+
+/// Data transfer object for creating a new intel report
+#[derive(Insertable, Debug, Clone, PartialEq)]
+#[diesel(table_name = intel_report, check_for_backend(diesel::pg::Pg))]
+pub struct NewIntelReport {
+	pub attacker_id: PlayerKey,
+	pub defender_id: PlayerKey,
+	pub food: i64,
+	pub wood: i64,
+	pub stone: i64,
+	pub gold: i64,
+	pub army: serde_json::Value,
+	pub noise_percent: i32,
+	pub spotted: bool,
+	pub scouted_at: DateTime<Utc>,
+}
+
+/// Represents scouts sent by one player to another
+#[derive(Queryable, Selectable, Identifiable, Serialize, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = scout_mission, check_for_backend(diesel::pg::Pg))]
+pub struct ScoutMission {
+	pub id: ScoutMissionKey,
+	pub attacker_id: PlayerKey,
+	pub defender_id: PlayerKey,
+	/// Number of scouts on the mission
+	pub scouts: i64,
+	/// The espionage job completing the mission on arrival
+	pub job_id: Option<JobKey>,
+	/// The intel report of the mission, once it completed
+	pub report_id: Option<IntelReportKey>,
+	pub arrives_at: DateTime<Utc>,
+	/// When the mission completed, `None` while the scouts are travelling
+	pub completed_at: Option<DateTime<Utc>>,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+impl ScoutMission {
+	/// Whether the mission completed and the scouts are back.
+	pub fn is_completed(&self) -> bool {
+		self.completed_at.is_some()
+	}
+}
+
+/// Data transfer object for sending a new scout mission
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = scout_mission, check_for_backend(diesel::pg::Pg))]
+pub struct NewScoutMission {
+	pub attacker_id: PlayerKey,
+	pub defender_id: PlayerKey,
+	pub scouts: i64,
+	pub arrives_at: DateTime<Utc>,
+}
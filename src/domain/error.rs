@@ -3,7 +3,8 @@ use std::{error, fmt, io};
 use axum::Json;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
-use serde_json::json;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 pub type Result<T, E = Error> = anyhow::Result<T, E>;
 
@@ -16,6 +17,8 @@ pub type Result<T, E = Error> = anyhow::Result<T, E>;
 /// The `Error` struct carries additional context, such as descriptions and details for specific errors.
 pub struct Error {
 	repr: ErrorRepr,
+	/// Structured context for clients, see [`Error::with_details`]
+	details: Option<serde_json::Value>,
 }
 
 #[derive(Debug)]
@@ -30,9 +33,17 @@ enum ErrorRepr {
 	SerdeError(serde_json::Error),
 }
 
-#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+/// Category of an [`Error`], which decides its HTTP status.
+///
+/// Serialized as the snake_case name of the variant, the `code` that clients branch on, so
+/// renaming a variant is a breaking change of the API.
+#[derive(Serialize, Deserialize, ToSchema, PartialEq, Eq, Copy, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
 pub enum ErrorKind {
 	InternalError,
+	NotImplementedError,
+	/// No route matches the method and path of the request
+	RouteNotFoundError,
 
 	// Packet Errors
 	InvalidPacket,
@@ -87,25 +98,158 @@ pub enum ErrorKind {
 	ConstructBuildingError,
 	UpgradeBuildingError,
 	ConfirmUpgradeError,
+	CancelUpgradeError,
+	DowngradeBuildingError,
+	ConstructionThrottledError,
+	QueueConstructionError,
+	CompleteConstructionError,
+	ConstructionQueueFullError,
+
+	// Ownership Errors
+	/// A player-owned entity is missing or belongs to another player
+	NotFoundError,
 
 	// Training Errors
 	StartTrainingError,
 	CancelTrainingError,
 	CompleteTrainingError,
+	UpgradeUnitsError,
 	TrainingQueueFullError,
 	InsufficientResourcesError,
 	InvalidBuildingTypeError,
 	InvalidQuantityError,
+	TrainingThrottledError,
+	PopulationCapReachedError,
+	PresetNameTakenError,
+	PresetLimitReachedError,
+	ChangeTrainingModeError,
+
+	// Faction Errors
+	FactionChangeError,
+	FactionChangeCooldownError,
+	FactionRequiredError,
+
+	// Planned Action Errors
+	CreatePlanError,
+	CancelPlanError,
+	PlanLimitReachedError,
+
+	// Combat Errors
+	AttackerProtectedError,
+	DefenderProtectedError,
+	AllianceMateError,
+	InsufficientUnitsError,
+
+	// Caravan Errors
+	SendCaravanError,
+
+	// Market Errors
+	InvalidMarketResourceError,
+	MarketOrderClosedError,
+
+	// Alliance Errors
+	AllianceMembershipError,
+	AlliancePermissionError,
+	AllianceNameTakenError,
+	AllianceFullError,
+
+	// Chat Errors
+	ChatMutedError,
+	ChatThrottledError,
+
+	// Relationship Errors
+	PlayerBlockedError,
+	RelationshipConflictError,
+
+	// Settlement Errors
+	FoundSettlementError,
+	SettlementNameTakenError,
+
+	// Item Errors
+	ItemUnavailableError,
+
+	// Hero Errors
+	HeroConflictError,
+	HeroMaxLevelError,
+
+	// Quest Errors
+	QuestIncompleteError,
+
+	// Limited Event Errors
+	EventNotRunningError,
+	InsufficientEventCurrencyError,
+
+	// Observer Errors
+	ObserverScopeError,
+
+	// Stats Errors
+	InvalidStatsWindowError,
+
+	// Job Queue Errors
+	InvalidScheduleError,
+	InvalidJobPayloadError,
+	DeadLetterNotFoundError,
+	JobNotFoundError,
+	JobTimeoutError,
+	PayloadEncryptionError,
+
+	// World Reset Errors
+	InvalidResetTokenError,
+	WorldResetConflictError,
+
+	// Season Errors
+	SeasonConflictError,
 
 	// Auth errors
 	NoSessionError,
+	/// Credentials were sent, but they are invalid, expired or of a deleted player
+	UnauthenticatedError,
+	InsufficientRoleError,
 	SessionExpiredError,
+	ApiKeyScopeError,
+	ApiKeyLimitReachedError,
+	ReauthenticationError,
+	OAuthProviderError,
+	WrongCredentialsError,
+	MissingCredentialsError,
+	TokenCreationError,
+	CryptographicError,
+	MissingSessionError,
+	ModalityMismatchError,
+	PasswordResetRequiredError,
+	UsernameTakenError,
+
+	// Request Limit Errors
+	RateLimitedError,
+	LoginLockedOutError,
+	RequestTooLargeError,
+	ServerBusyError,
 }
 
 impl Error {
 	pub fn new(kind: ErrorKind, desc: &'static str) -> Self {
 		Self {
 			repr: ErrorRepr::WithDescription(kind, desc),
+			details: None,
+		}
+	}
+
+	/// Attaches structured context for clients, sent as the `details` of the response body.
+	///
+	/// Unlike the detail of `(ErrorKind, &str, String)` errors, which only ends up in the logs,
+	/// details are public, so they must never carry internals.
+	pub fn with_details(mut self, details: impl Serialize) -> Self {
+		self.details = serde_json::to_value(details).ok();
+		self
+	}
+
+	/// Returns the category of the error, errors of other libraries are internal errors.
+	pub fn kind(&self) -> ErrorKind {
+		match self.repr {
+			ErrorRepr::WithDescription(kind, _) | ErrorRepr::WithDescriptionAndDetail(kind, ..) => {
+				kind
+			}
+			_ => ErrorKind::InternalError,
 		}
 	}
 }
@@ -114,6 +258,7 @@ impl Default for Error {
 	fn default() -> Self {
 		Self {
 			repr: ErrorRepr::WithDescription(ErrorKind::InternalError, "Internal error"),
+			details: None,
 		}
 	}
 }
@@ -122,6 +267,8 @@ impl From<ErrorKind> for StatusCode {
 	fn from(value: ErrorKind) -> Self {
 		match value {
 			ErrorKind::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+			ErrorKind::NotImplementedError => StatusCode::NOT_IMPLEMENTED,
+			ErrorKind::RouteNotFoundError => StatusCode::NOT_FOUND,
 
 			// Packet Errors
 			ErrorKind::InvalidPacket
@@ -175,20 +322,136 @@ impl From<ErrorKind> for StatusCode {
 			// Service Errors
 			ErrorKind::ConstructBuildingError
 			| ErrorKind::UpgradeBuildingError
-			| ErrorKind::ConfirmUpgradeError => StatusCode::CONFLICT,
+			| ErrorKind::ConfirmUpgradeError
+			| ErrorKind::CancelUpgradeError
+			| ErrorKind::DowngradeBuildingError
+			| ErrorKind::QueueConstructionError
+			| ErrorKind::CompleteConstructionError => StatusCode::CONFLICT,
+			ErrorKind::ConstructionThrottledError => StatusCode::TOO_MANY_REQUESTS,
+			ErrorKind::ConstructionQueueFullError => StatusCode::CONFLICT,
+
+			// Ownership errors
+			ErrorKind::NotFoundError => StatusCode::NOT_FOUND,
 
 			// Training Errors
 			ErrorKind::StartTrainingError
 			| ErrorKind::CancelTrainingError
-			| ErrorKind::CompleteTrainingError => StatusCode::CONFLICT,
+			| ErrorKind::CompleteTrainingError
+			| ErrorKind::UpgradeUnitsError => StatusCode::CONFLICT,
 			ErrorKind::TrainingQueueFullError => StatusCode::CONFLICT,
 			ErrorKind::InsufficientResourcesError => StatusCode::UNPROCESSABLE_ENTITY,
 			ErrorKind::InvalidBuildingTypeError => StatusCode::BAD_REQUEST,
 			ErrorKind::InvalidQuantityError => StatusCode::BAD_REQUEST,
+			ErrorKind::TrainingThrottledError => StatusCode::TOO_MANY_REQUESTS,
+			ErrorKind::PopulationCapReachedError => StatusCode::UNPROCESSABLE_ENTITY,
+			ErrorKind::PresetNameTakenError | ErrorKind::PresetLimitReachedError => {
+				StatusCode::CONFLICT
+			}
+			ErrorKind::ChangeTrainingModeError => StatusCode::CONFLICT,
+
+			// Faction errors
+			ErrorKind::FactionChangeError => StatusCode::CONFLICT,
+			ErrorKind::FactionChangeCooldownError => StatusCode::TOO_MANY_REQUESTS,
+			ErrorKind::FactionRequiredError => StatusCode::FORBIDDEN,
+
+			// Planned action errors
+			ErrorKind::CreatePlanError | ErrorKind::CancelPlanError => StatusCode::CONFLICT,
+			ErrorKind::PlanLimitReachedError => StatusCode::CONFLICT,
+
+			// Combat errors
+			ErrorKind::AttackerProtectedError
+			| ErrorKind::DefenderProtectedError
+			| ErrorKind::AllianceMateError => StatusCode::FORBIDDEN,
+			ErrorKind::InsufficientUnitsError => StatusCode::UNPROCESSABLE_ENTITY,
+
+			// Caravan errors
+			ErrorKind::SendCaravanError => StatusCode::BAD_REQUEST,
+
+			// Market errors
+			ErrorKind::InvalidMarketResourceError => StatusCode::BAD_REQUEST,
+			ErrorKind::MarketOrderClosedError => StatusCode::CONFLICT,
+
+			// Alliance errors
+			ErrorKind::AllianceMembershipError => StatusCode::CONFLICT,
+			ErrorKind::AlliancePermissionError => StatusCode::FORBIDDEN,
+			ErrorKind::AllianceNameTakenError | ErrorKind::AllianceFullError => {
+				StatusCode::CONFLICT
+			}
+
+			// Chat errors
+			ErrorKind::ChatMutedError => StatusCode::FORBIDDEN,
+			ErrorKind::ChatThrottledError => StatusCode::TOO_MANY_REQUESTS,
+
+			// Relationship errors
+			ErrorKind::PlayerBlockedError => StatusCode::FORBIDDEN,
+			ErrorKind::RelationshipConflictError => StatusCode::CONFLICT,
+
+			// Settlement errors
+			ErrorKind::FoundSettlementError | ErrorKind::SettlementNameTakenError => {
+				StatusCode::CONFLICT
+			}
+
+			// Item errors
+			ErrorKind::ItemUnavailableError => StatusCode::UNPROCESSABLE_ENTITY,
+
+			// Hero errors
+			ErrorKind::HeroConflictError | ErrorKind::HeroMaxLevelError => StatusCode::CONFLICT,
+
+			// Quest errors
+			ErrorKind::QuestIncompleteError => StatusCode::CONFLICT,
+
+			// Limited event errors
+			ErrorKind::EventNotRunningError => StatusCode::CONFLICT,
+			ErrorKind::InsufficientEventCurrencyError => StatusCode::UNPROCESSABLE_ENTITY,
+
+			// Observer errors
+			ErrorKind::ObserverScopeError => StatusCode::FORBIDDEN,
+
+			// Stats errors
+			ErrorKind::InvalidStatsWindowError => StatusCode::BAD_REQUEST,
+
+			// Job queue errors
+			ErrorKind::InvalidScheduleError | ErrorKind::InvalidJobPayloadError => {
+				StatusCode::BAD_REQUEST
+			}
+			ErrorKind::DeadLetterNotFoundError | ErrorKind::JobNotFoundError => {
+				StatusCode::NOT_FOUND
+			}
+			ErrorKind::JobTimeoutError | ErrorKind::PayloadEncryptionError => {
+				StatusCode::INTERNAL_SERVER_ERROR
+			}
+
+			// World reset errors
+			ErrorKind::InvalidResetTokenError => StatusCode::FORBIDDEN,
+			ErrorKind::WorldResetConflictError => StatusCode::CONFLICT,
+
+			// Season errors
+			ErrorKind::SeasonConflictError => StatusCode::CONFLICT,
 
 			// Auth errors
-			ErrorKind::NoSessionError => StatusCode::UNAUTHORIZED,
+			ErrorKind::NoSessionError | ErrorKind::UnauthenticatedError => StatusCode::UNAUTHORIZED,
+			ErrorKind::InsufficientRoleError => StatusCode::FORBIDDEN,
 			ErrorKind::SessionExpiredError => StatusCode::FORBIDDEN,
+			ErrorKind::ApiKeyScopeError => StatusCode::FORBIDDEN,
+			ErrorKind::ApiKeyLimitReachedError => StatusCode::CONFLICT,
+			ErrorKind::ReauthenticationError => StatusCode::FORBIDDEN,
+			ErrorKind::OAuthProviderError => StatusCode::BAD_GATEWAY,
+			ErrorKind::WrongCredentialsError => StatusCode::UNAUTHORIZED,
+			ErrorKind::MissingCredentialsError
+			| ErrorKind::MissingSessionError
+			| ErrorKind::ModalityMismatchError => StatusCode::BAD_REQUEST,
+			ErrorKind::TokenCreationError | ErrorKind::CryptographicError => {
+				StatusCode::INTERNAL_SERVER_ERROR
+			}
+			ErrorKind::PasswordResetRequiredError => StatusCode::FORBIDDEN,
+			ErrorKind::UsernameTakenError => StatusCode::CONFLICT,
+
+			// Request limit errors
+			ErrorKind::RateLimitedError | ErrorKind::LoginLockedOutError => {
+				StatusCode::TOO_MANY_REQUESTS
+			}
+			ErrorKind::RequestTooLargeError => StatusCode::PAYLOAD_TOO_LARGE,
+			ErrorKind::ServerBusyError => StatusCode::SERVICE_UNAVAILABLE,
 		}
 	}
 }
@@ -197,6 +460,7 @@ impl From<io::Error> for Error {
 	fn from(err: io::Error) -> Error {
 		Error {
 			repr: ErrorRepr::IoError(err),
+			details: None,
 		}
 	}
 }
@@ -205,6 +469,7 @@ impl From<diesel::result::Error> for Error {
 	fn from(err: diesel::result::Error) -> Error {
 		Error {
 			repr: ErrorRepr::DbError(err),
+			details: None,
 		}
 	}
 }
@@ -213,6 +478,7 @@ impl From<diesel::r2d2::Error> for Error {
 	fn from(err: diesel::r2d2::Error) -> Error {
 		Error {
 			repr: ErrorRepr::DieselPoolError(err),
+			details: None,
 		}
 	}
 }
@@ -221,6 +487,7 @@ impl From<r2d2::Error> for Error {
 	fn from(err: r2d2::Error) -> Error {
 		Error {
 			repr: ErrorRepr::PoolError(err),
+			details: None,
 		}
 	}
 }
@@ -229,6 +496,7 @@ impl From<anyhow::Error> for Error {
 	fn from(err: anyhow::Error) -> Error {
 		Error {
 			repr: ErrorRepr::AnyhowError(err),
+			details: None,
 		}
 	}
 }
@@ -237,6 +505,7 @@ impl From<serde_json::Error> for Error {
 	fn from(err: serde_json::Error) -> Error {
 		Error {
 			repr: ErrorRepr::SerdeError(err),
+			details: None,
 		}
 	}
 }
@@ -245,6 +514,7 @@ impl From<config::ConfigError> for Error {
 	fn from(err: config::ConfigError) -> Error {
 		Error {
 			repr: ErrorRepr::AnyhowError(err.into()),
+			details: None,
 		}
 	}
 }
@@ -253,6 +523,7 @@ impl From<(ErrorKind, &'static str)> for Error {
 	fn from((kind, desc): (ErrorKind, &'static str)) -> Error {
 		Error {
 			repr: ErrorRepr::WithDescription(kind, desc),
+			details: None,
 		}
 	}
 }
@@ -261,6 +532,7 @@ impl From<(ErrorKind, &'static str, String)> for Error {
 	fn from((kind, desc, detail): (ErrorKind, &'static str, String)) -> Error {
 		Error {
 			repr: ErrorRepr::WithDescriptionAndDetail(kind, desc, detail),
+			details: None,
 		}
 	}
 }
@@ -299,19 +571,52 @@ impl fmt::Debug for Error {
 	}
 }
 
-impl IntoResponse for Error {
-	fn into_response(self) -> Response {
-		let (status, message) = match self.repr {
-			ErrorRepr::WithDescription(kind, desc) => (kind.into(), desc),
-			ErrorRepr::WithDescriptionAndDetail(kind, desc, _) => (kind.into(), desc),
+impl Error {
+	/// Returns the status and the message shown to clients, leaving out the details that
+	/// are only meant for the logs.
+	pub fn public_parts(&self) -> (StatusCode, &'static str) {
+		match &self.repr {
+			ErrorRepr::WithDescription(kind, desc) => ((*kind).into(), desc),
+			ErrorRepr::WithDescriptionAndDetail(kind, desc, _) => ((*kind).into(), desc),
 			ErrorRepr::IoError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Internal I/O error"),
 			ErrorRepr::DbError(_) | ErrorRepr::DieselPoolError(_) | ErrorRepr::PoolError(_) => {
 				(StatusCode::INTERNAL_SERVER_ERROR, "Internal Database error")
 			}
 			ErrorRepr::AnyhowError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Internal error"),
 			ErrorRepr::SerdeError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Internal error"),
+		}
+	}
+}
+
+/// Body of the responses of failed requests.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, PartialEq, Eq)]
+pub struct ErrorBody {
+	/// Stable, machine-readable code of the error
+	pub code: ErrorKind,
+	/// Human-readable description of the error
+	pub message: String,
+	/// Structured context of the error, e.g. when to retry
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schema(value_type = Option<Object>)]
+	pub details: Option<serde_json::Value>,
+	/// ID of the failed request, to find it in the server logs
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub request_id: Option<String>,
+}
+
+impl IntoResponse for Error {
+	/// Responds with an [`ErrorBody`], which is also kept in the response extensions so the
+	/// router can stamp the request ID into it.
+	fn into_response(self) -> Response {
+		let (status, message) = self.public_parts();
+		let body = ErrorBody {
+			code: self.kind(),
+			message: message.to_string(),
+			details: self.details,
+			request_id: None,
 		};
-		let body = json!({"error": message});
-		(status, Json(body)).into_response()
+		let mut response = (status, Json(body.clone())).into_response();
+		response.extensions_mut().insert(body);
+		response
 	}
 }
@@ -0,0 +1,119 @@
+//! Real-time game events pushed to connected clients.
+//!
+//! Background processors publish [`GameEvent`]s onto the [`EventBus`], a broadcast channel
+//! shared through the application state. The WebSocket (`net::ws`) and Server-Sent Events
+//! (`net::sse`) endpoints subscribe to it and forward every event to the connections of the
+//! player it is addressed to.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tracing::trace;
+
+use crate::domain::modifier::ModifierKey;
+use crate::domain::modifier::active_modifier::ActiveModifierKey;
+use crate::domain::player::PlayerKey;
+use crate::domain::player::buildings::PlayerBuildingKey;
+use crate::domain::unit::UnitKey;
+use crate::domain::unit::training::TrainingQueueKey;
+
+/// Number of events buffered per subscriber before slow receivers start lagging.
+pub const EVENT_BUS_CAPACITY: usize = 1024;
+
+/// An event addressed to a single player.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GameEvent {
+	/// A batch of units was queued for training and will complete at `completes_at`
+	TrainingStarted {
+		player_id: PlayerKey,
+		training_id: TrainingQueueKey,
+		unit_id: UnitKey,
+		quantity: i64,
+		completes_at: DateTime<Utc>,
+	},
+	/// A batch of units finished training and was added to the inventory
+	TrainingCompleted {
+		player_id: PlayerKey,
+		training_id: TrainingQueueKey,
+		unit_id: UnitKey,
+		quantity: i64,
+	},
+	/// A building upgrade was confirmed and the building reached its new level
+	UpgradeCompleted {
+		player_id: PlayerKey,
+		player_building_id: PlayerBuildingKey,
+		level: i32,
+	},
+	/// Accumulated resources were moved into the player's storage.
+	/// Carries the stored amounts after the collection.
+	ResourcesCollected {
+		player_id: PlayerKey,
+		food: i64,
+		wood: i64,
+		stone: i64,
+		gold: i64,
+	},
+	/// A temporary modifier ran out and no longer applies
+	ModifierExpired {
+		player_id: PlayerKey,
+		active_modifier_id: ActiveModifierKey,
+		modifier_id: ModifierKey,
+	},
+	/// Another player launched an attack against this player
+	///
+	/// AIDEV-NOTE: Nothing publishes this yet; attacks are not modelled. The combat
+	/// resolver should publish it for the defender once marches exist.
+	AttackIncoming {
+		player_id: PlayerKey,
+		attacker_id: PlayerKey,
+		arrives_at: DateTime<Utc>,
+	},
+}
+
+impl GameEvent {
+	/// The player this event is addressed to.
+	pub fn player_id(&self) -> &PlayerKey {
+		match self {
+			GameEvent::TrainingStarted { player_id, .. }
+			| GameEvent::TrainingCompleted { player_id, .. }
+			| GameEvent::UpgradeCompleted { player_id, .. }
+			| GameEvent::ResourcesCollected { player_id, .. }
+			| GameEvent::ModifierExpired { player_id, .. }
+			| GameEvent::AttackIncoming { player_id, .. } => player_id,
+		}
+	}
+}
+
+/// Broadcast channel carrying [`GameEvent`]s from publishers to client connections.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+	tx: broadcast::Sender<GameEvent>,
+}
+
+impl Default for EventBus {
+	fn default() -> Self {
+		Self::new(EVENT_BUS_CAPACITY)
+	}
+}
+
+impl EventBus {
+	/// Creates a new event bus buffering up to `capacity` events per subscriber.
+	pub fn new(capacity: usize) -> Self {
+		let (tx, _) = broadcast::channel(capacity);
+		Self { tx }
+	}
+
+	/// Publishes an event to all current subscribers.
+	///
+	/// Events published while nobody is connected are dropped, so publishing never fails.
+	pub fn publish(&self, event: GameEvent) {
+		trace!("Publishing event: {:?}", event);
+		let _ = self.tx.send(event);
+	}
+
+	/// Subscribes to all events published from now on.
+	pub fn subscribe(&self) -> broadcast::Receiver<GameEvent> {
+		self.tx.subscribe()
+	}
+}
@@ -9,8 +9,9 @@ use diesel::prelude::*;
 use diesel::serialize::{IsNull, Output, ToSql};
 use diesel::{AsExpression, FromSqlRow, deserialize, serialize};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-use crate::schema::faction;
+use crate::schema::{faction, faction_standing};
 
 /// Type alias for the primary key of factions, using FactionCode as the identifier.
 pub type FactionKey = FactionCode;
@@ -20,6 +21,7 @@ pub type FactionKey = FactionCode;
 	FromSqlRow,
 	Serialize,
 	Deserialize,
+	ToSchema,
 	Display,
 	Debug,
 	Clone,
@@ -32,6 +34,7 @@ pub type FactionKey = FactionCode;
 )]
 #[diesel(sql_type = crate::schema::sql_types::FactionCode)]
 #[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "graphql", derive(async_graphql::Enum))]
 /// Represents the available faction types in the game.
 /// Each variant corresponds to a distinct playable faction, except Neutral.
 #[derive(Default)]
@@ -121,3 +124,27 @@ pub struct UpdateFaction {
 	/// New display name for the faction
 	pub name: Option<String>,
 }
+
+/// Represents the standing of a playable faction in the last aggregation, see
+/// [`crate::game::factions::standing_operations`]
+#[derive(Queryable, Selectable, Serialize, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = faction_standing, check_for_backend(diesel::pg::Pg))]
+pub struct FactionStanding {
+	pub faction: FactionKey,
+	/// Members that did not leave the game
+	pub players: i64,
+	/// Army points of the members, as last scored for the leaderboards
+	pub power: i64,
+	/// Battles members won against players of other factions during the last cycle
+	pub war_score: i64,
+	/// 1-based rank by war score and then power, factions with the same both share a rank
+	pub rank: i32,
+	pub computed_at: DateTime<Utc>,
+}
+
+/// Payload of a [`crate::domain::jobs::JobType::Faction`] job.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FactionJobPayload {
+	/// Aggregates the standings and hands the war victory modifiers to the leading faction
+	Standings,
+}
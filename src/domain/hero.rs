@@ -0,0 +1,143 @@
+//! Contains domain entities for heroes.
+//! Heroes are recruited by players and gain levels through training. An assigned hero applies
+//! the modifier of their level: army heroes to the combat strength of the player, production
+//! heroes to the production of the building they run. See [`crate::game::heroes`].
+
+use std::io::Write;
+use std::str::from_utf8;
+
+use chrono::{DateTime, Utc};
+use derive_more::Display;
+use diesel::deserialize::FromSql;
+use diesel::pg::{Pg, PgValue};
+use diesel::prelude::*;
+use diesel::serialize::{IsNull, Output, ToSql};
+use diesel::{AsExpression, FromSqlRow, deserialize, serialize};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::modifier::{Modifier, ModifierKey};
+use crate::domain::player::buildings::PlayerBuildingKey;
+use crate::domain::player::{Player, PlayerKey};
+use crate::schema::{hero, hero_level, player_hero};
+
+/// Unique identifier for a hero
+pub type HeroKey = Uuid;
+
+/// Unique identifier for a player hero entity
+pub type PlayerHeroKey = Uuid;
+
+/// What a hero is assigned to.
+#[derive(
+	AsExpression,
+	FromSqlRow,
+	Serialize,
+	Deserialize,
+	Display,
+	Debug,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	Hash,
+	PartialOrd,
+	Ord,
+)]
+#[diesel(sql_type = crate::schema::sql_types::HeroSpecialty)]
+#[serde(rename_all = "snake_case")]
+pub enum HeroSpecialty {
+	/// Leads the army, raising the combat strength of the player
+	Army,
+	/// Runs a building, raising its production
+	Production,
+}
+
+impl AsRef<str> for HeroSpecialty {
+	fn as_ref(&self) -> &str {
+		match self {
+			HeroSpecialty::Army => "army",
+			HeroSpecialty::Production => "production",
+		}
+	}
+}
+
+impl ToSql<crate::schema::sql_types::HeroSpecialty, Pg> for HeroSpecialty {
+	fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+		out.write_all(self.as_ref().as_bytes())?;
+		Ok(IsNull::No)
+	}
+}
+
+impl FromSql<crate::schema::sql_types::HeroSpecialty, Pg> for HeroSpecialty {
+	fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+		match from_utf8(bytes.as_bytes())? {
+			"army" => Ok(HeroSpecialty::Army),
+			"production" => Ok(HeroSpecialty::Production),
+			other => Err(format!("Unrecognized enum variant: {other}").into()),
+		}
+	}
+}
+
+/// Represents the definition of a hero players can recruit
+#[derive(Queryable, Selectable, Identifiable, Serialize, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = hero, check_for_backend(diesel::pg::Pg))]
+pub struct Hero {
+	pub id: HeroKey,
+	pub name: String,
+	pub description: String,
+	pub specialty: HeroSpecialty,
+	/// Gold it costs to recruit the hero
+	pub recruit_gold: i64,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+/// Represents a level of a hero, with the modifier the hero applies at that level
+#[derive(Queryable, Selectable, Identifiable, Associations, Debug, Clone, PartialEq, Eq)]
+#[diesel(belongs_to(Hero))]
+#[diesel(belongs_to(Modifier))]
+#[diesel(table_name = hero_level, primary_key(hero_id, level), check_for_backend(diesel::pg::Pg))]
+pub struct HeroLevel {
+	pub hero_id: HeroKey,
+	pub level: i32,
+	/// Experience the hero needs in total to reach the level
+	pub xp_required: i64,
+	pub modifier_id: ModifierKey,
+}
+
+/// Represents a hero recruited by a player
+#[derive(
+	Queryable, Selectable, Identifiable, Associations, Serialize, Debug, Clone, PartialEq, Eq,
+)]
+#[diesel(belongs_to(Player))]
+#[diesel(belongs_to(Hero))]
+#[diesel(table_name = player_hero, check_for_backend(diesel::pg::Pg))]
+pub struct PlayerHero {
+	pub id: PlayerHeroKey,
+	pub player_id: PlayerKey,
+	pub hero_id: HeroKey,
+	pub level: i32,
+	/// Experience gained in total, counting from level 1
+	pub xp: i64,
+	/// Building the hero runs, `None` unless a production hero is assigned
+	pub player_building_id: Option<PlayerBuildingKey>,
+	/// Whether the hero leads the player's army
+	pub leads_army: bool,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+impl PlayerHero {
+	/// Whether the hero is assigned and applies the modifier of their level.
+	pub fn is_assigned(&self) -> bool {
+		self.leads_army || self.player_building_id.is_some()
+	}
+}
+
+/// Data transfer object for recruiting a hero
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = player_hero, check_for_backend(diesel::pg::Pg))]
+pub struct NewPlayerHero {
+	pub player_id: PlayerKey,
+	pub hero_id: HeroKey,
+}
@@ -0,0 +1,68 @@
+//! Contains domain entities for consumable items.
+//! Items are boosts held by players, that apply a modifier to the player for a while when
+//! they are used, like two hours of faster wood production.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::domain::modifier::{Modifier, ModifierKey};
+use crate::domain::player::{Player, PlayerKey};
+use crate::schema::{item, player_item};
+
+/// Unique identifier for an item
+pub type ItemKey = Uuid;
+
+/// Unique identifier for a player item entity
+pub type PlayerItemKey = Uuid;
+
+/// Represents the definition of a consumable item
+#[derive(
+	Queryable, Selectable, Identifiable, Associations, Serialize, Debug, Clone, PartialEq, Eq,
+)]
+#[diesel(belongs_to(Modifier))]
+#[diesel(table_name = item, check_for_backend(diesel::pg::Pg))]
+pub struct Item {
+	pub id: ItemKey,
+	pub name: String,
+	pub description: String,
+	/// Modifier applied to the player when the item is used
+	pub modifier_id: ModifierKey,
+	/// How long the modifier lasts
+	pub duration_seconds: i32,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+impl Item {
+	/// How long the modifier applied by the item lasts.
+	pub fn duration(&self) -> TimeDelta {
+		TimeDelta::seconds(self.duration_seconds.into())
+	}
+}
+
+/// Represents the quantity of an item held by a player
+#[derive(
+	Queryable, Selectable, Identifiable, Associations, Serialize, Debug, Clone, PartialEq, Eq,
+)]
+#[diesel(belongs_to(Player))]
+#[diesel(belongs_to(Item))]
+#[diesel(table_name = player_item, check_for_backend(diesel::pg::Pg))]
+pub struct PlayerItem {
+	pub id: PlayerItemKey,
+	pub player_id: PlayerKey,
+	pub item_id: ItemKey,
+	pub quantity: i64,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+/// Data transfer object for creating a new player item entry
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = player_item, check_for_backend(diesel::pg::Pg))]
+pub struct NewPlayerItem {
+	pub player_id: PlayerKey,
+	pub item_id: ItemKey,
+	pub quantity: i64,
+}
@@ -1,5 +1,5 @@
 use std::io::Write;
-use std::str::from_utf8;
+use std::str::{FromStr, from_utf8};
 
 use chrono::{DateTime, Utc};
 use derive_more::Display;
@@ -11,11 +11,15 @@ use diesel::{AsExpression, FromSqlRow, deserialize, serialize};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::schema::job;
+use crate::domain::player::PlayerKey;
+use crate::schema::{job, job_dead_letter, recurring_job};
 
 /// Strongly typed alias for job identifier using UUID for clarity.
 pub type JobKey = Uuid;
 
+/// Strongly typed alias for recurring job identifier.
+pub type RecurringJobKey = Uuid;
+
 /// Enumerates valid job categories with PostgreSQL and serde integration.
 /// Derives facilitate conversion to/from DB and serialization.
 #[derive(
@@ -34,7 +38,7 @@ pub type JobKey = Uuid;
 	Ord,
 )]
 #[diesel(sql_type = crate::schema::sql_types::JobType)]
-#[serde(rename_all = "lowercase")]
+#[serde(rename_all = "snake_case")]
 pub enum JobType {
 	/// Modifier related tasks such as applying or removing game modifiers.
 	Modifier,
@@ -42,11 +46,57 @@ pub enum JobType {
 	Building,
 	/// Resource-related tasks such as gathering or distribution.
 	Resource,
+	/// Recurring resource production ticks, one per shard of players.
+	ResourceProduction,
 	/// Training-related tasks such as unit training completion.
 	Training,
+	/// Combat-related tasks such as battle report retention.
+	Combat,
+	/// Arrivals of scout missions, which write the intel reports.
+	Espionage,
+	/// Chunked data backfills that accompany schema migrations.
+	Backfill,
+	/// Steps of a confirmed world reset between seasons.
+	WorldReset,
+	/// Closing of time-limited events once they end.
+	LimitedEvent,
+	/// Periodic samples of the size of the busiest tables.
+	TableStats,
+	/// Periodic snapshots of the leaderboards.
+	Leaderboard,
+	/// Ending of seasons once they are over.
+	Season,
+	/// Anonymization of accounts once their deletion grace period ended.
+	AccountDeletion,
+	/// Periodic aggregation of the faction standings.
+	Faction,
+	/// Turns of the NPC players of development worlds.
+	#[cfg(feature = "simulation")]
+	Simulation,
 }
 
 impl JobType {
+	/// Every job type, in declaration order. Used to register a processor per type.
+	pub const ALL: [JobType; 15 + cfg!(feature = "simulation") as usize] = [
+		JobType::Modifier,
+		JobType::Building,
+		JobType::Resource,
+		JobType::ResourceProduction,
+		JobType::Training,
+		JobType::Combat,
+		JobType::Espionage,
+		JobType::Backfill,
+		JobType::WorldReset,
+		JobType::LimitedEvent,
+		JobType::TableStats,
+		JobType::Leaderboard,
+		JobType::Season,
+		JobType::AccountDeletion,
+		JobType::Faction,
+		#[cfg(feature = "simulation")]
+		JobType::Simulation,
+	];
+
 	/// Returns a static string slice for DB serialization.
 	#[inline]
 	pub fn as_str(&self) -> &'static str {
@@ -54,7 +104,20 @@ impl JobType {
 			JobType::Modifier => "modifier",
 			JobType::Building => "building",
 			JobType::Resource => "resource",
+			JobType::ResourceProduction => "resource_production",
 			JobType::Training => "training",
+			JobType::Combat => "combat",
+			JobType::Espionage => "espionage",
+			JobType::Backfill => "backfill",
+			JobType::WorldReset => "world_reset",
+			JobType::LimitedEvent => "limited_event",
+			JobType::TableStats => "table_stats",
+			JobType::Leaderboard => "leaderboard",
+			JobType::Season => "season",
+			JobType::AccountDeletion => "account_deletion",
+			JobType::Faction => "faction",
+			#[cfg(feature = "simulation")]
+			JobType::Simulation => "simulation",
 		}
 	}
 }
@@ -68,12 +131,33 @@ impl ToSql<crate::schema::sql_types::JobType, Pg> for JobType {
 
 impl FromSql<crate::schema::sql_types::JobType, Pg> for JobType {
 	fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
-		match from_utf8(bytes.as_bytes())? {
+		Ok(from_utf8(bytes.as_bytes())?.parse()?)
+	}
+}
+
+impl FromStr for JobType {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
 			"modifier" => Ok(JobType::Modifier),
 			"building" => Ok(JobType::Building),
 			"resource" => Ok(JobType::Resource),
+			"resource_production" => Ok(JobType::ResourceProduction),
 			"training" => Ok(JobType::Training),
-			other => Err(format!("Unrecognized job type: {other}").into()),
+			"combat" => Ok(JobType::Combat),
+			"espionage" => Ok(JobType::Espionage),
+			"backfill" => Ok(JobType::Backfill),
+			"world_reset" => Ok(JobType::WorldReset),
+			"limited_event" => Ok(JobType::LimitedEvent),
+			"table_stats" => Ok(JobType::TableStats),
+			"leaderboard" => Ok(JobType::Leaderboard),
+			"season" => Ok(JobType::Season),
+			"account_deletion" => Ok(JobType::AccountDeletion),
+			"faction" => Ok(JobType::Faction),
+			#[cfg(feature = "simulation")]
+			"simulation" => Ok(JobType::Simulation),
+			other => Err(format!("Unrecognized job type: {other}")),
 		}
 	}
 }
@@ -107,6 +191,8 @@ pub enum JobStatus {
 	Failed,
 	/// Job was cancelled.
 	Cancelled,
+	/// Job was cancelled while running, the worker finishes it as cancelled.
+	CancelRequested,
 }
 
 impl JobStatus {
@@ -119,6 +205,7 @@ impl JobStatus {
 			JobStatus::Completed => "completed",
 			JobStatus::Failed => "failed",
 			JobStatus::Cancelled => "cancelled",
+			JobStatus::CancelRequested => "cancel_requested",
 		}
 	}
 }
@@ -139,6 +226,7 @@ impl FromSql<crate::schema::sql_types::JobStatus, Pg> for JobStatus {
 			"completed" => Ok(JobStatus::Completed),
 			"failed" => Ok(JobStatus::Failed),
 			"cancelled" => Ok(JobStatus::Cancelled),
+			"cancel_requested" => Ok(JobStatus::CancelRequested),
 			other => Err(format!("Unrecognized job status: {other}").into()),
 		}
 	}
@@ -178,6 +266,44 @@ pub struct Job {
 	pub created_at: DateTime<Utc>,
 	/// Last update timestamp.
 	pub updated_at: DateTime<Utc>,
+	/// Every failed attempt as a list of [`JobError`], oldest first.
+	pub error_history: serde_json::Value,
+	/// Outcome reported by the processor once the job completed.
+	pub result: Option<serde_json::Value>,
+}
+
+impl Job {
+	/// How long a worker may run this job before it is failed.
+	pub fn timeout(&self) -> std::time::Duration {
+		std::time::Duration::from_secs(self.timeout_seconds.max(0) as u64)
+	}
+
+	/// Returns the player this job runs for, if any.
+	///
+	/// Read from the `player_id` field of the payload, or of the payload's single variant
+	/// for enum payloads such as `{"EvaluatePlans": {"player_id": ...}}`.
+	pub fn player_id(&self) -> Option<PlayerKey> {
+		let fields = match self.payload.as_object()? {
+			object if object.len() == 1 && object.values().all(|value| value.is_object()) => {
+				object.values().next()?.as_object()?
+			}
+			object => object,
+		};
+		// AIDEV-NOTE: resource payloads spell it `players_id`
+		let player_id = fields
+			.get("player_id")
+			.or_else(|| fields.get("players_id"))?;
+		serde_json::from_value(player_id.clone()).ok()
+	}
+}
+
+/// A single failed attempt of a job, as recorded in its error history.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct JobError {
+	/// Time the attempt failed.
+	pub failed_at: DateTime<Utc>,
+	/// Error message of the attempt.
+	pub error: String,
 }
 
 /// Data structure for inserting new jobs into the queue.
@@ -216,3 +342,94 @@ pub struct UpdateJob {
 	pub locked_at: Option<DateTime<Utc>>,
 	pub locked_by: Option<String>,
 }
+
+/// A job that exhausted its retries, moved out of the queue for inspection.
+/// Keeps the id of the original job.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = job_dead_letter)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DeadLetterJob {
+	/// Identifier of the original job.
+	pub id: JobKey,
+	/// Job category/type.
+	pub job_type: JobType,
+	/// JSON payload containing job-specific data.
+	pub payload: serde_json::Value,
+	/// Priority the job was scheduled with.
+	pub priority: i32,
+	/// Number of retries before the job was given up on.
+	pub retries: i32,
+	/// Maximum allowed retry attempts.
+	pub max_retries: i32,
+	/// Timeout threshold in seconds.
+	pub timeout_seconds: i32,
+	/// Error message of the final attempt.
+	pub last_error: Option<String>,
+	/// Every failed attempt as a list of [`JobError`], oldest first.
+	pub error_history: serde_json::Value,
+	/// Creation timestamp of the original job.
+	pub enqueued_at: DateTime<Utc>,
+	/// Time the job was dead-lettered.
+	pub created_at: DateTime<Utc>,
+	/// Last update timestamp.
+	pub updated_at: DateTime<Utc>,
+}
+
+/// Data structure for moving an exhausted job into the dead-letter table.
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = job_dead_letter)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewDeadLetterJob {
+	pub id: JobKey,
+	pub job_type: JobType,
+	pub payload: serde_json::Value,
+	pub priority: i32,
+	pub retries: i32,
+	pub max_retries: i32,
+	pub timeout_seconds: i32,
+	pub last_error: Option<String>,
+	pub error_history: serde_json::Value,
+	pub enqueued_at: DateTime<Utc>,
+}
+
+/// Definition of a job that runs on a cron schedule.
+/// Each run is a regular [`Job`] created from the payload template.
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = recurring_job)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct RecurringJob {
+	/// Unique recurring job identifier.
+	pub id: RecurringJobKey,
+	/// Unique name the definition is registered under.
+	pub name: String,
+	/// Cron expression with a leading seconds field, e.g. `0 */5 * * * *`.
+	pub cron_expression: String,
+	/// Type of the jobs created from this definition.
+	pub job_type: JobType,
+	/// Payload copied into every job created from this definition.
+	pub payload: serde_json::Value,
+	/// Priority of the jobs created from this definition.
+	pub priority: i32,
+	/// The currently enqueued run, if any.
+	pub next_job_id: Option<JobKey>,
+	/// Scheduled time of the currently enqueued run.
+	pub next_run_at: Option<DateTime<Utc>>,
+	/// Time the previous run finished.
+	pub last_run_at: Option<DateTime<Utc>>,
+	/// Creation timestamp.
+	pub created_at: DateTime<Utc>,
+	/// Last update timestamp.
+	pub updated_at: DateTime<Utc>,
+}
+
+/// Data structure for registering recurring jobs.
+#[derive(Insertable, AsChangeset, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = recurring_job)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NewRecurringJob {
+	pub name: String,
+	pub cron_expression: String,
+	pub job_type: JobType,
+	pub payload: serde_json::Value,
+	pub priority: i32,
+}
@@ -0,0 +1,138 @@
+//! Contains domain entities for the leaderboards.
+//! Every player has a score made of points for their buildings, their army and their research,
+//! and a recurring job ranks players and alliances by it. See [`crate::game::leaderboard`].
+
+use std::io::Write;
+use std::str::from_utf8;
+
+use chrono::{DateTime, Utc};
+use derive_more::Display;
+use diesel::deserialize::FromSql;
+use diesel::pg::{Pg, PgValue};
+use diesel::prelude::*;
+use diesel::serialize::{IsNull, Output, ToSql};
+use diesel::{AsExpression, FromSqlRow, deserialize, serialize};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::player::{Player, PlayerKey};
+use crate::schema::{leaderboard_entry, player_score};
+
+/// The board a ranking belongs to.
+#[derive(
+	AsExpression,
+	FromSqlRow,
+	Serialize,
+	Deserialize,
+	Display,
+	Debug,
+	Default,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	Hash,
+	PartialOrd,
+	Ord,
+)]
+#[diesel(sql_type = crate::schema::sql_types::LeaderboardCategory)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaderboardCategory {
+	/// Players ranked by the points of their army
+	#[default]
+	Power,
+	/// Players ranked by the points of their buildings and research
+	Economy,
+	/// Alliances ranked by the total points of their members
+	Alliance,
+}
+
+impl LeaderboardCategory {
+	/// Every board, in the order they are snapshotted.
+	pub const ALL: [LeaderboardCategory; 3] = [
+		LeaderboardCategory::Power,
+		LeaderboardCategory::Economy,
+		LeaderboardCategory::Alliance,
+	];
+}
+
+impl AsRef<str> for LeaderboardCategory {
+	fn as_ref(&self) -> &str {
+		match self {
+			LeaderboardCategory::Power => "power",
+			LeaderboardCategory::Economy => "economy",
+			LeaderboardCategory::Alliance => "alliance",
+		}
+	}
+}
+
+impl ToSql<crate::schema::sql_types::LeaderboardCategory, Pg> for LeaderboardCategory {
+	fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+		out.write_all(self.as_ref().as_bytes())?;
+		Ok(IsNull::No)
+	}
+}
+
+impl FromSql<crate::schema::sql_types::LeaderboardCategory, Pg> for LeaderboardCategory {
+	fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+		match from_utf8(bytes.as_bytes())? {
+			"power" => Ok(LeaderboardCategory::Power),
+			"economy" => Ok(LeaderboardCategory::Economy),
+			"alliance" => Ok(LeaderboardCategory::Alliance),
+			other => Err(format!("Unrecognized enum variant: {other}").into()),
+		}
+	}
+}
+
+/// Represents the points of a player
+#[derive(Queryable, Selectable, Identifiable, Associations, Debug, Clone, PartialEq, Eq)]
+#[diesel(belongs_to(Player))]
+#[diesel(table_name = player_score, primary_key(player_id), check_for_backend(diesel::pg::Pg))]
+pub struct PlayerScore {
+	pub player_id: PlayerKey,
+	/// Sum of the levels of the player's buildings, research buildings excluded
+	pub building_points: i64,
+	/// Sum of the attack and defense of the player's units
+	pub unit_points: i64,
+	/// Sum of the levels of the player's research buildings
+	pub tech_points: i64,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+impl PlayerScore {
+	/// Points counting towards the power board.
+	pub fn power(&self) -> i64 {
+		self.unit_points
+	}
+
+	/// Points counting towards the economy board.
+	pub fn economy(&self) -> i64 {
+		self.building_points + self.tech_points
+	}
+
+	/// All points of the player, counting towards the board of their alliance.
+	pub fn total(&self) -> i64 {
+		self.power() + self.economy()
+	}
+}
+
+/// Represents the rank of a player or alliance in the last snapshot of a board
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = leaderboard_entry, check_for_backend(diesel::pg::Pg))]
+pub struct LeaderboardEntry {
+	pub category: LeaderboardCategory,
+	/// The ranked player, or alliance on the alliance board
+	pub subject_id: Uuid,
+	/// 1-based rank, subjects with the same score share a rank
+	pub rank: i32,
+	pub score: i64,
+	pub snapshot_at: DateTime<Utc>,
+}
+
+/// Payload of a [`crate::domain::jobs::JobType::Leaderboard`] job.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaderboardJobPayload {
+	/// Recalculates every score and replaces the rankings of every board
+	Snapshot,
+}
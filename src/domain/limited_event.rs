@@ -0,0 +1,258 @@
+//! Contains domain entities for time-limited events.
+//! While an event runs, players earn its own currency by completing its objectives and spend
+//! it in the event's shop. Limited events are stored in the `game_event` tables, and are not
+//! to be confused with the [`crate::domain::events::GameEvent`]s pushed to clients, which
+//! are what the objectives count. See [`crate::game::limited_events`].
+
+use std::io::Write;
+use std::str::from_utf8;
+
+use chrono::{DateTime, Utc};
+use derive_more::Display;
+use diesel::deserialize::FromSql;
+use diesel::pg::{Pg, PgValue};
+use diesel::prelude::*;
+use diesel::serialize::{IsNull, Output, ToSql};
+use diesel::{AsExpression, FromSqlRow, deserialize, serialize};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::events::GameEvent;
+use crate::domain::item::ItemKey;
+use crate::domain::player::resource::ResourceType;
+use crate::domain::player::{Player, PlayerKey};
+use crate::domain::unit::UnitKey;
+use crate::schema::{
+	game_event, game_event_objective, game_event_offer, player_event_currency,
+	player_event_objective,
+};
+
+/// Unique identifier for a limited event
+pub type LimitedEventKey = Uuid;
+
+/// Unique identifier for an objective of a limited event
+pub type EventObjectiveKey = Uuid;
+
+/// Unique identifier for an offer of an event shop
+pub type EventOfferKey = Uuid;
+
+/// What players do to progress an event objective.
+#[derive(
+	AsExpression,
+	FromSqlRow,
+	Serialize,
+	Deserialize,
+	Display,
+	Debug,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	Hash,
+)]
+#[diesel(sql_type = crate::schema::sql_types::EventObjectiveKind)]
+#[serde(rename_all = "snake_case")]
+pub enum EventObjectiveKind {
+	/// Every unit that finishes training counts
+	TrainingCompleted,
+	/// Every building level reached counts
+	UpgradeCompleted,
+	/// Every collection of the accumulated resources counts
+	ResourcesCollected,
+}
+
+impl EventObjectiveKind {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Self::TrainingCompleted => "training_completed",
+			Self::UpgradeCompleted => "upgrade_completed",
+			Self::ResourcesCollected => "resources_collected",
+		}
+	}
+
+	/// The objective kind a game event progresses and by how much, `None` if it progresses
+	/// none.
+	pub fn progress_of(event: &GameEvent) -> Option<(Self, i64)> {
+		match event {
+			GameEvent::TrainingCompleted { quantity, .. } => {
+				Some((Self::TrainingCompleted, *quantity))
+			}
+			GameEvent::UpgradeCompleted { .. } => Some((Self::UpgradeCompleted, 1)),
+			GameEvent::ResourcesCollected { .. } => Some((Self::ResourcesCollected, 1)),
+			GameEvent::TrainingStarted { .. }
+			| GameEvent::ModifierExpired { .. }
+			| GameEvent::AttackIncoming { .. } => None,
+		}
+	}
+}
+
+impl ToSql<crate::schema::sql_types::EventObjectiveKind, Pg> for EventObjectiveKind {
+	fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+		out.write_all(self.as_str().as_bytes())?;
+		Ok(IsNull::No)
+	}
+}
+
+impl FromSql<crate::schema::sql_types::EventObjectiveKind, Pg> for EventObjectiveKind {
+	fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+		match from_utf8(bytes.as_bytes())? {
+			"training_completed" => Ok(Self::TrainingCompleted),
+			"upgrade_completed" => Ok(Self::UpgradeCompleted),
+			"resources_collected" => Ok(Self::ResourcesCollected),
+			other => Err(format!("Unrecognized enum variant: {other}").into()),
+		}
+	}
+}
+
+/// Represents a time-limited event
+#[derive(Queryable, Selectable, Identifiable, Serialize, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = game_event, check_for_backend(diesel::pg::Pg))]
+pub struct LimitedEvent {
+	pub id: LimitedEventKey,
+	pub name: String,
+	pub description: String,
+	/// Name of the currency earned during the event
+	pub currency_name: String,
+	pub starts_at: DateTime<Utc>,
+	pub ends_at: DateTime<Utc>,
+	/// Resource the currency left over at the end of the event is converted to
+	pub leftover_resource: ResourceType,
+	/// Amount of the resource granted for each unit of leftover currency
+	pub leftover_rate: i32,
+	/// When the leftover currency was converted, `None` until the event is closed
+	pub closed_at: Option<DateTime<Utc>>,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+impl LimitedEvent {
+	/// Whether players can earn and spend the event's currency at `now`.
+	pub fn is_running(&self, now: DateTime<Utc>) -> bool {
+		self.closed_at.is_none() && self.starts_at <= now && now < self.ends_at
+	}
+}
+
+/// Data transfer object for creating a new limited event
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = game_event, check_for_backend(diesel::pg::Pg))]
+pub struct NewLimitedEvent {
+	pub name: String,
+	pub description: String,
+	pub currency_name: String,
+	pub starts_at: DateTime<Utc>,
+	pub ends_at: DateTime<Utc>,
+	pub leftover_resource: ResourceType,
+	pub leftover_rate: i32,
+}
+
+/// Represents an objective of a limited event
+#[derive(
+	Queryable, Selectable, Identifiable, Associations, Serialize, Debug, Clone, PartialEq, Eq,
+)]
+#[diesel(belongs_to(LimitedEvent, foreign_key = event_id))]
+#[diesel(table_name = game_event_objective, check_for_backend(diesel::pg::Pg))]
+pub struct EventObjective {
+	pub id: EventObjectiveKey,
+	pub event_id: LimitedEventKey,
+	pub kind: EventObjectiveKind,
+	pub description: String,
+	/// Occurrences of the kind needed to complete the objective once
+	pub target: i64,
+	/// Currency earned for each completion
+	pub reward: i64,
+	/// How often the objective can be completed, `None` for no limit
+	pub max_completions: Option<i32>,
+	pub created_at: DateTime<Utc>,
+}
+
+impl EventObjective {
+	/// Number of completions reached with `progress` occurrences.
+	pub fn completions(&self, progress: i64) -> i32 {
+		let completions = i32::try_from(progress / self.target).unwrap_or(i32::MAX);
+		self.max_completions
+			.map_or(completions, |max| completions.min(max))
+	}
+}
+
+/// Data transfer object for creating a new event objective
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = game_event_objective, check_for_backend(diesel::pg::Pg))]
+pub struct NewEventObjective {
+	pub event_id: LimitedEventKey,
+	pub kind: EventObjectiveKind,
+	pub description: String,
+	pub target: i64,
+	pub reward: i64,
+	pub max_completions: Option<i32>,
+}
+
+/// Represents an offer of an event shop, selling either an item or a unit
+#[derive(
+	Queryable, Selectable, Identifiable, Associations, Serialize, Debug, Clone, PartialEq, Eq,
+)]
+#[diesel(belongs_to(LimitedEvent, foreign_key = event_id))]
+#[diesel(table_name = game_event_offer, check_for_backend(diesel::pg::Pg))]
+pub struct EventOffer {
+	pub id: EventOfferKey,
+	pub event_id: LimitedEventKey,
+	/// Price in the event's currency
+	pub price: i64,
+	pub item_id: Option<ItemKey>,
+	pub unit_id: Option<UnitKey>,
+	/// Number of items or units granted per purchase
+	pub quantity: i64,
+	pub created_at: DateTime<Utc>,
+}
+
+/// Data transfer object for creating a new event offer
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = game_event_offer, check_for_backend(diesel::pg::Pg))]
+pub struct NewEventOffer {
+	pub event_id: LimitedEventKey,
+	pub price: i64,
+	pub item_id: Option<ItemKey>,
+	pub unit_id: Option<UnitKey>,
+	pub quantity: i64,
+}
+
+/// Represents the event currency held by a player
+#[derive(
+	Queryable, Selectable, Identifiable, Associations, Serialize, Debug, Clone, PartialEq, Eq,
+)]
+#[diesel(belongs_to(Player))]
+#[diesel(belongs_to(LimitedEvent, foreign_key = event_id))]
+#[diesel(primary_key(player_id, event_id))]
+#[diesel(table_name = player_event_currency, check_for_backend(diesel::pg::Pg))]
+pub struct PlayerEventCurrency {
+	pub player_id: PlayerKey,
+	pub event_id: LimitedEventKey,
+	pub balance: i64,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+/// Represents a player's progress towards an event objective
+#[derive(
+	Queryable, Selectable, Identifiable, Associations, Serialize, Debug, Clone, PartialEq, Eq,
+)]
+#[diesel(belongs_to(Player))]
+#[diesel(belongs_to(EventObjective, foreign_key = objective_id))]
+#[diesel(primary_key(player_id, objective_id))]
+#[diesel(table_name = player_event_objective, check_for_backend(diesel::pg::Pg))]
+pub struct PlayerEventObjective {
+	pub player_id: PlayerKey,
+	pub objective_id: EventObjectiveKey,
+	/// Occurrences counted so far
+	pub progress: i64,
+	/// Completions rewarded so far
+	pub completions: i32,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+/// Payload of a [`crate::domain::jobs::JobType::LimitedEvent`] job, closing an event once it
+/// ends.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimitedEventJobPayload {
+	pub event_id: LimitedEventKey,
+}
@@ -0,0 +1,215 @@
+//! Contains domain entities for the player market.
+//! Players trade food, wood and stone for gold through limit orders. Orders rest in a
+//! per-resource order book until they are filled by orders of other players or cancelled.
+
+use std::io::Write;
+use std::str::from_utf8;
+
+use chrono::{DateTime, Utc};
+use diesel::deserialize::FromSql;
+use diesel::pg::{Pg, PgValue};
+use diesel::prelude::*;
+use diesel::serialize::{IsNull, Output, ToSql};
+use diesel::{AsExpression, FromSqlRow, deserialize, serialize};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::player::resource::ResourceType;
+use crate::domain::player::{Player, PlayerKey};
+use crate::schema::{market_order, market_trade};
+
+/// Unique identifier for a market order
+pub type MarketOrderKey = Uuid;
+
+/// Unique identifier for a market trade
+pub type MarketTradeKey = Uuid;
+
+/// Resources that can be traded on the market, gold is the currency they are priced in
+pub const TRADABLE_RESOURCES: [ResourceType; 3] =
+	[ResourceType::Food, ResourceType::Wood, ResourceType::Stone];
+
+/// Whether an order buys or sells its resource
+#[derive(
+	AsExpression,
+	FromSqlRow,
+	Serialize,
+	Deserialize,
+	Debug,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	Hash,
+	PartialOrd,
+	Ord,
+)]
+#[diesel(sql_type = crate::schema::sql_types::MarketOrderSide)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketOrderSide {
+	/// Pays gold for the resource
+	Buy,
+	/// Offers the resource for gold
+	Sell,
+}
+
+impl MarketOrderSide {
+	/// Returns the side that orders of this side are matched against.
+	pub fn opposite(&self) -> Self {
+		match self {
+			MarketOrderSide::Buy => MarketOrderSide::Sell,
+			MarketOrderSide::Sell => MarketOrderSide::Buy,
+		}
+	}
+}
+
+impl AsRef<str> for MarketOrderSide {
+	fn as_ref(&self) -> &str {
+		match self {
+			MarketOrderSide::Buy => "buy",
+			MarketOrderSide::Sell => "sell",
+		}
+	}
+}
+
+impl ToSql<crate::schema::sql_types::MarketOrderSide, Pg> for MarketOrderSide {
+	fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+		out.write_all(self.as_ref().as_bytes())?;
+		Ok(IsNull::No)
+	}
+}
+
+impl FromSql<crate::schema::sql_types::MarketOrderSide, Pg> for MarketOrderSide {
+	fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+		match from_utf8(bytes.as_bytes())? {
+			"buy" => Ok(MarketOrderSide::Buy),
+			"sell" => Ok(MarketOrderSide::Sell),
+			other => Err(format!("Unrecognized enum variant: {other}").into()),
+		}
+	}
+}
+
+/// Lifecycle state of a market order
+#[derive(
+	AsExpression,
+	FromSqlRow,
+	Serialize,
+	Deserialize,
+	Debug,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	Hash,
+	PartialOrd,
+	Ord,
+)]
+#[diesel(sql_type = crate::schema::sql_types::MarketOrderStatus)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketOrderStatus {
+	/// Resting in the order book, possibly partially filled
+	Open,
+	/// Completely filled
+	Filled,
+	/// Withdrawn by the player, the unfilled remainder was refunded
+	Cancelled,
+}
+
+impl AsRef<str> for MarketOrderStatus {
+	fn as_ref(&self) -> &str {
+		match self {
+			MarketOrderStatus::Open => "open",
+			MarketOrderStatus::Filled => "filled",
+			MarketOrderStatus::Cancelled => "cancelled",
+		}
+	}
+}
+
+impl ToSql<crate::schema::sql_types::MarketOrderStatus, Pg> for MarketOrderStatus {
+	fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+		out.write_all(self.as_ref().as_bytes())?;
+		Ok(IsNull::No)
+	}
+}
+
+impl FromSql<crate::schema::sql_types::MarketOrderStatus, Pg> for MarketOrderStatus {
+	fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+		match from_utf8(bytes.as_bytes())? {
+			"open" => Ok(MarketOrderStatus::Open),
+			"filled" => Ok(MarketOrderStatus::Filled),
+			"cancelled" => Ok(MarketOrderStatus::Cancelled),
+			other => Err(format!("Unrecognized enum variant: {other}").into()),
+		}
+	}
+}
+
+/// Represents a limit order placed by a player
+#[derive(
+	Queryable, Selectable, Identifiable, Associations, Serialize, Debug, Clone, PartialEq, Eq,
+)]
+#[diesel(belongs_to(Player))]
+#[diesel(table_name = market_order, check_for_backend(diesel::pg::Pg))]
+pub struct MarketOrder {
+	pub id: MarketOrderKey,
+	pub player_id: PlayerKey,
+	pub side: MarketOrderSide,
+	pub resource: ResourceType,
+	/// Units of the resource the order was placed for
+	pub quantity: i64,
+	/// Units that are not filled yet
+	pub remaining: i64,
+	/// Limit price in gold per unit
+	pub price: i64,
+	pub status: MarketOrderStatus,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+/// Data transfer object for creating a new market order
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = market_order, check_for_backend(diesel::pg::Pg))]
+pub struct NewMarketOrder {
+	pub player_id: PlayerKey,
+	pub side: MarketOrderSide,
+	pub resource: ResourceType,
+	pub quantity: i64,
+	pub remaining: i64,
+	pub price: i64,
+}
+
+/// Represents a fill between a buy and a sell order
+#[derive(Queryable, Selectable, Identifiable, Serialize, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = market_trade, check_for_backend(diesel::pg::Pg))]
+pub struct MarketTrade {
+	pub id: MarketTradeKey,
+	pub resource: ResourceType,
+	pub buy_order_id: MarketOrderKey,
+	pub sell_order_id: MarketOrderKey,
+	pub buyer_id: PlayerKey,
+	pub seller_id: PlayerKey,
+	/// Units of the resource that changed hands
+	pub quantity: i64,
+	/// Gold paid per unit, the price of the order that was resting in the book
+	pub price: i64,
+	pub created_at: DateTime<Utc>,
+}
+
+/// Data transfer object for recording a new market trade
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = market_trade, check_for_backend(diesel::pg::Pg))]
+pub struct NewMarketTrade {
+	pub resource: ResourceType,
+	pub buy_order_id: MarketOrderKey,
+	pub sell_order_id: MarketOrderKey,
+	pub buyer_id: PlayerKey,
+	pub seller_id: PlayerKey,
+	pub quantity: i64,
+	pub price: i64,
+}
+
+/// Open quantity of one side of the order book at a single price
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PriceLevel {
+	pub price: i64,
+	pub quantity: i64,
+	pub orders: i64,
+}
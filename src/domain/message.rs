@@ -0,0 +1,127 @@
+//! Contains domain entities for in-game mail.
+//! Players write messages to each other, and the server delivers messages about what
+//! happened to a player, like a battle they fought or a training that finished.
+//! Every message has a single recipient, who reads and deletes it on their own.
+
+use std::io::Write;
+use std::str::from_utf8;
+
+use chrono::{DateTime, Utc};
+use derive_more::Display;
+use diesel::deserialize::FromSql;
+use diesel::pg::{Pg, PgValue};
+use diesel::prelude::*;
+use diesel::serialize::{IsNull, Output, ToSql};
+use diesel::{AsExpression, FromSqlRow, deserialize, serialize};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::player::PlayerKey;
+use crate::schema::message;
+
+/// Unique identifier for a message
+pub type MessageKey = Uuid;
+
+/// What a message is about, and who sent it.
+#[derive(
+	AsExpression,
+	FromSqlRow,
+	Serialize,
+	Deserialize,
+	Display,
+	Debug,
+	Default,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	Hash,
+)]
+#[diesel(sql_type = crate::schema::sql_types::MessageKind)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageKind {
+	/// Written by another player
+	#[default]
+	Player,
+	/// Announcement from the server
+	System,
+	/// A battle the player fought, with the report ID in the details
+	BattleReport,
+	/// A training that finished, with the training, unit and quantity in the details
+	TrainingComplete,
+	/// What the player's scouts found out, with the intel report ID in the details
+	IntelReport,
+	/// Scouts of another player spotted by the Watchtower, with the scouting player in the details
+	ScoutsSpotted,
+}
+
+impl AsRef<str> for MessageKind {
+	fn as_ref(&self) -> &str {
+		match self {
+			MessageKind::Player => "player",
+			MessageKind::System => "system",
+			MessageKind::BattleReport => "battle_report",
+			MessageKind::TrainingComplete => "training_complete",
+			MessageKind::IntelReport => "intel_report",
+			MessageKind::ScoutsSpotted => "scouts_spotted",
+		}
+	}
+}
+
+impl ToSql<crate::schema::sql_types::MessageKind, Pg> for MessageKind {
+	fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+		out.write_all(self.as_ref().as_bytes())?;
+		Ok(IsNull::No)
+	}
+}
+
+impl FromSql<crate::schema::sql_types::MessageKind, Pg> for MessageKind {
+	fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+		match from_utf8(bytes.as_bytes())? {
+			"player" => Ok(MessageKind::Player),
+			"system" => Ok(MessageKind::System),
+			"battle_report" => Ok(MessageKind::BattleReport),
+			"training_complete" => Ok(MessageKind::TrainingComplete),
+			"intel_report" => Ok(MessageKind::IntelReport),
+			"scouts_spotted" => Ok(MessageKind::ScoutsSpotted),
+			other => Err(format!("Unrecognized enum variant: {other}").into()),
+		}
+	}
+}
+
+/// Represents a message in the inbox of its recipient
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, PartialEq)]
+#[diesel(table_name = message, check_for_backend(diesel::pg::Pg))]
+pub struct Message {
+	pub id: MessageKey,
+	pub recipient_id: PlayerKey,
+	/// Player who wrote the message, `None` for the server or once they are gone
+	pub sender_id: Option<PlayerKey>,
+	pub kind: MessageKind,
+	pub subject: String,
+	pub body: String,
+	/// JSON object with what a server message is about, empty for player messages
+	pub details: serde_json::Value,
+	/// When the recipient read the message, `None` while it is unread
+	pub read_at: Option<DateTime<Utc>>,
+	pub created_at: DateTime<Utc>,
+}
+
+impl Message {
+	/// Whether the recipient read the message.
+	pub fn is_read(&self) -> bool {
+		self.read_at.is_some()
+	}
+}
+
+/// Data transfer object for delivering a message
+#[derive(Insertable, Debug, Clone, PartialEq)]
+#[diesel(table_name = message, check_for_backend(diesel::pg::Pg))]
+pub struct NewMessage {
+	pub recipient_id: PlayerKey,
+	pub sender_id: Option<PlayerKey>,
+	pub kind: MessageKind,
+	pub subject: String,
+	pub body: String,
+	pub details: serde_json::Value,
+}
@@ -0,0 +1,411 @@
+//! In-memory server metrics backing the admin overview and the `/metrics` endpoint.
+//!
+//! Tracks HTTP responses in one-minute buckets and the last time each player made an
+//! authenticated request. Nothing is persisted, so the numbers reset on restart and only
+//! cover the current server instance.
+//!
+//! Per-endpoint latencies are kept as histograms labelled with the route template rather
+//! than the requested path, so IDs in paths do not grow the label set, and rendered in the
+//! Prometheus text format for SLA dashboards.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use axum::http::{Method, StatusCode};
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::domain::player::PlayerKey;
+
+/// How far back request buckets and player activity are kept.
+pub const METRICS_RETENTION: TimeDelta = TimeDelta::hours(1);
+
+/// Upper bounds of the latency histogram buckets, in seconds.
+pub const LATENCY_BUCKETS: [f64; 11] = [
+	0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Route label of requests that matched no route.
+pub const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Routes whose responses are streamed for as long as the client stays connected.
+///
+/// Their total duration says nothing about the server, so only the time to the first byte
+/// of the response is recorded for them.
+pub const STREAMING_ROUTES: [&str; 2] = ["/game/events", "/game/ws"];
+
+/// Feature area of a route, for defining latency objectives per part of the API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FeatureArea {
+	Auth,
+	Game,
+	Market,
+	Combat,
+	Player,
+	Health,
+	Admin,
+	Other,
+}
+
+impl FeatureArea {
+	/// Returns the area of a route template.
+	pub fn of(route: &str) -> Self {
+		let top_level = |prefix: &str| {
+			route == prefix
+				|| route
+					.strip_prefix(prefix)
+					.is_some_and(|rest| rest.starts_with('/'))
+		};
+		match route {
+			"/login" | "/register" | "/logout" | "/session" => Self::Auth,
+			_ if top_level("/game/market") => Self::Market,
+			_ if top_level("/game/combat") => Self::Combat,
+			_ if top_level("/game") => Self::Game,
+			_ if top_level("/player") || top_level("/users") => Self::Player,
+			_ if top_level("/health") || route == "/metrics" => Self::Health,
+			_ if top_level("/admin") => Self::Admin,
+			_ => Self::Other,
+		}
+	}
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Self::Auth => "auth",
+			Self::Game => "game",
+			Self::Market => "market",
+			Self::Combat => "combat",
+			Self::Player => "player",
+			Self::Health => "health",
+			Self::Admin => "admin",
+			Self::Other => "other",
+		}
+	}
+}
+
+/// Labels an endpoint response is recorded under.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EndpointLabels {
+	pub area: FeatureArea,
+	pub method: String,
+	/// Route template, like `/game/buildings/{building_id}`, or [`UNMATCHED_ROUTE`]
+	pub route: String,
+	/// Status class, like `2xx`
+	pub status_class: &'static str,
+	/// Whether the request passed the auth middleware
+	pub authenticated: bool,
+}
+
+impl EndpointLabels {
+	pub fn new(method: &Method, route: &str, status: StatusCode, authenticated: bool) -> Self {
+		Self {
+			area: FeatureArea::of(route),
+			method: method.to_string(),
+			route: route.to_owned(),
+			status_class: status_class(status),
+			authenticated,
+		}
+	}
+
+	fn render(&self) -> String {
+		format!(
+			"area=\"{}\",method=\"{}\",route=\"{}\",status_class=\"{}\",auth=\"{}\"",
+			self.area.as_str(),
+			escape_label(&self.method),
+			escape_label(&self.route),
+			self.status_class,
+			if self.authenticated {
+				"authenticated"
+			} else {
+				"anonymous"
+			}
+		)
+	}
+}
+
+/// Returns the class of a status code, like `2xx`.
+pub fn status_class(status: StatusCode) -> &'static str {
+	match status.as_u16() {
+		100..=199 => "1xx",
+		200..=299 => "2xx",
+		300..=399 => "3xx",
+		400..=499 => "4xx",
+		_ => "5xx",
+	}
+}
+
+/// Cumulative histogram over [`LATENCY_BUCKETS`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Histogram {
+	/// Observations at or below each bucket bound
+	buckets: [u64; LATENCY_BUCKETS.len()],
+	count: u64,
+	sum: f64,
+}
+
+impl Histogram {
+	pub fn observe(&mut self, elapsed: Duration) {
+		let seconds = elapsed.as_secs_f64();
+		for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+			*bucket += u64::from(seconds <= bound);
+		}
+		self.count += 1;
+		self.sum += seconds;
+	}
+
+	pub fn count(&self) -> u64 {
+		self.count
+	}
+
+	fn render(&self, out: &mut String, name: &str, labels: &str) {
+		for (bucket, bound) in self.buckets.iter().zip(LATENCY_BUCKETS) {
+			let _ = writeln!(out, "{name}_bucket{{{labels},le=\"{bound}\"}} {bucket}");
+		}
+		let _ = writeln!(out, "{name}_bucket{{{labels},le=\"+Inf\"}} {}", self.count);
+		let _ = writeln!(out, "{name}_sum{{{labels}}} {}", self.sum);
+		let _ = writeln!(out, "{name}_count{{{labels}}} {}", self.count);
+	}
+}
+
+fn escape_label(value: &str) -> String {
+	value
+		.replace('\\', "\\\\")
+		.replace('"', "\\\"")
+		.replace('\n', "\\n")
+}
+
+/// Responses recorded during a single minute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct MinuteBucket {
+	/// Unix timestamp of the start of the minute
+	minute: i64,
+	requests: u64,
+	server_errors: u64,
+}
+
+/// Aggregated request counts over a time window.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RequestStats {
+	pub requests: u64,
+	/// Responses with a 5xx status
+	pub server_errors: u64,
+}
+
+impl RequestStats {
+	/// Share of requests answered with a server error, between 0 and 1.
+	pub fn error_rate(&self) -> f64 {
+		if self.requests == 0 {
+			return 0.0;
+		}
+		self.server_errors as f64 / self.requests as f64
+	}
+}
+
+/// Request and player activity counters shared through the application state.
+#[derive(Debug, Default)]
+pub struct ServerMetrics {
+	buckets: Mutex<VecDeque<MinuteBucket>>,
+	last_seen: Mutex<HashMap<PlayerKey, DateTime<Utc>>>,
+	/// Time to respond, per endpoint
+	requests: Mutex<BTreeMap<EndpointLabels, Histogram>>,
+	/// Time to the first byte of the response, per streaming endpoint
+	streams: Mutex<BTreeMap<EndpointLabels, Histogram>>,
+}
+
+impl ServerMetrics {
+	/// Records a response with the given status.
+	pub fn record_response(&self, status: StatusCode) {
+		self.record_response_at(status, Utc::now());
+	}
+
+	/// Records how long an endpoint took to respond.
+	///
+	/// Responses of [`STREAMING_ROUTES`] are recorded as their time to first byte.
+	pub fn record_request(&self, labels: EndpointLabels, elapsed: Duration) {
+		let histograms = if STREAMING_ROUTES.contains(&labels.route.as_str()) {
+			&self.streams
+		} else {
+			&self.requests
+		};
+		let mut histograms = histograms.lock().expect("metrics lock poisoned");
+		histograms.entry(labels).or_default().observe(elapsed);
+	}
+
+	/// Renders the endpoint metrics in the Prometheus text exposition format.
+	pub fn render_prometheus(&self) -> String {
+		let mut out = String::new();
+		let families = [
+			(
+				"empire_http_request_duration_seconds",
+				"Time to respond to HTTP requests, per endpoint.",
+				&self.requests,
+			),
+			(
+				"empire_http_stream_ttfb_seconds",
+				"Time to the first byte of streaming responses, per endpoint.",
+				&self.streams,
+			),
+		];
+		for (name, help, histograms) in families {
+			let _ = writeln!(out, "# HELP {name} {help}");
+			let _ = writeln!(out, "# TYPE {name} histogram");
+			let histograms = histograms.lock().expect("metrics lock poisoned");
+			for (labels, histogram) in histograms.iter() {
+				histogram.render(&mut out, name, &labels.render());
+			}
+		}
+		out
+	}
+
+	/// Records that a player made an authenticated request.
+	pub fn record_activity(&self, player_id: &PlayerKey) {
+		self.record_activity_at(player_id, Utc::now());
+	}
+
+	/// Number of distinct players seen within `window`.
+	pub fn active_players(&self, window: TimeDelta) -> usize {
+		let now = Utc::now();
+		let since = now - window;
+		let mut last_seen = self.last_seen.lock().expect("metrics lock poisoned");
+		last_seen.retain(|_, seen| *seen >= now - METRICS_RETENTION);
+		last_seen.values().filter(|seen| **seen >= since).count()
+	}
+
+	/// Whether a player made an authenticated request within `window`.
+	pub fn is_active(&self, player_id: &PlayerKey, window: TimeDelta) -> bool {
+		let since = Utc::now() - window;
+		let last_seen = self.last_seen.lock().expect("metrics lock poisoned");
+		last_seen.get(player_id).is_some_and(|seen| *seen >= since)
+	}
+
+	/// Request counts of the minutes within `window`, including the current one.
+	pub fn request_stats(&self, window: TimeDelta) -> RequestStats {
+		let since = (Utc::now() - window).timestamp() / 60 * 60;
+		let buckets = self.buckets.lock().expect("metrics lock poisoned");
+		buckets.iter().filter(|bucket| bucket.minute >= since).fold(
+			RequestStats::default(),
+			|mut stats, bucket| {
+				stats.requests += bucket.requests;
+				stats.server_errors += bucket.server_errors;
+				stats
+			},
+		)
+	}
+
+	fn record_response_at(&self, status: StatusCode, at: DateTime<Utc>) {
+		let minute = at.timestamp() / 60 * 60;
+		let mut buckets = self.buckets.lock().expect("metrics lock poisoned");
+		match buckets.back_mut() {
+			Some(bucket) if bucket.minute == minute => {
+				bucket.requests += 1;
+				bucket.server_errors += u64::from(status.is_server_error());
+			}
+			_ => buckets.push_back(MinuteBucket {
+				minute,
+				requests: 1,
+				server_errors: u64::from(status.is_server_error()),
+			}),
+		}
+
+		let oldest = minute - METRICS_RETENTION.num_seconds();
+		while buckets.front().is_some_and(|bucket| bucket.minute < oldest) {
+			buckets.pop_front();
+		}
+	}
+
+	fn record_activity_at(&self, player_id: &PlayerKey, at: DateTime<Utc>) {
+		let mut last_seen = self.last_seen.lock().expect("metrics lock poisoned");
+		last_seen.insert(*player_id, at);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn request_stats_only_count_the_window() {
+		let metrics = ServerMetrics::default();
+		let now = Utc::now();
+		metrics.record_response_at(StatusCode::OK, now - TimeDelta::minutes(30));
+		metrics.record_response_at(
+			StatusCode::INTERNAL_SERVER_ERROR,
+			now - TimeDelta::minutes(30),
+		);
+		metrics.record_response_at(StatusCode::OK, now);
+		metrics.record_response_at(StatusCode::NOT_FOUND, now);
+		metrics.record_response_at(StatusCode::BAD_GATEWAY, now);
+
+		let recent = metrics.request_stats(TimeDelta::minutes(15));
+		assert_eq!(recent.requests, 3);
+		assert_eq!(recent.server_errors, 1);
+		assert!((recent.error_rate() - 1.0 / 3.0).abs() < f64::EPSILON);
+
+		let hour = metrics.request_stats(METRICS_RETENTION);
+		assert_eq!(hour.requests, 5);
+		assert_eq!(hour.server_errors, 2);
+	}
+
+	#[test]
+	fn routes_belong_to_feature_areas() {
+		assert_eq!(FeatureArea::of("/login"), FeatureArea::Auth);
+		assert_eq!(FeatureArea::of("/game/market/orders"), FeatureArea::Market);
+		assert_eq!(
+			FeatureArea::of("/game/combat/reports/{report_id}"),
+			FeatureArea::Combat
+		);
+		assert_eq!(FeatureArea::of("/game/marketing"), FeatureArea::Game);
+		assert_eq!(
+			FeatureArea::of("/game/buildings/{building_id}"),
+			FeatureArea::Game
+		);
+		assert_eq!(FeatureArea::of("/users/{id}"), FeatureArea::Player);
+		assert_eq!(FeatureArea::of("/metrics"), FeatureArea::Health);
+		assert_eq!(FeatureArea::of(UNMATCHED_ROUTE), FeatureArea::Other);
+	}
+
+	#[test]
+	fn endpoint_latencies_are_rendered_as_histograms() {
+		let metrics = ServerMetrics::default();
+		let route = "/game/buildings/{building_id}";
+		let labels = EndpointLabels::new(&Method::GET, route, StatusCode::OK, true);
+		metrics.record_request(labels.clone(), Duration::from_millis(20));
+		metrics.record_request(labels, Duration::from_millis(300));
+		let stream = EndpointLabels::new(&Method::GET, "/game/events", StatusCode::OK, true);
+		metrics.record_request(stream, Duration::from_millis(2));
+
+		let rendered = metrics.render_prometheus();
+		let series = "area=\"game\",method=\"GET\",route=\"/game/buildings/{building_id}\",\
+			status_class=\"2xx\",auth=\"authenticated\"";
+		for line in [
+			format!("empire_http_request_duration_seconds_bucket{{{series},le=\"0.01\"}} 0"),
+			format!("empire_http_request_duration_seconds_bucket{{{series},le=\"0.025\"}} 1"),
+			format!("empire_http_request_duration_seconds_bucket{{{series},le=\"+Inf\"}} 2"),
+			format!("empire_http_request_duration_seconds_count{{{series}}} 2"),
+		] {
+			assert!(rendered.lines().any(|rendered| rendered == line), "{line}");
+		}
+		assert!(rendered.contains(
+			"empire_http_stream_ttfb_seconds_count{area=\"game\",method=\"GET\",route=\"/game/events\""
+		));
+		assert!(!rendered.contains(
+			"empire_http_request_duration_seconds_count{area=\"game\",method=\"GET\",route=\"/game/events\""
+		));
+	}
+
+	#[test]
+	fn active_players_are_counted_once() {
+		let metrics = ServerMetrics::default();
+		let now = Utc::now();
+		let (active, idle) = (uuid::Uuid::new_v4(), uuid::Uuid::new_v4());
+		metrics.record_activity_at(&active, now - TimeDelta::minutes(20));
+		metrics.record_activity_at(&active, now);
+		metrics.record_activity_at(&idle, now - TimeDelta::minutes(20));
+
+		assert_eq!(metrics.active_players(TimeDelta::minutes(15)), 1);
+		assert_eq!(metrics.active_players(TimeDelta::minutes(30)), 2);
+		assert!(metrics.is_active(&active, TimeDelta::minutes(5)));
+		assert!(!metrics.is_active(&idle, TimeDelta::minutes(5)));
+		assert!(!metrics.is_active(&uuid::Uuid::new_v4(), TimeDelta::minutes(5)));
+	}
+}
@@ -1,10 +1,31 @@
+pub mod account_deletion;
+pub mod alliance;
 pub mod app_state;
+pub mod audit;
 pub mod auth;
+pub mod backfill;
 pub mod building;
+pub mod caravan;
+pub mod chat;
+pub mod combat;
 pub mod error;
+pub mod events;
 pub mod factions;
+pub mod hero;
+pub mod item;
 pub mod jobs;
+pub mod leaderboard;
+pub mod limited_event;
+pub mod market;
+pub mod message;
+pub mod metrics;
 pub mod modifier;
+pub mod observer;
 pub mod player;
+pub mod quest;
 pub mod resource_generation;
+pub mod season;
+pub mod settlement;
+pub mod table_stats;
 pub mod unit;
+pub mod world_reset;
@@ -12,6 +12,7 @@ use diesel::{
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::domain::player::buildings::PlayerBuildingKey;
 use crate::domain::{modifier, player};
 use crate::schema::active_modifiers;
 
@@ -41,6 +42,7 @@ pub enum ModifierSourceType {
 	Skill,
 	Research,
 	Event,
+	Hero,
 }
 
 impl ToSql<crate::schema::sql_types::ModifierSourceType, Pg> for ModifierSourceType {
@@ -51,6 +53,7 @@ impl ToSql<crate::schema::sql_types::ModifierSourceType, Pg> for ModifierSourceT
 			ModifierSourceType::Skill => out.write_all(b"skill")?,
 			ModifierSourceType::Research => out.write_all(b"research")?,
 			ModifierSourceType::Event => out.write_all(b"event")?,
+			ModifierSourceType::Hero => out.write_all(b"hero")?,
 		}
 		Ok(IsNull::No)
 	}
@@ -64,6 +67,7 @@ impl FromSql<crate::schema::sql_types::ModifierSourceType, Pg> for ModifierSourc
 			b"skill" => Ok(ModifierSourceType::Skill),
 			b"research" => Ok(ModifierSourceType::Research),
 			b"event" => Ok(ModifierSourceType::Event),
+			b"hero" => Ok(ModifierSourceType::Hero),
 			_ => {
 				let unrecognized_value = String::from_utf8_lossy(bytes.as_bytes());
 				Err(format!("Unrecognized enum variant: {unrecognized_value}").into())
@@ -84,6 +88,8 @@ pub struct ActiveModifier {
 	pub source_id: Option<Uuid>,
 	pub created_at: DateTime<Utc>,
 	pub updated_at: DateTime<Utc>,
+	/// Building the modifier is scoped to, `None` for player-wide modifiers
+	pub player_building_id: Option<PlayerBuildingKey>,
 }
 
 #[derive(Insertable, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -95,6 +101,7 @@ pub struct NewActiveModifier {
 	pub expires_at: Option<DateTime<Utc>>,
 	pub source_type: ModifierSourceType,
 	pub source_id: Option<Uuid>,
+	pub player_building_id: Option<PlayerBuildingKey>,
 }
 
 #[derive(Identifiable, AsChangeset, Debug, PartialEq, Eq, PartialOrd, Ord)]
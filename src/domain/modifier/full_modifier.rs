@@ -10,6 +10,7 @@ use crate::domain::modifier::{
 	MagnitudeKind, Modifier, ModifierKey, ModifierTarget, StackingBehaviour,
 };
 use crate::domain::player;
+use crate::domain::player::buildings::PlayerBuildingKey;
 use crate::domain::player::resource::ResourceType;
 
 /// Represents a complete modifier instance that can be applied to player resources or attributes.
@@ -22,6 +23,8 @@ pub struct AppliedModifier {
 	pub modifier_id: ModifierKey,
 	/// ID of the player this modifier is applied to
 	pub player_id: player::PlayerKey,
+	/// Building this modifier is scoped to, `None` if it applies to the whole player
+	pub player_building_id: Option<PlayerBuildingKey>,
 
 	/// Display name of the modifier
 	pub name: String,
@@ -64,6 +67,16 @@ impl AppliedModifier {
 			self.target_type.to_string().to_lowercase()
 		)
 	}
+
+	/// The change the modifier makes to the base value of 1.0, negative for debuffs.
+	///
+	/// Multipliers change it by their magnitude minus one, so x0.8 is a bonus of -0.2.
+	pub fn bonus(&self) -> BigDecimal {
+		match self.magnitude_kind {
+			MagnitudeKind::Multiplier => &self.magnitude - BigDecimal::from(1),
+			MagnitudeKind::Percentage | MagnitudeKind::Flat => self.magnitude.clone(),
+		}
+	}
 }
 
 impl Modifier {
@@ -72,6 +85,7 @@ impl Modifier {
 			id: active.id,
 			modifier_id: self.id,
 			player_id: active.player_id,
+			player_building_id: active.player_building_id,
 			name: self.name,
 			description: self.description,
 			magnitude: self.magnitude,
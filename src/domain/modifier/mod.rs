@@ -93,6 +93,8 @@ pub enum ModifierTarget {
 	Combat,
 	Training,
 	Research,
+	/// Keeps the player out of combat while active, its magnitude is not used
+	Truce,
 }
 
 impl ToSql<crate::schema::sql_types::ModifierTarget, Pg> for ModifierTarget {
@@ -102,6 +104,7 @@ impl ToSql<crate::schema::sql_types::ModifierTarget, Pg> for ModifierTarget {
 			ModifierTarget::Combat => out.write_all(b"combat")?,
 			ModifierTarget::Training => out.write_all(b"training")?,
 			ModifierTarget::Research => out.write_all(b"research")?,
+			ModifierTarget::Truce => out.write_all(b"truce")?,
 		}
 		Ok(IsNull::No)
 	}
@@ -114,6 +117,7 @@ impl FromSql<crate::schema::sql_types::ModifierTarget, Pg> for ModifierTarget {
 			b"combat" => Ok(ModifierTarget::Combat),
 			b"training" => Ok(ModifierTarget::Training),
 			b"research" => Ok(ModifierTarget::Research),
+			b"truce" => Ok(ModifierTarget::Truce),
 			_ => {
 				let unrecognized_value = String::from_utf8_lossy(bytes.as_bytes());
 				Err(format!("Unrecognized enum variant: {unrecognized_value}").into())
@@ -185,6 +189,50 @@ pub struct Modifier {
 	pub updated_at: DateTime<Utc>,
 }
 
+impl Modifier {
+	/// Generates a short, human-readable summary of the modifier's effect, e.g.
+	/// `+15% wood production`.
+	///
+	/// The summary is derived from the magnitude, its kind and the target, so it always
+	/// matches the values the server calculates with, unlike the free-form `description`.
+	pub fn effect_summary(&self) -> String {
+		let value = match self.magnitude_kind {
+			MagnitudeKind::Percentage => {
+				let percent = (&self.magnitude * BigDecimal::from(100)).normalized();
+				format!("{}{}%", sign(&self.magnitude), percent.abs())
+			}
+			MagnitudeKind::Flat => {
+				format!(
+					"{}{}",
+					sign(&self.magnitude),
+					self.magnitude.normalized().abs()
+				)
+			}
+			MagnitudeKind::Multiplier => format!("x{}", self.magnitude.normalized()),
+		};
+		let target = match (self.target_type, self.target_resource) {
+			(ModifierTarget::Resource, Some(res)) => format!("{} production", res.as_str()),
+			(ModifierTarget::Resource, None) => "resource production".to_string(),
+			(ModifierTarget::Combat, _) => "combat strength".to_string(),
+			// Training and research multipliers scale durations
+			(ModifierTarget::Training, _) => "training time".to_string(),
+			(ModifierTarget::Research, _) => "research time".to_string(),
+			// A truce has no magnitude worth showing
+			(ModifierTarget::Truce, _) => return "truce, no attacks either way".to_string(),
+		};
+		format!("{value} {target}")
+	}
+}
+
+/// Returns the sign prefix of a magnitude.
+fn sign(magnitude: &BigDecimal) -> &'static str {
+	if magnitude.sign() == bigdecimal::num_bigint::Sign::Minus {
+		"-"
+	} else {
+		"+"
+	}
+}
+
 #[derive(Insertable, Debug, PartialEq, Eq, PartialOrd, Ord)]
 #[diesel(table_name = modifiers, check_for_backend(diesel::pg::Pg))]
 pub struct NewModifier {
@@ -211,3 +259,70 @@ pub struct UpdateModifier {
 	pub stacking_behaviour: Option<StackingBehaviour>,
 	pub stacking_group: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+	use std::str::FromStr;
+
+	use super::*;
+
+	fn modifier(
+		magnitude: &str,
+		magnitude_kind: MagnitudeKind,
+		target_type: ModifierTarget,
+		target_resource: Option<ResourceType>,
+	) -> Modifier {
+		Modifier {
+			id: Uuid::new_v4(),
+			name: "test_modifier".to_string(),
+			description: String::new(),
+			magnitude: BigDecimal::from_str(magnitude).unwrap(),
+			magnitude_kind,
+			target_type,
+			target_resource,
+			stacking_behaviour: StackingBehaviour::Additive,
+			stacking_group: None,
+			created_at: Utc::now(),
+			updated_at: Utc::now(),
+		}
+	}
+
+	#[test]
+	fn effect_summary_for_percentages() {
+		let m = modifier(
+			"0.15",
+			MagnitudeKind::Percentage,
+			ModifierTarget::Resource,
+			Some(ResourceType::Wood),
+		);
+		assert_eq!(m.effect_summary(), "+15% wood production");
+
+		let m = modifier(
+			"-0.20",
+			MagnitudeKind::Percentage,
+			ModifierTarget::Training,
+			None,
+		);
+		assert_eq!(m.effect_summary(), "-20% training time");
+	}
+
+	#[test]
+	fn effect_summary_for_flat_and_multiplier() {
+		let m = modifier("50", MagnitudeKind::Flat, ModifierTarget::Resource, None);
+		assert_eq!(m.effect_summary(), "+50 resource production");
+
+		let m = modifier(
+			"1.5",
+			MagnitudeKind::Multiplier,
+			ModifierTarget::Combat,
+			None,
+		);
+		assert_eq!(m.effect_summary(), "x1.5 combat strength");
+	}
+
+	#[test]
+	fn effect_summary_for_truces() {
+		let m = modifier("1", MagnitudeKind::Flat, ModifierTarget::Truce, None);
+		assert_eq!(m.effect_summary(), "truce, no attacks either way");
+	}
+}
@@ -83,6 +83,8 @@ pub struct ModifierHistory {
 	pub reason: Option<String>,
 	pub created_at: DateTime<Utc>,
 	pub updated_at: DateTime<Utc>,
+	/// Operator who granted or revoked the modifier through the admin API
+	pub operator: Option<String>,
 }
 
 #[derive(Insertable, Debug)]
@@ -96,4 +98,5 @@ pub struct NewModifierHistory {
 	pub source_id: Option<Uuid>,
 	pub previous_state: Option<JsonValue>,
 	pub reason: Option<String>,
+	pub operator: Option<String>,
 }
@@ -0,0 +1,145 @@
+//! Contains the read-only observer accounts used to cast community tournaments.
+//! Operators create observers, select the players each of them can watch and grant them
+//! scopes. Observers only see players that opted in, see
+//! [`crate::game::observer_operations`].
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use derive_more::Display;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::player::{Player, PlayerKey};
+use crate::schema::{observer, observer_player};
+
+/// Unique identifier for an observer
+pub type ObserverKey = Uuid;
+
+/// What an observer is allowed to see of the players they watch.
+#[derive(Serialize, Deserialize, Display, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ObserverScope {
+	/// The player's buildings and their levels
+	City,
+	/// The battle reports of the player
+	Battles,
+	/// The ranking of the watched players
+	Leaderboard,
+}
+
+impl ObserverScope {
+	/// Every scope an observer can be granted.
+	pub const ALL: [ObserverScope; 3] = [
+		ObserverScope::City,
+		ObserverScope::Battles,
+		ObserverScope::Leaderboard,
+	];
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ObserverScope::City => "city",
+			ObserverScope::Battles => "battles",
+			ObserverScope::Leaderboard => "leaderboard",
+		}
+	}
+}
+
+impl FromStr for ObserverScope {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"city" => Ok(ObserverScope::City),
+			"battles" => Ok(ObserverScope::Battles),
+			"leaderboard" => Ok(ObserverScope::Leaderboard),
+			other => Err(format!("Unknown observer scope: {other}")),
+		}
+	}
+}
+
+/// A read-only account for casting tournaments.
+#[derive(Queryable, Selectable, Identifiable, Clone, PartialEq, Eq)]
+#[diesel(table_name = observer, check_for_backend(diesel::pg::Pg))]
+pub struct Observer {
+	pub id: ObserverKey,
+	pub name: String,
+	/// Hash of the token the observer authenticates with
+	pub token_hash: String,
+	/// Granted [`ObserverScope`]s, unknown ones are ignored
+	pub scopes: Vec<String>,
+	/// Operator who created the observer
+	pub created_by: Option<String>,
+	/// End of the observer's access, `None` to keep it until revoked
+	pub expires_at: Option<DateTime<Utc>>,
+	pub revoked_at: Option<DateTime<Utc>>,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+impl fmt::Debug for Observer {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Observer")
+			.field("id", &self.id)
+			.field("name", &self.name)
+			.field("token_hash", &"[redacted]")
+			.field("scopes", &self.scopes)
+			.field("created_by", &self.created_by)
+			.field("expires_at", &self.expires_at)
+			.field("revoked_at", &self.revoked_at)
+			.finish()
+	}
+}
+
+impl Observer {
+	/// Whether the observer can still authenticate at `now`.
+	pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+		self.revoked_at.is_none() && self.expires_at.is_none_or(|expires_at| expires_at > now)
+	}
+
+	/// The scopes granted to the observer.
+	pub fn scopes(&self) -> Vec<ObserverScope> {
+		self.scopes
+			.iter()
+			.filter_map(|scope| scope.parse().ok())
+			.collect()
+	}
+
+	/// Whether the observer was granted `scope`.
+	pub fn has_scope(&self, scope: ObserverScope) -> bool {
+		self.scopes.iter().any(|granted| granted == scope.as_str())
+	}
+}
+
+/// Data transfer object for creating a new observer
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = observer, check_for_backend(diesel::pg::Pg))]
+pub struct NewObserver {
+	pub name: String,
+	pub token_hash: String,
+	pub scopes: Vec<String>,
+	pub created_by: Option<String>,
+	pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// A player an observer was selected to watch
+#[derive(Queryable, Selectable, Identifiable, Associations, Debug, Clone, PartialEq, Eq)]
+#[diesel(belongs_to(Observer))]
+#[diesel(belongs_to(Player))]
+#[diesel(primary_key(observer_id, player_id))]
+#[diesel(table_name = observer_player, check_for_backend(diesel::pg::Pg))]
+pub struct ObserverPlayer {
+	pub observer_id: ObserverKey,
+	pub player_id: PlayerKey,
+	pub created_at: DateTime<Utc>,
+}
+
+/// Data transfer object for selecting a player for an observer
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = observer_player, check_for_backend(diesel::pg::Pg))]
+pub struct NewObserverPlayer {
+	pub observer_id: ObserverKey,
+	pub player_id: PlayerKey,
+}
@@ -1,8 +1,10 @@
+use bigdecimal::BigDecimal;
 use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use uuid::Uuid;
 
 use crate::domain::player::{Player, PlayerKey};
+use crate::domain::settlement::{Settlement, SettlementKey};
 use crate::schema::player_accumulator;
 
 pub type AccumulatorKey = Uuid;
@@ -10,6 +12,7 @@ pub type AccumulatorKey = Uuid;
 #[derive(Queryable, Selectable, Identifiable, Associations, Debug, PartialEq)]
 #[diesel(table_name = player_accumulator, check_for_backend(diesel::pg::Pg))]
 #[diesel(belongs_to(Player))]
+#[diesel(belongs_to(Settlement))]
 pub struct PlayerAccumulator {
 	pub id: AccumulatorKey,
 	pub player_id: PlayerKey,
@@ -19,6 +22,12 @@ pub struct PlayerAccumulator {
 	pub gold: i64,
 	pub created_at: DateTime<Utc>,
 	pub updated_at: DateTime<Utc>,
+	/// Fractional production carried over to the next tick
+	pub food_remainder: BigDecimal,
+	pub wood_remainder: BigDecimal,
+	pub stone_remainder: BigDecimal,
+	pub gold_remainder: BigDecimal,
+	pub settlement_id: SettlementKey,
 }
 
 #[derive(Identifiable, AsChangeset, Debug, PartialEq)]
@@ -0,0 +1,35 @@
+//! Contains the per-day activity summary of a player.
+//! Activity is aggregated from the training queue, the building upgrade ledger and the
+//! battle reports, in UTC days.
+
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Date};
+use serde::{Deserialize, Serialize};
+
+/// What a player did during one UTC day.
+#[derive(QueryableByName, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyActivity {
+	#[diesel(sql_type = Date)]
+	pub day: NaiveDate,
+	/// Units whose training completed
+	#[diesel(sql_type = BigInt)]
+	pub units_trained: i64,
+	/// Building upgrades that were confirmed
+	#[diesel(sql_type = BigInt)]
+	pub buildings_upgraded: i64,
+	/// Battles the player attacked or defended in
+	#[diesel(sql_type = BigInt)]
+	pub battles_fought: i64,
+	#[diesel(sql_type = BigInt)]
+	pub battles_won: i64,
+	// Resources spent on trainings and upgrades started that day
+	#[diesel(sql_type = BigInt)]
+	pub food_spent: i64,
+	#[diesel(sql_type = BigInt)]
+	pub wood_spent: i64,
+	#[diesel(sql_type = BigInt)]
+	pub stone_spent: i64,
+	#[diesel(sql_type = BigInt)]
+	pub gold_spent: i64,
+}
@@ -0,0 +1,111 @@
+//! API keys players create for server-to-server integrations like bots and companion apps.
+//! Keys authenticate as their player, limited to what their [`ApiKeyScope`] allows, see
+//! [`crate::auth::api_key_operations`].
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use derive_more::Display;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::player::{Player, PlayerKey};
+use crate::schema::api_key;
+
+/// Unique identifier for an API key
+pub type ApiKeyKey = Uuid;
+
+/// What requests authenticated with an API key may do.
+#[derive(Serialize, Deserialize, Display, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyScope {
+	/// Only requests that read, like `GET`
+	ReadOnly,
+	/// Every request the player could make, except managing API keys
+	Full,
+}
+
+impl ApiKeyScope {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ApiKeyScope::ReadOnly => "read_only",
+			ApiKeyScope::Full => "full",
+		}
+	}
+}
+
+impl FromStr for ApiKeyScope {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"read_only" => Ok(ApiKeyScope::ReadOnly),
+			"full" => Ok(ApiKeyScope::Full),
+			other => Err(format!("Unknown API key scope: {other}")),
+		}
+	}
+}
+
+/// A key a player authenticates their integrations with.
+#[derive(Queryable, Selectable, Identifiable, Associations, Clone, PartialEq, Eq)]
+#[diesel(belongs_to(Player))]
+#[diesel(table_name = api_key, check_for_backend(diesel::pg::Pg))]
+pub struct ApiKey {
+	pub id: ApiKeyKey,
+	pub player_id: PlayerKey,
+	pub name: String,
+	/// Hash of the key
+	pub key_hash: String,
+	/// Start of the key, to tell keys apart
+	pub key_prefix: String,
+	/// The key's [`ApiKeyScope`], kept by the database check to the known ones
+	pub scope: String,
+	/// Last time the key authenticated a request, updated at most once a minute
+	pub last_used_at: Option<DateTime<Utc>>,
+	/// End of the key's validity, `None` to keep it until revoked
+	pub expires_at: Option<DateTime<Utc>>,
+	pub revoked_at: Option<DateTime<Utc>>,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+impl fmt::Debug for ApiKey {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("ApiKey")
+			.field("id", &self.id)
+			.field("player_id", &self.player_id)
+			.field("name", &self.name)
+			.field("key_hash", &"[redacted]")
+			.field("key_prefix", &self.key_prefix)
+			.field("scope", &self.scope)
+			.field("expires_at", &self.expires_at)
+			.field("revoked_at", &self.revoked_at)
+			.finish()
+	}
+}
+
+impl ApiKey {
+	/// Whether the key can still authenticate at `now`.
+	pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+		self.revoked_at.is_none() && self.expires_at.is_none_or(|expires_at| expires_at > now)
+	}
+
+	/// The key's scope, read-only if it is not a known one.
+	pub fn scope(&self) -> ApiKeyScope {
+		self.scope.parse().unwrap_or(ApiKeyScope::ReadOnly)
+	}
+}
+
+/// Data transfer object for creating a new API key
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = api_key, check_for_backend(diesel::pg::Pg))]
+pub struct NewApiKey {
+	pub player_id: PlayerKey,
+	pub name: String,
+	pub key_hash: String,
+	pub key_prefix: String,
+	pub scope: String,
+	pub expires_at: Option<DateTime<Utc>>,
+}
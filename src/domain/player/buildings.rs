@@ -4,6 +4,8 @@ use uuid::Uuid;
 
 use crate::domain::building::Building;
 use crate::domain::player::{Player, PlayerKey};
+use crate::domain::settlement::{Settlement, SettlementKey};
+use crate::domain::unit::training::TrainingMode;
 use crate::schema::player_building;
 
 pub type PlayerBuildingKey = Uuid;
@@ -11,15 +13,19 @@ pub type PlayerBuildingKey = Uuid;
 #[derive(Identifiable, Queryable, Selectable, Associations, Debug)]
 #[diesel(belongs_to(Player))]
 #[diesel(belongs_to(Building))]
+#[diesel(belongs_to(Settlement))]
 #[diesel(table_name = player_building, check_for_backend(diesel::pg::Pg))]
 pub struct PlayerBuilding {
 	pub id: PlayerBuildingKey,
 	pub player_id: PlayerKey,
 	pub building_id: i32,
 	pub level: i32,
-	pub upgrade_finishes_at: Option<String>,
 	pub created_at: DateTime<Utc>,
 	pub updated_at: DateTime<Utc>,
+	pub upgrade_finishes_at: Option<DateTime<Utc>>,
+	pub settlement_id: SettlementKey,
+	/// How the building works through its training queue
+	pub training_mode: TrainingMode,
 }
 
 #[derive(Insertable, Debug, Clone, PartialEq, Eq, Hash)]
@@ -28,7 +34,9 @@ pub struct NewPlayerBuilding {
 	pub player_id: PlayerKey,
 	pub building_id: i32,
 	pub level: Option<i32>,
-	pub upgrade_finishes_at: Option<String>,
+	pub upgrade_finishes_at: Option<DateTime<Utc>>,
+	/// Settlement the building is constructed in, `None` for the player's capital
+	pub settlement_id: Option<SettlementKey>,
 }
 
 #[derive(Identifiable, AsChangeset, Debug, Clone, PartialEq, Eq, Hash)]
@@ -36,5 +44,5 @@ pub struct NewPlayerBuilding {
 pub struct UpdatePlayerBuilding {
 	pub id: PlayerBuildingKey,
 	pub level: Option<i32>,
-	pub upgrade_finishes_at: Option<String>,
+	pub upgrade_finishes_at: Option<DateTime<Utc>>,
 }
@@ -0,0 +1,37 @@
+//! Contains the history of faction changes of players.
+//! The first choice of a faction is not recorded, only changes between playable factions.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::domain::factions::FactionCode;
+use crate::domain::player::{Player, PlayerKey};
+use crate::schema::faction_change;
+
+/// Unique identifier for a faction change
+pub type FactionChangeKey = Uuid;
+
+/// A player's move from one faction to another.
+#[derive(
+	Queryable, Selectable, Identifiable, Associations, Serialize, Debug, Clone, PartialEq, Eq,
+)]
+#[diesel(belongs_to(Player))]
+#[diesel(table_name = faction_change, check_for_backend(diesel::pg::Pg))]
+pub struct FactionChange {
+	pub id: FactionChangeKey,
+	pub player_id: PlayerKey,
+	pub from_faction: FactionCode,
+	pub to_faction: FactionCode,
+	pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = faction_change, check_for_backend(diesel::pg::Pg))]
+pub struct NewFactionChange {
+	pub player_id: PlayerKey,
+	pub from_faction: FactionCode,
+	pub to_faction: FactionCode,
+	pub changed_at: DateTime<Utc>,
+}
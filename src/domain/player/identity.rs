@@ -0,0 +1,72 @@
+//! Accounts of players at external identity providers, which players log in with instead of
+//! a password, see [`crate::auth::oauth_operations`].
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use derive_more::Display;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::player::{Player, PlayerKey};
+use crate::schema::player_identity;
+
+/// Unique identifier for an identity
+pub type PlayerIdentityKey = Uuid;
+
+/// An external identity provider players can log in with.
+#[derive(Serialize, Deserialize, Display, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum OAuthProvider {
+	Google,
+	Discord,
+}
+
+impl OAuthProvider {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			OAuthProvider::Google => "google",
+			OAuthProvider::Discord => "discord",
+		}
+	}
+}
+
+impl FromStr for OAuthProvider {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"google" => Ok(OAuthProvider::Google),
+			"discord" => Ok(OAuthProvider::Discord),
+			other => Err(format!("Unknown OAuth provider: {other}")),
+		}
+	}
+}
+
+/// The account of a player at an identity provider.
+#[derive(Queryable, Selectable, Identifiable, Associations, Debug, Clone, PartialEq, Eq)]
+#[diesel(belongs_to(Player))]
+#[diesel(table_name = player_identity, check_for_backend(diesel::pg::Pg))]
+pub struct PlayerIdentity {
+	pub id: PlayerIdentityKey,
+	pub player_id: PlayerKey,
+	/// The identity's [`OAuthProvider`], kept by the database check to the known ones
+	pub provider: String,
+	/// Identifier of the account at the provider
+	pub subject: String,
+	/// Email address the provider verified, as of the last login
+	pub email: Option<String>,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+/// Data transfer object for linking an identity to a player
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = player_identity, check_for_backend(diesel::pg::Pg))]
+pub struct NewPlayerIdentity {
+	pub player_id: PlayerKey,
+	pub provider: String,
+	pub subject: String,
+	pub email: Option<String>,
+}
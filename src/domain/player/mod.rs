@@ -1,7 +1,15 @@
 pub mod accumulator;
+pub mod activity;
+pub mod api_key;
 pub mod buildings;
+pub mod faction_change;
+pub mod identity;
+pub mod planned_action;
+pub mod privacy;
+pub mod relationship;
 pub mod resource;
 pub mod resource_snapshot;
+pub mod role;
 pub mod session;
 mod user_email;
 mod user_name;
@@ -15,6 +23,7 @@ pub use user_name::UserName;
 use uuid::Uuid;
 
 use crate::domain::factions::FactionCode;
+use crate::domain::player::role::PlayerRole;
 use crate::schema::player;
 
 /// User Primary Key
@@ -30,6 +39,17 @@ pub struct Player {
 	pub faction: FactionCode,
 	pub created_at: DateTime<Utc>,
 	pub updated_at: DateTime<Utc>,
+	/// End of the beginner shield, `None` once it expired or was dropped
+	pub protected_until: Option<DateTime<Utc>>,
+	pub role: PlayerRole,
+	/// When the account is anonymized, `None` unless the player asked for its deletion
+	pub deletion_scheduled_for: Option<DateTime<Utc>>,
+	/// When the account was anonymized, `None` for live accounts
+	pub anonymized_at: Option<DateTime<Utc>>,
+	/// When the current email address was verified, `None` until it is
+	pub email_verified_at: Option<DateTime<Utc>>,
+	/// When the password was last changed, `None` if it never was
+	pub password_changed_at: Option<DateTime<Utc>>,
 }
 
 impl fmt::Debug for Player {
@@ -40,6 +60,12 @@ impl fmt::Debug for Player {
 			.field("password", &"[redacted]")
 			.field("email", &self.email)
 			.field("faction", &self.faction)
+			.field("protected_until", &self.protected_until)
+			.field("role", &self.role)
+			.field("deletion_scheduled_for", &self.deletion_scheduled_for)
+			.field("anonymized_at", &self.anonymized_at)
+			.field("email_verified_at", &self.email_verified_at)
+			.field("password_changed_at", &self.password_changed_at)
 			.finish()
 	}
 }
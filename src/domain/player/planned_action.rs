@@ -0,0 +1,163 @@
+//! Contains domain entities for the planned action queue.
+//! Planned actions are construction or upgrade orders that the player queues up
+//! in advance, to be executed automatically once they become affordable.
+
+use std::io::Write;
+use std::str::from_utf8;
+
+use chrono::{DateTime, Utc};
+use diesel::deserialize::FromSql;
+use diesel::pg::{Pg, PgValue};
+use diesel::prelude::*;
+use diesel::serialize::{IsNull, Output, ToSql};
+use diesel::{AsExpression, FromSqlRow, deserialize, serialize};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::building::BuildingKey;
+use crate::domain::player::buildings::PlayerBuildingKey;
+use crate::domain::player::{Player, PlayerKey};
+use crate::domain::settlement::SettlementKey;
+use crate::schema::planned_action;
+
+/// Unique identifier for a planned action
+pub type PlannedActionKey = Uuid;
+
+/// The kind of action a plan will perform when executed
+#[derive(
+	AsExpression,
+	FromSqlRow,
+	Serialize,
+	Deserialize,
+	Debug,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	Hash,
+	PartialOrd,
+	Ord,
+)]
+#[diesel(sql_type = crate::schema::sql_types::PlannedActionKind)]
+#[serde(rename_all = "snake_case")]
+pub enum PlannedActionKind {
+	/// Construct a new building of the given type
+	Construct,
+	/// Upgrade an existing player building by one level
+	Upgrade,
+}
+
+impl AsRef<str> for PlannedActionKind {
+	fn as_ref(&self) -> &str {
+		match self {
+			PlannedActionKind::Construct => "construct",
+			PlannedActionKind::Upgrade => "upgrade",
+		}
+	}
+}
+
+impl ToSql<crate::schema::sql_types::PlannedActionKind, Pg> for PlannedActionKind {
+	fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+		out.write_all(self.as_ref().as_bytes())?;
+		Ok(IsNull::No)
+	}
+}
+
+impl FromSql<crate::schema::sql_types::PlannedActionKind, Pg> for PlannedActionKind {
+	fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+		match from_utf8(bytes.as_bytes())? {
+			"construct" => Ok(PlannedActionKind::Construct),
+			"upgrade" => Ok(PlannedActionKind::Upgrade),
+			other => Err(format!("Unrecognized enum variant: {other}").into()),
+		}
+	}
+}
+
+/// Lifecycle state of a planned action
+#[derive(
+	AsExpression,
+	FromSqlRow,
+	Serialize,
+	Deserialize,
+	Debug,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	Hash,
+	PartialOrd,
+	Ord,
+)]
+#[diesel(sql_type = crate::schema::sql_types::PlannedActionStatus)]
+#[serde(rename_all = "snake_case")]
+pub enum PlannedActionStatus {
+	/// Waiting to be executed
+	Pending,
+	/// Executed successfully
+	Completed,
+	/// Removed by the player before it could be executed
+	Cancelled,
+}
+
+impl AsRef<str> for PlannedActionStatus {
+	fn as_ref(&self) -> &str {
+		match self {
+			PlannedActionStatus::Pending => "pending",
+			PlannedActionStatus::Completed => "completed",
+			PlannedActionStatus::Cancelled => "cancelled",
+		}
+	}
+}
+
+impl ToSql<crate::schema::sql_types::PlannedActionStatus, Pg> for PlannedActionStatus {
+	fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+		out.write_all(self.as_ref().as_bytes())?;
+		Ok(IsNull::No)
+	}
+}
+
+impl FromSql<crate::schema::sql_types::PlannedActionStatus, Pg> for PlannedActionStatus {
+	fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+		match from_utf8(bytes.as_bytes())? {
+			"pending" => Ok(PlannedActionStatus::Pending),
+			"completed" => Ok(PlannedActionStatus::Completed),
+			"cancelled" => Ok(PlannedActionStatus::Cancelled),
+			other => Err(format!("Unrecognized enum variant: {other}").into()),
+		}
+	}
+}
+
+/// Represents an action queued by a player for later execution
+#[derive(
+	Queryable, Selectable, Identifiable, Associations, Serialize, Debug, Clone, PartialEq, Eq,
+)]
+#[diesel(belongs_to(Player))]
+#[diesel(table_name = planned_action, check_for_backend(diesel::pg::Pg))]
+pub struct PlannedAction {
+	pub id: PlannedActionKey,
+	pub player_id: PlayerKey,
+	pub action: PlannedActionKind,
+	/// Building type to construct, set for [`PlannedActionKind::Construct`]
+	pub building_id: Option<BuildingKey>,
+	/// Player building to upgrade, set for [`PlannedActionKind::Upgrade`]
+	pub player_building_id: Option<PlayerBuildingKey>,
+	pub status: PlannedActionStatus,
+	/// Reason the last execution attempt did not go through, if any
+	pub last_error: Option<String>,
+	pub executed_at: Option<DateTime<Utc>>,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+	/// Settlement to construct in, the capital if it is `None`
+	pub settlement_id: Option<SettlementKey>,
+}
+
+/// Data transfer object for creating a new planned action
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = planned_action, check_for_backend(diesel::pg::Pg))]
+pub struct NewPlannedAction {
+	pub player_id: PlayerKey,
+	pub action: PlannedActionKind,
+	pub building_id: Option<BuildingKey>,
+	pub player_building_id: Option<PlayerBuildingKey>,
+	pub settlement_id: Option<SettlementKey>,
+}
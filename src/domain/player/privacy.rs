@@ -0,0 +1,55 @@
+//! Contains what a player hides from other players.
+//! Every player has a privacy row, created along with the player with everything visible.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::domain::player::{Player, PlayerKey};
+use crate::schema::player_privacy;
+
+/// A player's privacy settings.
+///
+/// AIDEV-NOTE: anything serializing a player for someone else (public profiles, search,
+/// leaderboards) must leave out what these flags hide. The player always sees everything.
+/// Tournament observers only see players that opted in with `allow_observers`.
+#[derive(
+	Queryable, Selectable, Identifiable, Associations, Serialize, Debug, Clone, PartialEq, Eq,
+)]
+#[diesel(belongs_to(Player))]
+#[diesel(primary_key(player_id))]
+#[diesel(table_name = player_privacy, check_for_backend(diesel::pg::Pg))]
+pub struct PlayerPrivacy {
+	pub player_id: PlayerKey,
+	/// Whether others can see if the player is online
+	pub hide_online_status: bool,
+	/// Whether others can see the player's power, buildings and battle record
+	pub hide_stats: bool,
+	/// Whether others can see the player's alliance
+	pub hide_alliance: bool,
+	/// Whether tournament observers selected to watch the player can see their city,
+	/// battles and leaderboard rank
+	pub allow_observers: bool,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+/// Changes to a player's privacy settings, `None` keeps a setting as it is.
+#[derive(AsChangeset, Debug, Clone, Default, PartialEq, Eq)]
+#[diesel(table_name = player_privacy, check_for_backend(diesel::pg::Pg))]
+pub struct UpdatePlayerPrivacy {
+	pub hide_online_status: Option<bool>,
+	pub hide_stats: Option<bool>,
+	pub hide_alliance: Option<bool>,
+	pub allow_observers: Option<bool>,
+}
+
+impl UpdatePlayerPrivacy {
+	/// Whether the changeset leaves every setting as it is.
+	pub fn is_empty(&self) -> bool {
+		self.hide_online_status.is_none()
+			&& self.hide_stats.is_none()
+			&& self.hide_alliance.is_none()
+			&& self.allow_observers.is_none()
+	}
+}
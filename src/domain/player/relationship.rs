@@ -0,0 +1,109 @@
+//! Contains the relationships between players.
+//! A relationship points from the player who started it to the other player: a friend
+//! request, the friendship it turns into once the other player accepts it, or a block.
+//! Blocked players cannot mail, attack or befriend each other, whoever of the two blocked.
+
+use std::io::Write;
+use std::str::from_utf8;
+
+use chrono::{DateTime, Utc};
+use derive_more::Display;
+use diesel::deserialize::FromSql;
+use diesel::pg::{Pg, PgValue};
+use diesel::prelude::*;
+use diesel::serialize::{IsNull, Output, ToSql};
+use diesel::{AsExpression, FromSqlRow, deserialize, serialize};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::player::PlayerKey;
+use crate::schema::player_relationship;
+
+/// Unique identifier for a relationship between two players
+pub type PlayerRelationshipKey = Uuid;
+
+/// Where a relationship stands.
+#[derive(
+	AsExpression,
+	FromSqlRow,
+	Serialize,
+	Deserialize,
+	Display,
+	Debug,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	Hash,
+)]
+#[diesel(sql_type = crate::schema::sql_types::RelationshipStatus)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationshipStatus {
+	/// The player asked the other player to be friends
+	Requested,
+	/// The other player accepted, both are friends
+	Accepted,
+	/// The player blocked the other player
+	Blocked,
+}
+
+impl AsRef<str> for RelationshipStatus {
+	fn as_ref(&self) -> &str {
+		match self {
+			RelationshipStatus::Requested => "requested",
+			RelationshipStatus::Accepted => "accepted",
+			RelationshipStatus::Blocked => "blocked",
+		}
+	}
+}
+
+impl ToSql<crate::schema::sql_types::RelationshipStatus, Pg> for RelationshipStatus {
+	fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+		out.write_all(self.as_ref().as_bytes())?;
+		Ok(IsNull::No)
+	}
+}
+
+impl FromSql<crate::schema::sql_types::RelationshipStatus, Pg> for RelationshipStatus {
+	fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+		match from_utf8(bytes.as_bytes())? {
+			"requested" => Ok(RelationshipStatus::Requested),
+			"accepted" => Ok(RelationshipStatus::Accepted),
+			"blocked" => Ok(RelationshipStatus::Blocked),
+			other => Err(format!("Unrecognized enum variant: {other}").into()),
+		}
+	}
+}
+
+/// Represents a relationship from one player to another
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = player_relationship, check_for_backend(diesel::pg::Pg))]
+pub struct PlayerRelationship {
+	pub id: PlayerRelationshipKey,
+	/// Player who sent the friend request or blocked the other player
+	pub player_id: PlayerKey,
+	pub other_id: PlayerKey,
+	pub status: RelationshipStatus,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+impl PlayerRelationship {
+	/// The player on the other end of the relationship, seen from `player_id`.
+	pub fn counterpart(&self, player_id: &PlayerKey) -> PlayerKey {
+		if self.player_id == *player_id {
+			self.other_id
+		} else {
+			self.player_id
+		}
+	}
+}
+
+/// Data transfer object for starting a relationship
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = player_relationship, check_for_backend(diesel::pg::Pg))]
+pub struct NewPlayerRelationship {
+	pub player_id: PlayerKey,
+	pub other_id: PlayerKey,
+	pub status: RelationshipStatus,
+}
@@ -12,6 +12,7 @@ use strum_macros::EnumIter;
 use uuid::Uuid;
 
 use crate::domain::player::{Player, PlayerKey};
+use crate::domain::settlement::{Settlement, SettlementKey};
 use crate::schema::player_resource;
 
 /// `PlayerResourceKey` is a type alias for `Uuid`.
@@ -77,10 +78,21 @@ impl FromSql<crate::schema::sql_types::ResourceType, Pg> for ResourceType {
 }
 
 #[derive(
-	Queryable, Selectable, Identifiable, Associations, Debug, Clone, PartialEq, Eq, PartialOrd, Ord,
+	Queryable,
+	QueryableByName,
+	Selectable,
+	Identifiable,
+	Associations,
+	Debug,
+	Clone,
+	PartialEq,
+	Eq,
+	PartialOrd,
+	Ord,
 )]
 #[diesel(table_name = player_resource)]
 #[diesel(belongs_to(Player))]
+#[diesel(belongs_to(Settlement))]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct PlayerResource {
 	pub id: PlayerResourceKey,
@@ -97,12 +109,15 @@ pub struct PlayerResource {
 	pub collected_at: DateTime<Utc>,
 	pub created_at: DateTime<Utc>,
 	pub updated_at: DateTime<Utc>,
+	pub settlement_id: SettlementKey,
 }
 
 #[derive(Insertable, AsChangeset, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 #[diesel(table_name = player_resource, check_for_backend(diesel::pg::Pg))]
 pub struct NewPlayerResource {
 	pub player_id: PlayerKey,
+	/// Settlement the storage belongs to, `None` for the player's capital
+	pub settlement_id: Option<SettlementKey>,
 	pub food: Option<i64>,
 	pub wood: Option<i64>,
 	pub stone: Option<i64>,
@@ -6,6 +6,7 @@ use chrono::{DateTime, Utc};
 /// - `player_resource` table (current storage amounts and caps)
 /// - `player_accumulator` table (accumulated resources awaiting collection)
 /// - `resource_generation` view (production rates and accumulator caps)
+/// - `player_unit` and `training_queue` tables (population and food upkeep of units)
 ///
 /// Used to provide a complete picture of a player's resources at a point in time.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -40,6 +41,11 @@ pub struct PlayerResourceSnapshot {
 	pub stone_acc_cap: i64,
 	pub gold_acc_cap: i64,
 
+	// Population and upkeep of units (food per hour)
+	pub population: i64,
+	pub population_cap: i64,
+	pub food_upkeep: i64,
+
 	// Timestamps
 	pub produced_at: DateTime<Utc>,
 	pub collected_at: DateTime<Utc>,
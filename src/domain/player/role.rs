@@ -0,0 +1,69 @@
+use std::io::Write;
+use std::str::from_utf8;
+
+use derive_more::Display;
+use diesel::deserialize::FromSql;
+use diesel::pg::{Pg, PgValue};
+use diesel::serialize::{IsNull, Output, ToSql};
+use diesel::{AsExpression, FromSqlRow, deserialize, serialize};
+use serde::{Deserialize, Serialize};
+
+/// What a player is allowed to do beyond playing.
+///
+/// Roles are ordered, every role can do what the roles before it can, see
+/// [`require_role`](crate::net::require_role).
+#[derive(
+	AsExpression,
+	FromSqlRow,
+	Serialize,
+	Deserialize,
+	Display,
+	Debug,
+	Default,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	Hash,
+	PartialOrd,
+	Ord,
+)]
+#[diesel(sql_type = crate::schema::sql_types::PlayerRole)]
+#[serde(rename_all = "snake_case")]
+pub enum PlayerRole {
+	/// Every player starts out as a regular player
+	#[default]
+	Player,
+	/// Can look up the accounts of other players
+	Moderator,
+	/// Can manage the accounts of other players
+	Admin,
+}
+
+impl AsRef<str> for PlayerRole {
+	fn as_ref(&self) -> &str {
+		match self {
+			PlayerRole::Player => "player",
+			PlayerRole::Moderator => "moderator",
+			PlayerRole::Admin => "admin",
+		}
+	}
+}
+
+impl ToSql<crate::schema::sql_types::PlayerRole, Pg> for PlayerRole {
+	fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+		out.write_all(self.as_ref().as_bytes())?;
+		Ok(IsNull::No)
+	}
+}
+
+impl FromSql<crate::schema::sql_types::PlayerRole, Pg> for PlayerRole {
+	fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+		match from_utf8(bytes.as_bytes())? {
+			"player" => Ok(PlayerRole::Player),
+			"moderator" => Ok(PlayerRole::Moderator),
+			"admin" => Ok(PlayerRole::Admin),
+			other => Err(format!("Unrecognized enum variant: {other}").into()),
+		}
+	}
+}
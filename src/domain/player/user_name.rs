@@ -17,6 +17,9 @@ use crate::{ErrorKind, Result};
 pub struct UserName(String);
 
 impl UserName {
+	/// Characters a username must not contain.
+	pub const FORBIDDEN_CHARACTERS: [char; 9] = ['/', '(', ')', '"', '<', '>', '\\', '{', '}'];
+
 	/// Attempts to create a new `Username` from a string, validating the input.
 	///
 	/// # Arguments
@@ -42,8 +45,8 @@ impl UserName {
 		// the recommended one.
 		let is_too_long = s.graphemes(true).count() > 256;
 
-		let forbidden_characters = ['/', '(', ')', '"', '<', '>', '\\', '{', '}'];
-		let contains_forbidden_characters = s.chars().any(|g| forbidden_characters.contains(&g));
+		let contains_forbidden_characters =
+			s.chars().any(|g| Self::FORBIDDEN_CHARACTERS.contains(&g));
 
 		if is_empty_or_whitespace || is_too_long || contains_forbidden_characters {
 			Err((ErrorKind::InvalidUsername, "Invalid username").into())
@@ -0,0 +1,140 @@
+//! Contains domain entities for quests.
+//! Quests guide new players through the game, like building their first Farm. They are game
+//! data seeded into the `quest` table, and players claim a quest's rewards once they reach
+//! its objective. See [`crate::game::quests`].
+
+use std::io::Write;
+use std::str::from_utf8;
+
+use chrono::{DateTime, Utc};
+use derive_more::Display;
+use diesel::deserialize::FromSql;
+use diesel::pg::{Pg, PgValue};
+use diesel::prelude::*;
+use diesel::serialize::{IsNull, Output, ToSql};
+use diesel::{AsExpression, FromSqlRow, deserialize, serialize};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::item::ItemKey;
+use crate::domain::player::{Player, PlayerKey};
+use crate::schema::{player_quest, quest};
+
+/// Unique identifier for a quest, like `build_farm`
+pub type QuestKey = String;
+
+/// What players do to progress a quest, and what its target names.
+#[derive(
+	AsExpression,
+	FromSqlRow,
+	Serialize,
+	Deserialize,
+	Display,
+	Debug,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	Hash,
+)]
+#[diesel(sql_type = crate::schema::sql_types::QuestObjective)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestObjective {
+	/// A building of the target name reaches the amount as its level
+	Build,
+	/// The amount of units of the target name finish training
+	Train,
+	/// The capital stores the amount of the target resource
+	Collect,
+}
+
+impl QuestObjective {
+	/// Helper function to get the string representation of the enum variant.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Self::Build => "build",
+			Self::Train => "train",
+			Self::Collect => "collect",
+		}
+	}
+}
+
+impl ToSql<crate::schema::sql_types::QuestObjective, Pg> for QuestObjective {
+	fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+		out.write_all(self.as_str().as_bytes())?;
+		Ok(IsNull::No)
+	}
+}
+
+impl FromSql<crate::schema::sql_types::QuestObjective, Pg> for QuestObjective {
+	fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+		match from_utf8(bytes.as_bytes())? {
+			"build" => Ok(Self::Build),
+			"train" => Ok(Self::Train),
+			"collect" => Ok(Self::Collect),
+			other => Err(format!("Unrecognized enum variant: {other}").into()),
+		}
+	}
+}
+
+/// Represents the definition of a quest
+#[derive(Queryable, Selectable, Identifiable, Serialize, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = quest, check_for_backend(diesel::pg::Pg))]
+pub struct Quest {
+	pub id: QuestKey,
+	pub name: String,
+	pub description: String,
+	/// Order of the quest in the quest log
+	pub position: i32,
+	/// Quests that have to be claimed first
+	pub requires: Vec<String>,
+	pub objective: QuestObjective,
+	/// Building, unit or resource the objective is about
+	pub target: String,
+	/// Level, quantity or stored amount the objective asks for
+	pub amount: i64,
+	pub reward_food: i64,
+	pub reward_wood: i64,
+	pub reward_stone: i64,
+	pub reward_gold: i64,
+	pub reward_item_id: Option<ItemKey>,
+	pub reward_item_quantity: i64,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+impl Quest {
+	/// Resources rewarded for the quest, as a food, wood, stone and gold delta.
+	pub fn reward_resources(&self) -> (i64, i64, i64, i64) {
+		(
+			self.reward_food,
+			self.reward_wood,
+			self.reward_stone,
+			self.reward_gold,
+		)
+	}
+}
+
+/// Represents the progress of a player towards a quest they unlocked
+#[derive(
+	Queryable, Selectable, Identifiable, Associations, Serialize, Debug, Clone, PartialEq, Eq,
+)]
+#[diesel(belongs_to(Player))]
+#[diesel(belongs_to(Quest))]
+#[diesel(table_name = player_quest, primary_key(player_id, quest_id), check_for_backend(diesel::pg::Pg))]
+pub struct PlayerQuest {
+	pub player_id: PlayerKey,
+	pub quest_id: QuestKey,
+	/// Progress towards the amount of the objective, the highest level or stored amount
+	/// reached for building and collecting objectives
+	pub progress: i64,
+	pub claimed_at: Option<DateTime<Utc>>,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+impl PlayerQuest {
+	/// Whether the rewards of the quest were claimed.
+	pub fn is_claimed(&self) -> bool {
+		self.claimed_at.is_some()
+	}
+}
@@ -2,11 +2,13 @@ use diesel::{Identifiable, Queryable, Selectable};
 
 use crate::custom_schema::resource_generation;
 use crate::domain::player;
+use crate::domain::settlement::SettlementKey;
 
 #[derive(Queryable, Selectable, Identifiable)]
 #[diesel(table_name = resource_generation, check_for_backend(diesel::pg::Pg))]
-#[diesel(belongs_to(Player), primary_key(player_id))]
+#[diesel(belongs_to(Player), primary_key(settlement_id))]
 pub struct ResourceGeneration {
+	pub settlement_id: SettlementKey,
 	pub player_id: player::PlayerKey,
 	pub population: i64,
 	/// Food per hour
@@ -0,0 +1,86 @@
+//! Contains domain entities for seasons.
+//! The world is played in seasons. Once a season ends, its final rankings are archived and
+//! the ranked players earn legacy points, which they keep across seasons and world resets.
+//! See [`crate::game::seasons`].
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::leaderboard::LeaderboardCategory;
+use crate::domain::player::{Player, PlayerKey};
+use crate::schema::{player_legacy, season, season_standing};
+
+/// Unique identifier for a season
+pub type SeasonKey = Uuid;
+
+/// Represents a season of the world
+#[derive(Queryable, Selectable, Identifiable, Serialize, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = season, check_for_backend(diesel::pg::Pg))]
+pub struct Season {
+	pub id: SeasonKey,
+	/// 1-based number of the season, counting up
+	pub number: i32,
+	pub name: String,
+	pub starts_at: DateTime<Utc>,
+	pub ends_at: DateTime<Utc>,
+	/// When the standings were archived, `None` until the season is ended
+	pub ended_at: Option<DateTime<Utc>>,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+impl Season {
+	/// Whether the season is being played at `now`.
+	pub fn is_running(&self, now: DateTime<Utc>) -> bool {
+		self.ended_at.is_none() && self.starts_at <= now && now < self.ends_at
+	}
+}
+
+/// Data transfer object for creating a new season
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = season, check_for_backend(diesel::pg::Pg))]
+pub struct NewSeason {
+	pub number: i32,
+	pub name: String,
+	pub starts_at: DateTime<Utc>,
+	pub ends_at: DateTime<Utc>,
+}
+
+/// Represents the final rank of a player or alliance on a board of an ended season
+#[derive(Queryable, Selectable, Associations, Debug, Clone, PartialEq, Eq)]
+#[diesel(belongs_to(Season))]
+#[diesel(table_name = season_standing, check_for_backend(diesel::pg::Pg))]
+pub struct SeasonStanding {
+	pub season_id: SeasonKey,
+	pub category: LeaderboardCategory,
+	/// The ranked player, or alliance on the alliance board
+	pub subject_id: Uuid,
+	/// Name of the player or alliance at the end of the season
+	pub name: String,
+	pub rank: i32,
+	pub score: i64,
+	/// Legacy points the rank earned, always 0 on the alliance board
+	pub legacy_points: i64,
+}
+
+/// Represents the legacy points a player earned over all seasons
+#[derive(Queryable, Selectable, Identifiable, Associations, Debug, Clone, PartialEq, Eq)]
+#[diesel(belongs_to(Player))]
+#[diesel(table_name = player_legacy, primary_key(player_id), check_for_backend(diesel::pg::Pg))]
+pub struct PlayerLegacy {
+	pub player_id: PlayerKey,
+	pub legacy_points: i64,
+	/// Number of seasons the player was ranked in
+	pub seasons: i32,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+/// Payload of a [`crate::domain::jobs::JobType::Season`] job, ending a season once it is
+/// over.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeasonJobPayload {
+	pub season_id: SeasonKey,
+}
@@ -0,0 +1,42 @@
+//! Contains domain entities for settlements.
+//! A player's buildings, storage and accumulator belong to one of their settlements. Every
+//! player has a capital, and founds further settlements once its Keep is high enough. Units,
+//! items and modifiers stay player-wide.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::domain::player::{Player, PlayerKey};
+use crate::schema::settlement;
+
+/// Unique identifier for a settlement
+pub type SettlementKey = Uuid;
+
+/// Name of the capital every player starts with.
+pub const CAPITAL_NAME: &str = "Capital";
+
+/// Represents a settlement of a player
+#[derive(
+	Queryable, Selectable, Identifiable, Associations, Debug, Clone, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[diesel(belongs_to(Player))]
+#[diesel(table_name = settlement, check_for_backend(diesel::pg::Pg))]
+pub struct Settlement {
+	pub id: SettlementKey,
+	pub player_id: PlayerKey,
+	/// Unique among the settlements of the player
+	pub name: String,
+	/// Whether this is the player's capital, which pays the upkeep of their units
+	pub is_capital: bool,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+/// Data transfer object for founding a new settlement
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = settlement, check_for_backend(diesel::pg::Pg))]
+pub struct NewSettlement {
+	pub player_id: PlayerKey,
+	pub name: String,
+}
@@ -0,0 +1,36 @@
+//! Contains domain entities for the table statistics.
+//! Row counts and on-disk sizes of the busiest tables are captured periodically, so the
+//! growth of the database and the effect of the retention policies can be followed from the
+//! admin API. See [`crate::game::table_stats`].
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::schema::table_stats;
+
+/// Unique identifier for a table statistics sample
+pub type TableStatsKey = Uuid;
+
+/// Represents the size of a table at the time it was captured
+#[derive(
+	Queryable, QueryableByName, Selectable, Identifiable, Serialize, Debug, Clone, PartialEq, Eq,
+)]
+#[diesel(table_name = table_stats, check_for_backend(diesel::pg::Pg))]
+pub struct TableStats {
+	pub id: TableStatsKey,
+	pub table_name: String,
+	/// Live rows estimated by the statistics collector, not an exact count
+	pub row_count: i64,
+	/// Size of the table on disk, its indexes and TOAST data included
+	pub total_bytes: i64,
+	pub captured_at: DateTime<Utc>,
+}
+
+/// Payload of a [`crate::domain::jobs::JobType::TableStats`] job.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableStatsJobPayload {
+	/// Captures the tracked tables and prunes the samples past their retention
+	Capture,
+}
@@ -3,7 +3,9 @@
 
 pub mod cost;
 pub mod player_unit;
+pub mod preset;
 pub mod training;
+pub mod upgrade;
 
 use std::io::Write;
 use std::str::from_utf8;
@@ -15,6 +17,7 @@ use diesel::prelude::*;
 use diesel::serialize::{IsNull, Output, ToSql};
 use diesel::{AsExpression, FromSqlRow, deserialize, serialize};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::schema::unit;
@@ -22,12 +25,16 @@ use crate::schema::unit;
 /// Unique identifier for a unit entity
 pub type UnitKey = Uuid;
 
+/// Attack and defense every tier above the first adds to a unit, in percent of its base stats.
+pub const TIER_BONUS_PERCENT: i64 = 20;
+
 /// Represents the type/class of a unit
 #[derive(
 	AsExpression,
 	FromSqlRow,
 	Serialize,
 	Deserialize,
+	ToSchema,
 	Debug,
 	Clone,
 	Copy,
@@ -39,6 +46,7 @@ pub type UnitKey = Uuid;
 )]
 #[diesel(sql_type = crate::schema::sql_types::UnitType)]
 #[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "graphql", derive(async_graphql::Enum))]
 pub enum UnitType {
 	Infantry,
 	Ranged,
@@ -92,6 +100,34 @@ pub struct Unit {
 	pub description: Option<String>,
 	pub created_at: DateTime<Utc>,
 	pub updated_at: DateTime<Utc>,
+	/// Food a single unit eats per hour
+	pub food_upkeep: i64,
+	/// Population a single unit takes up
+	pub population: i64,
+	/// Tier of the unit, 1 for units that are trained and higher for upgraded ones
+	pub tier: i32,
+}
+
+impl Unit {
+	/// Whether the unit is trained, units of higher tiers are only reached by upgrading.
+	pub fn is_trainable(&self) -> bool {
+		self.tier == 1
+	}
+
+	/// Bonus of the unit's tier to its attack and defense, in percent.
+	pub fn tier_bonus_percent(&self) -> i64 {
+		TIER_BONUS_PERCENT * i64::from(self.tier - 1)
+	}
+
+	/// Attack of a single unit, tier bonus included.
+	pub fn attack(&self) -> i64 {
+		self.base_atk * (100 + self.tier_bonus_percent()) / 100
+	}
+
+	/// Defense of a single unit, tier bonus included.
+	pub fn defense(&self) -> i64 {
+		self.base_def * (100 + self.tier_bonus_percent()) / 100
+	}
 }
 
 /// Data transfer object for creating a new unit
@@ -104,6 +140,8 @@ pub struct NewUnit {
 	pub base_def: i64,
 	pub base_training_seconds: i32,
 	pub description: Option<String>,
+	pub food_upkeep: i64,
+	pub population: i64,
 }
 
 /// Data transfer object for updating an existing unit
@@ -117,4 +155,6 @@ pub struct UpdateUnit {
 	pub base_def: Option<i64>,
 	pub base_training_seconds: Option<i32>,
 	pub description: Option<String>,
+	pub food_upkeep: Option<i64>,
+	pub population: Option<i64>,
 }
@@ -0,0 +1,58 @@
+//! Contains domain entities for army presets.
+//! An army preset is a named army composition a player saved to train again in one go.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::UnitKey;
+use crate::domain::player::PlayerKey;
+use crate::schema::{army_preset, army_preset_unit};
+
+/// Unique identifier for an army preset
+pub type ArmyPresetKey = Uuid;
+
+/// Represents a named army composition of a player
+#[derive(Queryable, Selectable, Identifiable, Serialize, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = army_preset, check_for_backend(diesel::pg::Pg))]
+pub struct ArmyPreset {
+	pub id: ArmyPresetKey,
+	pub player_id: PlayerKey,
+	pub name: String,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+/// Data transfer object for creating a new army preset
+#[derive(Insertable, Debug, PartialEq, Eq)]
+#[diesel(table_name = army_preset, check_for_backend(diesel::pg::Pg))]
+pub struct NewArmyPreset {
+	pub player_id: PlayerKey,
+	pub name: String,
+}
+
+/// Represents the units of a single type in an army preset
+#[derive(
+	Queryable,
+	Selectable,
+	Identifiable,
+	Associations,
+	Insertable,
+	Serialize,
+	Debug,
+	Clone,
+	PartialEq,
+	Eq,
+)]
+#[diesel(
+	table_name = army_preset_unit,
+	primary_key(preset_id, unit_id),
+	belongs_to(ArmyPreset, foreign_key = preset_id),
+	check_for_backend(diesel::pg::Pg)
+)]
+pub struct ArmyPresetUnit {
+	pub preset_id: ArmyPresetKey,
+	pub unit_id: UnitKey,
+	pub quantity: i64,
+}
@@ -4,13 +4,15 @@
 use std::io::Write;
 use std::str::from_utf8;
 
-use chrono::{DateTime, Utc};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, TimeDelta, Utc};
 use diesel::deserialize::FromSql;
 use diesel::pg::{Pg, PgValue};
 use diesel::prelude::*;
 use diesel::serialize::{IsNull, Output, ToSql};
 use diesel::{AsExpression, FromSqlRow, deserialize, serialize};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 use super::{Unit, UnitKey};
@@ -28,6 +30,7 @@ pub type TrainingQueueKey = Uuid;
 	FromSqlRow,
 	Serialize,
 	Deserialize,
+	ToSchema,
 	Debug,
 	Clone,
 	Copy,
@@ -39,6 +42,7 @@ pub type TrainingQueueKey = Uuid;
 )]
 #[diesel(sql_type = crate::schema::sql_types::TrainingStatus)]
 #[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "graphql", derive(async_graphql::Enum))]
 pub enum TrainingStatus {
 	Pending,
 	InProgress,
@@ -76,6 +80,59 @@ impl FromSql<crate::schema::sql_types::TrainingStatus, Pg> for TrainingStatus {
 	}
 }
 
+/// How a building works through its training queue
+#[derive(
+	AsExpression,
+	FromSqlRow,
+	Serialize,
+	Deserialize,
+	ToSchema,
+	Debug,
+	Default,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	Hash,
+	PartialOrd,
+	Ord,
+)]
+#[diesel(sql_type = crate::schema::sql_types::TrainingMode)]
+#[serde(rename_all = "snake_case")]
+pub enum TrainingMode {
+	/// Every entry of the queue trains at the same time
+	#[default]
+	Parallel,
+	/// One entry trains at a time, the others wait as pending until it completes
+	Sequential,
+}
+
+impl AsRef<str> for TrainingMode {
+	fn as_ref(&self) -> &str {
+		match self {
+			TrainingMode::Parallel => "parallel",
+			TrainingMode::Sequential => "sequential",
+		}
+	}
+}
+
+impl ToSql<crate::schema::sql_types::TrainingMode, Pg> for TrainingMode {
+	fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+		out.write_all(self.as_ref().as_bytes())?;
+		Ok(IsNull::No)
+	}
+}
+
+impl FromSql<crate::schema::sql_types::TrainingMode, Pg> for TrainingMode {
+	fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
+		match from_utf8(bytes.as_bytes())? {
+			"parallel" => Ok(TrainingMode::Parallel),
+			"sequential" => Ok(TrainingMode::Sequential),
+			other => Err(format!("Unrecognized enum variant: {other}").into()),
+		}
+	}
+}
+
 /// Represents an entry in the training queue
 #[derive(
 	Queryable, Selectable, Identifiable, Associations, Serialize, Debug, Clone, PartialEq, Eq,
@@ -95,6 +152,19 @@ pub struct TrainingQueueEntry {
 	pub job_id: Option<JobKey>,
 	pub created_at: DateTime<Utc>,
 	pub updated_at: DateTime<Utc>,
+	/// When the training finishes, fixed when it starts
+	pub completes_at: DateTime<Utc>,
+	/// Training speed multiplier that applied when the training started
+	pub training_modifier: BigDecimal,
+	/// Modified training time of a single unit, in seconds
+	pub seconds_per_unit: i64,
+}
+
+impl TrainingQueueEntry {
+	/// Training time of the whole entry as recorded at start.
+	pub fn duration(&self) -> TimeDelta {
+		TimeDelta::seconds(self.seconds_per_unit.saturating_mul(self.quantity))
+	}
 }
 
 /// Data transfer object for creating a new training queue entry
@@ -107,6 +177,9 @@ pub struct NewTrainingQueueEntry {
 	pub quantity: i64,
 	pub status: Option<TrainingStatus>,
 	pub job_id: Option<JobKey>,
+	pub completes_at: DateTime<Utc>,
+	pub training_modifier: BigDecimal,
+	pub seconds_per_unit: i64,
 }
 
 /// Data transfer object for updating a training queue entry
@@ -0,0 +1,48 @@
+//! Contains domain entities for unit upgrades.
+//! A unit upgrade turns trained units into the unit of the next tier at the Blacksmith.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+
+use super::{Unit, UnitKey};
+use crate::schema::unit_upgrade;
+
+/// Represents how a unit is upgraded to the next tier
+#[derive(
+	Queryable, Selectable, Identifiable, Associations, Serialize, Debug, Clone, PartialEq, Eq,
+)]
+#[diesel(
+	table_name = unit_upgrade,
+	primary_key(unit_id),
+	belongs_to(Unit),
+	check_for_backend(diesel::pg::Pg)
+)]
+pub struct UnitUpgrade {
+	/// Unit that is upgraded
+	pub unit_id: UnitKey,
+	/// Unit it is upgraded to
+	pub upgraded_unit_id: UnitKey,
+	/// Level of the Blacksmith needed for the upgrade
+	pub blacksmith_level: i32,
+	/// Level of the military research building needed for the upgrade
+	pub research_level: i32,
+	pub req_food: i64,
+	pub req_wood: i64,
+	pub req_stone: i64,
+	pub req_gold: i64,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+impl UnitUpgrade {
+	/// Resources to upgrade `quantity` units, as a tuple of (food, wood, stone, gold).
+	pub fn costs(&self, quantity: i64) -> (i64, i64, i64, i64) {
+		(
+			self.req_food.saturating_mul(quantity),
+			self.req_wood.saturating_mul(quantity),
+			self.req_stone.saturating_mul(quantity),
+			self.req_gold.saturating_mul(quantity),
+		)
+	}
+}
@@ -0,0 +1,157 @@
+//! Contains the resets of the world between seasons.
+//! A reset wipes the progression of every player but keeps their account, see
+//! [`crate::game::world::reset_operations`].
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use derive_more::Display;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::schema::world_reset;
+
+/// Unique identifier for a world reset
+pub type WorldResetKey = Uuid;
+
+/// A requested world reset and the progress of its job.
+#[derive(Queryable, Selectable, Identifiable, Clone, PartialEq, Eq)]
+#[diesel(table_name = world_reset, check_for_backend(diesel::pg::Pg))]
+pub struct WorldReset {
+	pub id: WorldResetKey,
+	/// Hash of the confirmation token handed to the requesting operator
+	pub token_hash: String,
+	/// Operator who requested the reset
+	pub requested_by: Option<String>,
+	/// End of the window in which the reset can be confirmed
+	pub expires_at: DateTime<Utc>,
+	/// Operator who confirmed the reset
+	pub confirmed_by: Option<String>,
+	pub confirmed_at: Option<DateTime<Utc>>,
+	/// Number of [`WorldResetStep`]s that ran, in order
+	pub completed_steps: i32,
+	pub completed_at: Option<DateTime<Utc>>,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+impl fmt::Debug for WorldReset {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("WorldReset")
+			.field("id", &self.id)
+			.field("token_hash", &"[redacted]")
+			.field("requested_by", &self.requested_by)
+			.field("expires_at", &self.expires_at)
+			.field("confirmed_by", &self.confirmed_by)
+			.field("confirmed_at", &self.confirmed_at)
+			.field("completed_steps", &self.completed_steps)
+			.field("completed_at", &self.completed_at)
+			.finish()
+	}
+}
+
+impl WorldReset {
+	/// Where the reset stands at `now`.
+	pub fn status(&self, now: DateTime<Utc>) -> WorldResetStatus {
+		if self.completed_at.is_some() {
+			WorldResetStatus::Completed
+		} else if self.confirmed_at.is_some() {
+			WorldResetStatus::Running
+		} else if self.expires_at <= now {
+			WorldResetStatus::Expired
+		} else {
+			WorldResetStatus::Requested
+		}
+	}
+
+	/// The step to run next, `None` once every step ran.
+	pub fn next_step(&self) -> Option<WorldResetStep> {
+		usize::try_from(self.completed_steps)
+			.ok()
+			.and_then(|completed| WorldResetStep::ALL.get(completed).copied())
+	}
+}
+
+/// Data transfer object for requesting a world reset
+#[derive(Insertable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = world_reset, check_for_backend(diesel::pg::Pg))]
+pub struct NewWorldReset {
+	pub token_hash: String,
+	pub requested_by: Option<String>,
+	pub expires_at: DateTime<Utc>,
+}
+
+/// Where a world reset stands.
+#[derive(Serialize, Deserialize, Display, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WorldResetStatus {
+	/// Waiting for its confirmation
+	Requested,
+	/// Was not confirmed in time
+	Expired,
+	/// Confirmed, its steps are running
+	Running,
+	/// Every step ran
+	Completed,
+}
+
+/// The steps of a world reset, each wiping a group of progression tables.
+///
+/// Steps run in the order of [`WorldResetStep::ALL`], one transaction each, so tables
+/// referencing others are wiped first.
+#[derive(Serialize, Deserialize, Display, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum WorldResetStep {
+	/// Training and construction queues, planned actions, caravans, scout missions and their
+	/// pending jobs
+	Queues,
+	/// Market orders and their trades
+	Market,
+	/// Battle and intel reports, the building upgrade ledger, the modifier history, and the
+	/// scores and leaderboards
+	Reports,
+	/// Unit and item inventories, recruited heroes, the event currency and objective progress,
+	/// and the quest progress of players
+	Units,
+	/// Modifiers, except those granted by the player's faction
+	Modifiers,
+	/// Buildings, resources and accumulators, back to the faction's starter buildings
+	Buildings,
+	/// Beginner shields, granted again to every player
+	Players,
+}
+
+impl WorldResetStep {
+	/// Every step, in the order they run.
+	pub const ALL: [WorldResetStep; 7] = [
+		WorldResetStep::Queues,
+		WorldResetStep::Market,
+		WorldResetStep::Reports,
+		WorldResetStep::Units,
+		WorldResetStep::Modifiers,
+		WorldResetStep::Buildings,
+		WorldResetStep::Players,
+	];
+
+	/// Position of the step in [`WorldResetStep::ALL`].
+	pub fn index(self) -> usize {
+		Self::ALL
+			.iter()
+			.position(|step| *step == self)
+			.expect("every step is listed")
+	}
+
+	/// The step running after this one, if any.
+	pub fn next(self) -> Option<WorldResetStep> {
+		Self::ALL.get(self.index() + 1).copied()
+	}
+}
+
+/// Payload of a [`crate::domain::jobs::JobType::WorldReset`] job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorldResetJobPayload {
+	pub reset_id: WorldResetKey,
+	/// The step the job runs
+	pub step: WorldResetStep,
+}
@@ -0,0 +1,147 @@
+//! Account deletion operations for the Empire game.
+//!
+//! [`request_deletion`] schedules the anonymization of an account after the grace period of
+//! [`AccountDeletionSettings`], [`cancel_on_login`] cancels it, and [`anonymize`] runs it.
+
+use chrono::{DateTime, SubsecRound, TimeDelta, Utc};
+use diesel::Connection;
+use serde_json::json;
+use tracing::{debug, info, instrument};
+
+use crate::auth::password::hash_password;
+use crate::auth::session_operations::gen_token;
+use crate::configuration::AccountDeletionSettings;
+use crate::db::{
+	DbConn, alliance_members, api_keys, audit_log, messages, player_identities, player_sessions,
+	players,
+};
+use crate::domain::account_deletion::AccountDeletionJobPayload;
+use crate::domain::audit::NewAuditEntry;
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::jobs::JobType;
+use crate::domain::player::{Player, PlayerKey};
+use crate::game::alliances::alliance_operations;
+use crate::game::audit_operations::ANONYMIZE_ACTION;
+use crate::job_queue::{JobPriority, JobQueue};
+
+/// Start of the names anonymized accounts are renamed to.
+pub const ANONYMIZED_NAME_PREFIX: &str = "deleted-";
+
+/// Schedules the anonymization of a player's account and logs the player out everywhere.
+///
+/// Asking again while a deletion is scheduled keeps the original schedule.
+///
+/// # Returns
+/// When the account will be anonymized
+#[instrument(skip(conn, job_queue, settings))]
+pub fn request_deletion(
+	conn: &mut DbConn,
+	job_queue: &JobQueue,
+	settings: &AccountDeletionSettings,
+	player_id: &PlayerKey,
+	now: DateTime<Utc>,
+) -> Result<DateTime<Utc>> {
+	let player = players::get_by_id(conn, player_id)?;
+	if let Some(scheduled_for) = player.deletion_scheduled_for {
+		debug!("Deletion of player {} already scheduled", player_id);
+		return Ok(scheduled_for);
+	}
+
+	// Truncated to what the database keeps, so the job payload matches the stored schedule
+	let scheduled_for = (now + TimeDelta::days(settings.grace_period_days.max(0))).trunc_subsecs(6);
+	conn.transaction(|conn| {
+		players::set_deletion_scheduled_for(conn, player_id, Some(scheduled_for))?;
+		player_sessions::delete_by_player(conn, player_id)?;
+		Ok::<_, Error>(())
+	})?;
+	// Enqueued once the schedule is committed, the job checks it even with no grace period
+	let payload = AccountDeletionJobPayload {
+		player_id: *player_id,
+		scheduled_for,
+	};
+	if let Err(err) = job_queue.enqueue(
+		JobType::AccountDeletion,
+		payload,
+		JobPriority::Low,
+		scheduled_for,
+	) {
+		players::set_deletion_scheduled_for(conn, player_id, None)?;
+		return Err(err);
+	}
+
+	info!(
+		"Scheduled deletion of player {} for {}",
+		player_id, scheduled_for
+	);
+	Ok(scheduled_for)
+}
+
+/// Cancels the scheduled deletion of a player who logged in.
+///
+/// # Returns
+/// Whether a deletion was cancelled
+#[instrument(skip_all, fields(player_id = %player.id))]
+pub fn cancel_on_login(conn: &mut DbConn, player: &Player) -> Result<bool> {
+	if player.deletion_scheduled_for.is_none() {
+		return Ok(false);
+	}
+	players::set_deletion_scheduled_for(conn, &player.id, None)?;
+	info!("Cancelled deletion of player {} on login", player.id);
+	Ok(true)
+}
+
+/// Anonymizes the account of a player whose deletion was scheduled for `scheduled_for`.
+///
+/// The name is replaced by a placeholder, the email is dropped, the password is replaced by
+/// one nobody knows, the sessions, API keys, external identities and mail are deleted, and
+/// the player leaves their alliance. The player row stays, so the history referencing it is
+/// kept, and the anonymization is recorded in the audit log.
+///
+/// # Returns
+/// Whether the account was anonymized, `false` if the deletion was cancelled or scheduled
+/// again since
+#[instrument(skip(conn))]
+pub fn anonymize(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	scheduled_for: DateTime<Utc>,
+	now: DateTime<Utc>,
+) -> Result<bool> {
+	let Some(player) = players::find_by_id(conn, player_id)? else {
+		debug!("Player {} no longer exists", player_id);
+		return Ok(false);
+	};
+	if player.deletion_scheduled_for != Some(scheduled_for.trunc_subsecs(6)) {
+		debug!("Deletion of player {} was cancelled", player_id);
+		return Ok(false);
+	}
+
+	let name = format!("{ANONYMIZED_NAME_PREFIX}{}", player_id.simple());
+	let pwd_hash = hash_password(gen_token())
+		.map_err(|_| Error::from((ErrorKind::InternalError, "Failed to hash password")))?;
+	conn.transaction(|conn| {
+		players::anonymize(conn, player_id, &name, &pwd_hash, now)?;
+		player_sessions::delete_by_player(conn, player_id)?;
+		api_keys::delete_by_player(conn, player_id)?;
+		player_identities::delete_by_player(conn, player_id)?;
+		messages::delete_by_recipient(conn, player_id)?;
+		if alliance_members::find_by_player(conn, player_id)?.is_some() {
+			alliance_operations::leave(conn, player_id)?;
+		}
+		audit_log::create(
+			conn,
+			NewAuditEntry {
+				action: ANONYMIZE_ACTION.to_string(),
+				subject: Some(player_id.to_string()),
+				details: json!({ "scheduled_for": scheduled_for }),
+				operator: None,
+				request_id: None,
+				player_id: None,
+			},
+		)?;
+		Ok::<_, Error>(())
+	})?;
+
+	info!("Anonymized the account of player {}", player_id);
+	Ok(true)
+}
@@ -0,0 +1,58 @@
+//! Account deletion job processor.
+//!
+//! This module implements the job processing functionality for the account deletion,
+//! anonymizing the accounts whose grace period ended.
+
+use axum::extract::FromRef;
+use chrono::Utc;
+use tracing::{debug, instrument, trace};
+
+use crate::Error;
+use crate::domain::account_deletion::AccountDeletionJobPayload;
+use crate::domain::app_state::{AppPool, AppState};
+use crate::domain::jobs::{Job, JobType};
+use crate::game::account_deletion::deletion_operations;
+use crate::job_queue::job_processor::{JobHandler, JobOutcome, JobWorker};
+
+/// A handler for [`JobType::AccountDeletion`] jobs.
+///
+/// Each job anonymizes one account, unless its deletion was cancelled since.
+pub struct AccountDeletionHandler {
+	/// Database connection pool
+	pool: AppPool,
+}
+
+/// Workers running the [`AccountDeletionHandler`].
+pub type AccountDeletionProcessor = JobWorker<AccountDeletionHandler>;
+
+impl JobHandler for AccountDeletionHandler {
+	const JOB_TYPE: JobType = JobType::AccountDeletion;
+	const WORKER_NAME: &'static str = "reaper";
+
+	/// Creates a new `AccountDeletionHandler` with what it needs from the application state.
+	fn new(app_state: &AppState) -> Self {
+		Self {
+			pool: AppPool::from_ref(app_state),
+		}
+	}
+
+	#[instrument(skip(self, job), fields(job_id = %job.id))]
+	async fn handle(&self, job: Job) -> Result<JobOutcome, Error> {
+		debug!("Processing account deletion job: {}", job.id);
+		trace!("Job details: {:?}", job);
+
+		let payload: AccountDeletionJobPayload = serde_json::from_value(job.payload.clone())?;
+		let anonymized = {
+			let mut conn = self.pool.get()?;
+			deletion_operations::anonymize(
+				&mut conn,
+				&payload.player_id,
+				payload.scheduled_for,
+				Utc::now(),
+			)?
+		};
+
+		debug!("Completed processing account deletion job: {}", job.id);
+		Ok(Some(serde_json::json!({ "anonymized": anonymized })))
+	}
+}
@@ -0,0 +1,11 @@
+//! Account deletion for the Empire game.
+//!
+//! Players ask for their account to be deleted and get a grace period to change their mind,
+//! logging in cancels the deletion. Once it ends, a [`JobType::AccountDeletion`] job
+//! anonymizes the account: its personal data is replaced, while the player row stays so
+//! battle reports, trades and the rest of the history referencing it keep their foreign keys.
+//!
+//! [`JobType::AccountDeletion`]: crate::domain::jobs::JobType::AccountDeletion
+
+pub mod deletion_operations;
+pub mod deletion_processor;
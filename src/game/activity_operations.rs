@@ -0,0 +1,144 @@
+//! Per-day activity statistics of a player, powering client-side progress graphs.
+//!
+//! The aggregation runs in SQL over the training queue, the building upgrade ledger and the
+//! battle reports, see [`player_activity::get_daily`]. Clients tend to poll these graphs,
+//! so results are kept in an [`ActivityCache`] for [`ACTIVITY_CACHE_TTL`].
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use tracing::{debug, instrument, trace};
+
+use crate::db::{DbConn, player_activity};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::player::PlayerKey;
+use crate::domain::player::activity::DailyActivity;
+
+/// How long aggregated activity is served from the cache.
+pub const ACTIVITY_CACHE_TTL: TimeDelta = TimeDelta::seconds(60);
+
+/// Window used when the client does not ask for one.
+pub const DEFAULT_ACTIVITY_WINDOW_DAYS: u32 = 7;
+
+/// Longest window that can be requested.
+pub const MAX_ACTIVITY_WINDOW_DAYS: u32 = 90;
+
+/// Number of UTC days covered by the activity statistics, the current day included.
+///
+/// Parsed from strings like `7d`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ActivityWindow(u32);
+
+impl ActivityWindow {
+	pub fn days(&self) -> u32 {
+		self.0
+	}
+
+	/// Returns the first and last day of the window ending on `today`.
+	pub fn bounds(&self, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+		let first_day = today - TimeDelta::days(i64::from(self.0) - 1);
+		(first_day, today)
+	}
+}
+
+impl Default for ActivityWindow {
+	fn default() -> Self {
+		Self(DEFAULT_ACTIVITY_WINDOW_DAYS)
+	}
+}
+
+impl FromStr for ActivityWindow {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		let days = s
+			.strip_suffix('d')
+			.and_then(|days| days.parse::<u32>().ok())
+			.ok_or_else(|| {
+				Error::from((
+					ErrorKind::InvalidStatsWindowError,
+					"Window must be a number of days, like 7d",
+				))
+			})?;
+		if days == 0 || days > MAX_ACTIVITY_WINDOW_DAYS {
+			return Err(Error::from((
+				ErrorKind::InvalidStatsWindowError,
+				"Window must be between 1d and 90d",
+			)));
+		}
+		Ok(Self(days))
+	}
+}
+
+/// A player's activity over a window of days.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerActivity {
+	pub player_id: PlayerKey,
+	pub window: ActivityWindow,
+	/// One entry per day of the window, oldest first
+	pub days: Vec<DailyActivity>,
+	pub generated_at: DateTime<Utc>,
+}
+
+/// Short-lived cache of aggregated activity, keyed by player and window.
+#[derive(Debug, Default)]
+pub struct ActivityCache {
+	entries: Mutex<HashMap<(PlayerKey, ActivityWindow), Arc<PlayerActivity>>>,
+}
+
+impl ActivityCache {
+	/// Returns the cached activity if it was generated less than [`ACTIVITY_CACHE_TTL`] ago.
+	pub fn get(
+		&self,
+		player_id: &PlayerKey,
+		window: ActivityWindow,
+		now: DateTime<Utc>,
+	) -> Option<Arc<PlayerActivity>> {
+		let entries = self.entries.lock().expect("activity cache poisoned");
+		entries
+			.get(&(*player_id, window))
+			.filter(|activity| now - activity.generated_at < ACTIVITY_CACHE_TTL)
+			.cloned()
+	}
+
+	/// Stores freshly aggregated activity, dropping every expired entry on the way.
+	pub fn insert(&self, activity: Arc<PlayerActivity>) {
+		let mut entries = self.entries.lock().expect("activity cache poisoned");
+		let now = activity.generated_at;
+		entries.retain(|_, cached| now - cached.generated_at < ACTIVITY_CACHE_TTL);
+		entries.insert((activity.player_id, activity.window), activity);
+	}
+}
+
+/// Returns a player's activity over `window`, served from `cache` while it is fresh.
+#[instrument(skip(conn, cache))]
+pub fn get_player_activity(
+	conn: &mut DbConn,
+	cache: &ActivityCache,
+	player_id: &PlayerKey,
+	window: ActivityWindow,
+) -> Result<Arc<PlayerActivity>> {
+	let now = Utc::now();
+	if let Some(activity) = cache.get(player_id, window, now) {
+		trace!("Serving cached activity for player {}", player_id);
+		return Ok(activity);
+	}
+
+	let (first_day, last_day) = window.bounds(now.date_naive());
+	let days = player_activity::get_daily(conn, player_id, first_day, last_day)?;
+	debug!(
+		"Aggregated activity of player {} from {} to {}",
+		player_id, first_day, last_day
+	);
+
+	let activity = Arc::new(PlayerActivity {
+		player_id: *player_id,
+		window,
+		days,
+		generated_at: now,
+	});
+	cache.insert(Arc::clone(&activity));
+	Ok(activity)
+}
@@ -0,0 +1,533 @@
+//! Operations behind the admin API.
+//!
+//! The world overview combines the in-memory [`ServerMetrics`] with a handful of aggregate
+//! queries. Every query is a single count or a narrow column scan, so the overview is cheap
+//! enough to poll from a dashboard.
+//!
+//! Operators can also enqueue jobs of any registered type, see [`enqueue_job`]. Payloads are
+//! parsed into the type the job's processor expects before anything is enqueued, and every
+//! enqueued job is recorded in the admin audit log.
+//!
+//! Modifiers can be granted to and revoked from players, see [`grant_modifier`] and
+//! [`revoke_modifier`]. Both record the operator in the modifier history as well as in the
+//! audit log. Operators hand out the moderator and admin roles with [`set_player_role`].
+
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::Connection;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use tracing::{debug, info, instrument, warn};
+
+use crate::db::{
+	DbConn, audit_log, backfills, caravans, construction_queue, limited_events, player_buildings,
+	players, scout_missions, seasons, training_queue, world_resets,
+};
+use crate::domain::account_deletion::AccountDeletionJobPayload;
+use crate::domain::audit::NewAuditEntry;
+use crate::domain::backfill::BackfillJobPayload;
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::factions::FactionJobPayload;
+use crate::domain::jobs::{Job, JobStatus, JobType};
+use crate::domain::leaderboard::LeaderboardJobPayload;
+use crate::domain::limited_event::LimitedEventJobPayload;
+use crate::domain::metrics::{RequestStats, ServerMetrics};
+use crate::domain::modifier::ModifierKey;
+use crate::domain::modifier::active_modifier::{
+	ActiveModifier, ModifierSourceType, NewActiveModifier,
+};
+use crate::domain::player::buildings::PlayerBuildingKey;
+use crate::domain::player::role::PlayerRole;
+use crate::domain::player::{Player, PlayerKey};
+use crate::domain::season::SeasonJobPayload;
+use crate::domain::table_stats::TableStatsJobPayload;
+use crate::domain::world_reset::{WorldResetJobPayload, WorldResetStatus};
+use crate::game::buildings::plan_operations::BuildingJobPayload;
+use crate::game::combat::combat_operations::CombatJobPayload;
+use crate::game::combat::espionage_operations::EspionageJobPayload;
+use crate::game::modifiers::modifier_scheduler::ModifierJobPayload;
+use crate::game::modifiers::modifier_service::{ModifierChange, ModifierService};
+use crate::game::resources::resource_scheduler::{ProductionJobPayload, ProductionTickPayload};
+#[cfg(feature = "simulation")]
+use crate::game::simulation::simulation_operations::SimulationJobPayload;
+use crate::game::units::training_operations::TrainingJobPayload;
+use crate::job_queue::{JobPriority, JobQueue};
+
+/// Window in which a player counts as active.
+pub const ACTIVE_PLAYER_WINDOW: TimeDelta = TimeDelta::minutes(15);
+
+/// How far ahead upcoming completions are counted.
+pub const UPCOMING_WINDOW: TimeDelta = TimeDelta::hours(1);
+
+/// Window over which the request error rate is computed.
+pub const ERROR_RATE_WINDOW: TimeDelta = TimeDelta::minutes(15);
+
+/// Outstanding jobs of a single type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueDepth {
+	pub job_type: JobType,
+	pub pending: i64,
+	pub in_progress: i64,
+}
+
+/// Snapshot of the world state assembled for the admin overview.
+#[derive(Debug, Clone)]
+pub struct WorldOverview {
+	pub generated_at: DateTime<Utc>,
+	/// Players that made an authenticated request within [`ACTIVE_PLAYER_WINDOW`]
+	pub active_players: usize,
+	/// Outstanding jobs, one entry per job type
+	pub queue_depth: Vec<QueueDepth>,
+	/// Building upgrades finishing within [`UPCOMING_WINDOW`]
+	pub upgrades_completing: i64,
+	/// Trainings finishing within [`UPCOMING_WINDOW`]
+	pub trainings_completing: i64,
+	/// Responses served within [`ERROR_RATE_WINDOW`]
+	pub requests: RequestStats,
+	/// Jobs moved to the dead-letter table within [`ERROR_RATE_WINDOW`]
+	pub failed_jobs: i64,
+}
+
+/// Assembles the current world overview.
+#[instrument(skip(conn, job_queue, metrics))]
+pub fn world_overview(
+	conn: &mut DbConn,
+	job_queue: &JobQueue,
+	metrics: &ServerMetrics,
+) -> Result<WorldOverview> {
+	let now = Utc::now();
+	let until = now + UPCOMING_WINDOW;
+
+	let depth = job_queue.depth_by_type()?;
+	let queue_depth = JobType::ALL
+		.iter()
+		.map(|job_type| {
+			let count_of = |wanted: JobStatus| {
+				depth
+					.iter()
+					.filter(|(t, s, _)| t == job_type && *s == wanted)
+					.map(|(_, _, count)| *count)
+					.sum()
+			};
+			QueueDepth {
+				job_type: *job_type,
+				pending: count_of(JobStatus::Pending),
+				in_progress: count_of(JobStatus::InProgress),
+			}
+		})
+		.collect();
+
+	let upgrades_completing = player_buildings::count_upgrades_finishing_before(conn, until)?;
+	let trainings_completing = training_queue::count_completing_before(conn, until)?;
+
+	let overview = WorldOverview {
+		generated_at: now,
+		active_players: metrics.active_players(ACTIVE_PLAYER_WINDOW),
+		queue_depth,
+		upgrades_completing,
+		trainings_completing,
+		requests: metrics.request_stats(ERROR_RATE_WINDOW),
+		failed_jobs: job_queue.count_dead_lettered_since(now - ERROR_RATE_WINDOW)?,
+	};
+	debug!("World overview: {:?}", overview);
+	Ok(overview)
+}
+
+/// Action recorded in the audit log for jobs enqueued through the admin API.
+pub const ENQUEUE_JOB_ACTION: &str = "enqueue_job";
+
+/// Who took an admin action, recorded with it in the audit log.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AdminActor {
+	/// Operator as declared by the caller
+	pub operator: Option<String>,
+	/// ID of the request that took the action
+	pub request_id: Option<String>,
+}
+
+/// A job an operator asked to enqueue.
+#[derive(Debug, Clone)]
+pub struct AdminJobRequest {
+	pub job_type: JobType,
+	/// Payload in the shape the job type's processor expects
+	pub payload: serde_json::Value,
+	pub priority: JobPriority,
+	pub run_at: DateTime<Utc>,
+}
+
+/// Validates the payload of `request` and enqueues the job, recording it in the audit log.
+///
+/// The job is cancelled again if it cannot be recorded.
+#[instrument(skip(conn, job_queue, request), fields(job_type = ?request.job_type))]
+pub fn enqueue_job(
+	conn: &mut DbConn,
+	job_queue: &JobQueue,
+	request: AdminJobRequest,
+	actor: AdminActor,
+) -> Result<Job> {
+	let payload = validate_payload(conn, request.job_type, request.payload)?;
+	let job_id = job_queue.enqueue(request.job_type, &payload, request.priority, request.run_at)?;
+
+	let entry = NewAuditEntry {
+		action: ENQUEUE_JOB_ACTION.to_string(),
+		subject: Some(job_id.to_string()),
+		details: json!({
+			"job_type": request.job_type,
+			"payload": payload,
+			"priority": request.priority,
+			"run_at": request.run_at,
+		}),
+		operator: actor.operator,
+		request_id: actor.request_id,
+		player_id: None,
+	};
+	if let Err(err) = audit_log::create(conn, entry) {
+		warn!(
+			"Cancelling job {} that could not be audited: {}",
+			job_id, err
+		);
+		job_queue.cancel_job(&job_id)?;
+		return Err(err);
+	}
+
+	info!(
+		"Enqueued {:?} job {} to run at {}",
+		request.job_type, job_id, request.run_at
+	);
+	job_queue.get_job(&job_id)
+}
+
+/// Action recorded in the audit log for modifiers granted through the admin API.
+pub const GRANT_MODIFIER_ACTION: &str = "grant_modifier";
+
+/// Action recorded in the audit log for modifiers revoked through the admin API.
+pub const REVOKE_MODIFIER_ACTION: &str = "revoke_modifier";
+
+/// A modifier an operator asked to grant to a player.
+#[derive(Debug, Clone)]
+pub struct AdminModifierGrant {
+	pub modifier_id: ModifierKey,
+	/// When the modifier runs out, `None` to keep it until it is revoked
+	pub expires_at: Option<DateTime<Utc>>,
+	pub source_type: ModifierSourceType,
+	/// Building the modifier is scoped to, `None` for a player-wide modifier
+	pub player_building_id: Option<PlayerBuildingKey>,
+	pub reason: Option<String>,
+}
+
+/// Grants a modifier to a player, recording the operator in the modifier history and the
+/// audit log.
+#[instrument(skip(conn, service, grant), fields(modifier_id = %grant.modifier_id))]
+pub async fn grant_modifier(
+	conn: &mut DbConn,
+	service: &mut ModifierService,
+	player_id: &PlayerKey,
+	grant: AdminModifierGrant,
+	actor: AdminActor,
+) -> Result<ActiveModifier> {
+	ensure_player(conn, player_id)?;
+	let now = Utc::now();
+	if grant.expires_at.is_some_and(|expires_at| expires_at <= now) {
+		return Err(Error::from((
+			ErrorKind::InvalidData,
+			"Modifier would already be expired",
+		)));
+	}
+
+	let change = ModifierChange {
+		operator: actor.operator.clone(),
+		reason: grant.reason.clone(),
+	};
+	let active = service
+		.grant_modifier(
+			NewActiveModifier {
+				player_id: *player_id,
+				modifier_id: grant.modifier_id,
+				started_at: Some(now),
+				expires_at: grant.expires_at,
+				source_type: grant.source_type,
+				source_id: None,
+				player_building_id: grant.player_building_id,
+			},
+			change,
+		)
+		.await?;
+
+	audit_log::create(
+		conn,
+		NewAuditEntry {
+			action: GRANT_MODIFIER_ACTION.to_string(),
+			subject: Some(player_id.to_string()),
+			details: json!({
+				"active_modifier_id": active.id,
+				"modifier_id": active.modifier_id,
+				"expires_at": active.expires_at,
+				"source_type": active.source_type,
+				"player_building_id": active.player_building_id,
+				"reason": grant.reason,
+			}),
+			operator: actor.operator,
+			request_id: actor.request_id,
+			player_id: None,
+		},
+	)?;
+	info!(
+		"Granted modifier {} to player {}",
+		active.modifier_id, player_id
+	);
+	Ok(active)
+}
+
+/// Revokes every instance of a modifier a player holds, recording the operator in the
+/// modifier history and the audit log.
+#[instrument(skip(conn, service))]
+pub async fn revoke_modifier(
+	conn: &mut DbConn,
+	service: &ModifierService,
+	player_id: &PlayerKey,
+	modifier_id: &ModifierKey,
+	reason: Option<String>,
+	actor: AdminActor,
+) -> Result<Vec<ActiveModifier>> {
+	ensure_player(conn, player_id)?;
+	let change = ModifierChange {
+		operator: actor.operator.clone(),
+		reason: reason.clone(),
+	};
+	let revoked = service
+		.revoke_modifier(player_id, modifier_id, change)
+		.await?;
+
+	audit_log::create(
+		conn,
+		NewAuditEntry {
+			action: REVOKE_MODIFIER_ACTION.to_string(),
+			subject: Some(player_id.to_string()),
+			details: json!({
+				"modifier_id": modifier_id,
+				"active_modifier_ids": revoked.iter().map(|active| active.id).collect::<Vec<_>>(),
+				"reason": reason,
+			}),
+			operator: actor.operator,
+			request_id: actor.request_id,
+			player_id: None,
+		},
+	)?;
+	Ok(revoked)
+}
+
+/// Action recorded in the audit log when an operator changes the role of a player.
+pub const SET_ROLE_ACTION: &str = "set_player_role";
+
+/// Gives a player a role, recording the previous one in the audit log.
+#[instrument(skip(conn))]
+pub fn set_player_role(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	role: PlayerRole,
+	actor: AdminActor,
+) -> Result<Player> {
+	conn.transaction(|conn| {
+		let previous = players::find_by_id(conn, player_id)?
+			.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Player not found")))?
+			.role;
+		let player = players::set_role(conn, player_id, role)?;
+		audit_log::create(
+			conn,
+			NewAuditEntry {
+				action: SET_ROLE_ACTION.to_string(),
+				subject: Some(player_id.to_string()),
+				details: json!({ "previous": previous, "role": role }),
+				operator: actor.operator,
+				request_id: actor.request_id,
+				player_id: None,
+			},
+		)?;
+		info!(
+			"Changed role of player {} from {} to {}",
+			player_id, previous, role
+		);
+		Ok(player)
+	})
+}
+
+/// Parses `payload` into the payload type of `job_type` and checks what it refers to.
+///
+/// Returns the payload as the processor will read it, without any unknown fields.
+fn validate_payload(
+	conn: &mut DbConn,
+	job_type: JobType,
+	payload: serde_json::Value,
+) -> Result<serde_json::Value> {
+	match job_type {
+		JobType::Modifier => {
+			let parsed: ModifierJobPayload = parse_payload(payload)?;
+			match &parsed {
+				ModifierJobPayload::ExpireModifier { player_id, .. }
+				| ModifierJobPayload::RecalculateResources { player_id, .. }
+				| ModifierJobPayload::UpdateModifierCache { player_id } => {
+					ensure_player(conn, player_id)?;
+				}
+				ModifierJobPayload::ExpireDueModifiers => {}
+			}
+			to_payload(&parsed)
+		}
+		JobType::Building => {
+			let parsed: BuildingJobPayload = parse_payload(payload)?;
+			match &parsed {
+				BuildingJobPayload::EvaluatePlans { player_id } => {
+					ensure_player(conn, player_id)?;
+				}
+				BuildingJobPayload::CompleteConstruction { entry_id } => {
+					if construction_queue::find_by_id(conn, entry_id)?.is_none() {
+						return Err(Error::from((
+							ErrorKind::NotFoundError,
+							"Construction entry not found",
+						)));
+					}
+				}
+				BuildingJobPayload::ConfirmUpgrade { player_building_id } => {
+					if player_buildings::find_by_id(conn, player_building_id)?.is_none() {
+						return Err(Error::from((
+							ErrorKind::NotFoundError,
+							"Player building not found",
+						)));
+					}
+				}
+			}
+			to_payload(&parsed)
+		}
+		JobType::Resource => {
+			let parsed: ProductionJobPayload = parse_payload(payload)?;
+			match &parsed {
+				ProductionJobPayload::CollectResources { players_id } => {
+					ensure_player(conn, players_id)?;
+				}
+				ProductionJobPayload::DeliverCaravan { caravan_id } => {
+					if caravans::find_by_id(conn, caravan_id)?.is_none() {
+						return Err(Error::from((ErrorKind::NotFoundError, "Caravan not found")));
+					}
+				}
+			}
+			to_payload(&parsed)
+		}
+		JobType::ResourceProduction => {
+			let parsed: ProductionTickPayload = parse_payload(payload)?;
+			if parsed.shards == 0 || parsed.shard >= parsed.shards {
+				return Err(Error::from((
+					ErrorKind::InvalidJobPayloadError,
+					"Invalid job payload",
+					format!(
+						"shard {} is not one of {} shards",
+						parsed.shard, parsed.shards
+					),
+				)));
+			}
+			to_payload(&parsed)
+		}
+		JobType::Training => {
+			let parsed: TrainingJobPayload = parse_payload(payload)?;
+			let entry = training_queue::get_owned(
+				conn,
+				&parsed.player_id,
+				&parsed.training_queue_entry_id,
+			)?;
+			if entry.unit_id != parsed.unit_id {
+				return Err(Error::from((
+					ErrorKind::InvalidJobPayloadError,
+					"Invalid job payload",
+					format!(
+						"unit {} is not trained by entry {}",
+						parsed.unit_id, entry.id
+					),
+				)));
+			}
+			to_payload(&parsed)
+		}
+		JobType::Combat => to_payload(&parse_payload::<CombatJobPayload>(payload)?),
+		JobType::Espionage => {
+			let parsed: EspionageJobPayload = parse_payload(payload)?;
+			match &parsed {
+				EspionageJobPayload::CompleteMission { mission_id } => {
+					if scout_missions::find_by_id(conn, mission_id)?.is_none() {
+						return Err(Error::from((
+							ErrorKind::NotFoundError,
+							"Scout mission not found",
+						)));
+					}
+				}
+			}
+			to_payload(&parsed)
+		}
+		JobType::Backfill => {
+			let parsed: BackfillJobPayload = parse_payload(payload)?;
+			if backfills::find(&parsed.name).is_none() {
+				return Err(Error::from((
+					ErrorKind::InvalidJobPayloadError,
+					"Invalid job payload",
+					format!("no backfill named {:?} is registered", parsed.name),
+				)));
+			}
+			to_payload(&parsed)
+		}
+		JobType::WorldReset => {
+			let parsed: WorldResetJobPayload = parse_payload(payload)?;
+			let reset = world_resets::find_by_id(conn, &parsed.reset_id)?
+				.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "World reset not found")))?;
+			// Only a confirmed reset may run, its steps are never started through the admin API
+			if reset.status(Utc::now()) != WorldResetStatus::Running {
+				return Err(Error::from((
+					ErrorKind::InvalidJobPayloadError,
+					"Invalid job payload",
+					format!("world reset {} is not running", reset.id),
+				)));
+			}
+			to_payload(&parsed)
+		}
+		JobType::LimitedEvent => {
+			let parsed: LimitedEventJobPayload = parse_payload(payload)?;
+			if limited_events::find_by_id(conn, &parsed.event_id)?.is_none() {
+				return Err(Error::from((ErrorKind::NotFoundError, "Event not found")));
+			}
+			to_payload(&parsed)
+		}
+		JobType::TableStats => to_payload(&parse_payload::<TableStatsJobPayload>(payload)?),
+		JobType::Leaderboard => to_payload(&parse_payload::<LeaderboardJobPayload>(payload)?),
+		JobType::Season => {
+			let parsed: SeasonJobPayload = parse_payload(payload)?;
+			if seasons::find_by_id(conn, &parsed.season_id)?.is_none() {
+				return Err(Error::from((ErrorKind::NotFoundError, "Season not found")));
+			}
+			to_payload(&parsed)
+		}
+		JobType::AccountDeletion => {
+			let parsed: AccountDeletionJobPayload = parse_payload(payload)?;
+			ensure_player(conn, &parsed.player_id)?;
+			to_payload(&parsed)
+		}
+		JobType::Faction => to_payload(&parse_payload::<FactionJobPayload>(payload)?),
+		#[cfg(feature = "simulation")]
+		JobType::Simulation => to_payload(&parse_payload::<SimulationJobPayload>(payload)?),
+	}
+}
+
+fn parse_payload<T: DeserializeOwned>(payload: serde_json::Value) -> Result<T> {
+	serde_json::from_value(payload).map_err(|err| {
+		Error::from((
+			ErrorKind::InvalidJobPayloadError,
+			"Invalid job payload",
+			err.to_string(),
+		))
+	})
+}
+
+fn to_payload(payload: &impl Serialize) -> Result<serde_json::Value> {
+	Ok(serde_json::to_value(payload)?)
+}
+
+fn ensure_player(conn: &mut DbConn, player_id: &PlayerKey) -> Result<()> {
+	if players::find_by_id(conn, player_id)?.is_none() {
+		return Err(Error::from((ErrorKind::NotFoundError, "Player not found")));
+	}
+	Ok(())
+}
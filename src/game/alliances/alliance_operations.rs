@@ -0,0 +1,474 @@
+//! Founding, joining and leaving alliances, managing their members and their treasury.
+//!
+//! A player founds an alliance as its leader, and the leader and officers invite other
+//! players, who join by accepting the invite. Officers can kick members, the leader can
+//! also kick officers and change roles. Making another member the leader hands the
+//! leadership over, the previous leader stays on as an officer.
+//!
+//! When the leader leaves, the most senior of the officers takes over, or the most senior
+//! member if there is no officer. The last member leaving disbands the alliance.
+//!
+//! Every change to the members of an alliance locks the alliance first, so the member cap
+//! and the single leader hold while several members act at once.
+
+use diesel::Connection;
+use tracing::{debug, info, instrument};
+
+use crate::configuration::AllianceSettings;
+use crate::db::resources::ResourceDelta;
+use crate::db::{
+	DbConn, alliance_donations, alliance_invites, alliance_members, alliances, players, resources,
+};
+use crate::domain::alliance::{
+	Alliance, AllianceDonation, AllianceInvite, AllianceKey, AllianceMember, AllianceRole,
+	NewAlliance, NewAllianceDonation, NewAllianceInvite, NewAllianceMember,
+};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::player::PlayerKey;
+
+/// Shortest and longest name of an alliance.
+pub const NAME_LENGTH: (usize, usize) = (3, 32);
+
+/// Shortest and longest tag of an alliance.
+pub const TAG_LENGTH: (usize, usize) = (2, 5);
+
+/// Maximum number of donations returned when listing them.
+pub const DONATION_HISTORY_LIMIT: i64 = 50;
+
+/// A member of an alliance with their name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberInfo {
+	pub member: AllianceMember,
+	pub name: String,
+}
+
+/// An alliance with its members, highest role first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllianceDetails {
+	pub alliance: Alliance,
+	pub members: Vec<MemberInfo>,
+}
+
+/// What happened to an alliance when a member left it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Departure {
+	pub alliance_id: AllianceKey,
+	/// Member who took over the leadership, if the leader left
+	pub successor: Option<PlayerKey>,
+	/// Whether the member was the last one and the alliance is gone
+	pub disbanded: bool,
+}
+
+fn membership_error(desc: &'static str) -> Error {
+	Error::from((ErrorKind::AllianceMembershipError, desc))
+}
+
+fn permission_error(desc: &'static str) -> Error {
+	Error::from((ErrorKind::AlliancePermissionError, desc))
+}
+
+/// Returns the membership of `player_id` in `alliance_id`.
+///
+/// # Errors
+/// `AlliancePermissionError` if the player is not a member of that alliance
+fn member_of(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	alliance_id: &AllianceKey,
+) -> Result<AllianceMember> {
+	alliance_members::find_by_player(conn, player_id)?
+		.filter(|member| member.alliance_id == *alliance_id)
+		.ok_or_else(|| permission_error("Not a member of this alliance"))
+}
+
+/// Trims the name and the tag and checks their length, tags are letters and digits only.
+fn validate_name_and_tag(name: &str, tag: &str) -> Result<(String, String)> {
+	let name = name.trim();
+	let name_length = name.chars().count();
+	if name_length < NAME_LENGTH.0 || name_length > NAME_LENGTH.1 {
+		return Err(Error::from((
+			ErrorKind::InvalidData,
+			"Alliance names are 3 to 32 characters long",
+		)));
+	}
+	let tag = tag.trim();
+	if tag.len() < TAG_LENGTH.0
+		|| tag.len() > TAG_LENGTH.1
+		|| !tag.chars().all(|c| c.is_ascii_alphanumeric())
+	{
+		return Err(Error::from((
+			ErrorKind::InvalidData,
+			"Alliance tags are 2 to 5 letters or digits",
+		)));
+	}
+	Ok((name.to_string(), tag.to_string()))
+}
+
+/// Founds an alliance led by `player_id`.
+///
+/// # Errors
+/// * `InvalidData` if the name or the tag is invalid
+/// * `AllianceMembershipError` if the player is already in an alliance
+/// * `AllianceNameTakenError` if another alliance uses the name or the tag
+#[instrument(skip(conn))]
+pub fn create_alliance(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	name: &str,
+	tag: &str,
+) -> Result<AllianceDetails> {
+	let (name, tag) = validate_name_and_tag(name, tag)?;
+
+	let details = conn.transaction(|conn| {
+		if alliance_members::find_by_player(conn, player_id)?.is_some() {
+			return Err(membership_error("Already in an alliance"));
+		}
+		if alliances::exists_by_name_or_tag(conn, &name, &tag)? {
+			return Err(Error::from((
+				ErrorKind::AllianceNameTakenError,
+				"Alliance name or tag is taken",
+			)));
+		}
+		let alliance = alliances::create(conn, NewAlliance { name, tag })?;
+		alliance_members::create(
+			conn,
+			NewAllianceMember {
+				player_id: *player_id,
+				alliance_id: alliance.id,
+				role: AllianceRole::Leader,
+			},
+		)?;
+		alliance_invites::delete_by_player(conn, player_id)?;
+		get_alliance(conn, &alliance.id)
+	})?;
+
+	info!(
+		"Player {} founded alliance {} [{}]",
+		player_id, details.alliance.name, details.alliance.tag
+	);
+	Ok(details)
+}
+
+/// Returns an alliance with its members.
+#[instrument(skip(conn))]
+pub fn get_alliance(conn: &mut DbConn, alliance_id: &AllianceKey) -> Result<AllianceDetails> {
+	let alliance = alliances::find_by_id(conn, alliance_id)?
+		.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Alliance not found")))?;
+	let members = alliance_members::get_by_alliance(conn, alliance_id)?
+		.into_iter()
+		.map(|(member, name)| MemberInfo { member, name })
+		.collect();
+	Ok(AllianceDetails { alliance, members })
+}
+
+/// Returns the alliance of a player, if they are in one.
+#[instrument(skip(conn))]
+pub fn get_player_alliance(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+) -> Result<Option<AllianceDetails>> {
+	match alliance_members::find_by_player(conn, player_id)? {
+		Some(member) => get_alliance(conn, &member.alliance_id).map(Some),
+		None => Ok(None),
+	}
+}
+
+/// Returns the most recent donations to the alliance of `player_id`, newest first.
+///
+/// # Errors
+/// `AlliancePermissionError` if the player is not a member of the alliance
+#[instrument(skip(conn))]
+pub fn list_donations(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	alliance_id: &AllianceKey,
+) -> Result<Vec<AllianceDonation>> {
+	member_of(conn, player_id, alliance_id)?;
+	alliance_donations::get_by_alliance(conn, alliance_id, DONATION_HISTORY_LIMIT)
+}
+
+/// Invites `invitee_id` to the alliance, on behalf of its leader or an officer.
+///
+/// # Errors
+/// * `AlliancePermissionError` if `inviter_id` is neither the leader nor an officer
+/// * `NotFoundError` if the invited player does not exist
+/// * `AllianceMembershipError` if the player is already in an alliance or invited
+#[instrument(skip(conn))]
+pub fn invite(
+	conn: &mut DbConn,
+	inviter_id: &PlayerKey,
+	alliance_id: &AllianceKey,
+	invitee_id: &PlayerKey,
+) -> Result<AllianceInvite> {
+	let inviter = member_of(conn, inviter_id, alliance_id)?;
+	if inviter.role < AllianceRole::Officer {
+		return Err(permission_error("Only the leader and officers can invite"));
+	}
+	if players::find_by_id(conn, invitee_id)?.is_none() {
+		return Err(Error::from((ErrorKind::NotFoundError, "Player not found")));
+	}
+	if alliance_members::find_by_player(conn, invitee_id)?.is_some() {
+		return Err(membership_error("Player is already in an alliance"));
+	}
+	if alliance_invites::find(conn, alliance_id, invitee_id)?.is_some() {
+		return Err(membership_error("Player is already invited"));
+	}
+
+	let invite = alliance_invites::create(
+		conn,
+		NewAllianceInvite {
+			alliance_id: *alliance_id,
+			player_id: *invitee_id,
+			invited_by: Some(*inviter_id),
+		},
+	)?;
+	info!(
+		"Player {} invited player {} to alliance {}",
+		inviter_id, invitee_id, alliance_id
+	);
+	Ok(invite)
+}
+
+/// Returns the pending invites of a player with the alliances they are for.
+#[instrument(skip(conn))]
+pub fn list_invites(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+) -> Result<Vec<(AllianceInvite, Alliance)>> {
+	alliance_invites::get_by_player(conn, player_id)
+}
+
+/// Joins an alliance the player was invited to, dropping their other invites.
+///
+/// # Errors
+/// * `NotFoundError` if the player was not invited to the alliance
+/// * `AllianceMembershipError` if the player is already in an alliance
+/// * `AllianceFullError` if the alliance has reached the member cap
+#[instrument(skip(conn, settings))]
+pub fn join(
+	conn: &mut DbConn,
+	settings: &AllianceSettings,
+	player_id: &PlayerKey,
+	alliance_id: &AllianceKey,
+) -> Result<AllianceMember> {
+	let member = conn.transaction(|conn| {
+		if alliance_invites::find(conn, alliance_id, player_id)?.is_none() {
+			return Err(Error::from((
+				ErrorKind::NotFoundError,
+				"No invite to this alliance",
+			)));
+		}
+		alliances::lock_by_id(conn, alliance_id)?;
+		if alliance_members::find_by_player(conn, player_id)?.is_some() {
+			return Err(membership_error("Already in an alliance"));
+		}
+		if alliance_members::count_by_alliance(conn, alliance_id)? >= settings.max_members {
+			return Err(Error::from((
+				ErrorKind::AllianceFullError,
+				"Alliance is full",
+			)));
+		}
+		alliance_invites::delete_by_player(conn, player_id)?;
+		alliance_members::create(
+			conn,
+			NewAllianceMember {
+				player_id: *player_id,
+				alliance_id: *alliance_id,
+				role: AllianceRole::Member,
+			},
+		)
+	})?;
+
+	info!("Player {} joined alliance {}", player_id, alliance_id);
+	Ok(member)
+}
+
+/// Leaves the player's alliance, handing the leadership over or disbanding it if needed.
+///
+/// # Errors
+/// `AllianceMembershipError` if the player is not in an alliance
+#[instrument(skip(conn))]
+pub fn leave(conn: &mut DbConn, player_id: &PlayerKey) -> Result<Departure> {
+	let departure = conn.transaction(|conn| {
+		let Some(member) = alliance_members::find_by_player(conn, player_id)? else {
+			return Err(membership_error("Not in an alliance"));
+		};
+		alliances::lock_by_id(conn, &member.alliance_id)?;
+		alliance_members::delete(conn, player_id)?;
+
+		let mut departure = Departure {
+			alliance_id: member.alliance_id,
+			successor: None,
+			disbanded: false,
+		};
+		if member.role != AllianceRole::Leader {
+			return Ok(departure);
+		}
+		match alliance_members::find_successor(conn, &member.alliance_id)? {
+			Some(successor) => {
+				alliance_members::set_role(conn, &successor.player_id, AllianceRole::Leader)?;
+				departure.successor = Some(successor.player_id);
+			}
+			None => {
+				alliances::delete(conn, &member.alliance_id)?;
+				departure.disbanded = true;
+			}
+		}
+		Ok(departure)
+	})?;
+
+	info!(
+		"Player {} left alliance {}, successor: {:?}, disbanded: {}",
+		player_id, departure.alliance_id, departure.successor, departure.disbanded
+	);
+	Ok(departure)
+}
+
+/// Removes `target_id` from the alliance on behalf of `actor_id`.
+///
+/// Officers can kick members, the leader can kick anyone but themselves.
+///
+/// # Errors
+/// * `AlliancePermissionError` if the actor's role is not above the target's
+/// * `NotFoundError` if the target is not a member of the alliance
+#[instrument(skip(conn))]
+pub fn kick(
+	conn: &mut DbConn,
+	actor_id: &PlayerKey,
+	alliance_id: &AllianceKey,
+	target_id: &PlayerKey,
+) -> Result<()> {
+	conn.transaction(|conn| {
+		alliances::lock_by_id(conn, alliance_id)?;
+		let actor = member_of(conn, actor_id, alliance_id)?;
+		let target = alliance_members::find_by_player(conn, target_id)?
+			.filter(|member| member.alliance_id == *alliance_id)
+			.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Member not found")))?;
+		if actor.role <= target.role || actor.role < AllianceRole::Officer {
+			return Err(permission_error(
+				"Only members of a lower role can be kicked",
+			));
+		}
+		alliance_members::delete(conn, target_id)?;
+		Ok::<_, Error>(())
+	})?;
+
+	info!(
+		"Player {} kicked player {} from alliance {}",
+		actor_id, target_id, alliance_id
+	);
+	Ok(())
+}
+
+/// Changes the role of `target_id` on behalf of the leader.
+///
+/// Making another member the leader hands the leadership over, the previous leader becomes
+/// an officer.
+///
+/// # Errors
+/// * `AlliancePermissionError` if the actor is not the leader
+/// * `NotFoundError` if the target is not a member of the alliance
+/// * `InvalidData` if the leader changes their own role
+#[instrument(skip(conn))]
+pub fn set_role(
+	conn: &mut DbConn,
+	actor_id: &PlayerKey,
+	alliance_id: &AllianceKey,
+	target_id: &PlayerKey,
+	role: AllianceRole,
+) -> Result<AllianceMember> {
+	let member = conn.transaction(|conn| {
+		alliances::lock_by_id(conn, alliance_id)?;
+		let actor = member_of(conn, actor_id, alliance_id)?;
+		if actor.role != AllianceRole::Leader {
+			return Err(permission_error("Only the leader can change roles"));
+		}
+		if actor_id == target_id {
+			return Err(Error::from((
+				ErrorKind::InvalidData,
+				"Hand the leadership over to another member instead",
+			)));
+		}
+		alliance_members::find_by_player(conn, target_id)?
+			.filter(|member| member.alliance_id == *alliance_id)
+			.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Member not found")))?;
+
+		if role == AllianceRole::Leader {
+			// The previous leader steps down first, there is only ever one
+			alliance_members::set_role(conn, actor_id, AllianceRole::Officer)?;
+			debug!("Player {} hands the leadership over", actor_id);
+		}
+		alliance_members::set_role(conn, target_id, role)
+	})?;
+
+	info!(
+		"Player {} made player {} {} of alliance {}",
+		actor_id, target_id, role, alliance_id
+	);
+	Ok(member)
+}
+
+/// Moves resources from a member to the treasury of their alliance.
+///
+/// # Errors
+/// * `InvalidQuantityError` if an amount is negative or nothing is donated
+/// * `AlliancePermissionError` if the player is not a member of the alliance
+/// * `InsufficientResourcesError` if the player does not have the resources
+#[instrument(skip(conn))]
+pub fn donate(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	alliance_id: &AllianceKey,
+	amounts: ResourceDelta,
+) -> Result<(Alliance, AllianceDonation)> {
+	let (food, wood, stone, gold) = amounts;
+	if [food, wood, stone, gold].iter().any(|amount| *amount < 0)
+		|| [food, wood, stone, gold].iter().all(|amount| *amount == 0)
+	{
+		return Err(Error::from((
+			ErrorKind::InvalidQuantityError,
+			"Donations must be positive",
+		)));
+	}
+
+	let donated = conn.transaction(|conn| {
+		member_of(conn, player_id, alliance_id)?;
+		let held = resources::lock_by_player_id(conn, player_id)?;
+		if held.food < food || held.wood < wood || held.stone < stone || held.gold < gold {
+			return Err(Error::from((
+				ErrorKind::InsufficientResourcesError,
+				"Not enough resources",
+			)));
+		}
+		resources::deduct(conn, player_id, &amounts)?;
+		let alliance = alliances::deposit(conn, alliance_id, &amounts)?;
+		let donation = alliance_donations::create(
+			conn,
+			NewAllianceDonation {
+				alliance_id: *alliance_id,
+				player_id: Some(*player_id),
+				food,
+				wood,
+				stone,
+				gold,
+			},
+		)?;
+		Ok::<_, Error>((alliance, donation))
+	})?;
+
+	info!(
+		"Player {} donated {:?} to alliance {}",
+		player_id, amounts, alliance_id
+	);
+	Ok(donated)
+}
+
+/// Whether two players are members of the same alliance.
+#[instrument(skip(conn))]
+pub fn are_allies(conn: &mut DbConn, player_id: &PlayerKey, other_id: &PlayerKey) -> Result<bool> {
+	let Some(member) = alliance_members::find_by_player(conn, player_id)? else {
+		return Ok(false);
+	};
+	let other = alliance_members::find_by_player(conn, other_id)?;
+	Ok(other.is_some_and(|other| other.alliance_id == member.alliance_id))
+}
@@ -0,0 +1,6 @@
+//! Alliance operations for the Empire game.
+//!
+//! This module lets players found alliances, invite others to join them, manage their
+//! members through the leader and officer roles, and pool resources in a shared treasury.
+
+pub mod alliance_operations;
@@ -0,0 +1,107 @@
+//! Recording the sensitive actions players take in the audit log.
+//!
+//! Operator actions are recorded by [`crate::game::admin_operations`] and friends together
+//! with the action itself. Player actions are recorded by [`record`] once they succeeded,
+//! and failing to record one is logged instead of undoing an action the player already saw
+//! succeed.
+
+use axum::http::HeaderMap;
+use serde_json::Value;
+use tracing::{instrument, warn};
+
+use crate::db::{DbConn, audit_log};
+use crate::domain::audit::{AuditEntry, AuditFilter, NewAuditEntry};
+use crate::domain::error::Result;
+use crate::domain::player::PlayerKey;
+use crate::net::router::REQUEST_ID_HEADER;
+
+/// Action recorded when a player logs in, with the method in the details.
+pub const LOGIN_ACTION: &str = "login";
+
+/// Action recorded when a player changes their password.
+pub const CHANGE_PASSWORD_ACTION: &str = "change_password";
+
+/// Action recorded when a player changes their email address.
+pub const CHANGE_EMAIL_ACTION: &str = "change_email";
+
+/// Action recorded when a player asks for their account to be deleted.
+pub const REQUEST_DELETION_ACTION: &str = "request_account_deletion";
+
+/// Action recorded when the server anonymizes a deleted account.
+pub const ANONYMIZE_ACTION: &str = "anonymize_account";
+
+/// Action recorded when an admin changes another player's account.
+pub const UPDATE_PLAYER_ACTION: &str = "update_player";
+
+/// Action recorded when an admin deletes another player's account.
+pub const DELETE_PLAYER_ACTION: &str = "delete_player";
+
+/// Action recorded when a moderator mutes a player in the chat.
+pub const MUTE_PLAYER_ACTION: &str = "mute_player";
+
+/// Action recorded when a moderator lifts the chat mute of a player.
+pub const UNMUTE_PLAYER_ACTION: &str = "unmute_player";
+
+/// Entries an audit log query returns unless it asks for another number.
+pub const DEFAULT_AUDIT_LIMIT: i64 = 100;
+
+/// Most entries a single audit log query returns.
+pub const MAX_AUDIT_LIMIT: i64 = 500;
+
+/// A player taking an action, and the request it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerActor {
+	pub player_id: PlayerKey,
+	pub request_id: Option<String>,
+}
+
+impl PlayerActor {
+	/// The player taking the action in the request with the given headers.
+	pub fn new(player_id: PlayerKey, headers: &HeaderMap) -> Self {
+		Self {
+			player_id,
+			request_id: request_id(headers),
+		}
+	}
+}
+
+/// The ID the request ID middleware gave the request with the given headers.
+pub fn request_id(headers: &HeaderMap) -> Option<String> {
+	headers
+		.get(REQUEST_ID_HEADER)
+		.and_then(|value| value.to_str().ok())
+		.map(str::to_string)
+}
+
+/// Records an action `actor` took on `subject`, logging instead of failing if it can't.
+#[instrument(skip(conn, details))]
+pub fn record(
+	conn: &mut DbConn,
+	actor: &PlayerActor,
+	action: &str,
+	subject: Option<String>,
+	details: Value,
+) {
+	let entry = NewAuditEntry {
+		action: action.to_string(),
+		subject,
+		details,
+		operator: None,
+		request_id: actor.request_id.clone(),
+		player_id: Some(actor.player_id),
+	};
+	if let Err(err) = audit_log::create(conn, entry) {
+		warn!(
+			"Failed to record {} by player {}: {}",
+			action, actor.player_id, err
+		);
+	}
+}
+
+/// Returns up to `limit` of the most recent entries matching `filter`, newest first.
+///
+/// The limit is capped at [`MAX_AUDIT_LIMIT`].
+#[instrument(skip(conn))]
+pub fn find(conn: &mut DbConn, filter: &AuditFilter, limit: i64) -> Result<Vec<AuditEntry>> {
+	audit_log::find(conn, filter, limit.clamp(1, MAX_AUDIT_LIMIT))
+}
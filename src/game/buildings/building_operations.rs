@@ -1,12 +1,16 @@
 //! Building operations for the Empire game.
 //!
 //! This module provides core functionality for managing player buildings, including
-//! construction, upgrades, and upgrade confirmation. It follows the functional programming
+//! construction, upgrades, upgrade confirmation and cancellation, and downgrades. It follows the functional programming
 //! approach with direct function calls rather than service structs, enabling better
 //! performance through single-connection-per-request optimization.
 //!
 //! All operations maintain transactional integrity and provide comprehensive validation
 //! of resource requirements, building constraints, and timing requirements.
+//!
+//! Constructions and upgrades are confirmed by a building job scheduled for the time they
+//! finish, so buildings reach their new level even while the player is offline. Players can
+//! still confirm them through [`confirm_upgrade`], which is a no-op once the job ran.
 
 use std::ops::Add;
 
@@ -15,26 +19,77 @@ use chrono::prelude::*;
 use diesel::Connection;
 use tracing::{debug, info, instrument, trace, warn};
 
+use crate::db::resources::ResourceDelta;
 use crate::db::{
-	DbConn, building_levels, building_requirements, player_buildings, players, resources,
+	DbConn, building_levels, building_requirements, building_upgrades, construction_queue,
+	player_buildings, players, resources,
 };
 use crate::domain::building::BuildingKey;
 use crate::domain::building::level::BuildingLevel;
+use crate::domain::building::upgrade::NewBuildingUpgrade;
 use crate::domain::error::{Error, ErrorKind, Result};
-use crate::domain::player::PlayerKey;
+use crate::domain::jobs::JobType;
 use crate::domain::player::buildings::{NewPlayerBuilding, PlayerBuilding, PlayerBuildingKey};
+use crate::domain::settlement::{Settlement, SettlementKey};
+use crate::game::buildings::plan_operations::BuildingJobPayload;
 use crate::game::buildings::requirement_operations::{self, ConstructionInfo};
+use crate::job_queue::{JobPriority, JobQueue};
+
+/// Maximum number of buildings a player can construct per [`CONSTRUCTION_THROTTLE_WINDOW`].
+pub const MAX_CONSTRUCTIONS_PER_WINDOW: i64 = 5;
+
+/// Window over which [`MAX_CONSTRUCTIONS_PER_WINDOW`] is enforced.
+pub const CONSTRUCTION_THROTTLE_WINDOW: TimeDelta = TimeDelta::minutes(1);
+
+/// Refund percentage when cancelling an upgrade (80% = 0.80), scaled by its time left.
+pub const CANCEL_UPGRADE_REFUND_RATE: f64 = 0.80;
+
+/// Share of the cost of a building's current level refunded when it is downgraded.
+pub const DOWNGRADE_REFUND_RATE: f64 = 0.50;
+
+/// Progress of a construction or upgrade that is underway.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UpgradeProgress {
+	/// Progress percentage (0.0 - 100.0)
+	pub progress_percent: f64,
+	/// Seconds until the upgrade can be confirmed
+	pub seconds_remaining: i64,
+}
+
+/// Computes the progress of an upgrade from its finish time and total duration at `now`.
+///
+/// Returns `None` if the building is not upgrading. An upgrade that finished but was not
+/// confirmed yet stays at 100%.
+pub fn upgrade_progress(
+	upgrade_finishes_at: Option<DateTime<Utc>>,
+	upgrade_seconds: i64,
+	now: DateTime<Utc>,
+) -> Option<UpgradeProgress> {
+	let seconds_remaining = (upgrade_finishes_at? - now).num_seconds().max(0);
+	let progress_percent = if upgrade_seconds <= 0 {
+		100.0
+	} else {
+		let elapsed = (upgrade_seconds - seconds_remaining).max(0);
+		(elapsed as f64 / upgrade_seconds as f64 * 100.0).min(100.0)
+	};
+	Some(UpgradeProgress {
+		progress_percent,
+		seconds_remaining,
+	})
+}
 
-/// Constructs a new building for a player.
+/// Constructs a new building in one of a player's settlements.
 ///
 /// This function handles the complete building construction process, including resource
 /// validation, constraint checking, and database operations. The construction is performed
-/// within a database transaction to ensure atomicity.
+/// within a database transaction to ensure atomicity. The building is paid for out of the
+/// settlement's storage, and its requirements and maximum count apply to the settlement.
 ///
 /// # Arguments
 ///
 /// * `conn` - Database connection for performing operations
-/// * `player_id` - Unique identifier of the player constructing the building
+/// * `job_queue` - Job queue used to schedule the confirmation of the construction
+/// * `settlement` - Settlement the building is constructed in, of the player constructing it
 /// * `bld_id` - Unique identifier of the building type to construct
 ///
 /// # Returns
@@ -50,24 +105,49 @@ use crate::game::buildings::requirement_operations::{self, ConstructionInfo};
 /// - Insufficient resources ("Not enough resources")
 /// - Building count limit exceeded ("Max buildings reached")
 /// - Transaction failure ("Failed to construct building")
-#[instrument(skip(conn))]
+///
+/// It returns `ConstructionThrottledError` if the player already constructed
+/// [`MAX_CONSTRUCTIONS_PER_WINDOW`] buildings within the last [`CONSTRUCTION_THROTTLE_WINDOW`].
+#[instrument(skip(conn, job_queue))]
 pub fn construct_building(
 	conn: &mut DbConn,
-	player_id: &PlayerKey,
+	job_queue: &JobQueue,
+	settlement: &Settlement,
 	bld_id: &BuildingKey,
 ) -> Result<PlayerBuilding> {
+	let player_id = &settlement.player_id;
 	debug!(
-		"Starting construct building {} for player {}",
-		bld_id, player_id
+		"Starting construct building {} for player {} in settlement {}",
+		bld_id, player_id, settlement.id
 	);
+
+	// Soft throttle: not serialized with the insert below, so bursts may overshoot slightly
+	let recent_constructions = player_buildings::count_constructed_since(
+		conn,
+		player_id,
+		Utc::now() - CONSTRUCTION_THROTTLE_WINDOW,
+	)?;
+	if recent_constructions >= MAX_CONSTRUCTIONS_PER_WINDOW {
+		debug!(
+			"Player {} constructed {} buildings within the throttle window",
+			player_id, recent_constructions
+		);
+		return Err(Error::from((
+			ErrorKind::ConstructionThrottledError,
+			"Too many constructions started, try again in a minute",
+		)));
+	}
+
 	let bld_lvl = building_levels::get_next_upgrade(conn, bld_id, &0)?;
 	trace!("Building level requirements: {:?}", bld_lvl);
 
 	let reqs = building_requirements::get_for_bld_and_level(conn, bld_id, bld_lvl.building_level)?;
-	let (bld, avail_data) = player_buildings::get_player_bld_count_level(conn, player_id, bld_id)?;
+	let (bld, avail_data) =
+		player_buildings::get_player_bld_count_level(conn, &settlement.id, bld_id)?;
 	// Get all building data to look up required building levels
 	let player = players::get_by_id(conn, player_id)?;
-	let (_, all_bld_data) = player_buildings::get_player_bld_counts_levels(conn, &player)?;
+	let (_, all_bld_data) =
+		player_buildings::get_player_bld_counts_levels(conn, &player, &settlement.id)?;
 	let bld_avail = requirement_operations::gen_avail_data(
 		bld,
 		avail_data,
@@ -90,10 +170,10 @@ pub fn construct_building(
 	}
 
 	// check for resources
-	if !has_enough_resources(conn, player_id, &bld_lvl)? {
+	if !has_enough_resources(conn, &settlement.id, &bld_lvl)? {
 		trace!(
-			"Player {} doesn't have enough resources to build {}",
-			player_id, bld_id
+			"Settlement {} doesn't have enough resources to build {}",
+			settlement.id, bld_id
 		);
 		return Err(Error::from((
 			ErrorKind::ConstructBuildingError,
@@ -101,12 +181,12 @@ pub fn construct_building(
 		)));
 	}
 
-	let res: Result<PlayerBuilding> = conn.transaction(|connection| {
+	let res: Result<(PlayerBuilding, DateTime<Utc>)> = conn.transaction(|connection| {
 		info!("Initiating construction transaction");
 		// deduct resources
-		resources::deduct(
+		resources::deduct_from_settlement(
 			connection,
-			player_id,
+			&settlement.id,
 			&(
 				bld_lvl.req_food.unwrap_or(0),
 				bld_lvl.req_wood.unwrap_or(0),
@@ -123,19 +203,21 @@ pub fn construct_building(
 				player_id: *player_id,
 				building_id: *bld_id,
 				level: Some(0),
-				upgrade_finishes_at: Some(upgrade_eta.to_rfc3339()),
+				upgrade_finishes_at: Some(upgrade_eta),
+				settlement_id: Some(settlement.id),
 			},
 		)?;
 		trace!("New player building details: {:#?}", player_bld);
-		Ok(player_bld)
+		Ok((player_bld, upgrade_eta))
 	});
 
 	match res {
-		Ok(player_bld) => {
+		Ok((player_bld, upgrade_eta)) => {
 			info!(
 				"Successfully constructed building {} for player {}",
 				bld_id, player_id
 			);
+			schedule_confirmation(job_queue, &player_bld.id, upgrade_eta);
 			Ok(player_bld)
 		}
 		Err(e) => {
@@ -161,6 +243,7 @@ pub fn construct_building(
 /// # Arguments
 ///
 /// * `conn` - Database connection for performing operations
+/// * `job_queue` - Job queue used to schedule the confirmation of the upgrade
 /// * `player_bld_id` - Unique identifier of the player building to upgrade
 ///
 /// # Returns
@@ -175,10 +258,12 @@ pub fn construct_building(
 /// This function returns `UpgradeBuildingError` variants for:
 /// - Insufficient resources ("Not enough resources")
 /// - Maximum level reached ("Building is at max level")
+/// - Upgrades waiting in the construction queue ("Building has queued upgrades")
 /// - Transaction failure ("Failed to upgrade building")
-#[instrument(skip(conn))]
+#[instrument(skip(conn, job_queue))]
 pub fn upgrade_building(
 	conn: &mut DbConn,
+	job_queue: &JobQueue,
 	player_bld_id: &PlayerBuildingKey,
 ) -> Result<PlayerBuilding> {
 	debug!("Starting upgrade building: {}", player_bld_id);
@@ -187,43 +272,27 @@ pub fn upgrade_building(
 		"Player building details: {:?}, max level: {:?}",
 		player_bld, max_level
 	);
-	let bld_id = &player_bld.building_id;
-	let player_id = &player_bld.player_id;
-
-	let bld_lvl = building_levels::get_next_upgrade(conn, bld_id, &player_bld.level)?;
-	trace!("Next building level details: {:?}", bld_lvl);
-
-	let reqs = building_requirements::get_for_bld_and_level(conn, bld_id, bld_lvl.building_level)?;
-	let (bld, avail_data) = player_buildings::get_player_bld_count_level(conn, player_id, bld_id)?;
-	// Get all building data to look up required building levels
-	let player = players::get_by_id(conn, player_id)?;
-	let (_, all_bld_data) = player_buildings::get_player_bld_counts_levels(conn, &player)?;
-	let bld_avail = requirement_operations::gen_avail_data(
-		bld,
-		avail_data,
-		reqs,
-		ConstructionInfo::default(),
-		&all_bld_data,
-	);
-	trace!("Building availability: {:?}", bld_avail);
-
-	if !bld_avail.buildable {
+	if construction_queue::count_active_for_building(conn, player_bld_id)? > 0 {
 		debug!(
-			"Building {} cannot be upgraded, locks present: {:?}",
-			player_bld.building_id, bld_avail.locks
+			"Building {} has upgrades in the construction queue",
+			player_bld_id
 		);
 		return Err(Error::from((
 			ErrorKind::UpgradeBuildingError,
-			"Building has locks",
-			format!("{:?}", bld_avail.locks),
+			"Building has queued upgrades",
 		)));
 	}
 
+	let bld_lvl =
+		building_levels::get_next_upgrade(conn, &player_bld.building_id, &player_bld.level)?;
+	trace!("Next building level details: {:?}", bld_lvl);
+	check_upgrade_locks(conn, &player_bld, &bld_lvl)?;
+
 	// check for resources
-	if !has_enough_resources(conn, &player_bld.player_id, &bld_lvl)? {
+	if !has_enough_resources(conn, &player_bld.settlement_id, &bld_lvl)? {
 		debug!(
-			"Player {} doesn't have enough resources for upgrade",
-			player_bld.player_id
+			"Settlement {} doesn't have enough resources for upgrade",
+			player_bld.settlement_id
 		);
 		return Err(Error::from((
 			ErrorKind::UpgradeBuildingError,
@@ -231,37 +300,45 @@ pub fn upgrade_building(
 		)));
 	}
 
-	let res: Result<PlayerBuilding> = conn.transaction(|connection| {
+	let res: Result<(PlayerBuilding, DateTime<Utc>)> = conn.transaction(|connection| {
 		info!("Initiating upgrade transaction");
 		// deduct resources
-		resources::deduct(
+		let cost = (
+			bld_lvl.req_food.unwrap_or(0),
+			bld_lvl.req_wood.unwrap_or(0),
+			bld_lvl.req_stone.unwrap_or(0),
+			bld_lvl.req_gold.unwrap_or(0),
+		);
+		resources::deduct_from_settlement(connection, &player_bld.settlement_id, &cost)?;
+		trace!("Deducted resources");
+		building_upgrades::create(
 			connection,
-			&player_bld.player_id,
-			&(
-				bld_lvl.req_food.unwrap_or(0),
-				bld_lvl.req_wood.unwrap_or(0),
-				bld_lvl.req_stone.unwrap_or(0),
-				bld_lvl.req_gold.unwrap_or(0),
-			),
+			NewBuildingUpgrade {
+				player_id: player_bld.player_id,
+				player_building_id: *player_bld_id,
+				building_id: player_bld.building_id,
+				level: bld_lvl.building_level,
+				food: cost.0,
+				wood: cost.1,
+				stone: cost.2,
+				gold: cost.3,
+			},
 		)?;
-		trace!("Deducted resources");
 		// upgrade building
 		let upgrade_eta = Utc::now().add(TimeDelta::seconds(bld_lvl.upgrade_seconds));
-		let player_bld = player_buildings::set_upgrade_eta(
-			connection,
-			player_bld_id,
-			Some(&upgrade_eta.to_rfc3339()),
-		)?;
+		let player_bld =
+			player_buildings::set_upgrade_eta(connection, player_bld_id, Some(upgrade_eta))?;
 		debug!("Building upgrade started: {:?}", player_bld);
-		Ok(player_bld)
+		Ok((player_bld, upgrade_eta))
 	});
 
 	match res {
-		Ok(player_bld) => {
+		Ok((player_bld, upgrade_eta)) => {
 			info!(
 				"Successfully started building {} upgrade for player {}",
 				player_bld_id, player_bld.player_id
 			);
+			schedule_confirmation(job_queue, player_bld_id, upgrade_eta);
 			Ok(player_bld)
 		}
 		Err(e) => {
@@ -274,11 +351,55 @@ pub fn upgrade_building(
 	}
 }
 
+/// Checks the requirements of the level a building is upgraded to, like a minimum Keep level
+/// in the building's settlement.
+///
+/// # Errors
+///
+/// Returns an `UpgradeBuildingError` ("Building has locks") listing the unmet requirements.
+pub fn check_upgrade_locks(
+	conn: &mut DbConn,
+	player_bld: &PlayerBuilding,
+	bld_lvl: &BuildingLevel,
+) -> Result<()> {
+	let bld_id = &player_bld.building_id;
+	let settlement_id = &player_bld.settlement_id;
+	let reqs = building_requirements::get_for_bld_and_level(conn, bld_id, bld_lvl.building_level)?;
+	let (bld, avail_data) =
+		player_buildings::get_player_bld_count_level(conn, settlement_id, bld_id)?;
+	// Get all building data to look up required building levels
+	let player = players::get_by_id(conn, &player_bld.player_id)?;
+	let (_, all_bld_data) =
+		player_buildings::get_player_bld_counts_levels(conn, &player, settlement_id)?;
+	let bld_avail = requirement_operations::gen_avail_data(
+		bld,
+		avail_data,
+		reqs,
+		ConstructionInfo::default(),
+		&all_bld_data,
+	);
+	trace!("Building availability: {:?}", bld_avail);
+
+	if !bld_avail.buildable {
+		debug!(
+			"Building {} cannot be upgraded, locks present: {:?}",
+			player_bld.building_id, bld_avail.locks
+		);
+		return Err(Error::from((
+			ErrorKind::UpgradeBuildingError,
+			"Building has locks",
+			format!("{:?}", bld_avail.locks),
+		)));
+	}
+	Ok(())
+}
+
 /// Confirms completion of a building upgrade.
 ///
 /// This function processes the completion of a building upgrade by verifying that
-/// the upgrade time has elapsed and incrementing the building level. It validates
-/// both the upgrade state and timing constraints.
+/// the upgrade time has elapsed and incrementing the building level. It is the fallback
+/// for the confirmation job scheduled when the upgrade started, and is idempotent: a
+/// building that is not upgrading, because the job already confirmed it, is returned as is.
 ///
 /// # Arguments
 ///
@@ -287,57 +408,269 @@ pub fn upgrade_building(
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` on successful upgrade confirmation, or an error if:
-/// - Building is not in upgrading state
+/// Returns the confirmed building, or an error if:
 /// - Upgrade time has not yet elapsed
-/// - Time format is invalid
 /// - Database operation fails
 ///
 /// # Errors
 ///
-/// This function returns `ConfirmUpgradeError` variants for:
-/// - Invalid state ("Building is not upgrading")
-/// - Premature confirmation ("Upgrade time has not passed")
-/// - Invalid time format ("Invalid time format")
+/// This function returns a `ConfirmUpgradeError` for premature confirmation ("Upgrade time
+/// has not passed").
 #[instrument(skip(conn))]
 pub fn confirm_upgrade(conn: &mut DbConn, id: &PlayerBuildingKey) -> Result<PlayerBuilding> {
 	debug!("Starting confirm upgrade for building {}", id);
-	let player_bld = player_buildings::get_by_id(conn, id)?;
-	trace!("Player building details: {:?}", player_bld);
-	match player_bld.upgrade_finishes_at {
-		None => {
-			debug!("Building {} is not in upgrading state", id);
-			Err(Error::from((
+	conn.transaction(|connection| {
+		let player_bld = player_buildings::lock_by_id(connection, id)?;
+		trace!("Player building details: {:?}", player_bld);
+		let Some(upgrade_finishes_at) = player_bld.upgrade_finishes_at else {
+			debug!("Building {} is not upgrading, nothing to confirm", id);
+			return Ok(player_bld);
+		};
+		if Utc::now() < upgrade_finishes_at {
+			debug!(
+				"Upgrade time has not passed yet: current={}, finishes_at={}",
+				Utc::now(),
+				upgrade_finishes_at
+			);
+			return Err(Error::from((
 				ErrorKind::ConfirmUpgradeError,
-				"Building is not upgrading",
-			)))
+				"Upgrade time has not passed",
+			)));
 		}
-		Some(eta) => {
-			let upgrade_finishes_at = DateTime::parse_from_rfc3339(&eta).map_err(|_| {
-				Error::from((ErrorKind::ConfirmUpgradeError, "Invalid time format"))
-			})?;
-			if Utc::now() >= upgrade_finishes_at.to_utc() {
-				debug!("Upgrade time has passed, incrementing building level");
-				let bld = player_buildings::inc_level(conn, id)?;
-				info!("Successfully confirmed upgrade for building {}", id);
-				trace!(?bld, "Updated player building details");
-				Ok(bld)
-			} else {
-				debug!(
-					"Upgrade time has not passed yet: current={}, finishes_at={}",
-					Utc::now(),
-					upgrade_finishes_at
-				);
-				Err(Error::from((
-					ErrorKind::ConfirmUpgradeError,
-					"Upgrade time has not passed",
-				)))
+		apply_upgrade(connection, id)
+	})
+}
+
+/// Confirms the upgrade of a building if it has finished, for the confirmation job.
+///
+/// Jobs of upgrades that were cancelled, or confirmed by the player in the meantime, find
+/// the building not upgrading or upgrading again, and leave it untouched.
+///
+/// # Returns
+///
+/// The confirmed building, or `None` if there was no finished upgrade to confirm
+#[instrument(skip(conn))]
+pub fn confirm_finished_upgrade(
+	conn: &mut DbConn,
+	id: &PlayerBuildingKey,
+) -> Result<Option<PlayerBuilding>> {
+	debug!("Confirming finished upgrade of building {}", id);
+	conn.transaction(|connection| {
+		let player_bld = player_buildings::lock_by_id(connection, id)?;
+		match player_bld.upgrade_finishes_at {
+			Some(finishes_at) if Utc::now() >= finishes_at => {
+				apply_upgrade(connection, id).map(Some)
+			}
+			_ => {
+				debug!("Building {} has no finished upgrade, skipping", id);
+				Ok(None)
 			}
 		}
+	})
+}
+
+/// Completes the pending upgrade of a locked building and raises it a level.
+fn apply_upgrade(conn: &mut DbConn, id: &PlayerBuildingKey) -> Result<PlayerBuilding> {
+	building_upgrades::complete_pending(conn, id)?;
+	let bld = player_buildings::inc_level(conn, id)?;
+	info!("Successfully confirmed upgrade for building {}", id);
+	trace!(?bld, "Updated player building details");
+	Ok(bld)
+}
+
+/// Schedules the job confirming a construction or upgrade once it finishes.
+///
+/// Failing to schedule it is not fatal, the player can still confirm the upgrade.
+fn schedule_confirmation(
+	job_queue: &JobQueue,
+	player_bld_id: &PlayerBuildingKey,
+	finishes_at: DateTime<Utc>,
+) {
+	let payload = BuildingJobPayload::ConfirmUpgrade {
+		player_building_id: *player_bld_id,
+	};
+	match job_queue.enqueue(JobType::Building, payload, JobPriority::Normal, finishes_at) {
+		Ok(job_id) => trace!("Scheduled upgrade confirmation job: {}", job_id),
+		Err(e) => warn!(
+			"Failed to schedule confirmation of building {}, it awaits the player: {}",
+			player_bld_id, e
+		),
+	}
+}
+
+/// Cancels the upgrade of a building that is still underway.
+///
+/// # Refund Calculation
+/// - Refunds 80% of the upgrade's cost based on its remaining time
+/// - If 50% of the upgrade time has passed: 40% refund (80% * 50%)
+///
+/// Clearing the upgrade timer, cancelling the ledger entry and the refund happen in a single
+/// transaction that holds a lock on the building, so a concurrent confirmation or second
+/// cancellation cannot act on the same upgrade.
+///
+/// # Errors
+///
+/// This function returns `CancelUpgradeError` variants for:
+/// - Invalid state ("Building is not upgrading")
+/// - An upgrade that can be confirmed instead ("Upgrade has already finished")
+#[instrument(skip(conn))]
+pub fn cancel_upgrade(
+	conn: &mut DbConn,
+	id: &PlayerBuildingKey,
+) -> Result<(PlayerBuilding, ResourceDelta)> {
+	debug!("Cancelling upgrade of building {}", id);
+	let (bld, refund) = conn.transaction(|connection| {
+		let player_bld = player_buildings::lock_by_id(connection, id)?;
+		let refund = cancel_pending_upgrade(connection, &player_bld, Utc::now())?;
+		let bld = player_buildings::set_upgrade_eta(connection, id, None)?;
+		refund_resources(connection, &bld.settlement_id, &refund)?;
+		Ok::<_, Error>((bld, refund))
+	})?;
+	info!(
+		"Cancelled upgrade of building {} for player {}, refunded {:?}",
+		id, bld.player_id, refund
+	);
+	Ok((bld, refund))
+}
+
+/// Lowers a building by one level, for players rebuilding their base.
+///
+/// Refunds [`DOWNGRADE_REFUND_RATE`] of what the current level cost. An upgrade underway is
+/// cancelled and refunded as by [`cancel_upgrade`] in the same transaction.
+///
+/// # Errors
+///
+/// This function returns a `DowngradeBuildingError` for buildings at level 1 or below
+/// ("Building is at its lowest level") or with upgrades in the construction queue ("Building
+/// has queued upgrades"), and the errors of [`cancel_upgrade`] if an upgrade finished but
+/// was not confirmed yet.
+#[instrument(skip(conn))]
+pub fn downgrade_building(
+	conn: &mut DbConn,
+	id: &PlayerBuildingKey,
+) -> Result<(PlayerBuilding, ResourceDelta)> {
+	debug!("Downgrading building {}", id);
+	let (bld, refund) = conn.transaction(|connection| {
+		let player_bld = player_buildings::lock_by_id(connection, id)?;
+		if player_bld.level <= 1 {
+			return Err(Error::from((
+				ErrorKind::DowngradeBuildingError,
+				"Building is at its lowest level",
+			)));
+		}
+		if construction_queue::count_active_for_building(connection, id)? > 0 {
+			return Err(Error::from((
+				ErrorKind::DowngradeBuildingError,
+				"Building has queued upgrades",
+			)));
+		}
+
+		let upgrade_refund = if player_bld.upgrade_finishes_at.is_some() {
+			cancel_pending_upgrade(connection, &player_bld, Utc::now())?
+		} else {
+			(0, 0, 0, 0)
+		};
+		let bld_lvl = building_levels::get_by_bld_and_level(
+			connection,
+			&player_bld.building_id,
+			player_bld.level,
+		)?;
+		let refund = (
+			upgrade_refund.0 + refund_share(bld_lvl.req_food.unwrap_or(0), DOWNGRADE_REFUND_RATE),
+			upgrade_refund.1 + refund_share(bld_lvl.req_wood.unwrap_or(0), DOWNGRADE_REFUND_RATE),
+			upgrade_refund.2 + refund_share(bld_lvl.req_stone.unwrap_or(0), DOWNGRADE_REFUND_RATE),
+			upgrade_refund.3 + refund_share(bld_lvl.req_gold.unwrap_or(0), DOWNGRADE_REFUND_RATE),
+		);
+
+		let bld = player_buildings::dec_level(connection, id)?;
+		refund_resources(connection, &bld.settlement_id, &refund)?;
+		Ok((bld, refund))
+	})?;
+	info!(
+		"Downgraded building {} of player {} to level {}, refunded {:?}",
+		id, bld.player_id, bld.level, refund
+	);
+	Ok((bld, refund))
+}
+
+/// Cancels the upgrade underway on a locked building and returns its refund.
+///
+/// The cost and start of the upgrade come from its ledger entry. Upgrades started before
+/// the ledger existed fall back to the definition of the level they lead to.
+fn cancel_pending_upgrade(
+	conn: &mut DbConn,
+	player_bld: &PlayerBuilding,
+	now: DateTime<Utc>,
+) -> Result<ResourceDelta> {
+	let Some(finishes_at) = player_bld.upgrade_finishes_at else {
+		return Err(Error::from((
+			ErrorKind::CancelUpgradeError,
+			"Building is not upgrading",
+		)));
+	};
+	if now >= finishes_at {
+		return Err(Error::from((
+			ErrorKind::CancelUpgradeError,
+			"Upgrade has already finished",
+		)));
+	}
+
+	let (cost, started_at) = match building_upgrades::cancel_pending(conn, &player_bld.id)? {
+		Some(upgrade) => (
+			(upgrade.food, upgrade.wood, upgrade.stone, upgrade.gold),
+			upgrade.created_at,
+		),
+		None => {
+			let bld_lvl = building_levels::get_next_upgrade(
+				conn,
+				&player_bld.building_id,
+				&player_bld.level,
+			)?;
+			(
+				(
+					bld_lvl.req_food.unwrap_or(0),
+					bld_lvl.req_wood.unwrap_or(0),
+					bld_lvl.req_stone.unwrap_or(0),
+					bld_lvl.req_gold.unwrap_or(0),
+				),
+				finishes_at - TimeDelta::seconds(bld_lvl.upgrade_seconds),
+			)
+		}
+	};
+
+	let total = (finishes_at - started_at).num_milliseconds();
+	let remaining_ratio = if total <= 0 {
+		1.0
+	} else {
+		((finishes_at - now).num_milliseconds() as f64 / total as f64).clamp(0.0, 1.0)
+	};
+	let refund_ratio = CANCEL_UPGRADE_REFUND_RATE * remaining_ratio;
+	trace!("Refunding {:.2} of upgrade cost {:?}", refund_ratio, cost);
+	Ok((
+		refund_share(cost.0, refund_ratio),
+		refund_share(cost.1, refund_ratio),
+		refund_share(cost.2, refund_ratio),
+		refund_share(cost.3, refund_ratio),
+	))
+}
+
+fn refund_share(amount: i64, ratio: f64) -> i64 {
+	(amount as f64 * ratio) as i64
+}
+
+fn refund_resources(
+	conn: &mut DbConn,
+	settlement_id: &SettlementKey,
+	refund: &ResourceDelta,
+) -> Result<()> {
+	if refund.0 > 0 || refund.1 > 0 || refund.2 > 0 || refund.3 > 0 {
+		resources::add_to_settlement(conn, settlement_id, refund)?;
+		trace!("Refunded resources");
 	}
+	Ok(())
 }
 
-/// Validates whether a player has sufficient resources for a building operation.
+/// Validates whether a settlement has sufficient resources for a building operation.
 ///
 /// This internal utility function checks all four resource types (food, wood, stone, gold)
 /// against the requirements specified in the building level configuration. It provides
@@ -346,12 +679,12 @@ pub fn confirm_upgrade(conn: &mut DbConn, id: &PlayerBuildingKey) -> Result<Play
 /// # Arguments
 ///
 /// * `conn` - Database connection for querying player resources
-/// * `player_id` - Unique identifier of the player whose resources to check
+/// * `settlement_id` - Unique identifier of the settlement whose resources to check
 /// * `bld_lvl` - Building level configuration containing resource requirements
 ///
 /// # Returns
 ///
-/// Returns `true` if the player has sufficient resources for all required types,
+/// Returns `true` if the settlement has sufficient resources for all required types,
 /// `false` if any resource is insufficient, or an error if the database query fails.
 ///
 /// # Errors
@@ -360,10 +693,10 @@ pub fn confirm_upgrade(conn: &mut DbConn, id: &PlayerBuildingKey) -> Result<Play
 #[instrument(skip(conn, bld_lvl))]
 fn has_enough_resources(
 	conn: &mut DbConn,
-	player_id: &PlayerKey,
+	settlement_id: &SettlementKey,
 	bld_lvl: &BuildingLevel,
 ) -> Result<bool> {
-	debug!("Checking resources for player: {}", player_id);
+	debug!("Checking resources for settlement: {}", settlement_id);
 	trace!(
 		"Required resources: food={}, wood={}, stone={}, gold={}",
 		bld_lvl.req_food.unwrap_or(0),
@@ -371,7 +704,7 @@ fn has_enough_resources(
 		bld_lvl.req_stone.unwrap_or(0),
 		bld_lvl.req_gold.unwrap_or(0)
 	);
-	let res = resources::get_by_player_id(conn, player_id)?;
+	let res = resources::get_by_settlement(conn, settlement_id)?;
 	let has_enough_food = res.food >= bld_lvl.req_food.unwrap_or(0);
 	let has_enough_wood = res.wood >= bld_lvl.req_wood.unwrap_or(0);
 	let has_enough_stone = res.stone >= bld_lvl.req_stone.unwrap_or(0);
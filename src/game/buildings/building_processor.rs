@@ -0,0 +1,121 @@
+//! Building job processor for background building tasks.
+//!
+//! This module implements the job processing functionality for building jobs: the periodic
+//! evaluation of players' planned actions, the completion of queued upgrades, and the
+//! confirmation of upgrades once they finish.
+
+use axum::extract::FromRef;
+use chrono::Utc;
+use tracing::{debug, info, instrument, trace};
+
+use crate::Error;
+use crate::domain::app_state::{AppEvents, AppModifierCache, AppPool, AppQueue, AppState};
+use crate::domain::building::construction::ConstructionStatus;
+use crate::domain::events::GameEvent;
+use crate::domain::jobs::{Job, JobType};
+use crate::game::buildings::plan_operations::{self, BuildingJobPayload, PLAN_EVALUATION_INTERVAL};
+use crate::game::buildings::{building_operations, construction_operations};
+use crate::job_queue::job_processor::{JobHandler, JobOutcome, JobWorker};
+
+/// A handler for building-related background jobs.
+///
+/// The `BuildingHandler` implements the `JobHandler` trait and is responsible
+/// for executing planned actions once they become affordable, rescheduling the
+/// evaluation for as long as a player has pending plans, for completing the
+/// entries of construction queues, and for confirming finished upgrades.
+pub struct BuildingHandler {
+	/// Database connection pool
+	pool: AppPool,
+	/// Job queue used to reschedule plan evaluations
+	job_queue: AppQueue,
+	/// Event bus for upgrade completion events
+	events: AppEvents,
+	/// Cache of modifier multipliers, invalidated for players whose buildings level up
+	modifier_cache: AppModifierCache,
+}
+
+/// Workers running the [`BuildingHandler`].
+pub type BuildingProcessor = JobWorker<BuildingHandler>;
+
+impl JobHandler for BuildingHandler {
+	const JOB_TYPE: JobType = JobType::Building;
+	const WORKER_NAME: &'static str = "building-goblin";
+
+	/// Creates a new `BuildingHandler` with what it needs from the application state.
+	fn new(app_state: &AppState) -> Self {
+		let pool = AppPool::from_ref(app_state);
+		let job_queue = AppQueue::from_ref(app_state);
+		let events = AppEvents::from_ref(app_state);
+		let modifier_cache = AppModifierCache::from_ref(app_state);
+		Self {
+			pool,
+			job_queue,
+			events,
+			modifier_cache,
+		}
+	}
+
+	#[instrument(skip(self, job), fields(job_id = %job.id))]
+	async fn handle(&self, job: Job) -> Result<JobOutcome, Error> {
+		debug!("Processing building job: {}", job.id);
+		trace!("Job details: {:?}", job);
+
+		let payload: BuildingJobPayload = serde_json::from_value(job.payload.clone())?;
+		let mut conn = self.pool.get()?;
+
+		let outcome = match payload {
+			BuildingJobPayload::EvaluatePlans { player_id } => {
+				let evaluation =
+					plan_operations::evaluate_plans(&mut conn, &self.job_queue, &player_id)?;
+				info!(
+					"Evaluated plans for player {}: {} executed, {} remaining",
+					player_id, evaluation.executed, evaluation.remaining
+				);
+				if evaluation.remaining > 0 {
+					let next_run = Utc::now() + PLAN_EVALUATION_INTERVAL;
+					plan_operations::schedule_evaluation(&self.job_queue, &player_id, next_run)?;
+				}
+				serde_json::to_value(evaluation)?
+			}
+			BuildingJobPayload::CompleteConstruction { entry_id } => {
+				let entry = construction_operations::complete_construction(&mut conn, &entry_id)?;
+				info!(
+					"Completed construction {} for player {}: building {} at level {}",
+					entry.id, entry.player_id, entry.player_building_id, entry.level
+				);
+				if entry.status == ConstructionStatus::Completed {
+					self.modifier_cache.invalidate_user(entry.player_id);
+					self.events.publish(GameEvent::UpgradeCompleted {
+						player_id: entry.player_id,
+						player_building_id: entry.player_building_id,
+						level: entry.level,
+					});
+				}
+				serde_json::to_value(entry)?
+			}
+			BuildingJobPayload::ConfirmUpgrade { player_building_id } => {
+				let confirmed =
+					building_operations::confirm_finished_upgrade(&mut conn, &player_building_id)?;
+				if let Some(bld) = &confirmed {
+					info!(
+						"Confirmed upgrade of building {} for player {} to level {}",
+						bld.id, bld.player_id, bld.level
+					);
+					self.modifier_cache.invalidate_user(bld.player_id);
+					self.events.publish(GameEvent::UpgradeCompleted {
+						player_id: bld.player_id,
+						player_building_id: bld.id,
+						level: bld.level,
+					});
+				}
+				serde_json::json!({
+					"player_building_id": player_building_id,
+					"level": confirmed.map(|bld| bld.level),
+				})
+			}
+		};
+
+		debug!("Completed processing building job: {}", job.id);
+		Ok(Some(outcome))
+	}
+}
@@ -0,0 +1,302 @@
+//! Construction queue operations for the Empire game.
+//!
+//! Players can queue several building upgrades that are worked on one after another, like
+//! units in the training queue. Each queued upgrade is paid when it is queued, starts once
+//! the one ahead of it completes, and is completed by a building job scheduled for the
+//! time it finishes. The queue is shared by all settlements of a player, and the number of
+//! upgrades it holds grows with the Keep of their capital. Upgrades are paid from the
+//! storage of the building's settlement.
+//!
+//! Buildings with queued upgrades cannot be upgraded or downgraded directly, and buildings
+//! with a direct upgrade underway cannot be queued, so both paths never touch the same
+//! upgrade ledger entries.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::Connection;
+use tracing::{debug, info, instrument, trace, warn};
+
+use crate::db::resources::ResourceDelta;
+use crate::db::{
+	DbConn, building_levels, building_upgrades, construction_queue, player_buildings, resources,
+	settlements,
+};
+use crate::domain::building::construction::{
+	ConstructionQueueEntry, ConstructionQueueKey, ConstructionStatus, NewConstructionQueueEntry,
+};
+use crate::domain::building::upgrade::NewBuildingUpgrade;
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::jobs::JobType;
+use crate::domain::player::PlayerKey;
+use crate::domain::player::buildings::PlayerBuildingKey;
+use crate::game::buildings::building_operations;
+use crate::game::buildings::plan_operations::BuildingJobPayload;
+use crate::job_queue::{JobPriority, JobQueue};
+
+/// Keep levels needed for each construction slot beyond the first.
+pub const KEEP_LEVELS_PER_CONSTRUCTION_SLOT: i32 = 3;
+
+/// A player's construction queue and how many entries it can hold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstructionQueue {
+	pub capacity: i64,
+	/// Active entries, in the order they complete
+	pub entries: Vec<ConstructionQueueEntry>,
+}
+
+/// Returns how many upgrades a player with a capital Keep at `keep_level` can queue.
+///
+/// One slot, plus one for every [`KEEP_LEVELS_PER_CONSTRUCTION_SLOT`] Keep levels.
+pub fn construction_slots(keep_level: i32) -> i64 {
+	1 + i64::from(keep_level.max(0) / KEEP_LEVELS_PER_CONSTRUCTION_SLOT)
+}
+
+/// Returns a player's active construction queue with its capacity.
+#[instrument(skip(conn))]
+pub fn get_queue(conn: &mut DbConn, player_id: &PlayerKey) -> Result<ConstructionQueue> {
+	let capital = settlements::get_capital(conn, player_id)?;
+	let keep_level = player_buildings::get_keep_level(conn, &capital.id)?;
+	let entries = construction_queue::get_active_for_player(conn, player_id)?;
+	Ok(ConstructionQueue {
+		capacity: construction_slots(keep_level),
+		entries,
+	})
+}
+
+/// Queues the next upgrade of a player's building.
+///
+/// A building can be queued several times, each entry raising it one level further. The
+/// upgrade is paid right away and starts when the last entry of the queue completes, or
+/// right away if the queue is empty.
+///
+/// # Validation
+/// - Building must be owned by the player and not have a direct upgrade underway
+/// - The queue must not be full, see [`construction_slots`]
+/// - The level the entry leads to must exist, and its requirements be met by the current
+///   buildings of its settlement; upgrades still in the queue do not count towards them
+/// - The building's settlement must have sufficient resources
+///
+/// # Errors
+/// - `QueueConstructionError` for buildings that are upgrading or would exceed their max level
+/// - `ConstructionQueueFullError` if every construction slot is taken
+/// - `UpgradeBuildingError` if requirements of the level are not met
+/// - `InsufficientResourcesError` if the player cannot afford the upgrade
+#[instrument(skip(conn, job_queue))]
+pub fn queue_upgrade(
+	conn: &mut DbConn,
+	job_queue: &JobQueue,
+	player_id: &PlayerKey,
+	player_bld_id: &PlayerBuildingKey,
+) -> Result<ConstructionQueueEntry> {
+	debug!(
+		"Queueing upgrade of building {} for player {}",
+		player_bld_id, player_id
+	);
+	let settlement_id = player_buildings::get_owned(conn, player_id, player_bld_id)?.settlement_id;
+
+	let (entry, cost) = conn.transaction(|connection| {
+		// AIDEV-NOTE: the capital's resource row lock serializes queueing per player, so
+		// concurrent requests cannot both take the last slot. The building's settlement is
+		// always locked after the capital, which pays for its own buildings with that lock.
+		let capital = settlements::get_capital(connection, player_id)?;
+		let capital_res = resources::lock_by_settlement(connection, &capital.id)?;
+		let player_res = if settlement_id == capital.id {
+			capital_res
+		} else {
+			resources::lock_by_settlement(connection, &settlement_id)?
+		};
+		let queue = construction_queue::get_active_for_player(connection, player_id)?;
+		let capacity =
+			construction_slots(player_buildings::get_keep_level(connection, &capital.id)?);
+		if queue.len() as i64 >= capacity {
+			return Err(Error::from((
+				ErrorKind::ConstructionQueueFullError,
+				"Construction queue is full",
+			)));
+		}
+		trace!("Queue capacity check passed: {}/{}", queue.len(), capacity);
+
+		let (player_bld, max_level) =
+			player_buildings::get_upgrade_tuple(connection, player_bld_id)?;
+		if player_bld.upgrade_finishes_at.is_some() {
+			return Err(Error::from((
+				ErrorKind::QueueConstructionError,
+				"Building is already upgrading",
+			)));
+		}
+		let queued = queue
+			.iter()
+			.filter(|entry| entry.player_building_id == *player_bld_id)
+			.count() as i32;
+		let from_level = player_bld.level + queued;
+		if max_level.is_some_and(|max| from_level >= max) {
+			return Err(Error::from((
+				ErrorKind::QueueConstructionError,
+				"Building is at max level",
+			)));
+		}
+
+		let bld_lvl =
+			building_levels::get_next_upgrade(connection, &player_bld.building_id, &from_level)?;
+		building_operations::check_upgrade_locks(connection, &player_bld, &bld_lvl)?;
+		let cost: ResourceDelta = (
+			bld_lvl.req_food.unwrap_or(0),
+			bld_lvl.req_wood.unwrap_or(0),
+			bld_lvl.req_stone.unwrap_or(0),
+			bld_lvl.req_gold.unwrap_or(0),
+		);
+		if player_res.food < cost.0
+			|| player_res.wood < cost.1
+			|| player_res.stone < cost.2
+			|| player_res.gold < cost.3
+		{
+			return Err(Error::from((
+				ErrorKind::InsufficientResourcesError,
+				"Not enough resources",
+			)));
+		}
+		resources::deduct_from_settlement(connection, &settlement_id, &cost)?;
+		trace!("Deducted resources: {:?}", cost);
+
+		let upgrade = building_upgrades::create(
+			connection,
+			NewBuildingUpgrade {
+				player_id: *player_id,
+				player_building_id: *player_bld_id,
+				building_id: player_bld.building_id,
+				level: bld_lvl.building_level,
+				food: cost.0,
+				wood: cost.1,
+				stone: cost.2,
+				gold: cost.3,
+			},
+		)?;
+
+		let now = Utc::now();
+		let starts_at = queue.last().map_or(now, |last| last.completes_at.max(now));
+		let status = if queue.is_empty() {
+			ConstructionStatus::InProgress
+		} else {
+			ConstructionStatus::Pending
+		};
+		let entry = construction_queue::create(
+			connection,
+			NewConstructionQueueEntry {
+				player_id: *player_id,
+				player_building_id: *player_bld_id,
+				upgrade_id: upgrade.id,
+				level: bld_lvl.building_level,
+				status,
+				starts_at,
+				completes_at: starts_at + TimeDelta::seconds(bld_lvl.upgrade_seconds),
+			},
+		)?;
+		Ok::<_, Error>((entry, cost))
+	})?;
+
+	// Schedule completion job (outside transaction to avoid holding locks)
+	let payload = BuildingJobPayload::CompleteConstruction { entry_id: entry.id };
+	let job_id = match job_queue.enqueue(
+		JobType::Building,
+		payload,
+		JobPriority::Normal,
+		entry.completes_at,
+	) {
+		Ok(id) => id,
+		Err(e) => {
+			// AIDEV-NOTE: Cleanup on enqueue failure - refund resources and delete the entry
+			warn!("Failed to schedule construction job, rolling back: {}", e);
+			if let Err(cleanup_err) = cleanup_failed_queueing(conn, &entry, &cost) {
+				warn!("Failed to cleanup after enqueue failure: {}", cleanup_err);
+			}
+			return Err(Error::from((
+				ErrorKind::QueueConstructionError,
+				"Failed to schedule construction job",
+				format!("{:?}", e),
+			)));
+		}
+	};
+	trace!("Scheduled construction job: {}", job_id);
+
+	// The completion job finds the entry through its payload, linking it is non-critical
+	let entry = match construction_queue::set_job_id(conn, &entry.id, &job_id) {
+		Ok(updated) => updated,
+		Err(e) => {
+			warn!("Failed to link job_id to entry, continuing anyway: {}", e);
+			entry
+		}
+	};
+
+	info!(
+		"Queued upgrade of building {} to level {} for player {}, completes at {}",
+		player_bld_id, entry.level, player_id, entry.completes_at
+	);
+	Ok(entry)
+}
+
+/// Completes a construction queue entry, raising its building a level.
+///
+/// Called by the building processor once the entry's time has elapsed. The next pending
+/// entry of the player's queue is moved in progress. This function is idempotent - calling
+/// it multiple times is safe.
+#[instrument(skip(conn))]
+pub fn complete_construction(
+	conn: &mut DbConn,
+	entry_id: &ConstructionQueueKey,
+) -> Result<ConstructionQueueEntry> {
+	debug!("Completing construction entry {}", entry_id);
+	let res: Result<ConstructionQueueEntry> = conn.transaction(|connection| {
+		let Some(completed) = construction_queue::complete(connection, entry_id)? else {
+			debug!("Construction {} already completed, skipping", entry_id);
+			return construction_queue::get_by_id(connection, entry_id);
+		};
+		let bld = player_buildings::inc_level(connection, &completed.player_building_id)?;
+		building_upgrades::complete(connection, &completed.upgrade_id)?;
+		trace!("Building {} reached level {}", bld.id, bld.level);
+
+		if let Some(next) = construction_queue::start_next(connection, &completed.player_id)? {
+			trace!("Started next construction {}", next.id);
+		}
+		Ok(completed)
+	});
+
+	res.map_err(|e| {
+		warn!("Failed to complete construction {}: {}", entry_id, e);
+		Error::from((
+			ErrorKind::CompleteConstructionError,
+			"Failed to complete construction",
+			format!("{:?}", e),
+		))
+	})
+}
+
+/// Share of a construction queue entry that has elapsed by `now`, between 0 and 1.
+///
+/// Entries waiting for the one ahead of them have not progressed at all.
+pub fn construction_progress(entry: &ConstructionQueueEntry, now: DateTime<Utc>) -> f64 {
+	let total_seconds = (entry.completes_at - entry.starts_at).num_seconds();
+	if total_seconds <= 0 {
+		return 1.0;
+	}
+	let elapsed_seconds = (now - entry.starts_at).num_seconds().max(0);
+	(elapsed_seconds as f64 / total_seconds as f64).min(1.0)
+}
+
+/// Cleans up a failed queueing by refunding resources and deleting the entry and its ledger row.
+///
+/// Called when job scheduling fails after the transaction has committed.
+fn cleanup_failed_queueing(
+	conn: &mut DbConn,
+	entry: &ConstructionQueueEntry,
+	cost: &ResourceDelta,
+) -> Result<()> {
+	conn.transaction(|connection| {
+		let settlement_id =
+			player_buildings::get_by_id(connection, &entry.player_building_id)?.settlement_id;
+		resources::add_to_settlement(connection, &settlement_id, cost)?;
+		trace!("Refunded resources after failed job scheduling");
+		construction_queue::delete(connection, &entry.id)?;
+		building_upgrades::delete(connection, &entry.upgrade_id)?;
+		trace!("Deleted orphaned construction entry {}", entry.id);
+		Ok(())
+	})
+}
@@ -1,2 +1,5 @@
-pub mod building_operations;
-pub mod requirement_operations;
+pub mod building_operations;
+pub mod building_processor;
+pub mod construction_operations;
+pub mod plan_operations;
+pub mod requirement_operations;
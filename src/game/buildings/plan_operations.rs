@@ -0,0 +1,275 @@
+//! Planned action operations for the Empire game.
+//!
+//! Players can queue construction and upgrade orders ahead of time ("upgrade the Keep
+//! once I can afford it"). This module validates and stores those plans, and provides
+//! the evaluator that the building processor runs periodically to execute them through
+//! the regular [`building_operations`] entry points.
+//!
+//! Plans are executed strictly in the order they were created: the evaluator stops at
+//! the first plan that cannot be executed yet, so cheaper plans further down the list
+//! never starve the one at the head of the queue.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, instrument, trace, warn};
+
+use crate::db::{DbConn, buildings, planned_actions, player_buildings, players, settlements};
+use crate::domain::building::BuildingKey;
+use crate::domain::building::construction::ConstructionQueueKey;
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::factions::FactionCode;
+use crate::domain::jobs::{JobKey, JobType};
+use crate::domain::player::PlayerKey;
+use crate::domain::player::buildings::PlayerBuildingKey;
+use crate::domain::player::planned_action::{
+	NewPlannedAction, PlannedAction, PlannedActionKey, PlannedActionKind, PlannedActionStatus,
+};
+use crate::domain::settlement::SettlementKey;
+use crate::game::buildings::building_operations;
+use crate::job_queue::{JobPriority, JobQueue};
+
+/// Maximum number of pending plans a player can have at once.
+///
+/// AIDEV-NOTE: There is no Planner building or premium tier yet, so the limit is flat.
+/// [`plan_capacity`] is the single place to extend once either exists.
+pub const MAX_PLANNED_ACTIONS: i64 = 5;
+
+/// How long the evaluator waits before re-checking plans that could not be executed.
+pub const PLAN_EVALUATION_INTERVAL: TimeDelta = TimeDelta::minutes(1);
+
+/// Job payloads handled by the building processor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum BuildingJobPayload {
+	/// Try to execute the pending planned actions of a player
+	EvaluatePlans { player_id: PlayerKey },
+	/// Complete an upgrade of the construction queue and start the next one
+	CompleteConstruction { entry_id: ConstructionQueueKey },
+	/// Confirm a construction or upgrade started outside the queue once it has finished
+	ConfirmUpgrade {
+		player_building_id: PlayerBuildingKey,
+	},
+}
+
+/// Result of a single evaluator pass over a player's plans.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PlanEvaluation {
+	/// Number of plans executed during this pass
+	pub executed: usize,
+	/// Number of plans still pending after this pass
+	pub remaining: usize,
+}
+
+/// Returns how many pending plans the player is allowed to hold.
+pub fn plan_capacity(conn: &mut DbConn, player_id: &PlayerKey) -> Result<i64> {
+	Ok(MAX_PLANNED_ACTIONS)
+}
+
+/// Queues a new planned action for a player.
+///
+/// # Validation
+/// - Construct plans must reference a building type available to the player's faction, and
+///   build in one of the player's settlements, the capital if `settlement_id` is `None`
+/// - Upgrade plans must reference a building owned by the player
+/// - The player must not exceed their plan capacity
+///
+/// When this is the player's first pending plan, an evaluation job is scheduled right away.
+#[instrument(skip(conn, job_queue))]
+pub fn create_plan(
+	conn: &mut DbConn,
+	job_queue: &JobQueue,
+	player_id: &PlayerKey,
+	action: PlannedActionKind,
+	building_id: Option<BuildingKey>,
+	player_building_id: Option<PlayerBuildingKey>,
+	settlement_id: Option<SettlementKey>,
+) -> Result<PlannedAction> {
+	debug!("Creating {:?} plan for player {}", action, player_id);
+	let new_plan = match action {
+		PlannedActionKind::Construct => {
+			let Some(bld_id) = building_id else {
+				return Err(Error::from((
+					ErrorKind::CreatePlanError,
+					"Construct plans require a building_id",
+				)));
+			};
+			let player = players::get_by_id(conn, player_id)?;
+			let bld = buildings::get_by_id(conn, &bld_id).map_err(|_| {
+				Error::from((ErrorKind::CreatePlanError, "Building does not exist"))
+			})?;
+			if bld.faction != player.faction && bld.faction != FactionCode::Neutral {
+				return Err(Error::from((
+					ErrorKind::CreatePlanError,
+					"Building is not available to this faction",
+				)));
+			}
+			if let Some(settlement_id) = settlement_id
+				&& settlements::find_owned(conn, player_id, &settlement_id)?.is_none()
+			{
+				return Err(Error::from((
+					ErrorKind::NotFoundError,
+					"Settlement not found",
+				)));
+			}
+			NewPlannedAction {
+				player_id: *player_id,
+				action,
+				building_id: Some(bld_id),
+				player_building_id: None,
+				settlement_id,
+			}
+		}
+		PlannedActionKind::Upgrade => {
+			let Some(player_bld_id) = player_building_id else {
+				return Err(Error::from((
+					ErrorKind::CreatePlanError,
+					"Upgrade plans require a player_building_id",
+				)));
+			};
+			let player_bld = player_buildings::get_owned(conn, player_id, &player_bld_id)?;
+			NewPlannedAction {
+				player_id: *player_id,
+				action,
+				building_id: None,
+				player_building_id: Some(player_bld.id),
+				settlement_id: None,
+			}
+		}
+	};
+
+	let pending = planned_actions::count_pending_for_player(conn, player_id)?;
+	let capacity = plan_capacity(conn, player_id)?;
+	if pending >= capacity {
+		debug!(
+			"Player {} has {} pending plans, capacity {}",
+			player_id, pending, capacity
+		);
+		return Err(Error::from((
+			ErrorKind::PlanLimitReachedError,
+			"Planned action limit reached",
+		)));
+	}
+
+	let plan = planned_actions::create(conn, new_plan)?;
+	info!("Created plan {} for player {}", plan.id, player_id);
+
+	// AIDEV-NOTE: Only the first pending plan kicks off the evaluator; from then on the
+	// evaluator reschedules itself for as long as there are pending plans left.
+	if pending == 0 {
+		schedule_evaluation(job_queue, player_id, Utc::now())?;
+	}
+
+	Ok(plan)
+}
+
+/// Lists the pending plans of a player in execution order.
+#[instrument(skip(conn))]
+pub fn list_plans(conn: &mut DbConn, player_id: &PlayerKey) -> Result<Vec<PlannedAction>> {
+	planned_actions::get_pending_for_player(conn, player_id)
+}
+
+/// Cancels a pending plan owned by the player.
+#[instrument(skip(conn))]
+pub fn cancel_plan(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	plan_id: &PlannedActionKey,
+) -> Result<PlannedAction> {
+	let plan = planned_actions::find_owned(conn, player_id, plan_id)?
+		.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Plan not found")))?;
+
+	if plan.status != PlannedActionStatus::Pending {
+		return Err(Error::from((
+			ErrorKind::CancelPlanError,
+			"Plan is no longer pending",
+		)));
+	}
+
+	let plan = planned_actions::cancel(conn, &plan.id)?;
+	info!("Cancelled plan {} for player {}", plan.id, player_id);
+	Ok(plan)
+}
+
+/// Executes as many pending plans of a player as currently possible, in order.
+///
+/// Stops at the first plan that cannot be executed and records the reason on it.
+/// Execution failures are not propagated, so a single unaffordable plan never fails
+/// the evaluator job itself.
+#[instrument(skip(conn, job_queue))]
+pub fn evaluate_plans(
+	conn: &mut DbConn,
+	job_queue: &JobQueue,
+	player_id: &PlayerKey,
+) -> Result<PlanEvaluation> {
+	let plans = planned_actions::get_pending_for_player(conn, player_id)?;
+	trace!("Evaluating {} pending plans", plans.len());
+
+	let mut evaluation = PlanEvaluation {
+		executed: 0,
+		remaining: plans.len(),
+	};
+	for plan in &plans {
+		match execute_plan(conn, job_queue, plan) {
+			Ok(()) => {
+				planned_actions::complete(conn, &plan.id)?;
+				evaluation.executed += 1;
+				evaluation.remaining -= 1;
+				info!("Executed plan {} for player {}", plan.id, player_id);
+			}
+			Err(err) => {
+				debug!("Plan {} not executable yet: {}", plan.id, err);
+				planned_actions::set_last_error(conn, &plan.id, &err.to_string())?;
+				break;
+			}
+		}
+	}
+
+	Ok(evaluation)
+}
+
+/// Schedules an evaluator pass for a player's plans.
+pub fn schedule_evaluation(
+	job_queue: &JobQueue,
+	player_id: &PlayerKey,
+	run_at: DateTime<Utc>,
+) -> Result<JobKey> {
+	let payload = BuildingJobPayload::EvaluatePlans {
+		player_id: *player_id,
+	};
+	job_queue
+		.enqueue(JobType::Building, payload, JobPriority::Low, run_at)
+		.inspect_err(|err| warn!("Failed to schedule plan evaluation: {:?}", err))
+}
+
+/// Runs a single plan through the matching building operation.
+fn execute_plan(conn: &mut DbConn, job_queue: &JobQueue, plan: &PlannedAction) -> Result<()> {
+	match (plan.action, plan.building_id, plan.player_building_id) {
+		(PlannedActionKind::Construct, Some(bld_id), _) => {
+			let settlement = match plan.settlement_id {
+				Some(settlement_id) => {
+					settlements::find_owned(conn, &plan.player_id, &settlement_id)?.ok_or_else(
+						|| Error::from((ErrorKind::NotFoundError, "Settlement not found")),
+					)?
+				}
+				None => settlements::get_capital(conn, &plan.player_id)?,
+			};
+			building_operations::construct_building(conn, job_queue, &settlement, &bld_id)?;
+		}
+		(PlannedActionKind::Upgrade, _, Some(player_bld_id)) => {
+			let player_bld = player_buildings::get_owned(conn, &plan.player_id, &player_bld_id)?;
+			if player_bld.upgrade_finishes_at.is_some() {
+				return Err(Error::from((
+					ErrorKind::UpgradeBuildingError,
+					"Building is already upgrading",
+				)));
+			}
+			building_operations::upgrade_building(conn, job_queue, &player_bld_id)?;
+		}
+		_ => {
+			return Err(Error::from((
+				ErrorKind::InvalidData,
+				"Plan is missing its target building",
+			)));
+		}
+	}
+	Ok(())
+}
@@ -7,13 +7,14 @@
 use std::collections::HashMap;
 
 use serde::Serialize;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::domain::building::requirement::BuildingRequirement;
 use crate::domain::building::{Building, BuildingKey};
 
 /// Contains resource costs and time required for building construction
-#[derive(Debug, Clone, Default, Serialize, Eq, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, ToSchema, Eq, PartialEq)]
 pub struct ConstructionInfo {
 	/// Food required for construction
 	pub food: i64,
@@ -28,7 +29,7 @@ pub struct ConstructionInfo {
 }
 
 /// Represents the availability status of a building, including build restrictions and current state
-#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+#[derive(Serialize, ToSchema, Clone, Debug, Eq, PartialEq)]
 pub struct BuildingAvailability {
 	/// The building definition
 	pub building: Building,
@@ -45,7 +46,7 @@ pub struct BuildingAvailability {
 }
 
 /// Represents different types of restrictions that can prevent a building from being constructed
-#[derive(Serialize, Copy, Clone, Debug, Eq, PartialEq, PartialOrd)]
+#[derive(Serialize, ToSchema, Copy, Clone, Debug, Eq, PartialEq, PartialOrd)]
 #[serde(tag = "kind")]
 pub enum BuildingLock {
 	/// Maximum allowed number of building instances has been reached
@@ -0,0 +1,211 @@
+//! Posting in the chat channels, reading their history, and muting players.
+//!
+//! The global channel reaches every player, the alliance channel the members of the
+//! poster's alliance. Every message is kept, and [`post`] returns the [`ChatBroadcast`] to
+//! publish on the chat bus, so connected players receive it right away.
+//!
+//! A player who posted `chat.flood_max_messages` messages within the last
+//! `chat.flood_window_seconds` has to wait before posting again, and players muted by a
+//! moderator cannot post until the mute runs out. Both still read the channels.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use tracing::{debug, info, instrument};
+
+use crate::configuration::ChatSettings;
+use crate::db::{DbConn, alliance_members, chat_messages, chat_mutes, players};
+use crate::domain::alliance::AllianceKey;
+use crate::domain::chat::{
+	ChatBroadcast, ChatChannel, ChatLine, ChatMute, NewChatMessage, NewChatMute,
+};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::player::PlayerKey;
+
+/// Longest message, in characters.
+pub const BODY_MAX_LENGTH: usize = 500;
+
+/// Default number of messages returned from the history.
+pub const DEFAULT_HISTORY_LIMIT: i64 = 50;
+
+/// Upper bound for the number of messages returned from the history.
+pub const MAX_HISTORY_LIMIT: i64 = 100;
+
+/// Longest mute a moderator can give, in minutes.
+pub const MAX_MUTE_MINUTES: i64 = 60 * 24 * 30;
+
+/// Returns the alliance whose channel the player reads and posts in, for the alliance
+/// channel, and `None` for the global channel.
+///
+/// # Errors
+/// `AllianceMembershipError` if the player is not in an alliance
+fn channel_alliance(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	channel: ChatChannel,
+) -> Result<Option<AllianceKey>> {
+	match channel {
+		ChatChannel::Global => Ok(None),
+		ChatChannel::Alliance => alliance_members::find_by_player(conn, player_id)?
+			.map(|member| Some(member.alliance_id))
+			.ok_or_else(|| Error::from((ErrorKind::AllianceMembershipError, "Not in an alliance"))),
+	}
+}
+
+/// Posts a message in a channel.
+///
+/// # Returns
+/// The message with the players it is for, to publish on the chat bus
+///
+/// # Errors
+/// - `InvalidMessage` if the message is empty or too long
+/// - `AllianceMembershipError` for the alliance channel if the player is not in an alliance
+/// - `ChatMutedError` if a moderator muted the player
+/// - `ChatThrottledError` if the player posted too many messages recently
+#[instrument(skip(conn, settings, body))]
+pub fn post(
+	conn: &mut DbConn,
+	settings: &ChatSettings,
+	player_id: &PlayerKey,
+	channel: ChatChannel,
+	body: &str,
+) -> Result<ChatBroadcast> {
+	let body = body.trim();
+	if body.is_empty() || body.chars().count() > BODY_MAX_LENGTH {
+		return Err(Error::from((
+			ErrorKind::InvalidMessage,
+			"Chat messages are 1 to 500 characters long",
+		)));
+	}
+	let alliance_id = channel_alliance(conn, player_id, channel)?;
+
+	let now = Utc::now();
+	if let Some(mute) = active_mute(conn, player_id, now)? {
+		return Err(Error::from((
+			ErrorKind::ChatMutedError,
+			"You are muted in the chat",
+			format!("until {}", mute.muted_until),
+		)));
+	}
+	// AIDEV-NOTE: soft limit like the training throttle, concurrent posts can overshoot it
+	// slightly. It keeps scripts from flooding the channels, it is not an exact quota.
+	let window = TimeDelta::seconds(settings.flood_window_seconds.max(0));
+	let recent = chat_messages::count_by_player_since(conn, player_id, now - window)?;
+	if recent >= settings.flood_max_messages {
+		debug!(
+			"Player {} posted {} chat messages within the flood window",
+			player_id, recent
+		);
+		return Err(Error::from((
+			ErrorKind::ChatThrottledError,
+			"Too many chat messages, slow down",
+		)));
+	}
+
+	let message = chat_messages::create(
+		conn,
+		NewChatMessage {
+			channel,
+			alliance_id,
+			player_id: Some(*player_id),
+			body: body.to_string(),
+		},
+	)?;
+	let recipients = match alliance_id {
+		Some(alliance_id) => Some(alliance_members::get_player_ids(conn, &alliance_id)?),
+		None => None,
+	};
+	let player_name = players::find_by_id(conn, player_id)?.map(|player| player.name);
+	debug!("Player {} posted in the {} channel", player_id, channel);
+	Ok(ChatBroadcast {
+		line: ChatLine {
+			message,
+			player_name,
+		},
+		recipients,
+	})
+}
+
+/// Reads the history of a channel, newest first, from before `before` if it is set.
+///
+/// Missing limits default to [`DEFAULT_HISTORY_LIMIT`] messages, and limits are capped at
+/// [`MAX_HISTORY_LIMIT`]. The alliance channel is the one of the player's alliance.
+#[instrument(skip(conn))]
+pub fn history(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	channel: ChatChannel,
+	before: Option<DateTime<Utc>>,
+	limit: Option<i64>,
+) -> Result<Vec<ChatLine>> {
+	let alliance_id = channel_alliance(conn, player_id, channel)?;
+	let limit = limit
+		.unwrap_or(DEFAULT_HISTORY_LIMIT)
+		.clamp(1, MAX_HISTORY_LIMIT);
+	chat_messages::get_history(conn, channel, alliance_id.as_ref(), before, limit)
+}
+
+/// Returns the mute of a player if it still applies at `now`.
+#[instrument(skip(conn))]
+pub fn active_mute(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	now: DateTime<Utc>,
+) -> Result<Option<ChatMute>> {
+	Ok(chat_mutes::find_by_player(conn, player_id)?.filter(|mute| mute.is_active(now)))
+}
+
+/// Mutes a player for `minutes`, replacing the mute they already have.
+///
+/// # Errors
+/// - `InvalidData` if the duration is not between a minute and [`MAX_MUTE_MINUTES`]
+/// - `NotFoundError` if the player does not exist
+#[instrument(skip(conn, reason))]
+pub fn mute(
+	conn: &mut DbConn,
+	moderator_id: &PlayerKey,
+	player_id: &PlayerKey,
+	minutes: i64,
+	reason: Option<String>,
+) -> Result<ChatMute> {
+	if !(1..=MAX_MUTE_MINUTES).contains(&minutes) {
+		return Err(Error::from((
+			ErrorKind::InvalidData,
+			"Mutes last from a minute to 30 days",
+		)));
+	}
+	if players::find_by_id(conn, player_id)?.is_none() {
+		return Err(Error::from((ErrorKind::NotFoundError, "Player not found")));
+	}
+
+	let mute = chat_mutes::upsert(
+		conn,
+		&NewChatMute {
+			player_id: *player_id,
+			muted_until: Utc::now() + TimeDelta::minutes(minutes),
+			reason: reason
+				.map(|reason| reason.trim().to_string())
+				.filter(|reason| !reason.is_empty()),
+			muted_by: Some(*moderator_id),
+		},
+	)?;
+	info!(
+		"Moderator {} muted player {} until {}",
+		moderator_id, player_id, mute.muted_until
+	);
+	Ok(mute)
+}
+
+/// Lifts the mute of a player.
+///
+/// # Errors
+/// `NotFoundError` if the player is not muted
+#[instrument(skip(conn))]
+pub fn unmute(conn: &mut DbConn, moderator_id: &PlayerKey, player_id: &PlayerKey) -> Result<()> {
+	if chat_mutes::delete(conn, player_id)? == 0 {
+		return Err(Error::from((
+			ErrorKind::NotFoundError,
+			"Player is not muted",
+		)));
+	}
+	info!("Moderator {} unmuted player {}", moderator_id, player_id);
+	Ok(())
+}
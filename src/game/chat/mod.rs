@@ -0,0 +1,6 @@
+//! Chat operations for the Empire game.
+//!
+//! This module lets players talk in the global and the alliance channel, keeps the history
+//! of both, holds back players who flood them, and lets moderators mute players.
+
+pub mod chat_operations;
@@ -0,0 +1,181 @@
+//! Battle report operations for the Empire game.
+//!
+//! Every resolved battle is persisted as a [`BattleReport`] that both the attacker and
+//! the defender can read back. Reports are kept for a configurable retention window
+//! (`combat.report_retention_days`) and removed afterwards by the combat processor.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::Connection;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, instrument, warn};
+
+use crate::db::{DbConn, battle_reports};
+use crate::domain::combat::{
+	AppliedModifier, BattleReport, BattleReportKey, Loot, NewBattleReport, UnitLoss,
+};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::jobs::{JobKey, JobType};
+use crate::domain::player::PlayerKey;
+use crate::game::mail::mail_operations;
+use crate::job_queue::{JobPriority, JobQueue};
+
+/// Default number of reports returned per page.
+pub const DEFAULT_PAGE_SIZE: i64 = 20;
+
+/// Upper bound for the number of reports returned per page.
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+/// How often expired battle reports are pruned.
+pub const REPORT_PRUNE_INTERVAL: TimeDelta = TimeDelta::days(1);
+
+/// Job payloads handled by the combat processor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum CombatJobPayload {
+	/// Delete battle reports that are older than the retention window
+	PruneReports,
+}
+
+/// The outcome of a resolved battle, as handed over by the combat resolver.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BattleOutcome {
+	pub attacker_id: PlayerKey,
+	pub defender_id: PlayerKey,
+	/// Winning player, or `None` for a draw
+	pub winner_id: Option<PlayerKey>,
+	pub attacker_losses: Vec<UnitLoss>,
+	pub defender_losses: Vec<UnitLoss>,
+	pub loot: Loot,
+	/// Modifiers that were in effect for either side
+	pub modifiers: Vec<AppliedModifier>,
+	pub fought_at: DateTime<Utc>,
+}
+
+/// A single page of battle reports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportPage {
+	pub reports: Vec<BattleReport>,
+	pub page: i64,
+	pub per_page: i64,
+	/// Total number of reports available to the player
+	pub total: i64,
+}
+
+/// Persists the outcome of a battle as a report, and mails it to both sides.
+///
+/// # Errors
+/// Returns an error if the winner did not take part in the battle
+#[instrument(skip(conn, outcome), fields(attacker = %outcome.attacker_id, defender = %outcome.defender_id))]
+pub fn record_battle(conn: &mut DbConn, outcome: BattleOutcome) -> Result<BattleReport> {
+	if let Some(winner) = outcome.winner_id
+		&& winner != outcome.attacker_id
+		&& winner != outcome.defender_id
+	{
+		return Err(Error::from((
+			ErrorKind::InvalidData,
+			"Battle winner must be the attacker or the defender",
+		)));
+	}
+
+	let new_report = NewBattleReport {
+		attacker_id: outcome.attacker_id,
+		defender_id: outcome.defender_id,
+		winner_id: outcome.winner_id,
+		attacker_losses: serde_json::to_value(&outcome.attacker_losses)?,
+		defender_losses: serde_json::to_value(&outcome.defender_losses)?,
+		loot_food: outcome.loot.food,
+		loot_wood: outcome.loot.wood,
+		loot_stone: outcome.loot.stone,
+		loot_gold: outcome.loot.gold,
+		modifiers: serde_json::to_value(&outcome.modifiers)?,
+		fought_at: outcome.fought_at,
+	};
+
+	let report = conn.transaction(|conn| {
+		let report = battle_reports::create(conn, new_report)?;
+		mail_operations::notify_battle(conn, &report)?;
+		Ok::<_, Error>(report)
+	})?;
+	info!("Recorded battle report {}", report.id);
+	Ok(report)
+}
+
+/// Lists the battle reports a player took part in, newest first.
+///
+/// Pages are 1-based. Missing values default to the first page of
+/// [`DEFAULT_PAGE_SIZE`] reports, and page sizes are capped at [`MAX_PAGE_SIZE`].
+#[instrument(skip(conn))]
+pub fn list_reports(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	page: Option<i64>,
+	per_page: Option<i64>,
+) -> Result<ReportPage> {
+	let page = page.unwrap_or(1).max(1);
+	let per_page = per_page
+		.unwrap_or(DEFAULT_PAGE_SIZE)
+		.clamp(1, MAX_PAGE_SIZE);
+	let offset = (page - 1).saturating_mul(per_page);
+
+	let (reports, total) = battle_reports::get_page_for_player(conn, player_id, per_page, offset)?;
+	debug!(
+		"Loaded {} of {} battle reports for player {}",
+		reports.len(),
+		total,
+		player_id
+	);
+	Ok(ReportPage {
+		reports,
+		page,
+		per_page,
+		total,
+	})
+}
+
+/// Retrieves a single battle report, as long as the player took part in the battle.
+///
+/// Reports of other players' battles are reported as not found.
+#[instrument(skip(conn))]
+pub fn get_report(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	report_id: &BattleReportKey,
+) -> Result<BattleReport> {
+	battle_reports::find_by_id(conn, report_id)?
+		.filter(|report| report.involves(player_id))
+		.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Battle report not found")))
+}
+
+/// Deletes battle reports that were fought more than `retention_days` ago.
+///
+/// # Returns
+/// The number of deleted reports
+#[instrument(skip(conn))]
+pub fn prune_reports(conn: &mut DbConn, retention_days: i64) -> Result<usize> {
+	let cutoff = Utc::now() - TimeDelta::days(retention_days.max(0));
+	let deleted = battle_reports::delete_older_than(conn, cutoff)?;
+	info!("Pruned {} battle reports older than {}", deleted, cutoff);
+	Ok(deleted)
+}
+
+/// Schedules a battle report pruning run, unless one is already pending.
+///
+/// # Returns
+/// The ID of the scheduled job, or `None` if a pruning run was already queued
+pub fn schedule_report_pruning(
+	job_queue: &JobQueue,
+	run_at: DateTime<Utc>,
+) -> Result<Option<JobKey>> {
+	if job_queue.has_pending_of_type(&JobType::Combat)? {
+		debug!("Battle report pruning already scheduled");
+		return Ok(None);
+	}
+	job_queue
+		.enqueue(
+			JobType::Combat,
+			CombatJobPayload::PruneReports,
+			JobPriority::Low,
+			run_at,
+		)
+		.map(Some)
+		.inspect_err(|err| warn!("Failed to schedule battle report pruning: {:?}", err))
+}
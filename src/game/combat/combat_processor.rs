@@ -0,0 +1,79 @@
+//! Combat job processor for background combat tasks.
+//!
+//! This module implements the job processing functionality for combat jobs,
+//! currently the daily pruning of expired battle and intel reports.
+
+use axum::extract::FromRef;
+use chrono::Utc;
+use tracing::{debug, info, instrument, trace};
+
+use crate::Error;
+use crate::domain::app_state::{AppPool, AppQueue, AppState};
+use crate::domain::jobs::{Job, JobType};
+use crate::game::combat::combat_operations::{self, CombatJobPayload, REPORT_PRUNE_INTERVAL};
+use crate::game::combat::espionage_operations;
+use crate::job_queue::job_processor::{JobHandler, JobOutcome, JobWorker};
+
+/// A handler for combat-related background jobs.
+///
+/// The `CombatHandler` implements the `JobHandler` trait and is responsible
+/// for removing battle and intel reports that have outlived the configured retention window,
+/// rescheduling itself after every run.
+pub struct CombatHandler {
+	/// Database connection pool
+	pool: AppPool,
+	/// Job queue used to reschedule report pruning
+	job_queue: AppQueue,
+	/// Number of days battle and intel reports are kept for
+	retention_days: i64,
+}
+
+/// Workers running the [`CombatHandler`].
+pub type CombatProcessor = JobWorker<CombatHandler>;
+
+impl JobHandler for CombatHandler {
+	const JOB_TYPE: JobType = JobType::Combat;
+	const WORKER_NAME: &'static str = "combat-goblin";
+
+	/// Creates a new `CombatHandler` with what it needs from the application state.
+	fn new(app_state: &AppState) -> Self {
+		let pool = AppPool::from_ref(app_state);
+		let job_queue = AppQueue::from_ref(app_state);
+		let retention_days = app_state.settings.combat.report_retention_days;
+		Self {
+			pool,
+			job_queue,
+			retention_days,
+		}
+	}
+
+	#[instrument(skip(self, job), fields(job_id = %job.id))]
+	async fn handle(&self, job: Job) -> Result<JobOutcome, Error> {
+		debug!("Processing combat job: {}", job.id);
+		trace!("Job details: {:?}", job);
+
+		let payload: CombatJobPayload = serde_json::from_value(job.payload.clone())?;
+		let mut conn = self.pool.get()?;
+
+		let outcome = match payload {
+			CombatJobPayload::PruneReports => {
+				let deleted = combat_operations::prune_reports(&mut conn, self.retention_days)?;
+				let deleted_intel =
+					espionage_operations::prune_reports(&mut conn, self.retention_days)?;
+				info!(
+					"Pruned {} expired battle reports and {} intel reports",
+					deleted, deleted_intel
+				);
+				let next_run = Utc::now() + REPORT_PRUNE_INTERVAL;
+				combat_operations::schedule_report_pruning(&self.job_queue, next_run)?;
+				serde_json::json!({
+					"pruned_reports": deleted,
+					"pruned_intel_reports": deleted_intel,
+				})
+			}
+		};
+
+		debug!("Completed processing combat job: {}", job.id);
+		Ok(Some(outcome))
+	}
+}
@@ -0,0 +1,88 @@
+//! Checks that must pass before one player may attack another.
+//!
+//! Every attack, raid or other hostile mission goes through [`validate_attack`] so the rules
+//! are enforced in one place rather than by each mission type.
+
+use tracing::{debug, instrument};
+
+use crate::configuration::ProtectionSettings;
+use crate::db::DbConn;
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::player::PlayerKey;
+use crate::game::alliances::alliance_operations;
+use crate::game::combat::protection_operations;
+use crate::game::friends::friend_operations;
+
+/// Validates that `attacker_id` may attack `defender_id`.
+///
+/// # Errors
+/// * `InvalidData` if a player attacks themselves
+/// * `AllianceMateError` if both players are members of the same alliance
+/// * `PlayerBlockedError` if either player blocked the other
+/// * `AttackerProtectedError` if the attacker is still under the beginner shield
+/// * `DefenderProtectedError` if the defender is still under the beginner shield
+/// * `AttackerProtectedError` if the attacker called a truce
+/// * `DefenderProtectedError` if the defender called a truce
+#[instrument(skip(conn, settings))]
+pub fn validate_attack(
+	conn: &mut DbConn,
+	settings: &ProtectionSettings,
+	attacker_id: &PlayerKey,
+	defender_id: &PlayerKey,
+) -> Result<()> {
+	if attacker_id == defender_id {
+		return Err(Error::from((
+			ErrorKind::InvalidData,
+			"Players cannot attack themselves",
+		)));
+	}
+	if alliance_operations::are_allies(conn, attacker_id, defender_id)? {
+		debug!("Players are members of the same alliance");
+		return Err(Error::from((
+			ErrorKind::AllianceMateError,
+			"Cannot attack members of your own alliance",
+		)));
+	}
+	if friend_operations::is_blocked(conn, attacker_id, defender_id)? {
+		debug!("One of the players blocked the other");
+		return Err(Error::from((
+			ErrorKind::PlayerBlockedError,
+			"Cannot attack this player",
+		)));
+	}
+
+	// AIDEV-NOTE: the shield works both ways, so new players cannot farm others while
+	// being untouchable themselves. Dropping it early lifts both restrictions.
+	if protection_operations::active_shield(conn, settings, attacker_id)?.is_some() {
+		debug!("Attacker is under the beginner shield");
+		return Err(Error::from((
+			ErrorKind::AttackerProtectedError,
+			"Cannot attack while under beginner protection",
+		)));
+	}
+	if protection_operations::active_shield(conn, settings, defender_id)?.is_some() {
+		debug!("Defender is under the beginner shield");
+		return Err(Error::from((
+			ErrorKind::DefenderProtectedError,
+			"Target is under beginner protection",
+		)));
+	}
+
+	// A truce protects both ways, like the shield
+	if protection_operations::active_truce(conn, attacker_id)?.is_some() {
+		debug!("Attacker called a truce");
+		return Err(Error::from((
+			ErrorKind::AttackerProtectedError,
+			"Cannot attack during your truce",
+		)));
+	}
+	if protection_operations::active_truce(conn, defender_id)?.is_some() {
+		debug!("Defender called a truce");
+		return Err(Error::from((
+			ErrorKind::DefenderProtectedError,
+			"Target is under a truce",
+		)));
+	}
+
+	Ok(())
+}
@@ -0,0 +1,305 @@
+//! Scout missions and the intel reports they bring back.
+//!
+//! Sending scouts takes them out of the attacker's army right away and schedules a
+//! [`JobType::Espionage`] job for their arrival. On arrival the scouts write an
+//! [`IntelReport`] on the capital of the defender and return home: the resources in its
+//! storage are exact, the size of its army is an estimate.
+//!
+//! The Watchtower of the defender's capital is their counter-espionage. Every level adds
+//! `espionage.noise_percent_per_level` of noise to the army estimates, up to
+//! `espionage.max_noise_percent`, and from `espionage.spot_watchtower_level` on it spots the
+//! scouts and tells the defender who sent them. Intel reports are kept as long as battle
+//! reports, and pruned along with them.
+
+use chrono::{TimeDelta, Utc};
+use diesel::Connection;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, instrument, trace, warn};
+
+use crate::configuration::{EspionageSettings, ProtectionSettings};
+use crate::db::{
+	DbConn, intel_reports, player_buildings, player_units, players, resources, scout_missions,
+	settlements, units,
+};
+use crate::domain::combat::{
+	IntelReport, IntelReportKey, NewIntelReport, NewScoutMission, ScoutMission, ScoutMissionKey,
+	UnitEstimate,
+};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::jobs::JobType;
+use crate::domain::player::PlayerKey;
+use crate::domain::unit::Unit;
+use crate::game::combat::combat_operations::{DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use crate::game::combat::combat_validator;
+use crate::game::mail::mail_operations;
+use crate::job_queue::{JobPriority, JobQueue};
+
+/// Name of the unit sent on scout missions.
+pub const SCOUT_UNIT_NAME: &str = "Scout";
+
+/// Name of the building that counters scout missions.
+pub const WATCHTOWER_NAME: &str = "Watchtower";
+
+/// Job payloads handled by the espionage processor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum EspionageJobPayload {
+	/// Write the intel report of an arrived scout mission and bring the scouts home
+	CompleteMission { mission_id: ScoutMissionKey },
+}
+
+/// A single page of intel reports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntelReportPage {
+	pub reports: Vec<IntelReport>,
+	pub page: i64,
+	pub per_page: i64,
+	/// Total number of reports available to the player
+	pub total: i64,
+}
+
+/// Returns how far off army estimates are, in percent, for a Watchtower of `level`.
+pub fn noise_percent(settings: &EspionageSettings, watchtower_level: i32) -> i32 {
+	let max_noise = settings.max_noise_percent.clamp(0, 100);
+	watchtower_level
+		.max(0)
+		.saturating_mul(settings.noise_percent_per_level.max(0))
+		.min(max_noise)
+}
+
+/// Estimates `quantity` units, off by a random amount of up to `noise_percent` either way.
+pub fn estimate(quantity: i64, noise_percent: i32) -> i64 {
+	if noise_percent <= 0 {
+		return quantity;
+	}
+	let noise = f64::from(noise_percent) / 100.0;
+	let factor = 1.0 + rand::random_range(-noise..=noise);
+	(quantity as f64 * factor).round().max(0.0) as i64
+}
+
+/// Retrieves the unit sent on scout missions.
+fn scout_unit(conn: &mut DbConn) -> Result<Unit> {
+	units::find_by_name(conn, SCOUT_UNIT_NAME)?
+		.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Scout unit not found")))
+}
+
+/// Sends `scouts` scouts of `attacker_id` to spy on `defender_id`.
+///
+/// The scouts leave the attacker's army immediately, and an espionage job completes the
+/// mission once they arrive.
+///
+/// # Errors
+/// - `InvalidQuantityError` if no scouts are sent
+/// - `NotFoundError` if the defender does not exist
+/// - Any error of [`combat_validator::validate_attack`], scouting is a hostile mission
+/// - `InsufficientUnitsError` if the attacker has fewer scouts
+#[instrument(skip(conn, job_queue, settings, protection))]
+pub fn send_scouts(
+	conn: &mut DbConn,
+	job_queue: &JobQueue,
+	settings: &EspionageSettings,
+	protection: &ProtectionSettings,
+	attacker_id: &PlayerKey,
+	defender_id: &PlayerKey,
+	scouts: i64,
+) -> Result<ScoutMission> {
+	if scouts <= 0 {
+		return Err(Error::from((
+			ErrorKind::InvalidQuantityError,
+			"At least one scout must be sent",
+		)));
+	}
+	if players::find_by_id(conn, defender_id)?.is_none() {
+		return Err(Error::from((ErrorKind::NotFoundError, "Player not found")));
+	}
+	combat_validator::validate_attack(conn, protection, attacker_id, defender_id)?;
+	let scout = scout_unit(conn)?;
+
+	let arrives_at = Utc::now() + TimeDelta::seconds(settings.travel_seconds.max(0));
+	let mission = conn.transaction(|conn| {
+		if player_units::remove_units(conn, attacker_id, &scout.id, scouts)?.is_none() {
+			return Err(Error::from((
+				ErrorKind::InsufficientUnitsError,
+				"Not enough scouts",
+			)));
+		}
+		scout_missions::create(
+			conn,
+			NewScoutMission {
+				attacker_id: *attacker_id,
+				defender_id: *defender_id,
+				scouts,
+				arrives_at,
+			},
+		)
+	})?;
+
+	// Schedule the arrival outside the transaction, like every other job of the game
+	let payload = EspionageJobPayload::CompleteMission {
+		mission_id: mission.id,
+	};
+	let job_id = match job_queue.enqueue(
+		JobType::Espionage,
+		payload,
+		JobPriority::Normal,
+		mission.arrives_at,
+	) {
+		Ok(id) => id,
+		Err(e) => {
+			// AIDEV-NOTE: Without an arrival job the scouts would never come back, so they are
+			// called home right away
+			warn!("Failed to schedule scout mission arrival, recalling: {}", e);
+			if let Err(recall_err) = recall_scouts(conn, &mission) {
+				warn!(
+					"Failed to recall scout mission {}: {}",
+					mission.id, recall_err
+				);
+			}
+			return Err(Error::from((
+				ErrorKind::InternalError,
+				"Failed to schedule scout mission arrival",
+				format!("{:?}", e),
+			)));
+		}
+	};
+	let mission = scout_missions::set_job_id(conn, &mission.id, &job_id)?;
+
+	info!(
+		"Scout mission {} of player {} on player {} arrives at {}",
+		mission.id, attacker_id, defender_id, mission.arrives_at
+	);
+	Ok(mission)
+}
+
+/// Returns the scouts of a mission to the attacker and marks it completed without a report.
+fn recall_scouts(conn: &mut DbConn, mission: &ScoutMission) -> Result<()> {
+	conn.transaction(|conn| {
+		let scout = scout_unit(conn)?;
+		player_units::add_units(conn, &mission.attacker_id, &scout.id, mission.scouts)?;
+		scout_missions::complete(conn, &mission.id, None)?;
+		Ok(())
+	})
+}
+
+/// Completes an arrived scout mission: writes the intel report on the capital of the
+/// defender, brings the scouts home, and mails the attacker and, if their Watchtower spotted
+/// the scouts, the defender.
+///
+/// # Returns
+/// The intel report, or `None` if the mission was already completed, so a retried arrival
+/// job never reports twice
+#[instrument(skip(conn, settings))]
+pub fn complete_mission(
+	conn: &mut DbConn,
+	settings: &EspionageSettings,
+	mission_id: &ScoutMissionKey,
+) -> Result<Option<IntelReport>> {
+	conn.transaction(|conn| {
+		let mission = scout_missions::lock_by_id(conn, mission_id)?;
+		if mission.is_completed() {
+			debug!("Scout mission {} was already completed", mission_id);
+			return Ok(None);
+		}
+
+		let capital = settlements::get_capital(conn, &mission.defender_id)?;
+		let watchtower_level =
+			player_buildings::get_level_by_name(conn, &capital.id, WATCHTOWER_NAME)?;
+		let noise = noise_percent(settings, watchtower_level);
+		let storage = resources::get_by_settlement(conn, &capital.id)?;
+		let army: Vec<UnitEstimate> = player_units::get_for_player(conn, &mission.defender_id)?
+			.into_iter()
+			.filter(|unit| unit.quantity > 0)
+			.map(|unit| UnitEstimate {
+				unit_id: unit.unit_id,
+				estimate: estimate(unit.quantity, noise),
+			})
+			.collect();
+		trace!("Scouted army: {:?}", army);
+
+		let report = intel_reports::create(
+			conn,
+			NewIntelReport {
+				attacker_id: mission.attacker_id,
+				defender_id: mission.defender_id,
+				food: storage.food,
+				wood: storage.wood,
+				stone: storage.stone,
+				gold: storage.gold,
+				army: serde_json::to_value(&army)?,
+				noise_percent: noise,
+				spotted: watchtower_level >= settings.spot_watchtower_level,
+				scouted_at: Utc::now(),
+			},
+		)?;
+
+		let scout = scout_unit(conn)?;
+		player_units::add_units(conn, &mission.attacker_id, &scout.id, mission.scouts)?;
+		scout_missions::complete(conn, &mission.id, Some(&report.id))?;
+
+		mail_operations::notify_intel(conn, &report)?;
+		if report.spotted {
+			mail_operations::notify_scouts_spotted(conn, &report)?;
+		}
+		info!(
+			"Scout mission {} reported on player {} with {}% noise",
+			mission.id, mission.defender_id, noise
+		);
+		Ok(Some(report))
+	})
+}
+
+/// Lists the intel reports of a player's scout missions, newest first.
+///
+/// Pages are 1-based. Missing values default to the first page of
+/// [`DEFAULT_PAGE_SIZE`] reports, and page sizes are capped at [`MAX_PAGE_SIZE`].
+#[instrument(skip(conn))]
+pub fn list_reports(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	page: Option<i64>,
+	per_page: Option<i64>,
+) -> Result<IntelReportPage> {
+	let page = page.unwrap_or(1).max(1);
+	let per_page = per_page
+		.unwrap_or(DEFAULT_PAGE_SIZE)
+		.clamp(1, MAX_PAGE_SIZE);
+	let offset = (page - 1).saturating_mul(per_page);
+
+	let (reports, total) = intel_reports::get_page_for_attacker(conn, player_id, per_page, offset)?;
+	debug!(
+		"Loaded {} of {} intel reports for player {}",
+		reports.len(),
+		total,
+		player_id
+	);
+	Ok(IntelReportPage {
+		reports,
+		page,
+		per_page,
+		total,
+	})
+}
+
+/// Retrieves a single intel report, as long as the player's scouts wrote it.
+///
+/// Reports of other players, the scouted player's included, are reported as not found.
+#[instrument(skip(conn))]
+pub fn get_report(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	report_id: &IntelReportKey,
+) -> Result<IntelReport> {
+	intel_reports::find_for_attacker(conn, player_id, report_id)?
+		.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Intel report not found")))
+}
+
+/// Deletes intel reports that were scouted more than `retention_days` ago.
+///
+/// # Returns
+/// The number of deleted reports
+#[instrument(skip(conn))]
+pub fn prune_reports(conn: &mut DbConn, retention_days: i64) -> Result<usize> {
+	let cutoff = Utc::now() - TimeDelta::days(retention_days.max(0));
+	let deleted = intel_reports::delete_older_than(conn, cutoff)?;
+	info!("Pruned {} intel reports older than {}", deleted, cutoff);
+	Ok(deleted)
+}
@@ -0,0 +1,61 @@
+//! Espionage job processor for scout missions.
+//!
+//! This module implements the job processing functionality for espionage jobs, which
+//! complete scout missions once the scouts arrive at their target.
+
+use axum::extract::FromRef;
+use tracing::{debug, info, instrument, trace};
+
+use crate::Error;
+use crate::configuration::EspionageSettings;
+use crate::domain::app_state::{AppPool, AppState};
+use crate::domain::jobs::{Job, JobType};
+use crate::game::combat::espionage_operations::{self, EspionageJobPayload};
+use crate::job_queue::job_processor::{JobHandler, JobOutcome, JobWorker};
+
+/// A handler for espionage background jobs.
+///
+/// The `EspionageHandler` implements the `JobHandler` trait and is responsible
+/// for writing the intel reports of arrived scout missions and bringing the scouts home.
+pub struct EspionageHandler {
+	/// Database connection pool
+	pool: AppPool,
+	/// Noise and spotting rules of the scout missions
+	settings: EspionageSettings,
+}
+
+/// Workers running the [`EspionageHandler`].
+pub type EspionageProcessor = JobWorker<EspionageHandler>;
+
+impl JobHandler for EspionageHandler {
+	const JOB_TYPE: JobType = JobType::Espionage;
+	const WORKER_NAME: &'static str = "espionage-goblin";
+
+	/// Creates a new `EspionageHandler` with what it needs from the application state.
+	fn new(app_state: &AppState) -> Self {
+		let pool = AppPool::from_ref(app_state);
+		let settings = app_state.settings.espionage;
+		Self { pool, settings }
+	}
+
+	#[instrument(skip(self, job), fields(job_id = %job.id))]
+	async fn handle(&self, job: Job) -> Result<JobOutcome, Error> {
+		debug!("Processing espionage job: {}", job.id);
+		trace!("Job details: {:?}", job);
+
+		let payload: EspionageJobPayload = serde_json::from_value(job.payload.clone())?;
+		let mut conn = self.pool.get()?;
+
+		let outcome = match payload {
+			EspionageJobPayload::CompleteMission { mission_id } => {
+				let report =
+					espionage_operations::complete_mission(&mut conn, &self.settings, &mission_id)?;
+				info!("Completed scout mission {}", mission_id);
+				serde_json::json!({ "report_id": report.map(|report| report.id) })
+			}
+		};
+
+		debug!("Completed processing espionage job: {}", job.id);
+		Ok(Some(outcome))
+	}
+}
@@ -0,0 +1,13 @@
+//! Combat operations for the Empire game.
+//!
+//! This module provides functionality for recording battle outcomes as reports,
+//! exposing them to the players involved, and pruning them once they expire. It also
+//! validates attacks against the beginner protection of both players, and sends scout
+//! missions that bring back intel reports on other players.
+
+pub mod combat_operations;
+pub mod combat_processor;
+pub mod combat_validator;
+pub mod espionage_operations;
+pub mod espionage_processor;
+pub mod protection_operations;
@@ -0,0 +1,115 @@
+//! Protection of players from combat.
+//!
+//! New players get a shield that keeps them out of combat for
+//! `protection.beginner_shield_days`. The shield ends early once the player's points, the sum
+//! of their building levels, reach `protection.beginner_shield_max_points`, once the Keep of
+//! their capital reaches `protection.beginner_shield_max_keep_level`, or when the player drops
+//! it themselves. The end of the shield is stored on the player, so changing the settings only
+//! affects players who register afterwards.
+//!
+//! Any player can also call a truce by using a truce item. A truce is a modifier targeting
+//! [`ModifierTarget::Truce`], and protects like the shield until the modifier expires.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use tracing::{debug, info, instrument};
+
+use crate::configuration::ProtectionSettings;
+use crate::db::{DbConn, active_modifiers, player_buildings, players, settlements};
+use crate::domain::error::Result;
+use crate::domain::modifier::ModifierTarget;
+use crate::domain::player::PlayerKey;
+
+/// Points of a player, used to end the beginner shield of fast-growing players early.
+pub fn player_points(conn: &mut DbConn, player_id: &PlayerKey) -> Result<i64> {
+	player_buildings::sum_levels(conn, player_id)
+}
+
+/// Starts the beginner shield of a newly registered player.
+///
+/// # Returns
+/// The end of the shield, or `None` if the shield is disabled
+#[instrument(skip(conn, settings))]
+pub fn grant_beginner_shield(
+	conn: &mut DbConn,
+	settings: &ProtectionSettings,
+	player_id: &PlayerKey,
+) -> Result<Option<DateTime<Utc>>> {
+	if settings.beginner_shield_days <= 0 {
+		debug!("Beginner shield is disabled");
+		return Ok(None);
+	}
+
+	let until = Utc::now() + TimeDelta::days(settings.beginner_shield_days);
+	let player = players::set_protected_until(conn, player_id, Some(until))?;
+	debug!("Beginner shield active until {}", until);
+	Ok(player.protected_until)
+}
+
+/// Returns the end of the player's shield if it still protects them.
+///
+/// A shield outgrown by the points or Keep level threshold is dropped on the spot, so it
+/// stays gone even if the player's points fall again later.
+#[instrument(skip(conn, settings))]
+pub fn active_shield(
+	conn: &mut DbConn,
+	settings: &ProtectionSettings,
+	player_id: &PlayerKey,
+) -> Result<Option<DateTime<Utc>>> {
+	let player = players::get_by_id(conn, player_id)?;
+	let Some(until) = player.protected_until.filter(|until| *until > Utc::now()) else {
+		return Ok(None);
+	};
+
+	if let Some(max_points) = settings.beginner_shield_max_points {
+		let points = player_points(conn, player_id)?;
+		if points >= max_points {
+			players::set_protected_until(conn, player_id, None)?;
+			info!(
+				"Beginner shield ended at {} points, threshold is {}",
+				points, max_points
+			);
+			return Ok(None);
+		}
+	}
+	if let Some(max_keep_level) = settings.beginner_shield_max_keep_level {
+		let capital = settlements::get_capital(conn, player_id)?;
+		let keep_level = player_buildings::get_keep_level(conn, &capital.id)?;
+		if keep_level >= max_keep_level {
+			players::set_protected_until(conn, player_id, None)?;
+			info!(
+				"Beginner shield ended at Keep level {}, threshold is {}",
+				keep_level, max_keep_level
+			);
+			return Ok(None);
+		}
+	}
+	Ok(Some(until))
+}
+
+/// Returns the end of the player's truce if one is active.
+///
+/// With several truces active, the one lasting longest counts.
+#[instrument(skip(conn))]
+pub fn active_truce(conn: &mut DbConn, player_id: &PlayerKey) -> Result<Option<DateTime<Utc>>> {
+	active_modifiers::latest_expiry_for_target(conn, player_id, ModifierTarget::Truce, Utc::now())
+}
+
+/// Drops the player's shield before it runs out.
+///
+/// # Returns
+/// * `Ok(true)` if a running shield was dropped
+/// * `Ok(false)` if the player was not protected
+#[instrument(skip(conn))]
+pub fn opt_out(conn: &mut DbConn, player_id: &PlayerKey) -> Result<bool> {
+	let player = players::get_by_id(conn, player_id)?;
+	let shielded = player
+		.protected_until
+		.is_some_and(|until| until > Utc::now());
+	if player.protected_until.is_some() {
+		players::set_protected_until(conn, player_id, None)?;
+	}
+	if shielded {
+		info!("Player dropped their beginner shield early");
+	}
+	Ok(shielded)
+}
@@ -0,0 +1,275 @@
+//! Read-only consistency checks of a single player's state for support diagnostics.
+//!
+//! Every check compares stored state against the invariants the game logic is supposed to
+//! uphold and reports each violation it finds. Nothing is corrected: all checks run in one
+//! read-only transaction, so the report describes a single snapshot of the player and a
+//! misbehaving check can never make matters worse.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use crate::db::{
+	DbConn, player_buildings, player_units, players, resources, settlements, training_queue,
+};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::jobs::JobStatus;
+use crate::domain::player::accumulator::PlayerAccumulator;
+use crate::domain::player::{Player, PlayerKey};
+use crate::domain::settlement::Settlement;
+use crate::game::resources::resource_operations;
+
+/// The checks run for every player.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsistencyCheck {
+	/// Stored resources are non-negative and within the storage caps
+	Resources,
+	/// Active training entries have a completion job that can still run
+	TrainingJobs,
+	/// Building counts and levels are within their definition's limits
+	Buildings,
+	/// Unit quantities are non-negative
+	Units,
+	/// Accumulated resources are non-negative and within the accumulator caps
+	Accumulator,
+}
+
+impl ConsistencyCheck {
+	pub const ALL: [ConsistencyCheck; 5] = [
+		ConsistencyCheck::Resources,
+		ConsistencyCheck::TrainingJobs,
+		ConsistencyCheck::Buildings,
+		ConsistencyCheck::Units,
+		ConsistencyCheck::Accumulator,
+	];
+}
+
+/// A single broken invariant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+	pub check: ConsistencyCheck,
+	/// What the violation was found on, for example `wood` or a training entry ID
+	pub subject: String,
+	pub detail: String,
+}
+
+/// Outcome of checking one player.
+#[derive(Debug, Clone)]
+pub struct ConsistencyReport {
+	pub player_id: PlayerKey,
+	pub checked_at: DateTime<Utc>,
+	pub checks: Vec<ConsistencyCheck>,
+	pub violations: Vec<Violation>,
+}
+
+/// Statuses of a job that still completes its training entry.
+const LIVE_JOB_STATUSES: [JobStatus; 4] = [
+	JobStatus::Pending,
+	JobStatus::InProgress,
+	JobStatus::Failed,
+	JobStatus::CancelRequested,
+];
+
+/// Runs every consistency check against a player.
+///
+/// Fails if the player does not exist.
+#[instrument(skip(conn))]
+pub fn check_player(conn: &mut DbConn, player_id: &PlayerKey) -> Result<ConsistencyReport> {
+	if players::find_by_id(conn, player_id)?.is_none() {
+		return Err(Error::from((ErrorKind::NotFoundError, "Player not found")));
+	}
+
+	let violations = conn.transaction(|conn| -> Result<Vec<Violation>> {
+		// Pooled connections cannot use the transaction builder, and this has to run first
+		diesel::sql_query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+			.execute(conn)?;
+
+		let mut violations = Vec::new();
+		check_resources(conn, player_id, &mut violations)?;
+		check_training_jobs(conn, player_id, &mut violations)?;
+		check_buildings(conn, player_id, &mut violations)?;
+		check_units(conn, player_id, &mut violations)?;
+		check_accumulator(conn, player_id, &mut violations)?;
+		Ok(violations)
+	})?;
+
+	debug!(
+		"Player {} has {} consistency violations",
+		player_id,
+		violations.len()
+	);
+	Ok(ConsistencyReport {
+		player_id: *player_id,
+		checked_at: Utc::now(),
+		checks: ConsistencyCheck::ALL.to_vec(),
+		violations,
+	})
+}
+
+/// Reports amounts that are negative or above their cap, naming the settlement they are in
+/// unless it is the capital.
+fn check_bounds(
+	check: ConsistencyCheck,
+	settlement: &Settlement,
+	amounts: [(&str, i64, i64); 4],
+	violations: &mut Vec<Violation>,
+) {
+	for (resource, amount, cap) in amounts {
+		let detail = if amount < 0 {
+			format!("{amount} is negative")
+		} else if amount > cap {
+			format!("{amount} exceeds the cap of {cap}")
+		} else {
+			continue;
+		};
+		let subject = if settlement.is_capital {
+			resource.to_string()
+		} else {
+			format!("{resource} in {}", settlement.name)
+		};
+		violations.push(Violation {
+			check,
+			subject,
+			detail,
+		});
+	}
+}
+
+fn check_resources(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	violations: &mut Vec<Violation>,
+) -> Result<()> {
+	for settlement in settlements::get_by_player(conn, player_id)? {
+		let res = resources::get_by_settlement(conn, &settlement.id)?;
+		check_bounds(
+			ConsistencyCheck::Resources,
+			&settlement,
+			[
+				("food", res.food, res.food_cap),
+				("wood", res.wood, res.wood_cap),
+				("stone", res.stone, res.stone_cap),
+				("gold", res.gold, res.gold_cap),
+			],
+			violations,
+		);
+	}
+	Ok(())
+}
+
+fn check_training_jobs(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	violations: &mut Vec<Violation>,
+) -> Result<()> {
+	for (entry, job_status) in training_queue::get_active_with_job_status(conn, player_id)? {
+		let detail = match (entry.job_id, job_status) {
+			(None, _) => "has no completion job".to_string(),
+			(Some(job_id), None) => format!("completion job {job_id} no longer exists"),
+			(Some(job_id), Some(status)) if !LIVE_JOB_STATUSES.contains(&status) => {
+				format!("completion job {job_id} is {status:?}")
+			}
+			_ => continue,
+		};
+		violations.push(Violation {
+			check: ConsistencyCheck::TrainingJobs,
+			subject: entry.id.to_string(),
+			detail,
+		});
+	}
+	Ok(())
+}
+
+fn check_buildings(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	violations: &mut Vec<Violation>,
+) -> Result<()> {
+	let player = players::get_by_id(conn, player_id)?;
+	for settlement in settlements::get_by_player(conn, player_id)? {
+		check_settlement_buildings(conn, &player, &settlement, violations)?;
+	}
+	Ok(())
+}
+
+fn check_settlement_buildings(
+	conn: &mut DbConn,
+	player: &Player,
+	settlement: &Settlement,
+	violations: &mut Vec<Violation>,
+) -> Result<()> {
+	let (buildings, counts) =
+		player_buildings::get_player_bld_counts_levels(conn, player, &settlement.id)?;
+	for (bld_id, (count, max_count, max_level)) in counts {
+		let Some(building) = buildings.get(&bld_id) else {
+			continue;
+		};
+		if count > i64::from(max_count) {
+			violations.push(Violation {
+				check: ConsistencyCheck::Buildings,
+				subject: building.name.clone(),
+				detail: format!("{count} built, at most {max_count} allowed"),
+			});
+		}
+		if let Some(level) = max_level
+			&& level > building.max_level
+		{
+			violations.push(Violation {
+				check: ConsistencyCheck::Buildings,
+				subject: building.name.clone(),
+				detail: format!(
+					"level {level} exceeds the maximum of {}",
+					building.max_level
+				),
+			});
+		}
+	}
+	Ok(())
+}
+
+fn check_units(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	violations: &mut Vec<Violation>,
+) -> Result<()> {
+	for unit in player_units::get_for_player(conn, player_id)? {
+		if unit.quantity < 0 {
+			violations.push(Violation {
+				check: ConsistencyCheck::Units,
+				subject: unit.unit_id.to_string(),
+				detail: format!("quantity {} is negative", unit.quantity),
+			});
+		}
+	}
+	Ok(())
+}
+
+fn check_accumulator(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	violations: &mut Vec<Violation>,
+) -> Result<()> {
+	use crate::schema::player_accumulator::dsl as pa;
+
+	for settlement in settlements::get_by_player(conn, player_id)? {
+		let acc: PlayerAccumulator = pa::player_accumulator
+			.filter(pa::settlement_id.eq(settlement.id))
+			.select(PlayerAccumulator::as_select())
+			.first(conn)?;
+		let caps = resource_operations::get_base_rates(conn, &settlement.id)?;
+		check_bounds(
+			ConsistencyCheck::Accumulator,
+			&settlement,
+			[
+				("food", acc.food, caps.food_acc_cap),
+				("wood", acc.wood, caps.wood_acc_cap),
+				("stone", acc.stone, caps.stone_acc_cap),
+				("gold", acc.gold, caps.gold_acc_cap),
+			],
+			violations,
+		);
+	}
+	Ok(())
+}
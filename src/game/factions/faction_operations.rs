@@ -0,0 +1,323 @@
+//! Faction selection and changes of players.
+//!
+//! Players register as neutral and pick their first faction with [`select_faction`], which
+//! grants the starter buildings of the faction. A player who picked a faction can move to
+//! another one once the configured cooldown since their last change has passed. The change
+//! converts the empire in one transaction:
+//!
+//! - Every building of the old faction becomes the building of the new faction that stands
+//!   in for it, see [`equivalent_building_name`]. The building keeps its id and level, so
+//!   trainings, heroes and building modifiers stay attached. Neutral buildings are shared by
+//!   every faction and stay as they are.
+//! - Pending construction plans for buildings of the old faction are converted the same way.
+//! - The faction modifiers are swapped by the database when the player's faction changes,
+//!   the cached multipliers of the player are invalidated afterwards.
+//!
+//! Units are shared by every faction, they are kept as they are. Buildings that are being
+//! upgraded cannot be converted, the upgrades have to complete or be cancelled first.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::Connection;
+use tracing::{debug, info, instrument, trace};
+
+use crate::configuration::FactionSettings;
+use crate::db::{
+	DbConn, buildings, construction_queue, faction_changes, planned_actions, player_buildings,
+	players,
+};
+use crate::domain::building::Building;
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::factions::FactionCode;
+use crate::domain::player::faction_change::{FactionChange, NewFactionChange};
+use crate::domain::player::{Player, PlayerKey, UpdatePlayer};
+use crate::game::modifiers::modifier_cache::ModifierCache;
+use crate::game::player_operations;
+
+/// Buildings whose name differs between factions, as the names of the Human, Orc, Elf, Dwarf
+/// and Goblin building. Every other building has the same name in every faction.
+const FACTION_BUILDINGS: [[&str; 5]; 5] = [
+	[
+		"Keep",
+		"Stronghold",
+		"Tree of Life",
+		"Hall of Thanes",
+		"The Big Shack",
+	],
+	["Academy", "Academy", "Academy", "Academy", "Cadet School"],
+	[
+		"University",
+		"University",
+		"University",
+		"University",
+		"Brainery",
+	],
+	[
+		"Mage Tower",
+		"The Circle",
+		"Arcanum",
+		"Hall of Runes",
+		"Mana Den",
+	],
+	[
+		"Church",
+		"Shamanic Altar",
+		"Shrine",
+		"Temple",
+		"Speaker's Hut",
+	],
+];
+
+/// A completed faction change and what it converted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FactionChanged {
+	pub player: Player,
+	pub change: FactionChange,
+	/// Number of buildings converted to the new faction
+	pub converted_buildings: usize,
+	/// Number of pending construction plans converted to the new faction
+	pub converted_plans: usize,
+	/// When the player can change their faction again
+	pub next_change_at: DateTime<Utc>,
+}
+
+/// A first faction choice and the starter buildings it granted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FactionSelected {
+	pub player: Player,
+	/// Number of starter buildings granted
+	pub starter_buildings: usize,
+}
+
+/// Name of the building of the faction `to` that stands in for the building `name` of the
+/// faction `from`.
+///
+/// Buildings with the same name in every faction, and buildings of the neutral faction,
+/// keep their name.
+pub fn equivalent_building_name(name: &str, from: FactionCode, to: FactionCode) -> &str {
+	let (Some(from_idx), Some(to_idx)) = (faction_index(from), faction_index(to)) else {
+		return name;
+	};
+	FACTION_BUILDINGS
+		.iter()
+		.find(|names| names[from_idx] == name)
+		.map_or(name, |names| names[to_idx])
+}
+
+/// Moves a neutral player into their first faction.
+///
+/// The player is provisioned the starter buildings of the faction, see
+/// [`provision_player`](player_operations::provision_player), and the faction triggers swap in
+/// its modifiers. The cooldown of
+/// [`change_faction`] does not apply to the first choice.
+///
+/// # Errors
+/// - `InvalidFaction` if the chosen faction is neutral
+/// - `FactionChangeError` if the player already picked a faction
+#[instrument(skip(conn, modifier_cache))]
+pub fn select_faction(
+	conn: &mut DbConn,
+	modifier_cache: &ModifierCache,
+	player_id: &PlayerKey,
+	faction: FactionCode,
+) -> Result<FactionSelected> {
+	if faction == FactionCode::Neutral {
+		return Err(Error::from((
+			ErrorKind::InvalidFaction,
+			"Players cannot become neutral",
+		)));
+	}
+	// Fails for unknown players before the selection is attempted
+	players::get_by_id(conn, player_id)?;
+
+	let (player, starter_buildings) = conn.transaction(|connection| {
+		let Some(player) = players::select_faction(connection, player_id, faction)? else {
+			return Err(Error::from((
+				ErrorKind::FactionChangeError,
+				"Faction already selected, change it instead",
+			)));
+		};
+		let provisioned = player_operations::provision_player(connection, &player)?;
+		Ok::<_, Error>((player, provisioned.starter_buildings))
+	})?;
+	modifier_cache.invalidate_user(*player_id);
+
+	info!(
+		"Player {} selected the {} faction with {} starter buildings",
+		player_id, faction, starter_buildings
+	);
+	Ok(FactionSelected {
+		player,
+		starter_buildings,
+	})
+}
+
+/// Moves a player to another faction, converting their buildings and construction plans.
+///
+/// # Errors
+/// - `InvalidFaction` if the new faction is neutral or the player's current faction
+/// - `FactionChangeError` if the player has not picked a faction yet, or buildings of the
+///   player are being upgraded
+/// - `FactionChangeCooldownError` if the player changed faction within the cooldown
+#[instrument(skip(conn, modifier_cache, settings))]
+pub fn change_faction(
+	conn: &mut DbConn,
+	modifier_cache: &ModifierCache,
+	settings: &FactionSettings,
+	player_id: &PlayerKey,
+	to: FactionCode,
+	now: DateTime<Utc>,
+) -> Result<FactionChanged> {
+	let player = players::get_by_id(conn, player_id)?;
+	let from = player.faction;
+	debug!(
+		"Changing faction of player {} from {} to {}",
+		player_id, from, to
+	);
+
+	if to == FactionCode::Neutral {
+		return Err(Error::from((
+			ErrorKind::InvalidFaction,
+			"Players cannot become neutral",
+		)));
+	}
+	if from == FactionCode::Neutral {
+		return Err(Error::from((
+			ErrorKind::FactionChangeError,
+			"Select a faction before changing it",
+		)));
+	}
+	if to == from {
+		return Err(Error::from((
+			ErrorKind::InvalidFaction,
+			"Already a member of this faction",
+		)));
+	}
+
+	let cooldown = TimeDelta::days(settings.change_cooldown_days.max(0));
+	if let Some(last) = faction_changes::find_latest(conn, player_id)?
+		&& last.changed_at + cooldown > now
+	{
+		return Err(Error::from((
+			ErrorKind::FactionChangeCooldownError,
+			"Faction was changed recently",
+			format!("Next change possible at {}", last.changed_at + cooldown),
+		)));
+	}
+
+	let owned = player_buildings::get_player_buildings(conn, player_id)?;
+	if owned.iter().any(|bld| bld.upgrade_finishes_at.is_some())
+		|| !construction_queue::get_active_for_player(conn, player_id)?.is_empty()
+	{
+		return Err(Error::from((
+			ErrorKind::FactionChangeError,
+			"Complete or cancel the running building upgrades first",
+		)));
+	}
+
+	let all_buildings: HashMap<_, _> = buildings::get_all(conn)?
+		.into_iter()
+		.map(|bld| (bld.id, bld))
+		.collect();
+	let stand_in = |building_id: i32| -> Result<Option<i32>> {
+		let Some(building) = all_buildings.get(&building_id) else {
+			return Ok(None);
+		};
+		if building.faction != from {
+			return Ok(None);
+		}
+		let name = equivalent_building_name(&building.name, from, to);
+		find_building(&all_buildings, name, to)
+			.map(|target| Some(target.id))
+			.ok_or_else(|| {
+				Error::from((
+					ErrorKind::InternalError,
+					"No building of the new faction stands in for a building",
+					format!("{} of {} has no equivalent in {}", building.name, from, to),
+				))
+			})
+	};
+
+	let (player, change, converted_buildings, converted_plans) =
+		conn.transaction(|connection| {
+			let mut converted_buildings = 0;
+			for bld in &owned {
+				if let Some(target) = stand_in(bld.building_id)? {
+					player_buildings::set_building(connection, &bld.id, target)?;
+					converted_buildings += 1;
+				}
+			}
+			trace!("Converted {} buildings", converted_buildings);
+
+			let mut converted_plans = 0;
+			for plan in planned_actions::get_pending_for_player(connection, player_id)? {
+				let Some(building_id) = plan.building_id else {
+					continue;
+				};
+				if let Some(target) = stand_in(building_id)? {
+					planned_actions::set_building(connection, &plan.id, target)?;
+					converted_plans += 1;
+				}
+			}
+			trace!("Converted {} construction plans", converted_plans);
+
+			// AIDEV-NOTE: the faction triggers swap the faction modifiers with this update
+			let player = players::update(
+				connection,
+				&UpdatePlayer {
+					id: *player_id,
+					name: None,
+					pwd_hash: None,
+					email: None,
+					faction: Some(to),
+				},
+			)?;
+			let change = faction_changes::create(
+				connection,
+				NewFactionChange {
+					player_id: *player_id,
+					from_faction: from,
+					to_faction: to,
+					changed_at: now,
+				},
+			)?;
+			Ok::<_, Error>((player, change, converted_buildings, converted_plans))
+		})?;
+	modifier_cache.invalidate_user(*player_id);
+
+	info!(
+		"Player {} changed faction from {} to {}, converted {} buildings and {} plans",
+		player_id, from, to, converted_buildings, converted_plans
+	);
+	Ok(FactionChanged {
+		next_change_at: change.changed_at + cooldown,
+		player,
+		change,
+		converted_buildings,
+		converted_plans,
+	})
+}
+
+/// Position of a playable faction in [`FACTION_BUILDINGS`], `None` for the neutral faction.
+fn faction_index(faction: FactionCode) -> Option<usize> {
+	match faction {
+		FactionCode::Neutral => None,
+		FactionCode::Human => Some(0),
+		FactionCode::Orc => Some(1),
+		FactionCode::Elf => Some(2),
+		FactionCode::Dwarf => Some(3),
+		FactionCode::Goblin => Some(4),
+	}
+}
+
+/// Finds the building of a faction by its name.
+fn find_building<'a>(
+	buildings: &'a HashMap<i32, Building>,
+	name: &str,
+	faction: FactionCode,
+) -> Option<&'a Building> {
+	buildings
+		.values()
+		.find(|bld| bld.faction == faction && bld.name == name)
+}
@@ -0,0 +1,9 @@
+//! Faction operations for the Empire game.
+//!
+//! This module holds what happens to a player's empire when they move between factions, and
+//! which building of one faction stands in for a building of another. A recurring job
+//! aggregates the standings of the factions and rewards the one leading the war.
+
+pub mod faction_operations;
+pub mod standing_operations;
+pub mod standings_processor;
@@ -0,0 +1,161 @@
+//! Faction standings.
+//!
+//! Every playable faction is measured by its players, its combined power and its war score.
+//! Power is the sum of the army points of the members, the war score counts the battles they
+//! won against players of other factions during the last cycle. A recurring
+//! [`JobType::Faction`] job closes a cycle once a day: it aggregates the standings, ranks the
+//! factions by war score and then power, and hands the war victory modifiers to every member
+//! of the leading faction. The modifiers last until the next cycle replaces them, and run out
+//! on their own a day later if the job stops.
+//!
+//! A faction only leads with a war score above zero, and factions sharing the first rank all
+//! lead. Members who change faction keep the modifiers until the end of the cycle.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::Connection;
+use tracing::{info, instrument};
+
+use crate::db::{
+	DbConn, active_modifiers, faction_standings, modifier_history, modifiers, players,
+};
+use crate::domain::error::{Error, Result};
+use crate::domain::factions::{FactionCode, FactionJobPayload, FactionStanding};
+use crate::domain::jobs::JobType;
+use crate::domain::modifier::active_modifier::{ModifierSourceType, NewActiveModifier};
+use crate::domain::modifier::modifier_history::ModifierActionType;
+use crate::domain::player::PlayerKey;
+use crate::game::modifiers::modifier_cache::ModifierCache;
+use crate::game::modifiers::modifier_service::ModifierChange;
+use crate::job_queue::{JobPriority, JobQueue};
+
+/// Name of the recurring job aggregating the faction standings.
+pub const FACTION_STANDINGS_TICK_NAME: &str = "faction_standings";
+
+/// Schedule of the aggregation: every day at midnight.
+pub const FACTION_STANDINGS_TICK_CRON: &str = "0 0 0 * * *";
+
+/// Modifiers the members of the leading faction hold until the next aggregation.
+pub const WAR_VICTORY_MODIFIERS: [&str; 2] = ["war_victory_combat", "war_victory_training"];
+
+/// Reason recorded in the modifier history of the war victory modifiers.
+const WAR_VICTORY_REASON: &str = "War victory";
+
+/// Outcome of an aggregation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StandingsSummary {
+	/// The standings of every playable faction, leading faction first
+	pub standings: Vec<FactionStanding>,
+	/// Factions granted the war victory modifiers
+	pub leaders: Vec<FactionCode>,
+	/// Players granted the war victory modifiers
+	pub rewarded: usize,
+}
+
+/// Registers the recurring aggregation of the faction standings. Safe to call on every
+/// startup.
+pub fn register_faction_standings_tick(job_queue: &JobQueue) -> Result<()> {
+	job_queue.register_recurring(
+		FACTION_STANDINGS_TICK_NAME,
+		FACTION_STANDINGS_TICK_CRON,
+		JobType::Faction,
+		FactionJobPayload::Standings,
+		JobPriority::Low,
+	)?;
+	Ok(())
+}
+
+/// Aggregates the standings of every faction and moves the war victory modifiers to the
+/// members of the leading faction, in one transaction.
+///
+/// The battles fought since the previous aggregation count into the war score, every battle
+/// on the first one.
+#[instrument(skip(conn, modifier_cache))]
+pub fn update_standings(
+	conn: &mut DbConn,
+	modifier_cache: &ModifierCache,
+	now: DateTime<Utc>,
+) -> Result<StandingsSummary> {
+	let (summary, changed_players) = conn.transaction(|conn| {
+		let since = faction_standings::last_computed_at(conn)?;
+		faction_standings::replace_standings(conn, now, since)?;
+		let standings = faction_standings::get_all(conn)?;
+		let leaders: Vec<FactionCode> = standings
+			.iter()
+			.filter(|standing| standing.rank == 1 && standing.war_score > 0)
+			.map(|standing| standing.faction)
+			.collect();
+
+		let victory_modifiers = modifiers::get_by_names(conn, &WAR_VICTORY_MODIFIERS)?;
+		let change = ModifierChange {
+			operator: None,
+			reason: Some(WAR_VICTORY_REASON.to_string()),
+		};
+		let mut changed_players: HashSet<PlayerKey> = HashSet::new();
+
+		let modifier_ids: Vec<_> = victory_modifiers.iter().map(|m| m.id).collect();
+		let revoked = active_modifiers::delete_by_modifiers(conn, &modifier_ids)?;
+		let mut history = Vec::with_capacity(revoked.len());
+		for active in &revoked {
+			if let Some(modifier) = victory_modifiers
+				.iter()
+				.find(|m| m.id == active.modifier_id)
+			{
+				history.push(change.history_entry(active, modifier, ModifierActionType::Removed));
+			}
+			changed_players.insert(active.player_id);
+		}
+
+		let members = players::get_member_ids(conn, &leaders)?;
+		let expires_at = now + TimeDelta::days(1);
+		let grants: Vec<NewActiveModifier> = members
+			.iter()
+			.flat_map(|player_id| {
+				victory_modifiers.iter().map(|modifier| NewActiveModifier {
+					player_id: *player_id,
+					modifier_id: modifier.id,
+					started_at: Some(now),
+					expires_at: Some(expires_at),
+					source_type: ModifierSourceType::Faction,
+					source_id: None,
+					player_building_id: None,
+				})
+			})
+			.collect();
+		for active in active_modifiers::create_batch(conn, &grants)? {
+			if let Some(modifier) = victory_modifiers
+				.iter()
+				.find(|m| m.id == active.modifier_id)
+			{
+				history.push(change.history_entry(&active, modifier, ModifierActionType::Applied));
+			}
+			changed_players.insert(active.player_id);
+		}
+		modifier_history::create_batch(conn, &history)?;
+
+		let summary = StandingsSummary {
+			standings,
+			leaders,
+			rewarded: members.len(),
+		};
+		Ok::<_, Error>((summary, changed_players))
+	})?;
+
+	for player_id in changed_players {
+		modifier_cache.invalidate_user(player_id);
+	}
+	info!(
+		"Aggregated {} faction standings, {} players of {:?} lead the war",
+		summary.standings.len(),
+		summary.rewarded,
+		summary.leaders
+	);
+	Ok(summary)
+}
+
+/// Retrieves the standings of the last aggregation, leading faction first.
+#[instrument(skip(conn))]
+pub fn get_standings(conn: &mut DbConn) -> Result<Vec<FactionStanding>> {
+	faction_standings::get_all(conn)
+}
@@ -0,0 +1,61 @@
+//! Faction job processor.
+//!
+//! This module implements the job processing functionality for the factions, aggregating
+//! their standings on every run.
+
+use axum::extract::FromRef;
+use chrono::Utc;
+use tracing::{debug, instrument, trace};
+
+use crate::Error;
+use crate::domain::app_state::{AppModifierCache, AppPool, AppState};
+use crate::domain::factions::FactionJobPayload;
+use crate::domain::jobs::{Job, JobType};
+use crate::game::factions::standing_operations;
+use crate::job_queue::job_processor::{JobHandler, JobOutcome, JobWorker};
+
+/// A handler for [`JobType::Faction`] jobs.
+///
+/// Each job aggregates the faction standings and moves the war victory modifiers to the
+/// leading faction.
+pub struct FactionHandler {
+	/// Database connection pool
+	pool: AppPool,
+	/// Cached modifier multipliers of the players
+	modifier_cache: AppModifierCache,
+}
+
+/// Workers running the [`FactionHandler`].
+pub type FactionProcessor = JobWorker<FactionHandler>;
+
+impl JobHandler for FactionHandler {
+	const JOB_TYPE: JobType = JobType::Faction;
+	const WORKER_NAME: &'static str = "faction-goblin";
+
+	/// Creates a new `FactionHandler` with what it needs from the application state.
+	fn new(app_state: &AppState) -> Self {
+		Self {
+			pool: AppPool::from_ref(app_state),
+			modifier_cache: AppModifierCache::from_ref(app_state),
+		}
+	}
+
+	#[instrument(skip(self, job), fields(job_id = %job.id))]
+	async fn handle(&self, job: Job) -> Result<JobOutcome, Error> {
+		debug!("Processing faction job: {}", job.id);
+		trace!("Job details: {:?}", job);
+
+		let FactionJobPayload::Standings = serde_json::from_value(job.payload.clone())?;
+		let summary = {
+			let mut conn = self.pool.get()?;
+			standing_operations::update_standings(&mut conn, &self.modifier_cache, Utc::now())?
+		};
+
+		debug!("Completed processing faction job: {}", job.id);
+		Ok(Some(serde_json::json!({
+			"standings": summary.standings.len(),
+			"leaders": summary.leaders,
+			"rewarded": summary.rewarded,
+		})))
+	}
+}
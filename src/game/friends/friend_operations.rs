@@ -0,0 +1,251 @@
+//! Friend requests, friendships and blocks between players.
+//!
+//! A player asks another to be friends with [`request_friend`], and the two are friends
+//! once the other player accepts with [`accept_friend`], or asks back. Either of them ends
+//! the friendship, or withdraws or declines the request, with [`remove_friend`].
+//!
+//! Blocking a player ends whatever friendship or request there was between the two, and
+//! keeps them from mailing, attacking or befriending each other until it is lifted, no matter
+//! which of them blocked. Players do not learn who blocked them.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::Connection;
+use tracing::{debug, info, instrument};
+
+use crate::db::{DbConn, player_relationships, players};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::metrics::ServerMetrics;
+use crate::domain::player::PlayerKey;
+use crate::domain::player::relationship::{
+	NewPlayerRelationship, PlayerRelationship, RelationshipStatus,
+};
+
+/// How recently a player must have made a request to count as online.
+pub const ONLINE_WINDOW: TimeDelta = TimeDelta::minutes(5);
+
+/// A player on the other end of a relationship.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelatedPlayer {
+	pub player_id: PlayerKey,
+	pub name: String,
+	/// When the request was sent, the friendship began or the block was put on
+	pub since: DateTime<Utc>,
+}
+
+/// A friend with their online status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Friend {
+	pub player: RelatedPlayer,
+	/// Whether the friend is online, `None` if they hide it
+	pub online: Option<bool>,
+}
+
+/// Everyone a player has a relationship with.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FriendList {
+	/// Friends, by name
+	pub friends: Vec<Friend>,
+	/// Friend requests the player received and has not answered
+	pub incoming: Vec<RelatedPlayer>,
+	/// Friend requests the player sent that were not answered
+	pub outgoing: Vec<RelatedPlayer>,
+	/// Players the player blocked
+	pub blocked: Vec<RelatedPlayer>,
+}
+
+/// Checks that a player can start a relationship with another one.
+fn check_other(conn: &mut DbConn, player_id: &PlayerKey, other_id: &PlayerKey) -> Result<()> {
+	if player_id == other_id {
+		return Err(Error::from((
+			ErrorKind::InvalidData,
+			"Players cannot befriend or block themselves",
+		)));
+	}
+	if players::find_by_id(conn, other_id)?.is_none() {
+		return Err(Error::from((ErrorKind::NotFoundError, "Player not found")));
+	}
+	Ok(())
+}
+
+/// Whether either player blocked the other.
+#[instrument(skip(conn))]
+pub fn is_blocked(conn: &mut DbConn, player_id: &PlayerKey, other_id: &PlayerKey) -> Result<bool> {
+	player_relationships::is_blocked_between(conn, player_id, other_id)
+}
+
+/// Asks another player to be friends.
+///
+/// If the other player already asked the player, their request is accepted instead.
+///
+/// # Errors
+/// - `InvalidData` if the player asks themselves
+/// - `NotFoundError` if the other player does not exist
+/// - `PlayerBlockedError` if either player blocked the other
+/// - `RelationshipConflictError` if they are friends already or the request was sent before
+#[instrument(skip(conn))]
+pub fn request_friend(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	other_id: &PlayerKey,
+) -> Result<PlayerRelationship> {
+	check_other(conn, player_id, other_id)?;
+	conn.transaction(|conn| {
+		let existing = player_relationships::get_between(conn, player_id, other_id)?;
+		if existing
+			.iter()
+			.any(|rel| rel.status == RelationshipStatus::Blocked)
+		{
+			return Err(Error::from((
+				ErrorKind::PlayerBlockedError,
+				"Cannot befriend this player",
+			)));
+		}
+		match existing.into_iter().next() {
+			Some(rel) if rel.status == RelationshipStatus::Accepted => Err(Error::from((
+				ErrorKind::RelationshipConflictError,
+				"Already friends",
+			))),
+			Some(rel) if rel.player_id == *player_id => Err(Error::from((
+				ErrorKind::RelationshipConflictError,
+				"Friend request already sent",
+			))),
+			Some(rel) => {
+				debug!("Player {} asked back, accepting the request", player_id);
+				player_relationships::set_status(conn, &rel.id, RelationshipStatus::Accepted)
+			}
+			None => {
+				let rel = player_relationships::create(
+					conn,
+					NewPlayerRelationship {
+						player_id: *player_id,
+						other_id: *other_id,
+						status: RelationshipStatus::Requested,
+					},
+				)?;
+				info!(
+					"Player {} asked player {} to be friends",
+					player_id, other_id
+				);
+				Ok(rel)
+			}
+		}
+	})
+}
+
+/// Accepts the friend request another player sent.
+///
+/// # Errors
+/// `NotFoundError` if the other player did not ask the player to be friends
+#[instrument(skip(conn))]
+pub fn accept_friend(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	requester_id: &PlayerKey,
+) -> Result<PlayerRelationship> {
+	let request = player_relationships::find(conn, requester_id, player_id)?
+		.filter(|rel| rel.status == RelationshipStatus::Requested)
+		.ok_or_else(|| {
+			Error::from((
+				ErrorKind::NotFoundError,
+				"No friend request from this player",
+			))
+		})?;
+	let friendship =
+		player_relationships::set_status(conn, &request.id, RelationshipStatus::Accepted)?;
+	info!("Players {} and {} are friends", requester_id, player_id);
+	Ok(friendship)
+}
+
+/// Ends a friendship, or withdraws or declines a friend request.
+///
+/// # Errors
+/// `NotFoundError` if the players are neither friends nor asked each other
+#[instrument(skip(conn))]
+pub fn remove_friend(conn: &mut DbConn, player_id: &PlayerKey, other_id: &PlayerKey) -> Result<()> {
+	let relationship = player_relationships::get_between(conn, player_id, other_id)?
+		.into_iter()
+		.find(|rel| rel.status != RelationshipStatus::Blocked)
+		.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Not friends with this player")))?;
+	player_relationships::delete(conn, &relationship.id)?;
+	debug!(
+		"Removed the {} relationship between players {} and {}",
+		relationship.status, player_id, other_id
+	);
+	Ok(())
+}
+
+/// Blocks another player, ending the friendship or friend requests between the two.
+///
+/// Blocking a player again keeps the block as it is.
+///
+/// # Errors
+/// - `InvalidData` if the player blocks themselves
+/// - `NotFoundError` if the other player does not exist
+#[instrument(skip(conn))]
+pub fn block(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	other_id: &PlayerKey,
+) -> Result<PlayerRelationship> {
+	check_other(conn, player_id, other_id)?;
+	let block = conn.transaction(|conn| {
+		for rel in player_relationships::get_between(conn, player_id, other_id)? {
+			// The other player's block stays, the player only lifts their own
+			if rel.status != RelationshipStatus::Blocked {
+				player_relationships::delete(conn, &rel.id)?;
+			}
+		}
+		player_relationships::upsert_block(conn, player_id, other_id)
+	})?;
+	info!("Player {} blocked player {}", player_id, other_id);
+	Ok(block)
+}
+
+/// Lifts the block a player put on another player.
+///
+/// # Errors
+/// `NotFoundError` if the player did not block the other player
+#[instrument(skip(conn))]
+pub fn unblock(conn: &mut DbConn, player_id: &PlayerKey, other_id: &PlayerKey) -> Result<()> {
+	let block = player_relationships::find(conn, player_id, other_id)?
+		.filter(|rel| rel.status == RelationshipStatus::Blocked)
+		.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Player is not blocked")))?;
+	player_relationships::delete(conn, &block.id)?;
+	info!("Player {} unblocked player {}", player_id, other_id);
+	Ok(())
+}
+
+/// Lists a player's friends, friend requests and blocked players.
+///
+/// Friends count as online if they made a request within [`ONLINE_WINDOW`], unless they
+/// hide their online status.
+#[instrument(skip(conn, metrics))]
+pub fn list(
+	conn: &mut DbConn,
+	metrics: &ServerMetrics,
+	player_id: &PlayerKey,
+) -> Result<FriendList> {
+	let mut list = FriendList::default();
+	for (rel, name, hide_online_status) in player_relationships::get_for_player(conn, player_id)? {
+		let player = RelatedPlayer {
+			player_id: rel.counterpart(player_id),
+			name,
+			since: rel.updated_at,
+		};
+		match rel.status {
+			RelationshipStatus::Accepted => {
+				let online = (!hide_online_status)
+					.then(|| metrics.is_active(&player.player_id, ONLINE_WINDOW));
+				list.friends.push(Friend { player, online });
+			}
+			RelationshipStatus::Requested if rel.player_id == *player_id => {
+				list.outgoing.push(player)
+			}
+			RelationshipStatus::Requested => list.incoming.push(player),
+			RelationshipStatus::Blocked => list.blocked.push(player),
+		}
+	}
+	list.friends
+		.sort_by(|a, b| a.player.name.cmp(&b.player.name));
+	Ok(list)
+}
@@ -0,0 +1,6 @@
+//! Friend operations for the Empire game.
+//!
+//! This module lets players send and answer friend requests, see which of their friends
+//! are online, and block players they want nothing to do with.
+
+pub mod friend_operations;
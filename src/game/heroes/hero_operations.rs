@@ -0,0 +1,385 @@
+//! Recruiting, training and assigning heroes.
+//!
+//! Heroes are recruited for gold and trained with gold, gaining [`GOLD_PER_XP`] gold worth
+//! of experience at a time until they reach their highest level. Every level of a hero has its
+//! own modifier, which applies while the hero is assigned: army heroes lead the army and raise
+//! the player's combat strength, production heroes run a building and raise its production of
+//! one resource. A production hero only makes a difference in a building producing that
+//! resource.
+//!
+//! The active modifier of an assigned hero names the player hero as its source. It is replaced
+//! when the hero levels up or is reassigned, and removed when the hero is unassigned, all in
+//! the same transaction as the change to the hero and recorded in the modifier history. The
+//! player's cached multiplier is invalidated once the transaction committed.
+
+use diesel::Connection;
+use tracing::{info, instrument};
+
+use crate::db::{
+	DbConn, active_modifiers, heroes, modifier_history, modifiers, player_buildings, resources,
+};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::hero::{Hero, HeroKey, HeroLevel, HeroSpecialty, NewPlayerHero, PlayerHero};
+use crate::domain::modifier::Modifier;
+use crate::domain::modifier::active_modifier::{
+	ActiveModifier, ModifierSourceType, NewActiveModifier,
+};
+use crate::domain::modifier::modifier_history::ModifierActionType;
+use crate::domain::player::PlayerKey;
+use crate::domain::player::buildings::PlayerBuildingKey;
+use crate::game::modifiers::modifier_service::{ModifierChange, ModifierService};
+
+/// Gold it costs to train a hero for a single point of experience.
+pub const GOLD_PER_XP: i64 = 2;
+
+/// What a hero is assigned to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assignment {
+	/// Leading the army, for army heroes
+	Army,
+	/// Running a building, for production heroes
+	Building(PlayerBuildingKey),
+}
+
+/// A hero as a player sees it, recruited or not.
+#[derive(Debug, Clone)]
+pub struct HeroStatus {
+	pub hero: Hero,
+	/// The player's hero, `None` until the player recruits them
+	pub recruited: Option<PlayerHero>,
+	/// Modifier the hero applies at their level, or at level 1 if not recruited
+	pub modifier: Modifier,
+	/// The level the hero trains for, `None` at the highest level
+	pub next_level: Option<HeroLevel>,
+}
+
+/// A hero that was trained.
+#[derive(Debug, Clone)]
+pub struct TrainedHero {
+	pub status: HeroStatus,
+	pub xp_gained: i64,
+	pub gold_spent: i64,
+	pub levels_gained: i32,
+}
+
+/// A hero that was assigned, with the modifier they apply.
+#[derive(Debug, Clone)]
+pub struct AssignedHero {
+	pub status: HeroStatus,
+	pub active: ActiveModifier,
+}
+
+/// The modifiers changed by a hero, for invalidating the player's cached multipliers once
+/// the change committed.
+#[derive(Debug, Default)]
+struct ModifierChanges {
+	applied: Option<(ActiveModifier, Modifier)>,
+	removed: Vec<Modifier>,
+}
+
+/// Lists every hero along with the player's progress on the ones they recruited.
+#[instrument(skip(conn))]
+pub fn list_heroes(conn: &mut DbConn, player_id: &PlayerKey) -> Result<Vec<HeroStatus>> {
+	let mut recruited = heroes::get_for_player(conn, player_id)?;
+	heroes::get_all(conn)?
+		.into_iter()
+		.map(|hero| {
+			let own = recruited
+				.iter()
+				.position(|(_, recruited_hero)| recruited_hero.id == hero.id)
+				.map(|at| recruited.swap_remove(at).0);
+			status(conn, hero, own)
+		})
+		.collect()
+}
+
+/// Recruits a hero for their gold.
+///
+/// Fails with [`ErrorKind::HeroConflictError`] if the player recruited the hero before.
+#[instrument(skip(conn))]
+pub fn recruit_hero(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	hero_id: &HeroKey,
+) -> Result<HeroStatus> {
+	let status = conn.transaction(|conn| {
+		let hero = heroes::get_by_id(conn, hero_id)?;
+		spend_gold(conn, player_id, hero.recruit_gold)?;
+		let recruited = heroes::recruit(
+			conn,
+			NewPlayerHero {
+				player_id: *player_id,
+				hero_id: hero.id,
+			},
+		)?
+		.ok_or_else(|| Error::from((ErrorKind::HeroConflictError, "Hero already recruited")))?;
+		status(conn, hero, Some(recruited))
+	})?;
+
+	info!("Player {} recruited hero {}", player_id, status.hero.name);
+	Ok(status)
+}
+
+/// Trains a hero for `xp` experience, paying [`GOLD_PER_XP`] gold for each point.
+///
+/// The hero gains levels as they reach the experience of the next ones, and an assigned hero
+/// applies the modifier of their new level right away. Training never goes past the highest
+/// level, the player only pays for the experience the hero can still gain. Fails with
+/// [`ErrorKind::HeroMaxLevelError`] if the hero is at their highest level.
+#[instrument(skip(conn, service))]
+pub async fn train_hero(
+	conn: &mut DbConn,
+	service: &ModifierService,
+	player_id: &PlayerKey,
+	hero_id: &HeroKey,
+	xp: i64,
+) -> Result<TrainedHero> {
+	if xp <= 0 {
+		return Err(Error::from((
+			ErrorKind::InvalidQuantityError,
+			"Experience must be positive",
+		)));
+	}
+
+	let (trained, changes) = conn.transaction(|conn| {
+		let player_hero = heroes::lock_recruited(conn, player_id, hero_id)?;
+		let hero = heroes::get_by_id(conn, hero_id)?;
+		let levels = heroes::get_levels(conn, &hero.id)?;
+		let max_xp = levels.last().map_or(0, |level| level.xp_required);
+		if player_hero.xp >= max_xp {
+			return Err(Error::from((
+				ErrorKind::HeroMaxLevelError,
+				"Hero is at the highest level",
+			)));
+		}
+
+		let xp_gained = xp.min(max_xp - player_hero.xp);
+		let gold_spent = xp_gained * GOLD_PER_XP;
+		spend_gold(conn, player_id, gold_spent)?;
+		let total_xp = player_hero.xp + xp_gained;
+		let level = levels
+			.iter()
+			.filter(|level| level.xp_required <= total_xp)
+			.map(|level| level.level)
+			.max()
+			.unwrap_or(player_hero.level);
+		let levels_gained = level - player_hero.level;
+		let updated = heroes::set_progress(conn, &player_hero.id, level, total_xp)?;
+
+		let changes = if levels_gained > 0 && updated.is_assigned() {
+			let reason = format!("{} reached level {}", hero.name, level);
+			let mut changes = remove_modifiers(conn, &updated, &reason)?;
+			changes.applied = Some(apply_modifier(conn, &updated, &reason)?);
+			changes
+		} else {
+			ModifierChanges::default()
+		};
+
+		let trained = TrainedHero {
+			status: status(conn, hero, Some(updated))?,
+			xp_gained,
+			gold_spent,
+			levels_gained,
+		};
+		Ok::<_, Error>((trained, changes))
+	})?;
+	track(service, player_id, changes).await?;
+
+	info!(
+		"Player {} trained hero {} for {} experience, {} levels gained",
+		player_id, trained.status.hero.name, trained.xp_gained, trained.levels_gained
+	);
+	Ok(trained)
+}
+
+/// Assigns a hero to lead the army or run a building, replacing their last assignment.
+///
+/// Army heroes can only lead the army and production heroes only run buildings. Fails with
+/// [`ErrorKind::HeroConflictError`] if another hero already leads the army or runs the
+/// building.
+#[instrument(skip(conn, service))]
+pub async fn assign_hero(
+	conn: &mut DbConn,
+	service: &ModifierService,
+	player_id: &PlayerKey,
+	hero_id: &HeroKey,
+	assignment: Assignment,
+) -> Result<AssignedHero> {
+	let (assigned, changes) = conn.transaction(|conn| {
+		let player_hero = heroes::lock_recruited(conn, player_id, hero_id)?;
+		let hero = heroes::get_by_id(conn, hero_id)?;
+		let occupant = match (hero.specialty, assignment) {
+			(HeroSpecialty::Army, Assignment::Army) => heroes::find_army_leader(conn, player_id)?,
+			(HeroSpecialty::Production, Assignment::Building(player_bld_id)) => {
+				player_buildings::get_owned(conn, player_id, &player_bld_id)?;
+				heroes::find_by_building(conn, &player_bld_id)?
+			}
+			(HeroSpecialty::Army, Assignment::Building(_)) => {
+				return Err(Error::from((
+					ErrorKind::InvalidData,
+					"Army heroes can only lead the army",
+				)));
+			}
+			(HeroSpecialty::Production, Assignment::Army) => {
+				return Err(Error::from((
+					ErrorKind::InvalidData,
+					"Production heroes can only run buildings",
+				)));
+			}
+		};
+		if occupant.is_some_and(|occupant| occupant.id != player_hero.id) {
+			return Err(Error::from((
+				ErrorKind::HeroConflictError,
+				match assignment {
+					Assignment::Army => "Another hero leads the army",
+					Assignment::Building(_) => "Another hero runs the building",
+				},
+			)));
+		}
+
+		let reason = format!("Assigned {}", hero.name);
+		let mut changes = remove_modifiers(conn, &player_hero, &reason)?;
+		let updated = match assignment {
+			Assignment::Army => heroes::set_assignment(conn, &player_hero.id, None, true)?,
+			Assignment::Building(player_bld_id) => {
+				heroes::set_assignment(conn, &player_hero.id, Some(player_bld_id), false)?
+			}
+		};
+		let (active, modifier) = apply_modifier(conn, &updated, &reason)?;
+		changes.applied = Some((active.clone(), modifier));
+
+		let assigned = AssignedHero {
+			status: status(conn, hero, Some(updated))?,
+			active,
+		};
+		Ok::<_, Error>((assigned, changes))
+	})?;
+	track(service, player_id, changes).await?;
+
+	info!(
+		"Player {} assigned hero {} to {:?}",
+		player_id, assigned.status.hero.name, assignment
+	);
+	Ok(assigned)
+}
+
+/// Unassigns a hero, removing the modifier they apply.
+#[instrument(skip(conn, service))]
+pub async fn unassign_hero(
+	conn: &mut DbConn,
+	service: &ModifierService,
+	player_id: &PlayerKey,
+	hero_id: &HeroKey,
+) -> Result<HeroStatus> {
+	let (status, changes) = conn.transaction(|conn| {
+		let player_hero = heroes::lock_recruited(conn, player_id, hero_id)?;
+		let hero = heroes::get_by_id(conn, hero_id)?;
+		let changes = remove_modifiers(conn, &player_hero, &format!("Unassigned {}", hero.name))?;
+		let updated = heroes::set_assignment(conn, &player_hero.id, None, false)?;
+		Ok::<_, Error>((status(conn, hero, Some(updated))?, changes))
+	})?;
+	track(service, player_id, changes).await?;
+
+	info!("Player {} unassigned hero {}", player_id, status.hero.name);
+	Ok(status)
+}
+
+/// Looks up the modifier and next level of a hero at the level of `recruited`.
+fn status(conn: &mut DbConn, hero: Hero, recruited: Option<PlayerHero>) -> Result<HeroStatus> {
+	let level = recruited.as_ref().map_or(1, |recruited| recruited.level);
+	let levels = heroes::get_levels(conn, &hero.id)?;
+	let current = levels
+		.iter()
+		.find(|candidate| candidate.level == level)
+		.ok_or_else(|| Error::from((ErrorKind::InternalError, "Hero level is missing")))?;
+	let modifier = modifiers::get_by_id(conn, &current.modifier_id)?;
+	let next_level = levels
+		.into_iter()
+		.find(|candidate| candidate.level == level + 1);
+
+	Ok(HeroStatus {
+		hero,
+		recruited,
+		modifier,
+		next_level,
+	})
+}
+
+/// Takes `gold` from the storage of the player's capital.
+fn spend_gold(conn: &mut DbConn, player_id: &PlayerKey, gold: i64) -> Result<()> {
+	let held = resources::lock_by_player_id(conn, player_id)?;
+	if held.gold < gold {
+		return Err(Error::from((
+			ErrorKind::InsufficientResourcesError,
+			"Not enough gold",
+		)));
+	}
+	resources::deduct(conn, player_id, &(0, 0, 0, gold))?;
+	Ok(())
+}
+
+/// Applies the modifier of the hero's level to what they are assigned to.
+fn apply_modifier(
+	conn: &mut DbConn,
+	player_hero: &PlayerHero,
+	reason: &str,
+) -> Result<(ActiveModifier, Modifier)> {
+	let level = heroes::get_level(conn, &player_hero.hero_id, player_hero.level)?;
+	let modifier = modifiers::get_by_id(conn, &level.modifier_id)?;
+	let active = active_modifiers::create(
+		conn,
+		NewActiveModifier {
+			player_id: player_hero.player_id,
+			modifier_id: modifier.id,
+			started_at: None,
+			expires_at: None,
+			source_type: ModifierSourceType::Hero,
+			source_id: Some(player_hero.id),
+			player_building_id: player_hero.player_building_id,
+		},
+	)?;
+	let change = ModifierChange {
+		operator: None,
+		reason: Some(reason.to_string()),
+	};
+	let entry = change.history_entry(&active, &modifier, ModifierActionType::Applied);
+	modifier_history::create_batch(conn, &[entry])?;
+	Ok((active, modifier))
+}
+
+/// Removes the modifiers the hero applies, if any.
+fn remove_modifiers(
+	conn: &mut DbConn,
+	player_hero: &PlayerHero,
+	reason: &str,
+) -> Result<ModifierChanges> {
+	let change = ModifierChange {
+		operator: None,
+		reason: Some(reason.to_string()),
+	};
+	let mut changes = ModifierChanges::default();
+	for active in
+		active_modifiers::delete_by_source(conn, ModifierSourceType::Hero, &player_hero.id)?
+	{
+		let modifier = modifiers::get_by_id(conn, &active.modifier_id)?;
+		let entry = change.history_entry(&active, &modifier, ModifierActionType::Removed);
+		modifier_history::create_batch(conn, &[entry])?;
+		changes.removed.push(modifier);
+	}
+	Ok(changes)
+}
+
+/// Invalidates the player's cached multipliers for the modifiers a hero changed.
+async fn track(
+	service: &ModifierService,
+	player_id: &PlayerKey,
+	changes: ModifierChanges,
+) -> Result<()> {
+	for modifier in &changes.removed {
+		service.invalidate(player_id, modifier);
+	}
+	if let Some((active, modifier)) = &changes.applied {
+		service.track_modifier(active, modifier).await?;
+	}
+	Ok(())
+}
@@ -0,0 +1,7 @@
+//! Hero operations for the Empire game.
+//!
+//! This module lets players recruit heroes, train them to higher levels and assign them to
+//! lead their army or run one of their buildings, where they apply the modifier of their
+//! level.
+
+pub mod hero_operations;
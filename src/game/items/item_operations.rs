@@ -0,0 +1,95 @@
+//! Using consumable items.
+//!
+//! Using an item takes one from the player's inventory and applies the item's modifier to
+//! the player until the item's duration runs out. Both happen in a single transaction, and
+//! the use is recorded in the modifier history with the item as its source. The player's
+//! cached multiplier is invalidated once the transaction committed, and the modifier expires
+//! like any other temporary modifier.
+
+use chrono::{DateTime, Utc};
+use diesel::Connection;
+use tracing::{info, instrument};
+
+use crate::db::{DbConn, active_modifiers, items, modifier_history, modifiers, player_items};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::item::{Item, ItemKey, PlayerItem};
+use crate::domain::modifier::Modifier;
+use crate::domain::modifier::active_modifier::{
+	ActiveModifier, ModifierSourceType, NewActiveModifier,
+};
+use crate::domain::modifier::modifier_history::ModifierActionType;
+use crate::domain::player::PlayerKey;
+use crate::game::modifiers::modifier_service::{ModifierChange, ModifierService};
+
+/// An item that was used, with the modifier it applied.
+#[derive(Debug, Clone)]
+pub struct UsedItem {
+	pub item: Item,
+	/// What is left of the item in the player's inventory
+	pub remaining: PlayerItem,
+	pub active: ActiveModifier,
+	pub modifier: Modifier,
+}
+
+/// Uses one of an item held by a player.
+///
+/// Fails with [`ErrorKind::ItemUnavailableError`] if the player holds none of the item.
+#[instrument(skip(conn, service))]
+pub async fn use_item(
+	conn: &mut DbConn,
+	service: &ModifierService,
+	player_id: &PlayerKey,
+	item_id: &ItemKey,
+) -> Result<UsedItem> {
+	let used = conn.transaction(|conn| consume_item(conn, player_id, item_id, Utc::now()))?;
+	service.track_modifier(&used.active, &used.modifier).await?;
+
+	info!(
+		"Player {} used item {}, {} left",
+		player_id, used.item.name, used.remaining.quantity
+	);
+	Ok(used)
+}
+
+/// Takes the item from the inventory and applies its modifier, starting at `now`.
+fn consume_item(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	item_id: &ItemKey,
+	now: DateTime<Utc>,
+) -> Result<UsedItem> {
+	let item = items::get_by_id(conn, item_id)?;
+	let remaining = player_items::consume_one(conn, player_id, item_id)?.ok_or_else(|| {
+		Error::from((
+			ErrorKind::ItemUnavailableError,
+			"No items of this kind left",
+		))
+	})?;
+	let modifier = modifiers::get_by_id(conn, &item.modifier_id)?;
+
+	let active = active_modifiers::create(
+		conn,
+		NewActiveModifier {
+			player_id: *player_id,
+			modifier_id: modifier.id,
+			started_at: Some(now),
+			expires_at: Some(now + item.duration()),
+			source_type: ModifierSourceType::Item,
+			source_id: Some(item.id),
+			player_building_id: None,
+		},
+	)?;
+	let change = ModifierChange {
+		operator: None,
+		reason: Some(format!("Used {}", item.name)),
+	};
+	let entry = change.history_entry(&active, &modifier, ModifierActionType::Applied);
+	modifier_history::create_batch(conn, &[entry])?;
+
+	Ok(UsedItem {
+		item,
+		remaining,
+		active,
+		modifier,
+	})
+}
@@ -0,0 +1,6 @@
+//! Item operations for the Empire game.
+//!
+//! This module lets players use the consumable items they hold, like boosts that speed up
+//! the production of a resource for a couple of hours.
+
+pub mod item_operations;
@@ -0,0 +1,166 @@
+//! Leaderboard operations for the Empire game.
+//!
+//! Players score a point per building level and per level of their research buildings, and
+//! as many points as the attack and defense of their units add up to, tier bonuses included.
+//! The power board ranks players by the points of their army, the economy board by the points
+//! of their buildings and research, and the alliance board ranks alliances by all points of
+//! their members.
+//!
+//! Scores follow the game as it is played, but the boards only change when a recurring
+//! [`JobType::Leaderboard`] job snapshots them. The snapshot recalculates every score first,
+//! so points lost without a game event, like units killed in battle, are caught up.
+
+use chrono::{DateTime, Utc};
+use diesel::Connection;
+use tracing::{info, instrument};
+
+use crate::db::{DbConn, alliance_members, leaderboards};
+use crate::domain::error::Result;
+use crate::domain::events::GameEvent;
+use crate::domain::jobs::JobType;
+use crate::domain::leaderboard::{
+	LeaderboardCategory, LeaderboardEntry, LeaderboardJobPayload, PlayerScore,
+};
+use crate::domain::player::PlayerKey;
+use crate::job_queue::{JobPriority, JobQueue};
+
+/// Name of the recurring job snapshotting the leaderboards.
+pub const LEADERBOARD_TICK_NAME: &str = "leaderboard";
+
+/// Schedule of the snapshot: every 15 minutes.
+pub const LEADERBOARD_TICK_CRON: &str = "0 */15 * * * *";
+
+/// Default number of entries per page.
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+
+/// Maximum number of entries per page.
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+/// Buildings of every faction that score tech points instead of building points.
+pub const RESEARCH_BUILDINGS: [&str; 5] = [
+	"Academy",
+	"University",
+	"Laboratory",
+	"Cadet School",
+	"Brainery",
+];
+
+/// A ranked player or alliance, with their name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RankedEntry {
+	pub entry: LeaderboardEntry,
+	pub name: String,
+}
+
+/// A single page of a board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeaderboardPage {
+	pub category: LeaderboardCategory,
+	pub entries: Vec<RankedEntry>,
+	pub page: i64,
+	pub per_page: i64,
+	/// Total number of entries on the board
+	pub total: i64,
+	/// The entry of the player, or of their alliance on the alliance board, if ranked
+	pub own: Option<LeaderboardEntry>,
+}
+
+/// Outcome of a snapshot run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapshotSummary {
+	/// Scores recalculated, one per player
+	pub scored: usize,
+	/// Entries written on all boards
+	pub ranked: usize,
+}
+
+/// Registers the recurring snapshot of the leaderboards. Safe to call on every startup.
+pub fn register_leaderboard_tick(job_queue: &JobQueue) -> Result<()> {
+	job_queue.register_recurring(
+		LEADERBOARD_TICK_NAME,
+		LEADERBOARD_TICK_CRON,
+		JobType::Leaderboard,
+		LeaderboardJobPayload::Snapshot,
+		JobPriority::Low,
+	)?;
+	Ok(())
+}
+
+/// Whether an event changes the points of the player it is addressed to.
+pub fn changes_score(event: &GameEvent) -> bool {
+	matches!(
+		event,
+		GameEvent::UpgradeCompleted { .. } | GameEvent::TrainingCompleted { .. }
+	)
+}
+
+/// Recalculates the score of a player.
+#[instrument(skip(conn))]
+pub fn refresh_score(conn: &mut DbConn, player_id: &PlayerKey) -> Result<PlayerScore> {
+	leaderboards::refresh_player_score(conn, player_id, &RESEARCH_BUILDINGS)
+}
+
+/// Retrieves the score of a player, calculating it if it was never calculated.
+#[instrument(skip(conn))]
+pub fn get_score(conn: &mut DbConn, player_id: &PlayerKey) -> Result<PlayerScore> {
+	match leaderboards::find_score(conn, player_id)? {
+		Some(score) => Ok(score),
+		None => refresh_score(conn, player_id),
+	}
+}
+
+/// Recalculates every score and replaces the rankings of every board, in one transaction so
+/// the boards never show a partial snapshot.
+#[instrument(skip(conn))]
+pub fn snapshot(conn: &mut DbConn, now: DateTime<Utc>) -> Result<SnapshotSummary> {
+	let summary = conn.transaction(|conn| {
+		let scored = leaderboards::refresh_scores(conn, &RESEARCH_BUILDINGS)?;
+		let ranked = leaderboards::replace_snapshot(conn, now)?;
+		Ok::<_, crate::Error>(SnapshotSummary { scored, ranked })
+	})?;
+	info!(
+		"Scored {} players, ranked {} entries",
+		summary.scored, summary.ranked
+	);
+	Ok(summary)
+}
+
+/// Returns a page of a board, along with the entry of the player.
+///
+/// Pages are 1-based. Missing values default to the first page of [`DEFAULT_PAGE_SIZE`]
+/// entries, and page sizes are capped at [`MAX_PAGE_SIZE`].
+#[instrument(skip(conn))]
+pub fn get_page(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	category: LeaderboardCategory,
+	page: Option<i64>,
+	per_page: Option<i64>,
+) -> Result<LeaderboardPage> {
+	let page = page.unwrap_or(1).max(1);
+	let per_page = per_page
+		.unwrap_or(DEFAULT_PAGE_SIZE)
+		.clamp(1, MAX_PAGE_SIZE);
+	let offset = (page - 1).saturating_mul(per_page);
+
+	let (entries, total) = leaderboards::get_page(conn, category, per_page, offset)?;
+	let own = match category {
+		LeaderboardCategory::Alliance => match alliance_members::find_by_player(conn, player_id)? {
+			Some(member) => leaderboards::find_entry(conn, category, &member.alliance_id)?,
+			None => None,
+		},
+		_ => leaderboards::find_entry(conn, category, player_id)?,
+	};
+
+	Ok(LeaderboardPage {
+		category,
+		entries: entries
+			.into_iter()
+			.map(|(entry, name)| RankedEntry { entry, name })
+			.collect(),
+		page,
+		per_page,
+		total,
+		own,
+	})
+}
@@ -0,0 +1,56 @@
+//! Leaderboard job processor.
+//!
+//! This module implements the job processing functionality for the leaderboards,
+//! snapshotting every board on every run.
+
+use axum::extract::FromRef;
+use chrono::Utc;
+use tracing::{debug, instrument, trace};
+
+use crate::Error;
+use crate::domain::app_state::{AppPool, AppState};
+use crate::domain::jobs::{Job, JobType};
+use crate::domain::leaderboard::LeaderboardJobPayload;
+use crate::game::leaderboard::leaderboard_operations;
+use crate::job_queue::job_processor::{JobHandler, JobOutcome, JobWorker};
+
+/// A handler for [`JobType::Leaderboard`] jobs.
+///
+/// Each job recalculates every score and replaces the rankings of every board.
+pub struct LeaderboardHandler {
+	/// Database connection pool
+	pool: AppPool,
+}
+
+/// Workers running the [`LeaderboardHandler`].
+pub type LeaderboardProcessor = JobWorker<LeaderboardHandler>;
+
+impl JobHandler for LeaderboardHandler {
+	const JOB_TYPE: JobType = JobType::Leaderboard;
+	const WORKER_NAME: &'static str = "leaderboard-goblin";
+
+	/// Creates a new `LeaderboardHandler` with what it needs from the application state.
+	fn new(app_state: &AppState) -> Self {
+		Self {
+			pool: AppPool::from_ref(app_state),
+		}
+	}
+
+	#[instrument(skip(self, job), fields(job_id = %job.id))]
+	async fn handle(&self, job: Job) -> Result<JobOutcome, Error> {
+		debug!("Processing leaderboard job: {}", job.id);
+		trace!("Job details: {:?}", job);
+
+		let LeaderboardJobPayload::Snapshot = serde_json::from_value(job.payload.clone())?;
+		let summary = {
+			let mut conn = self.pool.get()?;
+			leaderboard_operations::snapshot(&mut conn, Utc::now())?
+		};
+
+		debug!("Completed processing leaderboard job: {}", job.id);
+		Ok(Some(serde_json::json!({
+			"scored": summary.scored,
+			"ranked": summary.ranked,
+		})))
+	}
+}
@@ -0,0 +1,10 @@
+//! Leaderboards for the Empire game.
+//!
+//! This module scores every player by their buildings, army and research. A listener on the
+//! event bus keeps the score of a player up to date as their upgrades and trainings complete,
+//! and a recurring job recalculates every score and snapshots the rankings of the power,
+//! economy and alliance boards.
+
+pub mod leaderboard_operations;
+pub mod leaderboard_processor;
+pub mod score_listener;
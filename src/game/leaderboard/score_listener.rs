@@ -0,0 +1,47 @@
+//! Keeps the scores of the players up to date with the game events published on the event
+//! bus.
+
+use tokio::sync::broadcast::Receiver;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, warn};
+
+use crate::Result;
+use crate::domain::app_state::AppPool;
+use crate::domain::events::GameEvent;
+use crate::game::leaderboard::leaderboard_operations;
+
+/// Refreshes the score of every player an event received on `events` changes the points of,
+/// until `token` is cancelled.
+///
+/// Events missed while the listener lags behind the bus are caught up by the next snapshot.
+pub async fn listen(pool: AppPool, mut events: Receiver<GameEvent>, token: CancellationToken) {
+	loop {
+		tokio::select! {
+			_ = token.cancelled() => {
+				debug!("Score listener shutting down");
+				break;
+			}
+			received = events.recv() => match received {
+				Ok(event) => {
+					if let Err(err) = record(&pool, &event) {
+						error!("Failed to refresh the score for {:?}: {}", event, err);
+					}
+				}
+				Err(RecvError::Lagged(skipped)) => {
+					warn!("Score listener lagged, skipped {} events", skipped);
+				}
+				Err(RecvError::Closed) => break,
+			},
+		}
+	}
+}
+
+fn record(pool: &AppPool, event: &GameEvent) -> Result<()> {
+	if !leaderboard_operations::changes_score(event) {
+		return Ok(());
+	}
+	let mut conn = pool.get()?;
+	leaderboard_operations::refresh_score(&mut conn, event.player_id())?;
+	Ok(())
+}
@@ -0,0 +1,43 @@
+//! Counts the game events published on the event bus towards the objectives of the running
+//! limited events.
+
+use chrono::Utc;
+use tokio::sync::broadcast::Receiver;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, warn};
+
+use crate::Result;
+use crate::domain::app_state::AppPool;
+use crate::domain::events::GameEvent;
+use crate::game::limited_events::event_operations;
+
+/// Records the progress of every event received on `events` until `token` is cancelled.
+///
+/// Events missed while the listener lags behind the bus are not counted.
+pub async fn listen(pool: AppPool, mut events: Receiver<GameEvent>, token: CancellationToken) {
+	loop {
+		tokio::select! {
+			_ = token.cancelled() => {
+				debug!("Limited event listener shutting down");
+				break;
+			}
+			received = events.recv() => match received {
+				Ok(event) => {
+					if let Err(err) = record(&pool, &event) {
+						error!("Failed to record the progress of {:?}: {}", event, err);
+					}
+				}
+				Err(RecvError::Lagged(skipped)) => {
+					warn!("Limited event listener lagged, skipped {} events", skipped);
+				}
+				Err(RecvError::Closed) => break,
+			},
+		}
+	}
+}
+
+fn record(pool: &AppPool, event: &GameEvent) -> Result<i64> {
+	let mut conn = pool.get()?;
+	event_operations::record_progress(&mut conn, event, Utc::now())
+}
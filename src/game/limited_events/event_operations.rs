@@ -0,0 +1,459 @@
+//! Time-limited events and their shops.
+//!
+//! An operator creates an event with its objectives and its shop, which is recorded in the
+//! audit log. While the event runs, players earn its currency by completing the objectives:
+//! every [`GameEvent`] published on the event bus is counted by [`record_progress`], and
+//! every `target` occurrences of an objective's kind earn its reward, up to its maximum
+//! completions. Players spend the currency in the event's shop on items and units.
+//!
+//! A [`JobType::LimitedEvent`] job closes the event once it ends, converting the currency
+//! players did not spend into the event's leftover resource. What does not fit into a
+//! player's storage is lost. Closing an event twice does nothing, so a retried job never
+//! converts anything twice.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use diesel::Connection;
+use serde_json::json;
+use tracing::{debug, info, instrument, trace};
+
+use crate::db::resources::ResourceDelta;
+use crate::db::{
+	DbConn, audit_log, items, limited_events, player_event_progress, player_items, player_units,
+	resources, units,
+};
+use crate::domain::audit::NewAuditEntry;
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::events::GameEvent;
+use crate::domain::item::ItemKey;
+use crate::domain::jobs::{JobKey, JobType};
+use crate::domain::limited_event::{
+	EventObjective, EventObjectiveKind, EventOffer, EventOfferKey, LimitedEvent,
+	LimitedEventJobPayload, LimitedEventKey, NewEventObjective, NewEventOffer, NewLimitedEvent,
+	PlayerEventObjective,
+};
+use crate::domain::player::PlayerKey;
+use crate::domain::player::resource::{PlayerResource, ResourceType};
+use crate::domain::unit::UnitKey;
+use crate::game::admin_operations::AdminActor;
+use crate::job_queue::{JobPriority, JobQueue};
+
+/// Action recorded in the audit log when an event is created.
+pub const CREATE_EVENT_ACTION: &str = "create_limited_event";
+
+/// An objective of an event an operator asked to create.
+#[derive(Debug, Clone)]
+pub struct ObjectiveRequest {
+	pub kind: EventObjectiveKind,
+	pub description: String,
+	pub target: i64,
+	pub reward: i64,
+	pub max_completions: Option<i32>,
+}
+
+/// An offer of an event shop an operator asked to create, selling either an item or a unit.
+#[derive(Debug, Clone)]
+pub struct OfferRequest {
+	pub price: i64,
+	pub item_id: Option<ItemKey>,
+	pub unit_id: Option<UnitKey>,
+	pub quantity: i64,
+}
+
+/// An event an operator asked to create.
+#[derive(Debug, Clone)]
+pub struct EventRequest {
+	pub name: String,
+	pub description: String,
+	pub currency_name: String,
+	pub starts_at: DateTime<Utc>,
+	pub ends_at: DateTime<Utc>,
+	pub leftover_resource: ResourceType,
+	pub leftover_rate: i32,
+	pub objectives: Vec<ObjectiveRequest>,
+	pub offers: Vec<OfferRequest>,
+}
+
+/// An event with its objectives and its shop.
+#[derive(Debug, Clone)]
+pub struct EventDetails {
+	pub event: LimitedEvent,
+	pub objectives: Vec<EventObjective>,
+	pub offers: Vec<EventOffer>,
+}
+
+/// A running event as a player sees it.
+#[derive(Debug, Clone)]
+pub struct PlayerEventView {
+	pub details: EventDetails,
+	/// The player's balance of the event's currency
+	pub balance: i64,
+	/// The player's progress towards the objectives, if they made any
+	pub progress: Vec<PlayerEventObjective>,
+}
+
+/// An offer a player bought.
+#[derive(Debug, Clone)]
+pub struct Purchase {
+	pub offer: EventOffer,
+	/// The player's balance of the event's currency after the purchase
+	pub balance: i64,
+}
+
+/// How closing an event went.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CloseOutcome {
+	/// The leftover currency was converted and the event closed
+	Closed {
+		event: LimitedEvent,
+		/// Players whose leftover currency was converted
+		players: usize,
+		/// Resources granted to them, in total
+		converted: i64,
+	},
+	/// The event was closed before
+	AlreadyClosed,
+	/// The event has not ended yet, it has to be closed once it does
+	NotEnded(LimitedEvent),
+}
+
+/// Creates an event with its objectives and its shop, recording it in the audit log.
+///
+/// Enqueues the job closing the event once it ends.
+#[instrument(skip(conn, job_queue))]
+pub fn create_event(
+	conn: &mut DbConn,
+	job_queue: &JobQueue,
+	request: EventRequest,
+	actor: AdminActor,
+) -> Result<EventDetails> {
+	validate_request(&request, Utc::now())?;
+
+	let details = conn.transaction(|conn| {
+		let name = request.name.trim().to_string();
+		if limited_events::find_by_name(conn, &name)?.is_some() {
+			return Err(Error::from((
+				ErrorKind::InvalidData,
+				"An event with this name already exists",
+			)));
+		}
+		validate_goods(conn, &request.offers)?;
+
+		let event = limited_events::create(
+			conn,
+			NewLimitedEvent {
+				name,
+				description: request.description,
+				currency_name: request.currency_name.trim().to_string(),
+				starts_at: request.starts_at,
+				ends_at: request.ends_at,
+				leftover_resource: request.leftover_resource,
+				leftover_rate: request.leftover_rate,
+			},
+		)?;
+		let objectives = limited_events::create_objectives(
+			conn,
+			request
+				.objectives
+				.into_iter()
+				.map(|objective| NewEventObjective {
+					event_id: event.id,
+					kind: objective.kind,
+					description: objective.description,
+					target: objective.target,
+					reward: objective.reward,
+					max_completions: objective.max_completions,
+				})
+				.collect(),
+		)?;
+		let offers = limited_events::create_offers(
+			conn,
+			request
+				.offers
+				.into_iter()
+				.map(|offer| NewEventOffer {
+					event_id: event.id,
+					price: offer.price,
+					item_id: offer.item_id,
+					unit_id: offer.unit_id,
+					quantity: offer.quantity,
+				})
+				.collect(),
+		)?;
+		audit_log::create(
+			conn,
+			NewAuditEntry {
+				action: CREATE_EVENT_ACTION.to_string(),
+				subject: Some(event.id.to_string()),
+				details: json!({
+					"name": event.name,
+					"starts_at": event.starts_at,
+					"ends_at": event.ends_at,
+					"objectives": objectives.len(),
+					"offers": offers.len(),
+				}),
+				operator: actor.operator,
+				request_id: actor.request_id,
+				player_id: None,
+			},
+		)?;
+		Ok::<_, Error>(EventDetails {
+			event,
+			objectives,
+			offers,
+		})
+	})?;
+
+	let job_id = schedule_close(job_queue, &details.event)?;
+	info!(
+		"Created event {} ending at {}, closed by job {}",
+		details.event.name, details.event.ends_at, job_id
+	);
+	Ok(details)
+}
+
+fn validate_request(request: &EventRequest, now: DateTime<Utc>) -> Result<()> {
+	let invalid = |desc: &'static str| Err(Error::from((ErrorKind::InvalidData, desc)));
+	if request.name.trim().is_empty() || request.currency_name.trim().is_empty() {
+		return invalid("Event and currency names are required");
+	}
+	if request.ends_at <= request.starts_at || request.ends_at <= now {
+		return invalid("Event must end after it starts and in the future");
+	}
+	if request.leftover_resource == ResourceType::Population || request.leftover_rate < 0 {
+		return invalid("Leftover currency must convert to a storable resource");
+	}
+	if request.objectives.is_empty() {
+		return invalid("Event needs at least one objective");
+	}
+	if request.objectives.iter().any(|objective| {
+		objective.target <= 0
+			|| objective.reward <= 0
+			|| objective.max_completions.is_some_and(|max| max <= 0)
+	}) {
+		return invalid("Objective targets, rewards and maximum completions must be positive");
+	}
+	if request.offers.iter().any(|offer| {
+		offer.price <= 0
+			|| offer.quantity <= 0
+			|| offer.item_id.is_some() == offer.unit_id.is_some()
+	}) {
+		return invalid("Offers sell a positive quantity of either an item or a unit for a price");
+	}
+	Ok(())
+}
+
+/// Fails unless every item and unit the offers sell exists.
+fn validate_goods(conn: &mut DbConn, offers: &[OfferRequest]) -> Result<()> {
+	let item_ids: HashSet<ItemKey> = offers.iter().filter_map(|offer| offer.item_id).collect();
+	let unit_ids: HashSet<UnitKey> = offers.iter().filter_map(|offer| offer.unit_id).collect();
+	let item_ids: Vec<ItemKey> = item_ids.into_iter().collect();
+	let unit_ids: Vec<UnitKey> = unit_ids.into_iter().collect();
+	if items::get_all_by_id(conn, &item_ids)?.len() != item_ids.len()
+		|| units::get_all_by_id(conn, &unit_ids)?.len() != unit_ids.len()
+	{
+		return Err(Error::from((
+			ErrorKind::InvalidData,
+			"Offered item or unit not found",
+		)));
+	}
+	Ok(())
+}
+
+/// Enqueues the job closing an event once it ends.
+pub fn schedule_close(job_queue: &JobQueue, event: &LimitedEvent) -> Result<JobKey> {
+	job_queue.enqueue(
+		JobType::LimitedEvent,
+		LimitedEventJobPayload { event_id: event.id },
+		JobPriority::Normal,
+		event.ends_at,
+	)
+}
+
+/// Lists the events running at `now` with the player's balance and progress.
+#[instrument(skip(conn))]
+pub fn list_running(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	now: DateTime<Utc>,
+) -> Result<Vec<PlayerEventView>> {
+	limited_events::get_running(conn, now)?
+		.into_iter()
+		.map(|event| {
+			let objectives = limited_events::get_objectives(conn, &event.id)?;
+			let offers = limited_events::get_offers(conn, &event.id)?;
+			let objective_ids: Vec<_> = objectives.iter().map(|objective| objective.id).collect();
+			Ok(PlayerEventView {
+				balance: player_event_progress::get_balance(conn, player_id, &event.id)?,
+				progress: player_event_progress::get_progress(conn, player_id, &objective_ids)?,
+				details: EventDetails {
+					event,
+					objectives,
+					offers,
+				},
+			})
+		})
+		.collect()
+}
+
+/// Counts a game event towards the objectives of the running events.
+///
+/// # Returns
+/// The event currency the player earned with it, across all events
+#[instrument(skip(conn))]
+pub fn record_progress(conn: &mut DbConn, event: &GameEvent, now: DateTime<Utc>) -> Result<i64> {
+	let Some((kind, amount)) = EventObjectiveKind::progress_of(event) else {
+		return Ok(0);
+	};
+	if amount <= 0 {
+		return Ok(0);
+	}
+	let player_id = event.player_id();
+
+	conn.transaction(|conn| {
+		let mut earned = 0;
+		for objective in limited_events::get_running_objectives(conn, kind, now)? {
+			// The upsert locks the progress, so concurrent events never reward a completion twice
+			let progress =
+				player_event_progress::add_progress(conn, player_id, &objective.id, amount)?;
+			let completions = objective.completions(progress.progress);
+			if completions <= progress.completions {
+				continue;
+			}
+			player_event_progress::set_completions(conn, player_id, &objective.id, completions)?;
+			let reward = i64::from(completions - progress.completions) * objective.reward;
+			player_event_progress::add_currency(conn, player_id, &objective.event_id, reward)?;
+			trace!(
+				"Player {} completed objective {} {} times",
+				player_id, objective.id, completions
+			);
+			earned += reward;
+		}
+		if earned > 0 {
+			debug!("Player {} earned {} event currency", player_id, earned);
+		}
+		Ok(earned)
+	})
+}
+
+/// Buys an offer of a running event's shop for a player.
+///
+/// Fails with [`ErrorKind::EventNotRunningError`] outside the event, and with
+/// [`ErrorKind::InsufficientEventCurrencyError`] if the player cannot afford the offer.
+#[instrument(skip(conn))]
+pub fn purchase_offer(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	event_id: &LimitedEventKey,
+	offer_id: &EventOfferKey,
+) -> Result<Purchase> {
+	let purchase = conn.transaction(|conn| {
+		let event = limited_events::find_by_id(conn, event_id)?
+			.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Event not found")))?;
+		if !event.is_running(Utc::now()) {
+			return Err(Error::from((
+				ErrorKind::EventNotRunningError,
+				"Event is not running",
+			)));
+		}
+		let offer = limited_events::find_offer(conn, event_id, offer_id)?
+			.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Offer not found")))?;
+		let currency =
+			player_event_progress::spend_currency(conn, player_id, event_id, offer.price)?
+				.ok_or_else(|| {
+					Error::from((
+						ErrorKind::InsufficientEventCurrencyError,
+						"Not enough event currency",
+					))
+				})?;
+		if let Some(item_id) = offer.item_id {
+			player_items::add_items(conn, player_id, &item_id, offer.quantity)?;
+		}
+		if let Some(unit_id) = offer.unit_id {
+			player_units::add_units(conn, player_id, &unit_id, offer.quantity)?;
+		}
+		Ok(Purchase {
+			offer,
+			balance: currency.balance,
+		})
+	})?;
+
+	info!(
+		"Player {} bought offer {} of event {}, {} currency left",
+		player_id, offer_id, event_id, purchase.balance
+	);
+	Ok(purchase)
+}
+
+/// Closes an event that ended, converting the currency players have left into resources.
+#[instrument(skip(conn))]
+pub fn close_event(
+	conn: &mut DbConn,
+	event_id: &LimitedEventKey,
+	now: DateTime<Utc>,
+) -> Result<CloseOutcome> {
+	conn.transaction(|conn| {
+		let event = limited_events::lock_by_id(conn, event_id)?;
+		if event.closed_at.is_some() {
+			return Ok(CloseOutcome::AlreadyClosed);
+		}
+		if event.ends_at > now {
+			return Ok(CloseOutcome::NotEnded(event));
+		}
+
+		let leftovers = player_event_progress::lock_leftovers(conn, event_id)?;
+		let mut converted = 0;
+		for leftover in &leftovers {
+			let amount = leftover.balance.saturating_mul(event.leftover_rate.into());
+			let stored = resources::lock_by_player_id(conn, &leftover.player_id)?;
+			let accepted = amount.min(free_storage(&stored, event.leftover_resource));
+			if accepted > 0 {
+				resources::add(
+					conn,
+					&leftover.player_id,
+					&delta_of(event.leftover_resource, accepted),
+				)?;
+			}
+			player_event_progress::clear_balance(conn, &leftover.player_id, event_id)?;
+			converted += accepted;
+		}
+		let event = limited_events::close(conn, event_id, now)?;
+
+		info!(
+			"Closed event {}, converted the leftovers of {} players into {} {}",
+			event.name,
+			leftovers.len(),
+			converted,
+			event.leftover_resource.as_str()
+		);
+		Ok(CloseOutcome::Closed {
+			event,
+			players: leftovers.len(),
+			converted,
+		})
+	})
+}
+
+/// Returns how much more of `resource` fits into the player's storage.
+fn free_storage(res: &PlayerResource, resource: ResourceType) -> i64 {
+	let (stored, cap) = match resource {
+		ResourceType::Food => (res.food, res.food_cap),
+		ResourceType::Wood => (res.wood, res.wood_cap),
+		ResourceType::Stone => (res.stone, res.stone_cap),
+		ResourceType::Gold => (res.gold, res.gold_cap),
+		ResourceType::Population => (0, 0),
+	};
+	(cap - stored).max(0)
+}
+
+/// Returns the resource delta of `amount` units of `resource`.
+fn delta_of(resource: ResourceType, amount: i64) -> ResourceDelta {
+	match resource {
+		ResourceType::Food => (amount, 0, 0, 0),
+		ResourceType::Wood => (0, amount, 0, 0),
+		ResourceType::Stone => (0, 0, amount, 0),
+		ResourceType::Gold => (0, 0, 0, amount),
+		ResourceType::Population => (0, 0, 0, 0),
+	}
+}
@@ -0,0 +1,81 @@
+//! Limited event job processor.
+//!
+//! This module implements the job processing functionality for limited events, closing each
+//! event once it ends.
+
+use axum::extract::FromRef;
+use chrono::Utc;
+use tracing::{debug, info, instrument, trace};
+
+use crate::Error;
+use crate::domain::app_state::{AppPool, AppQueue, AppState};
+use crate::domain::jobs::{Job, JobType};
+use crate::domain::limited_event::LimitedEventJobPayload;
+use crate::game::limited_events::event_operations::{self, CloseOutcome};
+use crate::job_queue::job_processor::{JobHandler, JobOutcome, JobWorker};
+
+/// A handler for [`JobType::LimitedEvent`] jobs.
+///
+/// Each job closes an event, converting the currency players have left. An event that did
+/// not end yet, e.g. because the job was started early through the admin API, gets a new
+/// job at its end.
+pub struct LimitedEventHandler {
+	/// Database connection pool
+	pool: AppPool,
+	/// Job queue events that did not end yet are queued on again
+	job_queue: AppQueue,
+}
+
+/// Workers running the [`LimitedEventHandler`].
+pub type LimitedEventProcessor = JobWorker<LimitedEventHandler>;
+
+impl JobHandler for LimitedEventHandler {
+	const JOB_TYPE: JobType = JobType::LimitedEvent;
+	const WORKER_NAME: &'static str = "event-goblin";
+
+	/// Creates a new `LimitedEventHandler` with what it needs from the application state.
+	fn new(app_state: &AppState) -> Self {
+		Self {
+			pool: AppPool::from_ref(app_state),
+			job_queue: AppQueue::from_ref(app_state),
+		}
+	}
+
+	#[instrument(skip(self, job), fields(job_id = %job.id))]
+	async fn handle(&self, job: Job) -> Result<JobOutcome, Error> {
+		debug!("Processing limited event job: {}", job.id);
+		trace!("Job details: {:?}", job);
+
+		let LimitedEventJobPayload { event_id } = serde_json::from_value(job.payload.clone())?;
+		let outcome = {
+			let mut conn = self.pool.get()?;
+			event_operations::close_event(&mut conn, &event_id, Utc::now())?
+		};
+
+		let result = match outcome {
+			CloseOutcome::Closed {
+				event,
+				players,
+				converted,
+			} => {
+				info!("Event {} is over", event.name);
+				serde_json::json!({ "closed": true, "players": players, "converted": converted })
+			}
+			CloseOutcome::AlreadyClosed => {
+				debug!("Event {} was already closed", event_id);
+				serde_json::json!({ "closed": false, "skipped": true })
+			}
+			CloseOutcome::NotEnded(event) => {
+				let next_job = event_operations::schedule_close(&self.job_queue, &event)?;
+				debug!(
+					"Event {} has not ended, closing it in job {}",
+					event.id, next_job
+				);
+				serde_json::json!({ "closed": false, "rescheduled": next_job })
+			}
+		};
+
+		debug!("Completed processing limited event job: {}", job.id);
+		Ok(Some(result))
+	}
+}
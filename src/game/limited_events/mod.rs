@@ -0,0 +1,10 @@
+//! Time-limited events for the Empire game.
+//!
+//! This module runs events during which players earn a dedicated currency by completing
+//! objectives and spend it in the event's shop. A listener on the event bus counts the
+//! players' progress, and a background job closes each event once it ends, converting the
+//! currency players have left into resources.
+
+pub mod event_listener;
+pub mod event_operations;
+pub mod event_processor;
@@ -0,0 +1,325 @@
+//! Sending, reading and deleting in-game mail.
+//!
+//! Players write to each other with [`send`], and the server delivers messages about
+//! what happened to a player with [`deliver`], like the battles they fought, the
+//! trainings that finished and the scouts they sent or spotted. Server messages have no sender and carry what they are about
+//! in their details, so clients can link to the battle report or the trained units.
+//!
+//! Every message lives in the inbox of its single recipient, who marks it as read and
+//! deletes it without affecting anyone else.
+
+use chrono::Utc;
+use serde_json::{Value, json};
+use tracing::{debug, info, instrument};
+
+use crate::db::{DbConn, messages, players, units};
+use crate::domain::combat::{BattleReport, IntelReport};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::message::{Message, MessageKey, MessageKind, NewMessage};
+use crate::domain::player::PlayerKey;
+use crate::domain::unit::training::TrainingQueueEntry;
+use crate::game::friends::friend_operations;
+
+/// Longest subject of a message, in characters.
+pub const SUBJECT_MAX_LENGTH: usize = 100;
+
+/// Longest body of a message, in characters.
+pub const BODY_MAX_LENGTH: usize = 5000;
+
+/// Default number of messages returned per page.
+pub const DEFAULT_PAGE_SIZE: i64 = 20;
+
+/// Upper bound for the number of messages returned per page.
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+/// A single page of a player's inbox.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessagePage {
+	pub messages: Vec<Message>,
+	pub page: i64,
+	pub per_page: i64,
+	/// Total number of messages matching the listing
+	pub total: i64,
+	/// Number of unread messages in the whole inbox
+	pub unread: i64,
+}
+
+fn message_not_found() -> Error {
+	Error::from((ErrorKind::NotFoundError, "Message not found"))
+}
+
+/// Trims the subject and the body and checks their length.
+fn validate_message(subject: &str, body: &str) -> Result<(String, String)> {
+	let subject = subject.trim();
+	if subject.is_empty() || subject.chars().count() > SUBJECT_MAX_LENGTH {
+		return Err(Error::from((
+			ErrorKind::InvalidMessage,
+			"Subjects are 1 to 100 characters long",
+		)));
+	}
+	let body = body.trim();
+	if body.is_empty() || body.chars().count() > BODY_MAX_LENGTH {
+		return Err(Error::from((
+			ErrorKind::InvalidMessage,
+			"Messages are 1 to 5000 characters long",
+		)));
+	}
+	Ok((subject.to_string(), body.to_string()))
+}
+
+/// Sends a message from one player to another.
+///
+/// # Errors
+/// - `InvalidMessage` if the subject or the body is empty or too long, or the player
+///   writes to themselves
+/// - `NotFoundError` if the recipient does not exist
+/// - `PlayerBlockedError` if either player blocked the other
+#[instrument(skip(conn, subject, body))]
+pub fn send(
+	conn: &mut DbConn,
+	sender_id: &PlayerKey,
+	recipient_id: &PlayerKey,
+	subject: &str,
+	body: &str,
+) -> Result<Message> {
+	let (subject, body) = validate_message(subject, body)?;
+	if sender_id == recipient_id {
+		return Err(Error::from((
+			ErrorKind::InvalidMessage,
+			"Cannot send mail to yourself",
+		)));
+	}
+	if players::find_by_id(conn, recipient_id)?.is_none() {
+		return Err(Error::from((ErrorKind::NotFoundError, "Player not found")));
+	}
+	if friend_operations::is_blocked(conn, sender_id, recipient_id)? {
+		return Err(Error::from((
+			ErrorKind::PlayerBlockedError,
+			"Cannot send mail to this player",
+		)));
+	}
+
+	let message = messages::create(
+		conn,
+		NewMessage {
+			recipient_id: *recipient_id,
+			sender_id: Some(*sender_id),
+			kind: MessageKind::Player,
+			subject,
+			body,
+			details: json!({}),
+		},
+	)?;
+	info!(
+		"Player {} sent message {} to player {}",
+		sender_id, message.id, recipient_id
+	);
+	Ok(message)
+}
+
+/// Delivers a message from the server to a player.
+#[instrument(skip(conn, subject, body, details))]
+pub fn deliver(
+	conn: &mut DbConn,
+	recipient_id: &PlayerKey,
+	kind: MessageKind,
+	subject: impl Into<String>,
+	body: impl Into<String>,
+	details: Value,
+) -> Result<Message> {
+	messages::create(
+		conn,
+		NewMessage {
+			recipient_id: *recipient_id,
+			sender_id: None,
+			kind,
+			subject: subject.into(),
+			body: body.into(),
+			details,
+		},
+	)
+}
+
+/// Tells the attacker and the defender of a battle how it went.
+#[instrument(skip(conn, report), fields(report_id = %report.id))]
+pub fn notify_battle(conn: &mut DbConn, report: &BattleReport) -> Result<()> {
+	let attacker = players::get_by_id(conn, &report.attacker_id)?;
+	let defender = players::get_by_id(conn, &report.defender_id)?;
+	let details = json!({ "report_id": report.id });
+	let outcome = |player_id: &PlayerKey| match report.winner_id {
+		Some(winner) if winner == *player_id => "won",
+		Some(_) => "lost",
+		None => "drew",
+	};
+
+	deliver(
+		conn,
+		&attacker.id,
+		MessageKind::BattleReport,
+		format!("Battle against {}", defender.name),
+		format!(
+			"You {} your attack on {}. Read the battle report for your losses and the loot.",
+			outcome(&attacker.id),
+			defender.name
+		),
+		details.clone(),
+	)?;
+	deliver(
+		conn,
+		&defender.id,
+		MessageKind::BattleReport,
+		format!("Attacked by {}", attacker.name),
+		format!(
+			"You {} the defence of your city against {}. Read the battle report for your losses and the loot.",
+			outcome(&defender.id),
+			attacker.name
+		),
+		details,
+	)?;
+	debug!("Delivered battle report {} by mail", report.id);
+	Ok(())
+}
+
+/// Tells the attacker of a scout mission that the intel report is ready.
+#[instrument(skip(conn, report), fields(report_id = %report.id))]
+pub fn notify_intel(conn: &mut DbConn, report: &IntelReport) -> Result<Message> {
+	let defender = players::get_by_id(conn, &report.defender_id)?;
+	let spotted = if report.spotted {
+		" The Watchtower of the city spotted them."
+	} else {
+		""
+	};
+	deliver(
+		conn,
+		&report.attacker_id,
+		MessageKind::IntelReport,
+		format!("Intel on {}", defender.name),
+		format!(
+			"Your scouts are back from {}.{} Read the intel report for their resources and army.",
+			defender.name, spotted
+		),
+		json!({ "report_id": report.id }),
+	)
+}
+
+/// Tells the defender of a scout mission that their Watchtower spotted the scouts.
+#[instrument(skip(conn, report), fields(report_id = %report.id))]
+pub fn notify_scouts_spotted(conn: &mut DbConn, report: &IntelReport) -> Result<Message> {
+	let attacker = players::get_by_id(conn, &report.attacker_id)?;
+	deliver(
+		conn,
+		&report.defender_id,
+		MessageKind::ScoutsSpotted,
+		format!("Scouts of {} spotted", attacker.name),
+		format!(
+			"Your Watchtower spotted scouts of {} spying on your city.",
+			attacker.name
+		),
+		json!({ "player_id": attacker.id }),
+	)
+}
+
+/// Tells a player that their training finished.
+#[instrument(skip(conn, entry), fields(training_id = %entry.id))]
+pub fn notify_training(conn: &mut DbConn, entry: &TrainingQueueEntry) -> Result<Message> {
+	let unit = units::get_by_id(conn, &entry.unit_id)?;
+	deliver(
+		conn,
+		&entry.player_id,
+		MessageKind::TrainingComplete,
+		"Training complete",
+		format!("{} {} finished training.", entry.quantity, unit.name),
+		json!({
+			"training_id": entry.id,
+			"unit_id": entry.unit_id,
+			"quantity": entry.quantity,
+		}),
+	)
+}
+
+/// Lists a player's inbox, newest first, only the unread messages if `unread_only` is set.
+///
+/// Pages are 1-based. Missing values default to the first page of
+/// [`DEFAULT_PAGE_SIZE`] messages, and page sizes are capped at [`MAX_PAGE_SIZE`].
+#[instrument(skip(conn))]
+pub fn list_inbox(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	unread_only: bool,
+	page: Option<i64>,
+	per_page: Option<i64>,
+) -> Result<MessagePage> {
+	let page = page.unwrap_or(1).max(1);
+	let per_page = per_page
+		.unwrap_or(DEFAULT_PAGE_SIZE)
+		.clamp(1, MAX_PAGE_SIZE);
+	let offset = (page - 1).saturating_mul(per_page);
+
+	let (messages, total) =
+		messages::get_page_for_recipient(conn, player_id, unread_only, per_page, offset)?;
+	let unread = messages::count_unread(conn, player_id)?;
+	debug!(
+		"Loaded {} of {} messages for player {}",
+		messages.len(),
+		total,
+		player_id
+	);
+	Ok(MessagePage {
+		messages,
+		page,
+		per_page,
+		total,
+		unread,
+	})
+}
+
+/// Counts the unread messages in a player's inbox.
+#[instrument(skip(conn))]
+pub fn unread_count(conn: &mut DbConn, player_id: &PlayerKey) -> Result<i64> {
+	messages::count_unread(conn, player_id)
+}
+
+/// Retrieves a message from a player's inbox.
+///
+/// Messages of other players are reported as not found.
+#[instrument(skip(conn))]
+pub fn get_message(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	message_id: &MessageKey,
+) -> Result<Message> {
+	messages::find_for_recipient(conn, player_id, message_id)?.ok_or_else(message_not_found)
+}
+
+/// Marks a message in a player's inbox as read, keeping when it was first read.
+#[instrument(skip(conn))]
+pub fn mark_read(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	message_id: &MessageKey,
+) -> Result<Message> {
+	messages::mark_read(conn, player_id, message_id, Utc::now())?.ok_or_else(message_not_found)
+}
+
+/// Marks every message in a player's inbox as read.
+///
+/// # Returns
+/// The number of messages that were unread
+#[instrument(skip(conn))]
+pub fn mark_all_read(conn: &mut DbConn, player_id: &PlayerKey) -> Result<usize> {
+	messages::mark_all_read(conn, player_id, Utc::now())
+}
+
+/// Deletes a message from a player's inbox.
+#[instrument(skip(conn))]
+pub fn delete_message(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	message_id: &MessageKey,
+) -> Result<()> {
+	if messages::delete(conn, player_id, message_id)? == 0 {
+		return Err(message_not_found());
+	}
+	debug!("Player {} deleted message {}", player_id, message_id);
+	Ok(())
+}
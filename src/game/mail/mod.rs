@@ -0,0 +1,6 @@
+//! Mail operations for the Empire game.
+//!
+//! This module lets players write to each other, and the server deliver messages about
+//! battles and finished trainings, to inboxes the players read and clean up themselves.
+
+pub mod mail_operations;
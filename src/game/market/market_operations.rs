@@ -0,0 +1,265 @@
+//! Order placement, matching and cancellation for the player market.
+//!
+//! Orders are limit orders priced in gold per unit. Placing an order takes whatever it
+//! could spend into escrow: a sell order holds the resource it offers, a buy order holds
+//! `quantity * price` gold. The order is then matched against the opposite side of the
+//! book with price-time priority, and every fill trades at the price of the order that was
+//! already resting. A buyer whose limit was above that price gets the difference back.
+//! Whatever is not filled rests in the book until it is matched or cancelled, and
+//! cancelling refunds the unfilled remainder.
+//!
+//! Placement, matching and settlement run in a single transaction, so resources never
+//! leave one player without arriving at the other.
+
+use diesel::Connection;
+use tracing::{debug, info, instrument, trace};
+
+use crate::db::resources::ResourceDelta;
+use crate::db::{DbConn, market_orders, market_trades, resources};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::market::{
+	MarketOrder, MarketOrderKey, MarketOrderSide, MarketOrderStatus, MarketTrade, NewMarketOrder,
+	NewMarketTrade, PriceLevel, TRADABLE_RESOURCES,
+};
+use crate::domain::player::PlayerKey;
+use crate::domain::player::resource::{PlayerResource, ResourceType};
+
+/// Maximum number of orders returned when listing a player's orders.
+pub const ORDER_HISTORY_LIMIT: i64 = 100;
+
+/// Maximum number of price levels returned per side of the order book.
+pub const BOOK_DEPTH: i64 = 20;
+
+/// Both sides of a resource's order book.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderBook {
+	pub resource: ResourceType,
+	/// Buy orders per price, highest price first
+	pub bids: Vec<PriceLevel>,
+	/// Sell orders per price, lowest price first
+	pub asks: Vec<PriceLevel>,
+}
+
+/// Returns the resource delta of `amount` units of `resource`.
+fn delta_of(resource: ResourceType, amount: i64) -> ResourceDelta {
+	match resource {
+		ResourceType::Food => (amount, 0, 0, 0),
+		ResourceType::Wood => (0, amount, 0, 0),
+		ResourceType::Stone => (0, 0, amount, 0),
+		ResourceType::Gold => (0, 0, 0, amount),
+		ResourceType::Population => (0, 0, 0, 0),
+	}
+}
+
+/// Returns how much of `resource` the player currently holds.
+fn available(res: &PlayerResource, resource: ResourceType) -> i64 {
+	match resource {
+		ResourceType::Food => res.food,
+		ResourceType::Wood => res.wood,
+		ResourceType::Stone => res.stone,
+		ResourceType::Gold => res.gold,
+		ResourceType::Population => 0,
+	}
+}
+
+/// Returns the resource and amount an open order holds in escrow for `remaining` units.
+fn escrow_of(
+	side: MarketOrderSide,
+	resource: ResourceType,
+	price: i64,
+	remaining: i64,
+) -> (ResourceType, i64) {
+	match side {
+		MarketOrderSide::Sell => (resource, remaining),
+		MarketOrderSide::Buy => (ResourceType::Gold, remaining * price),
+	}
+}
+
+fn validate_resource(resource: ResourceType) -> Result<()> {
+	if TRADABLE_RESOURCES.contains(&resource) {
+		Ok(())
+	} else {
+		Err(Error::from((
+			ErrorKind::InvalidMarketResourceError,
+			"Only food, wood and stone can be traded for gold",
+		)))
+	}
+}
+
+/// Places a limit order and matches it against the order book.
+///
+/// # Returns
+/// The order as it was left after matching, and the trades it was filled by
+#[instrument(skip(conn))]
+pub fn place_order(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	side: MarketOrderSide,
+	resource: ResourceType,
+	quantity: i64,
+	price: i64,
+) -> Result<(MarketOrder, Vec<MarketTrade>)> {
+	validate_resource(resource)?;
+	if quantity <= 0 || price <= 0 {
+		return Err(Error::from((
+			ErrorKind::InvalidQuantityError,
+			"Quantity and price must be positive",
+		)));
+	}
+	if quantity.checked_mul(price).is_none() {
+		return Err(Error::from((
+			ErrorKind::InvalidQuantityError,
+			"Order value is too large",
+		)));
+	}
+	let (escrow_resource, escrow) = escrow_of(side, resource, price, quantity);
+
+	conn.transaction(|conn| {
+		market_orders::lock_market(conn)?;
+
+		let held = resources::lock_by_player_id(conn, player_id)?;
+		if available(&held, escrow_resource) < escrow {
+			return Err(Error::from((
+				ErrorKind::InsufficientResourcesError,
+				"Not enough resources",
+			)));
+		}
+		resources::deduct(conn, player_id, &delta_of(escrow_resource, escrow))?;
+		trace!("Escrowed {} {:?}", escrow, escrow_resource);
+
+		let order = market_orders::create(
+			conn,
+			NewMarketOrder {
+				player_id: *player_id,
+				side,
+				resource,
+				quantity,
+				remaining: quantity,
+				price,
+			},
+		)?;
+		match_order(conn, order)
+	})
+}
+
+/// Fills an incoming order from the resting orders it crosses, best price first.
+fn match_order(
+	conn: &mut DbConn,
+	mut order: MarketOrder,
+) -> Result<(MarketOrder, Vec<MarketTrade>)> {
+	let resting_orders = market_orders::lock_crossing(
+		conn,
+		&order.player_id,
+		order.resource,
+		order.side.opposite(),
+		order.price,
+	)?;
+
+	let mut trades = Vec::new();
+	for resting in resting_orders {
+		if order.remaining == 0 {
+			break;
+		}
+		let filled = order.remaining.min(resting.remaining);
+		let price = resting.price;
+		let (buy, sell) = match order.side {
+			MarketOrderSide::Buy => (&order, &resting),
+			MarketOrderSide::Sell => (&resting, &order),
+		};
+		trace!(
+			"Filling {} {:?} at {} between {} and {}",
+			filled, order.resource, price, buy.id, sell.id
+		);
+
+		// The seller's resource and the buyer's gold are already in escrow
+		resources::add(conn, &buy.player_id, &delta_of(order.resource, filled))?;
+		resources::add(
+			conn,
+			&sell.player_id,
+			&delta_of(ResourceType::Gold, filled * price),
+		)?;
+		if buy.price > price {
+			resources::add(
+				conn,
+				&buy.player_id,
+				&delta_of(ResourceType::Gold, filled * (buy.price - price)),
+			)?;
+		}
+
+		trades.push(market_trades::create(
+			conn,
+			NewMarketTrade {
+				resource: order.resource,
+				buy_order_id: buy.id,
+				sell_order_id: sell.id,
+				buyer_id: buy.player_id,
+				seller_id: sell.player_id,
+				quantity: filled,
+				price,
+			},
+		)?);
+		market_orders::set_remaining(conn, &resting.id, resting.remaining - filled)?;
+		order = market_orders::set_remaining(conn, &order.id, order.remaining - filled)?;
+	}
+
+	if !trades.is_empty() {
+		info!(
+			"Order {} was filled by {} trades, {} of {} remaining",
+			order.id,
+			trades.len(),
+			order.remaining,
+			order.quantity
+		);
+	}
+	Ok((order, trades))
+}
+
+/// Cancels an open order of the player and refunds its unfilled remainder.
+#[instrument(skip(conn))]
+pub fn cancel_order(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	order_id: &MarketOrderKey,
+) -> Result<MarketOrder> {
+	conn.transaction(|conn| {
+		market_orders::lock_market(conn)?;
+
+		let order = market_orders::lock_owned(conn, player_id, order_id)?
+			.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Order not found")))?;
+		if order.status != MarketOrderStatus::Open {
+			return Err(Error::from((
+				ErrorKind::MarketOrderClosedError,
+				"Order is no longer open",
+			)));
+		}
+
+		let (refund_resource, refund) =
+			escrow_of(order.side, order.resource, order.price, order.remaining);
+		resources::add(conn, player_id, &delta_of(refund_resource, refund))?;
+		debug!("Refunded {} {:?}", refund, refund_resource);
+
+		let order = market_orders::cancel(conn, &order.id)?;
+		info!(
+			"Cancelled market order {} for player {}",
+			order.id, player_id
+		);
+		Ok(order)
+	})
+}
+
+/// Lists the player's most recent orders, newest first.
+#[instrument(skip(conn))]
+pub fn list_orders(conn: &mut DbConn, player_id: &PlayerKey) -> Result<Vec<MarketOrder>> {
+	market_orders::get_for_player(conn, player_id, ORDER_HISTORY_LIMIT)
+}
+
+/// Returns the open orders of a resource's book, aggregated per price.
+#[instrument(skip(conn))]
+pub fn get_order_book(conn: &mut DbConn, resource: ResourceType) -> Result<OrderBook> {
+	validate_resource(resource)?;
+	Ok(OrderBook {
+		resource,
+		bids: market_orders::get_book_side(conn, resource, MarketOrderSide::Buy, BOOK_DEPTH)?,
+		asks: market_orders::get_book_side(conn, resource, MarketOrderSide::Sell, BOOK_DEPTH)?,
+	})
+}
@@ -0,0 +1,7 @@
+//! Market operations for the Empire game.
+//!
+//! This module lets players trade food, wood and stone for gold through limit orders,
+//! matches incoming orders against the order book, and settles every fill by moving
+//! resources and gold between the players involved.
+
+pub mod market_operations;
@@ -1,9 +1,34 @@
+pub mod account_deletion;
+pub mod activity_operations;
+pub mod admin_operations;
+pub mod alliances;
+pub mod audit_operations;
 pub mod buildings;
+pub mod chat;
+pub mod combat;
+pub mod consistency_operations;
 pub mod exp;
+pub mod factions;
+pub mod friends;
+pub mod heroes;
+pub mod items;
+pub mod leaderboard;
+pub mod limited_events;
+pub mod mail;
+pub mod market;
 pub mod modifiers;
+pub mod observer_operations;
+pub mod onboarding_operations;
 pub mod player_operations;
+pub mod quests;
 pub mod resources;
+pub mod seasons;
+pub mod settlements;
+#[cfg(feature = "simulation")]
+pub mod simulation;
+pub mod table_stats;
 pub mod units;
+pub mod world;
 
 /// Server tick rate in seconds
 pub static TICK_RATE: i32 = 60;
@@ -1,10 +1,9 @@
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::time::Instant;
 
 use bigdecimal::BigDecimal;
 use chrono::{DateTime, Utc};
-use tokio::sync::RwLock;
 use tracing::{debug, info, instrument, trace, warn};
 use uuid::Uuid;
 
@@ -43,6 +42,11 @@ impl std::fmt::Display for CacheKey {
 	}
 }
 
+/// Caches the player-wide multipliers calculated by
+/// [`calc_multiplier`](crate::game::modifiers::modifier_operations::calc_multiplier).
+///
+/// Multipliers are looked up from synchronous operations, so the entries sit behind a
+/// blocking lock that is never held across an `.await`.
 pub struct ModifierCache {
 	/// Main cache storage using RwLock for concurrent access
 	cache: Arc<RwLock<HashMap<CacheKey, CacheEntry>>>,
@@ -77,10 +81,10 @@ impl ModifierCache {
 
 	/// Get a cached modifier value if it exists and is valid
 	#[instrument(name = "cache_get", skip_all, fields(key = %key))]
-	pub async fn get(&self, key: &CacheKey) -> Option<CacheEntry> {
+	pub fn get(&self, key: &CacheKey) -> Option<CacheEntry> {
 		trace!("Retrieving cache entry");
 
-		let cache = self.cache.read().await;
+		let cache = self.cache.read().expect("modifier cache poisoned");
 		let entry = cache.get(key);
 
 		if entry.is_none() {
@@ -106,8 +110,11 @@ impl ModifierCache {
 	}
 
 	/// Set a new cache entry with optional expiration
+	///
+	/// Entries expire after the default TTL at the latest, so a missed invalidation does not
+	/// serve a stale multiplier for long.
 	#[instrument(name = "cache_set", skip_all, fields(key = %key))]
-	pub async fn set(
+	pub fn set(
 		&self,
 		key: CacheKey,
 		total_multiplier: BigDecimal,
@@ -118,7 +125,7 @@ impl ModifierCache {
 			total_multiplier, expires_at
 		);
 		let start = Instant::now();
-		let mut cache = self.cache.write().await;
+		let mut cache = self.cache.write().expect("modifier cache poisoned");
 
 		// Check player entry limit
 		let user_entries = cache
@@ -139,9 +146,10 @@ impl ModifierCache {
 			));
 		}
 
+		let ttl_end = Utc::now() + self.default_ttl;
 		let entry = CacheEntry {
 			total_multiplier,
-			expires_at,
+			expires_at: Some(expires_at.map_or(ttl_end, |expires_at| expires_at.min(ttl_end))),
 			version: 0,
 			last_updated: Utc::now(),
 		};
@@ -153,7 +161,7 @@ impl ModifierCache {
 
 	/// Update an existing cache entry with optimistic locking
 	#[instrument(name = "cache_update", skip_all, fields(key = %key))]
-	pub async fn update(
+	pub fn update(
 		&self,
 		key: &CacheKey,
 		total_multiplier: BigDecimal,
@@ -162,7 +170,7 @@ impl ModifierCache {
 	) -> Result<(), Error> {
 		trace!("Updating cache entry with version {}", expected_version);
 		let start = Instant::now();
-		let mut cache = self.cache.write().await;
+		let mut cache = self.cache.write().expect("modifier cache poisoned");
 
 		if let Some(entry) = cache.get(key) {
 			if entry.version != expected_version {
@@ -201,9 +209,9 @@ impl ModifierCache {
 
 	/// Invalidate a specific cache entry
 	#[instrument(name = "cache_invalidate", skip_all, fields(key = %key))]
-	pub async fn invalidate(&self, key: &CacheKey) {
+	pub fn invalidate(&self, key: &CacheKey) {
 		debug!("Invalidating cache entry");
-		let mut cache = self.cache.write().await;
+		let mut cache = self.cache.write().expect("modifier cache poisoned");
 		if cache.remove(key).is_some() {
 			debug!("Cache entry invalidated");
 		} else {
@@ -213,20 +221,30 @@ impl ModifierCache {
 
 	/// Invalidate all entries for a player
 	#[instrument(skip(self), fields(player_id = %player_id))]
-	pub async fn invalidate_user(&self, player_id: Uuid) {
+	pub fn invalidate_user(&self, player_id: Uuid) {
 		debug!("Invalidating all cache entries for player");
-		let mut cache = self.cache.write().await;
+		let mut cache = self.cache.write().expect("modifier cache poisoned");
 		let before_count = cache.len();
 		cache.retain(|k, _| k.player_id != player_id);
 		let removed = before_count - cache.len();
 		info!("Invalidated {} cache entries for player", removed);
 	}
 
+	/// Invalidate the entries of every player
+	#[instrument(skip(self))]
+	pub fn clear(&self) {
+		debug!("Invalidating all cache entries");
+		let mut cache = self.cache.write().expect("modifier cache poisoned");
+		let removed = cache.len();
+		cache.clear();
+		info!("Invalidated {} cache entries", removed);
+	}
+
 	/// Get the next expiration time for a player's modifiers
 	#[instrument(skip(self), fields(player_id = %player_id))]
-	pub async fn next_expiration(&self, player_id: Uuid) -> Option<DateTime<Utc>> {
+	pub fn next_expiration(&self, player_id: Uuid) -> Option<DateTime<Utc>> {
 		debug!("Getting next expiration time for player");
-		let cache = self.cache.read().await;
+		let cache = self.cache.read().expect("modifier cache poisoned");
 
 		let result = cache
 			.iter()
@@ -244,11 +262,11 @@ impl ModifierCache {
 
 	/// Clean up expired entries
 	#[instrument(name = "cache_cleanup", skip_all)]
-	pub async fn cleanup(&self) {
+	pub fn cleanup(&self) {
 		debug!("Starting cache cleanup");
 		let start = Instant::now();
 
-		let mut cache = self.cache.write().await;
+		let mut cache = self.cache.write().expect("modifier cache poisoned");
 		let before_count = cache.len();
 		let now = Utc::now();
 
@@ -276,8 +294,8 @@ impl ModifierCache {
 mod tests {
 	use super::*;
 
-	#[tokio::test]
-	async fn test_cache_basic_operations() {
+	#[test]
+	fn test_cache_basic_operations() {
 		let cache = ModifierCache::new(chrono::Duration::hours(1), 100);
 
 		let key = CacheKey {
@@ -293,15 +311,14 @@ mod tests {
 				BigDecimal::from(1),
 				Some(Utc::now() + chrono::Duration::hours(1)),
 			)
-			.await
 			.unwrap();
 
-		let entry = cache.get(&key).await.unwrap();
+		let entry = cache.get(&key).unwrap();
 		assert_eq!(entry.total_multiplier, BigDecimal::from(1));
 	}
 
-	#[tokio::test]
-	async fn test_version_conflict() {
+	#[test]
+	fn test_version_conflict() {
 		let cache = ModifierCache::new(chrono::Duration::hours(1), 100);
 		let key = CacheKey {
 			player_id: Uuid::new_v4(),
@@ -310,18 +327,15 @@ mod tests {
 		};
 
 		// Initial set
-		cache
-			.set(key.clone(), BigDecimal::from(1), None)
-			.await
-			.unwrap();
+		cache.set(key.clone(), BigDecimal::from(1), None).unwrap();
 
 		// Try to update with wrong version
-		let result = cache.update(&key, BigDecimal::from(2), None, 1).await;
+		let result = cache.update(&key, BigDecimal::from(2), None, 1);
 		assert!(result.is_err());
 	}
 
-	#[tokio::test]
-	async fn test_expiration() {
+	#[test]
+	fn test_expiration() {
 		let cache = ModifierCache::new(chrono::Duration::hours(1), 100);
 		let key = CacheKey {
 			player_id: Uuid::new_v4(),
@@ -336,15 +350,14 @@ mod tests {
 				BigDecimal::from(1),
 				Some(Utc::now() - chrono::Duration::seconds(1)),
 			)
-			.await
 			.unwrap();
 
 		// Should return None for expired entry
-		assert!(cache.get(&key).await.is_none());
+		assert!(cache.get(&key).is_none());
 	}
 
-	#[tokio::test]
-	async fn test_cleanup() {
+	#[test]
+	fn test_cleanup() {
 		let cache = ModifierCache::new(chrono::Duration::hours(1), 100);
 		let player_id = Uuid::new_v4();
 
@@ -367,7 +380,6 @@ mod tests {
 				BigDecimal::from(1),
 				Some(Utc::now() - chrono::Duration::seconds(1)),
 			)
-			.await
 			.unwrap();
 
 		cache
@@ -376,18 +388,17 @@ mod tests {
 				BigDecimal::from(1),
 				Some(Utc::now() + chrono::Duration::hours(1)),
 			)
-			.await
 			.unwrap();
 
 		// Run cleanup
-		cache.cleanup().await;
+		cache.cleanup();
 
-		assert!(cache.get(&key1).await.is_none());
-		assert!(cache.get(&key2).await.is_some());
+		assert!(cache.get(&key1).is_none());
+		assert!(cache.get(&key2).is_some());
 	}
 
-	#[tokio::test]
-	async fn test_user_limit() {
+	#[test]
+	fn test_user_limit() {
 		let cache = ModifierCache::new(chrono::Duration::hours(1), 2);
 		let player_id = Uuid::new_v4();
 
@@ -402,7 +413,7 @@ mod tests {
 					Some(ResourceType::Stone)
 				},
 			};
-			cache.set(key, BigDecimal::from(i), None).await.unwrap();
+			cache.set(key, BigDecimal::from(i), None).unwrap();
 		}
 
 		// Try to exceed limit
@@ -412,12 +423,12 @@ mod tests {
 			target_resource: Some(ResourceType::Wood),
 		};
 
-		let result = cache.set(key, BigDecimal::from(3), None).await;
+		let result = cache.set(key, BigDecimal::from(3), None);
 		assert!(result.is_err());
 	}
 
-	#[tokio::test]
-	async fn test_next_expiration() {
+	#[test]
+	fn test_next_expiration() {
 		let cache = ModifierCache::new(chrono::Duration::hours(1), 100);
 		let player_id = Uuid::new_v4();
 
@@ -437,16 +448,10 @@ mod tests {
 			target_resource: Some(ResourceType::Wood),
 		};
 
-		cache
-			.set(key1, BigDecimal::from(1), Some(exp1))
-			.await
-			.unwrap();
-		cache
-			.set(key2, BigDecimal::from(1), Some(exp2))
-			.await
-			.unwrap();
+		cache.set(key1, BigDecimal::from(1), Some(exp1)).unwrap();
+		cache.set(key2, BigDecimal::from(1), Some(exp2)).unwrap();
 
-		let next_exp = cache.next_expiration(player_id).await.unwrap();
+		let next_exp = cache.next_expiration(player_id).unwrap();
 		assert_eq!(next_exp, exp1);
 	}
 }
@@ -1,27 +1,31 @@
 //! Modifier operations for the Empire game.
 //!
-//! This module provides pure, stateless functions for modifier calculations and retrieval.
+//! This module provides stateless functions for modifier calculations and retrieval.
 //! It follows the functional programming approach with direct function calls rather than
 //! service structs, enabling better performance through single-connection-per-request optimization.
 //!
 //! ## Architecture
 //!
 //! This module is part of the operations layer, which provides:
-//! - **Pure functions** - No side effects, deterministic outputs
-//! - **No caching** - Always calculates fresh values from the database
+//! - **Stateless functions** - Deterministic outputs for the player's active modifiers
+//! - **Shared caching** - [`calc_multiplier`] consults the [`ModifierCache`] held by the
+//!   app state, which is invalidated whenever a player's modifiers change
 //! - **Synchronous** - Direct database queries without async overhead
-//! - **Lightweight** - Only requires a database connection
+//! - **Lightweight** - Only requires a database connection and the cache
 //!
 //! ## When to Use
 //!
 //! Use these operations in **handlers** and interactive endpoints where:
-//! - Fresh values are required (no cache staleness)
 //! - Single request optimization is important
 //! - You want to avoid instantiating service structs
 //!
-//! For **background jobs** that need caching and async processing, use
+//! For **background jobs** and changes to a player's modifiers, use
 //! [`ModifierService`](crate::game::modifiers::modifier_service::ModifierService) instead.
 //!
+//! AIDEV-NOTE: Anything changing what [`calc_multiplier`] would return for a player, like
+//! granting modifiers, changing factions or upgrading buildings, must invalidate the player's
+//! cache entries, see [`ModifierCache::invalidate_user`].
+//!
 //! ## Modifier Stacking Behavior
 //!
 //! The module implements three stacking behaviors for modifiers:
@@ -46,21 +50,148 @@
 //! Example: +10%, +20%, +15% in same group → only +20% applies
 //! ```
 //!
-//! Final results are capped between 0.5 (50%) and 3.0 (300%).
+//! ### Debuffs
+//! Debuffs are modifiers with a negative percentage or flat magnitude, or a multiplier below
+//! 1.0, and stack like any other modifier. A multiplier counts as a bonus of its magnitude
+//! minus one, so x0.8 stacks like -20%. Only the highest modifier of a stacking group
+//! applies, so a debuff sharing a group with a bonus is outweighed by it.
+//! ```text
+//! Example: +15% faction wood and -20% "Wounded Army" wood (both additive) → 0.95x multiplier
+//! ```
+//!
+//! Final results are clamped between the floor of their target and the highest multiplier,
+//! both configured in [`ModifierSettings`]. Production never drops below 10% of its base,
+//! other targets below 50%, and nothing rises above 300% by default. Callers rely on these
+//! limits instead of clamping multipliers themselves.
+//!
+//! ## Building-scoped Modifiers
+//!
+//! A modifier scoped to one of the player's buildings only affects what that building
+//! produces. The building's multiplier stacks its own modifiers together with the
+//! player-wide ones under the rules above, so a building bonus in the same stacking group as
+//! a player-wide bonus only counts if it is higher, and the caps apply to the combined value.
+//! ```text
+//! Example: +15% player-wide wood and +25% on Lumberyard #2 (both additive)
+//!          → Lumberyard #2 produces at 1.4x, every other building at 1.15x
+//! ```
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use std::sync::OnceLock;
 
 use bigdecimal::BigDecimal;
 use diesel::prelude::*;
+use tracing::{trace, warn};
 
-use crate::Result;
+use crate::configuration::ModifierSettings;
 use crate::db::DbConn;
 use crate::domain::modifier::active_modifier::ActiveModifier;
 use crate::domain::modifier::full_modifier::AppliedModifier;
 use crate::domain::modifier::{Modifier, ModifierTarget, StackingBehaviour};
 use crate::domain::player::PlayerKey;
+use crate::domain::player::buildings::PlayerBuildingKey;
 use crate::domain::player::resource::ResourceType;
+use crate::game::modifiers::modifier_cache::{CacheKey, ModifierCache};
 use crate::game::resources::ResourceMultiplier;
+use crate::{Error, Result};
+
+/// Lowest multiplier of targets without a configured floor.
+pub const DEFAULT_MODIFIER_FLOOR: f64 = 0.5;
+
+/// Limits of the calculated multipliers, using the defaults until [`init_limits`] is called.
+static LIMITS: OnceLock<ModifierLimits> = OnceLock::new();
+
+/// The multiplier limits of [`ModifierSettings`], checked and converted for the stacking engine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModifierLimits {
+	floors: BTreeMap<ModifierTarget, BigDecimal>,
+	default_floor: BigDecimal,
+	max_multiplier: BigDecimal,
+}
+
+impl ModifierLimits {
+	/// The lowest multiplier of a target.
+	fn floor(&self, target: ModifierTarget) -> &BigDecimal {
+		self.floors.get(&target).unwrap_or(&self.default_floor)
+	}
+}
+
+impl Default for ModifierLimits {
+	/// The limits of [`ModifierSettings::default`].
+	fn default() -> Self {
+		Self {
+			floors: BTreeMap::from([(ModifierTarget::Resource, BigDecimal::new(1.into(), 1))]),
+			default_floor: BigDecimal::new(5.into(), 1),
+			max_multiplier: BigDecimal::from(3),
+		}
+	}
+}
+
+impl TryFrom<&ModifierSettings> for ModifierLimits {
+	type Error = Error;
+
+	/// Rejects limits the multipliers cannot be clamped to: a highest multiplier that is not
+	/// finite, or a floor, including [`DEFAULT_MODIFIER_FLOOR`], that is not above zero and at
+	/// most the highest multiplier.
+	fn try_from(settings: &ModifierSettings) -> Result<Self> {
+		let max_multiplier = limit("modifiers.max_multiplier", settings.max_multiplier)?;
+		let floor = |name: &str, value: f64| {
+			let floor = limit(name, value)?;
+			if floor <= 0 || floor > max_multiplier {
+				return Err(Error::from(config::ConfigError::Message(format!(
+					"{name} must be above 0 and at most modifiers.max_multiplier ({max_multiplier}), got {value}"
+				))));
+			}
+			Ok(floor)
+		};
+
+		let floors = settings
+			.floors
+			.iter()
+			.map(|(target, value)| {
+				Ok((
+					*target,
+					floor(&format!("modifiers.floors.{target}"), *value)?,
+				))
+			})
+			.collect::<Result<_>>()?;
+		let default_floor = floor("the default modifier floor", DEFAULT_MODIFIER_FLOOR)?;
+		Ok(Self {
+			floors,
+			default_floor,
+			max_multiplier,
+		})
+	}
+}
+
+/// Converts a configured limit, keeping its shortest decimal representation.
+fn limit(name: &str, value: f64) -> Result<BigDecimal> {
+	if !value.is_finite() {
+		return Err(config::ConfigError::Message(format!(
+			"{name} must be a finite number, got {value}"
+		))
+		.into());
+	}
+	BigDecimal::from_str(&value.to_string()).map_err(|err| {
+		config::ConfigError::Message(format!("{name} is not a decimal number: {err}")).into()
+	})
+}
+
+/// One-time initialization of the multiplier limits from the settings.
+///
+/// # Errors
+/// Returns a configuration error if the limits are invalid, see [`ModifierLimits::try_from`]
+pub fn init_limits(settings: &ModifierSettings) -> Result<()> {
+	let limits = ModifierLimits::try_from(settings)?;
+	if LIMITS.set(limits).is_err() {
+		warn!("Modifier limits were already initialized");
+	}
+	Ok(())
+}
+
+fn limits() -> &'static ModifierLimits {
+	LIMITS.get_or_init(ModifierLimits::default)
+}
 
 /// Get all active modifiers for a player
 pub fn get_active_mods(conn: &mut DbConn, player_key: &PlayerKey) -> Result<Vec<ActiveModifier>> {
@@ -89,35 +220,109 @@ pub fn get_applied_mods(conn: &mut DbConn, player_key: &PlayerKey) -> Result<Vec
 
 /// Calculate the total modifier multiplier for a specific target and resource
 ///
-/// This is a pure function that doesn't use caching - suitable for handlers.
-/// For background jobs that need caching, use ModifierService::get_total_multiplier()
+/// Served from `cache` while the player's modifiers are unchanged. On a miss the multiplier
+/// is calculated from the database and cached until the first of the counted modifiers
+/// expires.
+///
+/// Only player-wide modifiers count, see [`building_multiplier`] for building-scoped ones.
 pub fn calc_multiplier(
 	conn: &mut DbConn,
+	cache: &ModifierCache,
 	player_id: &PlayerKey,
 	target_type: ModifierTarget,
 	target_resource: Option<ResourceType>,
 ) -> Result<ResourceMultiplier> {
+	let cache_key = CacheKey {
+		player_id: *player_id,
+		target_type,
+		target_resource,
+	};
+	if let Some(entry) = cache.get(&cache_key) {
+		trace!(%cache_key, total_multiplier = %entry.total_multiplier, "Cache hit for modifier calculation");
+		return Ok(entry.total_multiplier);
+	}
+
 	let player_mods = get_applied_mods(conn, player_id)?;
-	let modifiers: Vec<AppliedModifier> = player_mods
-		.into_iter()
+	let total_multiplier = player_multiplier(&player_mods, target_type, target_resource);
+	let expires_at = player_mods
+		.iter()
+		.filter(|m| m.player_building_id.is_none())
+		.filter(|m| m.target_type == target_type && m.target_resource == target_resource)
+		.filter_map(|m| m.expires_at)
+		.min();
+	trace!(%cache_key, %total_multiplier, "Cache miss, calculated total modifier");
+
+	// A full cache only costs the next lookup a query
+	if let Err(err) = cache.set(cache_key, total_multiplier.clone(), expires_at) {
+		warn!(%player_id, "Failed to cache modifier multiplier: {}", err);
+	}
+	Ok(total_multiplier)
+}
+
+/// Calculate the multiplier of the player-wide modifiers among `mods` for a target
+pub fn player_multiplier(
+	mods: &[AppliedModifier],
+	target_type: ModifierTarget,
+	target_resource: Option<ResourceType>,
+) -> ResourceMultiplier {
+	let modifiers: Vec<AppliedModifier> = mods
+		.iter()
+		.filter(|m| m.player_building_id.is_none())
+		.filter(|m| m.target_type == target_type && m.target_resource == target_resource)
+		.cloned()
+		.collect();
+
+	apply_stacking_rules(&modifiers, target_type)
+}
+
+/// Calculate the multiplier of a single building for a target
+///
+/// The player-wide modifiers among `mods` stack together with the ones scoped to
+/// `player_bld_id`, modifiers of other buildings are left out.
+pub fn building_multiplier(
+	mods: &[AppliedModifier],
+	player_bld_id: &PlayerBuildingKey,
+	target_type: ModifierTarget,
+	target_resource: Option<ResourceType>,
+) -> ResourceMultiplier {
+	let modifiers: Vec<AppliedModifier> = mods
+		.iter()
+		.filter(|m| m.player_building_id.is_none_or(|id| id == *player_bld_id))
 		.filter(|m| m.target_type == target_type && m.target_resource == target_resource)
+		.cloned()
 		.collect();
 
-	Ok(apply_stacking_rules(&modifiers))
+	apply_stacking_rules(&modifiers, target_type)
+}
+
+/// Get the buildings that have modifiers of their own for a target
+pub fn scoped_buildings(
+	mods: &[AppliedModifier],
+	target_type: ModifierTarget,
+) -> Vec<PlayerBuildingKey> {
+	let mut buildings: Vec<PlayerBuildingKey> = mods
+		.iter()
+		.filter(|m| m.target_type == target_type)
+		.filter_map(|m| m.player_building_id)
+		.collect();
+	buildings.sort_unstable();
+	buildings.dedup();
+	buildings
 }
 
 /// Calculate the final modifier value for a collection of modifiers
 ///
-/// This implements the stacking behavior logic:
-/// - Additive: Sum all magnitudes, then add to base (1.0)
-/// - Multiplicative: Multiply (1.0 + magnitude) for each modifier
-/// - HighestOnly: Take the highest magnitude per stacking group
+/// This implements the stacking behavior logic on the [bonus](AppliedModifier::bonus) of
+/// each modifier:
+/// - Additive: Sum all bonuses, then add to base (1.0)
+/// - Multiplicative: Multiply (1.0 + bonus) for each modifier
+/// - HighestOnly: Take the highest bonus per stacking group
 ///
-/// The result is capped between 0.5 (50%) and 3.0 (300%)
-fn apply_stacking_rules(modifiers: &[AppliedModifier]) -> ResourceMultiplier {
-	let global_max_cap: BigDecimal = BigDecimal::from(3); // 300%
-	let global_min_floor: BigDecimal =
-		BigDecimal::try_from(0.5).expect("Failed to create a 0.5 numeric."); // 50%
+/// The result is clamped between the floor of `target_type` and the highest multiplier.
+fn apply_stacking_rules(
+	modifiers: &[AppliedModifier],
+	target_type: ModifierTarget,
+) -> ResourceMultiplier {
 	let base = BigDecimal::from(1);
 
 	if modifiers.is_empty() {
@@ -146,33 +351,29 @@ fn apply_stacking_rules(modifiers: &[AppliedModifier]) -> ResourceMultiplier {
 	// Step 2: Calculate additive modifiers
 	let additive_total = additive_mods
 		.iter()
-		.fold(BigDecimal::from(0), |acc, m| acc + &m.magnitude);
+		.fold(BigDecimal::from(0), |acc, m| acc + m.bonus());
 
 	// Step 3: Calculate highest-only modifiers
 	let highest_only_values: Vec<BigDecimal> = highest_only_groups
 		.values()
-		.map(|group| {
-			group
-				.iter()
-				.map(|m| &m.magnitude)
-				.max_by(|a, b| a.cmp(b))
-				.unwrap_or(&BigDecimal::from(0))
-				.clone()
-		})
+		.filter_map(|group| group.iter().map(|m| m.bonus()).max())
 		.collect();
 
 	// Step 4: Calculate multiplicative effect
 	let multiplicative_total = multiplicative_mods
 		.iter()
-		.map(|m| &m.magnitude)
-		.chain(highest_only_values.iter())
-		.fold(base.clone(), |acc, magnitude| {
-			acc * (base.clone() + magnitude)
-		});
+		.map(|m| m.bonus())
+		.chain(highest_only_values)
+		.fold(base.clone(), |acc, bonus| acc * (base.clone() + bonus));
 
 	// Step 5: Combine all effects
 	let total = (base + additive_total) * multiplicative_total;
 
-	// Step 6: Apply global caps and floors using clamp
-	total.clamp(global_min_floor, global_max_cap)
+	// Step 6: Apply the floor of the target and the global cap
+	let limits = limits();
+	let floor = limits.floor(target_type);
+	if total < *floor {
+		trace!(%total, %floor, %target_type, "Debuffs clamped to the floor");
+	}
+	total.clamp(floor.clone(), limits.max_multiplier.clone())
 }
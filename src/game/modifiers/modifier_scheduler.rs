@@ -4,12 +4,18 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::Error;
 use crate::domain::jobs::JobType;
 use crate::domain::player::PlayerKey;
 use crate::domain::player::resource::ResourceType;
 use crate::domain::{jobs, modifier};
 use crate::job_queue::{JobPriority, JobQueue};
+use crate::{Error, Result};
+
+/// Name of the recurring job expiring modifiers.
+pub const EXPIRATION_TICK_NAME: &str = "modifier-expiration";
+
+/// Schedule of the expiration sweep: every minute, on the minute.
+pub const EXPIRATION_TICK_CRON: &str = "0 * * * * *";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum ModifierJobPayload {
@@ -24,6 +30,24 @@ pub enum ModifierJobPayload {
 	UpdateModifierCache {
 		player_id: PlayerKey,
 	},
+	/// Expires every modifier that ran out, see [`register_expiration_tick`]
+	ExpireDueModifiers,
+}
+
+/// Registers the recurring sweep expiring modifiers.
+///
+/// Expired modifiers already stop counting towards multipliers; the sweep deletes them,
+/// records their expiration in the modifier history, drops the cached multipliers of their
+/// players and tells the players. Safe to call on every startup.
+pub fn register_expiration_tick(job_queue: &JobQueue) -> Result<()> {
+	job_queue.register_recurring(
+		EXPIRATION_TICK_NAME,
+		EXPIRATION_TICK_CRON,
+		JobType::Modifier,
+		ModifierJobPayload::ExpireDueModifiers,
+		JobPriority::Normal,
+	)?;
+	Ok(())
 }
 
 pub struct ModifierScheduler {
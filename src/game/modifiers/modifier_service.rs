@@ -4,13 +4,16 @@ use std::sync::Arc;
 use axum::extract::FromRef;
 use bigdecimal::BigDecimal;
 use chrono::{DateTime, Utc};
+use diesel::Connection;
+use serde_json::json;
 use strum::IntoEnumIterator;
-use tracing::{info, trace};
+use tracing::info;
 
-use crate::db::{active_modifiers, modifiers};
+use crate::db::{DbConn, active_modifiers, modifier_history, modifiers, player_buildings};
 use crate::domain::app_state::{AppPool, AppState};
-use crate::domain::modifier::ModifierTarget;
 use crate::domain::modifier::active_modifier::{ActiveModifier, NewActiveModifier};
+use crate::domain::modifier::modifier_history::{ModifierActionType, NewModifierHistory};
+use crate::domain::modifier::{Modifier, ModifierKey, ModifierTarget};
 use crate::domain::player::PlayerKey;
 use crate::domain::player::resource::ResourceType;
 use crate::game::modifiers::modifier_cache::{CacheKey, ModifierCache};
@@ -18,7 +21,49 @@ use crate::game::modifiers::modifier_operations;
 use crate::game::modifiers::modifier_scheduler::ModifierScheduler;
 use crate::game::modifiers::modifier_system::ModifierSystem;
 use crate::game::resources::ResourceMultipliers;
-use crate::{Error, Result};
+use crate::{Error, ErrorKind, Result};
+
+/// An active modifier that expired, with the modifier it applied.
+#[derive(Debug, Clone)]
+pub struct ExpiredModifier {
+	pub active: ActiveModifier,
+	pub modifier: Modifier,
+}
+
+/// Who changed a player's modifiers and why, recorded in the modifier history.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModifierChange {
+	/// Operator who made the change
+	pub operator: Option<String>,
+	pub reason: Option<String>,
+}
+
+impl ModifierChange {
+	/// The history entry recording this change to `active`.
+	pub fn history_entry(
+		&self,
+		active: &ActiveModifier,
+		modifier: &Modifier,
+		action_type: ModifierActionType,
+	) -> NewModifierHistory {
+		NewModifierHistory {
+			player_id: active.player_id,
+			modifier_id: active.modifier_id,
+			action_type,
+			magnitude: modifier.magnitude.clone(),
+			source_type: active.source_type,
+			source_id: active.source_id,
+			previous_state: Some(json!({
+				"active_modifier_id": active.id,
+				"started_at": active.started_at,
+				"expires_at": active.expires_at,
+				"player_building_id": active.player_building_id,
+			})),
+			reason: self.reason.clone(),
+			operator: self.operator.clone(),
+		}
+	}
+}
 
 pub struct ModifierService {
 	pool: AppPool,
@@ -44,39 +89,137 @@ impl ModifierService {
 	}
 
 	/// Apply a new modifier to a player and update all relevant systems
+	///
+	/// Modifiers scoped to a building must target resources, and the building must belong
+	/// to the player.
 	pub async fn apply_modifier(
 		&mut self,
 		new_modifier: NewActiveModifier,
 	) -> Result<ActiveModifier> {
-		let mut conn = self.pool.get()?;
+		self.apply(new_modifier, None).await
+	}
 
-		// Store the modifier in the database
-		let active_mod = active_modifiers::create(&mut conn, new_modifier)?;
+	/// Grant a modifier to a player on behalf of an operator, like an event buff.
+	///
+	/// Works like [`ModifierService::apply_modifier`], and records the grant together with
+	/// the operator in the modifier history.
+	pub async fn grant_modifier(
+		&mut self,
+		new_modifier: NewActiveModifier,
+		change: ModifierChange,
+	) -> Result<ActiveModifier> {
+		self.apply(new_modifier, Some(change)).await
+	}
 
-		// Calculate new aggregate values for affected resources/targets
-		let modifier = modifiers::get_by_id(&mut conn, &active_mod.modifier_id)?;
+	/// Revoke every instance of a modifier a player holds, like an exploited bonus.
+	///
+	/// The modifiers are deleted and their removal is recorded in the modifier history in
+	/// one transaction, and the player's cached multiplier is invalidated afterwards.
+	pub async fn revoke_modifier(
+		&self,
+		player_id: &PlayerKey,
+		modifier_id: &ModifierKey,
+		change: ModifierChange,
+	) -> Result<Vec<ActiveModifier>> {
+		let (modifier, revoked) = {
+			let mut conn = self.pool.get()?;
+			conn.transaction(|conn| {
+				let modifier = modifiers::get_by_id(conn, modifier_id)?;
+				let revoked = active_modifiers::delete_for_player(conn, player_id, modifier_id)?;
+				if revoked.is_empty() {
+					return Err(Error::from((
+						ErrorKind::NotFoundError,
+						"Active modifier not found",
+					)));
+				}
+				let history: Vec<_> = revoked
+					.iter()
+					.map(|active| {
+						change.history_entry(active, &modifier, ModifierActionType::Removed)
+					})
+					.collect();
+				modifier_history::create_batch(conn, &history)?;
+				Ok::<_, Error>((modifier, revoked))
+			})?
+		};
+
+		self.invalidate(player_id, &modifier);
+
+		info!(
+			"Revoked {} instances of modifier {} from player {}",
+			revoked.len(),
+			modifier.name,
+			player_id
+		);
+		Ok(revoked)
+	}
+
+	/// Track a modifier that was stored outside of the service, like by using an item.
+	///
+	/// Invalidates the player's cached multiplier for the modifier's target and schedules
+	/// its expiration.
+	pub async fn track_modifier(&self, active: &ActiveModifier, modifier: &Modifier) -> Result<()> {
+		self.invalidate(&active.player_id, modifier);
+		if let Some(expires_at) = active.expires_at {
+			self.scheduler
+				.schedule_expiration(active.id, active.player_id, expires_at)?;
+		}
+		Ok(())
+	}
+
+	/// Invalidates the player's cached multiplier for the target of `modifier`.
+	///
+	/// Used on its own for modifiers removed outside of the service, like by unassigning a
+	/// hero.
+	pub fn invalidate(&self, player_id: &PlayerKey, modifier: &Modifier) {
 		let cache_key = CacheKey {
-			player_id: active_mod.player_id,
+			player_id: *player_id,
 			target_type: modifier.target_type,
 			target_resource: modifier.target_resource,
 		};
+		self.cache.invalidate(&cache_key);
+	}
+
+	/// Stores a new modifier, recording it in the history if it is a `change` by an operator.
+	async fn apply(
+		&mut self,
+		new_modifier: NewActiveModifier,
+		change: Option<ModifierChange>,
+	) -> Result<ActiveModifier> {
+		let mut conn = self.pool.get()?;
+
+		let modifier = modifiers::get_by_id(&mut conn, &new_modifier.modifier_id)?;
+		if let Some(player_bld_id) = new_modifier.player_building_id {
+			if modifier.target_type != ModifierTarget::Resource {
+				return Err(Error::from((
+					ErrorKind::InvalidData,
+					"Only resource modifiers can be scoped to a building",
+				)));
+			}
+			player_buildings::get_owned(&mut conn, &new_modifier.player_id, &player_bld_id)?;
+		}
 
-		// Invalidate existing cache entry
-		self.cache.invalidate(&cache_key).await;
+		// Store the modifier in the database
+		let active_mod = conn.transaction(|conn| {
+			let active_mod = active_modifiers::create(conn, new_modifier)?;
+			if let Some(change) = change {
+				let entry =
+					change.history_entry(&active_mod, &modifier, ModifierActionType::Applied);
+				modifier_history::create_batch(conn, &[entry])?;
+			}
+			Ok::<_, Error>(active_mod)
+		})?;
 
-		// Calculate and cache new values
-		let total_multiplier = modifier_operations::calc_multiplier(
+		// Invalidate the existing cache entry, then calculate and cache the new value
+		self.invalidate(&active_mod.player_id, &modifier);
+		modifier_operations::calc_multiplier(
 			&mut conn,
+			&self.cache,
 			&active_mod.player_id,
 			modifier.target_type,
 			modifier.target_resource,
 		)?;
 
-		// Update cache with new values
-		self.cache
-			.set(cache_key, total_multiplier, active_mod.expires_at)
-			.await?;
-
 		// Schedule expiration job if needed
 		if let Some(expires_at) = active_mod.expires_at {
 			self.scheduler
@@ -90,73 +233,48 @@ impl ModifierService {
 		Ok(active_mod)
 	}
 
-	/// Get the total modifier multiplier for a specific target and resource, with caching.
-	///
-	/// This method uses a cache with smart invalidation to provide accurate modifier values
-	/// while optimizing for repeated lookups. The cache is automatically invalidated when
-	/// modifiers are applied or expire, ensuring values are always accurate.
-	///
-	/// # Caching Behavior
-	///
-	/// - **Cache hit**: Returns cached value immediately (fast path)
-	/// - **Cache miss**: Calculates fresh value from DB, caches it with TTL
-	/// - **Invalidation**: Automatic when modifiers change or expire
-	/// - **TTL**: Set to the nearest modifier expiration time
-	///
-	/// # Performance Characteristics
-	///
-	/// - **Cache hit**: Very fast (no DB access)
-	/// - **Cache miss**: Slower than `modifier_operations::calc_multiplier` (async overhead + DB query)
-	/// - **Overall**: Faster for repeated lookups, slower for one-off queries
+	/// Expire every modifier that ran out at or before `now`.
 	///
-	/// # Comparison with `modifier_operations::calc_multiplier`
-	///
-	/// | Feature | This Method | `modifier_operations::calc_multiplier` |
-	/// |---------|-------------|---------------------------------------|
-	/// | Caching | ✓ Yes (auto-invalidated) | ✗ No |
-	/// | Async | ✓ Yes | ✗ No (sync) |
-	/// | Accuracy | Always accurate | Always accurate |
-	/// | Best For | Repeated lookups | Single lookups |
-	/// | Overhead | Async + cache check | Direct DB query |
-	///
-	/// # When to Use
+	/// The modifiers are deleted and their expiration is recorded in the modifier history in
+	/// one transaction, so concurrent sweeps never expire a modifier twice. The cached
+	/// multipliers of the affected players are invalidated afterwards.
+	pub async fn expire_modifiers(&self, now: DateTime<Utc>) -> Result<Vec<ExpiredModifier>> {
+		let expired = {
+			let mut conn = self.pool.get()?;
+			conn.transaction(|conn| expire_due(conn, now))?
+		};
+
+		for expired_mod in &expired {
+			let cache_key = CacheKey {
+				player_id: expired_mod.active.player_id,
+				target_type: expired_mod.modifier.target_type,
+				target_resource: expired_mod.modifier.target_resource,
+			};
+			self.cache.invalidate(&cache_key);
+		}
+
+		info!("Expired {} modifiers", expired.len());
+		Ok(expired)
+	}
+
+	/// Get the total modifier multiplier for a specific target and resource, with caching.
 	///
-	/// - **Use this method** for background processors with repeated calculations (e.g., resource production every 2 minutes)
-	/// - **Use `modifier_operations::calc_multiplier`** for one-off queries in HTTP handlers
+	/// Works like [`modifier_operations::calc_multiplier`] on a connection of the service's
+	/// pool, for background processors that do not hold a connection of their own.
 	pub async fn get_or_calc_multiplier(
 		&self,
 		player_id: &PlayerKey,
 		target_type: ModifierTarget,
 		target_resource: Option<ResourceType>,
 	) -> Result<BigDecimal> {
-		let cache_key = CacheKey {
-			player_id: *player_id,
+		let mut conn = self.pool.get()?;
+		modifier_operations::calc_multiplier(
+			&mut conn,
+			&self.cache,
+			player_id,
 			target_type,
 			target_resource,
-		};
-
-		// Try to get from cache first
-		if let Some(entry) = self.cache.get(&cache_key).await {
-			trace!(%cache_key, total_multiplier = %entry.total_multiplier, "Cache hit for modifier calculation");
-			return Ok(entry.total_multiplier);
-		}
-
-		// Calculate and cache if not found
-		let total_multiplier = {
-			let mut conn = self.pool.get()?;
-			modifier_operations::calc_multiplier(&mut conn, player_id, target_type, target_resource)
-		}?;
-		trace!(%cache_key, %total_multiplier, "Cache miss, calculated total modifier");
-
-		// Get the nearest expiration time from active modifiers
-		let expires_at = self.get_nearest_expiration(player_id, target_type, target_resource)?;
-
-		// Cache the result
-		self.cache
-			.set(cache_key, total_multiplier.clone(), expires_at)
-			.await?;
-
-		Ok(total_multiplier)
+		)
 	}
 
 	/// Get all resource modifier multipliers for a player in a single batch operation
@@ -173,20 +291,43 @@ impl ModifierService {
 		}
 		Ok(multipliers)
 	}
+}
 
-	/// Get the nearest expiration time for modifiers matching the criteria
-	fn get_nearest_expiration(
-		&self,
-		player_id: &PlayerKey,
-		target_type: ModifierTarget,
-		target_resource: Option<ResourceType>,
-	) -> Result<Option<DateTime<Utc>>, Error> {
-		let mut conn = self.pool.get()?;
-		let active_modifiers = modifier_operations::get_active_mods(&mut conn, player_id)?;
+/// Deletes the modifiers due at `now` and records their expiration in the history.
+fn expire_due(conn: &mut DbConn, now: DateTime<Utc>) -> Result<Vec<ExpiredModifier>> {
+	let expired = active_modifiers::delete_expired(conn, now)?;
+	let mut definitions: HashMap<ModifierKey, Modifier> = HashMap::new();
+	let mut history = Vec::with_capacity(expired.len());
+	let mut expired_mods = Vec::with_capacity(expired.len());
 
-		Ok(active_modifiers
-			.into_iter()
-			.filter_map(|m| m.expires_at)
-			.min())
+	for active in expired {
+		let modifier = match definitions.get(&active.modifier_id) {
+			Some(modifier) => modifier.clone(),
+			None => {
+				let modifier = modifiers::get_by_id(conn, &active.modifier_id)?;
+				definitions.insert(modifier.id, modifier.clone());
+				modifier
+			}
+		};
+		history.push(NewModifierHistory {
+			player_id: active.player_id,
+			modifier_id: active.modifier_id,
+			action_type: ModifierActionType::Expired,
+			magnitude: modifier.magnitude.clone(),
+			source_type: active.source_type,
+			source_id: active.source_id,
+			previous_state: Some(json!({
+				"active_modifier_id": active.id,
+				"started_at": active.started_at,
+				"expires_at": active.expires_at,
+				"player_building_id": active.player_building_id,
+			})),
+			reason: Some("Expired".to_string()),
+			operator: None,
+		});
+		expired_mods.push(ExpiredModifier { active, modifier });
 	}
+
+	modifier_history::create_batch(conn, &history)?;
+	Ok(expired_mods)
 }
@@ -25,8 +25,6 @@
 
 use std::sync::Arc;
 
-use chrono::Duration;
-
 use crate::configuration::Settings;
 use crate::domain::app_state::AppQueue;
 use crate::game::modifiers::modifier_cache::ModifierCache;
@@ -59,8 +57,7 @@ impl ModifierSystem {
 	/// * `settings` - Application settings containing cache configuration
 	/// * `job_queue` - Reference to the application's job queue for scheduling
 	pub fn with_job_queue(settings: &Settings, job_queue: &AppQueue) -> Self {
-		// TODO: get these from settings
-		let cache = Arc::new(ModifierCache::new(Duration::hours(1), 100));
+		let cache = Arc::new(ModifierCache::from_settings(&settings.cache));
 		let scheduler = Arc::new(ModifierScheduler::new(job_queue));
 		Self { cache, scheduler }
 	}
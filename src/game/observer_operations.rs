@@ -0,0 +1,295 @@
+//! Read-only observer accounts for casting community tournaments.
+//!
+//! An operator creates an observer with a name, the [`ObserverScope`]s it is granted and the
+//! players it is selected to watch, and receives the observer's token once. Only the hash of
+//! the token is stored. Creating and revoking observers is recorded in the audit log.
+//!
+//! Observers never change anything. They only see the players selected for them that opted
+//! in with their `allow_observers` privacy setting, and only what their scopes allow. Players
+//! they cannot watch are reported as not found, like players that do not exist, so an
+//! observer cannot tell who declined.
+
+use chrono::{DateTime, Utc};
+use diesel::Connection;
+use serde_json::json;
+use tracing::{debug, info, instrument};
+
+use crate::auth::session_operations::{encode_token, gen_token};
+use crate::db::player_buildings::FullBuilding;
+use crate::db::{DbConn, audit_log, battle_reports, observers, player_buildings, players};
+use crate::domain::audit::NewAuditEntry;
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::observer::{NewObserver, Observer, ObserverKey, ObserverScope};
+use crate::domain::player::{Player, PlayerKey};
+use crate::game::admin_operations::AdminActor;
+use crate::game::combat::combat_operations::{self, ReportPage};
+
+/// Action recorded in the audit log when an observer is created.
+pub const CREATE_OBSERVER_ACTION: &str = "create_observer";
+
+/// Action recorded in the audit log when an observer is revoked.
+pub const REVOKE_OBSERVER_ACTION: &str = "revoke_observer";
+
+/// An observer an operator asked to create.
+#[derive(Debug, Clone)]
+pub struct ObserverRequest {
+	pub name: String,
+	pub scopes: Vec<ObserverScope>,
+	/// Players the observer is selected to watch
+	pub player_ids: Vec<PlayerKey>,
+	/// End of the observer's access, `None` to keep it until revoked
+	pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// A created observer and the token it authenticates with.
+#[derive(Debug, Clone)]
+pub struct CreatedObserver {
+	pub observer: Observer,
+	/// Token of the observer, only handed out once
+	pub token: String,
+	pub player_ids: Vec<PlayerKey>,
+}
+
+/// A watched player's city.
+#[derive(Debug)]
+pub struct ObservedCity {
+	pub player: Player,
+	pub buildings: Vec<FullBuilding>,
+}
+
+/// A watched player's place on the leaderboard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeaderboardEntry {
+	/// 1-based rank among the watched players
+	pub rank: usize,
+	pub player: Player,
+	/// Levels of all the player's buildings, summed up
+	pub building_levels: i64,
+	pub battles_won: i64,
+}
+
+/// Creates an observer, recording it in the audit log.
+///
+/// Fails if the name or the scopes are empty, a selected player does not exist, or the
+/// observer would already be expired.
+#[instrument(skip(conn))]
+pub fn create_observer(
+	conn: &mut DbConn,
+	request: ObserverRequest,
+	actor: AdminActor,
+) -> Result<CreatedObserver> {
+	let name = request.name.trim().to_string();
+	if name.is_empty() {
+		return Err(Error::from((
+			ErrorKind::InvalidData,
+			"Observer name is required",
+		)));
+	}
+	if request.scopes.is_empty() {
+		return Err(Error::from((
+			ErrorKind::InvalidData,
+			"Observer needs at least one scope",
+		)));
+	}
+	if request
+		.expires_at
+		.is_some_and(|expires_at| expires_at <= Utc::now())
+	{
+		return Err(Error::from((
+			ErrorKind::InvalidData,
+			"Observer would already be expired",
+		)));
+	}
+	let mut scopes: Vec<String> = request
+		.scopes
+		.iter()
+		.map(|scope| scope.as_str().to_string())
+		.collect();
+	scopes.sort();
+	scopes.dedup();
+	let mut player_ids = request.player_ids;
+	player_ids.sort();
+	player_ids.dedup();
+
+	let token = gen_token();
+	let observer = conn.transaction(|conn| {
+		for player_id in &player_ids {
+			if players::find_by_id(conn, player_id)?.is_none() {
+				return Err(Error::from((
+					ErrorKind::InvalidData,
+					"Selected player not found",
+				)));
+			}
+		}
+		let observer = observers::create(
+			conn,
+			NewObserver {
+				name,
+				token_hash: encode_token(&token),
+				scopes,
+				created_by: actor.operator.clone(),
+				expires_at: request.expires_at,
+			},
+		)?;
+		observers::add_players(conn, &observer.id, &player_ids)?;
+		audit_log::create(
+			conn,
+			NewAuditEntry {
+				action: CREATE_OBSERVER_ACTION.to_string(),
+				subject: Some(observer.id.to_string()),
+				details: json!({
+					"name": observer.name,
+					"scopes": observer.scopes,
+					"player_ids": player_ids,
+					"expires_at": observer.expires_at,
+				}),
+				operator: actor.operator,
+				request_id: actor.request_id,
+				player_id: None,
+			},
+		)?;
+		Ok::<_, Error>(observer)
+	})?;
+
+	info!(
+		"Created observer {} watching {} players",
+		observer.id,
+		player_ids.len()
+	);
+	Ok(CreatedObserver {
+		observer,
+		token,
+		player_ids,
+	})
+}
+
+/// Lists every observer with the players selected for it, newest first.
+pub fn list_observers(conn: &mut DbConn) -> Result<Vec<(Observer, Vec<PlayerKey>)>> {
+	observers::get_all(conn)?
+		.into_iter()
+		.map(|observer| {
+			let player_ids = observers::get_player_ids(conn, &observer.id)?;
+			Ok((observer, player_ids))
+		})
+		.collect()
+}
+
+/// Revokes an observer, recording it in the audit log.
+///
+/// The observer's token stops working right away. Revoking an observer twice keeps the
+/// time of the first revocation.
+#[instrument(skip(conn))]
+pub fn revoke_observer(
+	conn: &mut DbConn,
+	observer_id: &ObserverKey,
+	actor: AdminActor,
+) -> Result<Observer> {
+	conn.transaction(|conn| {
+		let Some(observer) = observers::revoke(conn, observer_id, Utc::now())? else {
+			return observers::find_by_id(conn, observer_id)?
+				.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Observer not found")));
+		};
+		audit_log::create(
+			conn,
+			NewAuditEntry {
+				action: REVOKE_OBSERVER_ACTION.to_string(),
+				subject: Some(observer.id.to_string()),
+				details: json!({ "name": observer.name }),
+				operator: actor.operator,
+				request_id: actor.request_id,
+				player_id: None,
+			},
+		)?;
+		info!("Revoked observer {}", observer.id);
+		Ok(observer)
+	})
+}
+
+/// Finds the active observer authenticating with `token`.
+///
+/// Returns `None` for unknown tokens and for revoked or expired observers.
+#[instrument(skip_all)]
+pub fn authenticate(conn: &mut DbConn, token: &str) -> Result<Option<Observer>> {
+	let observer = observers::find_by_token_hash(conn, &encode_token(token))?
+		.filter(|observer| observer.is_active(Utc::now()));
+	Ok(observer)
+}
+
+/// Fails with [`ErrorKind::ObserverScopeError`] unless the observer was granted `scope`.
+pub fn require_scope(observer: &Observer, scope: ObserverScope) -> Result<()> {
+	if !observer.has_scope(scope) {
+		debug!("Observer {} lacks the {} scope", observer.id, scope);
+		return Err(Error::from((
+			ErrorKind::ObserverScopeError,
+			"Observer is not allowed to see this",
+		)));
+	}
+	Ok(())
+}
+
+/// Lists the players the observer can watch, by name.
+pub fn list_watched_players(conn: &mut DbConn, observer: &Observer) -> Result<Vec<Player>> {
+	observers::get_watched_players(conn, &observer.id)
+}
+
+/// Retrieves the city of a watched player.
+#[instrument(skip(conn))]
+pub fn get_city(
+	conn: &mut DbConn,
+	observer: &Observer,
+	player_id: &PlayerKey,
+) -> Result<ObservedCity> {
+	require_scope(observer, ObserverScope::City)?;
+	let player = watched_player(conn, observer, player_id)?;
+	let buildings = player_buildings::get_game_buildings(conn, &player.id)?;
+	Ok(ObservedCity { player, buildings })
+}
+
+/// Lists the battle reports of a watched player, newest first.
+///
+/// Pages work like [`combat_operations::list_reports`].
+#[instrument(skip(conn))]
+pub fn list_battles(
+	conn: &mut DbConn,
+	observer: &Observer,
+	player_id: &PlayerKey,
+	page: Option<i64>,
+	per_page: Option<i64>,
+) -> Result<ReportPage> {
+	require_scope(observer, ObserverScope::Battles)?;
+	let player = watched_player(conn, observer, player_id)?;
+	combat_operations::list_reports(conn, &player.id, page, per_page)
+}
+
+/// Ranks the watched players by the levels of their buildings, then by battles won.
+#[instrument(skip(conn))]
+pub fn leaderboard(conn: &mut DbConn, observer: &Observer) -> Result<Vec<LeaderboardEntry>> {
+	require_scope(observer, ObserverScope::Leaderboard)?;
+	let mut entries = observers::get_watched_players(conn, &observer.id)?
+		.into_iter()
+		.map(|player| {
+			Ok(LeaderboardEntry {
+				rank: 0,
+				building_levels: player_buildings::sum_levels(conn, &player.id)?,
+				battles_won: battle_reports::count_wins(conn, &player.id)?,
+				player,
+			})
+		})
+		.collect::<Result<Vec<_>>>()?;
+	entries.sort_by(|a, b| {
+		b.building_levels
+			.cmp(&a.building_levels)
+			.then(b.battles_won.cmp(&a.battles_won))
+			.then_with(|| a.player.name.cmp(&b.player.name))
+	});
+	for (idx, entry) in entries.iter_mut().enumerate() {
+		entry.rank = idx + 1;
+	}
+	Ok(entries)
+}
+
+/// Retrieves a player the observer can watch, reporting any other player as not found.
+fn watched_player(conn: &mut DbConn, observer: &Observer, player_id: &PlayerKey) -> Result<Player> {
+	observers::find_watched_player(conn, &observer.id, player_id)?
+		.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Player not found")))
+}
@@ -0,0 +1,112 @@
+//! Onboarding of new players.
+//!
+//! Every path that creates a player, registration and the user API alike, goes through
+//! [`create_player`], so new players always start out the same way. The database creates
+//! their privacy settings with the player row, [`provision_player`] then gives them their
+//! faction's starter kit: the capital with its default resources, accumulator and starter
+//! buildings. [`on_player_created`] grants the configured [`WelcomePack`] and starts the
+//! beginner shield, all in the same transaction as the insert.
+//!
+//! Production needs no job of its own, the recurring production ticks pick up every player
+//! of their shard from the next tick on.
+//!
+//! New players need no quest assigned, the first quests of the tutorial chain are open to
+//! every player, see [`crate::game::quests::quest_operations`].
+//!
+//! AIDEV-NOTE: The game sends no welcome message yet. Once it does, send it from
+//! [`on_player_created`].
+
+use chrono::{DateTime, Utc};
+use diesel::Connection;
+use tracing::{info, instrument, warn};
+
+use crate::configuration::{OnboardingSettings, ProtectionSettings, WelcomePack};
+use crate::db::{DbConn, items, player_items, players, resources};
+use crate::domain::error::Result;
+use crate::domain::player::{NewPlayer, Player};
+use crate::game::combat::protection_operations;
+use crate::game::player_operations::provision_player;
+
+/// A freshly created player and what their onboarding granted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OnboardedPlayer {
+	pub player: Player,
+	/// End of the beginner shield, `None` if the shield is disabled
+	pub protected_until: Option<DateTime<Utc>>,
+	/// Welcome pack items granted, by name
+	pub items: Vec<(String, i64)>,
+}
+
+/// Creates a player, provisions their starter kit and onboards them, see
+/// [`on_player_created`].
+///
+/// Nothing is kept if any step fails, so a player never exists without their onboarding.
+#[instrument(skip(conn, protection, onboarding, new_player), fields(name = %new_player.name))]
+pub fn create_player(
+	conn: &mut DbConn,
+	protection: &ProtectionSettings,
+	onboarding: &OnboardingSettings,
+	new_player: NewPlayer,
+) -> Result<OnboardedPlayer> {
+	conn.transaction(|conn| {
+		let player = players::create(conn, new_player)?;
+		provision_player(conn, &player)?;
+		on_player_created(conn, protection, onboarding, &player)
+	})
+}
+
+/// Onboards a player who was just created and provisioned: grants the welcome pack and starts the
+/// beginner shield.
+#[instrument(skip(conn, protection, onboarding, player), fields(player_id = %player.id))]
+pub fn on_player_created(
+	conn: &mut DbConn,
+	protection: &ProtectionSettings,
+	onboarding: &OnboardingSettings,
+	player: &Player,
+) -> Result<OnboardedPlayer> {
+	let items = grant_welcome_pack(conn, &onboarding.welcome_pack, player)?;
+	let protected_until =
+		protection_operations::grant_beginner_shield(conn, protection, &player.id)?;
+	let player = players::get_by_id(conn, &player.id)?;
+	info!(
+		"Onboarded player {} with {} welcome items",
+		player.id,
+		items.len()
+	);
+	Ok(OnboardedPlayer {
+		player,
+		protected_until,
+		items,
+	})
+}
+
+/// Adds the welcome pack's resources and items to the player.
+///
+/// The resources are a one-off gift like the starter resources, so they are not capped by
+/// the player's storage.
+fn grant_welcome_pack(
+	conn: &mut DbConn,
+	pack: &WelcomePack,
+	player: &Player,
+) -> Result<Vec<(String, i64)>> {
+	let delta = (pack.food, pack.wood, pack.stone, pack.gold);
+	if delta != (0, 0, 0, 0) {
+		resources::add(conn, &player.id, &delta)?;
+	}
+
+	let names: Vec<&str> = pack.items.iter().map(|item| item.name.as_str()).collect();
+	let known = items::get_all_by_name(conn, &names)?;
+	let mut granted = Vec::with_capacity(pack.items.len());
+	for welcome_item in pack.items.iter().filter(|item| item.quantity > 0) {
+		let Some(item) = known.iter().find(|item| item.name == welcome_item.name) else {
+			warn!(
+				"Welcome pack item {:?} does not exist, skipping it",
+				welcome_item.name
+			);
+			continue;
+		};
+		player_items::add_items(conn, &player.id, &item.id, welcome_item.quantity)?;
+		granted.push((item.name.clone(), welcome_item.quantity));
+	}
+	Ok(granted)
+}
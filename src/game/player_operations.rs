@@ -1,16 +1,25 @@
-use axum::http::StatusCode;
-use chrono::Utc;
-use tracing::{debug, error, info, warn};
+use diesel::Connection;
+use tracing::{debug, error, info, instrument, warn};
 
 use crate::auth::utils::hash_password;
 use crate::controllers::user::UpdateUserPayload;
-use crate::db::{DbConn, players};
+use crate::db::{DbConn, player_privacy, players, settlements};
 use crate::domain::factions::FactionCode;
 use crate::domain::player;
+use crate::domain::player::privacy::{PlayerPrivacy, UpdatePlayerPrivacy};
 use crate::domain::player::{Player, PlayerKey, UpdatePlayer};
-use crate::game::resources::resource_scheduler::ProductionScheduler;
+use crate::domain::settlement::Settlement;
+use crate::game::modifiers::modifier_cache::ModifierCache;
 use crate::{Error, ErrorKind, Result};
 
+/// The starter kit of a player, see [`provision_player`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProvisionedPlayer {
+	pub capital: Settlement,
+	/// Number of starter buildings added, none for neutral players
+	pub starter_buildings: usize,
+}
+
 /// Wrapper for player id and payload
 struct UpdateUserId(PlayerKey, UpdateUserPayload);
 
@@ -47,59 +56,52 @@ impl TryFrom<UpdateUserId> for UpdatePlayer {
 	}
 }
 
-pub fn get_player(conn: &mut DbConn, player_key: &PlayerKey) -> Result<Player, StatusCode> {
-	players::get_by_id(conn, player_key).map_err(|err| {
-		warn!(player_id = %player_key, error = %err, "Failed to get user");
-		StatusCode::NOT_FOUND
+pub fn get_player(conn: &mut DbConn, player_key: &PlayerKey) -> Result<Player> {
+	players::find_by_id(conn, player_key)?.ok_or_else(|| {
+		warn!(player_id = %player_key, "Failed to get user");
+		Error::from((ErrorKind::NotFoundError, "Player not found"))
 	})
 }
 
+/// Updates a player's profile.
+///
+/// Changing factions swaps the player's faction modifiers, so their cached multipliers are
+/// invalidated. Neutral players joining a faction are provisioned its starter buildings.
 pub fn update_player(
 	conn: &mut DbConn,
-	scheduler: &ProductionScheduler,
+	modifier_cache: &ModifierCache,
 	player_key: PlayerKey,
 	payload: UpdateUserPayload,
-) -> Result<Player, StatusCode> {
-	let changeset: UpdatePlayer = match UpdatePlayer::try_from(UpdateUserId(player_key, payload)) {
-		Ok(update) => update,
-		Err(err) => {
+) -> Result<Player> {
+	let changeset =
+		UpdatePlayer::try_from(UpdateUserId(player_key, payload)).inspect_err(|err| {
 			warn!(player_id = %player_key, error = %err, "User update validation failed");
-			return Err(StatusCode::BAD_REQUEST);
-		}
-	};
+		})?;
 
-	let user = players::get_by_id(conn, &player_key).map_err(|err| {
-		error!(player_id = %player_key, error = %err, "User not found for update");
-		StatusCode::NOT_FOUND
-	})?;
+	let user = get_player(conn, &player_key)?;
 
 	debug!(player_id = %player_key, "Found existing user, applying changes");
 
-	let updated_user = players::update(conn, &changeset).map_err(|err| {
-		error!(player_id = %player_key, error = %err, "Failed to update player in database");
-		StatusCode::INTERNAL_SERVER_ERROR
-	})?;
+	let updated_user = conn
+		.transaction(|conn| {
+			let updated = players::update(conn, &changeset)?;
+			// Players leaving the neutral faction get its starter buildings
+			if user.faction == FactionCode::Neutral && updated.faction != FactionCode::Neutral {
+				provision_player(conn, &updated)?;
+			}
+			Ok::<_, Error>(updated)
+		})
+		.inspect_err(|err| {
+			error!(player_id = %player_key, error = %err, "Failed to update player in database");
+		})?;
 
 	// Track state changes for key fields
 	let name_changed = changeset.name.is_some();
 	let email_changed = changeset.email.is_some();
 	let password_changed = changeset.pwd_hash.is_some();
 	let faction_changed = changeset.faction.is_some() && changeset.faction != Some(user.faction);
-
-	if faction_changed && user.faction == FactionCode::Neutral {
-		debug!(
-			player_id = %player_key,
-			old_faction = ?user.faction,
-			new_faction = ?updated_user.faction,
-			"Faction changed from Neutral, scheduling production"
-		);
-
-		scheduler
-			.schedule_production(&player_key, Utc::now())
-			.map_err(|err| {
-				error!(player_id = %player_key, error = %err, "Failed to schedule production after faction change");
-				StatusCode::INTERNAL_SERVER_ERROR
-			})?;
+	if faction_changed {
+		modifier_cache.invalidate_user(player_key);
 	}
 
 	info!(
@@ -113,3 +115,48 @@ pub fn update_player(
 
 	Ok(updated_user)
 }
+
+/// Provisions the starter kit of a player: their capital with its storage, accumulator and
+/// the starter buildings of their faction.
+///
+/// Runs when a player registers, and again when a neutral player joins their first faction,
+/// which only adds what is missing. Everything is provisioned in one transaction, so a player
+/// is never left with part of the kit.
+#[instrument(skip(conn, player), fields(player_id = %player.id))]
+pub fn provision_player(conn: &mut DbConn, player: &Player) -> Result<ProvisionedPlayer> {
+	conn.transaction(|conn| {
+		let capital = settlements::found_capital(conn, &player.id)?;
+		let starter_buildings = settlements::stock(conn, &capital)?;
+		debug!(
+			"Provisioned player {} of faction {} with {} starter buildings",
+			player.id, player.faction, starter_buildings
+		);
+		Ok(ProvisionedPlayer {
+			capital,
+			starter_buildings,
+		})
+	})
+}
+
+/// Returns what a player hides from other players.
+pub fn get_privacy(conn: &mut DbConn, player_key: &PlayerKey) -> Result<PlayerPrivacy> {
+	player_privacy::get_by_player_id(conn, player_key)
+}
+
+/// Changes what a player hides from other players, leaving unset settings as they are.
+pub fn update_privacy(
+	conn: &mut DbConn,
+	player_key: &PlayerKey,
+	changeset: UpdatePlayerPrivacy,
+) -> Result<PlayerPrivacy> {
+	let privacy = player_privacy::update(conn, player_key, &changeset)?;
+	info!(
+		player_id = %player_key,
+		hide_online_status = privacy.hide_online_status,
+		hide_stats = privacy.hide_stats,
+		hide_alliance = privacy.hide_alliance,
+		allow_observers = privacy.allow_observers,
+		"Updated privacy settings"
+	);
+	Ok(privacy)
+}
@@ -0,0 +1,9 @@
+//! Quest operations for the Empire game.
+//!
+//! This module defines quest chains, where quests unlock once others are completed, and
+//! branching choices that set permanent flags on a player's progression. The seeded tutorial
+//! quests are progressed by the game events and claimed for their rewards.
+
+pub mod quest_chains;
+pub mod quest_listener;
+pub mod quest_operations;
@@ -0,0 +1,392 @@
+//! Quest chains with prerequisites and branching choices.
+//!
+//! Quests are authored in a JSON data file, read into a [`QuestBook`]. A quest unlocks once
+//! every quest it `requires` is completed and the player's progression holds the flags it
+//! `requires_flags` and none it `excludes_flags`. Completing a quest with `choices` picks one
+//! of them, which sets that choice's flag permanently, so later quests can branch on it.
+//! ```json
+//! [
+//!   { "id": "village_fire", "name": "Fire in the Village",
+//!     "choices": [
+//!       { "id": "rebuild", "description": "Rebuild the mill", "sets_flag": "mill_rebuilt" },
+//!       { "id": "salvage", "description": "Sell the timber", "sets_flag": "timber_sold" }
+//!     ] },
+//!   { "id": "grand_mill", "name": "The Grand Mill",
+//!     "requires": ["village_fire"], "requires_flags": ["mill_rebuilt"] }
+//! ]
+//! ```
+//!
+//! [`QuestBook::lint`] reports authoring mistakes, like unknown prerequisites, cycles in a
+//! chain or flags no choice sets, and [`QuestBook::from_json`] refuses files with any.
+//!
+//! The seeded quests are read into a quest book by
+//! [`crate::game::quests::quest_operations`], which offers [`QuestBook::available`] quests
+//! only and completes them when they are claimed.
+//!
+//! AIDEV-NOTE: The `quest` table stores no choices or flags yet, so the seeded chains do not
+//! branch on choices. Once they do, store the flags of a [`QuestProgress`] along with the
+//! claimed quests in `player_quest`.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+use crate::domain::error::{Error, ErrorKind, Result};
+
+/// A single quest of a chain.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct QuestDefinition {
+	pub id: String,
+	pub name: String,
+	/// Quests that have to be completed first
+	#[serde(default)]
+	pub requires: Vec<String>,
+	/// Flags the player's progression has to hold
+	#[serde(default)]
+	pub requires_flags: Vec<String>,
+	/// Flags locking the quest away, like the flag of the other branch of a choice
+	#[serde(default)]
+	pub excludes_flags: Vec<String>,
+	/// Choices completing the quest, one of which has to be picked if there are any
+	#[serde(default)]
+	pub choices: Vec<QuestChoice>,
+}
+
+/// A branching choice completing a quest.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct QuestChoice {
+	pub id: String,
+	pub description: String,
+	/// Flag set permanently on the player's progression when the choice is picked
+	pub sets_flag: String,
+}
+
+/// The quests a player completed and the flags their choices set.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct QuestProgress {
+	pub completed: BTreeSet<String>,
+	pub flags: BTreeSet<String>,
+}
+
+/// Every quest of the game, checked for authoring mistakes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuestBook {
+	quests: Vec<QuestDefinition>,
+}
+
+impl QuestBook {
+	/// Reads the quests of a data file.
+	///
+	/// Fails if the file is malformed or [`QuestBook::lint`] reports any issue.
+	pub fn from_json(json: &str) -> Result<Self> {
+		let quests: Vec<QuestDefinition> = serde_json::from_str(json).map_err(|err| {
+			Error::from((
+				ErrorKind::InvalidData,
+				"Malformed quest data",
+				err.to_string(),
+			))
+		})?;
+		Self::new(quests)
+	}
+
+	/// Collects quests into a book, in the order given.
+	///
+	/// Fails if [`QuestBook::lint`] reports any issue.
+	pub fn new(quests: Vec<QuestDefinition>) -> Result<Self> {
+		let book = Self { quests };
+		let issues = book.lint();
+		if !issues.is_empty() {
+			debug!("Quest data has {} issues", issues.len());
+			return Err(Error::from((
+				ErrorKind::InvalidData,
+				"Invalid quest data",
+				issues.join("; "),
+			)));
+		}
+		trace!("Read {} quests", book.quests.len());
+		Ok(book)
+	}
+
+	/// Finds a quest by its ID.
+	pub fn quest(&self, quest_id: &str) -> Option<&QuestDefinition> {
+		self.quests.iter().find(|quest| quest.id == quest_id)
+	}
+
+	/// Lists the authoring mistakes of the quests, empty if there are none.
+	pub fn lint(&self) -> Vec<String> {
+		let mut issues = Vec::new();
+		let mut ids = HashSet::new();
+		for quest in &self.quests {
+			if !ids.insert(quest.id.as_str()) {
+				issues.push(format!("Quest {} is defined twice", quest.id));
+			}
+		}
+
+		let set_flags: HashSet<&str> = self
+			.quests
+			.iter()
+			.flat_map(|quest| &quest.choices)
+			.map(|choice| choice.sets_flag.as_str())
+			.collect();
+		for quest in &self.quests {
+			for required in &quest.requires {
+				if !ids.contains(required.as_str()) {
+					issues.push(format!(
+						"Quest {} requires unknown quest {}",
+						quest.id, required
+					));
+				}
+			}
+			for flag in quest.requires_flags.iter().chain(&quest.excludes_flags) {
+				if !set_flags.contains(flag.as_str()) {
+					issues.push(format!(
+						"Quest {} depends on flag {}, which no choice sets",
+						quest.id, flag
+					));
+				}
+			}
+			if let Some(flag) = quest
+				.requires_flags
+				.iter()
+				.find(|flag| quest.excludes_flags.contains(flag))
+			{
+				issues.push(format!(
+					"Quest {} both requires and excludes flag {}",
+					quest.id, flag
+				));
+			}
+			let mut choice_ids = HashSet::new();
+			for choice in &quest.choices {
+				if !choice_ids.insert(choice.id.as_str()) {
+					issues.push(format!("Quest {} has choice {} twice", quest.id, choice.id));
+				}
+			}
+		}
+
+		issues.extend(
+			self.cycles()
+				.into_iter()
+				.map(|quest_id| format!("Quest {quest_id} requires itself through its chain")),
+		);
+		issues
+	}
+
+	/// Quests the player can take on, in the order of the data file.
+	pub fn available(&self, progress: &QuestProgress) -> Vec<&QuestDefinition> {
+		self.quests
+			.iter()
+			.filter(|quest| !progress.completed.contains(&quest.id))
+			.filter(|quest| is_unlocked(quest, progress))
+			.collect()
+	}
+
+	/// Completes a quest, picking one of its choices if it has any.
+	///
+	/// Fails if the quest is unknown, already completed or still locked, or the choice does
+	/// not match the quest.
+	pub fn complete(
+		&self,
+		progress: &mut QuestProgress,
+		quest_id: &str,
+		choice_id: Option<&str>,
+	) -> Result<()> {
+		let quest = self
+			.quest(quest_id)
+			.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Quest not found")))?;
+		if progress.completed.contains(&quest.id) {
+			return Err(Error::from((
+				ErrorKind::InvalidData,
+				"Quest already completed",
+			)));
+		}
+		if !is_unlocked(quest, progress) {
+			return Err(Error::from((ErrorKind::InvalidData, "Quest is locked")));
+		}
+
+		let flag = match (quest.choices.is_empty(), choice_id) {
+			(true, None) => None,
+			(true, Some(_)) => {
+				return Err(Error::from((
+					ErrorKind::InvalidData,
+					"Quest has no choices",
+				)));
+			}
+			(false, None) => {
+				return Err(Error::from((
+					ErrorKind::InvalidData,
+					"Quest requires a choice",
+				)));
+			}
+			(false, Some(choice_id)) => {
+				let choice = quest
+					.choices
+					.iter()
+					.find(|choice| choice.id == choice_id)
+					.ok_or_else(|| Error::from((ErrorKind::InvalidData, "Unknown quest choice")))?;
+				Some(choice.sets_flag.clone())
+			}
+		};
+
+		debug!("Completed quest {} with choice {:?}", quest.id, choice_id);
+		progress.completed.insert(quest.id.clone());
+		progress.flags.extend(flag);
+		Ok(())
+	}
+
+	/// Quests that require themselves through their chain of prerequisites.
+	fn cycles(&self) -> Vec<&str> {
+		// Quests defined twice are reported on their own, their first definition counts
+		let mut requires: HashMap<&str, &[String]> = HashMap::new();
+		for quest in &self.quests {
+			requires
+				.entry(quest.id.as_str())
+				.or_insert(quest.requires.as_slice());
+		}
+
+		self.quests
+			.iter()
+			.filter(|quest| {
+				let mut seen = HashSet::new();
+				let mut open: Vec<&str> = quest.requires.iter().map(String::as_str).collect();
+				while let Some(current) = open.pop() {
+					if current == quest.id {
+						return true;
+					}
+					if seen.insert(current) {
+						open.extend(
+							requires
+								.get(current)
+								.into_iter()
+								.flat_map(|next| next.iter().map(String::as_str)),
+						);
+					}
+				}
+				false
+			})
+			.map(|quest| quest.id.as_str())
+			.collect()
+	}
+}
+
+/// Whether the prerequisites and flags of a quest are met.
+fn is_unlocked(quest: &QuestDefinition, progress: &QuestProgress) -> bool {
+	quest
+		.requires
+		.iter()
+		.all(|required| progress.completed.contains(required))
+		&& quest
+			.requires_flags
+			.iter()
+			.all(|flag| progress.flags.contains(flag))
+		&& !quest
+			.excludes_flags
+			.iter()
+			.any(|flag| progress.flags.contains(flag))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const VILLAGE_CHAIN: &str = r#"[
+		{ "id": "village_fire", "name": "Fire in the Village",
+		  "choices": [
+			{ "id": "rebuild", "description": "Rebuild the mill", "sets_flag": "mill_rebuilt" },
+			{ "id": "salvage", "description": "Sell the timber", "sets_flag": "timber_sold" }
+		  ] },
+		{ "id": "grand_mill", "name": "The Grand Mill",
+		  "requires": ["village_fire"], "requires_flags": ["mill_rebuilt"] },
+		{ "id": "timber_baron", "name": "Timber Baron",
+		  "requires": ["village_fire"], "excludes_flags": ["mill_rebuilt"] },
+		{ "id": "harvest", "name": "Harvest", "requires": ["grand_mill"] }
+	]"#;
+
+	fn available_ids<'a>(book: &'a QuestBook, progress: &QuestProgress) -> Vec<&'a str> {
+		book.available(progress)
+			.into_iter()
+			.map(|quest| quest.id.as_str())
+			.collect()
+	}
+
+	#[test]
+	fn test_choices_branch_the_chain() {
+		let book = QuestBook::from_json(VILLAGE_CHAIN).unwrap();
+		let mut progress = QuestProgress::default();
+		assert_eq!(available_ids(&book, &progress), vec!["village_fire"]);
+
+		let err = book
+			.complete(&mut progress, "grand_mill", None)
+			.expect_err("The chain starts with the fire");
+		assert!(err.to_string().contains("Quest is locked"), "{err}");
+		let err = book
+			.complete(&mut progress, "village_fire", None)
+			.expect_err("The fire needs a choice");
+		assert!(err.to_string().contains("requires a choice"), "{err}");
+
+		book.complete(&mut progress, "village_fire", Some("rebuild"))
+			.unwrap();
+		assert!(progress.flags.contains("mill_rebuilt"));
+		assert_eq!(available_ids(&book, &progress), vec!["grand_mill"]);
+		let err = book
+			.complete(&mut progress, "village_fire", Some("salvage"))
+			.expect_err("Choices are permanent");
+		assert!(err.to_string().contains("already completed"), "{err}");
+
+		book.complete(&mut progress, "grand_mill", None).unwrap();
+		assert_eq!(available_ids(&book, &progress), vec!["harvest"]);
+
+		// The other branch unlocks the other quest
+		let mut salvaged = QuestProgress::default();
+		book.complete(&mut salvaged, "village_fire", Some("salvage"))
+			.unwrap();
+		assert_eq!(available_ids(&book, &salvaged), vec!["timber_baron"]);
+	}
+
+	#[test]
+	fn test_authoring_mistakes_are_reported() {
+		let book = QuestBook {
+			quests: serde_json::from_str(
+				r#"[
+					{ "id": "a", "name": "A", "requires": ["c"] },
+					{ "id": "b", "name": "B", "requires": ["a", "missing"],
+					  "requires_flags": ["never_set"] },
+					{ "id": "c", "name": "C", "requires": ["b"],
+					  "choices": [
+						{ "id": "x", "description": "X", "sets_flag": "f" },
+						{ "id": "x", "description": "Y", "sets_flag": "g" }
+					  ] },
+					{ "id": "a", "name": "Another A" }
+				]"#,
+			)
+			.unwrap(),
+		};
+		let issues = book.lint();
+		for expected in [
+			"Quest a is defined twice",
+			"Quest b requires unknown quest missing",
+			"Quest b depends on flag never_set, which no choice sets",
+			"Quest c has choice x twice",
+			"Quest a requires itself through its chain",
+			"Quest c requires itself through its chain",
+		] {
+			assert!(
+				issues.iter().any(|issue| issue == expected),
+				"Missing {expected:?} in {issues:?}"
+			);
+		}
+
+		let err = QuestBook::from_json(r#"[{ "id": "a", "name": "A", "requires": ["a"] }]"#)
+			.expect_err("A quest cannot require itself");
+		assert!(err.to_string().contains("requires itself"), "{err}");
+		assert!(QuestBook::from_json(r#"[{ "id": "a", "title": "A" }]"#).is_err());
+		assert!(
+			QuestBook::from_json(VILLAGE_CHAIN)
+				.unwrap()
+				.lint()
+				.is_empty()
+		);
+	}
+}
@@ -0,0 +1,42 @@
+//! Counts the game events published on the event bus towards the quests open to the players.
+
+use tokio::sync::broadcast::Receiver;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, warn};
+
+use crate::Result;
+use crate::domain::app_state::AppPool;
+use crate::domain::events::GameEvent;
+use crate::game::quests::quest_operations;
+
+/// Records the progress of every event received on `events` until `token` is cancelled.
+///
+/// Events missed while the listener lags behind the bus are not counted, though building and
+/// collecting quests catch up when they are next listed or claimed.
+pub async fn listen(pool: AppPool, mut events: Receiver<GameEvent>, token: CancellationToken) {
+	loop {
+		tokio::select! {
+			_ = token.cancelled() => {
+				debug!("Quest listener shutting down");
+				break;
+			}
+			received = events.recv() => match received {
+				Ok(event) => {
+					if let Err(err) = record(&pool, &event) {
+						error!("Failed to record the progress of {:?}: {}", event, err);
+					}
+				}
+				Err(RecvError::Lagged(skipped)) => {
+					warn!("Quest listener lagged, skipped {} events", skipped);
+				}
+				Err(RecvError::Closed) => break,
+			},
+		}
+	}
+}
+
+fn record(pool: &AppPool, event: &GameEvent) -> Result<usize> {
+	let mut conn = pool.get()?;
+	quest_operations::record_progress(&mut conn, event)
+}
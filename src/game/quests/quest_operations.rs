@@ -0,0 +1,317 @@
+//! Quest operations for the Empire game.
+//!
+//! The seeded quests are read into a [`QuestBook`], so a quest is open to a player once every
+//! quest it requires is claimed. Players progress their open quests by playing: completed
+//! upgrades progress building quests, finished trainings progress training quests, and
+//! collections progress collecting quests, see [`record_progress`]. Once a quest reaches the
+//! amount of its objective, the player claims its rewards with [`claim_quest`].
+//!
+//! Building and collecting quests keep the highest level or stored amount reached, so spending
+//! the collected wood does not undo the quest. They are also checked against the player's
+//! current buildings and storage whenever quests are listed or claimed, so a quest whose
+//! objective was reached before it unlocked completes without waiting for the next event.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use diesel::Connection;
+use serde::Serialize;
+use tracing::{debug, info, instrument, trace};
+
+use crate::db::{DbConn, player_items, quests, resources, units};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::events::GameEvent;
+use crate::domain::player::PlayerKey;
+use crate::domain::player::resource::ResourceType;
+use crate::domain::quest::{PlayerQuest, Quest, QuestObjective};
+use crate::game::quests::quest_chains::{QuestBook, QuestDefinition, QuestProgress};
+
+/// Where a player stands with a quest they unlocked.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestState {
+	/// The objective is not reached yet
+	Active,
+	/// The objective is reached and the rewards can be claimed
+	Completed,
+	/// The rewards were claimed
+	Claimed,
+}
+
+/// A quest unlocked by a player, with their progress towards it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuestStatus {
+	pub quest: Quest,
+	/// Progress towards the amount of the objective, capped at the amount
+	pub progress: i64,
+	pub state: QuestState,
+	pub claimed_at: Option<DateTime<Utc>>,
+}
+
+/// Lists the quests open to a player and the ones they claimed, in the order of the quest log.
+#[instrument(skip(conn))]
+pub fn list_quests(conn: &mut DbConn, player_id: &PlayerKey) -> Result<Vec<QuestStatus>> {
+	let quests = quests::get_all(conn)?;
+	let book = quest_book(&quests)?;
+	let progress = quests::get_for_player(conn, player_id)?;
+	let open: Vec<&str> = book
+		.available(&quest_progress(&progress))
+		.into_iter()
+		.map(|quest| quest.id.as_str())
+		.collect();
+	let by_quest: HashMap<&str, &PlayerQuest> = progress
+		.iter()
+		.map(|progress| (progress.quest_id.as_str(), progress))
+		.collect();
+
+	let mut statuses = Vec::new();
+	for quest in &quests {
+		let status = match by_quest.get(quest.id.as_str()) {
+			Some(progress) if progress.is_claimed() => QuestStatus {
+				quest: quest.clone(),
+				progress: progress.progress.min(quest.amount),
+				state: QuestState::Claimed,
+				claimed_at: progress.claimed_at,
+			},
+			progress if open.contains(&quest.id.as_str()) => {
+				let stored = progress.map_or(0, |progress| progress.progress);
+				unclaimed_status(conn, player_id, quest, stored)?
+			}
+			_ => continue,
+		};
+		statuses.push(status);
+	}
+	trace!("Player {} has {} quests", player_id, statuses.len());
+	Ok(statuses)
+}
+
+/// Counts the quests of a player whose objective is reached and whose rewards wait to be
+/// claimed.
+#[instrument(skip(conn))]
+pub fn unclaimed_count(conn: &mut DbConn, player_id: &PlayerKey) -> Result<i64> {
+	let completed = list_quests(conn, player_id)?
+		.into_iter()
+		.filter(|status| status.state == QuestState::Completed)
+		.count();
+	Ok(completed as i64)
+}
+
+/// Counts a game event towards the quests open to its player.
+///
+/// # Returns
+/// The number of quests the event progressed
+#[instrument(skip(conn))]
+pub fn record_progress(conn: &mut DbConn, event: &GameEvent) -> Result<usize> {
+	let objective = match event {
+		GameEvent::UpgradeCompleted { .. } => QuestObjective::Build,
+		GameEvent::TrainingCompleted { .. } => QuestObjective::Train,
+		GameEvent::ResourcesCollected { .. } => QuestObjective::Collect,
+		GameEvent::TrainingStarted { .. }
+		| GameEvent::ModifierExpired { .. }
+		| GameEvent::AttackIncoming { .. } => return Ok(0),
+	};
+	let player_id = event.player_id();
+
+	conn.transaction(|conn| {
+		let quests = quests::get_all(conn)?;
+		let book = quest_book(&quests)?;
+		let progress = quest_progress(&quests::get_for_player(conn, player_id)?);
+		let open: Vec<&Quest> = book
+			.available(&progress)
+			.into_iter()
+			.filter_map(|definition| quests.iter().find(|quest| quest.id == definition.id))
+			.filter(|quest| quest.objective == objective)
+			.collect();
+		if open.is_empty() {
+			return Ok(0);
+		}
+
+		let mut progressed = 0;
+		for quest in open {
+			let updated = match event {
+				GameEvent::UpgradeCompleted { .. } => {
+					let level = quests::max_building_level(conn, player_id, &quest.target)?;
+					quests::raise_progress(conn, player_id, &quest.id, level)?
+				}
+				GameEvent::TrainingCompleted {
+					unit_id, quantity, ..
+				} => {
+					if units::get_by_id(conn, unit_id)?.name != quest.target {
+						continue;
+					}
+					quests::add_progress(conn, player_id, &quest.id, *quantity)?
+				}
+				GameEvent::ResourcesCollected {
+					food,
+					wood,
+					stone,
+					gold,
+					..
+				} => {
+					let stored =
+						amount_of(collected_resource(quest)?, (*food, *wood, *stone, *gold));
+					quests::raise_progress(conn, player_id, &quest.id, stored)?
+				}
+				_ => continue,
+			};
+			trace!(
+				"Player {} progressed quest {} to {}",
+				player_id, quest.id, updated.progress
+			);
+			progressed += 1;
+		}
+		Ok(progressed)
+	})
+}
+
+/// Claims the rewards of a quest whose objective the player reached.
+///
+/// Resources are credited to the capital like the starter resources, so they are not capped
+/// by the player's storage. Fails if the quest is unknown, locked or claimed already, or with
+/// [`ErrorKind::QuestIncompleteError`] while the objective is not reached.
+#[instrument(skip(conn))]
+pub fn claim_quest(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	quest_id: &str,
+	now: DateTime<Utc>,
+) -> Result<QuestStatus> {
+	conn.transaction(|conn| {
+		let quests = quests::get_all(conn)?;
+		let book = quest_book(&quests)?;
+		let progress = quests::get_for_player(conn, player_id)?;
+		let mut claimed = quest_progress(&progress);
+		book.complete(&mut claimed, quest_id, None)?;
+		let quest = quests
+			.into_iter()
+			.find(|quest| quest.id == quest_id)
+			.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Quest not found")))?;
+
+		let stored = progress
+			.iter()
+			.find(|progress| progress.quest_id == quest.id)
+			.map_or(0, |progress| progress.progress);
+		let status = unclaimed_status(conn, player_id, &quest, stored)?;
+		if status.state != QuestState::Completed {
+			return Err(Error::from((
+				ErrorKind::QuestIncompleteError,
+				"Quest objective is not reached yet",
+			)));
+		}
+		let claimed = quests::claim(conn, player_id, &quest.id, status.progress, now)?
+			.ok_or_else(|| Error::from((ErrorKind::InvalidData, "Quest already completed")))?;
+
+		let rewards = quest.reward_resources();
+		if rewards != (0, 0, 0, 0) {
+			resources::add(conn, player_id, &rewards)?;
+		}
+		if let Some(item_id) = quest.reward_item_id
+			&& quest.reward_item_quantity > 0
+		{
+			player_items::add_items(conn, player_id, &item_id, quest.reward_item_quantity)?;
+		}
+		info!("Player {} claimed quest {}", player_id, quest.id);
+
+		Ok(QuestStatus {
+			progress: status.progress,
+			state: QuestState::Claimed,
+			claimed_at: claimed.claimed_at,
+			quest,
+		})
+	})
+}
+
+/// Reads the seeded quests into a quest book, which checks their chains.
+fn quest_book(quests: &[Quest]) -> Result<QuestBook> {
+	QuestBook::new(
+		quests
+			.iter()
+			.map(|quest| QuestDefinition {
+				id: quest.id.clone(),
+				name: quest.name.clone(),
+				requires: quest.requires.clone(),
+				requires_flags: Vec::new(),
+				excludes_flags: Vec::new(),
+				choices: Vec::new(),
+			})
+			.collect(),
+	)
+}
+
+/// The quests a player claimed, which unlock the quests requiring them.
+fn quest_progress(progress: &[PlayerQuest]) -> QuestProgress {
+	QuestProgress {
+		completed: progress
+			.iter()
+			.filter(|progress| progress.is_claimed())
+			.map(|progress| progress.quest_id.clone())
+			.collect(),
+		..Default::default()
+	}
+}
+
+/// Works out where a player stands with an open quest, checking building and collecting
+/// quests against their current buildings and storage on top of the stored progress.
+fn unclaimed_status(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	quest: &Quest,
+	stored: i64,
+) -> Result<QuestStatus> {
+	let current = match quest.objective {
+		QuestObjective::Build => quests::max_building_level(conn, player_id, &quest.target)?,
+		QuestObjective::Train => 0,
+		QuestObjective::Collect => {
+			let storage = resources::get_by_player_id(conn, player_id)?;
+			amount_of(
+				collected_resource(quest)?,
+				(storage.food, storage.wood, storage.stone, storage.gold),
+			)
+		}
+	};
+	let progress = stored.max(current).min(quest.amount);
+	debug!(
+		"Player {} is at {}/{} of quest {}",
+		player_id, progress, quest.amount, quest.id
+	);
+	Ok(QuestStatus {
+		quest: quest.clone(),
+		progress,
+		state: if progress >= quest.amount {
+			QuestState::Completed
+		} else {
+			QuestState::Active
+		},
+		claimed_at: None,
+	})
+}
+
+/// The resource a collecting quest asks for.
+fn collected_resource(quest: &Quest) -> Result<ResourceType> {
+	[
+		ResourceType::Food,
+		ResourceType::Wood,
+		ResourceType::Stone,
+		ResourceType::Gold,
+	]
+	.into_iter()
+	.find(|resource| resource.as_str() == quest.target)
+	.ok_or_else(|| {
+		Error::from((
+			ErrorKind::InvalidData,
+			"Quest collects an unknown resource",
+			format!("Quest {} collects {}", quest.id, quest.target),
+		))
+	})
+}
+
+/// The amount of a resource in a food, wood, stone and gold delta.
+fn amount_of(resource: ResourceType, (food, wood, stone, gold): (i64, i64, i64, i64)) -> i64 {
+	match resource {
+		ResourceType::Food => food,
+		ResourceType::Wood => wood,
+		ResourceType::Stone => stone,
+		ResourceType::Gold => gold,
+		ResourceType::Population => 0,
+	}
+}
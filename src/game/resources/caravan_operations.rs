@@ -0,0 +1,244 @@
+//! Resource shipments between players.
+//!
+//! Sending a caravan takes the cargo out of the sender's storage right away and schedules a
+//! [`JobType::Resource`] job for its arrival. On arrival the receiver is credited as much
+//! of the cargo as fits into their storage, and whatever does not fit goes back to the
+//! sender, so a caravan never destroys resources.
+//!
+//! Travel time grows with the size of the cargo: a caravan takes
+//! [`CARAVAN_BASE_TRAVEL_TIME`], plus [`CARAVAN_TRAVEL_TIME_PER_LOAD`] for every
+//! [`CARAVAN_LOAD_SIZE`] units it carries.
+
+use chrono::{TimeDelta, Utc};
+use diesel::Connection;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, instrument, trace, warn};
+
+use crate::db::{DbConn, caravans, players, resources};
+use crate::domain::caravan::{Caravan, CaravanKey, CaravanStatus, Cargo, NewCaravan};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::jobs::JobType;
+use crate::domain::player::PlayerKey;
+use crate::domain::player::resource::PlayerResource;
+use crate::game::resources::resource_scheduler::ProductionJobPayload;
+use crate::job_queue::{JobPriority, JobQueue};
+
+/// Travel time of every caravan, regardless of its cargo.
+pub const CARAVAN_BASE_TRAVEL_TIME: TimeDelta = TimeDelta::minutes(5);
+
+/// Additional travel time for every full or partial load of cargo.
+pub const CARAVAN_TRAVEL_TIME_PER_LOAD: TimeDelta = TimeDelta::minutes(1);
+
+/// Number of units that make up one load of cargo.
+pub const CARAVAN_LOAD_SIZE: i64 = 1000;
+
+/// Outcome of a caravan's arrival.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CaravanDelivery {
+	/// Cargo credited to the receiver
+	pub delivered: Cargo,
+	/// Cargo that did not fit into the receiver's storage and went back to the sender
+	pub returned: Cargo,
+}
+
+/// Returns how long a caravan carrying `cargo` travels.
+///
+/// Fails with an `InvalidQuantityError` if the cargo is too large to be carried.
+pub fn travel_time(cargo: &Cargo) -> Result<TimeDelta> {
+	let time = cargo
+		.total()
+		.and_then(|total| total.checked_add(CARAVAN_LOAD_SIZE - 1))
+		.and_then(|total| i32::try_from(total / CARAVAN_LOAD_SIZE).ok())
+		.and_then(|loads| CARAVAN_TRAVEL_TIME_PER_LOAD.checked_mul(loads))
+		.and_then(|time| CARAVAN_BASE_TRAVEL_TIME.checked_add(&time));
+	time.ok_or_else(|| Error::from((ErrorKind::InvalidQuantityError, "Cargo is too large")))
+}
+
+/// Sends a caravan with `cargo` from `sender_id` to `receiver_id`.
+///
+/// The cargo is deducted from the sender immediately, and a resource job delivers it once
+/// the caravan arrives.
+#[instrument(skip(conn, job_queue))]
+pub fn send_caravan(
+	conn: &mut DbConn,
+	job_queue: &JobQueue,
+	sender_id: &PlayerKey,
+	receiver_id: &PlayerKey,
+	cargo: Cargo,
+) -> Result<Caravan> {
+	if sender_id == receiver_id {
+		return Err(Error::from((
+			ErrorKind::SendCaravanError,
+			"Cannot send resources to yourself",
+		)));
+	}
+	let amounts = [cargo.food, cargo.wood, cargo.stone, cargo.gold];
+	if amounts.iter().any(|amount| *amount < 0) || cargo.total() == Some(0) {
+		return Err(Error::from((
+			ErrorKind::InvalidQuantityError,
+			"Cargo must be non-negative and not empty",
+		)));
+	}
+	if players::find_by_id(conn, receiver_id)?.is_none() {
+		return Err(Error::from((
+			ErrorKind::NotFoundError,
+			"Receiver not found",
+		)));
+	}
+
+	let arrives_at = Utc::now() + travel_time(&cargo)?;
+	let delta = (cargo.food, cargo.wood, cargo.stone, cargo.gold);
+	let caravan = conn.transaction(|conn| {
+		let held = resources::lock_by_player_id(conn, sender_id)?;
+		if held.food < cargo.food
+			|| held.wood < cargo.wood
+			|| held.stone < cargo.stone
+			|| held.gold < cargo.gold
+		{
+			return Err(Error::from((
+				ErrorKind::InsufficientResourcesError,
+				"Not enough resources",
+			)));
+		}
+		resources::deduct(conn, sender_id, &delta)?;
+		trace!("Loaded cargo: {:?}", cargo);
+
+		caravans::create(
+			conn,
+			NewCaravan {
+				sender_id: *sender_id,
+				receiver_id: *receiver_id,
+				food: cargo.food,
+				wood: cargo.wood,
+				stone: cargo.stone,
+				gold: cargo.gold,
+				arrives_at,
+			},
+		)
+	})?;
+
+	// Schedule the arrival outside the transaction, like every other job of the game
+	let payload = ProductionJobPayload::DeliverCaravan {
+		caravan_id: caravan.id,
+	};
+	let job_id = match job_queue.enqueue(
+		JobType::Resource,
+		payload,
+		JobPriority::Normal,
+		caravan.arrives_at,
+	) {
+		Ok(id) => id,
+		Err(e) => {
+			// AIDEV-NOTE: Without an arrival job the cargo would be lost, so it is unloaded again
+			warn!("Failed to schedule caravan arrival, unloading: {}", e);
+			if let Err(unload_err) = unload_caravan(conn, &caravan) {
+				warn!("Failed to unload caravan {}: {}", caravan.id, unload_err);
+			}
+			return Err(Error::from((
+				ErrorKind::InternalError,
+				"Failed to schedule caravan arrival",
+				format!("{:?}", e),
+			)));
+		}
+	};
+	let caravan = caravans::set_job_id(conn, &caravan.id, &job_id)?;
+
+	info!(
+		"Caravan {} from player {} to player {} arrives at {}",
+		caravan.id, sender_id, receiver_id, caravan.arrives_at
+	);
+	Ok(caravan)
+}
+
+/// Returns a caravan's cargo to its sender and marks it delivered.
+fn unload_caravan(conn: &mut DbConn, caravan: &Caravan) -> Result<()> {
+	conn.transaction(|conn| {
+		let cargo = caravan.cargo();
+		resources::add(
+			conn,
+			&caravan.sender_id,
+			&(cargo.food, cargo.wood, cargo.stone, cargo.gold),
+		)?;
+		caravans::deliver(conn, &caravan.id)?;
+		Ok(())
+	})
+}
+
+/// Splits `amount` into what still fits under `cap` on top of `stored`, and the rest.
+fn fit(amount: i64, stored: i64, cap: i64) -> (i64, i64) {
+	let accepted = amount.min((cap - stored).max(0));
+	(accepted, amount - accepted)
+}
+
+/// Delivers an arrived caravan to its receiver, returning the overflow to the sender.
+///
+/// Delivering a caravan that was already delivered does nothing, so a retried arrival job
+/// never credits the cargo twice.
+#[instrument(skip(conn))]
+pub fn deliver_caravan(conn: &mut DbConn, caravan_id: &CaravanKey) -> Result<CaravanDelivery> {
+	conn.transaction(|conn| {
+		let caravan = caravans::lock_by_id(conn, caravan_id)?;
+		if caravan.status == CaravanStatus::Delivered {
+			debug!("Caravan {} was already delivered", caravan_id);
+			return Ok(CaravanDelivery {
+				delivered: Cargo::default(),
+				returned: Cargo::default(),
+			});
+		}
+
+		// Both players are locked in key order, so crossing caravans cannot deadlock
+		let (first, second) = if caravan.sender_id < caravan.receiver_id {
+			(caravan.sender_id, caravan.receiver_id)
+		} else {
+			(caravan.receiver_id, caravan.sender_id)
+		};
+		let mut locked: Vec<PlayerResource> = Vec::with_capacity(2);
+		for player_id in [first, second] {
+			locked.push(resources::lock_by_player_id(conn, &player_id)?);
+		}
+		let receiver = locked
+			.iter()
+			.find(|res| res.player_id == caravan.receiver_id)
+			.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Receiver not found")))?;
+
+		let cargo = caravan.cargo();
+		let (food, food_back) = fit(cargo.food, receiver.food, receiver.food_cap);
+		let (wood, wood_back) = fit(cargo.wood, receiver.wood, receiver.wood_cap);
+		let (stone, stone_back) = fit(cargo.stone, receiver.stone, receiver.stone_cap);
+		let (gold, gold_back) = fit(cargo.gold, receiver.gold, receiver.gold_cap);
+		let delivery = CaravanDelivery {
+			delivered: Cargo {
+				food,
+				wood,
+				stone,
+				gold,
+			},
+			returned: Cargo {
+				food: food_back,
+				wood: wood_back,
+				stone: stone_back,
+				gold: gold_back,
+			},
+		};
+
+		resources::add(conn, &caravan.receiver_id, &(food, wood, stone, gold))?;
+		if delivery.returned != Cargo::default() {
+			resources::add(
+				conn,
+				&caravan.sender_id,
+				&(food_back, wood_back, stone_back, gold_back),
+			)?;
+		}
+		caravans::deliver(conn, caravan_id)?;
+
+		info!(
+			"Caravan {} delivered {:?} to player {}, returned {:?} to player {}",
+			caravan_id,
+			delivery.delivered,
+			caravan.receiver_id,
+			delivery.returned,
+			caravan.sender_id
+		);
+		Ok(delivery)
+	})
+}
@@ -1,9 +1,13 @@
 use std::collections::HashMap;
 
 use bigdecimal::BigDecimal;
+use serde::Deserialize;
+use serde_aux::prelude::deserialize_number_from_string;
 
 use crate::domain::player::resource::ResourceType;
 
+pub mod caravan_operations;
+pub mod production_processor;
 pub mod resource_operations;
 pub mod resource_processor;
 pub mod resource_scheduler;
@@ -18,3 +22,35 @@ pub type ResourceProductionRate = BigDecimal;
 pub type ResourceMultipliers = HashMap<ResourceType, ResourceMultiplier>;
 /// A mapping of resource types to their corresponding decimal production rates.
 pub type ResourceProductionRates = HashMap<ResourceType, ResourceProductionRate>;
+
+/// What collecting does with accumulated resources that do not fit into storage.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+pub enum OverflowPolicy {
+	/// Overflow stays in the accumulator until storage frees up
+	#[default]
+	LeaveInAccumulator,
+	/// Overflow is dropped from the accumulator
+	Discard,
+	/// Food, wood and stone overflow is sold for `rate` gold per unit, as far as gold storage
+	/// allows. Whatever cannot be sold stays in the accumulator.
+	ConvertToGold {
+		#[serde(deserialize_with = "deserialize_number_from_string")]
+		rate: f64,
+	},
+}
+
+/// What happens when a player's army eats more food than they have left.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+pub enum StarvationPolicy {
+	/// Units starve, the ones eating the most first, until the food production feeds the rest
+	#[default]
+	KillUnits,
+	/// Wood, stone and gold production drops by `penalty`, a share between 0 and 1, for as
+	/// long as the player starves
+	ReduceProduction {
+		#[serde(deserialize_with = "deserialize_number_from_string")]
+		penalty: f64,
+	},
+}
@@ -0,0 +1,116 @@
+use axum::extract::FromRef;
+use tracing::{debug, error, info, instrument, trace};
+
+use crate::Error;
+use crate::domain::app_state::AppState;
+use crate::domain::jobs::{Job, JobType};
+use crate::domain::player::PlayerKey;
+use crate::game::modifiers::modifier_service::ModifierService;
+use crate::game::resources::resource_operations;
+use crate::game::resources::resource_scheduler::ProductionTickPayload;
+use crate::game::resources::resource_service::ResourceService;
+use crate::job_queue::job_processor::{JobHandler, JobOutcome, JobWorker};
+
+/// A handler for the recurring resource production ticks.
+///
+/// Every [`JobType::ResourceProduction`] job produces resources for the players of one
+/// shard, see [`crate::game::resources::resource_scheduler`]. A player whose production
+/// fails does not fail the tick, the failure is counted in the job result instead and the
+/// player catches up on the next tick.
+pub struct ProductionHandler {
+	/// Resource service instance
+	resource_srv: ResourceService,
+	/// Modifier service instance
+	modifier_srv: ModifierService,
+}
+
+/// Workers running the [`ProductionHandler`].
+pub type ProductionProcessor = JobWorker<ProductionHandler>;
+
+impl JobHandler for ProductionHandler {
+	const JOB_TYPE: JobType = JobType::ResourceProduction;
+	const WORKER_NAME: &'static str = "production-goblin";
+
+	/// Creates a new `ProductionHandler` with what it needs from the application state.
+	fn new(app_state: &AppState) -> Self {
+		let resource_srv = ResourceService::from_ref(app_state);
+		let modifier_srv = ModifierService::from_ref(app_state);
+		Self {
+			resource_srv,
+			modifier_srv,
+		}
+	}
+
+	#[instrument(skip(self, job))]
+	async fn handle(&self, job: Job) -> Result<JobOutcome, Error> {
+		debug!("Processing job: {}", job.id);
+		trace!("Job details: {:?}", job);
+
+		let ProductionTickPayload { shard, shards } = serde_json::from_value(job.payload.clone())?;
+		let players = self.resource_srv.get_shard_players(shard, shards)?;
+		debug!(
+			"Producing resources for {} players of shard {}/{}",
+			players.len(),
+			shard,
+			shards
+		);
+
+		let mut failed = 0;
+		for player_id in &players {
+			if let Err(e) = self.produce_resources_for_player(player_id).await {
+				error!(
+					"Failed to produce resources for player {}: {}",
+					player_id, e
+				);
+				trace!("Detailed error: {:?}", e);
+				failed += 1;
+			}
+		}
+		info!(
+			"Produced resources for {} of {} players in shard {}/{}",
+			players.len() - failed,
+			players.len(),
+			shard,
+			shards
+		);
+
+		debug!("Completed process job: {}", job.id);
+		Ok(Some(serde_json::json!({
+			"shard": shard,
+			"produced": players.len() - failed,
+			"failed": failed,
+		})))
+	}
+}
+
+impl ProductionHandler {
+	/// Orchestrates resource production by composing modifier and resource services
+	async fn produce_resources_for_player(&self, player_id: &PlayerKey) -> Result<(), Error> {
+		// Step 1: Fetch all resource modifiers (uses caching)
+		let modifiers = self
+			.modifier_srv
+			.get_resource_multipliers(player_id)
+			.await?;
+
+		// Every settlement produces with its own buildings
+		for settlement in self.resource_srv.get_settlements(player_id)? {
+			// Step 2: Get base rates from database
+			let base_rates = self.resource_srv.get_base_rates(&settlement.id)?;
+
+			// Step 3: Combine base rates with modifiers to get production rates
+			let mut production_rates =
+				resource_operations::apply_rate_modifiers(&base_rates, &modifiers);
+
+			// Step 4: Account for buildings with modifiers of their own
+			self.resource_srv
+				.apply_building_modifiers(&settlement, &mut production_rates)?;
+
+			// Step 5: Produce resources with the calculated rates
+			self.resource_srv
+				.produce(&settlement, &production_rates)
+				.await?;
+		}
+
+		Ok(())
+	}
+}
@@ -1,23 +1,25 @@
-use std::collections::HashMap;
-
-use bigdecimal::{BigDecimal, ToPrimitive};
+use bigdecimal::{BigDecimal, RoundingMode, ToPrimitive, Zero};
 use chrono::{DateTime, Utc};
-use diesel::dsl::now;
 use diesel::prelude::*;
-use diesel::sql_types::Int8;
+use diesel::sql_types::{Bool, Double, Int8};
 use tracing::{debug, trace, warn};
 
 use crate::Result;
-use crate::db::{DbConn, resources};
+use crate::configuration::ResourceSettings;
+use crate::db::{DbConn, player_units, resources};
 use crate::domain::modifier::ModifierTarget;
-use crate::domain::player::PlayerKey;
-use crate::domain::player::accumulator::{AccumulatorKey, PlayerAccumulator};
+use crate::domain::modifier::full_modifier::AppliedModifier;
+use crate::domain::player::accumulator::PlayerAccumulator;
+use crate::domain::player::buildings::PlayerBuildingKey;
 use crate::domain::player::resource::{PlayerResource, ResourceType};
 use crate::domain::resource_generation::ResourceGeneration;
+use crate::domain::settlement::{Settlement, SettlementKey};
 use crate::game::modifiers::modifier_operations;
 use crate::game::resources::{
-	ResourceMultipliers, ResourceProductionRate, ResourceProductionRates,
+	OverflowPolicy, ResourceMultipliers, ResourceProductionRate, ResourceProductionRates,
+	StarvationPolicy,
 };
+use crate::game::units::upkeep_operations;
 
 // AIDEV-NOTE: These SQL functions are not standard in all SQL dialects,
 // but are supported by PostgreSQL. `define_sql_function!` makes them
@@ -27,119 +29,144 @@ define_sql_function! {
 	fn least(a: Int8, b: Int8) -> Int8
 }
 
-/// Collects resources for a player by transferring the maximum possible amount from their
-/// resource accumulator to their resource storage, constrained by the storage capacity limits.
+/// Collects resources in a settlement by transferring the maximum possible amount from its
+/// resource accumulator to its resource storage, constrained by the storage capacity limits.
+/// What happens to the amounts that do not fit is decided by the `overflow` policy.
+///
+/// The transfer is worked out and applied in a single statement that locks both rows, so
+/// the amounts drained from the accumulator always match the amounts credited to storage,
+/// even while production or another collection runs for the same settlement.
 ///
 /// # Arguments
 /// * `conn` - Database connection
-/// * `player_id` - The unique identifier of the player to collect resources for
+/// * `settlement_id` - The unique identifier of the settlement to collect resources in
+/// * `overflow` - What to do with accumulated resources exceeding the storage capacity
 ///
 /// # Returns
 /// The updated [`PlayerResource`] state after collecting
-pub fn collect_resources(conn: &mut DbConn, player_id: &PlayerKey) -> Result<PlayerResource> {
-	// This query calculates the exact amount of each resource that can be moved
-	// from the accumulator to the main storage without exceeding the storage caps.
-	// It uses `LEAST` to take the minimum of what's in the accumulator and the remaining capacity.
-	let (collectible_food, collectible_wood, collectible_stone, collectible_gold) = {
-		use crate::schema::player_accumulator::dsl as pa;
-		use crate::schema::player_resource::dsl as pr;
-
-		pa::player_accumulator
-			.inner_join(pr::player_resource.on(pa::player_id.eq(pr::player_id)))
-			.filter(pa::player_id.nullable().eq(player_id))
-			.select((
-				least(pa::food, pr::food_cap - pr::food),
-				least(pa::wood, pr::wood_cap - pr::wood),
-				least(pa::stone, pr::stone_cap - pr::stone),
-				least(pa::gold, pr::gold_cap - pr::gold),
-			))
-			.first::<(i64, i64, i64, i64)>(conn)?
+pub fn collect_resources(
+	conn: &mut DbConn,
+	settlement_id: &SettlementKey,
+	overflow: OverflowPolicy,
+) -> Result<PlayerResource> {
+	let (discard, rate) = match overflow {
+		OverflowPolicy::LeaveInAccumulator => (false, 0.0),
+		OverflowPolicy::Discard => (true, 0.0),
+		OverflowPolicy::ConvertToGold { rate } if rate.is_finite() && rate > 0.0 => (false, rate),
+		OverflowPolicy::ConvertToGold { .. } => (false, 0.0),
 	};
 
+	// AIDEV-NOTE: `current` locks the accumulator and the storage and computes what fits with
+	// `LEAST`. `settled` sells food, wood and stone overflow in that order, each for as much
+	// gold as is still free after the previous one, a `rate` of 0 selling nothing. `drained`
+	// takes it all out of the accumulator and returns what the storage is credited with.
+	let res: PlayerResource = diesel::sql_query(
+		"WITH current AS ( \
+		 SELECT pa.id AS accumulator_id, \
+		 pa.food AS food_acc, pa.wood AS wood_acc, pa.stone AS stone_acc, pa.gold AS gold_acc, \
+		 LEAST(pa.food, pr.food_cap - pr.food) AS food, \
+		 LEAST(pa.wood, pr.wood_cap - pr.wood) AS wood, \
+		 LEAST(pa.stone, pr.stone_cap - pr.stone) AS stone, \
+		 LEAST(pa.gold, pr.gold_cap - pr.gold) AS gold, \
+		 GREATEST(pr.gold_cap - pr.gold - LEAST(pa.gold, pr.gold_cap - pr.gold), 0) AS gold_space \
+		 FROM player_accumulator pa JOIN player_resource pr ON pr.settlement_id = pa.settlement_id \
+		 WHERE pa.settlement_id = $1 \
+		 FOR UPDATE OF pa, pr \
+		 ), settled AS ( \
+		 SELECT c.*, f.sold AS food_sold, w.sold AS wood_sold, s.sold AS stone_sold, \
+		 f.earned + w.earned + s.earned AS sold_for_gold \
+		 FROM current c \
+		 CROSS JOIN LATERAL ( \
+		 SELECT sold, floor(sold * $3)::int8 AS earned FROM (SELECT CASE WHEN $3 > 0 \
+		 THEN GREATEST(LEAST((c.food_acc - c.food)::float8, floor(c.gold_space / $3)), 0)::int8 \
+		 ELSE 0 END AS sold) x \
+		 ) f \
+		 CROSS JOIN LATERAL ( \
+		 SELECT sold, floor(sold * $3)::int8 AS earned FROM (SELECT CASE WHEN $3 > 0 \
+		 THEN GREATEST(LEAST((c.wood_acc - c.wood)::float8, \
+		 floor((c.gold_space - f.earned) / $3)), 0)::int8 \
+		 ELSE 0 END AS sold) x \
+		 ) w \
+		 CROSS JOIN LATERAL ( \
+		 SELECT sold, floor(sold * $3)::int8 AS earned FROM (SELECT CASE WHEN $3 > 0 \
+		 THEN GREATEST(LEAST((c.stone_acc - c.stone)::float8, \
+		 floor((c.gold_space - f.earned - w.earned) / $3)), 0)::int8 \
+		 ELSE 0 END AS sold) x \
+		 ) s \
+		 ), drained AS ( \
+		 UPDATE player_accumulator pa SET \
+		 food = pa.food - CASE WHEN $2 THEN s.food_acc ELSE s.food + s.food_sold END, \
+		 wood = pa.wood - CASE WHEN $2 THEN s.wood_acc ELSE s.wood + s.wood_sold END, \
+		 stone = pa.stone - CASE WHEN $2 THEN s.stone_acc ELSE s.stone + s.stone_sold END, \
+		 gold = pa.gold - CASE WHEN $2 THEN s.gold_acc ELSE s.gold END \
+		 FROM settled s \
+		 WHERE pa.id = s.accumulator_id \
+		 RETURNING s.food, s.wood, s.stone, s.gold, s.sold_for_gold \
+		 ) \
+		 UPDATE player_resource pr SET \
+		 food = pr.food + d.food, \
+		 wood = pr.wood + d.wood, \
+		 stone = pr.stone + d.stone, \
+		 gold = pr.gold + d.gold + d.sold_for_gold, \
+		 collected_at = now() \
+		 FROM drained d \
+		 WHERE pr.settlement_id = $1 \
+		 RETURNING pr.*",
+	)
+	.bind::<diesel::sql_types::Uuid, _>(settlement_id)
+	.bind::<Bool, _>(discard)
+	.bind::<Double, _>(rate)
+	.get_result(conn)?;
+
 	debug!(
-		"Collectible amounts: Food: {}, Wood: {}, Stone: {}, Gold: {}",
-		collectible_food, collectible_wood, collectible_stone, collectible_gold
+		"Collected resources under {:?}, storage now: Food: {}, Wood: {}, Stone: {}, Gold: {}",
+		overflow, res.food, res.wood, res.stone, res.gold
 	);
-
-	// The resource transfer is performed in a transaction to ensure atomicity.
-	// First, we drain the calculated amounts from the accumulator.
-	// Second, we add those same amounts to the main resource storage.
-	conn.transaction(|conn| {
-		use crate::schema::player_accumulator::dsl as pa;
-		use crate::schema::player_resource::dsl as pr;
-
-		// Drain the accumulator
-		diesel::update(pa::player_accumulator.filter(pa::player_id.eq(player_id)))
-			.set((
-				pa::food.eq(pa::food - collectible_food),
-				pa::wood.eq(pa::wood - collectible_wood),
-				pa::stone.eq(pa::stone - collectible_stone),
-				pa::gold.eq(pa::gold - collectible_gold),
-			))
-			.execute(conn)?;
-
-		// Then, increase the main resource storage
-		diesel::update(pr::player_resource.filter(pr::player_id.eq(player_id)))
-			.set((
-				pr::food.eq(pr::food + collectible_food),
-				pr::wood.eq(pr::wood + collectible_wood),
-				pr::stone.eq(pr::stone + collectible_stone),
-				pr::gold.eq(pr::gold + collectible_gold),
-				pr::collected_at.eq(now),
-			))
-			.returning(PlayerResource::as_returning())
-			.get_result(conn)
-			.map_err(Into::into)
-	})
+	Ok(res)
 }
 
-/// Produces resources for a player based on their production rates and time elapsed since last production.
+/// Produces resources in a settlement based on its production rates and time elapsed since last production.
 ///
 /// This function calculates the amount of resources to produce, applies production rates,
-/// and updates the player's accumulator with the produced resources, respecting storage caps.
+/// and updates the settlement's accumulator with the produced resources, respecting storage caps.
+/// Fractions of a resource are carried over to the next production, kept to
+/// `accrual_precision` decimal places, so low rates still add up to whole resources.
+///
+/// The food upkeep of the player's units is paid by their capital, out of its food
+/// production, then out of its accumulator and finally out of its storage. Once all of it is
+/// eaten, the player starves and the configured [`StarvationPolicy`] applies.
 ///
 /// # Arguments
 /// * `conn` - Database connection
-/// * `player_id` - The unique identifier of the player to produce resources for
+/// * `settlement` - The settlement to produce resources in
 /// * `production_rates` - HashMap of production rates per hour for each resource type
 /// * `up_to_time` - Optional timestamp to produce up to (defaults to now)
+/// * `settings` - The accrual precision and starvation policy to produce with
 ///
 /// # Returns
 /// The updated [`PlayerAccumulator`] state after production
 pub fn produce_resources(
 	conn: &mut DbConn,
-	player_id: &PlayerKey,
+	settlement: &Settlement,
 	production_rates: &ResourceProductionRates,
 	up_to_time: Option<DateTime<Utc>>,
+	settings: &ResourceSettings,
 ) -> Result<PlayerAccumulator> {
 	let target_time = up_to_time.unwrap_or_else(Utc::now);
+	let player_id = &settlement.player_id;
+	let settlement_id = &settlement.id;
 
 	// Get the last production time
-	let last_prod = resources::get_by_player_id(conn, player_id)?.produced_at;
+	let last_prod = resources::get_by_settlement(conn, settlement_id)?.produced_at;
 	let delta = target_time - last_prod;
-	let delta_hours = BigDecimal::from(delta.num_seconds()) / BigDecimal::from(3600);
+	let delta_hours = BigDecimal::from(delta.num_milliseconds()) / BigDecimal::from(3_600_000);
 
 	debug!(
-		"Production Delta: {:.4}h, last produced at: {} for player: {}",
-		delta_hours, last_prod, player_id
+		"Production Delta: {:.4}h, last produced at: {} for settlement: {}",
+		delta_hours, last_prod, settlement_id
 	);
 	debug!("Production Rates: {:?}", production_rates);
 
-	// Calculate production amounts
-	let prod_amounts: HashMap<ResourceType, i64> = production_rates
-		.iter()
-		.map(|(res_type, prod_rate)| {
-			let amount = prod_rate * &delta_hours;
-			let truncated = amount.to_i64().unwrap_or_default();
-			(*res_type, truncated)
-		})
-		.collect();
-
-	debug!(
-		"Producing resources for player {}: {:?}",
-		player_id, prod_amounts
-	);
-
 	// Add generated resources to the player's accumulator, respecting storage caps
 	conn.transaction(|conn| -> Result<PlayerAccumulator> {
 		use crate::custom_schema::resource_generation::dsl as rg;
@@ -148,49 +175,109 @@ pub fn produce_resources(
 
 		trace!("Entering accumulator update transaction");
 
-		// Lock the player's accumulator row to prevent race conditions during the update
-		let acc_key: AccumulatorKey = pa::player_accumulator
-			.select(pa::id)
-			.filter(pa::player_id.eq(player_id))
+		// Lock the settlement's accumulator row to prevent race conditions during the update
+		let acc: PlayerAccumulator = pa::player_accumulator
+			.select(PlayerAccumulator::as_select())
+			.filter(pa::settlement_id.eq(settlement_id))
 			.for_update()
 			.first(conn)?;
-		trace!("Found player accumulator: {:?}", acc_key);
+		trace!("Found player accumulator: {:?}", acc.id);
+
+		let acc_caps: ResourceGeneration =
+			rg::resource_generation.find(settlement_id).first(conn)?;
+		let rate_of =
+			|res_type: ResourceType| production_rates.get(&res_type).cloned().unwrap_or_default();
+
+		// Units eat from the food production of the capital first
+		let food_upkeep = if settlement.is_capital {
+			player_units::get_upkeep(conn, player_id)?.food_per_hour
+		} else {
+			0
+		};
+		let food_rate = rate_of(ResourceType::Food) - BigDecimal::from(food_upkeep);
+		let (food, mut food_remainder) = accrue(
+			&food_rate,
+			&delta_hours,
+			&acc.food_remainder,
+			settings.accrual_precision,
+		);
+
+		// Then from the accumulator and storage, and whatever is missing starves them
+		let stored = resources::lock_by_settlement(conn, settlement_id)?;
+		let eaten_from_acc = (-food).clamp(0, acc.food.max(0));
+		let eaten_from_storage = (-food - eaten_from_acc).clamp(0, stored.food.max(0));
+		let starving = -food > eaten_from_acc + eaten_from_storage;
+		let food = if food < 0 { -eaten_from_acc } else { food };
+
+		let mut penalty = BigDecimal::from(1);
+		if starving {
+			// Nothing is left to carry a debt over with
+			food_remainder = BigDecimal::from(0);
+			match settings.starvation {
+				StarvationPolicy::KillUnits => {
+					let excess_food = (-&food_rate)
+						.with_scale_round(0, RoundingMode::Up)
+						.to_i64()
+						.unwrap_or_default();
+					let starved = upkeep_operations::starve_units(conn, player_id, excess_food)?;
+					warn!(
+						"Player {} is starving, {} units starved",
+						player_id, starved
+					);
+				}
+				StarvationPolicy::ReduceProduction { penalty: share } => {
+					penalty = BigDecimal::try_from(1.0 - share.clamp(0.0, 1.0))
+						.unwrap_or_else(|_| BigDecimal::from(1));
+					warn!(
+						"Player {} is starving, production reduced by {}",
+						player_id, share
+					);
+				}
+			}
+		}
+
+		// Calculate production amounts, including the fractions carried over last time
+		let produce = |res_type: ResourceType, remainder: &BigDecimal| {
+			let rate = rate_of(res_type) * &penalty;
+			accrue(&rate, &delta_hours, remainder, settings.accrual_precision)
+		};
+		let (wood, wood_remainder) = produce(ResourceType::Wood, &acc.wood_remainder);
+		let (stone, stone_remainder) = produce(ResourceType::Stone, &acc.stone_remainder);
+		let (gold, gold_remainder) = produce(ResourceType::Gold, &acc.gold_remainder);
 
-		let acc_caps: ResourceGeneration = rg::resource_generation.find(player_id).first(conn)?;
+		debug!(
+			"Producing resources for settlement {}: Food: {}, Wood: {}, Stone: {}, Gold: {}",
+			settlement_id, food, wood, stone, gold
+		);
 
-		let get_prod = |res_type: ResourceType| *prod_amounts.get(&res_type).unwrap_or(&0);
 		let res = diesel::update(pa::player_accumulator)
-			.filter(pa::id.eq(&acc_key))
+			.filter(pa::id.eq(&acc.id))
 			.set((
-				pa::food.eq(least(
-					pa::food + get_prod(ResourceType::Food),
-					acc_caps.food_acc_cap,
-				)),
-				pa::wood.eq(least(
-					pa::wood + get_prod(ResourceType::Wood),
-					acc_caps.wood_acc_cap,
-				)),
-				pa::stone.eq(least(
-					pa::stone + get_prod(ResourceType::Stone),
-					acc_caps.stone_acc_cap,
-				)),
-				pa::gold.eq(least(
-					pa::gold + get_prod(ResourceType::Gold),
-					acc_caps.gold_acc_cap,
-				)),
+				pa::food.eq(least(pa::food + food, acc_caps.food_acc_cap)),
+				pa::wood.eq(least(pa::wood + wood, acc_caps.wood_acc_cap)),
+				pa::stone.eq(least(pa::stone + stone, acc_caps.stone_acc_cap)),
+				pa::gold.eq(least(pa::gold + gold, acc_caps.gold_acc_cap)),
+				pa::food_remainder.eq(food_remainder),
+				pa::wood_remainder.eq(wood_remainder),
+				pa::stone_remainder.eq(stone_remainder),
+				pa::gold_remainder.eq(gold_remainder),
 			))
 			.returning(PlayerAccumulator::as_returning())
 			.get_result(conn)?;
 		debug!("New accumulator state: {:?}", res);
 
-		let updated_rows = diesel::update(pr::player_resource.filter(pr::player_id.eq(player_id)))
-			.set(pr::produced_at.eq(target_time))
-			.execute(conn)?;
+		let updated_rows =
+			diesel::update(pr::player_resource.filter(pr::settlement_id.eq(settlement_id)))
+				.set((
+					pr::food.eq(pr::food - eaten_from_storage),
+					pr::produced_at.eq(target_time),
+				))
+				.execute(conn)?;
 
 		if updated_rows != 1 {
 			warn!(
-				"Expected to update 1 `produced_at` timestamp, but updated {}. Player ID: {}",
-				updated_rows, player_id
+				"Expected to update 1 `produced_at` timestamp, but updated {}. Settlement ID: {}",
+				updated_rows, settlement_id
 			);
 		}
 
@@ -198,39 +285,61 @@ pub fn produce_resources(
 	})
 }
 
-/// Produces resources up to the current time and then collects them in a single operation.
+/// Splits what `rate` per hour produces over `hours`, plus the `remainder` carried over from
+/// the last production, into whole resources and the fraction to carry over next time.
+///
+/// The carried fraction is truncated to `precision` decimal places.
+pub fn accrue(
+	rate: &ResourceProductionRate,
+	hours: &BigDecimal,
+	remainder: &BigDecimal,
+	precision: u32,
+) -> (i64, BigDecimal) {
+	let amount = rate * hours + remainder;
+	let whole = amount.with_scale_round(0, RoundingMode::Down);
+	let carried = (amount - &whole).with_scale_round(i64::from(precision), RoundingMode::Down);
+	(whole.to_i64().unwrap_or_default(), carried)
+}
+
+/// Produces resources in a settlement up to the current time and then collects them in a
+/// single operation.
 ///
 /// This ensures that when a player clicks "collect", they receive resources produced
 /// up to that exact moment, preventing stale values.
 ///
 /// # Arguments
 /// * `conn` - Database connection
-/// * `player_id` - The unique identifier of the player
+/// * `settlement` - The settlement to produce and collect resources in
 /// * `production_rates` - HashMap of production rates per hour for each resource type
+/// * `settings` - The accrual precision and overflow policy to produce and collect with
 ///
 /// # Returns
 /// A tuple of (PlayerAccumulator after production, PlayerResource after collection)
 pub fn produce_and_collect_resources(
 	conn: &mut DbConn,
-	player_id: &PlayerKey,
+	settlement: &Settlement,
 	production_rates: &ResourceProductionRates,
+	settings: &ResourceSettings,
 ) -> Result<(PlayerAccumulator, PlayerResource)> {
 	// First produce resources up to now
-	let accumulator = produce_resources(conn, player_id, production_rates, None)?;
+	let accumulator = produce_resources(conn, settlement, production_rates, None, settings)?;
 
 	// Then collect the produced resources
-	let resources = collect_resources(conn, player_id)?;
+	let resources = collect_resources(conn, &settlement.id, settings.overflow)?;
 
 	Ok((accumulator, resources))
 }
 
-/// Get base resource generation rates for a player from the database
-pub fn get_base_rates(conn: &mut DbConn, player_key: &PlayerKey) -> Result<ResourceGeneration> {
-	use crate::custom_schema::resource_generation::dsl::{player_id, resource_generation};
+/// Get base resource generation rates for a settlement from the database
+pub fn get_base_rates(
+	conn: &mut DbConn,
+	settlement_key: &SettlementKey,
+) -> Result<ResourceGeneration> {
+	use crate::custom_schema::resource_generation::dsl::{resource_generation, settlement_id};
 
 	resource_generation
 		.select(ResourceGeneration::as_select())
-		.filter(player_id.eq(player_key))
+		.filter(settlement_id.eq(settlement_key))
 		.first(conn)
 		.map_err(Into::into)
 }
@@ -257,45 +366,124 @@ pub fn apply_rate_modifiers(
 		.collect()
 }
 
-/// Calculate production rates with modifiers applied
+/// Calculate production rates of a settlement with modifiers applied
 ///
-/// This is a pure function that calculates rates without caching.
+/// This is a pure function that calculates rates without caching. Player-wide modifiers
+/// apply to the production of every building, building-scoped ones to their building's.
 pub fn calc_prod_rates(
 	conn: &mut DbConn,
-	player_id: &PlayerKey,
+	settlement: &Settlement,
 ) -> Result<ResourceProductionRates> {
 	use strum::IntoEnumIterator;
 
-	// Get base rates from the database
-	let base_rates = get_base_rates(conn, player_id)?;
+	// Get base rates and modifiers from the database
+	let base_rates = get_base_rates(conn, &settlement.id)?;
+	let mods = modifier_operations::get_applied_mods(conn, &settlement.player_id)?;
 
-	// Calculate modifiers for each resource type
-	let production_rates: ResourceProductionRates = ResourceType::iter()
+	// Calculate player-wide modifiers for each resource type
+	let multipliers: ResourceMultipliers = ResourceType::iter()
 		.map(|res_type| {
-			let multiplier = modifier_operations::calc_multiplier(
-				conn,
-				player_id,
+			let multiplier = modifier_operations::player_multiplier(
+				&mods,
 				ModifierTarget::Resource,
 				Some(res_type),
-			)
-			.unwrap_or(BigDecimal::from(1));
-
-			let base_rate = match res_type {
-				ResourceType::Population => base_rates.population,
-				ResourceType::Food => base_rates.food,
-				ResourceType::Wood => base_rates.wood,
-				ResourceType::Stone => base_rates.stone,
-				ResourceType::Gold => base_rates.gold,
-			};
-
-			let final_rate = ResourceProductionRate::from(base_rate) * multiplier;
-			(res_type, final_rate)
+			);
+			(res_type, multiplier)
 		})
 		.collect();
+	let mut production_rates = apply_rate_modifiers(&base_rates, &multipliers);
 
+	apply_building_modifiers(conn, &mods, &settlement.id, &mut production_rates)?;
 	Ok(production_rates)
 }
 
+/// Adds the effect of building-scoped resource modifiers to the production rates of a
+/// settlement.
+///
+/// `rates` are expected to hold the production of every building in the settlement with the
+/// player-wide multipliers. Buildings with modifiers of their own produce with their
+/// [`building_multiplier`](modifier_operations::building_multiplier) instead, so the
+/// difference on their base production is added.
+pub fn apply_building_modifiers(
+	conn: &mut DbConn,
+	mods: &[AppliedModifier],
+	settlement_id: &SettlementKey,
+	rates: &mut ResourceProductionRates,
+) -> Result<()> {
+	let scoped = modifier_operations::scoped_buildings(mods, ModifierTarget::Resource);
+	if scoped.is_empty() {
+		return Ok(());
+	}
+
+	for (player_bld_id, base_rates) in building_base_rates(conn, settlement_id, &scoped)? {
+		for (res_type, base_rate) in base_rates {
+			let target = ModifierTarget::Resource;
+			let building = modifier_operations::building_multiplier(
+				mods,
+				&player_bld_id,
+				target,
+				Some(res_type),
+			);
+			let difference =
+				building - modifier_operations::player_multiplier(mods, target, Some(res_type));
+			if difference.is_zero() {
+				continue;
+			}
+			trace!(%player_bld_id, ?res_type, %difference, "Applying building modifiers");
+			*rates.entry(res_type).or_default() +=
+				ResourceProductionRate::from(base_rate) * difference;
+		}
+	}
+	Ok(())
+}
+
+/// Base production per hour of a single building, by resource.
+type BuildingBaseRates = (PlayerBuildingKey, [(ResourceType, i64); 5]);
+
+/// Loads the base production per hour of single buildings of a settlement at their current
+/// level.
+fn building_base_rates(
+	conn: &mut DbConn,
+	settlement_id: &SettlementKey,
+	player_bld_ids: &[PlayerBuildingKey],
+) -> Result<Vec<BuildingBaseRates>> {
+	use crate::schema::{building_resource as br, player_building as pb};
+
+	let rows: Vec<(PlayerBuildingKey, i64, i64, i64, i64, i64)> = pb::table
+		.inner_join(
+			br::table.on(pb::building_id
+				.eq(br::building_id)
+				.and(pb::level.eq(br::building_level))),
+		)
+		.filter(pb::id.eq_any(player_bld_ids))
+		.filter(pb::settlement_id.eq(settlement_id))
+		.select((
+			pb::id,
+			br::population,
+			br::food,
+			br::wood,
+			br::stone,
+			br::gold,
+		))
+		.load(conn)?;
+
+	Ok(rows
+		.into_iter()
+		.map(|(id, population, food, wood, stone, gold)| {
+			(
+				id,
+				[
+					(ResourceType::Population, population),
+					(ResourceType::Food, food),
+					(ResourceType::Wood, wood),
+					(ResourceType::Stone, stone),
+					(ResourceType::Gold, gold),
+				],
+			)
+		})
+		.collect())
+}
+
 // ---------------------------------------------------------------------------
 // Resource Snapshot
 // ---------------------------------------------------------------------------
@@ -305,7 +493,7 @@ use uuid::Uuid;
 use crate::domain::player::resource_snapshot::PlayerResourceSnapshot;
 
 type ResourceGenerationView = (
-	Uuid,       // player_id
+	Uuid,       // settlement_id
 	BigDecimal, // population
 	BigDecimal, // food
 	BigDecimal, // wood
@@ -320,8 +508,11 @@ type ResourceGenerationView = (
 /// Queries the aggregated resource generation rates from building_resource.
 ///
 /// This is essentially a materialized view that sums production rates and
-/// accumulator caps across all of a player's buildings at their current levels.
-fn res_gen_view(conn: &mut DbConn, player_key: &PlayerKey) -> Result<ResourceGenerationView> {
+/// accumulator caps across all of a settlement's buildings at their current levels.
+fn res_gen_view(
+	conn: &mut DbConn,
+	settlement_key: &SettlementKey,
+) -> Result<ResourceGenerationView> {
 	use diesel::dsl::sum;
 
 	use crate::schema::{building_resource as br, player_building as pb};
@@ -332,10 +523,10 @@ fn res_gen_view(conn: &mut DbConn, player_key: &PlayerKey) -> Result<ResourceGen
 				.eq(br::building_id)
 				.and(pb::level.eq(br::building_level))),
 		)
-		.group_by(pb::player_id)
-		.filter(pb::player_id.eq(player_key))
+		.group_by(pb::settlement_id)
+		.filter(pb::settlement_id.eq(settlement_key))
 		.select((
-			pb::player_id,
+			pb::settlement_id,
 			sum(br::population).assume_not_null(),
 			sum(br::food).assume_not_null(),
 			sum(br::wood).assume_not_null(),
@@ -351,22 +542,23 @@ fn res_gen_view(conn: &mut DbConn, player_key: &PlayerKey) -> Result<ResourceGen
 	Ok(result)
 }
 
-/// Returns an aggregated snapshot of a player's current resource state.
+/// Returns an aggregated snapshot of the current resource state of a settlement.
 ///
 /// Combines data from:
 /// - `player_resource` (current storage amounts and caps, timestamps)
 /// - `player_accumulator` (accumulated resources awaiting collection)
 /// - Building-derived production rates and accumulator caps
+/// - Population and food upkeep of the player's units, which are player-wide
 pub fn get_resource_snapshot(
 	conn: &mut DbConn,
-	player_key: &PlayerKey,
+	settlement: &Settlement,
 ) -> Result<PlayerResourceSnapshot> {
 	use crate::schema::player_accumulator::dsl as pa;
 	use crate::schema::player_resource::dsl as pr;
 
 	let (pr_data, pa_data) = pr::player_resource
-		.inner_join(pa::player_accumulator.on(pr::player_id.eq(pa::player_id)))
-		.filter(pr::player_id.eq(player_key))
+		.inner_join(pa::player_accumulator.on(pr::settlement_id.eq(pa::settlement_id)))
+		.filter(pr::settlement_id.eq(settlement.id))
 		.select((
 			// player_resource fields
 			(
@@ -401,7 +593,7 @@ pub fn get_resource_snapshot(
 		)>(conn)?;
 
 	let (
-		_, // player id
+		_, // settlement id
 		_, // population rate
 		_,
 		_,
@@ -411,8 +603,10 @@ pub fn get_resource_snapshot(
 		wood_acc_cap_val,
 		stone_acc_cap_val,
 		gold_acc_cap_val,
-	) = res_gen_view(conn, player_key)?;
-	let prod_rates = calc_prod_rates(conn, player_key)?;
+	) = res_gen_view(conn, &settlement.id)?;
+	let prod_rates = calc_prod_rates(conn, settlement)?;
+	let upkeep = player_units::get_upkeep(conn, &settlement.player_id)?;
+	let population = upkeep_operations::get_population(conn, &settlement.player_id)?;
 
 	Ok(PlayerResourceSnapshot {
 		food: pr_data.0,
@@ -453,5 +647,8 @@ pub fn get_resource_snapshot(
 		wood_acc_cap: wood_acc_cap_val.to_i64().unwrap_or_default(),
 		stone_acc_cap: stone_acc_cap_val.to_i64().unwrap_or_default(),
 		gold_acc_cap: gold_acc_cap_val.to_i64().unwrap_or_default(),
+		population: population.used,
+		population_cap: population.capacity,
+		food_upkeep: upkeep.food_per_hour,
 	})
 }
@@ -1,97 +1,102 @@
-use std::sync::Arc;
+//! Recurring resource production.
+//!
+//! Production runs as recurring [`JobType::ResourceProduction`] jobs, one per shard of
+//! players, instead of a job per player that enqueues its own successor. The definitions
+//! live in the recurring job table, so production survives restarts, and every tick is a
+//! row in the job table that records how many players it produced for.
 
-use axum::extract::FromRef;
-use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
-use tracing::{debug, warn};
+use tracing::{debug, info};
 
 use crate::Result;
-use crate::domain::app_state::AppState;
-use crate::domain::jobs::{JobKey, JobType};
+use crate::domain::caravan::CaravanKey;
+use crate::domain::jobs::JobType;
 use crate::domain::player::PlayerKey;
-use crate::job_queue::{JobPriority, JobQueue, JobRequest};
+use crate::job_queue::{JobPriority, JobQueue};
+
+/// Number of production shards when `jobs.production_shards` is not configured.
+pub const DEFAULT_PRODUCTION_SHARDS: u32 = 4;
+
+/// Schedule of every production shard: every two minutes, on the minute.
+pub const PRODUCTION_TICK_CRON: &str = "0 */2 * * * *";
+
+/// Prefix of the recurring job names of the production shards.
+const PRODUCTION_TICK_PREFIX: &str = "resource-production-";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum ProductionJobPayload {
-	ProduceResources { players_id: PlayerKey },
-	CollectResources { players_id: PlayerKey },
+	CollectResources {
+		players_id: PlayerKey,
+	},
+	/// Delivers a caravan that arrived at its receiver
+	DeliverCaravan {
+		caravan_id: CaravanKey,
+	},
 }
 
-impl FromRef<AppState> for ProductionScheduler {
-	fn from_ref(state: &AppState) -> Self {
-		Self::new(&state.job_queue)
-	}
+/// Payload of a production tick, produces resources for every player of one shard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProductionTickPayload {
+	pub shard: u32,
+	pub shards: u32,
 }
 
-pub struct ProductionScheduler {
-	job_queue: Arc<JobQueue>,
+/// Returns the shard a player's production runs in, out of `shards`.
+pub fn production_shard(player_id: &PlayerKey, shards: u32) -> u32 {
+	(player_id.as_u128() % u128::from(shards.max(1))) as u32
 }
 
-impl ProductionScheduler {
-	pub fn new(job_queue: &Arc<JobQueue>) -> Self {
-		Self {
-			job_queue: Arc::clone(job_queue),
-		}
+/// Registers the recurring production tick of every shard.
+///
+/// Definitions that did not change keep their pending tick, and the ticks of shards beyond
+/// `shards` are removed, so this is safe to call on every startup.
+pub fn register_production_ticks(job_queue: &JobQueue, shards: u32) -> Result<()> {
+	let shards = shards.max(1);
+	for shard in 0..shards {
+		job_queue.register_recurring(
+			&format!("{PRODUCTION_TICK_PREFIX}{shard}"),
+			PRODUCTION_TICK_CRON,
+			JobType::ResourceProduction,
+			ProductionTickPayload { shard, shards },
+			JobPriority::Normal,
+		)?;
 	}
 
-	/// Schedules resource jobs for multiple players simultaneously.
-	///
-	/// # Parameters
-	/// * `player_keys` - A vector of player identifiers to schedule resources for
-	/// * `produce_at` - The time when the resources should occur
-	///
-	/// # Note
-	/// This method silently ignores any scheduling errors for individual players
-	pub async fn batch_schedule_production(
-		&self,
-		player_keys: Vec<PlayerKey>,
-		produce_at: DateTime<Utc>,
-	) -> Result<Vec<JobKey>> {
-		let new_jobs: Vec<JobRequest> = player_keys
-			.into_iter()
-			.map(|key| -> JobRequest {
-				let payload = serde_json::to_value(ProductionJobPayload::ProduceResources {
-					players_id: key,
-				})
-				.unwrap_or(Value::Null); // should never fail
-				(JobType::Resource, payload, JobPriority::Normal, produce_at)
-			})
-			.filter(|(_, payload, _, _)| Value::Null.ne(payload))
-			.collect();
-		self.job_queue.enqueue_batch(new_jobs)
+	for recurring in job_queue.list_recurring()? {
+		let retired = recurring
+			.name
+			.strip_prefix(PRODUCTION_TICK_PREFIX)
+			.and_then(|shard| shard.parse::<u32>().ok())
+			.is_some_and(|shard| shard >= shards);
+		if retired {
+			debug!("Removing production tick '{}'", recurring.name);
+			job_queue.remove_recurring(&recurring.name)?;
+		}
 	}
 
-	/// Schedules a resource production job for a specific player.
-	///
-	/// # Parameters
-	/// * `player_id` - The unique identifier of the player
-	/// * `produce_at` - The time when the resources should occur
-	///
-	/// # Returns
-	/// A `Result` containing the `JobKey` if successful, or an error if scheduling fails
-	pub fn schedule_production(
-		&self,
-		player_id: &PlayerKey,
-		produce_at: DateTime<Utc>,
-	) -> Result<JobKey> {
-		let payload = ProductionJobPayload::ProduceResources {
-			players_id: *player_id,
-		};
+	info!("Registered resource production for {} shards", shards);
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use uuid::Uuid;
+
+	use super::*;
 
-		let job_key =
-			self.job_queue
-				.enqueue(JobType::Resource, payload, JobPriority::Normal, produce_at);
+	#[test]
+	fn test_players_are_spread_across_shards() {
+		let players: Vec<PlayerKey> = (0..64).map(|_| Uuid::new_v4()).collect();
 
-		match job_key {
-			Ok(key) => {
-				debug!("Scheduled resources job for player: {:?}", player_id);
-				Ok(key)
-			}
-			Err(e) => {
-				warn!("Error scheduling resources job: {:?}", e);
-				Err(e)
-			}
+		for player in &players {
+			assert!(production_shard(player, 4) < 4);
+			assert_eq!(production_shard(player, 4), production_shard(player, 4));
+			assert_eq!(production_shard(player, 1), 0);
+			assert_eq!(production_shard(player, 0), 0);
 		}
+		let mut used: Vec<u32> = players.iter().map(|p| production_shard(p, 4)).collect();
+		used.sort();
+		used.dedup();
+		assert!(used.len() > 1, "All players ended up in one shard");
 	}
 }
@@ -1,93 +1,137 @@
 use std::sync::Arc;
 
 use axum::extract::FromRef;
-use chrono::{Duration, Utc};
-use tracing::{instrument, warn};
+use tracing::instrument;
 
 use crate::Result;
+use crate::configuration::ResourceSettings;
+use crate::db::{players, settlements};
 use crate::domain::app_state::{AppPool, AppState};
-use crate::domain::jobs::JobKey;
+use crate::domain::caravan::CaravanKey;
 use crate::domain::player::PlayerKey;
 use crate::domain::player::accumulator::PlayerAccumulator;
 use crate::domain::player::resource::PlayerResource;
 use crate::domain::resource_generation::ResourceGeneration;
-use crate::game::resources::resource_scheduler::ProductionScheduler;
+use crate::domain::settlement::{Settlement, SettlementKey};
+use crate::game::modifiers::modifier_operations;
+use crate::game::resources::caravan_operations::{self, CaravanDelivery};
+use crate::game::resources::resource_scheduler::production_shard;
 use crate::game::resources::{ResourceProductionRates, resource_operations};
-use crate::job_queue::JobQueue;
 
 /// Service responsible for managing resources for players.
-/// Handles resource production and collection operations, with scheduling support.
+/// Handles resource production and collection operations.
 pub struct ResourceService {
 	pool: AppPool,
-	scheduler: ProductionScheduler,
+	settings: ResourceSettings,
 }
 
 impl FromRef<AppState> for ResourceService {
 	fn from_ref(state: &AppState) -> Self {
-		Self::new(&state.db_pool, &state.job_queue)
+		Self::new(&state.db_pool, state.settings.resources)
 	}
 }
 
 impl ResourceService {
-	pub fn new(pool: &AppPool, queue: &Arc<JobQueue>) -> Self {
+	pub fn new(pool: &AppPool, settings: ResourceSettings) -> Self {
 		Self {
 			pool: Arc::clone(pool),
-			scheduler: ProductionScheduler::new(queue),
+			settings,
 		}
 	}
 
-	/// Produces resources for a player based on pre-calculated production rates.
-	/// Updates the player's accumulator with produced resources and schedules the next production job.
+	/// Produces resources in a settlement based on pre-calculated production rates.
+	/// Updates the settlement's accumulator with the resources produced since the last production.
 	///
 	/// # Arguments
-	/// * `player_key` - The unique identifier of the player to produce resources for
+	/// * `settlement` - The settlement to produce resources in
 	/// * `production_rates` - Pre-calculated production rates (with modifiers already applied)
 	#[instrument(skip(self, production_rates))]
 	pub async fn produce(
 		&self,
-		player_key: &PlayerKey,
+		settlement: &Settlement,
 		production_rates: &ResourceProductionRates,
-	) -> Result<(PlayerAccumulator, JobKey)> {
+	) -> Result<PlayerAccumulator> {
 		// Delegate to operations module for production logic
 		let mut conn = self.pool.get()?;
-		let acc =
-			resource_operations::produce_resources(&mut conn, player_key, production_rates, None)?;
+		resource_operations::produce_resources(
+			&mut conn,
+			settlement,
+			production_rates,
+			None,
+			&self.settings,
+		)
+	}
 
-		// Enqueue the next production job.
-		// The 2-minute interval is a simple polling mechanism.
-		let next_production_time = Utc::now() + Duration::minutes(2);
-		let job_key = self
-			.scheduler
-			.schedule_production(player_key, next_production_time)
-			.map_err(|err| {
-				warn!("Failed to schedule next production: {:#?}", err);
-				err
-			})?;
+	/// Retrieves the producing players of one production shard.
+	///
+	/// # Arguments
+	/// * `shard` - The shard to retrieve the players of
+	/// * `shards` - The number of shards players are split into
+	pub fn get_shard_players(&self, shard: u32, shards: u32) -> Result<Vec<PlayerKey>> {
+		let mut conn = self.pool.get()?;
+		let mut player_keys = players::get_producing_ids(&mut conn)?;
+		player_keys.retain(|player_key| production_shard(player_key, shards) == shard);
+		Ok(player_keys)
+	}
 
-		Ok((acc, job_key))
+	/// Retrieves the settlements of a player, each of which produces on its own.
+	///
+	/// # Arguments
+	/// * `player_key` - The unique identifier of the player whose settlements are retrieved
+	pub fn get_settlements(&self, player_key: &PlayerKey) -> Result<Vec<Settlement>> {
+		let mut conn = self.pool.get()?;
+		settlements::get_by_player(&mut conn, player_key)
 	}
 
-	/// Collects resources for a player by transferring the maximum possible amount from their
-	/// resource accumulator to their resource storage, constrained by the storage capacity limits.
+	/// Collects resources in a player's capital by transferring the maximum possible amount from
+	/// its resource accumulator to its resource storage, constrained by the storage capacity limits.
+	/// Overflow is handled according to the configured [`OverflowPolicy`](crate::game::resources::OverflowPolicy).
 	///
 	/// # Arguments
 	/// * `player_key` - The unique identifier of the player whose resources are being collected
 	#[instrument(skip(self))]
 	pub fn collect(&self, player_key: &PlayerKey) -> Result<PlayerResource> {
 		let mut conn = self.pool.get()?;
-		resource_operations::collect_resources(&mut conn, player_key)
+		let capital = settlements::get_capital(&mut conn, player_key)?;
+		resource_operations::collect_resources(&mut conn, &capital.id, self.settings.overflow)
+	}
+
+	/// Delivers an arrived caravan, crediting its receiver up to their storage caps.
+	///
+	/// # Arguments
+	/// * `caravan_id` - The unique identifier of the arrived caravan
+	#[instrument(skip(self))]
+	pub fn deliver_caravan(&self, caravan_id: &CaravanKey) -> Result<CaravanDelivery> {
+		let mut conn = self.pool.get()?;
+		caravan_operations::deliver_caravan(&mut conn, caravan_id)
 	}
 
-	/// Retrieves the base resource generation rates for a specific player.
+	/// Retrieves the base resource generation rates for a specific settlement.
 	///
 	/// This method fetches the unmodified base rates at which different resources
-	/// are generated for the player. These rates serve as the foundation for
+	/// are generated in the settlement. These rates serve as the foundation for
 	/// actual resource production calculations when combined with modifiers.
 	///
 	/// # Arguments
-	/// * `player_key` - The unique identifier of the player whose base rates are being queried
-	pub fn get_base_rates(&self, player_key: &PlayerKey) -> Result<ResourceGeneration> {
+	/// * `settlement_key` - The unique identifier of the settlement whose base rates are being queried
+	pub fn get_base_rates(&self, settlement_key: &SettlementKey) -> Result<ResourceGeneration> {
+		let mut conn = self.pool.get()?;
+		resource_operations::get_base_rates(&mut conn, settlement_key)
+	}
+
+	/// Applies the building-scoped modifiers of a player to the production rates of one of
+	/// their settlements.
+	///
+	/// # Arguments
+	/// * `settlement` - The settlement producing
+	/// * `rates` - Production rates with the player-wide modifiers applied
+	pub fn apply_building_modifiers(
+		&self,
+		settlement: &Settlement,
+		rates: &mut ResourceProductionRates,
+	) -> Result<()> {
 		let mut conn = self.pool.get()?;
-		resource_operations::get_base_rates(&mut conn, player_key)
+		let mods = modifier_operations::get_applied_mods(&mut conn, &settlement.player_id)?;
+		resource_operations::apply_building_modifiers(&mut conn, &mods, &settlement.id, rates)
 	}
 }
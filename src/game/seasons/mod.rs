@@ -0,0 +1,8 @@
+//! Seasons of the world for the Empire game.
+//!
+//! This module runs the world in seasons. A background job ends each season once it is over,
+//! archiving the final rankings of the leaderboards and granting the ranked players legacy
+//! points, which they keep across seasons and world resets.
+
+pub mod season_operations;
+pub mod season_processor;
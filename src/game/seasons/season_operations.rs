@@ -0,0 +1,260 @@
+//! Seasons of the world and the legacy players carry over.
+//!
+//! An operator creates the next season once the last one ended, which is recorded in the
+//! audit log. Only one season can be running or upcoming at a time, and a season never
+//! starts before the last one ends.
+//!
+//! A [`JobType::Season`] job ends the season once it is over. It snapshots the leaderboards
+//! one last time and archives their rankings as the season's standings, then grants every
+//! ranked player the legacy points of their rank on the power and economy boards, see
+//! [`LEGACY_TIERS`]. Legacy points belong to the account and survive world resets. Ending a
+//! season twice does nothing, so a retried job never grants anything twice.
+//!
+//! Ending a season does not wipe the world: the per-season state is reset by a world reset,
+//! which operators request and confirm themselves, see [`crate::game::world`].
+
+use chrono::{DateTime, Utc};
+use diesel::Connection;
+use serde_json::json;
+use tracing::{info, instrument};
+
+use crate::db::{DbConn, alliance_members, audit_log, seasons};
+use crate::domain::audit::NewAuditEntry;
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::jobs::{JobKey, JobType};
+use crate::domain::leaderboard::LeaderboardCategory;
+use crate::domain::player::PlayerKey;
+use crate::domain::season::{
+	NewSeason, PlayerLegacy, Season, SeasonJobPayload, SeasonKey, SeasonStanding,
+};
+use crate::game::admin_operations::AdminActor;
+use crate::game::leaderboard::leaderboard_operations;
+use crate::job_queue::{JobPriority, JobQueue};
+
+/// Action recorded in the audit log when a season is created.
+pub const CREATE_SEASON_ACTION: &str = "create_season";
+
+/// Legacy points granted per board for a final rank, as the lowest rank of each tier and its
+/// points. Every ranked player earns at least the points of the last tier.
+pub const LEGACY_TIERS: [(i32, i64); 4] = [(1, 100), (10, 50), (100, 20), (i32::MAX, 5)];
+
+/// Number of standings shown per board of an ended season.
+pub const SHOWN_STANDINGS: i64 = 100;
+
+/// A season an operator asked to create.
+#[derive(Debug, Clone)]
+pub struct SeasonRequest {
+	pub name: String,
+	pub starts_at: DateTime<Utc>,
+	pub ends_at: DateTime<Utc>,
+}
+
+/// The season being played or coming up, as a player sees it.
+#[derive(Debug, Clone)]
+pub struct SeasonOverview {
+	/// The season that has not ended yet, if an operator created one
+	pub current: Option<Season>,
+	/// The legacy the player earned in the past seasons, if any
+	pub legacy: Option<PlayerLegacy>,
+}
+
+/// A board of a season, as a player sees it.
+#[derive(Debug, Clone)]
+pub struct SeasonResults {
+	pub season: Season,
+	pub category: LeaderboardCategory,
+	/// The best standings on the board, empty until the season ended
+	pub standings: Vec<SeasonStanding>,
+	/// The standing of the player, or of their alliance on the alliance board, if ranked
+	pub own: Option<SeasonStanding>,
+}
+
+/// How ending a season went.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EndOutcome {
+	/// The standings were archived and the legacy points granted
+	Ended {
+		season: Season,
+		/// Standings archived, on all boards
+		archived: usize,
+		/// Players who earned legacy points
+		players: usize,
+		/// Legacy points granted, in total
+		legacy_points: i64,
+	},
+	/// The season was ended before
+	AlreadyEnded,
+	/// The season is not over yet, it has to be ended once it is
+	NotOver(Season),
+}
+
+/// Creates the next season, recording it in the audit log.
+///
+/// Enqueues the job ending the season once it is over. Fails with
+/// [`ErrorKind::SeasonConflictError`] while another season has not ended.
+#[instrument(skip(conn, job_queue))]
+pub fn create_season(
+	conn: &mut DbConn,
+	job_queue: &JobQueue,
+	request: SeasonRequest,
+	actor: AdminActor,
+) -> Result<Season> {
+	let name = request.name.trim().to_string();
+	if name.is_empty() {
+		return Err(Error::from((
+			ErrorKind::InvalidData,
+			"Season name is required",
+		)));
+	}
+	if request.ends_at <= request.starts_at || request.ends_at <= Utc::now() {
+		return Err(Error::from((
+			ErrorKind::InvalidData,
+			"Season must end after it starts and in the future",
+		)));
+	}
+
+	let season = conn.transaction(|conn| {
+		// The unique index on open seasons backs this check against concurrent creations
+		if let Some(open) = seasons::find_open(conn)? {
+			return Err(Error::from((
+				ErrorKind::SeasonConflictError,
+				"Another season has not ended",
+				format!("season {} has not ended", open.number),
+			)));
+		}
+		let latest = seasons::find_latest(conn)?;
+		if latest
+			.as_ref()
+			.is_some_and(|latest| request.starts_at < latest.ends_at)
+		{
+			return Err(Error::from((
+				ErrorKind::InvalidData,
+				"Season must start after the last one ended",
+			)));
+		}
+
+		let season = seasons::create(
+			conn,
+			NewSeason {
+				number: latest.map_or(1, |latest| latest.number + 1),
+				name,
+				starts_at: request.starts_at,
+				ends_at: request.ends_at,
+			},
+		)?;
+		audit_log::create(
+			conn,
+			NewAuditEntry {
+				action: CREATE_SEASON_ACTION.to_string(),
+				subject: Some(season.id.to_string()),
+				details: json!({
+					"number": season.number,
+					"name": season.name,
+					"starts_at": season.starts_at,
+					"ends_at": season.ends_at,
+				}),
+				operator: actor.operator,
+				request_id: actor.request_id,
+				player_id: None,
+			},
+		)?;
+		Ok::<_, Error>(season)
+	})?;
+
+	let job_id = schedule_end(job_queue, &season)?;
+	info!(
+		"Created season {} ending at {}, ended by job {}",
+		season.number, season.ends_at, job_id
+	);
+	Ok(season)
+}
+
+/// Enqueues the job ending a season once it is over.
+pub fn schedule_end(job_queue: &JobQueue, season: &Season) -> Result<JobKey> {
+	job_queue.enqueue(
+		JobType::Season,
+		SeasonJobPayload {
+			season_id: season.id,
+		},
+		JobPriority::Normal,
+		season.ends_at,
+	)
+}
+
+/// Returns the season being played or coming up, along with the player's legacy.
+#[instrument(skip(conn))]
+pub fn get_overview(conn: &mut DbConn, player_id: &PlayerKey) -> Result<SeasonOverview> {
+	Ok(SeasonOverview {
+		current: seasons::find_open(conn)?,
+		legacy: seasons::find_legacy(conn, player_id)?,
+	})
+}
+
+/// Lists the seasons that ended, latest first.
+#[instrument(skip(conn))]
+pub fn list_history(conn: &mut DbConn) -> Result<Vec<Season>> {
+	seasons::get_ended(conn)
+}
+
+/// Returns the best [`SHOWN_STANDINGS`] standings of a board of a season, along with the
+/// standing of the player.
+#[instrument(skip(conn))]
+pub fn get_results(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	season_id: &SeasonKey,
+	category: LeaderboardCategory,
+) -> Result<SeasonResults> {
+	let season = seasons::find_by_id(conn, season_id)?
+		.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "Season not found")))?;
+	let standings = seasons::get_standings(conn, season_id, category, SHOWN_STANDINGS)?;
+	let own = match category {
+		LeaderboardCategory::Alliance => match alliance_members::find_by_player(conn, player_id)? {
+			Some(member) => seasons::find_standing(conn, season_id, category, &member.alliance_id)?,
+			None => None,
+		},
+		_ => seasons::find_standing(conn, season_id, category, player_id)?,
+	};
+
+	Ok(SeasonResults {
+		season,
+		category,
+		standings,
+		own,
+	})
+}
+
+/// Ends a season that is over, archiving its standings and granting legacy points.
+#[instrument(skip(conn))]
+pub fn end_season(
+	conn: &mut DbConn,
+	season_id: &SeasonKey,
+	now: DateTime<Utc>,
+) -> Result<EndOutcome> {
+	conn.transaction(|conn| {
+		let season = seasons::lock_by_id(conn, season_id)?;
+		if season.ended_at.is_some() {
+			return Ok(EndOutcome::AlreadyEnded);
+		}
+		if season.ends_at > now {
+			return Ok(EndOutcome::NotOver(season));
+		}
+
+		leaderboard_operations::snapshot(conn, now)?;
+		let archived = seasons::archive_standings(conn, season_id)?;
+		let players = seasons::grant_legacy(conn, season_id, &LEGACY_TIERS)?;
+		let legacy_points = seasons::sum_legacy_points(conn, season_id)?;
+		let season = seasons::end(conn, season_id, now)?;
+
+		info!(
+			"Ended season {}, archived {} standings and granted {} players {} legacy points",
+			season.number, archived, players, legacy_points
+		);
+		Ok(EndOutcome::Ended {
+			season,
+			archived,
+			players,
+			legacy_points,
+		})
+	})
+}
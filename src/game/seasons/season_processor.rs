@@ -0,0 +1,87 @@
+//! Season job processor.
+//!
+//! This module implements the job processing functionality for seasons, ending each season
+//! once it is over.
+
+use axum::extract::FromRef;
+use chrono::Utc;
+use tracing::{debug, info, instrument, trace};
+
+use crate::Error;
+use crate::domain::app_state::{AppPool, AppQueue, AppState};
+use crate::domain::jobs::{Job, JobType};
+use crate::domain::season::SeasonJobPayload;
+use crate::game::seasons::season_operations::{self, EndOutcome};
+use crate::job_queue::job_processor::{JobHandler, JobOutcome, JobWorker};
+
+/// A handler for [`JobType::Season`] jobs.
+///
+/// Each job ends a season, archiving its standings and granting legacy points. A season
+/// that is not over yet, e.g. because the job was started early through the admin API, gets
+/// a new job at its end.
+pub struct SeasonHandler {
+	/// Database connection pool
+	pool: AppPool,
+	/// Job queue seasons that are not over yet are queued on again
+	job_queue: AppQueue,
+}
+
+/// Workers running the [`SeasonHandler`].
+pub type SeasonProcessor = JobWorker<SeasonHandler>;
+
+impl JobHandler for SeasonHandler {
+	const JOB_TYPE: JobType = JobType::Season;
+	const WORKER_NAME: &'static str = "season-goblin";
+
+	/// Creates a new `SeasonHandler` with what it needs from the application state.
+	fn new(app_state: &AppState) -> Self {
+		Self {
+			pool: AppPool::from_ref(app_state),
+			job_queue: AppQueue::from_ref(app_state),
+		}
+	}
+
+	#[instrument(skip(self, job), fields(job_id = %job.id))]
+	async fn handle(&self, job: Job) -> Result<JobOutcome, Error> {
+		debug!("Processing season job: {}", job.id);
+		trace!("Job details: {:?}", job);
+
+		let SeasonJobPayload { season_id } = serde_json::from_value(job.payload.clone())?;
+		let outcome = {
+			let mut conn = self.pool.get()?;
+			season_operations::end_season(&mut conn, &season_id, Utc::now())?
+		};
+
+		let result = match outcome {
+			EndOutcome::Ended {
+				season,
+				archived,
+				players,
+				legacy_points,
+			} => {
+				info!("Season {} is over", season.name);
+				serde_json::json!({
+					"ended": true,
+					"archived": archived,
+					"players": players,
+					"legacy_points": legacy_points,
+				})
+			}
+			EndOutcome::AlreadyEnded => {
+				debug!("Season {} was already ended", season_id);
+				serde_json::json!({ "ended": false, "skipped": true })
+			}
+			EndOutcome::NotOver(season) => {
+				let next_job = season_operations::schedule_end(&self.job_queue, &season)?;
+				debug!(
+					"Season {} is not over, ending it in job {}",
+					season.id, next_job
+				);
+				serde_json::json!({ "ended": false, "rescheduled": next_job })
+			}
+		};
+
+		debug!("Completed processing season job: {}", job.id);
+		Ok(Some(result))
+	}
+}
@@ -0,0 +1,6 @@
+//! Settlement operations for the Empire game.
+//!
+//! This module lets players found settlements beyond their capital and list the
+//! settlements they have.
+
+pub mod settlement_operations;
@@ -0,0 +1,97 @@
+//! Founding settlements and listing the settlements of a player.
+//!
+//! Every player starts with a capital. Once the Keep of the capital reaches
+//! `settlements.found_keep_level`, they found further settlements, up to
+//! `settlements.max_settlements` including the capital. A new settlement gets the same
+//! starter buildings and default storage as a new player, and produces and stores its
+//! resources on its own.
+
+use diesel::Connection;
+use tracing::{info, instrument};
+
+use crate::configuration::SettlementSettings;
+use crate::db::{DbConn, player_buildings, resources, settlements};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::player::PlayerKey;
+use crate::domain::settlement::{NewSettlement, Settlement};
+
+/// Shortest and longest name of a settlement.
+pub const NAME_LENGTH: (usize, usize) = (3, 32);
+
+/// Trims the name of a settlement and checks its length.
+fn validate_name(name: &str) -> Result<String> {
+	let name = name.trim();
+	let name_length = name.chars().count();
+	if name_length < NAME_LENGTH.0 || name_length > NAME_LENGTH.1 {
+		return Err(Error::from((
+			ErrorKind::InvalidData,
+			"Settlement names are 3 to 32 characters long",
+		)));
+	}
+	Ok(name.to_string())
+}
+
+/// Founds a new settlement of `player_id`.
+///
+/// # Errors
+/// * `InvalidData` if the name is invalid
+/// * `FoundSettlementError` if the Keep of the capital is too low, or the player has as many
+///   settlements as they can have
+/// * `SettlementNameTakenError` if another settlement of the player uses the name
+#[instrument(skip(conn, settings))]
+pub fn found(
+	conn: &mut DbConn,
+	settings: &SettlementSettings,
+	player_id: &PlayerKey,
+	name: &str,
+) -> Result<Settlement> {
+	let name = validate_name(name)?;
+
+	let settlement = conn.transaction(|conn| {
+		// AIDEV-NOTE: the capital's resource row lock serializes founding per player, so
+		// concurrent requests cannot both take the last settlement
+		let capital = settlements::get_capital(conn, player_id)?;
+		resources::lock_by_settlement(conn, &capital.id)?;
+
+		let keep_level = player_buildings::get_keep_level(conn, &capital.id)?;
+		if keep_level < settings.found_keep_level {
+			return Err(Error::from((
+				ErrorKind::FoundSettlementError,
+				"The Keep of the capital is too low to found a settlement",
+				format!("level {keep_level} of {}", settings.found_keep_level),
+			)));
+		}
+		if settlements::count_by_player(conn, player_id)? >= settings.max_settlements {
+			return Err(Error::from((
+				ErrorKind::FoundSettlementError,
+				"No more settlements can be founded",
+			)));
+		}
+		if settlements::exists_by_name(conn, player_id, &name)? {
+			return Err(Error::from((
+				ErrorKind::SettlementNameTakenError,
+				"Settlement name is taken",
+			)));
+		}
+		settlements::found(
+			conn,
+			NewSettlement {
+				player_id: *player_id,
+				name,
+			},
+		)
+	})?;
+
+	info!(
+		"Player {} founded settlement {} ({})",
+		player_id, settlement.name, settlement.id
+	);
+	Ok(settlement)
+}
+
+/// Lists the settlements of a player, the capital first and the others in the order they
+/// were founded.
+#[instrument(skip(conn))]
+pub fn list(conn: &mut DbConn, player_id: &PlayerKey) -> Result<Vec<Settlement>> {
+	settlements::get_by_player(conn, player_id)
+}
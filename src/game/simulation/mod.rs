@@ -0,0 +1,10 @@
+//! NPC players for development and staging worlds.
+//!
+//! Built only with the `simulation` feature. NPCs are regular players marked in the
+//! `simulated_player` table, and they take their turns in [`JobType::Simulation`] jobs
+//! scheduled as recurring jobs, so a world fills with activity for load and gameplay tests.
+//!
+//! [`JobType::Simulation`]: crate::domain::jobs::JobType::Simulation
+
+pub mod simulation_operations;
+pub mod simulation_processor;
@@ -0,0 +1,260 @@
+//! Spawning and turns of the NPC players.
+//!
+//! NPCs act through the same operations as players do: they collect their resources, start
+//! constructions and upgrades through [`building_operations`] and fill their training slots
+//! through [`training_operations::train_to_fill`]. A turn that finds nothing affordable
+//! simply does nothing.
+//!
+//! AIDEV-NOTE: NPCs do not attack yet. There is no combat resolver to run a battle through;
+//! once there is, an attack turn belongs here, checked by `combat_validator::validate_attack`.
+
+use chrono::Utc;
+use diesel::Connection;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, instrument, trace, warn};
+
+use crate::auth::utils::hash_password;
+use crate::configuration::{ResourceSettings, SimulationSettings};
+use crate::db::building_requirements::get_construction_reqs;
+use crate::db::{DbConn, player_buildings, players, settlements, simulated_players};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::factions::FactionCode;
+use crate::domain::jobs::JobType;
+use crate::domain::player::buildings::PlayerBuilding;
+use crate::domain::player::{NewPlayer, PlayerKey, UserName};
+use crate::game::buildings::building_operations;
+use crate::game::buildings::requirement_operations::gen_avail_list;
+use crate::game::modifiers::modifier_cache::ModifierCache;
+use crate::game::player_operations;
+use crate::game::resources::resource_operations;
+use crate::game::units::training_operations;
+use crate::job_queue::{JobPriority, JobQueue};
+
+/// Name of the recurring job running the construction turns.
+pub const CONSTRUCT_TICK_NAME: &str = "simulation-construct";
+
+/// Name of the recurring job running the training turns.
+pub const TRAIN_TICK_NAME: &str = "simulation-train";
+
+/// Factions NPCs are spread across, in spawn order.
+const NPC_FACTIONS: [FactionCode; 5] = [
+	FactionCode::Human,
+	FactionCode::Orc,
+	FactionCode::Elf,
+	FactionCode::Dwarf,
+	FactionCode::Goblin,
+];
+
+/// Job payloads handled by the simulation processor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SimulationJobPayload {
+	/// Every NPC starts a construction or an upgrade, see [`construct_turn`]
+	Construct,
+	/// Every NPC fills the training slots of its buildings, see [`train_turn`]
+	Train { batch: i64 },
+}
+
+/// Spawns the configured NPCs and registers their turns, or retires the turns when the
+/// simulation is disabled.
+///
+/// Safe to call on every startup: existing NPCs are kept, and the recurring jobs keep their
+/// pending instance while the schedules do not change.
+#[instrument(skip(conn, job_queue))]
+pub fn start_simulation(
+	conn: &mut DbConn,
+	job_queue: &JobQueue,
+	settings: &SimulationSettings,
+) -> Result<Vec<PlayerKey>> {
+	if settings.npcs == 0 {
+		job_queue.remove_recurring(CONSTRUCT_TICK_NAME)?;
+		job_queue.remove_recurring(TRAIN_TICK_NAME)?;
+		debug!("Simulation disabled, no NPCs configured");
+		return Ok(vec![]);
+	}
+
+	let npcs = spawn_npcs(conn, settings)?;
+	job_queue.register_recurring(
+		CONSTRUCT_TICK_NAME,
+		&settings.construct_schedule,
+		JobType::Simulation,
+		SimulationJobPayload::Construct,
+		JobPriority::Low,
+	)?;
+	job_queue.register_recurring(
+		TRAIN_TICK_NAME,
+		&settings.train_schedule,
+		JobType::Simulation,
+		SimulationJobPayload::Train {
+			batch: settings.train_batch,
+		},
+		JobPriority::Low,
+	)?;
+
+	info!("Started the simulation with {} NPCs", npcs.len());
+	Ok(npcs)
+}
+
+/// Creates the NPCs named `{name_prefix}1` up to `{name_prefix}{npcs}` that do not exist yet.
+///
+/// A name taken by a real player is skipped rather than taken over.
+///
+/// # Returns
+/// The IDs of every simulated player, including ones spawned earlier
+#[instrument(skip(conn))]
+pub fn spawn_npcs(conn: &mut DbConn, settings: &SimulationSettings) -> Result<Vec<PlayerKey>> {
+	let simulated = simulated_players::get_all_ids(conn)?;
+	for number in 1..=settings.npcs {
+		let name = format!("{}{}", settings.name_prefix, number);
+		if let Some(existing) = players::find_by_name(conn, &name)? {
+			if !simulated.contains(&existing.id) {
+				warn!("Skipping NPC {}, the name belongs to a player", name);
+			}
+			continue;
+		}
+
+		// NPCs never log in, so their password is random and thrown away
+		let password: [u8; 32] = rand::random();
+		let pwd_hash = hash_password(password)
+			.map_err(|_| (ErrorKind::InternalError, "Failed to hash password"))?;
+		let faction = NPC_FACTIONS[(number as usize - 1) % NPC_FACTIONS.len()];
+		conn.transaction(|conn| -> Result<()> {
+			let npc = players::create(
+				conn,
+				NewPlayer {
+					name: UserName::parse(name.clone())?,
+					pwd_hash,
+					email: None,
+					faction,
+				},
+			)?;
+			player_operations::provision_player(conn, &npc)?;
+			simulated_players::create(conn, &npc.id)?;
+			Ok(())
+		})?;
+		debug!("Spawned NPC {} of faction {}", name, faction);
+	}
+
+	simulated_players::get_all_ids(conn)
+}
+
+/// Runs the construction turn of an NPC.
+///
+/// The NPC collects its resources under the resource `settings` and confirms finished upgrades. With no construction
+/// underway, it then starts one at random among the new buildings of its capital and upgrades it can
+/// afford, so it never has more than one construction going.
+///
+/// # Returns
+/// The building that started constructing or upgrading, if any
+#[instrument(skip(conn, job_queue))]
+pub fn construct_turn(
+	conn: &mut DbConn,
+	job_queue: &JobQueue,
+	npc_id: &PlayerKey,
+	settings: &ResourceSettings,
+) -> Result<Option<PlayerBuilding>> {
+	collect(conn, npc_id, settings)?;
+
+	let now = Utc::now();
+	let mut owned = Vec::new();
+	for bld in player_buildings::get_player_buildings(conn, npc_id)? {
+		match bld.upgrade_finishes_at {
+			Some(eta) if eta <= now => {
+				owned.push(building_operations::confirm_upgrade(conn, &bld.id)?)
+			}
+			Some(_) => {
+				trace!("NPC {} is still constructing building {}", npc_id, bld.id);
+				return Ok(None);
+			}
+			None => owned.push(bld),
+		}
+	}
+
+	let npc = players::get_by_id(conn, npc_id)?;
+	let capital = settlements::get_capital(conn, npc_id)?;
+	let (blds, bld_data) = player_buildings::get_player_bld_counts_levels(conn, &npc, &capital.id)?;
+	let reqs = get_construction_reqs(conn, &npc.faction)?;
+	let mut new_blds: Vec<_> = gen_avail_list(blds, bld_data, reqs)
+		.into_iter()
+		.filter(|avail| avail.buildable)
+		.map(|avail| avail.building.id)
+		.collect();
+	let mut rng = rand::rng();
+	new_blds.shuffle(&mut rng);
+	owned.shuffle(&mut rng);
+
+	for bld_id in new_blds {
+		match building_operations::construct_building(conn, job_queue, &capital, &bld_id) {
+			Ok(bld) => return Ok(Some(bld)),
+			Err(err) => trace!("NPC {} cannot construct {}: {}", npc_id, bld_id, err),
+		}
+	}
+	for bld in owned {
+		match building_operations::upgrade_building(conn, job_queue, &bld.id) {
+			Ok(bld) => return Ok(Some(bld)),
+			Err(err) => trace!("NPC {} cannot upgrade {}: {}", npc_id, bld.id, err),
+		}
+	}
+
+	trace!("NPC {} cannot afford any construction", npc_id);
+	Ok(None)
+}
+
+/// Runs the training turn of an NPC.
+///
+/// The NPC collects its resources under the resource `settings` and starts training up to `batch` units of a random
+/// trainable unit at every building with a free training slot, as far as it can afford.
+///
+/// # Returns
+/// The number of units that started training
+#[instrument(skip(conn, job_queue, modifier_cache))]
+pub fn train_turn(
+	conn: &mut DbConn,
+	job_queue: &JobQueue,
+	modifier_cache: &ModifierCache,
+	npc_id: &PlayerKey,
+	batch: i64,
+	settings: &ResourceSettings,
+) -> Result<i64> {
+	if batch <= 0 {
+		return Err(Error::from((
+			ErrorKind::InvalidQuantityError,
+			"Training batch must be positive",
+		)));
+	}
+	collect(conn, npc_id, settings)?;
+
+	let mut rng = rand::rng();
+	let mut trained = 0;
+	for bld in player_buildings::get_player_buildings(conn, npc_id)? {
+		let mut units =
+			training_operations::get_available_units_for_building(conn, npc_id, &bld.id)?;
+		units.shuffle(&mut rng);
+		let Some(unit) = units.first() else {
+			continue;
+		};
+		match training_operations::train_to_fill(
+			conn,
+			job_queue,
+			modifier_cache,
+			npc_id,
+			&bld.id,
+			&unit.id,
+			Some(batch),
+		) {
+			Ok(started) => trained += started.entry.quantity,
+			Err(err) => trace!("NPC {} cannot train at {}: {}", npc_id, bld.id, err),
+		}
+	}
+
+	Ok(trained)
+}
+
+/// Collects the resources an NPC produced in its settlements since its last turn.
+fn collect(conn: &mut DbConn, npc_id: &PlayerKey, settings: &ResourceSettings) -> Result<()> {
+	for settlement in settlements::get_by_player(conn, npc_id)? {
+		let rates = resource_operations::calc_prod_rates(conn, &settlement)?;
+		resource_operations::produce_and_collect_resources(conn, &settlement, &rates, settings)?;
+	}
+	Ok(())
+}
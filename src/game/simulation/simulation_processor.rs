@@ -0,0 +1,106 @@
+//! Simulation job processor for the turns of the NPC players.
+//!
+//! This module implements the job processing functionality for simulation jobs, which run
+//! one kind of turn for every NPC, see [`simulation_operations`].
+
+use axum::extract::FromRef;
+use tracing::{debug, info, instrument, trace, warn};
+
+use crate::Error;
+use crate::configuration::ResourceSettings;
+use crate::db::simulated_players;
+use crate::domain::app_state::{AppModifierCache, AppPool, AppQueue, AppState};
+use crate::domain::jobs::{Job, JobType};
+use crate::game::simulation::simulation_operations::{self, SimulationJobPayload};
+use crate::job_queue::job_processor::{JobHandler, JobOutcome, JobWorker};
+
+/// A handler for the turns of the NPC players.
+///
+/// The `SimulationHandler` implements the `JobHandler` trait. Every job runs one turn
+/// for each NPC; a turn that fails is logged and does not hold up the other NPCs.
+pub struct SimulationHandler {
+	/// Database connection pool
+	pool: AppPool,
+	/// Job queue training completions are scheduled on
+	job_queue: AppQueue,
+	/// Cache of the NPCs' modifier multipliers, like their training speed
+	modifier_cache: AppModifierCache,
+	/// How NPCs produce and collect their resources
+	resources: ResourceSettings,
+}
+
+/// Workers running the [`SimulationHandler`].
+pub type SimulationProcessor = JobWorker<SimulationHandler>;
+
+impl JobHandler for SimulationHandler {
+	const JOB_TYPE: JobType = JobType::Simulation;
+	const WORKER_NAME: &'static str = "simulation-goblin";
+
+	/// Creates a new `SimulationHandler` with what it needs from the application state.
+	fn new(app_state: &AppState) -> Self {
+		Self {
+			pool: AppPool::from_ref(app_state),
+			job_queue: AppQueue::from_ref(app_state),
+			modifier_cache: AppModifierCache::from_ref(app_state),
+			resources: app_state.settings.resources,
+		}
+	}
+
+	#[instrument(skip(self, job), fields(job_id = %job.id))]
+	async fn handle(&self, job: Job) -> Result<JobOutcome, Error> {
+		debug!("Processing simulation job: {}", job.id);
+		trace!("Job details: {:?}", job);
+
+		let payload: SimulationJobPayload = serde_json::from_value(job.payload.clone())?;
+		let mut conn = self.pool.get()?;
+		let npcs = simulated_players::get_all_ids(&mut conn)?;
+
+		let (mut acted, mut failed) = (0, 0);
+		for npc_id in &npcs {
+			let turn = match payload {
+				SimulationJobPayload::Construct => {
+					// The turn confirms finished upgrades, which changes the NPC's buildings
+					let turn = simulation_operations::construct_turn(
+						&mut conn,
+						&self.job_queue,
+						npc_id,
+						&self.resources,
+					);
+					self.modifier_cache.invalidate_user(*npc_id);
+					turn.map(|started| started.is_some())
+				}
+				SimulationJobPayload::Train { batch } => simulation_operations::train_turn(
+					&mut conn,
+					&self.job_queue,
+					&self.modifier_cache,
+					npc_id,
+					batch,
+					&self.resources,
+				)
+				.map(|trained| trained > 0),
+			};
+			match turn {
+				Ok(true) => acted += 1,
+				Ok(false) => {}
+				Err(err) => {
+					warn!("Turn of NPC {} failed: {}", npc_id, err);
+					failed += 1;
+				}
+			}
+		}
+		info!(
+			"{:?} turn: {} of {} NPCs acted, {} failed",
+			payload,
+			acted,
+			npcs.len(),
+			failed
+		);
+
+		debug!("Completed processing simulation job: {}", job.id);
+		Ok(Some(serde_json::json!({
+			"npcs": npcs.len(),
+			"acted": acted,
+			"failed": failed,
+		})))
+	}
+}
@@ -0,0 +1,8 @@
+//! Table statistics for the Empire game.
+//!
+//! This module captures the row counts and on-disk sizes of the busiest tables on a recurring
+//! job, and summarizes their growth over time for capacity planning and for checking that
+//! the retention policies keep up.
+
+pub mod stats_operations;
+pub mod stats_processor;
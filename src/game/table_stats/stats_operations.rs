@@ -0,0 +1,137 @@
+//! Table statistics operations for the Empire game.
+//!
+//! A recurring [`JobType::TableStats`] job samples the size of every table in
+//! [`TRACKED_TABLES`] and prunes samples older than [`TABLE_STATS_RETENTION_DAYS`]. The
+//! samples are grouped per table into a growth series for the admin API.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use tracing::{info, instrument};
+
+use crate::db::{DbConn, table_stats};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::jobs::JobType;
+use crate::domain::table_stats::{TableStats, TableStatsJobPayload};
+use crate::job_queue::{JobPriority, JobQueue};
+
+/// Name of the recurring job capturing the table statistics.
+pub const TABLE_STATS_TICK_NAME: &str = "table-stats";
+
+/// Schedule of the capture: every hour, on the hour.
+pub const TABLE_STATS_TICK_CRON: &str = "0 0 * * * *";
+
+/// How long samples are kept before the capture prunes them.
+pub const TABLE_STATS_RETENTION_DAYS: i64 = 365;
+
+/// Window of the growth series when the operator does not ask for one.
+pub const DEFAULT_GROWTH_WINDOW_DAYS: u32 = 30;
+
+/// Tables sampled by every capture: the ones growing with play and the ones kept in check
+/// by a retention policy.
+pub const TRACKED_TABLES: [&str; 16] = [
+	"active_modifiers",
+	"audit_log",
+	"battle_report",
+	"caravan",
+	"construction_queue",
+	"job",
+	"job_dead_letter",
+	"market_order",
+	"market_trade",
+	"modifier_history",
+	"player",
+	"player_building",
+	"player_session",
+	"player_unit",
+	"table_stats",
+	"training_queue",
+];
+
+/// Outcome of a capture run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureSummary {
+	/// Samples taken, one per tracked table
+	pub captured: Vec<TableStats>,
+	/// Samples deleted for being past their retention
+	pub pruned: usize,
+}
+
+/// Growth of a table over a window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableGrowth {
+	pub table_name: String,
+	/// Samples of the window, oldest first
+	pub samples: Vec<TableStats>,
+	/// Rows gained between the first and the last sample, negative if the table shrank
+	pub row_growth: i64,
+	/// Bytes gained between the first and the last sample, negative if the table shrank
+	pub byte_growth: i64,
+}
+
+/// Registers the recurring capture of the table statistics. Safe to call on every startup.
+pub fn register_table_stats_tick(job_queue: &JobQueue) -> Result<()> {
+	job_queue.register_recurring(
+		TABLE_STATS_TICK_NAME,
+		TABLE_STATS_TICK_CRON,
+		JobType::TableStats,
+		TableStatsJobPayload::Capture,
+		JobPriority::Low,
+	)?;
+	Ok(())
+}
+
+/// Samples the size of every tracked table and prunes the samples past their retention.
+#[instrument(skip(conn))]
+pub fn capture(conn: &mut DbConn, now: DateTime<Utc>) -> Result<CaptureSummary> {
+	let captured = table_stats::capture(conn, &TRACKED_TABLES, now)?;
+	let cutoff = now - TimeDelta::days(TABLE_STATS_RETENTION_DAYS);
+	let pruned = table_stats::delete_older_than(conn, cutoff)?;
+	info!(
+		"Captured {} table stats, pruned {} older than {}",
+		captured.len(),
+		pruned,
+		cutoff
+	);
+	Ok(CaptureSummary { captured, pruned })
+}
+
+/// Returns the growth of the tracked tables, or of `table_name` only, over the last `days`.
+///
+/// # Errors
+/// Returns an error if the window is empty or longer than the samples are kept
+#[instrument(skip(conn))]
+pub fn table_growth(
+	conn: &mut DbConn,
+	days: u32,
+	table_name: Option<&str>,
+	now: DateTime<Utc>,
+) -> Result<Vec<TableGrowth>> {
+	if days == 0 || i64::from(days) > TABLE_STATS_RETENTION_DAYS {
+		return Err(Error::from((
+			ErrorKind::InvalidStatsWindowError,
+			"Window must be between 1 and 365 days",
+		)));
+	}
+	let since = now - TimeDelta::days(i64::from(days));
+	let samples = table_stats::get_since(conn, since, table_name)?;
+
+	// Samples arrive ordered by table, so each table is a single run
+	let mut growth: Vec<TableGrowth> = Vec::new();
+	for sample in samples {
+		match growth.last_mut() {
+			Some(table) if table.table_name == sample.table_name => table.samples.push(sample),
+			_ => growth.push(TableGrowth {
+				table_name: sample.table_name.clone(),
+				samples: vec![sample],
+				row_growth: 0,
+				byte_growth: 0,
+			}),
+		}
+	}
+	for table in &mut growth {
+		if let (Some(first), Some(last)) = (table.samples.first(), table.samples.last()) {
+			table.row_growth = last.row_count - first.row_count;
+			table.byte_growth = last.total_bytes - first.total_bytes;
+		}
+	}
+	Ok(growth)
+}
@@ -0,0 +1,56 @@
+//! Table statistics job processor.
+//!
+//! This module implements the job processing functionality for the table statistics,
+//! sampling the size of the tracked tables on every run.
+
+use axum::extract::FromRef;
+use chrono::Utc;
+use tracing::{debug, instrument, trace};
+
+use crate::Error;
+use crate::domain::app_state::{AppPool, AppState};
+use crate::domain::jobs::{Job, JobType};
+use crate::domain::table_stats::TableStatsJobPayload;
+use crate::game::table_stats::stats_operations;
+use crate::job_queue::job_processor::{JobHandler, JobOutcome, JobWorker};
+
+/// A handler for [`JobType::TableStats`] jobs.
+///
+/// Each job captures the tracked tables once and prunes the samples past their retention.
+pub struct TableStatsHandler {
+	/// Database connection pool
+	pool: AppPool,
+}
+
+/// Workers running the [`TableStatsHandler`].
+pub type TableStatsProcessor = JobWorker<TableStatsHandler>;
+
+impl JobHandler for TableStatsHandler {
+	const JOB_TYPE: JobType = JobType::TableStats;
+	const WORKER_NAME: &'static str = "stats-goblin";
+
+	/// Creates a new `TableStatsHandler` with what it needs from the application state.
+	fn new(app_state: &AppState) -> Self {
+		Self {
+			pool: AppPool::from_ref(app_state),
+		}
+	}
+
+	#[instrument(skip(self, job), fields(job_id = %job.id))]
+	async fn handle(&self, job: Job) -> Result<JobOutcome, Error> {
+		debug!("Processing table stats job: {}", job.id);
+		trace!("Job details: {:?}", job);
+
+		let TableStatsJobPayload::Capture = serde_json::from_value(job.payload.clone())?;
+		let summary = {
+			let mut conn = self.pool.get()?;
+			stats_operations::capture(&mut conn, Utc::now())?
+		};
+
+		debug!("Completed processing table stats job: {}", job.id);
+		Ok(Some(serde_json::json!({
+			"captured": summary.captured.len(),
+			"pruned": summary.pruned,
+		})))
+	}
+}
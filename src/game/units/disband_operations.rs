@@ -0,0 +1,123 @@
+//! Unit disbanding operations for the Empire game.
+//!
+//! Players dismiss units they no longer need to stop paying their upkeep and free their
+//! population. A share of what the units cost, set by
+//! [`UnitSettings::disband_refund_percent`], goes back to the capital. Upgraded units refund
+//! the training of the units they were upgraded from and their upgrades as well.
+
+use diesel::Connection;
+use tracing::{debug, info, instrument};
+
+use crate::configuration::UnitSettings;
+use crate::db::{DbConn, player_units, resources, settlements, unit_costs, unit_upgrades, units};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::player::PlayerKey;
+use crate::domain::unit::{Unit, UnitKey};
+use crate::game::leaderboard::leaderboard_operations;
+
+/// Units disbanded by the player.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitsDisbanded {
+	pub unit: Unit,
+	pub quantity: i64,
+	/// Units of the type the player owns after disbanding
+	pub remaining: i64,
+	/// Resources refunded to the capital as (food, wood, stone, gold)
+	pub refund: (i64, i64, i64, i64),
+}
+
+/// Disbands `quantity` units of a player, refunding a share of their costs to the capital.
+///
+/// # Errors
+/// - `InvalidQuantityError` if `quantity` is not positive
+/// - `NotFoundError` if the unit does not exist
+/// - `InsufficientUnitsError` if the player owns fewer units
+#[instrument(skip(conn, settings))]
+pub fn disband_units(
+	conn: &mut DbConn,
+	settings: &UnitSettings,
+	player_id: &PlayerKey,
+	unit_id: &UnitKey,
+	quantity: i64,
+) -> Result<UnitsDisbanded> {
+	if quantity <= 0 {
+		return Err(Error::from((
+			ErrorKind::InvalidQuantityError,
+			"Quantity must be positive",
+		)));
+	}
+	let unit = units::get_by_id(conn, unit_id)?;
+	let cost = recruitment_cost(conn, unit_id)?;
+	let refund = calculate_refund(cost, quantity, settings.disband_refund_percent);
+	let capital = settlements::get_capital(conn, player_id)?;
+
+	let remaining = conn.transaction(|conn| {
+		let Some(remaining) = player_units::remove_units(conn, player_id, unit_id, quantity)?
+		else {
+			return Err(Error::from((
+				ErrorKind::InsufficientUnitsError,
+				"Not enough units",
+			)));
+		};
+		resources::add_to_settlement(conn, &capital.id, &refund)?;
+		leaderboard_operations::refresh_score(conn, player_id)?;
+		Ok::<_, Error>(remaining)
+	})?;
+
+	info!(
+		"Player {} disbanded {} {}, refunded {:?}",
+		player_id, quantity, unit.name, refund
+	);
+	Ok(UnitsDisbanded {
+		unit,
+		quantity,
+		remaining: remaining.quantity,
+		refund,
+	})
+}
+
+/// Returns what a single unit cost to recruit as (food, wood, stone, gold).
+///
+/// That is its training cost, or for an upgraded unit the training cost of the unit it was
+/// upgraded from plus the cost of every upgrade on the way.
+fn recruitment_cost(conn: &mut DbConn, unit_id: &UnitKey) -> Result<(i64, i64, i64, i64)> {
+	let mut total = (0, 0, 0, 0);
+	let mut current = *unit_id;
+	loop {
+		for cost in unit_costs::get_by_unit(conn, &current)? {
+			match cost.resource.as_str() {
+				"food" => total.0 += cost.amount,
+				"wood" => total.1 += cost.amount,
+				"stone" => total.2 += cost.amount,
+				"gold" => total.3 += cost.amount,
+				_ => {} // Ignore unknown resource types
+			}
+		}
+		let Some(upgrade) = unit_upgrades::find_by_upgraded_unit(conn, &current)? else {
+			break;
+		};
+		let upgrade_cost = upgrade.costs(1);
+		total = (
+			total.0 + upgrade_cost.0,
+			total.1 + upgrade_cost.1,
+			total.2 + upgrade_cost.2,
+			total.3 + upgrade_cost.3,
+		);
+		current = upgrade.unit_id;
+	}
+	debug!("Recruitment cost of unit {}: {:?}", unit_id, total);
+	Ok(total)
+}
+
+/// Calculates the refund of `quantity` units costing `cost` each, rounded down.
+///
+/// The percent is clamped to 0..=100, disbanding never pays more than the units cost.
+fn calculate_refund(
+	cost: (i64, i64, i64, i64),
+	quantity: i64,
+	refund_percent: i32,
+) -> (i64, i64, i64, i64) {
+	let percent = refund_percent.clamp(0, 100) as i64;
+	let share = |amount: i64| amount.saturating_mul(quantity).saturating_mul(percent) / 100;
+	(share(cost.0), share(cost.1), share(cost.2), share(cost.3))
+}
@@ -1,7 +1,12 @@
 //! Unit operations for the Empire game.
 //!
 //! This module provides core functionality for managing unit training,
-//! including queue management, resource validation, and job scheduling.
+//! including queue management, resource validation, and job scheduling, army presets trained
+//! in one go, their upkeep, their disbanding, and their upgrades to higher tiers.
 
+pub mod disband_operations;
+pub mod preset_operations;
 pub mod training_operations;
 pub mod training_processor;
+pub mod upgrade_operations;
+pub mod upkeep_operations;
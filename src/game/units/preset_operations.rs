@@ -0,0 +1,187 @@
+//! Army preset operations for the Empire game.
+//!
+//! Players save named army compositions and train them again in one go. Training a preset
+//! queues every unit of it at the military buildings of the capital, the one with the most
+//! free training slots first, and either all of it starts or none of it does, see
+//! [`start_training_batch`].
+
+use std::collections::HashMap;
+
+use tracing::{debug, info, instrument};
+
+use crate::db::{
+	DbConn, army_presets, building_unit_types, player_buildings, settlements, training_queue, units,
+};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::player::PlayerKey;
+use crate::domain::unit::preset::{ArmyPresetKey, NewArmyPreset};
+use crate::domain::unit::{Unit, UnitKey};
+use crate::game::modifiers::modifier_cache::ModifierCache;
+use crate::game::units::training_operations::{
+	TrainingOrder, TrainingStarted, start_training_batch,
+};
+use crate::job_queue::JobQueue;
+
+pub use crate::db::army_presets::FullArmyPreset;
+
+/// Maximum number of presets a player can save.
+pub const MAX_PRESETS_PER_PLAYER: i64 = 10;
+
+/// Maximum length of a preset name, in characters.
+pub const MAX_PRESET_NAME_LENGTH: usize = 32;
+
+/// Lists the presets of a player, sorted by name.
+#[instrument(skip(conn))]
+pub fn list_presets(conn: &mut DbConn, player_id: &PlayerKey) -> Result<Vec<FullArmyPreset>> {
+	army_presets::get_for_player(conn, player_id)
+}
+
+/// Saves a named army composition of `units`, given as pairs of unit and quantity.
+///
+/// # Errors
+/// - `InvalidData` if the name is blank or too long, or a unit is listed twice
+/// - `InvalidQuantityError` if there are no units or a quantity is not positive
+/// - `NotFoundError` if a unit does not exist
+/// - `InvalidBuildingTypeError` if a unit is not trained
+/// - `PresetNameTakenError` if the player has a preset of the name
+/// - `PresetLimitReachedError` if the player has [`MAX_PRESETS_PER_PLAYER`] presets
+#[instrument(skip(conn))]
+pub fn create_preset(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	name: &str,
+	units: &[(UnitKey, i64)],
+) -> Result<FullArmyPreset> {
+	let name = name.trim();
+	if name.is_empty() || name.chars().count() > MAX_PRESET_NAME_LENGTH {
+		return Err(Error::from((
+			ErrorKind::InvalidData,
+			"Preset name must be between 1 and 32 characters",
+		)));
+	}
+	if units.is_empty() || units.iter().any(|(_, quantity)| *quantity <= 0) {
+		return Err(Error::from((
+			ErrorKind::InvalidQuantityError,
+			"Quantity must be positive",
+		)));
+	}
+	let mut seen = Vec::with_capacity(units.len());
+	for (unit_id, _) in units {
+		if seen.contains(unit_id) {
+			return Err(Error::from((
+				ErrorKind::InvalidData,
+				"Each unit can only be listed once",
+			)));
+		}
+		seen.push(*unit_id);
+		if !units::get_by_id(conn, unit_id)?.is_trainable() {
+			return Err(Error::from((
+				ErrorKind::InvalidBuildingTypeError,
+				"Upgraded units cannot be trained",
+			)));
+		}
+	}
+	if army_presets::count_for_player(conn, player_id)? >= MAX_PRESETS_PER_PLAYER {
+		return Err(Error::from((
+			ErrorKind::PresetLimitReachedError,
+			"Preset limit reached",
+		)));
+	}
+
+	let preset = NewArmyPreset {
+		player_id: *player_id,
+		name: name.to_string(),
+	};
+	let created = army_presets::create(conn, preset, units)?.ok_or_else(|| {
+		Error::from((ErrorKind::PresetNameTakenError, "Preset name already taken"))
+	})?;
+	info!("Player {} saved army preset {}", player_id, created.0.name);
+	Ok(created)
+}
+
+/// Deletes a preset of a player.
+///
+/// Fails with `NotFoundError` if the player has no such preset.
+#[instrument(skip(conn))]
+pub fn delete_preset(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	preset_id: &ArmyPresetKey,
+) -> Result<()> {
+	if army_presets::delete(conn, player_id, preset_id)? == 0 {
+		return Err(Error::from((ErrorKind::NotFoundError, "Preset not found")));
+	}
+	Ok(())
+}
+
+/// Trains every unit of a preset at the military buildings of the capital.
+///
+/// Each unit of the preset takes a training slot of a building of the capital that trains
+/// its type, the building with the most free slots first. The trainings then start together
+/// through [`start_training_batch`], which fails the whole preset if anything is missing.
+///
+/// # Errors
+/// - `NotFoundError` if the player has no such preset
+/// - `TrainingQueueFullError` if no building of the capital has a free slot for a unit
+/// - Any error of [`start_training_batch`]
+#[instrument(skip(conn, job_queue, modifier_cache))]
+pub fn train_preset(
+	conn: &mut DbConn,
+	job_queue: &JobQueue,
+	modifier_cache: &ModifierCache,
+	player_id: &PlayerKey,
+	preset_id: &ArmyPresetKey,
+) -> Result<Vec<(Unit, TrainingStarted)>> {
+	let (preset, preset_units) = army_presets::get_owned(conn, player_id, preset_id)?;
+	let capital = settlements::get_capital(conn, player_id)?;
+
+	// Free training slots of every building of the capital
+	let mut free_slots = HashMap::new();
+	let capital_buildings: Vec<_> = player_buildings::get_player_buildings(conn, player_id)?
+		.into_iter()
+		.filter(|player_bld| player_bld.settlement_id == capital.id)
+		.collect();
+	for player_bld in &capital_buildings {
+		let queue = training_queue::get_queue_status(conn, &player_bld.id)?;
+		free_slots.insert(player_bld.id, queue.capacity - queue.active_count);
+	}
+
+	let mut orders = Vec::with_capacity(preset_units.len());
+	let mut trained_units = Vec::with_capacity(preset_units.len());
+	for preset_unit in &preset_units {
+		let unit = units::get_by_id(conn, &preset_unit.unit_id)?;
+		let mut best = None;
+		for player_bld in &capital_buildings {
+			let free = free_slots[&player_bld.id];
+			if free <= 0 || best.is_some_and(|(_, best_free)| best_free >= free) {
+				continue;
+			}
+			if building_unit_types::can_train_unit(conn, &player_bld.building_id, &unit.unit_type)?
+			{
+				best = Some((player_bld.id, free));
+			}
+		}
+		let Some((building_id, _)) = best else {
+			return Err(Error::from((
+				ErrorKind::TrainingQueueFullError,
+				"No free training slot for a unit of the preset",
+				format!("No building of the capital can train {} now", unit.name),
+			)));
+		};
+		*free_slots.entry(building_id).or_default() -= 1;
+		orders.push(TrainingOrder {
+			building_id,
+			unit_id: unit.id,
+			quantity: preset_unit.quantity,
+		});
+		trained_units.push(unit);
+	}
+	debug!("Training preset {} with orders {:?}", preset.name, orders);
+
+	let started = start_training_batch(conn, job_queue, modifier_cache, player_id, &orders)?;
+	info!(
+		"Player {} started training army preset {}",
+		player_id, preset.name
+	);
+	Ok(trained_units.into_iter().zip(started).collect())
+}
@@ -8,15 +8,17 @@
 //! All operations maintain transactional integrity and provide comprehensive validation
 //! of resource requirements, building constraints, and queue capacity limits.
 
+use std::collections::HashMap;
 use std::ops::Add;
 
-use bigdecimal::ToPrimitive;
+use bigdecimal::{BigDecimal, ToPrimitive};
 use chrono::{DateTime, TimeDelta, Utc};
 use diesel::Connection;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info, instrument, trace, warn};
 use uuid::Uuid;
 
+use crate::db::resources::ResourceDelta;
 use crate::db::{
 	DbConn, building_unit_types, player_buildings, player_units, resources, training_queue,
 	unit_costs, units,
@@ -25,17 +27,32 @@ use crate::domain::error::{Error, ErrorKind, Result};
 use crate::domain::jobs::JobType;
 use crate::domain::modifier::ModifierTarget;
 use crate::domain::player::PlayerKey;
-use crate::domain::player::buildings::PlayerBuildingKey;
+use crate::domain::player::buildings::{PlayerBuilding, PlayerBuildingKey};
+use crate::domain::player::resource::PlayerResource;
+use crate::domain::settlement::SettlementKey;
 use crate::domain::unit::training::{
-	NewTrainingQueueEntry, TrainingQueueEntry, TrainingQueueKey, TrainingStatus,
+	NewTrainingQueueEntry, TrainingMode, TrainingQueueEntry, TrainingQueueKey, TrainingStatus,
 };
 use crate::domain::unit::{Unit, UnitKey};
+use crate::game::mail::mail_operations;
+use crate::game::modifiers::modifier_cache::ModifierCache;
 use crate::game::modifiers::modifier_operations;
+use crate::game::units::upkeep_operations;
+use crate::job_queue::cancellation::JobCancellation;
 use crate::job_queue::{JobPriority, JobQueue};
 
 /// Refund percentage when cancelling training (80% = 0.80)
 pub const CANCEL_REFUND_RATE: f64 = 0.80;
 
+/// Maximum number of trainings a single building can start per [`TRAINING_THROTTLE_WINDOW`].
+///
+/// Cancelled trainings count as well, so refunds cannot be churned by starting and
+/// cancelling in a loop.
+pub const MAX_TRAINING_STARTS_PER_WINDOW: i64 = 10;
+
+/// Window over which [`MAX_TRAINING_STARTS_PER_WINDOW`] is enforced.
+pub const TRAINING_THROTTLE_WINDOW: TimeDelta = TimeDelta::minutes(1);
+
 /// Job payload for training completion jobs.
 ///
 /// This is serialized to JSON and stored in the job table.
@@ -47,28 +64,56 @@ pub struct TrainingJobPayload {
 	pub quantity: i64,
 }
 
+/// A started training, as recorded in the queue and scheduled in the job queue.
+///
+/// In a building that trains sequentially the entry may be pending behind other trainings,
+/// it has no completion job yet and its completion time is an estimate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrainingStarted {
+	pub entry: TrainingQueueEntry,
+	/// The exact time the completion job is scheduled for, also stored on the entry
+	pub completion_time: DateTime<Utc>,
+	/// Resources deducted as (food, wood, stone, gold)
+	pub costs: (i64, i64, i64, i64),
+}
+
+/// When an active training queue entry starts and completes, estimated for pending entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrainingEstimate {
+	pub starts_at: DateTime<Utc>,
+	pub completes_at: DateTime<Utc>,
+}
+
 /// Starts training units at a specified building.
 ///
 /// # Validation
 /// - Building must be owned by the player
 /// - Building must be capable of training the specified unit type
-/// - Player must have sufficient resources
+/// - Unit must be of the first tier, higher tiers are reached by upgrading
+/// - The settlement of the building must have sufficient resources
+/// - Player must have enough free population to house the units
 /// - Quantity must be positive
+/// - Building must not have started [`MAX_TRAINING_STARTS_PER_WINDOW`] trainings within the
+///   last [`TRAINING_THROTTLE_WINDOW`]
 /// - Building's training queue must not exceed its capacity (based on building level)
 ///
+/// A building in [`TrainingMode::Sequential`] trains one entry at a time: while its queue is
+/// not empty, the new entry is pending and starts once the entries before it completed.
+///
 /// # Returns
-/// A tuple of (TrainingQueueEntry, completion_time) where completion_time is the
-/// exact DateTime used to schedule the job, ensuring consistency between API responses
-/// and actual job execution.
-#[instrument(skip(conn, job_queue))]
+/// The [`TrainingStarted`] entry with its completion time and the resources spent. The
+/// completion time is stored on the entry and used to schedule the job, so API responses,
+/// progress and job execution all agree on it.
+#[instrument(skip(conn, job_queue, modifier_cache))]
 pub fn start_training(
 	conn: &mut DbConn,
 	job_queue: &JobQueue,
+	modifier_cache: &ModifierCache,
 	player_id: &PlayerKey,
 	building_id: &PlayerBuildingKey,
 	unit_id: &UnitKey,
 	quantity: i64,
-) -> Result<(TrainingQueueEntry, DateTime<Utc>)> {
+) -> Result<TrainingStarted> {
 	debug!(
 		"Starting training for player {} at building {}: unit {} x {}",
 		player_id, building_id, unit_id, quantity
@@ -83,9 +128,27 @@ pub fn start_training(
 	}
 
 	// Validate building ownership
-	let player_bld = validate_building_ownership(conn, player_id, building_id)?;
+	let player_bld = player_buildings::get_owned(conn, player_id, building_id)?;
 	trace!("Building ownership validated: {:?}", player_bld);
 
+	// AIDEV-NOTE: soft throttle, checked outside the transaction so concurrent requests can
+	// overshoot the limit slightly. It curbs scripted churn, it is not an exact quota.
+	let recent_starts = training_queue::count_started_since(
+		conn,
+		building_id,
+		Utc::now() - TRAINING_THROTTLE_WINDOW,
+	)?;
+	if recent_starts >= MAX_TRAINING_STARTS_PER_WINDOW {
+		debug!(
+			"Building {} started {} trainings within the throttle window",
+			building_id, recent_starts
+		);
+		return Err(Error::from((
+			ErrorKind::TrainingThrottledError,
+			"Too many trainings started at this building, try again in a minute",
+		)));
+	}
+
 	// Get unit details
 	let unit = units::get_by_id(conn, unit_id)?;
 	trace!("Unit details: {:?}", unit);
@@ -95,7 +158,7 @@ pub fn start_training(
 	trace!("Building-unit type match validated");
 
 	// Check resources (before transaction to fail fast)
-	if !has_enough_resources(conn, player_id, unit_id, quantity)? {
+	if !has_enough_resources(conn, &player_bld.settlement_id, unit_id, quantity)? {
 		return Err(Error::from((
 			ErrorKind::InsufficientResourcesError,
 			"Not enough resources",
@@ -103,12 +166,25 @@ pub fn start_training(
 	}
 	trace!("Resource check passed");
 
+	// AIDEV-NOTE: like the resource check, this runs outside the transaction, so concurrent
+	// trainings of the same player can overshoot the population capacity slightly.
+	let population = upkeep_operations::get_population(conn, player_id)?;
+	if population.housable(&unit) < quantity {
+		return Err(Error::from((
+			ErrorKind::PopulationCapReachedError,
+			"Not enough population",
+		)));
+	}
+	trace!("Population check passed: {:?}", population);
+
 	// Calculate training duration with faction bonuses
-	let duration = calculate_training_duration(conn, player_id, &unit, quantity)?;
+	let (training_modifier, seconds_per_unit) =
+		calculate_unit_training_time(conn, modifier_cache, player_id, &unit)?;
+	let duration = TimeDelta::seconds(seconds_per_unit * quantity);
 	let completion_time = Utc::now().add(duration);
 	trace!(
-		"Training duration: {:?}, completion at: {}",
-		duration, completion_time
+		"Training duration: {:?} ({}s per unit, modifier {}), completion at: {}",
+		duration, seconds_per_unit, training_modifier, completion_time
 	);
 
 	// Execute transaction: check queue capacity (with lock), deduct resources, create entry
@@ -132,17 +208,27 @@ pub fn start_training(
 
 		// Deduct resources
 		let costs = get_total_cost(connection, unit_id, quantity)?;
-		resources::deduct(connection, player_id, &costs)?;
+		resources::deduct_from_settlement(connection, &player_bld.settlement_id, &costs)?;
 		trace!("Resources deducted: {:?}", costs);
 
+		// A sequential queue that is busy trains the entry after the ones already queued
+		let (status, completes_at) =
+			match queue_end(connection, building_id, player_bld.training_mode)? {
+				Some(end) => (TrainingStatus::Pending, end.add(duration)),
+				None => (TrainingStatus::InProgress, completion_time),
+			};
+
 		// Create training queue entry
 		let new_entry = NewTrainingQueueEntry {
 			player_id: *player_id,
 			building_id: *building_id,
 			unit_id: *unit_id,
 			quantity,
-			status: Some(TrainingStatus::InProgress),
+			status: Some(status),
 			job_id: None, // Will be set after job is scheduled
+			completes_at,
+			training_modifier,
+			seconds_per_unit,
 		};
 		let entry = training_queue::create(connection, new_entry)?;
 		trace!("Training queue entry created: {:?}", entry);
@@ -159,6 +245,19 @@ pub fn start_training(
 		))
 	})?;
 
+	if entry.status == TrainingStatus::Pending {
+		// The completion job is scheduled when the entry starts
+		info!(
+			"Queued training for player {}: {} x {} units, pending until {}",
+			player_id, quantity, unit.name, entry.completes_at
+		);
+		return Ok(TrainingStarted {
+			completion_time: entry.completes_at,
+			entry,
+			costs,
+		});
+	}
+
 	// Schedule completion job (outside transaction to avoid holding locks)
 	let payload = TrainingJobPayload {
 		training_queue_entry_id: entry.id,
@@ -170,13 +269,15 @@ pub fn start_training(
 		JobType::Training,
 		payload,
 		JobPriority::Normal,
-		completion_time,
+		entry.completes_at,
 	) {
 		Ok(id) => id,
 		Err(e) => {
 			// AIDEV-NOTE: Cleanup on enqueue failure - refund resources and delete entry
 			warn!("Failed to schedule training job, rolling back: {}", e);
-			if let Err(cleanup_err) = cleanup_failed_training(conn, &entry.id, player_id, &costs) {
+			if let Err(cleanup_err) =
+				cleanup_failed_training(conn, &entry.id, &player_bld.settlement_id, &costs)
+			{
 				warn!("Failed to cleanup after enqueue failure: {}", cleanup_err);
 			}
 			return Err(Error::from((
@@ -203,7 +304,358 @@ pub fn start_training(
 		"Successfully started training for player {}: {} x {} units",
 		player_id, quantity, unit.name
 	);
-	Ok((entry, completion_time))
+	// The stored time has database precision, hand out exactly that one
+	Ok(TrainingStarted {
+		completion_time: entry.completes_at,
+		entry,
+		costs,
+	})
+}
+
+/// Starts training as many units as the player can afford and house, optionally capped.
+///
+/// The quantity is computed with [`max_affordable`], the same math the availability
+/// endpoint reports, limited to the free population, and the training is then started
+/// through [`start_training`], so a "max" button never asks for more than the server accepts.
+///
+/// # Errors
+/// - `InvalidQuantityError` if `max_quantity` is not positive
+/// - `TrainingQueueFullError` if the building has no free training slot
+/// - `InsufficientResourcesError` if not even a single unit is affordable
+/// - `PopulationCapReachedError` if not even a single unit can be housed
+#[instrument(skip(conn, job_queue, modifier_cache))]
+pub fn train_to_fill(
+	conn: &mut DbConn,
+	job_queue: &JobQueue,
+	modifier_cache: &ModifierCache,
+	player_id: &PlayerKey,
+	building_id: &PlayerBuildingKey,
+	unit_id: &UnitKey,
+	max_quantity: Option<i64>,
+) -> Result<TrainingStarted> {
+	if max_quantity.is_some_and(|max| max <= 0) {
+		return Err(Error::from((
+			ErrorKind::InvalidQuantityError,
+			"Quantity must be positive",
+		)));
+	}
+	let player_bld = player_buildings::get_owned(conn, player_id, building_id)?;
+
+	let queue_status = training_queue::get_queue_status(conn, building_id)?;
+	if queue_status.active_count >= queue_status.capacity {
+		return Err(Error::from((
+			ErrorKind::TrainingQueueFullError,
+			"Training queue is full for this building",
+		)));
+	}
+
+	let unit_cost = get_total_cost(conn, unit_id, 1)?;
+	let player_res = resources::get_by_settlement(conn, &player_bld.settlement_id)?;
+	let affordable = max_affordable(&player_res, unit_cost);
+	let unit = units::get_by_id(conn, unit_id)?;
+	let housable = upkeep_operations::get_population(conn, player_id)?.housable(&unit);
+	let quantity = max_quantity
+		.map_or(affordable, |max| max.min(affordable))
+		.min(housable);
+	debug!(
+		"Filling training at building {}: {} affordable, {} housable, training {}",
+		building_id, affordable, housable, quantity
+	);
+	if affordable <= 0 {
+		return Err(Error::from((
+			ErrorKind::InsufficientResourcesError,
+			"Not enough resources",
+		)));
+	}
+	if quantity <= 0 {
+		return Err(Error::from((
+			ErrorKind::PopulationCapReachedError,
+			"Not enough population",
+		)));
+	}
+
+	start_training(
+		conn,
+		job_queue,
+		modifier_cache,
+		player_id,
+		building_id,
+		unit_id,
+		quantity,
+	)
+}
+
+/// A training to start as part of a batch: `quantity` units of a unit at a building.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrainingOrder {
+	pub building_id: PlayerBuildingKey,
+	pub unit_id: UnitKey,
+	pub quantity: i64,
+}
+
+/// An order of a batch after validation, with what it costs and when it completes.
+struct PlannedTraining {
+	order: TrainingOrder,
+	settlement_id: SettlementKey,
+	training_mode: TrainingMode,
+	unit: Unit,
+	costs: (i64, i64, i64, i64),
+	training_modifier: BigDecimal,
+	seconds_per_unit: i64,
+	completes_at: DateTime<Utc>,
+}
+
+/// Starts several trainings at once, all of them or none.
+///
+/// Every order is validated like in [`start_training`], and the resources, population and
+/// training slots are checked for the orders together: an order fails the whole batch, and
+/// nothing is deducted or queued. The completion jobs are scheduled in one batch after the
+/// transaction, and if that fails every entry is refunded and removed again. Orders for a
+/// sequential building are queued one after another, in the order they are given.
+///
+/// # Errors
+/// - `InvalidQuantityError` if there are no orders or a quantity is not positive
+/// - `TrainingThrottledError` if a building would start too many trainings
+/// - `InsufficientResourcesError` if a settlement cannot pay for all of its orders
+/// - `PopulationCapReachedError` if the units of all orders cannot be housed
+/// - `TrainingQueueFullError` if a building has fewer free slots than orders
+/// - Any error of [`start_training`] for an invalid building or unit
+#[instrument(skip(conn, job_queue, modifier_cache))]
+pub fn start_training_batch(
+	conn: &mut DbConn,
+	job_queue: &JobQueue,
+	modifier_cache: &ModifierCache,
+	player_id: &PlayerKey,
+	orders: &[TrainingOrder],
+) -> Result<Vec<TrainingStarted>> {
+	debug!(
+		"Starting {} trainings for player {}",
+		orders.len(),
+		player_id
+	);
+	if orders.is_empty() || orders.iter().any(|order| order.quantity <= 0) {
+		return Err(Error::from((
+			ErrorKind::InvalidQuantityError,
+			"Quantity must be positive",
+		)));
+	}
+
+	// Validate every order and add up what the orders need, before the transaction to fail fast
+	let mut planned = Vec::with_capacity(orders.len());
+	let mut settlement_costs: HashMap<SettlementKey, (i64, i64, i64, i64)> = HashMap::new();
+	let mut building_orders: HashMap<PlayerBuildingKey, i64> = HashMap::new();
+	let mut population_needed: i64 = 0;
+	let now = Utc::now();
+	for order in orders {
+		let player_bld = player_buildings::get_owned(conn, player_id, &order.building_id)?;
+		let unit = units::get_by_id(conn, &order.unit_id)?;
+		validate_building_unit_match(conn, &player_bld.building_id, &unit)?;
+
+		let costs = get_total_cost(conn, &order.unit_id, order.quantity)?;
+		let total = settlement_costs
+			.entry(player_bld.settlement_id)
+			.or_default();
+		*total = (
+			total.0 + costs.0,
+			total.1 + costs.1,
+			total.2 + costs.2,
+			total.3 + costs.3,
+		);
+		*building_orders.entry(order.building_id).or_default() += 1;
+		population_needed = population_needed.saturating_add(unit.population * order.quantity);
+
+		let (training_modifier, seconds_per_unit) =
+			calculate_unit_training_time(conn, modifier_cache, player_id, &unit)?;
+		planned.push(PlannedTraining {
+			order: *order,
+			settlement_id: player_bld.settlement_id,
+			training_mode: player_bld.training_mode,
+			unit,
+			costs,
+			training_modifier,
+			seconds_per_unit,
+			completes_at: now.add(TimeDelta::seconds(seconds_per_unit * order.quantity)),
+		});
+	}
+
+	for (building_id, count) in &building_orders {
+		let recent_starts =
+			training_queue::count_started_since(conn, building_id, now - TRAINING_THROTTLE_WINDOW)?;
+		if recent_starts + count > MAX_TRAINING_STARTS_PER_WINDOW {
+			return Err(Error::from((
+				ErrorKind::TrainingThrottledError,
+				"Too many trainings started at this building, try again in a minute",
+			)));
+		}
+	}
+	for (settlement_id, costs) in &settlement_costs {
+		let player_res = resources::get_by_settlement(conn, settlement_id)?;
+		if player_res.food < costs.0
+			|| player_res.wood < costs.1
+			|| player_res.stone < costs.2
+			|| player_res.gold < costs.3
+		{
+			return Err(Error::from((
+				ErrorKind::InsufficientResourcesError,
+				"Not enough resources",
+			)));
+		}
+	}
+	let population = upkeep_operations::get_population(conn, player_id)?;
+	if population.free() < population_needed {
+		return Err(Error::from((
+			ErrorKind::PopulationCapReachedError,
+			"Not enough population",
+		)));
+	}
+	trace!("Batch validated: {:?}", settlement_costs);
+
+	// Execute transaction: check the queue capacity of every building (with lock), deduct
+	// the resources of every settlement, create all entries
+	let entries = conn.transaction(|connection| {
+		for (building_id, count) in &building_orders {
+			let queue_state = training_queue::get_queue_state(connection, building_id)?;
+			if queue_state.active_count + count > queue_state.capacity {
+				return Err(Error::from((
+					ErrorKind::TrainingQueueFullError,
+					"Training queue is full for this building",
+				)));
+			}
+		}
+		for (settlement_id, costs) in &settlement_costs {
+			resources::deduct_from_settlement(connection, settlement_id, costs)?;
+		}
+
+		let mut queue_ends: HashMap<PlayerBuildingKey, DateTime<Utc>> = HashMap::new();
+		let mut entries = Vec::with_capacity(planned.len());
+		for plan in &planned {
+			let building_id = plan.order.building_id;
+			let queued_after = match queue_ends.get(&building_id) {
+				Some(end) => Some(*end),
+				None => queue_end(connection, &building_id, plan.training_mode)?,
+			};
+			let (status, completes_at) = match queued_after {
+				Some(end) => (
+					TrainingStatus::Pending,
+					end.add(TimeDelta::seconds(
+						plan.seconds_per_unit * plan.order.quantity,
+					)),
+				),
+				None => (TrainingStatus::InProgress, plan.completes_at),
+			};
+			// The next order for a sequential building waits for this one
+			if plan.training_mode == TrainingMode::Sequential {
+				queue_ends.insert(building_id, completes_at);
+			}
+
+			let new_entry = NewTrainingQueueEntry {
+				player_id: *player_id,
+				building_id,
+				unit_id: plan.order.unit_id,
+				quantity: plan.order.quantity,
+				status: Some(status),
+				job_id: None, // Will be set after the jobs are scheduled
+				completes_at,
+				training_modifier: plan.training_modifier.clone(),
+				seconds_per_unit: plan.seconds_per_unit,
+			};
+			entries.push(training_queue::create(connection, new_entry)?);
+		}
+		Ok::<_, Error>(entries)
+	})?;
+
+	// Schedule the completion jobs of all started entries (outside transaction to avoid
+	// holding locks), pending entries get theirs when they start
+	let jobs = entries
+		.iter()
+		.filter(|entry| entry.status == TrainingStatus::InProgress)
+		.map(|entry| {
+			let payload = TrainingJobPayload {
+				training_queue_entry_id: entry.id,
+				player_id: *player_id,
+				unit_id: entry.unit_id,
+				quantity: entry.quantity,
+			};
+			Ok((
+				JobType::Training,
+				serde_json::to_value(payload)?,
+				JobPriority::Normal,
+				entry.completes_at,
+			))
+		})
+		.collect::<Result<Vec<_>>>()?;
+	let job_ids = match job_queue.enqueue_batch(jobs) {
+		Ok(ids) => ids,
+		Err(e) => {
+			// AIDEV-NOTE: Cleanup on enqueue failure - refund and delete every entry
+			warn!("Failed to schedule training jobs, rolling back: {}", e);
+			for (entry, plan) in entries.iter().zip(&planned) {
+				if let Err(cleanup_err) =
+					cleanup_failed_training(conn, &entry.id, &plan.settlement_id, &plan.costs)
+				{
+					warn!("Failed to cleanup after enqueue failure: {}", cleanup_err);
+				}
+			}
+			return Err(Error::from((
+				ErrorKind::StartTrainingError,
+				"Failed to schedule training jobs",
+				format!("{:?}", e),
+			)));
+		}
+	};
+
+	let mut job_ids = job_ids.into_iter();
+	let mut started = Vec::with_capacity(entries.len());
+	for (entry, plan) in entries.into_iter().zip(planned) {
+		let job_id = match entry.status {
+			TrainingStatus::InProgress => job_ids.next(),
+			_ => None,
+		};
+		// Non-critical like in start_training, complete_training uses the payload
+		let entry = match job_id.map(|job_id| training_queue::set_job_id(conn, &entry.id, &job_id))
+		{
+			Some(Ok(updated)) => updated,
+			Some(Err(e)) => {
+				warn!("Failed to link job_id to entry, continuing anyway: {}", e);
+				entry
+			}
+			None => entry,
+		};
+		trace!(
+			"Started training of {} x {}",
+			entry.quantity, plan.unit.name
+		);
+		started.push(TrainingStarted {
+			completion_time: entry.completes_at,
+			entry,
+			costs: plan.costs,
+		});
+	}
+
+	info!(
+		"Successfully started {} trainings for player {}",
+		started.len(),
+		player_id
+	);
+	Ok(started)
+}
+
+/// Returns how many units costing `unit_cost` each the player can afford.
+///
+/// A unit without any cost is affordable in any quantity, which is reported as `i64::MAX`.
+pub fn max_affordable(player_res: &PlayerResource, unit_cost: (i64, i64, i64, i64)) -> i64 {
+	let (food, wood, stone, gold) = unit_cost;
+	[
+		(player_res.food, food),
+		(player_res.wood, wood),
+		(player_res.stone, stone),
+		(player_res.gold, gold),
+	]
+	.into_iter()
+	.filter(|(_, cost)| *cost > 0)
+	.map(|(held, cost)| (held / cost).max(0))
+	.min()
+	.unwrap_or(i64::MAX)
 }
 
 /// Cancels an in-progress or pending training entry.
@@ -224,16 +676,8 @@ pub fn cancel_training(
 ) -> Result<(TrainingQueueEntry, (i64, i64, i64, i64))> {
 	debug!("Cancelling training {} for player {}", entry_id, player_id);
 
-	// Get training entry
-	let entry = training_queue::get_by_id(conn, entry_id)?;
-
-	// Validate ownership
-	if &entry.player_id != player_id {
-		return Err(Error::from((
-			ErrorKind::CancelTrainingError,
-			"Training entry not found",
-		)));
-	}
+	// Get training entry (validates ownership)
+	let entry = training_queue::get_owned(conn, player_id, entry_id)?;
 
 	// Check if cancellable
 	if entry.status == TrainingStatus::Completed || entry.status == TrainingStatus::Cancelled {
@@ -243,47 +687,191 @@ pub fn cancel_training(
 		)));
 	}
 
-	// Calculate refund
-	let refund = calculate_refund(conn, &entry)?;
-	trace!("Calculated refund: {:?}", refund);
+	// The refund goes to the settlement of the training building
+	let settlement_id = player_buildings::get_by_id(conn, &entry.building_id)?.settlement_id;
 
 	// Execute transaction
-	let res: Result<TrainingQueueEntry> = conn.transaction(|connection| {
-		// Refund resources
+	let res: Result<Option<(TrainingQueueEntry, ResourceDelta)>> = conn.transaction(|connection| {
+		// Cancel entry first, it only succeeds while the training has not completed, so a
+		// completion racing this cancellation cannot grant the units on top of the refund.
+		// The refund rate depends on whether the training started, so must its status.
+		let Some(cancelled) = training_queue::cancel(connection, entry_id, &entry.status)? else {
+			return Ok(None);
+		};
+		trace!("Training entry cancelled: {:?}", cancelled);
+
+		// Refund the units the entry held when it was cancelled, a partial cancellation
+		// may have reduced them since it was read
+		let refunded = TrainingQueueEntry {
+			status: entry.status,
+			..cancelled.clone()
+		};
+		let refund = calculate_refund(connection, &refunded, cancelled.quantity)?;
+		trace!("Calculated refund: {:?}", refund);
 		if refund.0 > 0 || refund.1 > 0 || refund.2 > 0 || refund.3 > 0 {
-			resources::add(connection, player_id, &refund)?;
+			resources::add_to_settlement(connection, &settlement_id, &refund)?;
 			trace!("Refunded resources");
 		}
 
-		// Cancel entry
-		training_queue::cancel(connection, entry_id)?;
-		let cancelled = training_queue::get_by_id(connection, entry_id)?;
-		trace!("Training entry cancelled: {:?}", cancelled);
-
-		Ok(cancelled)
+		Ok(Some((cancelled, refund)))
 	});
 
-	let cancelled_entry = res.map_err(|e| {
-		warn!("Failed to cancel training {}: {}", entry_id, e);
-		Error::from((
-			ErrorKind::CancelTrainingError,
-			"Failed to cancel training",
-			format!("{:?}", e),
-		))
-	})?;
+	let (cancelled_entry, refund) = res
+		.map_err(|e| {
+			warn!("Failed to cancel training {}: {}", entry_id, e);
+			Error::from((
+				ErrorKind::CancelTrainingError,
+				"Failed to cancel training",
+				format!("{:?}", e),
+			))
+		})?
+		.ok_or_else(|| {
+			debug!(
+				"Training {} completed before it could be cancelled",
+				entry_id
+			);
+			Error::from((
+				ErrorKind::CancelTrainingError,
+				"Training cannot be cancelled",
+			))
+		})?;
 
 	// Cancel the associated job if one exists
 	if let Some(job_id) = entry.job_id {
 		match job_queue.cancel_job(&job_id) {
-			Ok(true) => trace!("Cancelled job {}", job_id),
-			Ok(false) => trace!("Job {} was not pending, may have already started", job_id),
+			Ok(JobCancellation::Cancelled) => trace!("Cancelled job {}", job_id),
+			Ok(JobCancellation::Requested) => {
+				trace!("Job {} already started, requested its cancellation", job_id)
+			}
+			Ok(JobCancellation::NotCancellable) => trace!("Job {} already finished", job_id),
 			Err(e) => warn!("Failed to cancel job {}: {}", job_id, e),
 		}
 	}
 
+	// The cancelled entry frees a sequential queue, the next pending entry starts now
+	if entry.status == TrainingStatus::InProgress
+		&& let Err(e) = start_next_training(conn, job_queue, &entry.building_id)
+	{
+		warn!(
+			"Failed to start the next training at building {}: {}",
+			entry.building_id, e
+		);
+	}
+
 	Ok((cancelled_entry, refund))
 }
 
+/// Cancels part of a training entry, keeping the remaining units in training.
+///
+/// The cancelled units are the last ones of the batch, so the entry finishes earlier by
+/// their training time and its completion job is rescheduled accordingly. They are refunded
+/// like a full cancellation of their share: 80% of their cost for the time they had left.
+/// Cancelling the whole quantity is the same as [`cancel_training`].
+///
+/// # Returns
+/// A tuple of (TrainingQueueEntry with the remaining quantity, refund amounts as (food, wood, stone, gold))
+#[instrument(skip(conn, job_queue))]
+pub fn cancel_training_units(
+	conn: &mut DbConn,
+	job_queue: &JobQueue,
+	player_id: &PlayerKey,
+	entry_id: &TrainingQueueKey,
+	quantity: i64,
+) -> Result<(TrainingQueueEntry, (i64, i64, i64, i64))> {
+	debug!(
+		"Cancelling {} units of training {} for player {}",
+		quantity, entry_id, player_id
+	);
+
+	if quantity <= 0 {
+		return Err(Error::from((
+			ErrorKind::InvalidQuantityError,
+			"Quantity must be positive",
+		)));
+	}
+
+	// Get training entry (validates ownership)
+	let entry = training_queue::get_owned(conn, player_id, entry_id)?;
+	if quantity > entry.quantity {
+		return Err(Error::from((
+			ErrorKind::InvalidQuantityError,
+			"Cannot cancel more units than are in training",
+		)));
+	}
+	if quantity == entry.quantity {
+		return cancel_training(conn, job_queue, player_id, entry_id);
+	}
+	if entry.status == TrainingStatus::Completed || entry.status == TrainingStatus::Cancelled {
+		return Err(Error::from((
+			ErrorKind::CancelTrainingError,
+			"Training cannot be cancelled",
+		)));
+	}
+
+	let settlement_id = player_buildings::get_by_id(conn, &entry.building_id)?.settlement_id;
+
+	// Units that are already trained complete right away
+	let remaining = entry.quantity - quantity;
+	let completes_at = (entry.started_at
+		+ TimeDelta::seconds(entry.seconds_per_unit.saturating_mul(remaining)))
+	.max(Utc::now());
+
+	// Move the completion job first, a job that already started completes the full batch
+	if let Some(job_id) = entry.job_id
+		&& !job_queue.reschedule_job(&job_id, completes_at)?
+	{
+		return Err(Error::from((
+			ErrorKind::CancelTrainingError,
+			"Training is already completing",
+		)));
+	}
+
+	let res: Result<(TrainingQueueEntry, ResourceDelta)> = conn.transaction(|connection| {
+		// Only reduces the quantity read above, a concurrent cancellation fails this one
+		let reduced = training_queue::reduce_quantity(
+			connection,
+			entry_id,
+			entry.quantity,
+			remaining,
+			completes_at,
+		)?
+		.ok_or_else(|| {
+			Error::from((
+				ErrorKind::CancelTrainingError,
+				"Training cannot be cancelled",
+			))
+		})?;
+		trace!("Training entry reduced: {:?}", reduced);
+
+		// Refund the units the update took off the entry
+		let refund = calculate_refund(connection, &entry, entry.quantity - reduced.quantity)?;
+		trace!("Calculated refund: {:?}", refund);
+		if refund.0 > 0 || refund.1 > 0 || refund.2 > 0 || refund.3 > 0 {
+			resources::add_to_settlement(connection, &settlement_id, &refund)?;
+			trace!("Refunded resources");
+		}
+
+		Ok((reduced, refund))
+	});
+
+	res.map_err(|e| {
+		warn!("Failed to cancel units of training {}: {}", entry_id, e);
+		// AIDEV-NOTE: put the job back to when the entry completes now, otherwise it completes
+		// the batch early. A concurrent cancellation may have moved it in the meantime.
+		if let Some(job_id) = entry.job_id
+			&& let Err(restore_err) = training_queue::get_by_id(conn, entry_id)
+				.and_then(|current| job_queue.reschedule_job(&job_id, current.completes_at))
+		{
+			warn!("Failed to restore training job {}: {}", job_id, restore_err);
+		}
+		Error::from((
+			ErrorKind::CancelTrainingError,
+			"Failed to cancel training",
+			format!("{:?}", e),
+		))
+	})
+}
+
 /// Completes a training entry and adds units to player inventory.
 ///
 /// Called by the job processor when training time has elapsed.
@@ -321,16 +909,26 @@ pub fn complete_training(
 
 	// Execute transaction
 	let res: Result<TrainingQueueEntry> = conn.transaction(|connection| {
-		// Add units to player inventory
-		player_units::add_units(connection, &entry.player_id, &entry.unit_id, entry.quantity)?;
+		// Mark training as completed first, it only succeeds while the training is active,
+		// so units are never granted for a training cancelled in the meantime
+		let Some(completed) = training_queue::complete(connection, &entry.id)? else {
+			debug!("Training {} is no longer active, skipping", entry.id);
+			return training_queue::get_by_id(connection, &entry.id);
+		};
+		trace!("Training entry completed: {:?}", completed);
+
+		// Add units to player inventory, as many as are left after partial cancellations
+		player_units::add_units(
+			connection,
+			&completed.player_id,
+			&completed.unit_id,
+			completed.quantity,
+		)?;
 		trace!(
 			"Added {} units to player {} inventory",
-			entry.quantity, entry.player_id
+			completed.quantity, completed.player_id
 		);
-
-		// Mark training as completed
-		let completed = training_queue::complete(connection, &entry.id)?;
-		trace!("Training entry completed: {:?}", completed);
+		mail_operations::notify_training(connection, &completed)?;
 
 		Ok(completed)
 	});
@@ -348,6 +946,112 @@ pub fn complete_training(
 	})
 }
 
+/// Starts the next pending training of a building that trains sequentially.
+///
+/// Called when an entry of the building completes or is cancelled. Does nothing while an
+/// entry of the building is still in training or none is pending. The entry starts now with
+/// the training time recorded when it was queued, and its completion job is scheduled. If
+/// scheduling fails, the entry goes back to pending so that a retry starts it again.
+///
+/// # Returns
+/// The started entry, or `None` if no entry was started
+#[instrument(skip(conn, job_queue))]
+pub fn start_next_training(
+	conn: &mut DbConn,
+	job_queue: &JobQueue,
+	building_id: &PlayerBuildingKey,
+) -> Result<Option<TrainingQueueEntry>> {
+	let Some(entry) = conn.transaction(|connection| {
+		training_queue::start_next_pending(connection, building_id, Utc::now())
+	})?
+	else {
+		return Ok(None);
+	};
+	debug!(
+		"Started pending training {} at building {}",
+		entry.id, building_id
+	);
+
+	let payload = TrainingJobPayload {
+		training_queue_entry_id: entry.id,
+		player_id: entry.player_id,
+		unit_id: entry.unit_id,
+		quantity: entry.quantity,
+	};
+	let job_id = match job_queue.enqueue(
+		JobType::Training,
+		payload,
+		JobPriority::Normal,
+		entry.completes_at,
+	) {
+		Ok(id) => id,
+		Err(e) => {
+			warn!("Failed to schedule training job, back to pending: {}", e);
+			if let Err(revert_err) =
+				training_queue::update_status(conn, &entry.id, &TrainingStatus::Pending)
+			{
+				warn!("Failed to put training back to pending: {}", revert_err);
+			}
+			return Err(Error::from((
+				ErrorKind::StartTrainingError,
+				"Failed to schedule training job",
+				format!("{:?}", e),
+			)));
+		}
+	};
+	trace!("Scheduled training job: {}", job_id);
+
+	// Non-critical like in start_training, complete_training uses the payload
+	let entry = match training_queue::set_job_id(conn, &entry.id, &job_id) {
+		Ok(updated) => updated,
+		Err(e) => {
+			warn!("Failed to link job_id to entry, continuing anyway: {}", e);
+			entry
+		}
+	};
+	Ok(Some(entry))
+}
+
+/// Changes how a building works through its training queue.
+///
+/// The mode only changes while the queue of the building is empty, so no entry is ever
+/// trained under the rules of the other mode.
+///
+/// # Errors
+/// - `NotFoundError` if the building does not belong to the player
+/// - `InvalidBuildingTypeError` if the building does not train units
+/// - `ChangeTrainingModeError` if the building has trainings queued
+#[instrument(skip(conn))]
+pub fn set_training_mode(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	building_id: &PlayerBuildingKey,
+	mode: TrainingMode,
+) -> Result<PlayerBuilding> {
+	debug!(
+		"Setting training mode of building {} to {:?}",
+		building_id, mode
+	);
+	player_buildings::get_owned(conn, player_id, building_id)?;
+
+	conn.transaction(|connection| {
+		let queue_state = training_queue::get_queue_state(connection, building_id)?;
+		if queue_state.capacity == 0 {
+			return Err(Error::from((
+				ErrorKind::InvalidBuildingTypeError,
+				"Building does not train units",
+			)));
+		}
+		if queue_state.active_count > 0 {
+			return Err(Error::from((
+				ErrorKind::ChangeTrainingModeError,
+				"Training mode can only change while the training queue is empty",
+			)));
+		}
+		player_buildings::set_training_mode(connection, building_id, mode)
+	})
+}
+
 /// Gets all units that can be trained at a specific building.
 ///
 /// Validates building ownership and returns available unit types.
@@ -363,7 +1067,7 @@ pub fn get_available_units_for_building(
 	);
 
 	// Validate building ownership
-	let player_bld = validate_building_ownership(conn, player_id, building_id)?;
+	let player_bld = player_buildings::get_owned(conn, player_id, building_id)?;
 
 	// Get unit types trainable at this building
 	let unit_types =
@@ -373,31 +1077,130 @@ pub fn get_available_units_for_building(
 		player_bld.building_id, unit_types
 	);
 
-	// Get all units of those types
+	// Get all trainable units of those types, upgraded units come from the Blacksmith
 	let mut available_units = Vec::new();
 	for utype in unit_types {
 		let type_units = units::get_by_type(conn, &utype)?;
-		available_units.extend(type_units);
+		available_units.extend(type_units.into_iter().filter(Unit::is_trainable));
 	}
 
 	trace!("Found {} available units", available_units.len());
 	Ok(available_units)
 }
 
+/// Share of the training that has elapsed by `now`, between 0 and 1.
+///
+/// Measured against the duration recorded at start, so modifiers gained or lost since then
+/// do not change it. A pending training has not progressed at all.
+pub fn training_progress(entry: &TrainingQueueEntry, now: DateTime<Utc>) -> f64 {
+	if entry.status == TrainingStatus::Pending {
+		return 0.0;
+	}
+	let total_seconds = entry.duration().num_seconds();
+	if total_seconds <= 0 {
+		return 1.0;
+	}
+	let elapsed_seconds = (now - entry.started_at).num_seconds().max(0);
+	(elapsed_seconds as f64 / total_seconds as f64).min(1.0)
+}
+
+/// Share of the training time of the last `units` units of an entry that is still ahead at
+/// `now`, between 0 and 1.
+///
+/// For the whole entry this is `1 - training_progress`.
+fn untrained_ratio(entry: &TrainingQueueEntry, units: i64, now: DateTime<Utc>) -> f64 {
+	let units_seconds = entry.seconds_per_unit.saturating_mul(units);
+	if units_seconds <= 0 {
+		return 0.0;
+	}
+	if entry.status == TrainingStatus::Pending {
+		return 1.0;
+	}
+	let elapsed_seconds = (now - entry.started_at).num_seconds().max(0);
+	let ahead_seconds = (entry.duration().num_seconds() - elapsed_seconds).clamp(0, units_seconds);
+	ahead_seconds as f64 / units_seconds as f64
+}
+
+/// Estimates when the active entries of training queues start and complete.
+///
+/// Entries in training keep their recorded times. The pending entries of a building follow
+/// each other in the order they were queued, starting when its last training completes, and
+/// each takes the training time recorded when it was queued.
+pub fn estimate_queue(
+	entries: &[TrainingQueueEntry],
+	now: DateTime<Utc>,
+) -> HashMap<TrainingQueueKey, TrainingEstimate> {
+	let mut estimates = HashMap::with_capacity(entries.len());
+	let mut queue_ends: HashMap<PlayerBuildingKey, DateTime<Utc>> = HashMap::new();
+	for entry in entries
+		.iter()
+		.filter(|entry| entry.status == TrainingStatus::InProgress)
+	{
+		estimates.insert(
+			entry.id,
+			TrainingEstimate {
+				starts_at: entry.started_at,
+				completes_at: entry.completes_at,
+			},
+		);
+		let end = queue_ends.entry(entry.building_id).or_insert(now);
+		*end = (*end).max(entry.completes_at);
+	}
+
+	let mut pending: Vec<_> = entries
+		.iter()
+		.filter(|entry| entry.status == TrainingStatus::Pending)
+		.collect();
+	pending.sort_by_key(|entry| (entry.created_at, entry.id));
+	for entry in pending {
+		let end = queue_ends.entry(entry.building_id).or_insert(now);
+		let starts_at = *end;
+		*end = starts_at + entry.duration();
+		estimates.insert(
+			entry.id,
+			TrainingEstimate {
+				starts_at,
+				completes_at: *end,
+			},
+		);
+	}
+	estimates
+}
+
 // === Internal Helper Functions ===
 
+/// When the active entries of a sequential building will have completed, `None` if the
+/// building trains in parallel or its queue is empty.
+///
+/// Must be called within the transaction that locked the queue of the building.
+fn queue_end(
+	conn: &mut DbConn,
+	building_id: &PlayerBuildingKey,
+	mode: TrainingMode,
+) -> Result<Option<DateTime<Utc>>> {
+	if mode != TrainingMode::Sequential {
+		return Ok(None);
+	}
+	let now = Utc::now();
+	let active = training_queue::get_active_for_building(conn, building_id)?;
+	Ok(estimate_queue(&active, now)
+		.into_values()
+		.map(|estimate| estimate.completes_at.max(now))
+		.max())
+}
+
 /// Cleans up a failed training attempt by refunding resources and deleting the entry.
 ///
 /// Called when job scheduling fails after the transaction has committed.
 fn cleanup_failed_training(
 	conn: &mut DbConn,
 	entry_id: &TrainingQueueKey,
-	player_id: &PlayerKey,
+	settlement_id: &SettlementKey,
 	costs: &(i64, i64, i64, i64),
 ) -> Result<()> {
 	conn.transaction(|connection| {
 		// Refund resources
-		resources::add(connection, player_id, costs)?;
+		resources::add_to_settlement(connection, settlement_id, costs)?;
 		trace!("Refunded resources after failed job scheduling");
 
 		// Delete the orphaned entry
@@ -408,30 +1211,20 @@ fn cleanup_failed_training(
 	})
 }
 
-/// Validates building ownership by player.
-fn validate_building_ownership(
-	conn: &mut DbConn,
-	player_id: &PlayerKey,
-	building_id: &PlayerBuildingKey,
-) -> Result<crate::domain::player::buildings::PlayerBuilding> {
-	let player_bld = player_buildings::get_by_id(conn, building_id)?;
-
-	if &player_bld.player_id != player_id {
-		return Err(Error::from((
-			ErrorKind::StartTrainingError,
-			"Building not found",
-		)));
-	}
-
-	Ok(player_bld)
-}
-
-/// Validates that a building can train the specified unit type.
+/// Validates that a building can train the specified unit type, and that the unit is trained
+/// at all.
 fn validate_building_unit_match(
 	conn: &mut DbConn,
 	building_id: &crate::domain::building::BuildingKey,
 	unit: &Unit,
 ) -> Result<()> {
+	if !unit.is_trainable() {
+		return Err(Error::from((
+			ErrorKind::InvalidBuildingTypeError,
+			"Upgraded units cannot be trained",
+		)));
+	}
+
 	let can_train = building_unit_types::can_train_unit(conn, building_id, &unit.unit_type)?;
 
 	if !can_train {
@@ -444,15 +1237,15 @@ fn validate_building_unit_match(
 	Ok(())
 }
 
-/// Checks if player has enough resources for training.
+/// Checks if a settlement has enough resources for training.
 fn has_enough_resources(
 	conn: &mut DbConn,
-	player_id: &PlayerKey,
+	settlement_id: &SettlementKey,
 	unit_id: &UnitKey,
 	quantity: i64,
 ) -> Result<bool> {
 	let costs = get_total_cost(conn, unit_id, quantity)?;
-	let player_res = resources::get_by_player_id(conn, player_id)?;
+	let player_res = resources::get_by_settlement(conn, settlement_id)?;
 
 	let has_food = player_res.food >= costs.0;
 	let has_wood = player_res.wood >= costs.1;
@@ -488,21 +1281,25 @@ fn get_total_cost(
 	Ok((food, wood, stone, gold))
 }
 
-/// Calculates training duration with faction modifiers applied.
+/// Calculates the training time of a single unit with faction modifiers applied.
+///
+/// Returns the applied modifier and the modified seconds per unit, both stored on the
+/// training entry.
 ///
 /// AIDEV-NOTE: Modifier < 1.0 means faster training (e.g., Goblin 0.8 = 20% faster)
-fn calculate_training_duration(
+fn calculate_unit_training_time(
 	conn: &mut DbConn,
+	modifier_cache: &ModifierCache,
 	player_id: &PlayerKey,
 	unit: &Unit,
-	quantity: i64,
-) -> Result<TimeDelta> {
+) -> Result<(BigDecimal, i64)> {
 	// Get base training time per unit
 	let base_seconds = unit.base_training_seconds as i64;
 
 	// Get training speed modifier
 	let modifier = modifier_operations::calc_multiplier(
 		conn,
+		modifier_cache,
 		player_id,
 		ModifierTarget::Training,
 		None, // No specific resource target for training
@@ -512,43 +1309,28 @@ fn calculate_training_duration(
 	let modifier_f64 = modifier.to_f64().unwrap_or(1.0);
 	let modified_seconds = (base_seconds as f64 * modifier_f64) as i64;
 
-	// Total time = per_unit_time * quantity
-	let total_seconds = modified_seconds * quantity;
-
-	Ok(TimeDelta::seconds(total_seconds))
+	Ok((modifier, modified_seconds))
 }
 
-/// Calculates refund amount based on remaining time.
+/// Calculates refund amount for the last `cancelled` units of an entry based on remaining time.
 ///
-/// Uses the same faction-modified training time as the original duration calculation
-/// to ensure consistent refund ratios.
+/// Units train one after another, so the cancelled units are refunded for the share of
+/// their training time that has not elapsed yet. Uses the per-unit duration stored on the
+/// entry, so modifiers gained or lost since the start do not change the refund.
 ///
 /// Returns tuple of (food, wood, stone, gold) to refund.
-fn calculate_refund(conn: &mut DbConn, entry: &TrainingQueueEntry) -> Result<(i64, i64, i64, i64)> {
-	let costs = get_total_cost(conn, &entry.unit_id, entry.quantity)?;
+fn calculate_refund(
+	conn: &mut DbConn,
+	entry: &TrainingQueueEntry,
+	cancelled: i64,
+) -> Result<(i64, i64, i64, i64)> {
+	let costs = get_total_cost(conn, &entry.unit_id, cancelled)?;
 
 	// If still pending (not started), give full refund rate
 	let remaining_ratio = if entry.status == TrainingStatus::Pending {
 		1.0
 	} else {
-		// Calculate based on elapsed time vs expected duration (with faction modifiers)
-		let unit = units::get_by_id(conn, &entry.unit_id)?;
-
-		// Apply faction modifier to match original duration calculation
-		let modifier = modifier_operations::calc_multiplier(
-			conn,
-			&entry.player_id,
-			ModifierTarget::Training,
-			None,
-		)?;
-		let modifier_f64 = modifier.to_f64().unwrap_or(1.0);
-		let total_seconds =
-			unit.base_training_seconds as f64 * modifier_f64 * entry.quantity as f64;
-
-		let elapsed_seconds = (Utc::now() - entry.started_at).num_seconds() as f64;
-
-		let ratio = 1.0 - (elapsed_seconds / total_seconds);
-		ratio.max(0.0) // Don't go negative
+		untrained_ratio(entry, cancelled, Utc::now())
 	};
 
 	let refund_ratio = CANCEL_REFUND_RATE * remaining_ratio;
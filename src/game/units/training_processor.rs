@@ -4,146 +4,74 @@
 //! handling the addition of trained units to player inventories when training
 //! time has elapsed.
 
-use std::sync::Arc;
-use std::time::Duration;
-
 use axum::extract::FromRef;
-use tokio::sync::broadcast::Receiver;
-use tokio::time::sleep;
-use tracing::{debug, error, info, instrument, trace, warn};
-use ulid::Ulid;
+use tracing::{debug, error, info, instrument, trace};
 
 use crate::Error;
-use crate::domain::app_state::{AppPool, AppState};
+use crate::domain::app_state::{AppEvents, AppPool, AppQueue, AppState};
+use crate::domain::events::GameEvent;
 use crate::domain::jobs::{Job, JobType};
+use crate::domain::unit::training::TrainingStatus;
 use crate::game::units::training_operations::{self, TrainingJobPayload};
-use crate::job_queue::JobQueue;
-use crate::job_queue::job_processor::JobProcessor;
+use crate::job_queue::job_processor::{JobHandler, JobOutcome, JobWorker};
 
-/// A processor for handling training-related background jobs.
+/// A handler for training-related background jobs.
 ///
-/// The `TrainingProcessor` implements the `JobProcessor` trait and is responsible
+/// The `TrainingHandler` implements the `JobHandler` trait and is responsible
 /// for completing unit training when the training duration has elapsed.
-///
-/// Each processor instance runs in its own task and polls the job queue for new work.
-pub struct TrainingProcessor {
-	/// A unique ID for the processor instance
-	id: String,
-	/// A broadcast channel receiver for handling graceful shutdowns
-	shutdown_rx: Receiver<()>,
+pub struct TrainingHandler {
 	/// Database connection pool
 	pool: AppPool,
+	/// Event bus for training completion events
+	events: AppEvents,
+	/// Job queue to schedule the next training of sequential queues
+	job_queue: AppQueue,
 }
 
-impl TrainingProcessor {
-	/// Creates multiple TrainingProcessor instances for parallel processing.
-	pub fn initialise_n(n: usize, state: &AppState) -> Vec<TrainingProcessor> {
-		(0..n)
-			.map(|_| TrainingProcessor::from_ref(state))
-			.collect::<Vec<_>>()
-	}
-}
+/// Workers running the [`TrainingHandler`].
+pub type TrainingProcessor = JobWorker<TrainingHandler>;
 
-impl FromRef<AppState> for TrainingProcessor {
-	fn from_ref(state: &AppState) -> Self {
-		let rx = state.job_queue.subscribe_shutdown();
-		Self::new(state, rx)
-	}
-}
+impl JobHandler for TrainingHandler {
+	const JOB_TYPE: JobType = JobType::Training;
+	const WORKER_NAME: &'static str = "training-goblin";
 
-impl JobProcessor for TrainingProcessor {
-	/// Creates a new `TrainingProcessor` instance.
-	///
-	/// # Arguments
-	///
-	/// * `app_state` - A reference to the application state
-	/// * `shutdown_rx` - A broadcast channel receiver for handling graceful shutdowns
-	///
-	/// # Returns
-	///
-	/// A new `TrainingProcessor` instance with a unique ID
-	fn new(app_state: &AppState, shutdown_rx: Receiver<()>) -> Self
-	where
-		Self: Sized,
-	{
-		let id = format!("training-goblin-{}", Ulid::generate());
+	/// Creates a new `TrainingHandler` with what it needs from the application state.
+	fn new(app_state: &AppState) -> Self {
 		let pool = AppPool::from_ref(app_state);
-		debug!("Starting worker {}", id);
+		let events = AppEvents::from_ref(app_state);
+		let job_queue = AppQueue::from_ref(app_state);
 		Self {
-			id,
-			shutdown_rx,
 			pool,
+			events,
+			job_queue,
 		}
 	}
 
-	#[instrument(skip(self, queue))]
-	async fn run(&mut self, queue: Arc<JobQueue>) -> Result<(), Error> {
-		let mut interval = tokio::time::interval(Duration::from_secs(1));
-		trace!("Worker {} running", self.id);
-
-		loop {
-			tokio::select! {
-				_ = self.shutdown_rx.recv() => {
-					debug!("Worker {} shutting down", self.id);
-					break;
-				}
-				_ = interval.tick() => {
-					match queue.get_next_job_of_type(&self.id, &JobType::Training) {
-						Ok(Some(job)) => {
-							// Found a job, process it
-							trace!("Worker {} picked up job {}", self.id, job.id);
-							match self.process_job(job.clone()).await {
-								Ok(()) => {
-									trace!("Worker {} completed job {}", self.id, job.id);
-									queue.complete_job(&job.id)?;
-								}
-								Err(e) => {
-									warn!("Worker {} failed to process job {}", self.id, job.id);
-									debug!("Failed job: {:#?} {:?}", job, e);
-									queue.fail_job(&job.id, e.to_string())?;
-								}
-							}
-						}
-						Ok(None) => {
-							// No jobs available, continue polling
-							sleep(Duration::from_secs(1)).await;
-						}
-						Err(e) => {
-							// Error fetching job, retry after a short delay
-							error!("Error fetching job: {}", e);
-							sleep(Duration::from_secs(5)).await;
-						}
-					}
-				}
-			}
-		}
-
-		Ok(())
-	}
-
 	#[instrument(skip(self, job), fields(job_id = %job.id))]
-	async fn process_job(&self, job: Job) -> Result<(), Error> {
+	async fn handle(&self, job: Job) -> Result<JobOutcome, Error> {
 		debug!("Processing training job: {}", job.id);
 		trace!("Job details: {:?}", job);
 
-		assert_eq!(
-			job.job_type,
-			JobType::Training,
-			"Expected a training job, got: {}",
-			job.job_type
-		);
-
 		let payload: TrainingJobPayload = serde_json::from_value(job.payload.clone())?;
 
 		let mut conn = self.pool.get()?;
 
 		// AIDEV-NOTE: complete_training handles idempotency - calling multiple times is safe
-		match training_operations::complete_training(&mut conn, &payload) {
+		let entry = match training_operations::complete_training(&mut conn, &payload) {
 			Ok(entry) => {
 				info!(
 					"Successfully completed training {} for player {}: {} x {} units",
 					entry.id, payload.player_id, payload.quantity, payload.unit_id
 				);
+				if entry.status == TrainingStatus::Completed {
+					self.events.publish(GameEvent::TrainingCompleted {
+						player_id: entry.player_id,
+						training_id: entry.id,
+						unit_id: entry.unit_id,
+						quantity: entry.quantity,
+					});
+				}
+				entry
 			}
 			Err(e) => {
 				error!(
@@ -152,9 +80,29 @@ impl JobProcessor for TrainingProcessor {
 				);
 				return Err(e);
 			}
+		};
+
+		// A sequential queue moves on to its next pending training
+		if entry.status == TrainingStatus::Completed
+			&& let Some(next) = training_operations::start_next_training(
+				&mut conn,
+				&self.job_queue,
+				&entry.building_id,
+			)? {
+			info!(
+				"Started pending training {} at building {}",
+				next.id, next.building_id
+			);
+			self.events.publish(GameEvent::TrainingStarted {
+				player_id: next.player_id,
+				training_id: next.id,
+				unit_id: next.unit_id,
+				quantity: next.quantity,
+				completes_at: next.completes_at,
+			});
 		}
 
 		debug!("Completed processing training job: {}", job.id);
-		Ok(())
+		Ok(Some(serde_json::to_value(entry)?))
 	}
 }
@@ -0,0 +1,185 @@
+//! Unit upgrade operations for the Empire game.
+//!
+//! Trained units are upgraded to the unit of the next tier at the Blacksmith, e.g. Infantry
+//! to Veteran Infantry. An upgrade needs the Blacksmith and the military research building
+//! of the capital at the levels of the upgrade, and costs resources per unit. Upgraded units
+//! are taken out of the army and their upgrades put in, instantly.
+//!
+//! Every tier above the first adds
+//! [`TIER_BONUS_PERCENT`](crate::domain::unit::TIER_BONUS_PERCENT) to the attack and defense of a
+//! unit, which the power score of the player follows right away.
+
+use diesel::Connection;
+use tracing::{debug, info, instrument};
+
+use crate::db::{
+	DbConn, player_buildings, player_units, resources, settlements, unit_upgrades, units,
+};
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::player::PlayerKey;
+use crate::domain::settlement::SettlementKey;
+use crate::domain::unit::player_unit::PlayerUnit;
+use crate::domain::unit::upgrade::UnitUpgrade;
+use crate::domain::unit::{Unit, UnitKey};
+use crate::game::leaderboard::leaderboard_operations;
+
+/// Name of the building that upgrades units, every faction builds it.
+pub const BLACKSMITH_NAME: &str = "Blacksmith";
+
+/// Buildings of every faction whose level counts as military research for upgrades.
+pub const MILITARY_RESEARCH_BUILDINGS: [&str; 2] = ["Academy", "Cadet School"];
+
+/// A unit upgrade as the player sees it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpgradeStatus {
+	pub upgrade: UnitUpgrade,
+	/// Unit that is upgraded
+	pub unit: Unit,
+	/// Unit it is upgraded to
+	pub upgraded_unit: Unit,
+	/// Units of `unit` the player owns
+	pub owned: i64,
+	/// Whether the Blacksmith and research of the capital are high enough for the upgrade
+	pub unlocked: bool,
+}
+
+/// Units upgraded by the player.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitsUpgraded {
+	/// Unit that was upgraded
+	pub unit: Unit,
+	/// Unit it was upgraded to
+	pub upgraded_unit: Unit,
+	/// The player's units of the upgraded unit after the upgrade
+	pub upgraded: PlayerUnit,
+	pub quantity: i64,
+	/// Resources deducted as (food, wood, stone, gold)
+	pub costs: (i64, i64, i64, i64),
+}
+
+/// Lists every unit upgrade, with the units the player owns and whether it is unlocked.
+#[instrument(skip(conn))]
+pub fn list_upgrades(conn: &mut DbConn, player_id: &PlayerKey) -> Result<Vec<UpgradeStatus>> {
+	let capital = settlements::get_capital(conn, player_id)?;
+	let (blacksmith_level, research_level) = upgrade_levels(conn, &capital.id)?;
+	let owned = player_units::get_for_player(conn, player_id)?;
+
+	let mut statuses = Vec::new();
+	for upgrade in unit_upgrades::get_all(conn)? {
+		let unit = units::get_by_id(conn, &upgrade.unit_id)?;
+		let upgraded_unit = units::get_by_id(conn, &upgrade.upgraded_unit_id)?;
+		let owned = owned
+			.iter()
+			.find(|pu| pu.unit_id == upgrade.unit_id)
+			.map_or(0, |pu| pu.quantity);
+		let unlocked = blacksmith_level >= upgrade.blacksmith_level
+			&& research_level >= upgrade.research_level;
+		statuses.push(UpgradeStatus {
+			upgrade,
+			unit,
+			upgraded_unit,
+			owned,
+			unlocked,
+		});
+	}
+	Ok(statuses)
+}
+
+/// Upgrades `quantity` units of a player to the unit of the next tier.
+///
+/// # Errors
+/// - `InvalidQuantityError` if `quantity` is not positive
+/// - `NotFoundError` if the unit cannot be upgraded
+/// - `UpgradeUnitsError` if the Blacksmith or military research of the capital is too low
+/// - `InsufficientUnitsError` if the player owns fewer units
+/// - `InsufficientResourcesError` if the capital cannot pay for the upgrade
+#[instrument(skip(conn))]
+pub fn upgrade_units(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	unit_id: &UnitKey,
+	quantity: i64,
+) -> Result<UnitsUpgraded> {
+	if quantity <= 0 {
+		return Err(Error::from((
+			ErrorKind::InvalidQuantityError,
+			"Quantity must be positive",
+		)));
+	}
+	let upgrade = unit_upgrades::get_by_unit(conn, unit_id)?;
+	let capital = settlements::get_capital(conn, player_id)?;
+	let (blacksmith_level, research_level) = upgrade_levels(conn, &capital.id)?;
+	if blacksmith_level < upgrade.blacksmith_level {
+		return Err(Error::from((
+			ErrorKind::UpgradeUnitsError,
+			"Blacksmith level too low",
+			format!("Level {} is required", upgrade.blacksmith_level),
+		)));
+	}
+	if research_level < upgrade.research_level {
+		return Err(Error::from((
+			ErrorKind::UpgradeUnitsError,
+			"Military research level too low",
+			format!("Level {} is required", upgrade.research_level),
+		)));
+	}
+
+	let unit = units::get_by_id(conn, unit_id)?;
+	let upgraded_unit = units::get_by_id(conn, &upgrade.upgraded_unit_id)?;
+	let costs = upgrade.costs(quantity);
+
+	let upgraded = conn.transaction(|conn| {
+		let stored = resources::lock_by_settlement(conn, &capital.id)?;
+		if stored.food < costs.0
+			|| stored.wood < costs.1
+			|| stored.stone < costs.2
+			|| stored.gold < costs.3
+		{
+			return Err(Error::from((
+				ErrorKind::InsufficientResourcesError,
+				"Not enough resources",
+			)));
+		}
+		if player_units::remove_units(conn, player_id, unit_id, quantity)?.is_none() {
+			return Err(Error::from((
+				ErrorKind::InsufficientUnitsError,
+				"Not enough units",
+			)));
+		}
+		resources::deduct_from_settlement(conn, &capital.id, &costs)?;
+		let upgraded = player_units::add_units(conn, player_id, &upgraded_unit.id, quantity)?;
+		leaderboard_operations::refresh_score(conn, player_id)?;
+		Ok::<_, Error>(upgraded)
+	})?;
+
+	info!(
+		"Player {} upgraded {} {} to {}",
+		player_id, quantity, unit.name, upgraded_unit.name
+	);
+	Ok(UnitsUpgraded {
+		unit,
+		upgraded_unit,
+		upgraded,
+		quantity,
+		costs,
+	})
+}
+
+/// Returns the levels of the Blacksmith and the military research building of a settlement.
+fn upgrade_levels(conn: &mut DbConn, settlement_id: &SettlementKey) -> Result<(i32, i32)> {
+	let blacksmith_level =
+		player_buildings::get_level_by_name(conn, settlement_id, BLACKSMITH_NAME)?;
+	let mut research_level = 0;
+	for name in MILITARY_RESEARCH_BUILDINGS {
+		research_level = research_level.max(player_buildings::get_level_by_name(
+			conn,
+			settlement_id,
+			name,
+		)?);
+	}
+	debug!(
+		"Settlement {} has Blacksmith {} and military research {}",
+		settlement_id, blacksmith_level, research_level
+	);
+	Ok((blacksmith_level, research_level))
+}
@@ -0,0 +1,93 @@
+//! Unit upkeep operations for the Empire game.
+//!
+//! Every unit takes up population, bounded by the population capacity of the buildings in
+//! all of the player's settlements, and eats food every hour. The food upkeep is paid during resource production,
+//! see [`produce_resources`](crate::game::resources::resource_operations::produce_resources),
+//! and units that cannot be fed starve according to the configured
+//! [`StarvationPolicy`](crate::game::resources::StarvationPolicy).
+
+use bigdecimal::{BigDecimal, RoundingMode, ToPrimitive, Zero};
+use tracing::{debug, info, instrument};
+
+use crate::db::{DbConn, player_units, settlements, training_queue};
+use crate::domain::error::Result;
+use crate::domain::player::PlayerKey;
+use crate::domain::player::resource::ResourceType;
+use crate::domain::unit::Unit;
+use crate::game::resources::resource_operations;
+
+/// Population of a player: how much their buildings house and how much their units take up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Population {
+	/// Population housed by the buildings in all of the player's settlements, modifiers applied
+	pub capacity: i64,
+	/// Population taken up by owned units and units in training
+	pub used: i64,
+}
+
+impl Population {
+	/// Population still free for new units.
+	pub fn free(&self) -> i64 {
+		(self.capacity - self.used).max(0)
+	}
+
+	/// How many units of `unit` fit into the free population.
+	///
+	/// A unit taking up no population fits in any quantity, which is reported as `i64::MAX`.
+	pub fn housable(&self, unit: &Unit) -> i64 {
+		if unit.population <= 0 {
+			return i64::MAX;
+		}
+		self.free() / unit.population
+	}
+}
+
+/// Returns the population capacity and usage of a player.
+#[instrument(skip(conn))]
+pub fn get_population(conn: &mut DbConn, player_id: &PlayerKey) -> Result<Population> {
+	let mut capacity = BigDecimal::zero();
+	for settlement in settlements::get_by_player(conn, player_id)? {
+		capacity += resource_operations::calc_prod_rates(conn, &settlement)?
+			.remove(&ResourceType::Population)
+			.unwrap_or_default();
+	}
+	let capacity = capacity
+		.with_scale_round(0, RoundingMode::Down)
+		.to_i64()
+		.unwrap_or_default();
+
+	let owned = player_units::get_upkeep(conn, player_id)?.population;
+	let training = training_queue::get_training_population(conn, player_id)?;
+	let population = Population {
+		capacity,
+		used: owned + training,
+	};
+	debug!("Population of player {}: {:?}", player_id, population);
+	Ok(population)
+}
+
+/// Starves units of a player until the food they eat per hour has dropped by at least
+/// `excess_food`. The units eating the most food starve first.
+///
+/// # Returns
+/// The number of units that starved
+#[instrument(skip(conn))]
+pub fn starve_units(conn: &mut DbConn, player_id: &PlayerKey, excess_food: i64) -> Result<i64> {
+	let mut excess_food = excess_food;
+	let mut starved = 0;
+	for (player_unit, unit) in player_units::get_by_food_upkeep(conn, player_id)? {
+		if excess_food <= 0 || unit.food_upkeep <= 0 {
+			break;
+		}
+		let needed = (excess_food + unit.food_upkeep - 1) / unit.food_upkeep;
+		let lost = player_unit.quantity.min(needed);
+		player_units::update_quantity(conn, player_id, &unit.id, -lost)?;
+		info!(
+			"Player {} is starving, lost {} units of {}",
+			player_id, lost, unit.name
+		);
+		excess_food -= lost * unit.food_upkeep;
+		starved += lost;
+	}
+	Ok(starved)
+}
@@ -0,0 +1,201 @@
+//! Alliance influence over the tiles of the world map.
+//!
+//! Every member city is an [`InfluenceSource`] that projects its strength onto the tiles
+//! around it, losing one point for every step of Manhattan distance. The influence of the
+//! cities of an alliance adds up, and the alliance with the most influence on a tile controls
+//! it. Tiles where two alliances tie are contested and controlled by no one, and water is
+//! never controlled.
+//!
+//! AIDEV-NOTE: The game has no alliances, no city positions and no map tables yet, so
+//! nothing calls [`compute_influence`] so far. Once they exist, store the result in a
+//! `tile_influence` table from a recurring job, show it on the public map endpoints, and
+//! grant members inside [`InfluenceMap::is_friendly`] territory their modifier through the
+//! modifier pipeline, invalidating the modifier cache of every member whose tile changed.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use tracing::trace;
+use uuid::Uuid;
+
+use crate::game::world::pathfinding::{Terrain, TerrainMap, TileCoord};
+
+/// Unique identifier for an alliance
+pub type AllianceKey = Uuid;
+
+/// A member city projecting influence around it.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InfluenceSource {
+	pub alliance_id: AllianceKey,
+	pub coord: TileCoord,
+	/// Influence on the city's own tile, reaching `strength - 1` tiles away
+	pub strength: u32,
+}
+
+impl InfluenceSource {
+	/// Influence the city projects onto a tile.
+	pub fn influence_at(&self, coord: TileCoord) -> u32 {
+		self.strength
+			.saturating_sub(self.coord.manhattan_distance(coord))
+	}
+}
+
+/// The alliance controlling a tile.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileInfluence {
+	pub alliance_id: AllianceKey,
+	/// Combined influence of the alliance's cities on the tile
+	pub influence: u32,
+}
+
+/// Which alliance controls each tile of a [`TerrainMap`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InfluenceMap {
+	width: i32,
+	height: i32,
+	/// Controlling alliance of each tile, row by row
+	tiles: Vec<Option<TileInfluence>>,
+}
+
+impl InfluenceMap {
+	/// The alliance controlling a tile, `None` if the tile is uncontrolled, contested or lies
+	/// outside the map.
+	pub fn controller(&self, coord: TileCoord) -> Option<TileInfluence> {
+		let inside = (0..self.width).contains(&coord.x) && (0..self.height).contains(&coord.y);
+		inside
+			.then(|| self.tiles[(coord.y * self.width + coord.x) as usize])
+			.flatten()
+	}
+
+	/// Whether the tile lies in the territory of the alliance.
+	pub fn is_friendly(&self, coord: TileCoord, alliance_id: &AllianceKey) -> bool {
+		self.controller(coord)
+			.is_some_and(|tile| tile.alliance_id == *alliance_id)
+	}
+
+	/// Every controlled tile with its controller, row by row.
+	pub fn controlled_tiles(&self) -> impl Iterator<Item = (TileCoord, TileInfluence)> + '_ {
+		self.tiles.iter().enumerate().filter_map(|(idx, tile)| {
+			let idx = idx as i32;
+			tile.map(|tile| (TileCoord::new(idx % self.width, idx / self.width), tile))
+		})
+	}
+}
+
+/// Computes which alliance controls each tile of the map.
+pub fn compute_influence(map: &TerrainMap, sources: &[InfluenceSource]) -> InfluenceMap {
+	let mut tiles = Vec::with_capacity((map.width() * map.height()) as usize);
+	for y in 0..map.height() {
+		for x in 0..map.width() {
+			let coord = TileCoord::new(x, y);
+			let tile = if map.terrain(coord) == Some(Terrain::Water) {
+				None
+			} else {
+				strongest_alliance(sources, coord)
+			};
+			tiles.push(tile);
+		}
+	}
+
+	let influence = InfluenceMap {
+		width: map.width(),
+		height: map.height(),
+		tiles,
+	};
+	trace!(
+		"Computed influence of {} cities, {} tiles controlled",
+		sources.len(),
+		influence.controlled_tiles().count()
+	);
+	influence
+}
+
+/// The alliance with the most influence on a tile, `None` on a tie or without influence.
+fn strongest_alliance(sources: &[InfluenceSource], coord: TileCoord) -> Option<TileInfluence> {
+	let mut totals: HashMap<AllianceKey, u32> = HashMap::new();
+	for source in sources {
+		let influence = source.influence_at(coord);
+		if influence > 0 {
+			*totals.entry(source.alliance_id).or_default() += influence;
+		}
+	}
+
+	let mut strongest: Option<TileInfluence> = None;
+	let mut contested = false;
+	for (alliance_id, influence) in totals {
+		match strongest {
+			Some(best) if influence < best.influence => {}
+			Some(best) if influence == best.influence => contested = true,
+			_ => {
+				strongest = Some(TileInfluence {
+					alliance_id,
+					influence,
+				});
+				contested = false;
+			}
+		}
+	}
+	strongest.filter(|_| !contested)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn city(alliance_id: AllianceKey, x: i32, y: i32, strength: u32) -> InfluenceSource {
+		InfluenceSource {
+			alliance_id,
+			coord: TileCoord::new(x, y),
+			strength,
+		}
+	}
+
+	#[test]
+	fn test_cities_project_influence_that_fades_with_distance() {
+		let alliance = Uuid::new_v4();
+		let map = TerrainMap::new(7, 7, Terrain::Plains);
+		let influence = compute_influence(&map, &[city(alliance, 3, 3, 3)]);
+
+		let centre = influence.controller(TileCoord::new(3, 3)).unwrap();
+		assert_eq!(centre.influence, 3);
+		assert_eq!(
+			influence
+				.controller(TileCoord::new(4, 4))
+				.map(|tile| tile.influence),
+			Some(1)
+		);
+		assert!(influence.controller(TileCoord::new(5, 4)).is_none());
+		assert!(influence.controller(TileCoord::new(9, 9)).is_none());
+		// The city's tile and the two rings around it
+		assert_eq!(influence.controlled_tiles().count(), 1 + 4 + 8);
+		assert!(influence.is_friendly(TileCoord::new(2, 3), &alliance));
+		assert!(!influence.is_friendly(TileCoord::new(2, 3), &Uuid::new_v4()));
+	}
+
+	#[test]
+	fn test_the_strongest_alliance_controls_a_tile() {
+		let (north, south) = (Uuid::new_v4(), Uuid::new_v4());
+		let mut map = TerrainMap::new(5, 5, Terrain::Forest);
+		map.set_terrain(TileCoord::new(2, 1), Terrain::Water);
+		let influence = compute_influence(
+			&map,
+			&[
+				city(north, 2, 0, 4),
+				city(north, 1, 2, 2),
+				city(south, 2, 4, 4),
+			],
+		);
+
+		// Both alliances reach the middle tile, the north with two cities
+		let middle = influence.controller(TileCoord::new(2, 2)).unwrap();
+		assert_eq!(middle.alliance_id, north);
+		assert_eq!(middle.influence, 2 + 1);
+		// Halfway between the two strong cities, out of reach of the weak one
+		assert!(influence.controller(TileCoord::new(3, 2)).is_none());
+		assert!(
+			influence.controller(TileCoord::new(2, 1)).is_none(),
+			"Water"
+		);
+		assert!(influence.is_friendly(TileCoord::new(2, 3), &south));
+	}
+}
@@ -0,0 +1,11 @@
+//! World operations for the Empire game.
+//!
+//! This module resets the world between seasons, wiping the progression of every player
+//! while keeping their accounts, and runs the reset as a series of background jobs. It also
+//! finds the cheapest paths across the terrain of the world map, and works out which alliance
+//! controls each of its tiles.
+
+pub mod influence;
+pub mod pathfinding;
+pub mod reset_operations;
+pub mod reset_processor;
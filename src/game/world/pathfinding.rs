@@ -0,0 +1,370 @@
+//! Cheapest paths across the world map.
+//!
+//! Armies move between neighbouring tiles, up, down, left and right. Entering a tile costs
+//! the movement cost of its [`Terrain`], and impassable terrain cannot be entered at all.
+//! [`find_path`] runs A* with the Manhattan distance times the cheapest movement cost as its
+//! heuristic. The heuristic never overestimates, so the path found is a cheapest one, and
+//! its travel time is the cost times [`TRAVEL_TIME_PER_COST`].
+//!
+//! AIDEV-NOTE: The world has no map tables yet. Once tiles are stored, load them into a
+//! [`TerrainMap`], clear the [`PathCache`] whenever terrain changes, and show the path in
+//! the army dispatch preview, which does not exist yet either.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::{PoisonError, RwLock};
+
+use chrono::TimeDelta;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+/// Travel time for every point of movement cost along a path.
+pub const TRAVEL_TIME_PER_COST: TimeDelta = TimeDelta::minutes(1);
+
+/// Terrain of a single map tile.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Terrain {
+	Road,
+	Plains,
+	Forest,
+	Hills,
+	Swamp,
+	Mountains,
+	Water,
+}
+
+impl Terrain {
+	/// Every terrain type.
+	pub const ALL: [Terrain; 7] = [
+		Terrain::Road,
+		Terrain::Plains,
+		Terrain::Forest,
+		Terrain::Hills,
+		Terrain::Swamp,
+		Terrain::Mountains,
+		Terrain::Water,
+	];
+
+	/// Cost of entering a tile of this terrain, `None` if armies cannot enter it.
+	pub fn movement_cost(self) -> Option<u32> {
+		match self {
+			Terrain::Road => Some(1),
+			Terrain::Plains => Some(2),
+			Terrain::Forest => Some(3),
+			Terrain::Hills => Some(4),
+			Terrain::Swamp => Some(6),
+			Terrain::Mountains | Terrain::Water => None,
+		}
+	}
+
+	/// The lowest movement cost of any terrain.
+	fn min_movement_cost() -> u32 {
+		Self::ALL
+			.iter()
+			.filter_map(|terrain| terrain.movement_cost())
+			.min()
+			.unwrap_or(1)
+	}
+}
+
+/// Position of a tile on the map.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TileCoord {
+	pub x: i32,
+	pub y: i32,
+}
+
+impl TileCoord {
+	pub fn new(x: i32, y: i32) -> Self {
+		Self { x, y }
+	}
+
+	/// Number of steps between two tiles when nothing is in the way.
+	pub fn manhattan_distance(self, other: TileCoord) -> u32 {
+		self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+	}
+
+	fn neighbours(self) -> [TileCoord; 4] {
+		[
+			TileCoord::new(self.x, self.y - 1),
+			TileCoord::new(self.x + 1, self.y),
+			TileCoord::new(self.x, self.y + 1),
+			TileCoord::new(self.x - 1, self.y),
+		]
+	}
+}
+
+/// A rectangular map of terrain tiles, with the origin in the top left corner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TerrainMap {
+	width: i32,
+	height: i32,
+	/// Tiles row by row
+	tiles: Vec<Terrain>,
+}
+
+impl TerrainMap {
+	/// Creates a map covered in a single terrain.
+	pub fn new(width: u16, height: u16, terrain: Terrain) -> Self {
+		Self {
+			width: i32::from(width),
+			height: i32::from(height),
+			tiles: vec![terrain; usize::from(width) * usize::from(height)],
+		}
+	}
+
+	pub fn width(&self) -> i32 {
+		self.width
+	}
+
+	pub fn height(&self) -> i32 {
+		self.height
+	}
+
+	/// Whether the tile lies on the map.
+	pub fn contains(&self, coord: TileCoord) -> bool {
+		(0..self.width).contains(&coord.x) && (0..self.height).contains(&coord.y)
+	}
+
+	/// Terrain of a tile, `None` if it lies outside the map.
+	pub fn terrain(&self, coord: TileCoord) -> Option<Terrain> {
+		self.index(coord).map(|idx| self.tiles[idx])
+	}
+
+	/// Changes the terrain of a tile, ignoring tiles outside the map.
+	pub fn set_terrain(&mut self, coord: TileCoord, terrain: Terrain) {
+		if let Some(idx) = self.index(coord) {
+			self.tiles[idx] = terrain;
+		}
+	}
+
+	fn index(&self, coord: TileCoord) -> Option<usize> {
+		self.contains(coord)
+			.then(|| (coord.y * self.width + coord.x) as usize)
+	}
+
+	fn coord(&self, idx: usize) -> TileCoord {
+		let idx = idx as i32;
+		TileCoord::new(idx % self.width, idx / self.width)
+	}
+}
+
+/// A cheapest path between two tiles.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+	/// Tiles along the path, including the start and the destination
+	pub tiles: Vec<TileCoord>,
+	/// Sum of the movement costs of every tile entered
+	pub cost: u32,
+}
+
+impl Path {
+	/// How long an army takes to travel the path.
+	pub fn travel_time(&self) -> TimeDelta {
+		TRAVEL_TIME_PER_COST * self.cost as i32
+	}
+}
+
+/// Finds a cheapest path from `from` to `to`.
+///
+/// # Returns
+/// The path, or `None` if either tile lies outside the map, the destination is impassable,
+/// or impassable terrain cuts it off
+pub fn find_path(map: &TerrainMap, from: TileCoord, to: TileCoord) -> Option<Path> {
+	let start = map.index(from)?;
+	let goal = map.index(to)?;
+	map.terrain(to)?.movement_cost()?;
+
+	let min_cost = Terrain::min_movement_cost();
+	let heuristic = |coord: TileCoord| coord.manhattan_distance(to) * min_cost;
+
+	let mut best_cost = vec![u32::MAX; map.tiles.len()];
+	let mut came_from: Vec<Option<usize>> = vec![None; map.tiles.len()];
+	let mut open = BinaryHeap::new();
+	best_cost[start] = 0;
+	open.push(Reverse((heuristic(from), 0, start)));
+
+	while let Some(Reverse((_, cost, idx))) = open.pop() {
+		if idx == goal {
+			let path = Path {
+				tiles: walk_back(map, &came_from, goal),
+				cost,
+			};
+			trace!("Found path of cost {} from {:?} to {:?}", cost, from, to);
+			return Some(path);
+		}
+		if cost > best_cost[idx] {
+			// A cheaper way to this tile was found after it was queued
+			continue;
+		}
+
+		for next in map.coord(idx).neighbours() {
+			let Some(next_idx) = map.index(next) else {
+				continue;
+			};
+			let Some(step) = map.tiles[next_idx].movement_cost() else {
+				continue;
+			};
+			let next_cost = cost + step;
+			if next_cost < best_cost[next_idx] {
+				best_cost[next_idx] = next_cost;
+				came_from[next_idx] = Some(idx);
+				open.push(Reverse((next_cost + heuristic(next), next_cost, next_idx)));
+			}
+		}
+	}
+
+	debug!("No path from {:?} to {:?}", from, to);
+	None
+}
+
+/// The tiles from the start to `goal`, following `came_from` backwards.
+fn walk_back(map: &TerrainMap, came_from: &[Option<usize>], goal: usize) -> Vec<TileCoord> {
+	let mut tiles = vec![map.coord(goal)];
+	let mut current = goal;
+	while let Some(prev) = came_from[current] {
+		tiles.push(map.coord(prev));
+		current = prev;
+	}
+	tiles.reverse();
+	tiles
+}
+
+/// Cached paths across a single [`TerrainMap`].
+///
+/// The cache holds up to `capacity` paths, including the knowledge that no path exists,
+/// and is emptied once it is full. It has to be cleared whenever the terrain of the map
+/// changes.
+pub struct PathCache {
+	paths: RwLock<HashMap<(TileCoord, TileCoord), Option<Path>>>,
+	capacity: usize,
+}
+
+impl PathCache {
+	pub fn new(capacity: usize) -> Self {
+		Self {
+			paths: RwLock::new(HashMap::new()),
+			capacity,
+		}
+	}
+
+	/// Finds a cheapest path from `from` to `to`, see [`find_path`].
+	pub fn find_path(&self, map: &TerrainMap, from: TileCoord, to: TileCoord) -> Option<Path> {
+		{
+			let paths = self.paths.read().unwrap_or_else(PoisonError::into_inner);
+			if let Some(path) = paths.get(&(from, to)) {
+				trace!("Path cache hit for {:?} to {:?}", from, to);
+				return path.clone();
+			}
+		}
+
+		let path = find_path(map, from, to);
+		let mut paths = self.paths.write().unwrap_or_else(PoisonError::into_inner);
+		if paths.len() >= self.capacity {
+			debug!("Path cache is full, dropping {} paths", paths.len());
+			paths.clear();
+		}
+		paths.insert((from, to), path.clone());
+		path
+	}
+
+	/// Number of cached paths.
+	pub fn len(&self) -> usize {
+		self.paths
+			.read()
+			.unwrap_or_else(PoisonError::into_inner)
+			.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Drops every cached path, to be called when the terrain of the map changes.
+	pub fn clear(&self) {
+		self.paths
+			.write()
+			.unwrap_or_else(PoisonError::into_inner)
+			.clear();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_open_terrain_takes_the_direct_route() {
+		let map = TerrainMap::new(5, 5, Terrain::Plains);
+		let path = find_path(&map, TileCoord::new(0, 0), TileCoord::new(3, 2)).unwrap();
+		assert_eq!(path.tiles.len(), 6);
+		assert_eq!(path.cost, 5 * 2);
+		assert_eq!(path.travel_time(), TimeDelta::minutes(10));
+		assert_eq!(path.tiles.first(), Some(&TileCoord::new(0, 0)));
+		assert_eq!(path.tiles.last(), Some(&TileCoord::new(3, 2)));
+
+		let standing = find_path(&map, TileCoord::new(1, 1), TileCoord::new(1, 1)).unwrap();
+		assert_eq!(standing.cost, 0);
+		assert_eq!(standing.tiles, vec![TileCoord::new(1, 1)]);
+	}
+
+	#[test]
+	fn test_paths_avoid_impassable_and_costly_terrain() {
+		// A mountain ridge with a single pass at the bottom
+		let mut map = TerrainMap::new(5, 5, Terrain::Plains);
+		for y in 0..4 {
+			map.set_terrain(TileCoord::new(2, y), Terrain::Mountains);
+		}
+		let path = find_path(&map, TileCoord::new(0, 0), TileCoord::new(4, 0)).unwrap();
+		assert!(path.tiles.contains(&TileCoord::new(2, 4)));
+		assert_eq!(path.cost, 12 * 2);
+
+		// A road is longer but cheaper than the swamp in the way
+		let mut map = TerrainMap::new(5, 3, Terrain::Swamp);
+		for x in 0..5 {
+			map.set_terrain(TileCoord::new(x, 2), Terrain::Road);
+		}
+		map.set_terrain(TileCoord::new(0, 1), Terrain::Road);
+		map.set_terrain(TileCoord::new(4, 1), Terrain::Road);
+		let path = find_path(&map, TileCoord::new(0, 0), TileCoord::new(4, 0)).unwrap();
+		// Seven road tiles, then the swamp tile of the destination
+		assert_eq!(path.cost, 7 + 6);
+	}
+
+	#[test]
+	fn test_unreachable_tiles_have_no_path() {
+		let mut map = TerrainMap::new(4, 4, Terrain::Forest);
+		for y in 0..4 {
+			map.set_terrain(TileCoord::new(2, y), Terrain::Water);
+		}
+		assert!(find_path(&map, TileCoord::new(0, 0), TileCoord::new(3, 3)).is_none());
+		assert!(find_path(&map, TileCoord::new(0, 0), TileCoord::new(2, 0)).is_none());
+		assert!(find_path(&map, TileCoord::new(0, 0), TileCoord::new(9, 0)).is_none());
+	}
+
+	#[test]
+	fn test_paths_are_cached_until_cleared() {
+		let mut map = TerrainMap::new(4, 1, Terrain::Plains);
+		let cache = PathCache::new(2);
+		let (from, to) = (TileCoord::new(0, 0), TileCoord::new(3, 0));
+
+		let path = cache.find_path(&map, from, to).unwrap();
+		map.set_terrain(TileCoord::new(1, 0), Terrain::Water);
+		assert_eq!(
+			cache.find_path(&map, from, to),
+			Some(path),
+			"Served from cache"
+		);
+
+		cache.clear();
+		assert!(cache.is_empty());
+		assert_eq!(cache.find_path(&map, from, to), None);
+
+		// A full cache starts over
+		cache.find_path(&map, from, TileCoord::new(0, 0));
+		assert_eq!(cache.len(), 2);
+		cache.find_path(&map, to, to);
+		assert_eq!(cache.len(), 1);
+	}
+}
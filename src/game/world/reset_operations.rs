@@ -0,0 +1,356 @@
+//! Resets of the world between seasons.
+//!
+//! A reset is guarded by two requests: an operator requests it and receives a one-time
+//! token, and has to confirm it with that token within [`RESET_TOKEN_TTL`]. Only the hash
+//! of the token is stored. Both requests are recorded in the audit log.
+//!
+//! Once confirmed, the reset runs as a chain of [`JobType::WorldReset`] jobs, one per
+//! [`WorldResetStep`]. Each step wipes its tables and records its completion in a single
+//! transaction, and a step that already ran is skipped, so a retried or duplicated job
+//! never wipes anything twice. Only one reset can run at a time.
+//!
+//! Accounts, factions, sessions, privacy settings, past seasons with the legacy points
+//! players earned in them, and the static game data are kept.
+//! Players are left with their capital, with their faction's starter buildings, the default
+//! resources and a new beginner shield, as if they had just registered.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::prelude::*;
+use serde_json::json;
+use tracing::{debug, info, instrument, warn};
+
+use crate::auth::session_operations::{encode_token, gen_token};
+use crate::configuration::ProtectionSettings;
+use crate::db::{DbConn, audit_log, world_resets};
+use crate::domain::audit::NewAuditEntry;
+use crate::domain::error::{Error, ErrorKind, Result};
+use crate::domain::jobs::{JobKey, JobStatus, JobType};
+use crate::domain::modifier::active_modifier::ModifierSourceType;
+use crate::domain::world_reset::{
+	NewWorldReset, WorldReset, WorldResetJobPayload, WorldResetKey, WorldResetStatus,
+	WorldResetStep,
+};
+use crate::game::admin_operations::AdminActor;
+use crate::job_queue::{JobPriority, JobQueue};
+use crate::schema::{
+	active_modifiers, battle_report, building_upgrade, caravan, construction_queue, intel_report,
+	job, leaderboard_entry, market_order, market_trade, modifier_history, planned_action, player,
+	player_building, player_event_currency, player_event_objective, player_hero, player_item,
+	player_quest, player_score, player_unit, scout_mission, settlement, training_queue,
+};
+
+/// How long a requested reset can be confirmed.
+pub const RESET_TOKEN_TTL: TimeDelta = TimeDelta::minutes(15);
+
+/// Action recorded in the audit log when a reset is requested.
+pub const REQUEST_RESET_ACTION: &str = "request_world_reset";
+
+/// Action recorded in the audit log when a reset is confirmed.
+pub const CONFIRM_RESET_ACTION: &str = "confirm_world_reset";
+
+/// Statuses of a job that has not started running yet.
+const WAITING_JOB_STATUSES: [JobStatus; 2] = [JobStatus::Pending, JobStatus::Failed];
+
+/// A requested reset and the token confirming it.
+#[derive(Debug, Clone)]
+pub struct RequestedReset {
+	pub reset: WorldReset,
+	/// Token to confirm the reset with, only handed out once
+	pub token: String,
+}
+
+/// Requests a world reset, recording it in the audit log.
+#[instrument(skip(conn))]
+pub fn request_reset(conn: &mut DbConn, actor: AdminActor) -> Result<RequestedReset> {
+	let token = gen_token();
+	let reset = conn.transaction(|conn| {
+		let reset = world_resets::create(
+			conn,
+			NewWorldReset {
+				token_hash: encode_token(&token),
+				requested_by: actor.operator.clone(),
+				expires_at: Utc::now() + RESET_TOKEN_TTL,
+			},
+		)?;
+		audit_log::create(
+			conn,
+			NewAuditEntry {
+				action: REQUEST_RESET_ACTION.to_string(),
+				subject: Some(reset.id.to_string()),
+				details: json!({ "expires_at": reset.expires_at }),
+				operator: actor.operator,
+				request_id: actor.request_id,
+				player_id: None,
+			},
+		)?;
+		Ok::<_, Error>(reset)
+	})?;
+
+	info!(
+		"World reset {} requested, to be confirmed before {}",
+		reset.id, reset.expires_at
+	);
+	Ok(RequestedReset { reset, token })
+}
+
+/// Retrieves a world reset.
+pub fn get_reset(conn: &mut DbConn, reset_id: &WorldResetKey) -> Result<WorldReset> {
+	world_resets::find_by_id(conn, reset_id)?
+		.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "World reset not found")))
+}
+
+/// Confirms a requested world reset with its token and schedules its first step.
+///
+/// Fails if the token does not match, the reset expired or was confirmed already, or
+/// another reset is still running.
+#[instrument(skip(conn, job_queue, token))]
+pub fn confirm_reset(
+	conn: &mut DbConn,
+	job_queue: &JobQueue,
+	reset_id: &WorldResetKey,
+	token: &str,
+	actor: AdminActor,
+) -> Result<WorldReset> {
+	let reset = conn.transaction(|conn| {
+		let reset = world_resets::find_for_update(conn, reset_id)?
+			.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "World reset not found")))?;
+		let now = Utc::now();
+		match reset.status(now) {
+			WorldResetStatus::Requested => {}
+			WorldResetStatus::Expired => {
+				return Err(Error::from((
+					ErrorKind::InvalidResetTokenError,
+					"Reset token expired",
+				)));
+			}
+			WorldResetStatus::Running | WorldResetStatus::Completed => {
+				return Err(Error::from((
+					ErrorKind::WorldResetConflictError,
+					"World reset already confirmed",
+				)));
+			}
+		}
+		if encode_token(token) != reset.token_hash {
+			warn!("Rejected world reset {} with an invalid token", reset.id);
+			return Err(Error::from((
+				ErrorKind::InvalidResetTokenError,
+				"Invalid reset token",
+			)));
+		}
+		// The unique index on running resets backs this check against concurrent confirmations
+		if let Some(running) = world_resets::find_running(conn)? {
+			return Err(Error::from((
+				ErrorKind::WorldResetConflictError,
+				"Another world reset is running",
+				format!("world reset {} has not completed", running.id),
+			)));
+		}
+
+		let reset = world_resets::confirm(conn, reset_id, actor.operator.clone(), now)?;
+		audit_log::create(
+			conn,
+			NewAuditEntry {
+				action: CONFIRM_RESET_ACTION.to_string(),
+				subject: Some(reset.id.to_string()),
+				details: json!({ "steps": WorldResetStep::ALL }),
+				operator: actor.operator,
+				request_id: actor.request_id,
+				player_id: None,
+			},
+		)?;
+		Ok(reset)
+	})?;
+
+	let job_id = schedule_step(job_queue, &reset.id, WorldResetStep::ALL[0])?;
+	info!(
+		"World reset {} confirmed, running in job {}",
+		reset.id, job_id
+	);
+	Ok(reset)
+}
+
+/// Enqueues the job running a step of a world reset.
+pub fn schedule_step(
+	job_queue: &JobQueue,
+	reset_id: &WorldResetKey,
+	step: WorldResetStep,
+) -> Result<JobKey> {
+	job_queue.enqueue(
+		JobType::WorldReset,
+		WorldResetJobPayload {
+			reset_id: *reset_id,
+			step,
+		},
+		JobPriority::High,
+		Utc::now(),
+	)
+}
+
+/// Runs a step of a confirmed world reset and records its completion.
+///
+/// # Returns
+/// The reset after the step, or `None` if the reset is not running or the step is not the
+/// next one, e.g. because a previous job already ran it
+#[instrument(skip(conn, settings))]
+pub fn run_step(
+	conn: &mut DbConn,
+	settings: &ProtectionSettings,
+	reset_id: &WorldResetKey,
+	step: WorldResetStep,
+) -> Result<Option<WorldReset>> {
+	conn.transaction(|conn| {
+		let reset = world_resets::find_for_update(conn, reset_id)?
+			.ok_or_else(|| Error::from((ErrorKind::NotFoundError, "World reset not found")))?;
+		let now = Utc::now();
+		if reset.status(now) != WorldResetStatus::Running || reset.next_step() != Some(step) {
+			debug!(
+				"Skipping step {} of world reset {} at {} completed steps",
+				step, reset.id, reset.completed_steps
+			);
+			return Ok(None);
+		}
+
+		let wiped = match step {
+			WorldResetStep::Queues => wipe_queues(conn)?,
+			WorldResetStep::Market => wipe_market(conn)?,
+			WorldResetStep::Reports => wipe_reports(conn)?,
+			WorldResetStep::Units => wipe_inventories(conn)?,
+			WorldResetStep::Modifiers => diesel::delete(
+				active_modifiers::table
+					.filter(active_modifiers::source_type.ne(ModifierSourceType::Faction)),
+			)
+			.execute(conn)?,
+			WorldResetStep::Buildings => wipe_buildings(conn)?,
+			WorldResetStep::Players => reset_shields(conn, settings, now)?,
+		};
+		info!(
+			"Step {} of world reset {} changed {} rows",
+			step, reset.id, wiped
+		);
+
+		let reset = world_resets::complete_step(conn, reset_id, step.next().is_none(), now)?;
+		Ok(Some(reset))
+	})
+}
+
+/// Deletes every queue entry and the waiting jobs completing them.
+fn wipe_queues(conn: &mut DbConn) -> Result<usize> {
+	let mut job_ids: Vec<JobKey> = construction_queue::table
+		.filter(construction_queue::job_id.is_not_null())
+		.select(construction_queue::job_id.assume_not_null())
+		.load(conn)?;
+	job_ids.extend(
+		training_queue::table
+			.filter(training_queue::job_id.is_not_null())
+			.select(training_queue::job_id.assume_not_null())
+			.load::<JobKey>(conn)?,
+	);
+	job_ids.extend(
+		caravan::table
+			.filter(caravan::job_id.is_not_null())
+			.select(caravan::job_id.assume_not_null())
+			.load::<JobKey>(conn)?,
+	);
+	job_ids.extend(
+		scout_mission::table
+			.filter(scout_mission::job_id.is_not_null())
+			.select(scout_mission::job_id.assume_not_null())
+			.load::<JobKey>(conn)?,
+	);
+
+	let mut wiped = diesel::delete(construction_queue::table).execute(conn)?;
+	wiped += diesel::delete(training_queue::table).execute(conn)?;
+	wiped += diesel::delete(caravan::table).execute(conn)?;
+	wiped += diesel::delete(scout_mission::table).execute(conn)?;
+	wiped += diesel::delete(planned_action::table).execute(conn)?;
+	// Every building and training job refers to state the reset wipes
+	wiped += diesel::delete(
+		job::table
+			.filter(job::status.eq_any(WAITING_JOB_STATUSES))
+			.filter(
+				job::id
+					.eq_any(&job_ids)
+					.or(job::job_type.eq_any([JobType::Building, JobType::Training])),
+			),
+	)
+	.execute(conn)?;
+	Ok(wiped)
+}
+
+/// Deletes every market order and trade.
+fn wipe_market(conn: &mut DbConn) -> Result<usize> {
+	let mut wiped = diesel::delete(market_trade::table).execute(conn)?;
+	wiped += diesel::delete(market_order::table).execute(conn)?;
+	Ok(wiped)
+}
+
+/// Deletes battle and intel reports, the building upgrade ledger, the modifier history, and
+/// the scores and leaderboards.
+fn wipe_reports(conn: &mut DbConn) -> Result<usize> {
+	let mut wiped = diesel::delete(battle_report::table).execute(conn)?;
+	wiped += diesel::delete(intel_report::table).execute(conn)?;
+	wiped += diesel::delete(building_upgrade::table).execute(conn)?;
+	wiped += diesel::delete(modifier_history::table).execute(conn)?;
+	wiped += diesel::delete(player_score::table).execute(conn)?;
+	wiped += diesel::delete(leaderboard_entry::table).execute(conn)?;
+	Ok(wiped)
+}
+
+/// Wipes the units, items, heroes and event currency players hold, and their event and quest
+/// progress.
+fn wipe_inventories(conn: &mut DbConn) -> Result<usize> {
+	let mut wiped = diesel::delete(player_unit::table).execute(conn)?;
+	wiped += diesel::delete(player_item::table).execute(conn)?;
+	wiped += diesel::delete(player_hero::table).execute(conn)?;
+	wiped += diesel::delete(player_event_currency::table).execute(conn)?;
+	wiped += diesel::delete(player_event_objective::table).execute(conn)?;
+	wiped += diesel::delete(player_quest::table).execute(conn)?;
+	Ok(wiped)
+}
+
+/// Razes every settlement but the capitals, replaces every building with the starter
+/// buildings and resets resources to their defaults.
+fn wipe_buildings(conn: &mut DbConn) -> Result<usize> {
+	// Their buildings, storage and accumulators go along with them
+	let mut wiped =
+		diesel::delete(settlement::table.filter(settlement::is_capital.eq(false))).execute(conn)?;
+	wiped += diesel::delete(player_building::table).execute(conn)?;
+	// Caps start at zero and are raised again by the trigger on new buildings
+	wiped += diesel::sql_query(
+		"UPDATE player_resource
+		 SET food = DEFAULT, wood = DEFAULT, stone = DEFAULT, gold = DEFAULT,
+		     food_cap = DEFAULT, wood_cap = DEFAULT, stone_cap = DEFAULT, gold_cap = DEFAULT,
+		     produced_at = now(), collected_at = now()",
+	)
+	.execute(conn)?;
+	wiped += diesel::sql_query(
+		"UPDATE player_accumulator
+		 SET food = DEFAULT, wood = DEFAULT, stone = DEFAULT, gold = DEFAULT,
+		     food_remainder = DEFAULT, wood_remainder = DEFAULT,
+		     stone_remainder = DEFAULT, gold_remainder = DEFAULT",
+	)
+	.execute(conn)?;
+	// Same buildings as provision_player gives a new player, in their capital
+	wiped += diesel::sql_query(
+		"INSERT INTO player_building (player_id, building_id, level)
+		 SELECT p.id, b.id, CASE WHEN b.max_count = 1 THEN 1 ELSE 0 END
+		 FROM player p
+		 JOIN building b ON b.faction = p.faction AND b.starter = TRUE",
+	)
+	.execute(conn)?;
+	Ok(wiped)
+}
+
+/// Grants every player a new beginner shield, or drops all shields if they are disabled.
+fn reset_shields(
+	conn: &mut DbConn,
+	settings: &ProtectionSettings,
+	now: DateTime<Utc>,
+) -> Result<usize> {
+	let until = (settings.beginner_shield_days > 0)
+		.then(|| now + TimeDelta::days(settings.beginner_shield_days));
+	let updated = diesel::update(player::table)
+		.set(player::protected_until.eq(until))
+		.execute(conn)?;
+	Ok(updated)
+}
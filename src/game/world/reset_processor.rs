@@ -0,0 +1,90 @@
+//! World reset job processor.
+//!
+//! This module implements the job processing functionality for world resets, running one
+//! step of a confirmed reset per job and enqueueing the next step once it committed.
+
+use std::sync::Arc;
+
+use axum::extract::FromRef;
+use tracing::{debug, info, instrument, trace};
+
+use crate::Error;
+use crate::configuration::ProtectionSettings;
+use crate::domain::app_state::{AppPool, AppQueue, AppState};
+use crate::domain::jobs::{Job, JobType};
+use crate::domain::world_reset::{WorldResetJobPayload, WorldResetStep};
+use crate::game::modifiers::modifier_cache::ModifierCache;
+use crate::game::world::reset_operations;
+use crate::job_queue::job_processor::{JobHandler, JobOutcome, JobWorker};
+
+/// A handler for [`JobType::WorldReset`] jobs.
+///
+/// Each job runs a single step of a reset. Steps that already ran are skipped, so the chain
+/// continues from wherever a failed job left it once the job is retried.
+pub struct WorldResetHandler {
+	/// Database connection pool
+	pool: AppPool,
+	/// Job queue the next steps are enqueued on
+	job_queue: AppQueue,
+	/// Settings of the beginner shields granted again by the reset
+	protection: ProtectionSettings,
+	/// Cached modifier totals, stale once the modifiers are wiped
+	modifier_cache: Arc<ModifierCache>,
+}
+
+/// Workers running the [`WorldResetHandler`].
+pub type WorldResetProcessor = JobWorker<WorldResetHandler>;
+
+impl JobHandler for WorldResetHandler {
+	const JOB_TYPE: JobType = JobType::WorldReset;
+	const WORKER_NAME: &'static str = "reset-goblin";
+
+	/// Creates a new `WorldResetHandler` with what it needs from the application state.
+	fn new(app_state: &AppState) -> Self {
+		Self {
+			pool: AppPool::from_ref(app_state),
+			job_queue: AppQueue::from_ref(app_state),
+			protection: app_state.settings.protection.clone(),
+			modifier_cache: Arc::clone(&app_state.modifier_system.cache),
+		}
+	}
+
+	#[instrument(skip(self, job), fields(job_id = %job.id))]
+	async fn handle(&self, job: Job) -> Result<JobOutcome, Error> {
+		debug!("Processing world reset job: {}", job.id);
+		trace!("Job details: {:?}", job);
+
+		let WorldResetJobPayload { reset_id, step } = serde_json::from_value(job.payload.clone())?;
+		let reset = {
+			let mut conn = self.pool.get()?;
+			reset_operations::run_step(&mut conn, &self.protection, &reset_id, step)?
+		};
+		let Some(reset) = reset else {
+			debug!(
+				"Step {} of world reset {} has nothing to do",
+				step, reset_id
+			);
+			return Ok(Some(serde_json::json!({ "step": step, "skipped": true })));
+		};
+
+		if step == WorldResetStep::Modifiers {
+			self.modifier_cache.clear();
+		}
+		match reset.next_step() {
+			Some(next) => {
+				let next_job = reset_operations::schedule_step(&self.job_queue, &reset_id, next)?;
+				debug!(
+					"Step {} of world reset {} queued as job {}",
+					next, reset_id, next_job
+				);
+			}
+			None => info!("World reset {} is complete", reset_id),
+		}
+
+		debug!("Completed processing world reset job: {}", job.id);
+		Ok(Some(serde_json::json!({
+			"step": step,
+			"completed_steps": reset.completed_steps,
+		})))
+	}
+}
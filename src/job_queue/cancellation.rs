@@ -0,0 +1,164 @@
+//! Cancelling queued and running jobs.
+//!
+//! Jobs that have not been claimed yet are cancelled right away. A job a worker already
+//! claimed cannot be stopped from the outside, so it is marked [`JobStatus::CancelRequested`]
+//! instead: the worker checks for the request before running the job, and
+//! [`JobQueue::complete_job`] and [`JobQueue::fail_job`] finish a job carrying the request as
+//! cancelled. Every transition is a single conditional update, so a cancel racing a worker
+//! either wins or finds the job already finished.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use tracing::{debug, info};
+
+use crate::Result;
+use crate::db::DbConn;
+use crate::domain::jobs::{JobKey, JobStatus};
+use crate::job_queue::JobQueue;
+use crate::schema::job;
+
+/// Outcome of cancelling a single job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobCancellation {
+	/// The job had not started and will never run
+	Cancelled,
+	/// The job is running and ends as cancelled once its worker is done with it
+	Requested,
+	/// The job already finished, was cancelled before or does not exist
+	NotCancellable,
+}
+
+/// Outcome of cancelling a batch of jobs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchCancellation {
+	/// Jobs that had not started and will never run
+	pub cancelled: Vec<JobKey>,
+	/// Running jobs that end as cancelled once their worker is done with them
+	pub cancel_requested: Vec<JobKey>,
+}
+
+impl BatchCancellation {
+	/// Whether none of the jobs could be cancelled.
+	pub fn is_empty(&self) -> bool {
+		self.cancelled.is_empty() && self.cancel_requested.is_empty()
+	}
+}
+
+impl JobQueue {
+	/// Cancels a job, or asks its worker to cancel it if it is already running.
+	///
+	/// Pending jobs and failed jobs waiting for a retry are cancelled right away. Running jobs
+	/// move to [`JobStatus::CancelRequested`] and keep running until their worker notices.
+	pub fn cancel_job(&self, job_id: &JobKey) -> Result<JobCancellation> {
+		let batch = self.cancel_jobs_batch(std::slice::from_ref(job_id))?;
+
+		let outcome = if !batch.cancelled.is_empty() {
+			JobCancellation::Cancelled
+		} else if !batch.cancel_requested.is_empty() {
+			JobCancellation::Requested
+		} else {
+			JobCancellation::NotCancellable
+		};
+		debug!("Cancelling job {}: {:?}", job_id, outcome);
+		Ok(outcome)
+	}
+
+	/// Cancels several jobs at once, with the same transitions as [`JobQueue::cancel_job`].
+	///
+	/// Jobs that cannot be cancelled are left out of the result. Jobs that already carry a
+	/// cancel request are reported as requested again.
+	pub fn cancel_jobs_batch(&self, job_ids: &[JobKey]) -> Result<BatchCancellation> {
+		if job_ids.is_empty() {
+			return Ok(BatchCancellation::default());
+		}
+		let mut conn = self.pool.get()?;
+
+		let batch = conn.transaction(|conn| -> Result<BatchCancellation> {
+			let cancelled = diesel::update(job::table)
+				.filter(job::id.eq_any(job_ids))
+				.filter(job::status.eq_any([JobStatus::Pending, JobStatus::Failed]))
+				.set((
+					job::status.eq(JobStatus::Cancelled),
+					job::locked_at.eq(None::<DateTime<Utc>>),
+					job::locked_by.eq(None::<String>),
+				))
+				.returning(job::id)
+				.get_results(conn)?;
+
+			let cancel_requested = diesel::update(job::table)
+				.filter(job::id.eq_any(job_ids))
+				.filter(job::status.eq_any([JobStatus::InProgress, JobStatus::CancelRequested]))
+				.set(job::status.eq(JobStatus::CancelRequested))
+				.returning(job::id)
+				.get_results(conn)?;
+
+			Ok(BatchCancellation {
+				cancelled,
+				cancel_requested,
+			})
+		})?;
+
+		if !batch.is_empty() {
+			info!(
+				"Cancelled {} jobs, requested cancellation of {} running jobs",
+				batch.cancelled.len(),
+				batch.cancel_requested.len()
+			);
+		}
+		Ok(batch)
+	}
+
+	/// Finishes a claimed job as cancelled if a cancel was requested for it.
+	///
+	/// Workers call this before running a job they claimed, and skip the job when it
+	/// returns `true`.
+	pub fn take_cancel_request(&self, job_id: &JobKey) -> Result<bool> {
+		let mut conn = self.pool.get()?;
+
+		let taken = diesel::update(job::table.find(job_id))
+			.filter(job::status.eq(JobStatus::CancelRequested))
+			.set((
+				job::status.eq(JobStatus::Cancelled),
+				job::locked_at.eq(None::<DateTime<Utc>>),
+				job::locked_by.eq(None::<String>),
+			))
+			.execute(&mut conn)?;
+
+		if taken > 0 {
+			info!("Skipped job {} after a cancel request", job_id);
+		}
+		Ok(taken > 0)
+	}
+
+	/// Marks a job carrying a cancel request as cancelled once its worker finished.
+	///
+	/// Called by [`JobQueue::complete_job`] and [`JobQueue::fail_job`] inside their
+	/// transaction. A failure of the cancelled run is kept in the error history, but the job
+	/// is never retried and a recurring job is not rescheduled.
+	pub(super) fn finish_cancelled(
+		conn: &mut DbConn,
+		job_id: &JobKey,
+		failure: Option<(&str, serde_json::Value)>,
+	) -> Result<()> {
+		let release = (
+			job::status.eq(JobStatus::Cancelled),
+			job::locked_at.eq(None::<DateTime<Utc>>),
+			job::locked_by.eq(None::<String>),
+		);
+		match failure {
+			Some((error, error_history)) => diesel::update(job::table.find(job_id))
+				.set((
+					release,
+					job::last_error.eq(Some(error)),
+					job::error_history.eq(error_history),
+				))
+				.execute(conn)?,
+			None => diesel::update(job::table.find(job_id))
+				.set(release)
+				.execute(conn)?,
+		};
+
+		info!("Job {} finished as cancelled", job_id);
+		Ok(())
+	}
+}
@@ -0,0 +1,205 @@
+//! Dead-letter queue for jobs that exhausted their retries.
+//!
+//! [`JobQueue::fail_job`] moves a job here once it has no retries left, together with its
+//! payload and the error of every attempt. Dead-lettered jobs stay until an operator requeues
+//! or discards them through the admin API.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use tracing::{debug, info, instrument, warn};
+
+use crate::db::DbConn;
+use crate::domain::jobs::{
+	DeadLetterJob, Job, JobKey, JobStatus, JobType, NewDeadLetterJob, NewJob,
+};
+use crate::job_queue::JobQueue;
+use crate::schema::{job, job_dead_letter};
+use crate::{Error, ErrorKind, Result};
+
+/// Default number of dead-lettered jobs per page.
+pub const DEFAULT_PAGE_SIZE: i64 = 20;
+
+/// Upper bound for the page size of dead-lettered job listings.
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+/// A page of dead-lettered jobs, most recent first.
+#[derive(Debug, Clone)]
+pub struct DeadLetterPage {
+	pub jobs: Vec<DeadLetterJob>,
+	pub page: i64,
+	pub per_page: i64,
+	/// Number of dead-lettered jobs matching the filter
+	pub total: i64,
+}
+
+impl JobQueue {
+	/// Lists dead-lettered jobs, most recent first, optionally of a single type.
+	///
+	/// Pages are 1-based. Missing values default to the first page of [`DEFAULT_PAGE_SIZE`]
+	/// jobs, and page sizes are capped at [`MAX_PAGE_SIZE`].
+	pub fn list_dead_letters(
+		&self,
+		job_type: Option<JobType>,
+		page: Option<i64>,
+		per_page: Option<i64>,
+	) -> Result<DeadLetterPage> {
+		let page = page.unwrap_or(1).max(1);
+		let per_page = per_page
+			.unwrap_or(DEFAULT_PAGE_SIZE)
+			.clamp(1, MAX_PAGE_SIZE);
+		let offset = (page - 1).saturating_mul(per_page);
+		let mut conn = self.pool.get()?;
+
+		let filtered = || {
+			let mut query = job_dead_letter::table.into_boxed();
+			if let Some(job_type) = job_type {
+				query = query.filter(job_dead_letter::job_type.eq(job_type));
+			}
+			query
+		};
+		let total = filtered().count().get_result(&mut conn)?;
+		let jobs = filtered()
+			.order_by(job_dead_letter::created_at.desc())
+			.limit(per_page)
+			.offset(offset)
+			.select(DeadLetterJob::as_select())
+			.load(&mut conn)?;
+
+		Ok(DeadLetterPage {
+			jobs,
+			page,
+			per_page,
+			total,
+		})
+	}
+
+	/// Retrieves a single dead-lettered job.
+	pub fn get_dead_letter(&self, job_id: &JobKey) -> Result<DeadLetterJob> {
+		let mut conn = self.pool.get()?;
+
+		job_dead_letter::table
+			.find(job_id)
+			.select(DeadLetterJob::as_select())
+			.first(&mut conn)
+			.optional()?
+			.ok_or_else(|| {
+				Error::from((
+					ErrorKind::DeadLetterNotFoundError,
+					"Dead-lettered job not found",
+				))
+			})
+	}
+
+	/// Moves a dead-lettered job back into the queue under its original id.
+	///
+	/// The job runs right away with a fresh set of retries. Its error history is kept, so
+	/// failures from before the requeue remain visible on the job.
+	#[instrument(skip(self))]
+	pub fn requeue_dead_letter(&self, job_id: &JobKey) -> Result<Job> {
+		let mut conn = self.pool.get()?;
+
+		let requeued = conn.transaction(|conn| -> Result<Job> {
+			let dead: Option<DeadLetterJob> = diesel::delete(job_dead_letter::table.find(job_id))
+				.returning(DeadLetterJob::as_returning())
+				.get_result(conn)
+				.optional()?;
+			let Some(dead) = dead else {
+				return Err(Error::from((
+					ErrorKind::DeadLetterNotFoundError,
+					"Dead-lettered job not found",
+				)));
+			};
+
+			let new_job = NewJob {
+				job_type: dead.job_type,
+				status: JobStatus::Pending,
+				payload: dead.payload,
+				run_at: Utc::now(),
+				last_error: None,
+				max_retries: dead.max_retries,
+				priority: dead.priority,
+				timeout_seconds: dead.timeout_seconds,
+			};
+			let requeued = diesel::insert_into(job::table)
+				.values((
+					job::id.eq(dead.id),
+					&new_job,
+					job::error_history.eq(dead.error_history),
+				))
+				.returning(Job::as_returning())
+				.get_result(conn)?;
+
+			Self::notify(conn, &requeued.job_type);
+			Ok(requeued)
+		})?;
+
+		info!(
+			"Requeued dead-lettered {} job {}",
+			requeued.job_type, requeued.id
+		);
+		Ok(requeued)
+	}
+
+	/// Deletes a dead-lettered job for good.
+	///
+	/// # Returns
+	/// * `Ok(true)` if the job was discarded
+	/// * `Ok(false)` if there was no dead-lettered job with this id
+	pub fn discard_dead_letter(&self, job_id: &JobKey) -> Result<bool> {
+		let mut conn = self.pool.get()?;
+
+		let deleted = diesel::delete(job_dead_letter::table.find(job_id)).execute(&mut conn)?;
+		if deleted > 0 {
+			info!("Discarded dead-lettered job {}", job_id);
+		}
+		Ok(deleted > 0)
+	}
+
+	/// Counts the jobs dead-lettered since the given time.
+	pub fn count_dead_lettered_since(&self, since: DateTime<Utc>) -> Result<i64> {
+		let mut conn = self.pool.get()?;
+
+		let count = job_dead_letter::table
+			.filter(job_dead_letter::created_at.ge(since))
+			.count()
+			.get_result(&mut conn)?;
+
+		Ok(count)
+	}
+
+	/// Moves an exhausted job from the queue to the dead-letter table.
+	///
+	/// Called by [`JobQueue::fail_job`] inside its transaction, with the retry count and
+	/// error history including the final attempt.
+	pub(super) fn bury(
+		conn: &mut DbConn,
+		exhausted: &Job,
+		retries: i32,
+		error: &str,
+		error_history: serde_json::Value,
+	) -> Result<()> {
+		let dead = NewDeadLetterJob {
+			id: exhausted.id,
+			job_type: exhausted.job_type,
+			payload: exhausted.payload.clone(),
+			priority: exhausted.priority,
+			retries,
+			max_retries: exhausted.max_retries,
+			timeout_seconds: exhausted.timeout_seconds,
+			last_error: Some(error.to_owned()),
+			error_history,
+			enqueued_at: exhausted.created_at,
+		};
+		diesel::insert_into(job_dead_letter::table)
+			.values(&dead)
+			.execute(conn)?;
+		diesel::delete(job::table.find(exhausted.id)).execute(conn)?;
+
+		warn!(
+			"Dead-lettered {} job {} after {} retries: {}",
+			exhausted.job_type, exhausted.id, retries, error
+		);
+		debug!("Dead-lettered job payload: {}", exhausted.payload);
+		Ok(())
+	}
+}
@@ -0,0 +1,92 @@
+//! Forwards Postgres notifications on [`JOB_CHANNEL`] to the workers.
+//!
+//! Diesel connections are synchronous, so the listener runs on a blocking thread and holds
+//! one pooled connection while it runs. libpq buffers incoming notifications, and draining
+//! them every [`LISTEN_POLL_INTERVAL`] only reads from the socket without sending anything to
+//! the server, so an idle listener costs the database nothing.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use diesel::prelude::*;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, trace, warn};
+
+use crate::Result;
+use crate::domain::jobs::JobType;
+use crate::job_queue::{JOB_CHANNEL, JobQueue};
+
+/// How often buffered notifications are drained from the listening connection.
+pub const LISTEN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long to wait before listening again after the connection failed.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Starts listening for job notifications until the queue shuts down.
+///
+/// Every notification is re-broadcast to the receivers of [`JobQueue::subscribe_jobs`].
+pub fn spawn(queue: Arc<JobQueue>) -> JoinHandle<()> {
+	let mut shutdown_rx = queue.subscribe_shutdown();
+	tokio::task::spawn_blocking(move || {
+		while let Err(err) = listen(&queue, &mut shutdown_rx) {
+			warn!("Job listener failed, listening again shortly: {}", err);
+			if wait_for_shutdown(&mut shutdown_rx, RECONNECT_DELAY) {
+				break;
+			}
+		}
+		debug!("Job listener stopped");
+	})
+}
+
+/// Listens on a pooled connection until shutdown is requested or the connection fails.
+fn listen(queue: &JobQueue, shutdown_rx: &mut broadcast::Receiver<()>) -> Result<()> {
+	let mut conn = queue.pool.get()?;
+	diesel::sql_query(format!("LISTEN {JOB_CHANNEL}")).execute(&mut conn)?;
+	info!("Listening for job notifications on '{}'", JOB_CHANNEL);
+
+	let result = loop {
+		let drained = conn.notifications_iter().try_for_each(|notification| {
+			let notification = notification?;
+			match notification.payload.parse::<JobType>() {
+				Ok(job_type) => {
+					trace!("Received notification for a {} job", job_type);
+					// No receivers just means no worker is waiting right now
+					let _ = queue.wakeup_tx.send(job_type);
+				}
+				Err(err) => warn!("Ignoring job notification: {}", err),
+			}
+			Ok::<_, diesel::result::Error>(())
+		});
+		if let Err(err) = drained {
+			break Err(err.into());
+		}
+		if wait_for_shutdown(shutdown_rx, LISTEN_POLL_INTERVAL) {
+			break Ok(());
+		}
+	};
+
+	// AIDEV-NOTE: the connection goes back to the pool; a lingering LISTEN would keep
+	// buffering notifications on it forever.
+	if let Err(err) = diesel::sql_query("UNLISTEN *").execute(&mut conn) {
+		debug!("Failed to unlisten job notifications: {}", err);
+	}
+	result
+}
+
+/// Sleeps for up to `timeout`, returning early with `true` once shutdown is requested.
+fn wait_for_shutdown(shutdown_rx: &mut broadcast::Receiver<()>, timeout: Duration) -> bool {
+	let deadline = Instant::now() + timeout;
+	loop {
+		match shutdown_rx.try_recv() {
+			Err(broadcast::error::TryRecvError::Empty) => {}
+			// A signal, a lagged signal or a dropped queue all mean the same
+			_ => return true,
+		}
+		let now = Instant::now();
+		if now >= deadline {
+			return false;
+		}
+		std::thread::sleep((deadline - now).min(LISTEN_POLL_INTERVAL));
+	}
+}
@@ -2,14 +2,40 @@
 
 use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 
+use axum::extract::FromRef;
 use tokio::sync::broadcast;
-use tracing::{debug, info, instrument, trace};
+use tokio::time::sleep;
+use tracing::{debug, error, info, instrument, trace, warn};
+use ulid::Ulid;
 
-use crate::Error;
 use crate::domain::app_state::AppState;
-use crate::domain::jobs::Job;
-use crate::job_queue::JobQueue;
+use crate::domain::jobs::{Job, JobType};
+use crate::job_queue::{self, JOB_POLL_INTERVAL, JobQueue};
+use crate::{Error, ErrorKind};
+
+/// Serialized outcome of a processed job, stored on the job row for clients to poll.
+pub type JobOutcome = Option<serde_json::Value>;
+
+/// Runs a job's processing future, failing it once [`Job::timeout`] elapses.
+///
+/// The future is dropped on timeout, so work it had not committed yet is abandoned and the
+/// job is retried like any other failure.
+pub async fn with_job_timeout(
+	job: &Job,
+	processing: impl Future<Output = Result<JobOutcome, Error>>,
+) -> Result<JobOutcome, Error> {
+	tokio::time::timeout(job.timeout(), processing)
+		.await
+		.unwrap_or_else(|_| {
+			Err(Error::from((
+				ErrorKind::JobTimeoutError,
+				"Job timed out",
+				format!("no result after {} seconds", job.timeout_seconds),
+			)))
+		})
+}
 
 /// A trait defining the behaviour of a job processor component that handles background tasks.
 ///
@@ -45,9 +71,9 @@ pub trait JobProcessor {
 	/// * `job` - The job to be processed
 	///
 	/// # Returns
-	/// A Result indicating success or containing an error if job processing fails
+	/// The outcome to store on the job, if any, or an error if job processing fails
 	#[instrument(skip(self, job), fields(job.id = %job.id))]
-	async fn process_job(&self, job: Job) -> Result<(), Error> {
+	async fn process_job(&self, job: Job) -> Result<JobOutcome, Error> {
 		// This will be implemented based on job types
 		debug!("Starting to process job: {}", job.id);
 		trace!("Job details: {:?}", job);
@@ -56,6 +82,133 @@ pub trait JobProcessor {
 		// Actual implementations should override this method
 
 		info!("Successfully processed job: {}", job.id);
+		Ok(None)
+	}
+}
+
+/// The work done for the jobs of one [`JobType`], run by a [`JobWorker`].
+///
+/// Handlers only process the jobs handed to them. Claiming jobs, cancellations, timeouts
+/// and recording the outcome are left to the worker.
+pub trait JobHandler: Send + Sync + Sized + 'static {
+	/// The type of the jobs this handler processes.
+	const JOB_TYPE: JobType;
+
+	/// Prefix of the IDs of the workers running this handler, like `leaderboard-goblin`.
+	const WORKER_NAME: &'static str;
+
+	/// Creates the handler with what it needs from the application state.
+	fn new(app_state: &AppState) -> Self;
+
+	/// Processes a single job of [`Self::JOB_TYPE`].
+	///
+	/// # Returns
+	/// The outcome to store on the job, if any, or an error if job processing fails
+	fn handle(&self, job: Job) -> impl Future<Output = Result<JobOutcome, Error>> + Send;
+}
+
+/// A worker processing the jobs of its handler's [`JobType`].
+///
+/// Each worker instance runs in its own task and polls the job queue for new work, woken
+/// early when a job of its type is announced.
+pub struct JobWorker<H> {
+	/// A unique ID for the worker instance
+	id: String,
+	/// A broadcast channel receiver for handling graceful shutdowns
+	shutdown_rx: broadcast::Receiver<()>,
+	/// The handler processing the claimed jobs
+	handler: H,
+}
+
+impl<H: JobHandler> JobWorker<H> {
+	/// Creates multiple workers for parallel processing.
+	pub fn initialise_n(n: usize, state: &AppState) -> Vec<JobWorker<H>> {
+		(0..n).map(|_| JobWorker::from_ref(state)).collect()
+	}
+}
+
+impl<H: JobHandler> FromRef<AppState> for JobWorker<H> {
+	fn from_ref(state: &AppState) -> Self {
+		let rx = state.job_queue.subscribe_shutdown();
+		Self::new(state, rx)
+	}
+}
+
+impl<H: JobHandler> JobProcessor for JobWorker<H> {
+	fn new(app_state: &AppState, shutdown_rx: broadcast::Receiver<()>) -> Self {
+		let id = format!("{}-{}", H::WORKER_NAME, Ulid::generate());
+		debug!("Starting worker {}", id);
+		Self {
+			id,
+			shutdown_rx,
+			handler: H::new(app_state),
+		}
+	}
+
+	#[instrument(skip(self, queue), fields(worker.id = %self.id))]
+	async fn run(&mut self, queue: Arc<JobQueue>) -> Result<(), Error> {
+		let mut interval = tokio::time::interval(JOB_POLL_INTERVAL);
+		let mut wakeups = queue.subscribe_jobs();
+		trace!("Worker {} running", self.id);
+
+		loop {
+			tokio::select! {
+				_ = self.shutdown_rx.recv() => {
+					debug!("Worker {} shutting down", self.id);
+					break;
+				}
+				_ = job_queue::job_announced(&mut wakeups, H::JOB_TYPE) => {
+					trace!("Worker {} woken up by job notification", self.id);
+					interval.reset_immediately();
+				}
+				_ = interval.tick() => {
+					match queue.get_next_job_of_type(&self.id, &H::JOB_TYPE) {
+						Ok(Some(job)) => {
+							// Found a job, process it
+							trace!("Worker {} picked up job {}", self.id, job.id);
+							if queue.take_cancel_request(&job.id)? {
+								interval.reset_immediately();
+								continue;
+							}
+							match with_job_timeout(&job, self.process_job(job.clone())).await {
+								Ok(outcome) => {
+									trace!("Worker {} completed job {}", self.id, job.id);
+									queue.complete_job_with_result(&job.id, outcome)?;
+								}
+								Err(e) => {
+									warn!("Worker {} failed to process job {}", self.id, job.id);
+									debug!("Failed job: {:#?} {:?}", job, e);
+									queue.fail_job(&job.id, e.to_string())?;
+								}
+							}
+							// There may be more jobs waiting, look again right away
+							interval.reset_immediately();
+						}
+						Ok(None) => {
+							// No jobs available, wait for a notification or the next poll
+						}
+						Err(e) => {
+							// Error fetching job, retry after a short delay
+							error!("Error fetching job: {}", e);
+							sleep(Duration::from_secs(5)).await;
+						}
+					}
+				}
+			}
+		}
+
 		Ok(())
 	}
+
+	#[instrument(skip(self, job), fields(job_id = %job.id))]
+	async fn process_job(&self, job: Job) -> Result<JobOutcome, Error> {
+		assert_eq!(
+			job.job_type,
+			H::JOB_TYPE,
+			"Expected a {} job, got: {}",
+			H::JOB_TYPE,
+			job.job_type
+		);
+		self.handler.handle(job).await
+	}
 }
@@ -1,21 +1,61 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use chrono::{DateTime, Duration, Utc};
+use diesel::dsl::sql;
 use diesel::prelude::*;
-use serde::Serialize;
+use diesel::sql_types::{Bool, Double, Integer, Nullable, Text, Timestamptz};
+use serde::{Deserialize, Serialize};
 use tokio::sync::broadcast;
-use tracing::trace;
+use tracing::{trace, warn};
 
 use crate::db::DbConn;
 use crate::domain::app_state::AppPool;
-use crate::domain::jobs::{Job, JobKey, JobStatus, JobType, NewJob};
+use crate::domain::jobs::{Job, JobError, JobKey, JobStatus, JobType, NewJob};
+use crate::job_queue::job_processor::JobOutcome;
+use crate::job_queue::payload_encryption::PayloadCipher;
 use crate::schema::job::dsl::job;
 use crate::schema::job::*;
 use crate::{Error, ErrorKind, Result};
 
+pub mod cancellation;
+pub mod dead_letter;
+pub mod job_listener;
 pub mod job_processor;
+pub mod payload_encryption;
+pub mod recurring_scheduler;
+pub mod stats;
 pub mod worker_pool;
 
+/// Postgres channel that [`JobQueue::enqueue`] notifies with the type of a due job.
+pub const JOB_CHANNEL: &str = "jobs";
+
+/// How often workers poll for jobs when no notification arrives.
+///
+/// Notifications are only sent for jobs that are due when they are enqueued, so jobs
+/// scheduled for later are picked up by this poll.
+pub const JOB_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Priority points a due job gains per minute it waits, unless configured otherwise.
+///
+/// At this rate a [`JobPriority::Low`] job that waited 100 minutes competes with a
+/// [`JobPriority::High`] job that just became due.
+pub const DEFAULT_PRIORITY_AGING_PER_MINUTE: f64 = 1.0;
+
+/// Seconds a job may run before it is failed, unless configured for its type.
+pub const DEFAULT_JOB_TIMEOUT_SECONDS: i32 = 300;
+
+/// Extra seconds past its timeout before a job still locked is treated as abandoned.
+///
+/// Workers fail their own jobs on timeout, so the stuck-job cleanup only has to catch jobs
+/// whose worker died. The grace keeps it from racing a worker that is just timing out.
+const STUCK_JOB_GRACE_SECONDS: i32 = 30;
+
+/// First key of the advisory locks serializing claims of concurrency-limited job types.
+const JOB_LIMIT_LOCK_CLASS: i32 = 0x6a6f6273;
+
 /// Represents the priority level of a job or task.
-#[derive(Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
 pub enum JobPriority {
 	High = 0,
 	Normal = 50,
@@ -38,6 +78,18 @@ pub struct JobQueue {
 	pool: AppPool,
 	/// A broadcast channel transceiver used to coordinate graceful shutdown of workers
 	shutdown_tx: broadcast::Sender<()>,
+	/// Job types announced on [`JOB_CHANNEL`], fed by the [`job_listener`]
+	wakeup_tx: broadcast::Sender<JobType>,
+	/// Maximum number of in-progress jobs per type, across every server sharing the database
+	concurrency_limits: BTreeMap<JobType, usize>,
+	/// Priority points a due job gains per minute it waits
+	priority_aging_per_minute: f64,
+	/// Timeout in seconds of newly enqueued jobs, per type
+	timeouts: BTreeMap<JobType, i32>,
+	/// Cipher for the payloads of [`JobQueue::encrypted_types`]
+	payload_cipher: Option<PayloadCipher>,
+	/// Job types whose payloads are encrypted at rest
+	encrypted_types: BTreeSet<JobType>,
 }
 
 /// A job request is a tuple of the job type, payload, priority, and run time.
@@ -47,7 +99,67 @@ pub type JobRequest = (JobType, serde_json::Value, JobPriority, DateTime<Utc>);
 impl JobQueue {
 	pub fn new(pool: AppPool) -> Self {
 		let (shutdown_tx, _) = broadcast::channel(1);
-		Self { pool, shutdown_tx }
+		let (wakeup_tx, _) = broadcast::channel(64);
+		Self {
+			pool,
+			shutdown_tx,
+			wakeup_tx,
+			concurrency_limits: BTreeMap::new(),
+			priority_aging_per_minute: DEFAULT_PRIORITY_AGING_PER_MINUTE,
+			timeouts: BTreeMap::new(),
+			payload_cipher: None,
+			encrypted_types: BTreeSet::new(),
+		}
+	}
+
+	/// Limits how many jobs of each given type may be in progress at once.
+	///
+	/// The limit is counted in the database, so it holds across all servers. Job types
+	/// without a limit are only bounded by their number of workers.
+	pub fn with_concurrency_limits(mut self, limits: BTreeMap<JobType, usize>) -> Self {
+		self.concurrency_limits = limits;
+		self
+	}
+
+	/// Sets how many priority points a due job gains per minute it waits.
+	///
+	/// Jobs are claimed by effective priority, which never gets better than
+	/// [`JobPriority::High`]. Aged jobs tie with fresh high-priority jobs and then win by
+	/// being older, so no job waits forever. A rate of 0 orders by priority alone.
+	pub fn with_priority_aging(mut self, points_per_minute: f64) -> Self {
+		self.priority_aging_per_minute = points_per_minute.max(0.0);
+		self
+	}
+
+	/// Sets the timeout of newly enqueued jobs per type.
+	///
+	/// Types without a timeout use [`DEFAULT_JOB_TIMEOUT_SECONDS`]. The timeout is stored on
+	/// each job, so changing it does not affect jobs that are already queued.
+	pub fn with_timeouts(mut self, timeouts: BTreeMap<JobType, i32>) -> Self {
+		self.timeouts = timeouts;
+		self
+	}
+
+	/// Encrypts the payloads of `encrypted_types` with `cipher` before they are stored.
+	///
+	/// Claimed jobs are decrypted before workers see them. Without a cipher, jobs of the
+	/// flagged types cannot be enqueued at all.
+	pub fn with_payload_encryption(
+		mut self,
+		cipher: Option<PayloadCipher>,
+		encrypted_types: BTreeSet<JobType>,
+	) -> Self {
+		self.payload_cipher = cipher;
+		self.encrypted_types = encrypted_types;
+		self
+	}
+
+	/// Timeout in seconds for new jobs of `requested_type`.
+	pub fn timeout_for(&self, requested_type: &JobType) -> i32 {
+		self.timeouts
+			.get(requested_type)
+			.copied()
+			.unwrap_or(DEFAULT_JOB_TIMEOUT_SECONDS)
 	}
 
 	/// Enqueues a new job with the specified parameters
@@ -59,7 +171,7 @@ impl JobQueue {
 		job_run_at: DateTime<Utc>,
 	) -> Result<JobKey> {
 		let mut conn = self.pool.get()?;
-		let pld = serde_json::to_value(job_payload)?;
+		let pld = self.seal_payload(&new_job_type, serde_json::to_value(job_payload)?)?;
 
 		let new_job = NewJob {
 			job_type: new_job_type,
@@ -69,7 +181,7 @@ impl JobQueue {
 			last_error: None,
 			max_retries: 3,
 			priority: job_priority as i32,
-			timeout_seconds: 300,
+			timeout_seconds: self.timeout_for(&new_job_type),
 		};
 
 		let job_id = diesel::insert_into(job)
@@ -77,26 +189,36 @@ impl JobQueue {
 			.returning(id)
 			.get_result(&mut conn)?;
 
+		if job_run_at <= Utc::now() {
+			Self::notify(&mut conn, &new_job_type);
+		}
+
 		Ok(job_id)
 	}
 
 	/// Enqueues a batch of jobs with the specified parameters
 	pub fn enqueue_batch(&self, jobs: Vec<JobRequest>) -> Result<Vec<JobKey>> {
+		let now = Utc::now();
+		let due_types: BTreeSet<JobType> = jobs
+			.iter()
+			.filter(|(_, _, _, job_run_at)| *job_run_at <= now)
+			.map(|(new_job_type, _, _, _)| *new_job_type)
+			.collect();
 		let values: Vec<NewJob> = jobs
 			.into_iter()
-			.map(
-				|(new_job_type, job_payload, job_priority, job_run_at)| NewJob {
+			.map(|(new_job_type, job_payload, job_priority, job_run_at)| {
+				Ok(NewJob {
 					job_type: new_job_type,
 					status: JobStatus::Pending,
-					payload: job_payload,
+					payload: self.seal_payload(&new_job_type, job_payload)?,
 					run_at: job_run_at,
 					last_error: None,
 					max_retries: 3,
 					priority: job_priority as i32,
-					timeout_seconds: 300,
-				},
-			)
-			.collect();
+					timeout_seconds: self.timeout_for(&new_job_type),
+				})
+			})
+			.collect::<Result<_>>()?;
 		let mut conn = self.pool.get()?;
 
 		let job_ids: Vec<JobKey> = diesel::insert_into(job)
@@ -104,9 +226,36 @@ impl JobQueue {
 			.returning(id)
 			.get_results(&mut conn)?;
 
+		for due_type in &due_types {
+			Self::notify(&mut conn, due_type);
+		}
+
 		Ok(job_ids)
 	}
 
+	/// Announces a due job of the given type on [`JOB_CHANNEL`].
+	///
+	/// The job is already stored at this point, so a failed notification is only logged:
+	/// the workers still find the job on their next poll.
+	fn notify(conn: &mut DbConn, due_type: &JobType) {
+		let notified = diesel::sql_query("SELECT pg_notify($1, $2)")
+			.bind::<Text, _>(JOB_CHANNEL)
+			.bind::<Text, _>(due_type.as_str())
+			.execute(conn);
+		if let Err(err) = notified {
+			warn!("Failed to notify workers of a {} job: {}", due_type, err);
+		}
+	}
+
+	/// Creates a new receiver for job notifications.
+	///
+	/// Notifications carry the type of a job that became available. They are a hint only:
+	/// another worker may claim the job first, and notifications are dropped while no
+	/// [`job_listener`] is running.
+	pub fn subscribe_jobs(&self) -> broadcast::Receiver<JobType> {
+		self.wakeup_tx.subscribe()
+	}
+
 	/// Gets the next available job of a specific type for processing
 	pub fn get_next_job_of_type(
 		&self,
@@ -118,18 +267,55 @@ impl JobQueue {
 		let next: Option<Job> = conn.transaction(|conn| -> Result<Option<Job>> {
 			let now = Utc::now();
 
-			// First, clean up stuck jobs (those locked for too long)
+			// First, clean up stuck jobs (those locked for longer than their timeout)
+			let abandoned = || {
+				sql::<Bool>("locked_at < ")
+					.bind::<Timestamptz, _>(now)
+					.sql(" - make_interval(secs => timeout_seconds + ")
+					.bind::<Integer, _>(STUCK_JOB_GRACE_SECONDS)
+					.sql(")")
+			};
 			diesel::update(job)
 				.filter(status.eq(JobStatus::InProgress))
-				.filter(locked_at.lt(now - Duration::seconds(5))) // TODO: make job timeout configurable
+				.filter(abandoned())
 				.set((
 					status.eq(JobStatus::Failed),
-					last_error.eq(Some("Job timed out after 5min")),
+					last_error.eq(sql::<Nullable<Text>>(
+						"'Job timed out after ' || timeout_seconds || ' seconds'",
+					)),
+					locked_at.eq(None::<DateTime<Utc>>),
+					locked_by.eq(None::<String>),
+				))
+				.execute(conn)?;
+
+			// Workers that died with a cancel request pending leave their job cancelled
+			diesel::update(job)
+				.filter(status.eq(JobStatus::CancelRequested))
+				.filter(abandoned())
+				.set((
+					status.eq(JobStatus::Cancelled),
 					locked_at.eq(None::<DateTime<Utc>>),
 					locked_by.eq(None::<String>),
 				))
 				.execute(conn)?;
 
+			if let Some(limit) = self.concurrency_limits.get(requested_type)
+				&& Self::at_concurrency_limit(conn, requested_type, *limit)?
+			{
+				trace!(
+					"{} jobs are at their concurrency limit of {}",
+					requested_type, limit
+				);
+				return Ok(None);
+			}
+
+			// Due jobs gain priority while they wait, capped at the highest priority
+			let effective_priority = sql::<Double>("greatest(priority - ")
+				.bind::<Double, _>(self.priority_aging_per_minute)
+				.sql(" * extract(epoch from (")
+				.bind::<Timestamptz, _>(now)
+				.sql(" - run_at))::float8 / 60, 0)");
+
 			// Then select the next job to process
 			let next_job: Option<Job> = job
 				.filter(
@@ -141,8 +327,8 @@ impl JobQueue {
 				.filter(locked_at.is_null())
 				.filter(job_type.eq(requested_type))
 				.order_by((
-					priority.asc(), // Higher priority (lower number) first
-					run_at.asc(),   // Older jobs first
+					effective_priority.asc(), // Higher priority (lower number) first
+					run_at.asc(),             // Older jobs first
 				))
 				.limit(1)
 				.for_update() // This locks the row
@@ -157,48 +343,146 @@ impl JobQueue {
 			Ok(next_job)
 		})?;
 
-		Ok(next)
+		match next {
+			Some(mut claimed) => {
+				if let Err(err) = self.open_payload(&mut claimed) {
+					// Fail the job rather than leave it locked until it times out
+					warn!("Failed to open payload of job {}: {}", claimed.id, err);
+					self.fail_job(&claimed.id, err.to_string())?;
+					return Err(err);
+				}
+				Ok(Some(claimed))
+			}
+			None => Ok(None),
+		}
 	}
 
-	/// Marks a job as completed
-	pub fn complete_job(&self, job_id: &JobKey) -> Result<(), Error> {
+	/// Retrieves a job that is still in the queue.
+	///
+	/// Dead-lettered jobs are not found here, see [`JobQueue::get_dead_letter`].
+	pub fn get_job(&self, job_id: &JobKey) -> Result<Job> {
 		let mut conn = self.pool.get()?;
 
-		diesel::update(job.filter(id.eq(job_id)))
-			.set((
-				status.eq(JobStatus::Completed),
-				locked_at.eq(None::<DateTime<Utc>>),
-				locked_by.eq(None::<String>),
-			))
-			.execute(&mut conn)?;
+		let mut found = job
+			.find(job_id)
+			.select(Job::as_select())
+			.first(&mut conn)
+			.optional()?
+			.ok_or_else(|| Error::from((ErrorKind::JobNotFoundError, "Job not found")))?;
+		self.open_payload(&mut found)?;
+		Ok(found)
+	}
 
-		Ok(())
+	/// Moves a job that has not started yet to a new run time.
+	///
+	/// # Returns
+	/// * `Ok(true)` if the job was rescheduled
+	/// * `Ok(false)` if the job is no longer pending
+	pub fn reschedule_job(&self, job_id: &JobKey, new_run_at: DateTime<Utc>) -> Result<bool> {
+		let mut conn = self.pool.get()?;
+
+		conn.transaction(|conn| -> Result<bool> {
+			let rescheduled: Option<JobType> = diesel::update(job.find(job_id))
+				.filter(status.eq(JobStatus::Pending))
+				.set(run_at.eq(new_run_at))
+				.returning(job_type)
+				.get_result(conn)
+				.optional()?;
+
+			match rescheduled {
+				Some(due_type) => {
+					if new_run_at <= Utc::now() {
+						Self::notify(conn, &due_type);
+					}
+					Ok(true)
+				}
+				None => Ok(false),
+			}
+		})
 	}
 
-	/// Cancels a pending job, preventing it from being executed.
+	/// Checks whether a job of the given type is waiting to run.
 	///
-	/// Only cancels jobs that are still pending - jobs already in progress
-	/// or completed are not affected.
+	/// Used by self-rescheduling maintenance jobs to avoid enqueueing duplicates.
+	pub fn has_pending_of_type(&self, requested_type: &JobType) -> Result<bool> {
+		let mut conn = self.pool.get()?;
+
+		let pending = diesel::select(diesel::dsl::exists(
+			job.filter(job_type.eq(requested_type))
+				.filter(status.eq(JobStatus::Pending)),
+		))
+		.get_result(&mut conn)?;
+
+		Ok(pending)
+	}
+
+	/// Counts the pending and in-progress jobs of every type.
 	///
-	/// # Returns
-	/// * `Ok(true)` if the job was cancelled
-	/// * `Ok(false)` if the job was not pending (already running/completed/failed)
-	pub fn cancel_job(&self, job_id: &JobKey) -> Result<bool> {
+	/// Types without any outstanding jobs are omitted.
+	pub fn depth_by_type(&self) -> Result<Vec<(JobType, JobStatus, i64)>> {
 		let mut conn = self.pool.get()?;
 
-		let rows_affected = diesel::update(job.filter(id.eq(job_id)))
-			.filter(status.eq(JobStatus::Pending))
-			.set(status.eq(JobStatus::Cancelled))
-			.execute(&mut conn)?;
+		let depth = job
+			.filter(status.eq_any([JobStatus::Pending, JobStatus::InProgress]))
+			.group_by((job_type, status))
+			.select((job_type, status, diesel::dsl::count_star()))
+			.load(&mut conn)?;
 
-		Ok(rows_affected > 0)
+		Ok(depth)
+	}
+
+	/// Marks a job as completed without an outcome.
+	///
+	/// See [`JobQueue::complete_job_with_result`].
+	pub fn complete_job(&self, job_id: &JobKey) -> Result<(), Error> {
+		self.complete_job_with_result(job_id, None)
+	}
+
+	/// Marks a job as completed and stores the outcome its processor reported.
+	///
+	/// Enqueues the next run if the job belongs to a recurring job. A job that was asked to
+	/// cancel while it ran is marked as cancelled instead, and its outcome is dropped.
+	pub fn complete_job_with_result(
+		&self,
+		job_id: &JobKey,
+		outcome: JobOutcome,
+	) -> Result<(), Error> {
+		let mut conn = self.pool.get()?;
+
+		conn.transaction(|conn| -> Result<()> {
+			// Lock the row so a concurrent cancel either lands before this or sees the result
+			let cur_status: JobStatus = job
+				.find(job_id)
+				.select(status)
+				.for_update()
+				.get_result(conn)?;
+			if cur_status == JobStatus::CancelRequested {
+				return Self::finish_cancelled(conn, job_id, None);
+			}
+
+			diesel::update(job.filter(id.eq(job_id)))
+				.set((
+					status.eq(JobStatus::Completed),
+					locked_at.eq(None::<DateTime<Utc>>),
+					locked_by.eq(None::<String>),
+					result.eq(outcome),
+				))
+				.execute(conn)?;
+
+			self.reschedule_recurring(conn, job_id)
+		})?;
+
+		Ok(())
 	}
 
 	/// Marks a job as failed in the database and records the error message.
 	///
 	/// This method updates the job status to `Failed`, stores the error message,
 	/// and releases any locks on the job. This allows the job to be potentially
-	/// retried later if the maximum retry count hasn't been reached.
+	/// retried later if the maximum retry count hasn't been reached. Once it has, the job
+	/// is moved to the dead-letter table and the next run of a recurring job is enqueued.
+	/// Every failure is appended to the job's error history. A job that was asked to cancel
+	/// while it ran is marked as cancelled instead of being retried.
 	///
 	/// # Parameters
 	/// * `job_id` - The unique identifier of the job to mark as failed
@@ -211,17 +495,37 @@ impl JobQueue {
 	pub fn fail_job(&self, job_id: &JobKey, error: impl AsRef<str>) -> Result<(), Error> {
 		let mut conn = self.pool.get()?;
 
-		conn.transaction(|conn| {
+		conn.transaction(|conn| -> Result<()> {
 			// Get current job state with FOR UPDATE lock
 			let cur_job: Job = job.filter(id.eq(job_id)).for_update().get_result(conn)?;
 
 			// Only increment retries if the job failed before
-			let new_retries = if let Some(err) = cur_job.last_error {
+			let new_retries = if cur_job.last_error.is_some() {
 				cur_job.retries + 1
 			} else {
 				cur_job.retries
 			};
 
+			let mut history: Vec<JobError> =
+				serde_json::from_value(cur_job.error_history.clone()).unwrap_or_default();
+			history.push(JobError {
+				failed_at: Utc::now(),
+				error: error.as_ref().to_owned(),
+			});
+			let history = serde_json::to_value(history)?;
+
+			// A cancelled job is not retried
+			if cur_job.status == JobStatus::CancelRequested {
+				return Self::finish_cancelled(conn, job_id, Some((error.as_ref(), history)));
+			}
+
+			// Failed jobs are retried while retries <= max_retries
+			if new_retries > cur_job.max_retries {
+				self.reschedule_recurring(conn, job_id)?;
+				Self::bury(conn, &cur_job, new_retries, error.as_ref(), history)?;
+				return Ok(());
+			}
+
 			// Calculate next run time with exponential backoff
 			let backoff_seconds = if new_retries > 0 {
 				std::cmp::min(
@@ -240,10 +544,13 @@ impl JobQueue {
 					retries.eq(new_retries),
 					run_at.eq(next_run_at),
 					last_error.eq(Some(error.as_ref())),
+					error_history.eq(history),
 					locked_at.eq(None::<DateTime<Utc>>),
 					locked_by.eq(None::<String>),
 				))
-				.execute(conn)
+				.execute(conn)?;
+
+			Ok(())
 		})?;
 
 		Ok(())
@@ -290,6 +597,28 @@ impl JobQueue {
 		}
 	}
 
+	/// Checks whether `limit` jobs of `requested_type` are already running.
+	///
+	/// Takes a transaction-scoped advisory lock per job type first, so workers of the same
+	/// type count and claim one after another instead of all seeing the last free slot.
+	fn at_concurrency_limit(
+		conn: &mut DbConn,
+		requested_type: &JobType,
+		limit: usize,
+	) -> Result<bool> {
+		diesel::sql_query("SELECT pg_advisory_xact_lock($1, hashtext($2))")
+			.bind::<Integer, _>(JOB_LIMIT_LOCK_CLASS)
+			.bind::<Text, _>(requested_type.to_string())
+			.execute(conn)?;
+
+		let in_progress: i64 = job
+			.filter(job_type.eq(requested_type))
+			.filter(status.eq_any([JobStatus::InProgress, JobStatus::CancelRequested]))
+			.count()
+			.get_result(conn)?;
+		Ok(in_progress >= limit as i64)
+	}
+
 	fn lock_job(
 		&self,
 		conn: &mut DbConn,
@@ -314,3 +643,18 @@ pub struct JobQueueState {
 	pub num_workers: usize,
 	pub num_jobs: usize,
 }
+
+/// Waits until a job of the `wanted` type is announced on `wakeups`.
+///
+/// Resolves as well when notifications were missed, since one of them may have been for
+/// `wanted`. Never resolves once the channel is closed, leaving workers to their poll.
+pub async fn job_announced(wakeups: &mut broadcast::Receiver<JobType>, wanted: JobType) {
+	loop {
+		match wakeups.recv().await {
+			Ok(announced) if announced == wanted => return,
+			Ok(_) => continue,
+			Err(broadcast::error::RecvError::Lagged(_)) => return,
+			Err(broadcast::error::RecvError::Closed) => std::future::pending().await,
+		}
+	}
+}
@@ -0,0 +1,181 @@
+//! Encryption of job payloads at rest.
+//!
+//! Job types flagged with `encrypt_payload` in the job settings never store their payload in
+//! plaintext. [`JobQueue::enqueue`] seals the payload with AES-256-GCM before it is inserted,
+//! and the queue opens it again when a worker claims the job, so processors only ever see
+//! the original payload. A sealed payload is stored as `{"$encrypted": "<base64>"}`, holding
+//! the random nonce followed by the ciphertext.
+//!
+//! Dead-lettered jobs keep their sealed payload, so the admin API cannot read it either.
+
+use std::fmt;
+
+use aes_gcm::Aes256Gcm;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use data_encoding::BASE64;
+use secrecy::{ExposeSecret, SecretString};
+use serde_json::Value;
+
+use crate::domain::jobs::{Job, JobType};
+use crate::job_queue::JobQueue;
+use crate::{Error, ErrorKind, Result};
+
+/// Field of the JSON object a sealed payload is stored in.
+pub const SEALED_PAYLOAD_FIELD: &str = "$encrypted";
+
+/// Length of the AES-GCM nonce prepended to the ciphertext.
+const NONCE_LEN: usize = 12;
+
+/// AES-256-GCM cipher for job payloads.
+#[derive(Clone)]
+pub struct PayloadCipher {
+	cipher: Aes256Gcm,
+}
+
+impl fmt::Debug for PayloadCipher {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("PayloadCipher(..)")
+	}
+}
+
+impl PayloadCipher {
+	/// Creates a cipher from a base64-encoded 256-bit key.
+	pub fn from_base64_key(key: &SecretString) -> Result<Self> {
+		BASE64
+			.decode(key.expose_secret().as_bytes())
+			.ok()
+			.and_then(|key| Aes256Gcm::new_from_slice(&key).ok())
+			.map(|cipher| Self { cipher })
+			.ok_or_else(|| {
+				Error::from((
+					ErrorKind::PayloadEncryptionError,
+					"Job payload key must be 32 bytes of base64",
+				))
+			})
+	}
+
+	/// Whether `payload` was sealed by a [`PayloadCipher`].
+	pub fn is_sealed(payload: &Value) -> bool {
+		payload
+			.as_object()
+			.is_some_and(|fields| fields.len() == 1 && fields.contains_key(SEALED_PAYLOAD_FIELD))
+	}
+
+	/// Encrypts `payload` under a fresh nonce.
+	pub fn seal(&self, payload: &Value) -> Result<Value> {
+		let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+		let ciphertext = self
+			.cipher
+			.encrypt(&nonce, serde_json::to_vec(payload)?.as_slice())
+			.map_err(|_| {
+				Error::from((
+					ErrorKind::PayloadEncryptionError,
+					"Failed to encrypt job payload",
+				))
+			})?;
+
+		let mut sealed = nonce.to_vec();
+		sealed.extend(ciphertext);
+		Ok(serde_json::json!({ SEALED_PAYLOAD_FIELD: BASE64.encode(&sealed) }))
+	}
+
+	/// Decrypts a payload sealed by [`PayloadCipher::seal`] with the same key.
+	///
+	/// Fails for payloads that were sealed with a different key or altered since.
+	pub fn open(&self, payload: &Value) -> Result<Value> {
+		let unreadable = || {
+			Error::from((
+				ErrorKind::PayloadEncryptionError,
+				"Failed to decrypt job payload",
+			))
+		};
+		let sealed = payload[SEALED_PAYLOAD_FIELD]
+			.as_str()
+			.and_then(|sealed| BASE64.decode(sealed.as_bytes()).ok())
+			.filter(|sealed| sealed.len() > NONCE_LEN)
+			.ok_or_else(unreadable)?;
+
+		let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+		let plaintext = self
+			.cipher
+			.decrypt(&nonce.iter().copied().collect(), ciphertext)
+			.map_err(|_| unreadable())?;
+		Ok(serde_json::from_slice(&plaintext)?)
+	}
+}
+
+impl JobQueue {
+	/// Seals the payload of a new job if its type is flagged for encryption.
+	///
+	/// Flagged types are refused rather than stored in plaintext when no key is configured.
+	pub(super) fn seal_payload(&self, job_type: &JobType, payload: Value) -> Result<Value> {
+		if !self.encrypted_types.contains(job_type) {
+			return Ok(payload);
+		}
+		match &self.payload_cipher {
+			Some(cipher) => cipher.seal(&payload),
+			None => Err(Error::from((
+				ErrorKind::PayloadEncryptionError,
+				"Job payload encryption key is not configured",
+			))),
+		}
+	}
+
+	/// Replaces a sealed payload of `claimed` with the original one.
+	///
+	/// Payloads are recognized by their shape rather than by the job type, so jobs enqueued
+	/// before their type was unflagged can still be read.
+	pub(super) fn open_payload(&self, claimed: &mut Job) -> Result<()> {
+		if !PayloadCipher::is_sealed(&claimed.payload) {
+			return Ok(());
+		}
+		let cipher = self.payload_cipher.as_ref().ok_or_else(|| {
+			Error::from((
+				ErrorKind::PayloadEncryptionError,
+				"Job payload encryption key is not configured",
+			))
+		})?;
+		claimed.payload = cipher.open(&claimed.payload)?;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn cipher(key: [u8; 32]) -> PayloadCipher {
+		PayloadCipher::from_base64_key(&SecretString::from(BASE64.encode(&key))).unwrap()
+	}
+
+	#[test]
+	fn test_sealed_payloads_round_trip() {
+		let cipher = cipher([7; 32]);
+		let payload = serde_json::json!({ "email": "player@example.com", "digest": [1, 2] });
+
+		let sealed = cipher.seal(&payload).unwrap();
+		assert!(PayloadCipher::is_sealed(&sealed));
+		assert!(!sealed.to_string().contains("player@example.com"));
+		assert_ne!(sealed, cipher.seal(&payload).unwrap(), "Nonces must differ");
+		assert_eq!(cipher.open(&sealed).unwrap(), payload);
+		assert!(!PayloadCipher::is_sealed(&payload));
+	}
+
+	#[test]
+	fn test_foreign_or_altered_payloads_are_rejected() {
+		let sealed = cipher([7; 32])
+			.seal(&serde_json::json!({ "secret": "hook" }))
+			.unwrap();
+		assert!(cipher([8; 32]).open(&sealed).is_err());
+
+		let mut altered = BASE64
+			.decode(sealed[SEALED_PAYLOAD_FIELD].as_str().unwrap().as_bytes())
+			.unwrap();
+		*altered.last_mut().unwrap() ^= 1;
+		let altered = serde_json::json!({ SEALED_PAYLOAD_FIELD: BASE64.encode(&altered) });
+		assert!(cipher([7; 32]).open(&altered).is_err());
+
+		let short_key = SecretString::from(BASE64.encode(&[7; 16]));
+		assert!(PayloadCipher::from_base64_key(&short_key).is_err());
+	}
+}
@@ -0,0 +1,302 @@
+//! Cron-style recurring jobs.
+//!
+//! A [`RecurringJob`] is a named definition: a cron expression, a job type and a payload
+//! template. The queue keeps one instance of every definition enqueued at a time. When the
+//! instance completes, or fails for good, the next one is enqueued in the same transaction,
+//! so periodic work needs neither its own tokio interval nor a processor that reschedules
+//! itself.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use diesel::prelude::*;
+use serde::Serialize;
+use tracing::{debug, info, instrument, warn};
+
+use crate::db::DbConn;
+use crate::domain::jobs::{Job, JobKey, JobStatus, JobType, NewJob, NewRecurringJob, RecurringJob};
+use crate::job_queue::{JobPriority, JobQueue};
+use crate::schema::{job, recurring_job};
+use crate::{Error, ErrorKind, Result};
+
+/// Parses a cron expression with a leading seconds field, e.g. `0 30 3 * * *`.
+pub fn parse_schedule(cron_expression: &str) -> Result<Schedule> {
+	Schedule::from_str(cron_expression).map_err(|err| {
+		debug!("Invalid cron expression {:?}: {}", cron_expression, err);
+		Error::from((ErrorKind::InvalidScheduleError, "Invalid cron expression"))
+	})
+}
+
+/// Returns the first occurrence of the schedule strictly after `after`.
+pub fn next_occurrence(cron_expression: &str, after: DateTime<Utc>) -> Result<DateTime<Utc>> {
+	parse_schedule(cron_expression)?
+		.after(&after)
+		.next()
+		.ok_or_else(|| {
+			Error::from((
+				ErrorKind::InvalidScheduleError,
+				"Cron expression has no upcoming occurrence",
+			))
+		})
+}
+
+impl JobQueue {
+	/// Registers a recurring job, or updates the definition with the same name.
+	///
+	/// A pending instance of an existing definition is replaced so it picks up the new
+	/// schedule and payload. An instance that is already running is left alone, and the
+	/// new definition applies from its next run on.
+	#[instrument(skip(self, payload))]
+	pub fn register_recurring(
+		&self,
+		name: &str,
+		cron_expression: &str,
+		job_type: JobType,
+		payload: impl Serialize,
+		priority: JobPriority,
+	) -> Result<RecurringJob> {
+		// Reject invalid expressions before touching the table
+		let run_at = next_occurrence(cron_expression, Utc::now())?;
+
+		let definition = NewRecurringJob {
+			name: name.to_string(),
+			cron_expression: cron_expression.to_string(),
+			job_type,
+			payload: serde_json::to_value(payload)?,
+			priority: priority as i32,
+		};
+		let mut conn = self.pool.get()?;
+
+		let recurring = conn.transaction(|conn| -> Result<RecurringJob> {
+			let previous: Option<RecurringJob> = recurring_job::table
+				.filter(recurring_job::name.eq(name))
+				.select(RecurringJob::as_select())
+				.for_update()
+				.get_result(conn)
+				.optional()?;
+			let recurring: RecurringJob = diesel::insert_into(recurring_job::table)
+				.values(&definition)
+				.on_conflict(recurring_job::name)
+				.do_update()
+				.set(&definition)
+				.returning(RecurringJob::as_returning())
+				.get_result(conn)?;
+
+			let unchanged = previous.is_some_and(|previous| {
+				previous.cron_expression == recurring.cron_expression
+					&& previous.job_type == recurring.job_type
+					&& previous.payload == recurring.payload
+					&& previous.priority == recurring.priority
+			});
+			if !unchanged {
+				Self::cancel_pending_instance(conn, &recurring)?;
+			}
+
+			match Self::outstanding_instance(conn, &recurring)? {
+				Some(_) => Ok(recurring),
+				None => self.materialize(conn, &recurring, run_at),
+			}
+		})?;
+
+		info!(
+			"Registered recurring job '{}' ({}), next run at {:?}",
+			recurring.name, recurring.cron_expression, recurring.next_run_at
+		);
+		Ok(recurring)
+	}
+
+	/// Removes a recurring job and cancels its pending instance.
+	///
+	/// # Returns
+	/// * `Ok(true)` if the definition existed
+	/// * `Ok(false)` if there was no definition with this name
+	pub fn remove_recurring(&self, name: &str) -> Result<bool> {
+		let mut conn = self.pool.get()?;
+
+		conn.transaction(|conn| -> Result<bool> {
+			let removed: Option<RecurringJob> = diesel::delete(recurring_job::table)
+				.filter(recurring_job::name.eq(name))
+				.returning(RecurringJob::as_returning())
+				.get_result(conn)
+				.optional()?;
+
+			let Some(removed) = removed else {
+				return Ok(false);
+			};
+			Self::cancel_pending_instance(conn, &removed)?;
+			Ok(true)
+		})
+	}
+
+	/// Lists all recurring job definitions, ordered by name.
+	pub fn list_recurring(&self) -> Result<Vec<RecurringJob>> {
+		let mut conn = self.pool.get()?;
+
+		let definitions = recurring_job::table
+			.order_by(recurring_job::name.asc())
+			.select(RecurringJob::as_select())
+			.load(&mut conn)?;
+
+		Ok(definitions)
+	}
+
+	/// Enqueues an instance for every definition that has none outstanding.
+	///
+	/// Instances only go missing when a run was cancelled or the server stopped between
+	/// runs, so this is called once at startup.
+	///
+	/// # Returns
+	/// The number of instances that were enqueued
+	#[instrument(skip(self))]
+	pub fn sync_recurring(&self) -> Result<usize> {
+		let mut conn = self.pool.get()?;
+
+		conn.transaction(|conn| -> Result<usize> {
+			let definitions: Vec<RecurringJob> = recurring_job::table
+				.select(RecurringJob::as_select())
+				.for_update()
+				.load(conn)?;
+
+			let now = Utc::now();
+			let mut enqueued = 0;
+			for recurring in &definitions {
+				if Self::outstanding_instance(conn, recurring)?.is_some() {
+					continue;
+				}
+				match next_occurrence(&recurring.cron_expression, now) {
+					Ok(run_at) => {
+						self.materialize(conn, recurring, run_at)?;
+						enqueued += 1;
+					}
+					Err(err) => warn!("Recurring job '{}' not enqueued: {}", recurring.name, err),
+				}
+			}
+			debug!(
+				"Synced {} recurring jobs, enqueued {}",
+				definitions.len(),
+				enqueued
+			);
+			Ok(enqueued)
+		})
+	}
+
+	/// Enqueues the next instance of the recurring job that `finished_job` ran for, if any.
+	///
+	/// Called by [`JobQueue::complete_job`] and [`JobQueue::fail_job`] inside their
+	/// transaction, so a run and its successor are recorded together.
+	pub(super) fn reschedule_recurring(
+		&self,
+		conn: &mut DbConn,
+		finished_job: &JobKey,
+	) -> Result<()> {
+		let recurring: Option<RecurringJob> = recurring_job::table
+			.filter(recurring_job::next_job_id.eq(finished_job))
+			.select(RecurringJob::as_select())
+			.for_update()
+			.get_result(conn)
+			.optional()?;
+
+		let Some(recurring) = recurring else {
+			return Ok(());
+		};
+
+		let now = Utc::now();
+		diesel::update(recurring_job::table.find(recurring.id))
+			.set((
+				recurring_job::last_run_at.eq(Some(now)),
+				recurring_job::next_job_id.eq(None::<JobKey>),
+				recurring_job::next_run_at.eq(None::<DateTime<Utc>>),
+			))
+			.execute(conn)?;
+
+		// A schedule running out must not fail the job that just finished
+		match next_occurrence(&recurring.cron_expression, now) {
+			Ok(run_at) => {
+				self.materialize(conn, &recurring, run_at)?;
+			}
+			Err(err) => warn!(
+				"Recurring job '{}' has no next run: {}",
+				recurring.name, err
+			),
+		}
+		Ok(())
+	}
+
+	/// Cancels the instance of `recurring` if it has not started yet.
+	fn cancel_pending_instance(conn: &mut DbConn, recurring: &RecurringJob) -> Result<()> {
+		if let Some(next_job_id) = recurring.next_job_id {
+			diesel::update(job::table.find(next_job_id))
+				.filter(job::status.eq(JobStatus::Pending))
+				.set(job::status.eq(JobStatus::Cancelled))
+				.execute(conn)?;
+		}
+		Ok(())
+	}
+
+	/// Returns the instance of `recurring` that is still waiting to run or running, if any.
+	fn outstanding_instance(conn: &mut DbConn, recurring: &RecurringJob) -> Result<Option<Job>> {
+		let Some(next_job_id) = recurring.next_job_id else {
+			return Ok(None);
+		};
+
+		let instance = job::table
+			.find(next_job_id)
+			.filter(
+				job::status
+					.eq_any([
+						JobStatus::Pending,
+						JobStatus::InProgress,
+						JobStatus::CancelRequested,
+					])
+					.or(job::status
+						.eq(JobStatus::Failed)
+						.and(job::retries.le(job::max_retries))),
+			)
+			.select(Job::as_select())
+			.first(conn)
+			.optional()?;
+
+		Ok(instance)
+	}
+
+	/// Enqueues an instance of `recurring` to run at `run_at` and records it on the definition.
+	///
+	/// Callers schedule from the current time, so runs missed while the server was down are
+	/// skipped rather than run in a burst.
+	fn materialize(
+		&self,
+		conn: &mut DbConn,
+		recurring: &RecurringJob,
+		run_at: DateTime<Utc>,
+	) -> Result<RecurringJob> {
+		let new_job = NewJob {
+			job_type: recurring.job_type,
+			status: JobStatus::Pending,
+			payload: self.seal_payload(&recurring.job_type, recurring.payload.clone())?,
+			run_at,
+			last_error: None,
+			max_retries: 3,
+			priority: recurring.priority,
+			timeout_seconds: self.timeout_for(&recurring.job_type),
+		};
+		let job_id: JobKey = diesel::insert_into(job::table)
+			.values(&new_job)
+			.returning(job::id)
+			.get_result(conn)?;
+
+		let updated = diesel::update(recurring_job::table.find(recurring.id))
+			.set((
+				recurring_job::next_job_id.eq(Some(job_id)),
+				recurring_job::next_run_at.eq(Some(run_at)),
+			))
+			.returning(RecurringJob::as_returning())
+			.get_result(conn)?;
+
+		debug!(
+			"Enqueued run {} of recurring job '{}' at {}",
+			job_id, recurring.name, run_at
+		);
+		Ok(updated)
+	}
+}
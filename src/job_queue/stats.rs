@@ -0,0 +1,187 @@
+//! Queue statistics for the admin API and dashboards.
+//!
+//! [`JobQueue::stats`] aggregates the job table per type: how many jobs sit in each status,
+//! how long due jobs have been waiting, and how many runs failed over a recent window.
+//! Workers also log the statistics as gauges every [`STATS_REPORT_INTERVAL`], so log-based
+//! dashboards can chart the queue without polling the admin API.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Double, Nullable, Timestamptz};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::Result;
+use crate::domain::jobs::{JobStatus, JobType};
+use crate::job_queue::JobQueue;
+use crate::schema::{job, job_dead_letter};
+
+/// Window over which completed and failed runs are counted.
+pub const STATS_WINDOW: TimeDelta = TimeDelta::hours(1);
+
+/// How often workers log the queue statistics as gauges.
+pub const STATS_REPORT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Statistics of a single job type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobTypeStats {
+	pub job_type: JobType,
+	/// Jobs currently in the queue per status, statuses without jobs are omitted
+	pub depth: BTreeMap<JobStatus, i64>,
+	/// Average time due pending jobs have been waiting for a worker
+	pub avg_wait_seconds: Option<f64>,
+	/// Time the longest-waiting due pending job has been waiting for a worker
+	pub oldest_pending_seconds: Option<f64>,
+	/// Runs that completed within the window
+	pub completed: i64,
+	/// Runs that failed within the window, whether they are retried or dead-lettered
+	pub failed: i64,
+}
+
+impl JobTypeStats {
+	/// Share of finished runs within the window that failed, between 0 and 1.
+	pub fn failure_rate(&self) -> f64 {
+		match self.completed + self.failed {
+			0 => 0.0,
+			finished => self.failed as f64 / finished as f64,
+		}
+	}
+}
+
+/// Statistics of the whole queue, one entry per job type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueueStats {
+	pub generated_at: DateTime<Utc>,
+	/// Window the completed and failed runs were counted over
+	pub window: TimeDelta,
+	pub types: Vec<JobTypeStats>,
+}
+
+impl QueueStats {
+	/// Logs every job type's statistics as gauges on the `empire::metrics` target.
+	pub fn report(&self) {
+		for stats in &self.types {
+			let depth_of = |wanted: JobStatus| stats.depth.get(&wanted).copied().unwrap_or(0);
+			info!(
+				target: "empire::metrics",
+				job_type = %stats.job_type,
+				pending = depth_of(JobStatus::Pending),
+				in_progress = depth_of(JobStatus::InProgress),
+				failed = depth_of(JobStatus::Failed),
+				avg_wait_seconds = stats.avg_wait_seconds.unwrap_or(0.0),
+				oldest_pending_seconds = stats.oldest_pending_seconds.unwrap_or(0.0),
+				failure_rate = stats.failure_rate(),
+				"job queue gauges"
+			);
+		}
+	}
+}
+
+/// Waiting times of the due pending jobs of one type.
+#[derive(QueryableByName, Debug)]
+struct PendingWait {
+	#[diesel(sql_type = crate::schema::sql_types::JobType)]
+	job_type: JobType,
+	#[diesel(sql_type = Nullable<Double>)]
+	avg_wait_seconds: Option<f64>,
+	#[diesel(sql_type = Nullable<Double>)]
+	oldest_pending_seconds: Option<f64>,
+	#[diesel(sql_type = BigInt)]
+	due: i64,
+}
+
+impl JobQueue {
+	/// Aggregates the current queue statistics of every job type.
+	pub fn stats(&self) -> Result<QueueStats> {
+		let mut conn = self.pool.get()?;
+		let now = Utc::now();
+		let since = now - STATS_WINDOW;
+
+		let depth: Vec<(JobType, JobStatus, i64)> = job::table
+			.group_by((job::job_type, job::status))
+			.select((job::job_type, job::status, diesel::dsl::count_star()))
+			.load(&mut conn)?;
+		let waits: Vec<PendingWait> = diesel::sql_query(
+			"SELECT job_type, count(*) AS due, \
+			 avg(extract(epoch from ($1 - run_at)))::float8 AS avg_wait_seconds, \
+			 max(extract(epoch from ($1 - run_at)))::float8 AS oldest_pending_seconds \
+			 FROM job WHERE status = 'pending' AND run_at <= $1 GROUP BY job_type",
+		)
+		.bind::<Timestamptz, _>(now)
+		.load(&mut conn)?;
+		// Finished runs are counted by the status they were left in during the window
+		let finished: Vec<(JobType, JobStatus, i64)> = job::table
+			.filter(job::status.eq_any([JobStatus::Completed, JobStatus::Failed]))
+			.filter(job::updated_at.ge(since))
+			.group_by((job::job_type, job::status))
+			.select((job::job_type, job::status, diesel::dsl::count_star()))
+			.load(&mut conn)?;
+		let dead_lettered: Vec<(JobType, i64)> = job_dead_letter::table
+			.filter(job_dead_letter::created_at.ge(since))
+			.group_by(job_dead_letter::job_type)
+			.select((job_dead_letter::job_type, diesel::dsl::count_star()))
+			.load(&mut conn)?;
+
+		let types = JobType::ALL
+			.iter()
+			.map(|job_type| {
+				let finished_as = |wanted: JobStatus| -> i64 {
+					finished
+						.iter()
+						.filter(|(t, s, _)| t == job_type && *s == wanted)
+						.map(|(_, _, count)| *count)
+						.sum()
+				};
+				let wait = waits.iter().find(|wait| wait.job_type == *job_type);
+				let buried: i64 = dead_lettered
+					.iter()
+					.filter(|(t, _)| t == job_type)
+					.map(|(_, count)| *count)
+					.sum();
+				JobTypeStats {
+					job_type: *job_type,
+					depth: depth
+						.iter()
+						.filter(|(t, _, _)| t == job_type)
+						.map(|(_, s, count)| (*s, *count))
+						.collect(),
+					avg_wait_seconds: wait.and_then(|wait| wait.avg_wait_seconds),
+					oldest_pending_seconds: wait.and_then(|wait| wait.oldest_pending_seconds),
+					completed: finished_as(JobStatus::Completed),
+					failed: finished_as(JobStatus::Failed) + buried,
+				}
+			})
+			.collect();
+		debug!(
+			"Queue stats with {} due jobs",
+			waits.iter().map(|wait| wait.due).sum::<i64>()
+		);
+
+		Ok(QueueStats {
+			generated_at: now,
+			window: STATS_WINDOW,
+			types,
+		})
+	}
+}
+
+/// Logs the queue statistics every [`STATS_REPORT_INTERVAL`] until the queue shuts down.
+pub fn spawn_reporter(queue: Arc<JobQueue>) -> JoinHandle<()> {
+	let mut shutdown_rx = queue.subscribe_shutdown();
+	tokio::spawn(async move {
+		let mut interval = tokio::time::interval(STATS_REPORT_INTERVAL);
+		loop {
+			tokio::select! {
+				_ = shutdown_rx.recv() => break,
+				_ = interval.tick() => match queue.stats() {
+					Ok(stats) => stats.report(),
+					Err(err) => warn!("Failed to gather queue stats: {}", err),
+				},
+			}
+		}
+		debug!("Queue stats reporter stopped");
+	})
+}
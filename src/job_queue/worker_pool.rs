@@ -6,17 +6,23 @@ use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, instrument, trace, warn};
 
 use crate::Error;
-use crate::job_queue::JobQueue;
 use crate::job_queue::job_processor::JobProcessor;
+use crate::job_queue::{JobQueue, job_listener, stats};
 
 /// A pool of worker threads that process jobs from a [`JobQueue`].
 ///
 /// The `WorkerPool` manages a collection of worker threads that continuously poll
 /// for and process jobs from a shared queue. It handles worker lifecycle management,
 /// graceful shutdown, and coordination between workers.
+///
+/// The first worker added also starts the [`job_listener`], which wakes workers as soon
+/// as a job is enqueued instead of leaving them to their next poll, and the
+/// [`stats`] reporter that logs queue gauges.
 pub struct WorkerPool {
 	pub queue: Arc<JobQueue>,
 	workers: Vec<JoinHandle<()>>,
+	listener: Option<JoinHandle<()>>,
+	reporter: Option<JoinHandle<()>>,
 	cancellation_token: CancellationToken,
 }
 
@@ -32,6 +38,8 @@ impl WorkerPool {
 		Self {
 			queue,
 			workers: Vec::new(),
+			listener: None,
+			reporter: None,
 			cancellation_token: token,
 		}
 	}
@@ -88,6 +96,10 @@ impl WorkerPool {
 	#[instrument(skip(self, worker))]
 	pub fn add_worker(&mut self, mut worker: impl JobProcessor + Send + 'static) {
 		debug!("Adding new worker to pool");
+		if self.listener.is_none() {
+			self.listener = Some(job_listener::spawn(self.queue.clone()));
+			self.reporter = Some(stats::spawn_reporter(self.queue.clone()));
+		}
 		let queue = self.queue.clone();
 		let handle = tokio::spawn(async move {
 			if let Err(e) = worker.run(queue).await {
@@ -158,6 +170,8 @@ impl WorkerPool {
 
 		// Take ownership of the workers' vector
 		let workers = std::mem::take(&mut self.workers);
+		let listener = self.listener.take();
+		let reporter = self.reporter.take();
 		debug!(
 			"Waiting for {} workers to complete current tasks",
 			worker_count
@@ -174,6 +188,13 @@ impl WorkerPool {
 					// Ignore errors from cancelled tasks
 					let _ = handle.await;
 				}
+				if let Some(listener) = listener {
+					trace!("Waiting for job listener to stop");
+					let _ = listener.await;
+				}
+				if let Some(reporter) = reporter {
+					let _ = reporter.await;
+				}
 			} => {
 				info!("All {} workers shut down successfully", worker_count);
 			}
@@ -4,6 +4,7 @@ use std::sync::Arc;
 use anyhow::Result;
 use empire::db::{connection, migrations};
 use empire::domain::auth;
+use empire::game::modifiers::modifier_operations;
 use empire::startup::launch;
 use empire::{configuration, telemetry};
 use tracing::info;
@@ -16,6 +17,7 @@ async fn main() -> Result<ExitCode> {
 
 	let settings = configuration::get_settings().expect("Failed to read configuration.");
 	auth::init_keys(&settings.jwt.secret);
+	modifier_operations::init_limits(&settings.modifiers).expect("Invalid modifier limits.");
 
 	let pool = connection::initialize_pool(&settings.database);
 	{
@@ -1,49 +1,65 @@
 use std::convert::Infallible;
 
-use axum::extract::Request;
-use axum::http::StatusCode;
+use axum::debug_middleware;
+use axum::extract::{Request, State};
 use axum::middleware::Next;
-use axum::response::IntoResponse;
-use axum::{Json, debug_middleware};
+use axum::response::{IntoResponse, Response};
 use axum_extra::extract::CookieJar;
 use axum_extra::headers::authorization::Bearer;
 use axum_extra::headers::{Authorization, HeaderMapExt};
 use chrono::Utc;
 use cookie::{Cookie, SameSite, time};
 use derive_more::Deref;
+use secrecy::ExposeSecret;
 use serde::Serialize;
+use serde_json::json;
+use subtle::ConstantTimeEq;
 use tracing::{debug, error, instrument, trace, warn};
 
+use crate::auth::api_key_operations::{self, API_KEY_HEADER};
 use crate::auth::session_operations;
+use crate::configuration::Settings;
 use crate::db::extractor::DatabaseConnection;
 use crate::db::players;
-use crate::domain::app_state::AppState;
-use crate::domain::auth::{AuthenticatedUser, Claims, decode_token};
+use crate::domain::app_state::{AppMetrics, AppState};
+use crate::domain::auth::{AuthenticatedObserver, AuthenticatedUser, Claims, decode_token};
+use crate::domain::error::{Error, ErrorKind};
+use crate::domain::factions::FactionCode;
+use crate::domain::player::role::PlayerRole;
+use crate::game::observer_operations;
+use crate::net::router::route_not_found;
 
 pub const TOKEN_COOKIE_NAME: &str = "rstoken";
 pub const SESSION_COOKIE_NAME: &str = "rsession";
 
+/// The error of requests to protected routes without any credentials.
+fn not_logged_in() -> Error {
+	Error::new(
+		ErrorKind::NoSessionError,
+		"You are not logged in, please authenticate",
+	)
+}
+
 /// A session token.
 #[derive(Debug, Clone, Serialize, Deref)]
 pub struct SessionToken(String);
 
-#[derive(Debug, Serialize)]
-pub struct ErrorResponse {
-	pub status: &'static str,
-	pub message: String,
-}
+/// Marks responses to requests that passed the auth middleware, for the endpoint metrics.
+#[derive(Debug, Clone, Copy)]
+pub struct Authenticated;
 
 #[instrument(skip_all)]
 #[debug_middleware(state = AppState)]
 pub async fn auth_middleware(
 	DatabaseConnection(mut conn): DatabaseConnection,
+	State(metrics): State<AppMetrics>,
 	cookie_jar: CookieJar,
 	mut req: Request,
 	next: Next,
 ) -> crate::Result<impl IntoResponse, Infallible> {
 	let mut jar = cookie_jar.clone();
 
-	// user auth can be provided as a jwt token, session token, or Bearer token
+	// user auth can be provided as a jwt token, session token, API key, or Bearer token
 	let session_token = cookie_jar.get(SESSION_COOKIE_NAME).map(|cookie| {
 		trace!("Found session token in cookie");
 		cookie.value().to_string()
@@ -61,6 +77,14 @@ pub async fn auth_middleware(
 				bearer.token().to_string()
 			})
 		});
+	let api_key = req
+		.headers()
+		.get(API_KEY_HEADER)
+		.and_then(|value| value.to_str().ok())
+		.map(|value| {
+			trace!("Found API key in header");
+			value.to_string()
+		});
 
 	// try auth with session token
 	if let Some(token) = session_token {
@@ -78,6 +102,7 @@ pub async fn auth_middleware(
 					.build();
 
 				jar = jar.add(cookie);
+				metrics.record_activity(&player.id);
 				req.extensions_mut().insert(AuthenticatedUser(player));
 				req.extensions_mut().insert(session);
 				req.extensions_mut().insert(session_token);
@@ -86,11 +111,32 @@ pub async fn auth_middleware(
 				error!("Invalid session token!");
 				debug!("{:#?}", e);
 				jar = jar.remove(SESSION_COOKIE_NAME);
-				let json_error = ErrorResponse {
-					status: "fail",
-					message: "Invalid session token".to_string(),
-				};
-				return Ok(unauthorized!(json_error, jar));
+				let error = Error::new(ErrorKind::UnauthenticatedError, "Invalid session token");
+				return Ok((jar, error.into_response()));
+			}
+		}
+	} else if let Some(key) = api_key {
+		match api_key_operations::authenticate(&mut conn, &key) {
+			Ok(Some((api_key, player))) => {
+				if let Err(e) = api_key_operations::require_scope(&api_key, req.method()) {
+					warn!(
+						"Rejected request outside the scope of API key {}",
+						api_key.id
+					);
+					return Ok((jar, e.into_response()));
+				}
+				metrics.record_activity(&player.id);
+				req.extensions_mut().insert(AuthenticatedUser(player));
+				req.extensions_mut().insert(api_key);
+			}
+			Ok(None) => {
+				warn!("Invalid API key!");
+				let error = Error::new(ErrorKind::UnauthenticatedError, "Invalid API key");
+				return Ok((jar, error.into_response()));
+			}
+			Err(e) => {
+				error!("Error authenticating API key: {}", e);
+				return Ok((jar, e.into_response()));
 			}
 		}
 	} else if let Some(token) = jwt_token {
@@ -101,58 +147,193 @@ pub async fn auth_middleware(
 				if claims.exp <= Utc::now().timestamp() as usize {
 					jar = jar.remove(Cookie::new(TOKEN_COOKIE_NAME, ""));
 					error!("Token has expired!");
-					let json_error = ErrorResponse {
-						status: "fail",
-						message: "Token has expired".to_string(),
-					};
-					return Ok(unauthorized!(json_error, jar));
+					let error = Error::new(ErrorKind::UnauthenticatedError, "Token has expired");
+					return Ok((jar, error.into_response()));
 				}
 
 				let player_id = claims.sub;
 				match players::find_by_id(&mut conn, &player_id) {
-					Ok(Some(player)) => {
+					Ok(Some(player))
+						if player
+							.password_changed_at
+							.is_some_and(|changed_at| claims.issued_before(changed_at)) =>
+					{
+						warn!(
+							"Token of player {} predates their password change",
+							player.id
+						);
+						jar = jar.remove(Cookie::new(TOKEN_COOKIE_NAME, ""));
+						let error = Error::new(
+							ErrorKind::UnauthenticatedError,
+							"Token was revoked by a password change",
+						);
+						return Ok((jar, error.into_response()));
+					}
+					Ok(Some(player)) if player.anonymized_at.is_none() => {
+						metrics.record_activity(&player.id);
 						req.extensions_mut().insert(AuthenticatedUser(player));
 					}
-					Ok(None) => {
+					Ok(_) => {
 						error!("User not found in database");
 						jar = jar.remove(Cookie::new(TOKEN_COOKIE_NAME, ""));
-						let json_error = ErrorResponse {
-							status: "fail",
-							message: "The player belonging to this token no longer exists"
-								.to_string(),
-						};
-						return Ok(unauthorized!(json_error, jar));
+						let error = Error::new(
+							ErrorKind::UnauthenticatedError,
+							"The player belonging to this token no longer exists",
+						);
+						return Ok((jar, error.into_response()));
 					}
 					Err(e) => {
+						// The token may well be valid, so it is kept, and the cause stays in the logs
 						error!("Error fetching player from database: {}", e);
-						jar = jar.remove(Cookie::new(TOKEN_COOKIE_NAME, ""));
-						let json_error = ErrorResponse {
-							status: "fail",
-							message: format!("Error fetching player from database: {e}"),
-						};
-						return Ok(unauthorized!(json_error, jar));
+						return Ok((jar, e.into_response()));
 					}
 				}
 			}
 			Err(_) => {
 				error!("Invalid token!");
 				jar = jar.remove(Cookie::new(TOKEN_COOKIE_NAME, ""));
-				let json_error = ErrorResponse {
-					status: "fail",
-					message: "Invalid token".to_string(),
-				};
-				return Ok(unauthorized!(json_error, jar));
+				let error = Error::new(ErrorKind::UnauthenticatedError, "Invalid token");
+				return Ok((jar, error.into_response()));
 			}
 		}
 	} else {
 		// no auth provided
 		warn!("No token found in request");
-		let json_error = ErrorResponse {
-			status: "fail",
-			message: "You are not logged in, please authenticate".to_string(),
-		};
-		return Ok(unauthorized!(json_error, jar));
+		return Ok((jar, not_logged_in().into_response()));
+	}
+
+	let mut response = next.run(req).await;
+	response.extensions_mut().insert(Authenticated);
+	Ok((jar, response))
+}
+
+/// Authenticates tournament observers by the token in the `Authorization: Bearer` header.
+///
+/// Observers are separate from players, so player tokens and sessions are not accepted here,
+/// and observer tokens are not accepted by [`auth_middleware`]. What an observer can see is
+/// checked by the handlers against its scopes.
+#[instrument(skip_all)]
+#[debug_middleware(state = AppState)]
+pub async fn observer_middleware(
+	DatabaseConnection(mut conn): DatabaseConnection,
+	mut req: Request,
+	next: Next,
+) -> crate::Result<impl IntoResponse, Infallible> {
+	let bearer = req.headers().typed_get::<Authorization<Bearer>>();
+	let observer = match bearer {
+		Some(bearer) => observer_operations::authenticate(&mut conn, bearer.token()),
+		None => Ok(None),
+	};
+
+	match observer {
+		Ok(Some(observer)) => {
+			trace!("Authenticated observer {}", observer.id);
+			req.extensions_mut().insert(AuthenticatedObserver(observer));
+			Ok(next.run(req).await)
+		}
+		Ok(None) => {
+			warn!("Rejected observer request with missing or invalid token");
+			Ok(
+				Error::new(ErrorKind::UnauthenticatedError, "Invalid observer token")
+					.into_response(),
+			)
+		}
+		Err(e) => {
+			error!("Error authenticating observer: {}", e);
+			Ok(e.into_response())
+		}
+	}
+}
+
+/// Limits routes to players with at least the role it is layered with, like
+/// `middleware::from_fn_with_state(PlayerRole::Admin, require_role)`.
+///
+/// Must run after [`auth_middleware`]. Answers `403 Forbidden` to players whose role is lower
+/// than the required one. Roles are read with the player on every request, so a changed role
+/// applies right away.
+#[instrument(skip_all, fields(required = %required))]
+pub async fn require_role(
+	State(required): State<PlayerRole>,
+	req: Request,
+	next: Next,
+) -> Response {
+	let Some(AuthenticatedUser(player)) = req.extensions().get::<AuthenticatedUser>() else {
+		error!("Role required on a route without authentication");
+		return not_logged_in().into_response();
+	};
+	if player.role < required {
+		warn!(
+			"Player {} with role {} denied a route requiring {}",
+			player.id, player.role, required
+		);
+		return Error::new(
+			ErrorKind::InsufficientRoleError,
+			"Your role does not allow this",
+		)
+		.with_details(json!({ "required_role": required }))
+		.into_response();
+	}
+	next.run(req).await
+}
+
+/// Limits routes to players who picked a faction.
+///
+/// Must run after [`auth_middleware`]. Neutral players answer `403 Forbidden` with a
+/// `FactionRequiredError` until they select a faction at `POST /player/me/faction/select`.
+#[instrument(skip_all)]
+pub async fn require_faction(req: Request, next: Next) -> Response {
+	let Some(AuthenticatedUser(player)) = req.extensions().get::<AuthenticatedUser>() else {
+		error!("Faction required on a route without authentication");
+		return not_logged_in().into_response();
+	};
+	if player.faction == FactionCode::Neutral {
+		debug!("Neutral player {} denied a game route", player.id);
+		return Error::from((
+			ErrorKind::FactionRequiredError,
+			"Select a faction before playing",
+		))
+		.into_response();
+	}
+	next.run(req).await
+}
+
+/// Header carrying the admin API key.
+pub const ADMIN_KEY_HEADER: &str = "x-admin-key";
+
+/// Header naming the operator behind an admin request, recorded in the audit log.
+///
+/// The admin key is shared, so this is what the caller declares rather than an identity.
+pub const ADMIN_OPERATOR_HEADER: &str = "x-admin-operator";
+
+/// Guards the admin API with the key from [`AdminSettings`](crate::configuration::AdminSettings).
+///
+/// Answers `404 Not Found` when no key is configured, so a disabled admin API looks like it
+/// does not exist, and `401 Unauthorized` when the header is missing or does not match.
+#[instrument(skip_all)]
+#[debug_middleware(state = AppState)]
+pub async fn admin_middleware(
+	settings: Settings,
+	req: Request,
+	next: Next,
+) -> crate::Result<impl IntoResponse, Infallible> {
+	let Some(api_key) = settings.admin.api_key else {
+		debug!("Admin API is disabled, no key configured");
+		return Ok(route_not_found(req.uri()).into_response());
+	};
+
+	let provided = req
+		.headers()
+		.get(ADMIN_KEY_HEADER)
+		.map(|value| value.as_bytes());
+	// Compared in constant time, so the answer times do not give away how much of a guess is right
+	let matches = provided
+		.is_some_and(|provided| bool::from(provided.ct_eq(api_key.expose_secret().as_bytes())));
+	if !matches {
+		warn!("Rejected admin request with missing or invalid key");
+		return Ok(
+			Error::new(ErrorKind::UnauthenticatedError, "Invalid admin key").into_response(),
+		);
 	}
 
-	Ok((jar, next.run(req).await))
+	Ok(next.run(req).await)
 }
@@ -0,0 +1,105 @@
+//! Request body size limit.
+//!
+//! Requests declaring a body larger than the configured limit are answered with
+//! `413 Payload Too Large` before the body is read. Bodies of unknown length are cut off at
+//! the limit while the handler reads them, which fails their extraction with the same status.
+
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::header;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use http_body_util::Limited;
+use serde_json::json;
+use tracing::debug;
+
+use crate::{Error, ErrorKind};
+
+/// Turns away requests whose body is larger than `max_bytes`.
+pub async fn body_limit_middleware(
+	State(max_bytes): State<usize>,
+	req: Request,
+	next: Next,
+) -> Response {
+	let declared = req
+		.headers()
+		.get(header::CONTENT_LENGTH)
+		.and_then(|value| value.to_str().ok())
+		.and_then(|value| value.parse::<usize>().ok());
+
+	if declared.is_some_and(|length| length > max_bytes) {
+		debug!(declared, max_bytes, "Request body is too large");
+		return Error::new(ErrorKind::RequestTooLargeError, "Request body is too large")
+			.with_details(json!({ "max_bytes": max_bytes }))
+			.into_response();
+	}
+
+	let req = req.map(|body| Body::new(Limited::new(body, max_bytes)));
+	next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+	use axum::Router;
+	use axum::body::Bytes;
+	use axum::http::StatusCode;
+	use axum::middleware;
+	use axum::routing::post;
+	use futures_util::stream;
+	use http_body_util::BodyExt;
+	use tower::ServiceExt;
+
+	use super::*;
+
+	fn router(max_bytes: usize) -> Router {
+		Router::new()
+			.route("/echo", post(|body: Bytes| async move { body }))
+			.layer(middleware::from_fn_with_state(
+				max_bytes,
+				body_limit_middleware,
+			))
+	}
+
+	fn post_body(body: Body, length: Option<usize>) -> Request {
+		let mut request = Request::builder().method("POST").uri("/echo");
+		if let Some(length) = length {
+			request = request.header(header::CONTENT_LENGTH, length);
+		}
+		request.body(body).unwrap()
+	}
+
+	#[tokio::test]
+	async fn test_bodies_within_the_limit_pass() {
+		let response = router(8)
+			.oneshot(post_body(Body::from("12345678"), Some(8)))
+			.await
+			.unwrap();
+
+		assert_eq!(response.status(), StatusCode::OK);
+		let body = response.into_body().collect().await.unwrap().to_bytes();
+		assert_eq!(&body[..], b"12345678");
+	}
+
+	#[tokio::test]
+	async fn test_declared_large_bodies_are_turned_away() {
+		let response = router(8)
+			.oneshot(post_body(Body::from("123456789"), Some(9)))
+			.await
+			.unwrap();
+
+		assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+		let body = response.into_body().collect().await.unwrap().to_bytes();
+		let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+		assert_eq!(body["code"], "request_too_large_error");
+		assert_eq!(body["details"]["max_bytes"], 8);
+	}
+
+	#[tokio::test]
+	async fn test_streamed_large_bodies_are_cut_off() {
+		let chunks = ["12345", "6789"].map(Ok::<_, std::io::Error>);
+		let body = Body::from_stream(stream::iter(chunks));
+		let response = router(8).oneshot(post_body(body, None)).await.unwrap();
+
+		assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+	}
+}
@@ -0,0 +1,190 @@
+//! Concurrency limits for expensive routes.
+//!
+//! Routes that scan large tables are grouped, and every group serves a configured number of
+//! requests at once. A request waits up to the queue timeout for a free slot, then it is
+//! answered with `503 Service Unavailable` and a `Retry-After` hint instead of holding on to
+//! a database connection that cheaper requests need.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::{HeaderValue, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde_json::json;
+use tokio::sync::Semaphore;
+use tracing::{debug, warn};
+
+use crate::configuration::ConcurrencySettings;
+use crate::{Error, ErrorKind};
+
+/// Seconds clients are asked to wait before retrying a turned away request.
+const RETRY_AFTER_SECONDS: u64 = 1;
+
+/// Groups of routes that share a concurrency limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteGroup {
+	/// The admin API, whose overview and statistics aggregate whole tables
+	Admin,
+	/// The building catalog, generated from every definition and requirement
+	Catalog,
+}
+
+impl RouteGroup {
+	/// Returns the group of a matched route path, if it is limited.
+	pub fn of(path: &str) -> Option<Self> {
+		match path {
+			"/game/buildings/all" | "/game/buildings/available" => Some(Self::Catalog),
+			path if path.starts_with("/admin/") => Some(Self::Admin),
+			_ => None,
+		}
+	}
+}
+
+/// Permits of every route group, shared by all requests.
+#[derive(Debug, Clone)]
+pub struct RouteLimits {
+	admin: Arc<Semaphore>,
+	catalog: Arc<Semaphore>,
+	queue_timeout: Duration,
+}
+
+impl RouteLimits {
+	pub fn new(settings: &ConcurrencySettings) -> Self {
+		Self {
+			admin: Arc::new(Semaphore::new(settings.admin)),
+			catalog: Arc::new(Semaphore::new(settings.catalog)),
+			queue_timeout: Duration::from_millis(settings.queue_timeout_ms),
+		}
+	}
+
+	fn permits(&self, group: RouteGroup) -> &Arc<Semaphore> {
+		match group {
+			RouteGroup::Admin => &self.admin,
+			RouteGroup::Catalog => &self.catalog,
+		}
+	}
+}
+
+/// Holds requests to limited routes until their group has a free slot.
+pub async fn concurrency_middleware(
+	State(limits): State<RouteLimits>,
+	req: Request,
+	next: Next,
+) -> Response {
+	let group = req
+		.extensions()
+		.get::<MatchedPath>()
+		.and_then(|path| RouteGroup::of(path.as_str()));
+	let Some(group) = group else {
+		return next.run(req).await;
+	};
+
+	let acquired = tokio::time::timeout(
+		limits.queue_timeout,
+		limits.permits(group).clone().acquire_owned(),
+	)
+	.await;
+	match acquired {
+		Ok(Ok(_permit)) => {
+			debug!("Serving {:?} request to {}", group, req.uri().path());
+			next.run(req).await
+		}
+		_ => {
+			warn!(
+				"Turning away {:?} request to {}, all slots are busy",
+				group,
+				req.uri().path()
+			);
+			let mut response = Error::new(
+				ErrorKind::ServerBusyError,
+				"Too many concurrent requests, retry shortly",
+			)
+			.with_details(json!({ "retry_after_seconds": RETRY_AFTER_SECONDS }))
+			.into_response();
+			response
+				.headers_mut()
+				.insert(header::RETRY_AFTER, HeaderValue::from(RETRY_AFTER_SECONDS));
+			response
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use axum::Router;
+	use axum::body::Body;
+	use axum::http::StatusCode;
+	use axum::middleware;
+	use axum::routing::get;
+	use tower::ServiceExt;
+
+	use super::*;
+
+	fn router(settings: ConcurrencySettings) -> Router {
+		let slow = || async {
+			tokio::time::sleep(Duration::from_millis(200)).await;
+			"done"
+		};
+		Router::new()
+			.route("/admin/overview", get(slow))
+			.route("/game/buildings", get(slow))
+			.layer(middleware::from_fn_with_state(
+				RouteLimits::new(&settings),
+				concurrency_middleware,
+			))
+	}
+
+	/// Sends `requests` concurrent requests and returns the responses, successful ones first.
+	async fn burst(router: &Router, uri: &str, requests: usize) -> Vec<Response> {
+		let responses = (0..requests).map(|_| {
+			let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+			router.clone().oneshot(request)
+		});
+		let mut responses: Vec<Response> = futures_util::future::join_all(responses)
+			.await
+			.into_iter()
+			.map(Result::unwrap)
+			.collect();
+		responses.sort_by_key(|response| response.status());
+		responses
+	}
+
+	#[tokio::test]
+	async fn test_bursts_beyond_the_limit_are_turned_away() {
+		let router = router(ConcurrencySettings {
+			admin: 1,
+			catalog: 1,
+			queue_timeout_ms: 50,
+		});
+
+		let limited = burst(&router, "/admin/overview", 2).await;
+		assert_eq!(limited[0].status(), StatusCode::OK);
+		assert_eq!(limited[1].status(), StatusCode::SERVICE_UNAVAILABLE);
+		assert_eq!(limited[1].headers()[header::RETRY_AFTER], "1");
+
+		let unlimited = burst(&router, "/game/buildings", 3).await;
+		assert!(
+			unlimited
+				.iter()
+				.all(|response| response.status() == StatusCode::OK)
+		);
+	}
+
+	#[tokio::test]
+	async fn test_requests_wait_for_a_free_slot() {
+		let router = router(ConcurrencySettings {
+			admin: 1,
+			catalog: 1,
+			queue_timeout_ms: 1000,
+		});
+
+		let queued = burst(&router, "/admin/overview", 2).await;
+		assert!(
+			queued
+				.iter()
+				.all(|response| response.status() == StatusCode::OK)
+		);
+	}
+}
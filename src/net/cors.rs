@@ -0,0 +1,144 @@
+//! Cross-origin resource sharing.
+//!
+//! Browsers only let pages of other origins call the API when it answers with the CORS
+//! headers. The origins, whether cookies are sent along and how long preflight answers are
+//! cached all come from `cors` in the configuration.
+
+use std::time::Duration;
+
+use axum::http::{HeaderName, HeaderValue, header};
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
+use tracing::warn;
+
+use crate::configuration::CorsSettings;
+use crate::net::rate_limit::{
+	RATE_LIMIT_LIMIT_HEADER, RATE_LIMIT_REMAINING_HEADER, RATE_LIMIT_RESET_HEADER,
+};
+use crate::net::router::REQUEST_ID_HEADER;
+
+/// Builds the CORS layer of the configured origins.
+///
+/// Methods and headers asked for in preflight requests are mirrored back, browsers refuse
+/// wildcards for them along with credentials. The `*` origin allows any origin, but never
+/// with credentials, as it would let any website act on behalf of the logged in player.
+pub fn cors_layer(settings: &CorsSettings) -> CorsLayer {
+	let origins: Vec<&str> = settings
+		.allowed_origins
+		.iter()
+		.map(|origin| origin.trim())
+		.filter(|origin| !origin.is_empty())
+		.collect();
+
+	let any_origin = origins.contains(&"*");
+	if any_origin && settings.allow_credentials {
+		warn!("Any CORS origin is allowed, so credentials are not");
+	}
+	let allow_origin = if any_origin {
+		AllowOrigin::any()
+	} else {
+		AllowOrigin::list(origins.into_iter().filter_map(|origin| {
+			HeaderValue::from_str(origin)
+				.inspect_err(|_| warn!(origin, "Ignoring invalid CORS origin"))
+				.ok()
+		}))
+	};
+
+	CorsLayer::new()
+		.allow_origin(allow_origin)
+		.allow_credentials(settings.allow_credentials && !any_origin)
+		.allow_methods(AllowMethods::mirror_request())
+		.allow_headers(AllowHeaders::mirror_request())
+		.expose_headers([
+			HeaderName::from_static(REQUEST_ID_HEADER),
+			header::ETAG,
+			header::RETRY_AFTER,
+			RATE_LIMIT_LIMIT_HEADER,
+			RATE_LIMIT_REMAINING_HEADER,
+			RATE_LIMIT_RESET_HEADER,
+		])
+		.max_age(Duration::from_secs(settings.max_age_seconds))
+}
+
+#[cfg(test)]
+mod tests {
+	use axum::Router;
+	use axum::body::Body;
+	use axum::http::{Method, Request, Response};
+	use axum::routing::get;
+	use tower::ServiceExt;
+
+	use super::*;
+
+	fn settings(origins: &[&str]) -> CorsSettings {
+		CorsSettings {
+			allowed_origins: origins.iter().map(|origin| origin.to_string()).collect(),
+			allow_credentials: true,
+			max_age_seconds: 600,
+		}
+	}
+
+	async fn preflight(settings: &CorsSettings, origin: &str) -> Response<Body> {
+		let router = Router::new()
+			.route("/health", get(|| async { "ok" }))
+			.layer(cors_layer(settings));
+		let request = Request::builder()
+			.method(Method::OPTIONS)
+			.uri("/health")
+			.header(header::ORIGIN, origin)
+			.header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+			.header(header::ACCESS_CONTROL_REQUEST_HEADERS, "authorization")
+			.body(Body::empty())
+			.unwrap();
+		router.oneshot(request).await.unwrap()
+	}
+
+	#[tokio::test]
+	async fn test_allowed_origins_are_answered() {
+		let settings = settings(&["https://play.example.com", " https://beta.example.com"]);
+
+		for origin in ["https://play.example.com", "https://beta.example.com"] {
+			let response = preflight(&settings, origin).await;
+			let headers = response.headers();
+			assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], origin);
+			assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+			assert_eq!(
+				headers[header::ACCESS_CONTROL_ALLOW_HEADERS],
+				"authorization"
+			);
+			assert_eq!(headers[header::ACCESS_CONTROL_MAX_AGE], "600");
+		}
+	}
+
+	#[tokio::test]
+	async fn test_other_origins_are_not_answered() {
+		let response = preflight(
+			&settings(&["https://play.example.com"]),
+			"https://evil.example.com",
+		)
+		.await;
+		assert!(
+			!response
+				.headers()
+				.contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+		);
+
+		// Without allowed origins, cross-origin calls are kept out
+		let response = preflight(&CorsSettings::default(), "https://play.example.com").await;
+		assert!(
+			!response
+				.headers()
+				.contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+		);
+	}
+
+	#[tokio::test]
+	async fn test_wildcard_allows_any_origin_without_credentials() {
+		let settings = settings(&["*"]);
+		assert!(settings.allow_credentials);
+
+		let response = preflight(&settings, "https://evil.example.com").await;
+		let headers = response.headers();
+		assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+		assert!(!headers.contains_key(header::ACCESS_CONTROL_ALLOW_CREDENTIALS));
+	}
+}
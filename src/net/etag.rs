@@ -0,0 +1,138 @@
+//! Conditional requests for polled JSON endpoints.
+//!
+//! Responses are tagged with an `ETag`, and clients sending it back in `If-None-Match` are
+//! answered `304 Not Modified` while it stays the same, which spares polling clients from
+//! downloading unchanged state over and over. Static bodies are tagged with a hash of
+//! themselves, state that is costly to load with a hash of a cheap version of it.
+
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use blake2::{Blake2s256, Digest};
+use serde::Serialize;
+use tracing::trace;
+
+use crate::Result;
+
+/// Serves `body` as JSON tagged with an `ETag`, or `304 Not Modified` when it matches one of
+/// the tags in the `If-None-Match` header of the request.
+///
+/// Tags are weak, as the compression layer may send the same body in another encoding.
+pub fn tagged_json(headers: &HeaderMap, body: impl Serialize) -> Result<Response> {
+	let bytes = to_json(body)?;
+	let etag = entity_tag(&bytes);
+	if !none_match(headers, &etag) {
+		return Ok(not_modified(etag));
+	}
+	Ok(json_response(etag, bytes))
+}
+
+/// Serves the JSON body returned by `load`, tagged with an `ETag` hashed from `version`, or
+/// `304 Not Modified` without loading it when the tag matches the `If-None-Match` header.
+///
+/// `version` must change whenever the body would, apart from countdowns clients derive from
+/// the timestamps of the body.
+pub fn versioned_json<T: Serialize>(
+	headers: &HeaderMap,
+	version: impl Serialize,
+	load: impl FnOnce() -> Result<T>,
+) -> Result<Response> {
+	let etag = entity_tag(&to_json(version)?);
+	if !none_match(headers, &etag) {
+		return Ok(not_modified(etag));
+	}
+	Ok(json_response(etag, to_json(load()?)?))
+}
+
+/// Serializes `value` to JSON, going through a `Value`, which sorts the keys of maps, so
+/// equal values serialize alike.
+fn to_json(value: impl Serialize) -> Result<Vec<u8>> {
+	Ok(serde_json::to_vec(&serde_json::to_value(value)?)?)
+}
+
+fn json_response(etag: HeaderValue, bytes: Vec<u8>) -> Response {
+	(
+		[
+			(
+				header::CONTENT_TYPE,
+				HeaderValue::from_static("application/json"),
+			),
+			(header::ETAG, etag),
+			(header::CACHE_CONTROL, HeaderValue::from_static("no-cache")),
+		],
+		bytes,
+	)
+		.into_response()
+}
+
+fn not_modified(etag: HeaderValue) -> Response {
+	trace!("Client holds the current body, answering not modified");
+	(
+		StatusCode::NOT_MODIFIED,
+		[
+			(header::ETAG, etag),
+			(header::CACHE_CONTROL, HeaderValue::from_static("no-cache")),
+		],
+	)
+		.into_response()
+}
+
+/// The weak entity tag of a body.
+fn entity_tag(bytes: &[u8]) -> HeaderValue {
+	let mut hasher = Blake2s256::new();
+	Digest::update(&mut hasher, bytes);
+	let digest = hasher.finalize();
+	// Hex digits and quotes are always a valid header value
+	HeaderValue::from_str(&format!("W/\"{digest:x}\"")).expect("Entity tags are visible ASCII")
+}
+
+/// Whether none of the tags of the `If-None-Match` header match `etag`, using the weak
+/// comparison of RFC 9110.
+fn none_match(headers: &HeaderMap, etag: &HeaderValue) -> bool {
+	let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_owned();
+	let etag = opaque(etag.to_str().unwrap_or_default());
+	!headers
+		.get_all(header::IF_NONE_MATCH)
+		.iter()
+		.filter_map(|value| value.to_str().ok())
+		.flat_map(|value| value.split(','))
+		.any(|tag| tag.trim() == "*" || opaque(tag) == etag)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn request_with(if_none_match: &str) -> HeaderMap {
+		let mut headers = HeaderMap::new();
+		headers.insert(
+			header::IF_NONE_MATCH,
+			HeaderValue::from_str(if_none_match).unwrap(),
+		);
+		headers
+	}
+
+	#[test]
+	fn equal_bodies_have_equal_tags() {
+		let first = entity_tag(b"{\"a\":1}");
+		assert_eq!(first, entity_tag(b"{\"a\":1}"));
+		assert_ne!(first, entity_tag(b"{\"a\":2}"));
+		assert!(first.to_str().unwrap().starts_with("W/\""));
+	}
+
+	#[test]
+	fn matching_tags_are_not_modified() {
+		let etag = entity_tag(b"{}");
+		let tag = etag.to_str().unwrap();
+		let strong = tag.trim_start_matches("W/");
+
+		assert!(none_match(&HeaderMap::new(), &etag));
+		assert!(none_match(&request_with("\"other\""), &etag));
+		assert!(!none_match(&request_with(tag), &etag));
+		assert!(!none_match(&request_with(strong), &etag));
+		assert!(!none_match(
+			&request_with(&format!("\"other\", {tag}")),
+			&etag
+		));
+		assert!(!none_match(&request_with("*"), &etag));
+	}
+}
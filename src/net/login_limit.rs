@@ -0,0 +1,284 @@
+//! Rate limiting of failed logins.
+//!
+//! Failed logins are counted per username and per client IP. Once either reaches its
+//! threshold within `login_limits.window_seconds`, logins for that username or from that IP
+//! are answered with `429 Too Many Requests` and a `Retry-After` hint until the lockout
+//! ends, without checking the password. A successful login forgets the username's failures.
+//!
+//! The counts are kept in memory with the router, so every server instance limits on its
+//! own and a restart lifts every lockout. Lockouts and turned away logins are logged on the
+//! `empire::security` target.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::body::{Body, to_bytes};
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::{HeaderValue, Method, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{debug, warn};
+
+use crate::configuration::LoginLimitSettings;
+use crate::{Error, ErrorKind};
+
+/// Largest login body read to find the username, anything larger is refused.
+const MAX_LOGIN_BODY_BYTES: usize = 16 * 1024;
+
+/// What failed logins are counted against.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum LoginKey {
+	Username(String),
+	Ip(IpAddr),
+}
+
+/// Failed logins of a key within the current window.
+#[derive(Debug, Clone, Copy)]
+struct Failures {
+	count: u32,
+	first_at: Instant,
+	locked_until: Option<Instant>,
+}
+
+/// Failed login counts of every username and IP, shared by all login requests.
+#[derive(Debug)]
+pub struct LoginLimiter {
+	settings: LoginLimitSettings,
+	failures: Mutex<HashMap<LoginKey, Failures>>,
+}
+
+impl LoginLimiter {
+	pub fn new(settings: &LoginLimitSettings) -> Self {
+		Self {
+			settings: *settings,
+			failures: Mutex::new(HashMap::new()),
+		}
+	}
+
+	fn window(&self) -> Duration {
+		Duration::from_secs(self.settings.window_seconds)
+	}
+
+	fn keys(username: Option<&str>, ip: Option<IpAddr>) -> impl Iterator<Item = LoginKey> {
+		let username = username.map(|username| LoginKey::Username(username.to_string()));
+		username.into_iter().chain(ip.map(LoginKey::Ip))
+	}
+
+	fn max_failures(&self, key: &LoginKey) -> u32 {
+		match key {
+			LoginKey::Username(_) => self.settings.max_failures_per_username,
+			LoginKey::Ip(_) => self.settings.max_failures_per_ip,
+		}
+	}
+
+	/// Returns how much longer the username or the IP is locked out, the longer of both.
+	pub fn locked_for(
+		&self,
+		username: Option<&str>,
+		ip: Option<IpAddr>,
+		now: Instant,
+	) -> Option<Duration> {
+		let failures = self.failures.lock().expect("login limiter poisoned");
+		Self::keys(username, ip)
+			.filter_map(|key| failures.get(&key)?.locked_until)
+			.filter(|until| *until > now)
+			.map(|until| until - now)
+			.max()
+	}
+
+	/// Counts a failed login against the username and the IP.
+	///
+	/// # Returns
+	/// The lockout this failure started, `None` if it locked neither
+	pub fn record_failure(
+		&self,
+		username: Option<&str>,
+		ip: Option<IpAddr>,
+		now: Instant,
+	) -> Option<Duration> {
+		let window = self.window();
+		let lockout = Duration::from_secs(self.settings.lockout_seconds);
+		let mut failures = self.failures.lock().expect("login limiter poisoned");
+		// Forget failures that decayed, so the map only holds recent attempts
+		failures.retain(|_, entry| match entry.locked_until {
+			Some(until) => until > now,
+			None => now.duration_since(entry.first_at) < window,
+		});
+
+		let mut started = None;
+		for key in Self::keys(username, ip) {
+			let max_failures = self.max_failures(&key);
+			let entry = failures.entry(key).or_insert(Failures {
+				count: 0,
+				first_at: now,
+				locked_until: None,
+			});
+			entry.count += 1;
+			if entry.locked_until.is_none() && entry.count >= max_failures {
+				entry.locked_until = Some(now + lockout);
+				started = Some(lockout);
+			}
+		}
+		started
+	}
+
+	/// Forgets the failed logins of a username that logged in.
+	pub fn record_success(&self, username: &str) {
+		let mut failures = self.failures.lock().expect("login limiter poisoned");
+		failures.remove(&LoginKey::Username(username.to_string()));
+	}
+}
+
+/// The part of a login body the limiter needs.
+#[derive(Deserialize)]
+struct LoginAttempt {
+	username: String,
+}
+
+/// Turns away logins of locked usernames and IPs, and counts the failed ones.
+pub async fn login_limit_middleware(
+	State(limiter): State<Arc<LoginLimiter>>,
+	req: Request,
+	next: Next,
+) -> Response {
+	if req.method() != Method::POST || req.uri().path() != "/login" {
+		return next.run(req).await;
+	}
+
+	let ip = req
+		.extensions()
+		.get::<ConnectInfo<SocketAddr>>()
+		.map(|ConnectInfo(addr)| addr.ip());
+	let (parts, body) = req.into_parts();
+	let Ok(bytes) = to_bytes(body, MAX_LOGIN_BODY_BYTES).await else {
+		return Error::new(
+			ErrorKind::RequestTooLargeError,
+			"Login request is too large",
+		)
+		.into_response();
+	};
+	let username = serde_json::from_slice::<LoginAttempt>(&bytes)
+		.ok()
+		.map(|attempt| attempt.username)
+		.filter(|username| !username.is_empty());
+
+	if let Some(remaining) = limiter.locked_for(username.as_deref(), ip, Instant::now()) {
+		warn!(
+			target: "empire::security",
+			event = "login_rejected",
+			username = ?username,
+			ip = ?ip,
+			retry_after_seconds = remaining.as_secs(),
+			"Turned away a login of a locked out username or IP"
+		);
+		return too_many_attempts(remaining);
+	}
+
+	let response = next
+		.run(Request::from_parts(parts, Body::from(bytes)))
+		.await;
+	if response.status() == StatusCode::UNAUTHORIZED {
+		debug!("Counting failed login of {:?} from {:?}", username, ip);
+		if let Some(lockout) = limiter.record_failure(username.as_deref(), ip, Instant::now()) {
+			warn!(
+				target: "empire::security",
+				event = "login_lockout",
+				username = ?username,
+				ip = ?ip,
+				lockout_seconds = lockout.as_secs(),
+				"Locked out a username or IP after repeated failed logins"
+			);
+		}
+	} else if response.status().is_success()
+		&& let Some(username) = &username
+	{
+		limiter.record_success(username);
+	}
+	response
+}
+
+/// A `429 Too Many Requests` response asking the client to retry once the lockout ends.
+fn too_many_attempts(remaining: Duration) -> Response {
+	// Round up, so clients retrying on time never hit the end of the lockout early
+	let retry_after = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+	let mut response = Error::new(
+		ErrorKind::LoginLockedOutError,
+		"Too many failed logins, retry later",
+	)
+	.with_details(json!({ "retry_after_seconds": retry_after.max(1) }))
+	.into_response();
+	response
+		.headers_mut()
+		.insert(header::RETRY_AFTER, HeaderValue::from(retry_after.max(1)));
+	response
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn limiter() -> LoginLimiter {
+		LoginLimiter::new(&LoginLimitSettings {
+			max_failures_per_username: 3,
+			max_failures_per_ip: 5,
+			window_seconds: 60,
+			lockout_seconds: 300,
+		})
+	}
+
+	fn ip(last: u8) -> Option<IpAddr> {
+		Some(IpAddr::from([10, 0, 0, last]))
+	}
+
+	#[test]
+	fn test_usernames_and_ips_lock_at_their_thresholds() {
+		let limiter = limiter();
+		let now = Instant::now();
+
+		// Three failures lock the username, from whichever IP
+		assert_eq!(limiter.record_failure(Some("alice"), ip(1), now), None);
+		assert_eq!(limiter.record_failure(Some("alice"), ip(2), now), None);
+		assert_eq!(
+			limiter.record_failure(Some("alice"), ip(3), now),
+			Some(Duration::from_secs(300))
+		);
+		assert_eq!(
+			limiter.locked_for(Some("alice"), ip(9), now),
+			Some(Duration::from_secs(300))
+		);
+		assert_eq!(limiter.locked_for(Some("bob"), ip(1), now), None);
+
+		// Five failures lock the IP, whatever the username
+		for name in ["carol", "dave", "erin", "frank"] {
+			limiter.record_failure(Some(name), ip(7), now);
+		}
+		assert_eq!(limiter.locked_for(Some("grace"), ip(7), now), None);
+		limiter.record_failure(Some("grace"), ip(7), now);
+		assert!(limiter.locked_for(Some("heidi"), ip(7), now).is_some());
+
+		let later = now + Duration::from_secs(301);
+		assert_eq!(limiter.locked_for(Some("alice"), ip(7), later), None);
+	}
+
+	#[test]
+	fn test_failures_decay_and_successes_forget_them() {
+		let limiter = limiter();
+		let now = Instant::now();
+
+		limiter.record_failure(Some("alice"), None, now);
+		limiter.record_failure(Some("alice"), None, now);
+		// The window passed, counting starts over
+		let later = now + Duration::from_secs(61);
+		assert_eq!(limiter.record_failure(Some("alice"), None, later), None);
+		assert_eq!(limiter.record_failure(Some("alice"), None, later), None);
+
+		limiter.record_success("alice");
+		assert_eq!(limiter.record_failure(Some("alice"), None, later), None);
+		assert_eq!(limiter.record_failure(Some("alice"), None, later), None);
+		assert!(limiter.record_failure(Some("alice"), None, later).is_some());
+	}
+}
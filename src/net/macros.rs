@@ -1,24 +1,3 @@
-/// Creates an HTTP 401 Unauthorized response with a JSON body.
-///
-/// # Arguments
-/// * `$body` - The response body to be serialized as JSON
-/// * `$jar` - (Optional) A cookie jar to be included in the response
-///
-/// # Returns
-/// A response with status code 401 and the provided JSON body
-#[macro_export]
-macro_rules! unauthorized {
-	($body:expr) => {
-		(StatusCode::UNAUTHORIZED, Json($body)).into_response()
-	};
-	($body:expr, $jar:expr) => {
-		(
-			$jar,
-			(StatusCode::UNAUTHORIZED, Json($body)).into_response(),
-		)
-	};
-}
-
 /// Creates an HTTP 200 OK response with a JSON body.
 ///
 /// # Arguments
@@ -37,16 +16,14 @@ macro_rules! okay {
 	};
 }
 
-/// Creates an HTTP 501 Not Implemented response with a JSON message.
+/// Creates an HTTP 501 Not Implemented response with the error body of all failed requests.
 ///
 /// # Returns
-/// A response with status code 501 and a standard "Not implemented" JSON message
+/// A response with status code 501 and a `not_implemented_error` body
 #[macro_export]
 macro_rules! not_implemented {
-    () => {
-        (
-            axum::http::StatusCode::NOT_IMPLEMENTED,
-            axum::Json(serde_json::json!({"message": "Not implemented"}))
-        ).into_response()
-    };
+	() => {
+		$crate::Error::new($crate::ErrorKind::NotImplementedError, "Not implemented")
+			.into_response()
+	};
 }
@@ -0,0 +1,34 @@
+use std::time::Instant;
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+
+use crate::domain::app_state::AppMetrics;
+use crate::domain::metrics::{EndpointLabels, UNMATCHED_ROUTE};
+use crate::net::auth::Authenticated;
+
+/// Records the status of every response for the admin overview, and its latency under the
+/// matched route template for the `/metrics` endpoint.
+pub async fn metrics_middleware(
+	State(metrics): State<AppMetrics>,
+	req: Request,
+	next: Next,
+) -> Response {
+	let started = Instant::now();
+	let method = req.method().clone();
+	let route = req
+		.extensions()
+		.get::<MatchedPath>()
+		.map_or(UNMATCHED_ROUTE, MatchedPath::as_str)
+		.to_owned();
+
+	let response = next.run(req).await;
+	let authenticated = response.extensions().get::<Authenticated>().is_some();
+	metrics.record_response(response.status());
+	metrics.record_request(
+		EndpointLabels::new(&method, &route, response.status(), authenticated),
+		started.elapsed(),
+	);
+	response
+}
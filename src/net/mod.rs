@@ -2,8 +2,21 @@
 pub mod macros;
 
 mod auth;
+mod body_limit;
+mod concurrency;
+mod cors;
+mod etag;
+mod login_limit;
+mod metrics;
+mod rate_limit;
 mod request_id;
 pub mod router;
 pub mod server;
+pub mod sse;
+pub mod ws;
 
-pub use auth::{SESSION_COOKIE_NAME, SessionToken, TOKEN_COOKIE_NAME};
+pub use auth::{
+	ADMIN_OPERATOR_HEADER, SESSION_COOKIE_NAME, SessionToken, TOKEN_COOKIE_NAME, require_faction,
+	require_role,
+};
+pub use etag::{tagged_json, versioned_json};
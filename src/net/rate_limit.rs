@@ -0,0 +1,290 @@
+//! Request rate limits of the public API.
+//!
+//! Every client has a token bucket: authenticated players by their ID, anyone else by their
+//! IP. Each request takes a token, and buckets refill at a steady rate up to their burst, see
+//! [`RateLimitSettings`]. A client with an empty bucket is answered with
+//! `429 Too Many Requests` and a `Retry-After` hint, so a client calling an endpoint like
+//! `/game/resources/collect` in a tight loop cannot crowd out the others.
+//!
+//! Every limited response carries the `X-RateLimit-Limit`, `X-RateLimit-Remaining` and
+//! `X-RateLimit-Reset` headers, the latter in seconds until the bucket is full again. The
+//! buckets are kept in memory with the router, so every server instance limits on its own.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::{HeaderMap, HeaderName, HeaderValue, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde_json::json;
+use tracing::{trace, warn};
+
+use crate::configuration::RateLimitSettings;
+use crate::domain::auth::AuthenticatedUser;
+use crate::domain::player::PlayerKey;
+use crate::{Error, ErrorKind};
+
+/// Header with the burst of the client's bucket.
+pub const RATE_LIMIT_LIMIT_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-limit");
+
+/// Header with the requests left in the client's bucket.
+pub const RATE_LIMIT_REMAINING_HEADER: HeaderName =
+	HeaderName::from_static("x-ratelimit-remaining");
+
+/// Header with the seconds until the client's bucket is full again.
+pub const RATE_LIMIT_RESET_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-reset");
+
+/// How often buckets that refilled completely are dropped.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Who a bucket belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Client {
+	Player(PlayerKey),
+	Ip(IpAddr),
+}
+
+/// Burst and refill rate of a kind of client.
+#[derive(Debug, Clone, Copy)]
+struct BucketLimit {
+	burst: u32,
+	refill_per_second: u32,
+}
+
+impl BucketLimit {
+	/// Tokens added per second, at least one so an emptied bucket always refills.
+	fn rate(&self) -> f64 {
+		f64::from(self.refill_per_second.max(1))
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+	tokens: f64,
+	updated_at: Instant,
+}
+
+impl Bucket {
+	fn refill(&mut self, limit: BucketLimit, now: Instant) {
+		let elapsed = now.duration_since(self.updated_at).as_secs_f64();
+		self.tokens = (self.tokens + elapsed * limit.rate()).min(f64::from(limit.burst));
+		self.updated_at = now;
+	}
+}
+
+/// Outcome of taking a token from a client's bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateDecision {
+	pub allowed: bool,
+	/// Burst of the bucket
+	pub limit: u32,
+	/// Whole tokens left in the bucket
+	pub remaining: u32,
+	/// Seconds until the bucket is full again
+	pub reset_seconds: u64,
+	/// Seconds until the next token, 0 if the request was allowed
+	pub retry_after_seconds: u64,
+}
+
+impl RateDecision {
+	/// Adds the `X-RateLimit-*` headers, and `Retry-After` to refused requests.
+	fn apply(&self, headers: &mut HeaderMap) {
+		headers.insert(RATE_LIMIT_LIMIT_HEADER, HeaderValue::from(self.limit));
+		headers.insert(
+			RATE_LIMIT_REMAINING_HEADER,
+			HeaderValue::from(self.remaining),
+		);
+		headers.insert(
+			RATE_LIMIT_RESET_HEADER,
+			HeaderValue::from(self.reset_seconds),
+		);
+		if !self.allowed {
+			headers.insert(
+				header::RETRY_AFTER,
+				HeaderValue::from(self.retry_after_seconds),
+			);
+		}
+	}
+}
+
+/// Token buckets of every client, shared by all requests.
+#[derive(Debug)]
+pub struct RateLimiter {
+	player: BucketLimit,
+	ip: BucketLimit,
+	buckets: Mutex<Buckets>,
+}
+
+#[derive(Debug)]
+struct Buckets {
+	by_client: HashMap<Client, Bucket>,
+	pruned_at: Instant,
+}
+
+impl RateLimiter {
+	pub fn new(settings: &RateLimitSettings) -> Self {
+		Self {
+			player: BucketLimit {
+				burst: settings.player_burst,
+				refill_per_second: settings.player_refill_per_second,
+			},
+			ip: BucketLimit {
+				burst: settings.ip_burst,
+				refill_per_second: settings.ip_refill_per_second,
+			},
+			buckets: Mutex::new(Buckets {
+				by_client: HashMap::new(),
+				pruned_at: Instant::now(),
+			}),
+		}
+	}
+
+	fn limit_of(&self, client: &Client) -> BucketLimit {
+		match client {
+			Client::Player(_) => self.player,
+			Client::Ip(_) => self.ip,
+		}
+	}
+
+	/// Takes a token from the client's bucket.
+	///
+	/// # Returns
+	/// Whether the request may go ahead, `None` if clients of its kind are not limited
+	fn take(&self, client: Client, now: Instant) -> Option<RateDecision> {
+		let limit = self.limit_of(&client);
+		if limit.burst == 0 {
+			return None;
+		}
+
+		let mut buckets = self.buckets.lock().expect("rate limiter poisoned");
+		if now.duration_since(buckets.pruned_at) >= PRUNE_INTERVAL {
+			// A full bucket behaves like a missing one, so only partly used buckets are kept
+			buckets.by_client.retain(|client, bucket| {
+				let limit = self.limit_of(client);
+				bucket.refill(limit, now);
+				bucket.tokens < f64::from(limit.burst)
+			});
+			buckets.pruned_at = now;
+		}
+
+		let bucket = buckets.by_client.entry(client).or_insert(Bucket {
+			tokens: f64::from(limit.burst),
+			updated_at: now,
+		});
+		bucket.refill(limit, now);
+		let allowed = bucket.tokens >= 1.0;
+		if allowed {
+			bucket.tokens -= 1.0;
+		}
+
+		let seconds_until = |tokens: f64| (tokens.max(0.0) / limit.rate()).ceil() as u64;
+		Some(RateDecision {
+			allowed,
+			limit: limit.burst,
+			remaining: bucket.tokens.floor() as u32,
+			reset_seconds: seconds_until(f64::from(limit.burst) - bucket.tokens),
+			retry_after_seconds: if allowed {
+				0
+			} else {
+				seconds_until(1.0 - bucket.tokens).max(1)
+			},
+		})
+	}
+}
+
+/// Limits the request rate of every player and IP, see the module documentation.
+///
+/// Runs after the authentication middleware on protected routes, so authenticated requests
+/// are limited per player wherever they come from.
+pub async fn rate_limit_middleware(
+	State(limiter): State<Arc<RateLimiter>>,
+	req: Request,
+	next: Next,
+) -> Response {
+	let client = match req.extensions().get::<AuthenticatedUser>() {
+		Some(AuthenticatedUser(player)) => Some(Client::Player(player.id)),
+		None => req
+			.extensions()
+			.get::<ConnectInfo<SocketAddr>>()
+			.map(|ConnectInfo(addr)| Client::Ip(addr.ip())),
+	};
+	let Some(decision) = client.and_then(|client| limiter.take(client, Instant::now())) else {
+		return next.run(req).await;
+	};
+
+	if !decision.allowed {
+		warn!(
+			"Rate limited {:?} on {}, retry in {}s",
+			client,
+			req.uri().path(),
+			decision.retry_after_seconds
+		);
+		let mut response = Error::new(ErrorKind::RateLimitedError, "Too many requests, slow down")
+			.with_details(json!({ "retry_after_seconds": decision.retry_after_seconds }))
+			.into_response();
+		decision.apply(response.headers_mut());
+		return response;
+	}
+
+	trace!("{:?} has {} requests left", client, decision.remaining);
+	let mut response = next.run(req).await;
+	decision.apply(response.headers_mut());
+	response
+}
+
+#[cfg(test)]
+mod tests {
+	use uuid::Uuid;
+
+	use super::*;
+
+	fn limiter() -> RateLimiter {
+		RateLimiter::new(&RateLimitSettings {
+			player_burst: 3,
+			player_refill_per_second: 1,
+			ip_burst: 0,
+			ip_refill_per_second: 1,
+		})
+	}
+
+	#[test]
+	fn test_buckets_empty_and_refill() {
+		let limiter = limiter();
+		let player = Client::Player(Uuid::new_v4());
+		let now = Instant::now();
+
+		for remaining in [2, 1, 0] {
+			let decision = limiter.take(player, now).unwrap();
+			assert!(decision.allowed);
+			assert_eq!(decision.remaining, remaining);
+		}
+		let refused = limiter.take(player, now).unwrap();
+		assert!(!refused.allowed);
+		assert_eq!(refused.retry_after_seconds, 1);
+		assert_eq!(refused.reset_seconds, 3);
+
+		// Other players have their own bucket
+		assert!(
+			limiter
+				.take(Client::Player(Uuid::new_v4()), now)
+				.unwrap()
+				.allowed
+		);
+
+		let later = now + Duration::from_millis(1500);
+		let decision = limiter.take(player, later).unwrap();
+		assert!(decision.allowed);
+		assert_eq!(decision.remaining, 0);
+		assert!(!limiter.take(player, later).unwrap().allowed);
+	}
+
+	#[test]
+	fn test_a_burst_of_zero_disables_the_limit() {
+		let limiter = limiter();
+		let ip = Client::Ip(IpAddr::from([127, 0, 0, 1]));
+		assert_eq!(limiter.take(ip, Instant::now()), None);
+	}
+}
@@ -1,8 +1,15 @@
-use axum::http::{HeaderValue, Request};
+use axum::Json;
+use axum::body::Body;
+use axum::http::{HeaderValue, Request, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
 use tower_http::request_id::{MakeRequestId, RequestId};
 use tracing::{instrument, trace};
 use ulid::Ulid;
 
+use crate::domain::error::ErrorBody;
+use crate::net::router::REQUEST_ID_HEADER;
+
 #[derive(Clone, Copy, Default)]
 pub struct MakeRequestUlid;
 
@@ -16,3 +23,22 @@ impl MakeRequestId for MakeRequestUlid {
 		Some(RequestId::new(header_value))
 	}
 }
+
+/// Stamps the request ID into the [`ErrorBody`] of failed requests, so players can quote it
+/// when reporting an error.
+pub async fn error_request_id_middleware(req: Request<Body>, next: Next) -> Response {
+	let request_id = req
+		.headers()
+		.get(REQUEST_ID_HEADER)
+		.and_then(|value| value.to_str().ok())
+		.map(str::to_owned);
+	let mut response = next.run(req).await;
+
+	let Some(mut error) = response.extensions_mut().remove::<ErrorBody>() else {
+		return response;
+	};
+	error.request_id = request_id;
+	let (mut parts, _) = response.into_parts();
+	parts.headers.remove(header::CONTENT_LENGTH);
+	Response::from_parts(parts, Json(error).into_response().into_body())
+}
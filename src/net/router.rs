@@ -9,33 +9,47 @@
 //! - Defining the application's route structure
 //! - Managing timeouts and error handling
 
+use std::sync::Arc;
 use std::time::Duration;
 
 use axum::body::Body;
+use axum::extract::DefaultBodyLimit;
 use axum::http::{HeaderName, Request, StatusCode, Uri};
 use axum::{Router, middleware};
+use serde_json::json;
 use tower::ServiceBuilder;
 use tower_http::catch_panic::CatchPanicLayer as TowerCatchPanicLayer;
 use tower_http::compression::CompressionLayer as TowerCompressionLayer;
-use tower_http::cors::CorsLayer as TowerCorsLayer;
 use tower_http::request_id::{PropagateRequestIdLayer, SetRequestIdLayer};
 use tower_http::timeout::TimeoutLayer;
 use tower_http::trace::TraceLayer as TowerTraceLayer;
 use tracing::{error, info_span};
 
+#[cfg(feature = "graphql")]
+use crate::controllers::routes::graphql_routes;
 use crate::controllers::routes::{
-	auth_routes, game_routes, health_routes, player_routes, protected_auth_routes, user_routes,
+	admin_routes, auth_routes, docs_routes, game_routes, health_routes, metrics_routes,
+	observer_routes, player_routes, protected_auth_routes, user_routes,
 };
 use crate::domain::app_state::AppState;
-use crate::net::auth::auth_middleware;
-use crate::net::request_id::MakeRequestUlid;
+use crate::net::auth::{admin_middleware, auth_middleware, observer_middleware};
+use crate::net::body_limit::body_limit_middleware;
+use crate::net::concurrency::{RouteLimits, concurrency_middleware};
+use crate::net::cors::cors_layer;
+use crate::net::login_limit::{LoginLimiter, login_limit_middleware};
+use crate::net::metrics::metrics_middleware;
+use crate::net::rate_limit::{RateLimiter, rate_limit_middleware};
+use crate::net::request_id::{MakeRequestUlid, error_request_id_middleware};
+use crate::net::sse::sse_routes;
+use crate::net::ws::ws_routes;
+use crate::{Error, ErrorKind};
 
 /// HTTP header name used for request ID tracking across the application.
 /// This header is set and propagated through middleware layers to enable
 /// request tracing and correlation.
-const REQUEST_ID_HEADER: &str = "x-request-id";
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
 
-/// Initialises and configures the application router with all necessary middleware and routes.
+/// Initialises and configures the public application router with all necessary middleware and routes.
 ///
 /// # Arguments
 ///
@@ -45,13 +59,88 @@ const REQUEST_ID_HEADER: &str = "x-request-id";
 ///
 /// Returns a configured Router instance with the following features:
 /// - Request ID generation and propagation
+/// - Request IDs in the bodies of failed requests
 /// - Request tracing and logging
 /// - Panic recovery
-/// - CORS support
-/// - Response compression
+/// - CORS for the configured origins
+/// - Response compression with gzip or brotli
+/// - Request body size limit
 /// - Request timeout
 /// - Authentication middleware for protected routes
+/// - Lockout of usernames and IPs after repeated failed logins
+/// - Request rate limits per player, or per IP on the public routes
+/// - Observer authentication for the read-only observer routes
+/// - Response metrics for the admin overview
+/// - Concurrency limits for expensive routes
+/// - The OpenAPI document and the Swagger UI
+/// - The GraphQL endpoint, when built with the `graphql` feature
+///
+/// The admin API and metrics are not part of this router, see [`init_admin`].
 pub fn init(state: AppState) -> Router {
+	// Shared by every route, the buckets of players and IPs live as long as the router
+	let rate_limits = middleware::from_fn_with_state(
+		Arc::new(RateLimiter::new(&state.settings.rate_limits)),
+		rate_limit_middleware,
+	);
+
+	// Limited after authentication, so players are limited by their ID
+	let protected_routes = Router::new()
+		.merge(protected_auth_routes())
+		.merge(player_routes())
+		.merge(user_routes())
+		.merge(game_routes())
+		.merge(ws_routes())
+		.merge(sse_routes());
+	#[cfg(feature = "graphql")]
+	let protected_routes = protected_routes.merge(graphql_routes());
+	let protected_routes =
+		protected_routes
+			.layer(rate_limits.clone())
+			.layer(middleware::from_fn_with_state(
+				state.clone(),
+				auth_middleware,
+			));
+
+	// Observers authenticate separately and only reach the read-only observer routes
+	let observer_routes = observer_routes().layer(middleware::from_fn_with_state(
+		state.clone(),
+		observer_middleware,
+	));
+
+	// Failed logins are counted per router, so each server instance limits on its own
+	let auth_routes = auth_routes().route_layer(middleware::from_fn_with_state(
+		Arc::new(LoginLimiter::new(&state.settings.login_limits)),
+		login_limit_middleware,
+	));
+
+	let public_routes = Router::new()
+		.merge(health_routes())
+		.merge(docs_routes())
+		.merge(auth_routes)
+		.merge(observer_routes)
+		.layer(rate_limits);
+
+	let routes = Router::new().merge(public_routes).merge(protected_routes);
+	with_middleware(routes, state)
+}
+
+/// Initialises the router of the internal admin listener.
+///
+/// Serves the admin API behind the admin key middleware, and the metrics endpoint. It is
+/// bound to its own interface and port, so operational endpoints stay unreachable through
+/// the public listener even if the admin key check ever regresses.
+pub fn init_admin(state: AppState) -> Router {
+	let admin_routes = admin_routes().layer(middleware::from_fn_with_state(
+		state.clone(),
+		admin_middleware,
+	));
+
+	let routes = Router::new().merge(admin_routes).merge(metrics_routes());
+	with_middleware(routes, state)
+}
+
+/// Wraps `routes` in the middleware stack shared by both listeners.
+fn with_middleware(routes: Router<AppState>, state: AppState) -> Router {
 	let x_request_id = HeaderName::from_static(REQUEST_ID_HEADER);
 
 	let middleware = ServiceBuilder::new()
@@ -83,33 +172,47 @@ pub fn init(state: AppState) -> Router {
 			}),
 		)
 		.layer(TowerCatchPanicLayer::new())
-		.layer(TowerCorsLayer::permissive())
-		.layer(TowerCompressionLayer::new())
+		.layer(cors_layer(&state.settings.cors))
+		// Only the encodings browsers and the client all speak
+		.layer(TowerCompressionLayer::new().no_deflate().no_zstd())
 		.layer(TimeoutLayer::with_status_code(
 			StatusCode::REQUEST_TIMEOUT,
 			Duration::from_secs(10),
 		))
 		.layer(PropagateRequestIdLayer::new(x_request_id));
 
-	let protected_routes = Router::new()
-		.merge(protected_auth_routes())
-		.merge(player_routes())
-		.merge(user_routes())
-		.merge(game_routes())
-		.layer(middleware::from_fn_with_state(
-			state.clone(),
-			auth_middleware,
-		));
-
-	Router::new()
-		.merge(health_routes())
-		.merge(auth_routes())
-		.merge(protected_routes)
+	routes
+		// Before the layers, so unknown routes are answered through them like the others
 		.fallback(fallback)
+		// Inside the timeout, so waiting for a slot counts against the request's time
+		.layer(middleware::from_fn_with_state(
+			RouteLimits::new(&state.settings.concurrency),
+			concurrency_middleware,
+		))
+		// Outside the concurrency limits, so oversized requests never take a slot
+		.layer(middleware::from_fn_with_state(
+			state.settings.server.max_body_bytes,
+			body_limit_middleware,
+		))
+		// The limit above is the only one, whatever the extractor
+		.layer(DefaultBodyLimit::disable())
+		// Inside the request ID layer, so the ID is set by the time errors are stamped
+		.layer(middleware::from_fn(error_request_id_middleware))
 		.layer(middleware)
+		// Outermost, so responses produced by the middleware stack itself are counted too
+		.layer(middleware::from_fn_with_state(
+			state.clone(),
+			metrics_middleware,
+		))
 		.with_state(state)
 }
 
-async fn fallback(uri: Uri) -> (StatusCode, String) {
-	(StatusCode::NOT_FOUND, format!("No route for {uri}"))
+async fn fallback(uri: Uri) -> Error {
+	route_not_found(&uri)
+}
+
+/// The error of requests no route matches, with the path that was asked for.
+pub(crate) fn route_not_found(uri: &Uri) -> Error {
+	Error::new(ErrorKind::RouteNotFoundError, "No route for this path")
+		.with_details(json!({ "path": uri.path() }))
 }
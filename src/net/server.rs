@@ -32,3 +32,19 @@ pub async fn init(state: AppState) -> Result<(TcpListener, Router)> {
 	let router = router::init(state);
 	Ok((listener, router))
 }
+
+/// Initialises the internal admin listener and its router.
+///
+/// The listener is bound to the admin host and port, which must not be reachable through the
+/// public load balancer.
+///
+/// # Errors
+///
+/// Returns an error if binding to the specified address fails
+pub async fn init_admin(state: AppState) -> Result<(TcpListener, Router)> {
+	let settings = &state.settings.server;
+	let addr = SocketAddr::from((settings.admin_host, settings.admin_port));
+	let listener = TcpListener::bind(addr).await?;
+	let router = router::init_admin(state);
+	Ok((listener, router))
+}
@@ -0,0 +1,102 @@
+//! Server-Sent Events endpoint for clients that cannot hold a WebSocket.
+//!
+//! `GET /game/events` streams the player's training and building progress as SSE, reading
+//! from the same [`EventBus`](crate::domain::events::EventBus) as the WebSocket endpoint.
+//! Each message carries the event type as its SSE `event` name and the JSON encoded
+//! [`GameEvent`] as its data, so browsers can use `EventSource.addEventListener` per type.
+
+use std::convert::Infallible;
+
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::routing::get;
+use axum::{Extension, Router, debug_handler};
+use futures_util::Stream;
+use futures_util::stream;
+use tokio::sync::broadcast::Receiver;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, instrument, warn};
+
+use crate::domain::app_state::{AppEvents, AppState};
+use crate::domain::auth::AuthenticatedUser;
+use crate::domain::events::GameEvent;
+use crate::domain::player::PlayerKey;
+
+/// Returns a router with the Server-Sent Events route.
+///
+/// Routes:
+/// - `GET /game/events` - Stream the player's progress updates as Server-Sent Events
+pub fn sse_routes() -> Router<AppState> {
+	Router::new().route("/game/events", get(sse_handler))
+}
+
+/// GET /game/events
+///
+/// Opens an SSE stream of the player's training queue and building upgrade events.
+#[instrument(skip(events, player))]
+#[debug_handler(state = AppState)]
+async fn sse_handler(
+	State(events): State<AppEvents>,
+	player: Extension<AuthenticatedUser>,
+) -> impl IntoResponse {
+	let player_id = player.id;
+	info!("SSE stream opened for player {}", player_id);
+	let rx = events.subscribe();
+	Sse::new(progress_stream(rx, player_id)).keep_alive(KeepAlive::default())
+}
+
+/// Whether an event belongs on the progress stream.
+///
+/// AIDEV-NOTE: The SSE stream is scoped to progress updates. Resource collection and
+/// attack warnings are only pushed over the WebSocket.
+fn is_progress_event(event: &GameEvent) -> bool {
+	matches!(
+		event,
+		GameEvent::TrainingStarted { .. }
+			| GameEvent::TrainingCompleted { .. }
+			| GameEvent::UpgradeCompleted { .. }
+	)
+}
+
+/// Turns the broadcast receiver into a stream of the player's progress events.
+///
+/// The stream ends when the event bus is dropped; the client disconnecting drops the stream.
+fn progress_stream(
+	rx: Receiver<GameEvent>,
+	player_id: PlayerKey,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+	stream::unfold(rx, move |mut rx| async move {
+		loop {
+			match rx.recv().await {
+				Ok(event) if event.player_id() == &player_id && is_progress_event(&event) => {
+					match to_sse_event(&event) {
+						Ok(sse_event) => return Some((Ok(sse_event), rx)),
+						Err(err) => warn!("Failed to encode event {:?}: {}", event, err),
+					}
+				}
+				Ok(_) => {}
+				Err(RecvError::Lagged(skipped)) => {
+					warn!(
+						"SSE stream for player {} lagged, skipped {} events",
+						player_id, skipped
+					);
+				}
+				Err(RecvError::Closed) => {
+					debug!(
+						"Event bus closed, ending SSE stream for player {}",
+						player_id
+					);
+					return None;
+				}
+			}
+		}
+	})
+}
+
+/// Encodes an event as an SSE message named after its type.
+fn to_sse_event(event: &GameEvent) -> serde_json::Result<Event> {
+	let data = serde_json::to_value(event)?;
+	let name = data["type"].as_str().unwrap_or("message").to_owned();
+	Ok(Event::default().event(name).data(data.to_string()))
+}
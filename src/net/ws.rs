@@ -0,0 +1,188 @@
+//! WebSocket endpoint for real-time game state pushes and the chat.
+//!
+//! Clients connect to `/game/ws`. The route sits behind the regular auth middleware, so
+//! the session cookie, JWT cookie or Bearer token used for the REST API authenticate the
+//! upgrade request as well. Once connected, every [`GameEvent`] addressed to the player
+//! is pushed as a JSON text frame, e.g. `{"type":"training_completed",...}`.
+//!
+//! Clients post in the chat by sending `{"type":"chat","channel":"global","body":"..."}`.
+//! Messages of the channels the player is in are pushed as `{"type":"chat_message",...}`,
+//! the player's own included, and a refused post is answered with
+//! `{"type":"chat_error","error":"..."}`. Other incoming frames are ignored.
+
+use axum::extract::State;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{Extension, Router, debug_handler};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, instrument, trace, warn};
+
+use crate::configuration::{ChatSettings, Settings};
+use crate::controllers::game::chat::ChatMessageDto;
+use crate::domain::app_state::{AppChat, AppEvents, AppPool, AppState};
+use crate::domain::auth::AuthenticatedUser;
+use crate::domain::chat::ChatChannel;
+use crate::domain::events::GameEvent;
+use crate::domain::player::PlayerKey;
+use crate::game::chat::chat_operations;
+
+/// A frame sent by the client.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientFrame {
+	/// Posts a message in a chat channel
+	Chat { channel: ChatChannel, body: String },
+}
+
+/// A chat frame sent by the server, next to the [`GameEvent`]s.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatFrame {
+	/// A message posted in one of the player's channels
+	ChatMessage(ChatMessageDto),
+	/// The player's last post was refused
+	ChatError { error: String },
+}
+
+/// State a connection needs besides the socket.
+struct Connection {
+	player_id: PlayerKey,
+	pool: AppPool,
+	chat: AppChat,
+	settings: ChatSettings,
+}
+
+/// Returns a router with the WebSocket route.
+///
+/// Routes:
+/// - `GET /game/ws` - Upgrade to a WebSocket receiving the player's game events and chat
+pub fn ws_routes() -> Router<AppState> {
+	Router::new().route("/game/ws", get(ws_handler))
+}
+
+/// GET /game/ws
+///
+/// Upgrades the connection to a WebSocket that streams the player's game events and chat.
+#[instrument(skip(ws, events, chat, pool, settings, player))]
+#[debug_handler(state = AppState)]
+async fn ws_handler(
+	ws: WebSocketUpgrade,
+	State(events): State<AppEvents>,
+	State(chat): State<AppChat>,
+	State(pool): State<AppPool>,
+	settings: Settings,
+	player: Extension<AuthenticatedUser>,
+) -> impl IntoResponse {
+	let connection = Connection {
+		player_id: player.id,
+		pool,
+		chat,
+		settings: settings.chat,
+	};
+	debug!("Upgrading WebSocket connection for player {}", player.id);
+	ws.on_upgrade(move |socket| push_events(socket, events, connection))
+}
+
+/// Forwards the player's events and chat to the socket until either side goes away.
+async fn push_events(mut socket: WebSocket, events: AppEvents, connection: Connection) {
+	let player_id = connection.player_id;
+	// Subscribe before anything else so nothing published after the upgrade is missed
+	let mut rx = events.subscribe();
+	let mut chat_rx = connection.chat.subscribe();
+	info!("WebSocket connected for player {}", player_id);
+
+	loop {
+		tokio::select! {
+			event = rx.recv() => match event {
+				Ok(event) if event.player_id() == &player_id => {
+					if let Err(err) = send_event(&mut socket, &event).await {
+						debug!("Failed to push event to player {}: {}", player_id, err);
+						break;
+					}
+				}
+				Ok(_) => {}
+				Err(RecvError::Lagged(skipped)) => {
+					warn!("WebSocket for player {} lagged, skipped {} events", player_id, skipped);
+				}
+				Err(RecvError::Closed) => break,
+			},
+			broadcast = chat_rx.recv() => match broadcast {
+				Ok(broadcast) if broadcast.is_for(&player_id) => {
+					let frame = ChatFrame::ChatMessage(broadcast.line.into());
+					if let Err(err) = send_frame(&mut socket, &frame).await {
+						debug!("Failed to push chat message to player {}: {}", player_id, err);
+						break;
+					}
+				}
+				Ok(_) => {}
+				Err(RecvError::Lagged(skipped)) => {
+					warn!("WebSocket for player {} lagged, skipped {} chat messages", player_id, skipped);
+				}
+				Err(RecvError::Closed) => break,
+			},
+			msg = socket.recv() => match msg {
+				Some(Ok(Message::Close(_))) | None => break,
+				Some(Ok(Message::Text(text))) => {
+					let Ok(frame) = serde_json::from_str::<ClientFrame>(&text) else {
+						trace!("Ignoring WebSocket message: {}", text.as_str());
+						continue;
+					};
+					if let Err(err) = handle_frame(&connection, frame) {
+						let (_, message) = err.public_parts();
+						debug!("Refused chat message of player {}: {}", player_id, err);
+						let frame = ChatFrame::ChatError { error: message.to_string() };
+						if let Err(err) = send_frame(&mut socket, &frame).await {
+							debug!("Failed to send chat error to player {}: {}", player_id, err);
+							break;
+						}
+					}
+				}
+				Some(Ok(msg)) => trace!("Ignoring WebSocket message: {:?}", msg),
+				Some(Err(err)) => {
+					debug!("WebSocket error for player {}: {}", player_id, err);
+					break;
+				}
+			},
+		}
+	}
+
+	info!("WebSocket disconnected for player {}", player_id);
+}
+
+/// Handles a frame sent by the client.
+///
+/// Posted messages are published on the chat bus, which pushes them back to this
+/// connection like to every other one in the channel.
+fn handle_frame(connection: &Connection, frame: ClientFrame) -> crate::Result<()> {
+	match frame {
+		ClientFrame::Chat { channel, body } => {
+			let mut conn = connection.pool.get()?;
+			let broadcast = chat_operations::post(
+				&mut conn,
+				&connection.settings,
+				&connection.player_id,
+				channel,
+				&body,
+			)?;
+			connection.chat.publish(broadcast);
+		}
+	}
+	Ok(())
+}
+
+/// Serializes an event and sends it as a text frame.
+async fn send_event(socket: &mut WebSocket, event: &GameEvent) -> crate::Result<()> {
+	send_frame(socket, event).await
+}
+
+/// Serializes a frame and sends it as a text frame.
+async fn send_frame(socket: &mut WebSocket, frame: &impl Serialize) -> crate::Result<()> {
+	let json = serde_json::to_string(frame)?;
+	socket
+		.send(Message::Text(json.into()))
+		.await
+		.map_err(anyhow::Error::from)?;
+	Ok(())
+}
@@ -1,10 +1,34 @@
 // @generated automatically by Diesel CLI.
 
 pub mod sql_types {
+	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+	#[diesel(postgres_type(name = "alliance_role"))]
+	pub struct AllianceRole;
+
+	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+	#[diesel(postgres_type(name = "caravan_status"))]
+	pub struct CaravanStatus;
+
+	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+	#[diesel(postgres_type(name = "chat_channel"))]
+	pub struct ChatChannel;
+
+	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+	#[diesel(postgres_type(name = "construction_status"))]
+	pub struct ConstructionStatus;
+
+	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+	#[diesel(postgres_type(name = "event_objective_kind"))]
+	pub struct EventObjectiveKind;
+
 	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
 	#[diesel(postgres_type(name = "faction_code"))]
 	pub struct FactionCode;
 
+	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+	#[diesel(postgres_type(name = "hero_specialty"))]
+	pub struct HeroSpecialty;
+
 	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
 	#[diesel(postgres_type(name = "job_status"))]
 	pub struct JobStatus;
@@ -13,10 +37,26 @@ pub mod sql_types {
 	#[diesel(postgres_type(name = "job_type"))]
 	pub struct JobType;
 
+	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+	#[diesel(postgres_type(name = "leaderboard_category"))]
+	pub struct LeaderboardCategory;
+
 	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
 	#[diesel(postgres_type(name = "magnitude_kind"))]
 	pub struct MagnitudeKind;
 
+	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+	#[diesel(postgres_type(name = "market_order_side"))]
+	pub struct MarketOrderSide;
+
+	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+	#[diesel(postgres_type(name = "market_order_status"))]
+	pub struct MarketOrderStatus;
+
+	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+	#[diesel(postgres_type(name = "message_kind"))]
+	pub struct MessageKind;
+
 	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
 	#[diesel(postgres_type(name = "modifier_action_type"))]
 	pub struct ModifierActionType;
@@ -29,6 +69,26 @@ pub mod sql_types {
 	#[diesel(postgres_type(name = "modifier_target"))]
 	pub struct ModifierTarget;
 
+	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+	#[diesel(postgres_type(name = "planned_action_kind"))]
+	pub struct PlannedActionKind;
+
+	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+	#[diesel(postgres_type(name = "planned_action_status"))]
+	pub struct PlannedActionStatus;
+
+	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+	#[diesel(postgres_type(name = "player_role"))]
+	pub struct PlayerRole;
+
+	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+	#[diesel(postgres_type(name = "quest_objective"))]
+	pub struct QuestObjective;
+
+	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+	#[diesel(postgres_type(name = "relationship_status"))]
+	pub struct RelationshipStatus;
+
 	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
 	#[diesel(postgres_type(name = "resource_type"))]
 	pub struct ResourceType;
@@ -37,6 +97,10 @@ pub mod sql_types {
 	#[diesel(postgres_type(name = "stacking_behaviour"))]
 	pub struct StackingBehaviour;
 
+	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+	#[diesel(postgres_type(name = "training_mode"))]
+	pub struct TrainingMode;
+
 	#[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
 	#[diesel(postgres_type(name = "training_status"))]
 	pub struct TrainingStatus;
@@ -60,6 +124,135 @@ diesel::table! {
 		source_id -> Nullable<Uuid>,
 		created_at -> Timestamptz,
 		updated_at -> Timestamptz,
+		player_building_id -> Nullable<Uuid>,
+	}
+}
+
+diesel::table! {
+	alliance (id) {
+		id -> Uuid,
+		name -> Text,
+		tag -> Text,
+		food -> Int8,
+		wood -> Int8,
+		stone -> Int8,
+		gold -> Int8,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	alliance_donation (id) {
+		id -> Uuid,
+		alliance_id -> Uuid,
+		player_id -> Nullable<Uuid>,
+		food -> Int8,
+		wood -> Int8,
+		stone -> Int8,
+		gold -> Int8,
+		created_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	alliance_invite (id) {
+		id -> Uuid,
+		alliance_id -> Uuid,
+		player_id -> Uuid,
+		invited_by -> Nullable<Uuid>,
+		created_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	use diesel::sql_types::*;
+	use super::sql_types::AllianceRole;
+
+	alliance_member (player_id) {
+		player_id -> Uuid,
+		alliance_id -> Uuid,
+		role -> AllianceRole,
+		joined_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	api_key (id) {
+		id -> Uuid,
+		player_id -> Uuid,
+		name -> Text,
+		key_hash -> Text,
+		key_prefix -> Text,
+		scope -> Text,
+		last_used_at -> Nullable<Timestamptz>,
+		expires_at -> Nullable<Timestamptz>,
+		revoked_at -> Nullable<Timestamptz>,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	army_preset (id) {
+		id -> Uuid,
+		player_id -> Uuid,
+		name -> Text,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	army_preset_unit (preset_id, unit_id) {
+		preset_id -> Uuid,
+		unit_id -> Uuid,
+		quantity -> Int8,
+	}
+}
+
+diesel::table! {
+	audit_log (id) {
+		id -> Uuid,
+		action -> Text,
+		subject -> Nullable<Text>,
+		details -> Jsonb,
+		operator -> Nullable<Text>,
+		request_id -> Nullable<Text>,
+		created_at -> Timestamptz,
+		player_id -> Nullable<Uuid>,
+	}
+}
+
+diesel::table! {
+	backfill (name) {
+		name -> Text,
+		cursor -> Nullable<Uuid>,
+		processed -> Int8,
+		job_id -> Nullable<Uuid>,
+		started_at -> Nullable<Timestamptz>,
+		completed_at -> Nullable<Timestamptz>,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	battle_report (id) {
+		id -> Uuid,
+		attacker_id -> Uuid,
+		defender_id -> Uuid,
+		winner_id -> Nullable<Uuid>,
+		attacker_losses -> Jsonb,
+		defender_losses -> Jsonb,
+		loot_food -> Int8,
+		loot_wood -> Int8,
+		loot_stone -> Int8,
+		loot_gold -> Int8,
+		modifiers -> Jsonb,
+		fought_at -> Timestamptz,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
 	}
 }
 
@@ -144,6 +337,89 @@ diesel::table! {
 	}
 }
 
+diesel::table! {
+	building_upgrade (id) {
+		id -> Uuid,
+		player_id -> Uuid,
+		player_building_id -> Uuid,
+		building_id -> Int4,
+		level -> Int4,
+		food -> Int8,
+		wood -> Int8,
+		stone -> Int8,
+		gold -> Int8,
+		completed_at -> Nullable<Timestamptz>,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+		cancelled_at -> Nullable<Timestamptz>,
+	}
+}
+
+diesel::table! {
+	use diesel::sql_types::*;
+	use super::sql_types::CaravanStatus;
+
+	caravan (id) {
+		id -> Uuid,
+		sender_id -> Uuid,
+		receiver_id -> Uuid,
+		food -> Int8,
+		wood -> Int8,
+		stone -> Int8,
+		gold -> Int8,
+		status -> CaravanStatus,
+		job_id -> Nullable<Uuid>,
+		arrives_at -> Timestamptz,
+		delivered_at -> Nullable<Timestamptz>,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	use diesel::sql_types::*;
+	use super::sql_types::ChatChannel;
+
+	chat_message (id) {
+		id -> Uuid,
+		channel -> ChatChannel,
+		alliance_id -> Nullable<Uuid>,
+		player_id -> Nullable<Uuid>,
+		body -> Text,
+		created_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	chat_mute (player_id) {
+		player_id -> Uuid,
+		muted_until -> Timestamptz,
+		reason -> Nullable<Text>,
+		muted_by -> Nullable<Uuid>,
+		created_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	use diesel::sql_types::*;
+	use super::sql_types::ConstructionStatus;
+
+	construction_queue (id) {
+		id -> Uuid,
+		player_id -> Uuid,
+		player_building_id -> Uuid,
+		upgrade_id -> Uuid,
+		level -> Int4,
+		status -> ConstructionStatus,
+		starts_at -> Timestamptz,
+		completes_at -> Timestamptz,
+		completed_at -> Nullable<Timestamptz>,
+		job_id -> Nullable<Uuid>,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
 diesel::table! {
 	use diesel::sql_types::*;
 	use super::sql_types::FactionCode;
@@ -156,6 +432,134 @@ diesel::table! {
 	}
 }
 
+diesel::table! {
+	use diesel::sql_types::*;
+	use super::sql_types::FactionCode;
+
+	faction_change (id) {
+		id -> Uuid,
+		player_id -> Uuid,
+		from_faction -> FactionCode,
+		to_faction -> FactionCode,
+		changed_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	use diesel::sql_types::*;
+	use super::sql_types::FactionCode;
+
+	faction_standing (faction) {
+		faction -> FactionCode,
+		players -> Int8,
+		power -> Int8,
+		war_score -> Int8,
+		rank -> Int4,
+		computed_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	use diesel::sql_types::*;
+	use super::sql_types::ResourceType;
+
+	game_event (id) {
+		id -> Uuid,
+		name -> Text,
+		description -> Text,
+		currency_name -> Text,
+		starts_at -> Timestamptz,
+		ends_at -> Timestamptz,
+		leftover_resource -> ResourceType,
+		leftover_rate -> Int4,
+		closed_at -> Nullable<Timestamptz>,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	use diesel::sql_types::*;
+	use super::sql_types::EventObjectiveKind;
+
+	game_event_objective (id) {
+		id -> Uuid,
+		event_id -> Uuid,
+		kind -> EventObjectiveKind,
+		description -> Text,
+		target -> Int8,
+		reward -> Int8,
+		max_completions -> Nullable<Int4>,
+		created_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	game_event_offer (id) {
+		id -> Uuid,
+		event_id -> Uuid,
+		price -> Int8,
+		item_id -> Nullable<Uuid>,
+		unit_id -> Nullable<Uuid>,
+		quantity -> Int8,
+		created_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	use diesel::sql_types::*;
+	use super::sql_types::HeroSpecialty;
+
+	hero (id) {
+		id -> Uuid,
+		name -> Text,
+		description -> Text,
+		specialty -> HeroSpecialty,
+		recruit_gold -> Int8,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	hero_level (hero_id, level) {
+		hero_id -> Uuid,
+		level -> Int4,
+		xp_required -> Int8,
+		modifier_id -> Uuid,
+	}
+}
+
+diesel::table! {
+	intel_report (id) {
+		id -> Uuid,
+		attacker_id -> Uuid,
+		defender_id -> Uuid,
+		food -> Int8,
+		wood -> Int8,
+		stone -> Int8,
+		gold -> Int8,
+		army -> Jsonb,
+		noise_percent -> Int4,
+		spotted -> Bool,
+		scouted_at -> Timestamptz,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	item (id) {
+		id -> Uuid,
+		name -> Text,
+		description -> Text,
+		modifier_id -> Uuid,
+		duration_seconds -> Int4,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
 diesel::table! {
 	use diesel::sql_types::*;
 	use super::sql_types::JobType;
@@ -176,6 +580,95 @@ diesel::table! {
 		locked_by -> Nullable<Text>,
 		created_at -> Timestamptz,
 		updated_at -> Timestamptz,
+		error_history -> Jsonb,
+		result -> Nullable<Jsonb>,
+	}
+}
+
+diesel::table! {
+	use diesel::sql_types::*;
+	use super::sql_types::JobType;
+
+	job_dead_letter (id) {
+		id -> Uuid,
+		job_type -> JobType,
+		payload -> Jsonb,
+		priority -> Int4,
+		retries -> Int4,
+		max_retries -> Int4,
+		timeout_seconds -> Int4,
+		last_error -> Nullable<Text>,
+		error_history -> Jsonb,
+		enqueued_at -> Timestamptz,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	use diesel::sql_types::*;
+	use super::sql_types::LeaderboardCategory;
+
+	leaderboard_entry (category, subject_id) {
+		category -> LeaderboardCategory,
+		subject_id -> Uuid,
+		rank -> Int4,
+		score -> Int8,
+		snapshot_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	use diesel::sql_types::*;
+	use super::sql_types::MarketOrderSide;
+	use super::sql_types::ResourceType;
+	use super::sql_types::MarketOrderStatus;
+
+	market_order (id) {
+		id -> Uuid,
+		player_id -> Uuid,
+		side -> MarketOrderSide,
+		resource -> ResourceType,
+		quantity -> Int8,
+		remaining -> Int8,
+		price -> Int8,
+		status -> MarketOrderStatus,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	use diesel::sql_types::*;
+	use super::sql_types::ResourceType;
+
+	market_trade (id) {
+		id -> Uuid,
+		resource -> ResourceType,
+		buy_order_id -> Uuid,
+		sell_order_id -> Uuid,
+		buyer_id -> Uuid,
+		seller_id -> Uuid,
+		quantity -> Int8,
+		price -> Int8,
+		created_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	use diesel::sql_types::*;
+	use super::sql_types::MessageKind;
+
+	message (id) {
+		id -> Uuid,
+		recipient_id -> Uuid,
+		sender_id -> Nullable<Uuid>,
+		kind -> MessageKind,
+		subject -> Text,
+		body -> Text,
+		details -> Jsonb,
+		read_at -> Nullable<Timestamptz>,
+		created_at -> Timestamptz,
 	}
 }
 
@@ -197,6 +690,7 @@ diesel::table! {
 		reason -> Nullable<Text>,
 		created_at -> Timestamptz,
 		updated_at -> Timestamptz,
+		operator -> Nullable<Text>,
 	}
 }
 
@@ -222,9 +716,52 @@ diesel::table! {
 	}
 }
 
+diesel::table! {
+	observer (id) {
+		id -> Uuid,
+		name -> Text,
+		token_hash -> Text,
+		scopes -> Array<Text>,
+		created_by -> Nullable<Text>,
+		expires_at -> Nullable<Timestamptz>,
+		revoked_at -> Nullable<Timestamptz>,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	observer_player (observer_id, player_id) {
+		observer_id -> Uuid,
+		player_id -> Uuid,
+		created_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	use diesel::sql_types::*;
+	use super::sql_types::PlannedActionKind;
+	use super::sql_types::PlannedActionStatus;
+
+	planned_action (id) {
+		id -> Uuid,
+		player_id -> Uuid,
+		action -> PlannedActionKind,
+		building_id -> Nullable<Int4>,
+		player_building_id -> Nullable<Uuid>,
+		status -> PlannedActionStatus,
+		last_error -> Nullable<Text>,
+		executed_at -> Nullable<Timestamptz>,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+		settlement_id -> Nullable<Uuid>,
+	}
+}
+
 diesel::table! {
 	use diesel::sql_types::*;
 	use super::sql_types::FactionCode;
+	use super::sql_types::PlayerRole;
 
 	player (id) {
 		id -> Uuid,
@@ -235,6 +772,12 @@ diesel::table! {
 		faction -> FactionCode,
 		created_at -> Timestamptz,
 		updated_at -> Timestamptz,
+		protected_until -> Nullable<Timestamptz>,
+		role -> PlayerRole,
+		deletion_scheduled_for -> Nullable<Timestamptz>,
+		anonymized_at -> Nullable<Timestamptz>,
+		email_verified_at -> Nullable<Timestamptz>,
+		password_changed_at -> Nullable<Timestamptz>,
 	}
 }
 
@@ -248,16 +791,132 @@ diesel::table! {
 		gold -> Int8,
 		created_at -> Timestamptz,
 		updated_at -> Timestamptz,
+		food_remainder -> Numeric,
+		wood_remainder -> Numeric,
+		stone_remainder -> Numeric,
+		gold_remainder -> Numeric,
+		settlement_id -> Uuid,
 	}
 }
 
 diesel::table! {
+	use diesel::sql_types::*;
+	use super::sql_types::TrainingMode;
+
 	player_building (id) {
 		id -> Uuid,
 		player_id -> Uuid,
 		building_id -> Int4,
 		level -> Int4,
-		upgrade_finishes_at -> Nullable<Text>,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+		upgrade_finishes_at -> Nullable<Timestamptz>,
+		settlement_id -> Uuid,
+		training_mode -> TrainingMode,
+	}
+}
+
+diesel::table! {
+	player_event_currency (player_id, event_id) {
+		player_id -> Uuid,
+		event_id -> Uuid,
+		balance -> Int8,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	player_event_objective (player_id, objective_id) {
+		player_id -> Uuid,
+		objective_id -> Uuid,
+		progress -> Int8,
+		completions -> Int4,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	player_hero (id) {
+		id -> Uuid,
+		player_id -> Uuid,
+		hero_id -> Uuid,
+		level -> Int4,
+		xp -> Int8,
+		player_building_id -> Nullable<Uuid>,
+		leads_army -> Bool,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	player_identity (id) {
+		id -> Uuid,
+		player_id -> Uuid,
+		provider -> Text,
+		subject -> Text,
+		#[max_length = 254]
+		email -> Nullable<Varchar>,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	player_item (id) {
+		id -> Uuid,
+		player_id -> Uuid,
+		item_id -> Uuid,
+		quantity -> Int8,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	player_legacy (player_id) {
+		player_id -> Uuid,
+		legacy_points -> Int8,
+		seasons -> Int4,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	player_privacy (player_id) {
+		player_id -> Uuid,
+		hide_online_status -> Bool,
+		hide_stats -> Bool,
+		hide_alliance -> Bool,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+		allow_observers -> Bool,
+	}
+}
+
+diesel::table! {
+	player_quest (player_id, quest_id) {
+		player_id -> Uuid,
+		quest_id -> Text,
+		progress -> Int8,
+		claimed_at -> Nullable<Timestamptz>,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	use diesel::sql_types::*;
+	use super::sql_types::RelationshipStatus;
+
+	player_relationship (id) {
+		id -> Uuid,
+		player_id -> Uuid,
+		other_id -> Uuid,
+		status -> RelationshipStatus,
 		created_at -> Timestamptz,
 		updated_at -> Timestamptz,
 	}
@@ -279,6 +938,18 @@ diesel::table! {
 		collected_at -> Timestamptz,
 		created_at -> Timestamptz,
 		updated_at -> Timestamptz,
+		settlement_id -> Uuid,
+	}
+}
+
+diesel::table! {
+	player_score (player_id) {
+		player_id -> Uuid,
+		building_points -> Int8,
+		unit_points -> Int8,
+		tech_points -> Int8,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
 	}
 }
 
@@ -301,6 +972,120 @@ diesel::table! {
 	}
 }
 
+diesel::table! {
+	use diesel::sql_types::*;
+	use super::sql_types::QuestObjective;
+
+	quest (id) {
+		id -> Text,
+		name -> Text,
+		description -> Text,
+		position -> Int4,
+		requires -> Array<Text>,
+		objective -> QuestObjective,
+		target -> Text,
+		amount -> Int8,
+		reward_food -> Int8,
+		reward_wood -> Int8,
+		reward_stone -> Int8,
+		reward_gold -> Int8,
+		reward_item_id -> Nullable<Uuid>,
+		reward_item_quantity -> Int8,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	use diesel::sql_types::*;
+	use super::sql_types::JobType;
+
+	recurring_job (id) {
+		id -> Uuid,
+		name -> Text,
+		cron_expression -> Text,
+		job_type -> JobType,
+		payload -> Jsonb,
+		priority -> Int4,
+		next_job_id -> Nullable<Uuid>,
+		next_run_at -> Nullable<Timestamptz>,
+		last_run_at -> Nullable<Timestamptz>,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	scout_mission (id) {
+		id -> Uuid,
+		attacker_id -> Uuid,
+		defender_id -> Uuid,
+		scouts -> Int8,
+		job_id -> Nullable<Uuid>,
+		report_id -> Nullable<Uuid>,
+		arrives_at -> Timestamptz,
+		completed_at -> Nullable<Timestamptz>,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	season (id) {
+		id -> Uuid,
+		number -> Int4,
+		name -> Text,
+		starts_at -> Timestamptz,
+		ends_at -> Timestamptz,
+		ended_at -> Nullable<Timestamptz>,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	use diesel::sql_types::*;
+	use super::sql_types::LeaderboardCategory;
+
+	season_standing (season_id, category, subject_id) {
+		season_id -> Uuid,
+		category -> LeaderboardCategory,
+		subject_id -> Uuid,
+		name -> Text,
+		rank -> Int4,
+		score -> Int8,
+		legacy_points -> Int8,
+	}
+}
+
+diesel::table! {
+	settlement (id) {
+		id -> Uuid,
+		player_id -> Uuid,
+		name -> Text,
+		is_capital -> Bool,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	simulated_player (player_id) {
+		player_id -> Uuid,
+		created_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	table_stats (id) {
+		id -> Uuid,
+		table_name -> Text,
+		row_count -> Int8,
+		total_bytes -> Int8,
+		captured_at -> Timestamptz,
+	}
+}
+
 diesel::table! {
 	use diesel::sql_types::*;
 	use super::sql_types::TrainingStatus;
@@ -317,6 +1102,9 @@ diesel::table! {
 		job_id -> Nullable<Uuid>,
 		created_at -> Timestamptz,
 		updated_at -> Timestamptz,
+		completes_at -> Timestamptz,
+		training_modifier -> Numeric,
+		seconds_per_unit -> Int8,
 	}
 }
 
@@ -334,6 +1122,9 @@ diesel::table! {
 		description -> Nullable<Text>,
 		created_at -> Timestamptz,
 		updated_at -> Timestamptz,
+		food_upkeep -> Int8,
+		population -> Int8,
+		tier -> Int4,
 	}
 }
 
@@ -351,48 +1142,194 @@ diesel::table! {
 	}
 }
 
+diesel::table! {
+	unit_upgrade (unit_id) {
+		unit_id -> Uuid,
+		upgraded_unit_id -> Uuid,
+		blacksmith_level -> Int4,
+		research_level -> Int4,
+		req_food -> Int8,
+		req_wood -> Int8,
+		req_stone -> Int8,
+		req_gold -> Int8,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
+diesel::table! {
+	world_reset (id) {
+		id -> Uuid,
+		token_hash -> Text,
+		requested_by -> Nullable<Text>,
+		expires_at -> Timestamptz,
+		confirmed_by -> Nullable<Text>,
+		confirmed_at -> Nullable<Timestamptz>,
+		completed_steps -> Int4,
+		completed_at -> Nullable<Timestamptz>,
+		created_at -> Timestamptz,
+		updated_at -> Timestamptz,
+	}
+}
+
 diesel::joinable!(active_modifiers -> modifiers (modifier_id));
 diesel::joinable!(active_modifiers -> player (player_id));
+diesel::joinable!(active_modifiers -> player_building (player_building_id));
+diesel::joinable!(alliance_donation -> alliance (alliance_id));
+diesel::joinable!(alliance_donation -> player (player_id));
+diesel::joinable!(alliance_invite -> alliance (alliance_id));
+diesel::joinable!(alliance_member -> alliance (alliance_id));
+diesel::joinable!(alliance_member -> player (player_id));
+diesel::joinable!(api_key -> player (player_id));
+diesel::joinable!(army_preset -> player (player_id));
+diesel::joinable!(army_preset_unit -> army_preset (preset_id));
+diesel::joinable!(army_preset_unit -> unit (unit_id));
+diesel::joinable!(audit_log -> player (player_id));
+diesel::joinable!(backfill -> job (job_id));
 diesel::joinable!(building -> faction (faction));
 diesel::joinable!(building_level -> building (building_id));
 diesel::joinable!(building_requirement -> building (required_building_id));
 diesel::joinable!(building_requirement -> building_level (building_level_id));
 diesel::joinable!(building_resource -> building (building_id));
 diesel::joinable!(building_unit_type -> building (building_id));
+diesel::joinable!(building_upgrade -> building (building_id));
+diesel::joinable!(building_upgrade -> player (player_id));
+diesel::joinable!(building_upgrade -> player_building (player_building_id));
+diesel::joinable!(caravan -> job (job_id));
+diesel::joinable!(chat_message -> alliance (alliance_id));
+diesel::joinable!(chat_message -> player (player_id));
+diesel::joinable!(construction_queue -> building_upgrade (upgrade_id));
+diesel::joinable!(construction_queue -> job (job_id));
+diesel::joinable!(construction_queue -> player (player_id));
+diesel::joinable!(construction_queue -> player_building (player_building_id));
+diesel::joinable!(faction_change -> player (player_id));
+diesel::joinable!(faction_standing -> faction (faction));
+diesel::joinable!(game_event_objective -> game_event (event_id));
+diesel::joinable!(game_event_offer -> game_event (event_id));
+diesel::joinable!(game_event_offer -> item (item_id));
+diesel::joinable!(game_event_offer -> unit (unit_id));
+diesel::joinable!(hero_level -> hero (hero_id));
+diesel::joinable!(hero_level -> modifiers (modifier_id));
+diesel::joinable!(item -> modifiers (modifier_id));
+diesel::joinable!(market_order -> player (player_id));
 diesel::joinable!(modifier_history -> modifiers (modifier_id));
 diesel::joinable!(modifier_history -> player (player_id));
+diesel::joinable!(observer_player -> observer (observer_id));
+diesel::joinable!(observer_player -> player (player_id));
+diesel::joinable!(planned_action -> building (building_id));
+diesel::joinable!(planned_action -> player (player_id));
+diesel::joinable!(planned_action -> player_building (player_building_id));
+diesel::joinable!(planned_action -> settlement (settlement_id));
 diesel::joinable!(player -> faction (faction));
 diesel::joinable!(player_accumulator -> player (player_id));
+diesel::joinable!(player_accumulator -> settlement (settlement_id));
 diesel::joinable!(player_building -> building (building_id));
 diesel::joinable!(player_building -> player (player_id));
+diesel::joinable!(player_building -> settlement (settlement_id));
+diesel::joinable!(player_event_currency -> game_event (event_id));
+diesel::joinable!(player_event_currency -> player (player_id));
+diesel::joinable!(player_event_objective -> game_event_objective (objective_id));
+diesel::joinable!(player_event_objective -> player (player_id));
+diesel::joinable!(player_hero -> hero (hero_id));
+diesel::joinable!(player_hero -> player (player_id));
+diesel::joinable!(player_hero -> player_building (player_building_id));
+diesel::joinable!(player_identity -> player (player_id));
+diesel::joinable!(player_item -> item (item_id));
+diesel::joinable!(player_item -> player (player_id));
+diesel::joinable!(player_legacy -> player (player_id));
+diesel::joinable!(player_privacy -> player (player_id));
+diesel::joinable!(player_quest -> player (player_id));
+diesel::joinable!(player_quest -> quest (quest_id));
 diesel::joinable!(player_resource -> player (player_id));
+diesel::joinable!(player_resource -> settlement (settlement_id));
+diesel::joinable!(player_score -> player (player_id));
 diesel::joinable!(player_session -> player (player_id));
 diesel::joinable!(player_unit -> player (player_id));
 diesel::joinable!(player_unit -> unit (unit_id));
+diesel::joinable!(quest -> item (reward_item_id));
+diesel::joinable!(recurring_job -> job (next_job_id));
+diesel::joinable!(scout_mission -> intel_report (report_id));
+diesel::joinable!(scout_mission -> job (job_id));
+diesel::joinable!(season_standing -> season (season_id));
+diesel::joinable!(settlement -> player (player_id));
+diesel::joinable!(simulated_player -> player (player_id));
 diesel::joinable!(training_queue -> job (job_id));
 diesel::joinable!(training_queue -> player (player_id));
 diesel::joinable!(training_queue -> player_building (building_id));
 diesel::joinable!(training_queue -> unit (unit_id));
 diesel::joinable!(unit_cost -> unit (unit_id));
+diesel::joinable!(unit_upgrade -> unit (unit_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
 	active_modifiers,
+	alliance,
+	alliance_donation,
+	alliance_invite,
+	alliance_member,
+	api_key,
+	army_preset,
+	army_preset_unit,
+	audit_log,
+	backfill,
+	battle_report,
 	building,
 	building_level,
 	building_requirement,
 	building_resource,
 	building_unit_type,
+	building_upgrade,
+	caravan,
+	chat_message,
+	chat_mute,
+	construction_queue,
 	faction,
+	faction_change,
+	faction_standing,
+	game_event,
+	game_event_objective,
+	game_event_offer,
+	hero,
+	hero_level,
+	intel_report,
+	item,
 	job,
+	job_dead_letter,
+	leaderboard_entry,
+	market_order,
+	market_trade,
+	message,
 	modifier_history,
 	modifiers,
+	observer,
+	observer_player,
+	planned_action,
 	player,
 	player_accumulator,
 	player_building,
+	player_event_currency,
+	player_event_objective,
+	player_hero,
+	player_identity,
+	player_item,
+	player_legacy,
+	player_privacy,
+	player_quest,
+	player_relationship,
 	player_resource,
+	player_score,
 	player_session,
 	player_unit,
+	quest,
+	recurring_job,
+	scout_mission,
+	season,
+	season_standing,
+	settlement,
+	simulated_player,
+	table_stats,
 	training_queue,
 	unit,
 	unit_cost,
+	unit_upgrade,
+	world_reset,
 );
@@ -11,19 +11,47 @@
 //! and ensures proper initialisation of all required components including
 //! database connections, background tasks, and HTTP services.
 
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::thread::available_parallelism;
 
+use chrono::Utc;
 use tokio::signal;
 use tokio_util::sync::CancellationToken;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
 use crate::Result;
 use crate::configuration::{ServerSettings, Settings};
+use crate::db::backfills::backfill_processor::BackfillProcessor;
+use crate::db::migrations;
 use crate::domain::app_state::{App, AppPool, AppState};
+use crate::domain::jobs::JobType;
+use crate::game::account_deletion::deletion_processor::AccountDeletionProcessor;
+use crate::game::buildings::building_processor::BuildingProcessor;
+use crate::game::combat::combat_operations;
+use crate::game::combat::combat_processor::CombatProcessor;
+use crate::game::combat::espionage_processor::EspionageProcessor;
+use crate::game::factions::standing_operations;
+use crate::game::factions::standings_processor::FactionProcessor;
+use crate::game::leaderboard::leaderboard_processor::LeaderboardProcessor;
+use crate::game::leaderboard::{leaderboard_operations, score_listener};
+use crate::game::limited_events::event_listener;
+use crate::game::limited_events::event_processor::LimitedEventProcessor;
 use crate::game::modifiers::modifier_processor::ModifierProcessor;
+use crate::game::modifiers::modifier_scheduler;
+use crate::game::quests::quest_listener;
+use crate::game::resources::production_processor::ProductionProcessor;
 use crate::game::resources::resource_processor::ResourceProcessor;
+use crate::game::resources::resource_scheduler;
+use crate::game::seasons::season_processor::SeasonProcessor;
+#[cfg(feature = "simulation")]
+use crate::game::simulation::simulation_operations;
+#[cfg(feature = "simulation")]
+use crate::game::simulation::simulation_processor::SimulationProcessor;
+use crate::game::table_stats::stats_operations;
+use crate::game::table_stats::stats_processor::TableStatsProcessor;
 use crate::game::units::training_processor::TrainingProcessor;
+use crate::game::world::reset_processor::WorldResetProcessor;
 use crate::job_queue::worker_pool::WorkerPool;
 use crate::net::server;
 
@@ -32,7 +60,8 @@ use crate::net::server;
 /// This function performs the following actions:
 /// - Initializes the server listener and router from the provided server configuration.
 /// - Sets the application state, including the database connection pool.
-/// - Logs the server's listening address.
+/// - Initializes the internal admin listener, see [`server::init_admin`].
+/// - Logs the servers' listening addresses.
 /// - Starts serving requests with Axum, ensuring graceful shutdown on receiving termination signals.
 ///
 /// # Arguments
@@ -54,15 +83,23 @@ pub async fn launch(config: Settings, pool: AppPool) -> Result<()> {
 	let monitor = subroutines.monitor();
 	info!("Subroutines monitor started");
 
+	let (admin_listener, admin_router) = server::init_admin(app_state.clone()).await?;
+	info!("Admin API listening on {}", admin_listener.local_addr()?);
 	let (listener, router) = server::init(app_state).await?;
 	info!("Listening on {}", listener.local_addr()?);
 
-	let server = axum::serve(listener, router.into_make_service())
-		.with_graceful_shutdown(shutdown_signal(token));
+	let admin_server = axum::serve(admin_listener, admin_router.into_make_service())
+		.with_graceful_shutdown(token.clone().cancelled_owned());
+	// Client addresses are needed to limit failed logins per IP
+	let server = axum::serve(
+		listener,
+		router.into_make_service_with_connect_info::<SocketAddr>(),
+	)
+	.with_graceful_shutdown(shutdown_signal(token));
 	info!("Empire server started!");
 
-	let (srv, _) = tokio::join!(server, monitor);
-	srv.map_err(|err| {
+	let (srv, admin_srv, _) = tokio::join!(server, admin_server, monitor);
+	srv.and(admin_srv).map_err(|err| {
 		warn!("Server error while shutting down: {:#?}", err);
 		err.into()
 	})
@@ -87,9 +124,19 @@ pub async fn launch(config: Settings, pool: AppPool) -> Result<()> {
 /// The function performs the following:
 /// - Creates a new WorkerPool with the provided job queue and cancellation token
 /// - Calculates the number of workers based on available CPU cores (half of available cores)
-/// - Initializes ModifierProcessor workers for handling game modifiers
-/// - Initializes ResourceProcessor workers for handling resource calculations
-/// - Adds the modifier workers to the pool
+/// - Registers the processors for every job type, see [`register_processors`]
+/// - Schedules the first battle report pruning
+/// - Registers the recurring resource production ticks, see [`resource_scheduler`]
+/// - Registers the recurring modifier expiration, see [`modifier_scheduler`]
+/// - Registers the recurring capture of the table statistics, see [`stats_operations`]
+/// - Registers the recurring snapshot of the leaderboards, see [`leaderboard_operations`]
+/// - Registers the recurring aggregation of the faction standings, see [`standing_operations`]
+/// - Spawns the listener counting game events towards limited events, see [`event_listener`]
+/// - Spawns the listener keeping the scores of the players up to date, see [`score_listener`]
+/// - Spawns the listener counting game events towards the players' quests, see [`quest_listener`]
+/// - Spawns the NPCs and registers their turns when built with the `simulation` feature
+/// - Enqueues the next run of recurring jobs that have none, see [`crate::job_queue::JobQueue::sync_recurring`]
+/// - Enqueues the backfills that did not complete, see [`migrations::schedule_backfills`]
 ///
 /// The worker count is automatically adjusted based on the system's available parallelism
 /// to ensure optimal resource utilization.
@@ -101,16 +148,119 @@ fn start_subroutines(
 	let mut worker_pool = WorkerPool::new(Arc::clone(&app_state.job_queue), token.clone());
 
 	let default_workers = settings.workers.unwrap_or(available_parallelism()?.get()) / 2;
-	let mod_workers = ModifierProcessor::initialise_n(default_workers, app_state);
-	let res_workers = ResourceProcessor::initialise_n(default_workers, app_state);
-	let train_workers = TrainingProcessor::initialise_n(default_workers, app_state);
-	worker_pool.add_workers(mod_workers);
-	worker_pool.add_workers(res_workers);
-	worker_pool.add_workers(train_workers);
+	register_processors(&mut worker_pool, app_state, default_workers);
+
+	combat_operations::schedule_report_pruning(&app_state.job_queue, Utc::now())?;
+	resource_scheduler::register_production_ticks(
+		&app_state.job_queue,
+		app_state.settings.jobs.production_shards,
+	)?;
+	modifier_scheduler::register_expiration_tick(&app_state.job_queue)?;
+	stats_operations::register_table_stats_tick(&app_state.job_queue)?;
+	leaderboard_operations::register_leaderboard_tick(&app_state.job_queue)?;
+	standing_operations::register_faction_standings_tick(&app_state.job_queue)?;
+	tokio::spawn(event_listener::listen(
+		Arc::clone(&app_state.db_pool),
+		app_state.events.subscribe(),
+		token.clone(),
+	));
+	tokio::spawn(score_listener::listen(
+		Arc::clone(&app_state.db_pool),
+		app_state.events.subscribe(),
+		token.clone(),
+	));
+	tokio::spawn(quest_listener::listen(
+		Arc::clone(&app_state.db_pool),
+		app_state.events.subscribe(),
+		token.clone(),
+	));
+	#[cfg(feature = "simulation")]
+	simulation_operations::start_simulation(
+		&mut app_state.db_pool.get()?,
+		&app_state.job_queue,
+		&app_state.settings.simulation,
+	)?;
+	app_state.job_queue.sync_recurring()?;
+	migrations::schedule_backfills(&mut app_state.db_pool.get()?, &app_state.job_queue)?;
 
 	Ok(worker_pool)
 }
 
+/// Adds the processors for every [`JobType`] to the worker pool.
+///
+/// The match over [`JobType::ALL`] is the job dispatch table: a new job type does not
+/// compile until it has a processor here.
+///
+/// # Arguments
+///
+/// * `worker_pool` - The pool to add the workers to
+/// * `app_state` - A reference to `AppState` the processors are created from
+/// * `default_workers` - Number of workers for job types without a count in `jobs.types`
+pub fn register_processors(
+	worker_pool: &mut WorkerPool,
+	app_state: &AppState,
+	default_workers: usize,
+) {
+	for job_type in JobType::ALL {
+		let workers = app_state
+			.settings
+			.jobs
+			.workers_for(job_type, default_workers);
+		debug!("Starting {} {} workers", workers, job_type);
+		match job_type {
+			JobType::Modifier => {
+				worker_pool.add_workers(ModifierProcessor::initialise_n(workers, app_state))
+			}
+			JobType::Building => {
+				worker_pool.add_workers(BuildingProcessor::initialise_n(workers, app_state))
+			}
+			JobType::Resource => {
+				worker_pool.add_workers(ResourceProcessor::initialise_n(workers, app_state))
+			}
+			JobType::ResourceProduction => {
+				worker_pool.add_workers(ProductionProcessor::initialise_n(workers, app_state))
+			}
+			JobType::Training => {
+				worker_pool.add_workers(TrainingProcessor::initialise_n(workers, app_state))
+			}
+			JobType::Combat => {
+				worker_pool.add_workers(CombatProcessor::initialise_n(workers, app_state))
+			}
+			JobType::Espionage => {
+				worker_pool.add_workers(EspionageProcessor::initialise_n(workers, app_state))
+			}
+			JobType::Backfill => {
+				worker_pool.add_workers(BackfillProcessor::initialise_n(workers, app_state))
+			}
+			JobType::WorldReset => {
+				worker_pool.add_workers(WorldResetProcessor::initialise_n(workers, app_state))
+			}
+			JobType::LimitedEvent => {
+				worker_pool.add_workers(LimitedEventProcessor::initialise_n(workers, app_state))
+			}
+			JobType::TableStats => {
+				worker_pool.add_workers(TableStatsProcessor::initialise_n(workers, app_state))
+			}
+			JobType::Leaderboard => {
+				worker_pool.add_workers(LeaderboardProcessor::initialise_n(workers, app_state))
+			}
+			JobType::Season => {
+				worker_pool.add_workers(SeasonProcessor::initialise_n(workers, app_state))
+			}
+			JobType::AccountDeletion => {
+				worker_pool.add_workers(AccountDeletionProcessor::initialise_n(workers, app_state))
+			}
+			JobType::Faction => {
+				worker_pool.add_workers(FactionProcessor::initialise_n(workers, app_state))
+			}
+			#[cfg(feature = "simulation")]
+			JobType::Simulation => {
+				worker_pool.add_workers(SimulationProcessor::initialise_n(workers, app_state))
+			}
+		}
+	}
+}
+
 /// Waits for a shutdown signal in the application.
 ///
 /// This function listens for two types of signals:
@@ -0,0 +1,803 @@
+use chrono::{TimeDelta, Utc};
+use empire::auth::password::RETIRED_HASHES_BACKFILL;
+use empire::db::{migrations, player_buildings};
+use empire::domain::factions::FactionCode;
+use empire::domain::jobs::{JobStatus, JobType};
+use empire::game::table_stats::stats_operations;
+use empire::job_queue::JobPriority;
+use reqwest::{Client, StatusCode};
+
+use crate::common::TestApp;
+
+const ADMIN_KEY: &str = "dev-admin-key";
+
+#[tokio::test]
+async fn overview_requires_admin_key() {
+	let server = TestApp::new();
+	let client = Client::new();
+	let url = format!("{}/admin/overview", &server.admin_address);
+
+	let response = client.get(&url).send().await.unwrap();
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+	let response = client
+		.get(&url)
+		.header("x-admin-key", "not-the-key")
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+	// Player credentials do not grant admin access
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	let response = client
+		.get(&url)
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn admin_api_is_not_served_on_the_public_listener() {
+	let server = TestApp::new();
+	let client = Client::new();
+
+	for path in ["/admin/overview", "/admin/jobs/stats", "/metrics"] {
+		let response = client
+			.get(format!("{}{}", &server.address, path))
+			.header("x-admin-key", ADMIN_KEY)
+			.send()
+			.await
+			.unwrap();
+		assert_eq!(response.status(), StatusCode::NOT_FOUND, "{path}");
+	}
+
+	let response = client
+		.get(format!("{}/admin/overview", &server.admin_address))
+		.header("x-admin-key", ADMIN_KEY)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn overview_summarizes_activity_and_queues() {
+	let server = TestApp::new();
+	let client = Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+
+	// One authenticated request marks the player active
+	let response = client
+		.get(format!("{}/game", &server.address))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+
+	// One upgrade finishing soon, one finishing much later
+	let mut conn = server.get_conn();
+	let buildings = player_buildings::get_player_buildings(&mut conn, &user.id).unwrap();
+	assert!(buildings.len() >= 2, "Player needs two starter buildings");
+	for (bld, eta) in buildings
+		.iter()
+		.zip([TimeDelta::minutes(10), TimeDelta::hours(3)])
+	{
+		let eta = Utc::now() + eta;
+		player_buildings::set_upgrade_eta(&mut conn, &bld.id, Some(eta)).unwrap();
+	}
+
+	server
+		.app
+		.job_queue
+		.enqueue(
+			JobType::Combat,
+			serde_json::json!({}),
+			JobPriority::Low,
+			Utc::now() + TimeDelta::days(1),
+		)
+		.unwrap();
+
+	let response = client
+		.get(format!("{}/admin/overview", &server.admin_address))
+		.header("x-admin-key", ADMIN_KEY)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["active_players"], 1);
+	assert_eq!(body["queue_depth"]["combat"]["pending"], 1);
+	assert_eq!(body["queue_depth"]["training"]["pending"], 0);
+	assert_eq!(body["completing_next_hour"]["upgrades"], 1);
+	assert_eq!(body["completing_next_hour"]["trainings"], 0);
+	assert!(
+		body["errors"]["requests"].as_u64().unwrap() >= 1,
+		"Earlier requests should be counted: {body}"
+	);
+	assert_eq!(body["errors"]["server_errors"], 0);
+}
+
+#[tokio::test]
+async fn dead_letters_can_be_inspected_requeued_and_discarded() {
+	let server = TestApp::new();
+	let client = Client::new();
+	let queue = &server.app.job_queue;
+
+	let bury = |tag: &str| {
+		let job_id = queue
+			.enqueue(
+				JobType::Combat,
+				serde_json::json!({ "dead_letter": tag }),
+				JobPriority::Low,
+				Utc::now() + TimeDelta::days(1),
+			)
+			.unwrap();
+		for attempt in 0..5 {
+			queue
+				.fail_job(&job_id, format!("attempt {attempt}"))
+				.unwrap();
+		}
+		job_id
+	};
+	let requeued_id = bury("requeue");
+	let discarded_id = bury("discard");
+	let base = format!("{}/admin/dead-letters", &server.admin_address);
+
+	let response = client
+		.get(format!("{base}?job_type=combat&per_page=100"))
+		.header("x-admin-key", ADMIN_KEY)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	let listed: Vec<&str> = body["jobs"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.filter_map(|job| job["id"].as_str())
+		.collect();
+	assert!(listed.contains(&requeued_id.to_string().as_str()));
+	assert!(listed.contains(&discarded_id.to_string().as_str()));
+	assert_eq!(body["per_page"], 100);
+
+	let response = client
+		.get(format!("{base}/{requeued_id}"))
+		.header("x-admin-key", ADMIN_KEY)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["payload"]["dead_letter"], "requeue");
+	assert_eq!(body["errors"].as_array().unwrap().len(), 5);
+	assert_eq!(body["last_error"], "attempt 4");
+
+	let response = client
+		.post(format!("{base}/{requeued_id}/requeue"))
+		.header("x-admin-key", ADMIN_KEY)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::ACCEPTED);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["id"], requeued_id.to_string());
+	assert_eq!(
+		body["status"],
+		serde_json::to_value(JobStatus::Pending).unwrap()
+	);
+
+	let response = client
+		.get(format!("{base}/{requeued_id}"))
+		.header("x-admin-key", ADMIN_KEY)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+	for expected in [StatusCode::NO_CONTENT, StatusCode::NOT_FOUND] {
+		let response = client
+			.delete(format!("{base}/{discarded_id}"))
+			.header("x-admin-key", ADMIN_KEY)
+			.send()
+			.await
+			.unwrap();
+		assert_eq!(response.status(), expected);
+	}
+
+	let response = client.get(&base).send().await.unwrap();
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn job_stats_report_depth_waits_and_failures() {
+	let server = TestApp::new();
+	let client = Client::new();
+	let queue = &server.app.job_queue;
+
+	let due = |minutes: i64| Utc::now() - TimeDelta::minutes(minutes);
+	for minutes in [10, 30] {
+		queue
+			.enqueue(
+				JobType::Combat,
+				serde_json::json!({}),
+				JobPriority::Low,
+				due(minutes),
+			)
+			.unwrap();
+	}
+	queue
+		.enqueue(
+			JobType::Combat,
+			serde_json::json!({}),
+			JobPriority::Low,
+			Utc::now() + TimeDelta::days(1),
+		)
+		.unwrap();
+	for outcome in ["complete", "fail"] {
+		let job_id = queue
+			.enqueue(
+				JobType::Building,
+				serde_json::json!({}),
+				JobPriority::High,
+				due(1),
+			)
+			.unwrap();
+		match outcome {
+			"complete" => queue.complete_job(&job_id).unwrap(),
+			_ => queue.fail_job(&job_id, "broken").unwrap(),
+		}
+	}
+
+	let url = format!("{}/admin/jobs/stats", &server.admin_address);
+	let response = client.get(&url).send().await.unwrap();
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+	let response = client
+		.get(&url)
+		.header("x-admin-key", ADMIN_KEY)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let body: serde_json::Value = response.json().await.unwrap();
+	let combat = &body["types"]["combat"];
+	assert_eq!(combat["depth"]["pending"], 3);
+	let avg_wait = combat["avg_wait_seconds"].as_f64().unwrap();
+	assert!((1190.0..1300.0).contains(&avg_wait), "{body}");
+	let oldest = combat["oldest_pending_seconds"].as_f64().unwrap();
+	assert!((1790.0..1900.0).contains(&oldest), "{body}");
+
+	let building = &body["types"]["building"];
+	assert_eq!(building["completed"], 1);
+	assert_eq!(building["failed"], 1);
+	assert_eq!(building["failure_rate"], 0.5);
+	assert!(body["types"]["training"]["avg_wait_seconds"].is_null());
+	assert_eq!(body["window_seconds"], 3600);
+}
+
+#[tokio::test]
+async fn backfills_report_their_progress() {
+	let server = TestApp::new();
+	let client = Client::new();
+	let url = format!("{}/admin/backfills", &server.admin_address);
+
+	let listed: serde_json::Value = client
+		.get(&url)
+		.header("x-admin-key", ADMIN_KEY)
+		.send()
+		.await
+		.unwrap()
+		.json()
+		.await
+		.unwrap();
+	assert_eq!(listed, serde_json::json!([]));
+
+	let mut conn = server.get_conn();
+	migrations::schedule_backfills(&mut conn, &server.app.job_queue).unwrap();
+	let response = client
+		.get(&url)
+		.header("x-admin-key", ADMIN_KEY)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let listed: serde_json::Value = response.json().await.unwrap();
+	let backfill = &listed[0];
+	assert_eq!(backfill["name"], RETIRED_HASHES_BACKFILL);
+	assert_eq!(backfill["processed"], 0);
+	assert_eq!(backfill["completed"], false);
+	assert!(backfill["job_id"].is_string());
+}
+
+#[tokio::test]
+async fn table_stats_chart_the_growth_of_the_tracked_tables() {
+	let server = TestApp::new();
+	let client = Client::new();
+	let url = format!("{}/admin/table-stats", &server.admin_address);
+
+	let mut conn = server.get_conn();
+	let earlier = Utc::now() - TimeDelta::hours(2);
+	stats_operations::capture(&mut conn, earlier).unwrap();
+	server.create_test_user(Some(FactionCode::Human));
+	stats_operations::capture(&mut conn, Utc::now()).unwrap();
+
+	let response = client
+		.get(format!("{url}?days=7&table=player_building"))
+		.header("x-admin-key", ADMIN_KEY)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let stats: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(stats["days"], 7);
+	let tables = stats["tables"].as_array().unwrap();
+	assert_eq!(tables.len(), 1);
+	assert_eq!(tables[0]["table_name"], "player_building");
+	assert_eq!(tables[0]["samples"].as_array().unwrap().len(), 2);
+	assert!(tables[0]["samples"][1]["total_bytes"].as_i64().unwrap() > 0);
+
+	let response = client
+		.get(format!("{url}?days=400"))
+		.header("x-admin-key", ADMIN_KEY)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn player_consistency_lists_violations() {
+	use diesel::prelude::*;
+	use empire::db::player_units;
+	use empire::domain::unit::player_unit::NewPlayerUnit;
+	use empire::schema::{player_resource, unit};
+
+	let server = TestApp::new();
+	let client = Client::new();
+	let player = server.create_test_user(Some(FactionCode::Human));
+	let url = format!(
+		"{}/admin/players/{}/consistency",
+		&server.admin_address, player.id
+	);
+	let check = || async {
+		let response = client
+			.get(&url)
+			.header("x-admin-key", ADMIN_KEY)
+			.send()
+			.await
+			.unwrap();
+		assert_eq!(response.status(), StatusCode::OK);
+		response.json::<serde_json::Value>().await.unwrap()
+	};
+
+	// Test players own no storage buildings, so their caps start out at zero
+	let mut conn = server.get_conn();
+	let player_res = player_resource::table.filter(player_resource::player_id.eq(player.id));
+	diesel::update(player_res)
+		.set((
+			player_resource::food_cap.eq(1000),
+			player_resource::wood_cap.eq(1000),
+			player_resource::stone_cap.eq(1000),
+			player_resource::gold_cap.eq(1000),
+		))
+		.execute(&mut conn)
+		.unwrap();
+
+	let report = check().await;
+	assert_eq!(report["consistent"], true, "{report}");
+	assert_eq!(report["checks"].as_array().unwrap().len(), 5);
+
+	diesel::update(player_res)
+		.set(player_resource::wood.eq(-5))
+		.execute(&mut conn)
+		.unwrap();
+	let unit_id = unit::table.select(unit::id).first(&mut conn).unwrap();
+	player_units::create(
+		&mut conn,
+		NewPlayerUnit {
+			player_id: player.id,
+			unit_id,
+			quantity: -3,
+		},
+	)
+	.unwrap();
+
+	let report = check().await;
+	assert_eq!(report["consistent"], false);
+	let violations: Vec<(&str, &str)> = report["violations"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.map(|v| (v["check"].as_str().unwrap(), v["subject"].as_str().unwrap()))
+		.collect();
+	assert_eq!(
+		violations,
+		vec![
+			("resources", "wood"),
+			("units", unit_id.to_string().as_str())
+		]
+	);
+
+	let response = client
+		.get(format!(
+			"{}/admin/players/{}/consistency",
+			&server.admin_address,
+			uuid::Uuid::new_v4()
+		))
+		.header("x-admin-key", ADMIN_KEY)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn jobs_are_enqueued_with_validated_payloads_and_audited() {
+	use empire::db::audit_log;
+
+	let server = TestApp::new();
+	let client = Client::new();
+	let player = server.create_test_user(Some(FactionCode::Human));
+	let url = format!("{}/admin/jobs", &server.admin_address);
+	let run_at = Utc::now() + TimeDelta::days(1);
+
+	let response = client
+		.post(&url)
+		.header("x-admin-key", ADMIN_KEY)
+		.header("x-admin-operator", "ops@example.com")
+		.json(&serde_json::json!({
+			"job_type": "building",
+			"payload": { "EvaluatePlans": { "player_id": player.id, "ignored": true } },
+			"run_at": run_at,
+			"priority": "high",
+		}))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::ACCEPTED);
+	let body: serde_json::Value = response.json().await.unwrap();
+	let job_id: empire::domain::jobs::JobKey = body["id"].as_str().unwrap().parse().unwrap();
+	assert_eq!(body["priority"], JobPriority::High as i32);
+	assert_eq!(
+		body["payload"],
+		serde_json::json!({ "EvaluatePlans": { "player_id": player.id } }),
+		"Unknown fields are dropped"
+	);
+
+	let job = server.app.job_queue.get_job(&job_id).unwrap();
+	assert_eq!(job.job_type, JobType::Building);
+	assert_eq!(job.status, JobStatus::Pending);
+	assert_eq!(job.run_at.timestamp(), run_at.timestamp());
+
+	let mut conn = server.get_conn();
+	let audit = audit_log::get_recent(&mut conn, 10).unwrap();
+	assert_eq!(audit.len(), 1);
+	assert_eq!(audit[0].action, "enqueue_job");
+	assert_eq!(audit[0].subject, Some(job_id.to_string()));
+	assert_eq!(audit[0].operator.as_deref(), Some("ops@example.com"));
+	assert!(audit[0].request_id.is_some());
+	assert_eq!(audit[0].details["job_type"], "building");
+
+	// Defaults to a normal priority job that is due right away
+	let response = client
+		.post(&url)
+		.header("x-admin-key", ADMIN_KEY)
+		.json(&serde_json::json!({
+			"job_type": "backfill",
+			"payload": { "name": RETIRED_HASHES_BACKFILL },
+		}))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::ACCEPTED);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["priority"], JobPriority::Normal as i32);
+
+	let rejected = [
+		serde_json::json!({ "job_type": "building", "payload": { "Demolish": {} } }),
+		serde_json::json!({ "job_type": "backfill", "payload": { "name": "no_such_backfill" } }),
+		serde_json::json!({
+			"job_type": "resource_production",
+			"payload": { "shard": 4, "shards": 4 },
+		}),
+	];
+	for request in rejected {
+		let response = client
+			.post(&url)
+			.header("x-admin-key", ADMIN_KEY)
+			.json(&request)
+			.send()
+			.await
+			.unwrap();
+		assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{request}");
+	}
+
+	let response = client
+		.post(&url)
+		.header("x-admin-key", ADMIN_KEY)
+		.json(&serde_json::json!({
+			"job_type": "modifier",
+			"payload": { "UpdateModifierCache": { "player_id": uuid::Uuid::new_v4() } },
+		}))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+	assert_eq!(
+		audit_log::get_recent(&mut conn, 10).unwrap().len(),
+		2,
+		"Rejected jobs are neither enqueued nor audited"
+	);
+
+	let response = client
+		.post(&url)
+		.json(&serde_json::json!({ "job_type": "combat", "payload": "PruneReports" }))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn player_roles_are_changed_and_audited() {
+	use empire::db::audit_log;
+
+	let server = TestApp::new();
+	let client = Client::new();
+	let player = server.create_test_user(Some(FactionCode::Human));
+	let mut conn = server.get_conn();
+	let bearer = server.create_bearer_token(&player.id);
+	let users_url = format!("{}/users", &server.address);
+	let role_url = format!("{}/admin/players/{}/role", &server.admin_address, player.id);
+
+	let response = client
+		.get(&users_url)
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::FORBIDDEN);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["code"], "insufficient_role_error");
+	assert_eq!(body["details"]["required_role"], "moderator");
+	assert!(body["request_id"].is_string(), "{body}");
+
+	let response = client
+		.put(&role_url)
+		.header("x-admin-key", ADMIN_KEY)
+		.header("x-admin-operator", "ops@example.com")
+		.json(&serde_json::json!({ "role": "moderator" }))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["role"], "moderator");
+
+	// The new role applies to the player's next request
+	let response = client
+		.get(&users_url)
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let entry = audit_log::get_recent(&mut conn, 1).unwrap().remove(0);
+	assert_eq!(entry.action, "set_player_role");
+	assert_eq!(entry.details["previous"], "player");
+	assert_eq!(entry.operator.as_deref(), Some("ops@example.com"));
+
+	let response = client
+		.put(format!(
+			"{}/admin/players/{}/role",
+			&server.admin_address,
+			uuid::Uuid::new_v4()
+		))
+		.header("x-admin-key", ADMIN_KEY)
+		.json(&serde_json::json!({ "role": "admin" }))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn modifiers_are_granted_and_revoked_with_their_operator() {
+	use std::str::FromStr;
+
+	use bigdecimal::BigDecimal;
+	use empire::db::{active_modifiers, audit_log, modifier_history, modifiers};
+	use empire::domain::modifier::modifier_history::ModifierActionType;
+	use empire::domain::modifier::{MagnitudeKind, ModifierTarget, NewModifier, StackingBehaviour};
+	use empire::domain::player::resource::ResourceType;
+
+	let server = TestApp::new();
+	let client = Client::new();
+	let player = server.create_test_user(Some(FactionCode::Human));
+	let mut conn = server.get_conn();
+	let buff = modifiers::create(
+		&mut conn,
+		NewModifier {
+			name: "harvest_festival".to_string(),
+			description: "Harvest festival".to_string(),
+			magnitude: BigDecimal::from_str("0.25").unwrap(),
+			magnitude_kind: MagnitudeKind::Percentage,
+			target_type: ModifierTarget::Resource,
+			target_resource: Some(ResourceType::Food),
+			stacking_behaviour: Some(StackingBehaviour::Additive),
+			stacking_group: None,
+		},
+	)
+	.unwrap();
+	let url = format!(
+		"{}/admin/players/{}/modifiers",
+		&server.admin_address, player.id
+	);
+
+	let response = client
+		.post(&url)
+		.header("x-admin-key", ADMIN_KEY)
+		.header("x-admin-operator", "ops@example.com")
+		.json(&serde_json::json!({
+			"modifier_id": buff.id,
+			"expires_at": Utc::now() + TimeDelta::days(1),
+			"reason": "Harvest festival",
+		}))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::CREATED);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["source_type"], "event");
+	assert_eq!(body["modifier_id"], buff.id.to_string());
+
+	let response = client
+		.post(&url)
+		.header("x-admin-key", ADMIN_KEY)
+		.json(&serde_json::json!({
+			"modifier_id": buff.id,
+			"expires_at": Utc::now() - TimeDelta::minutes(1),
+		}))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+	let response = client
+		.delete(format!("{url}/{}?reason=Exploited", buff.id))
+		.header("x-admin-key", ADMIN_KEY)
+		.header("x-admin-operator", "security@example.com")
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body.as_array().unwrap().len(), 1);
+	let held = active_modifiers::get_by_player_id(&mut conn, &player.id)
+		.unwrap()
+		.into_iter()
+		.filter(|active| active.modifier_id == buff.id)
+		.count();
+	assert_eq!(held, 0);
+
+	// Both changes name their operator in the history and the audit log
+	let history: Vec<_> = modifier_history::get_by_player_id(&mut conn, &player.id)
+		.unwrap()
+		.into_iter()
+		.filter(|entry| entry.modifier_id == buff.id)
+		.map(|entry| (entry.action_type, entry.operator, entry.reason))
+		.collect();
+	assert_eq!(
+		history,
+		vec![
+			(
+				ModifierActionType::Applied,
+				Some("ops@example.com".to_string()),
+				Some("Harvest festival".to_string())
+			),
+			(
+				ModifierActionType::Removed,
+				Some("security@example.com".to_string()),
+				Some("Exploited".to_string())
+			),
+		]
+	);
+	let actions: Vec<_> = audit_log::get_recent(&mut conn, 10)
+		.unwrap()
+		.into_iter()
+		.map(|entry| entry.action)
+		.collect();
+	assert!(actions.contains(&"grant_modifier".to_string()));
+	assert!(actions.contains(&"revoke_modifier".to_string()));
+
+	let response = client
+		.delete(format!("{url}/{}", buff.id))
+		.header("x-admin-key", ADMIN_KEY)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn audit_log_is_filtered_by_actor_action_and_time() {
+	let server = TestApp::new();
+	let client = Client::new();
+	let player = server.create_named_user("audited", None);
+	let url = format!("{}/admin/audit", &server.admin_address);
+	let started = Utc::now();
+
+	let response = client
+		.post(format!("{}/login", &server.address))
+		.json(&serde_json::json!({ "username": "audited", "password": "1234" }))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let response = client
+		.put(format!(
+			"{}/admin/players/{}/role",
+			&server.admin_address, player.id
+		))
+		.header("x-admin-key", ADMIN_KEY)
+		.header("x-admin-operator", "ops@example.com")
+		.json(&serde_json::json!({ "role": "moderator" }))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let list = |query: Vec<(&'static str, String)>| {
+		let request = client
+			.get(&url)
+			.header("x-admin-key", ADMIN_KEY)
+			.query(&query);
+		async move {
+			let response = request.send().await.unwrap();
+			assert_eq!(response.status(), StatusCode::OK);
+			response.json::<Vec<serde_json::Value>>().await.unwrap()
+		}
+	};
+
+	let entries = list(vec![("player_id", player.id.to_string())]).await;
+	assert_eq!(entries.len(), 1);
+	assert_eq!(entries[0]["action"], "login");
+	assert_eq!(entries[0]["details"]["method"], "password");
+	assert!(entries[0]["request_id"].is_string());
+
+	let entries = list(vec![("operator", "ops@example.com".to_string())]).await;
+	assert_eq!(entries.len(), 1);
+	assert_eq!(entries[0]["action"], "set_player_role");
+	assert_eq!(entries[0]["subject"], player.id.to_string());
+
+	let entries = list(vec![("action", "login".to_string())]).await;
+	assert_eq!(entries.len(), 1);
+	let entries = list(vec![]).await;
+	assert_eq!(entries.len(), 2);
+	assert_eq!(entries[0]["action"], "set_player_role", "Newest first");
+
+	let entries = list(vec![(
+		"from",
+		(Utc::now() + TimeDelta::minutes(1)).to_rfc3339(),
+	)])
+	.await;
+	assert!(entries.is_empty());
+	let entries = list(vec![("until", started.to_rfc3339())]).await;
+	assert!(entries.is_empty());
+	let entries = list(vec![
+		("from", started.to_rfc3339()),
+		("limit", "1".to_string()),
+	])
+	.await;
+	assert_eq!(entries.len(), 1);
+}
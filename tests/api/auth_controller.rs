@@ -5,13 +5,21 @@ use axum_extra::headers;
 use axum_extra::headers::Authorization;
 use axum_extra::headers::authorization::Bearer;
 use claims::assert_gt;
+use diesel::prelude::*;
+use empire::auth::password;
 use empire::auth::utils::hash_password;
-use empire::controllers::auth::{LoginPayload, PlayerDtoResponse, RegisterPayload};
-use empire::db::{DbConn, players};
+use empire::configuration::OAuthProviderSettings;
+use empire::controllers::auth::{
+	LoginPayload, OAuthLoginResponse, PlayerDtoResponse, RegisterPayload,
+};
+use empire::db::{DbConn, player_identities, players};
 use empire::domain::auth::{Claims, encode_token};
 use empire::domain::factions::FactionCode;
 use empire::domain::player::{NewPlayer, Player, PlayerKey, UserEmail, UserName};
+use empire::game::player_operations::provision_player;
+use empire::schema::player;
 use http_body_util::BodyExt;
+use secrecy::SecretString;
 use serde_json::json;
 use tower::ServiceExt;
 
@@ -89,6 +97,37 @@ async fn login_fails_with_wrong_credentials() {
 	);
 }
 
+#[tokio::test]
+async fn repeated_failed_logins_lock_the_username() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = create_test_user(&mut server.get_conn());
+	let login = |password: &str| {
+		client
+			.post(format!("{}/login", &server.address))
+			.json(&json!({ "username": user.name, "password": password }))
+			.send()
+	};
+
+	for _ in 0..5 {
+		let response = login("WRONG :)").await.unwrap();
+		assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+	}
+	// Locked out, even with the right password
+	let response = login("1234").await.unwrap();
+	assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+	assert_eq!(response.headers()[http::header::RETRY_AFTER], "900");
+
+	// The IP is not locked yet, other players can still log in from it
+	let response = client
+		.post(format!("{}/login", &server.address))
+		.json(&json!({ "username": "someone_else", "password": "1234" }))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
 #[tokio::test]
 async fn login_succeeds_with_correct_credentials() {
 	let harness = TestHarness::new();
@@ -300,8 +339,55 @@ async fn session_returns_valid_info() {
 }
 
 /// Create a player. Uses internal DB functions.
+#[tokio::test]
+async fn login_rehashes_outdated_passwords() {
+	let server = TestApp::new();
+	let mut conn = server.get_conn();
+	let client = reqwest::Client::new();
+	let user = create_test_user(&mut conn);
+	let login = LoginPayload {
+		username: user.name.clone(),
+		password: "1234".to_string(),
+	};
+
+	// Hashes written before versioning verify with the legacy hasher
+	let legacy = password::hasher(0).unwrap().hash(b"1234").unwrap();
+	set_pwd_hash(&mut conn, &user.id, &legacy);
+	let response = client
+		.post(format!("{}/login", &server.address))
+		.json(&login)
+		.send()
+		.await
+		.expect("Failed to execute login request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let rehashed = get_user_by_name(&mut conn, &user.name).unwrap().pwd_hash;
+	assert!(
+		rehashed.starts_with(&format!("v{}$", password::CURRENT_HASH_VERSION)),
+		"{rehashed}"
+	);
+
+	// Erased hashes accept no password until it is reset
+	set_pwd_hash(&mut conn, &user.id, password::RESET_REQUIRED_HASH);
+	let response = client
+		.post(format!("{}/login", &server.address))
+		.json(&login)
+		.send()
+		.await
+		.expect("Failed to execute login request.");
+	assert_eq!(response.status(), StatusCode::FORBIDDEN);
+	let body = response.text().await.unwrap();
+	assert!(body.contains("Password reset required"), "{body}");
+}
+
+fn set_pwd_hash(conn: &mut DbConn, player_id: &PlayerKey, pwd_hash: &str) {
+	diesel::update(player::table.find(player_id))
+		.set(player::pwd_hash.eq(pwd_hash))
+		.execute(conn)
+		.expect("Failed to set the password hash");
+}
+
 fn create_test_user(conn: &mut DbConn) -> Player {
-	players::create(
+	let player = players::create(
 		conn,
 		NewPlayer {
 			name: UserName::parse("test_user".to_string()).unwrap(),
@@ -310,7 +396,9 @@ fn create_test_user(conn: &mut DbConn) -> Player {
 			faction: FactionCode::Human,
 		},
 	)
-	.expect("Failed to create user")
+	.expect("Failed to create user");
+	provision_player(conn, &player).expect("Failed to provision player");
+	player
 }
 
 fn get_user_by_name(conn: &mut DbConn, name: &str) -> empire::Result<Player> {
@@ -328,3 +416,121 @@ fn get_bearer(player_id: &PlayerKey) -> Authorization<Bearer> {
 
 	headers::Authorization::bearer(&token).unwrap()
 }
+
+/// Spawns a provider answering every code exchange with the same Google account.
+async fn spawn_mock_google() -> String {
+	let provider = axum::Router::new()
+		.route(
+			"/token",
+			axum::routing::post(|| async { axum::Json(json!({ "access_token": "mock-token" })) }),
+		)
+		.route(
+			"/userinfo",
+			axum::routing::get(|| async {
+				axum::Json(json!({
+					"sub": "google-1234",
+					"name": "Ada (Lovelace)",
+					"email": "ada@example.com",
+					"email_verified": true,
+				}))
+			}),
+		);
+	let listener = axum_test::util::new_random_tokio_tcp_listener().unwrap();
+	let address = format!("http://{}", listener.local_addr().unwrap());
+	tokio::spawn(async move { axum::serve(listener, provider).await });
+	address
+}
+
+#[tokio::test]
+async fn oauth_logins_register_and_then_log_in_the_same_player() {
+	let provider = spawn_mock_google().await;
+	let server = TestApp::with_settings(|settings| {
+		settings.oauth.google = Some(OAuthProviderSettings {
+			client_id: "empire".to_string(),
+			client_secret: SecretString::from("secret"),
+			authorize_url: Some(format!("{provider}/authorize")),
+			token_url: Some(format!("{provider}/token")),
+			userinfo_url: Some(format!("{provider}/userinfo")),
+		});
+	});
+	let client = reqwest::Client::builder()
+		.redirect(reqwest::redirect::Policy::none())
+		.build()
+		.unwrap();
+
+	let mut player_ids = Vec::new();
+	for expected in [StatusCode::CREATED, StatusCode::OK] {
+		let response = client
+			.get(format!("{}/auth/oauth/google/start", &server.address))
+			.send()
+			.await
+			.expect("Failed to execute request.");
+		assert_eq!(response.status(), StatusCode::SEE_OTHER);
+		let location =
+			reqwest::Url::parse(response.headers()[http::header::LOCATION].to_str().unwrap())
+				.unwrap();
+		assert!(
+			location
+				.as_str()
+				.starts_with(&format!("{provider}/authorize"))
+		);
+		let state = location
+			.query_pairs()
+			.find(|(key, _)| key == "state")
+			.map(|(_, value)| value.into_owned())
+			.unwrap();
+		let state_cookie = response.headers()[http::header::SET_COOKIE]
+			.to_str()
+			.unwrap()
+			.split(';')
+			.next()
+			.unwrap()
+			.to_string();
+
+		// A callback with another state is refused
+		let response = client
+			.get(format!("{}/auth/oauth/google/callback", &server.address))
+			.query(&[("code", "code"), ("state", "forged")])
+			.header(http::header::COOKIE, &state_cookie)
+			.send()
+			.await
+			.unwrap();
+		assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+		let response = client
+			.get(format!("{}/auth/oauth/google/callback", &server.address))
+			.query(&[("code", "code"), ("state", state.as_str())])
+			.header(http::header::COOKIE, &state_cookie)
+			.send()
+			.await
+			.unwrap();
+		assert_eq!(response.status(), expected);
+		assert!(
+			response
+				.headers()
+				.get_all(http::header::SET_COOKIE)
+				.iter()
+				.any(|cookie| cookie.to_str().unwrap().starts_with("rsession="))
+		);
+		let body: OAuthLoginResponse = response.json().await.unwrap();
+		assert_eq!(body.created, expected == StatusCode::CREATED);
+		assert_eq!(body.player.name, "Ada Lovelace");
+		assert_eq!(body.player.email.as_deref(), Some("ada@example.com"));
+		player_ids.push(body.player.id);
+	}
+	assert_eq!(player_ids[0], player_ids[1]);
+
+	let mut conn = server.get_conn();
+	let identities = player_identities::get_by_player(&mut conn, &player_ids[0]).unwrap();
+	assert_eq!(identities.len(), 1);
+	assert_eq!(identities[0].provider, "google");
+	assert_eq!(identities[0].subject, "google-1234");
+
+	// Providers without settings are disabled
+	let response = client
+		.get(format!("{}/auth/oauth/discord/start", &server.address))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
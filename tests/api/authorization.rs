@@ -0,0 +1,274 @@
+//! Negative-authorization tests for routes that address player-owned entities.
+//!
+//! Every route must answer with the same `404 Not Found` for entities owned by another
+//! player as for entities that do not exist, so players cannot probe for foreign IDs.
+
+use chrono::Utc;
+use empire::db::{player_buildings, training_queue};
+use empire::domain::combat::Loot;
+use empire::domain::factions::FactionCode;
+use empire::domain::player::Player;
+use empire::domain::player::buildings::PlayerBuilding;
+use empire::domain::unit::training::{NewTrainingQueueEntry, TrainingStatus};
+use empire::game::combat::combat_operations::{BattleOutcome, record_battle};
+use empire::schema::unit;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::common::TestApp;
+
+/// Sets up an owner with starter buildings and an intruder from another faction.
+fn setup() -> (TestApp, Player, Player, PlayerBuilding) {
+	let server = TestApp::new();
+	let owner = server.create_test_user(Some(FactionCode::Human));
+	let intruder = server.create_named_user("test_intruder", Some(FactionCode::Human));
+	let building = player_buildings::get_player_buildings(&mut server.get_conn(), &owner.id)
+		.expect("Failed to get player buildings")
+		.into_iter()
+		.next()
+		.expect("Owner has no starter buildings");
+	(server, owner, intruder, building)
+}
+
+/// Sends both requests as the given player and asserts they were answered with the same 404.
+///
+/// `foreign` addresses an entity owned by another player, `missing` one that does not exist.
+async fn assert_not_found(
+	server: &TestApp,
+	player: &Player,
+	foreign: RequestBuilder,
+	missing: RequestBuilder,
+) {
+	let bearer = server.create_bearer_token(&player.id);
+	let mut answers = Vec::new();
+	for request in [foreign, missing] {
+		let response = request
+			.bearer_auth(bearer.token())
+			.send()
+			.await
+			.expect("Failed to execute request.");
+		assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+		let body: serde_json::Value = response.json().await.unwrap();
+		assert_eq!(body["code"], "not_found_error");
+		assert!(
+			body["message"]
+				.as_str()
+				.unwrap_or_default()
+				.contains("not found"),
+			"Unexpected error body: {body}"
+		);
+		// Request IDs differ, everything else must not
+		answers.push((body["code"].clone(), body["message"].clone()));
+	}
+	assert_eq!(
+		answers[0], answers[1],
+		"Foreign and missing entities must be answered alike"
+	);
+}
+
+#[tokio::test]
+async fn get_building_of_other_player() {
+	let (server, _, intruder, building) = setup();
+	let client = Client::new();
+
+	let url = |id| format!("{}/game/buildings/{}", &server.address, id);
+	assert_not_found(
+		&server,
+		&intruder,
+		client.get(url(building.id)),
+		client.get(url(Uuid::new_v4())),
+	)
+	.await;
+}
+
+#[tokio::test]
+async fn upgrade_building_of_other_player() {
+	let (server, _, intruder, building) = setup();
+	let client = Client::new();
+
+	let url = |id| format!("{}/game/buildings/{}/upgrade", &server.address, id);
+	assert_not_found(
+		&server,
+		&intruder,
+		client.post(url(building.id)),
+		client.post(url(Uuid::new_v4())),
+	)
+	.await;
+
+	let building = player_buildings::get_by_id(&mut server.get_conn(), &building.id).unwrap();
+	assert!(
+		building.upgrade_finishes_at.is_none(),
+		"Foreign building must not start upgrading"
+	);
+}
+
+#[tokio::test]
+async fn confirm_upgrade_of_other_player() {
+	let (server, _, intruder, building) = setup();
+	let client = Client::new();
+
+	let url = |id| format!("{}/game/buildings/{}/upgrade/confirm", &server.address, id);
+	assert_not_found(
+		&server,
+		&intruder,
+		client.post(url(building.id)),
+		client.post(url(Uuid::new_v4())),
+	)
+	.await;
+}
+
+#[tokio::test]
+async fn available_units_of_other_player() {
+	let (server, _, intruder, building) = setup();
+	let client = Client::new();
+
+	let url = |id| {
+		format!(
+			"{}/game/units/available?building_id={}",
+			&server.address, id
+		)
+	};
+	assert_not_found(
+		&server,
+		&intruder,
+		client.get(url(building.id)),
+		client.get(url(Uuid::new_v4())),
+	)
+	.await;
+}
+
+#[tokio::test]
+async fn train_units_at_other_player() {
+	use diesel::prelude::*;
+
+	let (server, _, intruder, building) = setup();
+	let client = Client::new();
+	let unit_id: Uuid = unit::table
+		.select(unit::id)
+		.first(&mut server.get_conn())
+		.expect("No units seeded");
+
+	let url = format!("{}/game/units/train", &server.address);
+	let body = |id| json!({ "building_id": id, "unit_id": unit_id, "quantity": 1 });
+	assert_not_found(
+		&server,
+		&intruder,
+		client.post(&url).json(&body(building.id)),
+		client.post(&url).json(&body(Uuid::new_v4())),
+	)
+	.await;
+}
+
+#[tokio::test]
+async fn cancel_training_of_other_player() {
+	use diesel::prelude::*;
+
+	let (server, owner, intruder, building) = setup();
+	let client = Client::new();
+	let mut conn = server.get_conn();
+	let unit_id: Uuid = unit::table
+		.select(unit::id)
+		.first(&mut conn)
+		.expect("No units seeded");
+	let entry = training_queue::create(
+		&mut conn,
+		NewTrainingQueueEntry {
+			player_id: owner.id,
+			building_id: building.id,
+			unit_id,
+			quantity: 1,
+			status: Some(TrainingStatus::InProgress),
+			job_id: None,
+			completes_at: Utc::now(),
+			training_modifier: 1.into(),
+			seconds_per_unit: 60,
+		},
+	)
+	.expect("Failed to create training entry");
+
+	let url = |id| format!("{}/game/units/queue/{}", &server.address, id);
+	assert_not_found(
+		&server,
+		&intruder,
+		client.delete(url(entry.id)),
+		client.delete(url(Uuid::new_v4())),
+	)
+	.await;
+
+	let entry = training_queue::get_by_id(&mut conn, &entry.id).unwrap();
+	assert_eq!(entry.status, TrainingStatus::InProgress);
+}
+
+#[tokio::test]
+async fn plan_upgrade_of_other_player() {
+	let (server, _, intruder, building) = setup();
+	let client = Client::new();
+
+	let url = format!("{}/game/plans", &server.address);
+	let body = |id| json!({ "action": "upgrade", "player_building_id": id });
+	assert_not_found(
+		&server,
+		&intruder,
+		client.post(&url).json(&body(building.id)),
+		client.post(&url).json(&body(Uuid::new_v4())),
+	)
+	.await;
+}
+
+#[tokio::test]
+async fn cancel_plan_of_other_player() {
+	let (server, owner, intruder, building) = setup();
+	let client = Client::new();
+
+	let bearer = server.create_bearer_token(&owner.id);
+	let response = client
+		.post(format!("{}/game/plans", &server.address))
+		.bearer_auth(bearer.token())
+		.json(&json!({ "action": "upgrade", "player_building_id": building.id }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert!(response.status().is_success());
+	let plan: serde_json::Value = response.json().await.unwrap();
+
+	let url = |id: &str| format!("{}/game/plans/{}", &server.address, id);
+	assert_not_found(
+		&server,
+		&intruder,
+		client.delete(url(plan["id"].as_str().unwrap())),
+		client.delete(url(&Uuid::new_v4().to_string())),
+	)
+	.await;
+}
+
+#[tokio::test]
+async fn battle_report_of_other_players() {
+	let (server, owner, intruder, _) = setup();
+	let client = Client::new();
+	let defender = server.create_named_user("test_defender", Some(FactionCode::Orc));
+	let report = record_battle(
+		&mut server.get_conn(),
+		BattleOutcome {
+			attacker_id: owner.id,
+			defender_id: defender.id,
+			winner_id: None,
+			attacker_losses: vec![],
+			defender_losses: vec![],
+			loot: Loot::default(),
+			modifiers: vec![],
+			fought_at: Utc::now(),
+		},
+	)
+	.expect("Failed to record battle");
+
+	let url = |id| format!("{}/game/combat/reports/{}", &server.address, id);
+	assert_not_found(
+		&server,
+		&intruder,
+		client.get(url(report.id)),
+		client.get(url(Uuid::new_v4())),
+	)
+	.await;
+}
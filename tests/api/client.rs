@@ -0,0 +1,110 @@
+//! End-to-end tests driving the server through the typed `empire_client`.
+
+use empire::domain::factions::FactionCode;
+use empire::domain::player::planned_action::{PlannedActionKind, PlannedActionStatus};
+use empire_client::{
+	ClientError, CreatePlanRequest, EmpireClient, GameSection, LoginPayload, RegisterPayload,
+	ReportListQuery,
+};
+use reqwest::StatusCode;
+
+use crate::common::TestApp;
+
+#[tokio::test]
+async fn client_registers_and_plays_with_session() {
+	let server = TestApp::new();
+	let mut client = EmpireClient::new(&server.address).unwrap();
+
+	let player = client
+		.register(&RegisterPayload {
+			username: "client_player".to_string(),
+			password: "client1234".to_string(),
+			email: None,
+		})
+		.await
+		.expect("Failed to register");
+	assert_eq!(player.name, "client_player");
+	assert!(client.session_token().is_some());
+
+	let session = client.session().await.expect("Failed to get session");
+	assert_eq!(session.player.id, player.id);
+
+	let joined = client
+		.join_faction(FactionCode::Human)
+		.await
+		.expect("Failed to join faction");
+	assert_eq!(joined.faction, FactionCode::Human);
+
+	let state = client.game_state().await.expect("Failed to get game state");
+	assert_eq!(state.player.id, player.id);
+	let state = client
+		.game_state_sections(&[GameSection::Queue])
+		.await
+		.expect("Failed to get game state sections");
+	assert!(state.queue.is_some() && state.resources.is_none());
+
+	let buildings = client.buildings().await.expect("Failed to get buildings");
+	let keep = buildings.first().expect("Player has no starter buildings");
+	let fetched = client.building(&keep.id).await.unwrap();
+	assert_eq!(fetched.id, keep.id);
+
+	let plan = client
+		.create_plan(&CreatePlanRequest {
+			action: PlannedActionKind::Upgrade,
+			building_id: None,
+			player_building_id: Some(keep.id),
+		})
+		.await
+		.expect("Failed to create plan");
+	assert_eq!(client.plans().await.unwrap().plans.len(), 1);
+	let cancelled = client.cancel_plan(&plan.id).await.unwrap();
+	assert_eq!(cancelled.status, PlannedActionStatus::Cancelled);
+
+	let reports = client
+		.battle_reports(&ReportListQuery::default())
+		.await
+		.unwrap();
+	assert_eq!(reports.total, 0);
+
+	client.logout().await.expect("Failed to log out");
+	let err = client.game_state().await.unwrap_err();
+	assert_eq!(err.status(), Some(StatusCode::UNAUTHORIZED));
+
+	client
+		.login(&LoginPayload {
+			username: "client_player".to_string(),
+			password: "client1234".to_string(),
+		})
+		.await
+		.expect("Failed to log in");
+	assert!(client.game_state().await.is_ok());
+}
+
+#[tokio::test]
+async fn client_surfaces_api_errors() {
+	let server = TestApp::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	let client = EmpireClient::new(&server.address)
+		.unwrap()
+		.with_bearer_token(bearer.token());
+
+	let err = client
+		.building(&uuid::Uuid::new_v4())
+		.await
+		.expect_err("Unknown buildings should not be found");
+	let ClientError::Api {
+		status,
+		code,
+		message,
+	} = err
+	else {
+		panic!("Expected an API error, got {err:?}");
+	};
+	assert_eq!(status, StatusCode::NOT_FOUND);
+	assert_eq!(code.as_deref(), Some("not_found_error"));
+	assert!(
+		message.contains("not found"),
+		"Unexpected message: {message}"
+	);
+}
@@ -0,0 +1,126 @@
+use axum::Router;
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode};
+use empire::controllers::docs::{OPENAPI_PATH, SWAGGER_UI_PATH};
+use http_body_util::BodyExt;
+use serde_json::Value;
+use tower::ServiceExt;
+
+use crate::common::TestHarness;
+
+async fn get_openapi(router: Router) -> Value {
+	let response = router
+		.oneshot(
+			Request::builder()
+				.uri(OPENAPI_PATH)
+				.body(Body::empty())
+				.unwrap(),
+		)
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let body = response.into_body().collect().await.unwrap().to_bytes();
+	serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn openapi_document_describes_the_api() {
+	let router = TestHarness::new().router.owned();
+	let doc = get_openapi(router).await;
+
+	assert!(doc["openapi"].as_str().unwrap().starts_with("3."));
+	let paths = doc["paths"].as_object().unwrap();
+	for path in [
+		"/register",
+		"/login",
+		"/health",
+		"/game/buildings/{player_bld_key}/upgrade",
+		"/game/units/train",
+		"/game/resources/send",
+	] {
+		assert!(paths.contains_key(path), "{path} is not documented");
+	}
+	assert!(paths["/game/units/train"]["post"]["requestBody"].is_object());
+	assert_eq!(
+		paths["/game/units/train"]["post"]["security"],
+		serde_json::json!([{ "bearer": [] }, { "session": [] }])
+	);
+
+	let schemas = doc["components"]["schemas"].as_object().unwrap();
+	for schema in [
+		"ErrorBody",
+		"GameBuilding",
+		"TrainUnitsRequest",
+		"UnitType",
+		"Cargo",
+	] {
+		assert!(schemas.contains_key(schema), "{schema} has no schema");
+	}
+	// Key aliases are documented as the UUIDs they stand for
+	assert_eq!(
+		schemas["GameBuilding"]["properties"]["player_id"]["format"],
+		"uuid"
+	);
+	let security = doc["components"]["securitySchemes"].as_object().unwrap();
+	assert!(security.contains_key("bearer"));
+	assert_eq!(security["session"]["in"], "cookie");
+}
+
+#[tokio::test]
+async fn documented_routes_exist() {
+	let (router, _guard) = TestHarness::new().router.split();
+	let doc = get_openapi(router.clone()).await;
+
+	for (path, operations) in doc["paths"].as_object().unwrap() {
+		let uri = path.replace("{provider}", "github").replace(['{', '}'], "");
+		let uri = uri
+			.split('/')
+			.map(|segment| match segment {
+				"player_bld_key" | "training_id" | "preset_id" => uuid::Uuid::new_v4().to_string(),
+				segment => segment.to_string(),
+			})
+			.collect::<Vec<_>>()
+			.join("/");
+		for method in operations.as_object().unwrap().keys() {
+			let method = Method::from_bytes(method.to_uppercase().as_bytes()).unwrap();
+			let response = router
+				.clone()
+				.oneshot(
+					Request::builder()
+						.method(method.clone())
+						.uri(&uri)
+						.body(Body::empty())
+						.unwrap(),
+				)
+				.await
+				.unwrap();
+			let status = response.status();
+			let body = response.into_body().collect().await.unwrap().to_bytes();
+			let body = String::from_utf8_lossy(&body);
+			assert!(
+				status != StatusCode::METHOD_NOT_ALLOWED && !body.contains("route_not_found_error"),
+				"{method} {path} is documented but not routed"
+			);
+		}
+	}
+}
+
+#[tokio::test]
+async fn swagger_ui_is_served() {
+	let router = TestHarness::new().router.owned();
+
+	let response = router
+		.oneshot(
+			Request::builder()
+				.uri(format!("{SWAGGER_UI_PATH}/"))
+				.body(Body::empty())
+				.unwrap(),
+		)
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let body = response.into_body().collect().await.unwrap().to_bytes();
+	assert!(String::from_utf8_lossy(&body).contains("swagger"));
+}
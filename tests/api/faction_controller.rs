@@ -1,5 +1,8 @@
-use axum::http::StatusCode;
+use axum::http::{StatusCode, header};
+use chrono::Utc;
+use empire::controllers::game::factions::FactionStandingsResponse;
 use empire::domain::factions::FactionCode;
+use empire::game::factions::standing_operations;
 
 use crate::common::TestApp;
 
@@ -20,6 +23,44 @@ async fn get_factions_returns_200() {
 	assert_eq!(response.status(), StatusCode::OK);
 }
 
+#[tokio::test]
+async fn unchanged_factions_are_not_modified() {
+	let app = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = app.create_test_user(Some(FactionCode::Human));
+	let token = app.create_bearer_token(&user.id);
+	let url = format!("{}/game/factions", &app.address);
+
+	let response = client
+		.get(&url)
+		.bearer_auth(token.token())
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let etag = response.headers()[header::ETAG].clone();
+
+	let response = client
+		.get(&url)
+		.bearer_auth(token.token())
+		.header(header::IF_NONE_MATCH, etag.clone())
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+	// Stale tags get the current list
+	let response = client
+		.get(&url)
+		.bearer_auth(token.token())
+		.header(header::IF_NONE_MATCH, "W/\"stale\"")
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	assert_eq!(response.headers()[header::ETAG], etag);
+}
+
 #[tokio::test]
 async fn get_faction_details_returns_200() {
 	let app = TestApp::new();
@@ -36,3 +77,102 @@ async fn get_faction_details_returns_200() {
 
 	assert_eq!(response.status(), StatusCode::OK);
 }
+
+#[tokio::test]
+async fn get_faction_bonuses_returns_generated_effects() {
+	let app = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = app.create_test_user(Some(FactionCode::Human));
+	let token = app.create_bearer_token(&user.id);
+
+	let response = client
+		.get(format!("{}/game/factions/human/bonuses", &app.address))
+		.bearer_auth(token.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["faction"], "human");
+	let bonuses = body["bonuses"]
+		.as_array()
+		.expect("Bonuses should be an array");
+	assert!(!bonuses.is_empty(), "Humans should have faction bonuses");
+	let wood = bonuses
+		.iter()
+		.find(|b| b["name"] == "human_wood_production")
+		.expect("Missing human wood bonus");
+	assert_eq!(wood["target"], "resource");
+	assert_eq!(wood["target_resource"], "wood");
+	assert_eq!(wood["effect"], "+15% wood production");
+	assert!(
+		bonuses
+			.iter()
+			.all(|b| b["name"].as_str().unwrap().starts_with("human_")),
+		"Only human bonuses should be returned"
+	);
+}
+
+#[tokio::test]
+async fn get_faction_bonuses_unknown_faction() {
+	let app = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = app.create_test_user(Some(FactionCode::Human));
+	let token = app.create_bearer_token(&user.id);
+
+	let response = client
+		.get(format!("{}/game/factions/pirates/bonuses", &app.address))
+		.bearer_auth(token.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+
+	assert!(response.status().is_client_error());
+}
+
+#[tokio::test]
+async fn get_faction_standings_after_an_aggregation() {
+	let app = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = app.create_test_user(Some(FactionCode::Human));
+	let token = app.create_bearer_token(&user.id);
+	let url = format!("{}/game/factions/standings", &app.address);
+
+	let response = client
+		.get(&url)
+		.bearer_auth(token.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: FactionStandingsResponse = response.json().await.unwrap();
+	assert!(body.computed_at.is_none(), "Nothing was aggregated yet");
+	assert!(body.standings.is_empty());
+
+	let mut conn = app.get_conn();
+	standing_operations::update_standings(&mut conn, &app.app.modifier_system.cache, Utc::now())
+		.unwrap();
+
+	let response = client
+		.get(&url)
+		.bearer_auth(token.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: FactionStandingsResponse = response.json().await.unwrap();
+	assert!(body.computed_at.is_some());
+	assert_eq!(body.standings.len(), 5);
+	let humans = body
+		.standings
+		.iter()
+		.find(|standing| standing.faction == FactionCode::Human)
+		.expect("Missing human standing");
+	assert!(humans.players >= 1, "The player counts towards the humans");
+	assert!(
+		body.standings.iter().all(|standing| !standing.leading),
+		"Nobody leads a war without battles"
+	);
+}
@@ -1,6 +1,20 @@
 use axum::body::Body;
 use axum::http::{Method, Request, StatusCode, header};
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::prelude::*;
+use empire::controllers::auth::RegisterPayload;
+use empire::db::{
+	heroes, items, player_buildings, player_items, player_units, players, resources, units,
+};
+use empire::domain::events::GameEvent;
 use empire::domain::factions::FactionCode;
+use empire::domain::player::buildings::NewPlayerBuilding;
+use empire::domain::player::role::PlayerRole;
+use empire::game::buildings::building_operations::MAX_CONSTRUCTIONS_PER_WINDOW;
+use empire::game::leaderboard::leaderboard_operations::snapshot;
+use empire::game::limited_events::event_operations::record_progress;
+use empire::game::seasons::season_operations::end_season;
+use empire::schema::{building, player_building};
 use serde_json::json;
 use tower::ServiceExt;
 
@@ -15,6 +29,23 @@ async fn get_game_state_requires_authentication() {
 		.await
 		.unwrap();
 	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+	// The middleware answers with the error body of every other failure
+	let request_id = response.headers()["x-request-id"]
+		.to_str()
+		.unwrap()
+		.to_owned();
+	let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+		.await
+		.unwrap();
+	let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+	assert_eq!(body["code"], "no_session_error");
+	assert_eq!(
+		body["message"],
+		"You are not logged in, please authenticate"
+	);
+	assert_eq!(body["request_id"], request_id.as_str());
+	assert!(body.get("status").is_none(), "{body}");
 }
 
 #[tokio::test]
@@ -57,6 +88,219 @@ async fn get_game_state_success() {
 		body.get("buildings").is_some(),
 		"Game state should contain buildings"
 	);
+	assert!(
+		body.get("queue").is_some(),
+		"Game state should contain the queues"
+	);
+}
+
+#[tokio::test]
+async fn get_game_state_includes_only_requested_sections() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	let get_game = |include: &'static str| {
+		client
+			.get(format!("{}/game", &server.address))
+			.query(&[("include", include)])
+			.bearer_auth(bearer.token())
+			.send()
+	};
+
+	let body: serde_json::Value = get_game("resources, queue")
+		.await
+		.unwrap()
+		.json()
+		.await
+		.unwrap();
+	assert_eq!(body["player"]["id"], user.id.to_string());
+	assert!(body["resources"].is_object(), "{body}");
+	assert!(body["queue"]["construction"].is_object(), "{body}");
+	assert!(body["queue"]["training"].is_object(), "{body}");
+	assert!(body.get("buildings").is_none(), "{body}");
+
+	// Only the player is left without sections
+	let body: serde_json::Value = get_game("").await.unwrap().json().await.unwrap();
+	let keys: Vec<&String> = body.as_object().unwrap().keys().collect();
+	assert_eq!(keys, ["player"]);
+
+	let response = get_game("resources,army").await.unwrap();
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["code"], "invalid_data");
+	assert_eq!(body["details"]["section"], "army");
+}
+
+#[tokio::test]
+async fn unchanged_game_state_is_not_modified() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	let get_game = |etag: Option<&str>| {
+		let mut request = client
+			.get(format!("{}/game", &server.address))
+			.bearer_auth(bearer.token());
+		if let Some(etag) = etag {
+			request = request.header(header::IF_NONE_MATCH, etag);
+		}
+		request.send()
+	};
+
+	let response = get_game(None).await.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let etag = response.headers()[header::ETAG]
+		.to_str()
+		.unwrap()
+		.to_owned();
+
+	let response = get_game(Some(&etag)).await.unwrap();
+	assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+	assert_eq!(response.headers()[header::ETAG], etag.as_str());
+	assert!(response.bytes().await.unwrap().is_empty());
+
+	// Collecting resources changes the state, and with it the tag
+	let response = client
+		.post(format!("{}/game/resources/collect", &server.address))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let response = get_game(Some(&etag)).await.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	assert_ne!(response.headers()[header::ETAG], etag.as_str());
+}
+
+#[tokio::test]
+async fn shielded_players_polling_the_game_state_are_not_modified() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	players::set_protected_until(
+		&mut server.get_conn(),
+		&user.id,
+		Some(Utc::now() + TimeDelta::days(1)),
+	)
+	.expect("Failed to shield the player");
+	let get_game = |etag: Option<&str>| {
+		let mut request = client
+			.get(format!("{}/game", &server.address))
+			.bearer_auth(bearer.token());
+		if let Some(etag) = etag {
+			request = request.header(header::IF_NONE_MATCH, etag);
+		}
+		request.send()
+	};
+
+	let response = get_game(None).await.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let etag = response.headers()[header::ETAG]
+		.to_str()
+		.unwrap()
+		.to_owned();
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert!(body["player"]["protection"].is_object(), "{body}");
+
+	// The countdown of the shield ticks, the tag stays the same
+	tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+	let response = get_game(Some(&etag)).await.unwrap();
+	assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+	assert_eq!(response.headers()[header::ETAG], etag.as_str());
+}
+
+#[tokio::test]
+async fn unchanged_building_definitions_are_not_modified() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	let url = format!("{}/game/buildings/all", &server.address);
+
+	let response = client
+		.get(&url)
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let etag = response.headers()[header::ETAG].clone();
+	let definitions: Vec<serde_json::Value> = response.json().await.unwrap();
+	assert!(!definitions.is_empty());
+
+	let response = client
+		.get(&url)
+		.bearer_auth(bearer.token())
+		.header(header::IF_NONE_MATCH, etag)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+}
+
+#[tokio::test]
+async fn registered_players_start_under_protection() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+
+	let response = client
+		.post(format!("{}/register", &server.address))
+		.json(&RegisterPayload {
+			username: "shielded".to_string(),
+			password: "1234".to_string(),
+			email: None,
+		})
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::CREATED);
+	let player = players::get_by_name(&mut server.get_conn(), "shielded").unwrap();
+	let bearer = server.create_bearer_token(&player.id);
+	let response = client
+		.put(format!("{}/player/faction", &server.address))
+		.bearer_auth(bearer.token())
+		.json(&json!({"faction": "human"}))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+	let body: serde_json::Value = client
+		.get(format!("{}/game", &server.address))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.unwrap()
+		.json()
+		.await
+		.unwrap();
+	let remaining = body["player"]["protection"]["remaining_seconds"]
+		.as_i64()
+		.unwrap_or_else(|| panic!("New players should be protected: {body}"));
+	let days = server.app.settings.protection.beginner_shield_days;
+	assert!(remaining > (days - 1) * 24 * 3600 && remaining <= days * 24 * 3600);
+
+	let response = client
+		.delete(format!("{}/game/combat/protection", &server.address))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+	let body: serde_json::Value = client
+		.get(format!("{}/game", &server.address))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.unwrap()
+		.json()
+		.await
+		.unwrap();
+	assert!(body["player"]["protection"].is_null(), "{body}");
+	assert!(body["player"]["truce"].is_null(), "{body}");
 }
 
 #[tokio::test]
@@ -135,6 +379,188 @@ async fn get_buildings_success() {
 	);
 }
 
+#[tokio::test]
+async fn get_buildings_reports_upgrade_progress() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+
+	let mut conn = server.get_conn();
+	let buildings = player_buildings::get_player_buildings(&mut conn, &user.id).unwrap();
+	let upgrading = &buildings[0];
+	let eta = Utc::now() + TimeDelta::hours(1);
+	player_buildings::set_upgrade_eta(&mut conn, &upgrading.id, Some(eta)).unwrap();
+
+	let response = client
+		.get(format!("{}/game/buildings", &server.address))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let body: serde_json::Value = response.json().await.unwrap();
+	for building in body.as_array().unwrap() {
+		if building["id"] == upgrading.id.to_string() {
+			assert_eq!(building["upgrading"], true);
+			let remaining = building["seconds_remaining"].as_i64().unwrap();
+			assert!((3590..=3600).contains(&remaining), "{building}");
+			let percent = building["upgrade_progress_percent"].as_f64().unwrap();
+			assert!((0.0..=100.0).contains(&percent), "{building}");
+		} else {
+			assert_eq!(building["upgrading"], false);
+			assert!(building["upgrade_progress_percent"].is_null());
+			assert!(building["seconds_remaining"].is_null());
+		}
+	}
+}
+
+#[tokio::test]
+async fn cancel_upgrade_refunds_and_stops_the_upgrade() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	let rival = server.create_named_user("rival", Some(FactionCode::Orc));
+	let rival_bearer = server.create_bearer_token(&rival.id);
+
+	let mut conn = server.get_conn();
+	let buildings = player_buildings::get_player_buildings(&mut conn, &user.id).unwrap();
+	let upgrading = &buildings[0];
+	let eta = Utc::now() + TimeDelta::hours(1);
+	player_buildings::set_upgrade_eta(&mut conn, &upgrading.id, Some(eta)).unwrap();
+	let url = format!(
+		"{}/game/buildings/{}/cancel-upgrade",
+		&server.address, upgrading.id
+	);
+
+	let response = client
+		.post(&url)
+		.bearer_auth(rival_bearer.token())
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+	let response = client
+		.post(&url)
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["building"]["id"], upgrading.id.to_string());
+	assert_eq!(body["building"]["upgrading"], false);
+	assert!(body["refunded"]["wood"].is_i64(), "{body}");
+
+	let response = client
+		.post(&url)
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn construction_queue_accepts_and_lists_upgrades() {
+	use empire::schema::{player_building, player_resource};
+
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	let rival = server.create_named_user("rival", Some(FactionCode::Orc));
+	let rival_bearer = server.create_bearer_token(&rival.id);
+
+	let mut conn = server.get_conn();
+	diesel::update(player_resource::table.filter(player_resource::player_id.eq(user.id)))
+		.set((
+			player_resource::food.eq(100_000),
+			player_resource::wood.eq(100_000),
+			player_resource::stone.eq(100_000),
+			player_resource::gold.eq(100_000),
+		))
+		.execute(&mut conn)
+		.unwrap();
+	let building_of = |conn: &mut _, name: &str| -> uuid::Uuid {
+		player_building::table
+			.inner_join(building::table)
+			.filter(player_building::player_id.eq(user.id))
+			.filter(building::name.eq(name))
+			.select(player_building::id)
+			.first(conn)
+			.unwrap()
+	};
+	let keep = building_of(&mut conn, "Keep");
+	diesel::update(player_building::table.find(keep))
+		.set(player_building::level.eq(5))
+		.execute(&mut conn)
+		.unwrap();
+	let farm = building_of(&mut conn, "Farm");
+	let url = format!("{}/game/buildings/queue", &server.address);
+
+	let response = client
+		.post(&url)
+		.bearer_auth(rival_bearer.token())
+		.json(&json!({ "player_building_id": farm }))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+	let response = client
+		.post(&url)
+		.bearer_auth(bearer.token())
+		.json(&json!({ "player_building_id": farm }))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::CREATED);
+	let entry: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(entry["player_building_id"], farm.to_string());
+	assert_eq!(entry["status"], "in_progress");
+
+	let response = client
+		.get(&url)
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["capacity"], 2);
+	assert_eq!(body["free_slots"], 1);
+	assert_eq!(body["entries"][0]["id"], entry["id"]);
+}
+
+#[tokio::test]
+async fn badges_count_upgrades_waiting_for_confirmation() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+
+	let mut conn = server.get_conn();
+	let buildings = player_buildings::get_player_buildings(&mut conn, &user.id).unwrap();
+	let finished = Utc::now() - TimeDelta::minutes(1);
+	let underway = Utc::now() + TimeDelta::hours(1);
+	player_buildings::set_upgrade_eta(&mut conn, &buildings[0].id, Some(finished)).unwrap();
+	player_buildings::set_upgrade_eta(&mut conn, &buildings[1].id, Some(underway)).unwrap();
+
+	let response = client
+		.get(format!("{}/game/badges", &server.address))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["upgrades_ready"], 1, "{body}");
+}
+
 #[tokio::test]
 async fn get_building_by_id_not_found() {
 	let server = TestApp::new();
@@ -217,10 +643,10 @@ async fn get_available_buildings() {
 
 	let buildings: Vec<serde_json::Value> = response.json().await.unwrap();
 
-	// Human player should see Human buildings (17) + Neutral buildings (3) = 20 total
+	// Human player should see Human buildings (17) + Neutral buildings (5) = 22 total
 	assert_eq!(
 		buildings.len(),
-		20,
+		22,
 		"Human player should see all Human and Neutral faction buildings"
 	);
 
@@ -369,3 +795,1627 @@ async fn building_requirements_check_required_building_level() {
 		);
 	}
 }
+
+#[tokio::test]
+async fn get_battle_report_hidden_from_outsiders() {
+	use chrono::Utc;
+	use empire::domain::combat::Loot;
+	use empire::game::combat::combat_operations::{BattleOutcome, record_battle};
+
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let attacker = server.create_test_user(Some(FactionCode::Human));
+	let defender = server.create_named_user("test_defender", Some(FactionCode::Orc));
+	let outsider = server.create_named_user("test_outsider", Some(FactionCode::Elf));
+	let report = record_battle(
+		&mut server.get_conn(),
+		BattleOutcome {
+			attacker_id: attacker.id,
+			defender_id: defender.id,
+			winner_id: None,
+			attacker_losses: vec![],
+			defender_losses: vec![],
+			loot: Loot::default(),
+			modifiers: vec![],
+			fought_at: Utc::now(),
+		},
+	)
+	.expect("Failed to record battle");
+
+	for player in [&attacker, &defender] {
+		let bearer = server.create_bearer_token(&player.id);
+		let response = client
+			.get(format!("{}/game/combat/reports", &server.address))
+			.bearer_auth(bearer.token())
+			.send()
+			.await
+			.expect("Failed to execute request.");
+		assert_eq!(response.status(), StatusCode::OK);
+		let body: serde_json::Value = response.json().await.unwrap();
+		assert_eq!(body["total"], 1);
+		assert_eq!(body["reports"][0]["id"], json!(report.id));
+	}
+
+	let bearer = server.create_bearer_token(&outsider.id);
+	let response = client
+		.get(format!(
+			"{}/game/combat/reports/{}",
+			&server.address, report.id
+		))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn construct_building_is_throttled_per_player() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+
+	// The starter buildings do not count, the constructions right after do
+	let mut conn = server.get_conn();
+	let farm_id: i32 = building::table
+		.filter(building::name.eq("Farm"))
+		.filter(building::faction.eq(FactionCode::Human))
+		.select(building::id)
+		.first(&mut conn)
+		.unwrap();
+	let recent = player_buildings::count_constructed_since(
+		&mut conn,
+		&user.id,
+		Utc::now() - TimeDelta::minutes(1),
+	)
+	.unwrap();
+	assert_eq!(recent, 0);
+	for _ in 0..MAX_CONSTRUCTIONS_PER_WINDOW {
+		player_buildings::construct(
+			&mut conn,
+			NewPlayerBuilding {
+				player_id: user.id,
+				building_id: farm_id,
+				level: Some(0),
+				upgrade_finishes_at: None,
+				settlement_id: None,
+			},
+		)
+		.unwrap();
+	}
+
+	let response = client
+		.post(format!("{}/game/buildings/construct", &server.address))
+		.bearer_auth(bearer.token())
+		.json(&json!({ "building_id": farm_id }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+
+	assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert!(
+		body["message"]
+			.as_str()
+			.is_some_and(|msg| msg.contains("Too many constructions")),
+		"Unexpected body: {body}"
+	);
+}
+
+#[tokio::test]
+async fn job_status_can_be_polled_by_its_player() {
+	use empire::domain::jobs::JobType;
+	use empire::game::buildings::plan_operations::BuildingJobPayload;
+	use empire::job_queue::JobPriority;
+
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let owner = server.create_test_user(Some(FactionCode::Human));
+	let outsider = server.create_named_user("test_outsider", Some(FactionCode::Elf));
+	let queue = &server.app.job_queue;
+
+	let job_id = queue
+		.enqueue(
+			JobType::Building,
+			BuildingJobPayload::EvaluatePlans {
+				player_id: owner.id,
+			},
+			JobPriority::Normal,
+			Utc::now() + TimeDelta::days(1),
+		)
+		.unwrap();
+	let url = format!("{}/game/jobs/{}", &server.address, job_id);
+	let owner_bearer = server.create_bearer_token(&owner.id);
+
+	let response = client
+		.get(&url)
+		.bearer_auth(owner_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["status"], "pending");
+	assert_eq!(body["result"], serde_json::Value::Null);
+
+	queue
+		.complete_job_with_result(&job_id, Some(json!({ "executed": 1, "remaining": 0 })))
+		.unwrap();
+	let response = client
+		.get(&url)
+		.bearer_auth(owner_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["status"], "completed");
+	assert_eq!(body["result"]["executed"], 1);
+
+	let outsider_bearer = server.create_bearer_token(&outsider.id);
+	for url in [
+		url,
+		format!("{}/game/jobs/{}", &server.address, uuid::Uuid::new_v4()),
+	] {
+		let response = client
+			.get(&url)
+			.bearer_auth(outsider_bearer.token())
+			.send()
+			.await
+			.expect("Failed to execute request.");
+		assert_eq!(response.status(), StatusCode::NOT_FOUND);
+	}
+}
+
+#[tokio::test]
+async fn market_orders_trade_between_players() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let seller = server.create_test_user(Some(FactionCode::Human));
+	let buyer = server.create_named_user("test_buyer", Some(FactionCode::Elf));
+	let seller_bearer = server.create_bearer_token(&seller.id);
+	let buyer_bearer = server.create_bearer_token(&buyer.id);
+	let orders_url = format!("{}/game/market/orders", &server.address);
+
+	let response = client
+		.post(&orders_url)
+		.bearer_auth(seller_bearer.token())
+		.json(&json!({ "side": "sell", "resource": "food", "quantity": 40, "price": 1 }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CREATED);
+	let body: serde_json::Value = response.json().await.unwrap();
+	let ask_id = body["order"]["id"].as_str().unwrap().to_string();
+	assert_eq!(body["order"]["status"], "open");
+
+	let response = client
+		.get(format!("{}/game/market/book/food", &server.address))
+		.bearer_auth(buyer_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let book: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(book["asks"][0]["quantity"], 40);
+
+	let response = client
+		.post(&orders_url)
+		.bearer_auth(buyer_bearer.token())
+		.json(&json!({ "side": "buy", "resource": "food", "quantity": 10, "price": 2 }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CREATED);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["order"]["status"], "filled");
+	assert_eq!(body["trades"][0]["price"], 1);
+
+	let response = client
+		.get(&orders_url)
+		.bearer_auth(seller_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["orders"][0]["remaining"], 30);
+
+	let cancel_url = format!("{}/{}", &orders_url, ask_id);
+	let response = client
+		.delete(&cancel_url)
+		.bearer_auth(buyer_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+	let response = client
+		.delete(&cancel_url)
+		.bearer_auth(seller_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["status"], "cancelled");
+
+	let response = client
+		.post(&orders_url)
+		.bearer_auth(buyer_bearer.token())
+		.json(&json!({ "side": "sell", "resource": "gold", "quantity": 1, "price": 1 }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn alliances_are_founded_joined_and_funded() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let leader = server.create_test_user(Some(FactionCode::Human));
+	let recruit = server.create_named_user("test_recruit", Some(FactionCode::Elf));
+	let outsider = server.create_named_user("test_outsider", Some(FactionCode::Orc));
+	let leader_bearer = server.create_bearer_token(&leader.id);
+	let recruit_bearer = server.create_bearer_token(&recruit.id);
+	let outsider_bearer = server.create_bearer_token(&outsider.id);
+	let alliances_url = format!("{}/game/alliances", &server.address);
+
+	let response = client
+		.post(&alliances_url)
+		.bearer_auth(leader_bearer.token())
+		.json(&json!({ "name": "Northern Watch", "tag": "NW" }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CREATED);
+	let body: serde_json::Value = response.json().await.unwrap();
+	let alliance_url = format!("{}/{}", &alliances_url, body["id"].as_str().unwrap());
+	assert_eq!(body["members"][0]["role"], "leader");
+
+	let response = client
+		.post(format!("{alliance_url}/join"))
+		.bearer_auth(recruit_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(
+		response.status(),
+		StatusCode::NOT_FOUND,
+		"Joining takes an invite"
+	);
+
+	let response = client
+		.post(format!("{alliance_url}/invites"))
+		.bearer_auth(leader_bearer.token())
+		.json(&json!({ "player_id": recruit.id }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CREATED);
+	let response = client
+		.get(format!("{}/invites", &alliances_url))
+		.bearer_auth(recruit_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["invites"][0]["alliance_tag"], "NW");
+
+	let response = client
+		.post(format!("{alliance_url}/join"))
+		.bearer_auth(recruit_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["members"].as_array().unwrap().len(), 2);
+
+	let response = client
+		.post(format!("{alliance_url}/treasury"))
+		.bearer_auth(recruit_bearer.token())
+		.json(&json!({ "food": 25 }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CREATED);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["treasury"]["food"], 25);
+
+	// Only members see the treasury
+	let response = client
+		.get(&alliance_url)
+		.bearer_auth(outsider_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert!(body["treasury"].is_null());
+	let response = client
+		.get(format!("{}/me", &alliances_url))
+		.bearer_auth(recruit_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["treasury"]["food"], 25);
+
+	let response = client
+		.delete(format!("{alliance_url}/members/{}", leader.id))
+		.bearer_auth(recruit_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::FORBIDDEN);
+	let response = client
+		.delete(format!("{alliance_url}/members/{}", recruit.id))
+		.bearer_auth(leader_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+	let response = client
+		.post(format!("{}/leave", &alliances_url))
+		.bearer_auth(leader_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["disbanded"], true);
+	let response = client
+		.get(&alliance_url)
+		.bearer_auth(leader_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn mail_is_sent_read_and_deleted() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let sender = server.create_test_user(Some(FactionCode::Human));
+	let recipient = server.create_named_user("test_recipient", Some(FactionCode::Elf));
+	let sender_bearer = server.create_bearer_token(&sender.id);
+	let recipient_bearer = server.create_bearer_token(&recipient.id);
+	let mail_url = format!("{}/game/mail", &server.address);
+
+	let response = client
+		.post(&mail_url)
+		.bearer_auth(sender_bearer.token())
+		.json(
+			&json!({ "recipient_id": recipient.id, "subject": "Trade?", "body": "Wood for gold" }),
+		)
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CREATED);
+	let body: serde_json::Value = response.json().await.unwrap();
+	let message_url = format!("{}/{}", &mail_url, body["id"].as_str().unwrap());
+
+	let response = client
+		.post(&mail_url)
+		.bearer_auth(sender_bearer.token())
+		.json(&json!({ "recipient_id": recipient.id, "subject": "", "body": "Empty subject" }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+	// The unread message shows up in the badges and the inbox
+	let response = client
+		.get(format!("{}/game/badges", &server.address))
+		.bearer_auth(recipient_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["unread_mail"], 1);
+	let response = client
+		.get(format!("{mail_url}?unread=true&per_page=10"))
+		.bearer_auth(recipient_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["total"], 1);
+	assert_eq!(body["per_page"], 10);
+	assert_eq!(body["messages"][0]["subject"], "Trade?");
+	assert_eq!(body["messages"][0]["sender_id"], sender.id.to_string());
+
+	let response = client
+		.get(&message_url)
+		.bearer_auth(sender_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(
+		response.status(),
+		StatusCode::NOT_FOUND,
+		"Only the recipient reads it"
+	);
+
+	let response = client
+		.post(format!("{message_url}/read"))
+		.bearer_auth(recipient_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["read"], true);
+	let response = client
+		.get(format!("{mail_url}/unread"))
+		.bearer_auth(recipient_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["unread"], 0);
+
+	let response = client
+		.delete(&message_url)
+		.bearer_auth(recipient_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::NO_CONTENT);
+	let response = client
+		.get(&message_url)
+		.bearer_auth(recipient_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn send_resources_dispatches_a_caravan() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let sender = server.create_test_user(Some(FactionCode::Human));
+	let receiver = server.create_named_user("test_receiver", Some(FactionCode::Orc));
+	let bearer = server.create_bearer_token(&sender.id);
+	let url = format!("{}/game/resources/send", &server.address);
+
+	let response = client
+		.post(&url)
+		.bearer_auth(bearer.token())
+		.json(&json!({ "receiver_id": receiver.id, "wood": 40 }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CREATED);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["status"], "travelling");
+	assert_eq!(body["cargo"]["wood"], 40);
+	assert_eq!(body["cargo"]["food"], 0);
+	assert!(body["job_id"].is_string());
+
+	let response = client
+		.post(&url)
+		.bearer_auth(bearer.token())
+		.json(&json!({ "receiver_id": sender.id, "wood": 40 }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn activity_stats_cover_the_requested_window() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	let url = format!("{}/game/stats/activity", &server.address);
+
+	let response = client
+		.get(&url)
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["window_days"], 7);
+	assert_eq!(body["days"].as_array().unwrap().len(), 7);
+	assert_eq!(body["days"][6]["day"], Utc::now().date_naive().to_string());
+	assert_eq!(body["days"][6]["units_trained"], 0);
+
+	let response = client
+		.get(format!("{url}?window=30d"))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["days"].as_array().unwrap().len(), 30);
+
+	let response = client
+		.get(format!("{url}?window=1y"))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn items_are_used_from_the_inventory() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	let mut conn = server.get_conn();
+	let axe = items::get_by_name(&mut conn, "Lumberjack's Axe").expect("Items are seeded");
+	player_items::add_items(&mut conn, &user.id, &axe.id, 1).unwrap();
+
+	let response = client
+		.get(format!("{}/game/items", &server.address))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["items"][0]["id"], axe.id.to_string());
+	assert_eq!(body["items"][0]["quantity"], 1);
+
+	let use_url = format!("{}/game/items/{}/use", &server.address, axe.id);
+	let response = client
+		.post(&use_url)
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["remaining"], 0);
+	assert_eq!(body["boost"]["effect"], "+50% wood production");
+
+	let response = client
+		.post(&use_url)
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+	let response = client
+		.get(format!("{}/game/items", &server.address))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["items"], json!([]), "Used up items are left out");
+}
+
+#[tokio::test]
+async fn heroes_are_recruited_trained_and_assigned() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	let mut conn = server.get_conn();
+	resources::add(&mut conn, &user.id, &(0, 0, 0, 5000)).unwrap();
+	let marshal = heroes::get_by_name(&mut conn, "Aldric the Marshal").expect("Heroes are seeded");
+	let url = format!("{}/game/heroes/{}", &server.address, marshal.id);
+
+	let response = client
+		.post(format!("{url}/train"))
+		.bearer_auth(bearer.token())
+		.json(&json!({ "xp": 100 }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(
+		response.status(),
+		StatusCode::NOT_FOUND,
+		"Not recruited yet"
+	);
+
+	let response = client
+		.post(format!("{url}/recruit"))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["recruited"]["level"], 1);
+	assert_eq!(body["recruited"]["next_level_xp"], 100);
+
+	let response = client
+		.post(format!("{url}/train"))
+		.bearer_auth(bearer.token())
+		.json(&json!({ "xp": 100 }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["levels_gained"], 1);
+	assert_eq!(body["hero"]["effect"], "+10% combat strength");
+
+	let response = client
+		.put(format!("{url}/assignment"))
+		.bearer_auth(bearer.token())
+		.json(&json!({}))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["hero"]["recruited"]["leads_army"], true);
+
+	let response = client
+		.delete(format!("{url}/assignment"))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let response = client
+		.get(format!("{}/game/heroes", &server.address))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	let heroes = body["heroes"].as_array().unwrap();
+	let listed = heroes
+		.iter()
+		.find(|hero| hero["id"] == marshal.id.to_string())
+		.unwrap();
+	assert_eq!(listed["recruited"]["leads_army"], false);
+	assert!(
+		heroes
+			.iter()
+			.any(|hero| hero["recruited"] == serde_json::Value::Null)
+	);
+}
+
+#[tokio::test]
+async fn limited_event_shops_sell_for_the_event_currency() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	let mut conn = server.get_conn();
+	let axe = items::get_by_name(&mut conn, "Lumberjack's Axe").expect("Items are seeded");
+
+	let response = client
+		.post(format!("{}/admin/events", &server.admin_address))
+		.header("x-admin-key", "dev-admin-key")
+		.json(&json!({
+			"name": "Lumber Week",
+			"description": "Upgrade your city",
+			"currency_name": "Logs",
+			"starts_at": Utc::now() - TimeDelta::minutes(1),
+			"ends_at": Utc::now() + TimeDelta::days(7),
+			"leftover_resource": "wood",
+			"leftover_rate": 10,
+			"objectives": [{
+				"kind": "upgrade_completed",
+				"description": "Complete an upgrade",
+				"target": 1,
+				"reward": 3,
+			}],
+			"offers": [{ "price": 3, "item_id": axe.id, "quantity": 2 }],
+		}))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CREATED);
+	let created: serde_json::Value = response.json().await.unwrap();
+	let event_id = created["id"].as_str().unwrap().to_string();
+	let offer_id = created["offer_ids"][0].as_str().unwrap().to_string();
+
+	let purchase_url = format!(
+		"{}/game/limited-events/{}/offers/{}/purchase",
+		&server.address, event_id, offer_id
+	);
+	let response = client
+		.post(&purchase_url)
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+	record_progress(
+		&mut conn,
+		&GameEvent::UpgradeCompleted {
+			player_id: user.id,
+			player_building_id: uuid::Uuid::new_v4(),
+			level: 2,
+		},
+		Utc::now(),
+	)
+	.unwrap();
+	let response = client
+		.get(format!("{}/game/limited-events", &server.address))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["events"][0]["id"], event_id);
+	assert_eq!(body["events"][0]["balance"], 3);
+	assert_eq!(body["events"][0]["objectives"][0]["completions"], 1);
+
+	let response = client
+		.post(&purchase_url)
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["balance"], 0);
+	let inventory = player_items::get_for_player(&mut conn, &user.id).unwrap();
+	assert_eq!(inventory[0].0.quantity, 2);
+}
+
+#[tokio::test]
+async fn chat_is_posted_read_and_moderated() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let mut conn = server.get_conn();
+	let talker = server.create_test_user(Some(FactionCode::Human));
+	let moderator = server.create_named_user("test_moderator", Some(FactionCode::Elf));
+	players::set_role(&mut conn, &moderator.id, PlayerRole::Moderator).unwrap();
+	let talker_bearer = server.create_bearer_token(&talker.id);
+	let moderator_bearer = server.create_bearer_token(&moderator.id);
+	let global_url = format!("{}/game/chat/global", &server.address);
+	let mute_url = format!("{}/game/chat/mutes/{}", &server.address, talker.id);
+
+	let response = client
+		.post(&global_url)
+		.bearer_auth(talker_bearer.token())
+		.json(&json!({ "body": "Hello world" }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CREATED);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["channel"], "global");
+	assert_eq!(body["player_id"], talker.id.to_string());
+
+	let response = client
+		.get(format!("{global_url}?limit=10"))
+		.bearer_auth(moderator_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["messages"][0]["body"], "Hello world");
+	assert_eq!(body["messages"][0]["player_name"], talker.name);
+
+	// The alliance channel takes an alliance, and unknown channels do not exist
+	let response = client
+		.get(format!("{}/game/chat/alliance", &server.address))
+		.bearer_auth(talker_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CONFLICT);
+	let response = client
+		.get(format!("{}/game/chat/trade", &server.address))
+		.bearer_auth(talker_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+	// Only moderators mute players
+	let response = client
+		.put(&mute_url)
+		.bearer_auth(talker_bearer.token())
+		.json(&json!({ "minutes": 10 }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::FORBIDDEN);
+	let response = client
+		.put(&mute_url)
+		.bearer_auth(moderator_bearer.token())
+		.json(&json!({ "minutes": 10, "reason": "Spam" }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["muted_by"], moderator.id.to_string());
+
+	let response = client
+		.post(&global_url)
+		.bearer_auth(talker_bearer.token())
+		.json(&json!({ "body": "Still here" }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+	let response = client
+		.delete(&mute_url)
+		.bearer_auth(moderator_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::NO_CONTENT);
+	let response = client
+		.post(&global_url)
+		.bearer_auth(talker_bearer.token())
+		.json(&json!({ "body": "Sorry" }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn friends_are_requested_listed_and_blocked() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let player = server.create_test_user(Some(FactionCode::Human));
+	let friend = server.create_named_user("test_friend", Some(FactionCode::Elf));
+	let player_bearer = server.create_bearer_token(&player.id);
+	let friend_bearer = server.create_bearer_token(&friend.id);
+	let friends_url = format!("{}/game/friends", &server.address);
+
+	let response = client
+		.post(&friends_url)
+		.bearer_auth(player_bearer.token())
+		.json(&json!({ "player_id": friend.id }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CREATED);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["status"], "requested");
+
+	let response = client
+		.post(format!("{}/{}/accept", &friends_url, player.id))
+		.bearer_auth(friend_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["status"], "accepted");
+
+	// The friend made a request just now, so they are online
+	let response = client
+		.get(&friends_url)
+		.bearer_auth(player_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["friends"][0]["name"], friend.name);
+	assert_eq!(body["friends"][0]["online"], true);
+
+	let response = client
+		.put(format!("{}/game/blocks/{}", &server.address, player.id))
+		.bearer_auth(friend_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let response = client
+		.post(format!("{}/game/mail", &server.address))
+		.bearer_auth(player_bearer.token())
+		.json(&json!({ "recipient_id": friend.id, "subject": "Why?", "body": "We were friends" }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+	let response = client
+		.delete(format!("{}/game/blocks/{}", &server.address, player.id))
+		.bearer_auth(friend_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::NO_CONTENT);
+	let response = client
+		.get(&friends_url)
+		.bearer_auth(friend_bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["friends"], json!([]));
+	assert_eq!(body["blocked"], json!([]));
+}
+
+#[tokio::test]
+async fn settlements_are_founded_and_selected() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let player = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&player.id);
+	let settlements_url = format!("{}/game/settlements", &server.address);
+	let buildings_url = format!("{}/game/buildings", &server.address);
+
+	let response = client
+		.post(&settlements_url)
+		.bearer_auth(bearer.token())
+		.json(&json!({ "name": "Northwatch" }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CONFLICT);
+
+	let mut conn = server.get_conn();
+	let keep_id: i32 = building::table
+		.filter(building::name.eq("Keep"))
+		.select(building::id)
+		.first(&mut conn)
+		.unwrap();
+	diesel::update(
+		player_building::table
+			.filter(player_building::player_id.eq(player.id))
+			.filter(player_building::building_id.eq(keep_id)),
+	)
+	.set(player_building::level.eq(5))
+	.execute(&mut conn)
+	.unwrap();
+
+	let response = client
+		.post(&settlements_url)
+		.bearer_auth(bearer.token())
+		.json(&json!({ "name": "Northwatch" }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CREATED);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["is_capital"], false);
+	let settlement_id = body["id"].as_str().unwrap().to_string();
+
+	let response = client
+		.get(&settlements_url)
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["settlements"][0]["is_capital"], true);
+	assert_eq!(body["settlements"][1]["name"], "Northwatch");
+
+	// The header picks the settlement, the capital is used without it
+	let response = client
+		.get(&buildings_url)
+		.bearer_auth(bearer.token())
+		.header("X-Settlement-Id", &settlement_id)
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	let buildings = body.as_array().unwrap();
+	assert!(!buildings.is_empty());
+	assert!(
+		buildings
+			.iter()
+			.all(|bld| bld["settlement_id"] == settlement_id.as_str())
+	);
+	let response = client
+		.get(&buildings_url)
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert!(
+		body.as_array()
+			.unwrap()
+			.iter()
+			.all(|bld| bld["settlement_id"] != settlement_id.as_str())
+	);
+
+	// Settlements of other players cannot be picked
+	let other = server.create_named_user("other_settler", Some(FactionCode::Orc));
+	let other_bearer = server.create_bearer_token(&other.id);
+	let response = client
+		.get(&buildings_url)
+		.bearer_auth(other_bearer.token())
+		.header("X-Settlement-Id", &settlement_id)
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+	let response = client
+		.get(&buildings_url)
+		.bearer_auth(bearer.token())
+		.header("X-Settlement-Id", "not-a-settlement")
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn leaderboards_rank_the_last_snapshot() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	let url = format!("{}/game/leaderboard", &server.address);
+
+	let response = client
+		.get(format!("{url}/score"))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["power"], 0);
+	assert!(body["economy"].as_i64().unwrap() > 0);
+
+	let mut conn = server.get_conn();
+	snapshot(&mut conn, Utc::now()).unwrap();
+	let response = client
+		.get(format!("{url}?category=economy&per_page=1"))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["category"], "economy");
+	assert_eq!(body["entries"].as_array().unwrap().len(), 1);
+	assert_eq!(body["per_page"], 1);
+	assert!(body["own"]["rank"].as_i64().unwrap() >= 1);
+
+	let response = client
+		.get(format!("{url}?category=wealth"))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn quests_are_listed_and_claimed() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	let url = format!("{}/game/quests", &server.address);
+
+	let response = client
+		.get(&url)
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["quests"].as_array().unwrap().len(), 1);
+	assert_eq!(body["quests"][0]["id"], "build_farm");
+	assert_eq!(body["quests"][0]["state"], "active");
+
+	let response = client
+		.post(format!("{url}/build_farm/claim"))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CONFLICT);
+
+	let farms = building::table
+		.filter(building::name.eq("Farm"))
+		.select(building::id);
+	diesel::update(
+		player_building::table
+			.filter(player_building::player_id.eq(user.id))
+			.filter(player_building::building_id.eq_any(farms)),
+	)
+	.set(player_building::level.eq(1))
+	.execute(&mut server.get_conn())
+	.unwrap();
+
+	// The reward waiting to be claimed shows up in the badges until it is
+	let unclaimed_rewards = || async {
+		let response = client
+			.get(format!("{}/game/badges", &server.address))
+			.bearer_auth(bearer.token())
+			.send()
+			.await
+			.expect("Failed to execute request.");
+		let body: serde_json::Value = response.json().await.unwrap();
+		body["unclaimed_quest_rewards"].clone()
+	};
+	assert_eq!(unclaimed_rewards().await, 1);
+
+	let response = client
+		.post(format!("{url}/build_farm/claim"))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["state"], "claimed");
+	assert_eq!(body["rewards"]["wood"], 200);
+	assert_eq!(unclaimed_rewards().await, 0);
+
+	let response = client
+		.post(format!("{url}/slay_dragon/claim"))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn seasons_expose_the_current_season_and_history() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	let url = format!("{}/game/seasons", &server.address);
+
+	let response = client
+		.get(format!("{url}/current"))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["season"], serde_json::Value::Null);
+	assert_eq!(body["legacy_points"], 0);
+
+	let request = json!({
+		"name": "Age of Iron",
+		"starts_at": Utc::now() - TimeDelta::minutes(1),
+		"ends_at": Utc::now() + TimeDelta::days(30),
+	});
+	let response = client
+		.post(format!("{}/admin/seasons", &server.admin_address))
+		.header("x-admin-key", "dev-admin-key")
+		.json(&request)
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CREATED);
+	let created: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(created["number"], 1);
+	let response = client
+		.post(format!("{}/admin/seasons", &server.admin_address))
+		.header("x-admin-key", "dev-admin-key")
+		.json(&request)
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CONFLICT);
+
+	let season_id: uuid::Uuid = created["id"].as_str().unwrap().parse().unwrap();
+	let ends_at: DateTime<Utc> = serde_json::from_value(created["ends_at"].clone()).unwrap();
+	end_season(&mut server.get_conn(), &season_id, ends_at).unwrap();
+
+	let response = client
+		.get(&url)
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["seasons"][0]["id"], created["id"]);
+	assert!(body["seasons"][0]["ended_at"].is_string());
+
+	let response = client
+		.get(format!("{url}/{season_id}?category=economy"))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["category"], "economy");
+	assert_eq!(body["own"]["id"], user.id.to_string());
+	// The seeded test accounts are ranked too
+	assert!(body["own"]["rank"].as_i64().unwrap() >= 1);
+
+	let response = client
+		.get(format!("{url}/current"))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["season"], serde_json::Value::Null);
+	assert!(body["legacy_points"].as_i64().unwrap() > 0);
+	assert_eq!(body["seasons_played"], 1);
+
+	let response = client
+		.get(format!("{url}/{}", uuid::Uuid::new_v4()))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn units_are_upgraded_at_the_blacksmith() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	let mut conn = server.get_conn();
+	let infantry = units::find_by_name(&mut conn, "Infantry").unwrap().unwrap();
+	player_units::add_units(&mut conn, &user.id, &infantry.id, 5).unwrap();
+	resources::add(&mut conn, &user.id, &(1000, 1000, 0, 1000)).unwrap();
+	let upgrade = json!({ "unit_id": infantry.id, "quantity": 3 });
+
+	let response = client
+		.post(format!("{}/game/units/upgrade", &server.address))
+		.bearer_auth(bearer.token())
+		.json(&upgrade)
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CONFLICT, "No Blacksmith yet");
+
+	for (name, faction) in [
+		("Blacksmith", FactionCode::Neutral),
+		("Academy", FactionCode::Human),
+	] {
+		let building_id: i32 = building::table
+			.filter(building::name.eq(name))
+			.filter(building::faction.eq(faction))
+			.select(building::id)
+			.first(&mut conn)
+			.unwrap();
+		player_buildings::construct(
+			&mut conn,
+			NewPlayerBuilding {
+				player_id: user.id,
+				building_id,
+				level: Some(1),
+				upgrade_finishes_at: None,
+				settlement_id: None,
+			},
+		)
+		.unwrap();
+	}
+
+	let response = client
+		.get(format!("{}/game/units/upgrades", &server.address))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	let listed = body["upgrades"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.find(|upgrade| upgrade["unit_name"] == "Infantry")
+		.unwrap();
+	assert_eq!(listed["upgraded_unit_name"], "Veteran Infantry");
+	assert_eq!(listed["owned"], 5);
+	assert_eq!(listed["unlocked"], true);
+
+	let response = client
+		.post(format!("{}/game/units/upgrade", &server.address))
+		.bearer_auth(bearer.token())
+		.json(&upgrade)
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["upgraded_total"], 3);
+	assert_eq!(body["resources_spent"]["food"], 60);
+
+	let response = client
+		.get(format!("{}/game/units/inventory", &server.address))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	let tiers: Vec<_> = body["units"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.map(|unit| {
+			(
+				unit["tier"].as_i64().unwrap(),
+				unit["quantity"].as_i64().unwrap(),
+			)
+		})
+		.collect();
+	assert!(tiers.contains(&(1, 2)) && tiers.contains(&(2, 3)));
+}
+
+#[tokio::test]
+async fn army_presets_are_saved_and_trained() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	let mut conn = server.get_conn();
+	let infantry = units::find_by_name(&mut conn, "Infantry").unwrap().unwrap();
+	resources::add(&mut conn, &user.id, &(1000, 1000, 0, 1000)).unwrap();
+	let building_id: i32 = building::table
+		.filter(building::name.eq("Barracks"))
+		.filter(building::faction.eq(FactionCode::Human))
+		.select(building::id)
+		.first(&mut conn)
+		.unwrap();
+	player_buildings::construct(
+		&mut conn,
+		NewPlayerBuilding {
+			player_id: user.id,
+			building_id,
+			level: Some(3),
+			upgrade_finishes_at: None,
+			settlement_id: None,
+		},
+	)
+	.unwrap();
+
+	let response = client
+		.post(format!("{}/game/units/presets", &server.address))
+		.bearer_auth(bearer.token())
+		.json(&json!({ "name": "Wall", "units": [{ "unit_id": infantry.id, "quantity": 4 }] }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CREATED);
+	let preset: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(preset["name"], "Wall");
+	let preset_id = preset["id"].as_str().unwrap().to_string();
+
+	let response = client
+		.get(format!("{}/game/units/presets", &server.address))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["presets"][0]["units"][0]["quantity"], 4);
+
+	let response = client
+		.post(format!(
+			"{}/game/units/train/preset/{}",
+			&server.address, preset_id
+		))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CREATED);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["trainings"][0]["unit_name"], "Infantry");
+	assert_eq!(body["trainings"][0]["quantity"], 4);
+	assert_eq!(body["resources_spent"]["food"], 80);
+
+	let response = client
+		.delete(format!(
+			"{}/game/units/presets/{}",
+			&server.address, preset_id
+		))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::NO_CONTENT);
+	let response = client
+		.post(format!(
+			"{}/game/units/train/preset/{}",
+			&server.address, preset_id
+		))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn units_are_disbanded_for_a_partial_refund() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	let mut conn = server.get_conn();
+	let infantry = units::find_by_name(&mut conn, "Infantry").unwrap().unwrap();
+	player_units::add_units(&mut conn, &user.id, &infantry.id, 5).unwrap();
+
+	let response = client
+		.post(format!("{}/game/units/disband", &server.address))
+		.bearer_auth(bearer.token())
+		.json(&json!({ "unit_id": infantry.id, "quantity": 6 }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(
+		response.status(),
+		StatusCode::UNPROCESSABLE_ENTITY,
+		"Only 5 units owned"
+	);
+
+	let response = client
+		.post(format!("{}/game/units/disband", &server.address))
+		.bearer_auth(bearer.token())
+		.json(&json!({ "unit_id": infantry.id, "quantity": 2 }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["remaining"], 3);
+	assert_eq!(body["refunded"]["food"], 20);
+	assert_eq!(body["refunded"]["wood"], 10);
+}
+
+#[tokio::test]
+async fn units_are_trained_in_a_batch() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	let mut conn = server.get_conn();
+	resources::add(&mut conn, &user.id, &(1000, 1000, 0, 1000)).unwrap();
+	let mut orders = Vec::new();
+	for (building_name, unit_name) in [("Barracks", "Infantry"), ("Stables", "Cavalry")] {
+		let building_id: i32 = building::table
+			.filter(building::name.eq(building_name))
+			.filter(building::faction.eq(FactionCode::Human))
+			.select(building::id)
+			.first(&mut conn)
+			.unwrap();
+		let player_building = player_buildings::construct(
+			&mut conn,
+			NewPlayerBuilding {
+				player_id: user.id,
+				building_id,
+				level: Some(3),
+				upgrade_finishes_at: None,
+				settlement_id: None,
+			},
+		)
+		.unwrap();
+		let unit = units::find_by_name(&mut conn, unit_name).unwrap().unwrap();
+		orders.push(json!({
+			"building_id": player_building.id,
+			"unit_id": unit.id,
+			"quantity": 2,
+		}));
+	}
+
+	let response = client
+		.post(format!("{}/game/units/train/batch", &server.address))
+		.bearer_auth(bearer.token())
+		.json(&json!({ "orders": orders }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CREATED);
+	let body: serde_json::Value = response.json().await.unwrap();
+	let names: Vec<_> = body["trainings"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.map(|training| training["unit_name"].as_str().unwrap())
+		.collect();
+	assert_eq!(names, vec!["Infantry", "Cavalry"]);
+	assert_eq!(body["resources_spent"]["food"], 100);
+
+	let response = client
+		.post(format!("{}/game/units/train/batch", &server.address))
+		.bearer_auth(bearer.token())
+		.json(&json!({ "orders": [] }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn sequential_training_queue_reports_chained_etas() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	let mut conn = server.get_conn();
+	resources::add(&mut conn, &user.id, &(1000, 1000, 0, 0)).unwrap();
+	let building_id: i32 = building::table
+		.filter(building::name.eq("Barracks"))
+		.filter(building::faction.eq(FactionCode::Human))
+		.select(building::id)
+		.first(&mut conn)
+		.unwrap();
+	let barracks = player_buildings::construct(
+		&mut conn,
+		NewPlayerBuilding {
+			player_id: user.id,
+			building_id,
+			level: Some(3),
+			upgrade_finishes_at: None,
+			settlement_id: None,
+		},
+	)
+	.unwrap();
+	let infantry = units::find_by_name(&mut conn, "Infantry").unwrap().unwrap();
+	let set_mode = |mode: &'static str| {
+		client
+			.put(format!("{}/game/units/training-mode", &server.address))
+			.bearer_auth(bearer.token())
+			.json(&json!({ "building_id": barracks.id, "mode": mode }))
+			.send()
+	};
+
+	let response = set_mode("sequential")
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["training_mode"], "sequential");
+
+	for quantity in [2, 3] {
+		let response = client
+			.post(format!("{}/game/units/train", &server.address))
+			.bearer_auth(bearer.token())
+			.json(&json!({
+				"building_id": barracks.id,
+				"unit_id": infantry.id,
+				"quantity": quantity,
+			}))
+			.send()
+			.await
+			.expect("Failed to execute request.");
+		assert_eq!(response.status(), StatusCode::CREATED);
+	}
+
+	let response = client
+		.get(format!("{}/game/units/queue", &server.address))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: serde_json::Value = response.json().await.unwrap();
+	let entries = body["entries"].as_array().unwrap();
+	assert_eq!(entries.len(), 2);
+	assert_eq!(entries[0]["status"], "in_progress");
+	assert_eq!(entries[1]["status"], "pending");
+	assert_eq!(entries[1]["progress_percent"], 0.0);
+	assert_eq!(
+		entries[1]["estimated_start"],
+		entries[0]["estimated_completion"]
+	);
+
+	// The mode cannot change while trainings are queued
+	let response = set_mode("parallel")
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CONFLICT);
+}
@@ -0,0 +1,96 @@
+//! Tests for the GraphQL endpoint, built with the `graphql` feature.
+
+use empire::domain::factions::FactionCode;
+use reqwest::{Client, StatusCode};
+use serde_json::json;
+
+use crate::common::TestApp;
+
+const GAME_STATE_QUERY: &str = r#"
+	query {
+		me {
+			name
+			faction
+			settlements {
+				name
+				isCapital
+				resources { food foodCap }
+				buildings { name level }
+			}
+			units { totalUnits }
+			queue {
+				construction { capacity freeSlots }
+				training { totalEntries }
+			}
+		}
+	}
+"#;
+
+#[tokio::test]
+async fn graphql_requires_authentication() {
+	let server = TestApp::new();
+
+	let response = Client::new()
+		.post(format!("{}/graphql", &server.address))
+		.json(&json!({ "query": GAME_STATE_QUERY }))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn nested_game_state_is_fetched_at_once() {
+	let server = TestApp::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+
+	let response = Client::new()
+		.post(format!("{}/graphql", &server.address))
+		.bearer_auth(bearer.token())
+		.json(&json!({ "query": GAME_STATE_QUERY }))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert!(body.get("errors").is_none(), "{body}");
+	let me = &body["data"]["me"];
+	assert_eq!(me["name"], user.name.as_str());
+	assert_eq!(me["faction"], "HUMAN");
+
+	let capital = &me["settlements"][0];
+	assert_eq!(capital["isCapital"], true);
+	assert!(
+		capital["resources"]["foodCap"].as_i64().unwrap() > 0,
+		"{capital}"
+	);
+	assert!(
+		!capital["buildings"].as_array().unwrap().is_empty(),
+		"Capital has no buildings"
+	);
+	assert_eq!(me["units"]["totalUnits"], 0);
+	assert!(me["queue"]["construction"]["capacity"].as_i64().unwrap() > 0);
+	assert_eq!(me["queue"]["training"]["totalEntries"], 0);
+}
+
+#[tokio::test]
+async fn invalid_queries_are_answered_with_errors() {
+	let server = TestApp::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+
+	let response = Client::new()
+		.post(format!("{}/graphql", &server.address))
+		.bearer_auth(bearer.token())
+		.json(&json!({ "query": "{ me { password } }" }))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert!(body["data"].is_null(), "{body}");
+	assert!(!body["errors"].as_array().unwrap().is_empty());
+}
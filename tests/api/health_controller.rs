@@ -1,6 +1,7 @@
 use axum::body::Body;
 use axum::http::{Request, StatusCode};
 use empire::controllers::health::{HealthCheckBody, LivenessCheckBody, ReadyCheckBody};
+use empire::domain::factions::FactionCode;
 use http_body_util::BodyExt;
 use tower::ServiceExt;
 
@@ -87,3 +88,52 @@ async fn health_check_with_server() {
 	let body: HealthCheckBody = response.json().await.unwrap();
 	assert_eq!(body.status, "OK");
 }
+
+#[tokio::test]
+async fn metrics_are_tagged_with_route_templates() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+
+	let response = client
+		.get(format!(
+			"{}/game/jobs/{}",
+			&server.address,
+			uuid::Uuid::new_v4()
+		))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+	let response = client
+		.get(format!("{}/game", &server.address))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+	let response = client
+		.get(format!("{}/metrics", &server.admin_address))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body = response.text().await.unwrap();
+	assert!(
+		body.contains(
+			"empire_http_request_duration_seconds_count{area=\"game\",method=\"GET\",\
+			 route=\"/game/jobs/{job_id}\",status_class=\"4xx\",auth=\"authenticated\"} 1"
+		),
+		"{body}"
+	);
+	assert!(
+		body.contains(
+			"empire_http_request_duration_seconds_count{area=\"game\",method=\"GET\",\
+			 route=\"/game\",status_class=\"4xx\",auth=\"anonymous\"} 1"
+		),
+		"{body}"
+	);
+	assert!(!body.contains(&user.id.to_string()));
+}
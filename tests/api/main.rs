@@ -1,9 +1,20 @@
+mod admin_controller;
 mod auth_controller;
+mod authorization;
+mod client;
+mod docs_controller;
 mod faction_controller;
 mod game_controller;
+#[cfg(feature = "graphql")]
+mod graphql;
 mod health_controller;
+mod observer_controller;
 mod player_controller;
+mod rate_limits;
+mod sse;
+mod transport;
 mod user_controller;
+mod websocket;
 
 #[path = "../common/mod.rs"]
 mod common;
@@ -0,0 +1,261 @@
+//! Tests for the read-only observer accounts used to cast tournaments.
+//!
+//! Observers are created through the admin API, authenticate with their own token, and
+//! only see the selected players that opted in, within their scopes.
+
+use chrono::Utc;
+use empire::db::audit_log;
+use empire::domain::combat::Loot;
+use empire::domain::factions::FactionCode;
+use empire::domain::player::Player;
+use empire::game::combat::combat_operations::{BattleOutcome, record_battle};
+use empire::game::observer_operations::{CREATE_OBSERVER_ACTION, REVOKE_OBSERVER_ACTION};
+use reqwest::{Client, StatusCode};
+use serde_json::{Value, json};
+
+use crate::common::TestApp;
+
+const ADMIN_KEY: &str = "dev-admin-key";
+
+/// Creates an observer through the admin API and returns its response body.
+async fn create_observer(server: &TestApp, body: Value) -> (StatusCode, Value) {
+	let response = Client::new()
+		.post(format!("{}/admin/observers", &server.admin_address))
+		.header("x-admin-key", ADMIN_KEY)
+		.header("x-admin-operator", "alice")
+		.json(&body)
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	let status = response.status();
+	(status, response.json().await.unwrap_or_default())
+}
+
+/// Lets observers watch the player through their privacy settings.
+async fn allow_observers(server: &TestApp, player: &Player) {
+	let bearer = server.create_bearer_token(&player.id);
+	let response = Client::new()
+		.patch(format!("{}/player/privacy", &server.address))
+		.bearer_auth(bearer.token())
+		.json(&json!({ "allow_observers": true }))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: Value = response.json().await.unwrap();
+	assert_eq!(body["allow_observers"], true);
+}
+
+#[tokio::test]
+async fn observers_only_see_consenting_players_within_their_scopes() {
+	let server = TestApp::new();
+	let client = Client::new();
+	let champion = server.create_named_user("test_champion", Some(FactionCode::Human));
+	let shy = server.create_named_user("test_shy", Some(FactionCode::Orc));
+	let outsider = server.create_named_user("test_outsider", Some(FactionCode::Elf));
+	allow_observers(&server, &champion).await;
+	allow_observers(&server, &outsider).await;
+
+	let (status, _) = create_observer(&server, json!({ "name": "caster", "scopes": [] })).await;
+	assert_eq!(status, StatusCode::BAD_REQUEST, "Scopes are required");
+	let (status, _) = create_observer(
+		&server,
+		json!({ "name": "caster", "scopes": ["city"], "player_ids": [uuid::Uuid::new_v4()] }),
+	)
+	.await;
+	assert_eq!(status, StatusCode::BAD_REQUEST, "Players must exist");
+
+	let (status, created) = create_observer(
+		&server,
+		json!({
+			"name": "caster",
+			"scopes": ["city", "leaderboard"],
+			"player_ids": [champion.id, shy.id],
+		}),
+	)
+	.await;
+	assert_eq!(status, StatusCode::CREATED);
+	let token = created["token"].as_str().unwrap().to_string();
+	let observer_id = created["id"].as_str().unwrap().to_string();
+	assert_eq!(created["active"], true);
+
+	// Only selected players that opted in are visible
+	let response = client
+		.get(format!("{}/observe/players", &server.address))
+		.bearer_auth(&token)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: Value = response.json().await.unwrap();
+	assert_eq!(body["players"].as_array().unwrap().len(), 1);
+	assert_eq!(body["players"][0]["id"], champion.id.to_string());
+
+	let city_url =
+		|player: &Player| format!("{}/observe/players/{}/city", &server.address, player.id);
+	let response = client
+		.get(city_url(&champion))
+		.bearer_auth(&token)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: Value = response.json().await.unwrap();
+	assert!(!body["buildings"].as_array().unwrap().is_empty());
+	for player in [&shy, &outsider] {
+		let response = client
+			.get(city_url(player))
+			.bearer_auth(&token)
+			.send()
+			.await
+			.unwrap();
+		assert_eq!(response.status(), StatusCode::NOT_FOUND);
+	}
+
+	let response = client
+		.get(format!(
+			"{}/observe/players/{}/battles",
+			&server.address, champion.id
+		))
+		.bearer_auth(&token)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::FORBIDDEN, "No battles scope");
+
+	let response = client
+		.get(format!("{}/observe/leaderboard", &server.address))
+		.bearer_auth(&token)
+		.send()
+		.await
+		.unwrap();
+	let body: Value = response.json().await.unwrap();
+	assert_eq!(body["entries"].as_array().unwrap().len(), 1);
+	assert_eq!(body["entries"][0]["rank"], 1);
+
+	// Observers cannot change anything
+	let response = client
+		.post(format!("{}/observe/players", &server.address))
+		.bearer_auth(&token)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+	// Observer and player credentials do not mix
+	let response = client
+		.get(format!("{}/game", &server.address))
+		.bearer_auth(&token)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+	let bearer = server.create_bearer_token(&champion.id);
+	let response = client
+		.get(format!("{}/observe/players", &server.address))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+	let response = client
+		.delete(format!(
+			"{}/admin/observers/{}",
+			&server.admin_address, observer_id
+		))
+		.header("x-admin-key", ADMIN_KEY)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: Value = response.json().await.unwrap();
+	assert_eq!(body["active"], false);
+	let response = client
+		.get(format!("{}/observe/players", &server.address))
+		.bearer_auth(&token)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "Revoked");
+
+	let actions: Vec<String> = audit_log::get_recent(&mut server.get_conn(), 10)
+		.unwrap()
+		.into_iter()
+		.map(|entry| entry.action)
+		.collect();
+	assert!(actions.contains(&CREATE_OBSERVER_ACTION.to_string()));
+	assert!(actions.contains(&REVOKE_OBSERVER_ACTION.to_string()));
+}
+
+#[tokio::test]
+async fn observers_with_the_battles_scope_see_battle_reports() {
+	let server = TestApp::new();
+	let client = Client::new();
+	let attacker = server.create_named_user("test_attacker", Some(FactionCode::Human));
+	let defender = server.create_named_user("test_defender", Some(FactionCode::Orc));
+	allow_observers(&server, &attacker).await;
+	allow_observers(&server, &defender).await;
+	record_battle(
+		&mut server.get_conn(),
+		BattleOutcome {
+			attacker_id: attacker.id,
+			defender_id: defender.id,
+			winner_id: Some(defender.id),
+			attacker_losses: vec![],
+			defender_losses: vec![],
+			loot: Loot::default(),
+			modifiers: vec![],
+			fought_at: Utc::now(),
+		},
+	)
+	.expect("Failed to record battle");
+
+	let (status, created) = create_observer(
+		&server,
+		json!({
+			"name": "caster",
+			"scopes": ["battles", "leaderboard"],
+			"player_ids": [attacker.id, defender.id],
+		}),
+	)
+	.await;
+	assert_eq!(status, StatusCode::CREATED);
+	let token = created["token"].as_str().unwrap().to_string();
+
+	let response = client
+		.get(format!(
+			"{}/observe/players/{}/battles",
+			&server.address, attacker.id
+		))
+		.bearer_auth(&token)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: Value = response.json().await.unwrap();
+	assert_eq!(body["total"], 1);
+	assert_eq!(body["reports"][0]["winner_id"], defender.id.to_string());
+
+	// Both have the same starter buildings, so the win decides the ranking
+	let response = client
+		.get(format!("{}/observe/leaderboard", &server.address))
+		.bearer_auth(&token)
+		.send()
+		.await
+		.unwrap();
+	let body: Value = response.json().await.unwrap();
+	assert_eq!(body["entries"][0]["player"]["id"], defender.id.to_string());
+	assert_eq!(body["entries"][0]["battles_won"], 1);
+
+	let response = client
+		.get(format!(
+			"{}/observe/players/{}/city",
+			&server.address, attacker.id
+		))
+		.bearer_auth(&token)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::FORBIDDEN, "No city scope");
+}
@@ -1,8 +1,19 @@
+use std::time::Duration;
+
 use axum::http::{StatusCode, header};
 use diesel::RunQueryDsl;
 use diesel::prelude::*;
+use empire::auth::api_key_operations::API_KEY_HEADER;
+use empire::auth::session_operations;
+use empire::controllers::player::{
+	AccountDeletionResponse, ApiKeyResponse, ChangePasswordResponse, CreatedApiKeyResponse,
+	EmailResponse, FactionChangeResponse, FactionSelectionResponse, PlayerProfileResponse,
+	PrivacySettingsResponse,
+};
+use empire::db::{player_privacy, players};
+use empire::domain::factions::FactionCode;
 use empire::domain::player::buildings::PlayerBuilding;
-use empire::schema::player_building;
+use empire::schema::{building, player_building};
 use serde_json::json;
 
 use crate::common::TestApp;
@@ -36,3 +47,412 @@ async fn join_faction_success() {
 		"User should have buildings after joining faction"
 	);
 }
+
+#[tokio::test]
+async fn changing_faction_is_gated_by_the_cooldown() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	let url = format!("{}/player/me/faction", &server.address);
+
+	let response = client
+		.post(&url)
+		.bearer_auth(bearer.token())
+		.json(&json!({"faction": "neutral"}))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+	let response = client
+		.post(&url)
+		.bearer_auth(bearer.token())
+		.json(&json!({"faction": "orc"}))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: FactionChangeResponse = response.json().await.unwrap();
+	assert_eq!(body.faction, FactionCode::Orc);
+	assert_eq!(body.previous_faction, FactionCode::Human);
+	assert!(
+		body.converted_buildings > 0,
+		"Starter buildings are converted"
+	);
+
+	// The starter buildings now belong to the orcs
+	let mut conn = server.get_conn();
+	let factions: Vec<FactionCode> = player_building::table
+		.inner_join(building::table)
+		.filter(player_building::player_id.eq(&user.id))
+		.select(building::faction)
+		.load(&mut conn)
+		.unwrap();
+	assert!(factions.iter().all(|faction| *faction == FactionCode::Orc));
+
+	let response = client
+		.post(&url)
+		.bearer_auth(bearer.token())
+		.json(&json!({"faction": "elf"}))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn neutral_players_select_a_faction_before_playing() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(None);
+	let bearer = server.create_bearer_token(&user.id);
+
+	let response = client
+		.get(format!("{}/game/buildings", &server.address))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::FORBIDDEN);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["message"], "Select a faction before playing");
+
+	// The factions can be browsed to make the choice
+	let response = client
+		.get(format!("{}/game/factions", &server.address))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let url = format!("{}/player/me/faction/select", &server.address);
+	let response = client
+		.post(&url)
+		.bearer_auth(bearer.token())
+		.json(&json!({"faction": "dwarf"}))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CREATED);
+	let body: FactionSelectionResponse = response.json().await.unwrap();
+	assert_eq!(body.faction, FactionCode::Dwarf);
+	assert!(body.starter_buildings > 0, "Starter buildings are granted");
+
+	let response = client
+		.get(format!("{}/game/buildings", &server.address))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let response = client
+		.post(&url)
+		.bearer_auth(bearer.token())
+		.json(&json!({"faction": "goblin"}))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn privacy_settings_can_be_changed() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(None);
+	let bearer = server.create_bearer_token(&user.id);
+	let url = format!("{}/player/privacy", &server.address);
+
+	// New players hide nothing
+	let response = client
+		.get(&url)
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: PrivacySettingsResponse = response.json().await.unwrap();
+	assert!(!body.hide_online_status && !body.hide_stats && !body.hide_alliance);
+
+	// Omitted settings are kept
+	for payload in [json!({"hide_stats": true}), json!({"hide_alliance": true})] {
+		let response = client
+			.patch(&url)
+			.bearer_auth(bearer.token())
+			.json(&payload)
+			.send()
+			.await
+			.expect("Failed to execute request.");
+		assert_eq!(response.status(), StatusCode::OK);
+	}
+	let mut conn = server.db_pool.get().unwrap();
+	let privacy = player_privacy::get_by_player_id(&mut conn, &user.id).unwrap();
+	assert!(privacy.hide_stats && privacy.hide_alliance);
+	assert!(!privacy.hide_online_status);
+
+	let response = client
+		.patch(&url)
+		.bearer_auth(bearer.token())
+		.json(&json!({"hide_everything": true}))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert!(response.status().is_client_error());
+}
+
+#[tokio::test]
+async fn api_keys_authenticate_within_their_scope() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_test_user(None);
+	let bearer = server.create_bearer_token(&user.id);
+	let keys_url = format!("{}/player/api-keys", &server.address);
+	let profile_url = format!("{}/player/profile", &server.address);
+	let faction_url = format!("{}/player/faction", &server.address);
+
+	let mut created = Vec::new();
+	for payload in [
+		json!({"name": "stats bot"}),
+		json!({"name": "companion app", "scope": "full"}),
+	] {
+		let response = client
+			.post(&keys_url)
+			.bearer_auth(bearer.token())
+			.json(&payload)
+			.send()
+			.await
+			.expect("Failed to execute request.");
+		assert_eq!(response.status(), StatusCode::CREATED);
+		let body: CreatedApiKeyResponse = response.json().await.unwrap();
+		assert!(body.key.starts_with(&body.api_key.prefix));
+		created.push(body);
+	}
+	let (read_only, full) = (&created[0], &created[1]);
+
+	let listed: Vec<ApiKeyResponse> = client
+		.get(&keys_url)
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.unwrap()
+		.json()
+		.await
+		.unwrap();
+	assert_eq!(listed.len(), 2);
+	assert!(listed.iter().all(|key| key.active));
+
+	// Read-only keys can read but not write
+	let response = client
+		.get(&profile_url)
+		.header(API_KEY_HEADER, &read_only.key)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let profile: PlayerProfileResponse = response.json().await.unwrap();
+	assert_eq!(profile.id, user.id);
+	let response = client
+		.put(&faction_url)
+		.header(API_KEY_HEADER, &read_only.key)
+		.json(&json!({"faction": "human"}))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+	let response = client
+		.put(&faction_url)
+		.header(API_KEY_HEADER, &full.key)
+		.json(&json!({"faction": "human"}))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+	// Keys cannot manage keys
+	let response = client
+		.get(&keys_url)
+		.header(API_KEY_HEADER, &full.key)
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+	let response = client
+		.delete(format!("{}/{}", &keys_url, read_only.api_key.id))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let revoked: ApiKeyResponse = response.json().await.unwrap();
+	assert!(!revoked.active && revoked.revoked_at.is_some());
+
+	for key in [read_only.key.as_str(), "emp_not_a_key"] {
+		let response = client
+			.get(&profile_url)
+			.header(API_KEY_HEADER, key)
+			.send()
+			.await
+			.unwrap();
+		assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+	}
+}
+
+#[tokio::test]
+async fn deleting_the_account_is_cancelled_by_logging_in() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_named_user("leaving", None);
+	let bearer = server.create_bearer_token(&user.id);
+	let me_url = format!("{}/player/me", &server.address);
+
+	let response = client
+		.delete(&me_url)
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::ACCEPTED);
+	let body: AccountDeletionResponse = response.json().await.unwrap();
+	assert!(body.deletion_scheduled_for > chrono::Utc::now() + chrono::TimeDelta::days(13));
+	let mut conn = server.get_conn();
+	let stored = players::get_by_id(&mut conn, &user.id).unwrap();
+	assert_eq!(
+		stored.deletion_scheduled_for,
+		Some(body.deletion_scheduled_for)
+	);
+
+	let response = client
+		.post(format!("{}/login", &server.address))
+		.json(&json!({"username": "leaving", "password": "1234"}))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let stored = players::get_by_id(&mut conn, &user.id).unwrap();
+	assert!(stored.deletion_scheduled_for.is_none());
+}
+
+#[tokio::test]
+async fn credentials_change_only_with_the_current_password() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_named_user("careful", None);
+	let mut conn = server.get_conn();
+	for token in ["laptop", "phone"] {
+		session_operations::create(&mut conn, token.to_string(), &user.id).unwrap();
+	}
+	let cookie = |token: &str| format!("rsession={token}");
+	let password_url = format!("{}/player/me/password", &server.address);
+	let email_url = format!("{}/player/me/email", &server.address);
+	let profile_url = format!("{}/player/profile", &server.address);
+
+	let response = client
+		.post(&password_url)
+		.header(header::COOKIE, cookie("laptop"))
+		.json(&json!({"current_password": "wrong", "new_password": "hunter22"}))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+	let response = client
+		.post(&password_url)
+		.header(header::COOKIE, cookie("laptop"))
+		.json(&json!({"current_password": "1234", "new_password": "hunter22"}))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: ChangePasswordResponse = response.json().await.unwrap();
+	assert_eq!(body.invalidated_sessions, 1);
+	// Only the session that changed the password is still logged in
+	for (token, status) in [
+		("laptop", StatusCode::OK),
+		("phone", StatusCode::UNAUTHORIZED),
+	] {
+		let response = client
+			.get(&profile_url)
+			.header(header::COOKIE, cookie(token))
+			.send()
+			.await
+			.unwrap();
+		assert_eq!(response.status(), status, "session {token}");
+	}
+
+	let response = client
+		.post(&email_url)
+		.header(header::COOKIE, cookie("laptop"))
+		.json(&json!({"current_password": "1234", "email": "careful@example.com"}))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::FORBIDDEN);
+	let response = client
+		.post(&email_url)
+		.header(header::COOKIE, cookie("laptop"))
+		.json(&json!({"current_password": "hunter22", "email": "careful@example.com"}))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	let body: EmailResponse = response.json().await.unwrap();
+	assert_eq!(body.email.as_deref(), Some("careful@example.com"));
+	assert!(!body.email_verified);
+
+	// The profile update no longer changes credentials
+	let response = client
+		.put(&profile_url)
+		.header(header::COOKIE, cookie("laptop"))
+		.json(&json!({"password": "sneaky"}))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn password_changes_revoke_earlier_tokens() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let user = server.create_named_user("robbed", None);
+	let mut conn = server.get_conn();
+	session_operations::create(&mut conn, "owner".to_string(), &user.id).unwrap();
+	let stolen = server.create_bearer_token(&user.id);
+	let profile_url = format!("{}/player/profile", &server.address);
+	// Tokens are issued to the second, so the password changes in a later one
+	tokio::time::sleep(Duration::from_millis(1100)).await;
+
+	let response = client
+		.post(format!("{}/player/me/password", &server.address))
+		.header(header::COOKIE, "rsession=owner")
+		.json(&json!({"current_password": "1234", "new_password": "hunter22"}))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let response = client
+		.get(&profile_url)
+		.bearer_auth(stolen.token())
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["code"], "unauthenticated_error");
+
+	// Tokens issued after the change still work
+	let fresh = server.create_bearer_token(&user.id);
+	let response = client
+		.get(&profile_url)
+		.bearer_auth(fresh.token())
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+}
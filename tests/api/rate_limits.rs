@@ -0,0 +1,72 @@
+//! Tests for the request rate limits of the public API.
+//!
+//! Players are limited by their ID on the protected routes and everyone else by their IP on
+//! the public ones, with the burst and refill rate of `rate_limits` in the configuration.
+
+use empire::domain::factions::FactionCode;
+use reqwest::{Client, StatusCode};
+
+use crate::common::TestApp;
+
+#[tokio::test]
+async fn players_hammering_an_endpoint_are_rate_limited() {
+	let server = TestApp::new();
+	let client = Client::new();
+	let player = server.create_test_user(Some(FactionCode::Human));
+	let other = server.create_named_user("test_patient", Some(FactionCode::Human));
+	let url = format!("{}/game/factions", &server.address);
+	let get_as = |player_id| {
+		client
+			.get(&url)
+			.bearer_auth(server.create_bearer_token(&player_id).token())
+			.send()
+	};
+
+	let first = get_as(player.id).await.unwrap();
+	assert_eq!(first.status(), StatusCode::OK);
+	assert_eq!(first.headers()["x-ratelimit-limit"], "60");
+	assert_eq!(first.headers()["x-ratelimit-remaining"], "59");
+	assert!(first.headers().contains_key("x-ratelimit-reset"));
+
+	// The bucket refills while the requests go out, so it empties after a few more than 60
+	let mut limited = None;
+	for _ in 0..200 {
+		let response = get_as(player.id).await.unwrap();
+		if response.status() == StatusCode::TOO_MANY_REQUESTS {
+			limited = Some(response);
+			break;
+		}
+	}
+	let limited = limited.expect("Player was never rate limited");
+	assert_eq!(limited.headers()["x-ratelimit-remaining"], "0");
+	assert!(limited.headers().contains_key("retry-after"));
+
+	// The body tells clients what went wrong, when to retry and which request failed
+	let request_id = limited.headers()["x-request-id"]
+		.to_str()
+		.unwrap()
+		.to_owned();
+	let body: serde_json::Value = limited.json().await.unwrap();
+	assert_eq!(body["code"], "rate_limited_error");
+	assert_eq!(body["message"], "Too many requests, slow down");
+	assert!(body["details"]["retry_after_seconds"].is_u64(), "{body}");
+	assert_eq!(body["request_id"], request_id.as_str());
+
+	// Other players have a bucket of their own
+	assert_eq!(get_as(other.id).await.unwrap().status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn public_routes_are_limited_per_ip() {
+	let server = TestApp::new();
+	let client = Client::new();
+
+	let response = client
+		.get(format!("{}/health", &server.address))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+	assert_eq!(response.headers()["x-ratelimit-limit"], "30");
+	assert_eq!(response.headers()["x-ratelimit-remaining"], "29");
+}
@@ -0,0 +1,83 @@
+//! Integration tests for the Server-Sent Events progress stream.
+
+use std::time::Duration;
+
+use empire::domain::events::GameEvent;
+use empire::domain::factions::FactionCode;
+use reqwest::{Client, StatusCode, header};
+use tokio::time::timeout;
+
+use crate::common::TestApp;
+
+#[tokio::test]
+async fn event_stream_requires_authentication() {
+	let server = TestApp::new();
+	let client = Client::new();
+
+	let response = client
+		.get(format!("{}/game/events", &server.address))
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn event_stream_sends_own_progress_events_only() {
+	let server = TestApp::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let other = server.create_named_user("test_other_player", Some(FactionCode::Orc));
+	let bearer = server.create_bearer_token(&user.id);
+	let client = Client::new();
+
+	let mut response = client
+		.get(format!("{}/game/events", &server.address))
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.expect("Failed to execute request.");
+	assert_eq!(response.status(), StatusCode::OK);
+	assert_eq!(
+		response.headers()[header::CONTENT_TYPE],
+		"text/event-stream"
+	);
+
+	// Neither of these belongs on the player's progress stream
+	server.app.events.publish(GameEvent::UpgradeCompleted {
+		player_id: other.id,
+		player_building_id: uuid::Uuid::new_v4(),
+		level: 2,
+	});
+	server.app.events.publish(GameEvent::ResourcesCollected {
+		player_id: user.id,
+		food: 1,
+		wood: 1,
+		stone: 1,
+		gold: 1,
+	});
+	let own_event = GameEvent::UpgradeCompleted {
+		player_id: user.id,
+		player_building_id: uuid::Uuid::new_v4(),
+		level: 3,
+	};
+	server.app.events.publish(own_event.clone());
+
+	let chunk = timeout(Duration::from_secs(5), response.chunk())
+		.await
+		.expect("Timed out waiting for event")
+		.expect("Failed to read stream")
+		.expect("Stream ended");
+	let text = String::from_utf8(chunk.to_vec()).unwrap();
+
+	let mut lines = text.lines();
+	assert_eq!(lines.next(), Some("event: upgrade_completed"));
+	let data = lines
+		.next()
+		.and_then(|line| line.strip_prefix("data: "))
+		.expect("Missing data line");
+	let event: GameEvent = serde_json::from_str(data).unwrap();
+	assert_eq!(
+		event, own_event,
+		"Only the player's progress events are streamed"
+	);
+}
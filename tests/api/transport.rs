@@ -0,0 +1,83 @@
+//! Tests for the compression of responses, the size limit of request bodies and the answer
+//! to requests no route matches.
+//!
+//! All are part of the middleware stack shared by every route, the limit coming from
+//! `server.max_body_bytes` in the configuration.
+
+use empire::domain::factions::FactionCode;
+use reqwest::{Client, StatusCode, header};
+
+use crate::common::TestApp;
+
+#[tokio::test]
+async fn large_responses_are_compressed() {
+	let server = TestApp::new();
+	let client = Client::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let bearer = server.create_bearer_token(&user.id);
+	let url = format!("{}/game/buildings/all", &server.address);
+
+	for encoding in ["gzip", "br"] {
+		let response = client
+			.get(&url)
+			.bearer_auth(bearer.token())
+			.header(header::ACCEPT_ENCODING, encoding)
+			.send()
+			.await
+			.unwrap();
+		assert_eq!(response.status(), StatusCode::OK);
+		assert_eq!(response.headers()[header::CONTENT_ENCODING], encoding);
+	}
+
+	// Clients that do not ask for compression get the plain body
+	let response = client
+		.get(&url)
+		.bearer_auth(bearer.token())
+		.send()
+		.await
+		.unwrap();
+	assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+	let definitions: Vec<serde_json::Value> = response.json().await.unwrap();
+	assert!(!definitions.is_empty());
+}
+
+#[tokio::test]
+async fn oversized_request_bodies_are_rejected() {
+	let server = TestApp::new();
+	let client = Client::new();
+	let padding = "a".repeat(2 * 1024 * 1024);
+
+	let response = client
+		.post(format!("{}/register", &server.address))
+		.header(header::CONTENT_TYPE, "application/json")
+		.body(format!(r#"{{"username":"{padding}","password":"1234"}}"#))
+		.send()
+		.await
+		.unwrap();
+
+	assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["code"], "request_too_large_error");
+	assert_eq!(body["details"]["max_bytes"], 1024 * 1024);
+}
+
+#[tokio::test]
+async fn unknown_routes_answer_with_an_error_body() {
+	let server = TestApp::new();
+
+	let response = Client::new()
+		.get(format!("{}/no/such/route", &server.address))
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+	let request_id = response.headers()["x-request-id"]
+		.to_str()
+		.unwrap()
+		.to_owned();
+	let body: serde_json::Value = response.json().await.unwrap();
+	assert_eq!(body["code"], "route_not_found_error");
+	assert_eq!(body["message"], "No route for this path");
+	assert_eq!(body["details"]["path"], "/no/such/route");
+	assert_eq!(body["request_id"], request_id.as_str());
+}
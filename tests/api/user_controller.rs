@@ -7,11 +7,13 @@ use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
 use empire::auth::utils::hash_password;
 use empire::controllers::auth::RegisterPayload;
 use empire::controllers::user::{NewUserPayload, UpdateUserPayload, UserBody, UserListBody};
-use empire::db::{DbConn, players};
+use empire::db::{DbConn, player_items, players};
 use empire::domain::auth::{Claims, encode_token};
 use empire::domain::factions::FactionCode;
 use empire::domain::player::buildings::PlayerBuilding;
+use empire::domain::player::role::PlayerRole;
 use empire::domain::player::{NewPlayer, Player, PlayerKey, UserName};
+use empire::game::player_operations::provision_player;
 use empire::schema::{player, player_building};
 use http_body_util::BodyExt;
 use tower::ServiceExt;
@@ -21,7 +23,7 @@ use crate::common::{TestApp, TestHarness};
 #[tokio::test]
 async fn get_all() {
 	let harness = TestHarness::new();
-	let user = create_test_user(&mut harness.get_conn(), None);
+	let user = create_admin_user(&mut harness.get_conn());
 	let router = harness.router.owned();
 	let bearer = get_bearer(user.id);
 
@@ -42,8 +44,8 @@ async fn get_all() {
 	assert!(!body.is_empty(), "No users returned");
 	assert_eq!(
 		body.last().unwrap().username.as_str(),
-		"test_user",
-		"Last user isn't test_user"
+		"admin_user",
+		"Last user isn't admin_user"
 	)
 }
 
@@ -52,8 +54,8 @@ async fn create_and_get_by_id() {
 	let server = TestApp::new();
 	let client = reqwest::Client::new();
 	let mut conn = server.get_conn();
-	let user = create_test_user(&mut conn, None);
-	let bearer = get_bearer(user.id);
+	let admin = create_admin_user(&mut conn);
+	let bearer = get_bearer(admin.id);
 
 	let req = NewUserPayload {
 		username: "test1".to_string(),
@@ -78,8 +80,17 @@ async fn create_and_get_by_id() {
 		req.username.as_str(),
 		"New username isn't equal to request username"
 	);
+	// Created users are onboarded like registered players
+	let created = players::get_by_id(&mut conn, &new_user.id).unwrap();
+	assert!(created.protected_until.is_some(), "No beginner shield");
+	let inventory = player_items::get_for_player(&mut conn, &new_user.id).unwrap();
+	assert_eq!(inventory.len(), 1, "No welcome pack");
+	assert_eq!(
+		created.role,
+		PlayerRole::Player,
+		"Created users are regular players"
+	);
 
-	let bearer = get_bearer(new_user.id);
 	let response = client
 		.get(format!("{}/users/{}", &server.address, new_user.id))
 		.bearer_auth(bearer.token())
@@ -129,7 +140,8 @@ async fn update() {
 		.expect("Failed to get player buildings");
 	assert!(player_blds.is_empty(), "User has buildings");
 
-	let bearer = get_bearer(user.id);
+	let admin = create_admin_user(&mut conn);
+	let bearer = get_bearer(admin.id);
 	let body = UpdateUserPayload {
 		username: None,
 		password: None,
@@ -157,21 +169,20 @@ async fn update() {
 async fn delete() {
 	let server = TestApp::new();
 	let client = reqwest::Client::new();
-	let user = create_test_user(&mut server.get_conn(), None);
-
-	let bearer = get_bearer(user.id);
+	let admin = create_admin_user(&mut server.get_conn());
+	let user = create_named_user(&mut server.get_conn(), "doomed_user");
 
 	let res = client
 		.delete(format!("{}/users/{}", &server.address, user.id))
-		.bearer_auth(bearer.token())
+		.bearer_auth(get_bearer(admin.id).token())
 		.send()
 		.await
 		.expect("Failed to execute request.");
 	assert_eq!(res.status(), StatusCode::NO_CONTENT);
 
 	let res = client
-		.get(format!("{}/users/{}", &server.address, user.id))
-		.bearer_auth(bearer.token())
+		.get(format!("{}/player/profile", &server.address))
+		.bearer_auth(get_bearer(user.id).token())
 		.send()
 		.await
 		.expect("Failed to execute request.");
@@ -181,29 +192,84 @@ async fn delete() {
 		"Shouldn't be able to authorize with deleted user"
 	);
 
-	let user2 = create_test_user(&mut server.get_conn(), None);
-	let bearer2 = get_bearer(user2.id); // TODO: add a test to cover the expired player trying to reuse the token
+	// TODO: add a test to cover the expired player trying to reuse the token
 	let response = client
 		.get(format!("{}/users/{}", &server.address, user.id))
-		.bearer_auth(bearer2.token())
+		.bearer_auth(get_bearer(admin.id).token())
 		.send()
 		.await
 		.expect("Failed to execute request.");
 	assert_eq!(response.status(), StatusCode::NOT_FOUND);
 }
 
-/// Create a player. Uses internal DB functions.
-fn create_test_user(conn: &mut DbConn, faction: Option<FactionCode>) -> Player {
-	players::create(
+#[tokio::test]
+async fn managing_accounts_requires_a_role() {
+	let server = TestApp::new();
+	let client = reqwest::Client::new();
+	let mut conn = server.get_conn();
+	let victim = create_named_user(&mut conn, "victim");
+	let player = create_named_user(&mut conn, "regular");
+	let moderator = create_named_user(&mut conn, "moderator");
+	players::set_role(&mut conn, &moderator.id, PlayerRole::Moderator).unwrap();
+	let victim_url = format!("{}/users/{}", &server.address, victim.id);
+
+	// Regular players can neither look up nor delete other accounts
+	let response = client
+		.get(&victim_url)
+		.bearer_auth(get_bearer(player.id).token())
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::FORBIDDEN);
+	for caller in [&player, &moderator] {
+		let response = client
+			.delete(&victim_url)
+			.bearer_auth(get_bearer(caller.id).token())
+			.send()
+			.await
+			.unwrap();
+		assert_eq!(response.status(), StatusCode::FORBIDDEN);
+	}
+	assert!(
+		players::find_by_id(&mut conn, &victim.id)
+			.unwrap()
+			.is_some()
+	);
+
+	// Moderators can look accounts up
+	let response = client
+		.get(&victim_url)
+		.bearer_auth(get_bearer(moderator.id).token())
+		.send()
+		.await
+		.unwrap();
+	assert_eq!(response.status(), StatusCode::OK);
+}
+
+/// Create a regular player with the given name. Uses internal DB functions.
+fn create_named_user(conn: &mut DbConn, name: &str) -> Player {
+	create_player(conn, name)
+}
+
+/// Create a player with the admin role. Uses internal DB functions.
+fn create_admin_user(conn: &mut DbConn) -> Player {
+	let admin = create_player(conn, "admin_user");
+	players::set_role(conn, &admin.id, PlayerRole::Admin).expect("Failed to make player an admin")
+}
+
+fn create_player(conn: &mut DbConn, name: &str) -> Player {
+	let player = players::create(
 		conn,
 		NewPlayer {
-			name: UserName::parse("test_user".to_string()).unwrap(),
+			name: UserName::parse(name.to_string()).unwrap(),
 			pwd_hash: hash_password(b"1234").unwrap(),
 			email: None,
-			faction: faction.unwrap_or(FactionCode::Human),
+			faction: FactionCode::Human,
 		},
 	)
-	.expect("Failed to create player")
+	.expect("Failed to create player");
+	provision_player(conn, &player).expect("Failed to provision player");
+	player
 }
 
 /// Delete a player. Uses internal DB functions.
@@ -0,0 +1,144 @@
+//! Integration tests for the real-time WebSocket endpoint.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use empire::domain::events::GameEvent;
+use empire::domain::factions::FactionCode;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::json;
+use tokio::time::timeout;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::header;
+
+use crate::common::TestApp;
+
+/// Builds the WebSocket URL of the test server.
+fn ws_url(server: &TestApp) -> String {
+	format!("{}/game/ws", server.address.replacen("http", "ws", 1))
+}
+
+#[tokio::test]
+async fn websocket_requires_authentication() {
+	let server = TestApp::new();
+
+	let result = connect_async(ws_url(&server)).await;
+	assert!(
+		result.is_err(),
+		"Unauthenticated upgrade should be rejected"
+	);
+}
+
+#[tokio::test]
+async fn websocket_pushes_own_events_only() {
+	let server = TestApp::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let other = server.create_named_user("test_other_player", Some(FactionCode::Orc));
+	let bearer = server.create_bearer_token(&user.id);
+
+	let mut request = ws_url(&server).into_client_request().unwrap();
+	request.headers_mut().insert(
+		header::AUTHORIZATION,
+		format!("Bearer {}", bearer.token()).parse().unwrap(),
+	);
+	let (mut socket, _) = connect_async(request)
+		.await
+		.expect("Failed to connect WebSocket");
+
+	// The server subscribes right after the upgrade, give it a moment
+	tokio::time::sleep(Duration::from_millis(200)).await;
+
+	server.app.events.publish(GameEvent::AttackIncoming {
+		player_id: other.id,
+		attacker_id: user.id,
+		arrives_at: Utc::now(),
+	});
+	let own_event = GameEvent::TrainingCompleted {
+		player_id: user.id,
+		training_id: uuid::Uuid::new_v4(),
+		unit_id: uuid::Uuid::new_v4(),
+		quantity: 5,
+	};
+	server.app.events.publish(own_event.clone());
+
+	let msg = timeout(Duration::from_secs(5), socket.next())
+		.await
+		.expect("Timed out waiting for event")
+		.expect("Socket closed")
+		.expect("WebSocket error");
+	let Message::Text(text) = msg else {
+		panic!("Expected a text frame, got {msg:?}");
+	};
+	let event: GameEvent = serde_json::from_str(&text).unwrap();
+	assert_eq!(
+		event, own_event,
+		"Only events addressed to the player are pushed"
+	);
+
+	let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+	assert_eq!(body["type"], "training_completed");
+}
+
+#[tokio::test]
+async fn websocket_posts_and_pushes_chat_messages() {
+	let server = TestApp::new();
+	let user = server.create_test_user(Some(FactionCode::Human));
+	let other = server.create_named_user("test_other_player", Some(FactionCode::Orc));
+
+	let mut sockets = Vec::new();
+	for player_id in [user.id, other.id] {
+		let bearer = server.create_bearer_token(&player_id);
+		let mut request = ws_url(&server).into_client_request().unwrap();
+		request.headers_mut().insert(
+			header::AUTHORIZATION,
+			format!("Bearer {}", bearer.token()).parse().unwrap(),
+		);
+		let (socket, _) = connect_async(request)
+			.await
+			.expect("Failed to connect WebSocket");
+		sockets.push(socket);
+	}
+	tokio::time::sleep(Duration::from_millis(200)).await;
+
+	let frame = json!({ "type": "chat", "channel": "global", "body": "Hello everyone" });
+	sockets[0]
+		.send(Message::Text(frame.to_string().into()))
+		.await
+		.unwrap();
+
+	// Everyone in the channel receives the message, the poster included
+	for socket in sockets.iter_mut() {
+		let body = next_json(socket).await;
+		assert_eq!(body["type"], "chat_message");
+		assert_eq!(body["body"], "Hello everyone");
+		assert_eq!(body["player_id"], user.id.to_string());
+	}
+
+	// Refused posts are answered on the poster's socket only
+	let frame = json!({ "type": "chat", "channel": "alliance", "body": "Anyone?" });
+	sockets[1]
+		.send(Message::Text(frame.to_string().into()))
+		.await
+		.unwrap();
+	let body = next_json(&mut sockets[1]).await;
+	assert_eq!(body["type"], "chat_error");
+	assert_eq!(body["error"], "Not in an alliance");
+}
+
+/// Waits for the next text frame and parses it as JSON.
+async fn next_json<S>(socket: &mut S) -> serde_json::Value
+where
+	S: StreamExt<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+{
+	let msg = timeout(Duration::from_secs(5), socket.next())
+		.await
+		.expect("Timed out waiting for a frame")
+		.expect("Socket closed")
+		.expect("WebSocket error");
+	let Message::Text(text) = msg else {
+		panic!("Expected a text frame, got {msg:?}");
+	};
+	serde_json::from_str(&text).unwrap()
+}
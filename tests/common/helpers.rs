@@ -6,6 +6,7 @@ use empire::db::{DbConn, players};
 use empire::domain::auth::{Claims, encode_token};
 use empire::domain::factions::FactionCode;
 use empire::domain::player::{NewPlayer, Player, PlayerKey, UserName};
+use empire::game::player_operations::provision_player;
 
 pub(super) fn get_bearer(player_id: &PlayerKey) -> Authorization<Bearer> {
 	let now = chrono::Utc::now();
@@ -20,14 +21,24 @@ pub(super) fn get_bearer(player_id: &PlayerKey) -> Authorization<Bearer> {
 }
 
 pub(super) fn create_test_user(conn: &mut DbConn, faction: Option<FactionCode>) -> Player {
-	players::create(
+	create_named_user(conn, "test_game_user", faction)
+}
+
+pub(super) fn create_named_user(
+	conn: &mut DbConn,
+	name: &str,
+	faction: Option<FactionCode>,
+) -> Player {
+	let player = players::create(
 		conn,
 		NewPlayer {
-			name: UserName::parse("test_game_user".to_string()).unwrap(),
+			name: UserName::parse(name.to_string()).unwrap(),
 			pwd_hash: hash_password(b"1234").unwrap(),
 			email: None,
 			faction: faction.unwrap_or(FactionCode::Neutral),
 		},
 	)
-	.expect("Failed to create player")
+	.expect("Failed to create player");
+	provision_player(conn, &player).expect("Failed to provision player");
+	player
 }
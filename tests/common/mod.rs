@@ -3,6 +3,7 @@
 mod helpers;
 
 use std::env;
+use std::net::SocketAddr;
 use std::sync::{Arc, LazyLock};
 
 use axum::Router;
@@ -12,7 +13,7 @@ use axum_test::util::new_random_tokio_tcp_listener;
 use derive_more::Deref;
 use diesel::{Connection, PgConnection, RunQueryDsl, sql_query};
 use empire::Result;
-use empire::configuration::{DatabaseSettings, get_settings};
+use empire::configuration::{DatabaseSettings, Settings, get_settings};
 use empire::db::DbConn;
 use empire::db::connection::{DbPool, initialize_pool};
 use empire::db::migrations::run_pending;
@@ -20,6 +21,7 @@ use empire::domain::app_state::{App, AppPool, AppState};
 use empire::domain::auth::init_keys;
 use empire::domain::factions::FactionCode;
 use empire::domain::player::{Player, PlayerKey};
+use empire::game::modifiers::modifier_operations::init_limits;
 use empire::net::router;
 use secrecy::{ExposeSecret, SecretString};
 use tokio::task::AbortHandle;
@@ -154,10 +156,16 @@ pub struct TestApp {
 	app_pool: AppPool,
 	/// The HTTP address where the server is listening (e.g., "http://localhost:8080")
 	pub address: String,
+	/// The HTTP address of the internal admin listener
+	pub admin_address: String,
+	/// The application instance backing the server
+	pub app: Arc<App>,
 	/// Database connection pool for test data management
 	pub db_pool: TestPool,
 	/// Server join handle drop guard.
 	_handle: AbortOnDrop,
+	/// Admin server join handle drop guard.
+	_admin_handle: AbortOnDrop,
 }
 
 impl TestHarness {
@@ -179,11 +187,19 @@ impl TestHarness {
 	/// an actual HTTP server. This is more efficient for unit and integration tests
 	/// that don't require network communication.
 	pub fn new() -> Self {
+		Self::with_settings(|_| {})
+	}
+
+	/// Initializes a test harness like [`TestHarness::new`], with the configuration changed by
+	/// `configure` before the application is built.
+	pub fn with_settings(configure: impl FnOnce(&mut Settings)) -> Self {
 		// Ensure tracing is initialized for test output
 		LazyLock::force(&TRACING);
 
 		let mut settings = get_settings().expect("Failed to read configuration");
+		configure(&mut settings);
 		init_keys(&settings.jwt.secret);
+		init_limits(&settings.modifiers).expect("Invalid modifier limits");
 
 		// Create an isolated test database and update settings
 		let (db_pool, sys_con_str) = create_isolated_test_database(&mut settings.database);
@@ -209,6 +225,12 @@ impl TestHarness {
 		create_test_user(&mut conn, faction)
 	}
 
+	/// Create a player with the given name, for tests that need more than one player.
+	pub fn create_named_user(&self, name: &str, faction: Option<FactionCode>) -> Player {
+		let mut conn = self.get_conn();
+		create_named_user(&mut conn, name, faction)
+	}
+
 	pub fn create_bearer_token(&self, player_key: &PlayerKey) -> Authorization<Bearer> {
 		get_bearer(player_key)
 	}
@@ -232,7 +254,7 @@ impl TestApp {
 	/// 4. Returns connection details for making HTTP requests
 	///
 	/// # Returns
-	/// A [`TestApp`] containing the server address and database pool.
+	/// A [`TestApp`] containing the server and admin addresses and the database pool.
 	///
 	/// # Panics
 	/// This function will panic if:
@@ -244,8 +266,15 @@ impl TestApp {
 	/// Use this function when you need to test the full HTTP server functionality,
 	/// including middleware, routing, and request/response handling.
 	pub fn new() -> Self {
-		let harness = TestHarness::new();
+		Self::with_settings(|_| {})
+	}
+
+	/// Spawns a test server like [`TestApp::new`], with the configuration changed by
+	/// `configure` before the application is built.
+	pub fn with_settings(configure: impl FnOnce(&mut Settings)) -> Self {
+		let harness = TestHarness::with_settings(configure);
 		let app_pool = Arc::clone(&harness.app_pool);
+		let app = Arc::clone(&harness.app);
 
 		// Bind to a random available port
 		let listener = new_random_tokio_tcp_listener().expect("Failed to bind to random port");
@@ -256,16 +285,39 @@ impl TestApp {
 
 		// Start the server in a background task
 		let handle = tokio::spawn(async move {
-			axum::serve(listener, harness.router.0)
+			axum::serve(
+				listener,
+				harness
+					.router
+					.0
+					.into_make_service_with_connect_info::<SocketAddr>(),
+			)
+			.await
+			.expect("Server failed to start");
+		});
+
+		// The admin API listens separately, like in production
+		let admin_listener =
+			new_random_tokio_tcp_listener().expect("Failed to bind to random port");
+		let admin_port = admin_listener
+			.local_addr()
+			.expect("Failed to get local address")
+			.port();
+		let admin_router = router::init_admin(AppState(Arc::clone(&app)));
+		let admin_handle = tokio::spawn(async move {
+			axum::serve(admin_listener, admin_router)
 				.await
-				.expect("Server failed to start");
+				.expect("Admin server failed to start");
 		});
 
 		Self {
 			address: format!("http://localhost:{port}"),
+			admin_address: format!("http://localhost:{admin_port}"),
+			app,
 			db_pool: harness.db_pool,
 			app_pool,
 			_handle: AbortOnDrop(handle.abort_handle()),
+			_admin_handle: AbortOnDrop(admin_handle.abort_handle()),
 		}
 	}
 
@@ -275,6 +327,12 @@ impl TestApp {
 		create_test_user(&mut conn, faction)
 	}
 
+	/// Create a player with the given name, for tests that need more than one player.
+	pub fn create_named_user(&self, name: &str, faction: Option<FactionCode>) -> Player {
+		let mut conn = self.get_conn();
+		create_named_user(&mut conn, name, faction)
+	}
+
 	pub fn create_bearer_token(&self, player_key: &PlayerKey) -> Authorization<Bearer> {
 		get_bearer(player_key)
 	}
@@ -30,6 +30,7 @@ async fn test_timespan_validation() {
 		expires_at: Some(Utc::now() + Duration::hours(1)),
 		source_type: ModifierSourceType::Event,
 		source_id: None,
+		player_building_id: None,
 	};
 
 	let result = diesel::insert_into(active_modifiers::table)
@@ -45,6 +46,7 @@ async fn test_timespan_validation() {
 		expires_at: Some(Utc::now() - Duration::hours(1)),
 		source_type: ModifierSourceType::Event,
 		source_id: None,
+		player_building_id: None,
 	};
 
 	let result = diesel::insert_into(active_modifiers::table)
@@ -63,6 +65,7 @@ async fn test_timespan_validation() {
 		expires_at: None,
 		source_type: ModifierSourceType::Faction,
 		source_id: None,
+		player_building_id: None,
 	};
 
 	let result = diesel::insert_into(active_modifiers::table)
@@ -91,6 +94,7 @@ async fn test_cascade_deletion() {
 		expires_at: None,
 		source_type: ModifierSourceType::Event,
 		source_id: None,
+		player_building_id: None,
 	};
 
 	diesel::insert_into(active_modifiers::table)
@@ -125,6 +129,7 @@ async fn test_cascade_deletion() {
 		expires_at: None,
 		source_type: ModifierSourceType::Event,
 		source_id: None,
+		player_building_id: None,
 	};
 
 	diesel::insert_into(active_modifiers::table)
@@ -94,6 +94,44 @@ async fn test_invalid_modifier_constraints() {
 	);
 }
 
+#[tokio::test]
+async fn test_debuff_modifier_constraints() {
+	let db_pool = TestHarness::new().db_pool;
+	let mut conn = db_pool.get().unwrap();
+	let debuff = |name: &str, magnitude_kind, magnitude: &str| NewModifier {
+		name: name.to_string(),
+		description: "Debuff".to_string(),
+		magnitude_kind,
+		magnitude: BigDecimal::from_str(magnitude).unwrap(),
+		target_type: ModifierTarget::Training,
+		target_resource: None,
+		stacking_behaviour: None,
+		stacking_group: None,
+	};
+
+	for valid in [
+		debuff("wounded_army", MagnitudeKind::Percentage, "-0.20"),
+		debuff("flat_debuff", MagnitudeKind::Flat, "-50"),
+		debuff("multiplier_debuff", MagnitudeKind::Multiplier, "0.8"),
+	] {
+		let result = diesel::insert_into(modifiers::table)
+			.values(&valid)
+			.execute(&mut conn);
+		assert!(result.is_ok(), "Failed to insert debuff {}", valid.name);
+	}
+
+	for invalid in [
+		debuff("below_minus_100", MagnitudeKind::Percentage, "-1.5"),
+		debuff("zero_multiplier", MagnitudeKind::Multiplier, "0"),
+		debuff("negative_multiplier", MagnitudeKind::Multiplier, "-0.5"),
+	] {
+		let result = diesel::insert_into(modifiers::table)
+			.values(&invalid)
+			.execute(&mut conn);
+		assert!(result.is_err(), "Should fail: Debuff {}", invalid.name);
+	}
+}
+
 #[tokio::test]
 async fn test_unique_name_constraint() {
 	let db_pool = TestHarness::new().db_pool;
@@ -1,6 +1,7 @@
-use empire::db::{player_buildings, players};
+use empire::db::{player_buildings, players, resources, settlements};
 use empire::domain::factions::FactionCode;
 use empire::domain::player::{NewPlayer, UserName};
+use empire::game::player_operations::provision_player;
 
 use crate::common::TestHarness;
 
@@ -26,7 +27,17 @@ async fn test_user_triggers() {
 		"Faction should be human"
 	);
 
-	let mut conn = server.db_pool.get().expect("Failed to get connection");
+	// The starter kit is provisioned by the application, not by the player insert
+	let new_buildings = player_buildings::get_player_buildings(&mut conn, &new_player.id)
+		.expect("Failed to get player buildings");
+	assert_eq!(
+		new_buildings.len(),
+		0,
+		"Player should not be provisioned yet"
+	);
+	assert!(resources::get_by_player_id(&mut conn, &new_player.id).is_err());
+
+	let provisioned = provision_player(&mut conn, &new_player).expect("Failed to provision player");
 	let new_buildings = player_buildings::get_player_buildings(&mut conn, &new_player.id)
 		.expect("Failed to get player buildings");
 	assert_ne!(
@@ -34,4 +45,18 @@ async fn test_user_triggers() {
 		0,
 		"Player should have starter buildings"
 	);
+	assert_eq!(provisioned.starter_buildings, new_buildings.len());
+	let storage =
+		resources::get_by_player_id(&mut conn, &new_player.id).expect("Player should have storage");
+	assert_eq!(storage.settlement_id, provisioned.capital.id);
+	assert!(storage.food_cap > 0, "Starter buildings raise the caps");
+
+	// Provisioning again adds nothing
+	let again = provision_player(&mut conn, &new_player).expect("Failed to provision player");
+	assert_eq!(again.capital, provisioned.capital);
+	assert_eq!(again.starter_buildings, 0);
+	assert_eq!(
+		settlements::count_by_player(&mut conn, &new_player.id).unwrap(),
+		1
+	);
 }
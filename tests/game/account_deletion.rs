@@ -0,0 +1,162 @@
+//! Integration tests for the account deletion.
+//!
+//! These tests cover:
+//! - Scheduling the deletion after the grace period, and keeping it when asked again
+//! - Cancelling the deletion on login
+//! - Anonymizing the account while keeping the history referencing it, and auditing it
+
+use chrono::{SubsecRound, TimeDelta, Utc};
+use diesel::prelude::*;
+use empire::auth::session_operations;
+use empire::configuration::AccountDeletionSettings;
+use empire::db::{audit_log, battle_reports, player_sessions, players};
+use empire::domain::combat::NewBattleReport;
+use empire::domain::jobs::{JobStatus, JobType};
+use empire::game::account_deletion::deletion_operations::{
+	ANONYMIZED_NAME_PREFIX, anonymize, cancel_on_login, request_deletion,
+};
+use empire::game::mail::mail_operations;
+use empire::schema::{job, player};
+
+use crate::common::TestHarness;
+
+#[tokio::test]
+async fn test_deletion_is_scheduled_after_the_grace_period() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let leaving = harness.create_named_user("leaving", None);
+	let session = session_operations::create(&mut conn, "token".to_string(), &leaving.id).unwrap();
+	let settings = AccountDeletionSettings {
+		grace_period_days: 7,
+	};
+	let now = Utc::now();
+
+	let scheduled_for = request_deletion(
+		&mut conn,
+		&harness.app.job_queue,
+		&settings,
+		&leaving.id,
+		now,
+	)
+	.unwrap();
+	assert_eq!(scheduled_for, (now + TimeDelta::days(7)).trunc_subsecs(6));
+	let stored = players::get_by_id(&mut conn, &leaving.id).unwrap();
+	assert_eq!(stored.deletion_scheduled_for, Some(scheduled_for));
+	// Asking for the deletion logs the player out everywhere
+	assert!(
+		player_sessions::find_by_id(&mut conn, &session.id)
+			.unwrap()
+			.is_none()
+	);
+
+	// Asking again keeps the schedule and does not enqueue another job
+	let later = now + TimeDelta::days(1);
+	let again = request_deletion(
+		&mut conn,
+		&harness.app.job_queue,
+		&settings,
+		&leaving.id,
+		later,
+	)
+	.unwrap();
+	assert_eq!(again, scheduled_for);
+	let jobs: i64 = job::table
+		.filter(job::job_type.eq(JobType::AccountDeletion))
+		.filter(job::status.eq(JobStatus::Pending))
+		.count()
+		.get_result(&mut conn)
+		.unwrap();
+	assert_eq!(jobs, 1);
+}
+
+#[tokio::test]
+async fn test_logging_in_cancels_the_deletion() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let leaving = harness.create_named_user("undecided", None);
+	let scheduled_for = request_deletion(
+		&mut conn,
+		&harness.app.job_queue,
+		&AccountDeletionSettings::default(),
+		&leaving.id,
+		Utc::now(),
+	)
+	.unwrap();
+
+	let stored = players::get_by_id(&mut conn, &leaving.id).unwrap();
+	assert!(cancel_on_login(&mut conn, &stored).unwrap());
+	let stored = players::get_by_id(&mut conn, &leaving.id).unwrap();
+	assert!(stored.deletion_scheduled_for.is_none());
+	assert!(!cancel_on_login(&mut conn, &stored).unwrap());
+
+	// The job enqueued for the cancelled deletion leaves the account alone
+	assert!(!anonymize(&mut conn, &leaving.id, scheduled_for, Utc::now()).unwrap());
+	let stored = players::get_by_id(&mut conn, &leaving.id).unwrap();
+	assert_eq!(stored.name, "undecided");
+	assert!(stored.anonymized_at.is_none());
+}
+
+#[tokio::test]
+async fn test_anonymizing_keeps_the_history() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let leaving = harness.create_named_user("forgotten", None);
+	let rival = harness.create_named_user("rival", None);
+	diesel::update(player::table.find(leaving.id))
+		.set(player::email.eq("forgotten@example.com"))
+		.execute(&mut conn)
+		.unwrap();
+	let report = battle_reports::create(
+		&mut conn,
+		NewBattleReport {
+			attacker_id: leaving.id,
+			defender_id: rival.id,
+			winner_id: Some(leaving.id),
+			attacker_losses: serde_json::json!([]),
+			defender_losses: serde_json::json!([]),
+			loot_food: 10,
+			loot_wood: 0,
+			loot_stone: 0,
+			loot_gold: 0,
+			modifiers: serde_json::json!([]),
+			fought_at: Utc::now(),
+		},
+	)
+	.unwrap();
+	let sent = mail_operations::send(&mut conn, &leaving.id, &rival.id, "Hi", "Bye").unwrap();
+	mail_operations::send(&mut conn, &rival.id, &leaving.id, "Re: Hi", "Private").unwrap();
+	let scheduled_for = Utc::now() - TimeDelta::minutes(1);
+	players::set_deletion_scheduled_for(&mut conn, &leaving.id, Some(scheduled_for)).unwrap();
+
+	assert!(anonymize(&mut conn, &leaving.id, scheduled_for, Utc::now()).unwrap());
+
+	let stored = players::get_by_id(&mut conn, &leaving.id).unwrap();
+	assert!(stored.name.starts_with(ANONYMIZED_NAME_PREFIX));
+	assert!(stored.email.is_none());
+	assert!(stored.deletion_scheduled_for.is_none());
+	assert!(stored.anonymized_at.is_some());
+	assert_ne!(stored.pwd_hash, leaving.pwd_hash);
+	assert!(
+		players::find_by_name(&mut conn, "forgotten")
+			.unwrap()
+			.is_none()
+	);
+	// The battle still references the anonymized player
+	let kept = battle_reports::get_by_id(&mut conn, &report.id).unwrap();
+	assert_eq!(kept.attacker_id, leaving.id);
+	assert_eq!(kept.winner_id, Some(leaving.id));
+	// Their inbox is gone, the mail they sent stays with its recipient
+	assert_eq!(
+		mail_operations::unread_count(&mut conn, &leaving.id).unwrap(),
+		0
+	);
+	let kept = mail_operations::get_message(&mut conn, &rival.id, &sent.id).unwrap();
+	assert_eq!(kept.sender_id, Some(leaving.id));
+
+	// The server anonymized the account, not a player or an operator
+	let entry = audit_log::get_recent(&mut conn, 1).unwrap().remove(0);
+	assert_eq!(entry.action, "anonymize_account");
+	assert_eq!(entry.subject, Some(leaving.id.to_string()));
+	assert!(entry.player_id.is_none());
+	assert!(entry.operator.is_none());
+}
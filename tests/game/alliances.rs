@@ -0,0 +1,225 @@
+//! Integration tests for alliances.
+//!
+//! These tests cover:
+//! - Founding an alliance, and joining it only by invite and up to the member cap
+//! - The permissions of the leader, officers and members
+//! - Handing the leadership over when the leader leaves, and disbanding empty alliances
+//! - Donations to the treasury, and attacks between members being refused
+
+use empire::configuration::{AllianceSettings, ProtectionSettings};
+use empire::db::{DbConn, alliance_members, alliances, resources};
+use empire::domain::alliance::AllianceRole;
+use empire::domain::player::PlayerKey;
+use empire::game::alliances::alliance_operations::{
+	create_alliance, donate, invite, join, kick, leave, list_invites, set_role,
+};
+use empire::game::combat::combat_validator::validate_attack;
+
+use crate::common::TestHarness;
+use crate::fixtures::set_player_resources;
+
+fn role_of(conn: &mut DbConn, player_id: &PlayerKey) -> Option<AllianceRole> {
+	alliance_members::find_by_player(conn, player_id)
+		.unwrap()
+		.map(|member| member.role)
+}
+
+#[tokio::test]
+async fn test_alliances_are_joined_by_invite_up_to_the_cap() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let settings = AllianceSettings { max_members: 2 };
+	let founder = harness.create_named_user("founder", None).id;
+	let recruit = harness.create_named_user("recruit", None).id;
+	let latecomer = harness.create_named_user("latecomer", None).id;
+
+	let details = create_alliance(&mut conn, &founder, " Iron Pact ", "IRON").unwrap();
+	let alliance_id = details.alliance.id;
+	assert_eq!(details.alliance.name, "Iron Pact");
+	assert_eq!(details.members.len(), 1);
+	assert_eq!(role_of(&mut conn, &founder), Some(AllianceRole::Leader));
+
+	// Names and tags are unique regardless of case, and players found only one alliance
+	let err = create_alliance(&mut conn, &recruit, "iron pact", "IP").unwrap_err();
+	assert!(err.to_string().contains("name or tag is taken"));
+	let err = create_alliance(&mut conn, &founder, "Second Pact", "SP").unwrap_err();
+	assert!(err.to_string().contains("Already in an alliance"));
+	let err = create_alliance(&mut conn, &recruit, "Pact", "no tags!").unwrap_err();
+	assert!(err.to_string().contains("Alliance tags"));
+
+	// Joining takes an invite
+	let err = join(&mut conn, &settings, &recruit, &alliance_id).unwrap_err();
+	assert!(err.to_string().contains("No invite"));
+	invite(&mut conn, &founder, &alliance_id, &recruit).unwrap();
+	let err = invite(&mut conn, &founder, &alliance_id, &recruit).unwrap_err();
+	assert!(err.to_string().contains("already invited"));
+	assert_eq!(list_invites(&mut conn, &recruit).unwrap().len(), 1);
+
+	let member = join(&mut conn, &settings, &recruit, &alliance_id).unwrap();
+	assert_eq!(member.role, AllianceRole::Member);
+	assert!(
+		list_invites(&mut conn, &recruit).unwrap().is_empty(),
+		"Joining drops the invites"
+	);
+
+	// Members cannot invite, and the alliance is full once it reaches the cap
+	let err = invite(&mut conn, &recruit, &alliance_id, &latecomer).unwrap_err();
+	assert!(err.to_string().contains("Only the leader and officers"));
+	invite(&mut conn, &founder, &alliance_id, &latecomer).unwrap();
+	let err = join(&mut conn, &settings, &latecomer, &alliance_id).unwrap_err();
+	assert!(err.to_string().contains("Alliance is full"));
+	assert_eq!(role_of(&mut conn, &latecomer), None);
+}
+
+#[tokio::test]
+async fn test_roles_decide_who_can_kick_and_promote() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let settings = AllianceSettings::default();
+	let leader = harness.create_named_user("leader", None).id;
+	let officer = harness.create_named_user("officer", None).id;
+	let member = harness.create_named_user("member", None).id;
+	let other = harness.create_named_user("other", None).id;
+
+	let alliance_id = create_alliance(&mut conn, &leader, "Stone Wall", "WALL")
+		.unwrap()
+		.alliance
+		.id;
+	for player in [&officer, &member, &other] {
+		invite(&mut conn, &leader, &alliance_id, player).unwrap();
+		join(&mut conn, &settings, player, &alliance_id).unwrap();
+	}
+
+	// Only the leader changes roles
+	let err = set_role(
+		&mut conn,
+		&officer,
+		&alliance_id,
+		&member,
+		AllianceRole::Officer,
+	)
+	.unwrap_err();
+	assert!(err.to_string().contains("Only the leader can change roles"));
+	set_role(
+		&mut conn,
+		&leader,
+		&alliance_id,
+		&officer,
+		AllianceRole::Officer,
+	)
+	.unwrap();
+
+	// Officers kick members, but not other officers or the leader
+	let err = kick(&mut conn, &member, &alliance_id, &other).unwrap_err();
+	assert!(err.to_string().contains("lower role"));
+	let err = kick(&mut conn, &officer, &alliance_id, &leader).unwrap_err();
+	assert!(err.to_string().contains("lower role"));
+	kick(&mut conn, &officer, &alliance_id, &other).unwrap();
+	assert_eq!(role_of(&mut conn, &other), None);
+
+	// Handing the leadership over keeps the previous leader as an officer
+	set_role(
+		&mut conn,
+		&leader,
+		&alliance_id,
+		&member,
+		AllianceRole::Leader,
+	)
+	.unwrap();
+	assert_eq!(role_of(&mut conn, &member), Some(AllianceRole::Leader));
+	assert_eq!(role_of(&mut conn, &leader), Some(AllianceRole::Officer));
+	kick(&mut conn, &member, &alliance_id, &leader).unwrap();
+	assert_eq!(role_of(&mut conn, &leader), None);
+}
+
+#[tokio::test]
+async fn test_leaving_hands_the_leadership_over_and_disbands_empty_alliances() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let settings = AllianceSettings::default();
+	let leader = harness.create_named_user("leader", None).id;
+	let veteran = harness.create_named_user("veteran", None).id;
+	let officer = harness.create_named_user("officer", None).id;
+
+	let alliance_id = create_alliance(&mut conn, &leader, "Short Lived", "SL")
+		.unwrap()
+		.alliance
+		.id;
+	for player in [&veteran, &officer] {
+		invite(&mut conn, &leader, &alliance_id, player).unwrap();
+		join(&mut conn, &settings, player, &alliance_id).unwrap();
+	}
+	set_role(
+		&mut conn,
+		&leader,
+		&alliance_id,
+		&officer,
+		AllianceRole::Officer,
+	)
+	.unwrap();
+
+	// Officers take over before more senior members
+	let departure = leave(&mut conn, &leader).unwrap();
+	assert_eq!(departure.successor, Some(officer));
+	assert!(!departure.disbanded);
+	assert_eq!(role_of(&mut conn, &officer), Some(AllianceRole::Leader));
+
+	let departure = leave(&mut conn, &veteran).unwrap();
+	assert_eq!(departure.successor, None);
+	let departure = leave(&mut conn, &officer).unwrap();
+	assert!(departure.disbanded);
+	assert!(
+		alliances::find_by_id(&mut conn, &alliance_id)
+			.unwrap()
+			.is_none()
+	);
+
+	let err = leave(&mut conn, &officer).unwrap_err();
+	assert!(err.to_string().contains("Not in an alliance"));
+}
+
+#[tokio::test]
+async fn test_donations_fill_the_treasury_and_allies_cannot_attack_each_other() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let settings = AllianceSettings::default();
+	let protection = ProtectionSettings {
+		beginner_shield_days: 0,
+		beginner_shield_max_points: None,
+		beginner_shield_max_keep_level: None,
+	};
+	let leader = harness.create_named_user("leader", None).id;
+	let member = harness.create_named_user("member", None).id;
+	let outsider = harness.create_named_user("outsider", None).id;
+	for player in [&leader, &member, &outsider] {
+		set_player_resources(&mut conn, player, 100);
+	}
+
+	let alliance_id = create_alliance(&mut conn, &leader, "Gold Hoard", "GOLD")
+		.unwrap()
+		.alliance
+		.id;
+	invite(&mut conn, &leader, &alliance_id, &member).unwrap();
+	join(&mut conn, &settings, &member, &alliance_id).unwrap();
+
+	let (alliance, donation) = donate(&mut conn, &member, &alliance_id, (10, 0, 0, 40)).unwrap();
+	assert_eq!((alliance.food, alliance.gold), (10, 40));
+	assert_eq!(donation.player_id, Some(member));
+	let held = resources::get_by_player_id(&mut conn, &member).unwrap();
+	assert_eq!((held.food, held.gold), (90, 60));
+
+	let err = donate(&mut conn, &member, &alliance_id, (0, 0, 0, 61)).unwrap_err();
+	assert!(err.to_string().contains("Not enough resources"));
+	let err = donate(&mut conn, &member, &alliance_id, (-5, 0, 0, 10)).unwrap_err();
+	assert!(err.to_string().contains("must be positive"));
+	let err = donate(&mut conn, &outsider, &alliance_id, (1, 0, 0, 0)).unwrap_err();
+	assert!(err.to_string().contains("Not a member"));
+
+	let err = validate_attack(&mut conn, &protection, &member, &leader).unwrap_err();
+	assert!(err.to_string().contains("own alliance"));
+	assert!(validate_attack(&mut conn, &protection, &outsider, &leader).is_ok());
+
+	// Once they left, they are fair game again
+	leave(&mut conn, &member).unwrap();
+	assert!(validate_attack(&mut conn, &protection, &member, &leader).is_ok());
+}
@@ -0,0 +1,243 @@
+//! Integration tests for army presets.
+//!
+//! These tests cover:
+//! - Saving, listing and deleting presets, with their validation
+//! - Training a preset queues every unit at a building of the capital that trains it
+//! - Training a preset fails as a whole, leaving resources and queues untouched, if any
+//!   resource or queue check fails
+
+use diesel::prelude::*;
+use empire::db::{DbConn, player_buildings, resources, training_queue, units};
+use empire::domain::factions::FactionCode;
+use empire::domain::player::buildings::{NewPlayerBuilding, PlayerBuildingKey};
+use empire::domain::player::{Player, PlayerKey};
+use empire::domain::unit::{Unit, UnitKey};
+use empire::game::units::preset_operations::{
+	MAX_PRESETS_PER_PLAYER, create_preset, delete_preset, list_presets, train_preset,
+};
+use empire::game::units::training_operations::start_training;
+use empire::schema::{building, player_resource as pr};
+
+use crate::common::TestHarness;
+
+fn unit(conn: &mut DbConn, name: &str) -> Unit {
+	units::find_by_name(conn, name)
+		.unwrap()
+		.unwrap_or_else(|| panic!("{name} not seeded"))
+}
+
+/// Builds the human building named `name` at level 3 (2 training slots) in the capital.
+fn build(conn: &mut DbConn, player: &Player, name: &str) -> PlayerBuildingKey {
+	let building_id: i32 = building::table
+		.filter(building::name.eq(name))
+		.filter(building::faction.eq(FactionCode::Human))
+		.select(building::id)
+		.first(conn)
+		.unwrap_or_else(|_| panic!("{name} not seeded"));
+	player_buildings::construct(
+		conn,
+		NewPlayerBuilding {
+			player_id: player.id,
+			building_id,
+			level: Some(3),
+			upgrade_finishes_at: None,
+			settlement_id: None,
+		},
+	)
+	.unwrap_or_else(|_| panic!("Failed to build the {name}"))
+	.id
+}
+
+fn set_resources(conn: &mut DbConn, player_id: &PlayerKey, amount: i64) {
+	diesel::update(pr::table.filter(pr::player_id.eq(player_id)))
+		.set((
+			pr::food.eq(amount),
+			pr::wood.eq(amount),
+			pr::stone.eq(amount),
+			pr::gold.eq(amount),
+		))
+		.execute(conn)
+		.unwrap();
+}
+
+fn stored(conn: &mut DbConn, player_id: &PlayerKey) -> (i64, i64, i64, i64) {
+	let res = resources::get_by_player_id(conn, player_id).unwrap();
+	(res.food, res.wood, res.stone, res.gold)
+}
+
+#[tokio::test]
+async fn test_presets_are_saved_and_deleted() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("planner", Some(FactionCode::Human));
+	let infantry = unit(&mut conn, "Infantry");
+	let cavalry = unit(&mut conn, "Cavalry");
+	let veteran = unit(&mut conn, "Veteran Infantry");
+
+	let invalid = [
+		("  ", vec![(infantry.id, 5)], "Preset name must be"),
+		(
+			"Raid",
+			Vec::<(UnitKey, i64)>::new(),
+			"Quantity must be positive",
+		),
+		("Raid", vec![(infantry.id, 0)], "Quantity must be positive"),
+		(
+			"Raid",
+			vec![(infantry.id, 5), (infantry.id, 2)],
+			"only be listed once",
+		),
+		(
+			"Raid",
+			vec![(veteran.id, 5)],
+			"Upgraded units cannot be trained",
+		),
+	];
+	for (name, preset_units, message) in invalid {
+		let err = create_preset(&mut conn, &player.id, name, &preset_units).unwrap_err();
+		assert!(
+			err.to_string().contains(message),
+			"{name:?} {preset_units:?}"
+		);
+	}
+
+	let (raid, raid_units) = create_preset(
+		&mut conn,
+		&player.id,
+		" Raid ",
+		&[(infantry.id, 5), (cavalry.id, 2)],
+	)
+	.unwrap();
+	assert_eq!(raid.name, "Raid");
+	assert_eq!(raid_units.len(), 2);
+	let err = create_preset(&mut conn, &player.id, "Raid", &[(infantry.id, 1)]).unwrap_err();
+	assert!(err.to_string().contains("Preset name already taken"));
+
+	for i in 1..MAX_PRESETS_PER_PLAYER {
+		create_preset(
+			&mut conn,
+			&player.id,
+			&format!("Wave {i}"),
+			&[(infantry.id, 1)],
+		)
+		.unwrap();
+	}
+	let err = create_preset(&mut conn, &player.id, "One more", &[(infantry.id, 1)]).unwrap_err();
+	assert!(err.to_string().contains("Preset limit reached"));
+
+	let presets = list_presets(&mut conn, &player.id).unwrap();
+	assert_eq!(presets.len() as i64, MAX_PRESETS_PER_PLAYER);
+	assert_eq!(presets[0].0.id, raid.id, "Presets are sorted by name");
+
+	// Presets belong to their player
+	let other = harness.create_named_user("snoop", Some(FactionCode::Human));
+	let err = delete_preset(&mut conn, &other.id, &raid.id).unwrap_err();
+	assert!(err.to_string().contains("Preset not found"));
+	delete_preset(&mut conn, &player.id, &raid.id).unwrap();
+	assert_eq!(
+		list_presets(&mut conn, &player.id).unwrap().len() as i64,
+		MAX_PRESETS_PER_PLAYER - 1
+	);
+}
+
+#[tokio::test]
+async fn test_preset_is_trained_across_buildings() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("marshal", Some(FactionCode::Human));
+	set_resources(&mut conn, &player.id, 1000);
+	let barracks = build(&mut conn, &player, "Barracks");
+	let stables = build(&mut conn, &player, "Stables");
+	let infantry = unit(&mut conn, "Infantry");
+	let cavalry = unit(&mut conn, "Cavalry");
+	let (preset, _) = create_preset(
+		&mut conn,
+		&player.id,
+		"Raid",
+		&[(infantry.id, 5), (cavalry.id, 2)],
+	)
+	.unwrap();
+
+	let trainings = train_preset(
+		&mut conn,
+		&harness.app.job_queue,
+		&harness.app.modifier_system.cache,
+		&player.id,
+		&preset.id,
+	)
+	.unwrap();
+	assert_eq!(trainings.len(), 2);
+	let (trained_infantry, started_infantry) = &trainings[0];
+	assert_eq!(trained_infantry.id, infantry.id);
+	assert_eq!(started_infantry.entry.building_id, barracks);
+	assert_eq!(started_infantry.entry.quantity, 5);
+	assert!(started_infantry.entry.job_id.is_some());
+	let (trained_cavalry, started_cavalry) = &trainings[1];
+	assert_eq!(trained_cavalry.id, cavalry.id);
+	assert_eq!(started_cavalry.entry.building_id, stables);
+	assert_eq!(started_cavalry.costs, (60, 0, 0, 30));
+
+	// Infantry: food 20, wood 10 each; cavalry: food 30, gold 15 each
+	assert_eq!(stored(&mut conn, &player.id), (840, 950, 1000, 970));
+	let queued = training_queue::get_active_for_player(&mut conn, &player.id).unwrap();
+	assert_eq!(queued.len(), 2);
+}
+
+#[tokio::test]
+async fn test_preset_training_fails_atomically() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("cautious", Some(FactionCode::Human));
+	let barracks = build(&mut conn, &player, "Barracks");
+	build(&mut conn, &player, "Stables");
+	let infantry = unit(&mut conn, "Infantry");
+	let cavalry = unit(&mut conn, "Cavalry");
+	let (preset, _) = create_preset(
+		&mut conn,
+		&player.id,
+		"Raid",
+		&[(infantry.id, 5), (cavalry.id, 2)],
+	)
+	.unwrap();
+	let train = |conn: &mut DbConn| {
+		train_preset(
+			conn,
+			&harness.app.job_queue,
+			&harness.app.modifier_system.cache,
+			&player.id,
+			&preset.id,
+		)
+	};
+
+	// Enough for the infantry (100 food) but not for the cavalry as well (160 food)
+	set_resources(&mut conn, &player.id, 150);
+	let err = train(&mut conn).unwrap_err();
+	assert!(err.to_string().contains("Not enough resources"));
+	assert_eq!(stored(&mut conn, &player.id), (150, 150, 150, 150));
+	assert!(
+		training_queue::get_active_for_player(&mut conn, &player.id)
+			.unwrap()
+			.is_empty()
+	);
+
+	// A full Barracks leaves no slot for the infantry, so the cavalry is not trained either
+	set_resources(&mut conn, &player.id, 1000);
+	for _ in 0..2 {
+		start_training(
+			&mut conn,
+			&harness.app.job_queue,
+			&harness.app.modifier_system.cache,
+			&player.id,
+			&barracks,
+			&infantry.id,
+			1,
+		)
+		.unwrap();
+	}
+	let before = stored(&mut conn, &player.id);
+	let err = train(&mut conn).unwrap_err();
+	assert!(err.to_string().contains("No free training slot"));
+	assert_eq!(stored(&mut conn, &player.id), before);
+	let queued = training_queue::get_active_for_player(&mut conn, &player.id).unwrap();
+	assert_eq!(queued.len(), 2, "Only the trainings started before");
+}
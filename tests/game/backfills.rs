@@ -0,0 +1,110 @@
+//! Integration tests for online data backfills.
+//!
+//! These tests cover:
+//! - Chunks resume from the committed cursor until the backfill completes
+//! - Scheduling enqueues a backfill once and never after it completed
+
+use diesel::prelude::*;
+use empire::auth::password::{RESET_REQUIRED_HASH, RETIRED_HASHES_BACKFILL as NAME};
+use empire::db::backfills::{self, BACKFILLS};
+use empire::db::migrations;
+use empire::domain::factions::FactionCode;
+use empire::domain::jobs::JobType;
+use empire::domain::player::PlayerKey;
+use empire::schema::player;
+
+use crate::common::TestHarness;
+
+#[tokio::test]
+async fn test_retired_password_hashes_are_erased_in_chunks() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	for name in [
+		"first_player",
+		"second_player",
+		"third_player",
+		"fourth_player",
+	] {
+		harness.create_named_user(name, Some(FactionCode::Human));
+	}
+
+	let players: Vec<(PlayerKey, String)> = player::table
+		.select((player::id, player::pwd_hash))
+		.order_by(player::id)
+		.load(&mut conn)
+		.unwrap();
+	assert!(players.len() >= 4, "Players missing");
+	// Anything that is not a hash of a registered version is retired
+	let broken = players[1].0;
+	diesel::update(player::table.find(broken))
+		.set(player::pwd_hash.eq("not a hash"))
+		.execute(&mut conn)
+		.unwrap();
+
+	let backfill = backfills::find(NAME).expect("Backfill not registered");
+	backfills::ensure_progress(&mut conn, NAME).unwrap();
+	let first = backfills::run_next_chunk(&mut conn, backfill, 2).unwrap();
+	assert_eq!(first.processed, 2);
+	assert_eq!(first.cursor, Some(broken));
+	assert!(!first.is_completed());
+
+	// A later chunk continues after the committed cursor
+	let mut progress = first;
+	while !progress.is_completed() {
+		progress = backfills::run_next_chunk(&mut conn, backfill, 2).unwrap();
+	}
+	assert_eq!(progress.processed, players.len() as i64);
+	assert!(progress.started_at.is_some());
+
+	let erased: Vec<(PlayerKey, String)> = player::table
+		.select((player::id, player::pwd_hash))
+		.order_by(player::id)
+		.load(&mut conn)
+		.unwrap();
+	for ((id, hash), (_, before)) in erased.into_iter().zip(players) {
+		if id == broken {
+			assert_eq!(hash, RESET_REQUIRED_HASH, "Retired hashes are erased");
+		} else {
+			assert_eq!(hash, before, "Current hashes are kept");
+		}
+	}
+
+	// Running a completed backfill changes nothing
+	let again = backfills::run_next_chunk(&mut conn, backfill, 2).unwrap();
+	assert_eq!(again, progress);
+}
+
+#[tokio::test]
+async fn test_backfills_are_scheduled_until_completed() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let queue = &harness.app.job_queue;
+
+	let scheduled = migrations::schedule_backfills(&mut conn, queue).unwrap();
+	assert_eq!(scheduled, BACKFILLS.len());
+	// The first job is still pending, so nothing is enqueued twice
+	assert_eq!(migrations::schedule_backfills(&mut conn, queue).unwrap(), 0);
+
+	let job = queue
+		.get_next_job_of_type("worker", &JobType::Backfill)
+		.unwrap()
+		.expect("Backfill job should be claimed");
+	assert_eq!(job.payload["name"], NAME);
+	let progress = backfills::get_progress(&mut conn, NAME).unwrap();
+	assert_eq!(progress.job_id, Some(job.id));
+
+	// A job that failed for good no longer counts, the backfill is resumed
+	let mut progress = progress;
+	while backfills::has_outstanding_job(&mut conn, &progress).unwrap() {
+		queue.fail_job(&job.id, "interrupted").unwrap();
+		progress = backfills::get_progress(&mut conn, NAME).unwrap();
+	}
+	assert_eq!(migrations::schedule_backfills(&mut conn, queue).unwrap(), 1);
+
+	let backfill = backfills::find(NAME).unwrap();
+	while !backfills::run_next_chunk(&mut conn, backfill, 100)
+		.unwrap()
+		.is_completed()
+	{}
+	assert_eq!(migrations::schedule_backfills(&mut conn, queue).unwrap(), 0);
+}
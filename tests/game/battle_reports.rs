@@ -0,0 +1,140 @@
+//! Integration tests for battle reports.
+//!
+//! These tests cover:
+//! - Recording battle outcomes and reading them back as either side, and mailing them
+//! - Pagination of a player's reports
+//! - Pruning of reports outside the retention window
+
+use chrono::{TimeDelta, Utc};
+use empire::domain::combat::{Loot, UnitLoss};
+use empire::domain::factions::FactionCode;
+use empire::domain::message::MessageKind;
+use empire::domain::player::Player;
+use empire::game::combat::combat_operations::{
+	BattleOutcome, get_report, list_reports, prune_reports, record_battle, schedule_report_pruning,
+};
+use empire::game::mail::mail_operations::list_inbox;
+use uuid::Uuid;
+
+use crate::common::TestHarness;
+use crate::fixtures::create_test_player;
+
+/// Build a battle outcome won by the attacker, fought `days_ago` days ago.
+fn attacker_victory(attacker: &Player, defender: &Player, days_ago: i64) -> BattleOutcome {
+	BattleOutcome {
+		attacker_id: attacker.id,
+		defender_id: defender.id,
+		winner_id: Some(attacker.id),
+		attacker_losses: vec![UnitLoss {
+			unit_id: Uuid::new_v4(),
+			deployed: 50,
+			lost: 10,
+		}],
+		defender_losses: vec![UnitLoss {
+			unit_id: Uuid::new_v4(),
+			deployed: 30,
+			lost: 30,
+		}],
+		loot: Loot {
+			food: 100,
+			wood: 50,
+			stone: 25,
+			gold: 10,
+		},
+		modifiers: vec![],
+		fought_at: Utc::now() - TimeDelta::days(days_ago),
+	}
+}
+
+#[tokio::test]
+async fn test_reports_visible_to_both_sides() {
+	let TestHarness { db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	let attacker = create_test_player(&mut conn, FactionCode::Human);
+	let defender = create_test_player(&mut conn, FactionCode::Orc);
+	let outsider = create_test_player(&mut conn, FactionCode::Elf);
+
+	let report = record_battle(&mut conn, attacker_victory(&attacker, &defender, 0))
+		.expect("Failed to record battle");
+	assert_eq!(report.loot().food, 100);
+
+	for player in [&attacker, &defender] {
+		let found =
+			get_report(&mut conn, &player.id, &report.id).expect("Report should be visible");
+		assert_eq!(found.id, report.id);
+		let page = list_reports(&mut conn, &player.id, None, None).unwrap();
+		assert_eq!(page.total, 1);
+
+		// Both sides get the report by mail
+		let inbox = list_inbox(&mut conn, &player.id, false, None, None).unwrap();
+		assert_eq!(inbox.messages[0].kind, MessageKind::BattleReport);
+		assert_eq!(
+			inbox.messages[0].details["report_id"],
+			report.id.to_string()
+		);
+	}
+
+	let result = get_report(&mut conn, &outsider.id, &report.id);
+	assert!(result.is_err(), "Outsiders should not see the report");
+	assert!(result.unwrap_err().to_string().contains("not found"));
+	let page = list_reports(&mut conn, &outsider.id, None, None).unwrap();
+	assert_eq!(page.total, 0);
+
+	// The winner has to be one of the participants
+	let mut outcome = attacker_victory(&attacker, &defender, 0);
+	outcome.winner_id = Some(outsider.id);
+	assert!(record_battle(&mut conn, outcome).is_err());
+}
+
+#[tokio::test]
+async fn test_reports_pagination() {
+	let TestHarness { db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	let attacker = create_test_player(&mut conn, FactionCode::Human);
+	let defender = create_test_player(&mut conn, FactionCode::Orc);
+	for days_ago in 0..5 {
+		record_battle(&mut conn, attacker_victory(&attacker, &defender, days_ago)).unwrap();
+	}
+
+	let first = list_reports(&mut conn, &attacker.id, Some(1), Some(2)).unwrap();
+	assert_eq!(first.total, 5);
+	assert_eq!(first.reports.len(), 2);
+	assert!(
+		first.reports[0].fought_at > first.reports[1].fought_at,
+		"Reports should be ordered newest first"
+	);
+
+	let last = list_reports(&mut conn, &attacker.id, Some(3), Some(2)).unwrap();
+	assert_eq!(last.reports.len(), 1);
+	assert!(last.reports[0].fought_at < first.reports[1].fought_at);
+
+	// Out-of-range values are clamped
+	let clamped = list_reports(&mut conn, &defender.id, Some(0), Some(1_000)).unwrap();
+	assert_eq!(clamped.page, 1);
+	assert_eq!(clamped.per_page, 100);
+	assert_eq!(clamped.reports.len(), 5);
+}
+
+#[tokio::test]
+async fn test_prune_expired_reports() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	let attacker = create_test_player(&mut conn, FactionCode::Human);
+	let defender = create_test_player(&mut conn, FactionCode::Orc);
+	let recent = record_battle(&mut conn, attacker_victory(&attacker, &defender, 1)).unwrap();
+	let expired = record_battle(&mut conn, attacker_victory(&attacker, &defender, 45)).unwrap();
+
+	let deleted = prune_reports(&mut conn, 30).expect("Failed to prune reports");
+	assert_eq!(deleted, 1);
+	assert!(get_report(&mut conn, &attacker.id, &recent.id).is_ok());
+	assert!(get_report(&mut conn, &attacker.id, &expired.id).is_err());
+
+	// Only one pruning job is queued at a time
+	let job_id = schedule_report_pruning(&app.job_queue, Utc::now()).unwrap();
+	assert!(job_id.is_some());
+	let job_id = schedule_report_pruning(&app.job_queue, Utc::now()).unwrap();
+	assert!(job_id.is_none(), "Pruning should not be scheduled twice");
+}
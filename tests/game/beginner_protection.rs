@@ -0,0 +1,204 @@
+//! Integration tests for the beginner shield.
+//!
+//! These tests cover:
+//! - The combat validator rejects attacks by and against shielded players
+//! - The shield ends once it expires, the points or Keep level threshold is reached, or the
+//!   player opts out
+//! - Truce items protect both ways until they expire
+
+use chrono::{TimeDelta, Utc};
+use diesel::prelude::*;
+use empire::configuration::ProtectionSettings;
+use empire::db::{items, player_buildings, player_items, players, settlements};
+use empire::domain::factions::FactionCode;
+use empire::game::combat::combat_validator::validate_attack;
+use empire::game::combat::protection_operations::{
+	active_shield, active_truce, grant_beginner_shield, opt_out, player_points,
+};
+use empire::game::items::item_operations::use_item;
+use empire::game::modifiers::modifier_service::ModifierService;
+use empire::schema::{active_modifiers, building, player_building};
+
+use crate::common::TestHarness;
+use crate::fixtures::create_test_player;
+
+fn settings(days: i64, max_points: Option<i64>) -> ProtectionSettings {
+	ProtectionSettings {
+		beginner_shield_days: days,
+		beginner_shield_max_points: max_points,
+		beginner_shield_max_keep_level: None,
+	}
+}
+
+#[test]
+fn test_shield_blocks_attacks_in_both_directions() {
+	let harness = TestHarness::new();
+	let mut conn = harness.db_pool.get().unwrap();
+	let settings = settings(3, None);
+	let veteran = create_test_player(&mut conn, FactionCode::Human);
+	let newbie = create_test_player(&mut conn, FactionCode::Orc);
+
+	// Players without a shield can fight each other
+	assert!(validate_attack(&mut conn, &settings, &veteran.id, &newbie.id).is_ok());
+
+	let until = grant_beginner_shield(&mut conn, &settings, &newbie.id)
+		.unwrap()
+		.expect("Shield should be granted");
+	assert!(until > Utc::now() + TimeDelta::days(2));
+	assert_eq!(
+		active_shield(&mut conn, &settings, &newbie.id).unwrap(),
+		Some(until)
+	);
+
+	let err = validate_attack(&mut conn, &settings, &veteran.id, &newbie.id).unwrap_err();
+	assert!(
+		err.to_string()
+			.contains("Target is under beginner protection")
+	);
+	let err = validate_attack(&mut conn, &settings, &newbie.id, &veteran.id).unwrap_err();
+	assert!(
+		err.to_string()
+			.contains("Cannot attack while under beginner protection")
+	);
+
+	let err = validate_attack(&mut conn, &settings, &veteran.id, &veteran.id).unwrap_err();
+	assert!(err.to_string().contains("cannot attack themselves"));
+}
+
+#[test]
+fn test_shield_ends_early() {
+	let harness = TestHarness::new();
+	let mut conn = harness.db_pool.get().unwrap();
+	let player = create_test_player(&mut conn, FactionCode::Human);
+
+	// Disabled shields are never granted
+	assert_eq!(
+		grant_beginner_shield(&mut conn, &settings(0, None), &player.id).unwrap(),
+		None
+	);
+	assert_eq!(
+		active_shield(&mut conn, &settings(0, None), &player.id).unwrap(),
+		None
+	);
+
+	// Expired shields no longer protect
+	players::set_protected_until(
+		&mut conn,
+		&player.id,
+		Some(Utc::now() - TimeDelta::hours(1)),
+	)
+	.unwrap();
+	assert_eq!(
+		active_shield(&mut conn, &settings(3, None), &player.id).unwrap(),
+		None
+	);
+
+	// Reaching the points threshold drops the shield for good
+	let points = player_points(&mut conn, &player.id).unwrap();
+	assert!(points > 0, "Starter buildings should count as points");
+	grant_beginner_shield(&mut conn, &settings(3, None), &player.id).unwrap();
+	assert!(
+		active_shield(&mut conn, &settings(3, Some(points + 1)), &player.id)
+			.unwrap()
+			.is_some()
+	);
+	assert_eq!(
+		active_shield(&mut conn, &settings(3, Some(points)), &player.id).unwrap(),
+		None
+	);
+	assert_eq!(
+		players::get_by_id(&mut conn, &player.id)
+			.unwrap()
+			.protected_until,
+		None
+	);
+
+	// Opting out drops a running shield
+	grant_beginner_shield(&mut conn, &settings(3, None), &player.id).unwrap();
+	assert!(opt_out(&mut conn, &player.id).unwrap());
+	assert!(!opt_out(&mut conn, &player.id).unwrap());
+	assert_eq!(
+		active_shield(&mut conn, &settings(3, None), &player.id).unwrap(),
+		None
+	);
+}
+
+#[test]
+fn test_shield_ends_at_the_keep_level() {
+	let harness = TestHarness::new();
+	let mut conn = harness.db_pool.get().unwrap();
+	let player = create_test_player(&mut conn, FactionCode::Human);
+	let capital = settlements::get_capital(&mut conn, &player.id).unwrap();
+	let keep_level = player_buildings::get_keep_level(&mut conn, &capital.id).unwrap();
+	let settings = |max_keep_level| ProtectionSettings {
+		beginner_shield_max_keep_level: Some(max_keep_level),
+		..settings(3, None)
+	};
+
+	grant_beginner_shield(&mut conn, &settings(keep_level + 1), &player.id).unwrap();
+	assert!(
+		active_shield(&mut conn, &settings(keep_level + 1), &player.id)
+			.unwrap()
+			.is_some()
+	);
+
+	// Upgrading the Keep to the threshold drops the shield
+	let keep_ids = building::table
+		.filter(building::starter.eq(true))
+		.filter(building::max_count.eq(1))
+		.select(building::id);
+	diesel::update(
+		player_building::table
+			.filter(player_building::settlement_id.eq(capital.id))
+			.filter(player_building::building_id.eq_any(keep_ids)),
+	)
+	.set(player_building::level.eq(keep_level + 1))
+	.execute(&mut conn)
+	.unwrap();
+	assert_eq!(
+		active_shield(&mut conn, &settings(keep_level + 1), &player.id).unwrap(),
+		None
+	);
+	assert_eq!(
+		players::get_by_id(&mut conn, &player.id)
+			.unwrap()
+			.protected_until,
+		None
+	);
+}
+
+#[tokio::test]
+async fn test_truce_blocks_attacks_until_it_expires() {
+	let harness = TestHarness::new();
+	let mut conn = harness.db_pool.get().unwrap();
+	let service = ModifierService::new(&harness.app.db_pool, &harness.app.modifier_system);
+	let settings = settings(0, None);
+	let attacker = create_test_player(&mut conn, FactionCode::Human);
+	let pacifist = create_test_player(&mut conn, FactionCode::Elf);
+	let flag = items::get_by_name(&mut conn, "Flag of Truce").expect("Items are seeded");
+	player_items::add_items(&mut conn, &pacifist.id, &flag.id, 1).unwrap();
+	assert_eq!(active_truce(&mut conn, &pacifist.id).unwrap(), None);
+
+	let used = use_item(&mut conn, &service, &pacifist.id, &flag.id)
+		.await
+		.unwrap();
+	assert_eq!(
+		active_truce(&mut conn, &pacifist.id).unwrap(),
+		used.active.expires_at
+	);
+	let err = validate_attack(&mut conn, &settings, &attacker.id, &pacifist.id).unwrap_err();
+	assert!(err.to_string().contains("Target is under a truce"));
+	let err = validate_attack(&mut conn, &settings, &pacifist.id, &attacker.id).unwrap_err();
+	assert!(err.to_string().contains("Cannot attack during your truce"));
+
+	// An expired truce no longer protects, even before the modifier is cleaned up
+	diesel::update(active_modifiers::table.find(used.active.id))
+		.set((
+			active_modifiers::started_at.eq(Utc::now() - TimeDelta::hours(9)),
+			active_modifiers::expires_at.eq(Utc::now() - TimeDelta::hours(1)),
+		))
+		.execute(&mut conn)
+		.unwrap();
+	assert_eq!(active_truce(&mut conn, &pacifist.id).unwrap(), None);
+	assert!(validate_attack(&mut conn, &settings, &attacker.id, &pacifist.id).is_ok());
+}
@@ -0,0 +1,181 @@
+//! Integration tests for upgrade cancellation and building downgrades.
+//!
+//! These tests cover:
+//! - Refunding cancelled upgrades by the share of their time left
+//! - Rejecting cancellations of buildings that are not upgrading or already finished
+//! - Downgrading buildings with a refund, cancelling an upgrade underway on the way
+
+use chrono::{TimeDelta, Utc};
+use diesel::prelude::*;
+use empire::db::{DbConn, building_levels, building_upgrades, player_buildings, resources};
+use empire::domain::factions::FactionCode;
+use empire::domain::player::PlayerKey;
+use empire::domain::player::buildings::PlayerBuilding;
+use empire::game::buildings::building_operations::{
+	CANCEL_UPGRADE_REFUND_RATE, DOWNGRADE_REFUND_RATE, cancel_upgrade, confirm_upgrade,
+	downgrade_building, upgrade_building,
+};
+use empire::schema::{building, building_upgrade, player_building};
+
+use crate::common::TestHarness;
+use crate::fixtures::give_player_resources;
+
+/// Get one of the player's starter buildings, raised to `level`.
+fn get_building(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	name: &str,
+	level: i32,
+) -> PlayerBuilding {
+	let bld: PlayerBuilding = player_building::table
+		.inner_join(building::table)
+		.filter(player_building::player_id.eq(player_id))
+		.filter(building::name.eq(name))
+		.select(PlayerBuilding::as_select())
+		.first(conn)
+		.expect("Player has no such building");
+	diesel::update(player_building::table.find(bld.id))
+		.set(player_building::level.eq(level))
+		.returning(PlayerBuilding::as_returning())
+		.get_result(conn)
+		.expect("Failed to set the building level")
+}
+
+/// Get one of the player's starter Farms raised to `level`, with a Keep that allows upgrading it.
+fn get_farm(conn: &mut DbConn, player_id: &PlayerKey, level: i32) -> PlayerBuilding {
+	get_building(conn, player_id, "Keep", 5);
+	get_building(conn, player_id, "Farm", level)
+}
+
+fn wood(conn: &mut DbConn, player_id: &PlayerKey) -> i64 {
+	resources::get_by_player_id(conn, player_id).unwrap().wood
+}
+
+/// Moves the start of the farm's upgrade back so `elapsed` of its `total` time has passed.
+fn elapse_upgrade(conn: &mut DbConn, farm: &PlayerBuilding, elapsed: TimeDelta, total: TimeDelta) {
+	let now = Utc::now();
+	diesel::update(
+		building_upgrade::table
+			.filter(building_upgrade::player_building_id.eq(farm.id))
+			.filter(building_upgrade::completed_at.is_null()),
+	)
+	.set(building_upgrade::created_at.eq(now - elapsed))
+	.execute(conn)
+	.unwrap();
+	let eta = now - elapsed + total;
+	player_buildings::set_upgrade_eta(conn, &farm.id, Some(eta)).unwrap();
+}
+
+#[tokio::test]
+async fn test_cancel_upgrade_refunds_by_time_left() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("canceller", Some(FactionCode::Human));
+	give_player_resources(&mut conn, &player.id);
+	let farm = get_farm(&mut conn, &player.id, 1);
+
+	let before = wood(&mut conn, &player.id);
+	upgrade_building(&mut conn, &harness.app.job_queue, &farm.id).expect("Failed to start upgrade");
+	let ledger = building_upgrades::get_for_player_building(&mut conn, &farm.id).unwrap();
+	let cost = ledger[0].wood;
+	assert!(cost > 0, "The upgrade costs wood");
+	assert_eq!(wood(&mut conn, &player.id), before - cost);
+
+	// Half of the upgrade time has passed
+	elapse_upgrade(
+		&mut conn,
+		&farm,
+		TimeDelta::minutes(30),
+		TimeDelta::hours(1),
+	);
+	let (cancelled, refund) = cancel_upgrade(&mut conn, &farm.id).expect("Failed to cancel");
+	assert!(cancelled.upgrade_finishes_at.is_none());
+	assert_eq!(cancelled.level, 1);
+	let expected = (cost as f64 * CANCEL_UPGRADE_REFUND_RATE * 0.5) as i64;
+	assert!((refund.1 - expected).abs() <= 1, "{refund:?} vs {expected}");
+	assert_eq!(wood(&mut conn, &player.id), before - cost + refund.1);
+
+	let ledger = building_upgrades::get_for_player_building(&mut conn, &farm.id).unwrap();
+	assert!(ledger[0].cancelled_at.is_some());
+	assert!(ledger[0].completed_at.is_none());
+
+	let err = cancel_upgrade(&mut conn, &farm.id).expect_err("Nothing is left to cancel");
+	assert!(
+		err.to_string().contains("Building is not upgrading"),
+		"{err}"
+	);
+
+	// The building can be upgraded again, and its confirmation leaves the cancelled entry be
+	upgrade_building(&mut conn, &harness.app.job_queue, &farm.id)
+		.expect("Failed to restart upgrade");
+	let eta = Utc::now() - TimeDelta::seconds(1);
+	player_buildings::set_upgrade_eta(&mut conn, &farm.id, Some(eta)).unwrap();
+	let err = cancel_upgrade(&mut conn, &farm.id).expect_err("Finished upgrades are confirmed");
+	assert!(
+		err.to_string().contains("Upgrade has already finished"),
+		"{err}"
+	);
+	let confirmed = confirm_upgrade(&mut conn, &farm.id).expect("Failed to confirm");
+	assert_eq!(confirmed.level, 2);
+	let ledger = building_upgrades::get_for_player_building(&mut conn, &farm.id).unwrap();
+	assert!(
+		ledger[0].completed_at.is_none(),
+		"Cancelled upgrades stay incomplete"
+	);
+	assert!(ledger[1].completed_at.is_some());
+}
+
+#[tokio::test]
+async fn test_downgrade_refunds_level_cost() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("rebuilder", Some(FactionCode::Human));
+	give_player_resources(&mut conn, &player.id);
+	let farm = get_farm(&mut conn, &player.id, 3);
+	let level_cost = building_levels::get_by_bld_and_level(&mut conn, &farm.building_id, 3)
+		.unwrap()
+		.req_wood
+		.unwrap_or(0);
+
+	let before = wood(&mut conn, &player.id);
+	let (downgraded, refund) =
+		downgrade_building(&mut conn, &farm.id).expect("Failed to downgrade");
+	assert_eq!(downgraded.level, 2);
+	assert_eq!(refund.1, (level_cost as f64 * DOWNGRADE_REFUND_RATE) as i64);
+	assert_eq!(wood(&mut conn, &player.id), before + refund.1);
+
+	// An upgrade underway is cancelled and refunded along with the downgrade
+	upgrade_building(&mut conn, &harness.app.job_queue, &downgraded.id)
+		.expect("Failed to start upgrade");
+	let upgrade_cost =
+		building_upgrades::get_for_player_building(&mut conn, &farm.id).unwrap()[0].wood;
+	let before = wood(&mut conn, &player.id);
+	let (downgraded, refund) =
+		downgrade_building(&mut conn, &farm.id).expect("Failed to downgrade");
+	assert_eq!(downgraded.level, 1);
+	assert!(downgraded.upgrade_finishes_at.is_none());
+	let level_refund = building_levels::get_by_bld_and_level(&mut conn, &farm.building_id, 2)
+		.unwrap()
+		.req_wood
+		.map_or(0, |cost| (cost as f64 * DOWNGRADE_REFUND_RATE) as i64);
+	let upgrade_refund = refund.1 - level_refund;
+	assert!(
+		upgrade_refund > 0 && upgrade_refund <= upgrade_cost,
+		"{upgrade_refund} of {upgrade_cost}"
+	);
+	assert_eq!(wood(&mut conn, &player.id), before + refund.1);
+	let ledger = building_upgrades::get_for_player_building(&mut conn, &farm.id).unwrap();
+	assert!(ledger[0].cancelled_at.is_some());
+
+	let err = downgrade_building(&mut conn, &farm.id).expect_err("Level 1 is the lowest");
+	assert!(
+		err.to_string().contains("Building is at its lowest level"),
+		"{err}"
+	);
+	assert_eq!(
+		player_buildings::get_by_id(&mut conn, &farm.id)
+			.unwrap()
+			.level,
+		1
+	);
+}
@@ -0,0 +1,231 @@
+//! Integration tests for modifiers scoped to a single building.
+//!
+//! These tests cover:
+//! - Building-scoped resource modifiers only raising the production of their building
+//! - Stacking building-scoped modifiers with the player-wide ones
+//! - Rejecting building scopes on foreign buildings and non-resource modifiers
+
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use empire::db::{DbConn, active_modifiers, modifiers, player_buildings, settlements};
+use empire::domain::factions::FactionCode;
+use empire::domain::modifier::active_modifier::{ModifierSourceType, NewActiveModifier};
+use empire::domain::modifier::{
+	MagnitudeKind, ModifierKey, ModifierTarget, NewModifier, StackingBehaviour,
+};
+use empire::domain::player::PlayerKey;
+use empire::domain::player::buildings::{NewPlayerBuilding, PlayerBuilding, PlayerBuildingKey};
+use empire::domain::player::resource::ResourceType;
+use empire::game::modifiers::modifier_service::ModifierService;
+use empire::game::resources::resource_operations::calc_prod_rates;
+use empire::schema::{building, building_resource, player_building};
+use uuid::Uuid;
+
+use crate::common::TestHarness;
+
+/// Creates a modifier definition with a unique name.
+fn create_modifier(
+	conn: &mut DbConn,
+	magnitude: &str,
+	target_type: ModifierTarget,
+	target_resource: Option<ResourceType>,
+	stacking: (StackingBehaviour, Option<&str>),
+) -> ModifierKey {
+	modifiers::create(
+		conn,
+		NewModifier {
+			name: format!("test_modifier_{}", Uuid::new_v4()),
+			description: "Test modifier".to_string(),
+			magnitude: BigDecimal::from_str(magnitude).unwrap(),
+			magnitude_kind: MagnitudeKind::Percentage,
+			target_type,
+			target_resource,
+			stacking_behaviour: Some(stacking.0),
+			stacking_group: stacking.1.map(str::to_string),
+		},
+	)
+	.expect("Failed to create modifier")
+	.id
+}
+
+/// A modifier for a player, scoped to a building if one is given.
+fn new_active(
+	player_id: &PlayerKey,
+	modifier_id: ModifierKey,
+	player_bld_id: Option<PlayerBuildingKey>,
+) -> NewActiveModifier {
+	NewActiveModifier {
+		player_id: *player_id,
+		modifier_id,
+		started_at: None,
+		expires_at: None,
+		source_type: ModifierSourceType::Item,
+		source_id: None,
+		player_building_id: player_bld_id,
+	}
+}
+
+fn activate(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	modifier_id: ModifierKey,
+	player_bld_id: Option<PlayerBuildingKey>,
+) {
+	active_modifiers::create(conn, new_active(player_id, modifier_id, player_bld_id))
+		.expect("Failed to activate modifier");
+}
+
+/// Builds another level 1 Lumberyard for a player.
+fn build_lumberyard(conn: &mut DbConn, player_id: &PlayerKey) -> PlayerBuilding {
+	let bld_id: i32 = building::table
+		.filter(building::name.eq("Lumberyard"))
+		.select(building::id)
+		.first(conn)
+		.expect("Lumberyard missing");
+	player_buildings::construct(
+		conn,
+		NewPlayerBuilding {
+			player_id: *player_id,
+			building_id: bld_id,
+			level: Some(1),
+			upgrade_finishes_at: None,
+			settlement_id: None,
+		},
+	)
+	.expect("Failed to build a Lumberyard")
+}
+
+/// Wood a level 1 Lumberyard produces per hour without modifiers.
+fn lumberyard_wood(conn: &mut DbConn, bld: &PlayerBuilding) -> BigDecimal {
+	let wood: i64 = building_resource::table
+		.filter(building_resource::building_id.eq(bld.building_id))
+		.filter(building_resource::building_level.eq(bld.level))
+		.select(building_resource::wood)
+		.first(conn)
+		.expect("Lumberyard produces nothing");
+	BigDecimal::from(wood)
+}
+
+fn wood_rate(conn: &mut DbConn, player_id: &PlayerKey) -> BigDecimal {
+	let capital = settlements::get_capital(conn, player_id).unwrap();
+	calc_prod_rates(conn, &capital).unwrap()[&ResourceType::Wood].clone()
+}
+
+#[tokio::test]
+async fn test_building_modifiers_only_raise_their_building() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("lumberjack", Some(FactionCode::Human));
+	let lumberyard = build_lumberyard(&mut conn, &player.id);
+	let before = wood_rate(&mut conn, &player.id);
+
+	// Humans get +15% wood player-wide, additive with the building's +25%
+	let bonus = create_modifier(
+		&mut conn,
+		"0.25",
+		ModifierTarget::Resource,
+		Some(ResourceType::Wood),
+		(StackingBehaviour::Additive, None),
+	);
+	activate(&mut conn, &player.id, bonus, Some(lumberyard.id));
+
+	let after = wood_rate(&mut conn, &player.id);
+	let bonus_wood =
+		lumberyard_wood(&mut conn, &lumberyard) * BigDecimal::from_str("0.25").unwrap();
+	assert!(bonus_wood > 0, "Lumberyards produce wood");
+	assert_eq!(after - &before, bonus_wood);
+
+	// Demolishing the building removes its modifiers
+	diesel::delete(player_building::table.find(lumberyard.id))
+		.execute(&mut conn)
+		.unwrap();
+	let scoped = active_modifiers::get_by_player_id(&mut conn, &player.id)
+		.unwrap()
+		.into_iter()
+		.filter(|m| m.player_building_id.is_some())
+		.count();
+	assert_eq!(scoped, 0);
+}
+
+#[tokio::test]
+async fn test_building_modifiers_stack_with_player_modifiers() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("stacker", Some(FactionCode::Human));
+	let lumberyard = build_lumberyard(&mut conn, &player.id);
+	let highest = |conn: &mut DbConn, magnitude| {
+		create_modifier(
+			conn,
+			magnitude,
+			ModifierTarget::Resource,
+			Some(ResourceType::Wood),
+			(StackingBehaviour::HighestOnly, Some("wood_blessing")),
+		)
+	};
+
+	let player_wide = highest(&mut conn, "0.20");
+	activate(&mut conn, &player.id, player_wide, None);
+	let before = wood_rate(&mut conn, &player.id);
+
+	// A weaker bonus in the same group does not count
+	let weaker = highest(&mut conn, "0.10");
+	activate(&mut conn, &player.id, weaker, Some(lumberyard.id));
+	assert_eq!(wood_rate(&mut conn, &player.id), before);
+
+	// A stronger one replaces the player-wide bonus for its building
+	let stronger = highest(&mut conn, "0.30");
+	activate(&mut conn, &player.id, stronger, Some(lumberyard.id));
+	let human_bonus = BigDecimal::from_str("1.15").unwrap();
+	let expected = lumberyard_wood(&mut conn, &lumberyard)
+		* human_bonus
+		* BigDecimal::from_str("0.10").unwrap();
+	assert_eq!(wood_rate(&mut conn, &player.id) - before, expected);
+}
+
+#[tokio::test]
+async fn test_building_scopes_are_validated() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let mut service = ModifierService::new(&harness.app.db_pool, &harness.app.modifier_system);
+	let player = harness.create_named_user("scoped", Some(FactionCode::Human));
+	let stranger = harness.create_named_user("stranger", Some(FactionCode::Human));
+	let lumberyard = build_lumberyard(&mut conn, &stranger.id);
+
+	let wood = create_modifier(
+		&mut conn,
+		"0.25",
+		ModifierTarget::Resource,
+		Some(ResourceType::Wood),
+		(StackingBehaviour::Additive, None),
+	);
+	let err = service
+		.apply_modifier(new_active(&player.id, wood, Some(lumberyard.id)))
+		.await
+		.expect_err("The building belongs to someone else");
+	assert!(err.to_string().contains("Building not found"), "{err}");
+
+	let training = create_modifier(
+		&mut conn,
+		"0.25",
+		ModifierTarget::Training,
+		None,
+		(StackingBehaviour::Additive, None),
+	);
+	let err = service
+		.apply_modifier(new_active(&stranger.id, training, Some(lumberyard.id)))
+		.await
+		.expect_err("Training is not produced by a building");
+	assert!(
+		err.to_string()
+			.contains("Only resource modifiers can be scoped to a building"),
+		"{err}"
+	);
+
+	let applied = service
+		.apply_modifier(new_active(&stranger.id, wood, Some(lumberyard.id)))
+		.await
+		.expect("Failed to apply building modifier");
+	assert_eq!(applied.player_building_id, Some(lumberyard.id));
+}
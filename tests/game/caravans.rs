@@ -0,0 +1,134 @@
+//! Integration tests for resource caravans.
+//!
+//! These tests cover:
+//! - Loading the cargo and scheduling the arrival when a caravan is sent
+//! - Delivering up to the receiver's storage caps and returning the overflow
+//! - Rejecting invalid shipments
+
+use diesel::prelude::*;
+use empire::db::{DbConn, caravans, resources};
+use empire::domain::caravan::{CaravanStatus, Cargo};
+use empire::domain::jobs::{Job, JobType};
+use empire::domain::player::PlayerKey;
+use empire::game::resources::caravan_operations::{
+	CARAVAN_BASE_TRAVEL_TIME, CARAVAN_TRAVEL_TIME_PER_LOAD, deliver_caravan, send_caravan,
+	travel_time,
+};
+use empire::schema::job;
+
+use crate::common::TestHarness;
+use crate::fixtures::{set_player_resources, set_storage_caps};
+
+#[test]
+fn test_travel_time_grows_with_the_cargo() {
+	let cargo = |wood| Cargo {
+		wood,
+		..Cargo::default()
+	};
+	assert_eq!(
+		travel_time(&cargo(1)).unwrap(),
+		CARAVAN_BASE_TRAVEL_TIME + CARAVAN_TRAVEL_TIME_PER_LOAD
+	);
+	assert_eq!(
+		travel_time(&cargo(1000)).unwrap(),
+		travel_time(&cargo(1)).unwrap()
+	);
+	assert_eq!(
+		travel_time(&cargo(2500)).unwrap(),
+		CARAVAN_BASE_TRAVEL_TIME + CARAVAN_TRAVEL_TIME_PER_LOAD * 3
+	);
+}
+
+#[tokio::test]
+async fn test_caravans_deliver_up_to_the_storage_caps() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let sender = harness.create_named_user("sender", None).id;
+	let receiver = harness.create_named_user("receiver", None).id;
+	for (player, amount) in [(&sender, 1000), (&receiver, 900)] {
+		set_storage_caps(&mut conn, player, 1000);
+		set_player_resources(&mut conn, player, amount);
+	}
+
+	let caravan = send_caravan(
+		&mut conn,
+		&harness.app.job_queue,
+		&sender,
+		&receiver,
+		Cargo {
+			food: 50,
+			wood: 300,
+			..Cargo::default()
+		},
+	)
+	.unwrap();
+	assert_eq!(caravan.status, CaravanStatus::Travelling);
+	let res = resources::get_by_player_id(&mut conn, &sender).unwrap();
+	assert_eq!((res.food, res.wood, res.gold), (950, 700, 1000));
+
+	let arrival: Job = job::table
+		.find(caravan.job_id.expect("Caravan should have an arrival job"))
+		.select(Job::as_select())
+		.first(&mut conn)
+		.unwrap();
+	assert_eq!(arrival.job_type, JobType::Resource);
+	assert_eq!(arrival.run_at, caravan.arrives_at);
+
+	let delivery = deliver_caravan(&mut conn, &caravan.id).unwrap();
+	assert_eq!(
+		(delivery.delivered.food, delivery.delivered.wood),
+		(50, 100)
+	);
+	assert_eq!((delivery.returned.food, delivery.returned.wood), (0, 200));
+
+	let res = resources::get_by_player_id(&mut conn, &receiver).unwrap();
+	assert_eq!((res.food, res.wood), (950, 1000));
+	let res = resources::get_by_player_id(&mut conn, &sender).unwrap();
+	assert_eq!((res.food, res.wood), (950, 900));
+
+	// A retried arrival does not deliver twice
+	let again = deliver_caravan(&mut conn, &caravan.id).unwrap();
+	assert_eq!(again.delivered, Cargo::default());
+	assert_eq!(again.returned, Cargo::default());
+	let caravan = caravans::get_by_id(&mut conn, &caravan.id).unwrap();
+	assert_eq!(caravan.status, CaravanStatus::Delivered);
+	assert!(caravan.delivered_at.is_some());
+}
+
+#[tokio::test]
+async fn test_invalid_caravans_are_rejected() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let queue = &harness.app.job_queue;
+	let sender = harness.create_named_user("sender", None).id;
+	let receiver = harness.create_named_user("receiver", None).id;
+	set_storage_caps(&mut conn, &sender, 1000);
+	set_player_resources(&mut conn, &sender, 100);
+
+	let send = |conn: &mut DbConn, receiver: &PlayerKey, cargo: Cargo| {
+		send_caravan(conn, queue, &sender, receiver, cargo)
+			.unwrap_err()
+			.to_string()
+	};
+	let gold = |gold| Cargo {
+		gold,
+		..Cargo::default()
+	};
+	assert!(send(&mut conn, &sender, gold(10)).contains("yourself"));
+	assert!(send(&mut conn, &receiver, gold(0)).contains("not empty"));
+	assert!(send(&mut conn, &receiver, gold(-5)).contains("non-negative"));
+	assert!(send(&mut conn, &receiver, gold(101)).contains("Not enough resources"));
+	assert!(send(&mut conn, &uuid::Uuid::new_v4(), gold(10)).contains("Receiver not found"));
+	let overflowing = Cargo {
+		food: i64::MAX,
+		..gold(1)
+	};
+	assert!(send(&mut conn, &receiver, overflowing).contains("too large"));
+	assert!(send(&mut conn, &receiver, gold(i64::MAX)).contains("too large"));
+	assert_eq!(
+		resources::get_by_player_id(&mut conn, &sender)
+			.unwrap()
+			.gold,
+		100
+	);
+}
@@ -0,0 +1,180 @@
+//! Integration tests for the chat.
+//!
+//! These tests cover:
+//! - Posting in the global and alliance channels, and who receives the messages
+//! - Reading the history of a channel, newest first and paged back by time
+//! - The flood control, and mutes given and lifted by moderators
+
+use chrono::Utc;
+use empire::configuration::{AllianceSettings, ChatSettings};
+use empire::domain::chat::ChatChannel;
+use empire::game::alliances::alliance_operations::{create_alliance, invite, join};
+use empire::game::chat::chat_operations::{active_mute, history, mute, post, unmute};
+
+use crate::common::TestHarness;
+
+#[tokio::test]
+async fn test_messages_reach_the_players_in_the_channel() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let settings = ChatSettings::default();
+	let founder = harness.create_named_user("founder", None).id;
+	let recruit = harness.create_named_user("recruit", None).id;
+	let outsider = harness.create_named_user("outsider", None).id;
+
+	let broadcast = post(
+		&mut conn,
+		&settings,
+		&outsider,
+		ChatChannel::Global,
+		" Hello! ",
+	)
+	.unwrap();
+	assert_eq!(broadcast.line.message.body, "Hello!");
+	assert_eq!(broadcast.line.player_name.as_deref(), Some("outsider"));
+	assert!(broadcast.recipients.is_none());
+	assert!(
+		broadcast.is_for(&founder),
+		"The global channel reaches everyone"
+	);
+
+	// The alliance channel takes an alliance, and reaches its members only
+	let err = post(&mut conn, &settings, &founder, ChatChannel::Alliance, "Hi").unwrap_err();
+	assert!(err.to_string().contains("Not in an alliance"));
+	let err = history(&mut conn, &founder, ChatChannel::Alliance, None, None).unwrap_err();
+	assert!(err.to_string().contains("Not in an alliance"));
+
+	let alliance_id = create_alliance(&mut conn, &founder, "Iron Pact", "IRON")
+		.unwrap()
+		.alliance
+		.id;
+	invite(&mut conn, &founder, &alliance_id, &recruit).unwrap();
+	join(
+		&mut conn,
+		&AllianceSettings::default(),
+		&recruit,
+		&alliance_id,
+	)
+	.unwrap();
+
+	let broadcast = post(
+		&mut conn,
+		&settings,
+		&recruit,
+		ChatChannel::Alliance,
+		"Orders?",
+	)
+	.unwrap();
+	assert_eq!(broadcast.line.message.alliance_id, Some(alliance_id));
+	assert!(broadcast.is_for(&founder));
+	assert!(broadcast.is_for(&recruit));
+	assert!(!broadcast.is_for(&outsider));
+
+	let alliance_lines = history(&mut conn, &founder, ChatChannel::Alliance, None, None).unwrap();
+	assert_eq!(alliance_lines.len(), 1);
+	assert_eq!(alliance_lines[0].message.body, "Orders?");
+	let global_lines = history(&mut conn, &founder, ChatChannel::Global, None, None).unwrap();
+	assert_eq!(
+		global_lines.len(),
+		1,
+		"Alliance messages stay out of the global channel"
+	);
+
+	let err = post(&mut conn, &settings, &founder, ChatChannel::Global, "   ").unwrap_err();
+	assert!(err.to_string().contains("1 to 500 characters"));
+	let err = post(
+		&mut conn,
+		&settings,
+		&founder,
+		ChatChannel::Global,
+		&"a".repeat(501),
+	)
+	.unwrap_err();
+	assert!(err.to_string().contains("1 to 500 characters"));
+}
+
+#[tokio::test]
+async fn test_history_is_newest_first_and_paged_back() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let settings = ChatSettings {
+		flood_max_messages: 10,
+		..ChatSettings::default()
+	};
+	let player = harness.create_named_user("talker", None).id;
+	for i in 1..=5 {
+		post(
+			&mut conn,
+			&settings,
+			&player,
+			ChatChannel::Global,
+			&format!("Line {i}"),
+		)
+		.unwrap();
+	}
+
+	let newest = history(&mut conn, &player, ChatChannel::Global, None, Some(2)).unwrap();
+	let bodies: Vec<_> = newest
+		.iter()
+		.map(|line| line.message.body.as_str())
+		.collect();
+	assert_eq!(bodies, ["Line 5", "Line 4"]);
+
+	let before = newest.last().unwrap().message.created_at;
+	let older = history(&mut conn, &player, ChatChannel::Global, Some(before), None).unwrap();
+	let bodies: Vec<_> = older
+		.iter()
+		.map(|line| line.message.body.as_str())
+		.collect();
+	assert_eq!(bodies, ["Line 3", "Line 2", "Line 1"]);
+}
+
+#[tokio::test]
+async fn test_flooding_and_muted_players_are_refused() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let settings = ChatSettings {
+		flood_max_messages: 2,
+		flood_window_seconds: 60,
+	};
+	let moderator = harness.create_named_user("moderator", None).id;
+	let spammer = harness.create_named_user("spammer", None).id;
+	let troll = harness.create_named_user("troll", None).id;
+
+	post(&mut conn, &settings, &spammer, ChatChannel::Global, "One").unwrap();
+	post(&mut conn, &settings, &spammer, ChatChannel::Global, "Two").unwrap();
+	let err = post(&mut conn, &settings, &spammer, ChatChannel::Global, "Three").unwrap_err();
+	assert!(err.to_string().contains("Too many chat messages"));
+	// The limit is per player
+	post(&mut conn, &settings, &troll, ChatChannel::Global, "Hey").unwrap();
+
+	let err = mute(&mut conn, &moderator, &troll, 0, None).unwrap_err();
+	assert!(err.to_string().contains("from a minute to 30 days"));
+	let err = mute(&mut conn, &moderator, &uuid::Uuid::new_v4(), 10, None).unwrap_err();
+	assert!(err.to_string().contains("Player not found"));
+
+	let muted = mute(&mut conn, &moderator, &troll, 10, Some(" Spam ".into())).unwrap();
+	assert_eq!(muted.reason.as_deref(), Some("Spam"));
+	assert_eq!(muted.muted_by, Some(moderator));
+	let err = post(&mut conn, &settings, &troll, ChatChannel::Global, "Hey").unwrap_err();
+	assert!(err.to_string().contains("You are muted"));
+	assert!(
+		history(&mut conn, &troll, ChatChannel::Global, None, None).is_ok(),
+		"Muted players still read the channels"
+	);
+
+	// Muting again replaces the mute
+	let replaced = mute(&mut conn, &moderator, &troll, 60, None).unwrap();
+	assert!(replaced.muted_until > muted.muted_until);
+	assert!(replaced.reason.is_none());
+
+	unmute(&mut conn, &moderator, &troll).unwrap();
+	assert!(
+		active_mute(&mut conn, &troll, Utc::now())
+			.unwrap()
+			.is_none()
+	);
+	let err = unmute(&mut conn, &moderator, &troll).unwrap_err();
+	assert!(err.to_string().contains("Player is not muted"));
+	post(&mut conn, &settings, &troll, ChatChannel::Global, "Sorry").unwrap();
+}
@@ -0,0 +1,155 @@
+//! Integration tests for the construction queue.
+//!
+//! These tests cover:
+//! - Deriving the queue capacity from the Keep level
+//! - Queueing several upgrades of a building, each starting after the one ahead of it
+//! - Completing entries through their jobs, idempotently, and starting the next one
+//! - Keeping direct upgrades and downgrades away from buildings with queued upgrades
+
+use diesel::prelude::*;
+use empire::db::{DbConn, building_upgrades, player_buildings, resources};
+use empire::domain::building::construction::ConstructionStatus;
+use empire::domain::factions::FactionCode;
+use empire::domain::jobs::JobType;
+use empire::domain::player::PlayerKey;
+use empire::domain::player::buildings::PlayerBuilding;
+use empire::game::buildings::building_operations::{downgrade_building, upgrade_building};
+use empire::game::buildings::construction_operations::{
+	complete_construction, construction_slots, get_queue, queue_upgrade,
+};
+use empire::schema::{building, player_building};
+
+use crate::common::TestHarness;
+use crate::fixtures::give_player_resources;
+
+/// Get one of the player's starter buildings, raised to `level`.
+fn get_building(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	name: &str,
+	level: i32,
+) -> PlayerBuilding {
+	let bld: PlayerBuilding = player_building::table
+		.inner_join(building::table)
+		.filter(player_building::player_id.eq(player_id))
+		.filter(building::name.eq(name))
+		.select(PlayerBuilding::as_select())
+		.first(conn)
+		.expect("Player has no such building");
+	diesel::update(player_building::table.find(bld.id))
+		.set(player_building::level.eq(level))
+		.returning(PlayerBuilding::as_returning())
+		.get_result(conn)
+		.expect("Failed to set the building level")
+}
+
+#[test]
+fn test_construction_slots_grow_with_keep() {
+	assert_eq!(construction_slots(0), 1);
+	assert_eq!(construction_slots(1), 1);
+	assert_eq!(construction_slots(3), 2);
+	assert_eq!(construction_slots(5), 2);
+	assert_eq!(construction_slots(6), 3);
+	assert_eq!(construction_slots(10), 4);
+}
+
+#[tokio::test]
+async fn test_queued_upgrades_complete_in_order() {
+	let harness = TestHarness::new();
+	let job_queue = &harness.app.job_queue;
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("queue_builder", Some(FactionCode::Human));
+	give_player_resources(&mut conn, &player.id);
+	get_building(&mut conn, &player.id, "Keep", 5);
+	let farm = get_building(&mut conn, &player.id, "Farm", 2);
+
+	let queue = get_queue(&mut conn, &player.id).unwrap();
+	assert_eq!(queue.capacity, 2, "A level 5 Keep gives a second slot");
+	assert!(queue.entries.is_empty());
+
+	let before = resources::get_by_player_id(&mut conn, &player.id)
+		.unwrap()
+		.wood;
+	let first = queue_upgrade(&mut conn, job_queue, &player.id, &farm.id).expect("Failed to queue");
+	let second =
+		queue_upgrade(&mut conn, job_queue, &player.id, &farm.id).expect("Failed to queue again");
+	assert_eq!((first.level, second.level), (3, 4));
+	assert_eq!(first.status, ConstructionStatus::InProgress);
+	assert_eq!(second.status, ConstructionStatus::Pending);
+	assert_eq!(second.starts_at, first.completes_at);
+	assert!(second.completes_at > first.completes_at);
+
+	// Both upgrades are paid up front and recorded in the ledger
+	let ledger = building_upgrades::get_for_player_building(&mut conn, &farm.id).unwrap();
+	assert_eq!(ledger.len(), 2);
+	let spent = ledger[0].wood + ledger[1].wood;
+	assert_eq!(
+		resources::get_by_player_id(&mut conn, &player.id)
+			.unwrap()
+			.wood,
+		before - spent
+	);
+
+	// Every completion is scheduled as a building job
+	let job = job_queue
+		.get_job(&first.job_id.expect("Job is linked"))
+		.unwrap();
+	assert_eq!(job.job_type, JobType::Building);
+	assert_eq!(job.run_at, first.completes_at);
+
+	let err = queue_upgrade(&mut conn, job_queue, &player.id, &farm.id)
+		.expect_err("Both slots are taken");
+	assert!(
+		err.to_string().contains("Construction queue is full"),
+		"{err}"
+	);
+	let err = upgrade_building(&mut conn, job_queue, &farm.id).expect_err("Farm is in the queue");
+	assert!(
+		err.to_string().contains("Building has queued upgrades"),
+		"{err}"
+	);
+	let err = downgrade_building(&mut conn, &farm.id).expect_err("Farm is in the queue");
+	assert!(
+		err.to_string().contains("Building has queued upgrades"),
+		"{err}"
+	);
+
+	let completed = complete_construction(&mut conn, &first.id).expect("Failed to complete");
+	assert_eq!(completed.status, ConstructionStatus::Completed);
+	let again = complete_construction(&mut conn, &first.id).expect("Completion is idempotent");
+	assert_eq!(again.completed_at, completed.completed_at);
+	let farm_now = player_buildings::get_by_id(&mut conn, &farm.id).unwrap();
+	assert_eq!(farm_now.level, 3, "The farm is raised only once");
+
+	let queue = get_queue(&mut conn, &player.id).unwrap();
+	assert_eq!(queue.entries.len(), 1);
+	assert_eq!(queue.entries[0].id, second.id);
+	assert_eq!(queue.entries[0].status, ConstructionStatus::InProgress);
+
+	complete_construction(&mut conn, &second.id).expect("Failed to complete");
+	let farm_now = player_buildings::get_by_id(&mut conn, &farm.id).unwrap();
+	assert_eq!(farm_now.level, 4);
+	assert!(get_queue(&mut conn, &player.id).unwrap().entries.is_empty());
+	let ledger = building_upgrades::get_for_player_building(&mut conn, &farm.id).unwrap();
+	assert!(ledger.iter().all(|upgrade| upgrade.completed_at.is_some()));
+}
+
+#[tokio::test]
+async fn test_upgrading_buildings_cannot_be_queued() {
+	let harness = TestHarness::new();
+	let job_queue = &harness.app.job_queue;
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("impatient", Some(FactionCode::Human));
+	give_player_resources(&mut conn, &player.id);
+	get_building(&mut conn, &player.id, "Keep", 5);
+	let farm = get_building(&mut conn, &player.id, "Farm", 1);
+
+	upgrade_building(&mut conn, job_queue, &farm.id).expect("Failed to start upgrade");
+	let err = queue_upgrade(&mut conn, job_queue, &player.id, &farm.id)
+		.expect_err("Farm is upgrading directly");
+	assert!(
+		err.to_string().contains("Building is already upgrading"),
+		"{err}"
+	);
+	assert!(get_queue(&mut conn, &player.id).unwrap().entries.is_empty());
+}
@@ -0,0 +1,123 @@
+//! Integration tests for the job dead-letter queue.
+//!
+//! These tests cover:
+//! - Jobs that exhaust their retries move to the dead-letter table with their error history
+//! - Requeueing restores the job under its original id, discarding removes it for good
+
+use chrono::Utc;
+use diesel::prelude::*;
+use empire::db::DbConn;
+use empire::domain::jobs::{Job, JobError, JobKey, JobStatus, JobType};
+use empire::job_queue::JobPriority;
+use empire::schema::job;
+
+use crate::common::TestHarness;
+
+fn get_job(conn: &mut DbConn, job_id: &JobKey) -> Job {
+	job::table
+		.find(job_id)
+		.select(Job::as_select())
+		.first(conn)
+		.expect("Job not found")
+}
+
+#[tokio::test]
+async fn test_exhausted_jobs_are_dead_lettered() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+	let payload = serde_json::json!({ "dead_letter": "exhausted" });
+
+	let job_id = app
+		.job_queue
+		.enqueue(JobType::Combat, &payload, JobPriority::High, Utc::now())
+		.unwrap();
+	let max_retries = get_job(&mut conn, &job_id).max_retries;
+
+	// The first failure does not count as a retry
+	for attempt in 0..=max_retries {
+		app.job_queue
+			.fail_job(&job_id, format!("failure {attempt}"))
+			.unwrap();
+	}
+	let failed = get_job(&mut conn, &job_id);
+	assert_eq!(failed.status, JobStatus::Failed);
+	assert_eq!(failed.retries, max_retries);
+
+	app.job_queue.fail_job(&job_id, "final failure").unwrap();
+	let remaining: i64 = job::table
+		.find(job_id)
+		.count()
+		.get_result(&mut conn)
+		.unwrap();
+	assert_eq!(remaining, 0, "Exhausted job should leave the queue");
+
+	let dead = app.job_queue.get_dead_letter(&job_id).unwrap();
+	assert_eq!(dead.job_type, JobType::Combat);
+	assert_eq!(dead.payload, payload);
+	assert_eq!(dead.retries, max_retries + 1);
+	assert_eq!(dead.last_error.as_deref(), Some("final failure"));
+	let history: Vec<JobError> = serde_json::from_value(dead.error_history).unwrap();
+	assert_eq!(history.len() as i32, max_retries + 2);
+	assert_eq!(history[0].error, "failure 0");
+	assert_eq!(history.last().unwrap().error, "final failure");
+
+	let listed = app
+		.job_queue
+		.list_dead_letters(Some(JobType::Combat), None, None)
+		.unwrap();
+	assert!(listed.jobs.iter().any(|dead| dead.id == job_id));
+	let other_type = app
+		.job_queue
+		.list_dead_letters(Some(JobType::Training), None, None)
+		.unwrap();
+	assert!(other_type.jobs.iter().all(|dead| dead.id != job_id));
+	assert!(
+		app.job_queue
+			.count_dead_lettered_since(Utc::now() - chrono::TimeDelta::minutes(1))
+			.unwrap() >= 1
+	);
+}
+
+#[tokio::test]
+async fn test_requeue_and_discard_dead_letters() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	let mut bury = |tag: &str| {
+		let job_id = app
+			.job_queue
+			.enqueue(
+				JobType::Combat,
+				serde_json::json!({ "dead_letter": tag }),
+				JobPriority::High,
+				Utc::now(),
+			)
+			.unwrap();
+		let max_retries = get_job(&mut conn, &job_id).max_retries;
+		for attempt in 0..=max_retries + 1 {
+			app.job_queue
+				.fail_job(&job_id, format!("{tag} {attempt}"))
+				.unwrap();
+		}
+		job_id
+	};
+	let requeued_id = bury("requeue");
+	let discarded_id = bury("discard");
+
+	let requeued: Job = app.job_queue.requeue_dead_letter(&requeued_id).unwrap();
+	assert_eq!(requeued.id, requeued_id);
+	assert_eq!(requeued.status, JobStatus::Pending);
+	assert_eq!(requeued.retries, 0);
+	assert!(requeued.last_error.is_none());
+	let history: Vec<JobError> = serde_json::from_value(requeued.error_history).unwrap();
+	assert!(!history.is_empty(), "Requeued job should keep its history");
+
+	let err = app.job_queue.get_dead_letter(&requeued_id).unwrap_err();
+	assert!(err.to_string().contains("not found"));
+	let err = app.job_queue.requeue_dead_letter(&requeued_id).unwrap_err();
+	assert!(err.to_string().contains("not found"));
+
+	assert!(app.job_queue.discard_dead_letter(&discarded_id).unwrap());
+	assert!(!app.job_queue.discard_dead_letter(&discarded_id).unwrap());
+	assert!(app.job_queue.get_dead_letter(&discarded_id).is_err());
+}
@@ -0,0 +1,189 @@
+//! Integration tests for scout missions.
+//!
+//! These tests cover:
+//! - Sending scouts takes them out of the army, and only as many as the player has
+//! - Completing a mission writes the intel report once and brings the scouts home
+//! - The Watchtower of the defender adds noise to the estimates and spots the scouts
+
+use diesel::prelude::*;
+use empire::configuration::{EspionageSettings, ProtectionSettings};
+use empire::db::{DbConn, player_units, resources, settlements, units};
+use empire::domain::message::MessageKind;
+use empire::domain::player::{Player, PlayerKey};
+use empire::domain::unit::UnitKey;
+use empire::game::combat::espionage_operations::{
+	SCOUT_UNIT_NAME, WATCHTOWER_NAME, complete_mission, get_report, list_reports, noise_percent,
+	send_scouts,
+};
+use empire::game::mail::mail_operations::list_inbox;
+use empire::schema::{building, player_building, player_resource as pr};
+
+use crate::common::TestHarness;
+
+fn protection() -> ProtectionSettings {
+	ProtectionSettings {
+		beginner_shield_days: 0,
+		beginner_shield_max_points: None,
+		beginner_shield_max_keep_level: None,
+	}
+}
+
+fn scout_id(conn: &mut DbConn) -> UnitKey {
+	units::find_by_name(conn, SCOUT_UNIT_NAME)
+		.unwrap()
+		.expect("Scout not seeded")
+		.id
+}
+
+fn scout_count(conn: &mut DbConn, player_id: &PlayerKey) -> i64 {
+	let scout_id = scout_id(conn);
+	player_units::get_for_player(conn, player_id)
+		.unwrap()
+		.into_iter()
+		.filter(|unit| unit.unit_id == scout_id)
+		.map(|unit| unit.quantity)
+		.sum()
+}
+
+/// Builds a Watchtower of `level` in the capital of the player.
+fn build_watchtower(conn: &mut DbConn, player: &Player, level: i32) {
+	let capital = settlements::get_capital(conn, &player.id).unwrap();
+	let watchtower_id: i32 = building::table
+		.filter(building::name.eq(WATCHTOWER_NAME))
+		.select(building::id)
+		.first(conn)
+		.expect("Watchtower not seeded");
+	diesel::insert_into(player_building::table)
+		.values((
+			player_building::player_id.eq(player.id),
+			player_building::settlement_id.eq(capital.id),
+			player_building::building_id.eq(watchtower_id),
+			player_building::level.eq(level),
+		))
+		.execute(conn)
+		.expect("Failed to build the Watchtower");
+}
+
+#[tokio::test]
+async fn test_scouts_report_on_the_capital_and_come_home() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let settings = EspionageSettings::default();
+	let spy = harness.create_named_user("spy", None);
+	let target = harness.create_named_user("target", None);
+	let scout = scout_id(&mut conn);
+	player_units::add_units(&mut conn, &spy.id, &scout, 5).unwrap();
+	diesel::update(pr::table.filter(pr::player_id.eq(target.id)))
+		.set((pr::food.eq(123), pr::gold.eq(45)))
+		.execute(&mut conn)
+		.unwrap();
+
+	let err = send_scouts(
+		&mut conn,
+		&harness.app.job_queue,
+		&settings,
+		&protection(),
+		&spy.id,
+		&target.id,
+		6,
+	)
+	.unwrap_err();
+	assert!(err.to_string().contains("Not enough scouts"));
+	let err = send_scouts(
+		&mut conn,
+		&harness.app.job_queue,
+		&settings,
+		&protection(),
+		&spy.id,
+		&target.id,
+		0,
+	)
+	.unwrap_err();
+	assert!(err.to_string().contains("At least one scout"));
+	assert_eq!(scout_count(&mut conn, &spy.id), 5);
+
+	let mission = send_scouts(
+		&mut conn,
+		&harness.app.job_queue,
+		&settings,
+		&protection(),
+		&spy.id,
+		&target.id,
+		3,
+	)
+	.unwrap();
+	assert!(mission.job_id.is_some());
+	assert!(!mission.is_completed());
+	assert_eq!(scout_count(&mut conn, &spy.id), 2);
+
+	let report = complete_mission(&mut conn, &settings, &mission.id)
+		.unwrap()
+		.expect("Mission should report");
+	let storage = resources::get_by_player_id(&mut conn, &target.id).unwrap();
+	assert_eq!(report.food, 123);
+	assert_eq!(report.gold, 45);
+	assert_eq!(report.wood, storage.wood);
+	assert_eq!(report.noise_percent, 0);
+	assert!(!report.spotted);
+	assert_eq!(scout_count(&mut conn, &spy.id), 5);
+
+	// A retried arrival job neither reports nor returns the scouts twice
+	assert!(
+		complete_mission(&mut conn, &settings, &mission.id)
+			.unwrap()
+			.is_none()
+	);
+	assert_eq!(scout_count(&mut conn, &spy.id), 5);
+
+	// The report is the spy's alone, and they got mail about it
+	assert_eq!(get_report(&mut conn, &spy.id, &report.id).unwrap(), report);
+	let err = get_report(&mut conn, &target.id, &report.id).unwrap_err();
+	assert!(err.to_string().contains("Intel report not found"));
+	assert_eq!(
+		list_reports(&mut conn, &spy.id, None, None).unwrap().total,
+		1
+	);
+	let inbox = list_inbox(&mut conn, &spy.id, false, None, None).unwrap();
+	assert_eq!(inbox.messages[0].kind, MessageKind::IntelReport);
+	let inbox = list_inbox(&mut conn, &target.id, false, None, None).unwrap();
+	assert_eq!(inbox.total, 0);
+}
+
+#[tokio::test]
+async fn test_watchtower_adds_noise_and_spots_scouts() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let settings = EspionageSettings::default();
+	assert_eq!(noise_percent(&settings, 0), 0);
+	assert_eq!(noise_percent(&settings, 3), 30);
+	assert_eq!(noise_percent(&settings, 10), settings.max_noise_percent);
+
+	let spy = harness.create_named_user("spy", None);
+	let target = harness.create_named_user("target", None);
+	let scout = scout_id(&mut conn);
+	player_units::add_units(&mut conn, &spy.id, &scout, 1).unwrap();
+	player_units::add_units(&mut conn, &target.id, &scout, 100).unwrap();
+	build_watchtower(&mut conn, &target, 2);
+
+	let mission = send_scouts(
+		&mut conn,
+		&harness.app.job_queue,
+		&settings,
+		&protection(),
+		&spy.id,
+		&target.id,
+		1,
+	)
+	.unwrap();
+	let report = complete_mission(&mut conn, &settings, &mission.id)
+		.unwrap()
+		.unwrap();
+	assert_eq!(report.noise_percent, 20);
+	assert!(report.spotted);
+	let army: Vec<serde_json::Value> = serde_json::from_value(report.army).unwrap();
+	let estimate = army[0]["estimate"].as_i64().unwrap();
+	assert!((80..=120).contains(&estimate));
+
+	let inbox = list_inbox(&mut conn, &target.id, false, None, None).unwrap();
+	assert_eq!(inbox.messages[0].kind, MessageKind::ScoutsSpotted);
+}
@@ -0,0 +1,317 @@
+//! Integration tests for faction selection and changes of players.
+//!
+//! These tests cover:
+//! - The first faction choice granting the starter buildings
+//! - Buildings and construction plans converted to the new faction's equivalents
+//! - Faction modifiers swapped along with the faction
+//! - The cooldown between two changes
+//! - Changes rejected for invalid targets or running building upgrades
+
+use chrono::{TimeDelta, Utc};
+use diesel::prelude::*;
+use empire::configuration::FactionSettings;
+use empire::db::{DbConn, planned_actions, player_buildings};
+use empire::domain::building::Building;
+use empire::domain::factions::FactionCode;
+use empire::domain::player::PlayerKey;
+use empire::domain::player::buildings::{NewPlayerBuilding, PlayerBuilding};
+use empire::domain::player::planned_action::{NewPlannedAction, PlannedActionKind};
+use empire::game::factions::faction_operations::{
+	change_faction, equivalent_building_name, select_faction,
+};
+use empire::schema::{active_modifiers, building, modifiers};
+
+use crate::common::TestHarness;
+
+fn get_building(conn: &mut DbConn, name: &str, faction: FactionCode) -> Building {
+	building::table
+		.filter(building::name.eq(name))
+		.filter(building::faction.eq(faction))
+		.first(conn)
+		.unwrap_or_else(|_| panic!("Building '{}' not found for faction {:?}", name, faction))
+}
+
+fn construct(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	name: &str,
+	faction: FactionCode,
+	level: i32,
+) -> PlayerBuilding {
+	let bld = get_building(conn, name, faction);
+	player_buildings::construct(
+		conn,
+		NewPlayerBuilding {
+			player_id: *player_id,
+			building_id: bld.id,
+			level: Some(level),
+			upgrade_finishes_at: None,
+			settlement_id: None,
+		},
+	)
+	.expect("Failed to construct building")
+}
+
+fn building_of(conn: &mut DbConn, player_building: &PlayerBuilding) -> (PlayerBuilding, Building) {
+	let converted = player_buildings::get_by_id(conn, &player_building.id).unwrap();
+	let bld = building::table
+		.find(converted.building_id)
+		.first(conn)
+		.unwrap();
+	(converted, bld)
+}
+
+#[test]
+fn test_equivalent_building_names() {
+	use FactionCode::*;
+
+	assert_eq!(equivalent_building_name("Keep", Human, Orc), "Stronghold");
+	assert_eq!(equivalent_building_name("Mana Den", Goblin, Elf), "Arcanum");
+	assert_eq!(
+		equivalent_building_name("Academy", Dwarf, Goblin),
+		"Cadet School"
+	);
+	assert_eq!(equivalent_building_name("Temple", Dwarf, Human), "Church");
+	assert_eq!(equivalent_building_name("Barracks", Human, Orc), "Barracks");
+	assert_eq!(equivalent_building_name("Market", Neutral, Orc), "Market");
+}
+
+#[test]
+fn test_faction_change_converts_buildings_and_plans() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let cache = &harness.app.modifier_system.cache;
+	let player = harness.create_test_user(Some(FactionCode::Human));
+
+	let keep = player_buildings::get_player_buildings(&mut conn, &player.id)
+		.unwrap()
+		.into_iter()
+		.find(|bld| bld.building_id == get_building(&mut conn, "Keep", FactionCode::Human).id)
+		.expect("Human players start with a Keep");
+	let tower = construct(&mut conn, &player.id, "Mage Tower", FactionCode::Human, 2);
+	let barracks = construct(&mut conn, &player.id, "Barracks", FactionCode::Human, 3);
+	let market = construct(&mut conn, &player.id, "Market", FactionCode::Neutral, 1);
+	let church = get_building(&mut conn, "Church", FactionCode::Human);
+	let plan = planned_actions::create(
+		&mut conn,
+		NewPlannedAction {
+			player_id: player.id,
+			action: PlannedActionKind::Construct,
+			building_id: Some(church.id),
+			player_building_id: None,
+			settlement_id: None,
+		},
+	)
+	.unwrap();
+
+	let now = Utc::now();
+	let changed = change_faction(
+		&mut conn,
+		cache,
+		&FactionSettings::default(),
+		&player.id,
+		FactionCode::Orc,
+		now,
+	)
+	.expect("Faction change should succeed");
+
+	assert_eq!(changed.player.faction, FactionCode::Orc);
+	assert_eq!(changed.change.from_faction, FactionCode::Human);
+	assert_eq!(changed.change.to_faction, FactionCode::Orc);
+	assert_eq!(changed.converted_plans, 1);
+	assert_eq!(
+		changed.next_change_at,
+		changed.change.changed_at + TimeDelta::days(30)
+	);
+
+	let (converted, bld) = building_of(&mut conn, &keep);
+	assert_eq!(bld.name, "Stronghold");
+	assert_eq!(bld.faction, FactionCode::Orc);
+	assert_eq!(converted.level, keep.level, "Conversion keeps the level");
+
+	let (converted, bld) = building_of(&mut conn, &tower);
+	assert_eq!(
+		(bld.name.as_str(), bld.faction),
+		("The Circle", FactionCode::Orc)
+	);
+	assert_eq!(converted.level, 2);
+
+	let (_, bld) = building_of(&mut conn, &barracks);
+	assert_eq!(
+		(bld.name.as_str(), bld.faction),
+		("Barracks", FactionCode::Orc)
+	);
+
+	let (_, bld) = building_of(&mut conn, &market);
+	assert_eq!(bld.faction, FactionCode::Neutral, "Neutral buildings stay");
+
+	let plan = planned_actions::get_by_id(&mut conn, &plan.id).unwrap();
+	let altar = get_building(&mut conn, "Shamanic Altar", FactionCode::Orc);
+	assert_eq!(plan.building_id, Some(altar.id));
+
+	let modifier_names: Vec<String> = active_modifiers::table
+		.inner_join(modifiers::table.on(modifiers::id.eq(active_modifiers::modifier_id)))
+		.filter(active_modifiers::player_id.eq(player.id))
+		.select(modifiers::name)
+		.load(&mut conn)
+		.unwrap();
+	assert!(modifier_names.iter().any(|name| name.starts_with("orc_")));
+	assert!(
+		!modifier_names.iter().any(|name| name.starts_with("human_")),
+		"Modifiers of the old faction should be gone, got {:?}",
+		modifier_names
+	);
+}
+
+#[test]
+fn test_faction_change_respects_cooldown() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let cache = &harness.app.modifier_system.cache;
+	let settings = FactionSettings {
+		change_cooldown_days: 7,
+	};
+	let player = harness.create_test_user(Some(FactionCode::Elf));
+
+	let now = Utc::now();
+	change_faction(
+		&mut conn,
+		cache,
+		&settings,
+		&player.id,
+		FactionCode::Dwarf,
+		now,
+	)
+	.expect("First change should succeed");
+
+	let err = change_faction(
+		&mut conn,
+		cache,
+		&settings,
+		&player.id,
+		FactionCode::Goblin,
+		now + TimeDelta::days(6),
+	)
+	.expect_err("Change within the cooldown should fail");
+	assert!(err.to_string().contains("Faction was changed recently"));
+
+	let changed = change_faction(
+		&mut conn,
+		cache,
+		&settings,
+		&player.id,
+		FactionCode::Goblin,
+		now + TimeDelta::days(7),
+	)
+	.expect("Change after the cooldown should succeed");
+	assert_eq!(changed.player.faction, FactionCode::Goblin);
+	assert_eq!(changed.change.from_faction, FactionCode::Dwarf);
+}
+
+#[test]
+fn test_faction_change_rejects_invalid_targets() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let cache = &harness.app.modifier_system.cache;
+	let settings = FactionSettings::default();
+	let now = Utc::now();
+
+	let human = harness.create_test_user(Some(FactionCode::Human));
+	let err = change_faction(
+		&mut conn,
+		cache,
+		&settings,
+		&human.id,
+		FactionCode::Neutral,
+		now,
+	)
+	.expect_err("Players cannot become neutral");
+	assert!(err.to_string().contains("Players cannot become neutral"));
+
+	let err = change_faction(
+		&mut conn,
+		cache,
+		&settings,
+		&human.id,
+		FactionCode::Human,
+		now,
+	)
+	.expect_err("Players cannot change to their own faction");
+	assert!(err.to_string().contains("Already a member of this faction"));
+
+	let neutral = harness.create_named_user("neutral_player", None);
+	let err = change_faction(
+		&mut conn,
+		cache,
+		&settings,
+		&neutral.id,
+		FactionCode::Orc,
+		now,
+	)
+	.expect_err("Neutral players select a faction instead");
+	assert!(
+		err.to_string()
+			.contains("Select a faction before changing it")
+	);
+}
+
+#[test]
+fn test_faction_change_waits_for_running_upgrades() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let cache = &harness.app.modifier_system.cache;
+	let player = harness.create_test_user(Some(FactionCode::Human));
+
+	let tower = construct(&mut conn, &player.id, "Mage Tower", FactionCode::Human, 1);
+	player_buildings::set_upgrade_eta(&mut conn, &tower.id, Some(Utc::now() + TimeDelta::hours(1)))
+		.unwrap();
+
+	let err = change_faction(
+		&mut conn,
+		cache,
+		&FactionSettings::default(),
+		&player.id,
+		FactionCode::Orc,
+		Utc::now(),
+	)
+	.expect_err("Change during an upgrade should fail");
+	assert!(err.to_string().contains("running building upgrades"));
+
+	let (_, bld) = building_of(&mut conn, &tower);
+	assert_eq!(bld.faction, FactionCode::Human, "Nothing is converted");
+}
+
+#[test]
+fn test_faction_selection_grants_starter_buildings() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let cache = &harness.app.modifier_system.cache;
+	let player = harness.create_test_user(None);
+	assert!(
+		player_buildings::get_player_buildings(&mut conn, &player.id)
+			.unwrap()
+			.is_empty()
+	);
+
+	let err = select_faction(&mut conn, cache, &player.id, FactionCode::Neutral)
+		.expect_err("Neutral is not a faction to select");
+	assert!(err.to_string().contains("cannot become neutral"));
+
+	let selected = select_faction(&mut conn, cache, &player.id, FactionCode::Elf).unwrap();
+	assert_eq!(selected.player.faction, FactionCode::Elf);
+	let starters: i64 = building::table
+		.filter(building::faction.eq(FactionCode::Elf))
+		.filter(building::starter.eq(true))
+		.count()
+		.get_result(&mut conn)
+		.unwrap();
+	assert_eq!(selected.starter_buildings as i64, starters);
+	let owned = player_buildings::get_player_buildings(&mut conn, &player.id).unwrap();
+	assert_eq!(owned.len(), selected.starter_buildings);
+
+	let err = select_faction(&mut conn, cache, &player.id, FactionCode::Orc)
+		.expect_err("The first choice is only made once");
+	assert!(err.to_string().contains("Faction already selected"));
+	let owned_after = player_buildings::get_player_buildings(&mut conn, &player.id).unwrap();
+	assert_eq!(owned_after.len(), owned.len(), "Nothing else is granted");
+}
@@ -4,10 +4,15 @@ use std::sync::Arc;
 use bigdecimal::BigDecimal;
 use diesel::{ExpressionMethods, JoinOnDsl, QueryDsl, RunQueryDsl};
 use empire::auth::utils::hash_password;
+use empire::controllers::user::UpdateUserPayload;
 use empire::db::{DbConn, players};
 use empire::domain::factions::FactionCode;
+use empire::domain::modifier::ModifierTarget;
 use empire::domain::modifier::modifier_history::ModifierActionType;
+use empire::domain::player::resource::ResourceType;
 use empire::domain::player::{NewPlayer, Player, UserName};
+use empire::game::modifiers::modifier_operations;
+use empire::game::player_operations;
 use empire::schema::{active_modifiers, modifier_history, modifiers, player};
 
 use crate::common::TestHarness;
@@ -182,9 +187,45 @@ async fn test_faction_change() {
 	);
 }
 
+#[tokio::test]
+async fn test_faction_change_invalidates_cached_multipliers() {
+	let harness = TestHarness::new();
+	let cache = &harness.app.modifier_system.cache;
+	let mut conn = harness.get_conn();
+	let user = create_test_user(&mut conn, FactionCode::Human);
+	let wood_multiplier = |conn: &mut DbConn| {
+		modifier_operations::calc_multiplier(
+			conn,
+			cache,
+			&user.id,
+			ModifierTarget::Resource,
+			Some(ResourceType::Wood),
+		)
+		.expect("Failed to calculate multiplier")
+	};
+	let human_bonus = BigDecimal::from_str("1.15").unwrap();
+	assert_eq!(wood_multiplier(&mut conn), human_bonus);
+
+	// Repeated lookups are served from the cache without reading the modifiers again
+	diesel::delete(active_modifiers::table.filter(active_modifiers::player_id.eq(&user.id)))
+		.execute(&mut conn)
+		.unwrap();
+	assert_eq!(wood_multiplier(&mut conn), human_bonus);
+
+	let payload = UpdateUserPayload {
+		username: None,
+		password: None,
+		email: None,
+		faction: Some(FactionCode::Orc),
+	};
+	player_operations::update_player(&mut conn, cache, user.id, payload)
+		.expect("Failed to change faction");
+	assert_eq!(wood_multiplier(&mut conn), BigDecimal::from(1));
+}
+
 // Helper function to create test users
 fn create_test_user(conn: &mut DbConn, faction: FactionCode) -> Player {
-	players::create(
+	let player = players::create(
 		conn,
 		NewPlayer {
 			name: UserName::parse("test_user".to_string()).unwrap(),
@@ -193,5 +234,7 @@ fn create_test_user(conn: &mut DbConn, faction: FactionCode) -> Player {
 			faction,
 		},
 	)
-	.unwrap()
+	.unwrap();
+	player_operations::provision_player(conn, &player).expect("Failed to provision player");
+	player
 }
@@ -0,0 +1,146 @@
+//! Integration tests for the faction standings.
+//!
+//! These tests cover:
+//! - Players, power and war score aggregated per faction, battles within a faction left out
+//! - The war score counting only the battles since the previous aggregation
+//! - War victory modifiers granted to the leading faction and moved on the next cycle
+
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::prelude::*;
+use empire::db::{DbConn, battle_reports};
+use empire::domain::combat::NewBattleReport;
+use empire::domain::factions::{FactionCode, FactionStanding};
+use empire::domain::player::{Player, PlayerKey};
+use empire::game::factions::standing_operations::{WAR_VICTORY_MODIFIERS, update_standings};
+use empire::schema::{active_modifiers, modifiers, player};
+use serde_json::json;
+
+use crate::common::TestHarness;
+
+/// Records a battle `winner` won against `loser`, fought at `fought_at`.
+fn record_win(conn: &mut DbConn, winner: &Player, loser: &Player, fought_at: DateTime<Utc>) {
+	battle_reports::create(
+		conn,
+		NewBattleReport {
+			attacker_id: winner.id,
+			defender_id: loser.id,
+			winner_id: Some(winner.id),
+			attacker_losses: json!([]),
+			defender_losses: json!([]),
+			loot_food: 0,
+			loot_wood: 0,
+			loot_stone: 0,
+			loot_gold: 0,
+			modifiers: json!([]),
+			fought_at,
+		},
+	)
+	.expect("Failed to record battle");
+}
+
+/// Names of the war victory modifiers a player holds.
+fn victory_modifiers(conn: &mut DbConn, player_id: &PlayerKey) -> Vec<String> {
+	let mut names: Vec<String> = active_modifiers::table
+		.inner_join(modifiers::table.on(modifiers::id.eq(active_modifiers::modifier_id)))
+		.filter(active_modifiers::player_id.eq(player_id))
+		.filter(modifiers::name.eq_any(WAR_VICTORY_MODIFIERS))
+		.select(modifiers::name)
+		.load(conn)
+		.unwrap();
+	names.sort();
+	names
+}
+
+/// Number of players in a faction, the seeded accounts included.
+fn members(conn: &mut DbConn, faction: FactionCode) -> i64 {
+	player::table
+		.filter(player::faction.eq(faction))
+		.count()
+		.get_result(conn)
+		.unwrap()
+}
+
+fn standing_of(standings: &[FactionStanding], faction: FactionCode) -> &FactionStanding {
+	standings
+		.iter()
+		.find(|standing| standing.faction == faction)
+		.unwrap_or_else(|| panic!("No standing for {faction}"))
+}
+
+#[test]
+fn test_standings_aggregate_factions() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let cache = &harness.app.modifier_system.cache;
+	let arthur = harness.create_named_user("arthur", Some(FactionCode::Human));
+	let lancelot = harness.create_named_user("lancelot", Some(FactionCode::Human));
+	let grom = harness.create_named_user("grom", Some(FactionCode::Orc));
+	let legolas = harness.create_named_user("legolas", Some(FactionCode::Elf));
+
+	let fought_at = Utc::now() - TimeDelta::hours(1);
+	record_win(&mut conn, &arthur, &grom, fought_at);
+	record_win(&mut conn, &lancelot, &legolas, fought_at);
+	record_win(&mut conn, &grom, &legolas, fought_at);
+	// Battles within a faction are no war
+	record_win(&mut conn, &arthur, &lancelot, fought_at);
+
+	let summary = update_standings(&mut conn, cache, Utc::now()).unwrap();
+	assert_eq!(summary.standings.len(), 5, "Every playable faction stands");
+
+	let humans = standing_of(&summary.standings, FactionCode::Human);
+	assert_eq!(humans.players, members(&mut conn, FactionCode::Human));
+	assert_eq!((humans.war_score, humans.rank), (2, 1));
+	let orcs = standing_of(&summary.standings, FactionCode::Orc);
+	assert_eq!(orcs.players, members(&mut conn, FactionCode::Orc));
+	assert_eq!((orcs.war_score, orcs.rank), (1, 2));
+	let elves = standing_of(&summary.standings, FactionCode::Elf);
+	assert_eq!(elves.war_score, 0);
+	assert_eq!(summary.standings[0].faction, FactionCode::Human);
+
+	assert_eq!(summary.leaders, vec![FactionCode::Human]);
+	assert_eq!(summary.rewarded as i64, humans.players);
+	let mut expected: Vec<String> = WAR_VICTORY_MODIFIERS.map(String::from).to_vec();
+	expected.sort();
+	assert_eq!(victory_modifiers(&mut conn, &arthur.id), expected);
+	assert_eq!(victory_modifiers(&mut conn, &lancelot.id), expected);
+	assert!(victory_modifiers(&mut conn, &grom.id).is_empty());
+}
+
+#[test]
+fn test_war_victory_moves_with_the_cycle() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let cache = &harness.app.modifier_system.cache;
+	let arthur = harness.create_named_user("arthur", Some(FactionCode::Human));
+	let grom = harness.create_named_user("grom", Some(FactionCode::Orc));
+	let thorin = harness.create_named_user("thorin", Some(FactionCode::Dwarf));
+
+	let first_cycle = Utc::now();
+	record_win(&mut conn, &arthur, &grom, first_cycle - TimeDelta::hours(2));
+	let summary = update_standings(&mut conn, cache, first_cycle).unwrap();
+	assert_eq!(summary.leaders, vec![FactionCode::Human]);
+	assert!(!victory_modifiers(&mut conn, &arthur.id).is_empty());
+
+	// Only the battles of the new cycle count, the orcs win it
+	let second_cycle = first_cycle + TimeDelta::days(1);
+	record_win(&mut conn, &grom, &arthur, first_cycle + TimeDelta::hours(1));
+	record_win(&mut conn, &grom, &thorin, first_cycle + TimeDelta::hours(2));
+	let summary = update_standings(&mut conn, cache, second_cycle).unwrap();
+	assert_eq!(summary.leaders, vec![FactionCode::Orc]);
+	let humans = standing_of(&summary.standings, FactionCode::Human);
+	assert_eq!(humans.war_score, 0);
+	let orcs = standing_of(&summary.standings, FactionCode::Orc);
+	assert_eq!(orcs.war_score, 2);
+
+	assert!(
+		victory_modifiers(&mut conn, &arthur.id).is_empty(),
+		"The previous leaders lose the modifiers"
+	);
+	assert_eq!(victory_modifiers(&mut conn, &grom.id).len(), 2);
+
+	// A cycle without battles has no leader
+	let summary = update_standings(&mut conn, cache, second_cycle + TimeDelta::days(1)).unwrap();
+	assert!(summary.leaders.is_empty());
+	assert_eq!(summary.rewarded, 0);
+	assert!(victory_modifiers(&mut conn, &grom.id).is_empty());
+}
@@ -0,0 +1,58 @@
+//! Fixtures shared by the game integration tests.
+
+use diesel::prelude::*;
+use empire::auth::utils::hash_password;
+use empire::db::{DbConn, players};
+use empire::domain::factions::FactionCode;
+use empire::domain::player::{NewPlayer, Player, PlayerKey, UserName};
+use empire::game::player_operations::provision_player;
+
+/// Create a provisioned player with a unique name in the given faction.
+pub fn create_test_player(conn: &mut DbConn, faction: FactionCode) -> Player {
+	let player = players::create(
+		conn,
+		NewPlayer {
+			name: UserName::parse(format!("test_player_{}", uuid::Uuid::new_v4())).unwrap(),
+			pwd_hash: hash_password(b"test1234").unwrap(),
+			email: None,
+			faction,
+		},
+	)
+	.expect("Failed to create test player");
+	provision_player(conn, &player).expect("Failed to provision player");
+	player
+}
+
+/// Set all of a player's resources to the given amount.
+pub fn set_player_resources(conn: &mut DbConn, player_id: &PlayerKey, amount: i64) {
+	use empire::schema::player_resource::dsl as pr;
+	diesel::update(pr::player_resource.filter(pr::player_id.eq(player_id)))
+		.set((
+			pr::food.eq(amount),
+			pr::wood.eq(amount),
+			pr::stone.eq(amount),
+			pr::gold.eq(amount),
+		))
+		.execute(conn)
+		.expect("Failed to set player resources");
+}
+
+/// Set all of a player's storage caps to the given amount.
+pub fn set_storage_caps(conn: &mut DbConn, player_id: &PlayerKey, cap: i64) {
+	use empire::schema::player_resource::dsl as pr;
+	diesel::update(pr::player_resource.filter(pr::player_id.eq(player_id)))
+		.set((
+			pr::food_cap.eq(cap),
+			pr::wood_cap.eq(cap),
+			pr::stone_cap.eq(cap),
+			pr::gold_cap.eq(cap),
+		))
+		.execute(conn)
+		.expect("Failed to set storage caps");
+}
+
+/// Give a player plenty of resources, with room to store what they produce.
+pub fn give_player_resources(conn: &mut DbConn, player_id: &PlayerKey) {
+	set_storage_caps(conn, player_id, 1_000_000);
+	set_player_resources(conn, player_id, 100_000);
+}
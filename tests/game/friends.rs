@@ -0,0 +1,162 @@
+//! Integration tests for friends and blocks.
+//!
+//! These tests cover:
+//! - Sending, accepting, declining and withdrawing friend requests, and ending friendships
+//! - The online status of friends, hidden by their privacy settings
+//! - Blocks ending friendships and keeping players from befriending, mailing and attacking
+//!   each other
+
+use empire::configuration::ProtectionSettings;
+use empire::db::player_privacy;
+use empire::domain::metrics::ServerMetrics;
+use empire::domain::player::privacy::UpdatePlayerPrivacy;
+use empire::domain::player::relationship::RelationshipStatus;
+use empire::game::combat::combat_validator::validate_attack;
+use empire::game::friends::friend_operations::{
+	accept_friend, block, list, remove_friend, request_friend, unblock,
+};
+use empire::game::mail::mail_operations::send;
+
+use crate::common::TestHarness;
+
+#[tokio::test]
+async fn test_friend_requests_are_answered() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let metrics = ServerMetrics::default();
+	let asker = harness.create_named_user("asker", None).id;
+	let friend = harness.create_named_user("friend", None).id;
+	let shy = harness.create_named_user("shy", None).id;
+
+	let err = request_friend(&mut conn, &asker, &asker).unwrap_err();
+	assert!(err.to_string().contains("themselves"));
+	let err = request_friend(&mut conn, &asker, &uuid::Uuid::new_v4()).unwrap_err();
+	assert!(err.to_string().contains("Player not found"));
+
+	let request = request_friend(&mut conn, &asker, &friend).unwrap();
+	assert_eq!(request.status, RelationshipStatus::Requested);
+	let err = request_friend(&mut conn, &asker, &friend).unwrap_err();
+	assert!(err.to_string().contains("already sent"));
+	let listed = list(&mut conn, &metrics, &friend).unwrap();
+	assert_eq!(listed.incoming.len(), 1);
+	assert_eq!(listed.incoming[0].name, "asker");
+	assert_eq!(list(&mut conn, &metrics, &asker).unwrap().outgoing.len(), 1);
+
+	// Only the asked player accepts
+	let err = accept_friend(&mut conn, &asker, &friend).unwrap_err();
+	assert!(err.to_string().contains("No friend request"));
+	let friendship = accept_friend(&mut conn, &friend, &asker).unwrap();
+	assert_eq!(friendship.status, RelationshipStatus::Accepted);
+	let err = request_friend(&mut conn, &friend, &asker).unwrap_err();
+	assert!(err.to_string().contains("Already friends"));
+
+	// Asking back accepts the request
+	request_friend(&mut conn, &shy, &asker).unwrap();
+	let friendship = request_friend(&mut conn, &asker, &shy).unwrap();
+	assert_eq!(friendship.status, RelationshipStatus::Accepted);
+
+	let listed = list(&mut conn, &metrics, &asker).unwrap();
+	let names: Vec<_> = listed
+		.friends
+		.iter()
+		.map(|f| f.player.name.as_str())
+		.collect();
+	assert_eq!(names, ["friend", "shy"]);
+	assert!(listed.incoming.is_empty() && listed.outgoing.is_empty());
+
+	// Either side ends the friendship
+	remove_friend(&mut conn, &shy, &asker).unwrap();
+	let err = remove_friend(&mut conn, &shy, &asker).unwrap_err();
+	assert!(err.to_string().contains("Not friends"));
+	assert_eq!(list(&mut conn, &metrics, &asker).unwrap().friends.len(), 1);
+}
+
+#[tokio::test]
+async fn test_friends_show_their_online_status_unless_hidden() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let metrics = ServerMetrics::default();
+	let player = harness.create_named_user("player", None).id;
+	let online = harness.create_named_user("online", None).id;
+	let offline = harness.create_named_user("offline", None).id;
+	let hidden = harness.create_named_user("hidden", None).id;
+	for other in [online, offline, hidden] {
+		request_friend(&mut conn, &player, &other).unwrap();
+		accept_friend(&mut conn, &other, &player).unwrap();
+	}
+	player_privacy::update(
+		&mut conn,
+		&hidden,
+		&UpdatePlayerPrivacy {
+			hide_online_status: Some(true),
+			..Default::default()
+		},
+	)
+	.unwrap();
+	metrics.record_activity(&online);
+	metrics.record_activity(&hidden);
+
+	let friends = list(&mut conn, &metrics, &player).unwrap().friends;
+	let status: Vec<_> = friends
+		.iter()
+		.map(|f| (f.player.name.as_str(), f.online))
+		.collect();
+	assert_eq!(
+		status,
+		[
+			("hidden", None),
+			("offline", Some(false)),
+			("online", Some(true))
+		]
+	);
+}
+
+#[tokio::test]
+async fn test_blocked_players_cannot_befriend_mail_or_attack_each_other() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let metrics = ServerMetrics::default();
+	let protection = ProtectionSettings {
+		beginner_shield_days: 0,
+		beginner_shield_max_points: None,
+		beginner_shield_max_keep_level: None,
+	};
+	let player = harness.create_named_user("player", None).id;
+	let pest = harness.create_named_user("pest", None).id;
+
+	request_friend(&mut conn, &pest, &player).unwrap();
+	accept_friend(&mut conn, &player, &pest).unwrap();
+	assert!(validate_attack(&mut conn, &protection, &pest, &player).is_ok());
+
+	let blocked = block(&mut conn, &player, &pest).unwrap();
+	assert_eq!(blocked.status, RelationshipStatus::Blocked);
+	assert_eq!(blocked.player_id, player);
+	let listed = list(&mut conn, &metrics, &player).unwrap();
+	assert!(listed.friends.is_empty(), "Blocking ends the friendship");
+	assert_eq!(listed.blocked[0].name, "pest");
+	assert!(
+		list(&mut conn, &metrics, &pest).unwrap().blocked.is_empty(),
+		"Players do not learn who blocked them"
+	);
+
+	// Both ways, whoever blocked
+	let err = request_friend(&mut conn, &pest, &player).unwrap_err();
+	assert!(err.to_string().contains("Cannot befriend"));
+	let err = request_friend(&mut conn, &player, &pest).unwrap_err();
+	assert!(err.to_string().contains("Cannot befriend"));
+	let err = send(&mut conn, &pest, &player, "Hey", "Unblock me").unwrap_err();
+	assert!(err.to_string().contains("Cannot send mail"));
+	let err = send(&mut conn, &player, &pest, "Hey", "Go away").unwrap_err();
+	assert!(err.to_string().contains("Cannot send mail"));
+	let err = validate_attack(&mut conn, &protection, &pest, &player).unwrap_err();
+	assert!(err.to_string().contains("Cannot attack this player"));
+	let err = validate_attack(&mut conn, &protection, &player, &pest).unwrap_err();
+	assert!(err.to_string().contains("Cannot attack this player"));
+
+	// Only the player who blocked lifts the block
+	let err = unblock(&mut conn, &pest, &player).unwrap_err();
+	assert!(err.to_string().contains("not blocked"));
+	unblock(&mut conn, &player, &pest).unwrap();
+	send(&mut conn, &pest, &player, "Hey", "Thanks").unwrap();
+	assert!(validate_attack(&mut conn, &protection, &pest, &player).is_ok());
+}
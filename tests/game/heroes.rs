@@ -0,0 +1,236 @@
+//! Integration tests for heroes.
+//!
+//! These tests cover:
+//! - Recruiting heroes for gold and training them up to their highest level
+//! - Applying the modifier of an assigned hero to the army or the building they run
+//! - Replacing the modifier when the hero levels up, and removing it when unassigned
+
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use empire::db::{
+	DbConn, active_modifiers, heroes, modifier_history, player_buildings, resources, settlements,
+};
+use empire::domain::factions::FactionCode;
+use empire::domain::modifier::ModifierTarget;
+use empire::domain::modifier::active_modifier::ModifierSourceType;
+use empire::domain::modifier::modifier_history::ModifierActionType;
+use empire::domain::player::PlayerKey;
+use empire::domain::player::buildings::{NewPlayerBuilding, PlayerBuilding};
+use empire::domain::player::resource::ResourceType;
+use empire::game::heroes::hero_operations::{
+	Assignment, assign_hero, list_heroes, recruit_hero, train_hero, unassign_hero,
+};
+use empire::game::modifiers::modifier_service::ModifierService;
+use empire::game::resources::resource_operations::calc_prod_rates;
+use empire::schema::{building, building_resource};
+
+use crate::common::TestHarness;
+
+/// Builds another level 1 Lumberyard for a player.
+fn build_lumberyard(conn: &mut DbConn, player_id: &PlayerKey) -> PlayerBuilding {
+	let bld_id: i32 = building::table
+		.filter(building::name.eq("Lumberyard"))
+		.select(building::id)
+		.first(conn)
+		.expect("Lumberyard missing");
+	player_buildings::construct(
+		conn,
+		NewPlayerBuilding {
+			player_id: *player_id,
+			building_id: bld_id,
+			level: Some(1),
+			upgrade_finishes_at: None,
+			settlement_id: None,
+		},
+	)
+	.expect("Failed to build a Lumberyard")
+}
+
+fn wood_rate(conn: &mut DbConn, player_id: &PlayerKey) -> BigDecimal {
+	let capital = settlements::get_capital(conn, player_id).unwrap();
+	calc_prod_rates(conn, &capital).unwrap()[&ResourceType::Wood].clone()
+}
+
+fn gold(conn: &mut DbConn, player_id: &PlayerKey) -> i64 {
+	resources::get_by_player_id(conn, player_id).unwrap().gold
+}
+
+#[tokio::test]
+async fn test_heroes_are_recruited_and_trained() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let service = ModifierService::new(&harness.app.db_pool, &harness.app.modifier_system);
+	let player = harness.create_named_user("commander", Some(FactionCode::Human));
+	let marshal = heroes::get_by_name(&mut conn, "Aldric the Marshal").expect("Heroes are seeded");
+
+	let err = recruit_hero(&mut conn, &player.id, &marshal.id).unwrap_err();
+	assert!(err.to_string().contains("Not enough gold"));
+	resources::add(&mut conn, &player.id, &(0, 0, 0, 5000)).unwrap();
+	let before = gold(&mut conn, &player.id);
+
+	let recruited = recruit_hero(&mut conn, &player.id, &marshal.id).unwrap();
+	let own = recruited.recruited.unwrap();
+	assert_eq!((own.level, own.xp), (1, 0));
+	assert!(!own.is_assigned());
+	assert_eq!(recruited.modifier.effect_summary(), "+5% combat strength");
+	assert_eq!(recruited.next_level.unwrap().xp_required, 100);
+	assert_eq!(gold(&mut conn, &player.id), before - marshal.recruit_gold);
+	let err = recruit_hero(&mut conn, &player.id, &marshal.id).unwrap_err();
+	assert!(err.to_string().contains("Hero already recruited"));
+
+	let trained = train_hero(&mut conn, &service, &player.id, &marshal.id, 150)
+		.await
+		.unwrap();
+	assert_eq!(trained.levels_gained, 1);
+	assert_eq!(trained.gold_spent, 300);
+	let own = trained.status.recruited.unwrap();
+	assert_eq!((own.level, own.xp), (2, 150));
+	assert_eq!(
+		trained.status.modifier.effect_summary(),
+		"+10% combat strength"
+	);
+
+	// Training stops at the highest level, and only the experience gained is paid for
+	let trained = train_hero(&mut conn, &service, &player.id, &marshal.id, 10_000)
+		.await
+		.unwrap();
+	assert_eq!(trained.xp_gained, 850);
+	assert_eq!(trained.levels_gained, 3);
+	assert_eq!(trained.status.recruited.unwrap().level, 5);
+	assert_eq!(trained.status.next_level, None);
+	assert_eq!(
+		gold(&mut conn, &player.id),
+		before - marshal.recruit_gold - 1000 * 2
+	);
+	let err = train_hero(&mut conn, &service, &player.id, &marshal.id, 1)
+		.await
+		.unwrap_err();
+	assert!(err.to_string().contains("Hero is at the highest level"));
+
+	let roster = list_heroes(&mut conn, &player.id).unwrap();
+	let recruited: Vec<_> = roster
+		.iter()
+		.filter(|status| status.recruited.is_some())
+		.map(|status| status.hero.id)
+		.collect();
+	assert_eq!(recruited, vec![marshal.id]);
+	assert!(roster.len() > 1, "Heroes not recruited are listed too");
+}
+
+#[tokio::test]
+async fn test_assigned_heroes_apply_their_modifiers() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let service = ModifierService::new(&harness.app.db_pool, &harness.app.modifier_system);
+	let player = harness.create_named_user("steward", Some(FactionCode::Human));
+	resources::add(&mut conn, &player.id, &(0, 0, 0, 10_000)).unwrap();
+	let marshal = heroes::get_by_name(&mut conn, "Aldric the Marshal").unwrap();
+	let forester = heroes::get_by_name(&mut conn, "Elin the Forester").unwrap();
+	let quarrymaster = heroes::get_by_name(&mut conn, "Brann the Quarrymaster").unwrap();
+	for hero in [&marshal, &forester, &quarrymaster] {
+		recruit_hero(&mut conn, &player.id, &hero.id).unwrap();
+	}
+	let lumberyard = build_lumberyard(&mut conn, &player.id);
+	let lumberyard_wood: i64 = building_resource::table
+		.filter(building_resource::building_id.eq(lumberyard.building_id))
+		.filter(building_resource::building_level.eq(lumberyard.level))
+		.select(building_resource::wood)
+		.first(&mut conn)
+		.unwrap();
+	let before = wood_rate(&mut conn, &player.id);
+
+	// The forester runs the lumberyard, adding to the +15% of humans
+	let assigned = assign_hero(
+		&mut conn,
+		&service,
+		&player.id,
+		&forester.id,
+		Assignment::Building(lumberyard.id),
+	)
+	.await
+	.unwrap();
+	assert_eq!(assigned.active.source_type, ModifierSourceType::Hero);
+	assert_eq!(assigned.active.player_building_id, Some(lumberyard.id));
+	let bonus =
+		|percent: &str| BigDecimal::from(lumberyard_wood) * BigDecimal::from_str(percent).unwrap();
+	assert_eq!(wood_rate(&mut conn, &player.id) - &before, bonus("0.10"));
+
+	let err = assign_hero(
+		&mut conn,
+		&service,
+		&player.id,
+		&quarrymaster.id,
+		Assignment::Building(lumberyard.id),
+	)
+	.await
+	.unwrap_err();
+	assert!(err.to_string().contains("Another hero runs the building"));
+	let err = assign_hero(
+		&mut conn,
+		&service,
+		&player.id,
+		&marshal.id,
+		Assignment::Building(lumberyard.id),
+	)
+	.await
+	.unwrap_err();
+	assert!(
+		err.to_string()
+			.contains("Army heroes can only lead the army")
+	);
+
+	// Leveling up replaces the modifier of the hero
+	train_hero(&mut conn, &service, &player.id, &forester.id, 100)
+		.await
+		.unwrap();
+	assert_eq!(wood_rate(&mut conn, &player.id) - &before, bonus("0.20"));
+	let hero_mods: Vec<_> = active_modifiers::get_by_player_id(&mut conn, &player.id)
+		.unwrap()
+		.into_iter()
+		.filter(|active| active.source_type == ModifierSourceType::Hero)
+		.collect();
+	assert_eq!(hero_mods.len(), 1);
+	assert_ne!(hero_mods[0].id, assigned.active.id);
+
+	// The marshal leads the army for +5% combat strength
+	let combat_before = service
+		.get_or_calc_multiplier(&player.id, ModifierTarget::Combat, None)
+		.await
+		.unwrap();
+	assign_hero(
+		&mut conn,
+		&service,
+		&player.id,
+		&marshal.id,
+		Assignment::Army,
+	)
+	.await
+	.unwrap();
+	let combat_after = service
+		.get_or_calc_multiplier(&player.id, ModifierTarget::Combat, None)
+		.await
+		.unwrap();
+	assert_eq!(
+		combat_after - combat_before,
+		BigDecimal::from_str("0.05").unwrap()
+	);
+
+	let unassigned = unassign_hero(&mut conn, &service, &player.id, &forester.id)
+		.await
+		.unwrap();
+	assert!(!unassigned.recruited.unwrap().is_assigned());
+	assert_eq!(wood_rate(&mut conn, &player.id), before);
+
+	let history = modifier_history::get_by_player_id(&mut conn, &player.id).unwrap();
+	let count = |action: ModifierActionType| {
+		history
+			.iter()
+			.filter(|entry| entry.source_type == ModifierSourceType::Hero)
+			.filter(|entry| entry.action_type == action)
+			.count()
+	};
+	assert_eq!(count(ModifierActionType::Applied), 3);
+	assert_eq!(count(ModifierActionType::Removed), 2);
+}
@@ -0,0 +1,121 @@
+//! Integration tests for consumable items.
+//!
+//! These tests cover:
+//! - Taking the item from the inventory and applying its modifier for the item's duration
+//! - Recording the use in the modifier history and invalidating the cached multiplier
+//! - Refusing to use items the player does not hold
+
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+use empire::db::{active_modifiers, items, modifier_history, player_items};
+use empire::domain::factions::FactionCode;
+use empire::domain::modifier::ModifierTarget;
+use empire::domain::modifier::active_modifier::ModifierSourceType;
+use empire::domain::modifier::modifier_history::ModifierActionType;
+use empire::domain::player::resource::ResourceType;
+use empire::game::items::item_operations::use_item;
+use empire::game::modifiers::modifier_cache::CacheKey;
+use empire::game::modifiers::modifier_service::ModifierService;
+use uuid::Uuid;
+
+use crate::common::TestHarness;
+
+#[tokio::test]
+async fn test_using_an_item_applies_its_boost() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let service = ModifierService::new(&harness.app.db_pool, &harness.app.modifier_system);
+	let cache = &harness.app.modifier_system.cache;
+	let player = harness.create_named_user("booster", Some(FactionCode::Human));
+	let axe = items::get_by_name(&mut conn, "Lumberjack's Axe").expect("Items are seeded");
+	player_items::add_items(&mut conn, &player.id, &axe.id, 2).unwrap();
+
+	let cache_key = CacheKey {
+		player_id: player.id,
+		target_type: ModifierTarget::Resource,
+		target_resource: Some(ResourceType::Wood),
+	};
+	let before = service
+		.get_or_calc_multiplier(
+			&player.id,
+			ModifierTarget::Resource,
+			Some(ResourceType::Wood),
+		)
+		.await
+		.unwrap();
+	assert!(cache.get(&cache_key).is_some());
+
+	let used = use_item(&mut conn, &service, &player.id, &axe.id)
+		.await
+		.expect("Failed to use the item");
+	assert_eq!(used.remaining.quantity, 1);
+	assert_eq!(used.active.source_type, ModifierSourceType::Item);
+	assert_eq!(used.active.source_id, Some(axe.id));
+	assert_eq!(
+		used.active.expires_at,
+		Some(used.active.started_at + axe.duration())
+	);
+	assert!(cache.get(&cache_key).is_none(), "Cache is invalidated");
+
+	let after = service
+		.get_or_calc_multiplier(
+			&player.id,
+			ModifierTarget::Resource,
+			Some(ResourceType::Wood),
+		)
+		.await
+		.unwrap();
+	// The boost has its own stacking group and multiplies the faction bonus
+	assert_eq!(after, before * BigDecimal::from_str("1.5").unwrap());
+
+	let held = active_modifiers::get_by_player_id(&mut conn, &player.id).unwrap();
+	assert!(held.iter().any(|active| active.id == used.active.id));
+	let history = modifier_history::get_by_player_id(&mut conn, &player.id).unwrap();
+	let applied: Vec<_> = history
+		.iter()
+		.filter(|entry| entry.action_type == ModifierActionType::Applied)
+		.filter(|entry| entry.source_type == ModifierSourceType::Item)
+		.collect();
+	assert_eq!(applied.len(), 1);
+	assert_eq!(applied[0].source_id, Some(axe.id));
+	assert_eq!(applied[0].reason.as_deref(), Some("Used Lumberjack's Axe"));
+}
+
+#[tokio::test]
+async fn test_items_the_player_does_not_hold_cannot_be_used() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let service = ModifierService::new(&harness.app.db_pool, &harness.app.modifier_system);
+	let player = harness.create_named_user("empty_handed", Some(FactionCode::Human));
+	let modifiers_before = active_modifiers::get_by_player_id(&mut conn, &player.id)
+		.unwrap()
+		.len();
+	let ledger = items::get_by_name(&mut conn, "Merchant's Ledger").unwrap();
+
+	let err = use_item(&mut conn, &service, &player.id, &ledger.id)
+		.await
+		.expect_err("The player holds no ledgers");
+	assert!(
+		err.to_string().contains("No items of this kind left"),
+		"{err}"
+	);
+
+	player_items::add_items(&mut conn, &player.id, &ledger.id, 1).unwrap();
+	use_item(&mut conn, &service, &player.id, &ledger.id)
+		.await
+		.expect("Failed to use the item");
+	use_item(&mut conn, &service, &player.id, &ledger.id)
+		.await
+		.expect_err("The only ledger was used");
+	assert_eq!(
+		active_modifiers::get_by_player_id(&mut conn, &player.id)
+			.unwrap()
+			.len(),
+		modifiers_before + 1
+	);
+
+	use_item(&mut conn, &service, &player.id, &Uuid::new_v4())
+		.await
+		.expect_err("The item does not exist");
+}
@@ -0,0 +1,178 @@
+//! Integration tests for cancelling jobs.
+//!
+//! These tests cover:
+//! - Pending jobs are cancelled right away and never claimed
+//! - Running jobs carry a cancel request until their worker completes, fails or skips them
+//! - Batch cancellation racing a worker leaves every job either completed or cancelled
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use chrono::Utc;
+use diesel::prelude::*;
+use empire::db::DbConn;
+use empire::domain::jobs::{Job, JobKey, JobStatus, JobType};
+use empire::job_queue::JobPriority;
+use empire::job_queue::cancellation::JobCancellation;
+use empire::schema::job;
+
+use crate::common::TestHarness;
+
+fn get_job(conn: &mut DbConn, job_id: &JobKey) -> Job {
+	job::table
+		.find(job_id)
+		.select(Job::as_select())
+		.first(conn)
+		.expect("Job not found")
+}
+
+#[tokio::test]
+async fn test_pending_jobs_are_cancelled() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	let job_id = app
+		.job_queue
+		.enqueue(
+			JobType::Combat,
+			serde_json::json!({ "cancel": "pending" }),
+			JobPriority::High,
+			Utc::now(),
+		)
+		.unwrap();
+
+	assert_eq!(
+		app.job_queue.cancel_job(&job_id).unwrap(),
+		JobCancellation::Cancelled
+	);
+	assert_eq!(get_job(&mut conn, &job_id).status, JobStatus::Cancelled);
+	assert_eq!(
+		app.job_queue.cancel_job(&job_id).unwrap(),
+		JobCancellation::NotCancellable
+	);
+
+	let claimed = app
+		.job_queue
+		.get_next_job_of_type("worker", &JobType::Combat)
+		.unwrap();
+	assert!(
+		claimed.is_none_or(|claimed| claimed.id != job_id),
+		"Cancelled job must not be claimed"
+	);
+}
+
+#[tokio::test]
+async fn test_running_jobs_finish_as_cancelled() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+	let queue = &app.job_queue;
+
+	let mut claim = |tag: &str| {
+		let job_id = queue
+			.enqueue(
+				JobType::Combat,
+				serde_json::json!({ "cancel": tag }),
+				JobPriority::High,
+				Utc::now(),
+			)
+			.unwrap();
+		let claimed = queue
+			.get_next_job_of_type("worker", &JobType::Combat)
+			.unwrap()
+			.expect("Job should be claimed");
+		assert_eq!(claimed.id, job_id);
+		assert_eq!(
+			queue.cancel_job(&job_id).unwrap(),
+			JobCancellation::Requested
+		);
+		assert_eq!(
+			get_job(&mut conn, &job_id).status,
+			JobStatus::CancelRequested
+		);
+		job_id
+	};
+	let completed = claim("complete");
+	let failed = claim("fail");
+	let skipped = claim("skip");
+
+	// Asking twice keeps the request
+	assert_eq!(
+		queue.cancel_job(&completed).unwrap(),
+		JobCancellation::Requested
+	);
+	queue.complete_job(&completed).unwrap();
+	let completed = get_job(&mut conn, &completed);
+	assert_eq!(completed.status, JobStatus::Cancelled);
+	assert!(completed.locked_by.is_none());
+
+	// A failed run is recorded but not retried
+	queue.fail_job(&failed, "interrupted").unwrap();
+	let failed = get_job(&mut conn, &failed);
+	assert_eq!(failed.status, JobStatus::Cancelled);
+	assert_eq!(failed.last_error.as_deref(), Some("interrupted"));
+	assert_eq!(failed.retries, 0);
+
+	assert!(queue.take_cancel_request(&skipped).unwrap());
+	assert!(!queue.take_cancel_request(&skipped).unwrap());
+	assert_eq!(get_job(&mut conn, &skipped).status, JobStatus::Cancelled);
+}
+
+#[tokio::test]
+async fn test_batch_cancellation_racing_a_worker() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+	let queue = &app.job_queue;
+
+	let job_ids: Vec<JobKey> = (0..40)
+		.map(|n| {
+			queue
+				.enqueue(
+					JobType::Combat,
+					serde_json::json!({ "cancel": "race", "n": n }),
+					JobPriority::High,
+					Utc::now(),
+				)
+				.unwrap()
+		})
+		.collect();
+	let cancelling = AtomicBool::new(true);
+
+	std::thread::scope(|scope| {
+		scope.spawn(|| {
+			loop {
+				let done = !cancelling.load(Ordering::Acquire);
+				match queue
+					.get_next_job_of_type("worker", &JobType::Combat)
+					.unwrap()
+				{
+					Some(claimed) => {
+						if !queue.take_cancel_request(&claimed.id).unwrap() {
+							queue.complete_job(&claimed.id).unwrap();
+						}
+					}
+					None if done => break,
+					None => std::thread::yield_now(),
+				}
+			}
+		});
+
+		for chunk in job_ids.chunks(4).skip(1).step_by(2) {
+			let batch = queue.cancel_jobs_batch(chunk).unwrap();
+			assert!(batch.cancelled.len() + batch.cancel_requested.len() <= chunk.len());
+		}
+		cancelling.store(false, Ordering::Release);
+	});
+
+	let mut cancelled = 0;
+	for (n, job_id) in job_ids.iter().enumerate() {
+		let status = get_job(&mut conn, job_id).status;
+		match status {
+			JobStatus::Completed => {}
+			JobStatus::Cancelled => {
+				assert_eq!((n / 4) % 2, 1, "Job {n} was cancelled without being asked");
+				cancelled += 1;
+			}
+			other => panic!("Job {n} ended as {other:?}"),
+		}
+	}
+	assert!(cancelled <= job_ids.len() / 2);
+}
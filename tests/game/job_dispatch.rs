@@ -0,0 +1,437 @@
+//! Integration tests for the job dispatch table.
+//!
+//! Enqueues one job of every [`JobType`] with a representative payload, runs the worker
+//! pool exactly as the server registers it, and checks that every job reaches a terminal
+//! status with the expected side effects. A job type without a processor would be left
+//! pending and fail this suite.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{TimeDelta, Utc};
+use diesel::prelude::*;
+use empire::db::{
+	DbConn, backfills, battle_reports, limited_events, planned_actions, player_buildings,
+	player_units, players, scout_missions, seasons, training_queue, world_resets,
+};
+use empire::domain::account_deletion::AccountDeletionJobPayload;
+use empire::domain::app_state::AppState;
+use empire::domain::backfill::BackfillJobPayload;
+use empire::domain::combat::{NewBattleReport, NewScoutMission};
+use empire::domain::events::GameEvent;
+use empire::domain::factions::{FactionCode, FactionJobPayload};
+use empire::domain::jobs::{Job, JobKey, JobStatus, JobType};
+use empire::domain::leaderboard::LeaderboardJobPayload;
+use empire::domain::limited_event::{LimitedEventJobPayload, NewLimitedEvent};
+use empire::domain::player::Player;
+use empire::domain::player::planned_action::{
+	NewPlannedAction, PlannedActionKind, PlannedActionStatus,
+};
+use empire::domain::player::resource::ResourceType;
+use empire::domain::season::{NewSeason, SeasonJobPayload};
+use empire::domain::table_stats::TableStatsJobPayload;
+use empire::domain::unit::training::{NewTrainingQueueEntry, TrainingStatus};
+use empire::domain::world_reset::{NewWorldReset, WorldResetJobPayload, WorldResetStep};
+use empire::game::buildings::plan_operations::BuildingJobPayload;
+use empire::game::combat::combat_operations::CombatJobPayload;
+use empire::game::combat::espionage_operations::EspionageJobPayload;
+use empire::game::modifiers::modifier_scheduler::ModifierJobPayload;
+use empire::game::resources::resource_scheduler::{
+	ProductionJobPayload, ProductionTickPayload, production_shard,
+};
+#[cfg(feature = "simulation")]
+use empire::game::simulation::simulation_operations::SimulationJobPayload;
+use empire::game::table_stats::stats_operations::TRACKED_TABLES;
+use empire::game::units::training_operations::TrainingJobPayload;
+use empire::job_queue::JobPriority;
+use empire::job_queue::worker_pool::WorkerPool;
+use empire::schema::{building, job, player_accumulator as acc, player_resource as rsc, unit};
+use empire::startup::register_processors;
+use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::common::TestHarness;
+use crate::fixtures::create_test_player;
+
+/// Prepares the state a representative job of the given type works on and returns its payload.
+///
+/// The match is exhaustive on purpose: adding a job type fails to compile until it gets a
+/// representative payload here.
+fn representative_payload(
+	conn: &mut DbConn,
+	player: &Player,
+	job_type: JobType,
+) -> serde_json::Value {
+	let payload = match job_type {
+		JobType::Modifier => serde_json::to_value(ModifierJobPayload::UpdateModifierCache {
+			player_id: player.id,
+		}),
+		JobType::Building => {
+			let farm_id: i32 = building::table
+				.filter(building::name.eq("Farm"))
+				.filter(building::faction.eq(player.faction))
+				.select(building::id)
+				.first(conn)
+				.expect("Farm not found");
+			planned_actions::create(
+				conn,
+				NewPlannedAction {
+					player_id: player.id,
+					action: PlannedActionKind::Construct,
+					building_id: Some(farm_id),
+					player_building_id: None,
+					settlement_id: None,
+				},
+			)
+			.expect("Failed to create plan");
+			serde_json::to_value(BuildingJobPayload::EvaluatePlans {
+				player_id: player.id,
+			})
+		}
+		JobType::Resource => {
+			diesel::update(acc::table.filter(acc::player_id.eq(player.id)))
+				.set(acc::food.eq(100))
+				.execute(conn)
+				.expect("Failed to fill accumulator");
+			serde_json::to_value(ProductionJobPayload::CollectResources {
+				players_id: player.id,
+			})
+		}
+		JobType::ResourceProduction => serde_json::to_value(ProductionTickPayload {
+			shard: production_shard(&player.id, 4),
+			shards: 4,
+		}),
+		JobType::Training => {
+			let building = player_buildings::get_player_buildings(conn, &player.id)
+				.expect("Failed to get player buildings")
+				.remove(0);
+			let unit_id: Uuid = unit::table
+				.select(unit::id)
+				.first(conn)
+				.expect("No units seeded");
+			let entry = training_queue::create(
+				conn,
+				NewTrainingQueueEntry {
+					player_id: player.id,
+					building_id: building.id,
+					unit_id,
+					quantity: 3,
+					status: Some(TrainingStatus::InProgress),
+					job_id: None,
+					completes_at: Utc::now(),
+					training_modifier: 1.into(),
+					seconds_per_unit: 60,
+				},
+			)
+			.expect("Failed to create training entry");
+			serde_json::to_value(TrainingJobPayload {
+				training_queue_entry_id: entry.id,
+				player_id: player.id,
+				unit_id,
+				quantity: 3,
+			})
+		}
+		JobType::Combat => {
+			let defender = create_test_player(conn, FactionCode::Orc);
+			battle_reports::create(
+				conn,
+				NewBattleReport {
+					attacker_id: player.id,
+					defender_id: defender.id,
+					winner_id: None,
+					attacker_losses: serde_json::json!([]),
+					defender_losses: serde_json::json!([]),
+					loot_food: 0,
+					loot_wood: 0,
+					loot_stone: 0,
+					loot_gold: 0,
+					modifiers: serde_json::json!([]),
+					fought_at: Utc::now() - TimeDelta::days(365),
+				},
+			)
+			.expect("Failed to create battle report");
+			serde_json::to_value(CombatJobPayload::PruneReports)
+		}
+		JobType::Espionage => {
+			// Two other players, so the scouts coming home leave the army of the player alone
+			let attacker = create_test_player(conn, FactionCode::Human);
+			let defender = create_test_player(conn, FactionCode::Orc);
+			let mission = scout_missions::create(
+				conn,
+				NewScoutMission {
+					attacker_id: attacker.id,
+					defender_id: defender.id,
+					scouts: 2,
+					arrives_at: Utc::now(),
+				},
+			)
+			.expect("Failed to create scout mission");
+			serde_json::to_value(EspionageJobPayload::CompleteMission {
+				mission_id: mission.id,
+			})
+		}
+		JobType::Backfill => {
+			let name = backfills::BACKFILLS[0].name();
+			backfills::ensure_progress(conn, name).expect("Failed to create backfill progress");
+			serde_json::to_value(BackfillJobPayload {
+				name: name.to_string(),
+			})
+		}
+		JobType::WorldReset => {
+			// A step that is not due yet, so the job does not wipe what the other jobs work on
+			let reset = world_resets::create(
+				conn,
+				NewWorldReset {
+					token_hash: "hash".to_string(),
+					requested_by: None,
+					expires_at: Utc::now() + TimeDelta::minutes(15),
+				},
+			)
+			.expect("Failed to create world reset");
+			world_resets::confirm(conn, &reset.id, None, Utc::now())
+				.expect("Failed to confirm world reset");
+			serde_json::to_value(WorldResetJobPayload {
+				reset_id: reset.id,
+				step: WorldResetStep::Players,
+			})
+		}
+		JobType::LimitedEvent => {
+			// An event that already ended, so the job closes it
+			let event = limited_events::create(
+				conn,
+				NewLimitedEvent {
+					name: "Dispatch Festival".to_string(),
+					description: "Ended an hour ago".to_string(),
+					currency_name: "Tokens".to_string(),
+					starts_at: Utc::now() - TimeDelta::days(1),
+					ends_at: Utc::now() - TimeDelta::hours(1),
+					leftover_resource: ResourceType::Gold,
+					leftover_rate: 1,
+				},
+			)
+			.expect("Failed to create limited event");
+			serde_json::to_value(LimitedEventJobPayload { event_id: event.id })
+		}
+		JobType::TableStats => serde_json::to_value(TableStatsJobPayload::Capture),
+		JobType::Leaderboard => serde_json::to_value(LeaderboardJobPayload::Snapshot),
+		JobType::Season => {
+			// A season that is already over, so the job ends it
+			let season = seasons::create(
+				conn,
+				NewSeason {
+					number: 1,
+					name: "Dispatch Season".to_string(),
+					starts_at: Utc::now() - TimeDelta::days(30),
+					ends_at: Utc::now() - TimeDelta::hours(1),
+				},
+			)
+			.expect("Failed to create season");
+			serde_json::to_value(SeasonJobPayload {
+				season_id: season.id,
+			})
+		}
+		JobType::AccountDeletion => {
+			// Another player, whose grace period already ended
+			let leaving = create_test_player(conn, FactionCode::Elf);
+			let scheduled_for = Utc::now() - TimeDelta::minutes(1);
+			players::set_deletion_scheduled_for(conn, &leaving.id, Some(scheduled_for))
+				.expect("Failed to schedule deletion");
+			serde_json::to_value(AccountDeletionJobPayload {
+				player_id: leaving.id,
+				scheduled_for,
+			})
+		}
+		JobType::Faction => serde_json::to_value(FactionJobPayload::Standings),
+		#[cfg(feature = "simulation")]
+		JobType::Simulation => serde_json::to_value(SimulationJobPayload::Construct),
+	};
+	payload.expect("Failed to serialize payload")
+}
+
+/// Loads a job by its ID.
+fn get_job(conn: &mut DbConn, job_id: &JobKey) -> Job {
+	job::table
+		.find(job_id)
+		.select(Job::as_select())
+		.first(conn)
+		.expect("Job not found")
+}
+
+#[tokio::test]
+async fn test_every_job_type_is_dispatched() {
+	let h = TestHarness::new();
+	let mut conn = h.db_pool.get().unwrap();
+	let state = AppState(h.app);
+	let player = create_test_player(&mut conn, FactionCode::Human);
+	diesel::update(rsc::table.filter(rsc::player_id.eq(player.id)))
+		.set((
+			rsc::food.eq(100_000),
+			rsc::wood.eq(100_000),
+			rsc::stone.eq(100_000),
+			rsc::gold.eq(100_000),
+			rsc::food_cap.eq(1_000_000),
+		))
+		.execute(&mut conn)
+		.expect("Failed to set player resources");
+
+	let jobs: Vec<(JobType, JobKey)> = JobType::ALL
+		.into_iter()
+		.map(|job_type| {
+			let payload = representative_payload(&mut conn, &player, job_type);
+			let job_id = state
+				.job_queue
+				.enqueue(job_type, payload, JobPriority::Normal, Utc::now())
+				.expect("Failed to enqueue job");
+			(job_type, job_id)
+		})
+		.collect();
+
+	let mut events = state.events.subscribe();
+	let mut worker_pool = WorkerPool::new(Arc::clone(&state.job_queue), CancellationToken::new());
+	register_processors(&mut worker_pool, &state, 1);
+
+	// Wait until every job reached a terminal status
+	let deadline = Utc::now() + TimeDelta::seconds(30);
+	let mut pending: Vec<_> = jobs.clone();
+	while !pending.is_empty() && Utc::now() < deadline {
+		pending.retain(|(_, job_id)| {
+			let status = get_job(&mut conn, job_id).status;
+			status == JobStatus::Pending || status == JobStatus::InProgress
+		});
+		sleep(Duration::from_millis(250)).await;
+	}
+	worker_pool.shutdown().await.expect("Failed to shut down");
+
+	assert!(
+		pending.is_empty(),
+		"Jobs were never processed, is a processor missing? {:?}",
+		pending
+			.iter()
+			.map(|(job_type, _)| job_type)
+			.collect::<Vec<_>>()
+	);
+	for (job_type, job_id) in &jobs {
+		let job = get_job(&mut conn, job_id);
+		assert_eq!(
+			job.status,
+			JobStatus::Completed,
+			"{job_type} job did not complete: {:?}",
+			job.last_error
+		);
+	}
+
+	// Processors that report an outcome store it on the job
+	let result_of = |conn: &mut DbConn, wanted: JobType| {
+		let (_, job_id) = jobs
+			.iter()
+			.find(|(job_type, _)| *job_type == wanted)
+			.unwrap();
+		get_job(conn, job_id).result
+	};
+	assert_eq!(
+		result_of(&mut conn, JobType::Building).unwrap()["executed"],
+		1
+	);
+	assert_eq!(
+		result_of(&mut conn, JobType::Training).unwrap()["status"],
+		"completed"
+	);
+	assert_eq!(
+		result_of(&mut conn, JobType::Combat).unwrap()["pruned_reports"],
+		1
+	);
+	assert!(result_of(&mut conn, JobType::Espionage).unwrap()["report_id"].is_string());
+	assert!(result_of(&mut conn, JobType::Modifier).is_none());
+	let production = result_of(&mut conn, JobType::ResourceProduction).unwrap();
+	assert!(production["produced"].as_u64().unwrap() >= 1);
+	assert_eq!(production["failed"], 0);
+	assert_eq!(
+		result_of(&mut conn, JobType::Backfill).unwrap()["completed"],
+		true
+	);
+	assert_eq!(
+		result_of(&mut conn, JobType::WorldReset).unwrap()["skipped"],
+		true
+	);
+	assert_eq!(
+		result_of(&mut conn, JobType::LimitedEvent).unwrap()["closed"],
+		true
+	);
+	assert_eq!(
+		result_of(&mut conn, JobType::TableStats).unwrap()["captured"],
+		TRACKED_TABLES.len()
+	);
+	let leaderboard = result_of(&mut conn, JobType::Leaderboard).unwrap();
+	assert!(leaderboard["scored"].as_u64().unwrap() >= 1);
+	assert_eq!(
+		result_of(&mut conn, JobType::Season).unwrap()["ended"],
+		true
+	);
+	assert_eq!(
+		result_of(&mut conn, JobType::AccountDeletion).unwrap()["anonymized"],
+		true
+	);
+	assert_eq!(
+		result_of(&mut conn, JobType::Faction).unwrap()["standings"],
+		5
+	);
+
+	// Building: the planned construction was executed
+	let plans = planned_actions::get_pending_for_player(&mut conn, &player.id).unwrap();
+	assert!(plans.is_empty(), "Plan should have been executed");
+	let plan_statuses: Vec<PlannedActionStatus> = empire::schema::planned_action::table
+		.filter(empire::schema::planned_action::player_id.eq(player.id))
+		.select(empire::schema::planned_action::status)
+		.load(&mut conn)
+		.unwrap();
+	assert_eq!(plan_statuses, vec![PlannedActionStatus::Completed]);
+
+	// Resource: the accumulated food was collected
+	let acc_food: i64 = acc::table
+		.filter(acc::player_id.eq(player.id))
+		.select(acc::food)
+		.first(&mut conn)
+		.unwrap();
+	assert_eq!(acc_food, 0, "Accumulated food should have been collected");
+
+	// Training: the units were added to the inventory
+	let entries = training_queue::get_active_for_player(&mut conn, &player.id).unwrap();
+	assert!(entries.is_empty(), "Training should have completed");
+	let trained: i64 = player_units::get_for_player(&mut conn, &player.id)
+		.unwrap()
+		.iter()
+		.map(|pu| pu.quantity)
+		.sum();
+	assert_eq!(trained, 3);
+
+	// Combat: the expired report was pruned and the next run scheduled
+	let (reports, total) =
+		battle_reports::get_page_for_player(&mut conn, &player.id, 10, 0).unwrap();
+	assert!(reports.is_empty());
+	assert_eq!(total, 0);
+	assert!(
+		state
+			.job_queue
+			.has_pending_of_type(&JobType::Combat)
+			.unwrap()
+	);
+
+	// Completion events were published for connected clients
+	let mut published = Vec::new();
+	while let Ok(event) = events.try_recv() {
+		published.push(event);
+	}
+	assert!(
+		published
+			.iter()
+			.any(|e| matches!(e, GameEvent::TrainingCompleted { quantity: 3, .. })),
+		"Missing training completion event: {published:?}"
+	);
+	assert!(
+		published
+			.iter()
+			.any(|e| matches!(e, GameEvent::ResourcesCollected { .. })),
+		"Missing resource collection event: {published:?}"
+	);
+}
@@ -1,15 +1,22 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 use std::time::Duration;
 
-use chrono::Utc;
+use chrono::{TimeDelta, Utc};
+use diesel::prelude::*;
 use empire::domain::app_state::AppState;
-use empire::domain::jobs::JobType;
+use empire::domain::jobs::{JobStatus, JobType};
 use empire::domain::player::resource::ResourceType;
 use empire::game::modifiers::modifier_processor::ModifierProcessor;
 use empire::game::modifiers::modifier_scheduler::ModifierJobPayload;
-use empire::job_queue::JobPriority;
+use empire::job_queue::job_processor::with_job_timeout;
+use empire::job_queue::payload_encryption::PayloadCipher;
 use empire::job_queue::worker_pool::WorkerPool;
-use tokio::time::sleep;
+use empire::job_queue::{DEFAULT_JOB_TIMEOUT_SECONDS, JOB_POLL_INTERVAL, JobPriority, JobQueue};
+use empire::schema::job;
+use secrecy::SecretString;
+use serde_json::json;
+use tokio::time::{Instant, sleep};
 use tokio_util::sync::CancellationToken;
 use tracing::info;
 use uuid::Uuid;
@@ -99,3 +106,265 @@ async fn test_worker_pool_cancellation_token() {
 
 	assert_eq!(worker_pool.worker_count(), 0);
 }
+
+#[tokio::test]
+async fn test_notified_jobs_skip_the_poll_interval() {
+	let h = TestHarness::new();
+	let state = AppState(h.app);
+	let mut conn = state.db_pool.get().unwrap();
+
+	let mut worker_pool = WorkerPool::new(Arc::clone(&state.job_queue), CancellationToken::new());
+	worker_pool.add_workers(ModifierProcessor::initialise_n(1, &state));
+
+	// Let the worker finish its first poll and the listener subscribe to the channel
+	sleep(Duration::from_millis(500)).await;
+
+	let started = Instant::now();
+	let job_id = worker_pool
+		.queue
+		.enqueue(
+			JobType::Modifier,
+			ModifierJobPayload::UpdateModifierCache {
+				player_id: Uuid::new_v4(),
+			},
+			JobPriority::Normal,
+			Utc::now(),
+		)
+		.unwrap();
+
+	let mut job_status = JobStatus::Pending;
+	while job_status != JobStatus::Completed && started.elapsed() < JOB_POLL_INTERVAL {
+		sleep(Duration::from_millis(20)).await;
+		job_status = empire::schema::job::table
+			.find(job_id)
+			.select(empire::schema::job::status)
+			.first(&mut conn)
+			.unwrap();
+	}
+	let latency = started.elapsed();
+	worker_pool.shutdown().await.expect("Failed to shut down");
+
+	assert_eq!(job_status, JobStatus::Completed);
+	assert!(
+		latency < Duration::from_secs(1),
+		"Job should be picked up on notification, took {latency:?}"
+	);
+}
+
+#[tokio::test]
+async fn test_concurrency_limit_caps_in_progress_jobs() {
+	let h = TestHarness::new();
+	let queue = JobQueue::new(Arc::clone(&h.app.db_pool))
+		.with_concurrency_limits(BTreeMap::from([(JobType::Combat, 1)]));
+
+	let run_at = Utc::now() - TimeDelta::seconds(1);
+	for _ in 0..2 {
+		queue
+			.enqueue(JobType::Combat, json!({}), JobPriority::Low, run_at)
+			.unwrap();
+	}
+
+	let first = queue
+		.get_next_job_of_type("worker-1", &JobType::Combat)
+		.unwrap()
+		.expect("First job should be claimed");
+	let blocked = queue
+		.get_next_job_of_type("worker-2", &JobType::Combat)
+		.unwrap();
+	assert!(blocked.is_none(), "Limit should hold back the second job");
+
+	// Other job types are not affected by the combat limit
+	queue
+		.enqueue(JobType::Building, json!({}), JobPriority::Low, run_at)
+		.unwrap();
+	assert!(
+		queue
+			.get_next_job_of_type("worker-3", &JobType::Building)
+			.unwrap()
+			.is_some()
+	);
+
+	queue.complete_job(&first.id).unwrap();
+	let second = queue
+		.get_next_job_of_type("worker-2", &JobType::Combat)
+		.unwrap()
+		.expect("Second job should be claimed once the first is done");
+	assert_ne!(second.id, first.id);
+}
+
+#[tokio::test]
+async fn test_priority_aging_lets_waiting_jobs_through() {
+	let h = TestHarness::new();
+	let aging = JobQueue::new(Arc::clone(&h.app.db_pool)).with_priority_aging(10.0);
+	let strict = JobQueue::new(Arc::clone(&h.app.db_pool)).with_priority_aging(0.0);
+
+	// A low-priority job that waited 20 minutes next to a fresh high-priority one
+	let now = Utc::now();
+	let low = aging
+		.enqueue(
+			JobType::Combat,
+			json!({ "aging": "low" }),
+			JobPriority::Low,
+			now - TimeDelta::minutes(20),
+		)
+		.unwrap();
+	let high = aging
+		.enqueue(
+			JobType::Combat,
+			json!({ "aging": "high" }),
+			JobPriority::High,
+			now,
+		)
+		.unwrap();
+	let normal = aging
+		.enqueue(
+			JobType::Combat,
+			json!({ "aging": "normal" }),
+			JobPriority::Normal,
+			now - TimeDelta::minutes(1),
+		)
+		.unwrap();
+
+	let first = strict
+		.get_next_job_of_type("worker-1", &JobType::Combat)
+		.unwrap()
+		.expect("Job should be claimed");
+	assert_eq!(
+		first.id, high,
+		"Without aging the high-priority job goes first"
+	);
+	strict.complete_job(&first.id).unwrap();
+
+	// Aged past the highest priority, the low job ties with high and wins by age
+	let high = aging
+		.enqueue(
+			JobType::Combat,
+			json!({ "aging": "high again" }),
+			JobPriority::High,
+			now,
+		)
+		.unwrap();
+	let claimed: Vec<_> = (0..3)
+		.map(|_| {
+			aging
+				.get_next_job_of_type("worker-2", &JobType::Combat)
+				.unwrap()
+				.expect("Job should be claimed")
+				.id
+		})
+		.collect();
+	assert_eq!(claimed, vec![low, high, normal]);
+}
+
+#[tokio::test]
+async fn test_jobs_time_out_after_their_own_timeout() {
+	let h = TestHarness::new();
+	let queue = JobQueue::new(Arc::clone(&h.app.db_pool))
+		.with_timeouts(BTreeMap::from([(JobType::Combat, 1)]));
+	let mut conn = h.db_pool.get().unwrap();
+
+	let run_at = Utc::now() - TimeDelta::seconds(1);
+	let short = queue
+		.enqueue(
+			JobType::Combat,
+			json!({ "timeout": "short" }),
+			JobPriority::High,
+			run_at,
+		)
+		.unwrap();
+	let long = queue
+		.enqueue(
+			JobType::Building,
+			json!({ "timeout": "long" }),
+			JobPriority::High,
+			run_at,
+		)
+		.unwrap();
+	assert_eq!(queue.get_job(&short).unwrap().timeout_seconds, 1);
+	assert_eq!(
+		queue.get_job(&long).unwrap().timeout_seconds,
+		DEFAULT_JOB_TIMEOUT_SECONDS
+	);
+
+	// Workers give up on a job once its timeout elapses
+	let claimed = queue
+		.get_next_job_of_type("worker-1", &JobType::Combat)
+		.unwrap()
+		.expect("Job should be claimed");
+	let outcome = with_job_timeout(&claimed, async {
+		sleep(Duration::from_secs(5)).await;
+		Ok(None)
+	})
+	.await;
+	let err = outcome.expect_err("Processing should time out");
+	assert!(err.to_string().contains("Job timed out"), "{err}");
+
+	// Jobs locked well past their timeout are released by the next claim
+	queue
+		.get_next_job_of_type("worker-2", &JobType::Building)
+		.unwrap()
+		.expect("Job should be claimed");
+	diesel::update(job::table.filter(job::id.eq_any([short, long])))
+		.set(job::locked_at.eq(Some(Utc::now() - TimeDelta::minutes(1))))
+		.execute(&mut conn)
+		.unwrap();
+	let _ = queue
+		.get_next_job_of_type("worker-3", &JobType::Resource)
+		.unwrap();
+
+	let short = queue.get_job(&short).unwrap();
+	assert_eq!(short.status, JobStatus::Failed);
+	assert_eq!(
+		short.last_error.as_deref(),
+		Some("Job timed out after 1 seconds")
+	);
+	assert!(short.locked_by.is_none());
+	assert_eq!(queue.get_job(&long).unwrap().status, JobStatus::InProgress);
+}
+
+#[tokio::test]
+async fn test_flagged_payloads_are_encrypted_at_rest() {
+	let h = TestHarness::new();
+	let key = SecretString::from("MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=");
+	let cipher = PayloadCipher::from_base64_key(&key).unwrap();
+	let queue = JobQueue::new(Arc::clone(&h.app.db_pool))
+		.with_payload_encryption(Some(cipher), BTreeSet::from([JobType::Combat]));
+	let mut conn = h.db_pool.get().unwrap();
+
+	let payload = json!({ "webhook_secret": "hunter2" });
+	let run_at = Utc::now() - TimeDelta::seconds(1);
+	let sealed = queue
+		.enqueue(JobType::Combat, &payload, JobPriority::High, run_at)
+		.unwrap();
+	let plain = queue
+		.enqueue(JobType::Building, &payload, JobPriority::High, run_at)
+		.unwrap();
+
+	let mut stored = |job_id| -> serde_json::Value {
+		job::table
+			.find(job_id)
+			.select(job::payload)
+			.first(&mut conn)
+			.unwrap()
+	};
+	let stored_sealed = stored(sealed);
+	assert!(PayloadCipher::is_sealed(&stored_sealed));
+	assert!(!stored_sealed.to_string().contains("hunter2"));
+	assert_eq!(stored(plain), payload);
+
+	let claimed = queue
+		.get_next_job_of_type("worker-1", &JobType::Combat)
+		.unwrap()
+		.expect("Job should be claimed");
+	assert_eq!(claimed.id, sealed);
+	assert_eq!(claimed.payload, payload);
+	assert_eq!(queue.get_job(&sealed).unwrap().payload, payload);
+
+	// Without the key, flagged jobs are refused instead of stored in plaintext
+	let keyless = JobQueue::new(Arc::clone(&h.app.db_pool))
+		.with_payload_encryption(None, BTreeSet::from([JobType::Combat]));
+	let err = keyless
+		.enqueue(JobType::Combat, &payload, JobPriority::High, run_at)
+		.expect_err("Enqueue should be refused");
+	assert!(err.to_string().contains("not configured"), "{err}");
+}
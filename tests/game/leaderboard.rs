@@ -0,0 +1,189 @@
+//! Integration tests for the leaderboards.
+//!
+//! These tests cover:
+//! - Scoring players by their buildings, units and research buildings
+//! - Snapshotting the power, economy and alliance boards, without players hiding their stats
+//! - Reading pages of a board along with the player's own rank
+
+use chrono::Utc;
+use diesel::prelude::*;
+use empire::db::{DbConn, player_privacy, player_units, settlements, units};
+use empire::domain::events::GameEvent;
+use empire::domain::factions::FactionCode;
+use empire::domain::leaderboard::LeaderboardCategory;
+use empire::domain::player::PlayerKey;
+use empire::domain::player::privacy::UpdatePlayerPrivacy;
+use empire::game::alliances::alliance_operations::create_alliance;
+use empire::game::leaderboard::leaderboard_operations::{
+	changes_score, get_page, get_score, refresh_score, snapshot,
+};
+use empire::schema::{building, player_building};
+use uuid::Uuid;
+
+use crate::common::TestHarness;
+
+/// Gives the player infantry, worth 25 points each.
+fn recruit(conn: &mut DbConn, player_id: &PlayerKey, quantity: i64) {
+	let infantry = units::find_by_name(conn, "Infantry").unwrap().unwrap();
+	player_units::add_units(conn, player_id, &infantry.id, quantity).unwrap();
+}
+
+#[tokio::test]
+async fn test_players_score_buildings_units_and_research() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("scholar", Some(FactionCode::Human));
+
+	let score = get_score(&mut conn, &player.id).unwrap();
+	assert!(score.building_points > 0, "Starter buildings score points");
+	assert_eq!(score.unit_points, 0);
+	assert_eq!(score.tech_points, 0);
+
+	recruit(&mut conn, &player.id, 4);
+	let academy_id: i32 = building::table
+		.filter(building::name.eq("Academy"))
+		.filter(building::faction.eq(FactionCode::Human))
+		.select(building::id)
+		.first(&mut conn)
+		.unwrap();
+	let capital = settlements::get_capital(&mut conn, &player.id).unwrap();
+	diesel::insert_into(player_building::table)
+		.values((
+			player_building::player_id.eq(player.id),
+			player_building::settlement_id.eq(capital.id),
+			player_building::building_id.eq(academy_id),
+			player_building::level.eq(3),
+		))
+		.execute(&mut conn)
+		.unwrap();
+
+	// Scores only follow along once refreshed
+	assert_eq!(get_score(&mut conn, &player.id).unwrap(), score);
+	let refreshed = refresh_score(&mut conn, &player.id).unwrap();
+	assert_eq!(refreshed.building_points, score.building_points);
+	assert_eq!(refreshed.unit_points, 100);
+	assert_eq!(refreshed.tech_points, 3);
+	assert_eq!(refreshed.power(), 100);
+	assert_eq!(refreshed.economy(), score.building_points + 3);
+
+	// Completed upgrades and trainings refresh the score, other events do not
+	assert!(changes_score(&GameEvent::UpgradeCompleted {
+		player_id: player.id,
+		player_building_id: Uuid::new_v4(),
+		level: 2,
+	}));
+	assert!(!changes_score(&GameEvent::ResourcesCollected {
+		player_id: player.id,
+		food: 0,
+		wood: 0,
+		stone: 0,
+		gold: 0,
+	}));
+}
+
+#[tokio::test]
+async fn test_snapshot_ranks_players_and_alliances() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let general = harness.create_named_user("general", Some(FactionCode::Orc));
+	let captain = harness.create_named_user("captain", Some(FactionCode::Human));
+	let hermit = harness.create_named_user("hermit", Some(FactionCode::Elf));
+	recruit(&mut conn, &general.id, 10);
+	recruit(&mut conn, &captain.id, 2);
+	recruit(&mut conn, &hermit.id, 100);
+	player_privacy::update(
+		&mut conn,
+		&hermit.id,
+		&UpdatePlayerPrivacy {
+			hide_stats: Some(true),
+			..Default::default()
+		},
+	)
+	.unwrap();
+	let alliance = create_alliance(&mut conn, &hermit.id, "Quiet Ones", "QO")
+		.unwrap()
+		.alliance;
+
+	// Nothing is ranked before the first snapshot
+	let page = get_page(
+		&mut conn,
+		&general.id,
+		LeaderboardCategory::Power,
+		None,
+		None,
+	)
+	.unwrap();
+	assert!(page.entries.is_empty());
+	assert_eq!(page.own, None);
+
+	let summary = snapshot(&mut conn, Utc::now()).unwrap();
+	assert!(summary.scored >= 3);
+
+	let page = get_page(
+		&mut conn,
+		&captain.id,
+		LeaderboardCategory::Power,
+		None,
+		None,
+	)
+	.unwrap();
+	let ranked: Vec<_> = page.entries.iter().map(|r| r.entry.subject_id).collect();
+	let general_at = ranked.iter().position(|id| *id == general.id).unwrap();
+	let captain_at = ranked.iter().position(|id| *id == captain.id).unwrap();
+	assert!(general_at < captain_at);
+	assert!(!ranked.contains(&hermit.id), "Hidden stats are not ranked");
+	assert_eq!(page.entries[general_at].name, "general");
+	assert_eq!(page.entries[general_at].entry.score, 250);
+	let own = page.own.unwrap();
+	assert_eq!(own.rank, page.entries[captain_at].entry.rank);
+	assert_eq!(own.score, 50);
+	assert_eq!(page.total, ranked.len() as i64);
+
+	// Pages are 1-based and continue where the last one ended
+	let second = get_page(
+		&mut conn,
+		&captain.id,
+		LeaderboardCategory::Power,
+		Some(2),
+		Some(1),
+	)
+	.unwrap();
+	assert_eq!(second.entries.len(), 1);
+	assert_eq!(second.entries[0].entry.subject_id, ranked[1]);
+
+	// The alliance counts all points of its members, hidden or not
+	let page = get_page(
+		&mut conn,
+		&hermit.id,
+		LeaderboardCategory::Alliance,
+		None,
+		None,
+	)
+	.unwrap();
+	assert_eq!(page.entries.len(), 1);
+	assert_eq!(page.entries[0].name, "Quiet Ones");
+	assert_eq!(page.entries[0].entry.subject_id, alliance.id);
+	let hermit_score = get_score(&mut conn, &hermit.id).unwrap();
+	assert_eq!(page.entries[0].entry.score, hermit_score.total());
+	assert_eq!(page.own.unwrap().rank, 1);
+	let page = get_page(
+		&mut conn,
+		&general.id,
+		LeaderboardCategory::Alliance,
+		None,
+		None,
+	)
+	.unwrap();
+	assert_eq!(page.own, None);
+
+	let page = get_page(
+		&mut conn,
+		&hermit.id,
+		LeaderboardCategory::Economy,
+		None,
+		None,
+	)
+	.unwrap();
+	assert!(page.total >= 2);
+	assert_eq!(page.own, None);
+}
@@ -0,0 +1,231 @@
+//! Integration tests for time-limited events and their shops.
+//!
+//! These tests cover:
+//! - Earning the event currency from the objectives, up to their maximum completions
+//! - Buying offers of the event's shop with the currency
+//! - Closing an ended event, converting the leftover currency into capped resources
+//! - Counting the events published on the event bus
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::prelude::*;
+use empire::db::{DbConn, items, player_event_progress, player_items, resources};
+use empire::domain::events::GameEvent;
+use empire::domain::factions::FactionCode;
+use empire::domain::jobs::JobType;
+use empire::domain::limited_event::EventObjectiveKind;
+use empire::domain::player::PlayerKey;
+use empire::domain::player::resource::ResourceType;
+use empire::game::admin_operations::AdminActor;
+use empire::game::limited_events::event_listener;
+use empire::game::limited_events::event_operations::{
+	CloseOutcome, EventDetails, EventRequest, ObjectiveRequest, OfferRequest, close_event,
+	create_event, purchase_offer, record_progress,
+};
+use empire::schema::job;
+use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::common::TestHarness;
+
+fn operator() -> AdminActor {
+	AdminActor {
+		operator: Some("events-team".to_string()),
+		request_id: None,
+	}
+}
+
+/// Creates a running event rewarding trained units, with an axe in its shop.
+fn harvest_festival(
+	harness: &TestHarness,
+	conn: &mut DbConn,
+	ends_at: DateTime<Utc>,
+) -> EventDetails {
+	let axe = items::get_by_name(conn, "Lumberjack's Axe").expect("Items are seeded");
+	create_event(
+		conn,
+		&harness.app.job_queue,
+		EventRequest {
+			name: "Harvest Festival".to_string(),
+			description: "Train your troops for the harvest".to_string(),
+			currency_name: "Acorns".to_string(),
+			starts_at: Utc::now() - TimeDelta::minutes(1),
+			ends_at,
+			leftover_resource: ResourceType::Gold,
+			leftover_rate: 2,
+			objectives: vec![ObjectiveRequest {
+				kind: EventObjectiveKind::TrainingCompleted,
+				description: "Train 10 units".to_string(),
+				target: 10,
+				reward: 5,
+				max_completions: Some(2),
+			}],
+			offers: vec![OfferRequest {
+				price: 8,
+				item_id: Some(axe.id),
+				unit_id: None,
+				quantity: 1,
+			}],
+		},
+		operator(),
+	)
+	.expect("Failed to create event")
+}
+
+/// Sets a player's gold and gold storage cap.
+fn set_gold(conn: &mut DbConn, player_id: &PlayerKey, amount: i64, cap: i64) {
+	use empire::schema::player_resource::dsl as pr;
+	diesel::update(pr::player_resource.filter(pr::player_id.eq(player_id)))
+		.set((pr::gold.eq(amount), pr::gold_cap.eq(cap)))
+		.execute(conn)
+		.expect("Failed to set player gold");
+}
+
+fn trained(player_id: PlayerKey, quantity: i64) -> GameEvent {
+	GameEvent::TrainingCompleted {
+		player_id,
+		training_id: Uuid::new_v4(),
+		unit_id: Uuid::new_v4(),
+		quantity,
+	}
+}
+
+#[tokio::test]
+async fn test_objectives_earn_currency_spent_in_the_shop() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("harvester", Some(FactionCode::Human));
+	let festival = harvest_festival(&harness, &mut conn, Utc::now() + TimeDelta::days(3));
+	let event_id = festival.event.id;
+
+	// The event closes itself once it ends
+	let close_runs_at: Vec<DateTime<Utc>> = job::table
+		.filter(job::job_type.eq(JobType::LimitedEvent))
+		.select(job::run_at)
+		.load(&mut conn)
+		.unwrap();
+	assert_eq!(close_runs_at, vec![festival.event.ends_at]);
+
+	// 25 units complete the objective twice, the rest counts towards its limit
+	let earned = record_progress(&mut conn, &trained(player.id, 25), Utc::now()).unwrap();
+	assert_eq!(earned, 2 * 5);
+	let earned = record_progress(&mut conn, &trained(player.id, 10), Utc::now()).unwrap();
+	assert_eq!(earned, 0, "Objective was completed its maximum times");
+	let collected = GameEvent::ResourcesCollected {
+		player_id: player.id,
+		food: 0,
+		wood: 0,
+		stone: 0,
+		gold: 0,
+	};
+	assert_eq!(
+		record_progress(&mut conn, &collected, Utc::now()).unwrap(),
+		0
+	);
+	assert_eq!(
+		player_event_progress::get_balance(&mut conn, &player.id, &event_id).unwrap(),
+		10
+	);
+
+	let offer = &festival.offers[0];
+	let purchase = purchase_offer(&mut conn, &player.id, &event_id, &offer.id).unwrap();
+	assert_eq!(purchase.balance, 2);
+	let inventory = player_items::get_for_player(&mut conn, &player.id).unwrap();
+	assert_eq!(inventory.len(), 1);
+	assert_eq!(inventory[0].1.id, offer.item_id.unwrap());
+
+	let err = purchase_offer(&mut conn, &player.id, &event_id, &offer.id).unwrap_err();
+	assert!(err.to_string().contains("Not enough event currency"));
+	assert_eq!(
+		player_event_progress::get_balance(&mut conn, &player.id, &event_id).unwrap(),
+		2,
+		"A failed purchase spends nothing"
+	);
+}
+
+#[tokio::test]
+async fn test_closing_an_event_converts_the_leftover_currency() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let thrifty = harness.create_named_user("thrifty", Some(FactionCode::Human));
+	let hoarder = harness.create_named_user("hoarder", Some(FactionCode::Orc));
+	let ends_at = Utc::now() + TimeDelta::hours(1);
+	let festival = harvest_festival(&harness, &mut conn, ends_at);
+	let event_id = festival.event.id;
+
+	assert!(matches!(
+		close_event(&mut conn, &event_id, Utc::now()).unwrap(),
+		CloseOutcome::NotEnded(_)
+	));
+
+	set_gold(&mut conn, &thrifty.id, 100, 1_000);
+	set_gold(&mut conn, &hoarder.id, 990, 1_000);
+	player_event_progress::add_currency(&mut conn, &thrifty.id, &event_id, 3).unwrap();
+	// More than fits into the hoarder's storage
+	player_event_progress::add_currency(&mut conn, &hoarder.id, &event_id, 30).unwrap();
+
+	let CloseOutcome::Closed {
+		event,
+		players,
+		converted,
+	} = close_event(&mut conn, &event_id, ends_at).unwrap()
+	else {
+		panic!("Event should have been closed");
+	};
+	assert!(event.closed_at.is_some());
+	assert_eq!(players, 2);
+	assert_eq!(converted, 3 * 2 + 10);
+	let gold =
+		|conn: &mut DbConn, player_id| resources::get_by_player_id(conn, player_id).unwrap().gold;
+	assert_eq!(gold(&mut conn, &thrifty.id), 106);
+	assert_eq!(gold(&mut conn, &hoarder.id), 1_000, "The rest is lost");
+	for player_id in [thrifty.id, hoarder.id] {
+		assert_eq!(
+			player_event_progress::get_balance(&mut conn, &player_id, &event_id).unwrap(),
+			0
+		);
+	}
+
+	// Closing again converts nothing, and the shop is closed
+	assert_eq!(
+		close_event(&mut conn, &event_id, ends_at).unwrap(),
+		CloseOutcome::AlreadyClosed
+	);
+	let err =
+		purchase_offer(&mut conn, &thrifty.id, &event_id, &festival.offers[0].id).unwrap_err();
+	assert!(err.to_string().contains("Event is not running"));
+	assert_eq!(
+		record_progress(&mut conn, &trained(thrifty.id, 10), Utc::now()).unwrap(),
+		0
+	);
+}
+
+#[tokio::test]
+async fn test_listener_counts_published_events() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("drillmaster", Some(FactionCode::Elf));
+	let festival = harvest_festival(&harness, &mut conn, Utc::now() + TimeDelta::days(1));
+
+	let token = CancellationToken::new();
+	let listener = tokio::spawn(event_listener::listen(
+		Arc::clone(&harness.app.db_pool),
+		harness.app.events.subscribe(),
+		token.clone(),
+	));
+	harness.app.events.publish(trained(player.id, 10));
+
+	let deadline = Utc::now() + TimeDelta::seconds(5);
+	let mut balance = 0;
+	while balance == 0 && Utc::now() < deadline {
+		sleep(Duration::from_millis(50)).await;
+		balance =
+			player_event_progress::get_balance(&mut conn, &player.id, &festival.event.id).unwrap();
+	}
+	token.cancel();
+	listener.await.unwrap();
+	assert_eq!(balance, 5);
+}
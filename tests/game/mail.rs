@@ -0,0 +1,93 @@
+//! Integration tests for in-game mail.
+//!
+//! These tests cover:
+//! - Sending messages between players, and refusing invalid ones
+//! - Pagination of the inbox and the unread count
+//! - Marking messages as read and deleting them, only in the player's own inbox
+
+use empire::domain::message::MessageKind;
+use empire::game::mail::mail_operations::{
+	delete_message, get_message, list_inbox, mark_all_read, mark_read, send, unread_count,
+};
+use serde_json::json;
+
+use crate::common::TestHarness;
+
+#[tokio::test]
+async fn test_messages_are_sent_between_players() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let sender = harness.create_named_user("sender", None).id;
+	let recipient = harness.create_named_user("recipient", None).id;
+
+	let message = send(&mut conn, &sender, &recipient, " Truce? ", "Let us talk.").unwrap();
+	assert_eq!(message.subject, "Truce?");
+	assert_eq!(message.sender_id, Some(sender));
+	assert_eq!(message.kind, MessageKind::Player);
+	assert_eq!(message.details, json!({}));
+	assert!(!message.is_read());
+
+	// Only the recipient has it in their inbox
+	assert_eq!(unread_count(&mut conn, &recipient).unwrap(), 1);
+	assert_eq!(unread_count(&mut conn, &sender).unwrap(), 0);
+	let err = get_message(&mut conn, &sender, &message.id).unwrap_err();
+	assert!(err.to_string().contains("Message not found"));
+
+	let err = send(&mut conn, &sender, &recipient, "  ", "Body").unwrap_err();
+	assert!(err.to_string().contains("Subjects"));
+	let err = send(&mut conn, &sender, &recipient, "Subject", &"a".repeat(5001)).unwrap_err();
+	assert!(err.to_string().contains("Messages are"));
+	let err = send(&mut conn, &sender, &sender, "Note", "To self").unwrap_err();
+	assert!(err.to_string().contains("yourself"));
+	let err = send(&mut conn, &sender, &uuid::Uuid::new_v4(), "Hi", "Anyone?").unwrap_err();
+	assert!(err.to_string().contains("Player not found"));
+}
+
+#[tokio::test]
+async fn test_inbox_is_paged_marked_read_and_cleaned_up() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let sender = harness.create_named_user("sender", None).id;
+	let recipient = harness.create_named_user("recipient", None).id;
+	let ids: Vec<_> = (1..=5)
+		.map(|i| {
+			send(
+				&mut conn,
+				&sender,
+				&recipient,
+				&format!("Letter {i}"),
+				"Body",
+			)
+			.unwrap()
+			.id
+		})
+		.collect();
+
+	let page = list_inbox(&mut conn, &recipient, false, Some(1), Some(2)).unwrap();
+	assert_eq!((page.total, page.unread), (5, 5));
+	assert_eq!(page.messages[0].subject, "Letter 5", "Newest first");
+	let page = list_inbox(&mut conn, &recipient, false, Some(3), Some(2)).unwrap();
+	assert_eq!(page.messages.len(), 1);
+	assert_eq!(page.messages[0].id, ids[0]);
+
+	// Reading keeps the time it was first read
+	let read = mark_read(&mut conn, &recipient, &ids[0]).unwrap();
+	let read_again = mark_read(&mut conn, &recipient, &ids[0]).unwrap();
+	assert!(read.is_read());
+	assert_eq!(read.read_at, read_again.read_at);
+	assert!(mark_read(&mut conn, &sender, &ids[1]).is_err());
+	let page = list_inbox(&mut conn, &recipient, true, None, None).unwrap();
+	assert_eq!((page.total, page.unread), (4, 4));
+	assert!(page.messages.iter().all(|message| message.id != ids[0]));
+
+	assert_eq!(mark_all_read(&mut conn, &recipient).unwrap(), 4);
+	assert_eq!(unread_count(&mut conn, &recipient).unwrap(), 0);
+
+	// Players delete their own messages only
+	assert!(delete_message(&mut conn, &sender, &ids[1]).is_err());
+	delete_message(&mut conn, &recipient, &ids[1]).unwrap();
+	let err = delete_message(&mut conn, &recipient, &ids[1]).unwrap_err();
+	assert!(err.to_string().contains("Message not found"));
+	let page = list_inbox(&mut conn, &recipient, false, None, None).unwrap();
+	assert_eq!(page.total, 4);
+}
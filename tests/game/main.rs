@@ -1,8 +1,50 @@
+mod account_deletion;
+mod alliances;
+mod army_presets;
+mod backfills;
+mod battle_reports;
+mod beginner_protection;
+mod building_cancellation;
+mod building_modifiers;
+mod caravans;
+mod chat;
+mod construction_queue;
+mod dead_letter;
+mod espionage;
+mod faction_changes;
 mod faction_modifiers;
+mod faction_standings;
+mod fixtures;
+mod friends;
+mod heroes;
+mod items;
+mod job_cancellation;
+mod job_dispatch;
 mod job_processor;
+mod leaderboard;
+mod limited_events;
+mod mail;
+mod market;
+mod modifier_debuffs;
+mod modifier_expiration;
 mod modifier_scheduler;
+mod onboarding;
+mod planned_actions;
+mod player_activity;
+mod quests;
+mod recurring_jobs;
 mod resource_service;
+mod seasons;
+mod settlements;
+#[cfg(feature = "simulation")]
+mod simulation;
+mod table_stats;
 mod training_operations;
+mod unit_disbanding;
+mod unit_upgrades;
+mod unit_upkeep;
+mod upgrade_confirmation;
+mod world_reset;
 
 #[path = "../common/mod.rs"]
 mod common;
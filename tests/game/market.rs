@@ -0,0 +1,193 @@
+//! Integration tests for the player market.
+//!
+//! These tests cover:
+//! - Escrow of resources and gold when orders are placed
+//! - Matching with price-time priority and settlement between players
+//! - Cancelling orders and refunding their unfilled remainder
+
+use empire::db::{DbConn, market_orders, market_trades, resources};
+use empire::domain::market::{MarketOrderSide, MarketOrderStatus};
+use empire::domain::player::PlayerKey;
+use empire::domain::player::resource::ResourceType;
+use empire::game::market::market_operations::{cancel_order, get_order_book, place_order};
+
+use crate::common::TestHarness;
+use crate::fixtures::set_player_resources;
+
+/// Returns the player's (wood, gold).
+fn wood_and_gold(conn: &mut DbConn, player_id: &PlayerKey) -> (i64, i64) {
+	let res = resources::get_by_player_id(conn, player_id).unwrap();
+	(res.wood, res.gold)
+}
+
+#[tokio::test]
+async fn test_orders_are_matched_and_settled() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let cheap_seller = harness.create_named_user("cheap_seller", None).id;
+	let seller = harness.create_named_user("seller", None).id;
+	let buyer = harness.create_named_user("buyer", None).id;
+	for player in [&cheap_seller, &seller, &buyer] {
+		set_player_resources(&mut conn, player, 1000);
+	}
+
+	let (pricey, _) = place_order(
+		&mut conn,
+		&seller,
+		MarketOrderSide::Sell,
+		ResourceType::Wood,
+		100,
+		5,
+	)
+	.unwrap();
+	let (cheap, _) = place_order(
+		&mut conn,
+		&cheap_seller,
+		MarketOrderSide::Sell,
+		ResourceType::Wood,
+		50,
+		3,
+	)
+	.unwrap();
+	assert_eq!(wood_and_gold(&mut conn, &seller), (900, 1000));
+
+	// Crosses the cheaper ask first, then part of the pricier one
+	let (bid, trades) = place_order(
+		&mut conn,
+		&buyer,
+		MarketOrderSide::Buy,
+		ResourceType::Wood,
+		80,
+		6,
+	)
+	.unwrap();
+	assert_eq!(bid.status, MarketOrderStatus::Filled);
+	assert_eq!(bid.remaining, 0);
+	let fills: Vec<(i64, i64)> = trades.iter().map(|t| (t.quantity, t.price)).collect();
+	assert_eq!(fills, vec![(50, 3), (30, 5)]);
+
+	// Buyer paid the resting prices, the difference to the limit was refunded
+	assert_eq!(
+		wood_and_gold(&mut conn, &buyer),
+		(1080, 1000 - 50 * 3 - 30 * 5)
+	);
+	assert_eq!(wood_and_gold(&mut conn, &cheap_seller), (950, 1000 + 150));
+	assert_eq!(wood_and_gold(&mut conn, &seller), (900, 1000 + 150));
+
+	let cheap = market_orders::get_by_id(&mut conn, &cheap.id).unwrap();
+	assert_eq!(cheap.status, MarketOrderStatus::Filled);
+	let pricey = market_orders::get_by_id(&mut conn, &pricey.id).unwrap();
+	assert_eq!(pricey.status, MarketOrderStatus::Open);
+	assert_eq!(pricey.remaining, 70);
+	assert_eq!(
+		market_trades::get_for_order(&mut conn, &bid.id)
+			.unwrap()
+			.len(),
+		2
+	);
+
+	let book = get_order_book(&mut conn, ResourceType::Wood).unwrap();
+	assert!(book.bids.is_empty());
+	assert_eq!(book.asks.len(), 1);
+	assert_eq!((book.asks[0].price, book.asks[0].quantity), (5, 70));
+}
+
+#[tokio::test]
+async fn test_cancelling_refunds_the_remainder() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let buyer = harness.create_named_user("buyer", None).id;
+	let seller = harness.create_named_user("seller", None).id;
+	set_player_resources(&mut conn, &buyer, 1000);
+	set_player_resources(&mut conn, &seller, 1000);
+
+	let (bid, _) = place_order(
+		&mut conn,
+		&buyer,
+		MarketOrderSide::Buy,
+		ResourceType::Wood,
+		100,
+		4,
+	)
+	.unwrap();
+	assert_eq!(wood_and_gold(&mut conn, &buyer), (1000, 600));
+
+	// Own orders are never matched
+	let (own_ask, trades) = place_order(
+		&mut conn,
+		&buyer,
+		MarketOrderSide::Sell,
+		ResourceType::Wood,
+		10,
+		4,
+	)
+	.unwrap();
+	assert!(trades.is_empty());
+	cancel_order(&mut conn, &buyer, &own_ask.id).unwrap();
+
+	let (_, trades) = place_order(
+		&mut conn,
+		&seller,
+		MarketOrderSide::Sell,
+		ResourceType::Wood,
+		40,
+		2,
+	)
+	.unwrap();
+	assert_eq!(trades.len(), 1);
+	assert_eq!(trades[0].price, 4, "Trades run at the resting price");
+
+	let err = cancel_order(&mut conn, &seller, &bid.id).unwrap_err();
+	assert!(err.to_string().contains("Order not found"));
+
+	let cancelled = cancel_order(&mut conn, &buyer, &bid.id).unwrap();
+	assert_eq!(cancelled.status, MarketOrderStatus::Cancelled);
+	assert_eq!(cancelled.remaining, 60);
+	assert_eq!(wood_and_gold(&mut conn, &buyer), (1040, 1000 - 40 * 4));
+	assert_eq!(wood_and_gold(&mut conn, &seller), (960, 1000 + 40 * 4));
+
+	let err = cancel_order(&mut conn, &buyer, &bid.id).unwrap_err();
+	assert!(err.to_string().contains("no longer open"));
+}
+
+#[tokio::test]
+async fn test_invalid_orders_are_rejected() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_test_user(None).id;
+	set_player_resources(&mut conn, &player, 100);
+
+	let place = |conn: &mut DbConn, side, resource, quantity, price| {
+		place_order(conn, &player, side, resource, quantity, price)
+			.unwrap_err()
+			.to_string()
+	};
+	assert_eq!(
+		place(&mut conn, MarketOrderSide::Sell, ResourceType::Gold, 10, 1),
+		"Only food, wood and stone can be traded for gold"
+	);
+	assert_eq!(
+		place(&mut conn, MarketOrderSide::Sell, ResourceType::Food, 0, 1),
+		"Quantity and price must be positive"
+	);
+	assert_eq!(
+		place(&mut conn, MarketOrderSide::Buy, ResourceType::Food, 51, 2),
+		"Not enough resources"
+	);
+	assert_eq!(
+		place(
+			&mut conn,
+			MarketOrderSide::Sell,
+			ResourceType::Stone,
+			101,
+			1
+		),
+		"Not enough resources"
+	);
+	assert_eq!(
+		resources::get_by_player_id(&mut conn, &player)
+			.unwrap()
+			.gold,
+		100
+	);
+}
@@ -0,0 +1,163 @@
+//! Integration tests for debuffs, modifiers that lower a player's multipliers.
+//!
+//! These tests cover:
+//! - Negative percentages and multipliers below 1.0 stacking with bonuses
+//! - Clamping stacked debuffs to the floor of their target
+
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+use empire::db::{DbConn, active_modifiers, modifiers, settlements};
+use empire::domain::factions::FactionCode;
+use empire::domain::modifier::active_modifier::{ModifierSourceType, NewActiveModifier};
+use empire::domain::modifier::{
+	MagnitudeKind, ModifierKey, ModifierTarget, NewModifier, StackingBehaviour,
+};
+use empire::domain::player::PlayerKey;
+use empire::domain::player::resource::ResourceType;
+use empire::game::modifiers::modifier_operations::{get_applied_mods, player_multiplier};
+use empire::game::resources::resource_operations::calc_prod_rates;
+use uuid::Uuid;
+
+use crate::common::TestHarness;
+
+/// Creates an additive debuff, or a multiplicative one for multipliers.
+fn create_debuff(
+	conn: &mut DbConn,
+	magnitude: &str,
+	magnitude_kind: MagnitudeKind,
+	target_type: ModifierTarget,
+	target_resource: Option<ResourceType>,
+) -> ModifierKey {
+	let stacking_behaviour = match magnitude_kind {
+		MagnitudeKind::Multiplier => StackingBehaviour::Multiplicative,
+		_ => StackingBehaviour::Additive,
+	};
+	modifiers::create(
+		conn,
+		NewModifier {
+			name: format!("test_debuff_{}", Uuid::new_v4()),
+			description: "Test debuff".to_string(),
+			magnitude: BigDecimal::from_str(magnitude).unwrap(),
+			magnitude_kind,
+			target_type,
+			target_resource,
+			stacking_behaviour: Some(stacking_behaviour),
+			stacking_group: None,
+		},
+	)
+	.expect("Failed to create debuff")
+	.id
+}
+
+fn activate(conn: &mut DbConn, player_id: &PlayerKey, modifier_id: ModifierKey) {
+	active_modifiers::create(
+		conn,
+		NewActiveModifier {
+			player_id: *player_id,
+			modifier_id,
+			started_at: None,
+			expires_at: None,
+			source_type: ModifierSourceType::Event,
+			source_id: None,
+			player_building_id: None,
+		},
+	)
+	.expect("Failed to activate debuff");
+}
+
+fn multiplier(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	target_type: ModifierTarget,
+	target_resource: Option<ResourceType>,
+) -> BigDecimal {
+	let mods = get_applied_mods(conn, player_id).unwrap();
+	player_multiplier(&mods, target_type, target_resource)
+}
+
+fn decimal(value: &str) -> BigDecimal {
+	BigDecimal::from_str(value).unwrap()
+}
+
+#[tokio::test]
+async fn test_debuffs_stack_with_bonuses() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("wounded", Some(FactionCode::Human));
+	let wood = Some(ResourceType::Wood);
+	assert_eq!(
+		multiplier(&mut conn, &player.id, ModifierTarget::Resource, wood),
+		decimal("1.15")
+	);
+
+	// -20% adds up with the Human +15%
+	let wounded = create_debuff(
+		&mut conn,
+		"-0.20",
+		MagnitudeKind::Percentage,
+		ModifierTarget::Resource,
+		wood,
+	);
+	activate(&mut conn, &player.id, wounded);
+	assert_eq!(
+		multiplier(&mut conn, &player.id, ModifierTarget::Resource, wood),
+		decimal("0.95")
+	);
+
+	// A multiplier below 1.0 scales the rest
+	let halved = create_debuff(
+		&mut conn,
+		"0.5",
+		MagnitudeKind::Multiplier,
+		ModifierTarget::Resource,
+		wood,
+	);
+	activate(&mut conn, &player.id, halved);
+	assert_eq!(
+		multiplier(&mut conn, &player.id, ModifierTarget::Resource, wood),
+		decimal("0.475")
+	);
+}
+
+#[tokio::test]
+async fn test_debuffs_are_clamped_to_the_floor_of_their_target() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("besieged", Some(FactionCode::Orc));
+	let capital = settlements::get_capital(&mut conn, &player.id).unwrap();
+	let wood = Some(ResourceType::Wood);
+	let base_wood = calc_prod_rates(&mut conn, &capital).unwrap()[&ResourceType::Wood].clone();
+	assert!(base_wood > 0, "Starter buildings produce wood");
+
+	for magnitude in ["-1", "-0.5"] {
+		let debuff = create_debuff(
+			&mut conn,
+			magnitude,
+			MagnitudeKind::Percentage,
+			ModifierTarget::Resource,
+			wood,
+		);
+		activate(&mut conn, &player.id, debuff);
+	}
+	let combat = create_debuff(
+		&mut conn,
+		"-0.9",
+		MagnitudeKind::Percentage,
+		ModifierTarget::Combat,
+		None,
+	);
+	activate(&mut conn, &player.id, combat);
+
+	// Production never drops below 10% of its base, other targets below 50%
+	assert_eq!(
+		multiplier(&mut conn, &player.id, ModifierTarget::Resource, wood),
+		decimal("0.1")
+	);
+	let debuffed_wood = calc_prod_rates(&mut conn, &capital).unwrap()[&ResourceType::Wood].clone();
+	assert_eq!(debuffed_wood, base_wood * decimal("0.1"));
+	assert_eq!(
+		multiplier(&mut conn, &player.id, ModifierTarget::Combat, None),
+		decimal("0.5")
+	);
+}
@@ -0,0 +1,195 @@
+//! Integration tests for the expiration of temporary modifiers.
+//!
+//! These tests cover:
+//! - Deleting expired modifiers and recording their expiration in the history
+//! - Invalidating the cached multipliers of the affected players
+//! - Registering the recurring sweep and publishing an event per expired modifier
+
+use std::str::FromStr;
+
+use axum::extract::FromRef;
+use bigdecimal::BigDecimal;
+use chrono::{TimeDelta, Utc};
+use empire::db::{DbConn, active_modifiers, modifier_history, modifiers};
+use empire::domain::app_state::AppState;
+use empire::domain::events::GameEvent;
+use empire::domain::factions::FactionCode;
+use empire::domain::jobs::JobType;
+use empire::domain::modifier::active_modifier::{
+	ActiveModifier, ModifierSourceType, NewActiveModifier,
+};
+use empire::domain::modifier::modifier_history::ModifierActionType;
+use empire::domain::modifier::{
+	MagnitudeKind, ModifierKey, ModifierTarget, NewModifier, StackingBehaviour,
+};
+use empire::domain::player::PlayerKey;
+use empire::domain::player::resource::ResourceType;
+use empire::game::modifiers::modifier_cache::CacheKey;
+use empire::game::modifiers::modifier_processor::ModifierProcessor;
+use empire::game::modifiers::modifier_scheduler::{
+	EXPIRATION_TICK_NAME, ModifierJobPayload, register_expiration_tick,
+};
+use empire::game::modifiers::modifier_service::ModifierService;
+use empire::job_queue::JobPriority;
+use empire::job_queue::job_processor::JobProcessor;
+use uuid::Uuid;
+
+use crate::common::TestHarness;
+
+/// Creates an additive gold modifier with a unique name.
+fn create_modifier(conn: &mut DbConn) -> ModifierKey {
+	modifiers::create(
+		conn,
+		NewModifier {
+			name: format!("test_modifier_{}", Uuid::new_v4()),
+			description: "Test modifier".to_string(),
+			magnitude: BigDecimal::from_str("0.25").unwrap(),
+			magnitude_kind: MagnitudeKind::Percentage,
+			target_type: ModifierTarget::Resource,
+			target_resource: Some(ResourceType::Gold),
+			stacking_behaviour: Some(StackingBehaviour::Additive),
+			stacking_group: None,
+		},
+	)
+	.expect("Failed to create modifier")
+	.id
+}
+
+/// Activates a modifier for a player that expires `expires_in` from now.
+fn activate(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	modifier_id: ModifierKey,
+	expires_in: TimeDelta,
+) -> ActiveModifier {
+	let now = Utc::now();
+	active_modifiers::create(
+		conn,
+		NewActiveModifier {
+			player_id: *player_id,
+			modifier_id,
+			started_at: Some(now - TimeDelta::hours(2)),
+			expires_at: Some(now + expires_in),
+			source_type: ModifierSourceType::Item,
+			source_id: None,
+			player_building_id: None,
+		},
+	)
+	.expect("Failed to activate modifier")
+}
+
+#[tokio::test]
+async fn test_due_modifiers_are_expired() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let service = ModifierService::new(&harness.app.db_pool, &harness.app.modifier_system);
+	let cache = &harness.app.modifier_system.cache;
+	let player = harness.create_named_user("expiring", Some(FactionCode::Human));
+	let modifier_id = create_modifier(&mut conn);
+
+	let ran_out = activate(&mut conn, &player.id, modifier_id, TimeDelta::hours(-1));
+	let running = activate(&mut conn, &player.id, modifier_id, TimeDelta::hours(1));
+	let cache_key = CacheKey {
+		player_id: player.id,
+		target_type: ModifierTarget::Resource,
+		target_resource: Some(ResourceType::Gold),
+	};
+	cache
+		.set(
+			cache_key.clone(),
+			BigDecimal::from_str("1.5").unwrap(),
+			None,
+		)
+		.unwrap();
+
+	let expired = service
+		.expire_modifiers(Utc::now())
+		.await
+		.expect("Failed to expire modifiers");
+	assert_eq!(expired.len(), 1);
+	assert_eq!(expired[0].active.id, ran_out.id);
+	assert!(cache.get(&cache_key).is_none(), "Cache is invalidated");
+
+	// Faction modifiers never expire and are kept as well
+	let remaining: Vec<_> = active_modifiers::get_by_player_id(&mut conn, &player.id)
+		.unwrap()
+		.into_iter()
+		.filter(|active| active.modifier_id == modifier_id)
+		.collect();
+	assert_eq!(remaining.len(), 1);
+	assert_eq!(remaining[0].id, running.id);
+
+	let history = modifier_history::get_by_player_id(&mut conn, &player.id).unwrap();
+	let expirations: Vec<_> = history
+		.iter()
+		.filter(|entry| entry.action_type == ModifierActionType::Expired)
+		.collect();
+	assert_eq!(expirations.len(), 1);
+	assert_eq!(expirations[0].modifier_id, modifier_id);
+	assert_eq!(
+		expirations[0].magnitude,
+		BigDecimal::from_str("0.25").unwrap()
+	);
+	assert_eq!(
+		expirations[0].previous_state.as_ref().unwrap()["active_modifier_id"],
+		ran_out.id.to_string()
+	);
+
+	// A second sweep has nothing left to expire
+	let again = service.expire_modifiers(Utc::now()).await.unwrap();
+	assert!(again.is_empty());
+}
+
+#[tokio::test]
+async fn test_expiration_sweep_publishes_events() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("notified", Some(FactionCode::Human));
+	let modifier_id = create_modifier(&mut conn);
+	let ran_out = activate(&mut conn, &player.id, modifier_id, TimeDelta::minutes(-5));
+	let state = AppState(harness.app.clone());
+
+	register_expiration_tick(&state.job_queue).expect("Failed to register the sweep");
+	register_expiration_tick(&state.job_queue).expect("Registering is idempotent");
+	let sweeps: Vec<_> = state
+		.job_queue
+		.list_recurring()
+		.unwrap()
+		.into_iter()
+		.filter(|recurring| recurring.name == EXPIRATION_TICK_NAME)
+		.collect();
+	assert_eq!(sweeps.len(), 1);
+	assert_eq!(sweeps[0].job_type, JobType::Modifier);
+	let run_id = sweeps[0].next_job_id.expect("The first sweep is enqueued");
+	let run = state.job_queue.get_job(&run_id).unwrap();
+	assert!(matches!(
+		serde_json::from_value(run.payload.clone()),
+		Ok(ModifierJobPayload::ExpireDueModifiers)
+	));
+
+	// Run the sweep right away instead of waiting for the next minute
+	let job_id = state
+		.job_queue
+		.enqueue(
+			JobType::Modifier,
+			ModifierJobPayload::ExpireDueModifiers,
+			JobPriority::Normal,
+			Utc::now(),
+		)
+		.unwrap();
+	let job = state.job_queue.get_job(&job_id).unwrap();
+	let mut events = state.events.subscribe();
+	let processor = ModifierProcessor::from_ref(&state);
+	let outcome = processor.process_job(job).await.expect("Sweep failed");
+	assert_eq!(outcome.unwrap()["expired"], 1);
+
+	let event = events.try_recv().expect("An event is published");
+	assert_eq!(
+		event,
+		GameEvent::ModifierExpired {
+			player_id: player.id,
+			active_modifier_id: ran_out.id,
+			modifier_id,
+		}
+	);
+}
@@ -0,0 +1,104 @@
+//! Integration tests for the onboarding of new players.
+//!
+//! These tests cover:
+//! - Granting the configured welcome pack on top of the faction's starter kit
+//! - Starting the beginner shield as part of the same pipeline
+//! - Skipping welcome pack items the game does not know
+
+use empire::auth::utils::hash_password;
+use empire::configuration::{OnboardingSettings, ProtectionSettings, WelcomeItem, WelcomePack};
+use empire::db::{player_buildings, player_items, resources};
+use empire::domain::factions::FactionCode;
+use empire::domain::player::{NewPlayer, UserName};
+use empire::game::onboarding_operations::create_player;
+
+use crate::common::TestHarness;
+
+fn new_player(name: &str) -> NewPlayer {
+	NewPlayer {
+		name: UserName::parse(name.to_string()).unwrap(),
+		pwd_hash: hash_password(b"1234").unwrap(),
+		email: None,
+		faction: FactionCode::Human,
+	}
+}
+
+fn welcome_item(name: &str, quantity: i64) -> WelcomeItem {
+	WelcomeItem {
+		name: name.to_string(),
+		quantity,
+	}
+}
+
+#[tokio::test]
+async fn test_new_players_get_the_welcome_pack_and_a_shield() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let veteran = harness.create_named_user("veteran", Some(FactionCode::Human));
+	let starter_gold = resources::get_by_player_id(&mut conn, &veteran.id)
+		.unwrap()
+		.gold;
+
+	let onboarding = OnboardingSettings {
+		welcome_pack: WelcomePack {
+			gold: 250,
+			items: vec![
+				welcome_item("Harvest Festival", 2),
+				welcome_item("Philosopher's Stone", 1),
+			],
+			..WelcomePack::default()
+		},
+	};
+	let onboarded = create_player(
+		&mut conn,
+		&ProtectionSettings::default(),
+		&onboarding,
+		new_player("newcomer"),
+	)
+	.unwrap();
+	let player_id = onboarded.player.id;
+
+	// The starter kit comes with the player, the welcome pack on top of it
+	assert!(
+		!player_buildings::get_player_buildings(&mut conn, &player_id)
+			.unwrap()
+			.is_empty()
+	);
+	let gold = resources::get_by_player_id(&mut conn, &player_id)
+		.unwrap()
+		.gold;
+	assert_eq!(gold, starter_gold + 250);
+	assert_eq!(onboarded.items, vec![("Harvest Festival".to_string(), 2)]);
+	let inventory = player_items::get_for_player(&mut conn, &player_id).unwrap();
+	assert_eq!(inventory.len(), 1);
+	assert_eq!(inventory[0].0.quantity, 2);
+
+	assert!(onboarded.protected_until.is_some());
+	assert_eq!(onboarded.player.protected_until, onboarded.protected_until);
+}
+
+#[tokio::test]
+async fn test_onboarding_follows_the_settings() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let protection = ProtectionSettings {
+		beginner_shield_days: 0,
+		beginner_shield_max_points: None,
+		beginner_shield_max_keep_level: None,
+	};
+
+	let onboarded = create_player(
+		&mut conn,
+		&protection,
+		&OnboardingSettings::default(),
+		new_player("unshielded"),
+	)
+	.unwrap();
+	assert!(onboarded.items.is_empty());
+	assert!(onboarded.protected_until.is_none());
+	assert!(
+		player_items::get_for_player(&mut conn, &onboarded.player.id)
+			.unwrap()
+			.is_empty()
+	);
+}
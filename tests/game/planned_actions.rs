@@ -0,0 +1,207 @@
+//! Integration tests for the planned action queue.
+//!
+//! These tests cover:
+//! - Plan creation, validation and the per-player plan limit
+//! - Evaluator scheduling when the first plan is queued
+//! - Executing plans once the player can afford them
+
+use diesel::prelude::*;
+use empire::db::{DbConn, planned_actions, player_buildings};
+use empire::domain::factions::FactionCode;
+use empire::domain::jobs::{Job, JobType};
+use empire::domain::player::PlayerKey;
+use empire::domain::player::buildings::PlayerBuilding;
+use empire::domain::player::planned_action::{PlannedActionKind, PlannedActionStatus};
+use empire::game::buildings::plan_operations::{
+	MAX_PLANNED_ACTIONS, cancel_plan, create_plan, evaluate_plans,
+};
+use empire::schema::{building, job};
+
+use crate::common::TestHarness;
+use crate::fixtures::{create_test_player, set_player_resources};
+
+/// Get the ID of a building type by name for a specific faction.
+fn get_building_id(conn: &mut DbConn, name: &str, faction: FactionCode) -> i32 {
+	building::table
+		.filter(building::name.eq(name))
+		.filter(building::faction.eq(faction))
+		.select(building::id)
+		.first(conn)
+		.unwrap_or_else(|_| panic!("Building '{}' not found for faction {:?}", name, faction))
+}
+
+/// Count how many buildings of a type the player owns.
+fn count_buildings(conn: &mut DbConn, player_id: &PlayerKey, bld_id: i32) -> usize {
+	player_buildings::get_player_buildings(conn, player_id)
+		.expect("Failed to get player buildings")
+		.into_iter()
+		.filter(|bld| bld.building_id == bld_id)
+		.count()
+}
+
+/// Get the player's starter Keep.
+fn get_keep(conn: &mut DbConn, player_id: &PlayerKey) -> PlayerBuilding {
+	let keep_id = get_building_id(conn, "Keep", FactionCode::Human);
+	player_buildings::get_player_buildings(conn, player_id)
+		.expect("Failed to get player buildings")
+		.into_iter()
+		.find(|bld| bld.building_id == keep_id)
+		.expect("Player has no Keep")
+}
+
+#[tokio::test]
+async fn test_plan_waits_until_affordable() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	let player = create_test_player(&mut conn, FactionCode::Human);
+	let farm_id = get_building_id(&mut conn, "Farm", FactionCode::Human);
+	let farms_before = count_buildings(&mut conn, &player.id, farm_id);
+	set_player_resources(&mut conn, &player.id, 0);
+
+	let plan = create_plan(
+		&mut conn,
+		&app.job_queue,
+		&player.id,
+		PlannedActionKind::Construct,
+		Some(farm_id),
+		None,
+		None,
+	)
+	.expect("Failed to create plan");
+	assert_eq!(plan.status, PlannedActionStatus::Pending);
+
+	// The first plan schedules an evaluation job
+	let jobs: Vec<Job> = job::table
+		.filter(job::job_type.eq(JobType::Building))
+		.select(Job::as_select())
+		.load(&mut conn)
+		.expect("Failed to load jobs");
+	assert_eq!(jobs.len(), 1, "Expected exactly one evaluation job");
+
+	// Not affordable yet: the plan stays pending with the reason recorded
+	let evaluation =
+		evaluate_plans(&mut conn, &app.job_queue, &player.id).expect("Failed to evaluate");
+	assert_eq!(evaluation.executed, 0);
+	assert_eq!(evaluation.remaining, 1);
+	let plan = planned_actions::get_by_id(&mut conn, &plan.id).unwrap();
+	assert_eq!(plan.status, PlannedActionStatus::Pending);
+	assert!(
+		plan.last_error.is_some(),
+		"Failure reason should be recorded"
+	);
+
+	// Once affordable, the evaluator executes the construction
+	set_player_resources(&mut conn, &player.id, 100_000);
+	let evaluation =
+		evaluate_plans(&mut conn, &app.job_queue, &player.id).expect("Failed to evaluate");
+	assert_eq!(evaluation.executed, 1);
+	assert_eq!(evaluation.remaining, 0);
+
+	let plan = planned_actions::get_by_id(&mut conn, &plan.id).unwrap();
+	assert_eq!(plan.status, PlannedActionStatus::Completed);
+	assert!(plan.executed_at.is_some());
+	assert_eq!(
+		count_buildings(&mut conn, &player.id, farm_id),
+		farms_before + 1,
+		"A new farm should have been constructed"
+	);
+}
+
+#[tokio::test]
+async fn test_plan_limit() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	let player = create_test_player(&mut conn, FactionCode::Human);
+	let keep = get_keep(&mut conn, &player.id);
+
+	for _ in 0..MAX_PLANNED_ACTIONS {
+		create_plan(
+			&mut conn,
+			&app.job_queue,
+			&player.id,
+			PlannedActionKind::Upgrade,
+			None,
+			Some(keep.id),
+			None,
+		)
+		.expect("Failed to create plan");
+	}
+
+	let result = create_plan(
+		&mut conn,
+		&app.job_queue,
+		&player.id,
+		PlannedActionKind::Upgrade,
+		None,
+		Some(keep.id),
+		None,
+	);
+	assert!(result.is_err(), "Should fail once the limit is reached");
+	assert!(result.unwrap_err().to_string().contains("limit"));
+
+	// Cancelling frees up a slot
+	let plans = planned_actions::get_pending_for_player(&mut conn, &player.id).unwrap();
+	cancel_plan(&mut conn, &player.id, &plans[0].id).expect("Failed to cancel plan");
+	create_plan(
+		&mut conn,
+		&app.job_queue,
+		&player.id,
+		PlannedActionKind::Upgrade,
+		None,
+		Some(keep.id),
+		None,
+	)
+	.expect("Cancelled plans should not count towards the limit");
+}
+
+#[tokio::test]
+async fn test_plan_rejects_foreign_targets() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	let player = create_test_player(&mut conn, FactionCode::Human);
+	let other = create_test_player(&mut conn, FactionCode::Human);
+	let other_keep = get_keep(&mut conn, &other.id);
+
+	let result = create_plan(
+		&mut conn,
+		&app.job_queue,
+		&player.id,
+		PlannedActionKind::Upgrade,
+		None,
+		Some(other_keep.id),
+		None,
+	);
+	assert!(
+		result.is_err(),
+		"Should not plan upgrades of foreign buildings"
+	);
+
+	// Orc buildings are not available to humans
+	let stronghold_id = get_building_id(&mut conn, "Stronghold", FactionCode::Orc);
+	let result = create_plan(
+		&mut conn,
+		&app.job_queue,
+		&player.id,
+		PlannedActionKind::Construct,
+		Some(stronghold_id),
+		None,
+		None,
+	);
+	assert!(result.is_err(), "Should not plan other factions' buildings");
+
+	// Other players cannot cancel someone else's plan
+	let plan = create_plan(
+		&mut conn,
+		&app.job_queue,
+		&other.id,
+		PlannedActionKind::Upgrade,
+		None,
+		Some(other_keep.id),
+		None,
+	)
+	.unwrap();
+	assert!(cancel_plan(&mut conn, &player.id, &plan.id).is_err());
+}
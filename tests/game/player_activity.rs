@@ -0,0 +1,127 @@
+//! Integration tests for player activity statistics.
+//!
+//! These tests cover:
+//! - Recording started and confirmed upgrades in the building upgrade ledger
+//! - Aggregating upgrades, battles and spending per day
+//! - Serving repeated requests from the activity cache
+
+use chrono::{TimeDelta, Utc};
+use diesel::prelude::*;
+use empire::db::{DbConn, building_upgrades, player_buildings};
+use empire::domain::combat::Loot;
+use empire::domain::factions::FactionCode;
+use empire::domain::player::PlayerKey;
+use empire::domain::player::buildings::PlayerBuilding;
+use empire::game::activity_operations::{ActivityCache, ActivityWindow, get_player_activity};
+use empire::game::buildings::building_operations::{confirm_upgrade, upgrade_building};
+use empire::game::combat::combat_operations::{BattleOutcome, record_battle};
+use empire::schema::{building, player_building};
+
+use crate::common::TestHarness;
+use crate::fixtures::give_player_resources;
+
+/// Get one of the player's starter Farms.
+fn get_farm(conn: &mut DbConn, player_id: &PlayerKey) -> PlayerBuilding {
+	player_building::table
+		.inner_join(building::table)
+		.filter(player_building::player_id.eq(player_id))
+		.filter(building::name.eq("Farm"))
+		.select(PlayerBuilding::as_select())
+		.first(conn)
+		.expect("Player has no Farm")
+}
+
+/// Build a battle outcome won by the attacker, fought `days_ago` days ago.
+fn battle(attacker: &PlayerKey, defender: &PlayerKey, days_ago: i64) -> BattleOutcome {
+	BattleOutcome {
+		attacker_id: *attacker,
+		defender_id: *defender,
+		winner_id: Some(*attacker),
+		attacker_losses: vec![],
+		defender_losses: vec![],
+		loot: Loot::default(),
+		modifiers: vec![],
+		fought_at: Utc::now() - TimeDelta::days(days_ago),
+	}
+}
+
+#[test]
+fn test_activity_window_parsing() {
+	assert_eq!("7d".parse::<ActivityWindow>().unwrap().days(), 7);
+	assert_eq!("90d".parse::<ActivityWindow>().unwrap().days(), 90);
+	assert_eq!(ActivityWindow::default().days(), 7);
+	for invalid in ["0d", "91d", "7", "7h", "-1d", "d", ""] {
+		assert!(invalid.parse::<ActivityWindow>().is_err(), "{invalid}");
+	}
+}
+
+#[tokio::test]
+async fn test_activity_is_aggregated_per_day() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("active_player", Some(FactionCode::Human));
+	let rival = harness.create_named_user("rival_player", Some(FactionCode::Orc));
+	give_player_resources(&mut conn, &player.id);
+
+	let farm = get_farm(&mut conn, &player.id);
+	upgrade_building(&mut conn, &harness.app.job_queue, &farm.id).expect("Failed to start upgrade");
+	let ledger = building_upgrades::get_for_player_building(&mut conn, &farm.id).unwrap();
+	assert_eq!(ledger.len(), 1);
+	assert_eq!(ledger[0].level, farm.level + 1);
+	assert!(ledger[0].completed_at.is_none());
+	let spent = (
+		ledger[0].food,
+		ledger[0].wood,
+		ledger[0].stone,
+		ledger[0].gold,
+	);
+
+	let eta = Utc::now() - TimeDelta::seconds(1);
+	player_buildings::set_upgrade_eta(&mut conn, &farm.id, Some(eta)).unwrap();
+	confirm_upgrade(&mut conn, &farm.id).expect("Failed to confirm upgrade");
+	let ledger = building_upgrades::get_for_player_building(&mut conn, &farm.id).unwrap();
+	assert!(ledger[0].completed_at.is_some());
+
+	record_battle(&mut conn, battle(&player.id, &rival.id, 0)).unwrap();
+	record_battle(&mut conn, battle(&rival.id, &player.id, 0)).unwrap();
+	record_battle(&mut conn, battle(&player.id, &rival.id, 2)).unwrap();
+	record_battle(&mut conn, battle(&player.id, &rival.id, 10)).unwrap();
+
+	let cache = ActivityCache::default();
+	let activity =
+		get_player_activity(&mut conn, &cache, &player.id, ActivityWindow::default()).unwrap();
+	assert_eq!(activity.days.len(), 7);
+	assert_eq!(activity.days[6].day, Utc::now().date_naive());
+
+	let today = &activity.days[6];
+	assert_eq!(today.buildings_upgraded, 1);
+	assert_eq!((today.battles_fought, today.battles_won), (2, 1));
+	assert_eq!(
+		(
+			today.food_spent,
+			today.wood_spent,
+			today.stone_spent,
+			today.gold_spent
+		),
+		spent
+	);
+	assert_eq!(activity.days[4].battles_fought, 1);
+	let fought: i64 = activity.days.iter().map(|day| day.battles_fought).sum();
+	assert_eq!(fought, 3, "Battles outside the window are not counted");
+
+	// Repeated requests are served from the cache until it expires
+	record_battle(&mut conn, battle(&player.id, &rival.id, 0)).unwrap();
+	let cached =
+		get_player_activity(&mut conn, &cache, &player.id, ActivityWindow::default()).unwrap();
+	assert_eq!(cached.generated_at, activity.generated_at);
+	assert_eq!(cached.days[6].battles_fought, 2);
+
+	let fresh = get_player_activity(
+		&mut conn,
+		&ActivityCache::default(),
+		&player.id,
+		ActivityWindow::default(),
+	)
+	.unwrap();
+	assert_eq!(fresh.days[6].battles_fought, 3);
+}
@@ -0,0 +1,155 @@
+//! Integration tests for the quest chains.
+//!
+//! These tests cover:
+//! - Unlocking quests as the ones they require are claimed
+//! - Checking building and collecting quests against the player's buildings and storage
+//! - Counting game events towards the open quests only
+//! - Claiming rewards once, and only for reached objectives
+
+use chrono::Utc;
+use diesel::prelude::*;
+use empire::db::{DbConn, player_items, resources, units};
+use empire::domain::events::GameEvent;
+use empire::domain::factions::FactionCode;
+use empire::domain::player::PlayerKey;
+use empire::game::quests::quest_operations::{
+	QuestState, claim_quest, list_quests, record_progress,
+};
+use empire::schema::{building, player_building};
+use uuid::Uuid;
+
+use crate::common::TestHarness;
+
+/// The state of each quest in the player's quest log, by quest ID.
+fn quest_log(conn: &mut DbConn, player_id: &PlayerKey) -> Vec<(String, QuestState, i64)> {
+	list_quests(conn, player_id)
+		.unwrap()
+		.into_iter()
+		.map(|status| (status.quest.id, status.state, status.progress))
+		.collect()
+}
+
+fn set_building_level(conn: &mut DbConn, player_id: &PlayerKey, name: &str, level: i32) {
+	let building_ids = building::table
+		.filter(building::name.eq(name))
+		.select(building::id);
+	diesel::update(
+		player_building::table
+			.filter(player_building::player_id.eq(player_id))
+			.filter(player_building::building_id.eq_any(building_ids)),
+	)
+	.set(player_building::level.eq(level))
+	.execute(conn)
+	.unwrap();
+}
+
+fn trained(conn: &mut DbConn, player_id: &PlayerKey, unit: &str, quantity: i64) -> GameEvent {
+	GameEvent::TrainingCompleted {
+		player_id: *player_id,
+		training_id: Uuid::new_v4(),
+		unit_id: units::find_by_name(conn, unit).unwrap().unwrap().id,
+		quantity,
+	}
+}
+
+#[tokio::test]
+async fn test_quests_unlock_as_the_chain_is_claimed() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("novice", Some(FactionCode::Human));
+
+	assert_eq!(
+		quest_log(&mut conn, &player.id),
+		vec![("build_farm".to_string(), QuestState::Active, 0)]
+	);
+	let err = claim_quest(&mut conn, &player.id, "build_farm", Utc::now()).unwrap_err();
+	assert!(err.to_string().contains("not reached yet"), "{err}");
+	let err = claim_quest(&mut conn, &player.id, "train_infantry", Utc::now()).unwrap_err();
+	assert!(err.to_string().contains("Quest is locked"), "{err}");
+	let err = claim_quest(&mut conn, &player.id, "slay_dragon", Utc::now()).unwrap_err();
+	assert!(err.to_string().contains("Quest not found"), "{err}");
+
+	// The starter Farm is checked whenever the quests are listed, no event needed
+	set_building_level(&mut conn, &player.id, "Farm", 1);
+	assert_eq!(
+		quest_log(&mut conn, &player.id),
+		vec![("build_farm".to_string(), QuestState::Completed, 1)]
+	);
+
+	let before = resources::get_by_player_id(&mut conn, &player.id).unwrap();
+	let claimed = claim_quest(&mut conn, &player.id, "build_farm", Utc::now()).unwrap();
+	assert_eq!(claimed.state, QuestState::Claimed);
+	assert!(claimed.claimed_at.is_some());
+	let after = resources::get_by_player_id(&mut conn, &player.id).unwrap();
+	assert_eq!(after.wood, before.wood + claimed.quest.reward_wood);
+	assert_eq!(after.stone, before.stone + claimed.quest.reward_stone);
+
+	let err = claim_quest(&mut conn, &player.id, "build_farm", Utc::now()).unwrap_err();
+	assert!(err.to_string().contains("already completed"), "{err}");
+	let after_retry = resources::get_by_player_id(&mut conn, &player.id).unwrap();
+	assert_eq!(after_retry.wood, after.wood);
+
+	assert_eq!(
+		quest_log(&mut conn, &player.id),
+		vec![
+			("build_farm".to_string(), QuestState::Claimed, 1),
+			("train_infantry".to_string(), QuestState::Active, 0),
+			("collect_wood".to_string(), QuestState::Active, after.wood),
+		]
+	);
+}
+
+#[tokio::test]
+async fn test_events_progress_the_open_quests() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("recruiter", Some(FactionCode::Orc));
+
+	// Trainings do not count before their quest unlocks
+	let event = trained(&mut conn, &player.id, "Infantry", 5);
+	assert_eq!(record_progress(&mut conn, &event).unwrap(), 0);
+
+	set_building_level(&mut conn, &player.id, "Farm", 1);
+	claim_quest(&mut conn, &player.id, "build_farm", Utc::now()).unwrap();
+
+	let event = trained(&mut conn, &player.id, "Infantry", 3);
+	assert_eq!(record_progress(&mut conn, &event).unwrap(), 1);
+	let event = trained(&mut conn, &player.id, "Ranged", 10);
+	assert_eq!(record_progress(&mut conn, &event).unwrap(), 0);
+	let err = claim_quest(&mut conn, &player.id, "train_infantry", Utc::now()).unwrap_err();
+	assert!(err.to_string().contains("not reached yet"), "{err}");
+	let event = trained(&mut conn, &player.id, "Infantry", 2);
+	assert_eq!(record_progress(&mut conn, &event).unwrap(), 1);
+	claim_quest(&mut conn, &player.id, "train_infantry", Utc::now()).unwrap();
+
+	// Collections keep the highest amount stored, spending it afterwards does not undo it
+	let event = GameEvent::ResourcesCollected {
+		player_id: player.id,
+		food: 0,
+		wood: 600,
+		stone: 0,
+		gold: 0,
+	};
+	assert_eq!(record_progress(&mut conn, &event).unwrap(), 1);
+	let log = quest_log(&mut conn, &player.id);
+	assert!(log.contains(&("collect_wood".to_string(), QuestState::Completed, 500)));
+	let claimed = claim_quest(&mut conn, &player.id, "collect_wood", Utc::now()).unwrap();
+	let items = player_items::get_for_player(&mut conn, &player.id).unwrap();
+	let (held, item) = items
+		.iter()
+		.find(|(held, _)| Some(held.item_id) == claimed.quest.reward_item_id)
+		.expect("The quest rewards an item");
+	assert_eq!(item.name, "Lumberjack's Axe");
+	assert_eq!(held.quantity, 1);
+
+	// Both branches of the chain lead to the Lumberyard
+	set_building_level(&mut conn, &player.id, "Lumberyard", 2);
+	let event = GameEvent::UpgradeCompleted {
+		player_id: player.id,
+		player_building_id: Uuid::new_v4(),
+		level: 2,
+	};
+	assert_eq!(record_progress(&mut conn, &event).unwrap(), 1);
+	let log = quest_log(&mut conn, &player.id);
+	assert!(log.contains(&("upgrade_lumberyard".to_string(), QuestState::Completed, 2)));
+}
@@ -0,0 +1,190 @@
+//! Integration tests for cron-style recurring jobs.
+//!
+//! These tests cover:
+//! - Registering a definition enqueues its first run at the next cron occurrence
+//! - Re-registering is idempotent and replaces pending runs when the definition changes
+//! - Finished runs enqueue their successor, and startup sync restores missing runs
+
+use chrono::{DateTime, Timelike, Utc};
+use diesel::prelude::*;
+use empire::db::DbConn;
+use empire::domain::jobs::{Job, JobKey, JobStatus, JobType};
+use empire::game::modifiers::modifier_scheduler::ModifierJobPayload;
+use empire::job_queue::JobPriority;
+use empire::job_queue::cancellation::JobCancellation;
+use empire::job_queue::recurring_scheduler::next_occurrence;
+use empire::schema::job;
+use uuid::Uuid;
+
+use crate::common::TestHarness;
+
+const EVERY_MINUTE: &str = "0 * * * * *";
+
+fn get_job(conn: &mut DbConn, job_id: &JobKey) -> Job {
+	job::table
+		.find(job_id)
+		.select(Job::as_select())
+		.first(conn)
+		.expect("Job not found")
+}
+
+/// Counts the runs created from `payload`, other jobs may be queued by the harness.
+fn count_runs(conn: &mut DbConn, payload: &ModifierJobPayload, wanted: JobStatus) -> i64 {
+	job::table
+		.filter(job::payload.eq(serde_json::to_value(payload).unwrap()))
+		.filter(job::status.eq(wanted))
+		.count()
+		.get_result(conn)
+		.unwrap()
+}
+
+#[test]
+fn test_next_occurrence_follows_the_schedule() {
+	let after: DateTime<Utc> = "2025-03-03T10:15:30Z".parse().unwrap();
+
+	let next = next_occurrence(EVERY_MINUTE, after).unwrap();
+	assert_eq!(
+		next,
+		"2025-03-03T10:16:00Z".parse::<DateTime<Utc>>().unwrap()
+	);
+
+	let nightly = next_occurrence("0 30 3 * * *", after).unwrap();
+	assert_eq!(
+		nightly,
+		"2025-03-04T03:30:00Z".parse::<DateTime<Utc>>().unwrap()
+	);
+
+	let err = next_occurrence("every five minutes", after).unwrap_err();
+	assert!(err.to_string().contains("Invalid cron expression"));
+}
+
+#[tokio::test]
+async fn test_register_recurring_enqueues_a_single_run() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+	let payload = ModifierJobPayload::UpdateModifierCache {
+		player_id: Uuid::new_v4(),
+	};
+
+	let recurring = app
+		.job_queue
+		.register_recurring(
+			"cache_refresh",
+			EVERY_MINUTE,
+			JobType::Modifier,
+			&payload,
+			JobPriority::Low,
+		)
+		.expect("Failed to register recurring job");
+	let job_id = recurring.next_job_id.expect("First run should be enqueued");
+	let run = get_job(&mut conn, &job_id);
+	assert_eq!(run.job_type, JobType::Modifier);
+	assert_eq!(run.status, JobStatus::Pending);
+	assert_eq!(run.payload, serde_json::to_value(&payload).unwrap());
+	assert_eq!(run.priority, JobPriority::Low as i32);
+	assert!(run.run_at > Utc::now());
+	assert_eq!(run.run_at.second(), 0);
+	assert_eq!(recurring.next_run_at, Some(run.run_at));
+
+	// Registering the same definition again keeps the pending run
+	let again = app
+		.job_queue
+		.register_recurring(
+			"cache_refresh",
+			EVERY_MINUTE,
+			JobType::Modifier,
+			&payload,
+			JobPriority::Low,
+		)
+		.unwrap();
+	assert_eq!(again.id, recurring.id);
+	assert_eq!(again.next_job_id, Some(job_id));
+	assert_eq!(count_runs(&mut conn, &payload, JobStatus::Pending), 1);
+
+	// A changed definition replaces the pending run
+	let changed = app
+		.job_queue
+		.register_recurring(
+			"cache_refresh",
+			"0 0 * * * *",
+			JobType::Modifier,
+			&payload,
+			JobPriority::Low,
+		)
+		.unwrap();
+	assert_ne!(changed.next_job_id, Some(job_id));
+	assert_eq!(get_job(&mut conn, &job_id).status, JobStatus::Cancelled);
+	assert_eq!(count_runs(&mut conn, &payload, JobStatus::Pending), 1);
+	assert_eq!(app.job_queue.list_recurring().unwrap().len(), 1);
+
+	// Invalid schedules are rejected without storing anything
+	let result = app.job_queue.register_recurring(
+		"broken",
+		"not a cron",
+		JobType::Modifier,
+		&payload,
+		JobPriority::Low,
+	);
+	assert!(result.is_err());
+	assert_eq!(app.job_queue.list_recurring().unwrap().len(), 1);
+
+	assert!(app.job_queue.remove_recurring("cache_refresh").unwrap());
+	assert!(!app.job_queue.remove_recurring("cache_refresh").unwrap());
+	assert_eq!(count_runs(&mut conn, &payload, JobStatus::Pending), 0);
+}
+
+#[tokio::test]
+async fn test_finished_runs_enqueue_the_next_one() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+	let payload = ModifierJobPayload::UpdateModifierCache {
+		player_id: Uuid::new_v4(),
+	};
+
+	let recurring = app
+		.job_queue
+		.register_recurring(
+			"cache_refresh",
+			EVERY_MINUTE,
+			JobType::Modifier,
+			&payload,
+			JobPriority::Normal,
+		)
+		.unwrap();
+	let first_run = recurring.next_job_id.unwrap();
+
+	app.job_queue.complete_job(&first_run).unwrap();
+	let after_complete = app.job_queue.list_recurring().unwrap().remove(0);
+	let second_run = after_complete
+		.next_job_id
+		.expect("Next run should be enqueued");
+	assert_ne!(second_run, first_run);
+	assert!(after_complete.last_run_at.is_some());
+	assert_eq!(get_job(&mut conn, &second_run).status, JobStatus::Pending);
+
+	// Retryable failures keep the run, exhausted ones move on to the next
+	app.job_queue
+		.fail_job(&second_run, "first failure")
+		.unwrap();
+	let after_retryable = app.job_queue.list_recurring().unwrap().remove(0);
+	assert_eq!(after_retryable.next_job_id, Some(second_run));
+	for attempt in 0..=get_job(&mut conn, &second_run).max_retries {
+		app.job_queue
+			.fail_job(&second_run, format!("failure {attempt}"))
+			.unwrap();
+	}
+	let after_exhausted = app.job_queue.list_recurring().unwrap().remove(0);
+	let third_run = after_exhausted.next_job_id.unwrap();
+	assert_ne!(third_run, second_run);
+
+	// A cancelled run is restored by the startup sync
+	assert_eq!(
+		app.job_queue.cancel_job(&third_run).unwrap(),
+		JobCancellation::Cancelled
+	);
+	assert_eq!(app.job_queue.sync_recurring().unwrap(), 1);
+	assert_eq!(app.job_queue.sync_recurring().unwrap(), 0);
+	let synced = app.job_queue.list_recurring().unwrap().remove(0);
+	assert_ne!(synced.next_job_id, Some(third_run));
+	assert_eq!(count_runs(&mut conn, &payload, JobStatus::Pending), 1);
+}
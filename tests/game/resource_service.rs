@@ -1,14 +1,26 @@
+use std::sync::Barrier;
+
 use axum::extract::FromRef;
+use bigdecimal::BigDecimal;
+use chrono::{TimeDelta, Utc};
 use diesel::prelude::*;
 use diesel::update;
 use empire::auth::utils::hash_password;
-use empire::db::{DbConn, players};
+use empire::configuration::ResourceSettings;
+use empire::db::{DbConn, players, settlements};
 use empire::domain::app_state::AppState;
 use empire::domain::factions::FactionCode;
+use empire::domain::jobs::{JobStatus, JobType, RecurringJob};
+use empire::domain::player::PlayerKey;
 use empire::domain::player::accumulator::PlayerAccumulator;
+use empire::domain::player::resource::ResourceType;
 use empire::domain::player::{NewPlayer, Player, UserName};
+use empire::game::player_operations::provision_player;
+use empire::game::resources::resource_operations::produce_resources;
+use empire::game::resources::resource_scheduler::{production_shard, register_production_ticks};
 use empire::game::resources::resource_service::ResourceService;
-use empire::schema::{player_accumulator as acc, player_resource as rsc};
+use empire::game::resources::{OverflowPolicy, ResourceProductionRates};
+use empire::schema::{job, player_accumulator as acc, player_resource as rsc};
 
 use crate::common::TestHarness;
 
@@ -58,9 +70,249 @@ async fn test_collect_resource() {
 	assert_eq!(updated_accumulator.gold, 0);
 }
 
+#[tokio::test]
+async fn test_collect_overflow_policies() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+	let user = create_test_user(&mut conn);
+	let collect = |conn: &mut DbConn, overflow: OverflowPolicy| {
+		set_up_overflow(conn, &user.id);
+		let srv = ResourceService::new(
+			&app.db_pool,
+			ResourceSettings {
+				overflow,
+				..ResourceSettings::default()
+			},
+		);
+		let res = srv.collect(&user.id).expect("Failed to collect resources");
+		let accumulator: PlayerAccumulator = acc::table
+			.filter(acc::player_id.eq(&user.id))
+			.first(conn)
+			.expect("Failed to query resource accumulator");
+		(
+			(res.food, res.wood, res.stone, res.gold),
+			(
+				accumulator.food,
+				accumulator.wood,
+				accumulator.stone,
+				accumulator.gold,
+			),
+		)
+	};
+
+	let (stored, left) = collect(&mut conn, OverflowPolicy::LeaveInAccumulator);
+	assert_eq!(stored, (1000, 1000, 950, 950));
+	assert_eq!(left, (200, 400, 0, 0));
+
+	let (stored, left) = collect(&mut conn, OverflowPolicy::Discard);
+	assert_eq!(stored, (1000, 1000, 950, 950));
+	assert_eq!(left, (0, 0, 0, 0));
+
+	// Food overflow is sold until gold storage is full, the rest of it and all wood stay
+	let (stored, left) = collect(&mut conn, OverflowPolicy::ConvertToGold { rate: 0.5 });
+	assert_eq!(stored, (1000, 1000, 950, 1000));
+	assert_eq!(left, (100, 400, 0, 0));
+}
+
+#[tokio::test]
+async fn test_concurrent_production_and_collection_keep_resources() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+	let user = create_test_user(&mut conn);
+	let capital = settlements::get_capital(&mut conn, &user.id).unwrap();
+	let rates = ResourceProductionRates::from([(ResourceType::Wood, BigDecimal::from(100))]);
+	let started_at = Utc::now() - TimeDelta::hours(6);
+	update(acc::table.filter(acc::player_id.eq(&user.id)))
+		.set(acc::wood.eq(0))
+		.execute(&mut conn)
+		.expect("Failed to reset resource accumulator");
+	update(rsc::table.filter(rsc::player_id.eq(&user.id)))
+		.set((
+			rsc::wood.eq(0),
+			rsc::wood_cap.eq(150),
+			rsc::produced_at.eq(started_at),
+		))
+		.execute(&mut conn)
+		.expect("Failed to update resources");
+
+	// 30 ticks of six minutes produce 10 wood each, collected by four players at once
+	let barrier = Barrier::new(5);
+	std::thread::scope(|scope| {
+		scope.spawn(|| {
+			let mut conn = db_pool.get().unwrap();
+			barrier.wait();
+			for tick in 1..=30 {
+				let up_to = started_at + TimeDelta::minutes(6 * tick);
+				produce_resources(
+					&mut conn,
+					&capital,
+					&rates,
+					Some(up_to),
+					&ResourceSettings::default(),
+				)
+				.expect("Failed to produce resources");
+			}
+		});
+		for _ in 0..4 {
+			scope.spawn(|| {
+				let srv = ResourceService::new(&app.db_pool, ResourceSettings::default());
+				barrier.wait();
+				for _ in 0..25 {
+					let res = srv.collect(&user.id).expect("Failed to collect resources");
+					assert!(res.wood <= res.wood_cap, "Storage overflowed: {res:?}");
+				}
+			});
+		}
+	});
+
+	// Whatever was drained from the accumulator ended up in storage, and nothing more
+	let accumulator: PlayerAccumulator = acc::table
+		.filter(acc::player_id.eq(&user.id))
+		.first(&mut conn)
+		.expect("Failed to query resource accumulator");
+	let stored: i64 = rsc::table
+		.filter(rsc::player_id.eq(&user.id))
+		.select(rsc::wood)
+		.first(&mut conn)
+		.expect("Failed to query resources");
+	assert!(accumulator.wood >= 0, "Drained too much: {accumulator:?}");
+	assert_eq!(stored, 150);
+	assert_eq!(stored + accumulator.wood, 300);
+}
+
+#[tokio::test]
+async fn test_fractional_production_is_carried_over() {
+	let TestHarness { db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+	let user = create_test_user(&mut conn);
+	let capital = settlements::get_capital(&mut conn, &user.id).unwrap();
+	let rates = ResourceProductionRates::from([(ResourceType::Food, "0.4".parse().unwrap())]);
+	let started_at = Utc::now() - TimeDelta::hours(6);
+
+	// 50 ticks of six minutes produce 0.04 food each, 2 food over five hours
+	let produce_ticks = |conn: &mut DbConn, accrual_precision: u32| {
+		update(rsc::table.filter(rsc::player_id.eq(&user.id)))
+			.set(rsc::produced_at.eq(started_at))
+			.execute(conn)
+			.expect("Failed to reset production time");
+		update(acc::table.filter(acc::player_id.eq(&user.id)))
+			.set((acc::food.eq(0), acc::food_remainder.eq(BigDecimal::from(0))))
+			.execute(conn)
+			.expect("Failed to reset resource accumulator");
+		(1..=50)
+			.map(|tick| {
+				let up_to = started_at + TimeDelta::minutes(6 * tick);
+				let settings = ResourceSettings {
+					accrual_precision,
+					..ResourceSettings::default()
+				};
+				produce_resources(conn, &capital, &rates, Some(up_to), &settings)
+					.expect("Failed to produce resources")
+			})
+			.last()
+			.unwrap()
+	};
+
+	let accumulator = produce_ticks(&mut conn, 6);
+	assert_eq!(accumulator.food, 2);
+	assert_eq!(accumulator.food_remainder, BigDecimal::from(0));
+
+	// Without carrying, every tick truncates its production away
+	let accumulator = produce_ticks(&mut conn, 0);
+	assert_eq!(accumulator.food, 0);
+}
+
+#[tokio::test]
+async fn test_production_ticks_cover_every_shard() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let state = AppState(app);
+	let mut conn = db_pool.get().unwrap();
+	let producing = create_test_user(&mut conn);
+	let neutral = players::create(
+		&mut conn,
+		NewPlayer {
+			name: UserName::parse("neutral_user".to_string()).unwrap(),
+			pwd_hash: hash_password(b"1234").unwrap(),
+			email: None,
+			faction: FactionCode::Neutral,
+		},
+	)
+	.expect("Failed to create player");
+	provision_player(&mut conn, &neutral).expect("Failed to provision player");
+
+	register_production_ticks(&state.job_queue, 4).expect("Failed to register ticks");
+	register_production_ticks(&state.job_queue, 4).expect("Failed to register ticks again");
+	let ticks = |state: &AppState| -> Vec<RecurringJob> {
+		state
+			.job_queue
+			.list_recurring()
+			.unwrap()
+			.into_iter()
+			.filter(|recurring| recurring.job_type == JobType::ResourceProduction)
+			.collect()
+	};
+	let registered = ticks(&state);
+	assert_eq!(registered.len(), 4);
+	assert!(registered.iter().all(|tick| tick.next_job_id.is_some()));
+
+	// Shrinking the shard count retires the extra ticks
+	register_production_ticks(&state.job_queue, 2).expect("Failed to shrink shards");
+	let names: Vec<String> = ticks(&state).into_iter().map(|tick| tick.name).collect();
+	assert_eq!(names, ["resource-production-0", "resource-production-1"]);
+	let pending: i64 = job::table
+		.filter(job::job_type.eq(JobType::ResourceProduction))
+		.filter(job::status.eq(JobStatus::Pending))
+		.count()
+		.get_result(&mut conn)
+		.unwrap();
+	assert_eq!(pending, 2);
+
+	// Every producing player is in exactly one shard, neutral players in none
+	let srv = ResourceService::from_ref(&state);
+	let shards: Vec<Vec<PlayerKey>> = (0..2)
+		.map(|shard| srv.get_shard_players(shard, 2).unwrap())
+		.collect();
+	assert_eq!(
+		shards
+			.iter()
+			.flatten()
+			.filter(|id| **id == producing.id)
+			.count(),
+		1
+	);
+	assert!(shards.iter().flatten().all(|id| *id != neutral.id));
+	assert!(shards[production_shard(&producing.id, 2) as usize].contains(&producing.id));
+}
+
+/// Fill a player's storage close to its caps, with more food and wood accumulated than fits.
+fn set_up_overflow(conn: &mut DbConn, player_id: &PlayerKey) {
+	update(acc::table.filter(acc::player_id.eq(player_id)))
+		.set((
+			acc::food.eq(300),
+			acc::wood.eq(500),
+			acc::stone.eq(50),
+			acc::gold.eq(50),
+		))
+		.execute(conn)
+		.expect("Failed to update resource accumulator");
+	update(rsc::table.filter(rsc::player_id.eq(player_id)))
+		.set((
+			rsc::food.eq(900),
+			rsc::wood.eq(900),
+			rsc::stone.eq(900),
+			rsc::gold.eq(900),
+			rsc::food_cap.eq(1000),
+			rsc::wood_cap.eq(1000),
+			rsc::stone_cap.eq(1000),
+			rsc::gold_cap.eq(1000),
+		))
+		.execute(conn)
+		.expect("Failed to update resources");
+}
+
 /// Create a player. Uses internal DB functions.
 fn create_test_user(conn: &mut DbConn) -> Player {
-	players::create(
+	let player = players::create(
 		conn,
 		NewPlayer {
 			name: UserName::parse("test_user".to_string()).unwrap(),
@@ -69,5 +321,7 @@ fn create_test_user(conn: &mut DbConn) -> Player {
 			faction: FactionCode::Human,
 		},
 	)
-	.expect("Failed to create player")
+	.expect("Failed to create player");
+	provision_player(conn, &player).expect("Failed to provision player");
+	player
 }
@@ -0,0 +1,217 @@
+//! Integration tests for seasons and the legacy of players.
+//!
+//! These tests cover:
+//! - Creating seasons one after another, each ended by a job once it is over
+//! - Ending a season, archiving the standings and granting legacy points once
+
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::prelude::*;
+use empire::db::{DbConn, player_units, units};
+use empire::domain::factions::FactionCode;
+use empire::domain::jobs::JobType;
+use empire::domain::leaderboard::LeaderboardCategory;
+use empire::domain::player::PlayerKey;
+use empire::domain::season::Season;
+use empire::game::admin_operations::AdminActor;
+use empire::game::alliances::alliance_operations::create_alliance;
+use empire::game::seasons::season_operations::{
+	EndOutcome, LEGACY_TIERS, SeasonRequest, create_season, end_season, get_overview, get_results,
+	list_history,
+};
+use empire::schema::job;
+
+use crate::common::TestHarness;
+
+fn operator() -> AdminActor {
+	AdminActor {
+		operator: Some("seasons-team".to_string()),
+		request_id: None,
+	}
+}
+
+/// Creates a season starting at `starts_at` and lasting a week.
+fn start_season(harness: &TestHarness, conn: &mut DbConn, starts_at: DateTime<Utc>) -> Season {
+	create_season(
+		conn,
+		&harness.app.job_queue,
+		SeasonRequest {
+			name: "Age of Iron".to_string(),
+			starts_at,
+			ends_at: starts_at + TimeDelta::days(7),
+		},
+		operator(),
+	)
+	.expect("Failed to create season")
+}
+
+/// Legacy points a board grants for a final rank.
+fn tier_points(rank: i32) -> i64 {
+	LEGACY_TIERS
+		.iter()
+		.find(|(max_rank, _)| rank <= *max_rank)
+		.unwrap()
+		.1
+}
+
+/// Gives the player infantry, worth 25 points each.
+fn recruit(conn: &mut DbConn, player_id: &PlayerKey, quantity: i64) {
+	let infantry = units::find_by_name(conn, "Infantry").unwrap().unwrap();
+	player_units::add_units(conn, player_id, &infantry.id, quantity).unwrap();
+}
+
+#[tokio::test]
+async fn test_seasons_follow_one_another() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let first = start_season(&harness, &mut conn, Utc::now() - TimeDelta::minutes(1));
+	assert_eq!(first.number, 1);
+	assert!(first.is_running(Utc::now()));
+
+	// The season ends itself once it is over
+	let end_runs_at: Vec<DateTime<Utc>> = job::table
+		.filter(job::job_type.eq(JobType::Season))
+		.select(job::run_at)
+		.load(&mut conn)
+		.unwrap();
+	assert_eq!(end_runs_at, vec![first.ends_at]);
+
+	let err = create_season(
+		&mut conn,
+		&harness.app.job_queue,
+		SeasonRequest {
+			name: "Age of Steel".to_string(),
+			starts_at: first.ends_at,
+			ends_at: first.ends_at + TimeDelta::days(7),
+		},
+		operator(),
+	)
+	.unwrap_err();
+	assert!(err.to_string().contains("Another season has not ended"));
+
+	let outcome = end_season(&mut conn, &first.id, Utc::now()).unwrap();
+	assert_eq!(outcome, EndOutcome::NotOver(first.clone()));
+	let outcome = end_season(&mut conn, &first.id, first.ends_at).unwrap();
+	assert!(matches!(outcome, EndOutcome::Ended { .. }));
+	let outcome = end_season(&mut conn, &first.id, first.ends_at).unwrap();
+	assert_eq!(outcome, EndOutcome::AlreadyEnded);
+
+	let err = create_season(
+		&mut conn,
+		&harness.app.job_queue,
+		SeasonRequest {
+			name: "Age of Steel".to_string(),
+			starts_at: first.ends_at - TimeDelta::days(1),
+			ends_at: first.ends_at + TimeDelta::days(7),
+		},
+		operator(),
+	)
+	.unwrap_err();
+	assert!(
+		err.to_string()
+			.contains("Season must start after the last one ended")
+	);
+	let second = start_season(&harness, &mut conn, first.ends_at);
+	assert_eq!(second.number, 2);
+
+	let history: Vec<i32> = list_history(&mut conn)
+		.unwrap()
+		.iter()
+		.map(|season| season.number)
+		.collect();
+	assert_eq!(history, vec![1], "Only ended seasons are history");
+}
+
+#[tokio::test]
+async fn test_ending_a_season_archives_standings_and_grants_legacy() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let general = harness.create_named_user("general", Some(FactionCode::Human));
+	let captain = harness.create_named_user("captain", Some(FactionCode::Human));
+	recruit(&mut conn, &general.id, 10);
+	recruit(&mut conn, &captain.id, 2);
+	let alliance = create_alliance(&mut conn, &captain.id, "Iron Guard", "IG")
+		.unwrap()
+		.alliance;
+	let season = start_season(&harness, &mut conn, Utc::now() - TimeDelta::minutes(1));
+
+	// Nothing is archived while the season runs
+	let results = get_results(
+		&mut conn,
+		&general.id,
+		&season.id,
+		LeaderboardCategory::Power,
+	)
+	.unwrap();
+	assert!(results.standings.is_empty());
+	assert_eq!(results.own, None);
+
+	let outcome = end_season(&mut conn, &season.id, season.ends_at).unwrap();
+	let EndOutcome::Ended {
+		archived, players, ..
+	} = outcome
+	else {
+		panic!("Season did not end: {outcome:?}");
+	};
+	// The seeded test accounts are ranked as well
+	assert!(archived >= 5, "Two players on two boards and an alliance");
+	assert!(players >= 2);
+
+	let power = get_results(
+		&mut conn,
+		&captain.id,
+		&season.id,
+		LeaderboardCategory::Power,
+	)
+	.unwrap();
+	assert!(power.season.ended_at.is_some());
+	let general_power = power
+		.standings
+		.iter()
+		.find(|standing| standing.subject_id == general.id)
+		.unwrap();
+	assert_eq!(general_power.name, "general");
+	assert_eq!(general_power.score, 250);
+	assert_eq!(general_power.legacy_points, tier_points(general_power.rank));
+	let own = power.own.unwrap();
+	assert!(own.rank > general_power.rank);
+	assert_eq!(own.legacy_points, tier_points(own.rank));
+
+	let results = get_results(
+		&mut conn,
+		&captain.id,
+		&season.id,
+		LeaderboardCategory::Alliance,
+	)
+	.unwrap();
+	let own = results.own.unwrap();
+	assert_eq!(own.subject_id, alliance.id);
+	assert_eq!(own.name, "Iron Guard");
+	assert_eq!(own.legacy_points, 0, "Alliances earn no legacy points");
+
+	let economy = get_results(
+		&mut conn,
+		&general.id,
+		&season.id,
+		LeaderboardCategory::Economy,
+	)
+	.unwrap()
+	.own
+	.unwrap();
+	let overview = get_overview(&mut conn, &general.id).unwrap();
+	assert_eq!(overview.current, None);
+	let legacy = overview.legacy.unwrap();
+	assert_eq!(
+		legacy.legacy_points,
+		general_power.legacy_points + economy.legacy_points
+	);
+	assert_eq!(legacy.seasons, 1);
+
+	// Ending the season again grants nothing more
+	let outcome = end_season(&mut conn, &season.id, season.ends_at).unwrap();
+	assert_eq!(outcome, EndOutcome::AlreadyEnded);
+	let again = get_overview(&mut conn, &general.id)
+		.unwrap()
+		.legacy
+		.unwrap();
+	assert_eq!(again.legacy_points, legacy.legacy_points);
+}
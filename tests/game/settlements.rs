@@ -0,0 +1,168 @@
+//! Integration tests for settlements.
+//!
+//! These tests cover:
+//! - Founding settlements once the Keep of the capital allows it, up to the configured limit
+//! - New settlements getting the starter buildings and storage of their own
+//! - Settlements producing, storing and paying for their buildings on their own
+
+use bigdecimal::BigDecimal;
+use chrono::{SubsecRound, TimeDelta, Utc};
+use diesel::prelude::*;
+use empire::configuration::{ResourceSettings, SettlementSettings};
+use empire::db::{DbConn, player_buildings, resources, settlements};
+use empire::domain::factions::FactionCode;
+use empire::domain::player::resource::ResourceType;
+use empire::domain::settlement::{CAPITAL_NAME, Settlement};
+use empire::game::buildings::building_operations::construct_building;
+use empire::game::resources::ResourceProductionRates;
+use empire::game::resources::resource_operations::produce_resources;
+use empire::game::settlements::settlement_operations::{found, list};
+use empire::schema::{building, player_building, player_resource as pr};
+
+use crate::common::TestHarness;
+
+/// Raises the Keep of a settlement to `level`.
+fn set_keep_level(conn: &mut DbConn, settlement: &Settlement, level: i32) {
+	let keep_id: i32 = building::table
+		.filter(building::name.eq("Keep"))
+		.select(building::id)
+		.first(conn)
+		.expect("Keep not found");
+	diesel::update(
+		player_building::table
+			.filter(player_building::settlement_id.eq(settlement.id))
+			.filter(player_building::building_id.eq(keep_id)),
+	)
+	.set(player_building::level.eq(level))
+	.execute(conn)
+	.expect("Failed to set the Keep level");
+}
+
+/// Gives a settlement plenty of resources and storage.
+fn fill_storage(conn: &mut DbConn, settlement: &Settlement) {
+	diesel::update(pr::table.filter(pr::settlement_id.eq(settlement.id)))
+		.set((
+			pr::food.eq(100_000),
+			pr::wood.eq(100_000),
+			pr::stone.eq(100_000),
+			pr::gold.eq(100_000),
+			pr::food_cap.eq(1_000_000),
+			pr::wood_cap.eq(1_000_000),
+			pr::stone_cap.eq(1_000_000),
+			pr::gold_cap.eq(1_000_000),
+		))
+		.execute(conn)
+		.expect("Failed to set settlement resources");
+}
+
+#[tokio::test]
+async fn test_settlements_are_founded_once_the_keep_allows() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let settings = SettlementSettings::default();
+	let player = harness.create_named_user("settler", Some(FactionCode::Human));
+	let capital = settlements::get_capital(&mut conn, &player.id).unwrap();
+	assert_eq!(capital.name, CAPITAL_NAME);
+
+	let err = found(&mut conn, &settings, &player.id, "Northwatch").unwrap_err();
+	assert!(err.to_string().contains("Keep of the capital is too low"));
+
+	set_keep_level(&mut conn, &capital, settings.found_keep_level);
+	let err = found(&mut conn, &settings, &player.id, " ab ").unwrap_err();
+	assert!(err.to_string().contains("Settlement names"));
+	let northwatch = found(&mut conn, &settings, &player.id, " Northwatch ").unwrap();
+	assert_eq!(northwatch.name, "Northwatch");
+	assert!(!northwatch.is_capital);
+	for name in ["Northwatch", CAPITAL_NAME] {
+		let err = found(&mut conn, &settings, &player.id, name).unwrap_err();
+		assert!(err.to_string().contains("name is taken"));
+	}
+
+	// The capital counts towards the limit
+	found(&mut conn, &settings, &player.id, "Southgate").unwrap();
+	let err = found(&mut conn, &settings, &player.id, "Eastmarch").unwrap_err();
+	assert!(err.to_string().contains("No more settlements"));
+
+	let names: Vec<_> = list(&mut conn, &player.id)
+		.unwrap()
+		.into_iter()
+		.map(|settlement| settlement.name)
+		.collect();
+	assert_eq!(names, [CAPITAL_NAME, "Northwatch", "Southgate"]);
+
+	// Another player's settlements are none of theirs
+	let other = harness.create_named_user("other", None);
+	assert!(
+		settlements::find_owned(&mut conn, &other.id, &northwatch.id)
+			.unwrap()
+			.is_none()
+	);
+}
+
+#[tokio::test]
+async fn test_settlements_produce_and_store_on_their_own() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let settings = SettlementSettings::default();
+	let player = harness.create_named_user("mayor", Some(FactionCode::Human));
+	let capital = settlements::get_capital(&mut conn, &player.id).unwrap();
+	set_keep_level(&mut conn, &capital, settings.found_keep_level);
+	let town = found(&mut conn, &settings, &player.id, "Riverside").unwrap();
+
+	// A new settlement starts like a new player, with a level 1 Keep
+	let starter = player_buildings::get_settlement_buildings(&mut conn, &town.id).unwrap();
+	assert!(!starter.is_empty());
+	assert_eq!(
+		player_buildings::get_keep_level(&mut conn, &town.id).unwrap(),
+		1
+	);
+	let town_res = resources::get_by_settlement(&mut conn, &town.id).unwrap();
+	assert_eq!(town_res.player_id, player.id);
+	assert_ne!(
+		town_res.id,
+		resources::get_by_player_id(&mut conn, &player.id)
+			.unwrap()
+			.id
+	);
+
+	// Production of the town leaves the capital alone
+	let produced_at = (Utc::now() - TimeDelta::hours(1)).trunc_subsecs(6);
+	diesel::update(pr::table.filter(pr::player_id.eq(player.id)))
+		.set(pr::produced_at.eq(produced_at))
+		.execute(&mut conn)
+		.unwrap();
+	let rates = ResourceProductionRates::from([(ResourceType::Wood, BigDecimal::from(100))]);
+	let acc = produce_resources(
+		&mut conn,
+		&town,
+		&rates,
+		Some(produced_at + TimeDelta::hours(1)),
+		&ResourceSettings::default(),
+	)
+	.unwrap();
+	assert_eq!(acc.settlement_id, town.id);
+	let town_res = resources::get_by_settlement(&mut conn, &town.id).unwrap();
+	assert_eq!(town_res.produced_at, produced_at + TimeDelta::hours(1));
+	let capital_res = resources::get_by_settlement(&mut conn, &capital.id).unwrap();
+	assert_eq!(capital_res.produced_at, produced_at);
+
+	// Buildings of the town are paid from its storage
+	fill_storage(&mut conn, &town);
+	let capital_wood = capital_res.wood;
+	let farm_id: i32 = building::table
+		.filter(building::name.eq("Farm"))
+		.filter(building::faction.eq(FactionCode::Human))
+		.select(building::id)
+		.first(&mut conn)
+		.unwrap();
+	let farm = construct_building(&mut conn, &harness.app.job_queue, &town, &farm_id).unwrap();
+	assert_eq!(farm.settlement_id, town.id);
+	let town_res = resources::get_by_settlement(&mut conn, &town.id).unwrap();
+	assert!(town_res.food + town_res.wood + town_res.stone + town_res.gold < 400_000);
+	assert_eq!(
+		resources::get_by_settlement(&mut conn, &capital.id)
+			.unwrap()
+			.wood,
+		capital_wood
+	);
+}
@@ -0,0 +1,162 @@
+//! Integration tests for the NPC players of the simulation mode.
+//!
+//! These tests cover:
+//! - Spawning NPCs once, without taking over the names of real players
+//! - Registering and retiring the recurring turns
+//! - Construction turns, one construction at a time
+//! - Training turns filling free training slots
+
+use chrono::{TimeDelta, Utc};
+use diesel::prelude::*;
+use empire::configuration::{ResourceSettings, SimulationSettings};
+use empire::db::{player_buildings, players, simulated_players, training_queue};
+use empire::domain::factions::FactionCode;
+use empire::domain::player::buildings::NewPlayerBuilding;
+use empire::game::simulation::simulation_operations::{
+	CONSTRUCT_TICK_NAME, TRAIN_TICK_NAME, construct_turn, spawn_npcs, start_simulation, train_turn,
+};
+use empire::schema::building;
+
+use crate::common::TestHarness;
+use crate::fixtures::give_player_resources;
+
+fn settings(npcs: u32) -> SimulationSettings {
+	SimulationSettings {
+		npcs,
+		..SimulationSettings::default()
+	}
+}
+
+#[tokio::test]
+async fn test_npcs_are_spawned_once() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("npc_2", Some(FactionCode::Human));
+
+	let npcs = spawn_npcs(&mut conn, &settings(3)).expect("Failed to spawn NPCs");
+	assert_eq!(npcs.len(), 2, "The name of a real player is skipped");
+	assert!(!npcs.contains(&player.id));
+	let factions: Vec<FactionCode> = npcs
+		.iter()
+		.map(|npc| players::get_by_id(&mut conn, npc).unwrap().faction)
+		.collect();
+	assert_eq!(factions, [FactionCode::Human, FactionCode::Elf]);
+
+	let again = spawn_npcs(&mut conn, &settings(3)).expect("Failed to spawn NPCs again");
+	assert_eq!(again, npcs);
+	assert_eq!(simulated_players::get_all_ids(&mut conn).unwrap(), npcs);
+}
+
+#[tokio::test]
+async fn test_simulation_turns_are_registered_and_retired() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let job_queue = &harness.app.job_queue;
+	let tick_names = || -> Vec<String> {
+		let mut names: Vec<String> = job_queue
+			.list_recurring()
+			.unwrap()
+			.into_iter()
+			.map(|recurring| recurring.name)
+			.filter(|name| name.starts_with("simulation-"))
+			.collect();
+		names.sort();
+		names
+	};
+
+	let npcs = start_simulation(&mut conn, job_queue, &settings(2)).unwrap();
+	assert_eq!(npcs.len(), 2);
+	assert_eq!(tick_names(), [CONSTRUCT_TICK_NAME, TRAIN_TICK_NAME]);
+
+	let npcs = start_simulation(&mut conn, job_queue, &settings(0)).unwrap();
+	assert!(npcs.is_empty());
+	assert!(tick_names().is_empty());
+	assert_eq!(simulated_players::get_all_ids(&mut conn).unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_npc_constructs_one_building_at_a_time() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let npc = spawn_npcs(&mut conn, &settings(1)).unwrap()[0];
+	give_player_resources(&mut conn, &npc);
+	let resources = ResourceSettings::default();
+
+	let started = construct_turn(&mut conn, &harness.app.job_queue, &npc, &resources)
+		.unwrap()
+		.expect("NPC should start a construction");
+	assert!(started.upgrade_finishes_at.is_some());
+	let level = started.level;
+
+	// Nothing new starts while the construction is underway
+	assert!(
+		construct_turn(&mut conn, &harness.app.job_queue, &npc, &resources)
+			.unwrap()
+			.is_none()
+	);
+
+	// A finished construction is confirmed, and the next one starts
+	let eta = Utc::now() - TimeDelta::seconds(1);
+	player_buildings::set_upgrade_eta(&mut conn, &started.id, Some(eta)).unwrap();
+	let next = construct_turn(&mut conn, &harness.app.job_queue, &npc, &resources)
+		.unwrap()
+		.expect("NPC should start another construction");
+	let confirmed = player_buildings::get_by_id(&mut conn, &started.id).unwrap();
+	assert_eq!(confirmed.level, level + 1);
+	assert!(next.upgrade_finishes_at.is_some());
+}
+
+#[tokio::test]
+async fn test_npc_fills_training_slots() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let npc = spawn_npcs(&mut conn, &settings(1)).unwrap()[0];
+	give_player_resources(&mut conn, &npc);
+	let barracks_id: i32 = building::table
+		.filter(building::name.eq("Barracks"))
+		.filter(building::faction.eq(FactionCode::Human))
+		.select(building::id)
+		.first(&mut conn)
+		.expect("Barracks not found");
+	let barracks = player_buildings::construct(
+		&mut conn,
+		NewPlayerBuilding {
+			player_id: npc,
+			building_id: barracks_id,
+			level: Some(3),
+			upgrade_finishes_at: None,
+			settlement_id: None,
+		},
+	)
+	.unwrap();
+
+	let trained = train_turn(
+		&mut conn,
+		&harness.app.job_queue,
+		&harness.app.modifier_system.cache,
+		&npc,
+		5,
+		&ResourceSettings::default(),
+	)
+	.unwrap();
+	assert!(trained >= 5);
+	let entries = training_queue::get_active_for_player(&mut conn, &npc).unwrap();
+	let at_barracks: Vec<_> = entries
+		.iter()
+		.filter(|entry| entry.building_id == barracks.id)
+		.collect();
+	assert_eq!(at_barracks.len(), 1);
+	assert_eq!(at_barracks[0].quantity, 5);
+
+	assert!(
+		train_turn(
+			&mut conn,
+			&harness.app.job_queue,
+			&harness.app.modifier_system.cache,
+			&npc,
+			0,
+			&ResourceSettings::default()
+		)
+		.is_err()
+	);
+}
@@ -0,0 +1,82 @@
+//! Integration tests for the table statistics.
+//!
+//! These tests cover:
+//! - Capturing the size of every tracked table
+//! - Pruning samples past their retention on the next capture
+//! - Grouping samples into a growth series per table
+
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::prelude::*;
+use empire::db::DbConn;
+use empire::game::table_stats::stats_operations::{
+	TABLE_STATS_RETENTION_DAYS, TRACKED_TABLES, capture, table_growth,
+};
+use empire::schema::table_stats as ts;
+
+use crate::common::TestHarness;
+
+fn insert_sample(conn: &mut DbConn, table: &str, rows: i64, bytes: i64, at: DateTime<Utc>) {
+	diesel::insert_into(ts::table)
+		.values((
+			ts::table_name.eq(table),
+			ts::row_count.eq(rows),
+			ts::total_bytes.eq(bytes),
+			ts::captured_at.eq(at),
+		))
+		.execute(conn)
+		.expect("Failed to insert table stats");
+}
+
+#[tokio::test]
+async fn test_capture_samples_every_tracked_table_and_prunes_old_samples() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let now = Utc::now();
+	let expired = now - TimeDelta::days(TABLE_STATS_RETENTION_DAYS + 1);
+	insert_sample(&mut conn, "job", 10, 8192, expired);
+
+	let summary = capture(&mut conn, now).unwrap();
+	assert_eq!(summary.pruned, 1);
+	assert_eq!(summary.captured.len(), TRACKED_TABLES.len());
+	for sample in &summary.captured {
+		assert!(TRACKED_TABLES.contains(&sample.table_name.as_str()));
+		assert!(sample.row_count >= 0);
+		assert!(
+			sample.total_bytes > 0,
+			"{} has no size on disk",
+			sample.table_name
+		);
+	}
+	let remaining: i64 = ts::table.count().get_result(&mut conn).unwrap();
+	assert_eq!(remaining, TRACKED_TABLES.len() as i64);
+}
+
+#[tokio::test]
+async fn test_growth_is_measured_between_the_first_and_last_sample() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let now = Utc::now();
+	insert_sample(&mut conn, "job", 500, 65_536, now - TimeDelta::days(40));
+	insert_sample(&mut conn, "job", 1_000, 81_920, now - TimeDelta::days(20));
+	insert_sample(&mut conn, "job", 1_500, 98_304, now - TimeDelta::days(10));
+	insert_sample(&mut conn, "job", 400, 90_112, now - TimeDelta::hours(1));
+	insert_sample(&mut conn, "player", 10, 16_384, now - TimeDelta::days(2));
+
+	let growth = table_growth(&mut conn, 30, None, now).unwrap();
+	let names: Vec<&str> = growth.iter().map(|t| t.table_name.as_str()).collect();
+	assert_eq!(names, vec!["job", "player"]);
+	// The sample from 40 days ago is outside the window, pruning shrank the job queue
+	assert_eq!(growth[0].samples.len(), 3);
+	assert_eq!(growth[0].row_growth, 400 - 1_000);
+	assert_eq!(growth[0].byte_growth, 90_112 - 81_920);
+	assert_eq!(growth[1].row_growth, 0, "A single sample has no growth");
+
+	let only_players = table_growth(&mut conn, 30, Some("player"), now).unwrap();
+	assert_eq!(only_players.len(), 1);
+	assert_eq!(only_players[0].table_name, "player");
+
+	for days in [0, 366] {
+		let err = table_growth(&mut conn, days, None, now).unwrap_err();
+		assert!(err.to_string().contains("Window must be between"));
+	}
+}
@@ -5,26 +5,40 @@
 //! - Viewing training queue
 //! - Completing training and receiving units
 //! - Cancelling training with refunds
+//! - Cancellation racing completion, exactly one of them wins
 //! - Validation error cases
+//! - Throttling of training starts per building
+//! - Training capacity following the building level
+//! - Training as many units as are affordable ("train to fill")
+//! - Starting trainings of several units at once, all of them or none
+//! - Sequential training queues, one entry training at a time
 
+use std::sync::Barrier;
+
+use bigdecimal::ToPrimitive;
+use chrono::{TimeDelta, Utc};
 use diesel::prelude::*;
-use empire::auth::utils::hash_password;
-use empire::db::{
-	DbConn, player_buildings, player_units, players, resources, training_queue, units,
-};
+use empire::db::{DbConn, player_buildings, player_units, resources, training_queue, units};
 use empire::domain::factions::FactionCode;
-use empire::domain::jobs::JobType;
+use empire::domain::jobs::{JobStatus, JobType};
+use empire::domain::message::MessageKind;
+use empire::domain::player::PlayerKey;
 use empire::domain::player::buildings::{NewPlayerBuilding, PlayerBuilding};
-use empire::domain::player::{NewPlayer, Player, PlayerKey, UserName};
-use empire::domain::unit::training::TrainingStatus;
+use empire::domain::unit::training::{TrainingMode, TrainingStatus};
 use empire::domain::unit::{Unit, UnitType};
+use empire::game::buildings::building_operations::{confirm_upgrade, upgrade_building};
+use empire::game::mail::mail_operations::list_inbox;
 use empire::game::units::training_operations::{
-	TrainingJobPayload, cancel_training, complete_training, get_available_units_for_building,
-	start_training,
+	MAX_TRAINING_STARTS_PER_WINDOW, TrainingJobPayload, TrainingOrder, TrainingStarted,
+	cancel_training, cancel_training_units, complete_training, estimate_queue,
+	get_available_units_for_building, max_affordable, set_training_mode, start_next_training,
+	start_training, start_training_batch, train_to_fill,
 };
-use empire::schema::{job, unit};
+use empire::game::units::upkeep_operations::get_population;
+use empire::schema::{job, training_queue as tq, unit};
 
 use crate::common::TestHarness;
+use crate::fixtures::{create_test_player, give_player_resources};
 
 // ============================================================================
 // Helper Functions
@@ -38,20 +52,6 @@ fn shorten_training_times(conn: &mut DbConn) {
 		.expect("Failed to shorten training times");
 }
 
-/// Create a test player with the specified faction.
-fn create_test_player(conn: &mut DbConn, faction: FactionCode) -> Player {
-	players::create(
-		conn,
-		NewPlayer {
-			name: UserName::parse(format!("test_player_{}", uuid::Uuid::new_v4())).unwrap(),
-			pwd_hash: hash_password(b"test1234").unwrap(),
-			email: None,
-			faction,
-		},
-	)
-	.expect("Failed to create test player")
-}
-
 /// Get a building by name for a specific faction.
 fn get_building_by_name(
 	conn: &mut DbConn,
@@ -82,6 +82,7 @@ fn construct_building_for_player(
 			building_id: bld.id,
 			level: Some(3),
 			upgrade_finishes_at: None,
+			settlement_id: None,
 		},
 	)
 	.expect("Failed to construct building")
@@ -105,20 +106,6 @@ fn get_cavalry_unit(conn: &mut DbConn) -> Unit {
 		.expect("No cavalry unit found")
 }
 
-/// Give a player plenty of resources for training.
-fn give_player_resources(conn: &mut DbConn, player_id: &PlayerKey) {
-	use empire::schema::player_resource::dsl as pr;
-	diesel::update(pr::player_resource.filter(pr::player_id.eq(player_id)))
-		.set((
-			pr::food.eq(10000),
-			pr::wood.eq(10000),
-			pr::stone.eq(10000),
-			pr::gold.eq(10000),
-		))
-		.execute(conn)
-		.expect("Failed to set player resources");
-}
-
 /// Get player's current resources.
 fn get_player_resources(conn: &mut DbConn, player_id: &PlayerKey) -> (i64, i64, i64, i64) {
 	let res = resources::get_by_player_id(conn, player_id).expect("Failed to get resources");
@@ -153,9 +140,14 @@ async fn test_start_training_happy_path() {
 
 	// Start training 5 infantry units
 	let quantity = 5;
-	let (entry, _completion_time) = start_training(
+	let TrainingStarted {
+		entry,
+		completion_time,
+		costs,
+	} = start_training(
 		&mut conn,
 		&app.job_queue,
+		&app.modifier_system.cache,
 		&player.id,
 		&barracks.id,
 		&infantry.id,
@@ -177,6 +169,7 @@ async fn test_start_training_happy_path() {
 	let (food_after, wood_after, _, _) = get_player_resources(&mut conn, &player.id);
 	assert_eq!(food_after, food_before - expected_food_cost);
 	assert_eq!(wood_after, wood_before - expected_wood_cost);
+	assert_eq!(costs, (expected_food_cost, expected_wood_cost, 0, 0));
 
 	// Assert: job was created in the database
 	let job_id = entry.job_id.unwrap();
@@ -185,6 +178,11 @@ async fn test_start_training_happy_path() {
 		.first(&mut conn)
 		.expect("Job not found");
 	assert_eq!(created_job.job_type, JobType::Training);
+
+	// Assert: the entry, the result and the job agree on the completion time
+	assert_eq!(entry.completes_at, completion_time);
+	assert_eq!(created_job.run_at, entry.completes_at);
+	assert!(entry.completes_at > entry.started_at);
 }
 
 #[tokio::test]
@@ -201,18 +199,20 @@ async fn test_get_training_queue() {
 	let infantry = get_infantry_unit(&mut conn);
 
 	// Start two training entries
-	let (entry1, _) = start_training(
+	let TrainingStarted { entry: entry1, .. } = start_training(
 		&mut conn,
 		&app.job_queue,
+		&app.modifier_system.cache,
 		&player.id,
 		&barracks.id,
 		&infantry.id,
 		3,
 	)
 	.expect("Failed to start first training");
-	let (entry2, _) = start_training(
+	let TrainingStarted { entry: entry2, .. } = start_training(
 		&mut conn,
 		&app.job_queue,
+		&app.modifier_system.cache,
 		&player.id,
 		&barracks.id,
 		&infantry.id,
@@ -255,9 +255,10 @@ async fn test_complete_training() {
 
 	// Start training
 	let quantity = 7;
-	let (entry, _) = start_training(
+	let TrainingStarted { entry, .. } = start_training(
 		&mut conn,
 		&app.job_queue,
+		&app.modifier_system.cache,
 		&player.id,
 		&barracks.id,
 		&infantry.id,
@@ -295,6 +296,15 @@ async fn test_complete_training() {
 		final_count_again, final_count,
 		"Idempotent call should not add more units"
 	);
+
+	// Assert: the player was told once, with the training in the details
+	let inbox = list_inbox(&mut conn, &player.id, false, None, None).unwrap();
+	assert_eq!(inbox.total, 1);
+	assert_eq!(inbox.messages[0].kind, MessageKind::TrainingComplete);
+	assert_eq!(
+		inbox.messages[0].details["training_id"],
+		entry.id.to_string()
+	);
 }
 
 #[tokio::test]
@@ -312,9 +322,10 @@ async fn test_cancel_training_full_refund() {
 
 	// Start training
 	let quantity = 5;
-	let (entry, _) = start_training(
+	let TrainingStarted { entry, .. } = start_training(
 		&mut conn,
 		&app.job_queue,
+		&app.modifier_system.cache,
 		&player.id,
 		&barracks.id,
 		&infantry.id,
@@ -346,6 +357,133 @@ async fn test_cancel_training_full_refund() {
 	assert!((wood_after_cancel - (wood_after_start + expected_refund_wood)).abs() <= 1);
 }
 
+#[tokio::test]
+async fn test_cancel_training_refund_uses_recorded_duration() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	// Setup
+	let player = create_test_player(&mut conn, FactionCode::Human);
+	give_player_resources(&mut conn, &player.id);
+	let barracks =
+		construct_building_for_player(&mut conn, &player.id, "Barracks", FactionCode::Human);
+	diesel::update(unit::table)
+		.set(unit::base_training_seconds.eq(100))
+		.execute(&mut conn)
+		.expect("Failed to set training times");
+	let infantry = get_infantry_unit(&mut conn);
+
+	let quantity = 2;
+	let TrainingStarted { entry, .. } = start_training(
+		&mut conn,
+		&app.job_queue,
+		&app.modifier_system.cache,
+		&player.id,
+		&barracks.id,
+		&infantry.id,
+		quantity,
+	)
+	.expect("Failed to start training");
+
+	// Assert: the applied modifier and per-unit time are recorded
+	let modifier = entry.training_modifier.to_f64().unwrap();
+	assert_eq!(entry.seconds_per_unit, (100.0 * modifier) as i64);
+	assert_eq!(
+		entry.duration().num_seconds(),
+		entry.seconds_per_unit * quantity
+	);
+
+	// Halfway through, after the unit got rebalanced to train much slower
+	diesel::update(tq::table.find(entry.id))
+		.set(tq::started_at.eq(Utc::now() - entry.duration() / 2))
+		.execute(&mut conn)
+		.expect("Failed to move training start");
+	diesel::update(unit::table)
+		.set(unit::base_training_seconds.eq(10_000))
+		.execute(&mut conn)
+		.expect("Failed to set training times");
+
+	let (food_before_cancel, wood_before_cancel, _, _) =
+		get_player_resources(&mut conn, &player.id);
+	cancel_training(&mut conn, &app.job_queue, &player.id, &entry.id)
+		.expect("Failed to cancel training");
+
+	// Assert: refund is 80% of the remaining half
+	let (food_after_cancel, wood_after_cancel, _, _) = get_player_resources(&mut conn, &player.id);
+	assert!((food_after_cancel - food_before_cancel - (20 * quantity * 2 / 5)).abs() <= 1);
+	assert!((wood_after_cancel - wood_before_cancel - (10 * quantity * 2 / 5)).abs() <= 1);
+}
+
+#[tokio::test]
+async fn test_cancel_part_of_training() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	// Setup
+	let player = create_test_player(&mut conn, FactionCode::Human);
+	give_player_resources(&mut conn, &player.id);
+	let barracks =
+		construct_building_for_player(&mut conn, &player.id, "Barracks", FactionCode::Human);
+	diesel::update(unit::table)
+		.set(unit::base_training_seconds.eq(100))
+		.execute(&mut conn)
+		.expect("Failed to set training times");
+	let infantry = get_infantry_unit(&mut conn);
+
+	let TrainingStarted { entry, .. } = start_training(
+		&mut conn,
+		&app.job_queue,
+		&app.modifier_system.cache,
+		&player.id,
+		&barracks.id,
+		&infantry.id,
+		4,
+	)
+	.expect("Failed to start training");
+
+	// Assert: invalid quantities are rejected
+	for (quantity, message) in [
+		(0, "must be positive"),
+		(5, "more units than are in training"),
+	] {
+		let err = cancel_training_units(&mut conn, &app.job_queue, &player.id, &entry.id, quantity)
+			.expect_err("Invalid quantity should be rejected");
+		assert!(err.to_string().contains(message), "{err}");
+	}
+
+	let (food_before, wood_before, _, _) = get_player_resources(&mut conn, &player.id);
+	let (reduced, refund) =
+		cancel_training_units(&mut conn, &app.job_queue, &player.id, &entry.id, 1)
+			.expect("Failed to cancel part of the training");
+
+	// Assert: three units keep training and finish one unit earlier
+	assert_eq!(reduced.status, TrainingStatus::InProgress);
+	assert_eq!(reduced.quantity, 3);
+	assert_eq!(
+		reduced.completes_at,
+		entry.started_at + TimeDelta::seconds(entry.seconds_per_unit * 3)
+	);
+	let job: empire::domain::jobs::Job = job::table
+		.find(entry.job_id.unwrap())
+		.first(&mut conn)
+		.expect("Job not found");
+	assert_eq!(job.run_at, reduced.completes_at);
+
+	// Assert: the cancelled unit had not started, so it is refunded at 80%
+	// Infantry costs: Food 20, Wood 10 per unit
+	assert_eq!(refund, (16, 8, 0, 0));
+	let (food_after, wood_after, _, _) = get_player_resources(&mut conn, &player.id);
+	assert_eq!(
+		(food_after, wood_after),
+		(food_before + 16, wood_before + 8)
+	);
+
+	// Cancelling the rest cancels the entry
+	let (cancelled, _) = cancel_training_units(&mut conn, &app.job_queue, &player.id, &entry.id, 3)
+		.expect("Failed to cancel the rest of the training");
+	assert_eq!(cancelled.status, TrainingStatus::Cancelled);
+}
+
 #[tokio::test]
 async fn test_get_available_units_for_building() {
 	let TestHarness { db_pool, .. } = TestHarness::new();
@@ -414,6 +552,7 @@ async fn test_start_training_invalid_quantity() {
 	let result = start_training(
 		&mut conn,
 		&app.job_queue,
+		&app.modifier_system.cache,
 		&player.id,
 		&barracks.id,
 		&infantry.id,
@@ -448,6 +587,7 @@ async fn test_start_training_building_not_owned() {
 	let result = start_training(
 		&mut conn,
 		&app.job_queue,
+		&app.modifier_system.cache,
 		&player1.id,
 		&barracks.id,
 		&infantry.id,
@@ -473,6 +613,7 @@ async fn test_start_training_wrong_building_type() {
 	let result = start_training(
 		&mut conn,
 		&app.job_queue,
+		&app.modifier_system.cache,
 		&player.id,
 		&barracks.id,
 		&cavalry.id,
@@ -512,6 +653,7 @@ async fn test_start_training_insufficient_resources() {
 	let result = start_training(
 		&mut conn,
 		&app.job_queue,
+		&app.modifier_system.cache,
 		&player.id,
 		&barracks.id,
 		&infantry.id,
@@ -536,24 +678,26 @@ async fn test_start_training_queue_full() {
 	let queue_state = training_queue::get_queue_status(&mut conn, &barracks.id)
 		.expect("Failed to fetch queue status for barracks.");
 
-	// Fill the queue (max 5 per building at level 10)
+	// Fill the queue, its capacity comes from the building level
 	for i in 0..queue_state.capacity {
 		start_training(
 			&mut conn,
 			&app.job_queue,
+			&app.modifier_system.cache,
 			&player.id,
 			&barracks.id,
 			&infantry.id,
 			1,
 		)
 		.unwrap_or_else(|_| panic!("Failed to start training {}", i + 1));
-		// Discards (entry, completion_time) tuple - we only care that it succeeds
+		// Discards the started training - we only care that it succeeds
 	}
 
 	// Try to add one more - should fail
 	let result = start_training(
 		&mut conn,
 		&app.job_queue,
+		&app.modifier_system.cache,
 		&player.id,
 		&barracks.id,
 		&infantry.id,
@@ -563,6 +707,61 @@ async fn test_start_training_queue_full() {
 	assert!(result.is_err(), "Should fail when queue is full");
 }
 
+#[tokio::test]
+async fn test_training_capacity_grows_with_building_level() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	let player = create_test_player(&mut conn, FactionCode::Human);
+	give_player_resources(&mut conn, &player.id);
+	shorten_training_times(&mut conn);
+	let infantry = get_infantry_unit(&mut conn);
+	let bld = get_building_by_name(&mut conn, "Barracks", FactionCode::Human);
+	let barracks = player_buildings::construct(
+		&mut conn,
+		NewPlayerBuilding {
+			player_id: player.id,
+			building_id: bld.id,
+			level: Some(2),
+			upgrade_finishes_at: None,
+			settlement_id: None,
+		},
+	)
+	.expect("Failed to construct building");
+	let train = |conn: &mut DbConn| {
+		start_training(
+			conn,
+			&app.job_queue,
+			&app.modifier_system.cache,
+			&player.id,
+			&barracks.id,
+			&infantry.id,
+			1,
+		)
+	};
+
+	// Level 2 trains one batch at a time
+	let queue_state = training_queue::get_queue_status(&mut conn, &barracks.id).unwrap();
+	assert_eq!(queue_state.capacity, 1);
+	train(&mut conn).expect("Failed to fill the training slot");
+	let err = train(&mut conn).expect_err("The queue should be full at level 2");
+	assert!(err.to_string().contains("queue is full"), "{err}");
+
+	// Level 3 adds a second slot as soon as the upgrade is confirmed
+	upgrade_building(&mut conn, &app.job_queue, &barracks.id).expect("Failed to start upgrade");
+	let eta = Utc::now() - TimeDelta::seconds(1);
+	player_buildings::set_upgrade_eta(&mut conn, &barracks.id, Some(eta)).unwrap();
+	let upgraded = confirm_upgrade(&mut conn, &barracks.id).expect("Failed to confirm upgrade");
+	assert_eq!(upgraded.level, 3);
+
+	let queue_state = training_queue::get_queue_status(&mut conn, &barracks.id).unwrap();
+	assert_eq!(queue_state.capacity, 2);
+	assert_eq!(queue_state.active_count, 1);
+	train(&mut conn).expect("The upgrade should free a training slot");
+	let err = train(&mut conn).expect_err("The queue should be full at level 3");
+	assert!(err.to_string().contains("queue is full"), "{err}");
+}
+
 #[tokio::test]
 async fn test_cancel_training_already_completed() {
 	let TestHarness { app, db_pool, .. } = TestHarness::new();
@@ -578,9 +777,10 @@ async fn test_cancel_training_already_completed() {
 
 	// Start and complete training
 	let quantity = 3;
-	let (entry, _) = start_training(
+	let TrainingStarted { entry, .. } = start_training(
 		&mut conn,
 		&app.job_queue,
+		&app.modifier_system.cache,
 		&player.id,
 		&barracks.id,
 		&infantry.id,
@@ -603,3 +803,729 @@ async fn test_cancel_training_already_completed() {
 		"Should fail when cancelling completed training"
 	);
 }
+
+#[tokio::test]
+async fn test_cancel_training_after_job_completed() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	let player = create_test_player(&mut conn, FactionCode::Human);
+	give_player_resources(&mut conn, &player.id);
+	let barracks =
+		construct_building_for_player(&mut conn, &player.id, "Barracks", FactionCode::Human);
+	let infantry = get_infantry_unit(&mut conn);
+	let TrainingStarted { entry, .. } = start_training(
+		&mut conn,
+		&app.job_queue,
+		&app.modifier_system.cache,
+		&player.id,
+		&barracks.id,
+		&infantry.id,
+		2,
+	)
+	.expect("Failed to start training");
+	let resources_before = get_player_resources(&mut conn, &player.id);
+
+	// The completion job ran, the entry just has not caught up yet
+	diesel::update(job::table.find(entry.job_id.unwrap()))
+		.set(job::status.eq(JobStatus::Completed))
+		.execute(&mut conn)
+		.unwrap();
+	let err = cancel_training(&mut conn, &app.job_queue, &player.id, &entry.id)
+		.expect_err("A training whose job completed cannot be cancelled");
+	assert!(err.to_string().contains("cannot be cancelled"), "{err}");
+	assert_eq!(
+		get_player_resources(&mut conn, &player.id),
+		resources_before,
+		"Nothing should be refunded"
+	);
+	let entry = training_queue::get_by_id(&mut conn, &entry.id).unwrap();
+	assert_eq!(entry.status, TrainingStatus::InProgress);
+}
+
+#[tokio::test]
+async fn test_cancel_racing_completion_grants_units_or_refund() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	let player = create_test_player(&mut conn, FactionCode::Human);
+	give_player_resources(&mut conn, &player.id);
+	let barracks =
+		construct_building_for_player(&mut conn, &player.id, "Barracks", FactionCode::Human);
+	let infantry = get_infantry_unit(&mut conn);
+	let quantity = 2;
+
+	for round in 0..5 {
+		let TrainingStarted { entry, .. } = start_training(
+			&mut conn,
+			&app.job_queue,
+			&app.modifier_system.cache,
+			&player.id,
+			&barracks.id,
+			&infantry.id,
+			quantity,
+		)
+		.expect("Failed to start training");
+		let units_before =
+			player_units::get_player_unit_count(&mut conn, &player.id, &infantry.id).unwrap();
+		let resources_before = get_player_resources(&mut conn, &player.id);
+		let payload = TrainingJobPayload {
+			training_queue_entry_id: entry.id,
+			player_id: player.id,
+			unit_id: infantry.id,
+			quantity,
+		};
+
+		// Completion and cancellation start at the same time on their own connections
+		let barrier = Barrier::new(2);
+		let (completed, cancelled) = std::thread::scope(|scope| {
+			let completion = scope.spawn(|| {
+				let mut conn = db_pool.get().unwrap();
+				barrier.wait();
+				complete_training(&mut conn, &payload)
+			});
+			let cancellation = scope.spawn(|| {
+				let mut conn = db_pool.get().unwrap();
+				barrier.wait();
+				cancel_training(&mut conn, &app.job_queue, &player.id, &entry.id)
+			});
+			(completion.join().unwrap(), cancellation.join().unwrap())
+		});
+
+		let completed = completed.expect("Completion should never fail");
+		let units_after =
+			player_units::get_player_unit_count(&mut conn, &player.id, &infantry.id).unwrap();
+		let resources_after = get_player_resources(&mut conn, &player.id);
+		match cancelled {
+			Ok((cancelled, refund)) => {
+				assert_eq!(cancelled.status, TrainingStatus::Cancelled, "round {round}");
+				assert_eq!(completed.status, TrainingStatus::Cancelled, "round {round}");
+				assert_eq!(units_after, units_before, "round {round}: no units granted");
+				assert_eq!(resources_after.0, resources_before.0 + refund.0);
+				assert_eq!(resources_after.1, resources_before.1 + refund.1);
+			}
+			Err(err) => {
+				assert!(err.to_string().contains("cannot be cancelled"), "{err}");
+				assert_eq!(completed.status, TrainingStatus::Completed, "round {round}");
+				assert_eq!(units_after, units_before + quantity, "round {round}");
+				assert_eq!(
+					resources_after, resources_before,
+					"round {round}: no refund"
+				);
+			}
+		}
+	}
+}
+
+#[tokio::test]
+async fn test_start_training_throttled_per_building() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	let player = create_test_player(&mut conn, FactionCode::Human);
+	give_player_resources(&mut conn, &player.id);
+	let barracks =
+		construct_building_for_player(&mut conn, &player.id, "Barracks", FactionCode::Human);
+	let stables =
+		construct_building_for_player(&mut conn, &player.id, "Stables", FactionCode::Human);
+	let infantry = get_infantry_unit(&mut conn);
+	let cavalry = get_cavalry_unit(&mut conn);
+
+	// Cancelled trainings still count towards the limit
+	for _ in 0..MAX_TRAINING_STARTS_PER_WINDOW {
+		let TrainingStarted { entry, .. } = start_training(
+			&mut conn,
+			&app.job_queue,
+			&app.modifier_system.cache,
+			&player.id,
+			&barracks.id,
+			&infantry.id,
+			1,
+		)
+		.expect("Failed to start training");
+		cancel_training(&mut conn, &app.job_queue, &player.id, &entry.id)
+			.expect("Failed to cancel training");
+	}
+
+	let result = start_training(
+		&mut conn,
+		&app.job_queue,
+		&app.modifier_system.cache,
+		&player.id,
+		&barracks.id,
+		&infantry.id,
+		1,
+	);
+	let err = result.expect_err("Training should be throttled");
+	assert!(
+		err.to_string().contains("Too many trainings"),
+		"Error should mention the throttle: {}",
+		err
+	);
+
+	// Other buildings of the same player are throttled separately
+	start_training(
+		&mut conn,
+		&app.job_queue,
+		&app.modifier_system.cache,
+		&player.id,
+		&stables.id,
+		&cavalry.id,
+		1,
+	)
+	.expect("Stables should not be throttled");
+}
+
+#[tokio::test]
+async fn test_train_to_fill_trains_max_affordable() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	let player = create_test_player(&mut conn, FactionCode::Human);
+	give_player_resources(&mut conn, &player.id);
+	let barracks =
+		construct_building_for_player(&mut conn, &player.id, "Barracks", FactionCode::Human);
+	shorten_training_times(&mut conn);
+	let infantry = get_infantry_unit(&mut conn);
+
+	// The cap wins while more units are affordable
+	let capped = train_to_fill(
+		&mut conn,
+		&app.job_queue,
+		&app.modifier_system.cache,
+		&player.id,
+		&barracks.id,
+		&infantry.id,
+		Some(7),
+	)
+	.expect("Failed to train capped quantity");
+	assert_eq!(capped.entry.quantity, 7);
+
+	// Without a cap, the quantity matches what the availability endpoint reports, as far as
+	// the free population houses the units
+	let available = get_available_units_for_building(&mut conn, &player.id, &barracks.id)
+		.expect("Failed to get available units");
+	assert!(available.iter().any(|unit| unit.id == infantry.id));
+	let res = resources::get_by_player_id(&mut conn, &player.id).unwrap();
+	let affordable = max_affordable(&res, (20, 10, 0, 0));
+	let population = get_population(&mut conn, &player.id).unwrap();
+	let expected = affordable.min(population.housable(&infantry));
+	assert!(expected < affordable, "The population should be the limit");
+	let filled = train_to_fill(
+		&mut conn,
+		&app.job_queue,
+		&app.modifier_system.cache,
+		&player.id,
+		&barracks.id,
+		&infantry.id,
+		None,
+	)
+	.expect("Failed to train to fill");
+	assert_eq!(filled.entry.quantity, expected);
+	assert_eq!(filled.costs, (20 * expected, 10 * expected, 0, 0));
+	let population = get_population(&mut conn, &player.id).unwrap();
+	assert_eq!(
+		population.housable(&infantry),
+		0,
+		"No further unit should be housable"
+	);
+}
+
+#[tokio::test]
+async fn test_train_to_fill_validation() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	let player = create_test_player(&mut conn, FactionCode::Human);
+	let barracks =
+		construct_building_for_player(&mut conn, &player.id, "Barracks", FactionCode::Human);
+	shorten_training_times(&mut conn);
+	let infantry = get_infantry_unit(&mut conn);
+	let fill = |conn: &mut DbConn, max_quantity| {
+		train_to_fill(
+			conn,
+			&app.job_queue,
+			&app.modifier_system.cache,
+			&player.id,
+			&barracks.id,
+			&infantry.id,
+			max_quantity,
+		)
+	};
+
+	let err = fill(&mut conn, Some(0)).expect_err("A zero cap should be rejected");
+	assert!(err.to_string().contains("positive"), "{err}");
+
+	{
+		use empire::schema::player_resource::dsl as pr;
+		diesel::update(pr::player_resource.filter(pr::player_id.eq(player.id)))
+			.set(pr::food.eq(0))
+			.execute(&mut conn)
+			.unwrap();
+	}
+	let err = fill(&mut conn, None).expect_err("Nothing should be affordable");
+	assert!(err.to_string().contains("Not enough resources"), "{err}");
+
+	give_player_resources(&mut conn, &player.id);
+	let queue_state = training_queue::get_queue_status(&mut conn, &barracks.id).unwrap();
+	for _ in 0..queue_state.capacity {
+		fill(&mut conn, Some(1)).expect("Failed to fill a training slot");
+	}
+	let err = fill(&mut conn, None).expect_err("The queue should be full");
+	assert!(err.to_string().contains("queue is full"), "{err}");
+}
+
+// ============================================================================
+// Batch Training Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_start_training_batch_across_buildings() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	let player = create_test_player(&mut conn, FactionCode::Human);
+	give_player_resources(&mut conn, &player.id);
+	let before = get_player_resources(&mut conn, &player.id);
+	let barracks =
+		construct_building_for_player(&mut conn, &player.id, "Barracks", FactionCode::Human);
+	let stables =
+		construct_building_for_player(&mut conn, &player.id, "Stables", FactionCode::Human);
+	shorten_training_times(&mut conn);
+	let infantry = get_infantry_unit(&mut conn);
+	let cavalry = get_cavalry_unit(&mut conn);
+
+	let orders = [
+		TrainingOrder {
+			building_id: barracks.id,
+			unit_id: infantry.id,
+			quantity: 5,
+		},
+		TrainingOrder {
+			building_id: barracks.id,
+			unit_id: infantry.id,
+			quantity: 1,
+		},
+		TrainingOrder {
+			building_id: stables.id,
+			unit_id: cavalry.id,
+			quantity: 2,
+		},
+	];
+	let started = start_training_batch(
+		&mut conn,
+		&app.job_queue,
+		&app.modifier_system.cache,
+		&player.id,
+		&orders,
+	)
+	.expect("Failed to start the batch");
+
+	assert_eq!(started.len(), 3);
+	for (started, order) in started.iter().zip(&orders) {
+		assert_eq!(started.entry.building_id, order.building_id);
+		assert_eq!(started.entry.quantity, order.quantity);
+		assert_eq!(started.entry.status, TrainingStatus::InProgress);
+		assert!(started.entry.job_id.is_some(), "Job ID should be set");
+	}
+	// Infantry: food 20, wood 10 each; cavalry: food 30, gold 15 each
+	assert_eq!(
+		get_player_resources(&mut conn, &player.id),
+		(before.0 - 180, before.1 - 60, before.2, before.3 - 30)
+	);
+}
+
+#[tokio::test]
+async fn test_start_training_batch_fails_as_a_whole() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	let player = create_test_player(&mut conn, FactionCode::Human);
+	give_player_resources(&mut conn, &player.id);
+	let before = get_player_resources(&mut conn, &player.id);
+	let barracks =
+		construct_building_for_player(&mut conn, &player.id, "Barracks", FactionCode::Human);
+	let stables =
+		construct_building_for_player(&mut conn, &player.id, "Stables", FactionCode::Human);
+	shorten_training_times(&mut conn);
+	let infantry = get_infantry_unit(&mut conn);
+	let cavalry = get_cavalry_unit(&mut conn);
+	let capacity = training_queue::get_queue_status(&mut conn, &barracks.id)
+		.unwrap()
+		.capacity;
+	let batch = |conn: &mut DbConn, orders: &[TrainingOrder]| {
+		start_training_batch(
+			conn,
+			&app.job_queue,
+			&app.modifier_system.cache,
+			&player.id,
+			orders,
+		)
+	};
+	let cavalry_order = TrainingOrder {
+		building_id: stables.id,
+		unit_id: cavalry.id,
+		quantity: 1,
+	};
+	let infantry_order = TrainingOrder {
+		building_id: barracks.id,
+		unit_id: infantry.id,
+		quantity: 1,
+	};
+
+	let err = batch(&mut conn, &[]).expect_err("An empty batch should be rejected");
+	assert!(err.to_string().contains("positive"), "{err}");
+
+	// One order more than the Barracks has slots fails the cavalry as well
+	let mut orders = vec![cavalry_order];
+	orders.extend((0..=capacity).map(|_| infantry_order));
+	let err = batch(&mut conn, &orders).expect_err("The Barracks should be full");
+	assert!(err.to_string().contains("queue is full"), "{err}");
+
+	// A unit the building cannot train fails the whole batch
+	let wrong_building = TrainingOrder {
+		building_id: barracks.id,
+		unit_id: cavalry.id,
+		quantity: 1,
+	};
+	batch(&mut conn, &[cavalry_order, wrong_building])
+		.expect_err("Cavalry cannot be trained in the Barracks");
+
+	// Affordable on their own, but not together
+	{
+		use empire::schema::player_resource::dsl as pr;
+		diesel::update(pr::player_resource.filter(pr::player_id.eq(player.id)))
+			.set(pr::food.eq(40))
+			.execute(&mut conn)
+			.unwrap();
+	}
+	let err = batch(&mut conn, &[cavalry_order, infantry_order])
+		.expect_err("The batch should be unaffordable");
+	assert!(err.to_string().contains("Not enough resources"), "{err}");
+
+	assert_eq!(
+		get_player_resources(&mut conn, &player.id),
+		(40, before.1, before.2, before.3),
+		"Nothing should be deducted"
+	);
+	let queued = training_queue::get_active_for_player(&mut conn, &player.id).unwrap();
+	assert!(queued.is_empty(), "Nothing should be queued");
+}
+
+/// Construct a Barracks of the given level that trains its queue sequentially.
+fn construct_sequential_barracks(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	level: i32,
+) -> PlayerBuilding {
+	let bld = get_building_by_name(conn, "Barracks", FactionCode::Human);
+	let barracks = player_buildings::construct(
+		conn,
+		NewPlayerBuilding {
+			player_id: *player_id,
+			building_id: bld.id,
+			level: Some(level),
+			upgrade_finishes_at: None,
+			settlement_id: None,
+		},
+	)
+	.expect("Failed to construct building");
+	set_training_mode(conn, player_id, &barracks.id, TrainingMode::Sequential)
+		.expect("Failed to switch to sequential training")
+}
+
+#[tokio::test]
+async fn test_sequential_training_runs_one_entry_at_a_time() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	let player = create_test_player(&mut conn, FactionCode::Human);
+	give_player_resources(&mut conn, &player.id);
+	shorten_training_times(&mut conn);
+	let infantry = get_infantry_unit(&mut conn);
+	let barracks = construct_sequential_barracks(&mut conn, &player.id, 3);
+	let train = |conn: &mut DbConn, quantity| {
+		start_training(
+			conn,
+			&app.job_queue,
+			&app.modifier_system.cache,
+			&player.id,
+			&barracks.id,
+			&infantry.id,
+			quantity,
+		)
+		.expect("Failed to start training")
+	};
+
+	let first = train(&mut conn, 2);
+	let second = train(&mut conn, 3);
+	assert_eq!(first.entry.status, TrainingStatus::InProgress);
+	assert!(first.entry.job_id.is_some(), "Job ID should be set");
+	assert_eq!(second.entry.status, TrainingStatus::Pending);
+	assert!(
+		second.entry.job_id.is_none(),
+		"A pending training is not scheduled yet"
+	);
+	assert_eq!(
+		second.completion_time,
+		first.completion_time + second.entry.duration()
+	);
+
+	// The estimates chain the pending training after the one in progress
+	let active = training_queue::get_active_for_building(&mut conn, &barracks.id).unwrap();
+	let estimates = estimate_queue(&active, Utc::now());
+	assert_eq!(estimates[&second.entry.id].starts_at, first.completion_time);
+	assert_eq!(
+		estimates[&second.entry.id].completes_at,
+		second.completion_time
+	);
+
+	// Nothing starts while the first training is in progress
+	let next = start_next_training(&mut conn, &app.job_queue, &barracks.id).unwrap();
+	assert!(next.is_none(), "The queue is still busy");
+
+	let payload = TrainingJobPayload {
+		training_queue_entry_id: first.entry.id,
+		player_id: player.id,
+		unit_id: infantry.id,
+		quantity: 2,
+	};
+	complete_training(&mut conn, &payload).expect("Failed to complete training");
+	let next = start_next_training(&mut conn, &app.job_queue, &barracks.id)
+		.unwrap()
+		.expect("The pending training should start");
+	assert_eq!(next.id, second.entry.id);
+	assert_eq!(next.status, TrainingStatus::InProgress);
+	assert!(next.job_id.is_some(), "Job ID should be set once started");
+	assert!(next.started_at > second.entry.started_at);
+	assert_eq!(next.completes_at, next.started_at + next.duration());
+
+	let job_status: JobStatus = job::table
+		.find(next.job_id.unwrap())
+		.select(job::status)
+		.first(&mut conn)
+		.unwrap();
+	assert_eq!(job_status, JobStatus::Pending);
+}
+
+#[tokio::test]
+async fn test_sequential_batch_queues_orders_back_to_back() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	let player = create_test_player(&mut conn, FactionCode::Human);
+	give_player_resources(&mut conn, &player.id);
+	shorten_training_times(&mut conn);
+	let infantry = get_infantry_unit(&mut conn);
+	let barracks = construct_sequential_barracks(&mut conn, &player.id, 3);
+
+	let orders = [
+		TrainingOrder {
+			building_id: barracks.id,
+			unit_id: infantry.id,
+			quantity: 4,
+		},
+		TrainingOrder {
+			building_id: barracks.id,
+			unit_id: infantry.id,
+			quantity: 2,
+		},
+	];
+	let started = start_training_batch(
+		&mut conn,
+		&app.job_queue,
+		&app.modifier_system.cache,
+		&player.id,
+		&orders,
+	)
+	.expect("Failed to start the batch");
+
+	assert_eq!(started[0].entry.status, TrainingStatus::InProgress);
+	assert!(started[0].entry.job_id.is_some(), "Job ID should be set");
+	assert_eq!(started[1].entry.status, TrainingStatus::Pending);
+	assert!(started[1].entry.job_id.is_none());
+	assert_eq!(
+		started[1].completion_time,
+		started[0].completion_time + started[1].entry.duration()
+	);
+}
+
+#[tokio::test]
+async fn test_cancelling_sequential_training_starts_the_next() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	let player = create_test_player(&mut conn, FactionCode::Human);
+	give_player_resources(&mut conn, &player.id);
+	shorten_training_times(&mut conn);
+	let infantry = get_infantry_unit(&mut conn);
+	let barracks = construct_sequential_barracks(&mut conn, &player.id, 5);
+	let train = |conn: &mut DbConn| {
+		start_training(
+			conn,
+			&app.job_queue,
+			&app.modifier_system.cache,
+			&player.id,
+			&barracks.id,
+			&infantry.id,
+			5,
+		)
+		.expect("Failed to start training")
+		.entry
+	};
+	let first = train(&mut conn);
+	let second = train(&mut conn);
+	let third = train(&mut conn);
+
+	// A pending training has not progressed, it is refunded in full
+	let (_, refund) = cancel_training(&mut conn, &app.job_queue, &player.id, &third.id)
+		.expect("Failed to cancel pending training");
+	// Infantry: food 20, wood 10 each, 80% back
+	assert_eq!(refund, (80, 40, 0, 0));
+	let second_now = training_queue::get_by_id(&mut conn, &second.id).unwrap();
+	assert_eq!(second_now.status, TrainingStatus::Pending);
+
+	// Cancelling the training in progress moves the queue on
+	cancel_training(&mut conn, &app.job_queue, &player.id, &first.id)
+		.expect("Failed to cancel training");
+	let second_now = training_queue::get_by_id(&mut conn, &second.id).unwrap();
+	assert_eq!(second_now.status, TrainingStatus::InProgress);
+	assert!(
+		second_now.job_id.is_some(),
+		"Job ID should be set once started"
+	);
+}
+
+#[tokio::test]
+async fn test_training_mode_changes_only_with_empty_queue() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	let player = create_test_player(&mut conn, FactionCode::Human);
+	give_player_resources(&mut conn, &player.id);
+	shorten_training_times(&mut conn);
+	let infantry = get_infantry_unit(&mut conn);
+	let barracks =
+		construct_building_for_player(&mut conn, &player.id, "Barracks", FactionCode::Human);
+	assert_eq!(barracks.training_mode, TrainingMode::Parallel);
+
+	let started = start_training(
+		&mut conn,
+		&app.job_queue,
+		&app.modifier_system.cache,
+		&player.id,
+		&barracks.id,
+		&infantry.id,
+		1,
+	)
+	.expect("Failed to start training");
+	let err = set_training_mode(
+		&mut conn,
+		&player.id,
+		&barracks.id,
+		TrainingMode::Sequential,
+	)
+	.expect_err("The queue is not empty");
+	assert!(err.to_string().contains("queue is empty"), "{err}");
+
+	cancel_training(&mut conn, &app.job_queue, &player.id, &started.entry.id)
+		.expect("Failed to cancel training");
+	let updated = set_training_mode(
+		&mut conn,
+		&player.id,
+		&barracks.id,
+		TrainingMode::Sequential,
+	)
+	.expect("Failed to switch to sequential training");
+	assert_eq!(updated.training_mode, TrainingMode::Sequential);
+
+	// Other players cannot change the building
+	let other = create_test_player(&mut conn, FactionCode::Human);
+	let err = set_training_mode(&mut conn, &other.id, &barracks.id, TrainingMode::Parallel)
+		.expect_err("The building belongs to another player");
+	assert!(err.to_string().contains("not found"), "{err}");
+}
+
+#[tokio::test]
+async fn test_concurrent_partial_cancels_refund_each_unit_once() {
+	let TestHarness { app, db_pool, .. } = TestHarness::new();
+	let mut conn = db_pool.get().unwrap();
+
+	let player = create_test_player(&mut conn, FactionCode::Human);
+	give_player_resources(&mut conn, &player.id);
+	let barracks =
+		construct_building_for_player(&mut conn, &player.id, "Barracks", FactionCode::Human);
+	diesel::update(unit::table)
+		.set(unit::base_training_seconds.eq(100))
+		.execute(&mut conn)
+		.expect("Failed to set training times");
+	let infantry = get_infantry_unit(&mut conn);
+	let quantity = 4;
+
+	for round in 0..5 {
+		let TrainingStarted { entry, .. } = start_training(
+			&mut conn,
+			&app.job_queue,
+			&app.modifier_system.cache,
+			&player.id,
+			&barracks.id,
+			&infantry.id,
+			quantity,
+		)
+		.expect("Failed to start training");
+		let resources_before = get_player_resources(&mut conn, &player.id);
+
+		// Two partial cancellations start at the same time on their own connections
+		let barrier = Barrier::new(2);
+		let results = std::thread::scope(|scope| {
+			let cancellations: Vec<_> = (0..2)
+				.map(|_| {
+					scope.spawn(|| {
+						let mut conn = db_pool.get().unwrap();
+						barrier.wait();
+						cancel_training_units(&mut conn, &app.job_queue, &player.id, &entry.id, 1)
+					})
+				})
+				.collect();
+			cancellations
+				.into_iter()
+				.map(|cancellation| cancellation.join().unwrap())
+				.collect::<Vec<_>>()
+		});
+
+		let mut cancelled_units = 0;
+		let mut refunded = (0, 0, 0, 0);
+		for result in results {
+			match result {
+				Ok((_, refund)) => {
+					// Infantry costs: Food 20, Wood 10 per unit, refunded at 80% at most
+					assert!(refund.0 <= 16 && refund.1 <= 8, "round {round}: {refund:?}");
+					cancelled_units += 1;
+					refunded.0 += refund.0;
+					refunded.1 += refund.1;
+				}
+				Err(err) => assert!(err.to_string().contains("cancel"), "{err}"),
+			}
+		}
+		assert!(cancelled_units >= 1, "round {round}: one cancellation wins");
+
+		// Assert: every refund is paid for a unit taken off the batch
+		let current = training_queue::get_by_id(&mut conn, &entry.id).unwrap();
+		assert_eq!(
+			current.quantity,
+			quantity - cancelled_units,
+			"round {round}"
+		);
+		let resources_after = get_player_resources(&mut conn, &player.id);
+		assert_eq!(resources_after.0, resources_before.0 + refunded.0);
+		assert_eq!(resources_after.1, resources_before.1 + refunded.1);
+		let job: empire::domain::jobs::Job = job::table
+			.find(entry.job_id.unwrap())
+			.first(&mut conn)
+			.expect("Job not found");
+		assert_eq!(job.run_at, current.completes_at, "round {round}");
+
+		cancel_training(&mut conn, &app.job_queue, &player.id, &entry.id)
+			.expect("Failed to cancel the rest of the training");
+	}
+}
@@ -0,0 +1,90 @@
+//! Integration tests for disbanding units.
+//!
+//! These tests cover:
+//! - Disbanding refunds a share of the unit costs to the capital and frees their upkeep
+//! - Upgraded units refund their training and upgrade costs
+//! - Quantities are validated against the units the player owns
+
+use empire::configuration::UnitSettings;
+use empire::db::{DbConn, player_units, resources, units};
+use empire::domain::factions::FactionCode;
+use empire::domain::player::PlayerKey;
+use empire::domain::unit::Unit;
+use empire::game::units::disband_operations::disband_units;
+
+use crate::common::TestHarness;
+
+const SETTINGS: UnitSettings = UnitSettings {
+	disband_refund_percent: 50,
+};
+
+fn unit(conn: &mut DbConn, name: &str) -> Unit {
+	units::find_by_name(conn, name)
+		.unwrap()
+		.unwrap_or_else(|| panic!("{name} not seeded"))
+}
+
+fn stored(conn: &mut DbConn, player_id: &PlayerKey) -> (i64, i64, i64, i64) {
+	let res = resources::get_by_player_id(conn, player_id).unwrap();
+	(res.food, res.wood, res.stone, res.gold)
+}
+
+#[tokio::test]
+async fn test_disbanding_refunds_costs_and_frees_upkeep() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("demobilizer", Some(FactionCode::Human));
+	let infantry = unit(&mut conn, "Infantry");
+	player_units::add_units(&mut conn, &player.id, &infantry.id, 10).unwrap();
+	let upkeep_before = player_units::get_upkeep(&mut conn, &player.id).unwrap();
+	let before = stored(&mut conn, &player.id);
+
+	for quantity in [0, -1] {
+		let err =
+			disband_units(&mut conn, &SETTINGS, &player.id, &infantry.id, quantity).unwrap_err();
+		assert!(err.to_string().contains("Quantity must be positive"));
+	}
+	let err = disband_units(&mut conn, &SETTINGS, &player.id, &infantry.id, 11).unwrap_err();
+	assert!(err.to_string().contains("Not enough units"));
+	assert_eq!(stored(&mut conn, &player.id), before, "Nothing refunded");
+
+	// Infantry costs food 20, wood 10 each, half of it comes back
+	let disbanded = disband_units(&mut conn, &SETTINGS, &player.id, &infantry.id, 4).unwrap();
+	assert_eq!(disbanded.remaining, 6);
+	assert_eq!(disbanded.refund, (40, 20, 0, 0));
+	assert_eq!(
+		stored(&mut conn, &player.id),
+		(before.0 + 40, before.1 + 20, before.2, before.3)
+	);
+
+	let upkeep_after = player_units::get_upkeep(&mut conn, &player.id).unwrap();
+	assert_eq!(
+		upkeep_before.food_per_hour - upkeep_after.food_per_hour,
+		4 * infantry.food_upkeep
+	);
+	assert_eq!(
+		upkeep_before.population - upkeep_after.population,
+		4 * infantry.population
+	);
+
+	// The whole army can be disbanded, nothing is refunded at 0%
+	let none = UnitSettings {
+		disband_refund_percent: 0,
+	};
+	let disbanded = disband_units(&mut conn, &none, &player.id, &infantry.id, 6).unwrap();
+	assert_eq!(disbanded.remaining, 0);
+	assert_eq!(disbanded.refund, (0, 0, 0, 0));
+}
+
+#[tokio::test]
+async fn test_disbanded_veterans_refund_their_upgrades() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("veteran", Some(FactionCode::Human));
+	let veteran = unit(&mut conn, "Veteran Infantry");
+	player_units::add_units(&mut conn, &player.id, &veteran.id, 2).unwrap();
+
+	// Training (food 20, wood 10) and upgrade (food 20, wood 10, gold 10) of each veteran
+	let disbanded = disband_units(&mut conn, &SETTINGS, &player.id, &veteran.id, 2).unwrap();
+	assert_eq!(disbanded.refund, (40, 20, 0, 10));
+}
@@ -0,0 +1,153 @@
+//! Integration tests for unit upgrades.
+//!
+//! These tests cover:
+//! - Upgrades need the Blacksmith and military research of the capital at their levels
+//! - Upgrading swaps units for their veterans, charges the capital, and adds the tier bonus
+//!   to the power score
+//! - Upgraded units are not offered for training and cannot be trained
+
+use diesel::prelude::*;
+use empire::db::{DbConn, player_units, resources, settlements, units};
+use empire::domain::factions::FactionCode;
+use empire::domain::player::buildings::PlayerBuildingKey;
+use empire::domain::player::{Player, PlayerKey};
+use empire::domain::unit::{Unit, UnitKey};
+use empire::game::leaderboard::leaderboard_operations::refresh_score;
+use empire::game::units::training_operations::{get_available_units_for_building, start_training};
+use empire::game::units::upgrade_operations::{BLACKSMITH_NAME, list_upgrades, upgrade_units};
+use empire::schema::{building, player_building, player_resource as pr};
+
+use crate::common::TestHarness;
+
+fn unit(conn: &mut DbConn, name: &str) -> Unit {
+	units::find_by_name(conn, name)
+		.unwrap()
+		.unwrap_or_else(|| panic!("{name} not seeded"))
+}
+
+fn unit_count(conn: &mut DbConn, player_id: &PlayerKey, unit_id: &UnitKey) -> i64 {
+	player_units::get_for_player(conn, player_id)
+		.unwrap()
+		.into_iter()
+		.filter(|owned| owned.unit_id == *unit_id)
+		.map(|owned| owned.quantity)
+		.sum()
+}
+
+/// Builds the human or neutral building named `name` at `level` in the capital of the player.
+fn build(conn: &mut DbConn, player: &Player, name: &str, level: i32) -> PlayerBuildingKey {
+	let capital = settlements::get_capital(conn, &player.id).unwrap();
+	let bld_id: i32 = building::table
+		.filter(building::name.eq(name))
+		.filter(
+			building::faction
+				.eq(FactionCode::Human)
+				.or(building::faction.eq(FactionCode::Neutral)),
+		)
+		.select(building::id)
+		.first(conn)
+		.unwrap_or_else(|_| panic!("{name} not seeded"));
+	diesel::insert_into(player_building::table)
+		.values((
+			player_building::player_id.eq(player.id),
+			player_building::settlement_id.eq(capital.id),
+			player_building::building_id.eq(bld_id),
+			player_building::level.eq(level),
+		))
+		.returning(player_building::id)
+		.get_result(conn)
+		.unwrap_or_else(|_| panic!("Failed to build the {name}"))
+}
+
+fn give_resources(conn: &mut DbConn, player_id: &PlayerKey) {
+	diesel::update(pr::table.filter(pr::player_id.eq(player_id)))
+		.set((
+			pr::food.eq(1000),
+			pr::wood.eq(1000),
+			pr::stone.eq(1000),
+			pr::gold.eq(1000),
+		))
+		.execute(conn)
+		.unwrap();
+}
+
+#[tokio::test]
+async fn test_units_are_upgraded_at_the_blacksmith() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("smith", Some(FactionCode::Human));
+	let infantry = unit(&mut conn, "Infantry");
+	let veteran = unit(&mut conn, "Veteran Infantry");
+	assert_eq!((infantry.tier, veteran.tier), (1, 2));
+	assert_eq!((veteran.attack(), veteran.defense()), (12, 18));
+	player_units::add_units(&mut conn, &player.id, &infantry.id, 10).unwrap();
+	give_resources(&mut conn, &player.id);
+	let power_before = refresh_score(&mut conn, &player.id).unwrap().unit_points;
+	assert_eq!(power_before, 10 * 25);
+
+	let err = upgrade_units(&mut conn, &player.id, &infantry.id, 4).unwrap_err();
+	assert!(err.to_string().contains("Blacksmith level too low"));
+	build(&mut conn, &player, BLACKSMITH_NAME, 1);
+	let err = upgrade_units(&mut conn, &player.id, &infantry.id, 4).unwrap_err();
+	assert!(err.to_string().contains("Military research level too low"));
+	build(&mut conn, &player, "Academy", 1);
+
+	let err = upgrade_units(&mut conn, &player.id, &infantry.id, 11).unwrap_err();
+	assert!(err.to_string().contains("Not enough units"));
+	let err = upgrade_units(&mut conn, &player.id, &veteran.id, 1).unwrap_err();
+	assert!(err.to_string().contains("Unit cannot be upgraded"));
+
+	let upgraded = upgrade_units(&mut conn, &player.id, &infantry.id, 4).unwrap();
+	assert_eq!(upgraded.upgraded_unit.id, veteran.id);
+	assert_eq!(upgraded.upgraded.quantity, 4);
+	assert_eq!(upgraded.costs, (80, 40, 0, 40));
+	assert_eq!(unit_count(&mut conn, &player.id, &infantry.id), 6);
+	assert_eq!(unit_count(&mut conn, &player.id, &veteran.id), 4);
+	let stored = resources::get_by_player_id(&mut conn, &player.id).unwrap();
+	assert_eq!((stored.food, stored.wood, stored.gold), (920, 960, 960));
+
+	// Veterans score their tier bonus on the power board
+	let power_after = refresh_score(&mut conn, &player.id).unwrap().unit_points;
+	assert_eq!(power_after, 6 * 25 + 4 * 30);
+
+	// Only the infantry upgrade is unlocked with a level 1 Blacksmith and Academy
+	let upgrades = list_upgrades(&mut conn, &player.id).unwrap();
+	assert_eq!(upgrades.len(), 4);
+	let infantry_upgrade = upgrades
+		.iter()
+		.find(|status| status.unit.id == infantry.id)
+		.unwrap();
+	assert!(infantry_upgrade.unlocked);
+	assert_eq!(infantry_upgrade.owned, 6);
+	assert_eq!(
+		upgrades.iter().filter(|status| status.unlocked).count(),
+		1,
+		"Higher upgrades need higher levels"
+	);
+}
+
+#[tokio::test]
+async fn test_upgraded_units_cannot_be_trained() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("recruiter", Some(FactionCode::Human));
+	give_resources(&mut conn, &player.id);
+	let barracks = build(&mut conn, &player, "Barracks", 1);
+	let veteran = unit(&mut conn, "Veteran Infantry");
+
+	let available = get_available_units_for_building(&mut conn, &player.id, &barracks).unwrap();
+	let names: Vec<_> = available.iter().map(|unit| unit.name.as_str()).collect();
+	assert_eq!(names, vec!["Infantry"]);
+
+	let err = start_training(
+		&mut conn,
+		&harness.app.job_queue,
+		&harness.app.modifier_system.cache,
+		&player.id,
+		&barracks,
+		&veteran.id,
+		1,
+	)
+	.unwrap_err();
+	assert!(err.to_string().contains("Upgraded units cannot be trained"));
+}
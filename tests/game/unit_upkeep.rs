@@ -0,0 +1,249 @@
+//! Integration tests for unit upkeep and population.
+//!
+//! These tests cover:
+//! - Population capacity from buildings limiting training
+//! - Food upkeep paid out of production, the accumulator and storage
+//! - Starvation killing units or reducing production, depending on the policy
+
+use chrono::{DateTime, TimeDelta, Utc};
+use diesel::prelude::*;
+use empire::configuration::ResourceSettings;
+use empire::db::{DbConn, player_buildings, player_units, settlements, units};
+use empire::domain::factions::FactionCode;
+use empire::domain::player::PlayerKey;
+use empire::domain::player::accumulator::PlayerAccumulator;
+use empire::domain::player::buildings::NewPlayerBuilding;
+use empire::domain::player::resource::ResourceType;
+use empire::domain::unit::{Unit, UnitType};
+use empire::game::resources::resource_operations::produce_resources;
+use empire::game::resources::{ResourceProductionRates, StarvationPolicy};
+use empire::game::units::training_operations::{start_training, train_to_fill};
+use empire::game::units::upkeep_operations::get_population;
+use empire::schema::{building, player_accumulator as acc, player_resource as rsc};
+
+use crate::common::TestHarness;
+
+fn get_unit(conn: &mut DbConn, unit_type: UnitType) -> Unit {
+	units::get_by_type(conn, &unit_type)
+		.expect("Failed to get units")
+		.into_iter()
+		.next()
+		.expect("No unit of this type found")
+}
+
+/// Sets the food in storage and in the accumulator, and the last production an hour back.
+fn set_food(conn: &mut DbConn, player_id: &PlayerKey, stored: i64, accumulated: i64) {
+	diesel::update(rsc::table.filter(rsc::player_id.eq(player_id)))
+		.set((
+			rsc::food.eq(stored),
+			rsc::produced_at.eq(Utc::now() - TimeDelta::hours(1)),
+		))
+		.execute(conn)
+		.expect("Failed to set stored food");
+	diesel::update(acc::table.filter(acc::player_id.eq(player_id)))
+		.set((acc::food.eq(accumulated), acc::wood.eq(0)))
+		.execute(conn)
+		.expect("Failed to set accumulated food");
+}
+
+/// Produces an hour's worth of resources at `rates`.
+fn produce_hour(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	rates: &ResourceProductionRates,
+	settings: &ResourceSettings,
+) -> PlayerAccumulator {
+	let produced_at: DateTime<Utc> = rsc::table
+		.filter(rsc::player_id.eq(player_id))
+		.select(rsc::produced_at)
+		.first(conn)
+		.unwrap();
+	let capital = settlements::get_capital(conn, player_id).unwrap();
+	produce_resources(
+		conn,
+		&capital,
+		rates,
+		Some(produced_at + TimeDelta::hours(1)),
+		settings,
+	)
+	.expect("Failed to produce resources")
+}
+
+fn stored_food(conn: &mut DbConn, player_id: &PlayerKey) -> i64 {
+	rsc::table
+		.filter(rsc::player_id.eq(player_id))
+		.select(rsc::food)
+		.first(conn)
+		.unwrap()
+}
+
+#[tokio::test]
+async fn test_population_limits_training() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("populous", Some(FactionCode::Human));
+	diesel::update(rsc::table.filter(rsc::player_id.eq(player.id)))
+		.set((
+			rsc::food.eq(10_000),
+			rsc::wood.eq(10_000),
+			rsc::stone.eq(10_000),
+			rsc::gold.eq(10_000),
+		))
+		.execute(&mut conn)
+		.unwrap();
+	let barracks_id: i32 = building::table
+		.filter(building::name.eq("Barracks"))
+		.filter(building::faction.eq(FactionCode::Human))
+		.select(building::id)
+		.first(&mut conn)
+		.unwrap();
+	let barracks = player_buildings::construct(
+		&mut conn,
+		NewPlayerBuilding {
+			player_id: player.id,
+			building_id: barracks_id,
+			level: Some(3),
+			upgrade_finishes_at: None,
+			settlement_id: None,
+		},
+	)
+	.unwrap();
+	let infantry = get_unit(&mut conn, UnitType::Infantry);
+
+	let population = get_population(&mut conn, &player.id).unwrap();
+	assert!(population.capacity > 0, "The Keep houses population");
+	assert_eq!(population.used, 0);
+
+	let err = start_training(
+		&mut conn,
+		&harness.app.job_queue,
+		&harness.app.modifier_system.cache,
+		&player.id,
+		&barracks.id,
+		&infantry.id,
+		population.capacity + 1,
+	)
+	.expect_err("Training beyond the capacity should fail");
+	assert!(err.to_string().contains("Not enough population"), "{err}");
+
+	// Units in training take up population as well as owned ones
+	player_units::add_units(&mut conn, &player.id, &infantry.id, 2).unwrap();
+	let filled = train_to_fill(
+		&mut conn,
+		&harness.app.job_queue,
+		&harness.app.modifier_system.cache,
+		&player.id,
+		&barracks.id,
+		&infantry.id,
+		None,
+	)
+	.expect("Failed to train to fill");
+	assert_eq!(filled.entry.quantity, population.capacity - 2);
+	let population = get_population(&mut conn, &player.id).unwrap();
+	assert_eq!(population.free(), 0);
+
+	let err = train_to_fill(
+		&mut conn,
+		&harness.app.job_queue,
+		&harness.app.modifier_system.cache,
+		&player.id,
+		&barracks.id,
+		&infantry.id,
+		None,
+	)
+	.expect_err("No unit should be housable");
+	assert!(err.to_string().contains("Not enough population"), "{err}");
+}
+
+#[tokio::test]
+async fn test_upkeep_is_paid_from_accumulator_and_storage() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("hungry", Some(FactionCode::Human));
+	let infantry = get_unit(&mut conn, UnitType::Infantry);
+	player_units::add_units(&mut conn, &player.id, &infantry.id, 10).unwrap();
+	let upkeep = player_units::get_upkeep(&mut conn, &player.id).unwrap();
+	assert_eq!(upkeep.food_per_hour, 10 * infantry.food_upkeep);
+	assert_eq!(upkeep.population, 10 * infantry.population);
+
+	// 4 food produced against 10 eaten, the missing 6 come from the accumulator and storage
+	set_food(&mut conn, &player.id, 100, 3);
+	let rates = ResourceProductionRates::from([(ResourceType::Food, 4.into())]);
+	let accumulator = produce_hour(&mut conn, &player.id, &rates, &ResourceSettings::default());
+	assert_eq!(accumulator.food, 0);
+	assert_eq!(stored_food(&mut conn, &player.id), 97);
+	let owned = player_units::get_player_unit_count(&mut conn, &player.id, &infantry.id).unwrap();
+	assert_eq!(owned, 10, "Fed units do not starve");
+}
+
+#[tokio::test]
+async fn test_starving_units_die() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("starving", Some(FactionCode::Human));
+	let infantry = get_unit(&mut conn, UnitType::Infantry);
+	let cavalry = get_unit(&mut conn, UnitType::Cavalry);
+	player_units::add_units(&mut conn, &player.id, &infantry.id, 10).unwrap();
+	player_units::add_units(&mut conn, &player.id, &cavalry.id, 2).unwrap();
+	let upkeep = 10 * infantry.food_upkeep + 2 * cavalry.food_upkeep;
+
+	// The cavalry eats more and starves first, then infantry until the rest can be fed
+	set_food(&mut conn, &player.id, 0, 0);
+	let rates = ResourceProductionRates::from([(ResourceType::Food, 4.into())]);
+	let settings = ResourceSettings {
+		starvation: StarvationPolicy::KillUnits,
+		..ResourceSettings::default()
+	};
+	let accumulator = produce_hour(&mut conn, &player.id, &rates, &settings);
+	assert_eq!(accumulator.food, 0);
+	assert_eq!(stored_food(&mut conn, &player.id), 0);
+	let count = |conn: &mut DbConn, unit: &Unit| {
+		player_units::get_player_unit_count(conn, &player.id, &unit.id).unwrap()
+	};
+	assert_eq!(count(&mut conn, &cavalry), 0);
+	let excess = upkeep - 4 - 2 * cavalry.food_upkeep;
+	assert_eq!(
+		count(&mut conn, &infantry),
+		10 - excess / infantry.food_upkeep
+	);
+	let left = player_units::get_upkeep(&mut conn, &player.id).unwrap();
+	assert_eq!(left.food_per_hour, 4);
+
+	// The survivors are fed by the production
+	produce_hour(&mut conn, &player.id, &rates, &settings);
+	assert_eq!(
+		player_units::get_upkeep(&mut conn, &player.id).unwrap(),
+		left
+	);
+}
+
+#[tokio::test]
+async fn test_starvation_reduces_production() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("famished", Some(FactionCode::Human));
+	let infantry = get_unit(&mut conn, UnitType::Infantry);
+	player_units::add_units(&mut conn, &player.id, &infantry.id, 10).unwrap();
+
+	set_food(&mut conn, &player.id, 0, 0);
+	let rates = ResourceProductionRates::from([
+		(ResourceType::Food, 0.into()),
+		(ResourceType::Wood, 10.into()),
+	]);
+	let settings = ResourceSettings {
+		starvation: StarvationPolicy::ReduceProduction { penalty: 0.5 },
+		..ResourceSettings::default()
+	};
+	let accumulator = produce_hour(&mut conn, &player.id, &rates, &settings);
+	assert_eq!(accumulator.wood, 5);
+	let owned = player_units::get_player_unit_count(&mut conn, &player.id, &infantry.id).unwrap();
+	assert_eq!(owned, 10, "Units survive under this policy");
+
+	// Once the army is fed again, production is back to full
+	diesel::update(rsc::table.filter(rsc::player_id.eq(player.id)))
+		.set(rsc::food.eq(1_000))
+		.execute(&mut conn)
+		.unwrap();
+	let accumulator = produce_hour(&mut conn, &player.id, &rates, &settings);
+	assert_eq!(accumulator.wood, 15);
+}
@@ -0,0 +1,140 @@
+//! Integration tests for the server-side confirmation of building upgrades.
+//!
+//! These tests cover:
+//! - Scheduling a confirmation job for the time an upgrade finishes
+//! - Confirming finished upgrades once, and skipping stale confirmation jobs
+//! - Keeping the confirmation endpoint as an idempotent fallback
+
+use chrono::{TimeDelta, Utc};
+use diesel::prelude::*;
+use empire::db::{DbConn, building_upgrades, player_buildings};
+use empire::domain::factions::FactionCode;
+use empire::domain::jobs::{Job, JobType};
+use empire::domain::player::PlayerKey;
+use empire::domain::player::buildings::{PlayerBuilding, PlayerBuildingKey};
+use empire::game::buildings::building_operations::{
+	cancel_upgrade, confirm_finished_upgrade, confirm_upgrade, upgrade_building,
+};
+use empire::game::buildings::plan_operations::BuildingJobPayload;
+use empire::schema::{building, job, player_building};
+
+use crate::common::TestHarness;
+use crate::fixtures::give_player_resources;
+
+/// Get one of the player's starter buildings, raised to `level`.
+fn get_building(
+	conn: &mut DbConn,
+	player_id: &PlayerKey,
+	name: &str,
+	level: i32,
+) -> PlayerBuilding {
+	let bld: PlayerBuilding = player_building::table
+		.inner_join(building::table)
+		.filter(player_building::player_id.eq(player_id))
+		.filter(building::name.eq(name))
+		.select(PlayerBuilding::as_select())
+		.first(conn)
+		.expect("Player has no such building");
+	diesel::update(player_building::table.find(bld.id))
+		.set(player_building::level.eq(level))
+		.returning(PlayerBuilding::as_returning())
+		.get_result(conn)
+		.expect("Failed to set the building level")
+}
+
+/// Confirmation jobs scheduled for a building.
+fn confirmation_jobs(conn: &mut DbConn, player_bld_id: &PlayerBuildingKey) -> Vec<Job> {
+	let jobs: Vec<Job> = job::table
+		.filter(job::job_type.eq(JobType::Building))
+		.select(Job::as_select())
+		.load(conn)
+		.expect("Failed to load jobs");
+	jobs.into_iter()
+		.filter(|job| {
+			matches!(
+				serde_json::from_value(job.payload.clone()),
+				Ok(BuildingJobPayload::ConfirmUpgrade { player_building_id })
+					if player_building_id == *player_bld_id
+			)
+		})
+		.collect()
+}
+
+/// Moves the finish time of the building's upgrade into the past.
+fn finish_upgrade(conn: &mut DbConn, player_bld_id: &PlayerBuildingKey) {
+	let eta = Utc::now() - TimeDelta::seconds(1);
+	player_buildings::set_upgrade_eta(conn, player_bld_id, Some(eta)).unwrap();
+}
+
+#[tokio::test]
+async fn test_finished_upgrades_are_confirmed_once() {
+	let harness = TestHarness::new();
+	let job_queue = &harness.app.job_queue;
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("offline_builder", Some(FactionCode::Human));
+	give_player_resources(&mut conn, &player.id);
+	get_building(&mut conn, &player.id, "Keep", 5);
+	let farm = get_building(&mut conn, &player.id, "Farm", 1);
+
+	let upgrading = upgrade_building(&mut conn, job_queue, &farm.id).expect("Failed to upgrade");
+	let jobs = confirmation_jobs(&mut conn, &farm.id);
+	assert_eq!(jobs.len(), 1, "The upgrade schedules its confirmation");
+	assert_eq!(Some(jobs[0].run_at), upgrading.upgrade_finishes_at);
+
+	// A job running early leaves the upgrade underway
+	let early = confirm_finished_upgrade(&mut conn, &farm.id).expect("Failed to run early");
+	assert!(early.is_none());
+	assert_eq!(
+		player_buildings::get_by_id(&mut conn, &farm.id)
+			.unwrap()
+			.level,
+		1
+	);
+
+	finish_upgrade(&mut conn, &farm.id);
+	let confirmed = confirm_finished_upgrade(&mut conn, &farm.id)
+		.expect("Failed to confirm")
+		.expect("The upgrade has finished");
+	assert_eq!(confirmed.level, 2);
+	assert!(confirmed.upgrade_finishes_at.is_none());
+	let ledger = building_upgrades::get_for_player_building(&mut conn, &farm.id).unwrap();
+	assert!(ledger[0].completed_at.is_some());
+
+	// Repeated jobs and the endpoint fallback leave the confirmed building be
+	let again = confirm_finished_upgrade(&mut conn, &farm.id).expect("Failed to run again");
+	assert!(again.is_none());
+	let fallback = confirm_upgrade(&mut conn, &farm.id).expect("Confirmation is idempotent");
+	assert_eq!(fallback.level, 2);
+}
+
+#[tokio::test]
+async fn test_stale_confirmation_jobs_are_skipped() {
+	let harness = TestHarness::new();
+	let job_queue = &harness.app.job_queue;
+	let mut conn = harness.get_conn();
+	let player = harness.create_named_user("fickle_builder", Some(FactionCode::Human));
+	give_player_resources(&mut conn, &player.id);
+	get_building(&mut conn, &player.id, "Keep", 5);
+	let farm = get_building(&mut conn, &player.id, "Farm", 1);
+
+	// The job of a cancelled upgrade finds nothing to confirm
+	upgrade_building(&mut conn, job_queue, &farm.id).expect("Failed to upgrade");
+	cancel_upgrade(&mut conn, &farm.id).expect("Failed to cancel");
+	let skipped = confirm_finished_upgrade(&mut conn, &farm.id).expect("Failed to run");
+	assert!(skipped.is_none());
+
+	// The player confirmed before the job ran
+	upgrade_building(&mut conn, job_queue, &farm.id).expect("Failed to upgrade again");
+	finish_upgrade(&mut conn, &farm.id);
+	let confirmed = confirm_upgrade(&mut conn, &farm.id).expect("Failed to confirm");
+	assert_eq!(confirmed.level, 2);
+	let skipped = confirm_finished_upgrade(&mut conn, &farm.id).expect("Failed to run");
+	assert!(skipped.is_none());
+	assert_eq!(
+		player_buildings::get_by_id(&mut conn, &farm.id)
+			.unwrap()
+			.level,
+		2
+	);
+	assert_eq!(confirmation_jobs(&mut conn, &farm.id).len(), 2);
+}
@@ -0,0 +1,286 @@
+//! Integration tests for the world reset between seasons.
+//!
+//! These tests cover:
+//! - Guarding a reset behind its one-time token, its expiry and a single running reset
+//! - Wiping the progression of every player while keeping their accounts
+//! - Running every step once, in order, and chaining the steps through the job queue
+
+use axum::extract::FromRef;
+use chrono::{TimeDelta, Utc};
+use diesel::prelude::*;
+use empire::db::{
+	DbConn, active_modifiers, audit_log, player_buildings, player_units, players, resources,
+};
+use empire::domain::app_state::AppState;
+use empire::domain::factions::FactionCode;
+use empire::domain::jobs::{Job, JobStatus, JobType};
+use empire::domain::modifier::active_modifier::ModifierSourceType;
+use empire::domain::player::{Player, PlayerKey};
+use empire::domain::world_reset::{WorldResetJobPayload, WorldResetStatus, WorldResetStep};
+use empire::game::admin_operations::AdminActor;
+use empire::game::world::reset_operations::{
+	CONFIRM_RESET_ACTION, REQUEST_RESET_ACTION, confirm_reset, request_reset, run_step,
+};
+use empire::game::world::reset_processor::WorldResetProcessor;
+use empire::job_queue::job_processor::JobProcessor;
+use empire::schema::{building_resource, job, player_building, player_resource, unit, world_reset};
+use uuid::Uuid;
+
+use crate::common::TestHarness;
+
+fn operator(name: &str) -> AdminActor {
+	AdminActor {
+		operator: Some(name.to_string()),
+		request_id: None,
+	}
+}
+
+/// Pending jobs running a step of a world reset.
+fn pending_steps(conn: &mut DbConn) -> Vec<Job> {
+	job::table
+		.filter(job::job_type.eq(JobType::WorldReset))
+		.filter(job::status.eq(JobStatus::Pending))
+		.select(Job::as_select())
+		.load(conn)
+		.expect("Failed to load jobs")
+}
+
+/// Levels of a player's buildings, sorted so players can be compared.
+fn building_levels(conn: &mut DbConn, player_id: &PlayerKey) -> Vec<(i32, i32)> {
+	let mut levels: Vec<(i32, i32)> = player_buildings::get_player_buildings(conn, player_id)
+		.unwrap()
+		.into_iter()
+		.map(|bld| (bld.building_id, bld.level))
+		.collect();
+	levels.sort();
+	levels
+}
+
+/// Food storage the player's buildings provide at their current levels.
+fn starter_food_cap(conn: &mut DbConn, player_id: &PlayerKey) -> i64 {
+	building_levels(conn, player_id)
+		.into_iter()
+		.map(|(bld_id, level)| {
+			building_resource::table
+				.filter(building_resource::building_id.eq(bld_id))
+				.filter(building_resource::building_level.eq(level))
+				.select(building_resource::food_cap)
+				.first::<i64>(conn)
+				.optional()
+				.unwrap()
+				.unwrap_or(0)
+		})
+		.sum()
+}
+
+/// Gives a player some progression for the reset to wipe.
+fn make_progress(conn: &mut DbConn, player: &Player) {
+	diesel::update(player_building::table.filter(player_building::player_id.eq(player.id)))
+		.set(player_building::level.eq(player_building::level + 3))
+		.execute(conn)
+		.expect("Failed to level buildings");
+	let unit_id: Uuid = unit::table.select(unit::id).first(conn).expect("No units");
+	player_units::add_units(conn, &player.id, &unit_id, 50).expect("Failed to add units");
+	diesel::update(player_resource::table.filter(player_resource::player_id.eq(player.id)))
+		.set(player_resource::gold.eq(5_000))
+		.execute(conn)
+		.expect("Failed to set gold");
+	players::set_protected_until(conn, &player.id, None).unwrap();
+}
+
+#[tokio::test]
+async fn test_reset_is_guarded_by_its_token() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let job_queue = &harness.app.job_queue;
+
+	let requested = request_reset(&mut conn, operator("alice")).expect("Failed to request");
+	assert_eq!(
+		requested.reset.status(Utc::now()),
+		WorldResetStatus::Requested
+	);
+	assert_ne!(
+		requested.reset.token_hash, requested.token,
+		"Only the hash is stored"
+	);
+	assert!(pending_steps(&mut conn).is_empty(), "Nothing runs yet");
+
+	let err = confirm_reset(
+		&mut conn,
+		job_queue,
+		&requested.reset.id,
+		"not-the-token",
+		operator("bob"),
+	)
+	.expect_err("The token does not match");
+	assert!(err.to_string().contains("Invalid reset token"), "{err}");
+
+	// A reset that was not confirmed in time stays expired
+	let late = request_reset(&mut conn, operator("alice")).unwrap();
+	diesel::update(world_reset::table.find(late.reset.id))
+		.set(world_reset::expires_at.eq(Utc::now() - TimeDelta::seconds(1)))
+		.execute(&mut conn)
+		.unwrap();
+	let err = confirm_reset(
+		&mut conn,
+		job_queue,
+		&late.reset.id,
+		&late.token,
+		operator("bob"),
+	)
+	.expect_err("The token expired");
+	assert!(err.to_string().contains("Reset token expired"), "{err}");
+
+	let confirmed = confirm_reset(
+		&mut conn,
+		job_queue,
+		&requested.reset.id,
+		&requested.token,
+		operator("bob"),
+	)
+	.expect("Failed to confirm");
+	assert_eq!(confirmed.status(Utc::now()), WorldResetStatus::Running);
+	assert_eq!(confirmed.confirmed_by.as_deref(), Some("bob"));
+	let steps = pending_steps(&mut conn);
+	assert_eq!(steps.len(), 1, "The first step is scheduled");
+	let payload: WorldResetJobPayload = serde_json::from_value(steps[0].payload.clone()).unwrap();
+	assert_eq!(payload.step, WorldResetStep::Queues);
+
+	let err = confirm_reset(
+		&mut conn,
+		job_queue,
+		&requested.reset.id,
+		&requested.token,
+		operator("bob"),
+	)
+	.expect_err("The token is only good once");
+	assert!(err.to_string().contains("already confirmed"), "{err}");
+
+	let second = request_reset(&mut conn, operator("carol")).unwrap();
+	let err = confirm_reset(
+		&mut conn,
+		job_queue,
+		&second.reset.id,
+		&second.token,
+		operator("carol"),
+	)
+	.expect_err("A reset is running");
+	assert!(
+		err.to_string().contains("Another world reset is running"),
+		"{err}"
+	);
+
+	let actions: Vec<String> = audit_log::get_recent(&mut conn, 10)
+		.unwrap()
+		.into_iter()
+		.map(|entry| entry.action)
+		.collect();
+	assert_eq!(
+		actions
+			.iter()
+			.filter(|action| *action == CONFIRM_RESET_ACTION)
+			.count(),
+		1
+	);
+	assert_eq!(
+		actions
+			.iter()
+			.filter(|action| *action == REQUEST_RESET_ACTION)
+			.count(),
+		3
+	);
+}
+
+#[tokio::test]
+async fn test_reset_wipes_progression_but_keeps_accounts() {
+	let harness = TestHarness::new();
+	let mut conn = harness.get_conn();
+	let state = AppState(harness.app.clone());
+	let veteran = harness.create_named_user("veteran", Some(FactionCode::Human));
+	make_progress(&mut conn, &veteran);
+	let faction_modifiers = active_modifiers::get_by_player_id(&mut conn, &veteran.id)
+		.unwrap()
+		.into_iter()
+		.filter(|active| active.source_type == ModifierSourceType::Faction)
+		.count();
+	assert!(faction_modifiers > 0, "Humans have faction modifiers");
+
+	let requested = request_reset(&mut conn, operator("alice")).unwrap();
+	confirm_reset(
+		&mut conn,
+		&state.job_queue,
+		&requested.reset.id,
+		&requested.token,
+		operator("alice"),
+	)
+	.unwrap();
+
+	// The first job runs its step and chains the next one
+	let first = pending_steps(&mut conn)
+		.pop()
+		.expect("The first step is queued");
+	let first_id = first.id;
+	let processor = WorldResetProcessor::from_ref(&state);
+	let outcome = processor
+		.process_job(first)
+		.await
+		.expect("Step failed")
+		.unwrap();
+	assert_eq!(outcome["completed_steps"], 1);
+	let next: Vec<_> = pending_steps(&mut conn)
+		.into_iter()
+		.filter(|job| job.id != first_id)
+		.collect();
+	assert_eq!(next.len(), 1);
+	let payload: WorldResetJobPayload = serde_json::from_value(next[0].payload.clone()).unwrap();
+	assert_eq!(payload.step, WorldResetStep::Market);
+
+	// A step that already ran is skipped
+	let settings = &state.settings.protection;
+	let again = run_step(
+		&mut conn,
+		settings,
+		&requested.reset.id,
+		WorldResetStep::Queues,
+	)
+	.unwrap();
+	assert!(again.is_none());
+
+	let mut reset = None;
+	for step in &WorldResetStep::ALL[1..] {
+		reset = run_step(&mut conn, settings, &requested.reset.id, *step).unwrap();
+		assert!(reset.is_some(), "Step {step} did not run");
+	}
+	let reset = reset.unwrap();
+	assert_eq!(reset.status(Utc::now()), WorldResetStatus::Completed);
+	assert_eq!(reset.next_step(), None);
+
+	// The account is kept and looks like it was just registered
+	let kept = players::get_by_id(&mut conn, &veteran.id).expect("The account is kept");
+	assert_eq!(kept.faction, FactionCode::Human);
+	assert!(
+		kept.protected_until.is_some(),
+		"The shield is granted again"
+	);
+	assert!(
+		player_units::get_for_player(&mut conn, &veteran.id)
+			.unwrap()
+			.is_empty()
+	);
+	let modifiers = active_modifiers::get_by_player_id(&mut conn, &veteran.id).unwrap();
+	assert_eq!(modifiers.len(), faction_modifiers);
+
+	let rookie = harness.create_named_user("newcomer", Some(FactionCode::Human));
+	assert_eq!(
+		building_levels(&mut conn, &veteran.id),
+		building_levels(&mut conn, &rookie.id)
+	);
+	let veteran_rsc = resources::get_by_player_id(&mut conn, &veteran.id).unwrap();
+	let rookie_rsc = resources::get_by_player_id(&mut conn, &rookie.id).unwrap();
+	assert_eq!(veteran_rsc.gold, rookie_rsc.gold);
+	assert_eq!(
+		veteran_rsc.food_cap,
+		starter_food_cap(&mut conn, &veteran.id)
+	);
+	assert!(veteran_rsc.food_cap > 0, "Starter buildings raise the caps");
+}